@@ -3,15 +3,19 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::errors::AssertionError;
+use super::TraceRow;
+use crate::{errors::AssertionError, hidden::HiddenAssertion};
 use core::{
     cmp::{Ord, Ordering, PartialOrd},
     fmt::{Display, Formatter},
 };
-use math::FieldElement;
+use math::{FieldElement, StarkField};
 use utils::collections::Vec;
 
-#[cfg(test)]
+mod step;
+pub use step::AssertionStep;
+
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // CONSTANTS
@@ -19,6 +23,8 @@ mod tests;
 
 const MIN_STRIDE_LENGTH: usize = 2;
 const NO_STRIDE: usize = 0;
+const WINDOW_STRIDE: usize = 1;
+const NO_WINDOW: usize = 0;
 
 // ASSERTION
 // ================================================================================================
@@ -26,31 +32,36 @@ const NO_STRIDE: usize = 0;
 /// An assertion made against an execution trace.
 ///
 /// An assertion is always placed against a single column of an execution trace, but can cover
-/// multiple steps and multiple values. Specifically, there are three kinds of assertions:
+/// multiple steps and multiple values. Specifically, there are four kinds of assertions:
 ///
 /// 1. **Single** assertion - which requires that a value in a single cell of an execution trace
 ///    is equal to the specified value.
 /// 2. **Periodic** assertion - which requires that values in multiple cells of a single column
-///   are equal to the specified value. The cells must be evenly spaced at intervals with lengths
-///   equal to powers of two. For example, we can specify that values in a column must be equal
-///   to 0 at steps 0, 8, 16, 24, 32 etc. Steps can also start at some offset - e.g., 1, 9, 17,
-///   25, 33 is also a valid sequence of steps.
+///    are equal to the specified value. The cells must be evenly spaced at intervals with lengths
+///    equal to powers of two. For example, we can specify that values in a column must be equal
+///    to 0 at steps 0, 8, 16, 24, 32 etc. Steps can also start at some offset - e.g., 1, 9, 17,
+///    25, 33 is also a valid sequence of steps.
 /// 3. **Sequence** assertion - which requires that multiple cells in a single column are equal
-///   to the values from the provided list. The cells must be evenly spaced at intervals with
-///   lengths equal to powers of two. For example, we can specify that values in a column must
-///   be equal to a sequence 1, 2, 3, 4 at steps 0, 8, 16, 24. That is, value at step 0 should be
-///   equal to 1, value at step 8 should be equal to 2 etc.
+///    to the values from the provided list. The cells must be evenly spaced at intervals with
+///    lengths equal to powers of two. For example, we can specify that values in a column must
+///    be equal to a sequence 1, 2, 3, 4 at steps 0, 8, 16, 24. That is, value at step 0 should be
+///    equal to 1, value at step 8 should be equal to 2 etc.
+/// 4. **Window** assertion - which requires that values in a window of consecutive cells of a
+///    single column are equal to the specified value. The number of cells must be a power of two,
+///    and the window must be aligned to its length. For example, we can specify that values in a
+///    column must be equal to 0 at steps 0 - 15, or at steps 16 - 31.
 ///
 /// Note that single and periodic assertions are succinct. That is, a verifier can evaluate them
-/// very efficiently. However, sequence assertions have liner complexity in the number of
-/// asserted values. Though, unless many thousands of values are asserted, practical impact of
+/// very efficiently. However, sequence and window assertions have liner complexity in the number
+/// of asserted steps. Though, unless many thousands of steps are asserted, practical impact of
 /// this linear complexity should be negligible.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Assertion<E: FieldElement> {
     pub(super) column: usize,
-    pub(super) first_step: usize,
+    pub(super) first_step: TraceRow,
     pub(super) stride: usize,
     pub(super) values: Vec<E>,
+    pub(super) window_length: usize,
 }
 
 impl<E: FieldElement> Assertion<E> {
@@ -63,9 +74,10 @@ impl<E: FieldElement> Assertion<E> {
     pub fn single(column: usize, step: usize, value: E) -> Self {
         Assertion {
             column,
-            first_step: step,
+            first_step: TraceRow(step),
             stride: NO_STRIDE,
             values: vec![value],
+            window_length: NO_WINDOW,
         }
     }
 
@@ -83,9 +95,10 @@ impl<E: FieldElement> Assertion<E> {
         validate_stride(stride, first_step, column);
         Assertion {
             column,
-            first_step,
+            first_step: TraceRow(first_step),
             stride,
             values: vec![value],
+            window_length: NO_WINDOW,
         }
     }
 
@@ -114,10 +127,94 @@ impl<E: FieldElement> Assertion<E> {
         );
         Assertion {
             column,
-            first_step,
+            first_step: TraceRow(first_step),
             stride: if values.len() == 1 { NO_STRIDE } else { stride },
             values,
+            window_length: NO_WINDOW,
+        }
+    }
+
+    /// Returns a single-value assertion against a window of consecutive cells of a single
+    /// column.
+    ///
+    /// The returned assertion requires that values in the specified `column` must be equal to
+    /// the specified `value` at `length` consecutive steps starting at `first_step`.
+    ///
+    /// Constraints of all asserted steps are combined into a single boundary constraint with the
+    /// divisor $\prod_{i=0}^{k-1}(x - g^{a + i})$, where $g$ is the generator of the trace
+    /// domain, $a$ is the first step, and $k$ is the length of the window. Consecutive steps do
+    /// not form a coset of a subgroup of the trace domain, and thus, evaluating the divisor takes
+    /// $k$ multiplications; windows are restricted to be aligned to their power-of-two lengths so
+    /// that any two windows either are disjoint or one of them contains the other.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * `length` is not a power of two, or is smaller than 2.
+    /// * `first_step` is not a multiple of `length`; such a window can be decomposed into aligned
+    ///   windows via [decompose_window()](Assertion::decompose_window).
+    pub fn window(
+        column: usize,
+        first_step: usize,
+        length: usize,
+        value: E,
+    ) -> Result<Self, AssertionError> {
+        if !length.is_power_of_two() || length < MIN_STRIDE_LENGTH {
+            return Err(AssertionError::InvalidWindowLength { column, length });
+        }
+        if first_step % length != 0 {
+            return Err(AssertionError::UnalignedWindow {
+                column,
+                first_step,
+                length,
+            });
+        }
+        Ok(Assertion {
+            column,
+            first_step: TraceRow(first_step),
+            stride: WINDOW_STRIDE,
+            values: vec![value],
+            window_length: length,
+        })
+    }
+
+    /// Returns assertions which together require that values in the specified `column` must be
+    /// equal to the specified `value` at `length` consecutive steps starting at `first_step`.
+    ///
+    /// The steps are covered by the smallest number of aligned windows (see
+    /// [window()](Assertion::window)); steps which cannot be covered by a window of at least two
+    /// steps are covered by single assertions. The assertions are returned in the order of their
+    /// first steps.
+    pub fn decompose_window(
+        column: usize,
+        first_step: usize,
+        length: usize,
+        value: E,
+    ) -> Vec<Self> {
+        let mut result = Vec::new();
+        let mut step = first_step;
+        let end = first_step + length;
+        while step < end {
+            // the longest window which starts at the current step, is aligned to its length, and
+            // does not go past the last step
+            let max_alignment = if step == 0 {
+                usize::MAX
+            } else {
+                1 << step.trailing_zeros()
+            };
+            let remaining = end - step;
+            let max_length = 1 << (usize::BITS - 1 - remaining.leading_zeros());
+            let window_length = max_alignment.min(max_length);
+            if window_length == 1 {
+                result.push(Self::single(column, step, value));
+            } else {
+                result.push(
+                    Self::window(column, step, window_length, value)
+                        .expect("window must be aligned"),
+                );
+            }
+            step += window_length;
         }
+        result
     }
 
     // PUBLIC ACCESSORS
@@ -132,19 +229,32 @@ impl<E: FieldElement> Assertion<E> {
     ///
     /// For single value assertions this is equivalent to the assertion step.
     pub fn first_step(&self) -> usize {
+        self.first_step.as_usize()
+    }
+
+    /// Returns the first row of the execution trace against which this assertion is placed.
+    pub fn first_row(&self) -> TraceRow {
         self.first_step
     }
 
     /// Returns the interval at which the assertion repeats in the execution trace.
     ///
-    /// For single value assertions, this will be 0.
+    /// For single value assertions, this will be 0; for window assertions, this will be 1.
     pub fn stride(&self) -> usize {
         self.stride
     }
 
+    /// Returns the number of consecutive steps against which this assertion is placed.
+    ///
+    /// For assertions other than window assertions, this will be 0.
+    pub fn window_length(&self) -> usize {
+        self.window_length
+    }
+
     /// Returns asserted values.
     ///
-    /// For single value and periodic assertions this will be a slice containing one value.
+    /// For single value, periodic, and window assertions this will be a slice containing one
+    /// value.
     pub fn values(&self) -> &[E] {
         &self.values
     }
@@ -156,7 +266,7 @@ impl<E: FieldElement> Assertion<E> {
 
     /// Returns true if this is a periodic assertion (one value, many steps).
     pub fn is_periodic(&self) -> bool {
-        self.stride != NO_STRIDE && self.values.len() == 1
+        self.stride >= MIN_STRIDE_LENGTH && self.values.len() == 1
     }
 
     /// Returns true if this is a sequence assertion (many values, many steps).
@@ -164,6 +274,11 @@ impl<E: FieldElement> Assertion<E> {
         self.values.len() > 1
     }
 
+    /// Returns true if this is a window assertion (one value, many consecutive steps).
+    pub fn is_window(&self) -> bool {
+        self.stride == WINDOW_STRIDE
+    }
+
     // PUBLIC METHODS
     // --------------------------------------------------------------------------------------------
 
@@ -177,6 +292,12 @@ impl<E: FieldElement> Assertion<E> {
         if self.first_step == other.first_step {
             return true;
         }
+        if self.is_window() {
+            return other.hits_window(self.first_step.0, self.window_length);
+        }
+        if other.is_window() {
+            return self.hits_window(other.first_step.0, other.window_length);
+        }
         if self.stride == other.stride {
             return false;
         }
@@ -189,7 +310,7 @@ impl<E: FieldElement> Assertion<E> {
                 return false;
             }
             if other.is_single() || self.stride < other.stride {
-                (other.first_step - self.first_step) % self.stride == 0
+                (other.first_step.0 - self.first_step.0) % self.stride == 0
             } else {
                 false
             }
@@ -198,13 +319,34 @@ impl<E: FieldElement> Assertion<E> {
                 return false;
             }
             if self.is_single() || other.stride < self.stride {
-                (self.first_step - other.first_step) % other.stride == 0
+                (self.first_step.0 - other.first_step.0) % other.stride == 0
             } else {
                 false
             }
         }
     }
 
+    /// Checks if the assertion can be placed against an execution trace of the specified width
+    /// and length.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * `column` >= `trace_width`.
+    /// * `first_step` >= `trace_length`.
+    /// * The assertion is otherwise not valid against an execution trace of the specified length
+    ///   (see [validate_trace_length()](Assertion::validate_trace_length)).
+    pub fn validate(&self, trace_width: usize, trace_length: usize) -> Result<(), AssertionError> {
+        self.validate_trace_width(trace_width)?;
+        if self.first_step.0 >= trace_length {
+            return Err(AssertionError::StepOutOfRange {
+                column: self.column,
+                step: self.first_step.0,
+                trace_len: trace_length,
+            });
+        }
+        self.validate_trace_length(trace_length)
+    }
+
     /// Panics if the assertion cannot be placed against an execution trace of the specified width.
     pub fn validate_trace_width(&self, trace_width: usize) -> Result<(), AssertionError> {
         if self.column >= trace_width {
@@ -221,14 +363,15 @@ impl<E: FieldElement> Assertion<E> {
     /// * For single assertion, `first_step` >= `trace_length`.
     /// * For periodic assertion, `stride` > `trace_length`.
     /// * For sequence assertion, `num_values` * `stride` != `trace_length`;
+    /// * For window assertion, `first_step` + `length` > `trace_length`.
     pub fn validate_trace_length(&self, trace_length: usize) -> Result<(), AssertionError> {
         if !trace_length.is_power_of_two() {
             return Err(AssertionError::TraceLengthNotPowerOfTwo(trace_length));
         }
         if self.is_single() {
-            if self.first_step >= trace_length {
+            if self.first_step.0 >= trace_length {
                 return Err(AssertionError::TraceLengthTooShort(
-                    (self.first_step + 1).next_power_of_two(),
+                    (self.first_step.0 + 1).next_power_of_two(),
                     trace_length,
                 ));
             }
@@ -239,6 +382,14 @@ impl<E: FieldElement> Assertion<E> {
                     trace_length,
                 ));
             }
+        } else if self.is_window() {
+            let last_step = self.first_step.0 + self.window_length - 1;
+            if last_step >= trace_length {
+                return Err(AssertionError::TraceLengthTooShort(
+                    (last_step + 1).next_power_of_two(),
+                    trace_length,
+                ));
+            }
         } else {
             let expected_length = self.values.len() * self.stride;
             if expected_length != trace_length {
@@ -265,14 +416,18 @@ impl<E: FieldElement> Assertion<E> {
                 panic!("invalid trace length: {err}");
             });
         if self.is_single() {
-            f(self.first_step, self.values[0]);
+            f(self.first_step.0, self.values[0]);
         } else if self.is_periodic() {
             for i in 0..(trace_length / self.stride) {
-                f(self.first_step + self.stride * i, self.values[0]);
+                f(self.first_step.0 + self.stride * i, self.values[0]);
+            }
+        } else if self.is_window() {
+            for i in 0..self.window_length {
+                f(self.first_step.0 + i, self.values[0]);
             }
         } else {
             for (i, &value) in self.values.iter().enumerate() {
-                f(self.first_step + self.stride * i, value);
+                f(self.first_step.0 + self.stride * i, value);
             }
         }
     }
@@ -283,6 +438,7 @@ impl<E: FieldElement> Assertion<E> {
     /// * For single-value assertions, this will always be one.
     /// * For periodic assertions this will be equal to `trace_length` / `stride`.
     /// * For sequence assertions this will be equal to the number of asserted values.
+    /// * For window assertions this will be equal to the length of the window.
     ///
     /// # Panics
     /// Panics if the specified trace length is not valid for this assertion.
@@ -295,10 +451,50 @@ impl<E: FieldElement> Assertion<E> {
             1
         } else if self.is_periodic() {
             trace_length / self.stride
+        } else if self.is_window() {
+            self.window_length
         } else {
             self.values.len()
         }
     }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns true if this assertion is placed against any of `length` consecutive steps
+    /// starting at `first_step`.
+    fn hits_window(&self, first_step: usize, length: usize) -> bool {
+        let start = self.first_step.0;
+        let end = first_step + length;
+        if self.is_window() {
+            start < end && first_step < start + self.window_length
+        } else if start >= first_step {
+            start < end
+        } else if self.is_single() {
+            false
+        } else {
+            // find the first asserted step which is not smaller than the first step of the window
+            let offset = (first_step - start + self.stride - 1) / self.stride;
+            let step = start + offset * self.stride;
+            step < end && (self.is_periodic() || offset < self.values.len())
+        }
+    }
+}
+
+impl<B: StarkField> Assertion<B> {
+    /// Returns a hidden assertion against the specified column and step.
+    ///
+    /// The returned assertion requires that the value in the specified `column` at the
+    /// specified `step` hashes to the provided `digest`; the value itself is not revealed to
+    /// the verifier. Hidden assertions are wired into an AIR via
+    /// [HiddenAssertions](crate::hidden::HiddenAssertions).
+    pub fn hidden(column: usize, step: usize, digest: Vec<B>) -> HiddenAssertion<B> {
+        HiddenAssertion {
+            column,
+            step,
+            digest,
+        }
+    }
 }
 
 // OTHER TRAIT IMPLEMENTATIONS
@@ -330,9 +526,13 @@ impl<E: FieldElement> Display for Assertion<E> {
     fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "(column={}, ", self.column)?;
         match self.stride {
-            0 => write!(f, "step={}, ", self.first_step)?,
+            NO_STRIDE => write!(f, "step={}, ", self.first_step)?,
+            WINDOW_STRIDE => {
+                let last_step = self.first_step.0 + self.window_length - 1;
+                write!(f, "steps=[{}..{}], ", self.first_step, last_step)?;
+            }
             _ => {
-                let second_step = self.first_step + self.stride;
+                let second_step = self.first_step.0 + self.stride;
                 write!(f, "steps=[{}, {}, ...], ", self.first_step, second_step)?;
             }
         }
@@ -0,0 +1,99 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::errors::AssertionError;
+
+// ASSERTION STEP
+// ================================================================================================
+
+/// A step of an execution trace at which an assertion or a boundary expression is placed,
+/// computed with checked arithmetic.
+///
+/// Steps are often derived from values which are not under the control of the AIR: a step may be
+/// read from public inputs, in which case it can be arbitrarily large, or it may be computed
+/// relative to the last step of the trace (e.g., `trace_length - k`), in which case it can
+/// precede the first step. An assertion step records such a computation without performing it;
+/// the step is resolved against a concrete trace length via
+/// [clamped_or_error()](AssertionStep::clamped_or_error), which returns an [AssertionError]
+/// instead of overflowing or underflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssertionStep {
+    from_last: bool,
+    offset: i128,
+}
+
+impl AssertionStep {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the specified step counted from the first step of the execution trace.
+    pub fn new(step: usize) -> Self {
+        Self {
+            from_last: false,
+            offset: step as i128,
+        }
+    }
+
+    /// Returns the step counted from the first step of the execution trace by the specified
+    /// value read from public inputs.
+    ///
+    /// Values which do not fit into `i128` are saturated; such steps are outside of any
+    /// execution trace anyway.
+    pub fn from_pub_input(value: u128) -> Self {
+        Self {
+            from_last: false,
+            offset: saturate(value),
+        }
+    }
+
+    /// Returns the step which precedes the last step of the execution trace by `k` steps.
+    pub fn last_minus(k: usize) -> Self {
+        Self {
+            from_last: true,
+            offset: -(k as i128),
+        }
+    }
+
+    // PUBLIC METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the step which follows this step by the specified number of steps.
+    ///
+    /// The number of steps may come from public inputs; the result saturates rather than
+    /// overflows.
+    pub fn plus(self, num_steps: u128) -> Self {
+        Self {
+            from_last: self.from_last,
+            offset: self.offset.saturating_add(saturate(num_steps)),
+        }
+    }
+
+    /// Returns this step resolved against an execution trace of the specified length.
+    ///
+    /// # Errors
+    /// Returns an error if the step precedes the first step of the trace or follows the last
+    /// step of the trace.
+    pub fn clamped_or_error(&self, trace_length: usize) -> Result<usize, AssertionError> {
+        let step = if self.from_last {
+            (trace_length as i128 - 1).saturating_add(self.offset)
+        } else {
+            self.offset
+        };
+        if step < 0 || step >= trace_length as i128 {
+            return Err(AssertionError::StepOutOfTrace {
+                step,
+                trace_len: trace_length,
+            });
+        }
+        Ok(step as usize)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn saturate(value: u128) -> i128 {
+    value.min(i128::MAX as u128) as i128
+}
@@ -3,7 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{Assertion, AssertionError};
+use super::{Assertion, AssertionError, AssertionStep, TraceRow};
 use math::{fields::f128::BaseElement, FieldElement};
 use rand_utils::{rand_value, rand_vector};
 use utils::collections::Vec;
@@ -15,7 +15,7 @@ fn single_assertion() {
     let value = rand_value::<BaseElement>();
     let a = Assertion::single(2, 8, value);
     assert_eq!(2, a.column);
-    assert_eq!(8, a.first_step);
+    assert_eq!(TraceRow(8), a.first_step);
     assert_eq!(vec![value], a.values);
     assert_eq!(0, a.stride);
     assert_eq!(1, a.get_num_steps(16));
@@ -39,6 +39,24 @@ fn single_assertion() {
     );
 }
 
+#[test]
+fn single_assertion_validate() {
+    let a = Assertion::single(2, 8, BaseElement::ONE);
+    assert_eq!(Ok(()), a.validate(3, 16));
+    assert_eq!(
+        Err(AssertionError::TraceWidthTooShort(2, 2)),
+        a.validate(2, 16)
+    );
+    assert_eq!(
+        Err(AssertionError::StepOutOfRange {
+            column: 2,
+            step: 8,
+            trace_len: 8
+        }),
+        a.validate(3, 8)
+    );
+}
+
 // PERIODIC ASSERTIONS
 // ================================================================================================
 
@@ -47,7 +65,7 @@ fn periodic_assertion() {
     let value = rand_value::<BaseElement>();
     let a = Assertion::periodic(4, 1, 16, value);
     assert_eq!(4, a.column);
-    assert_eq!(1, a.first_step);
+    assert_eq!(TraceRow(1), a.first_step);
     assert_eq!(vec![value], a.values);
     assert_eq!(16, a.stride);
     assert_eq!(1, a.get_num_steps(16));
@@ -108,6 +126,20 @@ fn periodic_assertion_get_num_steps_error() {
     let _ = a.get_num_steps(4);
 }
 
+#[test]
+fn periodic_assertion_validate() {
+    let a = Assertion::periodic(0, 3, 4, BaseElement::ONE);
+    assert_eq!(Ok(()), a.validate(1, 16));
+    assert_eq!(
+        Err(AssertionError::StepOutOfRange {
+            column: 0,
+            step: 3,
+            trace_len: 2
+        }),
+        a.validate(1, 2)
+    );
+}
+
 // SEQUENCE ASSERTIONS
 // ================================================================================================
 
@@ -116,7 +148,7 @@ fn sequence_assertion() {
     let values = rand_vector::<BaseElement>(2);
     let a = Assertion::sequence(3, 2, 4, values.clone());
     assert_eq!(3, a.column);
-    assert_eq!(2, a.first_step);
+    assert_eq!(TraceRow(2), a.first_step);
     assert_eq!(values, a.values);
     assert_eq!(4, a.stride);
     assert_eq!(2, a.get_num_steps(8));
@@ -199,6 +231,147 @@ fn sequence_assertion_num_values_not_power_of_two() {
     );
 }
 
+// WINDOW ASSERTIONS
+// ================================================================================================
+
+#[test]
+fn window_assertion() {
+    let value = rand_value::<BaseElement>();
+    let a = Assertion::window(3, 8, 4, value).unwrap();
+    assert_eq!(3, a.column);
+    assert_eq!(TraceRow(8), a.first_step);
+    assert_eq!(vec![value], a.values);
+    assert_eq!(1, a.stride);
+    assert_eq!(4, a.window_length());
+    assert!(a.is_window());
+    assert!(!a.is_single());
+    assert!(!a.is_periodic());
+    assert!(!a.is_sequence());
+    assert_eq!(4, a.get_num_steps(16));
+    assert_eq!(4, a.get_num_steps(32));
+
+    let mut steps = Vec::new();
+    a.apply(16, |step, val| {
+        assert_eq!(value, val);
+        steps.push(step);
+    });
+    assert_eq!(vec![8, 9, 10, 11], steps);
+
+    assert_eq!(Ok(()), a.validate_trace_width(4));
+    assert_eq!(
+        Err(AssertionError::TraceWidthTooShort(3, 2)),
+        a.validate_trace_width(2)
+    );
+    assert_eq!(Ok(()), a.validate_trace_length(16));
+    assert_eq!(
+        Err(AssertionError::TraceLengthTooShort(16, 8)),
+        a.validate_trace_length(8)
+    );
+}
+
+#[test]
+fn window_assertion_invalid_length() {
+    for length in [0, 1, 3, 12] {
+        assert_eq!(
+            Err(AssertionError::InvalidWindowLength { column: 2, length }),
+            Assertion::window(2, 0, length, BaseElement::ONE)
+        );
+    }
+}
+
+#[test]
+fn window_assertion_unaligned() {
+    assert_eq!(
+        Err(AssertionError::UnalignedWindow {
+            column: 2,
+            first_step: 4,
+            length: 8
+        }),
+        Assertion::window(2, 4, 8, BaseElement::ONE)
+    );
+    assert!(Assertion::window(2, 16, 8, BaseElement::ONE).is_ok());
+}
+
+#[test]
+fn window_assertion_decompose() {
+    let value = BaseElement::ONE;
+
+    // an aligned window is not decomposed
+    assert_eq!(
+        vec![Assertion::window(1, 16, 16, value).unwrap()],
+        Assertion::decompose_window(1, 16, 16, value)
+    );
+
+    // steps 3 - 17 are covered by windows [4, 8), [8, 16), [16, 18) and a single step 3
+    let expected = vec![
+        Assertion::single(1, 3, value),
+        Assertion::window(1, 4, 4, value).unwrap(),
+        Assertion::window(1, 8, 8, value).unwrap(),
+        Assertion::window(1, 16, 2, value).unwrap(),
+    ];
+    assert_eq!(expected, Assertion::decompose_window(1, 3, 15, value));
+
+    // every step is asserted exactly once
+    let mut steps = Vec::new();
+    for assertion in Assertion::decompose_window(1, 5, 27, value) {
+        assertion.apply(64, |step, _| steps.push(step));
+    }
+    assert_eq!((5..32).collect::<Vec<_>>(), steps);
+}
+
+// ASSERTION STEPS
+// ================================================================================================
+
+#[test]
+fn assertion_step() {
+    assert_eq!(Ok(5), AssertionStep::new(5).clamped_or_error(16));
+    assert_eq!(
+        Ok(15),
+        AssertionStep::from_pub_input(15).clamped_or_error(16)
+    );
+    assert_eq!(Ok(15), AssertionStep::last_minus(0).clamped_or_error(16));
+    assert_eq!(Ok(0), AssertionStep::last_minus(15).clamped_or_error(16));
+    assert_eq!(
+        Ok(14),
+        AssertionStep::last_minus(3).plus(2).clamped_or_error(16)
+    );
+    assert_eq!(Ok(12), AssertionStep::new(5).plus(7).clamped_or_error(16));
+}
+
+#[test]
+fn assertion_step_out_of_trace() {
+    let out_of_trace = |step, trace_len| Err(AssertionError::StepOutOfTrace { step, trace_len });
+
+    assert_eq!(
+        out_of_trace(16, 16),
+        AssertionStep::new(16).clamped_or_error(16)
+    );
+    assert_eq!(
+        out_of_trace(-1, 16),
+        AssertionStep::last_minus(16).clamped_or_error(16)
+    );
+    assert_eq!(
+        out_of_trace(-(usize::MAX as i128) + 15, 16),
+        AssertionStep::last_minus(usize::MAX).clamped_or_error(16)
+    );
+    assert_eq!(
+        out_of_trace(1 << 64, 16),
+        AssertionStep::from_pub_input(1 << 64).clamped_or_error(16)
+    );
+
+    // values which do not fit into i128 saturate instead of wrapping around
+    let huge = AssertionStep::from_pub_input(u128::MAX);
+    assert_eq!(out_of_trace(i128::MAX, 16), huge.clamped_or_error(16));
+    assert_eq!(
+        out_of_trace(i128::MAX, 16),
+        huge.plus(u128::MAX).clamped_or_error(16)
+    );
+    assert_eq!(
+        out_of_trace(i128::MAX, 16),
+        AssertionStep::new(3).plus(u128::MAX).clamped_or_error(16)
+    );
+}
+
 // OVERLAPPING ASSERTIONS
 // ================================================================================================
 
@@ -362,4 +535,45 @@ fn assertion_overlap() {
     let b = Assertion::periodic(0, 0, 16, BaseElement::ONE);
     assert!(!a.overlaps_with(&b));
     assert!(!b.overlaps_with(&a));
+
+    // ----- window overlap -----------------------------------------------------------------------
+
+    let a = Assertion::window(3, 8, 8, BaseElement::ONE).unwrap();
+    let b = Assertion::single(3, 15, BaseElement::ONE);
+    assert!(a.overlaps_with(&b));
+    assert!(b.overlaps_with(&a));
+
+    let b = Assertion::periodic(3, 3, 4, BaseElement::ONE);
+    assert!(a.overlaps_with(&b));
+    assert!(b.overlaps_with(&a));
+
+    let b = Assertion::sequence(3, 1, 8, vec![BaseElement::ONE, BaseElement::ZERO]);
+    assert!(a.overlaps_with(&b));
+    assert!(b.overlaps_with(&a));
+
+    let b = Assertion::window(3, 12, 4, BaseElement::ONE).unwrap();
+    assert!(a.overlaps_with(&b));
+    assert!(b.overlaps_with(&a));
+
+    // different columns: no overlap
+    let b = Assertion::window(1, 8, 8, BaseElement::ONE).unwrap();
+    assert!(!a.overlaps_with(&b));
+    assert!(!b.overlaps_with(&a));
+
+    // steps outside of the window: no overlap
+    let b = Assertion::single(3, 16, BaseElement::ONE);
+    assert!(!a.overlaps_with(&b));
+    assert!(!b.overlaps_with(&a));
+
+    let b = Assertion::periodic(3, 3, 16, BaseElement::ONE);
+    assert!(!a.overlaps_with(&b));
+    assert!(!b.overlaps_with(&a));
+
+    let b = Assertion::sequence(3, 1, 16, vec![BaseElement::ONE, BaseElement::ZERO]);
+    assert!(!a.overlaps_with(&b));
+    assert!(!b.overlaps_with(&a));
+
+    let b = Assertion::window(3, 16, 16, BaseElement::ONE).unwrap();
+    assert!(!a.overlaps_with(&b));
+    assert!(!b.overlaps_with(&a));
 }
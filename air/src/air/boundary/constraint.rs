@@ -3,7 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{Assertion, BTreeMap, ExtensionOf, FieldElement, Vec};
+use super::{Assertion, ExtensionOf, FieldElement, PowerTable, TraceRow, Vec};
 use math::{fft, polynom};
 
 // BOUNDARY CONSTRAINT
@@ -47,44 +47,43 @@ where
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
     /// Creates a new boundary constraint from the specified assertion.
+    ///
+    /// For multi-value assertions, the asserted values are kept in evaluation form until
+    /// [interpolate_values()](BoundaryConstraint::interpolate_values) is called; this allows
+    /// value polynomials of many constraints to be interpolated as a single batch.
     pub(super) fn new(
         assertion: Assertion<F>,
-        inv_g: F::BaseField,
-        twiddle_map: &mut BTreeMap<usize, Vec<F::BaseField>>,
+        powers: &PowerTable<F::BaseField>,
         composition_coefficients: (E, E),
     ) -> Self {
-        // build a polynomial which evaluates to constraint values at asserted steps; for
-        // single-value assertions we use the value as constant coefficient of degree 0
-        // polynomial; but for multi-value assertions, we need to interpolate the values
+        // for single-value assertions we use the value as constant coefficient of degree 0
+        // polynomial; but for multi-value assertions, the values will need to be interpolated
         // into a polynomial using inverse FFT
         let mut poly_offset = (0, F::BaseField::ONE);
-        let mut poly = assertion.values;
-        if poly.len() > 1 {
-            // get the twiddles from the map; if twiddles for this domain haven't been built
-            // yet, build them and add them to the map
-            let inv_twiddles = twiddle_map
-                .entry(poly.len())
-                .or_insert_with(|| fft::get_inv_twiddles(poly.len()));
-            // interpolate the values into a polynomial
-            fft::interpolate_poly(&mut poly, inv_twiddles);
-            if assertion.first_step != 0 {
-                // if the assertions don't fall on the steps which are powers of two, we can't
-                // use FFT to interpolate the values into a polynomial. This would make such
-                // assertions quite impractical. To get around this, we still use FFT to build
-                // the polynomial, but then we evaluate it as f(x * offset) instead of f(x)
-                let x_offset = inv_g.exp((assertion.first_step as u64).into());
-                poly_offset = (assertion.first_step, x_offset);
-            }
+        if assertion.values.len() > 1 && assertion.first_step != TraceRow(0) {
+            // if the assertions don't fall on the steps which are powers of two, we can't
+            // use FFT to interpolate the values into a polynomial. This would make such
+            // assertions quite impractical. To get around this, we still use FFT to build
+            // the polynomial, but then we evaluate it as f(x * offset) instead of f(x)
+            let x_offset = powers.get_inv(assertion.first_step.as_usize());
+            poly_offset = (assertion.first_step.as_usize(), x_offset);
         }
 
         BoundaryConstraint {
             column: assertion.column,
-            poly,
+            poly: assertion.values,
             poly_offset,
             cc: composition_coefficients,
         }
     }
 
+    /// Interpolates the values of a multi-value assertion into the value polynomial of this
+    /// constraint using the provided inverse twiddles.
+    pub(super) fn interpolate_values(&mut self, inv_twiddles: &[F::BaseField]) {
+        debug_assert!(self.poly.len() > 1, "not a multi-value constraint");
+        fft::interpolate_poly(&mut self.poly, inv_twiddles);
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -4,7 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{
-    Assertion, BTreeMap, BoundaryConstraint, ConstraintDivisor, ExtensionOf, FieldElement, Vec,
+    Assertion, BoundaryConstraint, ConstraintDivisor, ExtensionOf, FieldElement, PowerTable, Vec,
 };
 
 // BOUNDARY CONSTRAINT GROUP
@@ -96,18 +96,21 @@ where
     pub(super) fn add(
         &mut self,
         assertion: Assertion<F>,
-        inv_g: F::BaseField,
-        twiddle_map: &mut BTreeMap<usize, Vec<F::BaseField>>,
+        powers: &PowerTable<F::BaseField>,
         composition_coefficients: (E, E),
     ) {
         self.constraints.push(BoundaryConstraint::new(
             assertion,
-            inv_g,
-            twiddle_map,
+            powers,
             composition_coefficients,
         ));
     }
 
+    /// Returns mutable references to constraints in this group.
+    pub(super) fn constraints_mut(&mut self) -> &mut [BoundaryConstraint<F, E>] {
+        &mut self.constraints
+    }
+
     /// Evaluates all constraints in this group at the specified point `x`.
     ///
     /// `xp` is a degree adjustment multiplier which must be computed as `x^degree_adjustment`.
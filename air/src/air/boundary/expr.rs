@@ -0,0 +1,251 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{AirContext, ConstraintDivisor, ExtensionOf, FieldElement};
+use crate::{AssertionError, AssertionStep};
+
+// STEP SELECTOR
+// ================================================================================================
+
+/// Describes the step of an execution trace at which a [BoundaryExpr] must hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepSelector {
+    /// The first step of the execution trace.
+    First,
+    /// The last step of the execution trace.
+    Last,
+    /// The specified step of the execution trace.
+    Step(usize),
+    /// The step computed with checked arithmetic (e.g., derived from public inputs, or counted
+    /// back from the last step of the execution trace).
+    At(AssertionStep),
+}
+
+impl StepSelector {
+    /// Returns the step selected by this selector in an execution trace of the specified length.
+    ///
+    /// # Panics
+    /// Panics if the selected step is outside of the execution trace.
+    pub fn get_step(&self, trace_length: usize) -> usize {
+        match *self {
+            Self::First => 0,
+            Self::Last => trace_length - 1,
+            Self::Step(step) => {
+                assert!(
+                    step < trace_length,
+                    "boundary expression step must be smaller than trace length {trace_length}, but was {step}"
+                );
+                step
+            }
+            Self::At(step) => step
+                .clamped_or_error(trace_length)
+                .unwrap_or_else(|err| panic!("invalid boundary expression step: {err}")),
+        }
+    }
+
+    /// Returns the step selected by this selector in an execution trace of the specified length.
+    ///
+    /// # Errors
+    /// Returns an error if the selected step is outside of the execution trace.
+    pub fn try_get_step(&self, trace_length: usize) -> Result<usize, AssertionError> {
+        match *self {
+            Self::First => AssertionStep::new(0),
+            Self::Last => AssertionStep::last_minus(0),
+            Self::Step(step) => AssertionStep::new(step),
+            Self::At(step) => step,
+        }
+        .clamped_or_error(trace_length)
+    }
+}
+
+// BOUNDARY EXPRESSION
+// ================================================================================================
+
+/// A non-linear boundary constraint placed against a single row of the main segment of an
+/// execution trace.
+///
+/// Unlike an [Assertion](crate::Assertion), which is placed against a single column, a boundary
+/// expression can combine all columns of a row: the expression requires that $f(T(g^s)) = v$,
+/// where $T(g^s)$ is the row of the main trace segment at step $s$ selected by the step selector,
+/// $f$ is an arbitrary polynomial over the values of the row, and $v$ is a public value. For
+/// example, `|row| row[0] * row[1]` can be used to assert the product of the first two columns.
+///
+/// The expression is enforced by the quotient $\frac{f(T(x)) - v}{x - g^s}$, which is composed
+/// together with all other constraints. Since $f$ is evaluated on the row of trace values, the
+/// verifier evaluates the quotient at the out-of-domain point using the current row of the
+/// out-of-domain trace frame, and thus, no additional trace openings are required. The cost of
+/// this approach is that the degree of the numerator is `degree * (trace_length - 1)`, and thus,
+/// the degree of $f$ must be declared via
+/// [AirContext::set_boundary_expr_degrees()](crate::AirContext::set_boundary_expr_degrees) so
+/// that the constraint evaluation domain can accommodate it.
+#[derive(Debug, Clone)]
+pub struct BoundaryExpr<E: FieldElement> {
+    step_selector: StepSelector,
+    eval: fn(&[E]) -> E,
+    degree: usize,
+    public_value: E,
+}
+
+impl<E: FieldElement> BoundaryExpr<E> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a boundary expression requiring that `eval` applied to the row of the main trace
+    /// segment selected by `step_selector` evaluates to `public_value`.
+    ///
+    /// `degree` is the degree of `eval` as a polynomial in the values of the row.
+    ///
+    /// # Panics
+    /// Panics if `degree` is zero.
+    pub fn new(
+        step_selector: StepSelector,
+        eval: fn(&[E]) -> E,
+        degree: usize,
+        public_value: E,
+    ) -> Self {
+        assert!(
+            degree > 0,
+            "boundary expression degree must be greater than zero"
+        );
+        BoundaryExpr {
+            step_selector,
+            eval,
+            degree,
+            public_value,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the selector of the step at which this expression must hold.
+    pub fn step_selector(&self) -> StepSelector {
+        self.step_selector
+    }
+
+    /// Returns the degree of this expression in the values of a trace row.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Returns the value to which this expression must evaluate.
+    pub fn public_value(&self) -> E {
+        self.public_value
+    }
+
+    // EVALUATOR
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the difference between the expression evaluated on the provided row and the public
+    /// value; this is zero when the row satisfies the expression.
+    pub fn evaluate(&self, row: &[E]) -> E {
+        (self.eval)(row) - self.public_value
+    }
+}
+
+// BOUNDARY EXPRESSION CONSTRAINT
+// ================================================================================================
+
+/// A [BoundaryExpr] together with the info needed to evaluate it and to compose its evaluations
+/// with other constraints.
+///
+/// Similarly to [BoundaryConstraint](super::BoundaryConstraint), types `F` and `E` are the same
+/// in the verifier, while in the prover `F` is the base field of the protocol, so that the
+/// expression can be evaluated directly on rows of the main trace segment.
+#[derive(Debug, Clone)]
+pub struct BoundaryExprConstraint<F, E>
+where
+    F: FieldElement,
+    E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
+{
+    expr: BoundaryExpr<F>,
+    step: usize,
+    divisor: ConstraintDivisor<F::BaseField>,
+    degree_adjustment: u64,
+    cc: (E, E),
+}
+
+impl<F, E> BoundaryExprConstraint<F, E>
+where
+    F: FieldElement,
+    E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
+{
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new constraint for the specified boundary expression.
+    ///
+    /// # Panics
+    /// Panics if the step selected by the expression is outside of the execution trace, or if the
+    /// composition degree of the context cannot accommodate the degree of the expression.
+    pub fn new(
+        expr: BoundaryExpr<F>,
+        context: &AirContext<F::BaseField>,
+        composition_coefficients: (E, E),
+    ) -> Self {
+        let step = expr.step_selector().get_step(context.trace_len());
+        let divisor = ConstraintDivisor::from_step(step, context.trace_len());
+
+        // the numerator f(T(x)) - v has degree `degree * (trace_length - 1)`; once it is
+        // divided by the divisor of degree 1, and multiplied by x^degree_adjustment, the
+        // degree of the result must be equal to the composition degree.
+        let eval_degree = expr.degree() * context.trace_poly_degree();
+        let target_degree = context.composition_degree() + divisor.degree();
+        assert!(
+            eval_degree <= target_degree,
+            "boundary expression of degree {} cannot be composed; composition degree must be at least {}, but was {}",
+            expr.degree(),
+            eval_degree - divisor.degree(),
+            context.composition_degree()
+        );
+
+        BoundaryExprConstraint {
+            expr,
+            step,
+            divisor,
+            degree_adjustment: (target_degree - eval_degree) as u64,
+            cc: composition_coefficients,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the boundary expression of this constraint.
+    pub fn expr(&self) -> &BoundaryExpr<F> {
+        &self.expr
+    }
+
+    /// Returns the step of the execution trace at which the expression must hold.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the divisor of this constraint; this is always $(x - g^s)$.
+    pub fn divisor(&self) -> &ConstraintDivisor<F::BaseField> {
+        &self.divisor
+    }
+
+    /// Returns the degree adjustment factor of this constraint.
+    pub fn degree_adjustment(&self) -> u64 {
+        self.degree_adjustment
+    }
+
+    /// Returns composition coefficients for this constraint.
+    pub fn cc(&self) -> &(E, E) {
+        &self.cc
+    }
+
+    // EVALUATOR
+    // --------------------------------------------------------------------------------------------
+
+    /// Evaluates the numerator of this constraint on the provided row and multiplies the result
+    /// by the composition coefficients of this constraint.
+    ///
+    /// `xp` must be computed as `x^degree_adjustment`, where `x` is the point at which the row
+    /// was evaluated. The result is not divided by the divisor of this constraint.
+    pub fn evaluate_at(&self, row: &[F], xp: F) -> E {
+        let evaluation = self.expr.evaluate(row);
+        (self.cc.0 + self.cc.1.mul_base(xp)).mul_base(evaluation)
+    }
+}
@@ -3,9 +3,15 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{AirContext, Assertion, ConstraintDivisor};
-use math::{ExtensionOf, FieldElement};
-use utils::collections::{BTreeMap, BTreeSet, Vec};
+use super::{AirContext, Assertion, ConstraintDivisor, PowerTable, TraceRow};
+use math::{fft, ExtensionOf, FieldElement};
+use utils::{
+    collections::{BTreeMap, BTreeSet, Vec},
+    iter_mut,
+};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
 
 mod constraint;
 pub use constraint::BoundaryConstraint;
@@ -13,7 +19,10 @@ pub use constraint::BoundaryConstraint;
 mod constraint_group;
 pub use constraint_group::BoundaryConstraintGroup;
 
-#[cfg(test)]
+mod expr;
+pub use expr::{BoundaryExpr, BoundaryExprConstraint, StepSelector};
+
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // BOUNDARY CONSTRAINT INFO
@@ -59,6 +68,40 @@ impl<E: FieldElement> BoundaryConstraints<E> {
         aux_assertions: Vec<Assertion<E>>,
         composition_coefficients: &[(E, E)],
     ) -> Self {
+        let powers = PowerTable::new(context.trace_len(), []);
+        Self::with_powers(
+            context,
+            main_assertions,
+            aux_assertions,
+            composition_coefficients,
+            &powers,
+        )
+    }
+
+    /// Returns a new instance of [BoundaryConstraints] for a computation described by the provided
+    /// assertions and AIR context; offsets of constraint divisors and value polynomials are read
+    /// from the provided table of powers of the trace domain generator.
+    ///
+    /// A table built via [PowerTable::for_constraints()] for the same assertions contains all
+    /// powers needed to build the constraints.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Any of the conditions listed for [BoundaryConstraints::new()] is violated.
+    /// * The domain of the power table is not the trace domain of the computation.
+    pub fn with_powers(
+        context: &AirContext<E::BaseField>,
+        main_assertions: Vec<Assertion<E::BaseField>>,
+        aux_assertions: Vec<Assertion<E>>,
+        composition_coefficients: &[(E, E)],
+        powers: &PowerTable<E::BaseField>,
+    ) -> Self {
+        assert_eq!(
+            context.trace_len(),
+            powers.domain_size(),
+            "power table must be built for the trace domain"
+        );
+
         // make sure the provided assertions are consistent with the specified context
         assert_eq!(
             main_assertions.len(),
@@ -92,36 +135,33 @@ impl<E: FieldElement> BoundaryConstraints<E> {
         let main_assertions = prepare_assertions(main_assertions, main_trace_width, trace_length);
         let aux_assertions = prepare_assertions(aux_assertions, aux_trace_width, trace_length);
 
-        // compute inverse of the trace domain generator; this will be used for offset
-        // computations when creating sequence constraints
-        let inv_g = context.trace_domain_generator.inv();
-
-        // cache inverse twiddles for multi-value assertions in this map so that we don't have
-        // to re-build them for assertions with identical strides
-        let mut twiddle_map = BTreeMap::new();
-
         // split composition coefficients into main and auxiliary parts
         let (main_composition_coefficients, aux_composition_coefficients) =
             composition_coefficients.split_at(main_assertions.len());
 
         // build constraints for the assertions against the main trace segment
-        let main_constraints = group_constraints(
+        let mut main_constraints = group_constraints(
             main_assertions,
             context,
             main_composition_coefficients,
-            inv_g,
-            &mut twiddle_map,
+            powers,
         );
 
         // build constraints for the assertions against auxiliary trace segments
-        let aux_constraints = group_constraints(
+        let mut aux_constraints = group_constraints(
             aux_assertions,
             context,
             aux_composition_coefficients,
-            inv_g,
-            &mut twiddle_map,
+            powers,
         );
 
+        // interpolate values of multi-value assertions into value polynomials; inverse twiddles
+        // are cached in this map so that we don't have to re-build them for assertions with
+        // identical strides
+        let mut twiddle_map = BTreeMap::new();
+        interpolate_value_polys(&mut main_constraints, &mut twiddle_map);
+        interpolate_value_polys(&mut aux_constraints, &mut twiddle_map);
+
         Self {
             main_constraints,
             aux_constraints,
@@ -153,28 +193,32 @@ fn group_constraints<F, E>(
     assertions: Vec<Assertion<F>>,
     context: &AirContext<F::BaseField>,
     composition_coefficients: &[(E, E)],
-    inv_g: F::BaseField,
-    twiddle_map: &mut BTreeMap<usize, Vec<F::BaseField>>,
+    powers: &PowerTable<F::BaseField>,
 ) -> Vec<BoundaryConstraintGroup<F, E>>
 where
     F: FieldElement,
     E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
 {
     // iterate over all assertions, which are sorted first by stride and then by first_step
-    // in ascending order
+    // in ascending order; window length is a part of the group key to tell apart windows of
+    // different lengths starting at the same step
     let mut groups = BTreeMap::new();
     for (assertion, &cc) in assertions.into_iter().zip(composition_coefficients) {
-        let key = (assertion.stride(), assertion.first_step());
+        let key = (
+            assertion.stride(),
+            assertion.first_step(),
+            assertion.window_length(),
+        );
         let group = groups.entry(key).or_insert_with(|| {
             BoundaryConstraintGroup::new(
-                ConstraintDivisor::from_assertion(&assertion, context.trace_len()),
+                ConstraintDivisor::from_assertion_with_powers(&assertion, powers),
                 context.trace_poly_degree(),
                 context.composition_degree(),
             )
         });
 
         // add a new assertion constraint to the current group (last group in the list)
-        group.add(assertion, inv_g, twiddle_map, cc);
+        group.add(assertion, powers, cc);
     }
 
     // make sure groups are sorted by adjustment degree
@@ -184,6 +228,41 @@ where
     groups
 }
 
+/// Interpolates values of all multi-value constraints in the provided groups into value
+/// polynomials.
+///
+/// Inverse twiddles for all required domain sizes are built first (or read from the provided
+/// map), and then all polynomials across all groups are interpolated as a single batch. When
+/// `concurrent` feature is enabled, the polynomials are interpolated in multiple threads; since
+/// each polynomial is interpolated independently, the results are identical to the sequential
+/// version.
+fn interpolate_value_polys<F, E>(
+    groups: &mut [BoundaryConstraintGroup<F, E>],
+    twiddle_map: &mut BTreeMap<usize, Vec<F::BaseField>>,
+) where
+    F: FieldElement,
+    E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
+{
+    let mut constraints = groups
+        .iter_mut()
+        .flat_map(|group| group.constraints_mut().iter_mut())
+        .filter(|constraint| constraint.poly().len() > 1)
+        .collect::<Vec<_>>();
+
+    for constraint in constraints.iter() {
+        let poly_length = constraint.poly().len();
+        twiddle_map
+            .entry(poly_length)
+            .or_insert_with(|| fft::get_inv_twiddles(poly_length));
+    }
+
+    let twiddle_map = &*twiddle_map;
+    iter_mut!(constraints).for_each(|constraint| {
+        let inv_twiddles = &twiddle_map[&constraint.poly().len()];
+        constraint.interpolate_values(inv_twiddles);
+    });
+}
+
 /// Makes sure the assertions are valid in the context of this computation and don't overlap with
 /// each other - i.e. no two assertions are placed against the same column and step combination.
 ///
@@ -4,11 +4,13 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{
-    super::tests::{build_prng, build_sequence_poly},
-    Assertion, BoundaryConstraint,
+    super::tests::{build_context, build_prng, build_sequence_poly},
+    Assertion, BoundaryConstraint, BoundaryConstraints, BoundaryExpr, BoundaryExprConstraint,
+    ConstraintDivisor, PowerTable, StepSelector,
 };
-use crypto::{hashers::Blake3_256, RandomCoin};
-use math::{fields::f128::BaseElement, log2, polynom, FieldElement, StarkField};
+use crate::{AssertionError, AssertionStep};
+use crypto::{hashers::Blake3_256, DefaultRandomCoin};
+use math::{fft, fields::f128::BaseElement, polynom, FieldElement};
 use rand_utils::{rand_value, rand_vector, shuffle};
 use utils::collections::{BTreeMap, Vec};
 
@@ -18,15 +20,14 @@ use utils::collections::{BTreeMap, Vec};
 #[test]
 fn boundary_constraint_from_single_assertion() {
     let mut test_prng = build_prng();
-    let (inv_g, mut twiddle_map, mut prng) = build_constraint_params(16);
+    let (powers, mut prng) = build_constraint_params(16);
 
     // constraint should be built correctly for column 0, step 0
     let value = rand_value::<BaseElement>();
     let assertion = Assertion::single(0, 0, value);
     let constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
         assertion,
-        inv_g,
-        &mut twiddle_map,
+        &powers,
         prng.draw_pair().unwrap(),
     );
     assert_eq!(0, constraint.column());
@@ -49,8 +50,7 @@ fn boundary_constraint_from_single_assertion() {
     let assertion = Assertion::single(1, 8, value);
     let constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
         assertion,
-        inv_g,
-        &mut twiddle_map,
+        &powers,
         prng.draw_pair().unwrap(),
     );
     assert_eq!(1, constraint.column());
@@ -67,23 +67,19 @@ fn boundary_constraint_from_single_assertion() {
         trace_value - value,
         constraint.evaluate_at(rand_value::<BaseElement>(), trace_value)
     );
-
-    // twiddle map was not touched
-    assert!(twiddle_map.is_empty());
 }
 
 #[test]
 fn boundary_constraint_from_periodic_assertion() {
     let mut test_prng = build_prng();
-    let (inv_g, mut twiddle_map, mut prng) = build_constraint_params(16);
+    let (powers, mut prng) = build_constraint_params(16);
 
     // constraint should be built correctly for column 0, step 0, stride 4
     let value = rand_value::<BaseElement>();
     let assertion = Assertion::periodic(0, 0, 4, value);
     let constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
         assertion,
-        inv_g,
-        &mut twiddle_map,
+        &powers,
         prng.draw_pair().unwrap(),
     );
     assert_eq!(0, constraint.column());
@@ -106,8 +102,7 @@ fn boundary_constraint_from_periodic_assertion() {
     let assertion = Assertion::periodic(2, 3, 8, value);
     let constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
         assertion,
-        inv_g,
-        &mut twiddle_map,
+        &powers,
         prng.draw_pair().unwrap(),
     );
     assert_eq!(2, constraint.column());
@@ -124,26 +119,25 @@ fn boundary_constraint_from_periodic_assertion() {
         trace_value - value,
         constraint.evaluate_at(rand_value::<BaseElement>(), trace_value)
     );
-
-    // twiddle map was not touched
-    assert!(twiddle_map.is_empty());
 }
 
 #[test]
 fn boundary_constraint_from_sequence_assertion() {
     let mut test_prng = build_prng();
-    let (inv_g, mut twiddle_map, mut prng) = build_constraint_params(16);
+    let (powers, mut prng) = build_constraint_params(16);
 
     // constraint should be built correctly for column 0, first step 0, stride 4
     let values = rand_vector::<BaseElement>(4);
     let constraint_poly = build_sequence_poly(&values, 16);
-    let assertion = Assertion::sequence(0, 0, 4, values);
-    let constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
+    let assertion = Assertion::sequence(0, 0, 4, values.clone());
+    let mut constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
         assertion,
-        inv_g,
-        &mut twiddle_map,
+        &powers,
         prng.draw_pair().unwrap(),
     );
+    // values are kept in evaluation form until they are interpolated
+    assert_eq!(values, constraint.poly());
+    constraint.interpolate_values(&fft::get_inv_twiddles(4));
     assert_eq!(0, constraint.column());
     assert_eq!(constraint_poly, constraint.poly());
     assert_eq!((0, BaseElement::ONE), constraint.poly_offset());
@@ -151,7 +145,6 @@ fn boundary_constraint_from_sequence_assertion() {
         &test_prng.draw_pair::<BaseElement>().unwrap(),
         constraint.cc()
     );
-    assert_eq!(1, twiddle_map.len());
 
     // sequence value constraints with no offset should evaluate to
     // trace_value - constraint_poly(x)
@@ -166,20 +159,22 @@ fn boundary_constraint_from_sequence_assertion() {
     let values = rand_vector::<BaseElement>(2);
     let constraint_poly = build_sequence_poly(&values, 16);
     let assertion = Assertion::sequence(0, 3, 8, values);
-    let constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
+    let mut constraint = BoundaryConstraint::<BaseElement, BaseElement>::new(
         assertion,
-        inv_g,
-        &mut twiddle_map,
+        &powers,
         prng.draw_pair().unwrap(),
     );
+    constraint.interpolate_values(&fft::get_inv_twiddles(2));
     assert_eq!(0, constraint.column());
     assert_eq!(constraint_poly, constraint.poly());
-    assert_eq!((3, inv_g.exp(3)), constraint.poly_offset());
+    assert_eq!(
+        (3, powers.generator().inv().exp(3)),
+        constraint.poly_offset()
+    );
     assert_eq!(
         &test_prng.draw_pair::<BaseElement>().unwrap(),
         constraint.cc()
     );
-    assert_eq!(2, twiddle_map.len());
 
     // sequence value constraints with offset should evaluate to
     // trace_value - constraint_poly(x * offset)
@@ -191,6 +186,146 @@ fn boundary_constraint_from_sequence_assertion() {
     );
 }
 
+#[test]
+fn boundary_constraints_interpolate_value_polys() {
+    let trace_length = 16;
+    let trace_width = 4;
+    let context = build_context::<BaseElement>(trace_length, trace_width, 3 * trace_width);
+
+    // place sequence assertions with different strides and first steps against all columns so
+    // that value polynomials of several groups get interpolated as a single batch
+    let mut assertions = Vec::new();
+    let mut expected = BTreeMap::new();
+    for column in 0..trace_width {
+        for (first_step, stride) in [(0, 4), (1, 4), (2, 8)] {
+            let values = rand_vector::<BaseElement>(trace_length / stride);
+            expected.insert(
+                (column, first_step),
+                build_sequence_poly(&values, trace_length),
+            );
+            assertions.push(Assertion::sequence(column, first_step, stride, values));
+        }
+    }
+
+    let mut prng = build_prng();
+    let coefficients = (0..assertions.len())
+        .map(|_| prng.draw_pair().unwrap())
+        .collect::<Vec<(BaseElement, BaseElement)>>();
+    let constraints =
+        BoundaryConstraints::<BaseElement>::new(&context, assertions, Vec::new(), &coefficients);
+
+    let groups = constraints.main_constraints();
+    assert_eq!(3, groups.len());
+    for constraint in groups.iter().flat_map(|group| group.constraints()) {
+        let key = (constraint.column(), constraint.poly_offset().0);
+        assert_eq!(expected[&key], constraint.poly());
+    }
+}
+
+// BOUNDARY EXPRESSION TESTS
+// ================================================================================================
+
+#[test]
+fn boundary_expr_constraint() {
+    // a degree 3 expression requires constraint evaluation blowup factor of 4
+    let context = build_context::<BaseElement>(16, 4, 1);
+    assert_eq!(2 * 16 - 1, context.composition_degree());
+    let context = context.set_boundary_expr_degrees(vec![3]);
+    assert_eq!(1, context.num_boundary_exprs());
+    assert_eq!(4 * 16 - 1, context.composition_degree());
+
+    let cc = build_prng().draw_pair().unwrap();
+    let expr = BoundaryExpr::new(
+        StepSelector::Step(5),
+        |row: &[BaseElement]| row[0] * row[1] * row[2],
+        3,
+        BaseElement::new(7),
+    );
+    let constraint = BoundaryExprConstraint::<BaseElement, BaseElement>::new(expr, &context, cc);
+    assert_eq!(5, constraint.step());
+    assert_eq!(&ConstraintDivisor::from_step(5, 16), constraint.divisor());
+
+    // the numerator has degree 3 * 15 = 45, and after the division by (x - g^5), the degree
+    // needs to be adjusted by 19 to reach the composition degree of 63
+    assert_eq!(19, constraint.degree_adjustment());
+
+    // the numerator is (f(row) - v) * (alpha + beta * xp)
+    let row = [2, 3, 4, 5].map(BaseElement::new);
+    let xp = rand_value::<BaseElement>();
+    assert_eq!(
+        (cc.0 + cc.1 * xp) * BaseElement::new(24 - 7),
+        constraint.evaluate_at(&row, xp)
+    );
+    let row = [1, 1, 7, 5].map(BaseElement::new);
+    assert_eq!(BaseElement::ZERO, constraint.evaluate_at(&row, xp));
+}
+
+#[test]
+fn boundary_expr_step_selector() {
+    assert_eq!(0, StepSelector::First.get_step(16));
+    assert_eq!(15, StepSelector::Last.get_step(16));
+    assert_eq!(3, StepSelector::Step(3).get_step(16));
+
+    // an expression at the first step shares the divisor with assertions against the first step
+    let assertion = Assertion::single(1, 0, BaseElement::ONE);
+    assert_eq!(
+        ConstraintDivisor::from_assertion(&assertion, 16),
+        ConstraintDivisor::from_step(0, 16)
+    );
+    let assertion = Assertion::single(1, 15, BaseElement::ONE);
+    assert_eq!(
+        ConstraintDivisor::from_assertion(&assertion, 16),
+        ConstraintDivisor::from_step(15, 16)
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "boundary expression step must be smaller than trace length 16, but was 16"
+)]
+fn boundary_expr_step_out_of_bounds() {
+    StepSelector::Step(16).get_step(16);
+}
+
+#[test]
+fn boundary_expr_try_get_step() {
+    assert_eq!(Ok(0), StepSelector::First.try_get_step(16));
+    assert_eq!(Ok(15), StepSelector::Last.try_get_step(16));
+    assert_eq!(Ok(3), StepSelector::Step(3).try_get_step(16));
+    let step = AssertionStep::last_minus(2);
+    assert_eq!(13, StepSelector::At(step).get_step(16));
+    assert_eq!(Ok(13), StepSelector::At(step).try_get_step(16));
+
+    assert_eq!(
+        Err(AssertionError::StepOutOfTrace {
+            step: 16,
+            trace_len: 16
+        }),
+        StepSelector::Step(16).try_get_step(16)
+    );
+    assert_eq!(
+        Err(AssertionError::StepOutOfTrace {
+            step: -2,
+            trace_len: 16
+        }),
+        StepSelector::At(AssertionStep::last_minus(17)).try_get_step(16)
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "invalid boundary expression step: step 1024 is outside of the execution trace of length 16"
+)]
+fn boundary_expr_step_from_pub_input_out_of_bounds() {
+    StepSelector::At(AssertionStep::from_pub_input(1024)).get_step(16);
+}
+
+#[test]
+#[should_panic(expected = "blowup factor too small; expected at least 16, but was 8")]
+fn boundary_expr_degree_too_large() {
+    let _ = build_context::<BaseElement>(16, 4, 1).set_boundary_expr_degrees(vec![9]);
+}
+
 // PREPARE ASSERTIONS
 // ================================================================================================
 
@@ -238,7 +373,7 @@ fn prepare_assertions() {
 
 #[test]
 #[should_panic(
-    expected = "assertion (column=0, steps=[1, 9, ...], value=7) overlaps with assertion (column=0, step=9, value=5)"
+    expected = "assertion (column=0, steps=[1, 9, ...], value=0x00000000000000000000000000000007) overlaps with assertion (column=0, step=9, value=0x00000000000000000000000000000005)"
 )]
 fn prepare_assertions_with_overlap() {
     let assertions = vec![
@@ -250,7 +385,7 @@ fn prepare_assertions_with_overlap() {
 
 #[test]
 #[should_panic(
-    expected = "assertion (column=0, step=16, value=5) is invalid: expected trace length to be at least 32, but was 16"
+    expected = "assertion (column=0, step=16, value=0x00000000000000000000000000000005) is invalid: expected trace length to be at least 32, but was 16"
 )]
 fn prepare_assertions_with_invalid_trace_length() {
     let assertions = vec![Assertion::single(0, 16, BaseElement::new(5))];
@@ -259,7 +394,7 @@ fn prepare_assertions_with_invalid_trace_length() {
 
 #[test]
 #[should_panic(
-    expected = "assertion (column=3, step=17, value=5) is invalid: expected trace width to be at least 3, but was 2"
+    expected = "assertion (column=3, step=17, value=0x00000000000000000000000000000005) is invalid: expected trace width to be at least 3, but was 2"
 )]
 fn prepare_assertions_with_invalid_trace_width() {
     let assertions = vec![Assertion::single(3, 17, BaseElement::new(5))];
@@ -269,16 +404,13 @@ fn prepare_assertions_with_invalid_trace_width() {
 // HELPER FUNCTIONS
 // ================================================================================================
 
-#[allow(clippy::type_complexity)]
 fn build_constraint_params(
     trace_length: usize,
 ) -> (
-    BaseElement,
-    BTreeMap<usize, Vec<BaseElement>>,
-    RandomCoin<BaseElement, Blake3_256<BaseElement>>,
+    PowerTable<BaseElement>,
+    DefaultRandomCoin<BaseElement, Blake3_256<BaseElement>>,
 ) {
-    let inv_g = BaseElement::get_root_of_unity(log2(trace_length)).inv();
+    let powers = PowerTable::new(trace_length, []);
     let prng = build_prng();
-    let twiddle_map = BTreeMap::<usize, Vec<BaseElement>>::new();
-    (inv_g, twiddle_map, prng)
+    (powers, prng)
 }
@@ -4,7 +4,18 @@
 // LICENSE file in the root directory of this source tree.
 
 use math::FieldElement;
-use utils::collections::Vec;
+use utils::collections::{InlineVec, Vec};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of constraints of each kind (transition constraints, assertions, and boundary
+/// expressions) for which composition coefficients are stored inline; coefficients for AIRs with
+/// more constraints are moved to the heap.
+pub const MAX_INLINE_CONSTRAINTS: usize = 256;
+
+/// Composition coefficients $(\alpha_i, \beta_i)$ for a list of constraints.
+pub type CoefficientPairs<E> = InlineVec<(E, E), MAX_INLINE_CONSTRAINTS>;
 
 // AUXILIARY TRACE SEGMENT RANDOMNESS
 // ================================================================================================
@@ -61,12 +72,16 @@ impl<E: FieldElement> Default for AuxTraceRandElements<E> {
 /// * $C_i(x)$ is an evaluation of the $i$th constraint at $x$.
 /// * $d_i$ is the degree adjustment factor needed to normalize all constraints to the same degree.
 ///
-/// The coefficients are separated into two lists: one for transition constraints and another one
-/// for boundary constraints. This separation is done for convenience only.
+/// The coefficients are separated into three lists: one for transition constraints, one for
+/// boundary constraints, and one for boundary expressions. This separation is done for
+/// convenience only. Each list keeps coefficients for up to [MAX_INLINE_CONSTRAINTS] constraints
+/// inline, and thus, drawing the coefficients does not require heap allocations beyond the one
+/// needed to hold a set of coefficients for every out-of-domain point.
 #[derive(Debug, Clone)]
 pub struct ConstraintCompositionCoefficients<E: FieldElement> {
-    pub transition: Vec<(E, E)>,
-    pub boundary: Vec<(E, E)>,
+    pub transition: CoefficientPairs<E>,
+    pub boundary: CoefficientPairs<E>,
+    pub boundary_exprs: CoefficientPairs<E>,
 }
 
 // DEEP COMPOSITION COEFFICIENTS
@@ -109,12 +124,79 @@ pub struct ConstraintCompositionCoefficients<E: FieldElement> {
 /// C(x) = Y(x) \cdot (\lambda + \mu \cdot x)
 /// $$
 /// where $\lambda$ and $\mu$ are the composition coefficients for degree adjustment.
+///
+/// When proof options specify more than one out-of-domain point, $Y(x)$ is a sum of the above
+/// combinations computed for each of the points, and a separate set of coefficients
+/// $\alpha_i, \beta_i, \gamma_i$ and $\delta_j$ is drawn for every point. Coefficients for all
+/// points are stored in the same vectors: coefficients for the first point are followed by
+/// coefficients for the second point etc.
+///
+/// When the evaluation frame of the AIR contains rows beyond the first two (see
+/// [AirContext::set_frame_offsets()](crate::AirContext::set_frame_offsets)), $\beta_i$ is used
+/// for the second row of the frame, and $Y(x)$ also includes the terms
+/// $\frac{T_i(x) - T_i(z \cdot g^k)}{x - z \cdot g^k}$ for every other row offset $k$, each with
+/// its own coefficient.
 #[derive(Debug, Clone)]
 pub struct DeepCompositionCoefficients<E: FieldElement> {
     /// Trace polynomial composition coefficients $\alpha_i$, $\beta_i$, and $\gamma_i$.
     pub trace: Vec<(E, E, E)>,
+    /// Trace polynomial composition coefficients for rows of the evaluation frame beyond the
+    /// first two; for every point, coefficients for the first trace polynomial (one for each of
+    /// the additional rows) are followed by coefficients for the second polynomial etc. This is
+    /// empty for AIRs with the default two-row frame.
+    pub trace_offsets: Vec<E>,
     /// Constraint column polynomial composition coefficients $\delta_j$.
     pub constraints: Vec<E>,
     /// Degree adjustment composition coefficients $\lambda$ and $\mu$.
     pub degree: (E, E),
 }
+
+impl<E: FieldElement> DeepCompositionCoefficients<E> {
+    /// Returns trace polynomial composition coefficients for the out-of-domain point with the
+    /// specified index.
+    ///
+    /// # Panics
+    /// Panics if `num_ood_points` does not evenly divide the number of trace coefficients, or if
+    /// `point_idx` is greater than or equal to `num_ood_points`.
+    pub fn trace_coefficients(&self, point_idx: usize, num_ood_points: usize) -> &[(E, E, E)] {
+        get_point_coefficients(&self.trace, point_idx, num_ood_points)
+    }
+
+    /// Returns trace polynomial composition coefficients for rows of the evaluation frame beyond
+    /// the first two for the out-of-domain point with the specified index.
+    ///
+    /// # Panics
+    /// Panics if `num_ood_points` does not evenly divide the number of these coefficients, or if
+    /// `point_idx` is greater than or equal to `num_ood_points`.
+    pub fn offset_coefficients(&self, point_idx: usize, num_ood_points: usize) -> &[E] {
+        get_point_coefficients(&self.trace_offsets, point_idx, num_ood_points)
+    }
+
+    /// Returns constraint column polynomial composition coefficients for the out-of-domain point
+    /// with the specified index.
+    ///
+    /// # Panics
+    /// Panics if `num_ood_points` does not evenly divide the number of constraint coefficients,
+    /// or if `point_idx` is greater than or equal to `num_ood_points`.
+    pub fn constraint_coefficients(&self, point_idx: usize, num_ood_points: usize) -> &[E] {
+        get_point_coefficients(&self.constraints, point_idx, num_ood_points)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the chunk of `coefficients` which corresponds to the specified out-of-domain point.
+fn get_point_coefficients<T>(coefficients: &[T], point_idx: usize, num_ood_points: usize) -> &[T] {
+    assert_eq!(
+        0,
+        coefficients.len() % num_ood_points,
+        "number of coefficients must be divisible by the number of OOD points"
+    );
+    assert!(
+        point_idx < num_ood_points,
+        "OOD point index must be smaller than {num_ood_points}, but was {point_idx}"
+    );
+    let chunk_size = coefficients.len() / num_ood_points;
+    &coefficients[point_idx * chunk_size..(point_idx + 1) * chunk_size]
+}
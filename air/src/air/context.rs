@@ -3,7 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::{air::TransitionConstraintDegree, ProofOptions, TraceInfo};
+use crate::{
+    air::{
+        transition::{validate_frame_offsets, DEFAULT_FRAME_OFFSETS},
+        ConstraintDescriptor, TransitionConstraintDegree, TransitionDivisorKind,
+    },
+    ProofOptions, TraceInfo,
+};
 use math::{log2, StarkField};
 use utils::collections::Vec;
 
@@ -21,7 +27,12 @@ pub struct AirContext<B: StarkField> {
     pub(super) ce_blowup_factor: usize,
     pub(super) trace_domain_generator: B,
     pub(super) lde_domain_generator: B,
-    pub(super) num_transition_exemptions: usize,
+    pub(super) transition_exemptions: Vec<usize>,
+    pub(super) boundary_expr_degrees: Vec<usize>,
+    pub(super) main_transition_constraint_ids: Vec<&'static str>,
+    pub(super) aux_transition_constraint_ids: Vec<&'static str>,
+    pub(super) keyed_coefficients: bool,
+    pub(super) frame_offsets: Vec<usize>,
 }
 
 impl<B: StarkField> AirContext<B> {
@@ -145,6 +156,8 @@ impl<B: StarkField> AirContext<B> {
 
         let trace_length = trace_info.length();
         let lde_domain_size = trace_length * options.blowup_factor();
+        let num_transition_constraints =
+            main_transition_constraint_degrees.len() + aux_transition_constraint_degrees.len();
 
         AirContext {
             options,
@@ -156,10 +169,106 @@ impl<B: StarkField> AirContext<B> {
             ce_blowup_factor,
             trace_domain_generator: B::get_root_of_unity(log2(trace_length)),
             lde_domain_generator: B::get_root_of_unity(log2(lde_domain_size)),
-            num_transition_exemptions: 1,
+            transition_exemptions: vec![1; num_transition_constraints],
+            boundary_expr_degrees: Vec::new(),
+            main_transition_constraint_ids: Vec::new(),
+            aux_transition_constraint_ids: Vec::new(),
+            keyed_coefficients: false,
+            frame_offsets: DEFAULT_FRAME_OFFSETS.to_vec(),
         }
     }
 
+    /// Returns a new instance of [AirContext] instantiated for computations which require a single
+    /// execution trace segment, with transition constraints described by the specified list of
+    /// constraint descriptors.
+    ///
+    /// This is the same as [AirContext::new()], except that every constraint is also assigned a
+    /// stable identifier; these identifiers are used to derive composition coefficients for
+    /// constraints when keyed coefficients are enabled via
+    /// [AirContext::set_keyed_coefficients()].
+    ///
+    /// # Panics
+    /// Panics if
+    /// * Any of the conditions listed for [AirContext::new()] is violated.
+    /// * Two or more descriptors have the same identifier.
+    pub fn new_with_descriptors(
+        trace_info: TraceInfo,
+        transition_constraints: Vec<ConstraintDescriptor>,
+        num_assertions: usize,
+        options: ProofOptions,
+    ) -> Self {
+        assert!(
+            !trace_info.is_multi_segment(),
+            "provided trace info describes a multi-segment execution trace"
+        );
+        Self::new_multi_segment_with_descriptors(
+            trace_info,
+            transition_constraints,
+            Vec::new(),
+            num_assertions,
+            0,
+            options,
+        )
+    }
+
+    /// Returns a new instance of [AirContext] instantiated for computations which require multiple
+    /// execution trace segments, with transition constraints described by the specified lists of
+    /// constraint descriptors.
+    ///
+    /// This is the same as [AirContext::new_multi_segment()], except that every constraint is
+    /// also assigned a stable identifier; identifiers must be unique across constraints placed
+    /// against all trace segments.
+    ///
+    /// # Panics
+    /// Panics if
+    /// * Any of the conditions listed for [AirContext::new_multi_segment()] is violated.
+    /// * Two or more descriptors have the same identifier.
+    pub fn new_multi_segment_with_descriptors(
+        trace_info: TraceInfo,
+        main_transition_constraints: Vec<ConstraintDescriptor>,
+        aux_transition_constraints: Vec<ConstraintDescriptor>,
+        num_main_assertions: usize,
+        num_aux_assertions: usize,
+        options: ProofOptions,
+    ) -> Self {
+        let main_ids = main_transition_constraints
+            .iter()
+            .map(|descriptor| descriptor.id())
+            .collect::<Vec<_>>();
+        let aux_ids = aux_transition_constraints
+            .iter()
+            .map(|descriptor| descriptor.id())
+            .collect::<Vec<_>>();
+
+        let mut sorted_ids = main_ids.iter().chain(aux_ids.iter()).collect::<Vec<_>>();
+        sorted_ids.sort_unstable();
+        for pair in sorted_ids.windows(2) {
+            assert!(
+                pair[0] != pair[1],
+                "constraint identifier '{}' is assigned to more than one constraint",
+                pair[0]
+            );
+        }
+
+        let mut result = Self::new_multi_segment(
+            trace_info,
+            main_transition_constraints
+                .into_iter()
+                .map(TransitionConstraintDegree::from)
+                .collect(),
+            aux_transition_constraints
+                .into_iter()
+                .map(TransitionConstraintDegree::from)
+                .collect(),
+            num_main_assertions,
+            num_aux_assertions,
+            options,
+        );
+        result.main_transition_constraint_ids = main_ids;
+        result.aux_transition_constraint_ids = aux_ids;
+        result
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -219,6 +328,17 @@ impl<B: StarkField> AirContext<B> {
         self.aux_transition_constraint_degrees.len()
     }
 
+    /// Returns degree descriptors of transition constraints placed against the main trace segment.
+    pub fn main_transition_constraint_degrees(&self) -> &[TransitionConstraintDegree] {
+        &self.main_transition_constraint_degrees
+    }
+
+    /// Returns degree descriptors of transition constraints placed against all auxiliary trace
+    /// segments.
+    pub fn aux_transition_constraint_degrees(&self) -> &[TransitionConstraintDegree] {
+        &self.aux_transition_constraint_degrees
+    }
+
     /// Returns the total number of assertions defined for a computation.
     ///
     /// The number of assertions consists of the assertions placed against the main segment of an
@@ -227,20 +347,88 @@ impl<B: StarkField> AirContext<B> {
         self.num_main_assertions + self.num_aux_assertions
     }
 
-    /// Returns the number of rows at the end of an execution trace to which transition constraints
-    /// do not apply.
+    /// Returns the largest number of rows at the end of an execution trace to which transition
+    /// constraints do not apply; cyclic transition constraints apply to all rows regardless of
+    /// this number.
     ///
     /// This is guaranteed to be at least 1 (which is the default value), but could be greater.
     /// The maximum number of exemptions is determined by a combination of transition constraint
-    /// degrees and blowup factor specified for the computation.
+    /// degrees and blowup factor specified for the computation. When all transition constraints
+    /// have the same number of exemptions, this is that number.
     pub fn num_transition_exemptions(&self) -> usize {
-        self.num_transition_exemptions
+        self.transition_exemptions
+            .iter()
+            .copied()
+            .max()
+            .expect("no transition exemptions")
+    }
+
+    /// Returns the number of rows at the end of an execution trace to which each of the
+    /// transition constraints does not apply.
+    ///
+    /// The list contains one entry per transition constraint: entries for constraints placed
+    /// against the main trace segment are followed by entries for constraints placed against
+    /// auxiliary trace segments. Entries for cyclic constraints are ignored since cyclic
+    /// constraints apply to all rows.
+    pub fn transition_exemptions(&self) -> &[usize] {
+        &self.transition_exemptions
+    }
+
+    /// Returns the number of boundary expressions defined for a computation.
+    ///
+    /// This number is used to determine how many boundary expression coefficients need to be
+    /// generated for merging boundary expressions into a composition polynomial.
+    pub fn num_boundary_exprs(&self) -> usize {
+        self.boundary_expr_degrees.len()
+    }
+
+    /// Returns degrees of all boundary expressions defined for a computation.
+    ///
+    /// Boundary expressions returned from
+    /// [Air::get_boundary_exprs()](crate::Air::get_boundary_exprs) are expected to be in the
+    /// order defined by this list.
+    pub fn boundary_expr_degrees(&self) -> &[usize] {
+        &self.boundary_expr_degrees
+    }
+
+    /// Returns identifiers of transition constraints placed against the main trace segment.
+    ///
+    /// The list is empty unless the context was instantiated from constraint descriptors.
+    pub fn main_transition_constraint_ids(&self) -> &[&'static str] {
+        &self.main_transition_constraint_ids
+    }
+
+    /// Returns identifiers of transition constraints placed against all auxiliary trace
+    /// segments.
+    ///
+    /// The list is empty unless the context was instantiated from constraint descriptors.
+    pub fn aux_transition_constraint_ids(&self) -> &[&'static str] {
+        &self.aux_transition_constraint_ids
+    }
+
+    /// Returns true if composition coefficients for transition constraints are derived from
+    /// constraint identifiers rather than drawn in the order in which constraints are listed.
+    pub fn keyed_coefficients(&self) -> bool {
+        self.keyed_coefficients
+    }
+
+    /// Returns offsets of the execution trace rows which form an evaluation frame, relative to
+    /// the current row.
+    ///
+    /// By default, an evaluation frame consists of the current row and the next row, i.e., the
+    /// offsets are `[0, 1]`.
+    pub fn frame_offsets(&self) -> &[usize] {
+        &self.frame_offsets
     }
 
     // DATA MUTATORS
     // --------------------------------------------------------------------------------------------
 
-    /// Sets the number of transition exemptions for this context.
+    /// Sets the number of transition exemptions for all transition constraints of this context.
+    ///
+    /// This is a convenience method which is equivalent to calling
+    /// [set_transition_exemptions()](Self::set_transition_exemptions) with `n` specified for
+    /// every transition constraint.
     ///
     /// # Panics
     /// Panics if:
@@ -249,34 +437,152 @@ impl<B: StarkField> AirContext<B> {
     /// * Given the combination of transition constraints degrees and the blowup factor in this
     ///   context, the number of exemptions is too larger for a valid computation of the constraint
     ///   composition polynomial.
-    pub fn set_num_transition_exemptions(mut self, n: usize) -> Self {
-        assert!(
-            n > 0,
-            "number of transition exemptions must be greater than zero"
-        );
-        // exemptions which are for more than half the trace plus one are probably a mistake
-        assert!(
-            n <= self.trace_len() / 2 + 1,
-            "number of transition exemptions cannot exceed {}, but was {}",
-            self.trace_len() / 2 + 1,
-            n
+    pub fn set_num_transition_exemptions(self, n: usize) -> Self {
+        let num_constraints = self.num_transition_constraints();
+        self.set_transition_exemptions(vec![n; num_constraints])
+    }
+
+    /// Sets the number of transition exemptions separately for each transition constraint of this
+    /// context.
+    ///
+    /// The list must contain one entry per transition constraint degree: entries for constraints
+    /// placed against the main trace segment followed by entries for constraints placed against
+    /// auxiliary trace segments. A constraint with `k` exemptions does not apply to the last `k`
+    /// rows of the execution trace. Entries for cyclic constraints are validated but otherwise
+    /// ignored since cyclic constraints apply to all rows.
+    ///
+    /// Exempting only the constraints which actually fail at the end of the trace keeps the
+    /// remaining constraints enforced over the entire trace (except for the last row).
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * The number of entries is not equal to the number of transition constraints.
+    /// * Any of the entries is zero.
+    /// * Any of the entries exceeds half of the trace length.
+    /// * Given the degree of a transition constraint and the blowup factor in this context, the
+    ///   number of exemptions for the constraint is too large for a valid computation of the
+    ///   constraint composition polynomial.
+    pub fn set_transition_exemptions(mut self, exemptions: Vec<usize>) -> Self {
+        assert_eq!(
+            exemptions.len(),
+            self.num_transition_constraints(),
+            "number of transition exemption entries must be equal to the number of transition constraints"
         );
-        // make sure the composition polynomial can be computed correctly with the specified
-        // number of exemptions
-        for degree in self
+
+        let degrees = self
             .main_transition_constraint_degrees
             .iter()
-            .chain(self.aux_transition_constraint_degrees.iter())
-        {
-            let eval_degree = degree.get_evaluation_degree(self.trace_len());
-            let max_exemptions = self.composition_degree() + self.trace_len() - eval_degree;
+            .chain(self.aux_transition_constraint_degrees.iter());
+        for (degree, &n) in degrees.zip(exemptions.iter()) {
+            assert!(
+                n > 0,
+                "number of transition exemptions must be greater than zero"
+            );
+            // exemptions which are for more than half the trace plus one are probably a mistake
+            assert!(
+                n <= self.trace_len() / 2 + 1,
+                "number of transition exemptions cannot exceed {}, but was {}",
+                self.trace_len() / 2 + 1,
+                n
+            );
+            // make sure the composition polynomial can be computed correctly with the specified
+            // number of exemptions; cyclic constraints are not affected by exemptions
+            if degree.divisor() == TransitionDivisorKind::Transition {
+                let eval_degree = degree.get_evaluation_degree(self.trace_len());
+                let max_exemptions = self.composition_degree() + self.trace_len() - eval_degree;
+                assert!(
+                    n <= max_exemptions,
+                    "number of transition exemptions cannot exceed: {max_exemptions}, but was {n}"
+                )
+            }
+        }
+
+        self.transition_exemptions = exemptions;
+        self
+    }
+
+    /// Sets the degrees of boundary expressions for this context.
+    ///
+    /// The list of degrees defines the total number of boundary expressions and their degrees in
+    /// the values of a trace row. Since a boundary expression of degree $d$ is composed as a
+    /// polynomial of degree $d \cdot (n - 1) - 1$, where $n$ is the trace length, this increases
+    /// the constraint evaluation blowup factor to be at least $d$ (rounded up to the next power
+    /// of two).
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Any of the degrees is zero.
+    /// * Blowup factor specified by the options of this context is too small to accommodate
+    ///   degrees of the specified boundary expressions.
+    pub fn set_boundary_expr_degrees(mut self, degrees: Vec<usize>) -> Self {
+        for &degree in degrees.iter() {
+            assert!(
+                degree > 0,
+                "boundary expression degree must be greater than zero"
+            );
+            let min_blowup_factor = degree.next_power_of_two();
             assert!(
-                n <= max_exemptions,
-                "number of transition exemptions cannot exceed: {max_exemptions}, but was {n}"
-            )
+                self.options.blowup_factor() >= min_blowup_factor,
+                "blowup factor too small; expected at least {}, but was {}",
+                min_blowup_factor,
+                self.options.blowup_factor()
+            );
+            self.ce_blowup_factor = self.ce_blowup_factor.max(min_blowup_factor);
         }
 
-        self.num_transition_exemptions = n;
+        self.boundary_expr_degrees = degrees;
+        self
+    }
+
+    /// Sets offsets of the execution trace rows which form an evaluation frame for this context.
+    ///
+    /// For the current row at step `i`, the evaluation frame passed to
+    /// [Air::evaluate_transition()](crate::Air::evaluate_transition) contains the rows at steps
+    /// `i + offsets[0]`, `i + offsets[1]` etc., and thus, transition constraints can relate rows
+    /// which are more than one step apart. The prover reads all these rows from the trace
+    /// low-degree extension, and the verifier receives out-of-domain evaluations of the trace at
+    /// every offset. Rows at the end of the trace wrap around to the start of the trace; a
+    /// constraint which reads the row at offset `k` usually needs to be exempt from the last `k`
+    /// steps of the trace (see [set_transition_exemptions()](Self::set_transition_exemptions)).
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `offsets` contains fewer than two offsets, does not start with zero, or is not sorted in
+    ///   strictly ascending order.
+    /// * Any of the offsets is greater than or equal to the trace length.
+    pub fn set_frame_offsets(mut self, offsets: Vec<usize>) -> Self {
+        validate_frame_offsets(&offsets);
+        let max_offset = offsets[offsets.len() - 1];
+        assert!(
+            max_offset < self.trace_len(),
+            "row offsets of an evaluation frame must be smaller than trace length {}, but offset {} was specified",
+            self.trace_len(),
+            max_offset
+        );
+        self.frame_offsets = offsets;
+        self
+    }
+
+    /// Specifies whether composition coefficients for transition constraints should be derived
+    /// from constraint identifiers.
+    ///
+    /// By default, coefficients for transition constraints are drawn from the public coin one
+    /// after another, and thus, inserting a new constraint into the list shifts coefficients of
+    /// all subsequent constraints. When keyed coefficients are enabled, coefficients for each
+    /// constraint are drawn from a coin derived from the state of the public coin and the
+    /// identifier of the constraint, and thus, coefficients of a constraint do not depend on
+    /// which other constraints are defined for the computation. Both the prover and the verifier
+    /// derive coefficients from the context of the AIR, and thus, use the same method.
+    ///
+    /// # Panics
+    /// Panics if keyed coefficients are enabled but the context was not instantiated from
+    /// constraint descriptors.
+    pub fn set_keyed_coefficients(mut self, enabled: bool) -> Self {
+        assert!(
+            !enabled || !self.main_transition_constraint_ids.is_empty(),
+            "keyed coefficients require transition constraints to be described by constraint descriptors"
+        );
+        self.keyed_coefficients = enabled;
         self
     }
 }
@@ -0,0 +1,301 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{Air, AssertionDescription, EvaluationFrame};
+use math::{fields::Tracked, FieldElement, StarkField};
+use utils::{collections::Vec, string::String};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of random evaluation frames over which transition constraints are evaluated by
+/// [Air::dependency_graph()].
+pub const DEFAULT_NUM_DEPENDENCY_SAMPLES: usize = 8;
+
+/// Maximum number of inputs tracked during a single evaluation of transition constraints.
+const BATCH_SIZE: usize = 64;
+
+/// Seed of the generator of values for evaluation frames; a fixed seed makes graphs of the same
+/// AIR identical across runs.
+const SAMPLING_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+// DEPENDENCY GRAPH
+// ================================================================================================
+/// A graph of dependencies between columns of the main trace segment, transition constraints,
+/// and boundary assertions of an instance of an AIR.
+///
+/// A graph is built from a compiled AIR via [Air::dependency_graph()]: transition constraints are
+/// evaluated over a set of pseudo-random evaluation frames with every input of an evaluation
+/// tracked separately, and the inputs each constraint evaluation depends on are recorded. Sets of
+/// inputs recorded for different frames are combined, and thus, a dependency which exists only
+/// for some values of the trace (e.g., when a constraint branches on the value of a column) is
+/// found as long as at least one of the frames takes the corresponding branch. To make this more
+/// likely, values of the frames are ZERO and ONE as often as they are uniformly random elements.
+///
+/// Only the transition constraints placed against the main trace segment are evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Number of columns in the main segment of the execution trace.
+    pub main_trace_width: usize,
+    /// Number of periodic columns defined by the AIR.
+    pub num_periodic_columns: usize,
+    /// Inputs read by each of the transition constraints placed against the main trace segment.
+    pub constraints: Vec<ConstraintDependencies>,
+    /// Assertions placed against the main trace segment; each assertion pins the cells of its
+    /// column at the asserted steps.
+    pub assertions: Vec<AssertionDescription>,
+}
+
+impl DependencyGraph {
+    /// Builds a dependency graph of the provided AIR by evaluating its transition constraints
+    /// over the specified number of pseudo-random evaluation frames.
+    ///
+    /// # Panics
+    /// Panics if `num_samples` is zero.
+    pub fn from_air<A: Air + ?Sized>(air: &A, num_samples: usize) -> Self {
+        assert!(
+            num_samples > 0,
+            "number of samples must be greater than zero"
+        );
+        let width = air.trace_layout().main_trace_width();
+        let num_periodic_columns = air.get_periodic_column_values().len();
+        let num_constraints = air.context().num_main_transition_constraints();
+        let frame_offsets = air.context().frame_offsets();
+        let num_frame_inputs = frame_offsets.len() * width;
+
+        // inputs of an evaluation are cells of the current row, followed by cells of all other
+        // rows of the frame, followed by values of periodic columns
+        let num_inputs = num_frame_inputs + num_periodic_columns;
+        let mut reads = vec![vec![false; num_inputs]; num_constraints];
+
+        let mut rng = SampleGenerator::new(SAMPLING_SEED);
+        for _ in 0..num_samples {
+            let values = (0..num_inputs)
+                .map(|_| rng.draw::<A::BaseField>())
+                .collect::<Vec<_>>();
+
+            for batch_start in (0..num_inputs).step_by(BATCH_SIZE) {
+                let batch = batch_start..(batch_start + BATCH_SIZE).min(num_inputs);
+                let inputs = values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &value)| {
+                        if batch.contains(&i) {
+                            Tracked::input(value, i - batch_start)
+                        } else {
+                            Tracked::from(value)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let rows = inputs[..num_frame_inputs]
+                    .chunks(width)
+                    .map(|row| row.to_vec())
+                    .collect();
+                let frame = EvaluationFrame::from_offset_rows(frame_offsets, rows);
+                let mut result = vec![Tracked::ZERO; num_constraints];
+                air.evaluate_transition(&frame, &inputs[num_frame_inputs..], &mut result);
+
+                for (constraint_reads, evaluation) in reads.iter_mut().zip(result.iter()) {
+                    for position in 0..batch.len() {
+                        if evaluation.reads() & (1 << position) != 0 {
+                            constraint_reads[batch_start + position] = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        let select = |reads: &[bool]| {
+            reads
+                .iter()
+                .enumerate()
+                .filter(|(_, &read)| read)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+        // reads of all rows which follow the current row are combined together
+        let select_later = |reads: &[bool]| {
+            (0..width)
+                .filter(|&column| reads.iter().skip(column).step_by(width).any(|&read| read))
+                .collect::<Vec<_>>()
+        };
+        let constraints = reads
+            .iter()
+            .map(|reads| ConstraintDependencies {
+                current: select(&reads[..width]),
+                next: select_later(&reads[width..num_frame_inputs]),
+                periodic: select(&reads[num_frame_inputs..]),
+            })
+            .collect();
+
+        let trace_length = air.trace_length();
+        let assertions = air
+            .get_assertions()
+            .iter()
+            .map(|assertion| AssertionDescription::new(assertion, trace_length, true))
+            .collect();
+
+        DependencyGraph {
+            main_trace_width: width,
+            num_periodic_columns,
+            constraints,
+            assertions,
+        }
+    }
+
+    /// Returns indexes of the main trace columns which are not read by any of the transition
+    /// constraints, in ascending order.
+    ///
+    /// Values in such columns can change from one row to the next arbitrarily; unless every cell
+    /// of such a column is pinned by an assertion, this is usually a bug in the AIR.
+    pub fn orphaned_columns(&self) -> Vec<usize> {
+        (0..self.main_trace_width)
+            .filter(|&column| !self.is_column_read(column))
+            .collect()
+    }
+
+    /// Returns this graph encoded as a JSON object; every constraint and every assertion is
+    /// placed on a separate line so that graphs can be compared with line-based diff tools.
+    pub fn to_json(&self) -> String {
+        let mut result = format!(
+            "{{\"main_trace_width\":{},\"num_periodic_columns\":{},\"constraints\":[",
+            self.main_trace_width, self.num_periodic_columns
+        );
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            result.push_str(&format!(
+                "\n{{\"index\":{},\"current\":{:?},\"next\":{:?},\"periodic\":{:?}}}",
+                i, constraint.current, constraint.next, constraint.periodic
+            ));
+        }
+        result.push_str("\n],\"assertions\":[");
+        for (i, assertion) in self.assertions.iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            result.push_str(&format!(
+                "\n{{\"index\":{},\"column\":{},\"first_step\":{},\"stride\":{},\"num_steps\":{}}}",
+                i, assertion.column, assertion.first_step, assertion.stride, assertion.num_steps
+            ));
+        }
+        result.push_str("\n]}");
+        result
+    }
+
+    /// Returns this graph rendered in the DOT language of Graphviz.
+    ///
+    /// Columns, periodic columns, constraints, and assertions are rendered as nodes. Edges lead
+    /// from columns to the constraints which read them (dashed edges for cells of the next row)
+    /// and from assertions to the columns they pin. Orphaned columns are highlighted in red.
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph air {\n    rankdir=LR;\n");
+        for column in 0..self.main_trace_width {
+            let color = if self.is_column_read(column) {
+                ""
+            } else {
+                ",color=red"
+            };
+            result.push_str(&format!(
+                "    col{column} [shape=box,label=\"column {column}\"{color}];\n"
+            ));
+        }
+        for column in 0..self.num_periodic_columns {
+            result.push_str(&format!(
+                "    per{column} [shape=box,style=dashed,label=\"periodic {column}\"];\n"
+            ));
+        }
+        for i in 0..self.constraints.len() {
+            result.push_str(&format!(
+                "    tc{i} [shape=ellipse,label=\"constraint {i}\"];\n"
+            ));
+        }
+        for (i, assertion) in self.assertions.iter().enumerate() {
+            let label = if assertion.num_steps == 1 {
+                format!("step {}", assertion.first_step)
+            } else {
+                format!(
+                    "steps {}..+{}x{}",
+                    assertion.first_step, assertion.stride, assertion.num_steps
+                )
+            };
+            result.push_str(&format!(
+                "    as{i} [shape=diamond,label=\"assertion {i}\\n{label}\"];\n"
+            ));
+        }
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            for column in constraint.current.iter() {
+                result.push_str(&format!("    col{column} -> tc{i};\n"));
+            }
+            for column in constraint.next.iter() {
+                result.push_str(&format!("    col{column} -> tc{i} [style=dashed];\n"));
+            }
+            for column in constraint.periodic.iter() {
+                result.push_str(&format!("    per{column} -> tc{i};\n"));
+            }
+        }
+        for (i, assertion) in self.assertions.iter().enumerate() {
+            result.push_str(&format!("    as{i} -> col{};\n", assertion.column));
+        }
+        result.push('}');
+        result
+    }
+
+    /// Returns true if the specified column is read by at least one transition constraint.
+    fn is_column_read(&self, column: usize) -> bool {
+        self.constraints.iter().any(|constraint| {
+            constraint.current.contains(&column) || constraint.next.contains(&column)
+        })
+    }
+}
+
+// CONSTRAINT DEPENDENCIES
+// ================================================================================================
+/// Inputs read by a single transition constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintDependencies {
+    /// Indexes of the columns whose cells in the current row are read by the constraint.
+    pub current: Vec<usize>,
+    /// Indexes of the columns whose cells in the next row are read by the constraint; for AIRs
+    /// with more than two rows in the evaluation frame, this includes cells in all rows which
+    /// follow the current row.
+    pub next: Vec<usize>,
+    /// Indexes of the periodic columns read by the constraint.
+    pub periodic: Vec<usize>,
+}
+
+// SAMPLE GENERATOR
+// ================================================================================================
+
+/// A SplitMix64 generator of values for evaluation frames.
+struct SampleGenerator(u64);
+
+impl SampleGenerator {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns ZERO or ONE with probability 1/4 each, and a uniformly random element otherwise.
+    fn draw<B: StarkField>(&mut self) -> B {
+        match self.next_u64() % 4 {
+            0 => B::ZERO,
+            1 => B::ONE,
+            _ => B::from(((self.next_u64() as u128) << 64) | self.next_u64() as u128),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{ConstraintDependencies, DependencyGraph};
+use crate::{
+    Air, AirContext, Assertion, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo,
+    TransitionConstraintDegree,
+};
+use math::{fields::f128::BaseElement, FieldElement};
+use utils::collections::Vec;
+
+// DEPENDENCY GRAPH TESTS
+// ================================================================================================
+
+#[test]
+fn dependency_graph() {
+    let air = BranchingAir::new(TraceInfo::new(4, 16), (), options());
+    let graph = air.dependency_graph();
+
+    assert_eq!(4, graph.main_trace_width);
+    assert_eq!(1, graph.num_periodic_columns);
+    assert_eq!(
+        vec![
+            dependencies(&[0], &[0, 2], &[]),
+            // both branches are taken across the sampled frames; the column on which the
+            // constraint branches is compared but never read
+            dependencies(&[0, 1], &[1], &[]),
+            dependencies(&[2], &[2], &[0]),
+        ],
+        graph.constraints
+    );
+    assert_eq!(vec![3], graph.orphaned_columns());
+    assert_eq!(1, graph.assertions.len());
+    assert_eq!(3, graph.assertions[0].column);
+}
+
+#[test]
+fn dependency_graph_wide_frame() {
+    // 40 columns yield 80 inputs, and thus, constraints are evaluated in two batches per frame
+    let air = BranchingAir::new(TraceInfo::new(40, 16), (), options());
+    let graph = air.dependency_graph();
+
+    assert_eq!(dependencies(&[0], &[0, 38], &[]), graph.constraints[0]);
+    assert_eq!(
+        (3..40).filter(|&c| c != 38).collect::<Vec<_>>(),
+        graph.orphaned_columns()
+    );
+}
+
+#[test]
+fn dependency_graph_dot_and_json() {
+    let air = BranchingAir::new(TraceInfo::new(4, 16), (), options());
+    let graph = air.dependency_graph();
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph air {"));
+    assert!(dot.contains("    col3 [shape=box,label=\"column 3\",color=red];\n"));
+    assert!(dot.contains("    col2 -> tc0 [style=dashed];\n"));
+    assert!(dot.contains("    per0 -> tc2;\n"));
+    assert!(dot.contains("    as0 -> col3;\n"));
+
+    let json = graph.to_json();
+    assert!(json.contains("\n{\"index\":2,\"current\":[2],\"next\":[2],\"periodic\":[0]}"));
+    assert!(json.ends_with(
+        "\n{\"index\":0,\"column\":3,\"first_step\":0,\"stride\":0,\"num_steps\":1}\n]}"
+    ));
+}
+
+#[test]
+fn dependency_graph_is_deterministic() {
+    let air = BranchingAir::new(TraceInfo::new(4, 16), (), options());
+    assert_eq!(
+        DependencyGraph::from_air(&air, 3),
+        DependencyGraph::from_air(&air, 3)
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn options() -> ProofOptions {
+    ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+}
+
+fn dependencies(current: &[usize], next: &[usize], periodic: &[usize]) -> ConstraintDependencies {
+    ConstraintDependencies {
+        current: current.to_vec(),
+        next: next.to_vec(),
+        periodic: periodic.to_vec(),
+    }
+}
+
+/// An AIR with a constraint which branches on a value of the trace, a constraint which reads a
+/// periodic column, and a last column which is asserted but not read by any constraint.
+struct BranchingAir {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for BranchingAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let degrees = vec![
+            TransitionConstraintDegree::new(2),
+            TransitionConstraintDegree::new(1),
+            TransitionConstraintDegree::with_cycles(1, vec![4]),
+        ];
+        BranchingAir {
+            context: AirContext::new(trace_info, degrees, 1, options),
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        let width = current.len();
+
+        result[0] = next[0] - current[0] * next[width - 2];
+        result[1] = if current[2] == E::ONE {
+            next[1] - current[0]
+        } else {
+            next[1] - current[1]
+        };
+        result[2] = periodic_values[0] * (next[2] - current[2]);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last = self.trace_info().width() - 1;
+        vec![Assertion::single(last, 0, BaseElement::ZERO)]
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        vec![(0..4).map(BaseElement::new).collect()]
+    }
+}
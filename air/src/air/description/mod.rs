@@ -0,0 +1,205 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    Air, Assertion, AuxTraceRandElements, TransitionConstraintDegree, TransitionDivisorKind,
+};
+use math::FieldElement;
+use utils::{
+    collections::Vec,
+    string::{String, ToString},
+};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// AIR DESCRIPTION
+// ================================================================================================
+/// A machine-readable description of the constraints defined by an instance of an AIR.
+///
+/// A description is built from a compiled AIR instantiated for a specific trace length and a
+/// specific set of public inputs via [Air::describe()]. It lists everything a verifier checks
+/// about the shape of an execution trace: trace widths, degrees of all transition constraints,
+/// all boundary assertions, and lengths of periodic columns. It does not include the logic of
+/// transition constraints themselves.
+///
+/// Field element values are rendered as strings using the [Display](core::fmt::Display)
+/// implementation of the base field. When `serde` feature is enabled, a description can be
+/// serialized, and [to_json()](AirDescription::to_json) can be used to render it as JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AirDescription {
+    /// Number of steps in the execution trace.
+    pub trace_length: usize,
+    /// Number of columns in the main segment of the execution trace.
+    pub main_trace_width: usize,
+    /// Number of columns in each of the auxiliary segments of the execution trace.
+    pub aux_segment_widths: Vec<usize>,
+    /// Number of random elements needed to build each of the auxiliary trace segments.
+    pub aux_segment_rand_elements: Vec<usize>,
+    /// Transition constraints placed against the main trace segment.
+    pub main_transition_constraints: Vec<ConstraintDescription>,
+    /// Transition constraints placed against auxiliary trace segments.
+    pub aux_transition_constraints: Vec<ConstraintDescription>,
+    /// Number of steps at the end of the trace which are exempt from transition constraints.
+    pub num_transition_exemptions: usize,
+    /// Assertions placed against the main trace segment.
+    pub main_assertions: Vec<AssertionDescription>,
+    /// Assertions placed against auxiliary trace segments.
+    pub aux_assertions: Vec<AssertionDescription>,
+    /// Number of values in each of the periodic columns.
+    pub periodic_column_lengths: Vec<usize>,
+}
+
+impl AirDescription {
+    /// Returns the total number of transition constraints in this description.
+    pub fn num_transition_constraints(&self) -> usize {
+        self.main_transition_constraints.len() + self.aux_transition_constraints.len()
+    }
+
+    /// Returns this description rendered as a pretty-printed JSON string.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize AIR description")
+    }
+}
+
+// CONSTRAINT DESCRIPTION
+// ================================================================================================
+/// A description of the degree of a single transition constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ConstraintDescription {
+    /// Degree of the constraint with respect to trace columns.
+    pub base_degree: usize,
+    /// Cycle lengths of periodic columns involved in the constraint.
+    pub cycles: Vec<usize>,
+    /// Degree of the constraint polynomial for the described trace length.
+    pub evaluation_degree: usize,
+    /// True if the constraint must hold on all steps of the execution trace (i.e., the divisor
+    /// of the constraint is cyclic).
+    pub cyclic: bool,
+}
+
+impl ConstraintDescription {
+    fn new(degree: &TransitionConstraintDegree, trace_length: usize) -> Self {
+        ConstraintDescription {
+            base_degree: degree.base(),
+            cycles: degree.cycles().to_vec(),
+            evaluation_degree: degree.get_evaluation_degree(trace_length),
+            cyclic: degree.divisor() == TransitionDivisorKind::Cyclic,
+        }
+    }
+}
+
+// ASSERTION DESCRIPTION
+// ================================================================================================
+/// A description of a single boundary assertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AssertionDescription {
+    /// Index of the asserted column.
+    pub column: usize,
+    /// The first step against which the assertion is placed.
+    pub first_step: usize,
+    /// Interval between asserted steps; this is 0 for single assertions.
+    pub stride: usize,
+    /// Number of steps against which the assertion is placed for the described trace length.
+    pub num_steps: usize,
+    /// Asserted values; this is None for values which are derived from random elements drawn
+    /// during proof generation.
+    pub values: Option<Vec<String>>,
+}
+
+impl AssertionDescription {
+    pub(super) fn new<E: FieldElement>(
+        assertion: &Assertion<E>,
+        trace_length: usize,
+        include_values: bool,
+    ) -> Self {
+        let values = if include_values {
+            Some(assertion.values().iter().map(|v| v.to_string()).collect())
+        } else {
+            None
+        };
+        AssertionDescription {
+            column: assertion.column(),
+            first_step: assertion.first_step(),
+            stride: assertion.stride(),
+            num_steps: assertion.get_num_steps(trace_length),
+            values,
+        }
+    }
+}
+
+// DESCRIPTION BUILDER
+// ================================================================================================
+
+/// Builds a description of the provided AIR.
+///
+/// Auxiliary assertions may depend on random elements, and thus, they are built using an
+/// arbitrary set of random elements and their values are reported as derived.
+pub(super) fn describe<A: Air + ?Sized>(air: &A) -> AirDescription {
+    let context = air.context();
+    let layout = air.trace_layout();
+    let trace_length = air.trace_length();
+
+    let mut aux_rand_elements = AuxTraceRandElements::<A::BaseField>::new();
+    for i in 0..layout.num_aux_segments() {
+        let num_elements = layout.get_aux_segment_rand_elements(i);
+        aux_rand_elements.add_segment_elements(
+            (0..num_elements)
+                .map(|j| A::BaseField::from(j as u32 + 2))
+                .collect(),
+        );
+    }
+
+    // auxiliary assertions are requested only when the AIR defines auxiliary segments, since
+    // AIRs are not required to handle an empty set of random elements
+    let aux_assertions = if layout.num_aux_segments() > 0 {
+        air.get_aux_assertions(&aux_rand_elements)
+            .iter()
+            .map(|assertion| AssertionDescription::new(assertion, trace_length, false))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    AirDescription {
+        trace_length,
+        main_trace_width: layout.main_trace_width(),
+        aux_segment_widths: (0..layout.num_aux_segments())
+            .map(|i| layout.get_aux_segment_width(i))
+            .collect(),
+        aux_segment_rand_elements: (0..layout.num_aux_segments())
+            .map(|i| layout.get_aux_segment_rand_elements(i))
+            .collect(),
+        main_transition_constraints: context
+            .main_transition_constraint_degrees
+            .iter()
+            .map(|degree| ConstraintDescription::new(degree, trace_length))
+            .collect(),
+        aux_transition_constraints: context
+            .aux_transition_constraint_degrees
+            .iter()
+            .map(|degree| ConstraintDescription::new(degree, trace_length))
+            .collect(),
+        num_transition_exemptions: context.num_transition_exemptions(),
+        main_assertions: air
+            .get_assertions()
+            .iter()
+            .map(|assertion| AssertionDescription::new(assertion, trace_length, true))
+            .collect(),
+        aux_assertions,
+        periodic_column_lengths: air
+            .get_periodic_column_values()
+            .iter()
+            .map(|column| column.len())
+            .collect(),
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{AirDescription, AssertionDescription, ConstraintDescription};
+use crate::{
+    Air, AirContext, Assertion, AuxTraceRandElements, EvaluationFrame, FieldExtension,
+    ProofOptions, TraceInfo, TraceLayoutBuilder, TransitionConstraintDegree,
+};
+use math::{fields::f128::BaseElement, ExtensionOf, FieldElement};
+use utils::{
+    collections::Vec,
+    string::{String, ToString},
+};
+
+// AIR DESCRIPTION TESTS
+// ================================================================================================
+
+#[test]
+fn describe_air() {
+    let layout = TraceLayoutBuilder::new()
+        .main_width(2)
+        .aux_segment(1, 3)
+        .build()
+        .unwrap();
+    let air = DescribedAir::new(
+        TraceInfo::new_multi_segment(layout, 32, vec![]),
+        BaseElement::new(7),
+        ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256),
+    );
+
+    let expected = AirDescription {
+        trace_length: 32,
+        main_trace_width: 2,
+        aux_segment_widths: vec![1],
+        aux_segment_rand_elements: vec![3],
+        main_transition_constraints: vec![
+            ConstraintDescription {
+                base_degree: 1,
+                cycles: vec![],
+                evaluation_degree: 31,
+                cyclic: false,
+            },
+            ConstraintDescription {
+                base_degree: 2,
+                cycles: vec![8],
+                evaluation_degree: 90,
+                cyclic: false,
+            },
+        ],
+        aux_transition_constraints: vec![ConstraintDescription {
+            base_degree: 2,
+            cycles: vec![],
+            evaluation_degree: 62,
+            cyclic: false,
+        }],
+        num_transition_exemptions: 2,
+        main_assertions: vec![
+            AssertionDescription {
+                column: 0,
+                first_step: 0,
+                stride: 0,
+                num_steps: 1,
+                values: Some(vec![hex(7)]),
+            },
+            AssertionDescription {
+                column: 1,
+                first_step: 1,
+                stride: 8,
+                num_steps: 4,
+                values: Some(vec![hex(0)]),
+            },
+            AssertionDescription {
+                column: 1,
+                first_step: 0,
+                stride: 16,
+                num_steps: 2,
+                values: Some(vec![hex(1), hex(2)]),
+            },
+        ],
+        aux_assertions: vec![AssertionDescription {
+            column: 0,
+            first_step: 31,
+            stride: 0,
+            num_steps: 1,
+            values: None,
+        }],
+        periodic_column_lengths: vec![8],
+    };
+
+    let description = air.describe();
+    assert_eq!(expected, description);
+    assert_eq!(3, description.num_transition_constraints());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn describe_air_json() {
+    let air = DescribedAir::new(
+        TraceInfo::new(2, 32),
+        BaseElement::new(7),
+        ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256),
+    );
+
+    let json = air.describe().to_json();
+    assert!(json.contains("\"main_trace_width\": 2"));
+    assert!(json.contains("\"values\": [\n        \"0x00000000000000000000000000000007\"\n      ]"));
+    assert!(json.contains("\"aux_assertions\": []"));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn hex(value: u128) -> String {
+    BaseElement::new(value).to_string()
+}
+
+/// An AIR which exercises all parts of a description: periodic columns, transition exemptions,
+/// all kinds of assertions and an auxiliary trace segment.
+struct DescribedAir {
+    context: AirContext<BaseElement>,
+    value: BaseElement,
+}
+
+impl Air for DescribedAir {
+    type BaseField = BaseElement;
+    type PublicInputs = BaseElement;
+
+    fn new(trace_info: TraceInfo, value: BaseElement, options: ProofOptions) -> Self {
+        let main_degrees = vec![
+            TransitionConstraintDegree::new(1),
+            TransitionConstraintDegree::with_cycles(2, vec![8]),
+        ];
+        let context = if trace_info.layout().num_aux_segments() == 0 {
+            AirContext::new(trace_info, main_degrees, 3, options)
+        } else {
+            AirContext::new_multi_segment(
+                trace_info,
+                main_degrees,
+                vec![TransitionConstraintDegree::new(2)],
+                3,
+                1,
+                options,
+            )
+        };
+        DescribedAir {
+            context: context.set_num_transition_exemptions(2),
+            value,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        _frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        _result: &mut [E],
+    ) {
+    }
+
+    fn evaluate_aux_transition<F, E>(
+        &self,
+        _main_frame: &EvaluationFrame<F>,
+        _aux_frame: &EvaluationFrame<E>,
+        _periodic_values: &[F],
+        _aux_rand_elements: &AuxTraceRandElements<E>,
+        _result: &mut [E],
+    ) where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        vec![
+            Assertion::single(0, 0, self.value),
+            Assertion::periodic(1, 1, 8, BaseElement::ZERO),
+            Assertion::sequence(1, 0, 16, vec![BaseElement::ONE, BaseElement::new(2)]),
+        ]
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Vec<Assertion<E>> {
+        let rand_elements = aux_rand_elements.get_segment_elements(0);
+        vec![Assertion::single(
+            0,
+            self.trace_length() - 1,
+            rand_elements[0] + rand_elements[2],
+        )]
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        vec![(0..8).map(BaseElement::new).collect()]
+    }
+}
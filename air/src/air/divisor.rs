@@ -3,9 +3,9 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::air::Assertion;
+use crate::air::{Assertion, PowerTable, TraceRow};
 use core::fmt::{Display, Formatter};
-use math::{log2, FieldElement, StarkField};
+use math::{FieldElement, StarkField};
 use utils::collections::Vec;
 
 // CONSTRAINT DIVISOR
@@ -21,7 +21,7 @@ use utils::collections::Vec;
 /// numerator: `[(a, 1), (b, 2)]`, exemptions: `[3]`.
 ///
 /// A divisor cannot be instantiated directly, and instead must be created either for an
-/// [Assertion] or for a transition constraint.
+/// [Assertion], for a constraint against a single step, or for a transition constraint.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConstraintDivisor<B: StarkField> {
     pub(super) numerator: Vec<(usize, B)>,
@@ -54,16 +54,35 @@ impl<B: StarkField> ConstraintDivisor<B> {
     /// The above divisor specifies that transition constraints must hold on all steps of the
     /// execution trace except for the last $k$ steps.
     pub fn from_transition(trace_length: usize, num_exemptions: usize) -> Self {
+        Self::from_transition_with_powers(num_exemptions, &PowerTable::new(trace_length, []))
+    }
+
+    /// Builds a divisor for transition constraints using the provided table of powers of the
+    /// trace domain generator; the length of the execution trace is the size of the domain of
+    /// the table.
+    ///
+    /// This is the same divisor as the one built by [from_transition()](Self::from_transition).
+    pub fn from_transition_with_powers(num_exemptions: usize, powers: &PowerTable<B>) -> Self {
         assert!(
             num_exemptions > 0,
             "invalid number of transition exemptions: must be greater than zero"
         );
+        let trace_length = powers.domain_size();
         let exemptions = (trace_length - num_exemptions..trace_length)
-            .map(|step| get_trace_domain_value_at::<B>(trace_length, step))
+            .map(|step| get_trace_domain_value_at(powers, TraceRow(step)))
             .collect();
         Self::new(vec![(trace_length, B::ONE)], exemptions)
     }
 
+    /// Builds a divisor for cyclic transition constraints.
+    ///
+    /// For cyclic transition constraints, the divisor polynomial is $z(x) = x^n - 1$, where $n$
+    /// is the length of the execution trace. This divisor specifies that the constraints must
+    /// hold on all steps of the execution trace, including the last one.
+    pub fn from_cyclic(trace_length: usize) -> Self {
+        Self::new(vec![(trace_length, B::ONE)], vec![])
+    }
+
     /// Builds a divisor for a boundary constraint described by the assertion.
     ///
     /// For boundary constraints, the divisor polynomial is defined as:
@@ -85,22 +104,59 @@ impl<B: StarkField> ConstraintDivisor<B> {
     ///   $(x - g^a) \cdot (x - g^{a + j}) \cdot (x - g^{a + 2 \cdot j}) ... (x - g^{a + (k  - 1) \cdot j})$,
     ///   where $j$ is the length of interval between asserted steps (e.g. 8).
     ///
+    /// The exception are assertions against windows of consecutive steps: these steps do not form
+    /// a coset of a subgroup of the trace domain, and thus, the divisor polynomial is
+    /// $(x - g^a) \cdot (x - g^{a + 1}) ... (x - g^{a + k - 1})$, where $a$ is the first step of
+    /// the window and $k$ is the length of the window.
+    ///
     /// # Panics
     /// Panics of the specified `trace_length` is inconsistent with the specified `assertion`.
     pub fn from_assertion<E>(assertion: &Assertion<E>, trace_length: usize) -> Self
     where
         E: FieldElement<BaseField = B>,
     {
-        let num_steps = assertion.get_num_steps(trace_length);
-        if assertion.first_step == 0 {
+        Self::from_assertion_with_powers(assertion, &PowerTable::new(trace_length, []))
+    }
+
+    /// Builds a divisor for a boundary constraint described by the assertion using the provided
+    /// table of powers of the trace domain generator; the length of the execution trace is the
+    /// size of the domain of the table.
+    ///
+    /// This is the same divisor as the one built by [from_assertion()](Self::from_assertion).
+    ///
+    /// # Panics
+    /// Panics of the trace length is inconsistent with the specified `assertion`.
+    pub fn from_assertion_with_powers<E>(assertion: &Assertion<E>, powers: &PowerTable<B>) -> Self
+    where
+        E: FieldElement<BaseField = B>,
+    {
+        let num_steps = assertion.get_num_steps(powers.domain_size());
+        if assertion.is_window() {
+            let first_step = assertion.first_step.as_usize();
+            let numerator = (first_step..first_step + num_steps)
+                .map(|step| (1, get_trace_domain_value_at(powers, TraceRow(step))))
+                .collect();
+            Self::new(numerator, vec![])
+        } else if assertion.first_step == TraceRow(0) {
             Self::new(vec![(num_steps, B::ONE)], vec![])
         } else {
-            let trace_offset = num_steps * assertion.first_step;
-            let offset = get_trace_domain_value_at::<B>(trace_length, trace_offset);
+            let trace_offset = TraceRow(num_steps * assertion.first_step.as_usize());
+            let offset = get_trace_domain_value_at(powers, trace_offset);
             Self::new(vec![(num_steps, offset)], vec![])
         }
     }
 
+    /// Builds a divisor for a constraint which must hold at a single step of the execution trace.
+    ///
+    /// The divisor polynomial is defined as $z(x) = x - g^s$, where $g$ is the generator of the
+    /// trace domain and $s$ is the step; this is the same divisor as the one of an assertion
+    /// against a single step $s$.
+    pub fn from_step(step: usize, trace_length: usize) -> Self {
+        let powers = PowerTable::new(trace_length, []);
+        let offset = get_trace_domain_value_at(&powers, TraceRow(step));
+        Self::new(vec![(1, offset)], vec![])
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -170,20 +226,20 @@ impl<B: StarkField> Display for ConstraintDivisor<B> {
 // HELPER FUNCTIONS
 // ================================================================================================
 
-/// Returns g^step, where g is the generator of trace domain.
-fn get_trace_domain_value_at<B: StarkField>(trace_length: usize, step: usize) -> B {
+/// Returns g^step, where g is the generator of trace domain described by the power table.
+fn get_trace_domain_value_at<B: StarkField>(powers: &PowerTable<B>, step: TraceRow) -> B {
     debug_assert!(
-        step < trace_length,
-        "step must be in the trace domain [0, {trace_length})"
+        step.as_usize() < powers.domain_size(),
+        "step must be in the trace domain [0, {})",
+        powers.domain_size()
     );
-    let g = B::get_root_of_unity(log2(trace_length));
-    g.exp((step as u64).into())
+    powers.get(step.as_usize())
 }
 
 // TESTS
 // ================================================================================================
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests {
     use super::*;
     use math::{fields::f128::BaseElement, polynom};
@@ -341,5 +397,31 @@ mod tests {
                 assert_eq!(BaseElement::ZERO, actual);
             }
         }
+
+        // ----- window assertion divisor ---------------------------------------------------------
+
+        // create a divisor for assertion against steps 4 - 7
+        let assertion = Assertion::window(0, 4, 4, BaseElement::ONE).unwrap();
+        let divisor = ConstraintDivisor::from_assertion(&assertion, n);
+        assert_eq!(4, divisor.degree());
+
+        // z(x) = (x - g^4) * (x - g^5) * (x - g^6) * (x - g^7)
+        let poly = polynom::mul(
+            &polynom::mul(
+                &[-g.exp(4), BaseElement::ONE],
+                &[-g.exp(5), BaseElement::ONE],
+            ),
+            &polynom::mul(
+                &[-g.exp(6), BaseElement::ONE],
+                &[-g.exp(7), BaseElement::ONE],
+            ),
+        );
+
+        for i in 0..n {
+            let expected = polynom::eval(&poly, g.exp((i as u32).into()));
+            let actual = divisor.evaluate_at(g.exp((i as u32).into()));
+            assert_eq!(expected, actual);
+            assert_eq!(i >= 4, actual == BaseElement::ZERO);
+        }
     }
 }
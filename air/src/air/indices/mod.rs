@@ -0,0 +1,187 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use core::fmt::{Display, Formatter};
+use utils::collections::Vec;
+
+#[cfg(test)]
+mod tests;
+
+// TRACE ROW
+// ================================================================================================
+/// An index of a row in the execution trace domain.
+///
+/// A trace row is a step of the computation: it is always smaller than the trace length, and
+/// the trace domain element corresponding to it is $g^i$, where $g$ is the generator of the
+/// trace domain and $i$ is the row index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TraceRow(pub usize);
+
+impl TraceRow {
+    /// Returns the underlying row index.
+    pub fn as_usize(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the row following this row in a trace of the specified length; at the end of
+    /// the trace, the next row wraps around to the first row.
+    pub fn next(&self, trace_length: usize) -> TraceRow {
+        TraceRow((self.0 + 1) % trace_length)
+    }
+
+    /// Returns the position in the constraint evaluation domain which corresponds to this row.
+    ///
+    /// `ce_blowup` is the ratio between the size of the constraint evaluation domain and the
+    /// trace length.
+    ///
+    /// # Panics
+    /// Panics if `ce_blowup` is not a power of two.
+    pub fn to_ce_pos(&self, ce_blowup: usize) -> CePos {
+        CePos(self.0 << blowup_shift(ce_blowup))
+    }
+
+    /// Returns the position in the LDE domain which corresponds to this row.
+    ///
+    /// `blowup` is the ratio between the size of the LDE domain and the trace length.
+    ///
+    /// # Panics
+    /// Panics if `blowup` is not a power of two.
+    pub fn to_lde_pos(&self, blowup: usize) -> LdePos {
+        LdePos(self.0 << blowup_shift(blowup))
+    }
+}
+
+impl From<TraceRow> for usize {
+    fn from(row: TraceRow) -> Self {
+        row.0
+    }
+}
+
+impl Display for TraceRow {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// CONSTRAINT EVALUATION DOMAIN POSITION
+// ================================================================================================
+/// An index of a point in the constraint evaluation domain.
+///
+/// Constraint evaluation domain is a coset of a subgroup which is larger than the trace domain
+/// by the constraint evaluation blowup factor, and smaller than (or equal to) the LDE domain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CePos(pub usize);
+
+impl CePos {
+    /// Returns the underlying position.
+    pub fn as_usize(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the position in the LDE domain which corresponds to this position.
+    ///
+    /// `ce_to_lde_blowup` is the ratio between the size of the LDE domain and the size of the
+    /// constraint evaluation domain.
+    ///
+    /// # Panics
+    /// Panics if `ce_to_lde_blowup` is not a power of two.
+    pub fn to_lde_pos(&self, ce_to_lde_blowup: usize) -> LdePos {
+        LdePos(self.0 << blowup_shift(ce_to_lde_blowup))
+    }
+
+    /// Returns the trace row which corresponds to this position, or None if this position does
+    /// not fall on a trace row.
+    ///
+    /// # Panics
+    /// Panics if `ce_blowup` is not a power of two.
+    pub fn to_trace_row(&self, ce_blowup: usize) -> Option<TraceRow> {
+        exact_index(self.0, ce_blowup).map(TraceRow)
+    }
+}
+
+impl From<CePos> for usize {
+    fn from(pos: CePos) -> Self {
+        pos.0
+    }
+}
+
+impl Display for CePos {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// LDE DOMAIN POSITION
+// ================================================================================================
+/// An index of a point in the low-degree extension domain.
+///
+/// Query positions drawn by the verifier, as well as leaf indexes of trace and constraint
+/// commitments, are positions in the LDE domain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LdePos(pub usize);
+
+impl LdePos {
+    /// Returns the underlying position.
+    pub fn as_usize(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the trace row which corresponds to this position, or None if this position does
+    /// not fall on a trace row (i.e., it is not a multiple of `blowup`).
+    ///
+    /// # Panics
+    /// Panics if `blowup` is not a power of two.
+    pub fn to_trace_row(&self, blowup: usize) -> Option<TraceRow> {
+        exact_index(self.0, blowup).map(TraceRow)
+    }
+
+    /// Returns the constraint evaluation domain position which corresponds to this position, or
+    /// None if this position does not fall into the constraint evaluation domain.
+    ///
+    /// # Panics
+    /// Panics if `ce_to_lde_blowup` is not a power of two.
+    pub fn to_ce_pos(&self, ce_to_lde_blowup: usize) -> Option<CePos> {
+        exact_index(self.0, ce_to_lde_blowup).map(CePos)
+    }
+
+    /// Converts a list of LDE domain positions into a list of raw indexes.
+    pub fn to_indexes(positions: &[LdePos]) -> Vec<usize> {
+        positions.iter().map(|p| p.0).collect()
+    }
+}
+
+impl From<LdePos> for usize {
+    fn from(pos: LdePos) -> Self {
+        pos.0
+    }
+}
+
+impl Display for LdePos {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns log2 of the specified blowup factor.
+fn blowup_shift(blowup: usize) -> u32 {
+    assert!(
+        blowup.is_power_of_two(),
+        "blowup factor must be a power of two, but was {blowup}"
+    );
+    blowup.trailing_zeros()
+}
+
+/// Divides `index` by `blowup` if `index` is a multiple of `blowup`; otherwise returns None.
+fn exact_index(index: usize, blowup: usize) -> Option<usize> {
+    let shift = blowup_shift(blowup);
+    if index & (blowup - 1) == 0 {
+        Some(index >> shift)
+    } else {
+        None
+    }
+}
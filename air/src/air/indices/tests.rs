@@ -0,0 +1,84 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{CePos, LdePos, TraceRow};
+
+// TRACE ROWS
+// ================================================================================================
+
+#[test]
+fn trace_row_to_domain_positions() {
+    let row = TraceRow(5);
+    assert_eq!(CePos(10), row.to_ce_pos(2));
+    assert_eq!(LdePos(40), row.to_lde_pos(8));
+    assert_eq!(LdePos(5), row.to_lde_pos(1));
+}
+
+#[test]
+fn trace_row_next() {
+    assert_eq!(TraceRow(4), TraceRow(3).next(8));
+    assert_eq!(TraceRow(0), TraceRow(7).next(8));
+}
+
+#[test]
+#[should_panic(expected = "blowup factor must be a power of two, but was 3")]
+fn trace_row_to_lde_pos_invalid_blowup() {
+    TraceRow(1).to_lde_pos(3);
+}
+
+// CE DOMAIN POSITIONS
+// ================================================================================================
+
+#[test]
+fn ce_pos_conversions() {
+    assert_eq!(LdePos(24), CePos(6).to_lde_pos(4));
+    assert_eq!(Some(TraceRow(3)), CePos(6).to_trace_row(2));
+    assert_eq!(None, CePos(7).to_trace_row(2));
+    assert_eq!(Some(TraceRow(7)), CePos(7).to_trace_row(1));
+}
+
+// LDE DOMAIN POSITIONS
+// ================================================================================================
+
+#[test]
+fn lde_pos_to_trace_row() {
+    assert_eq!(Some(TraceRow(0)), LdePos(0).to_trace_row(8));
+    assert_eq!(Some(TraceRow(3)), LdePos(24).to_trace_row(8));
+    assert_eq!(None, LdePos(25).to_trace_row(8));
+    assert_eq!(None, LdePos(4).to_trace_row(8));
+    assert_eq!(Some(TraceRow(25)), LdePos(25).to_trace_row(1));
+}
+
+#[test]
+fn lde_pos_to_ce_pos() {
+    assert_eq!(Some(CePos(6)), LdePos(24).to_ce_pos(4));
+    assert_eq!(None, LdePos(26).to_ce_pos(4));
+}
+
+#[test]
+fn lde_pos_round_trip() {
+    let blowup = 16;
+    for i in 0..64 {
+        let row = TraceRow(i);
+        assert_eq!(Some(row), row.to_lde_pos(blowup).to_trace_row(blowup));
+
+        let ce_pos = row.to_ce_pos(4);
+        let lde_pos = ce_pos.to_lde_pos(blowup / 4);
+        assert_eq!(row.to_lde_pos(blowup), lde_pos);
+        assert_eq!(Some(ce_pos), lde_pos.to_ce_pos(blowup / 4));
+    }
+}
+
+#[test]
+#[should_panic(expected = "blowup factor must be a power of two, but was 6")]
+fn lde_pos_to_trace_row_invalid_blowup() {
+    LdePos(12).to_trace_row(6);
+}
+
+#[test]
+fn lde_pos_to_indexes() {
+    let positions = [LdePos(3), LdePos(1), LdePos(7)];
+    assert_eq!(vec![3, 1, 7], LdePos::to_indexes(&positions));
+}
@@ -3,7 +3,10 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::ProofOptions;
+use crate::{
+    proof::{get_field_security, get_query_security},
+    AssertionError, ProofOptions,
+};
 use crypto::{Hasher, RandomCoin, RandomCoinError};
 use math::{fft, ExtensibleField, ExtensionOf, FieldElement, StarkField};
 use utils::{
@@ -12,31 +15,50 @@ use utils::{
 };
 
 mod trace_info;
-pub use trace_info::{TraceInfo, TraceLayout};
+pub use trace_info::{TraceInfo, TraceLayout, TraceLayoutBuilder};
 
 mod context;
 pub use context::AirContext;
 
 mod assertions;
-pub use assertions::Assertion;
+pub use assertions::{Assertion, AssertionStep};
 
 mod boundary;
-pub use boundary::{BoundaryConstraint, BoundaryConstraintGroup, BoundaryConstraints};
+pub use boundary::{
+    BoundaryConstraint, BoundaryConstraintGroup, BoundaryConstraints, BoundaryExpr,
+    BoundaryExprConstraint, StepSelector,
+};
 
 mod transition;
 pub use transition::{
-    EvaluationFrame, TransitionConstraintDegree, TransitionConstraintGroup, TransitionConstraints,
+    ConstraintDescriptor, EvaluationFrame, TransitionConstraintDegree, TransitionConstraintGroup,
+    TransitionConstraints, TransitionDivisorKind,
 };
 
 mod coefficients;
 pub use coefficients::{
-    AuxTraceRandElements, ConstraintCompositionCoefficients, DeepCompositionCoefficients,
+    AuxTraceRandElements, CoefficientPairs, ConstraintCompositionCoefficients,
+    DeepCompositionCoefficients, MAX_INLINE_CONSTRAINTS,
 };
 
 mod divisor;
 pub use divisor::ConstraintDivisor;
 
-#[cfg(test)]
+mod powers;
+pub use powers::PowerTable;
+
+mod indices;
+pub use indices::{CePos, LdePos, TraceRow};
+
+mod description;
+pub use description::{AirDescription, AssertionDescription, ConstraintDescription};
+
+#[cfg(feature = "dependency-graph")]
+mod dependency;
+#[cfg(feature = "dependency-graph")]
+pub use dependency::{ConstraintDependencies, DependencyGraph, DEFAULT_NUM_DEPENDENCY_SAMPLES};
+
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // CONSTANTS
@@ -76,7 +98,9 @@ const MIN_CYCLE_LENGTH: usize = 2;
 /// [Air::evaluate_transition()] function which takes the following parameters:
 ///
 /// - [EvaluationFrame] which contains vectors with current and next states of the
-///   computation.
+///   computation. Constraints which relate rows further apart (e.g., rows at steps `i` and
+///   `i + 4`) can be expressed by declaring additional row offsets via
+///   [AirContext::set_frame_offsets()]; the frame then contains a row for every declared offset.
 /// - A list of periodic values. When periodic columns are defined for a computation,
 ///   this will contain values of periodic columns at the current step of the computation.
 ///   Otherwise, this will be an empty list.
@@ -114,6 +138,10 @@ const MIN_CYCLE_LENGTH: usize = 2;
 ///   example, if our constraint involves multiplication of one trace column and one periodic
 ///   column with a cycle of 32 steps, the degree can be described as:
 ///   `TransitionConstraintDegree::with_cycles(1, vec![32])`.
+/// * By default, transition constraints are not enforced on the last step of the execution trace
+///   (where the next row wraps around to the first row). To enforce a constraint on all steps,
+///   declare its divisor as cyclic via [TransitionConstraintDegree::with_divisor()] - e.g.,
+///   `TransitionConstraintDegree::new(1).with_divisor(TransitionDivisorKind::Cyclic)`.
 ///
 /// In general, multiplications should be used judiciously - though, there are ways to ease this
 /// restriction a bit at the expense of wider execution trace.
@@ -303,6 +331,24 @@ pub trait Air: Send + Sync {
         Vec::new()
     }
 
+    /// Returns boundary expressions placed against rows of the main segment of the execution
+    /// trace.
+    ///
+    /// Boundary expressions are non-linear boundary constraints which combine multiple columns
+    /// of a single row (see [BoundaryExpr] for more info). The prover evaluates the expressions
+    /// over the base field of the protocol, while the verifier evaluates them over the field used
+    /// for out-of-domain evaluations; thus, the expressions must be defined generically over `E`.
+    ///
+    /// The default implementation of this method returns an empty vector. For computations which
+    /// rely on boundary expressions, this method should be overridden in the specialized
+    /// implementation, and the degrees of the expressions must be declared in the context via
+    /// [AirContext::set_boundary_expr_degrees()].
+    fn get_boundary_exprs<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+    ) -> Vec<BoundaryExpr<E>> {
+        Vec::new()
+    }
+
     /// Returns polynomial for all periodic columns.
     ///
     /// These polynomials are interpolated from the values returned from the
@@ -372,6 +418,150 @@ pub trait Air: Send + Sync {
         )
     }
 
+    /// Converts boundary expressions returned from [get_boundary_exprs()](Air::get_boundary_exprs)
+    /// into boundary expression constraints.
+    ///
+    /// This function also assigns composition coefficients to each constraint, and computes the
+    /// degree adjustment factors needed to compose the constraints with all other constraints.
+    ///
+    /// # Panics
+    /// Panics if the returned expressions are inconsistent with the boundary expression degrees
+    /// declared in the context of this AIR.
+    fn get_boundary_expr_constraints<F, E>(
+        &self,
+        composition_coefficients: &[(E, E)],
+    ) -> Vec<BoundaryExprConstraint<F, E>>
+    where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+        let exprs = self.get_boundary_exprs::<F>();
+        let degrees = exprs.iter().map(|expr| expr.degree()).collect::<Vec<_>>();
+        assert_eq!(
+            self.context().boundary_expr_degrees(),
+            degrees,
+            "boundary expression degrees do not match the degrees declared in the context"
+        );
+        assert_eq!(
+            exprs.len(),
+            composition_coefficients.len(),
+            "number of boundary expressions must match the number of composition coefficient tuples"
+        );
+
+        exprs
+            .into_iter()
+            .zip(composition_coefficients)
+            .map(|(expr, &cc)| BoundaryExprConstraint::new(expr, self.context(), cc))
+            .collect()
+    }
+
+    /// Checks that all assertions returned from [get_assertions()](Air::get_assertions) can be
+    /// placed against the main segment of the execution trace described by this AIR.
+    ///
+    /// This is invoked by both the prover and the verifier right after an instance of the AIR
+    /// is created, so that assertions which depend on public inputs (e.g., assertions placed
+    /// against a step specified in public inputs) are rejected before boundary constraints are
+    /// built from them. Steps of boundary expressions returned from
+    /// [get_boundary_exprs()](Air::get_boundary_exprs) are checked as well.
+    ///
+    /// # Errors
+    /// Returns an error if any of the assertions is placed against a column outside of the main
+    /// trace segment or against a step outside of the execution trace, or if a step of any of
+    /// the boundary expressions is outside of the execution trace.
+    fn validate_assertions(&self) -> Result<(), AssertionError> {
+        let trace_width = self.trace_layout().main_trace_width();
+        let trace_length = self.trace_length();
+        self.get_assertions()
+            .iter()
+            .try_for_each(|assertion| assertion.validate(trace_width, trace_length))?;
+        self.get_boundary_exprs::<Self::BaseField>()
+            .iter()
+            .try_for_each(|expr| expr.step_selector().try_get_step(trace_length).map(|_| ()))
+    }
+
+    /// Checks that all assertions returned from [get_aux_assertions()](Air::get_aux_assertions)
+    /// can be placed against the auxiliary segments of the execution trace described by this AIR.
+    ///
+    /// # Errors
+    /// Returns an error if any of the assertions is placed against a column outside of the
+    /// auxiliary trace segments or against a step outside of the execution trace.
+    fn validate_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Result<(), AssertionError> {
+        let trace_width = self.trace_layout().aux_trace_width();
+        let trace_length = self.trace_length();
+        self.get_aux_assertions(aux_rand_elements)
+            .iter()
+            .try_for_each(|assertion| assertion.validate(trace_width, trace_length))
+    }
+
+    /// Returns the smallest degree of field extension which proofs for this computation must use
+    /// to meet the security target implied by the specified `options`.
+    ///
+    /// The target is the security provided by the query phase of the protocol, i.e.,
+    /// $log_2(blowup) \cdot num\_queries$ bits (plus the grinding factor for proofs of adequate
+    /// security). Random values drawn from a field of $b$ bits over an LDE domain of size $2^d$
+    /// provide at most $b - d$ bits of security regardless of the number of out-of-domain points
+    /// (see [StarkProof::security_estimate()](crate::proof::StarkProof::security_estimate)), and
+    /// thus, for small base fields the target can be met only by drawing random values from an
+    /// extension field.
+    ///
+    /// The default implementation returns the smallest of 1, 2, or 3 which makes the field
+    /// security meet the target, or 3 if no supported extension meets it. The verifier rejects
+    /// proofs generated with a smaller extension degree than the one returned by this method.
+    fn required_extension_degree(options: &ProofOptions, trace_info: &TraceInfo) -> u32 {
+        let lde_domain_size = (trace_info.length() * options.blowup_factor()) as u64;
+        let target_security = get_query_security(options);
+        (1..=3)
+            .find(|&degree| {
+                let field_security = get_field_security(
+                    options,
+                    Self::BaseField::MODULUS_BITS,
+                    degree,
+                    lde_domain_size,
+                );
+                field_security >= target_security
+            })
+            .unwrap_or(3)
+    }
+
+    /// Returns conjectured security level (in bits) of proofs for this computation generated
+    /// using hash function `H`.
+    ///
+    /// This is the same security level as the one reported by
+    /// [StarkProof::security_level()](crate::proof::StarkProof::security_level) for proofs
+    /// generated against this AIR; unlike the latter, it can be computed before a proof is
+    /// generated.
+    fn conjectured_security_level<H: Hasher>(&self) -> u32 {
+        self.options().conjectured_security_level(
+            Self::BaseField::MODULUS_BITS,
+            self.lde_domain_size(),
+            H::COLLISION_RESISTANCE,
+        )
+    }
+
+    /// Returns a machine-readable description of the constraints defined by this AIR.
+    ///
+    /// The description includes trace widths, degrees of all transition constraints, all
+    /// boundary assertions, and lengths of periodic columns for the trace length and public
+    /// inputs this AIR was instantiated with. Values of auxiliary assertions depend on random
+    /// elements drawn during proof generation, and thus, they are not included.
+    fn describe(&self) -> AirDescription {
+        description::describe(self)
+    }
+
+    /// Returns a graph of dependencies between columns of the main trace segment, transition
+    /// constraints, and assertions defined by this AIR.
+    ///
+    /// The graph is built by evaluating main transition constraints over
+    /// [DEFAULT_NUM_DEPENDENCY_SAMPLES] pseudo-random evaluation frames; see [DependencyGraph]
+    /// for details. This method is available only when `dependency-graph` feature is enabled.
+    #[cfg(feature = "dependency-graph")]
+    fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::from_air(self, DEFAULT_NUM_DEPENDENCY_SAMPLES)
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -429,6 +619,15 @@ pub trait Air: Send + Sync {
         self.context().ce_blowup_factor
     }
 
+    /// Returns the number of constraint composition columns committed to by the prover.
+    ///
+    /// The prover builds a separate constraint composition polynomial for every out-of-domain
+    /// point, and breaks each of these polynomials into `ce_blowup_factor` columns; thus, this is
+    /// equal to `ce_blowup_factor * num_ood_points`.
+    fn num_composition_columns(&self) -> usize {
+        self.ce_blowup_factor() * self.options().num_ood_points()
+    }
+
     /// Returns size of the constraint evaluation domain.
     ///
     /// This is guaranteed to be a power of two, and is equal to `trace_length * ce_blowup_factor`.
@@ -480,14 +679,14 @@ pub trait Air: Send + Sync {
     /// with the specified index.
     ///
     /// The elements are drawn uniformly at random from the provided public coin.
-    fn get_aux_trace_segment_random_elements<E, H>(
+    fn get_aux_trace_segment_random_elements<E, C>(
         &self,
         aux_segment_idx: usize,
-        public_coin: &mut RandomCoin<Self::BaseField, H>,
+        public_coin: &mut C,
     ) -> Result<Vec<E>, RandomCoinError>
     where
         E: FieldElement<BaseField = Self::BaseField>,
-        H: Hasher,
+        C: RandomCoin<BaseField = Self::BaseField>,
     {
         let num_elements = self
             .trace_info()
@@ -503,57 +702,141 @@ pub trait Air: Send + Sync {
     // LINEAR COMBINATION COEFFICIENTS
     // --------------------------------------------------------------------------------------------
 
-    /// Returns coefficients needed for random linear combination during construction of constraint
-    /// composition polynomial.
-    fn get_constraint_composition_coefficients<E, H>(
+    /// Returns coefficients needed for random linear combinations during construction of
+    /// constraint composition polynomials.
+    ///
+    /// A separate set of coefficients is drawn for every out-of-domain point specified by the
+    /// proof options; the prover builds a separate composition polynomial from each set, and
+    /// constraints are checked against the polynomial for a given point only at that point. Sets
+    /// are drawn one point at a time, and thus, the set for the first point is the same as the
+    /// only set drawn for a single out-of-domain point.
+    ///
+    /// When keyed coefficients are enabled in the context of this AIR, coefficients for each
+    /// transition constraint are drawn from a coin derived from the public coin and the
+    /// identifier of the constraint (for all points but the first one, this coin is further derived
+    /// from the index of the point), and the state of the public coin is not advanced by these
+    /// draws.
+    fn get_constraint_composition_coefficients<E, C>(
         &self,
-        public_coin: &mut RandomCoin<Self::BaseField, H>,
-    ) -> Result<ConstraintCompositionCoefficients<E>, RandomCoinError>
+        public_coin: &mut C,
+    ) -> Result<Vec<ConstraintCompositionCoefficients<E>>, RandomCoinError>
     where
         E: FieldElement<BaseField = Self::BaseField>,
-        H: Hasher,
+        C: RandomCoin<BaseField = Self::BaseField>,
     {
-        let mut t_coefficients = Vec::new();
-        for _ in 0..self.context().num_transition_constraints() {
-            t_coefficients.push(public_coin.draw_pair()?);
+        let num_ood_points = self.options().num_ood_points();
+        let mut result = Vec::with_capacity(num_ood_points);
+        for point_idx in 0..num_ood_points {
+            let mut t_coefficients = CoefficientPairs::new();
+            if self.context().keyed_coefficients() {
+                let ids = self
+                    .context()
+                    .main_transition_constraint_ids()
+                    .iter()
+                    .chain(self.context().aux_transition_constraint_ids().iter());
+                for id in ids {
+                    let mut coin = public_coin.derive_keyed(id.as_bytes());
+                    if point_idx > 0 {
+                        coin = coin.derive_keyed(&[point_idx as u8]);
+                    }
+                    t_coefficients.push(coin.draw_pair()?);
+                }
+            } else {
+                for _ in 0..self.context().num_transition_constraints() {
+                    t_coefficients.push(public_coin.draw_pair()?);
+                }
+            }
+
+            let mut b_coefficients = CoefficientPairs::new();
+            for _ in 0..self.context().num_assertions() {
+                b_coefficients.push(public_coin.draw_pair()?);
+            }
+
+            let mut e_coefficients = CoefficientPairs::new();
+            for _ in 0..self.context().num_boundary_exprs() {
+                e_coefficients.push(public_coin.draw_pair()?);
+            }
+
+            result.push(ConstraintCompositionCoefficients {
+                transition: t_coefficients,
+                boundary: b_coefficients,
+                boundary_exprs: e_coefficients,
+            });
         }
 
-        let mut b_coefficients = Vec::new();
-        for _ in 0..self.context().num_assertions() {
-            b_coefficients.push(public_coin.draw_pair()?);
-        }
-
-        Ok(ConstraintCompositionCoefficients {
-            transition: t_coefficients,
-            boundary: b_coefficients,
-        })
+        Ok(result)
     }
 
     /// Returns coefficients needed for random linear combinations during construction of DEEP
     /// composition polynomial.
-    fn get_deep_composition_coefficients<E, H>(
+    fn get_deep_composition_coefficients<E, C>(
         &self,
-        public_coin: &mut RandomCoin<Self::BaseField, H>,
+        public_coin: &mut C,
     ) -> Result<DeepCompositionCoefficients<E>, RandomCoinError>
     where
         E: FieldElement<BaseField = Self::BaseField>,
-        H: Hasher,
+        C: RandomCoin<BaseField = Self::BaseField>,
     {
+        // coefficients for each of the OOD points are drawn one point at a time; for a single OOD
+        // point, this is the same as drawing all trace coefficients and then all constraint
+        // coefficients
+        let num_extra_offsets = self.context().frame_offsets().len() - 2;
         let mut t_coefficients = Vec::new();
-        for _ in 0..self.trace_info().width() {
-            t_coefficients.push(public_coin.draw_triple()?);
-        }
-
-        // self.ce_blowup_factor() is the same as number of composition columns
+        let mut o_coefficients = Vec::new();
         let mut c_coefficients = Vec::new();
-        for _ in 0..self.ce_blowup_factor() {
-            c_coefficients.push(public_coin.draw()?);
+        for _ in 0..self.options().num_ood_points() {
+            for _ in 0..self.trace_info().width() {
+                t_coefficients.push(public_coin.draw_triple()?);
+            }
+
+            // coefficients for frame rows beyond the first two are drawn only for AIRs which
+            // declare such rows
+            for _ in 0..self.trace_info().width() * num_extra_offsets {
+                o_coefficients.push(public_coin.draw()?);
+            }
+
+            // self.ce_blowup_factor() is the same as number of composition columns per point
+            for _ in 0..self.ce_blowup_factor() {
+                c_coefficients.push(public_coin.draw()?);
+            }
         }
 
         Ok(DeepCompositionCoefficients {
             trace: t_coefficients,
+            trace_offsets: o_coefficients,
             constraints: c_coefficients,
             degree: public_coin.draw_pair()?,
         })
     }
 }
+
+// SPLIT INPUTS AIR
+// ================================================================================================
+/// Describes an AIR whose assertions can be built from a subset of its public inputs.
+///
+/// Public inputs of a computation serve two roles during verification: serialized public inputs
+/// seed the public coin, and the values of the inputs define the assertions of the AIR. Some
+/// verifiers (e.g., verifiers running on-chain) receive only a digest of serialized public inputs
+/// and the handful of values needed to build the assertions. An AIR implementing this trait can
+/// be instantiated from such values alone, and thus, proofs for it can be verified against a
+/// pre-hashed seed (see [pub_inputs_seed()](crate::pub_inputs_seed)).
+///
+/// The seed must bind the assertion inputs: the verifier does not check that the seed was
+/// computed from public inputs with the same assertion values, and if the seed did not commit to
+/// these values, a prover could choose the assertions after learning the randomness drawn from
+/// the public coin.
+pub trait SplitInputsAir: Air {
+    /// Minimal set of values from which assertions of the computation can be built.
+    type AssertionInputs;
+
+    /// Returns an instance of this AIR for a computation described by the `trace_info` whose
+    /// assertions are defined by the `assertion_inputs`.
+    ///
+    /// The returned AIR must be the same as the one which [Air::new()] returns for public inputs
+    /// containing the `assertion_inputs`; otherwise, proofs generated for it will not verify.
+    fn from_assertion_inputs(
+        trace_info: TraceInfo,
+        assertion_inputs: Self::AssertionInputs,
+        options: ProofOptions,
+    ) -> Self;
+}
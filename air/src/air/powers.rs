@@ -0,0 +1,247 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::air::{AirContext, Assertion};
+use math::{log2, FieldElement, StarkField};
+use utils::collections::Vec;
+
+// POWER TABLE
+// ================================================================================================
+/// Precomputed powers of the generator of a multiplicative subgroup of size $n$.
+///
+/// Building constraints of a computation and evaluating them requires many powers of the trace
+/// domain generator $g$: offsets $g^{a \cdot k}$ of assertion divisors, exemption points
+/// $g^{n - i}$ of the transition divisor, offsets $g^{-a}$ of value polynomials of assertions
+/// against sequences of steps etc. Computing each of these powers via exponentiation requires
+/// about $log_2(n)$ squarings. A power table instead computes all powers requested at the time
+/// the table is built in a single pass: the exponents are sorted, and every power is derived from
+/// the previous one by multiplying it by the powers $g^{2^i}$ which make up the difference
+/// between the two exponents.
+///
+/// Powers which were not requested when the table was built can still be retrieved from the
+/// table; such powers are computed on the fly from the powers $g^{2^i}$.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerTable<B: StarkField> {
+    domain_size: usize,
+    squares: Vec<B>,
+    // precomputed powers sorted by their exponents
+    powers: Vec<(usize, B)>,
+}
+
+impl<B: StarkField> PowerTable<B> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+    /// Returns a table of powers of the generator of a domain of the specified size; all of the
+    /// specified `exponents` are precomputed.
+    ///
+    /// Since the generator has order `domain_size`, exponents are reduced modulo `domain_size`.
+    ///
+    /// # Panics
+    /// Panics if `domain_size` is not a power of two greater than one.
+    pub fn new<I: IntoIterator<Item = usize>>(domain_size: usize, exponents: I) -> Self {
+        assert!(
+            domain_size > 1 && domain_size.is_power_of_two(),
+            "domain size must be a power of two greater than one, but was {domain_size}"
+        );
+
+        // build powers g^(2^i) for all i in [0, log2(domain_size))
+        let mut squares = Vec::with_capacity(log2(domain_size) as usize);
+        let mut square = B::get_root_of_unity(log2(domain_size));
+        for _ in 0..log2(domain_size) {
+            squares.push(square);
+            square = square.square();
+        }
+
+        // sort the exponents, and derive each power from the previous one
+        let mut powers = exponents
+            .into_iter()
+            .map(|exponent| (exponent % domain_size, B::ONE))
+            .collect::<Vec<_>>();
+        powers.sort_unstable_by_key(|&(exponent, _)| exponent);
+        powers.dedup_by_key(|&mut (exponent, _)| exponent);
+        let mut prev = (0, B::ONE);
+        for (exponent, power) in powers.iter_mut() {
+            *power = prev.1 * compose_power(&squares, *exponent - prev.0);
+            prev = (*exponent, *power);
+        }
+
+        PowerTable {
+            domain_size,
+            squares,
+            powers,
+        }
+    }
+
+    /// Returns a table of powers of the trace domain generator which contains all powers needed
+    /// to build transition constraints and boundary constraints for the specified assertions.
+    ///
+    /// Powers needed for assertions which are not valid in the context of the computation are
+    /// not precomputed; such assertions are rejected once the boundary constraints are built.
+    pub fn for_constraints<E: FieldElement<BaseField = B>>(
+        context: &AirContext<B>,
+        main_assertions: &[Assertion<B>],
+        aux_assertions: &[Assertion<E>],
+    ) -> Self {
+        let trace_length = context.trace_len();
+
+        // the generator itself and exemption points of the transition divisor
+        let mut exponents = vec![1];
+        exponents.extend(trace_length - context.num_transition_exemptions()..trace_length);
+
+        // divisor offsets and value polynomial offsets of all assertions
+        let main_exponents = main_assertions
+            .iter()
+            .filter_map(|assertion| get_assertion_exponents(assertion, trace_length));
+        let aux_exponents = aux_assertions
+            .iter()
+            .filter_map(|assertion| get_assertion_exponents(assertion, trace_length));
+        for assertion_exponents in main_exponents.chain(aux_exponents) {
+            exponents.extend(assertion_exponents);
+        }
+
+        Self::new(trace_length, exponents)
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the size of the domain described by this table.
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Returns the generator of the domain described by this table.
+    pub fn generator(&self) -> B {
+        self.squares[0]
+    }
+
+    /// Returns the number of precomputed powers in this table.
+    pub fn len(&self) -> usize {
+        self.powers.len()
+    }
+
+    /// Returns true if this table does not contain any precomputed powers.
+    pub fn is_empty(&self) -> bool {
+        self.powers.is_empty()
+    }
+
+    /// Returns $g^e$, where $g$ is the generator of the domain and $e$ is the specified
+    /// `exponent`; if the power was not precomputed, it is computed on the fly.
+    pub fn get(&self, exponent: usize) -> B {
+        let exponent = exponent % self.domain_size;
+        match self
+            .powers
+            .binary_search_by_key(&exponent, |&(exponent, _)| exponent)
+        {
+            Ok(idx) => self.powers[idx].1,
+            Err(_) => compose_power(&self.squares, exponent),
+        }
+    }
+
+    /// Returns $g^{-e}$, where $g$ is the generator of the domain and $e$ is the specified
+    /// `exponent`; this is the same as $g^{n - e}$, where $n$ is the size of the domain.
+    pub fn get_inv(&self, exponent: usize) -> B {
+        self.get(self.domain_size - exponent % self.domain_size)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns g^exponent computed as a product of g^(2^i) for all bits i set in the exponent.
+fn compose_power<B: StarkField>(squares: &[B], exponent: usize) -> B {
+    let mut result = B::ONE;
+    let mut bits = exponent;
+    while bits != 0 {
+        let i = bits.trailing_zeros() as usize;
+        result *= squares[i];
+        bits &= bits - 1;
+    }
+    result
+}
+
+/// Returns the exponents of the divisor offsets of the specified assertion followed by the
+/// exponent of the inverse of its value polynomial offset (if any); returns None for invalid
+/// assertions.
+///
+/// Divisors of window assertions have an offset for every step of the window; divisors of other
+/// assertions have a single offset.
+fn get_assertion_exponents<E: FieldElement>(
+    assertion: &Assertion<E>,
+    trace_length: usize,
+) -> Option<Vec<usize>> {
+    assertion.validate_trace_length(trace_length).ok()?;
+    let num_steps = assertion.get_num_steps(trace_length);
+    let first_step = assertion.first_step();
+    if assertion.is_window() {
+        return Some((first_step..first_step + num_steps).collect());
+    }
+
+    let mut exponents = vec![num_steps * first_step];
+    if assertion.values().len() > 1 && first_step != 0 {
+        exponents.push(trace_length - first_step);
+    }
+    Some(exponents)
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(all(test, feature = "f128"))]
+mod tests {
+    use super::*;
+    use crate::{FieldExtension, ProofOptions, TraceInfo, TransitionConstraintDegree};
+    use math::fields::f128::BaseElement;
+
+    #[test]
+    fn power_table_get() {
+        let n = 64;
+        let g = BaseElement::get_root_of_unity(log2(n));
+        let table = PowerTable::<BaseElement>::new(n, [0, 3, 17, 63, 64 + 5, 17]);
+        assert_eq!(n, table.domain_size());
+        assert_eq!(g, table.generator());
+        assert_eq!(
+            vec![0, 3, 5, 17, 63],
+            table.powers.iter().map(|&(e, _)| e).collect::<Vec<_>>()
+        );
+
+        for exponent in 0..2 * n {
+            let expected = g.exp((exponent as u64).into());
+            assert_eq!(expected, table.get(exponent));
+            assert_eq!(expected.inv(), table.get_inv(exponent));
+        }
+
+        // powers are also available without being precomputed
+        let table = PowerTable::<BaseElement>::new(n, []);
+        assert!(table.is_empty());
+        assert_eq!(g.exp(45), table.get(45));
+    }
+
+    #[test]
+    fn power_table_for_constraints() {
+        let n = 16;
+        let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+        let degrees = vec![TransitionConstraintDegree::new(2)];
+        let mut context = AirContext::new(TraceInfo::new(2, n), degrees, 4, options);
+        context = context.set_num_transition_exemptions(2);
+
+        let assertions = vec![
+            Assertion::single(0, 3, BaseElement::ONE),
+            Assertion::periodic(1, 0, 4, BaseElement::ONE),
+            Assertion::sequence(0, 1, 8, vec![BaseElement::ONE, BaseElement::ZERO]),
+            // this assertion is invalid, and thus, it is ignored
+            Assertion::single(0, 2 * n, BaseElement::ONE),
+        ];
+        let table = PowerTable::for_constraints::<BaseElement>(&context, &assertions, &[]);
+
+        // g, exemptions at n - 2 and n - 1, offsets of the single assertion, the periodic
+        // assertion, and the sequence assertion (both for the divisor and the value polynomial)
+        let expected = [0, 1, 2, 3, 14, 15];
+        assert_eq!(
+            expected.to_vec(),
+            table.powers.iter().map(|&(e, _)| e).collect::<Vec<_>>()
+        );
+    }
+}
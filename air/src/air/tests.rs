@@ -4,12 +4,14 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{
-    Air, AirContext, Assertion, EvaluationFrame, ProofOptions, TraceInfo,
-    TransitionConstraintDegree,
+    Air, AirContext, Assertion, ConstraintDescriptor, ConstraintDivisor, EvaluationFrame,
+    ProofOptions, TraceInfo, TraceLayoutBuilder, TransitionConstraintDegree, TransitionConstraints,
+    TransitionDivisorKind,
 };
-use crate::{AuxTraceRandElements, FieldExtension};
-use crypto::{hashers::Blake3_256, RandomCoin};
+use crate::{AuxTraceRandElements, FieldExtension, TraceLayoutError};
+use crypto::{hashers::Blake3_256, DefaultRandomCoin};
 use math::{fields::f128::BaseElement, get_power_series, log2, polynom, FieldElement, StarkField};
+use rand_utils::rand_vector;
 use utils::collections::{BTreeMap, Vec};
 
 // PERIODIC COLUMNS
@@ -59,7 +61,230 @@ fn get_periodic_column_polys_num_values_not_power_of_two() {
 // TRANSITION CONSTRAINTS
 // ================================================================================================
 
-// TODO
+#[test]
+fn get_transition_constraints_cyclic() {
+    let trace_length = 16;
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+    let t_degrees = vec![
+        TransitionConstraintDegree::new(2),
+        TransitionConstraintDegree::new(2).with_divisor(TransitionDivisorKind::Cyclic),
+        TransitionConstraintDegree::new(2),
+    ];
+    let context = AirContext::new(TraceInfo::new(4, trace_length), t_degrees, 1, options);
+    let coefficients = (1..4)
+        .map(|i| (BaseElement::new(i), BaseElement::new(i + 10)))
+        .collect::<Vec<_>>();
+    let constraints = TransitionConstraints::new(&context, &coefficients);
+
+    // cyclic constraints are grouped separately from regular constraints of the same degree
+    assert!(constraints.has_cyclic_constraints());
+    let groups = constraints.main_constraints();
+    assert_eq!(2, groups.len());
+    assert_eq!(TransitionDivisorKind::Transition, groups[0].divisor_kind());
+    assert_eq!(vec![0, 2], groups[0].indexes());
+    assert_eq!(TransitionDivisorKind::Cyclic, groups[1].divisor_kind());
+    assert_eq!(vec![1], groups[1].indexes());
+
+    // the degree of the cyclic divisor is greater by one, and so is the degree adjustment
+    let divisors = constraints.get_divisors();
+    assert_eq!(
+        ConstraintDivisor::from_transition(trace_length, 1),
+        divisors[0]
+    );
+    assert_eq!(ConstraintDivisor::from_cyclic(trace_length), divisors[1]);
+    assert_eq!(trace_length, divisors[1].degree());
+    assert_eq!(
+        groups[0].degree_adjustment() + 1,
+        groups[1].degree_adjustment()
+    );
+
+    // each sum of merged evaluations is divided by its own divisor
+    let evaluations = [3u128, 5, 7].map(BaseElement::new);
+    let x = BaseElement::new(101);
+    let xp = x.exp(groups[0].degree_adjustment().into());
+    let expected = (groups[0].merge_evaluations::<BaseElement, BaseElement>(&evaluations, xp)
+        / divisors[0].evaluate_at(x))
+        + (groups[1].merge_evaluations::<BaseElement, BaseElement>(&evaluations, xp * x)
+            / divisors[1].evaluate_at(x));
+    assert_eq!(
+        expected,
+        constraints.combine_evaluations::<BaseElement>(&evaluations, &[], x)
+    );
+
+    // without cyclic constraints, only the transition divisor is used
+    let context = build_context::<BaseElement>(trace_length, 4, 1);
+    let constraints = TransitionConstraints::new(&context, &coefficients[..1]);
+    assert!(!constraints.has_cyclic_constraints());
+    assert_eq!(1, constraints.num_divisors());
+    assert_eq!(
+        vec![constraints.divisor().clone()],
+        constraints.get_divisors()
+    );
+}
+
+#[test]
+fn get_transition_constraints_exemptions() {
+    let trace_length = 16;
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+    let t_degrees = vec![
+        TransitionConstraintDegree::new(2),
+        TransitionConstraintDegree::new(2),
+        TransitionConstraintDegree::new(2).with_divisor(TransitionDivisorKind::Cyclic),
+        TransitionConstraintDegree::new(2),
+    ];
+    let context = AirContext::new(TraceInfo::new(4, trace_length), t_degrees, 1, options)
+        .set_transition_exemptions(vec![3, 1, 2, 1]);
+    assert_eq!(&[3, 1, 2, 1], context.transition_exemptions());
+    assert_eq!(3, context.num_transition_exemptions());
+
+    let coefficients = (1..5)
+        .map(|i| (BaseElement::new(i), BaseElement::new(i + 10)))
+        .collect::<Vec<_>>();
+    let constraints = TransitionConstraints::new(&context, &coefficients);
+
+    // transition divisors are ordered by the number of exemptions and are followed by the
+    // cyclic divisor; the exemptions of the cyclic constraint are ignored
+    let divisors = constraints.get_divisors();
+    assert_eq!(
+        vec![
+            ConstraintDivisor::from_transition(trace_length, 1),
+            ConstraintDivisor::from_transition(trace_length, 3),
+            ConstraintDivisor::from_cyclic(trace_length),
+        ],
+        divisors
+    );
+    assert_eq!(&[1, 0, 2, 0], constraints.divisor_indexes());
+    assert_eq!(&divisors[1], constraints.get_constraint_divisor(0));
+    assert_eq!(&divisors[0], constraints.divisor());
+
+    // constraints of the same degree are grouped separately for each divisor, and the degree
+    // adjustment accounts for the degree of the divisor
+    let groups = constraints.main_constraints();
+    assert_eq!(3, groups.len());
+    assert_eq!(vec![1, 3], groups[0].indexes());
+    assert_eq!(vec![0], groups[1].indexes());
+    assert_eq!(vec![2], groups[2].indexes());
+    assert_eq!(
+        groups[1].degree_adjustment() + 2,
+        groups[0].degree_adjustment()
+    );
+
+    // each sum of merged evaluations is divided by its own divisor
+    let evaluations = [3u128, 5, 7, 11].map(BaseElement::new);
+    let x = BaseElement::new(101);
+    let expected = groups.iter().fold(BaseElement::ZERO, |acc, group| {
+        let xp = x.exp(group.degree_adjustment().into());
+        acc + group.merge_evaluations::<BaseElement, BaseElement>(&evaluations, xp)
+            / divisors[group.divisor_idx()].evaluate_at(x)
+    });
+    assert_eq!(
+        expected,
+        constraints.combine_evaluations::<BaseElement>(&evaluations, &[], x)
+    );
+
+    // a single number of exemptions is applied to all constraints
+    let context = context.set_num_transition_exemptions(2);
+    assert_eq!(&[2, 2, 2, 2], context.transition_exemptions());
+    let constraints = TransitionConstraints::new(&context, &coefficients);
+    assert_eq!(2, constraints.num_divisors());
+    assert_eq!(
+        &ConstraintDivisor::from_transition(trace_length, 2),
+        constraints.divisor()
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "number of transition exemption entries must be equal to the number of transition constraints"
+)]
+fn set_transition_exemptions_wrong_length() {
+    build_context::<BaseElement>(16, 4, 1).set_transition_exemptions(vec![1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "number of transition exemptions must be greater than zero")]
+fn set_transition_exemptions_zero() {
+    build_context::<BaseElement>(16, 4, 1).set_transition_exemptions(vec![0]);
+}
+
+// EVALUATION FRAME
+// ================================================================================================
+
+#[test]
+fn evaluation_frame_set_rows() {
+    let current = rand_vector::<BaseElement>(4);
+    let next = rand_vector::<BaseElement>(4);
+    let expected = EvaluationFrame::from_rows(current.clone(), next.clone());
+
+    let frame = EvaluationFrame::from_rows_ref(&current, &next);
+    assert_eq!(expected.current(), frame.current());
+    assert_eq!(expected.next(), frame.next());
+
+    // updating a frame in place overwrites both of its rows
+    let mut frame = EvaluationFrame::new(4);
+    frame.set_rows(&current, &next);
+    assert_eq!(expected.current(), frame.current());
+    assert_eq!(expected.next(), frame.next());
+}
+
+#[test]
+#[should_panic(expected = "number of values in the current row must be the same")]
+fn evaluation_frame_set_rows_wrong_width() {
+    let mut frame = EvaluationFrame::<BaseElement>::new(4);
+    frame.set_rows(&rand_vector(3), &rand_vector(3));
+}
+
+#[test]
+fn evaluation_frame_with_offsets() {
+    let rows = (0..3)
+        .map(|_| rand_vector::<BaseElement>(4))
+        .collect::<Vec<_>>();
+    let frame = EvaluationFrame::from_offset_rows(&[0, 2, 5], rows.clone());
+    assert_eq!(&[0, 2, 5], frame.offsets());
+    assert_eq!(3, frame.num_rows());
+    assert_eq!(&rows[0], frame.current());
+    assert_eq!(&rows[1], frame.next());
+    assert_eq!(&rows[2], frame.row(2));
+
+    // frames built with the default constructor consist of the current and the next rows
+    let frame = EvaluationFrame::<BaseElement>::new(4);
+    assert_eq!(&[0, 1], frame.offsets());
+
+    let mut frame = EvaluationFrame::<BaseElement>::with_offsets(4, &[0, 3, 4]);
+    frame.row_mut(2).copy_from_slice(&rows[2]);
+    assert_eq!(&rows[2], frame.row(2));
+}
+
+#[test]
+#[should_panic(expected = "the first row offset of an evaluation frame must be zero")]
+fn evaluation_frame_with_offsets_not_starting_at_zero() {
+    EvaluationFrame::<BaseElement>::with_offsets(4, &[1, 2]);
+}
+
+#[test]
+#[should_panic(
+    expected = "row offsets of an evaluation frame must be sorted in strictly ascending"
+)]
+fn evaluation_frame_with_offsets_not_sorted() {
+    EvaluationFrame::<BaseElement>::with_offsets(4, &[0, 3, 3]);
+}
+
+#[test]
+fn set_frame_offsets() {
+    let context = build_context::<BaseElement>(16, 4, 1);
+    assert_eq!(&[0, 1], context.frame_offsets());
+
+    let context = context.set_frame_offsets(vec![0, 4, 15]);
+    assert_eq!(&[0, 4, 15], context.frame_offsets());
+}
+
+#[test]
+#[should_panic(
+    expected = "row offsets of an evaluation frame must be smaller than trace length 16"
+)]
+fn set_frame_offsets_too_large() {
+    build_context::<BaseElement>(16, 4, 1).set_frame_offsets(vec![0, 16]);
+}
 
 // BOUNDARY CONSTRAINTS
 // ================================================================================================
@@ -217,6 +442,234 @@ fn get_boundary_constraints() {
     assert_eq!(expected_cc[&7], constraint.cc().clone());
 }
 
+// CONSTRAINT COMPOSITION COEFFICIENTS
+// ================================================================================================
+
+#[test]
+fn get_constraint_composition_coefficients_keyed() {
+    let air1 = MockAir::with_constraint_ids(&["a", "b"], true);
+    let cc1 = air1
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap()
+        .swap_remove(0);
+
+    // inserting a new constraint does not change coefficients of the existing constraints, or
+    // coefficients of assertions
+    let air2 = MockAir::with_constraint_ids(&["a", "c", "b"], true);
+    let cc2 = air2
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap()
+        .swap_remove(0);
+    assert_eq!(3, cc2.transition.len());
+    assert_eq!(cc1.transition[0], cc2.transition[0]);
+    assert_eq!(cc1.transition[1], cc2.transition[2]);
+    assert_ne!(cc1.transition[1], cc2.transition[1]);
+    assert_eq!(cc1.boundary, cc2.boundary);
+
+    // coefficients of a constraint are derived from its identifier
+    let prng = build_prng();
+    let expected = prng.derive_keyed(b"b").draw_pair().unwrap();
+    assert_eq!(expected, cc1.transition[1]);
+}
+
+#[test]
+fn get_constraint_composition_coefficients_positional() {
+    // without keyed coefficients, inserting a new constraint shifts coefficients of all
+    // subsequent constraints
+    let air1 = MockAir::with_constraint_ids(&["a", "b"], false);
+    let cc1 = air1
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap()
+        .swap_remove(0);
+    let air2 = MockAir::with_constraint_ids(&["a", "c", "b"], false);
+    let cc2 = air2
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap()
+        .swap_remove(0);
+    assert_eq!(cc1.transition[0], cc2.transition[0]);
+    assert_eq!(cc1.transition[1], cc2.transition[1]);
+    assert_ne!(cc1.transition[1], cc2.transition[2]);
+}
+
+#[test]
+fn get_constraint_composition_coefficients_multiple_ood_points() {
+    let air = MockAir::with_num_ood_points(1, 16);
+    let cc1 = air
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap();
+    assert_eq!(1, cc1.len());
+
+    // a separate set of coefficients is drawn for every point; the set for the first point is
+    // drawn the same way as for a single point
+    let air = MockAir::with_num_ood_points(2, 16);
+    let cc2 = air
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap();
+    assert_eq!(2, cc2.len());
+    assert_eq!(cc1[0].transition, cc2[0].transition);
+    assert_eq!(cc1[0].boundary, cc2[0].boundary);
+    assert_ne!(cc2[0].transition, cc2[1].transition);
+    assert_ne!(cc2[0].boundary, cc2[1].boundary);
+}
+
+#[test]
+fn get_constraint_composition_coefficients_keyed_multiple_ood_points() {
+    let air = MockAir::with_constraint_ids(&["a", "b"], true);
+    let cc1 = air
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap();
+    let air = MockAir::with_constraint_ids_and_ood_points(&["a", "b"], true, 2);
+    let cc2 = air
+        .get_constraint_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap();
+    assert_eq!(cc1[0].transition, cc2[0].transition);
+
+    // keyed coefficients for the second point are derived from the identifier of the constraint
+    // and the index of the point
+    let prng = build_prng();
+    let expected = prng
+        .derive_keyed(b"b")
+        .derive_keyed(&[1])
+        .draw_pair()
+        .unwrap();
+    assert_eq!(expected, cc2[1].transition[1]);
+}
+
+#[test]
+#[should_panic(expected = "constraint identifier 'a' is assigned to more than one constraint")]
+fn get_constraint_composition_coefficients_duplicate_ids() {
+    MockAir::with_constraint_ids(&["a", "b", "a"], true);
+}
+
+#[test]
+#[should_panic(
+    expected = "keyed coefficients require transition constraints to be described by constraint descriptors"
+)]
+fn get_constraint_composition_coefficients_keyed_without_ids() {
+    build_context::<BaseElement>(16, 4, 1).set_keyed_coefficients(true);
+}
+
+// DEEP COMPOSITION COEFFICIENTS
+// ================================================================================================
+
+#[test]
+fn get_deep_composition_coefficients_multiple_ood_points() {
+    let air = MockAir::with_num_ood_points(1, 16);
+    let cc1 = air
+        .get_deep_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap();
+    assert_eq!(4, cc1.trace.len());
+    assert_eq!(air.ce_blowup_factor(), cc1.constraints.len());
+
+    let air = MockAir::with_num_ood_points(2, 16);
+    let cc2 = air
+        .get_deep_composition_coefficients::<BaseElement, _>(&mut build_prng())
+        .unwrap();
+    assert_eq!(8, cc2.trace.len());
+    assert_eq!(2 * air.ce_blowup_factor(), cc2.constraints.len());
+
+    // coefficients for the first point are drawn the same way as for a single point
+    assert_eq!(cc1.trace, cc2.trace_coefficients(0, 2));
+    assert_eq!(cc1.constraints, cc2.constraint_coefficients(0, 2));
+    assert_ne!(cc1.trace, cc2.trace_coefficients(1, 2));
+
+    // coefficients for the second point are drawn after all coefficients for the first point
+    let mut prng = build_prng();
+    for _ in 0..4 {
+        prng.draw_triple::<BaseElement>().unwrap();
+    }
+    for _ in 0..air.ce_blowup_factor() {
+        prng.draw::<BaseElement>().unwrap();
+    }
+    let expected = prng.draw_triple::<BaseElement>().unwrap();
+    assert_eq!(expected, cc2.trace_coefficients(1, 2)[0]);
+}
+
+// TRACE LAYOUT BUILDER
+// ================================================================================================
+
+#[test]
+fn trace_layout_builder() {
+    let layout = TraceLayoutBuilder::new().main_width(4).build().unwrap();
+    assert_eq!(4, layout.main_trace_width());
+    assert_eq!(0, layout.aux_trace_width());
+    assert_eq!(0, layout.num_aux_segments());
+
+    let layout = TraceLayoutBuilder::new()
+        .main_width(4)
+        .aux_segment(3, 7)
+        .num_aux_segments(1)
+        .build()
+        .unwrap();
+    assert_eq!(4, layout.main_trace_width());
+    assert_eq!(3, layout.aux_trace_width());
+    assert_eq!(2, layout.num_segments());
+    assert_eq!(3, layout.get_aux_segment_width(0));
+    assert_eq!(7, layout.get_aux_segment_rand_elements(0));
+
+    let layout = TraceLayoutBuilder::new()
+        .main_width(254)
+        .aux_segment(1, 255)
+        .build()
+        .unwrap();
+    assert_eq!(255, layout.main_trace_width() + layout.aux_trace_width());
+}
+
+#[test]
+fn trace_layout_builder_invalid() {
+    let builder = TraceLayoutBuilder::new;
+
+    assert_eq!(Err(TraceLayoutError::EmptyMainSegment), builder().build());
+    assert_eq!(
+        Err(TraceLayoutError::EmptyMainSegment),
+        builder().main_width(0).aux_segment(1, 1).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::AuxSegmentCountMismatch(1, 0)),
+        builder().main_width(2).num_aux_segments(1).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::AuxSegmentCountMismatch(0, 1)),
+        builder()
+            .main_width(2)
+            .aux_segment(1, 1)
+            .num_aux_segments(0)
+            .build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::TooManyAuxSegments(1, 2)),
+        builder()
+            .main_width(2)
+            .aux_segment(1, 1)
+            .aux_segment(1, 1)
+            .build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::EmptyAuxSegment(0)),
+        builder().main_width(2).aux_segment(0, 0).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::RandElementsForEmptyAuxSegment(0, 3)),
+        builder().main_width(2).aux_segment(0, 3).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::NoRandElementsForAuxSegment(0)),
+        builder().main_width(2).aux_segment(3, 0).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::TooManyRandElements(0, 255, 256)),
+        builder().main_width(2).aux_segment(3, 256).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::TooManyColumns(255, 256)),
+        builder().main_width(256).build()
+    );
+    assert_eq!(
+        Err(TraceLayoutError::TooManyColumns(255, 256)),
+        builder().main_width(250).aux_segment(6, 1).build()
+    );
+}
+
 // MOCK AIR
 // ================================================================================================
 
@@ -249,6 +702,43 @@ impl MockAir {
         result.assertions = assertions;
         result
     }
+
+    pub fn with_num_ood_points(num_ood_points: usize, trace_length: usize) -> Self {
+        let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+            .with_num_ood_points(num_ood_points);
+        let t_degrees = vec![TransitionConstraintDegree::new(2)];
+        let trace_info = TraceInfo::new(4, trace_length);
+        MockAir {
+            context: AirContext::new(trace_info, t_degrees, 1, options),
+            assertions: Vec::new(),
+            periodic_columns: Vec::new(),
+        }
+    }
+
+    pub fn with_constraint_ids(ids: &[&'static str], keyed: bool) -> Self {
+        Self::with_constraint_ids_and_ood_points(ids, keyed, 1)
+    }
+
+    pub fn with_constraint_ids_and_ood_points(
+        ids: &[&'static str],
+        keyed: bool,
+        num_ood_points: usize,
+    ) -> Self {
+        let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+            .with_num_ood_points(num_ood_points);
+        let descriptors = ids
+            .iter()
+            .map(|&id| ConstraintDescriptor::new(id, TransitionConstraintDegree::new(2)))
+            .collect();
+        let trace_info = TraceInfo::new(4, 16);
+        let context = AirContext::new_with_descriptors(trace_info, descriptors, 2, options)
+            .set_keyed_coefficients(keyed);
+        MockAir {
+            context,
+            assertions: Vec::new(),
+            periodic_columns: Vec::new(),
+        }
+    }
 }
 
 impl Air for MockAir {
@@ -300,8 +790,8 @@ pub fn build_context<B: StarkField>(
     AirContext::new(trace_info, t_degrees, num_assertions, options)
 }
 
-pub fn build_prng() -> RandomCoin<BaseElement, Blake3_256<BaseElement>> {
-    RandomCoin::new(&[0; 32])
+pub fn build_prng() -> DefaultRandomCoin<BaseElement, Blake3_256<BaseElement>> {
+    DefaultRandomCoin::new(&[0; 32])
 }
 
 pub fn build_sequence_poly(values: &[BaseElement], trace_length: usize) -> Vec<BaseElement> {
@@ -3,6 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use crate::TraceLayoutError;
 use utils::{
     collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
     DeserializationError, Serializable,
@@ -22,11 +23,15 @@ const NUM_AUX_SEGMENTS: usize = 1;
 /// specifies the number of columns for all trace segments. Currently, a trace can consist of at
 /// most two segments. Metadata is just a vector of bytes and can store any values up to 64KB in
 /// size.
+///
+/// Additionally, trace info may declare a preprocessed trace segment (see
+/// [TraceInfo::with_preprocessed_segment()]).
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TraceInfo {
     layout: TraceLayout,
     length: usize,
     meta: Vec<u8>,
+    preprocessed_width: usize,
 }
 
 impl TraceInfo {
@@ -65,7 +70,10 @@ impl TraceInfo {
     /// * Length of `meta` is greater than 65535;
     pub fn with_meta(width: usize, length: usize, meta: Vec<u8>) -> Self {
         assert!(width > 0, "trace width must be greater than 0");
-        let layout = TraceLayout::new(width, [0], [0]);
+        let layout = TraceLayoutBuilder::new()
+            .main_width(width)
+            .build()
+            .unwrap_or_else(|err| panic!("{err}"));
         Self::new_multi_segment(layout, length, meta)
     }
 
@@ -97,9 +105,43 @@ impl TraceInfo {
             layout,
             length,
             meta,
+            preprocessed_width: 0,
         }
     }
 
+    /// Returns this trace info extended with a declaration of a preprocessed trace segment
+    /// consisting of the specified number of columns.
+    ///
+    /// Columns of a preprocessed segment are committed to once, and the commitment is shared
+    /// across all proofs for traces of the same length (e.g., a program ROM which is the same for
+    /// every execution of a program). Proofs include only openings of this commitment at query
+    /// positions, while the commitment itself is obtained by the verifier out of band.
+    ///
+    /// Columns of the preprocessed segment follow the columns of the main segment in the main
+    /// evaluation frame, and thus, transition constraints can reference them in the same way as
+    /// the columns of the main segment. Assertions cannot be placed against these columns.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `width` is zero.
+    /// * Total width of all trace segments (including the preprocessed segment) is greater than
+    ///   255.
+    pub fn with_preprocessed_segment(mut self, width: usize) -> Self {
+        assert!(
+            width > 0,
+            "preprocessed segment width must be greater than 0"
+        );
+        let full_width = self.width() + width;
+        assert!(
+            full_width <= Self::MAX_TRACE_WIDTH,
+            "total number of columns in the trace cannot be greater than {}, but was {}",
+            Self::MAX_TRACE_WIDTH,
+            full_width
+        );
+        self.preprocessed_width = width;
+        self
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -110,11 +152,24 @@ impl TraceInfo {
         &self.layout
     }
 
-    /// Returns the total number of columns in an execution trace.
+    /// Returns the total number of columns in an execution trace, including the columns of the
+    /// preprocessed segment (if any).
     ///
     /// This is guaranteed to be between 1 and 255.
     pub fn width(&self) -> usize {
-        self.layout.main_trace_width() + self.layout().aux_trace_width()
+        self.main_frame_width() + self.layout().aux_trace_width()
+    }
+
+    /// Returns the number of columns in the preprocessed trace segment, or zero if the trace does
+    /// not have a preprocessed segment.
+    pub fn preprocessed_width(&self) -> usize {
+        self.preprocessed_width
+    }
+
+    /// Returns the number of columns in the main evaluation frame, i.e., the number of columns in
+    /// the main trace segment followed by the columns of the preprocessed segment (if any).
+    pub fn main_frame_width(&self) -> usize {
+        self.layout.main_trace_width() + self.preprocessed_width
     }
 
     /// Returns execution trace length.
@@ -135,6 +190,61 @@ impl TraceInfo {
     }
 }
 
+impl Serializable for TraceInfo {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.layout.write_into(target);
+        // the trace length is a power of two, and thus, it is stored as its log2
+        target.write_u8(self.length.trailing_zeros() as u8);
+        target.write_u8(self.preprocessed_width as u8);
+        target.write_u16(self.meta.len() as u16);
+        target.write_u8_slice(&self.meta);
+    }
+}
+
+impl Deserializable for TraceInfo {
+    /// Reads [TraceInfo] from the specified `source` and returns the result.
+    ///
+    /// # Errors
+    /// Returns an error if a valid [TraceInfo] struct could not be read from the specified
+    /// `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let layout = TraceLayout::read_from(source)?;
+
+        // read and validate trace length
+        let log_length = source.read_u8()? as u32;
+        if log_length < Self::MIN_TRACE_LENGTH.trailing_zeros() || log_length >= usize::BITS {
+            return Err(DeserializationError::InvalidValue(format!(
+                "trace length must be between 2^{} and 2^{}, but was 2^{}",
+                Self::MIN_TRACE_LENGTH.trailing_zeros(),
+                usize::BITS - 1,
+                log_length
+            )));
+        }
+
+        // read and validate the width of the preprocessed segment
+        let preprocessed_width = source.read_u8()? as usize;
+        let full_width = layout.main_trace_width() + layout.aux_trace_width() + preprocessed_width;
+        if full_width > Self::MAX_TRACE_WIDTH {
+            return Err(DeserializationError::InvalidValue(format!(
+                "total number of columns in the trace cannot be greater than {}, but was {}",
+                Self::MAX_TRACE_WIDTH,
+                full_width
+            )));
+        }
+
+        let num_meta_bytes = source.read_u16()? as usize;
+        let meta = source.read_u8_vec(num_meta_bytes)?;
+
+        Ok(TraceInfo {
+            layout,
+            length: 1 << log_length,
+            meta,
+            preprocessed_width,
+        })
+    }
+}
+
 // TRACE LAYOUT
 // ================================================================================================
 
@@ -153,6 +263,8 @@ impl TraceInfo {
 /// The number of random elements may be different from the number of columns in a given auxiliary
 /// segment. For example, an auxiliary segment may contain just one column, but may require many
 /// random elements.
+///
+/// A layout is built using [TraceLayoutBuilder].
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TraceLayout {
     main_segment_width: usize,
@@ -166,6 +278,10 @@ impl TraceLayout {
     // --------------------------------------------------------------------------------------------
     /// Returns a new [TraceLayout] instantiated with the provided info.
     ///
+    /// Widths of auxiliary segments and numbers of random elements they require are passed as
+    /// arrays of the same type, and thus, transposing them goes unnoticed by the compiler;
+    /// [TraceLayoutBuilder] should be used instead.
+    ///
     /// # Panics
     /// Panics if:
     /// * Width of the main trace segment is set to zero.
@@ -174,6 +290,7 @@ impl TraceLayout {
     /// * Number of random elements for an auxiliary trace segment of non-zero width is set to zero.
     /// * Number of random elements for an auxiliary trace segment of zero width is set to non-zero.
     /// * Number of random elements for any auxiliary trace segment is greater than 255.
+    #[deprecated(note = "use TraceLayoutBuilder instead")]
     pub fn new(
         main_width: usize,
         aux_widths: [usize; NUM_AUX_SEGMENTS],
@@ -350,6 +467,152 @@ impl Deserializable for TraceLayout {
             }
         }
 
-        Ok(TraceLayout::new(main_width, aux_widths, aux_rands))
+        let mut builder = TraceLayoutBuilder::new().main_width(main_width);
+        for (&width, &num_rand_elements) in aux_widths.iter().zip(aux_rands.iter()) {
+            if width != 0 {
+                builder = builder.aux_segment(width, num_rand_elements);
+            }
+        }
+        builder
+            .build()
+            .map_err(|err| DeserializationError::InvalidValue(err.to_string()))
+    }
+}
+
+// TRACE LAYOUT BUILDER
+// ================================================================================================
+
+/// A builder of [TraceLayout] which describes every trace segment via a named setter.
+///
+/// All properties of the layout are validated together when [TraceLayoutBuilder::build()] is
+/// called, and a [TraceLayoutError] is returned if the described layout is invalid. For example,
+/// a layout of a trace with 2 main columns and an auxiliary segment consisting of 3 columns which
+/// are built using 4 random elements is described as follows:
+///
+/// ```
+/// # use winter_air::TraceLayoutBuilder;
+/// let layout = TraceLayoutBuilder::new()
+///     .main_width(2)
+///     .aux_segment(3, 4)
+///     .build()
+///     .unwrap();
+/// assert_eq!(3, layout.aux_trace_width());
+/// assert_eq!(4, layout.get_aux_segment_rand_elements(0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TraceLayoutBuilder {
+    main_width: usize,
+    aux_segments: Vec<(usize, usize)>,
+    num_aux_segments: Option<usize>,
+}
+
+impl TraceLayoutBuilder {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new builder of a layout without any columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // SETTERS
+    // --------------------------------------------------------------------------------------------
+
+    /// Sets the number of columns in the main trace segment.
+    pub fn main_width(mut self, width: usize) -> Self {
+        self.main_width = width;
+        self
+    }
+
+    /// Appends an auxiliary trace segment consisting of `width` columns which are built using
+    /// `num_rand_elements` random elements.
+    pub fn aux_segment(mut self, width: usize, num_rand_elements: usize) -> Self {
+        self.aux_segments.push((width, num_rand_elements));
+        self
+    }
+
+    /// Declares the number of auxiliary trace segments in the layout.
+    ///
+    /// When declared, the number is checked against the number of segments appended via
+    /// [TraceLayoutBuilder::aux_segment()]; this guards against a segment being added or dropped
+    /// by mistake.
+    pub fn num_aux_segments(mut self, num_aux_segments: usize) -> Self {
+        self.num_aux_segments = Some(num_aux_segments);
+        self
+    }
+
+    // BUILDER
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the [TraceLayout] described by this builder.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * Width of the main trace segment was not set or was set to zero.
+    /// * Number of auxiliary segments was declared and is different from the number of appended
+    ///   segments.
+    /// * More than one auxiliary segment was appended.
+    /// * An auxiliary segment has zero columns, or requires zero random elements.
+    /// * An auxiliary segment has zero columns but requires random elements.
+    /// * An auxiliary segment requires more than 255 random elements.
+    /// * Total number of columns in all segments is greater than 255.
+    pub fn build(self) -> Result<TraceLayout, TraceLayoutError> {
+        if self.main_width == 0 {
+            return Err(TraceLayoutError::EmptyMainSegment);
+        }
+        let num_aux_segments = self.aux_segments.len();
+        if let Some(expected) = self.num_aux_segments {
+            if expected != num_aux_segments {
+                return Err(TraceLayoutError::AuxSegmentCountMismatch(
+                    expected,
+                    num_aux_segments,
+                ));
+            }
+        }
+        if num_aux_segments > NUM_AUX_SEGMENTS {
+            return Err(TraceLayoutError::TooManyAuxSegments(
+                NUM_AUX_SEGMENTS,
+                num_aux_segments,
+            ));
+        }
+
+        let mut aux_segment_widths = [0; NUM_AUX_SEGMENTS];
+        let mut aux_segment_rands = [0; NUM_AUX_SEGMENTS];
+        for (i, &(width, num_rand_elements)) in self.aux_segments.iter().enumerate() {
+            match (width, num_rand_elements) {
+                (0, 0) => return Err(TraceLayoutError::EmptyAuxSegment(i)),
+                (0, _) => {
+                    return Err(TraceLayoutError::RandElementsForEmptyAuxSegment(
+                        i,
+                        num_rand_elements,
+                    ))
+                }
+                (_, 0) => return Err(TraceLayoutError::NoRandElementsForAuxSegment(i)),
+                _ => (),
+            }
+            if num_rand_elements > TraceInfo::MAX_RAND_SEGMENT_ELEMENTS {
+                return Err(TraceLayoutError::TooManyRandElements(
+                    i,
+                    TraceInfo::MAX_RAND_SEGMENT_ELEMENTS,
+                    num_rand_elements,
+                ));
+            }
+            aux_segment_widths[i] = width;
+            aux_segment_rands[i] = num_rand_elements;
+        }
+
+        let full_width = self.main_width + aux_segment_widths.iter().sum::<usize>();
+        if full_width > TraceInfo::MAX_TRACE_WIDTH {
+            return Err(TraceLayoutError::TooManyColumns(
+                TraceInfo::MAX_TRACE_WIDTH,
+                full_width,
+            ));
+        }
+
+        Ok(TraceLayout {
+            main_segment_width: self.main_width,
+            aux_segment_widths,
+            aux_segment_rands,
+            num_aux_segments,
+        })
     }
 }
@@ -6,6 +6,23 @@
 use super::{super::super::ProofOptions, Vec, MIN_CYCLE_LENGTH};
 use core::cmp;
 
+// TRANSITION DIVISOR KIND
+// ================================================================================================
+/// Describes the set of steps of an execution trace on which a transition constraint must hold.
+///
+/// The kind of a divisor is declared together with the degree of a constraint via
+/// [TransitionConstraintDegree::with_divisor()]; by default, all transition constraints use
+/// [TransitionDivisorKind::Transition] divisor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TransitionDivisorKind {
+    /// The constraint must hold on all steps of the execution trace except for the last $k$
+    /// steps, where $k$ is the number of transition exemptions specified for the computation.
+    Transition,
+    /// The constraint must hold on all steps of the execution trace, including the last step at
+    /// which the next row of the evaluation frame wraps around to the first row of the trace.
+    Cyclic,
+}
+
 // TRANSITION CONSTRAINT DEGREE
 // ================================================================================================
 /// Degree descriptor of a transition constraint.
@@ -19,6 +36,7 @@ use core::cmp;
 pub struct TransitionConstraintDegree {
     base: usize,
     cycles: Vec<usize>,
+    divisor: TransitionDivisorKind,
 }
 
 impl TransitionConstraintDegree {
@@ -39,6 +57,7 @@ impl TransitionConstraintDegree {
         TransitionConstraintDegree {
             base: degree,
             cycles: vec![],
+            divisor: TransitionDivisorKind::Transition,
         }
     }
 
@@ -71,9 +90,36 @@ impl TransitionConstraintDegree {
         TransitionConstraintDegree {
             base: base_degree,
             cycles,
+            divisor: TransitionDivisorKind::Transition,
         }
     }
 
+    /// Returns this degree descriptor with the kind of the constraint divisor set to the
+    /// specified value.
+    ///
+    /// For example, a constraint which must hold on all steps of the execution trace (including
+    /// the transition from the last step back to the first one) can be described as:
+    /// `TransitionConstraintDegree::new(1).with_divisor(TransitionDivisorKind::Cyclic)`.
+    pub fn with_divisor(mut self, divisor: TransitionDivisorKind) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    /// Returns the degree of this constraint with respect to trace columns.
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Returns cycle lengths of periodic columns involved in this constraint.
+    pub fn cycles(&self) -> &[usize] {
+        &self.cycles
+    }
+
+    /// Returns the kind of the divisor of this constraint.
+    pub fn divisor(&self) -> TransitionDivisorKind {
+        self.divisor
+    }
+
     /// Computes a degree to which this degree description expands in the context of execution
     /// trace of the specified length.
     ///
@@ -124,3 +170,47 @@ impl TransitionConstraintDegree {
         )
     }
 }
+
+// CONSTRAINT DESCRIPTOR
+// ================================================================================================
+/// Descriptor of a transition constraint consisting of a stable identifier of the constraint and
+/// its degree.
+///
+/// Identifiers allow composition coefficients to be derived for each constraint independently of
+/// the position of the constraint in the list of constraints (see
+/// [AirContext::set_keyed_coefficients()](crate::AirContext::set_keyed_coefficients)). An
+/// identifier can be specified either as a string or as any type (e.g., an enum) which can be
+/// converted into a static string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintDescriptor {
+    id: &'static str,
+    degree: TransitionConstraintDegree,
+}
+
+impl ConstraintDescriptor {
+    /// Creates a new descriptor for a constraint with the specified identifier and degree.
+    ///
+    /// # Panics
+    /// Panics if the identifier is an empty string.
+    pub fn new<I: Into<&'static str>>(id: I, degree: TransitionConstraintDegree) -> Self {
+        let id = id.into();
+        assert!(!id.is_empty(), "constraint identifier must not be empty");
+        ConstraintDescriptor { id, degree }
+    }
+
+    /// Returns the identifier of this constraint.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// Returns the degree descriptor of this constraint.
+    pub fn degree(&self) -> &TransitionConstraintDegree {
+        &self.degree
+    }
+}
+
+impl From<ConstraintDescriptor> for TransitionConstraintDegree {
+    fn from(descriptor: ConstraintDescriptor) -> Self {
+        descriptor.degree
+    }
+}
@@ -5,17 +5,35 @@
 
 use super::{FieldElement, Vec};
 
+// CONSTANTS
+// ================================================================================================
+
+/// Row offsets of a default evaluation frame: the current row and the next row.
+pub const DEFAULT_FRAME_OFFSETS: [usize; 2] = [0, 1];
+
 // EVALUATION FRAME
 // ================================================================================================
 /// A set of execution trace rows required for evaluation of transition constraints.
 ///
-/// In the current implementation, an evaluation frame always contains two consecutive rows of the
-/// execution trace. It is passed in as one of the parameters into
+/// By default, an evaluation frame contains two consecutive rows of the execution trace: the
+/// current row and the next row. An AIR can declare a wider frame via
+/// [AirContext::set_frame_offsets()](crate::AirContext::set_frame_offsets); such a frame contains
+/// a row for each of the declared offsets, and thus, for the current row at step `i`, the frame
+/// row at position `j` holds the trace row at step `i + offsets[j]`. The first offset of a frame
+/// is always zero. An evaluation frame is passed in as one of the parameters into
 /// [Air::evaluate_transition()](crate::Air::evaluate_transition) function.
+///
+/// Evaluation frames are meant to be allocated once and reused: the prover allocates a frame
+/// before iterating over a fragment of the constraint evaluation domain, and then overwrites its
+/// rows in place at every step. Thus, values in a frame are valid only for the duration of a
+/// single constraint evaluation call, and implementations of [Air](crate::Air) should not assume
+/// anything about the contents of the frame beyond the rows at the declared offsets. Custom
+/// evaluators can follow the same pattern by updating a frame via [EvaluationFrame::set_rows()]
+/// or [EvaluationFrame::row_mut()].
 #[derive(Debug, Clone)]
 pub struct EvaluationFrame<E: FieldElement> {
-    current: Vec<E>,
-    next: Vec<E>,
+    offsets: Vec<usize>,
+    rows: Vec<Vec<E>>,
 }
 
 impl<E: FieldElement> EvaluationFrame<E> {
@@ -24,16 +42,34 @@ impl<E: FieldElement> EvaluationFrame<E> {
 
     /// Returns a new evaluation frame instantiated with the specified number of columns.
     ///
+    /// The frame contains two rows: the current row and the next row.
+    ///
     /// # Panics
     /// Panics if `num_columns` is zero.
     pub fn new(num_columns: usize) -> Self {
+        Self::with_offsets(num_columns, &DEFAULT_FRAME_OFFSETS)
+    }
+
+    /// Returns a new evaluation frame instantiated with the specified number of columns and a
+    /// row for each of the specified row offsets.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `num_columns` is zero.
+    /// * `offsets` contains fewer than two offsets, does not start with zero, or is not sorted in
+    ///   strictly ascending order.
+    pub fn with_offsets(num_columns: usize, offsets: &[usize]) -> Self {
         assert!(
             num_columns > 0,
             "number of columns must be greater than zero"
         );
+        validate_offsets(offsets);
         EvaluationFrame {
-            current: E::zeroed_vector(num_columns),
-            next: E::zeroed_vector(num_columns),
+            offsets: offsets.to_vec(),
+            rows: offsets
+                .iter()
+                .map(|_| E::zeroed_vector(num_columns))
+                .collect(),
         }
     }
 
@@ -44,39 +80,165 @@ impl<E: FieldElement> EvaluationFrame<E> {
     /// * Lengths of the provided rows are zero.
     /// * Lengths of the provided rows are not the same.
     pub fn from_rows(current: Vec<E>, next: Vec<E>) -> Self {
-        assert!(!current.is_empty(), "a row must contain at least one value");
+        Self::from_offset_rows(&DEFAULT_FRAME_OFFSETS, vec![current, next])
+    }
+
+    /// Returns a new evaluation frame instantiated from the provided rows, one row for each of the
+    /// specified row offsets.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `offsets` contains fewer than two offsets, does not start with zero, or is not sorted in
+    ///   strictly ascending order.
+    /// * The number of rows is not the same as the number of offsets.
+    /// * Lengths of the provided rows are zero.
+    /// * Lengths of the provided rows are not the same.
+    pub fn from_offset_rows(offsets: &[usize], rows: Vec<Vec<E>>) -> Self {
+        validate_offsets(offsets);
         assert_eq!(
+            offsets.len(),
+            rows.len(),
+            "number of rows must be the same as the number of row offsets"
+        );
+        assert!(!rows[0].is_empty(), "a row must contain at least one value");
+        for row in rows.iter().skip(1) {
+            assert_eq!(
+                rows[0].len(),
+                row.len(),
+                "number of values in the rows must be the same"
+            );
+        }
+        Self {
+            offsets: offsets.to_vec(),
+            rows,
+        }
+    }
+
+    /// Returns a new evaluation frame instantiated by copying the provided rows.
+    ///
+    /// This allocates memory for the rows of the frame; to avoid allocations when frames are
+    /// built in a loop, a single frame should be updated via [EvaluationFrame::set_rows()]
+    /// instead.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Lengths of the provided rows are zero.
+    /// * Lengths of the provided rows are not the same.
+    pub fn from_rows_ref(current: &[E], next: &[E]) -> Self {
+        Self::from_rows(current.to_vec(), next.to_vec())
+    }
+
+    // ROW MUTATORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Overwrites the rows of this frame with the provided rows without allocating any memory.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * This frame contains more than two rows; rows of such frames should be updated via
+    ///   [EvaluationFrame::row_mut()].
+    /// * Lengths of the provided rows are not the same as the width of this frame.
+    pub fn set_rows(&mut self, current: &[E], next: &[E]) {
+        assert_eq!(
+            self.rows.len(),
+            2,
+            "rows of a frame with more than two rows cannot be set together"
+        );
+        assert_eq!(
+            self.rows[0].len(),
             current.len(),
+            "number of values in the current row must be the same as the width of the frame"
+        );
+        assert_eq!(
+            self.rows[1].len(),
             next.len(),
-            "number of values in the rows must be the same"
+            "number of values in the next row must be the same as the width of the frame"
         );
-        Self { current, next }
+        self.rows[0].copy_from_slice(current);
+        self.rows[1].copy_from_slice(next);
     }
 
     // ROW ACCESSORS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns offsets of the rows in this frame relative to the current row.
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// Returns the number of rows in this frame.
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns a reference to the row at the specified position in this frame; this row is
+    /// located `offsets()[idx]` steps after the current row.
+    ///
+    /// # Panics
+    /// Panics if `idx` is greater than or equal to the number of rows in this frame.
+    #[inline(always)]
+    pub fn row(&self, idx: usize) -> &[E] {
+        &self.rows[idx]
+    }
+
+    /// Returns a mutable reference to the row at the specified position in this frame.
+    ///
+    /// # Panics
+    /// Panics if `idx` is greater than or equal to the number of rows in this frame.
+    #[inline(always)]
+    pub fn row_mut(&mut self, idx: usize) -> &mut [E] {
+        &mut self.rows[idx]
+    }
+
     /// Returns a reference to the current row.
     #[inline(always)]
     pub fn current(&self) -> &[E] {
-        &self.current
+        &self.rows[0]
     }
 
     /// Returns a mutable reference to the current row.
     #[inline(always)]
     pub fn current_mut(&mut self) -> &mut [E] {
-        &mut self.current
+        &mut self.rows[0]
     }
 
-    /// Returns a reference to the next row.
+    /// Returns a reference to the next row, i.e., the second row of this frame.
+    ///
+    /// For frames with default offsets, this is the row which immediately follows the current
+    /// row.
     #[inline(always)]
     pub fn next(&self) -> &[E] {
-        &self.next
+        &self.rows[1]
     }
 
-    /// Returns a mutable reference to the next row.
+    /// Returns a mutable reference to the next row, i.e., the second row of this frame.
     #[inline(always)]
     pub fn next_mut(&mut self) -> &mut [E] {
-        &mut self.next
+        &mut self.rows[1]
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure the specified row offsets describe a valid evaluation frame.
+///
+/// # Panics
+/// Panics if `offsets` contains fewer than two offsets, does not start with zero, or is not
+/// sorted in strictly ascending order.
+pub(crate) fn validate_offsets(offsets: &[usize]) {
+    assert!(
+        offsets.len() >= 2,
+        "an evaluation frame must contain at least two rows"
+    );
+    assert_eq!(
+        offsets[0], 0,
+        "the first row offset of an evaluation frame must be zero"
+    );
+    for pair in offsets.windows(2) {
+        assert!(
+            pair[0] < pair[1],
+            "row offsets of an evaluation frame must be sorted in strictly ascending order"
+        );
     }
 }
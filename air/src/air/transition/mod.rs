@@ -3,13 +3,14 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{AirContext, BTreeMap, ConstraintDivisor, ExtensionOf, FieldElement, Vec};
+use super::{AirContext, BTreeMap, ConstraintDivisor, ExtensionOf, FieldElement, PowerTable, Vec};
 
 mod frame;
 pub use frame::EvaluationFrame;
+pub(crate) use frame::{validate_offsets as validate_frame_offsets, DEFAULT_FRAME_OFFSETS};
 
 mod degree;
-pub use degree::TransitionConstraintDegree;
+pub use degree::{ConstraintDescriptor, TransitionConstraintDegree, TransitionDivisorKind};
 
 // CONSTANTS
 // ================================================================================================
@@ -23,15 +24,19 @@ const MIN_CYCLE_LENGTH: usize = 2;
 /// This metadata includes:
 /// - List of transition constraint degrees for the main trace segment, as well as for auxiliary
 ///   trace segments (if any).
-/// - Groupings of constraints by their degree, separately for the main trace segment and for
-///   auxiliary tace segment.
-/// - Divisor of transition constraints for a computation.
+/// - Groupings of constraints by their degree and divisor kind, separately for the main trace
+///   segment and for auxiliary tace segment.
+/// - Divisors of transition and cyclic transition constraints for a computation; transition
+///   constraints with different numbers of exemptions have different divisors.
 pub struct TransitionConstraints<E: FieldElement> {
     main_constraints: Vec<TransitionConstraintGroup<E>>,
     main_constraint_degrees: Vec<TransitionConstraintDegree>,
     aux_constraints: Vec<TransitionConstraintGroup<E>>,
     aux_constraint_degrees: Vec<TransitionConstraintDegree>,
-    divisor: ConstraintDivisor<E::BaseField>,
+    divisors: Vec<ConstraintDivisor<E::BaseField>>,
+    divisor_indexes: Vec<usize>,
+    cyclic_divisor: ConstraintDivisor<E::BaseField>,
+    has_cyclic_constraints: bool,
 }
 
 impl<E: FieldElement> TransitionConstraints<E> {
@@ -44,37 +49,100 @@ impl<E: FieldElement> TransitionConstraints<E> {
     /// Panics if the number of transition constraints in the context does not match the number of
     /// provided composition coefficients.
     pub fn new(context: &AirContext<E::BaseField>, composition_coefficients: &[(E, E)]) -> Self {
+        let powers = PowerTable::new(context.trace_len(), []);
+        Self::with_powers(context, composition_coefficients, &powers)
+    }
+
+    /// Returns a new instance of [TransitionConstraints] for a computation described by the
+    /// specified AIR context; exemption points of transition divisors are read from the provided
+    /// table of powers of the trace domain generator.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * The number of transition constraints in the context does not match the number of
+    ///   provided composition coefficients.
+    /// * The domain of the power table is not the trace domain of the computation.
+    pub fn with_powers(
+        context: &AirContext<E::BaseField>,
+        composition_coefficients: &[(E, E)],
+        powers: &PowerTable<E::BaseField>,
+    ) -> Self {
+        assert_eq!(
+            context.trace_len(),
+            powers.domain_size(),
+            "power table must be built for the trace domain"
+        );
         assert_eq!(
             context.num_transition_constraints(),
             composition_coefficients.len(),
             "number of transition constraints must match the number of composition coefficient tuples"
         );
 
-        // build constraint divisor; the same divisor applies to all transition constraints
-        let divisor = ConstraintDivisor::from_transition(
-            context.trace_len(),
-            context.num_transition_exemptions(),
-        );
+        let main_constraint_degrees = context.main_transition_constraint_degrees.clone();
+        let aux_constraint_degrees = context.aux_transition_constraint_degrees.clone();
+        let all_degrees = main_constraint_degrees
+            .iter()
+            .chain(aux_constraint_degrees.iter());
+
+        // build constraint divisors; there is one divisor per distinct number of exemptions of
+        // transition constraints (in ascending order), and one more divisor for all cyclic
+        // constraints, which is placed after transition divisors
+        let mut num_exemptions = all_degrees
+            .clone()
+            .zip(context.transition_exemptions())
+            .filter(|(degree, _)| degree.divisor() == TransitionDivisorKind::Transition)
+            .map(|(_, &n)| n)
+            .collect::<Vec<_>>();
+        if num_exemptions.is_empty() {
+            num_exemptions.push(context.num_transition_exemptions());
+        }
+        num_exemptions.sort_unstable();
+        num_exemptions.dedup();
+        let mut divisors = num_exemptions
+            .iter()
+            .map(|&n| ConstraintDivisor::from_transition_with_powers(n, powers))
+            .collect::<Vec<_>>();
+        let cyclic_divisor = ConstraintDivisor::from_cyclic(context.trace_len());
+
+        let has_cyclic_constraints = all_degrees
+            .clone()
+            .any(|degree| degree.divisor() == TransitionDivisorKind::Cyclic);
+        if has_cyclic_constraints {
+            divisors.push(cyclic_divisor.clone());
+        }
 
-        // group constraints by their degree, separately for constraints against main and auxiliary
-        // trace segments
+        // map every constraint to the index of its divisor
+        let divisor_indexes = all_degrees
+            .zip(context.transition_exemptions())
+            .map(|(degree, n)| match degree.divisor() {
+                TransitionDivisorKind::Transition => num_exemptions
+                    .binary_search(n)
+                    .expect("missing transition divisor"),
+                TransitionDivisorKind::Cyclic => num_exemptions.len(),
+            })
+            .collect::<Vec<_>>();
+
+        // group constraints by their degree and divisor, separately for constraints against
+        // main and auxiliary trace segments
 
         let (main_constraint_coefficients, aux_constraint_coefficients) =
-            composition_coefficients.split_at(context.main_transition_constraint_degrees.len());
+            composition_coefficients.split_at(main_constraint_degrees.len());
+        let (main_divisor_indexes, aux_divisor_indexes) =
+            divisor_indexes.split_at(main_constraint_degrees.len());
 
-        let main_constraint_degrees = context.main_transition_constraint_degrees.clone();
         let main_constraints = group_constraints(
             &main_constraint_degrees,
+            main_divisor_indexes,
             context,
             main_constraint_coefficients,
-            divisor.degree(),
+            &divisors,
         );
-        let aux_constraint_degrees = context.aux_transition_constraint_degrees.clone();
         let aux_constraints = group_constraints(
             &aux_constraint_degrees,
+            aux_divisor_indexes,
             context,
             aux_constraint_coefficients,
-            divisor.degree(),
+            &divisors,
         );
 
         Self {
@@ -82,7 +150,10 @@ impl<E: FieldElement> TransitionConstraints<E> {
             main_constraint_degrees,
             aux_constraints,
             aux_constraint_degrees,
-            divisor,
+            divisors,
+            divisor_indexes,
+            cyclic_divisor,
+            has_cyclic_constraints,
         }
     }
 
@@ -133,24 +204,84 @@ impl<E: FieldElement> TransitionConstraints<E> {
 
     /// Returns a divisor for transition constraints.
     ///
-    /// All transition constraints have the same divisor which has the form:
+    /// Transition constraints (except for cyclic ones) with $k$ exemptions have the same divisor
+    /// which has the form:
     /// $$
-    /// z(x) = \frac{x^n - 1}{x - g^{n - 1}}
+    /// z(x) = \frac{x^n - 1}{\prod_{i=1}^{k}(x - g^{n - i})}
     /// $$
     /// where: $n$ is the length of the execution trace and $g$ is the generator of the trace
     /// domain.
     ///
     /// This divisor specifies that transition constraints must hold on all steps of the
-    /// execution trace except for the last one.
+    /// execution trace except for the last $k$ steps. When transition constraints have different
+    /// numbers of exemptions, this returns the divisor with the fewest exemptions; divisors of
+    /// individual constraints can be retrieved via [get_constraint_divisor()](Self::get_constraint_divisor).
     pub fn divisor(&self) -> &ConstraintDivisor<E::BaseField> {
-        &self.divisor
+        &self.divisors[0]
+    }
+
+    /// Returns a divisor for cyclic transition constraints.
+    ///
+    /// All cyclic transition constraints have the same divisor $z(x) = x^n - 1$, where $n$ is the
+    /// length of the execution trace. This divisor specifies that cyclic constraints must hold on
+    /// all steps of the execution trace.
+    pub fn cyclic_divisor(&self) -> &ConstraintDivisor<E::BaseField> {
+        &self.cyclic_divisor
+    }
+
+    /// Returns the divisor of the specified kind.
+    ///
+    /// For [TransitionDivisorKind::Transition], this is the same as [divisor()](Self::divisor).
+    pub fn get_divisor(&self, kind: TransitionDivisorKind) -> &ConstraintDivisor<E::BaseField> {
+        match kind {
+            TransitionDivisorKind::Transition => &self.divisors[0],
+            TransitionDivisorKind::Cyclic => &self.cyclic_divisor,
+        }
+    }
+
+    /// Returns the divisor of the transition constraint at the specified index; constraints
+    /// placed against the main trace segment are indexed first, followed by constraints placed
+    /// against auxiliary trace segments.
+    ///
+    /// # Panics
+    /// Panics if the index is greater than or equal to the number of transition constraints.
+    pub fn get_constraint_divisor(&self, index: usize) -> &ConstraintDivisor<E::BaseField> {
+        &self.divisors[self.divisor_indexes[index]]
+    }
+
+    /// Returns indexes of divisors of all transition constraints in the list returned by
+    /// [get_divisors()](Self::get_divisors); constraints placed against the main trace segment
+    /// are listed first, followed by constraints placed against auxiliary trace segments.
+    pub fn divisor_indexes(&self) -> &[usize] {
+        &self.divisor_indexes
+    }
+
+    /// Returns true if at least one of the transition constraints is cyclic.
+    pub fn has_cyclic_constraints(&self) -> bool {
+        self.has_cyclic_constraints
+    }
+
+    /// Returns the number of distinct divisors used by transition constraints of a computation;
+    /// this is the number of distinct exemption counts of non-cyclic constraints, plus 1 if at
+    /// least one of the constraints is cyclic.
+    pub fn num_divisors(&self) -> usize {
+        self.divisors.len()
+    }
+
+    /// Returns a list of distinct divisors used by transition constraints of a computation.
+    ///
+    /// Divisors of transition constraints are listed first, ordered by the number of exemptions
+    /// in ascending order; the divisor of cyclic constraints follows them only if at least one of
+    /// the constraints is cyclic.
+    pub fn get_divisors(&self) -> Vec<ConstraintDivisor<E::BaseField>> {
+        self.divisors.clone()
     }
 
     // CONSTRAINT COMPOSITION
     // --------------------------------------------------------------------------------------------
 
     /// Computes a linear combination of all transition constraint evaluations and divides the
-    /// result by transition constraint divisor.
+    /// result by transition constraint divisors.
     ///
     /// A transition constraint is described by a rational function of the form $\frac{C(x)}{z(x)}$,
     /// where:
@@ -160,38 +291,48 @@ impl<E: FieldElement> TransitionConstraints<E> {
     /// Thus, this function computes a linear combination of $C(x)$ evaluations. For more detail on
     /// how this linear combination is computed refer to [TransitionConstraintGroup::merge_evaluations].
     ///
-    /// Since, the divisor polynomial is the same for all transition constraints (see
-    /// [ConstraintDivisor::from_transition]), we can divide the linear combination by the
-    /// divisor rather than dividing each individual $C(x)$ evaluation. This requires executing only
-    /// one division at the end.
+    /// Since, the divisor polynomial is the same for all transition constraints of the same kind
+    /// and with the same number of exemptions (see [ConstraintDivisor::from_transition] and
+    /// [ConstraintDivisor::from_cyclic]), we can divide the linear combination by the divisor
+    /// rather than dividing each individual $C(x)$ evaluation. This requires executing only one
+    /// division at the end for each distinct divisor.
     pub fn combine_evaluations<F>(&self, main_evaluations: &[F], aux_evaluations: &[E], x: F) -> E
     where
         F: FieldElement<BaseField = E::BaseField>,
         E: ExtensionOf<F>,
     {
-        // merge constraint evaluations for the main trace segment
-        let mut result = self.main_constraints().iter().fold(E::ZERO, |acc, group| {
-            let xp = x.exp(group.degree_adjustment.into());
-            acc + group.merge_evaluations::<F, F>(main_evaluations, xp)
-        });
-
-        // merge constraint evaluations for auxiliary trace segments (if any)
-        if self.num_aux_constraints() > 0 {
-            result += self.aux_constraints().iter().fold(E::ZERO, |acc, group| {
-                let xp = x.exp(group.degree_adjustment.into());
-                acc + group.merge_evaluations::<F, E>(aux_evaluations, xp)
-            });
+        // merge constraint evaluations separately for each divisor, and divide out the evaluation
+        // of the divisor at x; the number of divisors is small, so iterating over all groups for
+        // each divisor is cheaper than allocating a buffer for intermediate results
+        let mut result = E::ZERO;
+        for (divisor_idx, divisor) in self.divisors.iter().enumerate() {
+            let mut evaluation = E::ZERO;
+
+            // merge constraint evaluations for the main trace segment
+            for group in self.main_constraints().iter() {
+                if group.divisor_idx() == divisor_idx {
+                    let xp = x.exp(group.degree_adjustment.into());
+                    evaluation += group.merge_evaluations::<F, F>(main_evaluations, xp);
+                }
+            }
+
+            // merge constraint evaluations for auxiliary trace segments (if any)
+            for group in self.aux_constraints().iter() {
+                if group.divisor_idx() == divisor_idx {
+                    let xp = x.exp(group.degree_adjustment.into());
+                    evaluation += group.merge_evaluations::<F, E>(aux_evaluations, xp);
+                }
+            }
+
+            result += evaluation / E::from(divisor.evaluate_at(x));
         }
-
-        // divide out the evaluation of divisor at x and return the result
-        let z = E::from(self.divisor.evaluate_at(x));
-        result / z
+        result
     }
 }
 
 // TRANSITION CONSTRAINT GROUP
 // ================================================================================================
-/// A group of transition constraints all having the same degree.
+/// A group of transition constraints all having the same degree and the same divisor.
 ///
 /// A transition constraint group does not actually store transition constraints - it stores only
 /// their indexes and the info needed to compute their random linear combination. The indexes are
@@ -201,6 +342,7 @@ impl<E: FieldElement> TransitionConstraints<E> {
 #[derive(Clone, Debug)]
 pub struct TransitionConstraintGroup<E: FieldElement> {
     degree: TransitionConstraintDegree,
+    divisor_idx: usize,
     degree_adjustment: u64,
     domain_offset_exp: E::BaseField,
     indexes: Vec<usize>,
@@ -210,9 +352,12 @@ pub struct TransitionConstraintGroup<E: FieldElement> {
 impl<E: FieldElement> TransitionConstraintGroup<E> {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
-    /// Returns a new transition constraint group to hold constraints of the specified degree.
+    /// Returns a new transition constraint group to hold constraints of the specified degree;
+    /// `divisor_degree` must be the degree of the divisor at `divisor_idx` in the list returned by
+    /// [TransitionConstraints::get_divisors()].
     pub(super) fn new(
         degree: TransitionConstraintDegree,
+        divisor_idx: usize,
         trace_length: usize,
         composition_degree: usize,
         divisor_degree: usize,
@@ -230,6 +375,7 @@ impl<E: FieldElement> TransitionConstraintGroup<E> {
 
         TransitionConstraintGroup {
             degree,
+            divisor_idx,
             degree_adjustment,
             domain_offset_exp,
             indexes: vec![],
@@ -250,6 +396,17 @@ impl<E: FieldElement> TransitionConstraintGroup<E> {
         &self.degree
     }
 
+    /// Returns the kind of the divisor shared by all constraints in this group.
+    pub fn divisor_kind(&self) -> TransitionDivisorKind {
+        self.degree.divisor()
+    }
+
+    /// Returns the index of the divisor of this group in the list returned by
+    /// [TransitionConstraints::get_divisors()].
+    pub fn divisor_idx(&self) -> usize {
+        self.divisor_idx
+    }
+
     /// Returns degree adjustment factor for this constraint group.
     pub fn degree_adjustment(&self) -> u64 {
         self.degree_adjustment
@@ -314,24 +471,28 @@ impl<E: FieldElement> TransitionConstraintGroup<E> {
 // HELPER FUNCTIONS
 // ================================================================================================
 
-/// Groups transition constraints by their degree.
+/// Groups transition constraints by their divisor and degree; `divisor_indexes` specifies the
+/// index of the divisor in `divisors` for each of the constraints.
 fn group_constraints<E: FieldElement>(
     degrees: &[TransitionConstraintDegree],
+    divisor_indexes: &[usize],
     context: &AirContext<E::BaseField>,
     coefficients: &[(E, E)],
-    divisor_degree: usize,
+    divisors: &[ConstraintDivisor<E::BaseField>],
 ) -> Vec<TransitionConstraintGroup<E>> {
     // iterate over transition constraint degrees, and assign each constraint to the appropriate
-    // group based on its degree
+    // group based on its divisor and degree
     let mut groups = BTreeMap::new();
-    for (i, degree) in degrees.iter().enumerate() {
+    for (i, (degree, &divisor_idx)) in degrees.iter().zip(divisor_indexes).enumerate() {
         let evaluation_degree = degree.get_evaluation_degree(context.trace_len());
-        let group = groups.entry(evaluation_degree).or_insert_with(|| {
+        let key = (divisor_idx, evaluation_degree);
+        let group = groups.entry(key).or_insert_with(|| {
             TransitionConstraintGroup::new(
                 degree.clone(),
+                divisor_idx,
                 context.trace_len(),
                 context.composition_degree(),
-                divisor_degree,
+                divisors[divisor_idx].degree(),
                 context.options.domain_offset(),
             )
         });
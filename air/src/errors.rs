@@ -4,11 +4,12 @@
 // LICENSE file in the root directory of this source tree.
 
 use core::fmt;
+use utils::string::String;
 
 // ASSERTION ERROR
 // ================================================================================================
 /// Represents an error returned during assertion evaluation.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AssertionError {
     /// This error occurs when an assertion is evaluated against an execution trace which does not
     /// contain a column specified by the assertion.
@@ -22,6 +23,27 @@ pub enum AssertionError {
     /// This error occurs when a `Sequence` assertion is placed against an execution trace with
     /// length which conflicts with the trace length implied by the assertion.
     TraceLengthNotExact(usize, usize),
+    /// This error occurs when an assertion is placed against a step which is outside of the
+    /// execution trace (e.g., when the step is derived from public inputs).
+    StepOutOfRange {
+        column: usize,
+        step: usize,
+        trace_len: usize,
+    },
+    /// This error occurs when a step computed via [AssertionStep](crate::AssertionStep) (e.g.,
+    /// a step derived from public inputs, or a step counted back from the last step) precedes
+    /// the first step or follows the last step of the execution trace.
+    StepOutOfTrace { step: i128, trace_len: usize },
+    /// This error occurs when a window assertion is created with a length which is not a power
+    /// of two, or which is smaller than 2.
+    InvalidWindowLength { column: usize, length: usize },
+    /// This error occurs when a window assertion is created with a first step which is not a
+    /// multiple of the window length.
+    UnalignedWindow {
+        column: usize,
+        first_step: usize,
+        length: usize,
+    },
 }
 
 impl fmt::Display for AssertionError {
@@ -40,6 +62,215 @@ impl fmt::Display for AssertionError {
             Self::TraceLengthNotExact(expected, actual) => {
                 write!(f, "expected trace length to be exactly {expected}, but was {actual}")
             }
+            Self::StepOutOfRange { column, step, trace_len } => {
+                write!(f, "assertion against column {column} is placed at step {step}, but trace length is {trace_len}")
+            }
+            Self::StepOutOfTrace { step, trace_len } => {
+                write!(f, "step {step} is outside of the execution trace of length {trace_len}")
+            }
+            Self::InvalidWindowLength { column, length } => {
+                write!(f, "window assertion against column {column} must cover a power of two of at least 2 steps, but covers {length} steps")
+            }
+            Self::UnalignedWindow { column, first_step, length } => {
+                write!(f, "window assertion against column {column} covers {length} steps starting at step {first_step}, but must start at a multiple of its length; decompose it into aligned windows via Assertion::decompose_window()")
+            }
+        }
+    }
+}
+
+// SELECTOR ERROR
+// ================================================================================================
+/// Represents an error returned during validation of selector flags in an execution trace.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SelectorError {
+    /// This error occurs when a selector flag column contains a value other than ZERO or ONE.
+    /// The values are the step and the column index of the flag.
+    FlagNotBinary(usize, usize),
+    /// This error occurs when none of the selector flags is set at a given step.
+    NoFlagSet(usize),
+    /// This error occurs when more than one selector flag is set at a given step. The values are
+    /// the step and the indexes of the first two flags which are set.
+    MultipleFlagsSet(usize, usize, usize),
+}
+
+impl fmt::Display for SelectorError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FlagNotBinary(step, column) => {
+                write!(f, "selector flag in column {column} is not binary at step {step}")
+            }
+            Self::NoFlagSet(step) => {
+                write!(f, "no selector flag is set at step {step}")
+            }
+            Self::MultipleFlagsSet(step, first, second) => {
+                write!(f, "selector flags {first} and {second} are both set at step {step}")
+            }
+        }
+    }
+}
+
+// TRACE LAYOUT ERROR
+// ================================================================================================
+/// Represents an error returned by [TraceLayoutBuilder](crate::TraceLayoutBuilder) when the
+/// described trace layout is invalid.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TraceLayoutError {
+    /// This error occurs when the width of the main trace segment was not set or was set to zero.
+    EmptyMainSegment,
+    /// This error occurs when the number of auxiliary segments declared via
+    /// `num_aux_segments()` is different from the number of auxiliary segments described. The
+    /// values are the declared and the described numbers of segments.
+    AuxSegmentCountMismatch(usize, usize),
+    /// This error occurs when more auxiliary segments are described than a trace can contain.
+    /// The values are the maximum and the described numbers of segments.
+    TooManyAuxSegments(usize, usize),
+    /// This error occurs when an auxiliary segment has zero columns and requires no random
+    /// elements. The value is the index of the segment.
+    EmptyAuxSegment(usize),
+    /// This error occurs when an auxiliary segment has zero columns but requires random
+    /// elements. The values are the index of the segment and the number of random elements.
+    RandElementsForEmptyAuxSegment(usize, usize),
+    /// This error occurs when an auxiliary segment has columns but requires no random elements.
+    /// The value is the index of the segment.
+    NoRandElementsForAuxSegment(usize),
+    /// This error occurs when an auxiliary segment requires more random elements than allowed.
+    /// The values are the index of the segment, the maximum, and the required numbers of random
+    /// elements.
+    TooManyRandElements(usize, usize, usize),
+    /// This error occurs when the total number of columns in all segments exceeds the maximum
+    /// trace width. The values are the maximum and the total numbers of columns.
+    TooManyColumns(usize, usize),
+}
+
+impl fmt::Display for TraceLayoutError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyMainSegment => {
+                write!(f, "main trace segment must consist of at least one column")
+            }
+            Self::AuxSegmentCountMismatch(expected, actual) => {
+                write!(f, "expected {expected} auxiliary trace segments, but {actual} were described")
+            }
+            Self::TooManyAuxSegments(max, actual) => {
+                write!(f, "number of auxiliary trace segments cannot exceed {max}, but was {actual}")
+            }
+            Self::EmptyAuxSegment(segment) => {
+                write!(f, "auxiliary trace segment {segment} must consist of at least one column")
+            }
+            Self::RandElementsForEmptyAuxSegment(segment, num_rand_elements) => {
+                write!(f, "auxiliary trace segment {segment} has no columns, but requires {num_rand_elements} random elements")
+            }
+            Self::NoRandElementsForAuxSegment(segment) => {
+                write!(f, "number of random elements for auxiliary trace segment {segment} must be greater than zero")
+            }
+            Self::TooManyRandElements(segment, max, actual) => {
+                write!(f, "number of random elements required by auxiliary trace segment {segment} cannot exceed {max}, but was {actual}")
+            }
+            Self::TooManyColumns(max, actual) => {
+                write!(f, "total number of columns in the trace cannot be greater than {max}, but was {actual}")
+            }
+        }
+    }
+}
+
+// SECURITY ERROR
+// ================================================================================================
+/// Represents an error returned by [ProofOptions::with_security()](crate::ProofOptions::with_security)
+/// when proof options cannot achieve the targeted security level.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SecurityError {
+    /// This error occurs when the targeted security exceeds collision resistance of the hash
+    /// function. The values are the targeted security and the collision resistance.
+    InsufficientCollisionResistance(u32, u32),
+    /// This error occurs when security contributed by the field is lower than security
+    /// contributed by the queries needed to meet the target. The values are the security
+    /// contributed by the queries and the security contributed by the field.
+    InsufficientFieldSecurity(u32, u32),
+    /// This error occurs when the targeted security cannot be met with the largest allowed
+    /// number of queries. The value is the targeted security.
+    InsufficientQuerySecurity(u32),
+}
+
+impl fmt::Display for SecurityError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientCollisionResistance(target, actual) => {
+                write!(f, "targeted security of {target} bits exceeds hash collision resistance of {actual} bits")
+            }
+            Self::InsufficientFieldSecurity(expected, actual) => {
+                write!(f, "expected field to contribute at least {expected} bits of security, but it contributes {actual} bits")
+            }
+            Self::InsufficientQuerySecurity(target) => {
+                write!(f, "targeted security of {target} bits cannot be met with the largest allowed number of queries")
+            }
+        }
+    }
+}
+
+// ARCHIVE ERROR
+// ================================================================================================
+/// Represents an error returned when a proof cannot be unsealed from an
+/// [ArchivalEnvelope](crate::proof::ArchivalEnvelope).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// This error occurs when the envelope does not start with the expected magic bytes.
+    InvalidMagic,
+    /// This error occurs when the envelope was written using an unknown format version.
+    UnsupportedVersion(u16),
+    /// This error occurs when the envelope is shorter than its header implies. The values are
+    /// the expected and the actual number of bytes in the envelope.
+    Truncated(usize, usize),
+    /// This error occurs when the envelope is longer than its header implies. The value is the
+    /// number of unexpected trailing bytes.
+    TrailingBytes(usize),
+    /// This error occurs when the digest of the enclosed proof does not match the proof digest
+    /// recorded in the header.
+    DigestMismatch,
+    /// This error occurs when a signature verifier is provided, but the envelope is not signed.
+    MissingSignature,
+    /// This error occurs when the signature of the envelope is rejected by the verifier.
+    InvalidSignature,
+    /// This error occurs when the enclosed proof cannot be deserialized.
+    InvalidProof(String),
+    /// This error occurs when the trace shape recorded in the header does not match the trace
+    /// shape of the enclosed proof.
+    TraceShapeMismatch,
+}
+
+impl fmt::Display for ArchiveError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => {
+                write!(f, "archival envelope does not start with the expected magic bytes")
+            }
+            Self::UnsupportedVersion(version) => {
+                write!(f, "archival envelope format version {version} is not supported")
+            }
+            Self::Truncated(expected, actual) => {
+                write!(f, "expected archival envelope to contain {expected} bytes, but it contains only {actual} bytes")
+            }
+            Self::TrailingBytes(num_bytes) => {
+                write!(f, "archival envelope contains {num_bytes} unexpected trailing bytes")
+            }
+            Self::DigestMismatch => {
+                write!(f, "digest of the archived proof does not match the digest in the header")
+            }
+            Self::MissingSignature => {
+                write!(f, "archival envelope is not signed")
+            }
+            Self::InvalidSignature => {
+                write!(f, "signature of the archival envelope is not valid")
+            }
+            Self::InvalidProof(err) => {
+                write!(f, "archived proof could not be deserialized: {err}")
+            }
+            Self::TraceShapeMismatch => {
+                write!(f, "trace shape of the archived proof does not match the trace shape in the header")
+            }
         }
     }
 }
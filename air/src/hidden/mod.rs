@@ -0,0 +1,514 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Components for asserting that trace cells contain preimages of public digests.
+//!
+//! A regular [Assertion] reveals the asserted value to the verifier. A [HiddenAssertion]
+//! instead requires that the value in a given cell of the execution trace hashes to a public
+//! digest, and thus, the value itself does not need to be included in public inputs. For this to
+//! work, the digest must be computed inside the AIR, and therefore, the hash function must be
+//! described by a [HashGadget] - i.e., its permutation must be expressible as transition
+//! constraints (e.g., Rescue or Poseidon).
+//!
+//! [HiddenAssertions] wires a set of hidden assertions into an AIR. For every hidden assertion,
+//! the following columns are appended to the execution trace:
+//! * An advice column which contains random padding in every row, except for a short chain of
+//!   rows which links the asserted cell to the first row of the cycle in which the hidden value
+//!   is hashed (the hashing cycle). The chain never spans more than `CYCLE_LENGTH` rows.
+//! * `STATE_WIDTH` columns which contain the state of the hash function. In the first
+//!   `NUM_ROUNDS` rows of every cycle, a single round of the permutation is applied to the state;
+//!   in the remaining rows of the cycle, the state is reset to `[advice, 0, ..., 0]`. Thus, at
+//!   the end of the rounds of every cycle (except for the first one), the first `DIGEST_WIDTH`
+//!   elements of the state contain the digest of the advice value in the first row of the cycle.
+//!
+//! The digest computed in the hashing cycle is asserted against the public digest via regular
+//! assertions, and the chain of advice cells is enforced by a transition constraint gated on two
+//! periodic selector columns. An AIR opts into hidden assertions by appending degrees, periodic
+//! columns, assertions, and constraint evaluations of [HiddenAssertions] to its own; on the
+//! prover side, columns built by [build_columns()](HiddenAssertions::build_columns) are appended
+//! to the execution trace.
+//!
+//! The hashing cycle is the cycle which starts at the closest multiple of `CYCLE_LENGTH` below
+//! the asserted step; if there is no such cycle other than the first one, the cycle which starts
+//! at the closest multiple of `CYCLE_LENGTH` above the asserted step is used.
+//!
+//! # Privacy
+//! Values in all rows of the advice column outside of the chain are derived from a random seed
+//! supplied by the prover, and thus, the advice column is masked by random rows: trace
+//! polynomials of the columns of a hidden assertion are not constant, and their evaluations
+//! revealed by trace queries and by the out-of-domain frame do not contain the hidden value in
+//! plain form. This, however, is not a zero-knowledge guarantee: each revealed evaluation is a
+//! linear combination of trace values which includes the hidden value. Also, columns of the AIR
+//! itself (e.g., the column against which the assertion is placed) are not masked.
+//!
+//! Note that a digest of a value hides the value only when the value is hard to guess: a hidden
+//! value which comes from a small set can be recovered from its digest by trying all candidates.
+
+use crate::{Assertion, EvaluationFrame, TransitionConstraintDegree};
+use core::marker::PhantomData;
+use crypto::{hash_to_field, hashers::Blake3_256};
+use math::{FieldElement, StarkField};
+use utils::collections::Vec;
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// HASH GADGET
+// ================================================================================================
+/// Describes the permutation of an algebraic hash function in terms of transition constraints.
+///
+/// The permutation is applied over a cycle of `CYCLE_LENGTH` steps: a single round of the
+/// permutation is applied at each of the first `NUM_ROUNDS` steps of the cycle. A digest of a
+/// value is computed by applying all rounds of the permutation to the state `[value, 0, ..., 0]`
+/// and taking the first `DIGEST_WIDTH` elements of the resulting state.
+pub trait HashGadget<B: StarkField> {
+    /// Number of field elements in the state of the permutation.
+    const STATE_WIDTH: usize;
+
+    /// Number of field elements in a digest.
+    const DIGEST_WIDTH: usize;
+
+    /// Number of steps in a cycle of the permutation; must be a power of two.
+    const CYCLE_LENGTH: usize;
+
+    /// Number of rounds in the permutation; must be smaller than the cycle length.
+    const NUM_ROUNDS: usize;
+
+    /// Degree of the constraints describing a single round of the permutation.
+    const ROUND_DEGREE: usize;
+
+    /// Applies the round at the specified position within the cycle to the provided state.
+    fn apply_round(state: &mut [B], round: usize);
+
+    /// Returns periodic columns of round constants used by [enforce_round()](Self::enforce_round);
+    /// every column must contain `CYCLE_LENGTH` values.
+    fn get_round_constants() -> Vec<Vec<B>>;
+
+    /// When `flag` = 1, enforces that the `next` state is the result of applying a single round
+    /// of the permutation to the `current` state; the results are added to the first
+    /// `STATE_WIDTH` slots of `result`.
+    ///
+    /// `round_constants` contain values of the columns returned by
+    /// [get_round_constants()](Self::get_round_constants) at the current step.
+    fn enforce_round<E: FieldElement + From<B>>(
+        result: &mut [E],
+        current: &[E],
+        next: &[E],
+        round_constants: &[E],
+        flag: E,
+    );
+
+    /// Returns a digest of the provided value.
+    fn digest(value: B) -> Vec<B> {
+        let mut state = init_state::<B>(value, Self::STATE_WIDTH);
+        for round in 0..Self::NUM_ROUNDS {
+            Self::apply_round(&mut state, round);
+        }
+        state.truncate(Self::DIGEST_WIDTH);
+        state
+    }
+}
+
+// HIDDEN ASSERTION
+// ================================================================================================
+/// An assertion that the value in a single cell of an execution trace is a preimage of a public
+/// digest.
+///
+/// Hidden assertions are created via [Assertion::hidden()], and are wired into an AIR via
+/// [HiddenAssertions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HiddenAssertion<B: StarkField> {
+    pub(crate) column: usize,
+    pub(crate) step: usize,
+    pub(crate) digest: Vec<B>,
+}
+
+impl<B: StarkField> HiddenAssertion<B> {
+    /// Returns index of the column against which this assertion is placed.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the step of the execution trace against which this assertion is placed.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the public digest of the asserted value.
+    pub fn digest(&self) -> &[B] {
+        &self.digest
+    }
+}
+
+// HIDDEN ASSERTIONS
+// ================================================================================================
+/// A set of hidden assertions along with the trace columns, transition constraints, periodic
+/// columns, and regular assertions needed to enforce them.
+///
+/// Columns of the hidden assertions are placed into a contiguous range of trace columns starting
+/// at `first_column`: for the assertion `i`, the advice column is at index
+/// `first_column + i * (STATE_WIDTH + 1)`, and it is followed by `STATE_WIDTH` columns of the
+/// hash function state. Every hidden assertion defines `STATE_WIDTH + 1` transition constraints
+/// in the following order: `STATE_WIDTH` constraints for the permutation, and a constraint which
+/// links the asserted cell to the first row of the hashing cycle through the advice column.
+pub struct HiddenAssertions<B: StarkField, G: HashGadget<B>> {
+    first_column: usize,
+    trace_length: usize,
+    num_round_constants: usize,
+    assertions: Vec<HiddenAssertion<B>>,
+    hash_rows: Vec<usize>,
+    _gadget: PhantomData<G>,
+}
+
+impl<B: StarkField, G: HashGadget<B>> HiddenAssertions<B, G> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new set of hidden assertions against a trace of the specified length, with
+    /// columns of the assertions starting at `first_column`.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Parameters of the hash gadget are invalid.
+    /// * `trace_length` is not a multiple of the gadget's cycle length or is smaller than two
+    ///   cycles.
+    /// * Any of the assertions is placed against a step outside of the trace, is placed against
+    ///   a column of the hidden assertions, or has a digest of an invalid length.
+    /// * There is no hashing cycle for any of the assertions; this happens only for assertions
+    ///   against step `CYCLE_LENGTH` of a trace which consists of two cycles.
+    pub fn new(
+        first_column: usize,
+        trace_length: usize,
+        assertions: Vec<HiddenAssertion<B>>,
+    ) -> Self {
+        assert!(
+            G::CYCLE_LENGTH.is_power_of_two(),
+            "cycle length of a hash gadget must be a power of two"
+        );
+        assert!(
+            G::NUM_ROUNDS < G::CYCLE_LENGTH,
+            "number of rounds of a hash gadget must be smaller than its cycle length"
+        );
+        assert!(
+            G::DIGEST_WIDTH <= G::STATE_WIDTH,
+            "digest of a hash gadget cannot be wider than its state"
+        );
+        assert!(
+            trace_length % G::CYCLE_LENGTH == 0 && trace_length >= 2 * G::CYCLE_LENGTH,
+            "trace length must be a multiple of {} and must be at least {}, but was {}",
+            G::CYCLE_LENGTH,
+            2 * G::CYCLE_LENGTH,
+            trace_length
+        );
+
+        let columns = first_column..first_column + assertions.len() * (G::STATE_WIDTH + 1);
+        let mut hash_rows = Vec::with_capacity(assertions.len());
+        for assertion in assertions.iter() {
+            assert!(
+                assertion.step < trace_length,
+                "hidden assertion step must be smaller than {}, but was {}",
+                trace_length,
+                assertion.step
+            );
+            assert!(
+                !columns.contains(&assertion.column),
+                "hidden assertion cannot be placed against column {} of hidden assertions",
+                assertion.column
+            );
+            assert_eq!(
+                G::DIGEST_WIDTH,
+                assertion.digest.len(),
+                "hidden assertion digest must consist of {} elements, but was {}",
+                G::DIGEST_WIDTH,
+                assertion.digest.len()
+            );
+            let hash_row = get_hash_row(assertion.step, trace_length, G::CYCLE_LENGTH)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "hidden assertion cannot be placed against step {} of a trace of length {}",
+                        assertion.step, trace_length
+                    )
+                });
+            hash_rows.push(hash_row);
+        }
+
+        HiddenAssertions {
+            first_column,
+            trace_length,
+            num_round_constants: G::get_round_constants().len(),
+            assertions,
+            hash_rows,
+            _gadget: PhantomData,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the hidden assertions of this set.
+    pub fn assertions(&self) -> &[HiddenAssertion<B>] {
+        &self.assertions
+    }
+
+    /// Returns the number of trace columns required by this set.
+    pub fn num_columns(&self) -> usize {
+        self.assertions.len() * (G::STATE_WIDTH + 1)
+    }
+
+    /// Returns the number of transition constraints defined by this set.
+    pub fn num_constraints(&self) -> usize {
+        self.assertions.len() * (G::STATE_WIDTH + 1)
+    }
+
+    /// Returns the number of periodic columns defined by this set.
+    pub fn num_periodic_columns(&self) -> usize {
+        1 + self.num_round_constants + 2 * self.assertions.len()
+    }
+
+    /// Returns the number of regular assertions returned by
+    /// [get_assertions()](HiddenAssertions::get_assertions).
+    pub fn num_assertions(&self) -> usize {
+        self.assertions.len() * G::DIGEST_WIDTH
+    }
+
+    /// Returns the step at which the digest of the value of the hidden assertion with the
+    /// specified index is asserted.
+    pub fn digest_step(&self, assertion_idx: usize) -> usize {
+        self.hash_rows[assertion_idx] + G::NUM_ROUNDS
+    }
+
+    /// Returns degrees of the transition constraints defined by this set in the order in which
+    /// they are evaluated by [evaluate_transition()](HiddenAssertions::evaluate_transition).
+    pub fn constraint_degrees(&self) -> Vec<TransitionConstraintDegree> {
+        let mut result = Vec::with_capacity(self.num_constraints());
+        for _ in self.assertions.iter() {
+            for _ in 0..G::STATE_WIDTH {
+                result.push(TransitionConstraintDegree::with_cycles(
+                    G::ROUND_DEGREE,
+                    vec![G::CYCLE_LENGTH],
+                ));
+            }
+            result.push(TransitionConstraintDegree::with_cycles(
+                1,
+                vec![self.trace_length],
+            ));
+        }
+        result
+    }
+
+    /// Returns values of the periodic columns used by
+    /// [evaluate_transition()](HiddenAssertions::evaluate_transition).
+    ///
+    /// The first column is a flag which is set to ONE at steps at which a permutation round is
+    /// applied; it is followed by the round constants of the gadget, and by two selector columns
+    /// for every hidden assertion: the first one is set to ONE at the step which links the
+    /// asserted cell to the advice column, and the second one is set to ONE at steps which link
+    /// adjacent cells of the advice column.
+    pub fn get_periodic_column_values(&self) -> Vec<Vec<B>> {
+        let mut round_flags = vec![B::ZERO; G::CYCLE_LENGTH];
+        round_flags[..G::NUM_ROUNDS].fill(B::ONE);
+
+        let mut result = vec![round_flags];
+        result.append(&mut G::get_round_constants());
+        for (assertion, &hash_row) in self.assertions.iter().zip(self.hash_rows.iter()) {
+            let (head_row, link_rows) = get_chain_rows(assertion.step, hash_row);
+            let mut head = vec![B::ZERO; self.trace_length];
+            head[head_row] = B::ONE;
+            let mut link = vec![B::ZERO; self.trace_length];
+            link[link_rows].fill(B::ONE);
+            result.push(head);
+            result.push(link);
+        }
+        result
+    }
+
+    /// Returns regular assertions which require that the digests of hidden values computed in
+    /// the hashing cycles of the trace are equal to the public digests.
+    pub fn get_assertions(&self) -> Vec<Assertion<B>> {
+        let mut result = Vec::with_capacity(self.num_assertions());
+        for (i, assertion) in self.assertions.iter().enumerate() {
+            let state_column = self.advice_column(i) + 1;
+            let step = self.digest_step(i);
+            for (j, &value) in assertion.digest.iter().enumerate() {
+                result.push(Assertion::single(state_column + j, step, value));
+            }
+        }
+        result
+    }
+
+    // EVALUATOR
+    // --------------------------------------------------------------------------------------------
+
+    /// Evaluates transition constraints defined by this set against the provided frame and
+    /// writes the results into the first [num_constraints()](HiddenAssertions::num_constraints)
+    /// slots of `result`.
+    ///
+    /// `periodic_values` must contain values of the columns returned by
+    /// [get_periodic_column_values()](HiddenAssertions::get_periodic_column_values) at the
+    /// current step.
+    pub fn evaluate_transition<E: FieldElement + From<B>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        let round_flag = periodic_values[0];
+        let reset_flag = E::ONE - round_flag;
+        let round_constants = &periodic_values[1..1 + self.num_round_constants];
+        let selectors = &periodic_values[1 + self.num_round_constants..];
+
+        let result = &mut result[..self.num_constraints()];
+        result.fill(E::ZERO);
+        for (i, (assertion, result)) in self
+            .assertions
+            .iter()
+            .zip(result.chunks_mut(G::STATE_WIDTH + 1))
+            .enumerate()
+        {
+            let advice = self.advice_column(i);
+            let state = advice + 1..advice + 1 + G::STATE_WIDTH;
+
+            // apply a round of the permutation, or reset the state to [advice, 0, ..., 0]
+            let state_result = &mut result[..G::STATE_WIDTH];
+            G::enforce_round(
+                state_result,
+                &current[state.clone()],
+                &next[state.clone()],
+                round_constants,
+                round_flag,
+            );
+            state_result[0] += reset_flag * (next[state.start] - next[advice]);
+            for (result, &value) in state_result.iter_mut().zip(&next[state]).skip(1) {
+                *result += reset_flag * value;
+            }
+
+            // the value in the asserted cell is copied into the advice column at the head of
+            // the chain, and is then carried along the advice column towards the first row of
+            // the hashing cycle; the chain runs forward when the hashing cycle follows the
+            // asserted step, and backward otherwise
+            let (head, link) = (selectors[2 * i], selectors[2 * i + 1]);
+            result[G::STATE_WIDTH] = if assertion.step < self.hash_rows[i] {
+                head * (next[advice] - current[assertion.column])
+                    + link * (next[advice] - current[advice])
+            } else {
+                head * (current[advice] - next[assertion.column])
+                    + link * (current[advice] - next[advice])
+            };
+        }
+    }
+
+    // TRACE BUILDER
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns trace columns for this set computed for the specified hidden values.
+    ///
+    /// `values` must contain the value of the asserted cell for every hidden assertion; the
+    /// returned columns should be placed into the execution trace starting at `first_column`.
+    ///
+    /// Rows of the advice columns which are not part of a chain are filled with field elements
+    /// derived from `seed`; to keep hidden values private, the seed must be drawn uniformly at
+    /// random and must not be reused across proofs.
+    ///
+    /// # Panics
+    /// Panics if the number of values is not equal to the number of hidden assertions.
+    pub fn build_columns(&self, values: &[B], seed: [u8; 32]) -> Vec<Vec<B>> {
+        assert_eq!(
+            self.assertions.len(),
+            values.len(),
+            "expected {} hidden values, but received {}",
+            self.assertions.len(),
+            values.len()
+        );
+
+        let mut result = Vec::with_capacity(self.num_columns());
+        for (i, (&value, assertion)) in values.iter().zip(self.assertions.iter()).enumerate() {
+            // fill the advice column with random padding, and then place the value into the
+            // rows of the chain
+            let mut msg = seed.to_vec();
+            msg.extend_from_slice(&(i as u64).to_le_bytes());
+            let mut advice =
+                hash_to_field::<B, Blake3_256<B>>(HIDDEN_PADDING_DOMAIN, &msg, self.trace_length);
+            let hash_row = self.hash_rows[i];
+            let chain = if assertion.step < hash_row {
+                assertion.step + 1..hash_row + 1
+            } else {
+                hash_row..assertion.step
+            };
+            advice[chain].fill(value);
+
+            // the state in the first row is not constrained; in all other rows, the state is
+            // either a result of applying a round of the permutation, or is reset to the value
+            // in the advice column
+            let mut columns = vec![Vec::with_capacity(self.trace_length); G::STATE_WIDTH];
+            let mut state = init_state(advice[0], G::STATE_WIDTH);
+            for step in 0..self.trace_length {
+                for (column, &element) in columns.iter_mut().zip(state.iter()) {
+                    column.push(element);
+                }
+                let round = step % G::CYCLE_LENGTH;
+                if round < G::NUM_ROUNDS {
+                    G::apply_round(&mut state, round);
+                } else if step + 1 < self.trace_length {
+                    state = init_state(advice[step + 1], G::STATE_WIDTH);
+                }
+            }
+            result.push(advice);
+            result.append(&mut columns);
+        }
+        result
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns index of the advice column for the hidden assertion with the specified index.
+    fn advice_column(&self, assertion_idx: usize) -> usize {
+        self.first_column + assertion_idx * (G::STATE_WIDTH + 1)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Domain separation tag for deriving the padding of advice columns from a seed.
+const HIDDEN_PADDING_DOMAIN: &[u8] = b"WINTERFELL_HIDDEN_PADDING";
+
+/// Returns the state `[value, 0, ..., 0]` of the specified width.
+fn init_state<B: StarkField>(value: B, width: usize) -> Vec<B> {
+    let mut state = vec![B::ZERO; width];
+    state[0] = value;
+    state
+}
+
+/// Returns the first row of the cycle in which the value asserted at the specified step is
+/// hashed, or None if there is no such cycle.
+///
+/// The closest cycle which starts before the step is preferred; the first cycle cannot be used
+/// because the state in the first row of the trace is not constrained, and a cycle following the
+/// step cannot be used when it would end after the end of the trace.
+fn get_hash_row(step: usize, trace_length: usize, cycle_length: usize) -> Option<usize> {
+    let preceding = step.saturating_sub(1) / cycle_length * cycle_length;
+    let following = step / cycle_length * cycle_length + cycle_length;
+    if step > 0 && preceding >= cycle_length {
+        Some(preceding)
+    } else if following <= trace_length - cycle_length {
+        Some(following)
+    } else {
+        None
+    }
+}
+
+/// Returns the row at which the asserted cell is linked to the advice column, together with the
+/// rows at which adjacent cells of the advice column are linked, for a chain between the asserted
+/// step and the first row of the hashing cycle.
+fn get_chain_rows(step: usize, hash_row: usize) -> (usize, core::ops::Range<usize>) {
+    if step < hash_row {
+        (step, step + 1..hash_row)
+    } else {
+        (step - 1, hash_row..step - 1)
+    }
+}
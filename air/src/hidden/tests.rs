@@ -0,0 +1,245 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{HashGadget, HiddenAssertions};
+use crate::{Assertion, EvaluationFrame, TransitionConstraintDegree};
+use math::{fields::f128::BaseElement, FieldElement};
+use utils::collections::Vec;
+
+const TRACE_LENGTH: usize = 16;
+const SEED: [u8; 32] = [1; 32];
+
+// CONSTRAINTS
+// ================================================================================================
+
+#[test]
+fn hidden_constraint_degrees() {
+    let hidden = build_hidden_assertions(&[(0, 5), (1, 0)]);
+    assert_eq!(4, hidden.num_columns());
+    assert_eq!(4, hidden.num_constraints());
+    assert_eq!(6, hidden.num_periodic_columns());
+    assert_eq!(2, hidden.num_assertions());
+    assert_eq!(7, hidden.digest_step(0));
+    assert_eq!(7, hidden.digest_step(1));
+
+    let round_degree = TransitionConstraintDegree::with_cycles(3, vec![4]);
+    let chain_degree = TransitionConstraintDegree::with_cycles(1, vec![TRACE_LENGTH]);
+    assert_eq!(
+        vec![
+            round_degree.clone(),
+            chain_degree.clone(),
+            round_degree,
+            chain_degree,
+        ],
+        hidden.constraint_degrees()
+    );
+}
+
+#[test]
+fn hidden_constraints() {
+    let hidden = build_hidden_assertions(&[(0, 5), (1, 0)]);
+    let trace = build_trace(&hidden, &[], SEED);
+
+    // all constraints are satisfied by a valid trace
+    let result = evaluate_constraints(&hidden, &trace);
+    assert!(result.iter().flatten().all(|&v| v == BaseElement::ZERO));
+
+    // digests computed in the trace match the asserted digests
+    for assertion in hidden.get_assertions() {
+        let value = trace[assertion.column()][assertion.first_step()];
+        assert_eq!(assertion.values()[0], value);
+    }
+}
+
+#[test]
+fn hidden_constraints_wrong_preimage() {
+    let hidden = build_hidden_assertions(&[(0, 5), (1, 0)]);
+
+    // gadget columns of the first assertion are computed for a value which is not in the
+    // asserted cell; only the chain constraint of this assertion is violated, and only at the
+    // head of the chain
+    let trace = build_trace(&hidden, &[BaseElement::ONE], SEED);
+    let result = evaluate_constraints(&hidden, &trace);
+    for (step, result) in result.iter().enumerate() {
+        for (i, &value) in result.iter().enumerate() {
+            assert_eq!(step == 4 && i == 1, value != BaseElement::ZERO);
+        }
+    }
+}
+
+#[test]
+fn hidden_chains() {
+    // chains run backward to a preceding cycle other than the first one when possible, and
+    // forward otherwise; steps 4 and 12 are multiples of the cycle length
+    let cells = [(0, 0), (0, 3), (0, 4), (0, 6), (1, 12), (1, 15)];
+    let hidden = build_hidden_assertions(&cells);
+    let digest_steps = (0..cells.len())
+        .map(|i| hidden.digest_step(i))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![7, 7, 11, 7, 11, 15], digest_steps);
+
+    let trace = build_trace(&hidden, &[], SEED);
+    let result = evaluate_constraints(&hidden, &trace);
+    assert!(result.iter().flatten().all(|&v| v == BaseElement::ZERO));
+    for assertion in hidden.get_assertions() {
+        let value = trace[assertion.column()][assertion.first_step()];
+        assert_eq!(assertion.values()[0], value);
+    }
+}
+
+#[test]
+fn hidden_advice_padding() {
+    let hidden = build_hidden_assertions(&[(0, 5), (1, 0)]);
+    let trace = build_trace(&hidden, &[], SEED);
+
+    // hidden values appear in the advice columns only within the chains
+    let advice = &trace[2];
+    let value = main_value(0, 5);
+    let rows = (0..TRACE_LENGTH)
+        .filter(|&step| advice[step] == value)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![4], rows);
+
+    let advice = &trace[4];
+    let value = main_value(1, 0);
+    let rows = (0..TRACE_LENGTH)
+        .filter(|&step| advice[step] == value)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![1, 2, 3, 4], rows);
+
+    // padding depends on the seed, while cells of the chains do not
+    let other = build_trace(&hidden, &[], [7; 32]);
+    assert_ne!(trace[2][0], other[2][0]);
+    assert_eq!(trace[2][4], other[2][4]);
+}
+
+#[test]
+#[should_panic(expected = "hidden assertion step must be smaller than 16, but was 16")]
+fn hidden_assertion_invalid_step() {
+    build_hidden_assertions(&[(0, TRACE_LENGTH)]);
+}
+
+#[test]
+#[should_panic(
+    expected = "hidden assertion cannot be placed against column 3 of hidden assertions"
+)]
+fn hidden_assertion_invalid_column() {
+    build_hidden_assertions(&[(3, 0)]);
+}
+
+#[test]
+#[should_panic(
+    expected = "hidden assertion cannot be placed against step 4 of a trace of length 8"
+)]
+fn hidden_assertion_no_hashing_cycle() {
+    let assertions = vec![Assertion::hidden(0, 4, vec![BaseElement::ONE])];
+    HiddenAssertions::<BaseElement, CubeGadget>::new(2, 8, assertions);
+}
+
+#[test]
+#[should_panic(expected = "trace length must be a multiple of 4 and must be at least 8, but was 4")]
+fn hidden_assertion_invalid_trace_length() {
+    HiddenAssertions::<BaseElement, CubeGadget>::new(2, 4, Vec::new());
+}
+
+// TEST GADGET
+// ================================================================================================
+
+/// A toy permutation which maps x to x^3 + k over 3 rounds.
+struct CubeGadget;
+
+impl HashGadget<BaseElement> for CubeGadget {
+    const STATE_WIDTH: usize = 1;
+    const DIGEST_WIDTH: usize = 1;
+    const CYCLE_LENGTH: usize = 4;
+    const NUM_ROUNDS: usize = 3;
+    const ROUND_DEGREE: usize = 3;
+
+    fn apply_round(state: &mut [BaseElement], round: usize) {
+        state[0] = state[0].exp(3) + BaseElement::new(round as u128 + 1);
+    }
+
+    fn get_round_constants() -> Vec<Vec<BaseElement>> {
+        vec![(0..4).map(|i| BaseElement::new((i + 1) % 4)).collect()]
+    }
+
+    fn enforce_round<E: FieldElement + From<BaseElement>>(
+        result: &mut [E],
+        current: &[E],
+        next: &[E],
+        round_constants: &[E],
+        flag: E,
+    ) {
+        result[0] += flag * (next[0] - current[0].exp(3u32.into()) - round_constants[0]);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds hidden assertions against the specified (column, step) pairs of a trace with 2 main
+/// columns.
+fn build_hidden_assertions(cells: &[(usize, usize)]) -> HiddenAssertions<BaseElement, CubeGadget> {
+    let assertions = cells
+        .iter()
+        .map(|&(column, step)| {
+            let value = main_value(column, step);
+            Assertion::hidden(column, step, CubeGadget::digest(value))
+        })
+        .collect();
+    HiddenAssertions::new(2, TRACE_LENGTH, assertions)
+}
+
+/// Builds a trace consisting of 2 main columns followed by the columns of the hidden assertions;
+/// hidden values are shifted by the provided offsets, and padding is derived from `seed`.
+fn build_trace(
+    hidden: &HiddenAssertions<BaseElement, CubeGadget>,
+    offsets: &[BaseElement],
+    seed: [u8; 32],
+) -> Vec<Vec<BaseElement>> {
+    let mut trace = (0..2)
+        .map(|column| {
+            (0..TRACE_LENGTH)
+                .map(|step| main_value(column, step))
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    let values = hidden
+        .assertions()
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            main_value(a.column(), a.step()) + offsets.get(i).copied().unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    trace.append(&mut hidden.build_columns(&values, seed));
+    trace
+}
+
+/// Evaluates constraints of the hidden assertions at all steps but the last one.
+fn evaluate_constraints(
+    hidden: &HiddenAssertions<BaseElement, CubeGadget>,
+    trace: &[Vec<BaseElement>],
+) -> Vec<Vec<BaseElement>> {
+    let periodic_columns = hidden.get_periodic_column_values();
+    (0..TRACE_LENGTH - 1)
+        .map(|step| {
+            let current = trace.iter().map(|column| column[step]).collect();
+            let next = trace.iter().map(|column| column[step + 1]).collect();
+            let frame = EvaluationFrame::from_rows(current, next);
+            let periodic_values = periodic_columns
+                .iter()
+                .map(|column| column[step % column.len()])
+                .collect::<Vec<_>>();
+            let mut result = vec![BaseElement::ZERO; hidden.num_constraints()];
+            hidden.evaluate_transition(&frame, &periodic_values, &mut result);
+            result
+        })
+        .collect()
+}
+
+fn main_value(column: usize, step: usize) -> BaseElement {
+    BaseElement::new((column * TRACE_LENGTH + step) as u128 + 7)
+}
@@ -28,6 +28,7 @@
 //! proof structure ([StarkProof](proof::StarkProof)).
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(all(feature = "safe_only", not(test)), forbid(unsafe_code))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -35,16 +36,37 @@ extern crate alloc;
 
 pub mod proof;
 
+pub mod rap;
+
+pub mod selectors;
+
+pub mod hidden;
+
+#[cfg(feature = "test-kit")]
+pub mod test_kit;
+
+#[cfg(feature = "serde")]
+pub mod serde_bytes;
+
 mod errors;
-pub use errors::AssertionError;
+pub use errors::{ArchiveError, AssertionError, SecurityError, SelectorError, TraceLayoutError};
 
 mod options;
-pub use options::{FieldExtension, ProofOptions};
+pub use options::{FieldExtension, GrindingStrategy, LeafEncoding, ProofOptions};
+
+mod transcript;
+pub use transcript::{labels as transcript_labels, pub_inputs_seed, PowPuzzle, TranscriptProfile};
 
 mod air;
 pub use air::{
-    Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint, BoundaryConstraintGroup,
-    BoundaryConstraints, ConstraintCompositionCoefficients, ConstraintDivisor,
-    DeepCompositionCoefficients, EvaluationFrame, TraceInfo, TraceLayout,
+    Air, AirContext, AirDescription, Assertion, AssertionDescription, AssertionStep,
+    AuxTraceRandElements, BoundaryConstraint, BoundaryConstraintGroup, BoundaryConstraints,
+    BoundaryExpr, BoundaryExprConstraint, CePos, CoefficientPairs,
+    ConstraintCompositionCoefficients, ConstraintDescription, ConstraintDescriptor,
+    ConstraintDivisor, DeepCompositionCoefficients, EvaluationFrame, LdePos, PowerTable,
+    SplitInputsAir, StepSelector, TraceInfo, TraceLayout, TraceLayoutBuilder, TraceRow,
     TransitionConstraintDegree, TransitionConstraintGroup, TransitionConstraints,
+    TransitionDivisorKind, MAX_INLINE_CONSTRAINTS,
 };
+#[cfg(feature = "dependency-graph")]
+pub use air::{ConstraintDependencies, DependencyGraph, DEFAULT_NUM_DEPENDENCY_SAMPLES};
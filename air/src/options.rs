@@ -3,9 +3,17 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use crate::{
+    proof::{get_conjectured_security, get_security_per_query, SecurityEstimate},
+    SecurityError, TraceInfo, TranscriptProfile,
+};
+use crypto::{ElementHasher, HashFunctionId, Hasher};
 use fri::FriOptions;
-use math::StarkField;
-use utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable};
+use math::{FieldElement, StarkField};
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable, SliceReader,
+};
 
 // TYPES AND INTERFACES
 // ================================================================================================
@@ -34,6 +42,53 @@ pub enum FieldExtension {
     Cubic = 3,
 }
 
+/// Defines how rows of execution trace commitments are encoded into leaves of Merkle trees.
+///
+/// By default, every row of a trace commitment is hashed, and the resulting digest is used as a
+/// leaf of the commitment Merkle tree. For traces with short rows (e.g., rows of two 64-bit field
+/// elements), the digest is larger than the row itself; for such traces, the raw encoding can be
+/// used instead. In this encoding, serialized row elements padded with zeros to the size of a
+/// digest are used as a leaf directly, and the Merkle tree hashes pairs of such leaves in the same
+/// way as it hashes pairs of internal nodes. This removes row hashing from the prover and the
+/// verifier, and shrinks Merkle authentication paths because sibling leaves are sent as raw rows.
+///
+/// The raw encoding can be used only when rows of all trace commitments fit into a digest of the
+/// hash function used by the protocol (see [LeafEncoding::is_supported()]). Rows of the
+/// constraint commitment are always hashed.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LeafEncoding {
+    /// Leaves are hashes of trace rows.
+    Hashed = 0,
+    /// Leaves are trace rows padded with zeros to the size of a digest.
+    Raw = 1,
+}
+
+/// Defines how the prover searches for a proof-of-work nonce for the query seed.
+///
+/// With the [TimeBoxed](GrindingStrategy::TimeBoxed) strategy, a proof may contain a nonce which
+/// yields fewer proof-of-work bits than the grinding factor; every missing bit is compensated by
+/// drawing more queries (see [ProofOptions::num_extra_queries()]). The verifier recomputes the
+/// number of extra queries from the nonce, and thus, the strategy does not reduce proof
+/// soundness. However, proofs generated with this strategy depend on how fast the prover is: the
+/// same statement proven twice on the same machine may yield proofs with different nonces and
+/// different numbers of queries.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GrindingStrategy {
+    /// The prover searches for a nonce which yields the number of proof-of-work bits specified
+    /// by the grinding factor, regardless of how long the search takes.
+    FixedBits,
+    /// The prover searches for a nonce which yields the number of proof-of-work bits specified
+    /// by the grinding factor for up to `max_millis` milliseconds. Once the deadline passes, the
+    /// prover uses the best nonce found so far, as long as the bits it is missing can be
+    /// compensated by at most `fallback_extra_queries` extra queries; otherwise, the prover keeps
+    /// searching until such a nonce is found.
+    TimeBoxed {
+        max_millis: u32,
+        fallback_extra_queries: usize,
+    },
+}
+
 /// STARK protocol parameters.
 ///
 /// These parameters have a direct impact on proof soundness, proof generation time, and proof
@@ -50,7 +105,60 @@ pub enum FieldExtension {
 ///    time decrease the number of queries in such a way that the proofs become smaller.
 /// 4. Grinding factor - higher values increase proof soundness, but also may increase proof
 ///    generation time. More precisely, proof soundness is bounded by
-///    `num_queries * log2(blowup_factor) + grinding_factor`.
+///    `num_queries * log2(blowup_factor) + grinding_factor`. The time spent on grinding can be
+///    bounded via [with_grinding_strategy()](ProofOptions::with_grinding_strategy) at the expense
+///    of proof size.
+///
+/// Proof options also specify a [TranscriptProfile] which defines how query positions are derived
+/// from the proof-of-work nonce. The profile does not affect proof soundness, and is set to
+/// [TranscriptProfile::Winterfell] by default.
+///
+/// For very small execution traces, proof options can also enable explicit composition via
+/// [with_explicit_composition()](ProofOptions::with_explicit_composition). In this mode, the
+/// prover does not commit to evaluations of the constraint composition polynomial; instead, it
+/// sends coefficients of the polynomial to the verifier in the clear. This does not affect proof
+/// soundness, but may reduce proof size for traces of up to a few hundred steps.
+///
+/// By default, the DEEP step of the protocol samples a single out-of-domain point. Proof options
+/// can increase the number of out-of-domain points via
+/// [with_num_ood_points()](ProofOptions::with_num_ood_points). The prover then builds a separate
+/// constraint composition polynomial (from a separate set of random coefficients) for every point,
+/// and each point increases security of the DEEP/ALI step of the protocol; this may increase the
+/// number of bits of security contributed by the field by up to the point where the FRI commit
+/// phase becomes the bottleneck. The cost of additional points is larger proofs and more work for
+/// the prover and the verifier.
+///
+/// Proof options can also instruct the prover to commit to a random linear combination of all
+/// trace columns via [with_combined_trace_column()](ProofOptions::with_combined_trace_column).
+/// This does not affect proof soundness, but lets external systems open the entire trace at a
+/// given position with a single authentication path against the combined column commitment.
+///
+/// Proof options can also instruct the prover to bind the FRI remainder polynomial to the query
+/// positions via [with_fri_remainder_digest()](ProofOptions::with_fri_remainder_digest). In this
+/// case, a digest of the coefficients of the remainder polynomial is absorbed into the public
+/// coin before the query positions are drawn, and the verifier recomputes and checks this digest.
+///
+/// For traces with short rows, proof options can also set the [LeafEncoding] of trace
+/// commitments to [LeafEncoding::Raw] via [with_leaf_encoding()](ProofOptions::with_leaf_encoding).
+///
+/// Trace and constraint commitments can also use Merkle trees with arity 4 or 8 via
+/// [with_merkle_arity()](ProofOptions::with_merkle_arity). This reduces the number of tree
+/// levels, and thus, the number of hashes the verifier computes per query, at the expense of
+/// larger authentication paths. Commitments to FRI layers always use binary Merkle trees.
+///
+/// Proof options can also request salted trace and constraint commitments via
+/// [with_zk()](ProofOptions::with_zk). In this case, every leaf of a commitment Merkle tree is a
+/// hash of the row digest and a random salt, and salts of opened leaves are included in the
+/// proof. Thus, digests of unopened rows which appear in authentication paths do not reveal
+/// anything about these rows. Rows opened at query positions are still revealed to the verifier.
+///
+/// By default, the query seed proof-of-work is computed with the hash function of the protocol
+/// over a 64-bit nonce. Proof options can select a different (e.g., cheaper or hardware
+/// accelerated) hash function for grinding via
+/// [with_grinding_hash()](ProofOptions::with_grinding_hash), and widen the nonce to 128 bits via
+/// [with_wide_grinding_nonce()](ProofOptions::with_wide_grinding_nonce). Proof-of-work bits
+/// count towards proof soundness in the same way regardless of the hash function used for
+/// grinding.
 ///
 /// Another important parameter in defining STARK security level, which is not a part of [ProofOptions]
 /// is the hash function used in the protocol. The soundness of a STARK proof is limited by the
@@ -64,6 +172,17 @@ pub struct ProofOptions {
     field_extension: FieldExtension,
     fri_folding_factor: u8,
     fri_max_remainder_size: u8, // stored as power of 2
+    transcript_profile: TranscriptProfile,
+    explicit_composition_threshold: u8, // stored as power of 2; 0 means disabled
+    num_ood_points: u8,
+    combined_trace_column: bool,
+    fri_remainder_digest: bool,
+    leaf_encoding: LeafEncoding,
+    merkle_arity: u8,
+    zk: bool,
+    grinding_strategy: GrindingStrategy,
+    grinding_hash: Option<HashFunctionId>,
+    wide_grinding_nonce: bool,
 }
 
 // PROOF OPTIONS IMPLEMENTATION
@@ -79,6 +198,29 @@ impl ProofOptions {
     /// have a blowup factor smaller than 2.
     pub const MIN_BLOWUP_FACTOR: usize = 2;
 
+    /// Largest execution trace length for which explicit composition can be enabled; currently
+    /// set to 2^10.
+    ///
+    /// The number of composition polynomial coefficients sent in explicit composition mode grows
+    /// linearly with trace length, while the size of Merkle authentication paths it replaces
+    /// grows only logarithmically. Thus, explicit composition stops paying off for larger traces.
+    pub const MAX_EXPLICIT_COMPOSITION_TRACE_LENGTH: usize = 1024;
+
+    /// Largest number of out-of-domain points which can be sampled during the DEEP step of the
+    /// protocol; currently set to 4.
+    pub const MAX_OOD_POINTS: usize = 4;
+
+    /// Largest allowed number of queries; currently set to 128.
+    pub const MAX_NUM_QUERIES: usize = 128;
+
+    /// Size of the largest LDE domain for which options returned by
+    /// [with_security()](ProofOptions::with_security) achieve the targeted security level;
+    /// currently set to 2^24.
+    ///
+    /// Security contributed by the field decreases as the LDE domain grows, and thus, proofs for
+    /// computations with larger LDE domains may provide less security than targeted.
+    pub const MAX_SECURITY_LDE_DOMAIN_SIZE: usize = 1 << 24;
+
     // CONSTRUCTORS
     // --------------------------------------------------------------------------------------------
     /// Returns a new instance of [ProofOptions] struct constructed from the specified parameters.
@@ -101,7 +243,8 @@ impl ProofOptions {
     ) -> ProofOptions {
         // TODO: return errors instead of panicking
         assert!(num_queries > 0, "number of queries must be greater than 0");
-        assert!(num_queries <= 128, "number of queries cannot be greater than 128");
+        assert!(num_queries <= Self::MAX_NUM_QUERIES,
+            "number of queries cannot be greater than {}", Self::MAX_NUM_QUERIES);
 
         assert!(blowup_factor.is_power_of_two(), "blowup factor must be a power of 2");
         assert!(blowup_factor >= Self::MIN_BLOWUP_FACTOR,
@@ -125,9 +268,290 @@ impl ProofOptions {
             field_extension,
             fri_folding_factor: fri_folding_factor as u8,
             fri_max_remainder_size: fri_max_remainder_size.trailing_zeros() as u8,
+            transcript_profile: TranscriptProfile::Winterfell,
+            explicit_composition_threshold: 0,
+            num_ood_points: 1,
+            combined_trace_column: false,
+            fri_remainder_digest: false,
+            leaf_encoding: LeafEncoding::Hashed,
+            merkle_arity: 2,
+            zk: false,
+            grinding_strategy: GrindingStrategy::FixedBits,
+            grinding_hash: None,
+            wide_grinding_nonce: false,
         }
     }
 
+    /// Returns proof options which achieve at least `target_bits` of security for computations
+    /// over base field `B` when hash function `H` is used, and the composition polynomial is
+    /// constructed in the specified `field_extension`.
+    ///
+    /// The options use blowup factor 8, grinding factor 16, FRI folding factor 8, and FRI max
+    /// remainder size 256; the number of queries is the smallest one which meets the target.
+    /// Security is estimated in the same way as in
+    /// [StarkProof::security_level()](crate::proof::StarkProof::security_level), and the target
+    /// is met for all LDE domains of up to
+    /// [MAX_SECURITY_LDE_DOMAIN_SIZE](ProofOptions::MAX_SECURITY_LDE_DOMAIN_SIZE) elements.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * `target_bits` exceeds collision resistance of the hash function.
+    /// * Security contributed by the field (with the specified extension) over LDE domains of
+    ///   [MAX_SECURITY_LDE_DOMAIN_SIZE](ProofOptions::MAX_SECURITY_LDE_DOMAIN_SIZE) elements is
+    ///   below security contributed by the queries. Such proofs would fall short of the target
+    ///   and would be rejected by the verifier.
+    /// * The target cannot be met with [MAX_NUM_QUERIES](ProofOptions::MAX_NUM_QUERIES)
+    ///   queries.
+    pub fn with_security<B: StarkField, H: Hasher>(
+        target_bits: u32,
+        conjectured: bool,
+        field_extension: FieldExtension,
+    ) -> Result<Self, SecurityError> {
+        if target_bits > H::COLLISION_RESISTANCE {
+            return Err(SecurityError::InsufficientCollisionResistance(
+                target_bits,
+                H::COLLISION_RESISTANCE,
+            ));
+        }
+
+        let estimate = |options: &Self| {
+            let lde_domain_size = Self::MAX_SECURITY_LDE_DOMAIN_SIZE as u64;
+            if conjectured {
+                SecurityEstimate::conjectured(options, B::MODULUS_BITS, lde_domain_size, 0)
+            } else {
+                SecurityEstimate::proven(options, B::MODULUS_BITS, lde_domain_size, 0)
+            }
+        };
+
+        // security is one bit lower than security contributed by the queries, and thus, the
+        // queries must contribute more bits than targeted
+        let (options, estimate) = (1..=Self::MAX_NUM_QUERIES)
+            .map(|num_queries| Self::new(num_queries, 8, 16, field_extension, 8, 256))
+            .map(|options| {
+                let estimate = estimate(&options);
+                (options, estimate)
+            })
+            .find(|(_, estimate)| {
+                estimate.query_security() + estimate.grinding_bits() > target_bits
+            })
+            .ok_or(SecurityError::InsufficientQuerySecurity(target_bits))?;
+
+        let query_security = estimate.query_security() + estimate.grinding_bits();
+        if estimate.field_security() < query_security {
+            return Err(SecurityError::InsufficientFieldSecurity(
+                query_security,
+                estimate.field_security(),
+            ));
+        }
+
+        Ok(options)
+    }
+
+    /// Returns a copy of these proof options with the transcript profile set to the specified
+    /// value.
+    pub fn with_transcript_profile(mut self, transcript_profile: TranscriptProfile) -> Self {
+        self.transcript_profile = transcript_profile;
+        self
+    }
+
+    /// Returns a copy of these proof options with explicit composition enabled for execution
+    /// traces of up to `max_trace_length` steps.
+    ///
+    /// For such traces, the prover sends coefficients of the constraint composition polynomial
+    /// instead of committing to its evaluations over the LDE domain, and the verifier evaluates
+    /// the polynomial at the out-of-domain point and at the query positions itself instead of
+    /// checking Merkle authentication paths against a commitment.
+    ///
+    /// # Panics
+    /// Panics if `max_trace_length` is not a power of two, is smaller than 8, or is greater than
+    /// [MAX_EXPLICIT_COMPOSITION_TRACE_LENGTH](ProofOptions::MAX_EXPLICIT_COMPOSITION_TRACE_LENGTH).
+    pub fn with_explicit_composition(mut self, max_trace_length: usize) -> Self {
+        assert!(
+            max_trace_length.is_power_of_two(),
+            "explicit composition threshold must be a power of 2"
+        );
+        assert!(
+            max_trace_length >= TraceInfo::MIN_TRACE_LENGTH,
+            "explicit composition threshold cannot be smaller than {}",
+            TraceInfo::MIN_TRACE_LENGTH
+        );
+        assert!(
+            max_trace_length <= Self::MAX_EXPLICIT_COMPOSITION_TRACE_LENGTH,
+            "explicit composition threshold cannot be greater than {}",
+            Self::MAX_EXPLICIT_COMPOSITION_TRACE_LENGTH
+        );
+        self.explicit_composition_threshold = max_trace_length.trailing_zeros() as u8;
+        self
+    }
+
+    /// Returns a copy of these proof options with the number of out-of-domain points sampled
+    /// during the DEEP step of the protocol set to the specified value.
+    ///
+    /// For each out-of-domain point z, the prover builds a separate constraint composition
+    /// polynomial from a separate set of random coefficients, and sends evaluations of all trace
+    /// polynomials at z and z * g, as well as evaluations of columns of the composition polynomial
+    /// for z at z^m. The DEEP composition polynomial is built as a random linear combination of
+    /// the quotients for all points.
+    ///
+    /// # Panics
+    /// Panics if `num_ood_points` is zero or greater than
+    /// [MAX_OOD_POINTS](ProofOptions::MAX_OOD_POINTS).
+    pub fn with_num_ood_points(mut self, num_ood_points: usize) -> Self {
+        assert!(
+            num_ood_points > 0,
+            "number of OOD points must be greater than 0"
+        );
+        assert!(
+            num_ood_points <= Self::MAX_OOD_POINTS,
+            "number of OOD points cannot be greater than {}",
+            Self::MAX_OOD_POINTS
+        );
+        self.num_ood_points = num_ood_points as u8;
+        self
+    }
+
+    /// Returns a copy of these proof options which instruct the prover to commit to a random
+    /// linear combination of all trace columns.
+    ///
+    /// After all trace segments have been committed to, a random value gamma is drawn from the
+    /// public coin, and the prover commits to the low-degree extension of the column
+    /// sum(gamma^i * T_i(x)), where T_i are the trace polynomials of all trace segments. The
+    /// evaluation of this column at each out-of-domain point as well as its openings at the query
+    /// positions are included in the proof, and the verifier checks that they are consistent with
+    /// the evaluations and openings of the individual trace columns.
+    pub fn with_combined_trace_column(mut self) -> Self {
+        self.combined_trace_column = true;
+        self
+    }
+
+    /// Returns a copy of these proof options which instruct the prover to absorb a digest of the
+    /// FRI remainder polynomial into the public coin before query positions are drawn.
+    ///
+    /// The digest is a hash of the coefficients of the remainder polynomial interpolated over the
+    /// subgroup of the same size as the remainder. The digest is included in the proof after the
+    /// FRI layer commitments, and the verifier recomputes it from the remainder included in the
+    /// proof. Thus, query positions depend on the remainder polynomial in its canonical form.
+    pub fn with_fri_remainder_digest(mut self) -> Self {
+        self.fri_remainder_digest = true;
+        self
+    }
+
+    /// Returns a copy of these proof options with the encoding of trace rows into leaves of trace
+    /// commitment Merkle trees set to the specified value.
+    ///
+    /// Whether the [Raw](LeafEncoding::Raw) encoding can be used depends on the width of the
+    /// execution trace and on the hash function; this is checked by the prover and the verifier.
+    ///
+    /// # Panics
+    /// Panics if the encoding is [Raw](LeafEncoding::Raw) and:
+    /// * the Merkle tree arity is not 2, or
+    /// * salted commitments were requested via [with_zk()](ProofOptions::with_zk).
+    pub fn with_leaf_encoding(mut self, leaf_encoding: LeafEncoding) -> Self {
+        assert!(
+            leaf_encoding == LeafEncoding::Hashed || self.merkle_arity == 2,
+            "raw leaf encoding can be used only with binary Merkle trees"
+        );
+        assert!(
+            leaf_encoding == LeafEncoding::Hashed || !self.zk,
+            "raw leaf encoding cannot be used with salted commitments"
+        );
+        self.leaf_encoding = leaf_encoding;
+        self
+    }
+
+    /// Returns a copy of these proof options with the arity of Merkle trees used for trace and
+    /// constraint commitments set to the specified value.
+    ///
+    /// Every internal node of a tree with arity 4 or 8 is a hash of 4 or 8 children, and thus,
+    /// such trees have 2 or 3 times fewer levels than binary trees. Authentication paths in such
+    /// trees contain 3 or 7 siblings per level, and thus, proofs are usually larger. Merkle trees
+    /// used for FRI layer commitments are always binary.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `merkle_arity` is not 2, 4, or 8.
+    /// * `merkle_arity` is not 2 and the leaf encoding is [Raw](LeafEncoding::Raw).
+    pub fn with_merkle_arity(mut self, merkle_arity: usize) -> Self {
+        assert!(
+            merkle_arity == 2 || merkle_arity == 4 || merkle_arity == 8,
+            "Merkle tree arity must be 2, 4, or 8"
+        );
+        assert!(
+            merkle_arity == 2 || self.leaf_encoding == LeafEncoding::Hashed,
+            "raw leaf encoding can be used only with binary Merkle trees"
+        );
+        self.merkle_arity = merkle_arity as u8;
+        self
+    }
+
+    /// Returns a copy of these proof options with salted trace and constraint commitments.
+    ///
+    /// For every commitment, the prover draws a random salt for every leaf of the commitment
+    /// Merkle tree, and builds the leaf by hashing the row digest together with the salt (see
+    /// [LeafEncoding::salted_leaf()]). Salts of leaves opened at query positions are included
+    /// in the proof, and the verifier uses them to rebuild the leaves. Commitments to FRI layers
+    /// are not salted.
+    ///
+    /// # Panics
+    /// Panics if the leaf encoding is [Raw](LeafEncoding::Raw).
+    pub fn with_zk(mut self) -> Self {
+        assert!(
+            self.leaf_encoding == LeafEncoding::Hashed,
+            "raw leaf encoding cannot be used with salted commitments"
+        );
+        self.zk = true;
+        self
+    }
+
+    /// Returns a copy of these proof options with the strategy used to search for the query seed
+    /// proof-of-work nonce set to the specified value.
+    ///
+    /// # Panics
+    /// Panics if the strategy is [TimeBoxed](GrindingStrategy::TimeBoxed) and the number of
+    /// queries together with `fallback_extra_queries` is greater than 128.
+    pub fn with_grinding_strategy(mut self, grinding_strategy: GrindingStrategy) -> Self {
+        if let GrindingStrategy::TimeBoxed {
+            fallback_extra_queries,
+            ..
+        } = grinding_strategy
+        {
+            assert!(
+                self.num_queries() + fallback_extra_queries <= 128,
+                "number of queries including fallback extra queries cannot be greater than 128"
+            );
+        }
+        self.grinding_strategy = grinding_strategy;
+        self
+    }
+
+    /// Returns a copy of these proof options with the hash function used to compute the query
+    /// seed proof-of-work set to the specified function.
+    ///
+    /// In this case, the prover grinds on a challenge derived from the public coin rather than
+    /// on the public coin itself, and thus, the grinding hash function can be chosen
+    /// independently of the hash function used by the rest of the protocol (e.g., BLAKE3 can be
+    /// used for grinding in proofs which use Rescue Prime elsewhere). See
+    /// [PowPuzzle](crate::PowPuzzle) for details.
+    pub fn with_grinding_hash(mut self, grinding_hash: HashFunctionId) -> Self {
+        self.grinding_hash = Some(grinding_hash);
+        self
+    }
+
+    /// Returns a copy of these proof options with the query seed proof-of-work nonce widened
+    /// from 64 to 128 bits.
+    ///
+    /// # Panics
+    /// Panics if the grinding hash function has not been set via
+    /// [with_grinding_hash()](ProofOptions::with_grinding_hash).
+    pub fn with_wide_grinding_nonce(mut self) -> Self {
+        assert!(
+            self.grinding_hash.is_some(),
+            "wide grinding nonce can be used only with a grinding hash function"
+        );
+        self.wide_grinding_nonce = true;
+        self
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -161,6 +585,71 @@ impl ProofOptions {
         self.grinding_factor as u32
     }
 
+    /// Returns the strategy used by the prover to search for the query seed proof-of-work nonce.
+    pub fn grinding_strategy(&self) -> GrindingStrategy {
+        self.grinding_strategy
+    }
+
+    /// Returns true if the prover searches for the query seed proof-of-work nonce using the
+    /// [TimeBoxed](GrindingStrategy::TimeBoxed) strategy.
+    pub fn uses_time_boxed_grinding(&self) -> bool {
+        matches!(self.grinding_strategy, GrindingStrategy::TimeBoxed { .. })
+    }
+
+    /// Returns the hash function used to compute the query seed proof-of-work, or None if
+    /// proof-of-work is computed with the hash function of the protocol.
+    pub fn grinding_hash(&self) -> Option<HashFunctionId> {
+        self.grinding_hash
+    }
+
+    /// Returns true if the query seed proof-of-work nonce is 128 bits wide.
+    pub fn uses_wide_grinding_nonce(&self) -> bool {
+        self.wide_grinding_nonce
+    }
+
+    /// Returns the number of bytes in the query seed proof-of-work nonce; this is 16 for wide
+    /// nonces and 8 otherwise.
+    pub fn grinding_nonce_bytes(&self) -> usize {
+        if self.wide_grinding_nonce {
+            16
+        } else {
+            8
+        }
+    }
+
+    /// Returns the smallest number of proof-of-work bits the query seed nonce of a valid proof
+    /// can yield.
+    ///
+    /// This is the grinding factor for the [FixedBits](GrindingStrategy::FixedBits) strategy.
+    /// For the [TimeBoxed](GrindingStrategy::TimeBoxed) strategy, this is the grinding factor
+    /// reduced by the number of bits which can be compensated by the fallback extra queries.
+    pub fn min_grinding_factor(&self) -> u32 {
+        match self.grinding_strategy {
+            GrindingStrategy::FixedBits => self.grinding_factor(),
+            GrindingStrategy::TimeBoxed {
+                fallback_extra_queries,
+                ..
+            } => {
+                let compensated_bits = get_security_per_query(self) * fallback_extra_queries as u32;
+                self.grinding_factor().saturating_sub(compensated_bits)
+            }
+        }
+    }
+
+    /// Returns the number of queries which must be drawn in addition to
+    /// [num_queries()](ProofOptions::num_queries) when the query seed nonce yields `pow_bits`
+    /// proof-of-work bits.
+    ///
+    /// Each query adds `log2(blowup_factor)` bits of security to a proof, and thus, every such
+    /// number of bits missing from the grinding factor requires one extra query. The conjectured
+    /// security of a proof with the extra queries is at least the security of a proof with the
+    /// full grinding factor.
+    pub fn num_extra_queries(&self, pow_bits: u32) -> usize {
+        let missing_bits = self.grinding_factor().saturating_sub(pow_bits);
+        let security_per_query = get_security_per_query(self);
+        ((missing_bits + security_per_query - 1) / security_per_query) as usize
+    }
+
     /// Specifies whether composition polynomial should be constructed in an extension field
     /// of STARK protocol.
     ///
@@ -170,6 +659,65 @@ impl ProofOptions {
         self.field_extension
     }
 
+    /// Returns the transcript profile which defines how query positions are derived from the
+    /// proof-of-work nonce.
+    pub fn transcript_profile(&self) -> TranscriptProfile {
+        self.transcript_profile
+    }
+
+    /// Returns the number of out-of-domain points sampled during the DEEP step of the protocol.
+    ///
+    /// Each additional point increases security of the DEEP/ALI step of the protocol, and thus,
+    /// may increase the security level of the proof (see
+    /// [StarkProof::security_level()](crate::proof::StarkProof::security_level)); it also
+    /// increases proof size and the amount of work performed by the prover and the verifier.
+    pub fn num_ood_points(&self) -> usize {
+        self.num_ood_points as usize
+    }
+
+    /// Returns the largest execution trace length for which explicit composition is used, or
+    /// None if explicit composition is disabled.
+    pub fn explicit_composition_threshold(&self) -> Option<usize> {
+        match self.explicit_composition_threshold {
+            0 => None,
+            threshold => Some(1 << threshold),
+        }
+    }
+
+    /// Returns true if coefficients of the constraint composition polynomial are sent explicitly
+    /// (rather than committed to) for an execution trace of the specified length.
+    pub fn uses_explicit_composition(&self, trace_length: usize) -> bool {
+        self.explicit_composition_threshold()
+            .map_or(false, |threshold| trace_length <= threshold)
+    }
+
+    /// Returns true if the prover commits to a random linear combination of all trace columns as
+    /// a single extra column of the trace commitment.
+    pub fn uses_combined_trace_column(&self) -> bool {
+        self.combined_trace_column
+    }
+
+    /// Returns true if a digest of the FRI remainder polynomial is absorbed into the public coin
+    /// before query positions are drawn.
+    pub fn uses_fri_remainder_digest(&self) -> bool {
+        self.fri_remainder_digest
+    }
+
+    /// Returns the encoding of trace rows into leaves of trace commitment Merkle trees.
+    pub fn leaf_encoding(&self) -> LeafEncoding {
+        self.leaf_encoding
+    }
+
+    /// Returns the arity of Merkle trees used for trace and constraint commitments.
+    pub fn merkle_arity(&self) -> usize {
+        self.merkle_arity as usize
+    }
+
+    /// Returns true if leaves of trace and constraint commitments are salted.
+    pub fn is_zk(&self) -> bool {
+        self.zk
+    }
+
     /// Returns the offset by which the low-degree extension domain is shifted in relation to the
     /// trace domain.
     ///
@@ -184,17 +732,85 @@ impl ProofOptions {
         let max_remainder_size = 2usize.pow(self.fri_max_remainder_size as u32);
         FriOptions::new(self.blowup_factor(), folding_factor, max_remainder_size)
     }
+
+    /// Returns conjectured security level (in bits) of proofs generated with these options for a
+    /// computation over a base field with a `base_field_bits`-bit modulus and an LDE domain of
+    /// the specified size, using a hash function with the specified collision resistance.
+    ///
+    /// This is the same security level as the one returned by
+    /// [Air::conjectured_security_level()](crate::Air::conjectured_security_level); unlike the
+    /// latter, it does not require an instance of the AIR, and thus, it can be used to choose
+    /// proof options before the computation is instantiated.
+    pub fn conjectured_security_level(
+        &self,
+        base_field_bits: u32,
+        lde_domain_size: usize,
+        collision_resistance: u32,
+    ) -> u32 {
+        get_conjectured_security(
+            self,
+            base_field_bits,
+            lde_domain_size as u64,
+            collision_resistance,
+        )
+    }
 }
 
 impl Serializable for ProofOptions {
     /// Serializes `self` and writes the resulting bytes into the `target`.
+    ///
+    /// The number of OOD points (minus one) is stored in the upper 2 bits of the grinding factor
+    /// byte, the transcript profile is stored in the upper 4 bits of the field extension byte,
+    /// and the explicit composition threshold is stored in the upper 4 bits of the FRI max
+    /// remainder size byte; the combined trace column flag and the leaf encoding are stored in the
+    /// two most significant bits of the FRI folding factor byte. Thus, options with a single OOD
+    /// point, the default profile, hashed leaves, and with explicit composition and the combined
+    /// trace column disabled serialize the same way as options which predate these parameters.
+    ///
+    /// The FRI remainder digest flag is stored in the fourth least significant bit of the field
+    /// extension byte, and thus, options without the digest serialize as before as well. The
+    /// Merkle tree arity is stored as log2(arity) - 1 in the two most significant bits of the
+    /// field extension byte, and thus, options with binary trees serialize as before too.
+    ///
+    /// The [TimeBoxed](GrindingStrategy::TimeBoxed) grinding strategy is flagged by the third
+    /// most significant bit of the FRI folding factor byte, and its parameters are appended after
+    /// all other options.
+    ///
+    /// The grinding hash function is flagged by the least significant bit of the FRI folding
+    /// factor byte (the folding factor is at least 4, and thus, this bit is otherwise unused);
+    /// the identifier of the function and the nonce width in bytes are appended after the
+    /// parameters of the grinding strategy.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         target.write_u8(self.num_queries);
         target.write_u8(self.blowup_factor);
-        target.write_u8(self.grinding_factor);
-        target.write(self.field_extension);
-        target.write_u8(self.fri_folding_factor);
-        target.write_u8(self.fri_max_remainder_size);
+        target.write_u8((self.num_ood_points - 1) << 6 | self.grinding_factor);
+        target.write_u8(
+            (self.merkle_arity.trailing_zeros() as u8 - 1) << 6
+                | (self.transcript_profile as u8) << 4
+                | (self.fri_remainder_digest as u8) << 3
+                | (self.zk as u8) << 2
+                | self.field_extension as u8,
+        );
+        target.write_u8(
+            (self.combined_trace_column as u8) << 7
+                | (self.leaf_encoding as u8) << 6
+                | (self.uses_time_boxed_grinding() as u8) << 5
+                | self.fri_folding_factor
+                | self.grinding_hash.is_some() as u8,
+        );
+        target.write_u8(self.explicit_composition_threshold << 4 | self.fri_max_remainder_size);
+        if let GrindingStrategy::TimeBoxed {
+            max_millis,
+            fallback_extra_queries,
+        } = self.grinding_strategy
+        {
+            target.write_u32(max_millis);
+            target.write_u8(fallback_extra_queries as u8);
+        }
+        if let Some(grinding_hash) = self.grinding_hash {
+            grinding_hash.write_into(target);
+            target.write_u8(self.grinding_nonce_bytes() as u8);
+        }
     }
 }
 
@@ -204,14 +820,187 @@ impl Deserializable for ProofOptions {
     /// # Errors
     /// Returns an error of a valid proof options could not be read from the specified `source`.
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        Ok(ProofOptions::new(
-            source.read_u8()? as usize,
-            source.read_u8()? as usize,
-            source.read_u8()? as u32,
-            FieldExtension::read_from(source)?,
-            source.read_u8()? as usize,
-            2usize.pow(source.read_u8()? as u32),
-        ))
+        let num_queries = source.read_u8()? as usize;
+        let blowup_factor = source.read_u8()? as usize;
+        let grinding_and_ood_points = source.read_u8()?;
+        let grinding_factor = (grinding_and_ood_points & 0x3f) as u32;
+        let num_ood_points = (grinding_and_ood_points >> 6) as usize + 1;
+        let extension_and_profile = source.read_u8()?;
+        let field_extension = FieldExtension::from_u8(extension_and_profile & 0x3)?;
+        let zk = (extension_and_profile >> 2) & 1 == 1;
+        let fri_remainder_digest = (extension_and_profile >> 3) & 1 == 1;
+        let transcript_profile = TranscriptProfile::from_u8((extension_and_profile >> 4) & 0x3)?;
+        let merkle_arity = 2usize << (extension_and_profile >> 6);
+        if merkle_arity > 8 {
+            return Err(DeserializationError::InvalidValue(format!(
+                "Merkle tree arity must be 2, 4, or 8, but was {merkle_arity}"
+            )));
+        }
+        let folding_factor_and_flags = source.read_u8()?;
+        let fri_folding_factor = (folding_factor_and_flags & 0x1c) as usize;
+        let time_boxed = (folding_factor_and_flags >> 5) & 1 == 1;
+        let grinding_hash = folding_factor_and_flags & 1 == 1;
+        if folding_factor_and_flags & 2 != 0 {
+            return Err(DeserializationError::InvalidValue(
+                "FRI folding factor byte has an unused bit set".to_string(),
+            ));
+        }
+        let combined_trace_column = folding_factor_and_flags >> 7 == 1;
+        let leaf_encoding = LeafEncoding::from_u8((folding_factor_and_flags >> 6) & 1)?;
+        if merkle_arity != 2 && leaf_encoding == LeafEncoding::Raw {
+            return Err(DeserializationError::InvalidValue(
+                "raw leaf encoding can be used only with binary Merkle trees".to_string(),
+            ));
+        }
+        if zk && leaf_encoding == LeafEncoding::Raw {
+            return Err(DeserializationError::InvalidValue(
+                "raw leaf encoding cannot be used with salted commitments".to_string(),
+            ));
+        }
+        let remainder_and_threshold = source.read_u8()?;
+        let mut options = ProofOptions::new(
+            num_queries,
+            blowup_factor,
+            grinding_factor,
+            field_extension,
+            fri_folding_factor,
+            2usize.pow((remainder_and_threshold & 0xf) as u32),
+        )
+        .with_transcript_profile(transcript_profile)
+        .with_num_ood_points(num_ood_points)
+        .with_leaf_encoding(leaf_encoding)
+        .with_merkle_arity(merkle_arity);
+        if combined_trace_column {
+            options = options.with_combined_trace_column();
+        }
+        if fri_remainder_digest {
+            options = options.with_fri_remainder_digest();
+        }
+        if zk {
+            options = options.with_zk();
+        }
+        if time_boxed {
+            let max_millis = source.read_u32()?;
+            let fallback_extra_queries = source.read_u8()? as usize;
+            if num_queries + fallback_extra_queries > 128 {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "number of queries including fallback extra queries cannot be greater than \
+                    128, but was {}",
+                    num_queries + fallback_extra_queries
+                )));
+            }
+            options = options.with_grinding_strategy(GrindingStrategy::TimeBoxed {
+                max_millis,
+                fallback_extra_queries,
+            });
+        }
+        if grinding_hash {
+            options = options.with_grinding_hash(HashFunctionId::read_from(source)?);
+            match source.read_u8()? {
+                8 => (),
+                16 => options = options.with_wide_grinding_nonce(),
+                value => {
+                    return Err(DeserializationError::InvalidValue(format!(
+                        "grinding nonce must be 8 or 16 bytes wide, but was {value} bytes"
+                    )))
+                }
+            }
+        }
+
+        let threshold = remainder_and_threshold >> 4;
+        if threshold == 0 {
+            return Ok(options);
+        }
+        let max_threshold = Self::MAX_EXPLICIT_COMPOSITION_TRACE_LENGTH.trailing_zeros() as u8;
+        let min_threshold = TraceInfo::MIN_TRACE_LENGTH.trailing_zeros() as u8;
+        if threshold < min_threshold || threshold > max_threshold {
+            return Err(DeserializationError::InvalidValue(format!(
+                "explicit composition threshold must be between 2^{min_threshold} and \
+                2^{max_threshold}, but was 2^{threshold}"
+            )));
+        }
+        Ok(options.with_explicit_composition(1 << threshold))
+    }
+}
+
+// LEAF ENCODING IMPLEMENTATION
+// ================================================================================================
+
+impl LeafEncoding {
+    /// Returns true if rows of the specified number of bytes can be encoded into leaves of Merkle
+    /// trees built with the hash function `H`.
+    ///
+    /// Hashed rows are supported for all hash functions. Raw rows are supported only if they are
+    /// not longer than a digest of `H`; in addition, digests of algebraic hash functions must
+    /// consist of whole base field elements so that the elements of a row are placed into the
+    /// elements of a digest as is.
+    pub fn is_supported<H: ElementHasher>(&self, row_bytes: usize) -> bool {
+        match self {
+            Self::Hashed => true,
+            Self::Raw => {
+                let digest = H::Digest::default();
+                let digest_bytes = digest.to_bytes().len();
+                let is_aligned = H::digest_as_elements(&digest).map_or(true, |elements| {
+                    elements.len() * H::BaseField::ELEMENT_BYTES == digest_bytes
+                });
+                is_aligned && row_bytes <= digest_bytes
+            }
+        }
+    }
+
+    /// Returns the leaf into which the specified trace row is encoded.
+    ///
+    /// # Panics
+    /// Panics if this encoding is not supported for rows of this length and the hash function `H`.
+    pub fn encode_row<H, E>(&self, row: &[E]) -> H::Digest
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+        E: FieldElement,
+    {
+        match self {
+            Self::Hashed => H::hash_elements(row),
+            Self::Raw => {
+                let mut bytes = Vec::with_capacity(row.len() * E::ELEMENT_BYTES);
+                bytes.write(row);
+                Self::raw_leaf::<H>(&bytes).expect("failed to encode a trace row into a raw leaf")
+            }
+        }
+    }
+
+    /// Returns a raw leaf built from the specified row bytes padded with zeros to the size of a
+    /// digest of the hash function `H`.
+    ///
+    /// # Errors
+    /// Returns an error if the row bytes are longer than a digest, or if the padded bytes cannot
+    /// be read as a digest of `H`.
+    pub fn raw_leaf<H: Hasher>(row_bytes: &[u8]) -> Result<H::Digest, DeserializationError> {
+        let mut bytes = H::Digest::default().to_bytes();
+        if row_bytes.len() > bytes.len() {
+            return Err(DeserializationError::InvalidValue(format!(
+                "a raw leaf cannot contain more than {} bytes, but was {}",
+                bytes.len(),
+                row_bytes.len()
+            )));
+        }
+        bytes[..row_bytes.len()].copy_from_slice(row_bytes);
+        H::Digest::read_from(&mut SliceReader::new(&bytes))
+    }
+
+    /// Returns a leaf of a salted commitment (see [ProofOptions::with_zk()]) built from the
+    /// digest of a row and the salt of the leaf.
+    pub fn salted_leaf<H: Hasher>(row_digest: H::Digest, salt: H::Digest) -> H::Digest {
+        H::merge(&[row_digest, salt])
+    }
+
+    /// Returns a leaf encoding enum encoded by the specified value.
+    fn from_u8(value: u8) -> Result<Self, DeserializationError> {
+        match value {
+            0 => Ok(Self::Hashed),
+            1 => Ok(Self::Raw),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "value {value} cannot be deserialized as LeafEncoding enum"
+            ))),
+        }
     }
 }
 
@@ -232,6 +1021,18 @@ impl FieldExtension {
             Self::Cubic => 3,
         }
     }
+
+    /// Returns a field extension enum encoded by the specified value.
+    fn from_u8(value: u8) -> Result<Self, DeserializationError> {
+        match value {
+            1 => Ok(FieldExtension::None),
+            2 => Ok(FieldExtension::Quadratic),
+            3 => Ok(FieldExtension::Cubic),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "value {value} cannot be deserialized as FieldExtension enum"
+            ))),
+        }
+    }
 }
 
 impl Serializable for FieldExtension {
@@ -244,13 +1045,6 @@ impl Serializable for FieldExtension {
 impl Deserializable for FieldExtension {
     /// Reads a field extension enum from the specified `source`.
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        match source.read_u8()? {
-            1 => Ok(FieldExtension::None),
-            2 => Ok(FieldExtension::Quadratic),
-            3 => Ok(FieldExtension::Cubic),
-            value => Err(DeserializationError::InvalidValue(format!(
-                "value {value} cannot be deserialized as FieldExtension enum"
-            ))),
-        }
+        Self::from_u8(source.read_u8()?)
     }
 }
@@ -0,0 +1,391 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::StarkProof;
+use crate::ArchiveError;
+use core::convert::TryInto;
+use utils::{collections::Vec, string::ToString};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Magic bytes with which every archival envelope starts.
+pub const ARCHIVE_MAGIC: [u8; 8] = *b"WFPROOF\0";
+
+/// Format version of archival envelopes written by this library.
+pub const ARCHIVE_FORMAT_VERSION: u16 = 1;
+
+/// Number of bytes in the fixed-layout part of the envelope header; the header is followed by
+/// a signature of variable length (which may be empty), and then by the proof itself.
+pub const ARCHIVE_HEADER_SIZE: usize = 74;
+
+// byte offsets of the header fields; these must never change for a given format version
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 8;
+const FIELD_ID_OFFSET: usize = 10;
+const HASH_ID_OFFSET: usize = 11;
+const TRACE_WIDTH_OFFSET: usize = 12;
+const TRACE_LENGTH_OFFSET: usize = 16;
+const CREATED_AT_OFFSET: usize = 24;
+const PROOF_DIGEST_OFFSET: usize = 32;
+const PROOF_SIZE_OFFSET: usize = 64;
+const SIGNATURE_SIZE_OFFSET: usize = 72;
+
+// ARCHIVE METADATA
+// ================================================================================================
+/// Metadata which is recorded in the header of an [ArchivalEnvelope] but cannot be derived from
+/// the proof itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveMetadata {
+    /// Identifier of the hash function with which the proof was generated; the meaning of the
+    /// identifier is defined by the application.
+    pub hash_id: u8,
+    /// Time at which the proof was created, in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+// ARCHIVE HEADER
+// ================================================================================================
+/// Fixed-layout header of an [ArchivalEnvelope].
+///
+/// The header occupies the first [ARCHIVE_HEADER_SIZE] bytes of a serialized envelope; all
+/// integers are encoded in little-endian byte order:
+///
+/// | Offset | Size | Field                                                              |
+/// | -----: | ---: | ------------------------------------------------------------------ |
+/// |      0 |    8 | magic bytes `WFPROOF\0`                                            |
+/// |      8 |    2 | format version                                                     |
+/// |     10 |    1 | field id (bit length of the base field modulus)                    |
+/// |     11 |    1 | hash id (as specified via [ArchiveMetadata])                       |
+/// |     12 |    4 | execution trace width                                              |
+/// |     16 |    8 | execution trace length                                             |
+/// |     24 |    8 | creation timestamp (seconds since the Unix epoch)                  |
+/// |     32 |   32 | BLAKE3 digest of the serialized proof                              |
+/// |     64 |    8 | size of the serialized proof in bytes                              |
+/// |     72 |    2 | size of the signature in bytes (zero if the envelope is unsigned)  |
+///
+/// The header is followed by the signature (if any), and then by the serialized proof. The
+/// signature is computed over the first 72 bytes of the envelope, i.e., over all header fields
+/// except for the size of the signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    version: u16,
+    field_id: u8,
+    hash_id: u8,
+    trace_width: u32,
+    trace_length: u64,
+    created_at: u64,
+    proof_digest: [u8; 32],
+    proof_size: u64,
+    signature_size: u16,
+}
+
+impl ArchiveHeader {
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the format version of the envelope.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the identifier of the base field of the proof, defined as the bit length of the
+    /// field modulus.
+    pub fn field_id(&self) -> u8 {
+        self.field_id
+    }
+
+    /// Returns the identifier of the hash function with which the proof was generated.
+    pub fn hash_id(&self) -> u8 {
+        self.hash_id
+    }
+
+    /// Returns the width of the execution trace described by the proof.
+    pub fn trace_width(&self) -> usize {
+        self.trace_width as usize
+    }
+
+    /// Returns the length of the execution trace described by the proof.
+    pub fn trace_length(&self) -> u64 {
+        self.trace_length
+    }
+
+    /// Returns the time at which the proof was created, in seconds since the Unix epoch.
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Returns the BLAKE3 digest of the serialized proof.
+    pub fn proof_digest(&self) -> &[u8; 32] {
+        &self.proof_digest
+    }
+
+    /// Returns the size of the serialized proof in bytes.
+    pub fn proof_size(&self) -> u64 {
+        self.proof_size
+    }
+
+    /// Returns the size of the signature in bytes; this is zero if the envelope is not signed.
+    pub fn signature_size(&self) -> usize {
+        self.signature_size as usize
+    }
+
+    /// Returns the total size of an envelope with this header in bytes.
+    pub fn envelope_size(&self) -> u128 {
+        ARCHIVE_HEADER_SIZE as u128 + self.signature_size as u128 + self.proof_size as u128
+    }
+
+    // SERIALIZATION / DESERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes this header into an array of bytes.
+    pub fn to_bytes(&self) -> [u8; ARCHIVE_HEADER_SIZE] {
+        let mut result = [0; ARCHIVE_HEADER_SIZE];
+        result[MAGIC_OFFSET..VERSION_OFFSET].copy_from_slice(&ARCHIVE_MAGIC);
+        result[VERSION_OFFSET..FIELD_ID_OFFSET].copy_from_slice(&self.version.to_le_bytes());
+        result[FIELD_ID_OFFSET] = self.field_id;
+        result[HASH_ID_OFFSET] = self.hash_id;
+        result[TRACE_WIDTH_OFFSET..TRACE_LENGTH_OFFSET]
+            .copy_from_slice(&self.trace_width.to_le_bytes());
+        result[TRACE_LENGTH_OFFSET..CREATED_AT_OFFSET]
+            .copy_from_slice(&self.trace_length.to_le_bytes());
+        result[CREATED_AT_OFFSET..PROOF_DIGEST_OFFSET]
+            .copy_from_slice(&self.created_at.to_le_bytes());
+        result[PROOF_DIGEST_OFFSET..PROOF_SIZE_OFFSET].copy_from_slice(&self.proof_digest);
+        result[PROOF_SIZE_OFFSET..SIGNATURE_SIZE_OFFSET]
+            .copy_from_slice(&self.proof_size.to_le_bytes());
+        result[SIGNATURE_SIZE_OFFSET..ARCHIVE_HEADER_SIZE]
+            .copy_from_slice(&self.signature_size.to_le_bytes());
+        result
+    }
+
+    /// Reads a header from the start of the specified `source`; bytes following the header are
+    /// ignored.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * `source` is shorter than [ARCHIVE_HEADER_SIZE] bytes.
+    /// * `source` does not start with [ARCHIVE_MAGIC] bytes.
+    /// * The format version of the header is not supported.
+    pub fn read_from(source: &[u8]) -> Result<Self, ArchiveError> {
+        if source.len() < ARCHIVE_HEADER_SIZE {
+            return Err(ArchiveError::Truncated(ARCHIVE_HEADER_SIZE, source.len()));
+        }
+        if source[MAGIC_OFFSET..VERSION_OFFSET] != ARCHIVE_MAGIC {
+            return Err(ArchiveError::InvalidMagic);
+        }
+        let version = u16::from_le_bytes(read_array(source, VERSION_OFFSET));
+        if version != ARCHIVE_FORMAT_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+
+        Ok(ArchiveHeader {
+            version,
+            field_id: source[FIELD_ID_OFFSET],
+            hash_id: source[HASH_ID_OFFSET],
+            trace_width: u32::from_le_bytes(read_array(source, TRACE_WIDTH_OFFSET)),
+            trace_length: u64::from_le_bytes(read_array(source, TRACE_LENGTH_OFFSET)),
+            created_at: u64::from_le_bytes(read_array(source, CREATED_AT_OFFSET)),
+            proof_digest: read_array(source, PROOF_DIGEST_OFFSET),
+            proof_size: u64::from_le_bytes(read_array(source, PROOF_SIZE_OFFSET)),
+            signature_size: u16::from_le_bytes(read_array(source, SIGNATURE_SIZE_OFFSET)),
+        })
+    }
+}
+
+// ARCHIVAL ENVELOPE
+// ================================================================================================
+/// A serialized STARK proof wrapped into a self-describing, format-stable envelope intended for
+/// long-term archival.
+///
+/// The envelope starts with a fixed-layout [ArchiveHeader] which records basic properties of the
+/// proof (i.e., base field, hash function, trace shape, and creation time) together with a digest
+/// of the proof. The header can be parsed without this library by following the byte offsets
+/// documented for [ArchiveHeader], even if serialization format of the proof itself changes.
+///
+/// Optionally, the envelope can be signed by its creator: the signature is computed over the
+/// header fields, and thus, it also covers the proof digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivalEnvelope {
+    header: ArchiveHeader,
+    signature: Vec<u8>,
+    proof_bytes: Vec<u8>,
+}
+
+impl ArchivalEnvelope {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Wraps the specified proof into an archival envelope.
+    ///
+    /// If `signer` is provided, it is invoked with the signed part of the serialized header (see
+    /// [ArchiveHeader]), and the returned bytes are recorded in the envelope as the signature of
+    /// its creator.
+    ///
+    /// # Panics
+    /// Panics if the signature returned by `signer` is longer than 65535 bytes.
+    pub fn seal<S>(proof: &StarkProof, metadata: ArchiveMetadata, signer: Option<S>) -> Self
+    where
+        S: Fn(&[u8]) -> Vec<u8>,
+    {
+        let proof_bytes = proof.to_bytes();
+        let trace_info = proof.get_trace_info();
+        let mut header = ArchiveHeader {
+            version: ARCHIVE_FORMAT_VERSION,
+            field_id: proof.context.num_modulus_bits() as u8,
+            hash_id: metadata.hash_id,
+            trace_width: trace_info.width() as u32,
+            trace_length: trace_info.length() as u64,
+            created_at: metadata.created_at,
+            proof_digest: *blake3::hash(&proof_bytes).as_bytes(),
+            proof_size: proof_bytes.len() as u64,
+            signature_size: 0,
+        };
+
+        let signature = match signer {
+            Some(signer) => {
+                let signature = signer(&header.to_bytes()[..SIGNATURE_SIZE_OFFSET]);
+                assert!(
+                    signature.len() <= u16::MAX as usize,
+                    "signature cannot be longer than {} bytes",
+                    u16::MAX
+                );
+                header.signature_size = signature.len() as u16;
+                signature
+            }
+            None => Vec::new(),
+        };
+
+        ArchivalEnvelope {
+            header,
+            signature,
+            proof_bytes,
+        }
+    }
+
+    /// Unwraps the proof from the specified serialized envelope.
+    ///
+    /// If `verifier` is provided, it is invoked with the signed part of the serialized header (see
+    /// [ArchiveHeader]) and the signature of the envelope, and must return true for the signature to be accepted. If `verifier` is not
+    /// provided, the signature (if any) is not checked.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The header of the envelope is not valid, or the envelope is truncated or contains
+    ///   trailing bytes.
+    /// * `verifier` is provided but the envelope is not signed, or `verifier` rejects the
+    ///   signature.
+    /// * The digest of the proof does not match the digest recorded in the header.
+    /// * The proof cannot be deserialized, or its trace shape does not match the trace shape
+    ///   recorded in the header.
+    pub fn unseal<V>(source: &[u8], verifier: Option<V>) -> Result<StarkProof, ArchiveError>
+    where
+        V: Fn(&[u8], &[u8]) -> bool,
+    {
+        let envelope = Self::from_bytes(source)?;
+
+        if let Some(verifier) = verifier {
+            if envelope.signature.is_empty() {
+                return Err(ArchiveError::MissingSignature);
+            }
+            if !verifier(&source[..SIGNATURE_SIZE_OFFSET], &envelope.signature) {
+                return Err(ArchiveError::InvalidSignature);
+            }
+        }
+
+        let proof = StarkProof::from_bytes(&envelope.proof_bytes)
+            .map_err(|err| ArchiveError::InvalidProof(err.to_string()))?;
+        let trace_info = proof.get_trace_info();
+        if trace_info.width() != envelope.header.trace_width()
+            || trace_info.length() as u64 != envelope.header.trace_length()
+        {
+            return Err(ArchiveError::TraceShapeMismatch);
+        }
+
+        Ok(proof)
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the header of this envelope.
+    pub fn header(&self) -> &ArchiveHeader {
+        &self.header
+    }
+
+    /// Returns the signature of this envelope, or None if the envelope is not signed.
+    pub fn signature(&self) -> Option<&[u8]> {
+        if self.signature.is_empty() {
+            None
+        } else {
+            Some(&self.signature)
+        }
+    }
+
+    /// Returns the serialized proof contained in this envelope.
+    pub fn proof_bytes(&self) -> &[u8] {
+        &self.proof_bytes
+    }
+
+    // SERIALIZATION / DESERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes this envelope into a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result =
+            Vec::with_capacity(ARCHIVE_HEADER_SIZE + self.signature.len() + self.proof_bytes.len());
+        result.extend_from_slice(&self.header.to_bytes());
+        result.extend_from_slice(&self.signature);
+        result.extend_from_slice(&self.proof_bytes);
+        result
+    }
+
+    /// Returns an envelope read from the specified `source`.
+    ///
+    /// The proof contained in the envelope is checked against the digest recorded in the header,
+    /// but it is not deserialized; use [unseal()](ArchivalEnvelope::unseal) to obtain the proof.
+    ///
+    /// # Errors
+    /// Returns an error if the header of the envelope is not valid, if the envelope is truncated
+    /// or contains trailing bytes, or if the digest of the proof does not match the digest
+    /// recorded in the header.
+    pub fn from_bytes(source: &[u8]) -> Result<Self, ArchiveError> {
+        let header = ArchiveHeader::read_from(source)?;
+
+        let expected_size = header.envelope_size();
+        if (source.len() as u128) < expected_size {
+            let expected_size = expected_size.try_into().unwrap_or(usize::MAX);
+            return Err(ArchiveError::Truncated(expected_size, source.len()));
+        }
+        if (source.len() as u128) > expected_size {
+            return Err(ArchiveError::TrailingBytes(
+                source.len() - expected_size as usize,
+            ));
+        }
+
+        let proof_offset = ARCHIVE_HEADER_SIZE + header.signature_size();
+        let signature = source[ARCHIVE_HEADER_SIZE..proof_offset].to_vec();
+        let proof_bytes = source[proof_offset..].to_vec();
+        if blake3::hash(&proof_bytes).as_bytes() != header.proof_digest() {
+            return Err(ArchiveError::DigestMismatch);
+        }
+
+        Ok(ArchivalEnvelope {
+            header,
+            signature,
+            proof_bytes,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Reads an array of `N` bytes from `source` starting at the specified offset.
+fn read_array<const N: usize>(source: &[u8], offset: usize) -> [u8; N] {
+    source[offset..offset + N]
+        .try_into()
+        .expect("header field out of bounds")
+}
@@ -0,0 +1,122 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{read_supported_format_version, Context, StarkProof, PROOF_FORMAT_VERSION};
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable, SliceReader,
+};
+
+// BATCH STARK PROOF
+// ================================================================================================
+/// A batch of STARK proofs generated for many instances of the same computation.
+///
+/// All proofs in a batch share the same [Context] (i.e., they were generated for traces of the
+/// same shape using the same proof options), but each proof attests to the execution of the
+/// computation against its own public inputs.
+///
+/// When serialized via [to_bytes()](BatchStarkProof::to_bytes), the format version and the
+/// shared context are written only once, followed by the number of proofs and the remaining
+/// components of every proof. Thus, a serialized batch is smaller than the sum of sizes of the
+/// individually serialized proofs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BatchStarkProof {
+    context: Context,
+    proofs: Vec<StarkProof>,
+}
+
+impl BatchStarkProof {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a batch consisting of the specified proofs.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `proofs` is empty.
+    /// * Contexts of the specified proofs are not all the same.
+    pub fn new(proofs: Vec<StarkProof>) -> Self {
+        assert!(
+            !proofs.is_empty(),
+            "a batch must contain at least one proof"
+        );
+        let context = proofs[0].context.clone();
+        assert!(
+            proofs.iter().all(|proof| proof.context == context),
+            "all proofs in a batch must have the same context"
+        );
+        assert!(
+            proofs.len() <= u32::MAX as usize,
+            "a batch cannot contain more than {} proofs",
+            u32::MAX
+        );
+        BatchStarkProof { context, proofs }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the context shared by all proofs in this batch.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Returns the number of proofs in this batch.
+    pub fn num_proofs(&self) -> usize {
+        self.proofs.len()
+    }
+
+    /// Returns the proofs in this batch.
+    pub fn proofs(&self) -> &[StarkProof] {
+        &self.proofs
+    }
+
+    /// Consumes this batch and returns the proofs contained in it.
+    pub fn into_proofs(self) -> Vec<StarkProof> {
+        self.proofs
+    }
+
+    // SERIALIZATION / DESERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes this batch into a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = vec![PROOF_FORMAT_VERSION];
+        self.context.write_into(&mut result);
+        result.write_u32(self.proofs.len() as u32);
+        for proof in self.proofs.iter() {
+            proof.write_body_into(&mut result);
+        }
+        result
+    }
+
+    /// Returns a batch of STARK proofs read from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid batch of STARK proofs could not be read from the specified
+    /// `source`, including when the batch contains no proofs.
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        read_supported_format_version(&mut source)?;
+        let context = Context::read_from(&mut source)?;
+
+        let num_proofs = source.read_u32()?;
+        if num_proofs == 0 {
+            return Err(DeserializationError::InvalidValue(
+                "a batch must contain at least one proof".to_string(),
+            ));
+        }
+
+        // the number of proofs is not trusted, and thus, the vector is not pre-allocated
+        let mut proofs = Vec::new();
+        for _ in 0..num_proofs {
+            proofs.push(StarkProof::read_body_from(context.clone(), &mut source)?);
+        }
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+
+        Ok(BatchStarkProof { context, proofs })
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::Context;
+use crate::LdePos;
+use math::log2;
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable,
+};
+
+// COMMITMENT LAYOUT
+// ================================================================================================
+/// Describes how positions of the LDE domain are mapped onto leaves of commitment Merkle trees.
+///
+/// The LDE domain of $n$ positions is split into `num_partitions` contiguous ranges of equal
+/// size, and each range is committed to by a separate Merkle tree. Within a tree, every leaf
+/// packs `rows_per_leaf` consecutive rows. Thus, position $p$ is located in tree
+/// $p / (n / num\_partitions)$, and within this tree, it is located at offset
+/// $p \mod rows\_per\_leaf$ of the leaf with index $(p \mod (n / num\_partitions)) /
+/// rows\_per\_leaf$.
+///
+/// Both the prover (when opening commitments at query positions) and the verifier (when checking
+/// the openings) map positions onto Merkle tree leaves via this struct; external verifiers can
+/// use it to interpret query openings of a proof.
+///
+/// Neither leaf packing nor partitioning can currently be configured via proof options; thus, a
+/// layout built from a proof context via [CommitmentLayout::from_context()] always uses a single
+/// tree with one row per leaf, and leaf indexes are the same as query positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitmentLayout {
+    lde_domain_size: usize,
+    rows_per_leaf: usize,
+    num_partitions: usize,
+}
+
+impl CommitmentLayout {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new layout for an LDE domain of the specified size in which every leaf packs
+    /// `rows_per_leaf` rows and the domain is split into `num_partitions` trees.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Any of the parameters is zero or is not a power of two.
+    /// * A single tree would contain fewer rows than a single leaf.
+    pub fn new(lde_domain_size: usize, rows_per_leaf: usize, num_partitions: usize) -> Self {
+        assert!(
+            lde_domain_size.is_power_of_two(),
+            "LDE domain size must be a power of two, but was {lde_domain_size}"
+        );
+        assert!(
+            rows_per_leaf.is_power_of_two(),
+            "number of rows per leaf must be a power of two, but was {rows_per_leaf}"
+        );
+        assert!(
+            num_partitions.is_power_of_two(),
+            "number of partitions must be a power of two, but was {num_partitions}"
+        );
+        assert!(
+            rows_per_leaf * num_partitions <= lde_domain_size,
+            "a partition of an LDE domain of {lde_domain_size} rows must contain at least \
+            {rows_per_leaf} rows, but {num_partitions} partitions were requested"
+        );
+        CommitmentLayout {
+            lde_domain_size,
+            rows_per_leaf,
+            num_partitions,
+        }
+    }
+
+    /// Returns the layout of commitments to an execution described by the specified context.
+    pub fn from_context(context: &Context) -> Self {
+        Self::new(context.lde_domain_size(), 1, 1)
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of positions in the LDE domain described by this layout.
+    pub fn lde_domain_size(&self) -> usize {
+        self.lde_domain_size
+    }
+
+    /// Returns the number of rows packed into a single leaf.
+    pub fn rows_per_leaf(&self) -> usize {
+        self.rows_per_leaf
+    }
+
+    /// Returns the number of Merkle trees (partitions) the LDE domain is split into.
+    pub fn num_partitions(&self) -> usize {
+        self.num_partitions
+    }
+
+    /// Returns the number of leaves in each of the Merkle trees.
+    pub fn leaves_per_tree(&self) -> usize {
+        self.lde_domain_size / self.num_partitions / self.rows_per_leaf
+    }
+
+    // POSITION MAPPING
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the location of the specified position in the commitment trees.
+    ///
+    /// # Panics
+    /// Panics if the position is outside of the LDE domain.
+    pub fn leaf_of(&self, position: LdePos) -> LeafPosition {
+        let position = position.as_usize();
+        assert!(
+            position < self.lde_domain_size,
+            "position {position} is outside of the LDE domain of {} elements",
+            self.lde_domain_size
+        );
+        let partition_size = self.lde_domain_size / self.num_partitions;
+        LeafPosition {
+            tree: position / partition_size,
+            leaf: (position % partition_size) / self.rows_per_leaf,
+            offset: position % self.rows_per_leaf,
+        }
+    }
+
+    /// Returns positions contained in the specified leaf of the specified tree; the position at
+    /// index `i` of the returned vector is located at offset `i` within the leaf.
+    ///
+    /// # Panics
+    /// Panics if the tree or the leaf index are out of bounds.
+    pub fn positions_in_leaf(&self, tree: usize, leaf: usize) -> Vec<LdePos> {
+        assert!(
+            tree < self.num_partitions,
+            "tree index must be smaller than {}, but was {tree}",
+            self.num_partitions
+        );
+        assert!(
+            leaf < self.leaves_per_tree(),
+            "leaf index must be smaller than {}, but was {leaf}",
+            self.leaves_per_tree()
+        );
+        let first = tree * (self.lde_domain_size / self.num_partitions) + leaf * self.rows_per_leaf;
+        (first..first + self.rows_per_leaf).map(LdePos).collect()
+    }
+
+    /// Returns indexes of leaves which need to be opened to reveal values at the specified
+    /// positions.
+    ///
+    /// Leaves are indexed across all trees: the index of leaf `i` in tree `t` is
+    /// `t * leaves_per_tree + i`. Every leaf is included only once, and leaves are listed in the
+    /// order in which their first position appears in `positions`; thus, for a layout with a
+    /// single tree and one row per leaf, the returned indexes are the same as the positions.
+    ///
+    /// # Panics
+    /// Panics if any of the positions is outside of the LDE domain.
+    pub fn leaf_indexes(&self, positions: &[LdePos]) -> Vec<usize> {
+        let mut result = Vec::with_capacity(positions.len());
+        for &position in positions {
+            let leaf = self.leaf_of(position);
+            let index = leaf.tree * self.leaves_per_tree() + leaf.leaf;
+            if !result.contains(&index) {
+                result.push(index);
+            }
+        }
+        result
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+impl Serializable for CommitmentLayout {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(log2(self.lde_domain_size) as u8);
+        target.write_u8(log2(self.rows_per_leaf) as u8);
+        target.write_u8(log2(self.num_partitions) as u8);
+    }
+}
+
+impl Deserializable for CommitmentLayout {
+    /// Reads a commitment layout from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid layout could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let lde_domain_size_log2 = source.read_u8()? as u32;
+        let rows_per_leaf_log2 = source.read_u8()? as u32;
+        let num_partitions_log2 = source.read_u8()? as u32;
+        if lde_domain_size_log2 >= usize::BITS {
+            return Err(DeserializationError::InvalidValue(format!(
+                "LDE domain size cannot be greater than 2^{}, but was 2^{lde_domain_size_log2}",
+                usize::BITS - 1
+            )));
+        }
+        if rows_per_leaf_log2 + num_partitions_log2 > lde_domain_size_log2 {
+            return Err(DeserializationError::InvalidValue(
+                "a partition of the LDE domain must contain at least one leaf".to_string(),
+            ));
+        }
+        Ok(CommitmentLayout {
+            lde_domain_size: 1 << lde_domain_size_log2,
+            rows_per_leaf: 1 << rows_per_leaf_log2,
+            num_partitions: 1 << num_partitions_log2,
+        })
+    }
+}
+
+// LEAF POSITION
+// ================================================================================================
+/// Location of a single position of the LDE domain in commitment Merkle trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LeafPosition {
+    tree: usize,
+    leaf: usize,
+    offset: usize,
+}
+
+impl LeafPosition {
+    /// Returns the index of the tree (partition) containing the position.
+    pub fn tree(&self) -> usize {
+        self.tree
+    }
+
+    /// Returns the index of the leaf containing the position within its tree.
+    pub fn leaf(&self) -> usize {
+        self.leaf
+    }
+
+    /// Returns the offset of the position within its leaf.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
@@ -15,9 +15,11 @@ use utils::{
 ///
 /// These commitments include:
 /// * Commitment to the extended execution trace, which may include commitments to one or more
-///   execution trace segments.
+///   execution trace segments, followed by a commitment to the combined trace column when the
+///   proof options enable it.
 /// * Commitment to the evaluations of constraint composition polynomial over LDE domain.
-/// * Commitments to the evaluations of polynomials at all FRI layers.
+/// * Commitments to the evaluations of polynomials at all FRI layers, followed by a digest of
+///   the FRI remainder polynomial when the proof options enable it.
 ///
 /// Internally, the commitments are stored as a sequence of bytes. Thus, to retrieve the
 /// commitments, [parse()](Commitments::parse) function should be used.
@@ -54,9 +56,11 @@ impl Commitments {
     /// Parses the serialized commitments into distinct parts.
     ///
     /// The parts are (in the order in which they appear in the tuple):
-    /// 1. Extended execution trace commitments.
+    /// 1. Extended execution trace commitments (including the combined trace column commitment,
+    ///    if any, in which case `num_trace_segments` should account for it).
     /// 2. Constraint composition polynomial evaluation commitment.
-    /// 3. FRI layer commitments.
+    /// 3. FRI layer commitments (including the FRI remainder digest, if any, in which case
+    ///    `num_fri_layers` should account for it).
     ///
     /// # Errors
     /// Returns an error if the bytes stored in self could not be parsed into the requested number
@@ -0,0 +1,98 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use math::FieldElement;
+use utils::{
+    collections::Vec, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+    SliceReader,
+};
+
+// COMPOSITION COEFFICIENTS
+// ================================================================================================
+/// Coefficients of constraint composition polynomial columns sent by the prover in explicit
+/// composition mode.
+///
+/// Coefficients are stored in a serialized form, column by column, with coefficients of each
+/// column arranged from the lowest degree term to the highest degree term. Parsing them requires
+/// knowing the field in which the composition polynomial is defined, the number of columns, and
+/// the length of each column.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CompositionCoefficients(Vec<u8>);
+
+impl CompositionCoefficients {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns composition coefficients constructed from the provided column polynomials.
+    ///
+    /// # Panics
+    /// Panics if not all columns have the same length.
+    pub fn new<E: FieldElement>(columns: &[&[E]]) -> Self {
+        let column_len = columns.first().map_or(0, |column| column.len());
+        let mut bytes = Vec::with_capacity(columns.len() * column_len * E::ELEMENT_BYTES);
+        for column in columns {
+            assert_eq!(
+                column_len,
+                column.len(),
+                "all composition polynomial columns must have the same length"
+            );
+            bytes.write(*column);
+        }
+        CompositionCoefficients(bytes)
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of field elements of type `E` which fit into these coefficients; this
+    /// does not guarantee that the coefficients can be parsed into elements of type `E`.
+    pub fn num_elements<E: FieldElement>(&self) -> usize {
+        self.0.len() / E::ELEMENT_BYTES
+    }
+
+    // PARSING
+    // --------------------------------------------------------------------------------------------
+
+    /// Parses the serialized coefficients into `num_columns` column polynomials each consisting
+    /// of `column_len` coefficients.
+    ///
+    /// # Errors
+    /// Returns an error if the bytes stored in self could not be parsed into the requested number
+    /// of columns, or if there are any unconsumed bytes remaining after the parsing completes.
+    pub fn parse<E: FieldElement>(
+        self,
+        num_columns: usize,
+        column_len: usize,
+    ) -> Result<Vec<Vec<E>>, DeserializationError> {
+        let mut reader = SliceReader::new(&self.0);
+        let mut columns = Vec::with_capacity(num_columns);
+        for _ in 0..num_columns {
+            columns.push(E::read_batch_from(&mut reader, column_len)?);
+        }
+        if reader.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(columns)
+    }
+}
+
+impl Serializable for CompositionCoefficients {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.0.len() as u32);
+        target.write_u8_slice(&self.0);
+    }
+}
+
+impl Deserializable for CompositionCoefficients {
+    /// Reads composition coefficients from the specified `source` and returns the result.
+    ///
+    /// # Errors
+    /// Returns an error of a valid [CompositionCoefficients] struct could not be read from the
+    /// specified `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_bytes = source.read_u32()? as usize;
+        Ok(CompositionCoefficients(source.read_u8_vec(num_bytes)?))
+    }
+}
@@ -3,22 +3,59 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::{ProofOptions, TraceInfo, TraceLayout};
-use math::StarkField;
+use crate::{
+    transcript::bytes_to_elements, LdePos, ProofOptions, TraceInfo, TraceLayout, TraceRow,
+};
+use core::convert::TryFrom;
+use crypto::HashFunctionId;
+use math::{FieldElement, StarkField};
 use utils::{
     collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
     DeserializationError, Serializable,
 };
 
+// CONSTANTS
+// ================================================================================================
+
+/// Flag set in the serialized trace length to indicate that the context describes a trace with
+/// a preprocessed segment.
+const PREPROCESSED_SEGMENT_FLAG: u8 = 0x80;
+
+/// Flag set in the serialized trace length to indicate that the context describes a computation
+/// in which some transition constraints have more than one exemption.
+const TRANSITION_EXEMPTIONS_FLAG: u8 = 0x40;
+
+/// Flag set in the serialized length of the field modulus to indicate that the context records
+/// the hash function with which the proof was generated.
+const HASH_FUNCTION_FLAG: u8 = 0x80;
+
+/// Flag set in the serialized length of the field modulus to indicate that the context records
+/// the vector commitment scheme with which the proof was generated.
+const COMMITMENT_SCHEME_FLAG: u8 = 0x40;
+
+/// Flag set in the serialized length of the field modulus to indicate that the context records
+/// the random coin with which the proof was generated.
+const RANDOM_COIN_FLAG: u8 = 0x20;
+
 // PROOF CONTEXT
 // ================================================================================================
 /// Basic metadata about a specific execution of a computation.
+///
+/// The trace length is stored as `u64` so that a context read from a proof can describe a
+/// computation which is too large to be processed on the current platform (e.g., a trace of
+/// 2^32 steps on a 32-bit platform); whether this is the case can be checked via
+/// [Context::fits_platform()].
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Context {
     trace_layout: TraceLayout,
-    trace_length: usize,
+    trace_length: u64,
     trace_meta: Vec<u8>,
+    preprocessed_width: usize,
+    transition_exemptions: Vec<usize>,
     field_modulus_bytes: Vec<u8>,
+    hash_fn: Option<HashFunctionId>,
+    commitment_scheme: u8,
+    random_coin: u8,
     options: ProofOptions,
 }
 
@@ -27,16 +64,97 @@ impl Context {
     // --------------------------------------------------------------------------------------------
     /// Creates a new context for a computation described by the specified field, trace info, and
     /// proof options.
+    ///
+    /// The context describes a computation in which every transition constraint has a single
+    /// exemption; for computations with more exemptions, the exemptions must be set via
+    /// [Context::with_transition_exemptions()].
     pub fn new<B: StarkField>(trace_info: &TraceInfo, options: ProofOptions) -> Self {
         Context {
             trace_layout: trace_info.layout().clone(),
-            trace_length: trace_info.length(),
+            trace_length: trace_info.length() as u64,
             trace_meta: trace_info.meta().to_vec(),
+            preprocessed_width: trace_info.preprocessed_width(),
+            transition_exemptions: Vec::new(),
             field_modulus_bytes: B::get_modulus_le_bytes(),
+            hash_fn: None,
+            commitment_scheme: 0,
+            random_coin: 0,
             options,
         }
     }
 
+    /// Sets the number of transition exemptions of each transition constraint of the computation
+    /// described by this context (in the order defined by
+    /// [AirContext::transition_exemptions()](crate::AirContext::transition_exemptions)).
+    ///
+    /// The exemptions are bound into the context (and thus, into the transcript of the protocol)
+    /// so that a proof generated against an AIR with one set of exemptions cannot be verified
+    /// against an AIR with different exemptions, even if the largest numbers of exemptions of the
+    /// two AIRs are the same. When every constraint has a single exemption, nothing is recorded,
+    /// and thus, such contexts are the same as contexts of computations without exemptions.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * The number of constraints is greater than 2^16 - 1.
+    /// * Any of the numbers of exemptions is zero or is greater than half of the trace length
+    ///   plus one.
+    pub fn with_transition_exemptions(mut self, exemptions: &[usize]) -> Self {
+        assert!(
+            exemptions.len() <= u16::MAX as usize,
+            "number of transition constraints cannot exceed {}, but was {}",
+            u16::MAX,
+            exemptions.len()
+        );
+        for &num_exemptions in exemptions {
+            assert!(
+                num_exemptions > 0 && num_exemptions as u64 <= self.trace_length / 2 + 1,
+                "number of transition exemptions must be between 1 and {}, but was {}",
+                self.trace_length / 2 + 1,
+                num_exemptions
+            );
+        }
+        self.transition_exemptions = if exemptions.iter().all(|&n| n == 1) {
+            Vec::new()
+        } else {
+            exemptions.to_vec()
+        };
+        self
+    }
+
+    /// Records the hash function with which a proof in this context is generated.
+    ///
+    /// The hash function is bound into the context (and thus, into the transcript of the
+    /// protocol), and can be read by the verifier to select the hash function at runtime.
+    pub fn with_hash_fn(mut self, hash_fn: HashFunctionId) -> Self {
+        self.hash_fn = Some(hash_fn);
+        self
+    }
+
+    /// Records the identifier of the vector commitment scheme with which trace and constraint
+    /// commitments of a proof in this context are built (see
+    /// [VectorCommitment::SCHEME_ID](crypto::VectorCommitment::SCHEME_ID)).
+    ///
+    /// The identifier is bound into the context (and thus, into the transcript of the protocol)
+    /// so that a proof cannot be verified with a scheme other than the one with which it was
+    /// generated. Identifier 0 denotes Merkle trees and is not recorded explicitly; thus, contexts
+    /// of proofs committed to with Merkle trees are the same as before the scheme was recorded.
+    pub fn with_commitment_scheme(mut self, scheme_id: u8) -> Self {
+        self.commitment_scheme = scheme_id;
+        self
+    }
+
+    /// Records the identifier of the random coin from which Fiat-Shamir randomness of a proof in
+    /// this context is drawn (see [RandomCoin::COIN_ID](crypto::RandomCoin::COIN_ID)).
+    ///
+    /// The identifier is bound into the context (and thus, into the transcript of the protocol)
+    /// so that a proof cannot be verified with a coin other than the one with which it was
+    /// generated. Identifier 0 denotes [DefaultRandomCoin](crypto::DefaultRandomCoin) and is not
+    /// recorded explicitly.
+    pub fn with_random_coin(mut self, coin_id: u8) -> Self {
+        self.random_coin = coin_id;
+        self
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -47,22 +165,75 @@ impl Context {
     }
 
     /// Returns execution trace length of the computation described by this context.
+    ///
+    /// # Panics
+    /// Panics if the trace length cannot be represented as `usize` on the current platform.
     pub fn trace_length(&self) -> usize {
-        self.trace_length
+        usize::try_from(self.trace_length).expect("trace length is too large for this platform")
     }
 
     /// Returns execution trace info for the computation described by this context.
+    ///
+    /// # Panics
+    /// Panics if the trace length cannot be represented as `usize` on the current platform.
     pub fn get_trace_info(&self) -> TraceInfo {
-        TraceInfo::new_multi_segment(
+        let trace_info = TraceInfo::new_multi_segment(
             self.trace_layout.clone(),
             self.trace_length(),
             self.trace_meta.clone(),
-        )
+        );
+        match self.preprocessed_width {
+            0 => trace_info,
+            width => trace_info.with_preprocessed_segment(width),
+        }
+    }
+
+    /// Returns the number of columns in the preprocessed trace segment of the computation
+    /// described by this context, or zero if the trace does not have a preprocessed segment.
+    pub fn preprocessed_width(&self) -> usize {
+        self.preprocessed_width
+    }
+
+    /// Returns the largest number of rows at the end of the execution trace to which transition
+    /// constraints of the computation described by this context do not apply.
+    pub fn num_transition_exemptions(&self) -> usize {
+        self.transition_exemptions
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Returns the number of rows at the end of the execution trace to which each of the
+    /// transition constraints of the computation described by this context does not apply.
+    ///
+    /// The list is empty when every transition constraint has a single exemption.
+    pub fn transition_exemptions(&self) -> &[usize] {
+        &self.transition_exemptions
     }
 
     /// Returns the size of the LDE domain for the computation described by this context.
+    ///
+    /// # Panics
+    /// Panics if the size of the LDE domain cannot be represented as `usize` on the current
+    /// platform.
     pub fn lde_domain_size(&self) -> usize {
-        self.trace_length() * self.options.blowup_factor()
+        pow2_to_usize(self.lde_domain_size_log2())
+            .expect("LDE domain is too large for this platform")
+    }
+
+    /// Returns log2 of the size of the LDE domain for the computation described by this context.
+    ///
+    /// Unlike [Context::lde_domain_size()], this never overflows, and thus, can be used to
+    /// describe domains which are too large for the current platform.
+    pub fn lde_domain_size_log2(&self) -> u32 {
+        self.trace_length.trailing_zeros() + self.options.blowup_factor().trailing_zeros()
+    }
+
+    /// Returns true if the LDE domain for the computation described by this context (and thus,
+    /// the execution trace as well) can be indexed by `usize` on the current platform.
+    pub fn fits_platform(&self) -> bool {
+        pow2_to_usize(self.lde_domain_size_log2()).is_some()
     }
 
     /// Returns modulus of the field for the computation described by this context.
@@ -70,6 +241,25 @@ impl Context {
         &self.field_modulus_bytes
     }
 
+    /// Returns the hash function with which the proof was generated, or None if the hash
+    /// function was not recorded in this context (e.g., because it is not one of the hash
+    /// functions defined in the crypto crate).
+    pub fn hash_fn(&self) -> Option<HashFunctionId> {
+        self.hash_fn
+    }
+
+    /// Returns the identifier of the vector commitment scheme with which trace and constraint
+    /// commitments of the proof were built; identifier 0 denotes Merkle trees.
+    pub fn commitment_scheme(&self) -> u8 {
+        self.commitment_scheme
+    }
+
+    /// Returns the identifier of the random coin from which Fiat-Shamir randomness of the proof
+    /// was drawn; identifier 0 denotes the default random coin.
+    pub fn random_coin(&self) -> u8 {
+        self.random_coin
+    }
+
     /// Returns number of bits in the base field modulus for the computation described by this
     /// context.
     ///
@@ -87,22 +277,222 @@ impl Context {
         0
     }
 
+    /// Returns true if coefficients of the constraint composition polynomial are sent explicitly
+    /// in proofs generated in this context; this is determined by the explicit composition
+    /// threshold in proof options and the trace length.
+    pub fn uses_explicit_composition(&self) -> bool {
+        // a trace which does not fit into usize is always longer than the threshold
+        usize::try_from(self.trace_length).map_or(false, |trace_length| {
+            self.options.uses_explicit_composition(trace_length)
+        })
+    }
+
+    /// Returns true if proofs generated in this context include a commitment to a random linear
+    /// combination of all trace columns.
+    pub fn uses_combined_trace_column(&self) -> bool {
+        self.options.uses_combined_trace_column()
+    }
+
+    /// Returns true if proofs generated in this context bind query positions to a digest of the
+    /// FRI remainder polynomial.
+    pub fn uses_fri_remainder_digest(&self) -> bool {
+        self.options.uses_fri_remainder_digest()
+    }
+
+    /// Returns the number of bytes in the widest row of trace commitments (i.e., commitments to
+    /// trace segments and to the combined trace column) described by this context.
+    ///
+    /// `E` must be the field in which auxiliary trace segments and the combined trace column are
+    /// built; rows of the main and preprocessed trace segments consist of elements of its base
+    /// field.
+    pub fn max_trace_row_bytes<E: FieldElement>(&self) -> usize {
+        let main_row_bytes = self
+            .trace_layout
+            .main_trace_width()
+            .max(self.preprocessed_width)
+            * E::BaseField::ELEMENT_BYTES;
+        let aux_row_bytes = (0..self.trace_layout.num_aux_segments())
+            .map(|i| self.trace_layout.get_aux_segment_width(i) * E::ELEMENT_BYTES);
+        let combined_row_bytes = self.uses_combined_trace_column().then(|| E::ELEMENT_BYTES);
+        aux_row_bytes
+            .chain(combined_row_bytes)
+            .fold(main_row_bytes, usize::max)
+    }
+
     /// Returns proof options which were used to a proof in this context.
     pub fn options(&self) -> &ProofOptions {
         &self.options
     }
+
+    // DOMAIN ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the generator of the execution trace domain for the computation described by this
+    /// context.
+    ///
+    /// `B` must be the base field of the computation; the generator is the same as the one used
+    /// by the prover and the verifier (i.e., the one returned by `Air::trace_domain_generator()`).
+    pub fn trace_domain_generator<B: StarkField>(&self) -> B {
+        B::get_root_of_unity(self.trace_length.trailing_zeros())
+    }
+
+    /// Returns the generator of the LDE domain for the computation described by this context.
+    ///
+    /// `B` must be the base field of the computation; the generator is the same as the one used
+    /// by the prover and the verifier (i.e., the one returned by `Air::lde_domain_generator()`).
+    pub fn lde_domain_generator<B: StarkField>(&self) -> B {
+        B::get_root_of_unity(self.lde_domain_size_log2())
+    }
+
+    /// Returns the element of the LDE domain at the specified position.
+    ///
+    /// The LDE domain is a coset of the subgroup generated by the LDE domain generator, and its
+    /// elements are not permuted; thus, the element at position `i` is computed as `offset * g^i`,
+    /// where `g` is the LDE domain generator and `offset` is the domain offset defined by proof
+    /// options. For query positions of a proof, this is exactly the element at which the verifier
+    /// evaluates DEEP composition polynomial.
+    ///
+    /// # Panics
+    /// Panics if the position is outside of the LDE domain.
+    pub fn lde_domain_element<B: StarkField>(&self, position: LdePos) -> B {
+        assert!(
+            position.as_usize() < self.lde_domain_size(),
+            "position {} is outside of the LDE domain of {} elements",
+            position,
+            self.lde_domain_size()
+        );
+        let g = self.lde_domain_generator::<B>();
+        g.exp_vartime((position.as_usize() as u64).into()) * self.options.domain_offset::<B>()
+    }
+
+    /// Returns the execution trace row which corresponds to the specified position in the LDE
+    /// domain, or None if the position does not correspond to a trace row.
+    ///
+    /// A position corresponds to row `r` when it is equal to `r * blowup`. In such a case, the
+    /// element of the LDE domain at this position is `offset * w^r`, where `w` is the trace
+    /// domain generator; that is, it is the element of the trace domain for row `r` shifted by
+    /// the domain offset. Since the LDE domain is a coset of the trace domain, trace polynomials
+    /// evaluated at this element are not equal to the values in row `r` of the execution trace.
+    ///
+    /// # Panics
+    /// Panics if the position is outside of the LDE domain.
+    pub fn map_position_to_trace_row(&self, position: LdePos) -> Option<TraceRow> {
+        assert!(
+            position.as_usize() < self.lde_domain_size(),
+            "position {} is outside of the LDE domain of {} elements",
+            position,
+            self.lde_domain_size()
+        );
+        position.to_trace_row(self.options.blowup_factor())
+    }
+
+    // ELEMENT ENCODING
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns this context encoded as a sequence of base field elements.
+    ///
+    /// Trace widths, numbers of random elements, the trace length, and every byte of the
+    /// serialized proof options are encoded as individual elements using their canonical field
+    /// encodings; trace metadata and field modulus bytes are packed into elements and are
+    /// prefixed with their lengths. The width of the preprocessed trace segment (if any) is
+    /// encoded in bits 8 through 15 of the element encoding the number of auxiliary segments,
+    /// and the number of recorded transition exemptions is encoded in the bits above these; the
+    /// exemptions themselves follow the trace length as individual elements. If
+    /// the hash function is recorded in the context, its identifier plus 2^8 is appended as the
+    /// last element (which cannot be confused with a byte of proof options); similarly, if the
+    /// context records a commitment scheme other than Merkle trees, its identifier plus 2^9 is
+    /// appended after it, and if it records a random coin other than the default one, the coin
+    /// identifier plus 2^10 is appended last. This encoding is used to seed the public coin in
+    /// the [Arithmetic](crate::TranscriptProfile::Arithmetic) transcript profile.
+    pub fn to_elements<B: StarkField>(&self) -> Vec<B> {
+        let num_aux_segments = self.trace_layout.num_aux_segments();
+        let mut result = Vec::new();
+        result.push(B::from(self.trace_layout.main_trace_width() as u64));
+        result.push(B::from(
+            num_aux_segments as u64
+                | (self.preprocessed_width as u64) << 8
+                | (self.transition_exemptions.len() as u64) << 16,
+        ));
+        for i in 0..num_aux_segments {
+            result.push(B::from(self.trace_layout.get_aux_segment_width(i) as u64));
+            result.push(B::from(
+                self.trace_layout.get_aux_segment_rand_elements(i) as u64
+            ));
+        }
+        result.push(B::from(self.trace_length));
+        for &num_exemptions in self.transition_exemptions.iter() {
+            result.push(B::from(num_exemptions as u64));
+        }
+        result.append(&mut bytes_to_elements(&self.trace_meta));
+        result.append(&mut bytes_to_elements(&self.field_modulus_bytes));
+        for byte in self.options.to_bytes() {
+            result.push(B::from(byte));
+        }
+        if let Some(hash_fn) = self.hash_fn {
+            result.push(B::from(1_u64 << 8 | hash_fn as u64));
+        }
+        if self.commitment_scheme != 0 {
+            result.push(B::from(1_u64 << 9 | self.commitment_scheme as u64));
+        }
+        if self.random_coin != 0 {
+            result.push(B::from(1_u64 << 10 | self.random_coin as u64));
+        }
+        result
+    }
 }
 
 impl Serializable for Context {
     /// Serializes `self` and writes the resulting bytes into the `target`.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         self.trace_layout.write_into(target);
-        target.write_u8(math::log2(self.trace_length) as u8); // store as power of two
+
+        // the trace length is stored as a power of two, and thus, it never exceeds 63; the two
+        // most significant bits of this byte are used to indicate that the width of the
+        // preprocessed trace segment and the transition exemptions follow
+        let mut log_trace_length = self.trace_length.trailing_zeros() as u8;
+        if self.preprocessed_width != 0 {
+            log_trace_length |= PREPROCESSED_SEGMENT_FLAG;
+        }
+        if !self.transition_exemptions.is_empty() {
+            log_trace_length |= TRANSITION_EXEMPTIONS_FLAG;
+        }
+        target.write_u8(log_trace_length);
+        if self.preprocessed_width != 0 {
+            target.write_u8(self.preprocessed_width as u8);
+        }
+        if !self.transition_exemptions.is_empty() {
+            target.write_u16(self.transition_exemptions.len() as u16);
+            for &num_exemptions in self.transition_exemptions.iter() {
+                target.write_u64(num_exemptions as u64);
+            }
+        }
         target.write_u16(self.trace_meta.len() as u16);
         target.write_u8_slice(&self.trace_meta);
-        assert!(self.field_modulus_bytes.len() < u8::MAX as usize);
-        target.write_u8(self.field_modulus_bytes.len() as u8);
+
+        // the three most significant bits of the modulus length indicate that the identifiers of
+        // the hash function, of the commitment scheme, and of the random coin follow the modulus
+        assert!(self.field_modulus_bytes.len() < RANDOM_COIN_FLAG as usize);
+        let mut num_modulus_bytes = self.field_modulus_bytes.len() as u8;
+        if self.hash_fn.is_some() {
+            num_modulus_bytes |= HASH_FUNCTION_FLAG;
+        }
+        if self.commitment_scheme != 0 {
+            num_modulus_bytes |= COMMITMENT_SCHEME_FLAG;
+        }
+        if self.random_coin != 0 {
+            num_modulus_bytes |= RANDOM_COIN_FLAG;
+        }
+        target.write_u8(num_modulus_bytes);
         target.write_u8_slice(&self.field_modulus_bytes);
+        if let Some(hash_fn) = self.hash_fn {
+            hash_fn.write_into(target);
+        }
+        if self.commitment_scheme != 0 {
+            target.write_u8(self.commitment_scheme);
+        }
+        if self.random_coin != 0 {
+            target.write_u8(self.random_coin);
+        }
         self.options.write_into(target);
     }
 }
@@ -116,8 +506,25 @@ impl Deserializable for Context {
         // read and validate trace layout info
         let trace_layout = TraceLayout::read_from(source)?;
 
-        // read and validate trace length (which was stored as a power of two)
+        // read and validate trace length (which was stored as a power of two), and the width of
+        // the preprocessed trace segment (if any)
         let trace_length = source.read_u8()?;
+        let preprocessed_width = if trace_length & PREPROCESSED_SEGMENT_FLAG != 0 {
+            let width = source.read_u8()? as usize;
+            let full_width = trace_layout.main_trace_width() + trace_layout.aux_trace_width();
+            if width == 0 || full_width + width > TraceInfo::MAX_TRACE_WIDTH {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "preprocessed segment width must be between 1 and {}, but was {}",
+                    TraceInfo::MAX_TRACE_WIDTH - full_width,
+                    width
+                )));
+            }
+            width
+        } else {
+            0
+        };
+        let has_transition_exemptions = trace_length & TRANSITION_EXEMPTIONS_FLAG != 0;
+        let trace_length = trace_length & !(PREPROCESSED_SEGMENT_FLAG | TRANSITION_EXEMPTIONS_FLAG);
         if trace_length < math::log2(TraceInfo::MIN_TRACE_LENGTH) as u8 {
             return Err(DeserializationError::InvalidValue(format!(
                 "trace length cannot be smaller than 2^{}, but was 2^{}",
@@ -125,7 +532,38 @@ impl Deserializable for Context {
                 trace_length
             )));
         }
-        let trace_length = 2_usize.pow(trace_length as u32);
+        let trace_length = 1_u64.checked_shl(trace_length as u32).ok_or_else(|| {
+            DeserializationError::InvalidValue(format!(
+                "trace length cannot be greater than 2^63, but was 2^{trace_length}"
+            ))
+        })?;
+
+        // read and validate transition exemptions (if recorded); exemptions may not cover more
+        // than half of the trace plus one row, and at least one constraint must have more than
+        // one exemption as otherwise the exemptions must not be recorded
+        let transition_exemptions = if has_transition_exemptions {
+            let num_constraints = source.read_u16()? as usize;
+            let mut exemptions = Vec::with_capacity(num_constraints);
+            for _ in 0..num_constraints {
+                let num_exemptions = source.read_u64()?;
+                if num_exemptions == 0 || num_exemptions > trace_length / 2 + 1 {
+                    return Err(DeserializationError::InvalidValue(format!(
+                        "number of transition exemptions must be between 1 and {}, but was {}",
+                        trace_length / 2 + 1,
+                        num_exemptions
+                    )));
+                }
+                exemptions.push(num_exemptions as usize);
+            }
+            if exemptions.iter().all(|&n| n == 1) {
+                return Err(DeserializationError::InvalidValue(
+                    "transition exemptions must not be recorded when every constraint has a single exemption".to_string(),
+                ));
+            }
+            exemptions
+        } else {
+            Vec::new()
+        };
 
         // read trace metadata
         let num_meta_bytes = source.read_u16()? as usize;
@@ -135,14 +573,50 @@ impl Deserializable for Context {
             vec![]
         };
 
-        // read and validate field modulus bytes
-        let num_modulus_bytes = source.read_u8()? as usize;
+        // read and validate field modulus bytes, the hash function, the commitment scheme, and
+        // the random coin (if recorded)
+        let num_modulus_bytes = source.read_u8()?;
+        let has_hash_fn = num_modulus_bytes & HASH_FUNCTION_FLAG != 0;
+        let has_commitment_scheme = num_modulus_bytes & COMMITMENT_SCHEME_FLAG != 0;
+        let has_random_coin = num_modulus_bytes & RANDOM_COIN_FLAG != 0;
+        let num_modulus_bytes = (num_modulus_bytes
+            & !(HASH_FUNCTION_FLAG | COMMITMENT_SCHEME_FLAG | RANDOM_COIN_FLAG))
+            as usize;
         if num_modulus_bytes == 0 {
             return Err(DeserializationError::InvalidValue(
                 "field modulus cannot be an empty value".to_string(),
             ));
         }
         let field_modulus_bytes = source.read_u8_vec(num_modulus_bytes)?;
+        let hash_fn = if has_hash_fn {
+            Some(HashFunctionId::read_from(source)?)
+        } else {
+            None
+        };
+        let commitment_scheme = if has_commitment_scheme {
+            match source.read_u8()? {
+                0 => {
+                    return Err(DeserializationError::InvalidValue(
+                        "commitment scheme 0 must not be recorded explicitly".to_string(),
+                    ))
+                }
+                scheme_id => scheme_id,
+            }
+        } else {
+            0
+        };
+        let random_coin = if has_random_coin {
+            match source.read_u8()? {
+                0 => {
+                    return Err(DeserializationError::InvalidValue(
+                        "random coin 0 must not be recorded explicitly".to_string(),
+                    ))
+                }
+                coin_id => coin_id,
+            }
+        } else {
+            0
+        };
 
         // read options
         let options = ProofOptions::read_from(source)?;
@@ -151,8 +625,22 @@ impl Deserializable for Context {
             trace_layout,
             trace_length,
             trace_meta,
+            preprocessed_width,
+            transition_exemptions,
             field_modulus_bytes,
+            hash_fn,
+            commitment_scheme,
+            random_coin,
             options,
         })
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns 2^`log2` as `usize`, or None if the result cannot be represented as `usize` on the
+/// current platform.
+pub(crate) fn pow2_to_usize(log2: u32) -> Option<usize> {
+    1_usize.checked_shl(log2)
+}
@@ -6,10 +6,11 @@
 //! Contains STARK proof struct and associated components.
 
 use crate::{ProofOptions, TraceInfo, TraceLayout};
-use core::cmp;
 use crypto::Hasher;
 use fri::FriProof;
 use math::log2;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use utils::{
     collections::Vec, ByteReader, Deserializable, DeserializationError, Serializable, SliceReader,
 };
@@ -17,6 +18,9 @@ use utils::{
 mod context;
 pub use context::Context;
 
+mod commitment_layout;
+pub use commitment_layout::{CommitmentLayout, LeafPosition};
+
 mod commitments;
 pub use commitments::Commitments;
 
@@ -29,11 +33,40 @@ pub use ood_frame::OodFrame;
 mod table;
 pub use table::Table;
 
+mod composition;
+pub use composition::CompositionCoefficients;
+
+mod security;
+pub use security::{SecurityBottleneck, SecurityEstimate};
+
+mod size;
+pub use size::ProofSizeBreakdown;
+
+mod batch;
+pub use batch::BatchStarkProof;
+
+mod archive;
+pub use archive::{
+    ArchivalEnvelope, ArchiveHeader, ArchiveMetadata, ARCHIVE_FORMAT_VERSION, ARCHIVE_HEADER_SIZE,
+    ARCHIVE_MAGIC,
+};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
 // CONSTANTS
 // ================================================================================================
 
 const GRINDING_CONTRIBUTION_FLOOR: u32 = 80;
 
+/// Version of the format in which proofs are serialized; the version is written as the first
+/// byte of every serialized proof (and of every serialized batch of proofs).
+///
+/// The version must be incremented whenever a change to the protocol or to the serialization
+/// format makes previously serialized proofs unreadable or unverifiable, so that such proofs are
+/// rejected up front rather than failing in an obscure way.
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+
 // STARK PROOF
 // ================================================================================================
 /// A proof generated by Winterfell prover.
@@ -44,28 +77,41 @@ const GRINDING_CONTRIBUTION_FLOOR: u32 = 80;
 ///
 /// A proof can be serialized into a sequence of bytes using [to_bytes()](StarkProof::to_bytes)
 /// function, and deserialized from a sequence of bytes using [from_bytes()](StarkProof::from_bytes)
-/// function.
+/// function. When `serde` feature is enabled, a proof can also be serialized with any serde data
+/// format; components of the proof are then encoded as hex strings in human-readable formats, and
+/// as byte strings in binary formats (see [serde_bytes](crate::serde_bytes)).
 ///
 /// To estimate soundness of a proof (in bits), [security_level()](StarkProof::security_level)
 /// function can be used.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StarkProof {
     /// Basic metadata about the execution of the computation described by this proof.
     pub context: Context,
     /// Commitments made by the prover during the commit phase of the protocol.
     pub commitments: Commitments,
     /// Decommitments of extended execution trace values (for all trace segments) at position
-    ///  queried by the verifier.
+    ///  queried by the verifier; when the proof commits to a combined trace column, decommitments
+    /// of the combined column follow decommitments of all trace segments.
     pub trace_queries: Vec<Queries>,
     /// Decommitments of constraint composition polynomial evaluations at positions queried by
-    /// the verifier.
+    /// the verifier; this is empty when the composition polynomial is sent explicitly.
     pub constraint_queries: Queries,
+    /// Coefficients of constraint composition polynomial columns; these are included only when
+    /// the proof options enable explicit composition for the trace length of the proof.
+    pub composition_coefficients: Option<CompositionCoefficients>,
     /// Trace and constraint polynomial evaluations at an out-of-domain point.
     pub ood_frame: OodFrame,
     /// Low-degree proof for a DEEP composition polynomial.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_bytes"))]
     pub fri_proof: FriProof,
-    /// Proof-of-work nonce for query seed grinding.
-    pub pow_nonce: u64,
+    /// Proof-of-work nonce for query seed grinding; this is always smaller than 2^64 unless the
+    /// proof options specify a wide grinding nonce.
+    pub pow_nonce: u128,
+    /// Number of queries drawn in addition to the number of queries specified by the proof
+    /// options to compensate for the proof-of-work bits the nonce is missing; this is always
+    /// zero unless the proof options specify the time-boxed grinding strategy.
+    pub num_extra_queries: usize,
 }
 
 impl StarkProof {
@@ -95,6 +141,54 @@ impl StarkProof {
         self.context.lde_domain_size()
     }
 
+    /// Returns the number of queries against which trace and constraint commitments are opened
+    /// in this proof, including the extra queries drawn to compensate for proof-of-work bits
+    /// missing from the nonce.
+    pub fn num_queries(&self) -> usize {
+        self.options().num_queries() + self.num_extra_queries
+    }
+
+    // FRI STRUCTURE
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of FRI layers in the low-degree proof included in this proof.
+    ///
+    /// The remainder layer (the last FRI layer) is not included in the returned value.
+    pub fn fri_layer_count(&self) -> usize {
+        self.fri_proof.num_layers()
+    }
+
+    /// Returns sizes of the evaluation domains of FRI layers in the low-degree proof included in
+    /// this proof.
+    ///
+    /// The first layer is evaluated over the LDE domain, and the domain of each subsequent layer
+    /// is smaller than the domain of the previous layer by the FRI folding factor.
+    pub fn fri_layer_domain_sizes(&self) -> Vec<usize> {
+        let folding_factor = self.options().to_fri_options().folding_factor();
+        let mut domain_size = self.lde_domain_size();
+        let mut result = Vec::with_capacity(self.fri_layer_count());
+        for _ in 0..self.fri_layer_count() {
+            result.push(domain_size);
+            domain_size /= folding_factor;
+        }
+        result
+    }
+
+    /// Returns the degree of the FRI remainder polynomial implied by the number of FRI layers in
+    /// the low-degree proof included in this proof.
+    ///
+    /// The remainder is evaluated over the domain obtained by folding the domain of the last FRI
+    /// layer, and the degree of the remainder polynomial is one less than the size of this domain
+    /// divided by the blowup factor.
+    pub fn fri_remainder_degree(&self) -> usize {
+        let folding_factor = self.options().to_fri_options().folding_factor();
+        let remainder_domain_size = self
+            .fri_layer_domain_sizes()
+            .last()
+            .map_or(self.lde_domain_size(), |&size| size / folding_factor);
+        (remainder_domain_size / self.options().blowup_factor()).saturating_sub(1)
+    }
+
     // SECURITY LEVEL
     // --------------------------------------------------------------------------------------------
     /// Returns security level of this proof (in bits).
@@ -104,67 +198,156 @@ impl StarkProof {
     /// 2x - 3x higher than the number of queries needed for conjectured security at the same
     /// security level.
     pub fn security_level<H: Hasher>(&self, conjectured: bool) -> u32 {
+        self.security_estimate::<H>(conjectured).security_level()
+    }
+
+    /// Returns a breakdown of the security level of this proof into contributions of the field,
+    /// the queries, grinding, and the hash function `H`.
+    ///
+    /// When `conjectured` is true, the breakdown of the conjectured security level is returned;
+    /// otherwise, the breakdown of the provable security level is returned. The security level
+    /// implied by the returned estimate is the same as the one returned by
+    /// [security_level()](StarkProof::security_level).
+    pub fn security_estimate<H: Hasher>(&self, conjectured: bool) -> SecurityEstimate {
+        let options = self.context.options();
+        let base_field_bits = self.context.num_modulus_bits();
+        let lde_domain_size = self.lde_domain_size() as u64;
         if conjectured {
-            get_conjectured_security(
-                self.context.options(),
-                self.context.num_modulus_bits(),
-                self.lde_domain_size() as u64,
+            SecurityEstimate::conjectured(
+                options,
+                base_field_bits,
+                lde_domain_size,
                 H::COLLISION_RESISTANCE,
             )
         } else {
-            // TODO: implement provable security estimation
-            unimplemented!("proven security estimation has not been implement yet")
+            SecurityEstimate::proven(
+                options,
+                base_field_bits,
+                lde_domain_size,
+                H::COLLISION_RESISTANCE,
+            )
         }
     }
 
+    /// Returns a breakdown of the serialized size of this proof into sizes of its components
+    /// (e.g., trace queries, the out-of-domain frame, and individual FRI layers).
+    ///
+    /// The sizes of all components add up to the length of the vector returned by
+    /// [to_bytes()](StarkProof::to_bytes).
+    pub fn size_breakdown(&self) -> ProofSizeBreakdown {
+        ProofSizeBreakdown::new(self)
+    }
+
     // SERIALIZATION / DESERIALIZATION
     // --------------------------------------------------------------------------------------------
 
-    /// Serializes this proof into a vector of bytes.
+    /// Serializes this proof into a vector of bytes; the bytes start with
+    /// [PROOF_FORMAT_VERSION].
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut result = Vec::new();
+        let mut result = vec![PROOF_FORMAT_VERSION];
         self.context.write_into(&mut result);
-        self.commitments.write_into(&mut result);
-        self.trace_queries.write_into(&mut result);
-        self.constraint_queries.write_into(&mut result);
-        self.ood_frame.write_into(&mut result);
-        self.fri_proof.write_into(&mut result);
-        result.extend_from_slice(&self.pow_nonce.to_le_bytes());
+        self.write_body_into(&mut result);
         result
     }
 
     /// Returns a STARK proof read from the specified `source`.
     ///
     /// # Errors
-    /// Returns an error of a valid STARK proof could not be read from the specified `source`.
+    /// Returns an error of a valid STARK proof could not be read from the specified `source`,
+    /// including when the proof was serialized in a format version other than
+    /// [PROOF_FORMAT_VERSION].
     pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
         let mut source = SliceReader::new(source);
-
-        // parse the context
+        read_supported_format_version(&mut source)?;
         let context = Context::read_from(&mut source)?;
+        let proof = Self::read_body_from(context, &mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(proof)
+    }
+
+    /// Returns the format version declared by a proof (or a batch of proofs) serialized into the
+    /// specified `source` without parsing the rest of the proof.
+    ///
+    /// Unlike [from_bytes()](StarkProof::from_bytes), this succeeds for any version, and thus,
+    /// can be used to tell proofs serialized by other versions of the library apart from
+    /// malformed proofs.
+    ///
+    /// # Errors
+    /// Returns an error if the `source` is empty.
+    pub fn read_format_version(source: &[u8]) -> Result<u8, DeserializationError> {
+        SliceReader::new(source).read_u8()
+    }
 
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes all components of this proof except for the context into the `target`.
+    pub(crate) fn write_body_into(&self, target: &mut Vec<u8>) {
+        self.commitments.write_into(target);
+        self.trace_queries.write_into(target);
+        self.constraint_queries.write_into(target);
+        if let Some(ref coefficients) = self.composition_coefficients {
+            coefficients.write_into(target);
+        }
+        self.ood_frame.write_into(target);
+        self.fri_proof.write_into(target);
+        let nonce_bytes = self.options().grinding_nonce_bytes();
+        target.extend_from_slice(&self.pow_nonce.to_le_bytes()[..nonce_bytes]);
+        if self.options().uses_time_boxed_grinding() {
+            target.push(self.num_extra_queries as u8);
+        }
+    }
+
+    /// Reads all components of a proof except for the context from the `source`; the structure
+    /// of the components is determined by the specified `context`.
+    pub(crate) fn read_body_from<R: ByteReader>(
+        context: Context,
+        source: &mut R,
+    ) -> Result<Self, DeserializationError> {
         // parse the commitments
-        let commitments = Commitments::read_from(&mut source)?;
+        let commitments = Commitments::read_from(source)?;
 
-        // parse trace queries
-        let num_trace_segments = context.trace_layout().num_segments();
-        let mut trace_queries = Vec::with_capacity(num_trace_segments);
-        for _ in 0..num_trace_segments {
-            trace_queries.push(Queries::read_from(&mut source)?);
+        // parse trace queries; the preprocessed segment (if any) is queried right after the main
+        // segment, and the combined trace column (if any) is queried after all trace segments
+        let num_trace_commitments = context.trace_layout().num_segments()
+            + (context.preprocessed_width() > 0) as usize
+            + context.uses_combined_trace_column() as usize;
+        let mut trace_queries = Vec::with_capacity(num_trace_commitments);
+        for _ in 0..num_trace_commitments {
+            trace_queries.push(Queries::read_from(source)?);
         }
 
-        // parse the rest of the proof
-        let proof = StarkProof {
+        // parse constraint queries and, for proofs with explicit composition, coefficients of the
+        // composition polynomial
+        let constraint_queries = Queries::read_from(source)?;
+        let composition_coefficients = if context.uses_explicit_composition() {
+            Some(CompositionCoefficients::read_from(source)?)
+        } else {
+            None
+        };
+
+        // parse the rest of the proof; the number of extra queries is included only in proofs
+        // generated with the time-boxed grinding strategy
+        let mut proof = StarkProof {
             context,
             commitments,
             trace_queries,
-            constraint_queries: Queries::read_from(&mut source)?,
-            ood_frame: OodFrame::read_from(&mut source)?,
-            fri_proof: FriProof::read_from(&mut source)?,
-            pow_nonce: source.read_u64()?,
+            constraint_queries,
+            composition_coefficients,
+            ood_frame: OodFrame::read_from(source)?,
+            fri_proof: FriProof::read_from(source)?,
+            pow_nonce: 0,
+            num_extra_queries: 0,
         };
-        if source.has_more_bytes() {
-            return Err(DeserializationError::UnconsumedBytes);
+        proof.pow_nonce = if proof.options().uses_wide_grinding_nonce() {
+            source.read_u128()?
+        } else {
+            source.read_u64()? as u128
+        };
+        if proof.options().uses_time_boxed_grinding() {
+            proof.num_extra_queries = source.read_u8()? as usize;
         }
         Ok(proof)
     }
@@ -173,28 +356,78 @@ impl StarkProof {
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Reads the format version from the `source` and checks that it is [PROOF_FORMAT_VERSION].
+pub(crate) fn read_supported_format_version<R: ByteReader>(
+    source: &mut R,
+) -> Result<(), DeserializationError> {
+    let version = source.read_u8()?;
+    if version != PROOF_FORMAT_VERSION {
+        return Err(DeserializationError::InvalidValue(format!(
+            "proof format version {version} is not supported; expected version {PROOF_FORMAT_VERSION}"
+        )));
+    }
+    Ok(())
+}
+
 /// Computes conjectured security level for the specified proof parameters.
-fn get_conjectured_security(
+pub(crate) fn get_conjectured_security(
     options: &ProofOptions,
     base_field_bits: u32,
     lde_domain_size: u64,
     collision_resistance: u32,
 ) -> u32 {
-    // compute max security we can get for a given field size
-    let field_size = base_field_bits * options.field_extension().degree();
-    let field_security = field_size - lde_domain_size.trailing_zeros();
+    SecurityEstimate::conjectured(
+        options,
+        base_field_bits,
+        lde_domain_size,
+        collision_resistance,
+    )
+    .security_level()
+}
+
+/// Computes max security (in bits) we can get for a given field size and LDE domain size when
+/// proofs are generated with the specified options.
+///
+/// For a field of $f$ bits, this is the smaller of:
+/// * Security of the FRI commit phase: $f - log_2(|D|)$ bits, where $|D|$ is the size of the LDE
+///   domain.
+/// * Security of the DEEP/ALI step: $k \cdot (f - log_2(d \cdot (n + 1) + n - 1))$ bits, where
+///   $k$ is the number of out-of-domain points, $d$ is the blowup factor, and $n$ is the trace
+///   length. A separate constraint composition polynomial, built with its own set of random
+///   coefficients, is checked at each of the points, and thus, a cheating prover must get lucky
+///   at every one of them.
+pub(crate) fn get_field_security(
+    options: &ProofOptions,
+    base_field_bits: u32,
+    extension_degree: u32,
+    lde_domain_size: u64,
+) -> u32 {
+    let field_bits = (base_field_bits * extension_degree) as f64;
+    let blowup = options.blowup_factor() as f64;
+    let trace_length = lde_domain_size as f64 / blowup;
+    let num_ood_points = options.num_ood_points() as f64;
 
-    // compute security we get by executing multiple query rounds
-    let security_per_query = log2(options.blowup_factor());
+    let commit_security = field_bits - lde_domain_size.trailing_zeros() as f64;
+    let deep_security = num_ood_points
+        * (field_bits - security::log2(blowup * (trace_length + 1.0) + trace_length - 1.0));
+    security::to_bits(commit_security.min(deep_security))
+}
+
+/// Computes security (in bits) we get by executing the number of query rounds specified by the
+/// provided options.
+pub(crate) fn get_query_security(options: &ProofOptions) -> u32 {
+    let security_per_query = get_security_per_query(options);
     let mut query_security = security_per_query * options.num_queries() as u32;
 
     // include grinding factor contributions only for proofs adequate security
     if query_security >= GRINDING_CONTRIBUTION_FLOOR {
         query_security += options.grinding_factor();
     }
+    query_security
+}
 
-    cmp::min(
-        cmp::min(field_security, query_security) - 1,
-        collision_resistance,
-    )
+/// Computes security (in bits) added to a proof by each query round executed with the specified
+/// options.
+pub(crate) fn get_security_per_query(options: &ProofOptions) -> u32 {
+    log2(options.blowup_factor())
 }
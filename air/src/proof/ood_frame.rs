@@ -17,21 +17,27 @@ type ParsedOodFrame<E> = (EvaluationFrame<E>, Option<EvaluationFrame<E>>, Vec<E>
 
 // OUT-OF-DOMAIN FRAME
 // ================================================================================================
-/// Trace and constraint polynomial evaluations at an out-of-domain point.
+/// Trace and constraint polynomial evaluations at one or more out-of-domain points.
 ///
-/// This struct contains the following evaluations:
+/// For each out-of-domain point *z*, this struct contains the following evaluations:
 /// * Evaluations of all trace polynomials at *z*.
-/// * Evaluations of all trace polynomials at *z * g*.
+/// * Evaluations of all trace polynomials at *z * g^k* for every other row offset *k* of the
+///   evaluation frame (for the default frame, this is just *z * g*).
 /// * Evaluations of constraint composition column polynomials at *z*.
+/// * Evaluation of the combined trace column at *z*, if the proof commits to such a column.
 ///
-/// where *z* is an out-of-domain point and *g* is the generator of the trace domain.
+/// where *g* is the generator of the trace domain. When more than one out-of-domain point is
+/// used, evaluations for the first point are followed by evaluations for the second point etc.
 ///
 /// Internally, the evaluations are stored as a sequence of bytes. Thus, to retrieve the
-/// evaluations, [parse()](OodFrame::parse) function should be used.
+/// evaluations, [parse()](OodFrame::parse) or [parse_points()](OodFrame::parse_points) function
+/// should be used; evaluations of the combined trace column can be retrieved via
+/// [parse_combined_trace_evaluations()](OodFrame::parse_combined_trace_evaluations).
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct OodFrame {
     trace_states: Vec<u8>,
     evaluations: Vec<u8>,
+    combined_trace_evaluations: Vec<u8>,
 }
 
 impl OodFrame {
@@ -47,6 +53,12 @@ impl OodFrame {
             self.trace_states.is_empty(),
             "trace sates have already been set"
         );
+        self.add_trace_states(trace_states);
+    }
+
+    /// Appends trace states for the next out-of-domain point to the trace state portion of this
+    /// out-of-domain frame.
+    pub fn add_trace_states<E: FieldElement>(&mut self, trace_states: &[Vec<E>]) {
         for trace_state in trace_states {
             trace_state.write_into(&mut self.trace_states);
         }
@@ -63,6 +75,15 @@ impl OodFrame {
             self.evaluations.is_empty(),
             "constraint evaluations have already been set"
         );
+        self.add_constraint_evaluations(evaluations);
+    }
+
+    /// Appends constraint evaluations for the next out-of-domain point to the constraint
+    /// evaluation portion of this out-of-domain frame.
+    ///
+    /// # Panics
+    /// Panics if `evaluations` is an empty vector.
+    pub fn add_constraint_evaluations<E: FieldElement>(&mut self, evaluations: &[E]) {
         assert!(
             !evaluations.is_empty(),
             "cannot set to empty constraint evaluations"
@@ -70,18 +91,46 @@ impl OodFrame {
         evaluations.write_into(&mut self.evaluations)
     }
 
+    /// Appends the evaluation of the combined trace column at the next out-of-domain point to
+    /// this out-of-domain frame.
+    pub fn add_combined_trace_evaluation<E: FieldElement>(&mut self, evaluation: E) {
+        evaluation.write_into(&mut self.combined_trace_evaluations);
+    }
+
     // PARSER
     // --------------------------------------------------------------------------------------------
+    /// Returns evaluations of the combined trace column at each of the `num_points` out-of-domain
+    /// points contained in `self`; for proofs without a combined trace column, `num_points`
+    /// should be zero.
+    ///
+    /// # Errors
+    /// Returns an error if `num_points` evaluations could not be parsed from the internal bytes,
+    /// or if any unconsumed bytes remained after the parsing was complete.
+    pub fn parse_combined_trace_evaluations<E: FieldElement>(
+        &self,
+        num_points: usize,
+    ) -> Result<Vec<E>, DeserializationError> {
+        let mut reader = SliceReader::new(&self.combined_trace_evaluations);
+        let evaluations = E::read_batch_from(&mut reader, num_points)?;
+        if reader.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(evaluations)
+    }
+
     /// Returns main and auxiliary (if any) trace evaluation frames and a vector of out-of-domain
     /// constraint evaluations contained in `self`.
     ///
+    /// This assumes that `self` contains evaluations for a single out-of-domain point.
+    ///
     /// # Panics
-    /// Panics if either `main_trace_width` or `num_evaluations` are equal to zero.
+    /// Panics if either `main_trace_width` or `num_evaluations` are equal to zero, or if
+    /// `frame_offsets` are not valid row offsets of an [EvaluationFrame].
     ///
     /// # Errors
     /// Returns an error if:
-    /// * Valid [EvaluationFrame]s for the specified `main_trace_width` and `aux_trace_width`
-    ///   could not be parsed from the internal bytes.
+    /// * Valid [EvaluationFrame]s for the specified `main_trace_width`, `aux_trace_width`, and
+    ///   `frame_offsets` could not be parsed from the internal bytes.
     /// * A vector of evaluations specified by `num_evaluations` could not be parsed from the
     ///   internal bytes.
     /// * Any unconsumed bytes remained after the parsing was complete.
@@ -89,37 +138,84 @@ impl OodFrame {
         self,
         main_trace_width: usize,
         aux_trace_width: usize,
+        frame_offsets: &[usize],
         num_evaluations: usize,
     ) -> Result<ParsedOodFrame<E>, DeserializationError> {
+        let mut frames = self.parse_points(
+            main_trace_width,
+            aux_trace_width,
+            frame_offsets,
+            num_evaluations,
+            1,
+        )?;
+        Ok(frames.remove(0))
+    }
+
+    /// Returns main and auxiliary (if any) trace evaluation frames and a vector of out-of-domain
+    /// constraint evaluations for each of the `num_points` out-of-domain points contained in
+    /// `self`.
+    ///
+    /// The frames take ownership of the parsed rows, and thus, no memory beyond what is needed
+    /// to hold the parsed values is allocated. Unlike frames used by the prover during constraint
+    /// evaluation, these frames are built once per out-of-domain point and are not reused.
+    ///
+    /// For every point, trace states are expected to be stored one row offset after another, with
+    /// main trace values of a row followed by auxiliary trace values of the same row.
+    ///
+    /// # Panics
+    /// Panics if either `main_trace_width`, `num_evaluations`, or `num_points` are equal to zero,
+    /// or if `frame_offsets` are not valid row offsets of an [EvaluationFrame].
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * Valid [EvaluationFrame]s for the specified `main_trace_width`, `aux_trace_width`, and
+    ///   `frame_offsets` could not be parsed from the internal bytes for each of the points.
+    /// * A vector of evaluations specified by `num_evaluations` could not be parsed from the
+    ///   internal bytes for each of the points.
+    /// * Any unconsumed bytes remained after the parsing was complete.
+    pub fn parse_points<E: FieldElement>(
+        self,
+        main_trace_width: usize,
+        aux_trace_width: usize,
+        frame_offsets: &[usize],
+        num_evaluations: usize,
+        num_points: usize,
+    ) -> Result<Vec<ParsedOodFrame<E>>, DeserializationError> {
         assert!(main_trace_width > 0, "trace width cannot be zero");
         assert!(num_evaluations > 0, "number of evaluations cannot be zero");
+        assert!(num_points > 0, "number of OOD points cannot be zero");
 
-        // parse current and next trace states for main and auxiliary trace evaluation frames
-        let mut reader = SliceReader::new(&self.trace_states);
-        let current = E::read_batch_from(&mut reader, main_trace_width)?;
-        let current_aux = E::read_batch_from(&mut reader, aux_trace_width)?;
-        let next = E::read_batch_from(&mut reader, main_trace_width)?;
-        let next_aux = E::read_batch_from(&mut reader, aux_trace_width)?;
-        if reader.has_more_bytes() {
-            return Err(DeserializationError::UnconsumedBytes);
-        }
+        let mut trace_reader = SliceReader::new(&self.trace_states);
+        let mut evaluation_reader = SliceReader::new(&self.evaluations);
+        let mut result = Vec::with_capacity(num_points);
+        for _ in 0..num_points {
+            // parse trace states at every row offset for main and auxiliary trace evaluation
+            // frames
+            let mut main_rows = Vec::with_capacity(frame_offsets.len());
+            let mut aux_rows = Vec::with_capacity(frame_offsets.len());
+            for _ in 0..frame_offsets.len() {
+                main_rows.push(E::read_batch_from(&mut trace_reader, main_trace_width)?);
+                aux_rows.push(E::read_batch_from(&mut trace_reader, aux_trace_width)?);
+            }
 
-        // instantiate the frames from the parsed rows
-        let main_frame = EvaluationFrame::from_rows(current, next);
-        let aux_frame = if aux_trace_width > 0 {
-            Some(EvaluationFrame::from_rows(current_aux, next_aux))
-        } else {
-            None
-        };
-
-        // parse the constraint evaluations
-        let mut reader = SliceReader::new(&self.evaluations);
-        let evaluations = E::read_batch_from(&mut reader, num_evaluations)?;
-        if reader.has_more_bytes() {
+            // instantiate the frames from the parsed rows
+            let main_frame = EvaluationFrame::from_offset_rows(frame_offsets, main_rows);
+            let aux_frame = if aux_trace_width > 0 {
+                Some(EvaluationFrame::from_offset_rows(frame_offsets, aux_rows))
+            } else {
+                None
+            };
+
+            // parse the constraint evaluations
+            let evaluations = E::read_batch_from(&mut evaluation_reader, num_evaluations)?;
+
+            result.push((main_frame, aux_frame, evaluations));
+        }
+        if trace_reader.has_more_bytes() || evaluation_reader.has_more_bytes() {
             return Err(DeserializationError::UnconsumedBytes);
         }
 
-        Ok((main_frame, aux_frame, evaluations))
+        Ok(result)
     }
 }
 
@@ -132,7 +228,11 @@ impl Serializable for OodFrame {
 
         // write constraint evaluations row
         target.write_u16(self.evaluations.len() as u16);
-        target.write_u8_slice(&self.evaluations)
+        target.write_u8_slice(&self.evaluations);
+
+        // write combined trace column evaluations
+        target.write_u16(self.combined_trace_evaluations.len() as u16);
+        target.write_u8_slice(&self.combined_trace_evaluations)
     }
 }
 
@@ -150,9 +250,14 @@ impl Deserializable for OodFrame {
         let num_constraint_evaluation_bytes = source.read_u16()? as usize;
         let evaluations = source.read_u8_vec(num_constraint_evaluation_bytes)?;
 
+        // read combined trace column evaluations
+        let num_combined_evaluation_bytes = source.read_u16()? as usize;
+        let combined_trace_evaluations = source.read_u8_vec(num_combined_evaluation_bytes)?;
+
         Ok(OodFrame {
             trace_states,
             evaluations,
+            combined_trace_evaluations,
         })
     }
 }
@@ -4,8 +4,9 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::Table;
-use crypto::{BatchMerkleProof, ElementHasher, Hasher};
-use math::{log2, FieldElement};
+use crate::LeafEncoding;
+use crypto::{BatchMerkleProof, ElementHasher, Hasher, MerkleTree, VectorCommitment};
+use math::FieldElement;
 use utils::{
     collections::Vec, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
     SliceReader,
@@ -27,7 +28,25 @@ use utils::{
 /// Internally, all Merkle paths and query values are stored as a sequence of bytes. Thus, to
 /// retrieve query values and the corresponding Merkle authentication paths,
 /// [parse()](Queries::parse) function should be used.
-#[derive(Debug, Clone, Eq, PartialEq)]
+///
+/// When leaves of the commitment are raw rows (see [LeafEncoding]), the leaves adjacent to queried
+/// leaves in Merkle authentication paths are also raw rows; such queries are built via
+/// [with_raw_leaves()](Queries::with_raw_leaves), and store these leaves as row bytes rather than
+/// as full digests.
+///
+/// When leaves of the commitment are salted (see [ProofOptions::with_zk()](crate::ProofOptions::with_zk)),
+/// salts of queried leaves are stored in front of the Merkle authentication paths; such queries
+/// are built via [with_salts()](Queries::with_salts) and parsed via
+/// [parse_salted()](Queries::parse_salted).
+///
+/// Commitments built with vector commitment schemes other than Merkle trees (see
+/// [VectorCommitment]) are decommitted to in the same way; such queries are built via
+/// [with_commitment_proof()](Queries::with_commitment_proof) and parsed via
+/// [parse_with_commitment()](Queries::parse_with_commitment).
+///
+/// The default value of this struct contains no queries; such a value is used for constraint
+/// queries of proofs in which the constraint composition polynomial is sent explicitly.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct Queries {
     paths: Vec<u8>,
     values: Vec<u8>,
@@ -51,26 +70,7 @@ impl Queries {
         merkle_proof: BatchMerkleProof<H>,
         query_values: Vec<Vec<E>>,
     ) -> Self {
-        assert!(!query_values.is_empty(), "query values cannot be empty");
-        let elements_per_query = query_values[0].len();
-        assert_ne!(
-            elements_per_query, 0,
-            "a query must contain at least one evaluation"
-        );
-
-        // TODO: add debug check that values actually hash into the leaf nodes of the batch proof
-
-        // concatenate all elements together into a single vector of bytes
-        let num_queries = query_values.len();
-        let mut values = Vec::with_capacity(num_queries * elements_per_query * E::ELEMENT_BYTES);
-        for elements in query_values.iter() {
-            assert_eq!(
-                elements.len(),
-                elements_per_query,
-                "all queries must contain the same number of evaluations"
-            );
-            values.write(elements);
-        }
+        let values = serialize_values(&query_values);
 
         // serialize internal nodes of the batch Merkle proof; we care about internal nodes only
         // because leaf nodes can be reconstructed from hashes of query values
@@ -79,6 +79,109 @@ impl Queries {
         Queries { paths, values }
     }
 
+    /// Returns queries constructed from rows of a commitment with raw leaves at the specified
+    /// leaf indexes and their corresponding Merkle authentication paths.
+    ///
+    /// Queried leaves are reconstructed from the query values, and thus, are not stored; the
+    /// leaves adjacent to queried leaves (i.e., the first nodes of authentication paths for
+    /// leaves whose siblings were not queried) are stored as row bytes, and a bit mask marking
+    /// the paths starting with such leaves is stored in front of the paths.
+    ///
+    /// # Panics
+    /// Panics if any of the conditions listed for [new()](Queries::new) is violated.
+    pub fn with_raw_leaves<H: Hasher, E: FieldElement>(
+        merkle_proof: BatchMerkleProof<H>,
+        query_values: Vec<Vec<E>>,
+        leaf_indexes: &[usize],
+    ) -> Self {
+        let values = serialize_values(&query_values);
+        let row_bytes = query_values[0].len() * E::ELEMENT_BYTES;
+        let paths = merkle_proof.serialize_nodes_with_raw_leaves(leaf_indexes, row_bytes);
+
+        Queries { paths, values }
+    }
+
+    /// Returns queries constructed from evaluations of a set of functions at some number of points
+    /// in a domain, salts of the leaves into which these evaluations are committed, and the
+    /// corresponding Merkle authentication paths.
+    ///
+    /// Salts must be provided in the same order as query values; a leaf of the Merkle proof is
+    /// built from a hash of the query values and the corresponding salt (see
+    /// [LeafEncoding::salted_leaf()]).
+    ///
+    /// # Panics
+    /// Panics if any of the conditions listed for [new()](Queries::new) is violated, or if the
+    /// number of salts is different from the number of queries.
+    pub fn with_salts<H: Hasher, E: FieldElement>(
+        merkle_proof: BatchMerkleProof<H>,
+        query_values: Vec<Vec<E>>,
+        salts: Vec<H::Digest>,
+    ) -> Self {
+        assert_eq!(
+            query_values.len(),
+            salts.len(),
+            "number of salts must be equal to the number of queries"
+        );
+        let values = serialize_values(&query_values);
+
+        let mut paths = Vec::new();
+        H::Digest::write_batch_into(&salts, &mut paths);
+        paths.extend_from_slice(&merkle_proof.serialize_nodes());
+
+        Queries { paths, values }
+    }
+
+    /// Returns queries constructed from evaluations of a set of functions at some number of points
+    /// in a domain and a proof opening a vector commitment of type `V` at the leaves containing
+    /// these points; `leaf_indexes` must be the indexes at which the commitment was opened.
+    ///
+    /// The proof is serialized in the same way as by the constructors specific to Merkle trees:
+    /// rows are encoded into leaves using the specified `leaf_encoding`, and if `salts` are
+    /// provided, they are stored in front of the proof (see [with_salts()](Queries::with_salts)).
+    /// Thus, for [MerkleTree](crypto::MerkleTree) the resulting queries are the same as the ones
+    /// built by [new()](Queries::new), [with_raw_leaves()](Queries::with_raw_leaves), and
+    /// [with_salts()](Queries::with_salts).
+    ///
+    /// # Panics
+    /// Panics if any of the conditions listed for [new()](Queries::new) is violated, or if salts
+    /// are provided and their number is different from the number of queries.
+    pub fn with_commitment_proof<H, V, E>(
+        proof: V::MultiProof,
+        query_values: Vec<Vec<E>>,
+        leaf_indexes: &[usize],
+        leaf_encoding: LeafEncoding,
+        salts: Option<Vec<H::Digest>>,
+    ) -> Self
+    where
+        H: Hasher,
+        V: VectorCommitment<H>,
+        E: FieldElement,
+    {
+        let values = serialize_values(&query_values);
+
+        let mut paths = Vec::new();
+        match (leaf_encoding, salts) {
+            (_, Some(salts)) => {
+                assert_eq!(
+                    query_values.len(),
+                    salts.len(),
+                    "number of salts must be equal to the number of queries"
+                );
+                H::Digest::write_batch_into(&salts, &mut paths);
+                V::write_multiproof(&proof, leaf_indexes, None, &mut paths);
+            }
+            (LeafEncoding::Hashed, None) => {
+                V::write_multiproof(&proof, leaf_indexes, None, &mut paths);
+            }
+            (LeafEncoding::Raw, None) => {
+                let row_bytes = query_values[0].len() * E::ELEMENT_BYTES;
+                V::write_multiproof(&proof, leaf_indexes, Some(row_bytes), &mut paths);
+            }
+        }
+
+        Queries { paths, values }
+    }
+
     // PARSER
     // --------------------------------------------------------------------------------------------
     /// Convert internally stored bytes into a set of query values and the corresponding Merkle
@@ -98,6 +201,138 @@ impl Queries {
     where
         E: FieldElement,
         H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.parse_with_encoding(
+            domain_size,
+            num_queries,
+            values_per_query,
+            LeafEncoding::Hashed,
+        )
+    }
+
+    /// Convert internally stored bytes into a set of query values and the corresponding Merkle
+    /// authentication paths, where leaves of the Merkle tree are encoded from the query values
+    /// using the specified `leaf_encoding`.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `domain_size` is not a power of two.
+    /// * `num_queries` is zero.
+    /// * `values_per_query` is zero.
+    pub fn parse_with_encoding<H, E>(
+        self,
+        domain_size: usize,
+        num_queries: usize,
+        values_per_query: usize,
+        leaf_encoding: LeafEncoding,
+    ) -> Result<(BatchMerkleProof<H>, Table<E>), DeserializationError>
+    where
+        E: FieldElement,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.parse_with_arity(domain_size, num_queries, values_per_query, leaf_encoding, 2)
+    }
+
+    /// Convert internally stored bytes into a set of query values and the corresponding Merkle
+    /// authentication paths, where leaves of the Merkle tree are encoded from the query values
+    /// using the specified `leaf_encoding`, and internal nodes of the tree have `merkle_arity`
+    /// children.
+    ///
+    /// # Errors
+    /// In addition to errors returned when the bytes cannot be parsed, returns an error if
+    /// `merkle_arity` is not 2, 4, or 8, or if raw leaves are used with arity other than 2.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `domain_size` is not a power of two.
+    /// * `num_queries` is zero.
+    /// * `values_per_query` is zero.
+    pub fn parse_with_arity<H, E>(
+        self,
+        domain_size: usize,
+        num_queries: usize,
+        values_per_query: usize,
+        leaf_encoding: LeafEncoding,
+        merkle_arity: usize,
+    ) -> Result<(BatchMerkleProof<H>, Table<E>), DeserializationError>
+    where
+        E: FieldElement,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.parse_with_commitment::<H, MerkleTree<H>, E>(
+            domain_size,
+            num_queries,
+            values_per_query,
+            leaf_encoding,
+            false,
+            merkle_arity,
+        )
+    }
+
+    /// Convert internally stored bytes into a set of query values and the corresponding Merkle
+    /// authentication paths, where leaves of the Merkle tree are built from hashes of the query
+    /// values and salts stored in front of the paths (see [with_salts()](Queries::with_salts)),
+    /// and internal nodes of the tree have `merkle_arity` children.
+    ///
+    /// # Errors
+    /// In addition to errors returned when the bytes cannot be parsed, returns an error if
+    /// `merkle_arity` is not 2, 4, or 8.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `domain_size` is not a power of two.
+    /// * `num_queries` is zero.
+    /// * `values_per_query` is zero.
+    pub fn parse_salted<H, E>(
+        self,
+        domain_size: usize,
+        num_queries: usize,
+        values_per_query: usize,
+        merkle_arity: usize,
+    ) -> Result<(BatchMerkleProof<H>, Table<E>), DeserializationError>
+    where
+        E: FieldElement,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.parse_with_commitment::<H, MerkleTree<H>, E>(
+            domain_size,
+            num_queries,
+            values_per_query,
+            LeafEncoding::Hashed,
+            true,
+            merkle_arity,
+        )
+    }
+
+    /// Convert internally stored bytes into a set of query values and the corresponding proof
+    /// opening a vector commitment of type `V` with the specified `arity`.
+    ///
+    /// Items opened by the proof are encoded from the query values using the specified
+    /// `leaf_encoding`; if the commitment is `salted`, items are built from hashes of the query
+    /// values and salts stored in front of the proof (see [with_salts()](Queries::with_salts)).
+    ///
+    /// # Errors
+    /// Returns an error if the bytes cannot be parsed, or if the proof cannot be read by the
+    /// commitment scheme.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `domain_size` is not a power of two.
+    /// * `num_queries` is zero.
+    /// * `values_per_query` is zero.
+    pub fn parse_with_commitment<H, V, E>(
+        self,
+        domain_size: usize,
+        num_queries: usize,
+        values_per_query: usize,
+        leaf_encoding: LeafEncoding,
+        salted: bool,
+        arity: usize,
+    ) -> Result<(V::MultiProof, Table<E>), DeserializationError>
+    where
+        E: FieldElement,
+        H: ElementHasher<BaseField = E::BaseField>,
+        V: VectorCommitment<H>,
     {
         assert!(
             domain_size.is_power_of_two(),
@@ -120,23 +355,48 @@ impl Queries {
             )));
         }
 
-        // read bytes corresponding to each query, convert them into field elements,
-        // and also hash them to build leaf nodes of the batch Merkle proof
+        // read bytes corresponding to each query, convert them into field elements, and also
+        // encode them to build the items opened by the proof
         let query_values = Table::<E>::from_bytes(&self.values, num_queries, values_per_query)?;
-        let hashed_queries = query_values
-            .rows()
-            .map(|row| H::hash_elements(row))
-            .collect();
-
-        // build batch Merkle proof
         let mut reader = SliceReader::new(&self.paths);
-        let tree_depth = log2(domain_size) as u8;
-        let merkle_proof = BatchMerkleProof::deserialize(&mut reader, hashed_queries, tree_depth)?;
+        let proof = match (leaf_encoding, salted) {
+            (_, true) => {
+                // read salts of queried leaves, and build the items from hashes of query values
+                // and their salts
+                let salts = H::Digest::read_batch_from(&mut reader, num_queries)?;
+                let salted_queries = query_values
+                    .rows()
+                    .zip(salts)
+                    .map(|(row, salt)| LeafEncoding::salted_leaf::<H>(H::hash_elements(row), salt))
+                    .collect();
+                V::read_multiproof(&mut reader, salted_queries, None, domain_size, arity)?
+            }
+            (LeafEncoding::Hashed, false) => {
+                let hashed_queries = query_values
+                    .rows()
+                    .map(|row| H::hash_elements(row))
+                    .collect();
+                V::read_multiproof(&mut reader, hashed_queries, None, domain_size, arity)?
+            }
+            (LeafEncoding::Raw, false) => {
+                let mut raw_queries = Vec::with_capacity(num_queries);
+                for query in self.values.chunks(num_query_bytes) {
+                    raw_queries.push(LeafEncoding::raw_leaf::<H>(query)?);
+                }
+                V::read_multiproof(
+                    &mut reader,
+                    raw_queries,
+                    Some(num_query_bytes),
+                    domain_size,
+                    arity,
+                )?
+            }
+        };
         if reader.has_more_bytes() {
             return Err(DeserializationError::UnconsumedBytes);
         }
 
-        Ok((merkle_proof, query_values))
+        Ok((proof, query_values))
     }
 }
 
@@ -170,3 +430,31 @@ impl Deserializable for Queries {
         Ok(Queries { paths, values })
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Concatenates all query values into a single vector of bytes.
+fn serialize_values<E: FieldElement>(query_values: &[Vec<E>]) -> Vec<u8> {
+    assert!(!query_values.is_empty(), "query values cannot be empty");
+    let elements_per_query = query_values[0].len();
+    assert_ne!(
+        elements_per_query, 0,
+        "a query must contain at least one evaluation"
+    );
+
+    // TODO: add debug check that values actually hash into the leaf nodes of the batch proof
+
+    // concatenate all elements together into a single vector of bytes
+    let num_queries = query_values.len();
+    let mut values = Vec::with_capacity(num_queries * elements_per_query * E::ELEMENT_BYTES);
+    for elements in query_values.iter() {
+        assert_eq!(
+            elements.len(),
+            elements_per_query,
+            "all queries must contain the same number of evaluations"
+        );
+        values.write(elements);
+    }
+    values
+}
@@ -0,0 +1,297 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{get_field_security, get_security_per_query, GRINDING_CONTRIBUTION_FLOOR};
+use crate::ProofOptions;
+use core::cmp;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of points at which trace polynomials are opened for every out-of-domain point (i.e.,
+/// $z$ and $z \cdot g$); this is used in the DEEP term of the provable security estimate.
+const NUM_OOD_OPENINGS: f64 = 2.0;
+
+/// Smallest Johnson bound proximity parameter $m$ considered by the provable security estimate.
+const MIN_JOHNSON_PARAM: u32 = 3;
+
+/// Largest Johnson bound proximity parameter $m$ considered by the provable security estimate;
+/// beyond this, the FRI commit phase term decreases faster than the query phase term grows for
+/// all supported parameters.
+const MAX_JOHNSON_PARAM: u32 = 256;
+
+// SECURITY ESTIMATE
+// ================================================================================================
+/// Breakdown of the security level of STARK proofs (in bits) into contributions of individual
+/// protocol parameters.
+///
+/// The security level is the smallest of:
+/// * Security contributed by the field from which random values are drawn, minus one bit.
+/// * Security contributed by the query phase of the protocol (including grinding), minus one bit.
+/// * Collision resistance of the hash function.
+///
+/// The contribution which determines the security level is reported by
+/// [bottleneck()](SecurityEstimate::bottleneck).
+///
+/// An estimate can be either conjectured or provable. A conjectured estimate relies on the
+/// conjecture that Reed-Solomon codes are list-decodable up to capacity, and thus, every query
+/// contributes $log_2(blowup)$ bits. A provable estimate relies only on the Johnson bound, and
+/// follows the round-by-round soundness analysis of
+/// [eprint 2021/582](https://eprint.iacr.org/2021/582) and
+/// [eprint 2022/1216](https://eprint.iacr.org/2022/1216): a proximity parameter $m \geq 3$
+/// defines the list size $L = (m + 1/2) / \sqrt{\rho}$ and the agreement
+/// $\alpha = (1 + 1/(2m)) \cdot \sqrt{\rho}$, where $\rho = 1 / blowup$; the estimate is
+/// computed for the value of $m$ which yields the highest security level.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SecurityEstimate {
+    field_security: u32,
+    query_security: u32,
+    grinding_bits: u32,
+    collision_resistance: u32,
+}
+
+/// Contribution which determines the security level of a [SecurityEstimate].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SecurityBottleneck {
+    /// Security is limited by the size of the field from which random values are drawn.
+    Field,
+    /// Security is limited by the number of queries, the blowup factor, and the grinding factor.
+    Queries,
+    /// Security is limited by collision resistance of the hash function.
+    CollisionResistance,
+}
+
+impl SecurityEstimate {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a conjectured security estimate for proofs generated with the specified options
+    /// for a computation over a base field with a `base_field_bits`-bit modulus and an LDE domain
+    /// of the specified size, using a hash function with the specified collision resistance.
+    pub(crate) fn conjectured(
+        options: &ProofOptions,
+        base_field_bits: u32,
+        lde_domain_size: u64,
+        collision_resistance: u32,
+    ) -> Self {
+        let field_security = get_field_security(
+            options,
+            base_field_bits,
+            options.field_extension().degree(),
+            lde_domain_size,
+        );
+        let query_security = get_security_per_query(options) * options.num_queries() as u32;
+
+        // grinding factor contributes only to proofs of adequate security
+        let grinding_bits = if query_security >= GRINDING_CONTRIBUTION_FLOOR {
+            options.grinding_factor()
+        } else {
+            0
+        };
+
+        SecurityEstimate {
+            field_security,
+            query_security,
+            grinding_bits,
+            collision_resistance,
+        }
+    }
+
+    /// Returns a provable security estimate for proofs generated with the specified options
+    /// for a computation over a base field with a `base_field_bits`-bit modulus and an LDE domain
+    /// of the specified size, using a hash function with the specified collision resistance.
+    pub(crate) fn proven(
+        options: &ProofOptions,
+        base_field_bits: u32,
+        lde_domain_size: u64,
+        collision_resistance: u32,
+    ) -> Self {
+        // use the proximity parameter which yields the highest security; if several parameters
+        // yield the same security, the smallest one is used
+        let mut result = Self::proven_for_param(
+            options,
+            base_field_bits,
+            lde_domain_size,
+            collision_resistance,
+            MIN_JOHNSON_PARAM,
+        );
+        for m in MIN_JOHNSON_PARAM + 1..=MAX_JOHNSON_PARAM {
+            let estimate = Self::proven_for_param(
+                options,
+                base_field_bits,
+                lde_domain_size,
+                collision_resistance,
+                m,
+            );
+            if estimate.stark_security() > result.stark_security() {
+                result = estimate;
+            }
+        }
+        result
+    }
+
+    /// Returns a provable security estimate computed for the Johnson bound proximity parameter
+    /// `m`.
+    ///
+    /// The field contributes the smallest of the following terms, where $|F|$ is the size of the
+    /// field from which random values are drawn, $n$ is the trace length, and $L$ is the list
+    /// size implied by `m`:
+    /// * ALI: $|F|^p / L$, where $p$ is the number of out-of-domain points; a separate constraint
+    ///   composition polynomial, built with its own set of random coefficients, is checked at each
+    ///   of the points.
+    /// * DEEP: $(|F| / (d \cdot (n + k - 1) + n - 1))^p / L$, where $k$ is the number of points at
+    ///   which trace polynomials are opened for every out-of-domain point and the blowup factor
+    ///   $d$ bounds the degree of constraints.
+    /// * FRI commit phase: $|F| \cdot \rho^{3/2} / (0.5 \cdot (m + 1/2)^7 \cdot |D|^2)$, where $|D|$
+    ///   is the size of the LDE domain.
+    ///
+    /// The queries contribute $-num\_queries \cdot log_2(\alpha)$ bits.
+    fn proven_for_param(
+        options: &ProofOptions,
+        base_field_bits: u32,
+        lde_domain_size: u64,
+        collision_resistance: u32,
+        m: u32,
+    ) -> Self {
+        let field_bits = (base_field_bits * options.field_extension().degree()) as f64;
+        let blowup = options.blowup_factor() as f64;
+        let lde_domain_size = lde_domain_size as f64;
+        let trace_length = lde_domain_size / blowup;
+        let m = m as f64;
+
+        let num_ood_points = options.num_ood_points() as f64;
+        let log_list_size = log2(m + 0.5) + 0.5 * log2(blowup);
+        let ali_security = num_ood_points * field_bits - log_list_size;
+        let deep_security = num_ood_points
+            * (field_bits
+                - log2(blowup * (trace_length + NUM_OOD_OPENINGS - 1.0) + trace_length - 1.0))
+            - log_list_size;
+        let commit_security = field_bits
+            - (7.0 * log2(m + 0.5) + 1.5 * log2(blowup) + 2.0 * log2(lde_domain_size) - 1.0);
+        let field_security = to_bits(ali_security.min(deep_security).min(commit_security));
+
+        let log_alpha = log2(1.0 + 0.5 / m) - 0.5 * log2(blowup);
+        let query_security = to_bits(-log_alpha * options.num_queries() as f64);
+
+        // grinding factor contributes only to proofs of adequate security
+        let grinding_bits = if query_security >= GRINDING_CONTRIBUTION_FLOOR {
+            options.grinding_factor()
+        } else {
+            0
+        };
+
+        SecurityEstimate {
+            field_security,
+            query_security,
+            grinding_bits,
+            collision_resistance,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns security (in bits) contributed by the field from which random values are drawn.
+    ///
+    /// For conjectured estimates with a field extension of degree $e$ over a base field of $b$
+    /// bits and an LDE domain of size $2^d$, this is the smaller of $e \cdot b - d$ bits (for the
+    /// FRI commit phase) and the security of the DEEP/ALI step, which grows with the number of
+    /// out-of-domain points. For provable estimates, this is the smallest of the ALI, DEEP, and
+    /// FRI commit phase terms implied by the list-decoding bound.
+    pub fn field_security(&self) -> u32 {
+        self.field_security
+    }
+
+    /// Returns security (in bits) contributed by the queries alone (i.e., excluding grinding).
+    ///
+    /// For conjectured estimates, this is $log_2(blowup) \cdot num\_queries$ bits. For provable
+    /// estimates, this is $-log_2(\alpha) \cdot num\_queries$ bits, where $\alpha$ is the
+    /// agreement implied by the Johnson bound.
+    pub fn query_security(&self) -> u32 {
+        self.query_security
+    }
+
+    /// Returns security (in bits) contributed by query seed grinding.
+    ///
+    /// This is the grinding factor of the proof options, unless the queries alone contribute
+    /// fewer than 80 bits, in which case grinding is not taken into account.
+    pub fn grinding_bits(&self) -> u32 {
+        self.grinding_bits
+    }
+
+    /// Returns collision resistance (in bits) of the hash function.
+    pub fn collision_resistance(&self) -> u32 {
+        self.collision_resistance
+    }
+
+    /// Returns the security level (in bits) implied by the individual contributions.
+    pub fn security_level(&self) -> u32 {
+        cmp::min(
+            self.stark_security().saturating_sub(1),
+            self.collision_resistance,
+        )
+    }
+
+    /// Returns the contribution which determines the security level.
+    ///
+    /// When several contributions limit security to the same level, collision resistance takes
+    /// precedence over the field, and the field takes precedence over the queries.
+    pub fn bottleneck(&self) -> SecurityBottleneck {
+        let query_security = self.query_security + self.grinding_bits;
+        if self.collision_resistance < cmp::min(self.field_security, query_security) {
+            SecurityBottleneck::CollisionResistance
+        } else if self.field_security <= query_security {
+            SecurityBottleneck::Field
+        } else {
+            SecurityBottleneck::Queries
+        }
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns security (in bits) contributed by the field and the queries, disregarding the hash
+    /// function.
+    fn stark_security(&self) -> u32 {
+        cmp::min(
+            self.field_security,
+            self.query_security + self.grinding_bits,
+        )
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Converts a (possibly negative) number of bits of security into a whole number of bits.
+pub(super) fn to_bits(security: f64) -> u32 {
+    if security <= 0.0 {
+        0
+    } else {
+        security as u32
+    }
+}
+
+/// Returns the base 2 logarithm of the specified positive value.
+///
+/// Floating point logarithms are not available in `no_std` environments, and thus, the value is
+/// split into an exponent and a mantissa $m \in [1, 2)$, and the natural logarithm of the
+/// mantissa is computed as $2 \cdot atanh((m - 1) / (m + 1))$ using the Taylor series of $atanh$.
+pub(super) fn log2(value: f64) -> f64 {
+    debug_assert!(value > 0.0, "logarithm is defined only for positive values");
+    let bits = value.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mantissa = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | 0x3ff0_0000_0000_0000);
+
+    // y is in [0, 1/3), and thus, 24 terms of the series are more than enough for f64 precision
+    let y = (mantissa - 1.0) / (mantissa + 1.0);
+    let y2 = y * y;
+    let mut term = y;
+    let mut atanh = 0.0;
+    for i in 0..24 {
+        atanh += term / (2 * i + 1) as f64;
+        term *= y2;
+    }
+    exponent as f64 + 2.0 * atanh / core::f64::consts::LN_2
+}
@@ -0,0 +1,158 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::StarkProof;
+use core::fmt;
+use utils::{collections::Vec, Serializable};
+
+// PROOF SIZE BREAKDOWN
+// ================================================================================================
+/// Breakdown of the size of a serialized STARK proof (in bytes) into sizes of its components.
+///
+/// Sizes of all components add up to the size of the proof serialized via
+/// [StarkProof::to_bytes()]. When formatted via [Display](fmt::Display), the breakdown is
+/// rendered as a table with one row per component.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProofSizeBreakdown {
+    context: usize,
+    commitments: usize,
+    trace_queries: usize,
+    constraint_queries: usize,
+    composition_coefficients: usize,
+    ood_frame: usize,
+    fri_layers: Vec<usize>,
+    fri_remainder: usize,
+    pow_nonce: usize,
+}
+
+impl ProofSizeBreakdown {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a breakdown of the size of the specified proof.
+    ///
+    /// Every component is measured by serializing it in the same way as [StarkProof::to_bytes()]
+    /// does, and thus, the breakdown stays consistent with the serialization format.
+    pub(crate) fn new(proof: &StarkProof) -> Self {
+        let fri_layers = proof.fri_proof.layer_sizes();
+        let fri_size = proof.fri_proof.to_bytes().len();
+
+        let mut pow_nonce = proof.options().grinding_nonce_bytes();
+        if proof.options().uses_time_boxed_grinding() {
+            // the number of extra queries is serialized as a single byte
+            pow_nonce += 1;
+        }
+
+        ProofSizeBreakdown {
+            // the format version is serialized as a single byte preceding the context
+            context: 1 + proof.context.to_bytes().len(),
+            commitments: proof.commitments.to_bytes().len(),
+            trace_queries: proof.trace_queries.to_bytes().len(),
+            constraint_queries: proof.constraint_queries.to_bytes().len(),
+            composition_coefficients: proof
+                .composition_coefficients
+                .as_ref()
+                .map_or(0, |coefficients| coefficients.to_bytes().len()),
+            ood_frame: proof.ood_frame.to_bytes().len(),
+            fri_remainder: fri_size - fri_layers.iter().sum::<usize>(),
+            fri_layers,
+            pow_nonce,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of bytes taken by the proof context, including the byte which specifies
+    /// the proof format version.
+    pub fn context(&self) -> usize {
+        self.context
+    }
+
+    /// Returns the number of bytes taken by commitments to the trace, the constraint composition
+    /// polynomial, and FRI layers.
+    pub fn commitments(&self) -> usize {
+        self.commitments
+    }
+
+    /// Returns the number of bytes taken by trace queries (for all trace segments) together with
+    /// their Merkle authentication paths.
+    pub fn trace_queries(&self) -> usize {
+        self.trace_queries
+    }
+
+    /// Returns the number of bytes taken by constraint composition polynomial queries together
+    /// with their Merkle authentication paths.
+    pub fn constraint_queries(&self) -> usize {
+        self.constraint_queries
+    }
+
+    /// Returns the number of bytes taken by coefficients of the constraint composition
+    /// polynomial; this is zero unless the proof uses explicit composition.
+    pub fn composition_coefficients(&self) -> usize {
+        self.composition_coefficients
+    }
+
+    /// Returns the number of bytes taken by trace and constraint evaluations at out-of-domain
+    /// points.
+    pub fn ood_frame(&self) -> usize {
+        self.ood_frame
+    }
+
+    /// Returns the number of bytes taken by each of the FRI layers (queried values together with
+    /// their Merkle authentication paths).
+    pub fn fri_layers(&self) -> &[usize] {
+        &self.fri_layers
+    }
+
+    /// Returns the number of bytes taken by the FRI remainder, including the bytes which describe
+    /// the structure of the FRI proof (i.e., the number of layers and partitions).
+    pub fn fri_remainder(&self) -> usize {
+        self.fri_remainder
+    }
+
+    /// Returns the number of bytes taken by the proof-of-work nonce, including the number of
+    /// extra queries for proofs generated with time-boxed grinding.
+    pub fn pow_nonce(&self) -> usize {
+        self.pow_nonce
+    }
+
+    /// Returns the size of the proof in bytes, i.e., the sum of sizes of all components.
+    pub fn total(&self) -> usize {
+        self.context
+            + self.commitments
+            + self.trace_queries
+            + self.constraint_queries
+            + self.composition_coefficients
+            + self.ood_frame
+            + self.fri_layers.iter().sum::<usize>()
+            + self.fri_remainder
+            + self.pow_nonce
+    }
+}
+
+impl fmt::Display for ProofSizeBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        let write_row = |f: &mut fmt::Formatter<'_>, name: &str, size: usize| {
+            let percent = size as f64 * 100.0 / total as f64;
+            writeln!(f, "{name:<26}{size:>10} B{percent:>7.1}%")
+        };
+
+        write_row(f, "context", self.context)?;
+        write_row(f, "commitments", self.commitments)?;
+        write_row(f, "trace queries", self.trace_queries)?;
+        write_row(f, "constraint queries", self.constraint_queries)?;
+        if self.composition_coefficients != 0 {
+            write_row(f, "composition coefficients", self.composition_coefficients)?;
+        }
+        write_row(f, "OOD frame", self.ood_frame)?;
+        for (i, &size) in self.fri_layers.iter().enumerate() {
+            write_row(f, &format!("FRI layer {i}"), size)?;
+        }
+        write_row(f, "FRI remainder", self.fri_remainder)?;
+        write_row(f, "PoW nonce", self.pow_nonce)?;
+        write!(f, "{:<26}{total:>10} B", "total")
+    }
+}
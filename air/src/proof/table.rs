@@ -61,6 +61,27 @@ impl<E: FieldElement> Table<E> {
         })
     }
 
+    /// Returns a new [Table] with rows of the specified width built from the provided elements
+    /// which are assumed to be arranged in row-major order.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `row_width` is 0 or greater than 255.
+    /// * The number of provided elements is not a multiple of `row_width`.
+    pub fn from_elements(data: Vec<E>, row_width: usize) -> Self {
+        assert!(row_width > 0, "number of columns must be greater than 0");
+        assert!(
+            row_width < MAX_COLS,
+            "number of columns cannot exceed {MAX_COLS}, but was {row_width}"
+        );
+        assert_eq!(
+            data.len() % row_width,
+            0,
+            "number of elements must be a multiple of the number of columns"
+        );
+        Self { data, row_width }
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -81,7 +102,7 @@ impl<E: FieldElement> Table<E> {
     }
 
     /// Returns an iterator over rows of this table.
-    pub fn rows(&self) -> RowIterator<E> {
+    pub fn rows(&self) -> RowIterator<'_, E> {
         RowIterator::new(self)
     }
 
@@ -91,17 +112,30 @@ impl<E: FieldElement> Table<E> {
     /// Combines multiple tables together into a single table by stacking tables column-wise (e.g.
     /// the number of rows remains the same but the number of columns changes).
     ///
-    /// Currently, this method does not support inputs containing more than one table.
-    ///
     /// # Panics
-    /// Panics if the list of tables is empty.
+    /// Panics if:
+    /// * The list of tables is empty.
+    /// * Not all tables have the same number of rows.
+    /// * The total number of columns in the merged table is greater than 255.
     pub fn merge(mut tables: Vec<Table<E>>) -> Table<E> {
         assert!(!tables.is_empty(), "cannot merge an empty set of tables");
         if tables.len() == 1 {
-            tables.remove(0)
-        } else {
-            unimplemented!("merging of multiple tables is not yet implemented")
+            return tables.remove(0);
+        }
+
+        let num_rows = tables[0].num_rows();
+        assert!(
+            tables.iter().all(|table| table.num_rows() == num_rows),
+            "all tables must have the same number of rows"
+        );
+        let row_width = tables.iter().map(|table| table.num_columns()).sum();
+        let mut data = Vec::with_capacity(num_rows * row_width);
+        for row_idx in 0..num_rows {
+            for table in tables.iter() {
+                data.extend_from_slice(table.get_row(row_idx));
+            }
         }
+        Self::from_elements(data, row_width)
     }
 }
 
@@ -132,12 +166,13 @@ impl<'a, E: FieldElement> Iterator for RowIterator<'a, E> {
             }
         }
     }
-}
 
-impl<'a, E: FieldElement> ExactSizeIterator for RowIterator<'a, E> {
-    fn len(&self) -> usize {
-        self.table.num_rows()
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let num_rows = self.table.num_rows() - self.cursor;
+        (num_rows, Some(num_rows))
     }
 }
 
+impl<'a, E: FieldElement> ExactSizeIterator for RowIterator<'a, E> {}
+
 impl<'a, E: FieldElement> FusedIterator for RowIterator<'a, E> {}
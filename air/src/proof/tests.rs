@@ -0,0 +1,1446 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    context::pow2_to_usize, ArchivalEnvelope, ArchiveHeader, CommitmentLayout,
+    CompositionCoefficients, Context, OodFrame, Queries, SecurityBottleneck, SecurityEstimate,
+    Table, ARCHIVE_FORMAT_VERSION, ARCHIVE_HEADER_SIZE,
+};
+use crate::{
+    ArchiveError, FieldExtension, GrindingStrategy, LdePos, LeafEncoding, ProofOptions,
+    SecurityError, TraceInfo, TraceLayoutBuilder, TraceRow, TranscriptProfile,
+};
+use crypto::{
+    hashers::{Blake3_192, Blake3_256},
+    ElementHasher, HashFunctionId, Hasher, MerkleTree,
+};
+use math::{
+    fields::{f128::BaseElement, f64::BaseElement as Felt64, QuadExtension},
+    FieldElement, StarkField,
+};
+use utils::{collections::Vec, Deserializable, DeserializationError, Serializable, SliceReader};
+
+// EXPLICIT COMPOSITION OPTIONS
+// ================================================================================================
+
+#[test]
+fn explicit_composition_options_serialization() {
+    let options = build_options();
+    assert_eq!(None, options.explicit_composition_threshold());
+    assert!(!options.uses_explicit_composition(8));
+
+    // options with explicit composition disabled serialize the same way as before
+    assert_eq!(vec![32, 8, 0, 1, 4, 8], options.to_bytes());
+
+    let options = build_options().with_explicit_composition(256);
+    assert_eq!(Some(256), options.explicit_composition_threshold());
+    assert!(options.uses_explicit_composition(256));
+    assert!(!options.uses_explicit_composition(512));
+
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 1, 4, 8 << 4 | 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+}
+
+#[test]
+fn explicit_composition_options_invalid_threshold() {
+    // thresholds of 2^2 and 2^11 are outside of the allowed range
+    for threshold in [2, 11] {
+        let bytes = vec![32, 8, 0, 1, 4, threshold << 4 | 8];
+        assert!(matches!(
+            ProofOptions::read_from(&mut SliceReader::new(&bytes)),
+            Err(DeserializationError::InvalidValue(_))
+        ));
+    }
+}
+
+#[test]
+#[should_panic(expected = "explicit composition threshold must be a power of 2")]
+fn explicit_composition_options_not_power_of_two() {
+    build_options().with_explicit_composition(100);
+}
+
+#[test]
+#[should_panic(expected = "explicit composition threshold cannot be greater than 1024")]
+fn explicit_composition_options_too_large() {
+    build_options().with_explicit_composition(2048);
+}
+
+#[test]
+fn explicit_composition_context() {
+    let options = build_options().with_explicit_composition(16);
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 16), options.clone());
+    assert!(context.uses_explicit_composition());
+
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 32), options);
+    assert!(!context.uses_explicit_composition());
+}
+
+// PLATFORM LIMITS
+// ================================================================================================
+
+#[test]
+fn context_trace_length_too_large() {
+    let result = Context::read_from(&mut SliceReader::new(&build_context_bytes(64)));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+}
+
+#[test]
+fn context_domain_too_large_for_platform() {
+    // a trace of 2^62 steps with blowup factor of 8 results in an LDE domain of 2^65 elements,
+    // which cannot be indexed on any supported platform
+    let bytes = build_context_bytes(62);
+    let context = Context::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(65, context.lde_domain_size_log2());
+    assert!(!context.fits_platform());
+    assert!(!context.uses_explicit_composition());
+    assert_eq!(bytes, context.to_bytes());
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn context_domain_fits_64_bit_platform() {
+    let context = Context::read_from(&mut SliceReader::new(&build_context_bytes(40))).unwrap();
+    assert!(context.fits_platform());
+    assert_eq!(1 << 40, context.trace_length());
+    assert_eq!(1 << 43, context.lde_domain_size());
+}
+
+#[test]
+#[cfg(target_pointer_width = "32")]
+fn context_domain_too_large_for_32_bit_platform() {
+    // with blowup factor of 8, a trace of 2^28 steps results in an LDE domain of 2^31 elements
+    let context = Context::read_from(&mut SliceReader::new(&build_context_bytes(28))).unwrap();
+    assert!(context.fits_platform());
+    assert_eq!(1 << 31, context.lde_domain_size());
+
+    let context = Context::read_from(&mut SliceReader::new(&build_context_bytes(29))).unwrap();
+    assert_eq!(32, context.lde_domain_size_log2());
+    assert!(!context.fits_platform());
+}
+
+#[test]
+fn pow2_to_usize_bounds() {
+    assert_eq!(Some(1), pow2_to_usize(0));
+    assert_eq!(Some(1 << (usize::BITS - 1)), pow2_to_usize(usize::BITS - 1));
+    assert_eq!(None, pow2_to_usize(usize::BITS));
+    assert_eq!(None, pow2_to_usize(u32::MAX));
+}
+
+#[test]
+#[cfg(target_pointer_width = "32")]
+fn pow2_to_usize_32_bit() {
+    assert_eq!(Some(1 << 31), pow2_to_usize(31));
+    assert_eq!(None, pow2_to_usize(32));
+}
+
+// DOMAIN HELPERS
+// ================================================================================================
+
+#[test]
+fn context_domain_elements() {
+    // trace of 2^6 steps with blowup factor of 8 results in an LDE domain of 2^9 elements
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 64), build_options());
+    let g_trace = BaseElement::get_root_of_unity(6);
+    let g_lde = BaseElement::get_root_of_unity(9);
+    assert_eq!(g_trace, context.trace_domain_generator());
+    assert_eq!(g_lde, context.lde_domain_generator());
+
+    let offset = BaseElement::GENERATOR;
+    let mut x = offset;
+    for position in 0..context.lde_domain_size() {
+        assert_eq!(x, context.lde_domain_element(LdePos(position)));
+        x *= g_lde;
+    }
+
+    // a position falls on a trace row only when it is a multiple of the blowup factor; the
+    // domain element at such a position is the element of the trace domain shifted by offset
+    for position in 0..context.lde_domain_size() {
+        let row = context.map_position_to_trace_row(LdePos(position));
+        if position % 8 == 0 {
+            assert_eq!(Some(TraceRow(position / 8)), row);
+            let expected = offset * g_trace.exp(((position / 8) as u64).into());
+            assert_eq!(expected, context.lde_domain_element(LdePos(position)));
+        } else {
+            assert_eq!(None, row);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "position 512 is outside of the LDE domain of 512 elements")]
+fn context_domain_element_out_of_bounds() {
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 64), build_options());
+    context.lde_domain_element::<BaseElement>(LdePos(512));
+}
+
+// MULTIPLE OOD POINTS
+// ================================================================================================
+
+#[test]
+fn ood_points_options_serialization() {
+    let options = build_options().with_num_ood_points(2);
+    assert_eq!(2, options.num_ood_points());
+
+    // the number of OOD points less one is stored in the upper 2 bits of the grinding factor
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0x40, 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let options = ProofOptions::new(32, 8, 20, FieldExtension::None, 4, 256).with_num_ood_points(4);
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0xc0 | 20, 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+}
+
+#[test]
+#[should_panic(expected = "number of OOD points cannot be greater than 4")]
+fn ood_points_options_too_many() {
+    build_options().with_num_ood_points(5);
+}
+
+#[test]
+fn ood_frame_multiple_points() {
+    let states = |offset: u128| {
+        vec![
+            vec![BaseElement::new(offset), BaseElement::new(offset + 1)],
+            vec![BaseElement::new(offset + 2), BaseElement::new(offset + 3)],
+        ]
+    };
+    let mut frame = OodFrame::default();
+    frame.add_trace_states(&states(1));
+    frame.add_constraint_evaluations(&[BaseElement::new(5)]);
+    frame.add_trace_states(&states(11));
+    frame.add_constraint_evaluations(&[BaseElement::new(15)]);
+
+    // trace states of all points are followed by constraint evaluations of all points
+    let mut expected = Vec::new();
+    expected.extend_from_slice(&(8 * 16u16).to_le_bytes());
+    for value in [1u128, 2, 3, 4, 11, 12, 13, 14] {
+        expected.extend_from_slice(&value.to_le_bytes());
+    }
+    expected.extend_from_slice(&(2 * 16u16).to_le_bytes());
+    for value in [5u128, 15] {
+        expected.extend_from_slice(&value.to_le_bytes());
+    }
+    expected.extend_from_slice(&0u16.to_le_bytes());
+    let bytes = frame.to_bytes();
+    assert_eq!(expected, bytes);
+
+    let frame = OodFrame::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    let parsed = frame
+        .clone()
+        .parse_points::<BaseElement>(2, 0, &[0, 1], 1, 2)
+        .unwrap();
+    assert_eq!(2, parsed.len());
+    for ((main_frame, aux_frame, evaluations), offset) in parsed.into_iter().zip([1, 11]) {
+        let states = states(offset);
+        assert_eq!(&states[0], main_frame.current());
+        assert_eq!(&states[1], main_frame.next());
+        assert!(aux_frame.is_none());
+        assert_eq!(vec![BaseElement::new(offset + 4)], evaluations);
+    }
+
+    // parsing a frame with 2 points as a frame with a single point leaves unconsumed bytes
+    assert_eq!(
+        Err(DeserializationError::UnconsumedBytes),
+        frame.parse::<BaseElement>(2, 0, &[0, 1], 1).map(|_| ())
+    );
+}
+
+// COMBINED TRACE COLUMN
+// ================================================================================================
+
+#[test]
+fn combined_trace_column_options_serialization() {
+    let options = build_options();
+    assert!(!options.uses_combined_trace_column());
+
+    // the combined trace column flag is stored in the most significant bit of the FRI folding
+    // factor byte
+    let options = build_options().with_combined_trace_column();
+    assert!(options.uses_combined_trace_column());
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 1, 0x80 | 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 16), options);
+    assert!(context.uses_combined_trace_column());
+}
+
+// FRI REMAINDER DIGEST
+// ================================================================================================
+
+#[test]
+fn fri_remainder_digest_options_serialization() {
+    let options = build_options();
+    assert!(!options.uses_fri_remainder_digest());
+
+    // the FRI remainder digest flag is stored in the fourth least significant bit of the field
+    // extension byte, below the transcript profile
+    let options = build_options().with_fri_remainder_digest();
+    assert!(options.uses_fri_remainder_digest());
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x08 | 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let options = options.with_transcript_profile(TranscriptProfile::EthStarkCompat);
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x18 | 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 16), options);
+    assert!(context.uses_fri_remainder_digest());
+}
+
+// SECURITY ESTIMATE
+// ================================================================================================
+
+#[test]
+fn security_estimate_limited_by_queries() {
+    // 128-bit field over LDE domain of 2^7 elements with blowup factor 8: the FRI commit phase
+    // contributes 128 - 7 = 121 bits, and the DEEP/ALI step 128 - log2(8 * 17 + 15) = 120.76
+    // bits; 32 queries with blowup factor 8: 3 * 32 = 96 bits; grinding factor is 0
+    let options = build_options();
+    let estimate = SecurityEstimate::conjectured(&options, 128, 128, 128);
+    assert_eq!(120, estimate.field_security());
+    assert_eq!(96, estimate.query_security());
+    assert_eq!(0, estimate.grinding_bits());
+    assert_eq!(128, estimate.collision_resistance());
+    assert_eq!(95, estimate.security_level());
+    assert_eq!(SecurityBottleneck::Queries, estimate.bottleneck());
+}
+
+#[test]
+fn security_estimate_limited_by_field() {
+    // 64-bit field over LDE domain of 2^20 elements with blowup factor 16: the FRI commit phase
+    // contributes 64 - 20 = 44 bits, and the DEEP/ALI step 64 - log2(16 * (2^16 + 1) + 2^16 - 1)
+    // = 43.91 bits; 40 queries with blowup factor 16: 4 * 40 = 160 bits plus 20 bits of grinding
+    let options = ProofOptions::new(40, 16, 20, FieldExtension::None, 8, 256);
+    let estimate = SecurityEstimate::conjectured(&options, 64, 1 << 20, 128);
+    assert_eq!(43, estimate.field_security());
+    assert_eq!(160, estimate.query_security());
+    assert_eq!(20, estimate.grinding_bits());
+    assert_eq!(42, estimate.security_level());
+    assert_eq!(SecurityBottleneck::Field, estimate.bottleneck());
+
+    // cubic extension of the same field: 3 * 64 - 20.09 = 171.91 bits; the security is now
+    // limited by the hash function
+    let options = ProofOptions::new(40, 16, 20, FieldExtension::Cubic, 8, 256);
+    let estimate = SecurityEstimate::conjectured(&options, 64, 1 << 20, 128);
+    assert_eq!(171, estimate.field_security());
+    assert_eq!(128, estimate.security_level());
+    assert_eq!(
+        SecurityBottleneck::CollisionResistance,
+        estimate.bottleneck()
+    );
+}
+
+#[test]
+fn security_estimate_depends_on_ood_points() {
+    // with a single OOD point, the DEEP/ALI step limits the field security to 43 bits (see
+    // above); with 2 points, the DEEP/ALI step contributes 2 * 43.91 = 87.83 bits, and the field
+    // security is limited by the 44 bits of the FRI commit phase
+    let options = ProofOptions::new(40, 16, 20, FieldExtension::None, 8, 256);
+    let estimate = SecurityEstimate::conjectured(&options, 64, 1 << 20, 128);
+    let options = options.with_num_ood_points(2);
+    let ood_estimate = SecurityEstimate::conjectured(&options, 64, 1 << 20, 128);
+    assert_eq!(43, estimate.field_security());
+    assert_eq!(44, ood_estimate.field_security());
+    assert_eq!(42, estimate.security_level());
+    assert_eq!(43, ood_estimate.security_level());
+
+    // additional points do not increase security beyond the FRI commit phase
+    let options = options.with_num_ood_points(4);
+    let ood_estimate = SecurityEstimate::conjectured(&options, 64, 1 << 20, 128);
+    assert_eq!(44, ood_estimate.field_security());
+}
+
+#[test]
+fn security_estimate_without_field_security() {
+    // a 16-bit field over LDE domain of 2^20 elements contributes no security at all
+    let options = ProofOptions::new(40, 16, 20, FieldExtension::None, 8, 256);
+    let estimate = SecurityEstimate::conjectured(&options, 16, 1 << 20, 128);
+    assert_eq!(0, estimate.field_security());
+    assert_eq!(0, estimate.security_level());
+}
+
+#[test]
+fn security_estimate_grinding_floor() {
+    // 30 queries with blowup factor 4 contribute 2 * 30 = 60 bits, which is below the 80-bit
+    // floor for grinding contributions
+    let options = ProofOptions::new(30, 4, 16, FieldExtension::None, 4, 256);
+    let estimate = SecurityEstimate::conjectured(&options, 128, 1 << 10, 96);
+    assert_eq!(60, estimate.query_security());
+    assert_eq!(0, estimate.grinding_bits());
+    assert_eq!(59, estimate.security_level());
+    assert_eq!(SecurityBottleneck::Queries, estimate.bottleneck());
+
+    // 40 queries contribute exactly 80 bits, and thus, grinding is taken into account; with
+    // collision resistance lowered to 95 bits, the hash function becomes the bottleneck
+    let options = ProofOptions::new(40, 4, 16, FieldExtension::None, 4, 256);
+    let estimate = SecurityEstimate::conjectured(&options, 128, 1 << 10, 96);
+    assert_eq!(80, estimate.query_security());
+    assert_eq!(16, estimate.grinding_bits());
+    assert_eq!(95, estimate.security_level());
+    assert_eq!(SecurityBottleneck::Queries, estimate.bottleneck());
+    let estimate = SecurityEstimate::conjectured(&options, 128, 1 << 10, 95);
+    assert_eq!(95, estimate.security_level());
+    assert_eq!(
+        SecurityBottleneck::CollisionResistance,
+        estimate.bottleneck()
+    );
+}
+
+#[test]
+fn proven_security_estimate_limited_by_queries() {
+    // 128-bit field over LDE domain of 2^7 elements with blowup factor 8; the best Johnson bound
+    // proximity parameter is m = 23, for which:
+    // * list size: log2(23.5) + 0.5 * log2(8) = 6.05 bits
+    // * ALI: 128 - 6.05 = 121.95 bits
+    // * DEEP: 128 - 6.05 - log2(8 * (16 + 1) + 15) = 114.71 bits
+    // * FRI commit phase: 128 - (7 * log2(23.5) + 1.5 * log2(8) + 2 * 7 - 1) = 78.62 bits
+    // * 32 queries: 32 * (0.5 * log2(8) - log2(1 + 1 / 46)) = 47.01 bits
+    let options = build_options();
+    let estimate = SecurityEstimate::proven(&options, 128, 128, 128);
+    assert_eq!(78, estimate.field_security());
+    assert_eq!(47, estimate.query_security());
+    assert_eq!(0, estimate.grinding_bits());
+    assert_eq!(128, estimate.collision_resistance());
+    assert_eq!(46, estimate.security_level());
+    assert_eq!(SecurityBottleneck::Queries, estimate.bottleneck());
+
+    // conjectured security of the same options is much higher
+    let estimate = SecurityEstimate::conjectured(&options, 128, 128, 128);
+    assert_eq!(95, estimate.security_level());
+}
+
+#[test]
+fn proven_security_estimate_limited_by_field() {
+    // quadratic extension of a 64-bit field over LDE domain of 2^20 elements with blowup factor
+    // 8; the best Johnson bound proximity parameter is m = 3, for which:
+    // * FRI commit phase: 128 - (7 * log2(3.5) + 1.5 * log2(8) + 2 * 20 - 1) = 71.85 bits
+    // * 80 queries: 80 * (0.5 * log2(8) - log2(1 + 1 / 6)) = 102.21 bits, plus 20 bits of
+    //   grinding
+    let options = ProofOptions::new(80, 8, 20, FieldExtension::Quadratic, 8, 256);
+    let estimate = SecurityEstimate::proven(&options, 64, 1 << 20, 128);
+    assert_eq!(71, estimate.field_security());
+    assert_eq!(102, estimate.query_security());
+    assert_eq!(20, estimate.grinding_bits());
+    assert_eq!(70, estimate.security_level());
+    assert_eq!(SecurityBottleneck::Field, estimate.bottleneck());
+}
+
+#[test]
+fn proven_security_estimate_without_field_security() {
+    // a 16-bit field over LDE domain of 2^20 elements contributes no security at all
+    let options = ProofOptions::new(40, 16, 20, FieldExtension::None, 8, 256);
+    let estimate = SecurityEstimate::proven(&options, 16, 1 << 20, 128);
+    assert_eq!(0, estimate.field_security());
+    assert_eq!(0, estimate.security_level());
+}
+
+#[test]
+fn options_with_proven_security() {
+    // cubic extension of a 64-bit field over LDE domain of 2^24 elements: 58 queries contribute
+    // 86 bits, plus 16 bits of grinding, and the field contributes 102 bits
+    let options = ProofOptions::with_security::<Felt64, Blake3_256<Felt64>>(
+        100,
+        false,
+        FieldExtension::Cubic,
+    )
+    .unwrap();
+    assert_eq!(58, options.num_queries());
+    let estimate = SecurityEstimate::proven(&options, 64, 1 << 24, 128);
+    assert_eq!(102, estimate.field_security());
+    assert_eq!(86, estimate.query_security());
+    assert_eq!(101, estimate.security_level());
+
+    // with quadratic extension, the field contributes only 63 bits
+    assert_eq!(
+        Err(SecurityError::InsufficientFieldSecurity(101, 63)),
+        ProofOptions::with_security::<Felt64, Blake3_256<Felt64>>(
+            100,
+            false,
+            FieldExtension::Quadratic
+        )
+    );
+}
+
+// LEAF ENCODING
+// ================================================================================================
+
+#[test]
+fn leaf_encoding_options_serialization() {
+    let options = build_options();
+    assert_eq!(LeafEncoding::Hashed, options.leaf_encoding());
+
+    // the leaf encoding is stored in the second most significant bit of the FRI folding factor
+    // byte, next to the combined trace column flag
+    let options = build_options().with_leaf_encoding(LeafEncoding::Raw);
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 1, 0x40 | 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let options = options.with_combined_trace_column();
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 1, 0xc0 | 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+}
+
+#[test]
+fn leaf_encoding_support() {
+    // hashed leaves are supported for rows of any length
+    assert!(LeafEncoding::Hashed.is_supported::<Blake3_256<BaseElement>>(1024));
+
+    // raw leaves are supported only for rows which fit into a digest
+    assert!(LeafEncoding::Raw.is_supported::<Blake3_256<BaseElement>>(32));
+    assert!(!LeafEncoding::Raw.is_supported::<Blake3_256<BaseElement>>(33));
+    assert!(LeafEncoding::Raw.is_supported::<Blake3_192<BaseElement>>(24));
+    assert!(!LeafEncoding::Raw.is_supported::<Blake3_192<BaseElement>>(32));
+
+    // digests of Rp62_248 pack four 62-bit elements into 31 bytes, and thus, the elements of a
+    // row cannot be placed into the elements of a digest as is
+    #[cfg(all(feature = "f62", feature = "f64"))]
+    {
+        use crypto::hashers::{Rp62_248, Rp64_256};
+        assert!(LeafEncoding::Raw.is_supported::<Rp64_256>(32));
+        assert!(!LeafEncoding::Raw.is_supported::<Rp62_248>(8));
+    }
+
+    // a raw leaf is the row padded with zeros to the size of a digest
+    let row = [BaseElement::new(3)];
+    let leaf = LeafEncoding::Raw.encode_row::<Blake3_192<BaseElement>, _>(&row);
+    let mut expected = [0_u8; 24];
+    expected[..16].copy_from_slice(BaseElement::elements_as_bytes(&row));
+    assert_eq!(expected.to_vec(), leaf.to_bytes());
+    assert!(LeafEncoding::raw_leaf::<Blake3_192<BaseElement>>(&[0; 25]).is_err());
+}
+
+// MERKLE ARITY
+// ================================================================================================
+
+#[test]
+fn merkle_arity_options_serialization() {
+    let options = build_options();
+    assert_eq!(2, options.merkle_arity());
+
+    // log2(arity) - 1 is stored in the two most significant bits of the field extension byte,
+    // above the transcript profile
+    let options = build_options().with_merkle_arity(4);
+    assert_eq!(4, options.merkle_arity());
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x40 | 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let options = build_options()
+        .with_merkle_arity(8)
+        .with_transcript_profile(TranscriptProfile::EthStarkCompat);
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x90 | 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    // arity 16 is not supported
+    let mut bytes = bytes;
+    bytes[3] = 0xc0 | 1;
+    assert!(matches!(
+        ProofOptions::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // raw leaves cannot be used with trees of arity greater than 2
+    let mut bytes = build_options()
+        .with_leaf_encoding(LeafEncoding::Raw)
+        .to_bytes();
+    bytes[3] |= 0x40;
+    assert!(matches!(
+        ProofOptions::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Merkle tree arity must be 2, 4, or 8")]
+fn merkle_arity_unsupported() {
+    build_options().with_merkle_arity(16);
+}
+
+#[test]
+#[should_panic(expected = "raw leaf encoding can be used only with binary Merkle trees")]
+fn merkle_arity_with_raw_leaves() {
+    build_options()
+        .with_leaf_encoding(LeafEncoding::Raw)
+        .with_merkle_arity(4);
+}
+
+// SALTED COMMITMENTS
+// ================================================================================================
+
+#[test]
+fn zk_options_serialization() {
+    let options = build_options();
+    assert!(!options.is_zk());
+
+    // the zk flag is stored in the third least significant bit of the field extension byte,
+    // above the field extension
+    let options = build_options().with_zk();
+    assert!(options.is_zk());
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x04 | 1, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256)
+        .with_merkle_arity(4)
+        .with_zk();
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x44 | 2, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    // raw leaves cannot be salted
+    let mut bytes = build_options()
+        .with_leaf_encoding(LeafEncoding::Raw)
+        .to_bytes();
+    bytes[3] |= 0x04;
+    assert!(matches!(
+        ProofOptions::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "raw leaf encoding cannot be used with salted commitments")]
+fn zk_options_with_raw_leaves() {
+    build_options()
+        .with_leaf_encoding(LeafEncoding::Raw)
+        .with_zk();
+}
+
+#[test]
+fn salted_queries_parsing() {
+    type H = Blake3_256<BaseElement>;
+
+    // build a tree of 8 salted leaves, each committing to a row of 2 values
+    let rows = (0..8_u128)
+        .map(|i| vec![BaseElement::new(i), BaseElement::new(i + 8)])
+        .collect::<Vec<_>>();
+    let salts = (0..8_u8).map(|i| H::hash(&[i])).collect::<Vec<_>>();
+    let leaves = rows
+        .iter()
+        .zip(salts.iter())
+        .map(|(row, &salt)| LeafEncoding::salted_leaf::<H>(H::hash_elements(row), salt))
+        .collect::<Vec<_>>();
+    let tree = MerkleTree::<H>::new(leaves).unwrap();
+
+    let indexes = [1, 6];
+    let proof = tree.prove_batch(&indexes).unwrap();
+    let values = indexes.iter().map(|&i| rows[i].clone()).collect();
+    let queries = Queries::with_salts::<H, BaseElement>(
+        tree.prove_batch(&indexes).unwrap(),
+        values,
+        indexes.iter().map(|&i| salts[i]).collect(),
+    );
+
+    let (parsed_proof, parsed_values) = queries
+        .clone()
+        .parse_salted::<H, BaseElement>(8, 2, 2, 2)
+        .unwrap();
+    assert_eq!(proof, parsed_proof);
+    assert_eq!(&rows[1][..], parsed_values.get_row(0));
+    assert_eq!(&rows[6][..], parsed_values.get_row(1));
+    assert_eq!(Ok(*tree.root()), parsed_proof.get_root(&indexes));
+
+    // parsed as unsalted queries, the queries do not authenticate against the root of the tree
+    let result = queries.parse_with_arity::<H, BaseElement>(8, 2, 2, LeafEncoding::Hashed, 2);
+    if let Ok((parsed_proof, _)) = result {
+        assert_ne!(Ok(*tree.root()), parsed_proof.get_root(&indexes));
+    }
+}
+
+// GRINDING STRATEGY
+// ================================================================================================
+
+#[test]
+fn time_boxed_grinding_options_serialization() {
+    let options = build_options();
+    assert_eq!(GrindingStrategy::FixedBits, options.grinding_strategy());
+
+    // the time-boxed strategy is flagged by the third most significant bit of the FRI folding
+    // factor byte, and its parameters follow all other options
+    let strategy = GrindingStrategy::TimeBoxed {
+        max_millis: 0x0102_0304,
+        fallback_extra_queries: 6,
+    };
+    let options = build_options()
+        .with_leaf_encoding(LeafEncoding::Raw)
+        .with_grinding_strategy(strategy);
+    assert!(options.uses_time_boxed_grinding());
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 1, 0x60 | 4, 8, 4, 3, 2, 1, 6], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    // the number of queries including fallback extra queries cannot exceed 128
+    let mut bytes = bytes;
+    bytes[10] = 97;
+    assert!(matches!(
+        ProofOptions::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn grinding_hash_options_serialization() {
+    let options = build_options();
+    assert_eq!(None, options.grinding_hash());
+    assert!(!options.uses_wide_grinding_nonce());
+    assert_eq!(8, options.grinding_nonce_bytes());
+
+    // the grinding hash function is flagged by the least significant bit of the FRI folding
+    // factor byte, and its identifier and the nonce width follow the grinding strategy
+    let options = build_options().with_grinding_hash(HashFunctionId::Blake3_256);
+    assert_eq!(Some(HashFunctionId::Blake3_256), options.grinding_hash());
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 1, 4 | 1, 8, 2, 8], bytes);
+    assert_eq!(
+        options,
+        ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    let options = build_options()
+        .with_grinding_strategy(GrindingStrategy::TimeBoxed {
+            max_millis: 0x0102_0304,
+            fallback_extra_queries: 6,
+        })
+        .with_grinding_hash(HashFunctionId::Sha3_256)
+        .with_wide_grinding_nonce();
+    assert!(options.uses_wide_grinding_nonce());
+    assert_eq!(16, options.grinding_nonce_bytes());
+    let bytes = options.to_bytes();
+    assert_eq!(
+        vec![32, 8, 0, 1, 0x20 | 4 | 1, 8, 4, 3, 2, 1, 6, 3, 16],
+        bytes
+    );
+    assert_eq!(
+        options,
+        ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    // the nonce must be 8 or 16 bytes wide
+    let mut invalid_bytes = bytes.clone();
+    invalid_bytes[12] = 12;
+    assert!(matches!(
+        ProofOptions::read_from(&mut SliceReader::new(&invalid_bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // the second least significant bit of the FRI folding factor byte is unused
+    let mut invalid_bytes = bytes;
+    invalid_bytes[4] |= 2;
+    assert!(matches!(
+        ProofOptions::read_from(&mut SliceReader::new(&invalid_bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "wide grinding nonce can be used only with a grinding hash function")]
+fn wide_grinding_nonce_without_grinding_hash() {
+    build_options().with_wide_grinding_nonce();
+}
+
+#[test]
+#[should_panic(
+    expected = "number of queries including fallback extra queries cannot be greater than 128"
+)]
+fn time_boxed_grinding_too_many_queries() {
+    build_options().with_grinding_strategy(GrindingStrategy::TimeBoxed {
+        max_millis: 100,
+        fallback_extra_queries: 97,
+    });
+}
+
+#[test]
+fn time_boxed_grinding_extra_queries() {
+    // with blowup factor 8, every query compensates for 3 missing bits
+    let options = ProofOptions::new(32, 8, 20, FieldExtension::None, 4, 256);
+    assert_eq!(20, options.min_grinding_factor());
+    assert_eq!(0, options.num_extra_queries(20));
+    assert_eq!(0, options.num_extra_queries(25));
+    assert_eq!(1, options.num_extra_queries(19));
+    assert_eq!(1, options.num_extra_queries(17));
+    assert_eq!(2, options.num_extra_queries(16));
+    assert_eq!(7, options.num_extra_queries(0));
+
+    let options = options.with_grinding_strategy(GrindingStrategy::TimeBoxed {
+        max_millis: 100,
+        fallback_extra_queries: 4,
+    });
+    assert_eq!(8, options.min_grinding_factor());
+    let options = options.with_grinding_strategy(GrindingStrategy::TimeBoxed {
+        max_millis: 100,
+        fallback_extra_queries: 10,
+    });
+    assert_eq!(0, options.min_grinding_factor());
+
+    // extra queries restore the conjectured security of the full grinding factor
+    for pow_bits in 0..20 {
+        let extra_queries = options.num_extra_queries(pow_bits);
+        assert!(3 * (32 + extra_queries as u32) + pow_bits >= 3 * 32 + 20);
+    }
+}
+
+#[test]
+fn context_max_trace_row_bytes() {
+    let options = build_options();
+    let layout = TraceLayoutBuilder::new()
+        .main_width(2)
+        .aux_segment(3, 1)
+        .build()
+        .unwrap();
+    let trace_info = TraceInfo::new_multi_segment(layout, 16, Vec::new());
+
+    // the auxiliary segment is built in the extension field, and thus, its rows are the longest
+    let context = Context::new::<BaseElement>(&trace_info, options.clone());
+    assert_eq!(48, context.max_trace_row_bytes::<BaseElement>());
+    assert_eq!(
+        96,
+        context.max_trace_row_bytes::<QuadExtension<BaseElement>>()
+    );
+
+    // the combined trace column consists of a single element of the extension field
+    let context =
+        Context::new::<BaseElement>(&TraceInfo::new(1, 16), options.with_combined_trace_column());
+    assert_eq!(16, context.max_trace_row_bytes::<BaseElement>());
+    assert_eq!(
+        32,
+        context.max_trace_row_bytes::<QuadExtension<BaseElement>>()
+    );
+}
+
+#[test]
+fn ood_frame_combined_trace_evaluations() {
+    let mut frame = OodFrame::default();
+    frame.add_trace_states(&[vec![BaseElement::ONE], vec![BaseElement::ONE]]);
+    frame.add_constraint_evaluations(&[BaseElement::ONE]);
+    frame.add_combined_trace_evaluation(BaseElement::new(7));
+
+    let bytes = frame.to_bytes();
+    let frame = OodFrame::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(
+        vec![BaseElement::new(7)],
+        frame
+            .parse_combined_trace_evaluations::<BaseElement>(1)
+            .unwrap()
+    );
+
+    // parsing the evaluations as if the proof had no combined trace column leaves unconsumed
+    // bytes
+    assert_eq!(
+        Err(DeserializationError::UnconsumedBytes),
+        frame.parse_combined_trace_evaluations::<BaseElement>(0)
+    );
+}
+
+// COMPOSITION COEFFICIENTS
+// ================================================================================================
+
+#[test]
+fn composition_coefficients_serialization() {
+    let column0 = (0..4).map(BaseElement::new).collect::<Vec<_>>();
+    let column1 = (4..8).map(BaseElement::new).collect::<Vec<_>>();
+    let coefficients = CompositionCoefficients::new(&[&column0, &column1]);
+    assert_eq!(8, coefficients.num_elements::<BaseElement>());
+
+    let bytes = coefficients.to_bytes();
+    let result = CompositionCoefficients::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(coefficients, result);
+    assert_eq!(
+        vec![column0, column1],
+        result.parse::<BaseElement>(2, 4).unwrap()
+    );
+}
+
+#[test]
+fn composition_coefficients_parse_errors() {
+    let column = vec![BaseElement::ONE; 4];
+    let coefficients = CompositionCoefficients::new(&[&column]);
+
+    // too few bytes for the requested columns
+    assert!(coefficients.clone().parse::<BaseElement>(2, 4).is_err());
+
+    // unconsumed bytes after parsing
+    assert_eq!(
+        Err(DeserializationError::UnconsumedBytes),
+        coefficients.parse::<BaseElement>(1, 2)
+    );
+}
+
+// COMMITMENT LAYOUT
+// ================================================================================================
+
+#[test]
+fn commitment_layout_round_trip() {
+    let lde_domain_size = 64;
+    for rows_per_leaf in [1, 2, 4, 8] {
+        for num_partitions in [1, 2, 4] {
+            let layout = CommitmentLayout::new(lde_domain_size, rows_per_leaf, num_partitions);
+            let leaves_per_tree = lde_domain_size / num_partitions / rows_per_leaf;
+            assert_eq!(leaves_per_tree, layout.leaves_per_tree());
+
+            // every position is found at its offset within the leaf it is mapped to
+            for position in (0..lde_domain_size).map(LdePos) {
+                let leaf = layout.leaf_of(position);
+                assert!(leaf.tree() < num_partitions);
+                assert!(leaf.leaf() < leaves_per_tree);
+                assert!(leaf.offset() < rows_per_leaf);
+                let positions = layout.positions_in_leaf(leaf.tree(), leaf.leaf());
+                assert_eq!(position, positions[leaf.offset()]);
+            }
+
+            // every leaf contains consecutive positions, and all leaves together cover each
+            // position of the domain exactly once
+            let mut covered = Vec::new();
+            for tree in 0..num_partitions {
+                for leaf in 0..leaves_per_tree {
+                    let positions = layout.positions_in_leaf(tree, leaf);
+                    assert_eq!(rows_per_leaf, positions.len());
+                    for (offset, &position) in positions.iter().enumerate() {
+                        let leaf_position = layout.leaf_of(position);
+                        assert_eq!(tree, leaf_position.tree());
+                        assert_eq!(leaf, leaf_position.leaf());
+                        assert_eq!(offset, leaf_position.offset());
+                    }
+                    covered.extend(positions);
+                }
+            }
+            assert_eq!(
+                (0..lde_domain_size).map(LdePos).collect::<Vec<_>>(),
+                covered
+            );
+
+            // leaf indexes are global across trees, and each leaf is opened only once
+            let positions = (0..lde_domain_size).rev().map(LdePos).collect::<Vec<_>>();
+            let expected = (0..lde_domain_size / rows_per_leaf)
+                .rev()
+                .collect::<Vec<_>>();
+            assert_eq!(expected, layout.leaf_indexes(&positions));
+
+            // serialization round trip
+            let bytes = layout.to_bytes();
+            let result = CommitmentLayout::read_from(&mut SliceReader::new(&bytes)).unwrap();
+            assert_eq!(layout, result);
+        }
+    }
+}
+
+#[test]
+fn commitment_layout_from_context() {
+    let trace_info = TraceInfo::new(2, 16);
+    let context = Context::new::<BaseElement>(&trace_info, build_options());
+    let layout = CommitmentLayout::from_context(&context);
+    assert_eq!(CommitmentLayout::new(128, 1, 1), layout);
+
+    // without packing and partitioning, leaf indexes are the same as positions
+    let positions = [LdePos(5), LdePos(100), LdePos(0), LdePos(5)];
+    assert_eq!(vec![5, 100, 0], layout.leaf_indexes(&positions));
+}
+
+#[test]
+fn commitment_layout_invalid_bytes() {
+    // 8 rows per leaf and 16 partitions do not fit into a domain of 64 positions
+    let bytes = vec![6, 3, 4];
+    assert!(matches!(
+        CommitmentLayout::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    let bytes = vec![usize::BITS as u8, 0, 0];
+    assert!(matches!(
+        CommitmentLayout::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+// PREPROCESSED TRACE SEGMENT
+// ================================================================================================
+
+#[test]
+fn preprocessed_segment_context_serialization() {
+    let trace_info = TraceInfo::new(2, 16).with_preprocessed_segment(3);
+    assert_eq!(5, trace_info.main_frame_width());
+    assert_eq!(5, trace_info.width());
+
+    let context = Context::new::<BaseElement>(&trace_info, build_options());
+    assert_eq!(3, context.preprocessed_width());
+    assert_eq!(trace_info, context.get_trace_info());
+
+    // the width of the preprocessed segment is flagged in the trace length byte and follows it
+    let bytes = context.to_bytes();
+    let offset = trace_info.layout().to_bytes().len();
+    assert_eq!([0x80 | 4, 3], bytes[offset..offset + 2]);
+    assert_eq!(
+        context,
+        Context::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    // contexts without a preprocessed segment are serialized as before
+    let plain = Context::new::<BaseElement>(&TraceInfo::new(2, 16), build_options());
+    assert_eq!(bytes.len() - 1, plain.to_bytes().len());
+    assert_ne!(
+        context.to_elements::<BaseElement>(),
+        plain.to_elements::<BaseElement>()
+    );
+}
+
+#[test]
+fn preprocessed_segment_context_invalid_width() {
+    let trace_info = TraceInfo::new(2, 16).with_preprocessed_segment(3);
+    let mut bytes = Context::new::<BaseElement>(&trace_info, build_options()).to_bytes();
+    let offset = trace_info.layout().to_bytes().len() + 1;
+
+    bytes[offset] = 0;
+    let result = Context::read_from(&mut SliceReader::new(&bytes));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+
+    bytes[offset] = 254;
+    let result = Context::read_from(&mut SliceReader::new(&bytes));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+}
+
+#[test]
+#[should_panic(expected = "total number of columns in the trace cannot be greater than 255")]
+fn preprocessed_segment_too_wide() {
+    TraceInfo::new(200, 16).with_preprocessed_segment(56);
+}
+
+#[test]
+fn table_merge() {
+    let a = Table::from_elements((1..=4u32).map(BaseElement::from).collect::<Vec<_>>(), 2);
+    let b = Table::from_elements(vec![BaseElement::from(5u32), BaseElement::from(6u32)], 1);
+    let merged = Table::merge(vec![a, b]);
+    assert_eq!(3, merged.num_columns());
+    assert_eq!(2, merged.num_rows());
+    assert_eq!(
+        [1u32, 2, 5].map(BaseElement::from).as_slice(),
+        merged.get_row(0)
+    );
+    assert_eq!(
+        [3u32, 4, 6].map(BaseElement::from).as_slice(),
+        merged.get_row(1)
+    );
+}
+
+// TRANSITION EXEMPTIONS
+// ================================================================================================
+
+#[test]
+fn transition_exemptions_context_serialization() {
+    let trace_info = TraceInfo::new(2, 16);
+    let context = Context::new::<BaseElement>(&trace_info, build_options())
+        .with_transition_exemptions(&[1, 3]);
+    assert_eq!(&[1, 3], context.transition_exemptions());
+    assert_eq!(3, context.num_transition_exemptions());
+
+    // the exemptions are flagged in the trace length byte and follow it
+    let bytes = context.to_bytes();
+    let offset = trace_info.layout().to_bytes().len();
+    assert_eq!(0x40 | 4, bytes[offset]);
+    assert_eq!(2u16.to_le_bytes(), bytes[offset + 1..offset + 3]);
+    assert_eq!(1u64.to_le_bytes(), bytes[offset + 3..offset + 11]);
+    assert_eq!(3u64.to_le_bytes(), bytes[offset + 11..offset + 19]);
+    assert_eq!(
+        context,
+        Context::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    // contexts in which every constraint has a single exemption are serialized as before, and
+    // the exemptions are bound into the element encoding of the context
+    let plain = Context::new::<BaseElement>(&trace_info, build_options())
+        .with_transition_exemptions(&[1, 1]);
+    assert!(plain.transition_exemptions().is_empty());
+    assert_eq!(1, plain.num_transition_exemptions());
+    assert_eq!(bytes.len() - 18, plain.to_bytes().len());
+    assert_ne!(
+        context.to_elements::<BaseElement>(),
+        plain.to_elements::<BaseElement>()
+    );
+
+    // exemptions with the same maximum but assigned to different constraints are distinguished
+    let swapped = Context::new::<BaseElement>(&trace_info, build_options())
+        .with_transition_exemptions(&[3, 1]);
+    assert_eq!(3, swapped.num_transition_exemptions());
+    assert_ne!(context.to_bytes(), swapped.to_bytes());
+    assert_ne!(
+        context.to_elements::<BaseElement>(),
+        swapped.to_elements::<BaseElement>()
+    );
+
+    // the exemptions are serialized together with a preprocessed segment
+    let trace_info = trace_info.with_preprocessed_segment(3);
+    let context =
+        Context::new::<BaseElement>(&trace_info, build_options()).with_transition_exemptions(&[9]);
+    assert_eq!(
+        context,
+        Context::read_from(&mut SliceReader::new(&context.to_bytes())).unwrap()
+    );
+}
+
+#[test]
+fn transition_exemptions_context_invalid_count() {
+    let trace_info = TraceInfo::new(2, 16);
+    let mut bytes = Context::new::<BaseElement>(&trace_info, build_options())
+        .with_transition_exemptions(&[1, 3])
+        .to_bytes();
+    let offset = trace_info.layout().to_bytes().len() + 11;
+
+    // exemptions in which every constraint has a single exemption must not be flagged, and
+    // exemptions cannot cover more than half of the trace plus one row
+    for num_exemptions in [0u64, 1, 10] {
+        bytes[offset..offset + 8].copy_from_slice(&num_exemptions.to_le_bytes());
+        let result = Context::read_from(&mut SliceReader::new(&bytes));
+        assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+    }
+}
+
+#[test]
+#[should_panic(expected = "number of transition exemptions must be between 1 and 9, but was 10")]
+fn transition_exemptions_too_many() {
+    Context::new::<BaseElement>(&TraceInfo::new(2, 16), build_options())
+        .with_transition_exemptions(&[1, 10]);
+}
+
+// HASH FUNCTION
+// ================================================================================================
+
+#[test]
+fn hash_function_context_serialization() {
+    let trace_info = TraceInfo::new(2, 16);
+    let plain = Context::new::<BaseElement>(&trace_info, build_options());
+    assert_eq!(None, plain.hash_fn());
+
+    let context = plain.clone().with_hash_fn(HashFunctionId::Sha3_256);
+    assert_eq!(Some(HashFunctionId::Sha3_256), context.hash_fn());
+
+    // the hash function is flagged in the modulus length byte and follows the modulus bytes
+    let plain_bytes = plain.to_bytes();
+    let bytes = context.to_bytes();
+    let offset = plain_bytes.len() - build_options().to_bytes().len() - 17;
+    assert_eq!(16, plain_bytes[offset]);
+    assert_eq!(0x80 | 16, bytes[offset]);
+    assert_eq!(HashFunctionId::Sha3_256 as u8, bytes[offset + 17]);
+    assert_eq!(plain_bytes.len() + 1, bytes.len());
+    assert_eq!(
+        context,
+        Context::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    // the hash function is bound into the element encoding of the context
+    let mut elements = context.to_elements::<BaseElement>();
+    assert_eq!(
+        Some(BaseElement::from(0x100 | HashFunctionId::Sha3_256 as u64)),
+        elements.pop()
+    );
+    assert_eq!(plain.to_elements::<BaseElement>(), elements);
+
+    // unknown hash function identifiers are rejected
+    let mut bytes = bytes;
+    bytes[offset + 17] = 0;
+    let result = Context::read_from(&mut SliceReader::new(&bytes));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+}
+
+// COMMITMENT SCHEME
+// ================================================================================================
+
+#[test]
+fn commitment_scheme_context_serialization() {
+    let trace_info = TraceInfo::new(2, 16);
+    let plain = Context::new::<BaseElement>(&trace_info, build_options())
+        .with_hash_fn(HashFunctionId::Sha3_256);
+    assert_eq!(0, plain.commitment_scheme());
+
+    // the Merkle tree scheme is not recorded explicitly
+    let merkle = plain.clone().with_commitment_scheme(0);
+    assert_eq!(plain.to_bytes(), merkle.to_bytes());
+
+    // other schemes are flagged in the modulus length byte and follow the hash function
+    let context = plain.clone().with_commitment_scheme(7);
+    assert_eq!(7, context.commitment_scheme());
+    let plain_bytes = plain.to_bytes();
+    let bytes = context.to_bytes();
+    let offset = plain_bytes.len() - build_options().to_bytes().len() - 18;
+    assert_eq!(0x80 | 16, plain_bytes[offset]);
+    assert_eq!(0xc0 | 16, bytes[offset]);
+    assert_eq!(7, bytes[offset + 18]);
+    assert_eq!(plain_bytes.len() + 1, bytes.len());
+    assert_eq!(
+        context,
+        Context::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    // the scheme is bound into the element encoding of the context
+    let mut elements = context.to_elements::<BaseElement>();
+    assert_eq!(Some(BaseElement::from(0x200 | 7_u64)), elements.pop());
+    assert_eq!(plain.to_elements::<BaseElement>(), elements);
+
+    // the Merkle tree scheme cannot be recorded explicitly
+    let mut bytes = bytes;
+    bytes[offset + 18] = 0;
+    let result = Context::read_from(&mut SliceReader::new(&bytes));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+}
+
+// RANDOM COIN
+// ================================================================================================
+
+#[test]
+fn random_coin_context_serialization() {
+    let trace_info = TraceInfo::new(2, 16);
+    let plain = Context::new::<BaseElement>(&trace_info, build_options())
+        .with_hash_fn(HashFunctionId::Sha3_256)
+        .with_commitment_scheme(7);
+    assert_eq!(0, plain.random_coin());
+
+    // the default coin is not recorded explicitly
+    let default = plain.clone().with_random_coin(0);
+    assert_eq!(plain.to_bytes(), default.to_bytes());
+
+    // other coins are flagged in the modulus length byte and follow the commitment scheme
+    let context = plain.clone().with_random_coin(3);
+    assert_eq!(3, context.random_coin());
+    let plain_bytes = plain.to_bytes();
+    let bytes = context.to_bytes();
+    let offset = plain_bytes.len() - build_options().to_bytes().len() - 19;
+    assert_eq!(0xc0 | 16, plain_bytes[offset]);
+    assert_eq!(0xe0 | 16, bytes[offset]);
+    assert_eq!(7, bytes[offset + 18]);
+    assert_eq!(3, bytes[offset + 19]);
+    assert_eq!(plain_bytes.len() + 1, bytes.len());
+    assert_eq!(
+        context,
+        Context::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    );
+
+    // the coin is bound into the element encoding of the context
+    let mut elements = context.to_elements::<BaseElement>();
+    assert_eq!(Some(BaseElement::from(0x400 | 3_u64)), elements.pop());
+    assert_eq!(plain.to_elements::<BaseElement>(), elements);
+
+    // the default coin cannot be recorded explicitly
+    let mut bytes = bytes;
+    bytes[offset + 19] = 0;
+    let result = Context::read_from(&mut SliceReader::new(&bytes));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+}
+
+// TRACE INFO SERIALIZATION
+// ================================================================================================
+
+#[test]
+fn trace_info_serialization() {
+    let trace_info = TraceInfo::with_meta(3, 32, vec![1, 2]).with_preprocessed_segment(2);
+    let bytes = trace_info.to_bytes();
+    assert_eq!(vec![3, 0, 0, 5, 2, 2, 0, 1, 2], bytes);
+    let result = TraceInfo::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(trace_info, result);
+
+    // trace length must be at least 8
+    let mut bytes = TraceInfo::new(3, 32).to_bytes();
+    bytes[3] = 2;
+    assert!(matches!(
+        TraceInfo::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // total trace width cannot exceed 255 columns
+    let mut bytes = TraceInfo::new(255, 32).to_bytes();
+    bytes[4] = 1;
+    assert!(matches!(
+        TraceInfo::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+// SERDE
+// ================================================================================================
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_json_encodes_bytes_as_hex() {
+    let options = build_options().with_fri_remainder_digest();
+    let json = serde_json::to_string(&options).unwrap();
+    assert_eq!("\"200800090408\"", json);
+    assert_eq!(options, serde_json::from_str(&json).unwrap());
+
+    let trace_info = TraceInfo::with_meta(3, 32, vec![1, 2]).with_preprocessed_segment(2);
+    let json = serde_json::to_string(&trace_info).unwrap();
+    assert_eq!("\"030000050202000102\"", json);
+    assert_eq!(trace_info, serde_json::from_str(&json).unwrap());
+
+    let context = Context::new::<BaseElement>(&trace_info, options);
+    let json = serde_json::to_string(&context).unwrap();
+    assert_eq!(context, serde_json::from_str(&json).unwrap());
+
+    // upper case digits are accepted as well
+    assert_ne!(json, json.to_uppercase());
+    assert_eq!(context, serde_json::from_str(&json.to_uppercase()).unwrap());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_json_invalid_bytes() {
+    // odd number of digits
+    let err = serde_json::from_str::<ProofOptions>("\"200800090\"").unwrap_err();
+    assert!(err.to_string().contains("even number of digits"));
+
+    // invalid digit
+    let err = serde_json::from_str::<ProofOptions>("\"2008000x0408\"").unwrap_err();
+    assert!(err.to_string().contains("invalid hex digit at position 7"));
+
+    // unconsumed bytes
+    let err = serde_json::from_str::<ProofOptions>("\"2008000904080000\"").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&DeserializationError::UnconsumedBytes.to_string()));
+}
+
+// ARCHIVAL ENVELOPE
+// ================================================================================================
+
+#[test]
+fn archive_header_byte_offsets() {
+    let digest = [0xab; 32];
+    let bytes = build_envelope_header(digest, 5, 3);
+
+    let header = ArchiveHeader::read_from(&bytes).unwrap();
+    assert_eq!(ARCHIVE_FORMAT_VERSION, header.version());
+    assert_eq!(64, header.field_id());
+    assert_eq!(7, header.hash_id());
+    assert_eq!(300, header.trace_width());
+    assert_eq!(1 << 20, header.trace_length());
+    assert_eq!(1_700_000_000, header.created_at());
+    assert_eq!(&digest, header.proof_digest());
+    assert_eq!(5, header.proof_size());
+    assert_eq!(3, header.signature_size());
+    assert_eq!((ARCHIVE_HEADER_SIZE + 8) as u128, header.envelope_size());
+
+    // the header serializes back into exactly the same bytes
+    assert_eq!(bytes, header.to_bytes().to_vec());
+}
+
+#[test]
+fn archive_header_parse_errors() {
+    let bytes = build_envelope_header([0; 32], 0, 0);
+    assert_eq!(
+        Err(ArchiveError::Truncated(ARCHIVE_HEADER_SIZE, 73)),
+        ArchiveHeader::read_from(&bytes[..73])
+    );
+
+    let mut invalid_magic = bytes.clone();
+    invalid_magic[7] = b'X';
+    assert_eq!(
+        Err(ArchiveError::InvalidMagic),
+        ArchiveHeader::read_from(&invalid_magic)
+    );
+
+    let mut invalid_version = bytes;
+    invalid_version[8] = 2;
+    assert_eq!(
+        Err(ArchiveError::UnsupportedVersion(2)),
+        ArchiveHeader::read_from(&invalid_version)
+    );
+}
+
+#[test]
+fn archival_envelope_parsing() {
+    let proof_bytes = [1_u8, 2, 3, 4, 5];
+    let signature = [9_u8, 8, 7];
+    let digest = *blake3::hash(&proof_bytes).as_bytes();
+    let mut bytes = build_envelope_header(digest, proof_bytes.len(), signature.len());
+    bytes.extend_from_slice(&signature);
+    bytes.extend_from_slice(&proof_bytes);
+
+    let envelope = ArchivalEnvelope::from_bytes(&bytes).unwrap();
+    assert_eq!(Some(&signature[..]), envelope.signature());
+    assert_eq!(&proof_bytes[..], envelope.proof_bytes());
+    assert_eq!(bytes, envelope.to_bytes());
+
+    // truncated envelope
+    assert_eq!(
+        Err(ArchiveError::Truncated(bytes.len(), bytes.len() - 1)),
+        ArchivalEnvelope::from_bytes(&bytes[..bytes.len() - 1])
+    );
+
+    // trailing bytes
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert_eq!(
+        Err(ArchiveError::TrailingBytes(1)),
+        ArchivalEnvelope::from_bytes(&extended)
+    );
+
+    // tampered proof
+    let mut tampered = bytes.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        Err(ArchiveError::DigestMismatch),
+        ArchivalEnvelope::from_bytes(&tampered)
+    );
+
+    // the proof cannot be unsealed because the enclosed bytes do not encode a valid proof
+    assert!(matches!(
+        ArchivalEnvelope::unseal(&bytes, None::<fn(&[u8], &[u8]) -> bool>),
+        Err(ArchiveError::InvalidProof(_))
+    ));
+    assert_eq!(
+        Err(ArchiveError::InvalidSignature),
+        ArchivalEnvelope::unseal(&bytes, Some(|_: &[u8], _: &[u8]| false))
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_options() -> ProofOptions {
+    ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+}
+
+/// Returns a serialized context in which the trace length is set to 2^`log_trace_length`.
+fn build_context_bytes(log_trace_length: u8) -> Vec<u8> {
+    let trace_info = TraceInfo::new(2, 16);
+    let mut bytes = Context::new::<BaseElement>(&trace_info, build_options()).to_bytes();
+    bytes[trace_info.layout().to_bytes().len()] = log_trace_length;
+    bytes
+}
+
+/// Returns a serialized archival envelope header with fields written at their documented byte
+/// offsets.
+fn build_envelope_header(digest: [u8; 32], proof_size: usize, signature_size: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"WFPROOF\0"); // offset 0: magic
+    bytes.extend_from_slice(&[1, 0]); // offset 8: format version
+    bytes.push(64); // offset 10: field id
+    bytes.push(7); // offset 11: hash id
+    bytes.extend_from_slice(&300_u32.to_le_bytes()); // offset 12: trace width
+    bytes.extend_from_slice(&(1_u64 << 20).to_le_bytes()); // offset 16: trace length
+    bytes.extend_from_slice(&1_700_000_000_u64.to_le_bytes()); // offset 24: creation time
+    bytes.extend_from_slice(&digest); // offset 32: proof digest
+    bytes.extend_from_slice(&(proof_size as u64).to_le_bytes()); // offset 64: proof size
+    bytes.extend_from_slice(&(signature_size as u16).to_le_bytes()); // offset 72: signature size
+    assert_eq!(ARCHIVE_HEADER_SIZE, bytes.len());
+    bytes
+}
@@ -0,0 +1,302 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Components for building permutation and multiset-equality arguments over auxiliary trace
+//! segments.
+//!
+//! Randomized AIRs with preprocessing (RAPs) commonly rely on running product columns. Given a
+//! set of random elements $\alpha, \beta_0, ..., \beta_{k-1}$ drawn after the main trace segment
+//! has been committed to, a running product over trace columns $c_0, ..., c_{k-1}$ is defined as:
+//!
+//! $$
+//! p_0 = 1, \quad p_{i+1} = p_i \cdot (\alpha + \sum_{j=0}^{k-1} \beta_j \cdot c_j(i))
+//! $$
+//!
+//! Two such products built from the same random elements are equal (with high probability) only
+//! if the rows they are built over form the same multiset. [MultisetEquality] combines two
+//! running products into a single auxiliary column which steps by the ratio of the two products
+//! and thus must start and end with $1$.
+//!
+//! Both components expose the three pieces an AIR needs: a column builder to be used by the
+//! prover when building an auxiliary trace segment, a transition constraint evaluator together
+//! with its degree, and boundary assertions against the auxiliary column.
+
+use crate::{Assertion, TransitionConstraintDegree};
+use math::{batch_inversion, ExtensionOf, FieldElement};
+use utils::collections::Vec;
+
+#[cfg(all(test, feature = "f128", feature = "quadratic"))]
+mod tests;
+
+// RUNNING PRODUCT
+// ================================================================================================
+/// A running product of random linear combinations of trace row values.
+///
+/// Each row `r` contributes the factor $\alpha + \sum_j \beta_j \cdot r[c_j]$, where $c_j$ are
+/// the column indices specified at construction time. Rows are read from whichever slice is passed
+/// in by the caller, and thus, the columns can refer either to the main trace segment or to
+/// previously built auxiliary columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningProduct<E: FieldElement> {
+    alpha: E,
+    betas: Vec<E>,
+    columns: Vec<usize>,
+}
+
+impl<E: FieldElement> RunningProduct<E> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new running product over the specified columns.
+    ///
+    /// The first element of `random_elements` is used as $\alpha$, and the next
+    /// `column_indices.len()` elements are used as $\beta_j$ coefficients; any remaining elements
+    /// are ignored.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `column_indices` is empty.
+    /// * Fewer than `column_indices.len() + 1` random elements were provided.
+    pub fn new(random_elements: &[E], column_indices: Vec<usize>) -> Self {
+        assert!(
+            !column_indices.is_empty(),
+            "a running product must be defined over at least one column"
+        );
+        let num_rand_elements = Self::num_rand_elements(column_indices.len());
+        assert!(
+            random_elements.len() >= num_rand_elements,
+            "expected at least {} random elements, but was {}",
+            num_rand_elements,
+            random_elements.len()
+        );
+        RunningProduct {
+            alpha: random_elements[0],
+            betas: random_elements[1..num_rand_elements].to_vec(),
+            columns: column_indices,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of random elements needed to build a running product over the specified
+    /// number of columns.
+    pub fn num_rand_elements(num_columns: usize) -> usize {
+        num_columns + 1
+    }
+
+    /// Returns indexes of the columns included in this running product.
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Returns the degree of the transition constraint enforced by this running product.
+    ///
+    /// The constraint multiplies the auxiliary column by a linear combination of trace values,
+    /// and thus, its degree is always 2.
+    pub fn transition_degree(&self) -> TransitionConstraintDegree {
+        TransitionConstraintDegree::new(2)
+    }
+
+    // EVALUATORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns $\alpha + \sum_j \beta_j \cdot row[c_j]$ for the provided row.
+    pub fn combine<F>(&self, row: &[F]) -> E
+    where
+        F: FieldElement<BaseField = E::BaseField>,
+        E: ExtensionOf<F>,
+    {
+        self.columns
+            .iter()
+            .zip(self.betas.iter())
+            .fold(self.alpha, |acc, (&column, &beta)| {
+                acc + beta.mul_base(row[column])
+            })
+    }
+
+    /// Evaluates the transition constraint $p' - p \cdot (\alpha + \sum_j \beta_j \cdot c_j)$
+    /// where `current` is the current row from which combined values are read, and `aux_current`
+    /// and `aux_next` are values of the running product column in the current and next rows.
+    pub fn evaluate_transition<F>(&self, current: &[F], aux_current: E, aux_next: E) -> E
+    where
+        F: FieldElement<BaseField = E::BaseField>,
+        E: ExtensionOf<F>,
+    {
+        aux_next - aux_current * self.combine(current)
+    }
+
+    // COLUMN BUILDER
+    // --------------------------------------------------------------------------------------------
+
+    /// Builds the running product column over a trace of the specified length.
+    ///
+    /// `read_row` is invoked for rows `0` through `trace_length - 2` and must write values of the
+    /// specified row into the provided buffer of `row_width` elements. The last row of the
+    /// trace does not contribute to the product because transition constraints do not apply to
+    /// it.
+    pub fn build_column<F, R>(
+        &self,
+        trace_length: usize,
+        row_width: usize,
+        mut read_row: R,
+    ) -> Vec<E>
+    where
+        F: FieldElement<BaseField = E::BaseField>,
+        E: ExtensionOf<F>,
+        R: FnMut(usize, &mut [F]),
+    {
+        let mut row = vec![F::ZERO; row_width];
+        let mut result = Vec::with_capacity(trace_length);
+        result.push(E::ONE);
+        for i in 0..trace_length - 1 {
+            read_row(i, &mut row);
+            result.push(result[i] * self.combine(&row));
+        }
+        result
+    }
+
+    /// Returns assertions against the running product column at index `aux_column` requiring the
+    /// column to start with ONE and end with `final_value`.
+    pub fn get_assertions(
+        &self,
+        aux_column: usize,
+        trace_length: usize,
+        final_value: E,
+    ) -> Vec<Assertion<E>> {
+        vec![
+            Assertion::single(aux_column, 0, E::ONE),
+            Assertion::single(aux_column, trace_length - 1, final_value),
+        ]
+    }
+}
+
+// MULTISET EQUALITY
+// ================================================================================================
+/// An argument that the rows combined by two running products form the same multiset.
+///
+/// The argument uses a single auxiliary column $z$ defined as:
+///
+/// $$
+/// z_0 = 1, \quad z_{i+1} = z_i \cdot \frac{l(i)}{r(i)}
+/// $$
+///
+/// where $l(i)$ and $r(i)$ are the combined values of row $i$ for the left and the right running
+/// products respectively. The argument holds if $z$ is equal to $1$ in the last row of the trace.
+///
+/// When both sides combine a single column each, this argument shows that one column is a
+/// permutation of the other (over all but the last row of the trace).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisetEquality<E: FieldElement> {
+    left: RunningProduct<E>,
+    right: RunningProduct<E>,
+}
+
+impl<E: FieldElement> MultisetEquality<E> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new multiset equality argument between the `left` and the `right` running
+    /// products.
+    ///
+    /// # Panics
+    /// Panics if the running products are defined over different numbers of columns.
+    pub fn new(left: RunningProduct<E>, right: RunningProduct<E>) -> Self {
+        assert_eq!(
+            left.columns().len(),
+            right.columns().len(),
+            "running products must be defined over the same number of columns"
+        );
+        MultisetEquality { left, right }
+    }
+
+    /// Returns a new multiset equality argument between the `left` and `right` sets of columns
+    /// using the same random elements for both sides.
+    ///
+    /// # Panics
+    /// Panics if `left` and `right` have different lengths, or not enough random elements were
+    /// provided.
+    pub fn from_columns(random_elements: &[E], left: Vec<usize>, right: Vec<usize>) -> Self {
+        Self::new(
+            RunningProduct::new(random_elements, left),
+            RunningProduct::new(random_elements, right),
+        )
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the left side of this argument.
+    pub fn left(&self) -> &RunningProduct<E> {
+        &self.left
+    }
+
+    /// Returns the right side of this argument.
+    pub fn right(&self) -> &RunningProduct<E> {
+        &self.right
+    }
+
+    /// Returns the degree of the transition constraint enforced by this argument.
+    pub fn transition_degree(&self) -> TransitionConstraintDegree {
+        TransitionConstraintDegree::new(2)
+    }
+
+    // EVALUATORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Evaluates the transition constraint $z' \cdot r - z \cdot l$, where `current` is the
+    /// current row from which combined values are read, and `aux_current` and `aux_next` are
+    /// values of the argument column in the current and next rows.
+    pub fn evaluate_transition<F>(&self, current: &[F], aux_current: E, aux_next: E) -> E
+    where
+        F: FieldElement<BaseField = E::BaseField>,
+        E: ExtensionOf<F>,
+    {
+        aux_next * self.right.combine(current) - aux_current * self.left.combine(current)
+    }
+
+    // COLUMN BUILDER
+    // --------------------------------------------------------------------------------------------
+
+    /// Builds the argument column over a trace of the specified length.
+    ///
+    /// `read_row` is invoked for rows `0` through `trace_length - 2` and must write values of the
+    /// specified row into the provided buffer of `row_width` elements.
+    pub fn build_column<F, R>(
+        &self,
+        trace_length: usize,
+        row_width: usize,
+        mut read_row: R,
+    ) -> Vec<E>
+    where
+        F: FieldElement<BaseField = E::BaseField>,
+        E: ExtensionOf<F>,
+        R: FnMut(usize, &mut [F]),
+    {
+        let mut row = vec![F::ZERO; row_width];
+        let mut numerators = Vec::with_capacity(trace_length - 1);
+        let mut denominators = Vec::with_capacity(trace_length - 1);
+        for i in 0..trace_length - 1 {
+            read_row(i, &mut row);
+            numerators.push(self.left.combine(&row));
+            denominators.push(self.right.combine(&row));
+        }
+        let denominators = batch_inversion(&denominators);
+
+        let mut result = Vec::with_capacity(trace_length);
+        result.push(E::ONE);
+        for (i, (&num, &denom_inv)) in numerators.iter().zip(denominators.iter()).enumerate() {
+            result.push(result[i] * num * denom_inv);
+        }
+        result
+    }
+
+    /// Returns assertions against the argument column at index `aux_column` requiring the column
+    /// to start and end with ONE.
+    pub fn get_assertions(&self, aux_column: usize, trace_length: usize) -> Vec<Assertion<E>> {
+        vec![
+            Assertion::single(aux_column, 0, E::ONE),
+            Assertion::single(aux_column, trace_length - 1, E::ONE),
+        ]
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{MultisetEquality, RunningProduct};
+use crate::{
+    Air, AirContext, Assertion, AuxTraceRandElements, EvaluationFrame, FieldExtension,
+    ProofOptions, TraceInfo, TraceLayoutBuilder, TransitionConstraintDegree,
+};
+use math::{
+    fields::{f128::BaseElement, QuadExtension},
+    ExtensionOf, FieldElement,
+};
+use rand_utils::{rand_value, rand_vector};
+use utils::collections::Vec;
+
+type QuadElement = QuadExtension<BaseElement>;
+
+// RUNNING PRODUCT
+// ================================================================================================
+
+#[test]
+fn running_product_combine() {
+    let rand_elements: Vec<QuadElement> = rand_vector(4);
+    let product = RunningProduct::new(&rand_elements, vec![2, 0]);
+    assert_eq!(&[2, 0], product.columns());
+
+    let row = [
+        BaseElement::new(3),
+        BaseElement::new(5),
+        BaseElement::new(7),
+    ];
+    let expected = rand_elements[0]
+        + rand_elements[1] * QuadElement::from(row[2])
+        + rand_elements[2] * QuadElement::from(row[0]);
+    assert_eq!(expected, product.combine(&row));
+}
+
+#[test]
+fn running_product_column() {
+    let trace_length = 16;
+    let columns = [rand_vector::<BaseElement>(trace_length)];
+    let rand_elements: Vec<QuadElement> = rand_vector(2);
+    let product = RunningProduct::new(&rand_elements, vec![0]);
+
+    let column = product.build_column(trace_length, 1, |i, row| row[0] = columns[0][i]);
+    assert_eq!(trace_length, column.len());
+    assert_eq!(QuadElement::ONE, column[0]);
+
+    // the transition constraint must hold on all steps but the last one
+    for i in 0..trace_length - 1 {
+        let current = [columns[0][i]];
+        assert_eq!(
+            QuadElement::ZERO,
+            product.evaluate_transition(&current, column[i], column[i + 1])
+        );
+    }
+
+    // the final value must be the product of all combined rows but the last one
+    let expected = columns[0][..trace_length - 1]
+        .iter()
+        .fold(QuadElement::ONE, |acc, &v| {
+            acc * (rand_elements[0] + rand_elements[1].mul_base(v))
+        });
+    let assertions = product.get_assertions(3, trace_length, expected);
+    check_assertions(&assertions, &column, trace_length);
+}
+
+#[test]
+#[should_panic(expected = "expected at least 3 random elements, but was 2")]
+fn running_product_not_enough_rand_elements() {
+    let rand_elements: Vec<BaseElement> = rand_vector(2);
+    RunningProduct::new(&rand_elements, vec![0, 1]);
+}
+
+#[test]
+#[should_panic(expected = "running products must be defined over the same number of columns")]
+fn multiset_equality_inconsistent_widths() {
+    let rand_elements: Vec<BaseElement> = rand_vector(3);
+    MultisetEquality::from_columns(&rand_elements, vec![0, 1], vec![2]);
+}
+
+// PERMUTATION AIR
+// ================================================================================================
+
+#[test]
+fn permutation_air_valid_trace() {
+    let trace_length = 32;
+    let (main_trace, aux_rand_elements) = build_permutation_trace(trace_length, false);
+    let air = PermutationAir::new(
+        build_trace_info(trace_length),
+        (),
+        ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256),
+    );
+
+    let aux_column = air.build_aux_column(&main_trace, &aux_rand_elements);
+    assert_eq!(QuadElement::ONE, aux_column[trace_length - 1]);
+
+    for step in 0..trace_length - 1 {
+        let result = air.evaluate_aux_step(&main_trace, &aux_column, &aux_rand_elements, step);
+        assert_eq!(QuadElement::ZERO, result);
+    }
+
+    let assertions = air.get_aux_assertions(&aux_rand_elements);
+    check_assertions(&assertions, &aux_column, trace_length);
+}
+
+#[test]
+fn permutation_air_invalid_trace() {
+    let trace_length = 32;
+    let (main_trace, aux_rand_elements) = build_permutation_trace(trace_length, true);
+    let air = PermutationAir::new(
+        build_trace_info(trace_length),
+        (),
+        ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256),
+    );
+
+    // the column can still be built so that transition constraints are satisfied, but the final
+    // value is not ONE, and thus, the boundary assertion against the last step fails
+    let aux_column = air.build_aux_column(&main_trace, &aux_rand_elements);
+    for step in 0..trace_length - 1 {
+        let result = air.evaluate_aux_step(&main_trace, &aux_column, &aux_rand_elements, step);
+        assert_eq!(QuadElement::ZERO, result);
+    }
+    assert_ne!(QuadElement::ONE, aux_column[trace_length - 1]);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// An AIR which proves that column 1 is a permutation of column 0 (over all but the last row),
+/// while column 2 is a step counter.
+struct PermutationAir {
+    context: AirContext<BaseElement>,
+}
+
+impl PermutationAir {
+    fn build_aux_column(
+        &self,
+        main_trace: &[Vec<BaseElement>],
+        aux_rand_elements: &AuxTraceRandElements<QuadElement>,
+    ) -> Vec<QuadElement> {
+        let rand_elements = aux_rand_elements.get_segment_elements(0);
+        build_argument(rand_elements).build_column(self.trace_length(), 3, |i, row| {
+            for (value, column) in row.iter_mut().zip(main_trace) {
+                *value = column[i];
+            }
+        })
+    }
+
+    fn evaluate_aux_step(
+        &self,
+        main_trace: &[Vec<BaseElement>],
+        aux_column: &[QuadElement],
+        aux_rand_elements: &AuxTraceRandElements<QuadElement>,
+        step: usize,
+    ) -> QuadElement {
+        let main_frame = EvaluationFrame::from_rows(
+            main_trace.iter().map(|c| c[step]).collect(),
+            main_trace.iter().map(|c| c[step + 1]).collect(),
+        );
+        let aux_frame =
+            EvaluationFrame::from_rows(vec![aux_column[step]], vec![aux_column[step + 1]]);
+        let mut result = [QuadElement::ZERO];
+        self.evaluate_aux_transition(&main_frame, &aux_frame, &[], aux_rand_elements, &mut result);
+        result[0]
+    }
+}
+
+impl Air for PermutationAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let context = AirContext::new_multi_segment(
+            trace_info,
+            vec![TransitionConstraintDegree::new(1)],
+            vec![TransitionConstraintDegree::new(2)],
+            1,
+            2,
+            options,
+        );
+        PermutationAir { context }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        result[0] = frame.next()[2] - frame.current()[2] - E::ONE;
+    }
+
+    fn evaluate_aux_transition<F, E>(
+        &self,
+        main_frame: &EvaluationFrame<F>,
+        aux_frame: &EvaluationFrame<E>,
+        _periodic_values: &[F],
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        result: &mut [E],
+    ) where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+        let argument = build_argument(aux_rand_elements.get_segment_elements(0));
+        result[0] = argument.evaluate_transition(
+            main_frame.current(),
+            aux_frame.current()[0],
+            aux_frame.next()[0],
+        );
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        vec![Assertion::single(2, 0, BaseElement::ZERO)]
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Vec<Assertion<E>> {
+        build_argument(aux_rand_elements.get_segment_elements(0))
+            .get_assertions(0, self.trace_length())
+    }
+}
+
+fn build_argument<E: FieldElement>(rand_elements: &[E]) -> MultisetEquality<E> {
+    MultisetEquality::from_columns(rand_elements, vec![0], vec![1])
+}
+
+fn build_trace_info(trace_length: usize) -> TraceInfo {
+    let layout = TraceLayoutBuilder::new()
+        .main_width(3)
+        .aux_segment(1, 2)
+        .build()
+        .unwrap();
+    TraceInfo::new_multi_segment(layout, trace_length, vec![])
+}
+
+/// Builds a trace where column 1 is a reversal of column 0 over the first `trace_length - 1`
+/// rows; when `tamper` is true, one value in column 1 is changed.
+fn build_permutation_trace(
+    trace_length: usize,
+    tamper: bool,
+) -> (Vec<Vec<BaseElement>>, AuxTraceRandElements<QuadElement>) {
+    let mut a = rand_vector::<BaseElement>(trace_length);
+    a[trace_length - 1] = BaseElement::ZERO;
+    let mut b = a[..trace_length - 1]
+        .iter()
+        .rev()
+        .copied()
+        .collect::<Vec<_>>();
+    b.push(BaseElement::ONE);
+    if tamper {
+        b[3] += BaseElement::ONE;
+    }
+    let counter = (0..trace_length as u128).map(BaseElement::new).collect();
+
+    let mut aux_rand_elements = AuxTraceRandElements::new();
+    aux_rand_elements.add_segment_elements(vec![rand_value(), rand_value()]);
+    (vec![a, b, counter], aux_rand_elements)
+}
+
+fn check_assertions<E: FieldElement>(
+    assertions: &[Assertion<E>],
+    column: &[E],
+    trace_length: usize,
+) {
+    for assertion in assertions {
+        assertion.apply(trace_length, |step, value| assert_eq!(value, column[step]));
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Components for gating transition constraints on per-row operation selector flags.
+//!
+//! AIRs describing virtual machines usually dedicate a set of trace columns to operation flags:
+//! exactly one flag is set to ONE in every row, and constraints describing a given operation
+//! are multiplied by the flag of that operation so that they apply only to rows in which the
+//! operation is executed. [SelectorSet] removes the boilerplate involved in this pattern:
+//!
+//! * It provides constraints which enforce that all flags are binary and that exactly one flag
+//!   is set in every row, together with degrees of these constraints.
+//! * It provides [gate()](SelectorSet::gate) and [gate_degree()](SelectorSet::gate_degree)
+//!   functions which multiply a constraint by a flag and compute the degree of the resulting
+//!   constraint respectively.
+//! * It provides functions for validating flags of an execution trace on the prover side, so
+//!   that an invalid trace can be caught before a proof is generated for it.
+
+use crate::{SelectorError, TransitionConstraintDegree};
+use core::ops::Range;
+use math::FieldElement;
+use utils::collections::Vec;
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// SELECTOR SET
+// ================================================================================================
+/// A set of mutually exclusive operation flags stored in a contiguous range of trace columns.
+///
+/// The set defines `num_flags + 1` transition constraints: the first `num_flags` constraints
+/// enforce that every flag is binary (i.e., $f_i^2 - f_i = 0$), and the last constraint enforces
+/// that exactly one flag is set (i.e., $\sum_i f_i - 1 = 0$). Since the flags are binary, the
+/// latter constraint is equivalent to a one-hot constraint.
+///
+/// Same as all other transition constraints, these constraints apply to all rows of the trace
+/// except for the last one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorSet {
+    columns: Range<usize>,
+}
+
+impl SelectorSet {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new selector set with flags stored in the specified range of trace columns.
+    ///
+    /// Flag `i` of the set is stored in column `columns.start + i`.
+    ///
+    /// # Panics
+    /// Panics if `columns` is empty.
+    pub fn new(columns: Range<usize>) -> Self {
+        assert!(
+            !columns.is_empty(),
+            "a selector set must contain at least one flag"
+        );
+        SelectorSet { columns }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the range of trace columns containing flags of this set.
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    /// Returns the number of flags in this set.
+    pub fn num_flags(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the number of transition constraints defined by this set.
+    pub fn num_constraints(&self) -> usize {
+        self.num_flags() + 1
+    }
+
+    /// Returns degrees of the transition constraints defined by this set in the order in which
+    /// they are evaluated by [evaluate_constraints()](SelectorSet::evaluate_constraints).
+    pub fn constraint_degrees(&self) -> Vec<TransitionConstraintDegree> {
+        let mut result = vec![TransitionConstraintDegree::new(2); self.num_flags()];
+        result.push(TransitionConstraintDegree::new(1));
+        result
+    }
+
+    // EVALUATORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the value of the specified flag in the provided row.
+    ///
+    /// # Panics
+    /// Panics if `flag_idx` is greater than or equal to the number of flags in this set.
+    pub fn flag<E: FieldElement>(&self, row: &[E], flag_idx: usize) -> E {
+        assert!(
+            flag_idx < self.num_flags(),
+            "flag index must be smaller than {}, but was {}",
+            self.num_flags(),
+            flag_idx
+        );
+        row[self.columns.start + flag_idx]
+    }
+
+    /// Evaluates transition constraints defined by this set against the provided row and writes
+    /// the results into `result`.
+    ///
+    /// # Panics
+    /// Panics if `result` is shorter than the number of constraints defined by this set.
+    pub fn evaluate_constraints<E: FieldElement>(&self, row: &[E], result: &mut [E]) {
+        let flags = &row[self.columns.clone()];
+        let result = &mut result[..self.num_constraints()];
+        for (result, &flag) in result.iter_mut().zip(flags) {
+            *result = flag * flag - flag;
+        }
+        result[self.num_flags()] = flags.iter().fold(E::ZERO, |acc, &flag| acc + flag) - E::ONE;
+    }
+
+    /// Returns the provided constraint evaluation multiplied by the specified flag in the
+    /// provided row.
+    ///
+    /// The degree of the resulting constraint can be computed via
+    /// [gate_degree()](SelectorSet::gate_degree).
+    ///
+    /// # Panics
+    /// Panics if `flag_idx` is greater than or equal to the number of flags in this set.
+    pub fn gate<E: FieldElement>(&self, row: &[E], flag_idx: usize, value: E) -> E {
+        self.flag(row, flag_idx) * value
+    }
+
+    /// Returns the degree of a constraint of the specified degree after it has been gated on a
+    /// flag of this set.
+    ///
+    /// Flags are trace columns, and thus, gating increases the base degree of a constraint by
+    /// one.
+    pub fn gate_degree(&self, degree: &TransitionConstraintDegree) -> TransitionConstraintDegree {
+        TransitionConstraintDegree::with_cycles(degree.base() + 1, degree.cycles().to_vec())
+    }
+
+    // VALIDATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the index of the flag set in the provided row.
+    ///
+    /// `step` is used only for error reporting.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * Any of the flags is not binary.
+    /// * None of the flags is set.
+    /// * More than one flag is set.
+    pub fn validate_row<E: FieldElement>(
+        &self,
+        step: usize,
+        row: &[E],
+    ) -> Result<usize, SelectorError> {
+        let mut active_flag = None;
+        for (i, &flag) in row[self.columns.clone()].iter().enumerate() {
+            if flag == E::ONE {
+                if let Some(first) = active_flag {
+                    return Err(SelectorError::MultipleFlagsSet(step, first, i));
+                }
+                active_flag = Some(i);
+            } else if flag != E::ZERO {
+                return Err(SelectorError::FlagNotBinary(step, self.columns.start + i));
+            }
+        }
+        active_flag.ok_or(SelectorError::NoFlagSet(step))
+    }
+
+    /// Validates flags in all rows of a trace of the specified length.
+    ///
+    /// `read_row` is invoked for every row of the trace and must write values of the specified
+    /// row into the provided buffer; the buffer is wide enough to contain all columns up to and
+    /// including the last flag column of this set. Unlike transition constraints, validation is
+    /// also applied to the last row of the trace.
+    ///
+    /// # Errors
+    /// Returns an error for the first row with invalid flags as described in
+    /// [validate_row()](SelectorSet::validate_row).
+    pub fn validate_rows<E, R>(
+        &self,
+        trace_length: usize,
+        mut read_row: R,
+    ) -> Result<(), SelectorError>
+    where
+        E: FieldElement,
+        R: FnMut(usize, &mut [E]),
+    {
+        let mut row = vec![E::ZERO; self.columns.end];
+        for step in 0..trace_length {
+            read_row(step, &mut row);
+            self.validate_row(step, &row)?;
+        }
+        Ok(())
+    }
+}
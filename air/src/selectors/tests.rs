@@ -0,0 +1,129 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::SelectorSet;
+use crate::{SelectorError, TransitionConstraintDegree};
+use math::{fields::f128::BaseElement, FieldElement};
+use utils::collections::Vec;
+
+// CONSTRAINTS
+// ================================================================================================
+
+#[test]
+fn selector_constraint_degrees() {
+    let selectors = SelectorSet::new(2..5);
+    assert_eq!(3, selectors.num_flags());
+    assert_eq!(4, selectors.num_constraints());
+    assert_eq!(
+        vec![
+            TransitionConstraintDegree::new(2),
+            TransitionConstraintDegree::new(2),
+            TransitionConstraintDegree::new(2),
+            TransitionConstraintDegree::new(1),
+        ],
+        selectors.constraint_degrees()
+    );
+}
+
+#[test]
+fn selector_constraints() {
+    let selectors = SelectorSet::new(1..4);
+    let mut result = vec![BaseElement::ZERO; selectors.num_constraints()];
+
+    // exactly one flag is set
+    selectors.evaluate_constraints(&build_row(&[0, 1, 0]), &mut result);
+    assert!(result.iter().all(|&v| v == BaseElement::ZERO));
+
+    // two flags are set: the flags are binary, but the one-hot constraint fails
+    selectors.evaluate_constraints(&build_row(&[1, 1, 0]), &mut result);
+    assert_eq!(&[BaseElement::ZERO; 3], &result[..3]);
+    assert_eq!(BaseElement::ONE, result[3]);
+
+    // no flags are set
+    selectors.evaluate_constraints(&build_row(&[0, 0, 0]), &mut result);
+    assert_eq!(BaseElement::ZERO - BaseElement::ONE, result[3]);
+
+    // a flag is not binary
+    selectors.evaluate_constraints(&build_row(&[0, 2, 0]), &mut result);
+    assert_eq!(BaseElement::new(2), result[1]);
+}
+
+#[test]
+fn gate_constraints() {
+    let selectors = SelectorSet::new(1..3);
+    let row = build_row(&[0, 1]);
+    let value = BaseElement::new(42);
+    assert_eq!(BaseElement::ZERO, selectors.gate(&row, 0, value));
+    assert_eq!(value, selectors.gate(&row, 1, value));
+
+    assert_eq!(
+        TransitionConstraintDegree::new(3),
+        selectors.gate_degree(&TransitionConstraintDegree::new(2))
+    );
+    assert_eq!(
+        TransitionConstraintDegree::with_cycles(2, vec![16]),
+        selectors.gate_degree(&TransitionConstraintDegree::with_cycles(1, vec![16]))
+    );
+}
+
+#[test]
+#[should_panic(expected = "flag index must be smaller than 2, but was 2")]
+fn gate_invalid_flag() {
+    let selectors = SelectorSet::new(1..3);
+    selectors.gate(&build_row(&[0, 1]), 2, BaseElement::ONE);
+}
+
+// VALIDATION
+// ================================================================================================
+
+#[test]
+fn validate_selector_rows() {
+    let selectors = SelectorSet::new(1..4);
+    assert_eq!(Ok(2), selectors.validate_row(0, &build_row(&[0, 0, 1])));
+    assert_eq!(
+        Err(SelectorError::MultipleFlagsSet(5, 0, 2)),
+        selectors.validate_row(5, &build_row(&[1, 0, 1]))
+    );
+    assert_eq!(
+        Err(SelectorError::NoFlagSet(3)),
+        selectors.validate_row(3, &build_row(&[0, 0, 0]))
+    );
+    assert_eq!(
+        Err(SelectorError::FlagNotBinary(1, 2)),
+        selectors.validate_row(1, &build_row(&[0, 3, 0]))
+    );
+}
+
+#[test]
+fn validate_selector_trace() {
+    let selectors = SelectorSet::new(1..4);
+    let mut flags = (0..16).map(|i| 1 << (i % 3)).collect::<Vec<u128>>();
+    let read_row = |flags: &[u128]| {
+        let flags = flags.to_vec();
+        move |step: usize, row: &mut [BaseElement]| {
+            row[0] = BaseElement::new(step as u128);
+            for (i, value) in row[1..].iter_mut().enumerate() {
+                *value = BaseElement::new((flags[step] >> i) & 1);
+            }
+        }
+    };
+    assert_eq!(Ok(()), selectors.validate_rows(16, read_row(&flags)));
+
+    flags[7] = 0b101;
+    assert_eq!(
+        Err(SelectorError::MultipleFlagsSet(7, 0, 2)),
+        selectors.validate_rows(16, read_row(&flags))
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds a row with a single non-flag column followed by the specified flag values.
+fn build_row(flags: &[u128]) -> Vec<BaseElement> {
+    let mut row = vec![BaseElement::new(7)];
+    row.extend(flags.iter().map(|&f| BaseElement::new(f)));
+    row
+}
@@ -0,0 +1,197 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Serde support for proof components.
+//!
+//! Proof components are (de)serialized via their canonical byte encoding (i.e., the encoding
+//! produced by [Serializable::to_bytes()]). In human-readable formats (e.g., JSON) the bytes are
+//! encoded as a lowercase hex string; in binary formats the bytes are written as is.
+
+use crate::{
+    proof::{Commitments, CompositionCoefficients, Context, OodFrame, Queries},
+    ProofOptions, TraceInfo,
+};
+use core::fmt;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserializer, Serializer,
+};
+use utils::{
+    collections::Vec, string::String, ByteReader, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+// SERIALIZATION FUNCTIONS
+// ================================================================================================
+
+/// Serializes the `value` into the `serializer` via the canonical byte encoding of the value.
+///
+/// This function can be used in `#[serde(serialize_with = "...")]` attributes.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serializable,
+    S: Serializer,
+{
+    let bytes = value.to_bytes();
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&encode_hex(&bytes))
+    } else {
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+/// Deserializes a value from the `deserializer` via the canonical byte encoding of the value.
+///
+/// This function can be used in `#[serde(deserialize_with = "...")]` attributes.
+///
+/// # Errors
+/// Returns an error if the input is not a valid encoding of a byte string, if the bytes could not
+/// be parsed into a value, or if some bytes were left over after the value was parsed.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserializable,
+    D: Deserializer<'de>,
+{
+    let bytes = if deserializer.is_human_readable() {
+        deserializer.deserialize_str(BytesVisitor)?
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor)?
+    };
+    read_value(&bytes).map_err(de::Error::custom)
+}
+
+// SERDE IMPLEMENTATIONS
+// ================================================================================================
+
+macro_rules! impl_serde_via_bytes {
+    ($($type:ty),*) => {$(
+        impl serde::Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize(self, serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserialize(deserializer)
+            }
+        }
+    )*};
+}
+
+impl_serde_via_bytes!(
+    ProofOptions,
+    TraceInfo,
+    Context,
+    Commitments,
+    Queries,
+    OodFrame,
+    CompositionCoefficients
+);
+
+// BYTES VISITOR
+// ================================================================================================
+
+/// Reads a byte string from either a hex string, a sequence of bytes, or a sequence of integers
+/// (the latter is how some formats encode bytes).
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a hex string or a sequence of bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        decode_hex(value).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(value)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut result = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            result.push(byte);
+        }
+        Ok(result)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Parses a value from the `bytes` making sure that all bytes are consumed.
+fn read_value<T: Deserializable>(bytes: &[u8]) -> Result<T, DeserializationError> {
+    let mut source = SliceReader::new(bytes);
+    let value = T::read_from(&mut source)?;
+    if source.has_more_bytes() {
+        return Err(DeserializationError::UnconsumedBytes);
+    }
+    Ok(value)
+}
+
+/// Encodes the `bytes` as a lowercase hex string.
+fn encode_hex(bytes: &[u8]) -> String {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        result.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        result.push(HEX_DIGITS[(byte & 0xf) as usize] as char);
+    }
+    result
+}
+
+/// Decodes a hex string (in either case) into bytes.
+fn decode_hex(value: &str) -> Result<Vec<u8>, HexError> {
+    let value = value.as_bytes();
+    if value.len() % 2 != 0 {
+        return Err(HexError::OddLength(value.len()));
+    }
+    value
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| Ok(hex_digit(pair[0], 2 * i)? << 4 | hex_digit(pair[1], 2 * i + 1)?))
+        .collect()
+}
+
+/// Returns the value of the hex digit `c` located at the specified position of a hex string.
+fn hex_digit(c: u8, position: usize) -> Result<u8, HexError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(HexError::InvalidDigit(position)),
+    }
+}
+
+/// Errors which can occur when decoding a hex string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HexError {
+    /// The string has an odd number of digits.
+    OddLength(usize),
+    /// The string contains a character which is not a hex digit at the specified position.
+    InvalidDigit(usize),
+}
+
+impl fmt::Display for HexError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddLength(len) => {
+                write!(f, "hex string must have an even number of digits, but had {len}")
+            }
+            Self::InvalidDigit(pos) => {
+                write!(f, "invalid hex digit at position {pos}")
+            }
+        }
+    }
+}
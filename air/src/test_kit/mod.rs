@@ -0,0 +1,360 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Utilities for testing AIR implementations without running the prover.
+//!
+//! The checks in this module operate on polynomials interpolated from the columns of an
+//! execution trace, and mirror the relations which the verifier relies on:
+//!
+//! * [evaluate_at_random_point()] checks that every transition constraint is divisible by its
+//!   divisor, and that the constraint composition polynomial evaluated at a point equals the
+//!   value the verifier reconstructs from the out-of-domain evaluation frame at that point.
+//! * [boundary_poly_check()] checks that every assertion divides the polynomial of the asserted
+//!   column without a remainder.
+//!
+//! Both checks panic with a description of the first violated relation. Only the constraints
+//! against the main trace segment are checked.
+//!
+//! This module is available only when the `test-kit` feature is enabled.
+
+use crate::{
+    Air, AuxTraceRandElements, BoundaryConstraints, ConstraintCompositionCoefficients,
+    ConstraintDivisor, EvaluationFrame, TransitionConstraints,
+};
+use crypto::{hashers::Blake3_256, DefaultRandomCoin};
+use math::{
+    fft, get_power_series_with_offset, log2, polynom, ExtensionOf, FieldElement, StarkField,
+};
+use utils::collections::Vec;
+
+// TRACE INTERPOLATION
+// ================================================================================================
+
+/// Returns polynomials in coefficient form interpolated from the specified trace columns over
+/// the trace domain.
+///
+/// # Panics
+/// Panics if the columns are not all of the same length, or if the length is not a power of two.
+pub fn interpolate_trace_columns<B: StarkField>(columns: &[&[B]]) -> Vec<Vec<B>> {
+    let trace_length = columns.first().map_or(0, |column| column.len());
+    assert!(
+        trace_length.is_power_of_two(),
+        "trace length must be a power of two, but was {trace_length}"
+    );
+
+    let inv_twiddles = fft::get_inv_twiddles::<B>(trace_length);
+    columns
+        .iter()
+        .map(|column| {
+            assert_eq!(
+                trace_length,
+                column.len(),
+                "all trace columns must have the same length"
+            );
+            let mut poly = column.to_vec();
+            fft::interpolate_poly(&mut poly, &inv_twiddles);
+            poly
+        })
+        .collect()
+}
+
+// OOD EVALUATION CHECK
+// ================================================================================================
+
+/// Evaluates the constraints of the specified AIR at `point` and checks them against the values
+/// implied by the specified trace polynomials; returns the evaluation of the constraint
+/// composition polynomial at `point`.
+///
+/// The check is performed as follows:
+/// 1. Transition constraints are evaluated over the constraint evaluation domain, and each
+///    evaluation is divided by the divisor of the constraint. The quotient of every constraint is
+///    interpolated and its degree is compared against the degree implied by the declared
+///    constraint degree.
+/// 2. Transition constraints are evaluated directly at `point`, and each evaluation is compared
+///    against the product of the divisor and the quotient evaluated at `point`.
+/// 3. The constraint composition polynomial is interpolated from its evaluations over the
+///    constraint evaluation domain and evaluated at `point`; the result is compared against the
+///    value reconstructed from the evaluation frame at `point` in the same way as the verifier
+///    does it.
+///
+/// Composition coefficients are drawn from a public coin seeded with `point`.
+///
+/// # Panics
+/// Panics if:
+/// * The number or the length of `trace_polys` is inconsistent with the main trace segment of
+///   the AIR.
+/// * A transition constraint is not satisfied by the trace, or its degree exceeds the declared
+///   degree.
+/// * A boundary constraint is not satisfied by the trace.
+pub fn evaluate_at_random_point<A, E>(air: &A, trace_polys: &[Vec<A::BaseField>], point: E) -> E
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+{
+    validate_trace_polys(air, trace_polys);
+
+    let coefficients = build_composition_coefficients(air, point);
+    let t_constraints = air.get_transition_constraints(&coefficients.transition);
+    let aux_rand_elements = AuxTraceRandElements::<E>::new();
+    let b_constraints = air.get_boundary_constraints(&aux_rand_elements, &coefficients.boundary);
+    let num_constraints = t_constraints.num_main_constraints();
+
+    // 1 ----- evaluate constraints over the constraint evaluation domain -------------------------
+
+    let ce_domain_size = air.ce_domain_size();
+    let ce_blowup = air.ce_blowup_factor();
+    let domain_offset = air.domain_offset();
+    let twiddles = fft::get_twiddles::<A::BaseField>(air.trace_length());
+    let trace_evaluations = trace_polys
+        .iter()
+        .map(|poly| fft::evaluate_poly_with_offset(poly, &twiddles, domain_offset, ce_blowup))
+        .collect::<Vec<_>>();
+
+    let ce_domain_generator = A::BaseField::get_root_of_unity(log2(ce_domain_size));
+    let ce_domain =
+        get_power_series_with_offset(ce_domain_generator, domain_offset, ce_domain_size);
+
+    let mut quotients = vec![Vec::with_capacity(ce_domain_size); num_constraints];
+    let mut composition = Vec::with_capacity(ce_domain_size);
+    let mut evaluations = A::BaseField::zeroed_vector(num_constraints);
+    let frame_offsets = air.context().frame_offsets();
+    for (i, &x) in ce_domain.iter().enumerate() {
+        let rows = frame_offsets
+            .iter()
+            .map(|&offset| {
+                let row_idx = (i + offset * ce_blowup) % ce_domain_size;
+                trace_evaluations
+                    .iter()
+                    .map(|column| column[row_idx])
+                    .collect()
+            })
+            .collect();
+        let frame = EvaluationFrame::from_offset_rows(frame_offsets, rows);
+        let periodic_values = evaluate_periodic_columns(air, x);
+        evaluations.fill(A::BaseField::ZERO);
+        air.evaluate_transition(&frame, &periodic_values, &mut evaluations);
+
+        for (i, (quotient, &evaluation)) in quotients.iter_mut().zip(&evaluations).enumerate() {
+            let z = t_constraints.get_constraint_divisor(i).evaluate_at(x);
+            quotient.push(evaluation / z);
+        }
+
+        let state = frame
+            .current()
+            .iter()
+            .map(|&v| E::from(v))
+            .collect::<Vec<_>>();
+        composition.push(compose(
+            &t_constraints,
+            &b_constraints,
+            &evaluations,
+            &state,
+            x,
+        ));
+    }
+
+    // make sure the quotient of every transition constraint is a polynomial of expected degree
+    let inv_twiddles = fft::get_inv_twiddles::<A::BaseField>(ce_domain_size);
+    for (i, quotient) in quotients.iter_mut().enumerate() {
+        fft::interpolate_poly_with_offset(quotient, &inv_twiddles, domain_offset);
+        let degree = &t_constraints.main_constraint_degrees()[i];
+        let divisor_degree = t_constraints.get_constraint_divisor(i).degree();
+        let expected_degree = degree
+            .get_evaluation_degree(air.trace_length())
+            .saturating_sub(divisor_degree);
+        let actual_degree = polynom::degree_of(quotient);
+        assert!(
+            actual_degree <= expected_degree,
+            "transition constraint {i} is not satisfied by the trace or its degree is greater \
+            than declared; expected quotient of degree at most {expected_degree}, but was {actual_degree}"
+        );
+    }
+
+    // 2 ----- evaluate constraints at the specified point ----------------------------------------
+
+    let g = air.trace_domain_generator();
+    let rows = frame_offsets
+        .iter()
+        .map(|&offset| {
+            let x = point * E::from(g.exp_vartime((offset as u32).into()));
+            trace_polys
+                .iter()
+                .map(|poly| polynom::eval(poly, x))
+                .collect()
+        })
+        .collect();
+    let frame = EvaluationFrame::from_offset_rows(frame_offsets, rows);
+    let periodic_values = evaluate_periodic_columns(air, point);
+    let mut ood_evaluations = E::zeroed_vector(num_constraints);
+    air.evaluate_transition(&frame, &periodic_values, &mut ood_evaluations);
+
+    for (i, (quotient, &evaluation)) in quotients.iter().zip(ood_evaluations.iter()).enumerate() {
+        let z = t_constraints.get_constraint_divisor(i).evaluate_at(point);
+        assert_eq!(
+            evaluation,
+            polynom::eval(quotient, point) * z,
+            "evaluation of transition constraint {i} at the point is not equal to the product \
+            of its divisor and quotient at the point"
+        );
+    }
+
+    // 3 ----- compare composition polynomial against the verifier's reconstruction -------------
+
+    fft::interpolate_poly_with_offset(&mut composition, &inv_twiddles, domain_offset);
+    let expected = polynom::eval(&composition, point);
+    let actual = compose::<E, E>(
+        &t_constraints,
+        &b_constraints,
+        &ood_evaluations,
+        frame.current(),
+        point,
+    );
+    assert_eq!(
+        expected, actual,
+        "evaluation of the constraint composition polynomial at the point is not equal to the \
+        value reconstructed from the out-of-domain evaluation frame"
+    );
+
+    actual
+}
+
+// BOUNDARY POLYNOMIAL CHECK
+// ================================================================================================
+
+/// Checks that every assertion of the specified AIR divides the polynomial of the asserted column
+/// without a remainder.
+///
+/// For an assertion against column $i$, the polynomial $P_i(x) - I(x)$, where $P_i(x)$ is the
+/// trace polynomial of column $i$ and $I(x)$ interpolates the asserted values over the asserted
+/// steps, is divided by the divisor of the assertion; the remainder of this division is zero if
+/// and only if the assertion is satisfied by the trace.
+///
+/// # Panics
+/// Panics if:
+/// * The number or the length of `trace_polys` is inconsistent with the main trace segment of
+///   the AIR.
+/// * Any of the assertions is not satisfied by the trace.
+pub fn boundary_poly_check<A: Air>(air: &A, trace_polys: &[Vec<A::BaseField>]) {
+    validate_trace_polys(air, trace_polys);
+
+    let trace_length = air.trace_length();
+    let g = air.trace_domain_generator();
+    for assertion in air.get_assertions() {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        assertion.apply(trace_length, |step, value| {
+            xs.push(g.exp((step as u64).into()));
+            ys.push(value);
+        });
+        let values_poly = polynom::interpolate(&xs, &ys, false);
+        let numerator = polynom::sub(&trace_polys[assertion.column()], &values_poly);
+
+        let divisor = ConstraintDivisor::from_assertion(&assertion, trace_length);
+        let remainder = get_remainder(&numerator, &build_divisor_poly(&divisor));
+        assert!(
+            remainder.iter().all(|&c| c == A::BaseField::ZERO),
+            "assertion {assertion} is not satisfied by the trace; dividing the polynomial of \
+            column {} by {divisor} leaves a non-zero remainder",
+            assertion.column()
+        );
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn validate_trace_polys<A: Air>(air: &A, trace_polys: &[Vec<A::BaseField>]) {
+    assert_eq!(
+        air.trace_layout().main_trace_width(),
+        trace_polys.len(),
+        "number of trace polynomials must be equal to the width of the main trace segment"
+    );
+    for poly in trace_polys {
+        assert_eq!(
+            air.trace_length(),
+            poly.len(),
+            "number of coefficients in trace polynomials must be equal to the trace length"
+        );
+    }
+}
+
+/// Draws constraint composition coefficients from a public coin seeded with the specified point;
+/// only the coefficients for the first out-of-domain point are returned.
+fn build_composition_coefficients<A, E>(air: &A, point: E) -> ConstraintCompositionCoefficients<E>
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+{
+    let mut coin =
+        DefaultRandomCoin::<A::BaseField, Blake3_256<A::BaseField>>::new(&point.to_bytes());
+    air.get_constraint_composition_coefficients(&mut coin)
+        .expect("failed to draw composition coefficients")
+        .swap_remove(0)
+}
+
+/// Evaluates periodic columns of the specified AIR at `x` in the same way as the verifier does.
+fn evaluate_periodic_columns<A, E>(air: &A, x: E) -> Vec<E>
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+{
+    air.get_periodic_column_polys()
+        .iter()
+        .map(|poly| {
+            let num_cycles = air.trace_length() / poly.len();
+            polynom::eval(poly, x.exp_vartime((num_cycles as u32).into()))
+        })
+        .collect()
+}
+
+/// Computes a random linear combination of transition and boundary constraints at `x` in the
+/// same way as the verifier does it.
+fn compose<F, E>(
+    t_constraints: &TransitionConstraints<E>,
+    b_constraints: &BoundaryConstraints<E>,
+    t_evaluations: &[F],
+    state: &[E],
+    x: F,
+) -> E
+where
+    F: FieldElement,
+    E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
+{
+    let result = t_constraints.combine_evaluations::<F>(t_evaluations, &[], x);
+    let x = E::from(x);
+    b_constraints
+        .main_constraints()
+        .iter()
+        .fold(result, |acc, group| {
+            let xp = x.exp_vartime(group.degree_adjustment().into());
+            acc + group.evaluate_at(state, x, xp)
+        })
+}
+
+/// Returns the divisor polynomial in coefficient form.
+fn build_divisor_poly<B: StarkField>(divisor: &ConstraintDivisor<B>) -> Vec<B> {
+    let numerator = divisor
+        .numerator()
+        .iter()
+        .fold(vec![B::ONE], |acc, &(degree, constant)| {
+            let mut term = B::zeroed_vector(degree + 1);
+            term[0] = -constant;
+            term[degree] = B::ONE;
+            polynom::mul(&acc, &term)
+        });
+    divisor
+        .exemptions()
+        .iter()
+        .fold(numerator, |acc, &e| polynom::div(&acc, &[-e, B::ONE]))
+}
+
+/// Returns the remainder of dividing polynomial `a` by polynomial `b`.
+fn get_remainder<B: StarkField>(a: &[B], b: &[B]) -> Vec<B> {
+    if polynom::degree_of(a) < polynom::degree_of(b) {
+        return a.to_vec();
+    }
+    let quotient = polynom::div(a, b);
+    polynom::sub(a, &polynom::mul(&quotient, b))
+}
@@ -0,0 +1,362 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::{proof::Context, LdePos, ProofOptions};
+use core::cmp;
+use crypto::{DynHasher, ElementHasher, HashFunctionId, Hasher, RandomCoin, RandomCoinError};
+use math::StarkField;
+use utils::{collections::Vec, DeserializationError, Serializable};
+
+#[cfg(all(test, feature = "f64", feature = "f128"))]
+mod tests;
+
+// TRANSCRIPT LABELS
+// ================================================================================================
+
+/// Labels of the protocol steps during which the prover and the verifier interact with the public
+/// coin.
+///
+/// Both parties label every interaction with the public coin in the same way via
+/// [RandomCoin::label()], and thus, when `transcript-log` feature of the `winter-crypto` crate is
+/// enabled, transcript logs recorded by the prover and by the verifier can be compared entry by
+/// entry. Entries recorded when the public coin is constructed are labeled with `seed`.
+pub mod labels {
+    /// Absorbing a commitment to the preprocessed trace segment.
+    pub const PREPROCESSED_COMMITMENT: &str = "preprocessed_commitment";
+    /// Absorbing a commitment to a trace segment.
+    pub const TRACE_COMMITMENT: &str = "trace_commitment";
+    /// Drawing random elements for building an auxiliary trace segment.
+    pub const AUX_RAND_ELEMENTS: &str = "aux_rand_elements";
+    /// Drawing the coefficient of the combined trace column.
+    pub const COMBINED_TRACE_COEFFICIENT: &str = "combined_trace_coefficient";
+    /// Absorbing a commitment to the combined trace column.
+    pub const COMBINED_TRACE_COMMITMENT: &str = "combined_trace_commitment";
+    /// Drawing coefficients of the constraint composition polynomial.
+    pub const CONSTRAINT_COEFFICIENTS: &str = "constraint_coefficients";
+    /// Absorbing a commitment to the constraint composition polynomial.
+    pub const CONSTRAINT_COMMITMENT: &str = "constraint_commitment";
+    /// Drawing an out-of-domain point.
+    pub const OOD_POINT: &str = "ood_point";
+    /// Absorbing out-of-domain trace frame states.
+    pub const OOD_TRACE_FRAME: &str = "ood_trace_frame";
+    /// Absorbing an out-of-domain evaluation of the combined trace column.
+    pub const OOD_COMBINED_TRACE_EVALUATION: &str = "ood_combined_trace_evaluation";
+    /// Absorbing out-of-domain evaluations of constraint composition polynomial columns.
+    pub const OOD_CONSTRAINT_EVALUATIONS: &str = "ood_constraint_evaluations";
+    /// Drawing coefficients of the DEEP composition polynomial.
+    pub const DEEP_COEFFICIENTS: &str = "deep_coefficients";
+    /// Absorbing a FRI layer commitment and drawing the folding challenge for it.
+    pub const FRI_LAYER: &str = "fri_layer";
+    /// Absorbing a digest of the FRI remainder polynomial coefficients.
+    pub const FRI_REMAINDER_DIGEST: &str = "fri_remainder_digest";
+    /// Absorbing the proof-of-work nonce.
+    pub const POW_NONCE: &str = "pow_nonce";
+    /// Drawing query positions.
+    pub const QUERY_POSITIONS: &str = "query_positions";
+}
+
+// PUBLIC INPUTS SEED
+// ================================================================================================
+
+/// Returns the digest which seeds the public coin in place of the specified serialized public
+/// inputs when the transcript is seeded with pre-hashed public inputs.
+///
+/// This is the seed expected by verifiers which receive only a digest of public inputs rather
+/// than the inputs themselves (see [SplitInputsAir](crate::SplitInputsAir)); such a verifier
+/// absorbs the digest into the public coin exactly as serialized public inputs would otherwise
+/// be absorbed.
+pub fn pub_inputs_seed<H: Hasher>(pub_inputs_bytes: &[u8]) -> H::Digest {
+    H::hash(pub_inputs_bytes)
+}
+
+// TRANSCRIPT PROFILE
+// ================================================================================================
+/// Defines the rules by which the prover and the verifier seed the public coin and derive
+/// Fiat-Shamir randomness from the proof-of-work nonce.
+///
+/// All profiles absorb commitments, out-of-domain evaluations and FRI layer commitments into the
+/// public coin in the same order; they differ only in how the public coin is seeded, how the
+/// query seed is ground, and how query positions are derived from it:
+///
+/// * [Winterfell](TranscriptProfile::Winterfell) - the default profile. Proof-of-work is measured
+///   as the number of trailing zero bits of the first 8 bytes of hash(`seed` || `nonce`) read
+///   in little-endian byte order; the nonce is absorbed by setting the seed to
+///   hash(`seed` || `nonce`); query positions are read from the coin in little-endian byte
+///   order and are kept in the order in which they were drawn.
+/// * [EthStarkCompat](TranscriptProfile::EthStarkCompat) - a profile matching the conventions of
+///   ethSTARK-style verifiers. Proof-of-work is measured as the number of leading zero bits of
+///   the first 8 bytes of hash(`seed` || `nonce`) read in big-endian byte order; the nonce is
+///   absorbed by reseeding the coin with the hash of the nonce's big-endian bytes; query
+///   positions are read from the coin in big-endian byte order and are sorted in ascending
+///   order.
+/// * [Arithmetic](TranscriptProfile::Arithmetic) - a profile which keeps the transcript entirely
+///   in field elements, and thus, is suitable for recursive verifiers operating over the same
+///   field. The public coin is seeded with the hash of the proof context and the public inputs
+///   encoded as field elements (see [Context::to_elements()]); integer values (e.g., the trace
+///   length and the nonce) are absorbed via their canonical field encodings, and proof-of-work
+///   is measured as the number of trailing zero bits of the canonical value of the first element
+///   of hash(`seed` || `nonce`). Query positions are derived in the same way as in the default
+///   profile. This profile can be used only with algebraic hash functions, i.e., hash functions
+///   for which [ElementHasher::digest_as_elements()] returns digest elements.
+///
+/// For all profiles, the coin is reseeded with digests via
+/// [Hasher::merge()](crypto::Hasher::merge); for algebraic hash functions, this absorbs digests
+/// in their native element representation.
+///
+/// The profile is a part of [ProofOptions](crate::ProofOptions), and thus, is recorded in the
+/// proof context.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TranscriptProfile {
+    /// Default Winterfell transcript.
+    Winterfell = 0,
+    /// Transcript compatible with ethSTARK-style verifiers.
+    EthStarkCompat = 1,
+    /// Transcript operating entirely over field elements; requires an algebraic hash function.
+    Arithmetic = 2,
+}
+
+impl TranscriptProfile {
+    // PUBLIC COIN
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns true if this profile can be used with the hash function `H`.
+    ///
+    /// The [Arithmetic](TranscriptProfile::Arithmetic) profile is supported only for hash
+    /// functions with digests composed of base field elements; other profiles are supported for
+    /// all hash functions.
+    pub fn is_supported<H: ElementHasher>(&self) -> bool {
+        match self {
+            Self::Winterfell | Self::EthStarkCompat => true,
+            Self::Arithmetic => H::digest_as_elements(&H::Digest::default()).is_some(),
+        }
+    }
+
+    /// Returns a public coin seeded with the specified proof `context` and serialized public
+    /// inputs.
+    ///
+    /// For byte-oriented profiles, the seed is the hash of public input bytes followed by the
+    /// serialized context; for the [Arithmetic](TranscriptProfile::Arithmetic) profile, the seed
+    /// is the hash of the context elements followed by public input bytes packed into elements.
+    ///
+    /// # Panics
+    /// Panics if this profile is not supported for the hash function of the coin.
+    pub fn build_public_coin<C: RandomCoin>(
+        &self,
+        context: &Context,
+        pub_inputs_bytes: &[u8],
+    ) -> C {
+        assert!(
+            self.is_supported::<C::Hasher>(),
+            "transcript profile {self:?} is not supported for the specified hash function"
+        );
+        match self {
+            Self::Winterfell | Self::EthStarkCompat => {
+                let mut seed = pub_inputs_bytes.to_vec();
+                context.write_into(&mut seed);
+                C::new(&seed)
+            }
+            Self::Arithmetic => {
+                let mut seed = context.to_elements::<C::BaseField>();
+                seed.append(&mut bytes_to_elements(pub_inputs_bytes));
+                C::from_elements(&seed)
+            }
+        }
+    }
+
+    // PROOF-OF-WORK
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of proof-of-work bits provided by the specified `nonce` for the current
+    /// state of the `public_coin`.
+    pub fn get_pow_bits<C: RandomCoin>(&self, public_coin: &C, nonce: u64) -> u32 {
+        match self {
+            Self::Winterfell => public_coin.check_leading_zeros(nonce),
+            Self::EthStarkCompat => public_coin.check_leading_zeros_be(nonce),
+            Self::Arithmetic => public_coin.check_trailing_zeros_in_element(nonce),
+        }
+    }
+
+    /// Absorbs the proof-of-work `nonce` into the `public_coin`.
+    pub fn absorb_pow_nonce<C: RandomCoin>(&self, public_coin: &mut C, nonce: u64) {
+        public_coin.label(labels::POW_NONCE);
+        match self {
+            Self::Winterfell | Self::Arithmetic => public_coin.reseed_with_int(nonce),
+            Self::EthStarkCompat => public_coin.reseed(C::Hasher::hash(&nonce.to_be_bytes())),
+        }
+    }
+
+    /// Absorbs the 128-bit proof-of-work `nonce` into the `public_coin`.
+    ///
+    /// The nonce is absorbed as two 64-bit integers (the lower half first); for the
+    /// [EthStarkCompat](TranscriptProfile::EthStarkCompat) profile, the coin is reseeded with
+    /// the hash of the nonce's 16 big-endian bytes.
+    pub fn absorb_wide_pow_nonce<C: RandomCoin>(&self, public_coin: &mut C, nonce: u128) {
+        public_coin.label(labels::POW_NONCE);
+        match self {
+            Self::Winterfell | Self::Arithmetic => {
+                public_coin.reseed_with_int(nonce as u64);
+                public_coin.reseed_with_int((nonce >> 64) as u64);
+            }
+            Self::EthStarkCompat => public_coin.reseed(C::Hasher::hash(&nonce.to_be_bytes())),
+        }
+    }
+
+    // QUERY POSITIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Draws `num_queries` unique positions in an LDE domain of the specified size from the
+    /// `public_coin`.
+    ///
+    /// # Errors
+    /// Returns an error if the specified number of unique positions could not be drawn from the
+    /// public coin.
+    pub fn draw_query_positions<C: RandomCoin>(
+        &self,
+        public_coin: &mut C,
+        num_queries: usize,
+        lde_domain_size: usize,
+    ) -> Result<Vec<LdePos>, RandomCoinError> {
+        public_coin.label(labels::QUERY_POSITIONS);
+        let positions = match self {
+            Self::Winterfell | Self::Arithmetic => {
+                public_coin.draw_integers(num_queries, lde_domain_size)?
+            }
+            Self::EthStarkCompat => {
+                let mut positions = public_coin.draw_integers_be(num_queries, lde_domain_size)?;
+                positions.sort_unstable();
+                positions
+            }
+        };
+        Ok(positions.into_iter().map(LdePos).collect())
+    }
+
+    // SERIALIZATION HELPERS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a transcript profile encoded by the specified value.
+    pub(crate) fn from_u8(value: u8) -> Result<Self, DeserializationError> {
+        match value {
+            0 => Ok(Self::Winterfell),
+            1 => Ok(Self::EthStarkCompat),
+            2 => Ok(Self::Arithmetic),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "value {value} cannot be deserialized as TranscriptProfile enum"
+            ))),
+        }
+    }
+}
+
+impl Default for TranscriptProfile {
+    fn default() -> Self {
+        Self::Winterfell
+    }
+}
+
+// PROOF-OF-WORK PUZZLE
+// ================================================================================================
+
+/// Number of bytes in the challenge of a proof-of-work puzzle which uses a grinding hash function.
+const POW_CHALLENGE_BYTES: usize = 32;
+
+/// The query seed proof-of-work puzzle for the current state of a public coin.
+///
+/// By default, a nonce is hashed with the seed of the public coin using the hash function of the
+/// coin, and proof-of-work bits are counted as defined by the [TranscriptProfile].
+///
+/// When proof options specify a grinding hash function (see
+/// [ProofOptions::with_grinding_hash()]), a 32-byte challenge is drawn from a coin derived from
+/// the public coin (the public coin itself is not modified), and a nonce is hashed as
+/// grinding_hash(`challenge` || `nonce`), where the nonce is encoded into 8 or 16 little-endian
+/// bytes depending on [ProofOptions::grinding_nonce_bytes()]. Proof-of-work is then measured as
+/// the number of leading zero bits of the first 8 bytes of the digest read in big-endian byte
+/// order for the [EthStarkCompat](TranscriptProfile::EthStarkCompat) profile, and as the number
+/// of trailing zero bits of the first 8 bytes read in little-endian byte order otherwise.
+///
+/// The prover and the verifier build the puzzle in the same state of the public coin: right
+/// before the nonce is absorbed into it.
+pub struct PowPuzzle<'a, C: RandomCoin> {
+    profile: TranscriptProfile,
+    public_coin: &'a C,
+    grinding: Option<(DynHasher<C::BaseField>, Vec<u8>)>,
+    nonce_bytes: usize,
+}
+
+impl<'a, C: RandomCoin> PowPuzzle<'a, C> {
+    /// Returns the proof-of-work puzzle defined by the proof `options` for the current state of
+    /// the `public_coin`.
+    ///
+    /// # Errors
+    /// Returns the identifier of the grinding hash function if this function cannot be used with
+    /// the base field of the public coin.
+    pub fn new(options: &ProofOptions, public_coin: &'a C) -> Result<Self, HashFunctionId> {
+        let grinding = match options.grinding_hash() {
+            Some(hash_fn) => {
+                let hasher = DynHasher::new(hash_fn).ok_or(hash_fn)?;
+                let mut challenge_coin = public_coin.derive_keyed(labels::POW_NONCE.as_bytes());
+                let mut challenge = Vec::with_capacity(POW_CHALLENGE_BYTES);
+                while challenge.len() < POW_CHALLENGE_BYTES {
+                    let element: C::BaseField = challenge_coin
+                        .draw()
+                        .expect("failed to draw proof-of-work challenge");
+                    element.write_into(&mut challenge);
+                }
+                challenge.truncate(POW_CHALLENGE_BYTES);
+                Some((hasher, challenge))
+            }
+            None => None,
+        };
+
+        Ok(PowPuzzle {
+            profile: options.transcript_profile(),
+            public_coin,
+            grinding,
+            nonce_bytes: options.grinding_nonce_bytes(),
+        })
+    }
+
+    /// Returns the number of proof-of-work bits provided by the specified `nonce`.
+    ///
+    /// Without a grinding hash function, only the lower 64 bits of the nonce are used.
+    pub fn get_pow_bits(&self, nonce: u128) -> u32 {
+        let (hasher, challenge) = match &self.grinding {
+            Some(grinding) => grinding,
+            None => return self.profile.get_pow_bits(self.public_coin, nonce as u64),
+        };
+
+        let mut data = [0u8; POW_CHALLENGE_BYTES + 16];
+        let data_len = challenge.len() + self.nonce_bytes;
+        data[..challenge.len()].copy_from_slice(challenge);
+        data[challenge.len()..data_len].copy_from_slice(&nonce.to_le_bytes()[..self.nonce_bytes]);
+
+        let digest = hasher.hash(&data[..data_len]);
+        let mut head = [0u8; 8];
+        head.copy_from_slice(&digest.as_bytes()[..8]);
+        match self.profile {
+            TranscriptProfile::EthStarkCompat => u64::from_be_bytes(head).leading_zeros(),
+            _ => u64::from_le_bytes(head).trailing_zeros(),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Encodes the specified bytes as a sequence of base field elements.
+///
+/// The first element of the result is the number of bytes; the bytes are then split into chunks
+/// small enough for every chunk to map to a unique field element, and each chunk is interpreted
+/// as an integer in little-endian byte order.
+pub(crate) fn bytes_to_elements<B: StarkField>(bytes: &[u8]) -> Vec<B> {
+    let chunk_size = cmp::min((B::MODULUS_BITS as usize - 1) / 8, 16);
+    let mut result = Vec::with_capacity(bytes.len() / chunk_size + 2);
+    result.push(B::from(bytes.len() as u64));
+    for chunk in bytes.chunks(chunk_size) {
+        let mut buf = [0u8; 16];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        result.push(B::from(u128::from_le_bytes(buf)));
+    }
+    result
+}
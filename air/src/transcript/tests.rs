@@ -0,0 +1,296 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{bytes_to_elements, labels, PowPuzzle, TranscriptProfile};
+use crate::{proof::Context, FieldExtension, LdePos, ProofOptions, TraceInfo};
+use crypto::{
+    hashers::{Blake3_256, Rp64_256},
+    DefaultRandomCoin, Digest, ElementHasher, HashFunctionId, Hasher, RandomCoin,
+};
+use math::{
+    fields::{f128::BaseElement, f64},
+    FieldElement, StarkField,
+};
+use utils::{collections::Vec, Deserializable, DeserializationError, Serializable, SliceReader};
+
+type Coin = DefaultRandomCoin<BaseElement, Blake3_256<BaseElement>>;
+type ArithmeticCoin = DefaultRandomCoin<f64::BaseElement, Rp64_256>;
+
+// TEST VECTORS
+// ================================================================================================
+
+#[test]
+fn winterfell_profile_vectors() {
+    let (nonce, positions) = run_transcript(TranscriptProfile::Winterfell);
+    assert_eq!(2, nonce);
+    assert_eq!(
+        vec![361, 593, 954, 648, 475, 1003, 749, 128],
+        LdePos::to_indexes(&positions)
+    );
+}
+
+#[test]
+fn eth_stark_compat_profile_vectors() {
+    let (nonce, positions) = run_transcript(TranscriptProfile::EthStarkCompat);
+    assert_eq!(15, nonce);
+    assert_eq!(
+        vec![244, 296, 455, 750, 777, 927, 983, 1019],
+        LdePos::to_indexes(&positions)
+    );
+}
+
+#[test]
+fn arithmetic_profile_vectors() {
+    let seed = [1, 2, 3, 4].map(f64::BaseElement::new);
+    let mut coin = ArithmeticCoin::from_elements(&seed);
+    let (nonce, positions) = run_transcript_with(TranscriptProfile::Arithmetic, &mut coin);
+    assert_eq!(6, nonce);
+    assert_eq!(
+        vec![752, 838, 395, 474, 509, 256, 443, 313],
+        LdePos::to_indexes(&positions)
+    );
+
+    // the coin state after absorbing the nonce is defined entirely by field elements
+    let element = coin.draw::<f64::BaseElement>().unwrap();
+    assert_eq!(f64::BaseElement::new(11019273610166384897), element);
+}
+
+#[test]
+fn fri_remainder_digest_vectors() {
+    // the digest is a hash of the coefficients of the remainder polynomial (in the order of
+    // increasing degree) rather than of its evaluations
+    let coefficients = (1..=8).map(BaseElement::new).collect::<Vec<_>>();
+    let mut remainder = coefficients.clone();
+    let twiddles = math::fft::get_twiddles::<BaseElement>(remainder.len());
+    math::fft::evaluate_poly(&mut remainder, &twiddles);
+    let digest = fri::utils::hash_remainder_coefficients::<Blake3_256<BaseElement>, _>(&remainder);
+    assert_eq!(
+        Blake3_256::<BaseElement>::hash_elements(&coefficients),
+        digest
+    );
+
+    // absorbing the digest before grinding changes both the nonce and the query positions
+    let mut coin = Coin::new(&[1, 2, 3, 4]);
+    coin.label(labels::FRI_REMAINDER_DIGEST).reseed(digest);
+    let (nonce, positions) = run_transcript_with(TranscriptProfile::Winterfell, &mut coin);
+    assert_eq!(90, nonce);
+    assert_eq!(
+        vec![800, 738, 667, 939, 59, 17, 1019, 858],
+        LdePos::to_indexes(&positions)
+    );
+}
+
+#[test]
+fn arithmetic_profile_pow_bits() {
+    // proof-of-work bits are counted over the canonical value of the first digest element
+    let coin = ArithmeticCoin::from_elements(&[f64::BaseElement::ONE]);
+    for nonce in 1..64 {
+        let bits = TranscriptProfile::Arithmetic.get_pow_bits(&coin, nonce);
+        let digest =
+            Rp64_256::merge_with_int(Rp64_256::hash_elements(&[f64::BaseElement::ONE]), nonce);
+        let element = Rp64_256::digest_as_elements(&digest).unwrap()[0];
+        assert_eq!(element.as_int().trailing_zeros(), bits);
+    }
+}
+
+#[test]
+fn arithmetic_profile_public_coin() {
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+        .with_transcript_profile(TranscriptProfile::Arithmetic);
+    let context = Context::new::<f64::BaseElement>(&TraceInfo::new(2, 16), options);
+    let pub_inputs = [1u8, 2, 3, 4, 5, 6, 7, 8];
+
+    // the context is encoded as: main width, number of aux segments, trace length, trace meta
+    // (length-prefixed), field modulus bytes (length-prefixed), and option bytes
+    let expected = [
+        2,
+        0,
+        16,
+        0,
+        8,
+        0x00ff_ffff_0000_0001,
+        0xff,
+        32,
+        8,
+        0,
+        0x21,
+        4,
+        8,
+    ];
+    let elements = context.to_elements::<f64::BaseElement>();
+    assert_eq!(expected.map(f64::BaseElement::new).to_vec(), elements);
+
+    // the coin is seeded with context elements followed by public input elements
+    let mut seed = elements;
+    seed.extend_from_slice(&bytes_to_elements(&pub_inputs));
+    let mut expected = ArithmeticCoin::from_elements(&seed);
+    let mut coin =
+        TranscriptProfile::Arithmetic.build_public_coin::<ArithmeticCoin>(&context, &pub_inputs);
+    assert_eq!(
+        expected.draw::<f64::BaseElement>().unwrap(),
+        coin.draw::<f64::BaseElement>().unwrap()
+    );
+}
+
+#[test]
+fn arithmetic_profile_support() {
+    assert!(TranscriptProfile::Arithmetic.is_supported::<Rp64_256>());
+    assert!(!TranscriptProfile::Arithmetic.is_supported::<Blake3_256<BaseElement>>());
+    assert!(TranscriptProfile::Winterfell.is_supported::<Blake3_256<BaseElement>>());
+}
+
+#[test]
+#[should_panic(expected = "transcript profile Arithmetic is not supported")]
+fn arithmetic_profile_byte_hasher() {
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+        .with_transcript_profile(TranscriptProfile::Arithmetic);
+    let context = Context::new::<BaseElement>(&TraceInfo::new(2, 16), options);
+    TranscriptProfile::Arithmetic.build_public_coin::<Coin>(&context, &[]);
+}
+
+#[test]
+fn bytes_to_elements_encoding() {
+    // f64 elements are packed with 7 bytes each, and the result is prefixed with the length
+    let bytes = (1..=9).collect::<Vec<u8>>();
+    let elements = bytes_to_elements::<f64::BaseElement>(&bytes);
+    let expected = [9, 0x0007_0605_0403_0201, 0x0908].map(f64::BaseElement::new);
+    assert_eq!(expected.to_vec(), elements);
+
+    // trailing zero bytes result in a different encoding
+    let elements2 = bytes_to_elements::<f64::BaseElement>(&[bytes, vec![0]].concat());
+    assert_ne!(elements, elements2);
+}
+
+#[test]
+fn profiles_count_pow_bits_differently() {
+    let coin = Coin::new(&[1, 2, 3, 4]);
+    let winterfell = (1..64)
+        .map(|nonce| TranscriptProfile::Winterfell.get_pow_bits(&coin, nonce))
+        .collect::<Vec<_>>();
+    let eth_stark = (1..64)
+        .map(|nonce| TranscriptProfile::EthStarkCompat.get_pow_bits(&coin, nonce))
+        .collect::<Vec<_>>();
+    assert_ne!(winterfell, eth_stark);
+}
+
+#[test]
+fn pow_puzzle_with_grinding_hash() {
+    let coin = ArithmeticCoin::new(&[1, 2, 3, 4]);
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+
+    // without a grinding hash function, bits are counted as defined by the transcript profile
+    let puzzle = PowPuzzle::new(&options, &coin).unwrap();
+    for nonce in 1..64 {
+        let expected = TranscriptProfile::Winterfell.get_pow_bits(&coin, nonce);
+        assert_eq!(expected, puzzle.get_pow_bits(nonce as u128));
+    }
+
+    // with a grinding hash function, bits are counted over hash(challenge || nonce), where the
+    // challenge is drawn from a coin derived from the public coin
+    let options = options.with_grinding_hash(HashFunctionId::Blake3_256);
+    let puzzle = PowPuzzle::new(&options, &coin).unwrap();
+    let mut challenge_coin = coin.derive_keyed(labels::POW_NONCE.as_bytes());
+    let challenge = (0..4)
+        .flat_map(|_| {
+            challenge_coin
+                .draw::<f64::BaseElement>()
+                .unwrap()
+                .to_bytes()
+        })
+        .collect::<Vec<u8>>();
+    for nonce in 1..64u64 {
+        let data = [&challenge[..], &nonce.to_le_bytes()].concat();
+        let digest = Blake3_256::<f64::BaseElement>::hash(&data).as_bytes();
+        let head = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        assert_eq!(head.trailing_zeros(), puzzle.get_pow_bits(nonce as u128));
+    }
+
+    // wide nonces are hashed with all 16 of their bytes
+    let wide_options = options.clone().with_wide_grinding_nonce();
+    let wide_puzzle = PowPuzzle::new(&wide_options, &coin).unwrap();
+    let narrow_bits = (1..64).map(|nonce| puzzle.get_pow_bits(nonce));
+    let wide_bits = (1..64).map(|nonce| wide_puzzle.get_pow_bits(nonce));
+    assert!(narrow_bits.ne(wide_bits));
+
+    // algebraic hash functions over a different field cannot be used for grinding
+    let options = options.with_grinding_hash(HashFunctionId::Poseidon128_256);
+    let result = PowPuzzle::new(&options, &coin).map(|_| ());
+    assert_eq!(Err(HashFunctionId::Poseidon128_256), result);
+}
+
+#[test]
+fn wide_pow_nonce_absorption() {
+    for profile in [
+        TranscriptProfile::Winterfell,
+        TranscriptProfile::EthStarkCompat,
+    ] {
+        // a wide nonce is absorbed differently from a narrow nonce with the same value, and both
+        // halves of a wide nonce affect the coin
+        let mut narrow_coin = Coin::new(&[1, 2, 3, 4]);
+        profile.absorb_pow_nonce(&mut narrow_coin, 5);
+        let mut wide_coin = Coin::new(&[1, 2, 3, 4]);
+        profile.absorb_wide_pow_nonce(&mut wide_coin, 5);
+        let mut high_coin = Coin::new(&[1, 2, 3, 4]);
+        profile.absorb_wide_pow_nonce(&mut high_coin, 5 | 1 << 64);
+
+        let narrow: BaseElement = narrow_coin.draw().unwrap();
+        let wide: BaseElement = wide_coin.draw().unwrap();
+        let high: BaseElement = high_coin.draw().unwrap();
+        assert_ne!(narrow, wide);
+        assert_ne!(wide, high);
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+#[test]
+fn default_profile_serialization_is_unchanged() {
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256);
+    assert_eq!(TranscriptProfile::Winterfell, options.transcript_profile());
+    assert_eq!(vec![32, 8, 0, 2, 4, 8], options.to_bytes());
+}
+
+#[test]
+fn profile_serialization() {
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256)
+        .with_transcript_profile(TranscriptProfile::EthStarkCompat);
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x12, 4, 8], bytes);
+
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let options = options.with_transcript_profile(TranscriptProfile::Arithmetic);
+    let bytes = options.to_bytes();
+    assert_eq!(vec![32, 8, 0, 0x22, 4, 8], bytes);
+    let result = ProofOptions::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(options, result);
+
+    let result = ProofOptions::read_from(&mut SliceReader::new(&[32, 8, 0, 0x32, 4, 8]));
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Grinds a query seed with 5 bits of proof-of-work for a fixed coin and draws 8 query positions
+/// from an LDE domain of 1024 elements.
+fn run_transcript(profile: TranscriptProfile) -> (u64, Vec<LdePos>) {
+    run_transcript_with(profile, &mut Coin::new(&[1, 2, 3, 4]))
+}
+
+/// Same as [run_transcript()], but uses the provided coin.
+fn run_transcript_with<C: RandomCoin>(
+    profile: TranscriptProfile,
+    coin: &mut C,
+) -> (u64, Vec<LdePos>) {
+    let nonce = (1..u64::MAX)
+        .find(|&nonce| profile.get_pow_bits(coin, nonce) >= 5)
+        .unwrap();
+    profile.absorb_pow_nonce(coin, nonce);
+    let positions = profile.draw_query_positions(coin, 8, 1024).unwrap();
+    (nonce, positions)
+}
@@ -3,7 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use math::fields::f128;
 use rand_utils::rand_value;
 use winter_crypto::{
@@ -42,6 +42,36 @@ fn blake3(c: &mut Criterion) {
     });
 }
 
+/// Hashes a layer of 2^22 leaves of a Merkle tree; with `blake3-accel` feature enabled, batched
+/// merging hashes several nodes in parallel using SIMD instructions, while merging nodes one by
+/// one is the same regardless of the feature.
+fn blake3_layer(c: &mut Criterion) {
+    const NUM_LEAVES: usize = 1 << 22;
+
+    let mut group = c.benchmark_group("hash_blake3 (layer of 2^22 leaves)");
+    group.sample_size(10);
+
+    let leaves = (0..NUM_LEAVES)
+        .map(|_| Blake3::hash(&rand_value::<u64>().to_le_bytes()))
+        .collect::<Vec<_>>();
+    let mut nodes = vec![Blake3Digest::default(); NUM_LEAVES / 2];
+    group.bench_function("merge", |bench| {
+        bench.iter(|| {
+            for (node, children) in nodes.iter_mut().zip(leaves.chunks(2)) {
+                *node = Blake3::merge(&[children[0], children[1]]);
+            }
+        })
+    });
+
+    for arity in [2, 4, 8] {
+        let mut nodes = vec![Blake3Digest::default(); NUM_LEAVES / arity];
+        group.bench_function(BenchmarkId::new("merge_batch", arity), |bench| {
+            bench.iter(|| Blake3::merge_batch(&leaves, arity, &mut nodes))
+        });
+    }
+    group.finish();
+}
+
 fn sha3(c: &mut Criterion) {
     let v: [Sha3Digest; 2] = [Sha3::hash(&[1u8]), Sha3::hash(&[2u8])];
     c.bench_function("hash_sha3 (cached)", |bench| {
@@ -148,6 +178,7 @@ fn griffin_jive256(c: &mut Criterion) {
 criterion_group!(
     hash_group,
     blake3,
+    blake3_layer,
     sha3,
     rescue248,
     rescue256,
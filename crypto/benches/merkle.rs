@@ -7,7 +7,7 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use math::fields::f128::BaseElement;
 use rand_utils::rand_value;
 use utils::uninit_vector;
-use winter_crypto::{build_merkle_nodes, concurrent, hashers::Blake3_256, Hasher};
+use winter_crypto::{build_merkle_nodes, concurrent, hashers::Blake3_256, Hasher, MerkleTree};
 
 type Blake3 = Blake3_256<BaseElement>;
 type Blake3Digest = <Blake3 as Hasher>::Digest;
@@ -34,5 +34,37 @@ pub fn merkle_tree_construction(c: &mut Criterion) {
     }
 }
 
-criterion_group!(merkle_group, merkle_tree_construction,);
+pub fn merkle_batch_proof(c: &mut Criterion) {
+    let mut proof_group = c.benchmark_group("merkle batch proof verification");
+
+    const NUM_LEAVES: usize = 65536;
+    const NUM_QUERIES: usize = 32;
+
+    let leaves: Vec<Blake3Digest> = (0..NUM_LEAVES)
+        .map(|_| Blake3::hash(&rand_value::<u128>().to_le_bytes()))
+        .collect();
+    let mut indexes = Vec::with_capacity(NUM_QUERIES);
+    while indexes.len() < NUM_QUERIES {
+        let index = rand_value::<u64>() as usize % NUM_LEAVES;
+        if !indexes.contains(&index) {
+            indexes.push(index);
+        }
+    }
+
+    // trees with greater arity have fewer levels, but batch proofs for them contain more nodes;
+    // print the size of the proof for each arity so that the trade-off can be compared
+    for arity in [2, 4, 8] {
+        let tree = MerkleTree::<Blake3>::with_arity(leaves.clone(), arity).unwrap();
+        let proof = tree.prove_batch(&indexes).unwrap();
+        println!(
+            "arity {arity}: batch proof for {NUM_QUERIES} of {NUM_LEAVES} leaves is {} bytes",
+            proof.serialize_nodes().len()
+        );
+        proof_group.bench_with_input(BenchmarkId::new("arity", arity), &proof, |b, proof| {
+            b.iter(|| MerkleTree::verify_batch(tree.root(), &indexes, proof).unwrap())
+        });
+    }
+}
+
+criterion_group!(merkle_group, merkle_tree_construction, merkle_batch_proof);
 criterion_main!(merkle_group);
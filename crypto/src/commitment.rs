@@ -0,0 +1,144 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::Hasher;
+use core::fmt::Debug;
+use utils::{
+    collections::Vec, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
+
+// VECTOR COMMITMENT
+// ================================================================================================
+
+/// A scheme for committing to a vector of digests and opening the commitment at a set of
+/// positions.
+///
+/// Provers commit to execution traces and constraint evaluations via this trait: every row of
+/// a committed matrix is hashed into a digest (an *item*), and the vector of row digests is then
+/// committed to. [MerkleTree](crate::MerkleTree) is the default implementation of the trait, but
+/// other schemes can be plugged into the prover and the verifier as well.
+///
+/// Every scheme is identified by [SCHEME_ID](VectorCommitment::SCHEME_ID); the identifier is
+/// recorded in the context of proofs generated with schemes other than Merkle trees, and thus,
+/// a proof cannot be verified with a scheme other than the one with which it was generated.
+pub trait VectorCommitment<H: Hasher>:
+    Sized + Clone + Send + Sync + Serializable + Deserializable
+{
+    /// Identifier of this scheme; identifier 0 is reserved for [MerkleTree](crate::MerkleTree).
+    const SCHEME_ID: u8;
+
+    /// Proof that a set of items is committed to at the specified positions; the proof contains
+    /// the opened items.
+    type MultiProof: Send + Sync;
+
+    /// Error returned when a commitment cannot be built, opened, or verified.
+    type Error: Debug;
+
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a commitment to the specified `items`.
+    ///
+    /// `arity` is the arity of the commitment, if the notion of arity applies to the scheme;
+    /// schemes for which it does not apply must accept arity 2.
+    ///
+    /// # Errors
+    /// Returns an error if the scheme cannot commit to the items with the specified arity.
+    fn with_arity(items: Vec<H::Digest>, arity: usize) -> Result<Self, Self::Error>;
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the commitment to the vector of items which is sent to the verifier.
+    fn commitment(&self) -> H::Digest;
+
+    /// Returns the number of items in the committed vector.
+    fn domain_len(&self) -> usize;
+
+    // OPENING AND VERIFICATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a proof of the items at the specified `indexes`; items in the proof are in the
+    /// same order as the indexes.
+    ///
+    /// # Errors
+    /// Returns an error if a proof for the specified indexes could not be built.
+    fn open_many(&self, indexes: &[usize]) -> Result<Self::MultiProof, Self::Error>;
+
+    /// Checks whether the `proof` opens the specified `commitment` at the specified `indexes`.
+    ///
+    /// # Errors
+    /// Returns an error if the proof is not valid for the commitment and the indexes.
+    fn verify_many(
+        commitment: &H::Digest,
+        indexes: &[usize],
+        proof: &Self::MultiProof,
+    ) -> Result<(), Self::Error>;
+
+    // SERIALIZATION / DESERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes the specified `proof` opened at `indexes` and writes the resulting bytes into
+    /// the `target`; the opened items are not serialized because the verifier reconstructs them
+    /// from the opened rows.
+    ///
+    /// When items are raw rows of `raw_item_bytes` bytes padded with zeros to the size of a
+    /// digest rather than digests of rows, `raw_item_bytes` is provided; the scheme may use this
+    /// to serialize items other than the opened ones (if the proof contains any) more compactly.
+    fn write_multiproof<W: ByteWriter>(
+        proof: &Self::MultiProof,
+        indexes: &[usize],
+        raw_item_bytes: Option<usize>,
+        target: &mut W,
+    );
+
+    /// Reads a proof serialized via [write_multiproof()](VectorCommitment::write_multiproof)
+    /// from the `source`, and builds the proof from it and the opened `items`; `domain_len` and
+    /// `arity` must be the same as for the commitment from which the proof was opened.
+    ///
+    /// # Errors
+    /// Returns an error if a valid proof could not be read from the `source`.
+    fn read_multiproof<R: ByteReader>(
+        source: &mut R,
+        items: Vec<H::Digest>,
+        raw_item_bytes: Option<usize>,
+        domain_len: usize,
+        arity: usize,
+    ) -> Result<Self::MultiProof, DeserializationError>;
+}
+
+// COMMITMENT OPENER
+// ================================================================================================
+
+/// Opens a vector commitment at the specified indexes.
+///
+/// Provers open commitments to execution traces via this trait. Every [VectorCommitment]
+/// implements it by opening the commitment kept in memory; an implementation for a Merkle tree
+/// kept in an external storage can instead fetch individual Merkle paths from the storage and
+/// aggregate them into a batch proof via
+/// [BatchMerkleProof::from_single_paths()](crate::BatchMerkleProof::from_single_paths).
+pub trait CommitmentOpener<H: Hasher, V: VectorCommitment<H> = crate::MerkleTree<H>>:
+    Send + Sync
+{
+    /// Returns the opened commitment.
+    fn root(&self) -> H::Digest;
+
+    /// Returns a proof for the items at the specified indexes; items in the proof must be in the
+    /// same order as the indexes.
+    ///
+    /// # Errors
+    /// Returns an error if a proof for the specified indexes could not be built.
+    fn open(&self, indexes: &[usize]) -> Result<V::MultiProof, V::Error>;
+}
+
+impl<H: Hasher, V: VectorCommitment<H>> CommitmentOpener<H, V> for V {
+    fn root(&self) -> H::Digest {
+        self.commitment()
+    }
+
+    fn open(&self, indexes: &[usize]) -> Result<V::MultiProof, V::Error> {
+        self.open_many(indexes)
+    }
+}
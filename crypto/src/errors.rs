@@ -25,6 +25,17 @@ pub enum MerkleTreeError {
     TooManyLeafIndexes(usize, usize),
     /// Merkle proof is not valid for the specified position(s).
     InvalidProof,
+    /// Merkle paths aggregated into a batch proof had different lengths.
+    PathLengthMismatch(usize, usize),
+    /// Merkle paths aggregated into a batch proof claimed different nodes at the same index of
+    /// the tree (the root has index 1, and children of the node at index i have indexes 2i and
+    /// 2i + 1).
+    ConflictingNodes(usize),
+    /// Arity of a Merkle tree was not 2, 4, or 8.
+    UnsupportedArity(usize),
+    /// An operation defined only for binary Merkle trees was invoked on a tree (or a proof) with
+    /// the specified arity.
+    NotBinaryTree(usize),
 }
 
 impl fmt::Display for MerkleTreeError {
@@ -60,6 +71,24 @@ impl fmt::Display for MerkleTreeError {
             Self::InvalidProof => {
                 write!(f, "Merkle proof is invalid")
             }
+            Self::PathLengthMismatch(expected, actual) => {
+                write!(f, "all Merkle paths must contain {expected} nodes, but a path with {actual} nodes was provided")
+            }
+            Self::ConflictingNodes(index) => {
+                write!(
+                    f,
+                    "Merkle paths claim different nodes at index {index} of the tree"
+                )
+            }
+            Self::UnsupportedArity(arity) => {
+                write!(f, "Merkle tree arity must be 2, 4, or 8, but was {arity}")
+            }
+            Self::NotBinaryTree(arity) => {
+                write!(
+                    f,
+                    "operation is supported only for binary Merkle trees, but tree arity is {arity}"
+                )
+            }
         }
     }
 }
@@ -68,7 +97,7 @@ impl fmt::Display for MerkleTreeError {
 // ================================================================================================
 
 /// Defines errors which can occur when drawing values from a random coin.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RandomCoinError {
     /// A valid element could not be drawn from the field after the specified number of tries.
     FailedToDrawFieldElement(usize),
@@ -0,0 +1,97 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Batch hashing of equally-sized inputs via the SIMD-dispatching backend of BLAKE3.
+//!
+//! [blake3::hash()] hashes a single input at a time, and thus, when the input fits into a single
+//! chunk (1 KiB), only one SIMD lane is used. The backend of the `blake3` crate can hash several
+//! single-chunk inputs in parallel, one input per SIMD lane (e.g., 16 lanes with AVX-512, 8 with
+//! AVX2, and 4 with NEON); the instruction set is selected at runtime when `std` feature is
+//! enabled. The backend is exposed by the `blake3` crate via its undocumented `platform` module,
+//! and thus, digests computed here are checked against [blake3::hash()] by golden-vector tests.
+
+use blake3::{platform::Platform, IncrementCounter, BLOCK_LEN, CHUNK_LEN, OUT_LEN};
+use core::convert::TryInto;
+use utils::collections::Vec;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Initialization vector of BLAKE3.
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+// Domain separation flags of BLAKE3 compression function.
+const CHUNK_START: u8 = 1 << 0;
+const CHUNK_END: u8 = 1 << 1;
+const ROOT: u8 = 1 << 3;
+
+// BATCH HASHING
+// ================================================================================================
+
+/// Returns true if inputs of the specified length can be hashed in parallel; this is the case
+/// when every input consists of whole blocks (64 bytes), and fits into a single chunk (1 KiB).
+pub fn supports_input_len(input_len: usize) -> bool {
+    input_len != 0 && input_len % BLOCK_LEN == 0 && input_len <= CHUNK_LEN
+}
+
+/// Hashes each `input_len` consecutive bytes of `inputs` and writes the 32-byte digests into
+/// `result`.
+///
+/// Digests are the same as the ones returned by [blake3::hash()] for each input.
+///
+/// # Panics
+/// Panics if inputs of the specified length cannot be hashed in parallel.
+pub fn hash_many(inputs: &[u8], input_len: usize, result: &mut [u8]) {
+    assert!(
+        supports_input_len(input_len),
+        "inputs of {input_len} bytes cannot be hashed in parallel"
+    );
+    debug_assert_eq!(0, inputs.len() % input_len);
+    debug_assert_eq!(inputs.len() / input_len * OUT_LEN, result.len());
+
+    match input_len / BLOCK_LEN {
+        1 => hash_many_blocks::<{ BLOCK_LEN }>(inputs, result),
+        2 => hash_many_blocks::<{ 2 * BLOCK_LEN }>(inputs, result),
+        3 => hash_many_blocks::<{ 3 * BLOCK_LEN }>(inputs, result),
+        4 => hash_many_blocks::<{ 4 * BLOCK_LEN }>(inputs, result),
+        5 => hash_many_blocks::<{ 5 * BLOCK_LEN }>(inputs, result),
+        6 => hash_many_blocks::<{ 6 * BLOCK_LEN }>(inputs, result),
+        7 => hash_many_blocks::<{ 7 * BLOCK_LEN }>(inputs, result),
+        8 => hash_many_blocks::<{ 8 * BLOCK_LEN }>(inputs, result),
+        9 => hash_many_blocks::<{ 9 * BLOCK_LEN }>(inputs, result),
+        10 => hash_many_blocks::<{ 10 * BLOCK_LEN }>(inputs, result),
+        11 => hash_many_blocks::<{ 11 * BLOCK_LEN }>(inputs, result),
+        12 => hash_many_blocks::<{ 12 * BLOCK_LEN }>(inputs, result),
+        13 => hash_many_blocks::<{ 13 * BLOCK_LEN }>(inputs, result),
+        14 => hash_many_blocks::<{ 14 * BLOCK_LEN }>(inputs, result),
+        15 => hash_many_blocks::<{ 15 * BLOCK_LEN }>(inputs, result),
+        16 => hash_many_blocks::<{ 16 * BLOCK_LEN }>(inputs, result),
+        _ => unreachable!(),
+    }
+}
+
+/// Hashes each `N` consecutive bytes of `inputs` as a root chunk of BLAKE3 and writes the
+/// digests into `result`.
+fn hash_many_blocks<const N: usize>(inputs: &[u8], result: &mut [u8]) {
+    let inputs = inputs
+        .chunks_exact(N)
+        .map(|input| input.try_into().unwrap())
+        .collect::<Vec<&[u8; N]>>();
+
+    // every input is hashed as the only chunk of a message, and thus, the chunk counter is always
+    // zero and the last block of every chunk is the root
+    Platform::detect().hash_many(
+        &inputs,
+        &IV,
+        0,
+        IncrementCounter::No,
+        0,
+        CHUNK_START,
+        CHUNK_END | ROOT,
+        result,
+    );
+}
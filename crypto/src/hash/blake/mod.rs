@@ -3,14 +3,28 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{ByteDigest, ElementHasher, Hasher};
+use super::{ByteDigest, ElementHasher, HashFunctionId, Hasher};
 use core::{convert::TryInto, fmt::Debug, marker::PhantomData};
 use math::{FieldElement, StarkField};
 use utils::ByteWriter;
 
-#[cfg(test)]
+#[cfg(all(feature = "blake3-accel", feature = "safe_only"))]
+use utils::collections::Vec;
+
+#[cfg(feature = "blake3-accel")]
+mod accel;
+
+#[cfg(all(test, feature = "f62"))]
 mod tests;
 
+// CONSTANTS
+// ================================================================================================
+
+/// Number of inputs hashed by a single call to the SIMD-dispatching backend of BLAKE3; this is
+/// several times greater than the number of SIMD lanes of the widest backend (16 with AVX-512).
+#[cfg(feature = "blake3-accel")]
+const ACCEL_BATCH_SIZE: usize = 64;
+
 // BLAKE3 256-BIT OUTPUT
 // ================================================================================================
 
@@ -24,12 +38,27 @@ impl<B: StarkField> Hasher for Blake3_256<B> {
 
     const COLLISION_RESISTANCE: u32 = 128;
 
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Blake3_256);
+
     fn hash(bytes: &[u8]) -> Self::Digest {
         ByteDigest(*blake3::hash(bytes).as_bytes())
     }
 
     fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
-        ByteDigest(blake3::hash(ByteDigest::digests_as_bytes(values)).into())
+        let mut data = [0; 64];
+        data[..32].copy_from_slice(&values[0].0);
+        data[32..].copy_from_slice(&values[1].0);
+        ByteDigest(blake3::hash(&data).into())
+    }
+
+    #[cfg(feature = "blake3-accel")]
+    fn merge_batch(values: &[Self::Digest], arity: usize, result: &mut [Self::Digest]) {
+        assert_eq!(
+            values.len(),
+            arity * result.len(),
+            "invalid number of digests"
+        );
+        merge_batch(values, arity, result, Self::merge, Self::merge_many);
     }
 
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
@@ -42,20 +71,50 @@ impl<B: StarkField> Hasher for Blake3_256<B> {
 
 impl<B: StarkField> ElementHasher for Blake3_256<B> {
     type BaseField = B;
+    type State = BlakeHasher;
 
     fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // when element's internal and canonical representations are the same, we can hash
+        // element bytes directly
+        #[cfg(not(feature = "safe_only"))]
         if B::IS_CANONICAL {
-            // when element's internal and canonical representations are the same, we can hash
-            // element bytes directly
             let bytes = E::elements_as_bytes(elements);
-            ByteDigest(*blake3::hash(bytes).as_bytes())
-        } else {
-            // when elements' internal and canonical representations differ, we need to serialize
-            // them before hashing
-            let mut hasher = BlakeHasher::new();
-            hasher.write(elements);
-            ByteDigest(hasher.finalize())
+            return ByteDigest(*blake3::hash(bytes).as_bytes());
         }
+
+        // when elements' internal and canonical representations differ (or when `safe_only`
+        // feature is enabled), we need to serialize them before hashing
+        let mut hasher = BlakeHasher::new();
+        hasher.write(elements);
+        ByteDigest(hasher.finalize())
+    }
+
+    #[cfg(feature = "blake3-accel")]
+    fn hash_elements_batch<E>(elements: &[E], num_elements: usize, result: &mut [Self::Digest])
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
+        assert_eq!(
+            elements.len(),
+            num_elements * result.len(),
+            "invalid number of elements"
+        );
+        hash_elements_batch(elements, num_elements, result, Self::hash_elements);
+    }
+
+    fn begin(_num_elements: usize) -> Self::State {
+        BlakeHasher::new()
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        state.absorb(elements);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        ByteDigest(state.finalize())
     }
 }
 
@@ -72,16 +131,31 @@ impl<B: StarkField> Hasher for Blake3_192<B> {
 
     const COLLISION_RESISTANCE: u32 = 96;
 
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Blake3_192);
+
     fn hash(bytes: &[u8]) -> Self::Digest {
         let result = blake3::hash(bytes);
         ByteDigest(result.as_bytes()[..24].try_into().unwrap())
     }
 
     fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
-        let result = blake3::hash(ByteDigest::digests_as_bytes(values));
+        let mut data = [0; 48];
+        data[..24].copy_from_slice(&values[0].0);
+        data[24..].copy_from_slice(&values[1].0);
+        let result = blake3::hash(&data);
         ByteDigest(result.as_bytes()[..24].try_into().unwrap())
     }
 
+    #[cfg(feature = "blake3-accel")]
+    fn merge_batch(values: &[Self::Digest], arity: usize, result: &mut [Self::Digest]) {
+        assert_eq!(
+            values.len(),
+            arity * result.len(),
+            "invalid number of digests"
+        );
+        merge_batch(values, arity, result, Self::merge, Self::merge_many);
+    }
+
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
         let mut data = [0; 32];
         data[..24].copy_from_slice(&seed.0);
@@ -94,36 +168,164 @@ impl<B: StarkField> Hasher for Blake3_192<B> {
 
 impl<B: StarkField> ElementHasher for Blake3_192<B> {
     type BaseField = B;
+    type State = BlakeHasher;
 
     fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // when element's internal and canonical representations are the same, we can hash
+        // element bytes directly
+        #[cfg(not(feature = "safe_only"))]
         if B::IS_CANONICAL {
-            // when element's internal and canonical representations are the same, we can hash
-            // element bytes directly
             let bytes = E::elements_as_bytes(elements);
             let result = blake3::hash(bytes);
-            ByteDigest(result.as_bytes()[..24].try_into().unwrap())
-        } else {
-            // when elements' internal and canonical representations differ, we need to serialize
-            // them before hashing
-            let mut hasher = BlakeHasher::new();
-            hasher.write(elements);
-            let result = hasher.finalize();
-            ByteDigest(result[..24].try_into().unwrap())
+            return ByteDigest(result.as_bytes()[..24].try_into().unwrap());
         }
+
+        // when elements' internal and canonical representations differ (or when `safe_only`
+        // feature is enabled), we need to serialize them before hashing
+        let mut hasher = BlakeHasher::new();
+        hasher.write(elements);
+        let result = hasher.finalize();
+        ByteDigest(result[..24].try_into().unwrap())
+    }
+
+    #[cfg(feature = "blake3-accel")]
+    fn hash_elements_batch<E>(elements: &[E], num_elements: usize, result: &mut [Self::Digest])
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
+        assert_eq!(
+            elements.len(),
+            num_elements * result.len(),
+            "invalid number of elements"
+        );
+        hash_elements_batch(elements, num_elements, result, Self::hash_elements);
+    }
+
+    fn begin(_num_elements: usize) -> Self::State {
+        BlakeHasher::new()
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        state.absorb(elements);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        ByteDigest(state.finalize()[..24].try_into().unwrap())
+    }
+}
+
+// BATCH HASHING
+// ================================================================================================
+
+/// Hashes every `arity` consecutive digests in `values` in parallel when the concatenated
+/// digests can be hashed in parallel, and via `merge` or `merge_many` otherwise.
+#[cfg(feature = "blake3-accel")]
+fn merge_batch<const N: usize>(
+    values: &[ByteDigest<N>],
+    arity: usize,
+    result: &mut [ByteDigest<N>],
+    merge: fn(&[ByteDigest<N>; 2]) -> ByteDigest<N>,
+    merge_many: fn(&[ByteDigest<N>]) -> ByteDigest<N>,
+) {
+    #[cfg(not(feature = "safe_only"))]
+    let bytes = ByteDigest::digests_as_bytes(values);
+    #[cfg(feature = "safe_only")]
+    let bytes = &values
+        .iter()
+        .flat_map(|digest| digest.0)
+        .collect::<Vec<_>>();
+
+    if !hash_many(bytes, arity * N, result) {
+        for (target, children) in result.iter_mut().zip(values.chunks_exact(arity)) {
+            *target = match children {
+                [left, right] => merge(&[*left, *right]),
+                _ => merge_many(children),
+            };
+        }
+    }
+}
+
+/// Hashes every `num_elements` consecutive elements in `elements` in parallel when the elements
+/// can be hashed as bytes and the groups can be hashed in parallel, and via `hash_elements`
+/// otherwise.
+#[cfg(feature = "blake3-accel")]
+fn hash_elements_batch<E: FieldElement, const N: usize>(
+    elements: &[E],
+    num_elements: usize,
+    result: &mut [ByteDigest<N>],
+    hash_elements: fn(&[E]) -> ByteDigest<N>,
+) {
+    #[cfg(not(feature = "safe_only"))]
+    if E::BaseField::IS_CANONICAL {
+        let bytes = E::elements_as_bytes(elements);
+        if hash_many(bytes, num_elements * E::ELEMENT_BYTES, result) {
+            return;
+        }
+    }
+
+    for (target, elements) in result.iter_mut().zip(elements.chunks_exact(num_elements)) {
+        *target = hash_elements(elements);
     }
 }
 
+/// Hashes every `input_len` consecutive bytes of `inputs` in parallel and writes the digests,
+/// truncated to `N` bytes, into `result`; returns false if inputs of this length cannot be
+/// hashed in parallel.
+///
+/// Inputs are hashed in batches of [ACCEL_BATCH_SIZE] inputs, so that the digests of a batch can
+/// be kept on the stack.
+#[cfg(feature = "blake3-accel")]
+fn hash_many<const N: usize>(
+    inputs: &[u8],
+    input_len: usize,
+    result: &mut [ByteDigest<N>],
+) -> bool {
+    if !accel::supports_input_len(input_len) {
+        return false;
+    }
+
+    let mut digests = [0; ACCEL_BATCH_SIZE * blake3::OUT_LEN];
+    for (inputs, result) in inputs
+        .chunks(ACCEL_BATCH_SIZE * input_len)
+        .zip(result.chunks_mut(ACCEL_BATCH_SIZE))
+    {
+        let digests = &mut digests[..result.len() * blake3::OUT_LEN];
+        accel::hash_many(inputs, input_len, digests);
+        for (target, digest) in result.iter_mut().zip(digests.chunks_exact(blake3::OUT_LEN)) {
+            *target = ByteDigest(digest[..N].try_into().unwrap());
+        }
+    }
+    true
+}
+
 // BLAKE HASHER
 // ================================================================================================
 
 /// Wrapper around BLAKE3 hasher to implement [ByteWriter] trait for it.
-struct BlakeHasher(blake3::Hasher);
+///
+/// This is also used as the state of incremental hash computations for BLAKE3 hashers.
+pub struct BlakeHasher(blake3::Hasher);
 
 impl BlakeHasher {
     pub fn new() -> Self {
         Self(blake3::Hasher::new())
     }
 
+    /// Absorbs the provided elements into this hasher in the same way as
+    /// [hash_elements()](ElementHasher::hash_elements) does.
+    pub fn absorb<E: FieldElement>(&mut self, elements: &[E]) {
+        #[cfg(not(feature = "safe_only"))]
+        if E::BaseField::IS_CANONICAL {
+            self.0.update(E::elements_as_bytes(elements));
+            return;
+        }
+
+        self.write(elements);
+    }
+
     pub fn finalize(&self) -> [u8; 32] {
         *self.0.finalize().as_bytes()
     }
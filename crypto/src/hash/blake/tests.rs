@@ -3,7 +3,8 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{Blake3_256, ElementHasher, Hasher};
+use super::{Blake3_192, Blake3_256, ByteDigest, ElementHasher, Hasher};
+use crate::Digest;
 use math::{fields::f62::BaseElement, FieldElement};
 use rand_utils::rand_array;
 
@@ -28,3 +29,111 @@ fn hash_elements_padding() {
     let r2 = Blake3_256::hash_elements(&e2);
     assert_ne!(r1, r2);
 }
+
+#[test]
+fn merge_many_vs_merge() {
+    let digests = [
+        Blake3_256::<BaseElement>::hash(&rand_array::<u8, 32>()),
+        Blake3_256::<BaseElement>::hash(&rand_array::<u8, 32>()),
+    ];
+
+    // merging two digests as a sequence should be the same as merging them as a pair
+    let m_result = Blake3_256::<BaseElement>::merge(&digests);
+    let h_result = Blake3_256::<BaseElement>::merge_many(&digests);
+    assert_eq!(m_result, h_result);
+}
+
+// BATCH HASHING
+// ================================================================================================
+
+#[test]
+fn merge_batch_vs_merge() {
+    for arity in [2, 4, 8] {
+        let values = (0..100 * arity)
+            .map(|_| Blake3_256::<BaseElement>::hash(&rand_array::<u8, 32>()))
+            .collect::<Vec<_>>();
+        let mut result = vec![ByteDigest::default(); 100];
+        Blake3_256::<BaseElement>::merge_batch(&values, arity, &mut result);
+        for (digest, children) in result.iter().zip(values.chunks(arity)) {
+            assert_eq!(Blake3_256::<BaseElement>::merge_many(children), *digest);
+        }
+
+        let values = (0..100 * arity)
+            .map(|_| Blake3_192::<BaseElement>::hash(&rand_array::<u8, 32>()))
+            .collect::<Vec<_>>();
+        let mut result = vec![ByteDigest::default(); 100];
+        Blake3_192::<BaseElement>::merge_batch(&values, arity, &mut result);
+        for (digest, children) in result.iter().zip(values.chunks(arity)) {
+            assert_eq!(Blake3_192::<BaseElement>::merge_many(children), *digest);
+        }
+    }
+}
+
+#[test]
+fn merge_batch_golden_vectors() {
+    // digests must not depend on whether inputs are hashed one by one or in parallel
+    let values = (0..32_u8)
+        .map(|i| Blake3_256::<BaseElement>::hash(&[i]))
+        .collect::<Vec<_>>();
+    for (arity, expected) in [
+        (
+            2,
+            "edb21d5be7f43892dc02aff959cfefd4ac49ed3abf18b96b72679ce3698d0626",
+        ),
+        (
+            4,
+            "de70d0bb7d89915d3cb3dd95c889e1ff60be37076b95015b7d985b910e422cba",
+        ),
+        (
+            8,
+            "a189ba09763a789144d7fbd4208c67e57757362a6ec462519f49d5c8db5115fb",
+        ),
+    ] {
+        let mut result = vec![ByteDigest::default(); 32 / arity];
+        Blake3_256::<BaseElement>::merge_batch(&values, arity, &mut result);
+        assert_eq!(expected, to_hex(&result[result.len() - 1].as_bytes()));
+    }
+}
+
+#[cfg(feature = "f128")]
+#[test]
+fn hash_elements_batch_golden_vectors() {
+    use math::fields::f128::BaseElement;
+
+    // rows of 4 elements are 64 bytes long, and thus, they can be hashed in parallel
+    let elements = (0..64_u128).map(BaseElement::new).collect::<Vec<_>>();
+    let mut result = vec![ByteDigest::default(); 16];
+    Blake3_256::hash_elements_batch(&elements, 4, &mut result);
+    assert_eq!(
+        "a94f654305ce4db171b60370fcb21bb4fbd8b7b55d3812a903e189e515c220b8",
+        to_hex(&result[15].as_bytes())
+    );
+}
+
+#[cfg(feature = "f128")]
+#[test]
+fn hash_elements_batch_vs_hash_elements() {
+    use math::fields::f128::BaseElement;
+    use rand_utils::rand_vector;
+
+    // rows of 48, 64, 128, 1024, and 1040 bytes; only rows consisting of whole BLAKE3 blocks
+    // which fit into a single chunk can be hashed in parallel
+    for num_elements in [3, 4, 8, 64, 65] {
+        let elements = rand_vector::<BaseElement>(100 * num_elements);
+        let mut result = vec![ByteDigest::default(); 100];
+        Blake3_256::hash_elements_batch(&elements, num_elements, &mut result);
+        for (digest, row) in result.iter().zip(elements.chunks(num_elements)) {
+            assert_eq!(Blake3_256::hash_elements(row), *digest);
+        }
+
+        let mut result = vec![ByteDigest::default(); 100];
+        Blake3_192::hash_elements_batch(&elements, num_elements, &mut result);
+        for (digest, row) in result.iter().zip(elements.chunks(num_elements)) {
+            assert_eq!(Blake3_192::hash_elements(row), *digest);
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
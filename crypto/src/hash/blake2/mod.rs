@@ -0,0 +1,87 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{sha::ShaHasher, ByteDigest, ElementHasher, HashFunctionId, Hasher};
+use blake2::{Blake2s256, Digest};
+use core::marker::PhantomData;
+use math::{FieldElement, StarkField};
+use utils::ByteWriter;
+
+#[cfg(all(test, feature = "f64", feature = "quadratic"))]
+mod tests;
+
+// BLAKE2s WITH 256-BIT OUTPUT
+// ================================================================================================
+
+/// Implementation of the [Hasher](super::Hasher) trait for BLAKE2s hash function with 256-bit
+/// output.
+///
+/// Elements are hashed in the same way as with other byte-oriented hashers: each base field
+/// element is encoded as its canonical value in little-endian byte order.
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Blake2s_256<B: StarkField>(PhantomData<B>);
+
+impl<B: StarkField> Hasher for Blake2s_256<B> {
+    type Digest = ByteDigest<32>;
+
+    const COLLISION_RESISTANCE: u32 = 128;
+
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Blake2s256);
+
+    fn hash(bytes: &[u8]) -> Self::Digest {
+        ByteDigest(Blake2s256::digest(bytes).into())
+    }
+
+    fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
+        let mut data = [0; 64];
+        data[..32].copy_from_slice(&values[0].0);
+        data[32..].copy_from_slice(&values[1].0);
+        ByteDigest(Blake2s256::digest(data).into())
+    }
+
+    fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
+        let mut data = [0; 40];
+        data[..32].copy_from_slice(&seed.0);
+        data[32..].copy_from_slice(&value.to_le_bytes());
+        ByteDigest(Blake2s256::digest(data).into())
+    }
+}
+
+impl<B: StarkField> ElementHasher for Blake2s_256<B> {
+    type BaseField = B;
+    type State = ShaHasher<Blake2s256>;
+
+    fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // when element's internal and canonical representations are the same, we can hash
+        // element bytes directly
+        #[cfg(not(feature = "safe_only"))]
+        if B::IS_CANONICAL {
+            let bytes = E::elements_as_bytes(elements);
+            return ByteDigest(Blake2s256::digest(bytes).into());
+        }
+
+        // when elements' internal and canonical representations differ (or when `safe_only`
+        // feature is enabled), we need to serialize them before hashing
+        let mut hasher = ShaHasher::<Blake2s256>::new();
+        hasher.write(elements);
+        ByteDigest(hasher.finalize())
+    }
+
+    fn begin(_num_elements: usize) -> Self::State {
+        ShaHasher::new()
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        state.absorb(elements);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        ByteDigest(state.finalize())
+    }
+}
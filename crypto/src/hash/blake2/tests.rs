@@ -0,0 +1,64 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{Blake2s_256, ByteDigest, ElementHasher, Hasher};
+use math::{fields::f64::BaseElement, FieldElement};
+use utils::{collections::Vec, string::String};
+
+type Blake2s = Blake2s_256<BaseElement>;
+
+// BLAKE2s-256 TEST VECTORS
+// ================================================================================================
+// Expected values match BLAKE2s-256 (unkeyed, as specified in RFC 7693) of the same byte strings.
+
+#[test]
+fn blake2s_hash() {
+    let expected = "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9";
+    assert_eq!(expected, to_hex(Blake2s::hash(b"")));
+
+    let expected = "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982";
+    assert_eq!(expected, to_hex(Blake2s::hash(b"abc")));
+
+    // 200 bytes span several blocks of the compression function
+    let bytes = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+    let expected = "6d244e1a06ce4ef578dd0f63aff0936706735119ca9c8d22d86c801414ab9741";
+    assert_eq!(expected, to_hex(Blake2s::hash(&bytes)));
+}
+
+#[test]
+fn blake2s_merge() {
+    let expected = "ae09db7cd54f42b490ef09b6bc541af688e4959bb8c53f359a6f56e38ab454a3";
+    assert_eq!(
+        expected,
+        to_hex(Blake2s::merge(&[ByteDigest([0; 32]), ByteDigest([0; 32])]))
+    );
+
+    let mut seed = [0; 32];
+    seed.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+    let expected = "c3c8c2c8b9cc4a5171c108d6f45686360fbe01450f2821912d7056a866f3c88a";
+    assert_eq!(
+        expected,
+        to_hex(Blake2s::merge_with_int(ByteDigest(seed), 42))
+    );
+}
+
+#[test]
+fn blake2s_hash_elements() {
+    let elements = [BaseElement::ONE, BaseElement::new(2), -BaseElement::ONE];
+    let expected = "4a76c32366aa3d8135c6fe00fde29bfb28a2f4f3939e3f69ed468362e0b5e107";
+    assert_eq!(expected, to_hex(Blake2s::hash_elements(&elements)));
+
+    let mut state = Blake2s::begin(elements.len());
+    Blake2s::absorb(&mut state, &elements[..2]);
+    Blake2s::absorb(&mut state, &elements[2..]);
+    assert_eq!(expected, to_hex(Blake2s::finalize(state)));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn to_hex(digest: ByteDigest<32>) -> String {
+    digest.0.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,406 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    Blake2s_256, Blake3_192, Blake3_256, Digest, ElementHasher, Hasher, Keccak_256, Sha3_256,
+};
+use core::{convert::Infallible, fmt, marker::PhantomData};
+use math::{FieldElement, StarkField};
+use utils::{
+    collections::Vec, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+    SliceReader,
+};
+
+#[cfg(feature = "f64")]
+use super::GriffinJive64_256;
+#[cfg(feature = "f128")]
+use super::Poseidon128_256;
+#[cfg(feature = "f64")]
+use super::Poseidon64_256;
+#[cfg(feature = "f62")]
+use super::Rp62_248;
+#[cfg(feature = "f64")]
+use super::{Rp64_256, RpJive64_256};
+
+#[cfg(test)]
+mod tests;
+
+// HASH FUNCTION ID
+// ================================================================================================
+
+/// Identifies one of the hash functions defined in this crate.
+///
+/// The identifier of a hash function is available via [Hasher::ID]; it is bound into the context
+/// of proofs generated with this hash function so that a verifier can learn the hash function of
+/// a proof at runtime (e.g., to instantiate a [DynHasher]).
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HashFunctionId {
+    /// BLAKE3 hash function with 192-bit output.
+    Blake3_192 = 1,
+    /// BLAKE3 hash function with 256-bit output.
+    Blake3_256 = 2,
+    /// SHA3 hash function with 256-bit output.
+    Sha3_256 = 3,
+    /// Keccak hash function with 256-bit output (as used by Ethereum).
+    Keccak256 = 4,
+    /// BLAKE2s hash function with 256-bit output.
+    Blake2s256 = 5,
+    /// Rescue Prime hash function over the 62-bit field with 248-bit output.
+    Rp62_248 = 16,
+    /// Rescue Prime hash function over the 64-bit field with 256-bit output.
+    Rp64_256 = 17,
+    /// Rescue Prime hash function over the 64-bit field with 256-bit output and Jive compression.
+    RpJive64_256 = 18,
+    /// Griffin hash function over the 64-bit field with 256-bit output and Jive compression.
+    GriffinJive64_256 = 19,
+    /// Poseidon hash function over the 64-bit field with 256-bit output.
+    Poseidon64_256 = 20,
+    /// Poseidon hash function over the 128-bit field with 256-bit output.
+    Poseidon128_256 = 21,
+}
+
+impl HashFunctionId {
+    /// Returns true if this hash function operates over a specific base field (i.e., it is an
+    /// algebraic hash function), and false if it can be used with any base field.
+    pub fn is_algebraic(&self) -> bool {
+        *self as u8 >= Self::Rp62_248 as u8
+    }
+
+    /// Returns a hash function identifier encoded by the specified value.
+    fn from_u8(value: u8) -> Result<Self, DeserializationError> {
+        match value {
+            1 => Ok(Self::Blake3_192),
+            2 => Ok(Self::Blake3_256),
+            3 => Ok(Self::Sha3_256),
+            4 => Ok(Self::Keccak256),
+            5 => Ok(Self::Blake2s256),
+            16 => Ok(Self::Rp62_248),
+            17 => Ok(Self::Rp64_256),
+            18 => Ok(Self::RpJive64_256),
+            19 => Ok(Self::GriffinJive64_256),
+            20 => Ok(Self::Poseidon64_256),
+            21 => Ok(Self::Poseidon128_256),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "value {value} cannot be deserialized as HashFunctionId enum"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for HashFunctionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blake3_192 => write!(f, "blake3_192"),
+            Self::Blake3_256 => write!(f, "blake3_256"),
+            Self::Sha3_256 => write!(f, "sha3_256"),
+            Self::Keccak256 => write!(f, "keccak_256"),
+            Self::Blake2s256 => write!(f, "blake2s_256"),
+            Self::Rp62_248 => write!(f, "rp62_248"),
+            Self::Rp64_256 => write!(f, "rp64_256"),
+            Self::RpJive64_256 => write!(f, "rp_jive64_256"),
+            Self::GriffinJive64_256 => write!(f, "griffin_jive64_256"),
+            Self::Poseidon64_256 => write!(f, "poseidon64_256"),
+            Self::Poseidon128_256 => write!(f, "poseidon128_256"),
+        }
+    }
+}
+
+impl Serializable for HashFunctionId {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(*self as u8);
+    }
+}
+
+impl Deserializable for HashFunctionId {
+    /// Reads a hash function identifier from the specified `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Self::from_u8(source.read_u8()?)
+    }
+}
+
+// DYNAMIC DIGEST
+// ================================================================================================
+
+/// Digest returned by a [DynHasher].
+///
+/// The digest holds the serialized form of the digest of the underlying hash function; it can be
+/// converted into the digest type of this function via [DynDigest::to_digest()].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DynDigest {
+    bytes: [u8; 32],
+    size: usize,
+}
+
+impl DynDigest {
+    /// Returns a new dynamic digest holding the serialized form of the provided digest.
+    ///
+    /// # Panics
+    /// Panics if the digest is serialized into more than 32 bytes.
+    pub fn new<D: Digest>(digest: D) -> Self {
+        let digest_bytes = digest.to_bytes();
+        assert!(
+            digest_bytes.len() <= 32,
+            "digest cannot be longer than 32 bytes"
+        );
+        let mut bytes = [0; 32];
+        bytes[..digest_bytes.len()].copy_from_slice(&digest_bytes);
+        Self {
+            bytes,
+            size: digest_bytes.len(),
+        }
+    }
+
+    /// Returns the serialized form of this digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.size]
+    }
+
+    /// Deserializes this digest into the digest type `D`.
+    ///
+    /// # Errors
+    /// Returns an error if this digest is not a valid serialized digest of type `D`.
+    pub fn to_digest<D: Digest>(&self) -> Result<D, DeserializationError> {
+        let mut source = SliceReader::new(self.as_bytes());
+        let digest = D::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(digest)
+    }
+}
+
+// DYNAMIC HASHER
+// ================================================================================================
+
+/// A hash function selected at runtime from the hash functions defined in this crate.
+///
+/// This provides the same operations as [Hasher] and [ElementHasher] traits via inherent methods
+/// which dispatch to the selected hash function; digests are returned as [DynDigest]s. Algebraic
+/// hash functions are available only if they are defined over the base field `B`, and only if
+/// the feature enabling the field of the hash function is enabled.
+///
+/// The selection costs a single branch per operation; however, elements hashed with algebraic
+/// hash functions are copied into their native field representation first.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DynHasher<B: StarkField> {
+    Blake3_192,
+    Blake3_256,
+    Sha3_256,
+    Keccak256,
+    Blake2s256,
+    #[cfg(feature = "f62")]
+    Rp62_248,
+    #[cfg(feature = "f64")]
+    Rp64_256,
+    #[cfg(feature = "f64")]
+    RpJive64_256,
+    #[cfg(feature = "f64")]
+    GriffinJive64_256,
+    #[cfg(feature = "f64")]
+    Poseidon64_256,
+    #[cfg(feature = "f128")]
+    Poseidon128_256,
+    #[doc(hidden)]
+    _Field(PhantomData<B>, Infallible),
+}
+
+/// Invokes the specified generic function with the hash function selected by a [DynHasher] as
+/// the first type parameter.
+macro_rules! dispatch {
+    ($hasher:expr, $func:ident::<H $(, $param:ty)*>($($arg:expr),*)) => {
+        match $hasher {
+            Self::Blake3_192 => $func::<Blake3_192<B> $(, $param)*>($($arg),*),
+            Self::Blake3_256 => $func::<Blake3_256<B> $(, $param)*>($($arg),*),
+            Self::Sha3_256 => $func::<Sha3_256<B> $(, $param)*>($($arg),*),
+            Self::Keccak256 => $func::<Keccak_256<B> $(, $param)*>($($arg),*),
+            Self::Blake2s256 => $func::<Blake2s_256<B> $(, $param)*>($($arg),*),
+            #[cfg(feature = "f62")]
+            Self::Rp62_248 => $func::<Rp62_248 $(, $param)*>($($arg),*),
+            #[cfg(feature = "f64")]
+            Self::Rp64_256 => $func::<Rp64_256 $(, $param)*>($($arg),*),
+            #[cfg(feature = "f64")]
+            Self::RpJive64_256 => $func::<RpJive64_256 $(, $param)*>($($arg),*),
+            #[cfg(feature = "f64")]
+            Self::GriffinJive64_256 => $func::<GriffinJive64_256 $(, $param)*>($($arg),*),
+            #[cfg(feature = "f64")]
+            Self::Poseidon64_256 => $func::<Poseidon64_256 $(, $param)*>($($arg),*),
+            #[cfg(feature = "f128")]
+            Self::Poseidon128_256 => $func::<Poseidon128_256 $(, $param)*>($($arg),*),
+            Self::_Field(_, never) => match *never {},
+        }
+    };
+}
+
+impl<B: StarkField> DynHasher<B> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a hasher for the hash function with the specified identifier, or None if this
+    /// hash function cannot be used with base field `B` or is not available because the feature
+    /// enabling its field is disabled.
+    pub fn new(id: HashFunctionId) -> Option<Self> {
+        let hasher = match id {
+            HashFunctionId::Blake3_192 => Self::Blake3_192,
+            HashFunctionId::Blake3_256 => Self::Blake3_256,
+            HashFunctionId::Sha3_256 => Self::Sha3_256,
+            HashFunctionId::Keccak256 => Self::Keccak256,
+            HashFunctionId::Blake2s256 => Self::Blake2s256,
+            #[cfg(feature = "f62")]
+            HashFunctionId::Rp62_248 => Self::Rp62_248,
+            #[cfg(feature = "f64")]
+            HashFunctionId::Rp64_256 => Self::Rp64_256,
+            #[cfg(feature = "f64")]
+            HashFunctionId::RpJive64_256 => Self::RpJive64_256,
+            #[cfg(feature = "f64")]
+            HashFunctionId::GriffinJive64_256 => Self::GriffinJive64_256,
+            #[cfg(feature = "f64")]
+            HashFunctionId::Poseidon64_256 => Self::Poseidon64_256,
+            #[cfg(feature = "f128")]
+            HashFunctionId::Poseidon128_256 => Self::Poseidon128_256,
+            #[allow(unreachable_patterns)]
+            _ => return None,
+        };
+
+        // algebraic hash functions can be used only with their own base field
+        let modulus = dispatch!(&hasher, base_field_modulus::<H>());
+        if modulus == B::get_modulus_le_bytes() {
+            Some(hasher)
+        } else {
+            None
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the identifier of the hash function selected by this hasher.
+    pub fn id(&self) -> HashFunctionId {
+        dispatch!(self, hash_fn_id::<H>())
+    }
+
+    /// Returns collision resistance of the selected hash function measured in bits.
+    pub fn collision_resistance(&self) -> u32 {
+        dispatch!(self, collision_resistance::<H>())
+    }
+
+    // HASHING
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a hash of the provided sequence of bytes.
+    pub fn hash(&self, bytes: &[u8]) -> DynDigest {
+        dispatch!(self, hash::<H>(bytes))
+    }
+
+    /// Returns a hash of two digests.
+    ///
+    /// # Panics
+    /// Panics if any of the digests was not produced by the selected hash function.
+    pub fn merge(&self, values: &[DynDigest; 2]) -> DynDigest {
+        dispatch!(self, merge::<H>(values))
+    }
+
+    /// Returns a hash of the provided sequence of digests.
+    ///
+    /// # Panics
+    /// Panics if any of the digests was not produced by the selected hash function.
+    pub fn merge_many(&self, values: &[DynDigest]) -> DynDigest {
+        dispatch!(self, merge_many::<H>(values))
+    }
+
+    /// Returns hash(`seed` || `value`).
+    ///
+    /// # Panics
+    /// Panics if the seed was not produced by the selected hash function.
+    pub fn merge_with_int(&self, seed: DynDigest, value: u64) -> DynDigest {
+        dispatch!(self, merge_with_int::<H>(seed, value))
+    }
+
+    /// Returns a hash of the provided field elements.
+    pub fn hash_elements<E>(&self, elements: &[E]) -> DynDigest
+    where
+        E: FieldElement<BaseField = B>,
+    {
+        match self {
+            Self::Blake3_192 => DynDigest::new(Blake3_192::hash_elements(elements)),
+            Self::Blake3_256 => DynDigest::new(Blake3_256::hash_elements(elements)),
+            Self::Sha3_256 => DynDigest::new(Sha3_256::hash_elements(elements)),
+            Self::Keccak256 => DynDigest::new(Keccak_256::hash_elements(elements)),
+            Self::Blake2s256 => DynDigest::new(Blake2s_256::hash_elements(elements)),
+            #[cfg(feature = "f62")]
+            Self::Rp62_248 => hash_native_elements::<Rp62_248, E>(elements),
+            #[cfg(feature = "f64")]
+            Self::Rp64_256 => hash_native_elements::<Rp64_256, E>(elements),
+            #[cfg(feature = "f64")]
+            Self::RpJive64_256 => hash_native_elements::<RpJive64_256, E>(elements),
+            #[cfg(feature = "f64")]
+            Self::GriffinJive64_256 => hash_native_elements::<GriffinJive64_256, E>(elements),
+            #[cfg(feature = "f64")]
+            Self::Poseidon64_256 => hash_native_elements::<Poseidon64_256, E>(elements),
+            #[cfg(feature = "f128")]
+            Self::Poseidon128_256 => hash_native_elements::<Poseidon128_256, E>(elements),
+            Self::_Field(_, never) => match *never {},
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn hash_fn_id<H: Hasher>() -> HashFunctionId {
+    H::ID.expect("hash functions defined in this crate must have an identifier")
+}
+
+fn collision_resistance<H: Hasher>() -> u32 {
+    H::COLLISION_RESISTANCE
+}
+
+/// Returns modulus of the base field of the specified hash function; for hash functions which
+/// can be used with any base field, this is the modulus of the field they were instantiated with.
+fn base_field_modulus<H: ElementHasher>() -> Vec<u8> {
+    H::BaseField::get_modulus_le_bytes()
+}
+
+fn hash<H: Hasher>(bytes: &[u8]) -> DynDigest {
+    DynDigest::new(H::hash(bytes))
+}
+
+fn merge<H: Hasher>(values: &[DynDigest; 2]) -> DynDigest {
+    let values = [to_digest::<H>(&values[0]), to_digest::<H>(&values[1])];
+    DynDigest::new(H::merge(&values))
+}
+
+fn merge_many<H: Hasher>(values: &[DynDigest]) -> DynDigest {
+    let values = values.iter().map(to_digest::<H>).collect::<Vec<_>>();
+    DynDigest::new(H::merge_many(&values))
+}
+
+fn merge_with_int<H: Hasher>(seed: DynDigest, value: u64) -> DynDigest {
+    DynDigest::new(H::merge_with_int(to_digest::<H>(&seed), value))
+}
+
+fn to_digest<H: Hasher>(digest: &DynDigest) -> H::Digest {
+    digest
+        .to_digest()
+        .expect("digest was not produced by the selected hash function")
+}
+
+/// Hashes the provided elements with an algebraic hash function after converting their base
+/// field decompositions into elements of the base field of the hash function.
+///
+/// Algebraic hash functions hash extension field elements in the same way as their base field
+/// decompositions, and thus, the result is the same as if the elements were hashed directly.
+#[cfg(any(feature = "f62", feature = "f64", feature = "f128"))]
+fn hash_native_elements<H: ElementHasher, E: FieldElement>(elements: &[E]) -> DynDigest {
+    let base_elements = E::to_base_elements(elements);
+    let mut bytes = Vec::new();
+    E::BaseField::write_batch_into(&base_elements, &mut bytes);
+    let native_elements =
+        H::BaseField::read_batch_from(&mut SliceReader::new(&bytes), base_elements.len())
+            .expect("elements must be valid in the base field of the hash function");
+    DynDigest::new(H::hash_elements(&native_elements))
+}
@@ -0,0 +1,194 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    Blake2s_256, Blake3_192, Blake3_256, DynDigest, DynHasher, ElementHasher, HashFunctionId,
+    Keccak_256, Sha3_256,
+};
+use math::fields::f128;
+use rand_utils::{rand_array, rand_vector};
+use utils::{Deserializable, DeserializationError, Serializable, SliceReader};
+
+#[cfg(feature = "f128")]
+use super::Poseidon128_256;
+#[cfg(feature = "f62")]
+use super::Rp62_248;
+#[cfg(feature = "f64")]
+use super::{GriffinJive64_256, Poseidon64_256, Rp64_256, RpJive64_256};
+#[cfg(feature = "f64")]
+use math::fields::f64;
+
+const ALL_IDS: [HashFunctionId; 11] = [
+    HashFunctionId::Blake3_192,
+    HashFunctionId::Blake3_256,
+    HashFunctionId::Sha3_256,
+    HashFunctionId::Keccak256,
+    HashFunctionId::Blake2s256,
+    HashFunctionId::Rp62_248,
+    HashFunctionId::Rp64_256,
+    HashFunctionId::RpJive64_256,
+    HashFunctionId::GriffinJive64_256,
+    HashFunctionId::Poseidon64_256,
+    HashFunctionId::Poseidon128_256,
+];
+
+// HASH FUNCTION ID
+// ================================================================================================
+
+#[test]
+fn hash_function_id_serialization() {
+    for id in ALL_IDS {
+        let bytes = id.to_bytes();
+        assert_eq!(vec![id as u8], bytes);
+        assert_eq!(
+            Ok(id),
+            HashFunctionId::read_from(&mut SliceReader::new(&bytes))
+        );
+    }
+
+    for value in [0, 6, 15, 22, 255] {
+        let err = HashFunctionId::read_from(&mut SliceReader::new(&[value])).unwrap_err();
+        assert_eq!(
+            DeserializationError::InvalidValue(format!(
+                "value {value} cannot be deserialized as HashFunctionId enum"
+            )),
+            err
+        );
+    }
+}
+
+// DYNAMIC HASHER
+// ================================================================================================
+
+#[test]
+fn dyn_hasher_selection() {
+    // hash functions which can be used with any field are available for every field
+    for id in ALL_IDS.iter().filter(|id| !id.is_algebraic()) {
+        assert_eq!(
+            Some(*id),
+            DynHasher::<f128::BaseElement>::new(*id).map(|h| h.id())
+        );
+    }
+
+    // algebraic hash functions are available only for their own field
+    assert_eq!(
+        None,
+        DynHasher::<f128::BaseElement>::new(HashFunctionId::Rp64_256)
+    );
+    assert_eq!(
+        Some(HashFunctionId::Poseidon128_256),
+        DynHasher::<f128::BaseElement>::new(HashFunctionId::Poseidon128_256).map(|h| h.id())
+    );
+    #[cfg(feature = "f64")]
+    {
+        assert_eq!(
+            None,
+            DynHasher::<f64::BaseElement>::new(HashFunctionId::Poseidon128_256)
+        );
+        assert_eq!(
+            None,
+            DynHasher::<f64::BaseElement>::new(HashFunctionId::Rp62_248)
+        );
+        for id in [
+            HashFunctionId::Rp64_256,
+            HashFunctionId::RpJive64_256,
+            HashFunctionId::GriffinJive64_256,
+            HashFunctionId::Poseidon64_256,
+        ] {
+            assert_eq!(
+                Some(id),
+                DynHasher::<f64::BaseElement>::new(id).map(|h| h.id())
+            );
+        }
+    }
+}
+
+#[test]
+fn dyn_hasher_matches_static_hashers() {
+    check_dyn_hasher::<Blake3_192<f128::BaseElement>>();
+    check_dyn_hasher::<Blake3_256<f128::BaseElement>>();
+    check_dyn_hasher::<Sha3_256<f128::BaseElement>>();
+    check_dyn_hasher::<Keccak_256<f128::BaseElement>>();
+    check_dyn_hasher::<Blake2s_256<f128::BaseElement>>();
+    #[cfg(feature = "f62")]
+    check_dyn_hasher::<Rp62_248>();
+    #[cfg(feature = "f64")]
+    {
+        check_dyn_hasher::<Blake3_256<f64::BaseElement>>();
+        check_dyn_hasher::<Rp64_256>();
+        check_dyn_hasher::<RpJive64_256>();
+        check_dyn_hasher::<GriffinJive64_256>();
+        check_dyn_hasher::<Poseidon64_256>();
+    }
+    #[cfg(feature = "f128")]
+    check_dyn_hasher::<Poseidon128_256>();
+}
+
+#[cfg(all(feature = "f64", feature = "quadratic"))]
+#[test]
+fn dyn_hasher_extension_elements() {
+    use math::fields::QuadExtension;
+    let elements = rand_vector::<QuadExtension<f64::BaseElement>>(5);
+
+    let hasher = DynHasher::new(HashFunctionId::Sha3_256).unwrap();
+    let expected = Sha3_256::hash_elements(&elements);
+    assert_eq!(DynDigest::new(expected), hasher.hash_elements(&elements));
+
+    let hasher = DynHasher::new(HashFunctionId::Rp64_256).unwrap();
+    assert_eq!(
+        DynDigest::new(Rp64_256::hash_elements(&elements)),
+        hasher.hash_elements(&elements)
+    );
+}
+
+#[test]
+#[should_panic(expected = "digest was not produced by the selected hash function")]
+fn dyn_hasher_merge_foreign_digest() {
+    let blake3_192 = DynHasher::<f128::BaseElement>::new(HashFunctionId::Blake3_192).unwrap();
+    let blake3_256 = DynHasher::<f128::BaseElement>::new(HashFunctionId::Blake3_256).unwrap();
+    let digest = blake3_192.hash(b"abc");
+    blake3_256.merge(&[digest, digest]);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Checks that every operation of a dynamic hasher selecting hash function `H` returns the same
+/// result as the corresponding operation of `H`.
+fn check_dyn_hasher<H: ElementHasher>() {
+    let hasher = DynHasher::<H::BaseField>::new(H::ID.unwrap()).unwrap();
+    assert_eq!(H::COLLISION_RESISTANCE, hasher.collision_resistance());
+
+    let bytes = rand_vector::<u8>(50);
+    let digest = hasher.hash(&bytes);
+    assert_eq!(DynDigest::new(H::hash(&bytes)), digest);
+    assert_eq!(H::hash(&bytes), digest.to_digest::<H::Digest>().unwrap());
+
+    let digests = [
+        H::hash(&rand_array::<u8, 32>()),
+        H::hash(&rand_array::<u8, 32>()),
+    ];
+    let dyn_digests = [DynDigest::new(digests[0]), DynDigest::new(digests[1])];
+    assert_eq!(
+        DynDigest::new(H::merge(&digests)),
+        hasher.merge(&dyn_digests)
+    );
+    assert_eq!(
+        DynDigest::new(H::merge_many(&digests)),
+        hasher.merge_many(&dyn_digests)
+    );
+    assert_eq!(
+        DynDigest::new(H::merge_with_int(digests[0], 42)),
+        hasher.merge_with_int(dyn_digests[0], 42)
+    );
+
+    for num_elements in [1, 7, 64] {
+        let elements = rand_vector::<H::BaseField>(num_elements);
+        assert_eq!(
+            DynDigest::new(H::hash_elements(&elements)),
+            hasher.hash_elements(&elements)
+        );
+    }
+}
@@ -3,7 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{Digest, ElementHasher, Hasher};
+use super::{Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
 
 mod griffin64_256_jive;
 pub use griffin64_256_jive::GriffinJive64_256;
@@ -3,24 +3,48 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use core::{fmt::Debug, slice};
+use core::fmt::Debug;
+#[cfg(any(feature = "f62", feature = "f64", feature = "f128"))]
+use core::ops::Range;
 use math::{FieldElement, StarkField};
-use utils::{ByteReader, Deserializable, DeserializationError, Serializable};
+use utils::{collections::Vec, ByteReader, Deserializable, DeserializationError, Serializable};
+
+use core::slice;
 
 mod blake;
 pub use blake::{Blake3_192, Blake3_256};
 
+mod blake2;
+pub use blake2::Blake2s_256;
+
 mod sha;
-pub use sha::Sha3_256;
+pub use sha::{Keccak_256, Sha3_256};
 
+mod dynamic;
+pub use dynamic::{DynDigest, DynHasher, HashFunctionId};
+
+#[cfg(feature = "f64")]
 mod mds;
 
+#[cfg(any(feature = "f62", feature = "f64"))]
 mod rescue;
-pub use rescue::{Rp62_248, Rp64_256, RpJive64_256};
+#[cfg(feature = "f62")]
+pub use rescue::Rp62_248;
+#[cfg(feature = "f64")]
+pub use rescue::{Rp64_256, RpJive64_256};
 
+#[cfg(feature = "f64")]
 mod griffin;
+#[cfg(feature = "f64")]
 pub use griffin::GriffinJive64_256;
 
+#[cfg(any(feature = "f64", feature = "f128"))]
+mod poseidon;
+#[cfg(feature = "f128")]
+pub use poseidon::Poseidon128_256;
+#[cfg(feature = "f64")]
+pub use poseidon::Poseidon64_256;
+
 // HASHER TRAITS
 // ================================================================================================
 
@@ -29,6 +53,9 @@ pub use griffin::GriffinJive64_256;
 /// This trait defines hash procedures for the following inputs:
 /// * A sequence of bytes.
 /// * Two digests - this is intended for use in Merkle tree constructions.
+/// * A sequence of digests - this is intended for use in Merkle trees with arity greater than 2.
+/// * A batch of groups of digests - this is intended for building a full row of Merkle tree
+///   nodes at once.
 /// * A digests and a u64 value - this intended for use in PRNG or PoW contexts.
 pub trait Hasher {
     /// Specifies a digest type returned by this hasher.
@@ -37,6 +64,12 @@ pub trait Hasher {
     /// Collision resistance of the hash function measured in bits.
     const COLLISION_RESISTANCE: u32;
 
+    /// Identifier of the hash function, or None if the hash function is not one of the hash
+    /// functions defined in this crate.
+    ///
+    /// The identifier is bound into the context of proofs generated with this hash function.
+    const ID: Option<HashFunctionId> = None;
+
     /// Returns a hash of the provided sequence of bytes.
     fn hash(bytes: &[u8]) -> Self::Digest;
 
@@ -44,6 +77,41 @@ pub trait Hasher {
     /// Merkle trees.
     fn merge(values: &[Self::Digest; 2]) -> Self::Digest;
 
+    /// Returns a hash of the provided sequence of digests. This method is intended for use in
+    /// construction of Merkle trees in which internal nodes have more than two children.
+    ///
+    /// The default implementation hashes the concatenation of serialized digests.
+    fn merge_many(values: &[Self::Digest]) -> Self::Digest {
+        let mut bytes = Vec::new();
+        Self::Digest::write_batch_into(values, &mut bytes);
+        Self::hash(&bytes)
+    }
+
+    /// Hashes every `arity` consecutive digests in `values` and writes the resulting digests into
+    /// `result`. This method is intended for use in construction of Merkle trees, where a full
+    /// row of nodes is built at once.
+    ///
+    /// Groups of two digests are hashed as in [merge()](Hasher::merge), and larger groups are
+    /// hashed as in [merge_many()](Hasher::merge_many); the default implementation does exactly
+    /// that. Hash functions which can hash several inputs in parallel (e.g., using SIMD
+    /// instructions) may override this method, but must return the same digests.
+    ///
+    /// # Panics
+    /// Panics if the number of `values` is not equal to `arity` times the length of `result`.
+    fn merge_batch(values: &[Self::Digest], arity: usize, result: &mut [Self::Digest]) {
+        assert_eq!(
+            values.len(),
+            arity * result.len(),
+            "invalid number of digests"
+        );
+        for (target, children) in result.iter_mut().zip(values.chunks_exact(arity)) {
+            *target = match children {
+                [left, right] => Self::merge(&[*left, *right]),
+                _ => Self::merge_many(children),
+            };
+        }
+    }
+
     /// Returns hash(`seed` || `value`). This method is intended for use in PRNG and PoW contexts.
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest;
 }
@@ -52,14 +120,126 @@ pub trait Hasher {
 ///
 /// This trait defines a hash procedure for a sequence of field elements. The elements can be
 /// either in the base field specified for this hasher, or in an extension of the base field.
+///
+/// In addition to hashing a contiguous slice of elements, the trait defines an incremental
+/// procedure: a hashing state is created via [begin()](ElementHasher::begin), elements are fed
+/// into it via one or more calls to [absorb()](ElementHasher::absorb), and the digest is
+/// computed via [finalize()](ElementHasher::finalize). The result of an incremental computation
+/// is the same as the result of [hash_elements()](ElementHasher::hash_elements) invoked on the
+/// concatenation of all absorbed elements.
 pub trait ElementHasher: Hasher {
     /// Specifies a base field for elements which can be hashed with this hasher.
     type BaseField: StarkField;
 
+    /// Specifies the type of the state of an incremental hash computation.
+    type State;
+
     /// Returns a hash of the provided field elements.
     fn hash_elements<E>(elements: &[E]) -> Self::Digest
     where
         E: FieldElement<BaseField = Self::BaseField>;
+
+    /// Hashes every `num_elements` consecutive elements in `elements` and writes the resulting
+    /// digests into `result`. This method is intended for hashing rows of a matrix laid out in
+    /// row-major order.
+    ///
+    /// Every group of elements is hashed as in [hash_elements()](ElementHasher::hash_elements);
+    /// the default implementation does exactly that. Hash functions which can hash several
+    /// inputs in parallel (e.g., using SIMD instructions) may override this method, but must
+    /// return the same digests.
+    ///
+    /// # Panics
+    /// Panics if the number of `elements` is not equal to `num_elements` times the length of
+    /// `result`.
+    fn hash_elements_batch<E>(elements: &[E], num_elements: usize, result: &mut [Self::Digest])
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
+        assert_eq!(
+            elements.len(),
+            num_elements * result.len(),
+            "invalid number of elements"
+        );
+        for (target, elements) in result.iter_mut().zip(elements.chunks_exact(num_elements)) {
+            *target = Self::hash_elements(elements);
+        }
+    }
+
+    /// Returns a new state for incrementally hashing `num_elements` base field elements.
+    ///
+    /// Each element in an extension field counts as several base field elements (e.g., two for
+    /// elements in a quadratic extension).
+    /// Some hash functions bind the length of the input into the initial state, and thus, the
+    /// total number of elements must be known upfront.
+    fn begin(num_elements: usize) -> Self::State;
+
+    /// Absorbs the provided field elements into the hashing state.
+    fn absorb<E>(state: &mut Self::State, elements: &[E])
+    where
+        E: FieldElement<BaseField = Self::BaseField>;
+
+    /// Returns the digest of all elements absorbed into the hashing state.
+    ///
+    /// The number of absorbed elements must be equal to the number of elements specified when
+    /// the state was created; otherwise, the result is undefined.
+    fn finalize(state: Self::State) -> Self::Digest;
+
+    /// Returns the native field element representation of the provided digest, or None if
+    /// digests of this hasher are not composed of base field elements.
+    ///
+    /// Algebraic hash functions (e.g., Rescue Prime) produce digests which consist of elements
+    /// of the base field; for such hash functions, this method should be overridden so that
+    /// digests could be absorbed as field elements without going through their byte encoding.
+    fn digest_as_elements(_digest: &Self::Digest) -> Option<&[Self::BaseField]> {
+        None
+    }
+}
+
+// SPONGE STATE
+// ================================================================================================
+
+/// State of an incremental hash computation for algebraic hash functions based on a sponge
+/// construction.
+///
+/// The state keeps track of the position in the rate portion of the sponge at which the next
+/// element is to be absorbed; once the rate portion is filled up, the permutation is applied and
+/// absorption starts again from the beginning of the rate.
+#[cfg(any(feature = "f62", feature = "f64", feature = "f128"))]
+#[derive(Debug, Clone, Copy)]
+pub struct SpongeState<B: StarkField, const WIDTH: usize> {
+    state: [B; WIDTH],
+    rate_idx: usize,
+}
+
+#[cfg(any(feature = "f62", feature = "f64", feature = "f128"))]
+impl<B: StarkField, const WIDTH: usize> SpongeState<B, WIDTH> {
+    /// Returns a new sponge state initialized to the provided values.
+    pub(crate) fn new(state: [B; WIDTH]) -> Self {
+        Self { state, rate_idx: 0 }
+    }
+
+    /// Adds the provided elements to the rate portion of the state one by one, applying the
+    /// permutation every time the rate portion is filled up.
+    pub(crate) fn absorb<P>(&mut self, elements: &[B], rate: Range<usize>, permutation: P)
+    where
+        P: Fn(&mut [B; WIDTH]),
+    {
+        let rate_width = rate.end - rate.start;
+        for &element in elements {
+            self.state[rate.start + self.rate_idx] += element;
+            self.rate_idx += 1;
+            if self.rate_idx == rate_width {
+                permutation(&mut self.state);
+                self.rate_idx = 0;
+            }
+        }
+    }
+
+    /// Returns the underlying state together with the number of elements absorbed since the
+    /// permutation was last applied.
+    pub(crate) fn into_parts(self) -> ([B; WIDTH], usize) {
+        (self.state, self.rate_idx)
+    }
 }
 
 // DIGEST TRAIT
@@ -89,6 +269,7 @@ impl<const N: usize> ByteDigest<N> {
         Self(value)
     }
 
+    #[allow(unsafe_code)]
     #[inline(always)]
     pub fn bytes_as_digests(bytes: &[[u8; N]]) -> &[ByteDigest<N>] {
         let p = bytes.as_ptr();
@@ -96,6 +277,7 @@ impl<const N: usize> ByteDigest<N> {
         unsafe { slice::from_raw_parts(p as *const ByteDigest<N>, len) }
     }
 
+    #[allow(unsafe_code)]
     #[inline(always)]
     pub fn digests_as_bytes(digests: &[ByteDigest<N>]) -> &[u8] {
         let p = digests.as_ptr();
@@ -130,9 +312,25 @@ impl<const N: usize> Deserializable for ByteDigest<N> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "f62",
+    feature = "f64",
+    feature = "f128",
+    feature = "quadratic",
+    feature = "cubic"
+))]
 mod tests {
-    use super::{ByteDigest, Digest};
+    use super::{
+        Blake2s_256, Blake3_192, Blake3_256, ByteDigest, Digest, ElementHasher, GriffinJive64_256,
+        Keccak_256, Poseidon128_256, Poseidon64_256, Rp62_248, Rp64_256, RpJive64_256, Sha3_256,
+    };
+    use math::{
+        fields::{f128, f62, f64, CubeExtension, QuadExtension},
+        FieldElement,
+    };
+    use rand_utils::{rand_array, rand_vector};
+    use utils::Serializable;
 
     #[test]
     fn byte_digest_as_bytes() {
@@ -144,4 +342,110 @@ mod tests {
         expected[31] = 0;
         assert_eq!(expected, d.as_bytes());
     }
+
+    #[test]
+    fn incremental_hashing() {
+        check_incremental::<Blake3_256<f62::BaseElement>, f62::BaseElement>();
+        check_incremental::<Blake3_256<f64::BaseElement>, QuadExtension<f64::BaseElement>>();
+        check_incremental::<Blake3_192<f62::BaseElement>, QuadExtension<f62::BaseElement>>();
+        check_incremental::<Sha3_256<f62::BaseElement>, f62::BaseElement>();
+        check_incremental::<Sha3_256<f64::BaseElement>, CubeExtension<f64::BaseElement>>();
+        check_incremental::<Keccak_256<f62::BaseElement>, QuadExtension<f62::BaseElement>>();
+        check_incremental::<Blake2s_256<f64::BaseElement>, f64::BaseElement>();
+        check_incremental::<Rp62_248, f62::BaseElement>();
+        check_incremental::<Rp64_256, f64::BaseElement>();
+        check_incremental::<Rp64_256, QuadExtension<f64::BaseElement>>();
+        check_incremental::<RpJive64_256, f64::BaseElement>();
+        check_incremental::<GriffinJive64_256, CubeExtension<f64::BaseElement>>();
+        check_incremental::<Poseidon64_256, QuadExtension<f64::BaseElement>>();
+        check_incremental::<Poseidon128_256, f128::BaseElement>();
+    }
+
+    #[test]
+    fn serialized_hashing() {
+        check_serialized::<Blake3_256<f128::BaseElement>, f128::BaseElement>();
+        check_serialized::<Blake3_256<f128::BaseElement>, QuadExtension<f128::BaseElement>>();
+        check_serialized::<Blake3_192<f128::BaseElement>, f128::BaseElement>();
+        check_serialized::<Sha3_256<f128::BaseElement>, QuadExtension<f128::BaseElement>>();
+        check_serialized::<Keccak_256<f128::BaseElement>, f128::BaseElement>();
+        check_serialized::<Keccak_256<f64::BaseElement>, CubeExtension<f64::BaseElement>>();
+        check_serialized::<Blake2s_256<f62::BaseElement>, QuadExtension<f62::BaseElement>>();
+        check_serialized::<Blake3_256<f62::BaseElement>, f62::BaseElement>();
+    }
+
+    #[test]
+    fn base_element_hashing() {
+        check_base_elements::<Rp62_248, QuadExtension<f62::BaseElement>>();
+        check_base_elements::<Rp64_256, QuadExtension<f64::BaseElement>>();
+        check_base_elements::<RpJive64_256, CubeExtension<f64::BaseElement>>();
+        check_base_elements::<GriffinJive64_256, QuadExtension<f64::BaseElement>>();
+        check_base_elements::<Poseidon64_256, CubeExtension<f64::BaseElement>>();
+        check_base_elements::<Poseidon128_256, QuadExtension<f128::BaseElement>>();
+    }
+
+    /// Checks that hashing elements and merging digests with byte-oriented hashers yields the same
+    /// digests as hashing serialized elements and concatenated digests; for fields with canonical
+    /// internal representation, this checks that hashing re-interpreted memory is equivalent to
+    /// hashing serialized elements.
+    fn check_serialized<H, E>()
+    where
+        H: ElementHasher,
+        E: FieldElement<BaseField = H::BaseField>,
+    {
+        for num_elements in [1, 7, 64, 1000] {
+            let elements = rand_vector::<E>(num_elements);
+            let mut bytes = Vec::new();
+            elements.write_into(&mut bytes);
+            assert_eq!(H::hash(&bytes), H::hash_elements(&elements));
+
+            let mut state = H::begin(num_elements);
+            H::absorb(&mut state, &elements);
+            assert_eq!(H::hash(&bytes), H::finalize(state));
+        }
+
+        let values = [
+            H::hash(&rand_array::<u8, 32>()),
+            H::hash(&rand_array::<u8, 32>()),
+        ];
+        let mut bytes = Vec::new();
+        values[0].write_into(&mut bytes);
+        values[1].write_into(&mut bytes);
+        assert_eq!(H::hash(&bytes), H::merge(&values));
+    }
+
+    /// Checks that hashing extension field elements yields the same digest as hashing their
+    /// decompositions into base field elements.
+    fn check_base_elements<H, E>()
+    where
+        H: ElementHasher,
+        E: FieldElement<BaseField = H::BaseField>,
+    {
+        for num_elements in [1, 7, 64, 1000] {
+            let elements = rand_vector::<E>(num_elements);
+            let base_elements = E::to_base_elements(&elements);
+            assert_eq!(
+                H::hash_elements(&base_elements),
+                H::hash_elements(&elements)
+            );
+        }
+    }
+
+    /// Checks that hashing elements incrementally in two parts yields the same digest as hashing
+    /// all elements at once, for all split points of inputs of 1 to 20 elements.
+    fn check_incremental<H, E>()
+    where
+        H: ElementHasher,
+        E: FieldElement<BaseField = H::BaseField>,
+    {
+        for num_elements in 1..=20 {
+            let elements = rand_vector::<E>(num_elements);
+            let expected = H::hash_elements(&elements);
+            for split in 0..=num_elements {
+                let mut state = H::begin(E::to_base_elements(&elements).len());
+                H::absorb(&mut state, &elements[..split]);
+                H::absorb(&mut state, &elements[split..]);
+                assert_eq!(expected, H::finalize(state));
+            }
+        }
+    }
 }
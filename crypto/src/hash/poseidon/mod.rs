@@ -0,0 +1,16 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
+
+#[cfg(feature = "f64")]
+mod poseidon64_256;
+#[cfg(feature = "f64")]
+pub use poseidon64_256::Poseidon64_256;
+
+#[cfg(feature = "f128")]
+mod poseidon128_256;
+#[cfg(feature = "f128")]
+pub use poseidon128_256::Poseidon128_256;
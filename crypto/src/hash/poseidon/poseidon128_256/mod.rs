@@ -0,0 +1,1173 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
+use core::convert::TryInto;
+use core::ops::Range;
+use math::{fields::f128::BaseElement, FieldElement};
+#[cfg(feature = "safe_only")]
+use utils::collections::Vec;
+
+mod digest;
+pub use digest::ElementDigest;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Sponge state is set to 6 field elements or 96 bytes; 4 elements are reserved for rate and
+/// the remaining 2 elements are reserved for capacity.
+const STATE_WIDTH: usize = 6;
+
+/// The rate portion of the state is located in elements 2 through 5.
+const RATE_RANGE: Range<usize> = 2..6;
+const RATE_WIDTH: usize = RATE_RANGE.end - RATE_RANGE.start;
+
+const INPUT1_RANGE: Range<usize> = 2..4;
+const INPUT2_RANGE: Range<usize> = 4..6;
+
+/// The capacity portion of the state is located in elements 0 and 1.
+const CAPACITY_RANGE: Range<usize> = 0..2;
+
+/// The output of the hash function is a digest which consists of 2 field elements or 32 bytes.
+///
+/// The digest is returned from state elements 2 and 3 (the first two elements of the rate
+/// portion).
+const DIGEST_RANGE: Range<usize> = 2..4;
+const DIGEST_SIZE: usize = DIGEST_RANGE.end - DIGEST_RANGE.start;
+
+/// The number of full rounds is set to 8 and the number of partial rounds is set to 84 to target
+/// 128-bit security level; computed using the round number calculation of the reference
+/// implementation, which includes a security margin of 2 full rounds and 7.5% of partial rounds.
+const NUM_FULL_ROUNDS: usize = 8;
+const NUM_PARTIAL_ROUNDS: usize = 84;
+const NUM_ROUNDS: usize = NUM_FULL_ROUNDS + NUM_PARTIAL_ROUNDS;
+
+/// S-Box power; this is the smallest power for which x^alpha is a permutation of the field.
+///
+/// The constant is defined for tests only because the exponentiation in the code is unrolled for
+/// efficiency reasons.
+#[cfg(test)]
+const ALPHA: u128 = 3;
+
+// HASHER IMPLEMENTATION
+// ================================================================================================
+
+/// Implementation of [Hasher] trait for Poseidon hash function with 256-bit output over the
+/// 128-bit field.
+///
+/// The hash function is implemented according to the Poseidon
+/// [specifications](https://eprint.iacr.org/2019/458.pdf) with the following exceptions:
+/// * When hashing a sequence of elements, we do not append Fp(1) followed by Fp(0) elements
+///   to the end of the sequence as padding. Instead, we initialize the first capacity element
+///   to the number of elements to be hashed, and pad the sequence with Fp(0) elements only. This
+///   ensures consistency of hash outputs between different hashing methods (see section below).
+///   However, it also means that our instantiation of Poseidon cannot be used in a stream mode as
+///   the number of elements to be hashed must be known upfront.
+/// * We use the first 2 elements of the state (rather than the last 2 elements of the state) for
+///   capacity and the remaining 4 elements for rate. The output of the hash function comes from
+///   the first two elements of the rate portion of the state (elements 2 and 3).
+///
+/// The round constants are generated in the same way as in the reference implementation. The
+/// MDS matrix is a Cauchy matrix as suggested in the specifications.
+///
+/// The parameters used to instantiate the function are:
+/// * Field: 128-bit prime field with modulus 2^128 - 45 * 2^40 + 1.
+/// * State width: 6 field elements.
+/// * Capacity size: 2 field elements.
+/// * Number of full rounds: 8.
+/// * Number of partial rounds: 84.
+/// * S-Box degree: 3.
+///
+/// The above parameters target 128-bit security level. The digest consists of two field elements
+/// and it can be serialized into 32 bytes (256 bits).
+///
+/// ## Hash output consistency
+/// Functions [hash_elements()](Poseidon128_256::hash_elements),
+/// [merge()](Poseidon128_256::merge), and [merge_with_int()](Poseidon128_256::merge_with_int) are
+/// internally consistent. That is, computing a hash for the same set of elements using these
+/// functions will always produce the same result. For example, merging two digests using
+/// [merge()](Poseidon128_256::merge) will produce the same result as hashing 4 elements which
+/// make up these digests using [hash_elements()](Poseidon128_256::hash_elements) function.
+///
+/// However, [hash()](Poseidon128_256::hash) function is not consistent with functions mentioned
+/// above: the function needs to be able to handle arbitrary binary strings, which may or may not
+/// encode valid field elements, and thus, the strings are split into 15-byte chunks rather than
+/// deserialized into field elements.
+pub struct Poseidon128_256();
+
+impl Hasher for Poseidon128_256 {
+    type Digest = ElementDigest;
+
+    const COLLISION_RESISTANCE: u32 = 128;
+
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Poseidon128_256);
+
+    fn hash(bytes: &[u8]) -> Self::Digest {
+        // compute the number of elements required to represent the string; we will be processing
+        // the string in 15-byte chunks, thus the number of elements will be equal to the number
+        // of such chunks (including a potential partial chunk at the end).
+        let num_elements = if bytes.len() % 15 == 0 {
+            bytes.len() / 15
+        } else {
+            bytes.len() / 15 + 1
+        };
+
+        // initialize state to all zeros, except for the first element of the capacity part, which
+        // is set to the number of elements to be hashed. this is done so that adding zero elements
+        // at the end of the list always results in a different hash.
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(num_elements as u128);
+
+        // break the string into 15-byte chunks, convert each chunk into a field element, and
+        // absorb the element into the rate portion of the state. we use 15-byte chunks because
+        // every 15-byte chunk is guaranteed to map to some field element.
+        let mut i = 0;
+        let mut buf = [0_u8; 16];
+        for chunk in bytes.chunks(15) {
+            if i < num_elements - 1 {
+                buf[..15].copy_from_slice(chunk);
+            } else {
+                // if we are dealing with the last chunk, it may be smaller than 7 bytes long, so
+                // we need to handle it slightly differently. we also append a byte with value 1
+                // to the end of the string; this pads the string in such a way that adding
+                // trailing zeros results in different hash
+                let chunk_len = chunk.len();
+                buf = [0_u8; 16];
+                buf[..chunk_len].copy_from_slice(chunk);
+                buf[chunk_len] = 1;
+            }
+
+            // convert the bytes into a field element and absorb it into the rate portion of the
+            // state; if the rate is filled up, apply the Poseidon permutation and start absorbing
+            // again from zero index.
+            state[RATE_RANGE.start + i] += BaseElement::new(u128::from_le_bytes(buf));
+            i += 1;
+            if i % RATE_WIDTH == 0 {
+                Self::apply_permutation(&mut state);
+                i = 0;
+            }
+        }
+
+        // if we absorbed some elements but didn't apply a permutation to them (would happen when
+        // the number of elements is not a multiple of RATE_WIDTH), apply the Poseidon permutation.
+        // we don't need to apply any extra padding because we injected total number of elements
+        // in the input list into the capacity portion of the state during initialization.
+        if i > 0 {
+            Self::apply_permutation(&mut state);
+        }
+
+        // return the first 2 elements of the rate portion of the state as hash result
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
+        // initialize the state by copying the digest elements into the rate portion of the state
+        // (4 total elements), and set the first capacity element to 4 (the number of elements to
+        // be hashed).
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[RATE_RANGE.start..RATE_RANGE.start + DIGEST_SIZE]
+            .copy_from_slice(values[0].as_elements());
+        state[RATE_RANGE.start + DIGEST_SIZE..RATE_RANGE.end]
+            .copy_from_slice(values[1].as_elements());
+        state[CAPACITY_RANGE.start] = BaseElement::new(RATE_WIDTH as u128);
+
+        // apply the Poseidon permutation and return the first two elements of the rate
+        Self::apply_permutation(&mut state);
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn merge_many(values: &[Self::Digest]) -> Self::Digest {
+        // digests consist of base field elements, and thus, can be hashed as a sequence of
+        // elements without going through their byte encoding
+        #[cfg(not(feature = "safe_only"))]
+        let elements = ElementDigest::digests_as_elements(values);
+        #[cfg(feature = "safe_only")]
+        let elements = &values
+            .iter()
+            .flat_map(|digest| digest.as_elements().iter().copied())
+            .collect::<Vec<_>>();
+        Self::hash_elements(elements)
+    }
+
+    fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
+        // initialize the state as follows:
+        // - seed is copied into the first 2 elements of the rate portion of the state.
+        // - the value always fits into a single field element; copy it into the third rate
+        //   element and set the first capacity element to 3 (the number of elements to be hashed).
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[INPUT1_RANGE].copy_from_slice(seed.as_elements());
+        state[INPUT2_RANGE.start] = BaseElement::new(value as u128);
+        state[CAPACITY_RANGE.start] = BaseElement::new(DIGEST_SIZE as u128 + 1);
+
+        // apply the Poseidon permutation and return the first two elements of the rate
+        Self::apply_permutation(&mut state);
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+}
+
+impl ElementHasher for Poseidon128_256 {
+    type BaseField = BaseElement;
+    type State = SpongeState<BaseElement, STATE_WIDTH>;
+
+    fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // convert the elements into a list of base field elements
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = E::to_base_elements(elements);
+
+        // initialize state to all zeros, except for the first element of the capacity part, which
+        // is set to the number of elements to be hashed. this is done so that adding zero elements
+        // at the end of the list always results in a different hash.
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(elements.len() as u128);
+
+        // absorb elements into the state one by one until the rate portion of the state is filled
+        // up; then apply the Poseidon permutation and start absorbing again; repeat until all
+        // elements have been absorbed
+        let mut i = 0;
+        for &element in elements.iter() {
+            state[RATE_RANGE.start + i] += element;
+            i += 1;
+            if i % RATE_WIDTH == 0 {
+                Self::apply_permutation(&mut state);
+                i = 0;
+            }
+        }
+
+        // if we absorbed some elements but didn't apply a permutation to them (would happen when
+        // the number of elements is not a multiple of RATE_WIDTH), apply the Poseidon permutation.
+        // we don't need to apply any extra padding because we injected total number of elements
+        // in the input list into the capacity portion of the state during initialization.
+        if i > 0 {
+            Self::apply_permutation(&mut state);
+        }
+
+        // return the first 2 elements of the rate portion of the state as hash result
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn begin(num_elements: usize) -> Self::State {
+        // the number of elements to be hashed is injected into the capacity portion of the state
+        // in the same way as in hash_elements()
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(num_elements as u128);
+        SpongeState::new(state)
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = &E::to_base_elements(elements);
+
+        state.absorb(elements, RATE_RANGE, Self::apply_permutation);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        let (mut state, num_pending) = state.into_parts();
+        if num_pending > 0 {
+            Self::apply_permutation(&mut state);
+        }
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn digest_as_elements(digest: &Self::Digest) -> Option<&[Self::BaseField]> {
+        Some(digest.as_elements())
+    }
+}
+
+// HASH FUNCTION IMPLEMENTATION
+// ================================================================================================
+
+impl Poseidon128_256 {
+    // CONSTANTS
+    // --------------------------------------------------------------------------------------------
+
+    /// The number of full rounds; half of the full rounds are applied before the partial rounds,
+    /// and the other half is applied after them.
+    pub const NUM_FULL_ROUNDS: usize = NUM_FULL_ROUNDS;
+
+    /// The number of partial rounds.
+    pub const NUM_PARTIAL_ROUNDS: usize = NUM_PARTIAL_ROUNDS;
+
+    /// Sponge state is set to 6 field elements or 96 bytes; 4 elements are reserved for rate and
+    /// the remaining 2 elements are reserved for capacity.
+    pub const STATE_WIDTH: usize = STATE_WIDTH;
+
+    /// The rate portion of the state is located in elements 2 through 5 (inclusive).
+    pub const RATE_RANGE: Range<usize> = RATE_RANGE;
+
+    /// The capacity portion of the state is located in elements 0 and 1.
+    pub const CAPACITY_RANGE: Range<usize> = CAPACITY_RANGE;
+
+    /// The output of the hash function can be read from state elements 2 and 3.
+    pub const DIGEST_RANGE: Range<usize> = DIGEST_RANGE;
+
+    /// MDS matrix used for computing the linear layer in a Poseidon round.
+    pub const MDS: [[BaseElement; STATE_WIDTH]; STATE_WIDTH] = MDS;
+
+    /// Round constants added to the hasher state at the beginning of every Poseidon round.
+    pub const ARK: [[BaseElement; STATE_WIDTH]; NUM_ROUNDS] = ARK;
+
+    // POSEIDON PERMUTATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Applies Poseidon permutation to the provided state.
+    pub fn apply_permutation(state: &mut [BaseElement; STATE_WIDTH]) {
+        for round in 0..NUM_ROUNDS {
+            Self::apply_round(state, round);
+        }
+    }
+
+    /// Poseidon round function; the S-Box is applied to all state elements in full rounds, and
+    /// only to the first state element in partial rounds.
+    #[inline(always)]
+    pub fn apply_round(state: &mut [BaseElement; STATE_WIDTH], round: usize) {
+        Self::add_constants(state, &ARK[round]);
+        if Self::is_full_round(round) {
+            Self::apply_sbox(state);
+        } else {
+            state[0] = state[0].cube();
+        }
+        Self::apply_mds(state);
+    }
+
+    /// Returns true if the round with the specified index is a full round.
+    pub const fn is_full_round(round: usize) -> bool {
+        round < NUM_FULL_ROUNDS / 2 || round >= NUM_FULL_ROUNDS / 2 + NUM_PARTIAL_ROUNDS
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+
+    #[inline(always)]
+    fn apply_mds(state: &mut [BaseElement; STATE_WIDTH]) {
+        let mut result = [BaseElement::ZERO; STATE_WIDTH];
+        result.iter_mut().zip(MDS).for_each(|(r, mds_row)| {
+            state.iter().zip(mds_row).for_each(|(&s, m)| *r += m * s);
+        });
+        *state = result;
+    }
+
+    #[inline(always)]
+    fn add_constants(state: &mut [BaseElement; STATE_WIDTH], ark: &[BaseElement; STATE_WIDTH]) {
+        state.iter_mut().zip(ark).for_each(|(s, &k)| *s += k);
+    }
+
+    #[inline(always)]
+    fn apply_sbox(state: &mut [BaseElement; STATE_WIDTH]) {
+        state.iter_mut().for_each(|s| *s = s.cube());
+    }
+}
+
+// MDS
+// ================================================================================================
+/// Poseidon MDS matrix; this is a Cauchy matrix with entries 1 / (x_i + y_j), where x_i = i and
+/// y_j = 6 + j.
+const MDS: [[BaseElement; STATE_WIDTH]; STATE_WIDTH] = [
+    [
+        BaseElement::new(56713727820156410577229092992290826923),
+        BaseElement::new(48611766702991209066196365421963565934),
+        BaseElement::new(297747071055821155530452738209526841345),
+        BaseElement::new(264664063160729916027069100630690525640),
+        BaseElement::new(238197656844656924424362190567621473076),
+        BaseElement::new(216543324404233567658511082334201339160),
+    ],
+    [
+        BaseElement::new(48611766702991209066196365421963565934),
+        BaseElement::new(297747071055821155530452738209526841345),
+        BaseElement::new(264664063160729916027069100630690525640),
+        BaseElement::new(238197656844656924424362190567621473076),
+        BaseElement::new(216543324404233567658511082334201339160),
+        BaseElement::new(198498047370547437020301825473017894230),
+    ],
+    [
+        BaseElement::new(297747071055821155530452738209526841345),
+        BaseElement::new(264664063160729916027069100630690525640),
+        BaseElement::new(238197656844656924424362190567621473076),
+        BaseElement::new(216543324404233567658511082334201339160),
+        BaseElement::new(198498047370547437020301825473017894230),
+        BaseElement::new(130877833431130178255144060751440369822),
+    ],
+    [
+        BaseElement::new(264664063160729916027069100630690525640),
+        BaseElement::new(238197656844656924424362190567621473076),
+        BaseElement::new(216543324404233567658511082334201339160),
+        BaseElement::new(198498047370547437020301825473017894230),
+        BaseElement::new(130877833431130178255144060751440369822),
+        BaseElement::new(24305883351495604533098182710981782967),
+    ],
+    [
+        BaseElement::new(238197656844656924424362190567621473076),
+        BaseElement::new(216543324404233567658511082334201339160),
+        BaseElement::new(198498047370547437020301825473017894230),
+        BaseElement::new(130877833431130178255144060751440369822),
+        BaseElement::new(24305883351495604533098182710981782967),
+        BaseElement::new(158798437896437949616241460378414315384),
+    ],
+    [
+        BaseElement::new(216543324404233567658511082334201339160),
+        BaseElement::new(198498047370547437020301825473017894230),
+        BaseElement::new(130877833431130178255144060751440369822),
+        BaseElement::new(24305883351495604533098182710981782967),
+        BaseElement::new(158798437896437949616241460378414315384),
+        BaseElement::new(319014718988379809496913648081635901441),
+    ],
+];
+
+// ROUND CONSTANTS
+// ================================================================================================
+
+/// Round constants added to the hasher state at the beginning of every Poseidon round.
+///
+/// The constants are generated using the Grain LFSR in self-shrinking mode as specified in the
+/// reference implementation (`generate_parameters_grain.sage`) with the following parameters:
+/// field = 1 (prime field), s-box = 0 (x^alpha), field size = 128, state width = 6, number of full
+/// rounds = 8, number of partial rounds = 84.
+const ARK: [[BaseElement; STATE_WIDTH]; NUM_ROUNDS] = [
+    [
+        BaseElement::new(64288233653144374583442648670172416818),
+        BaseElement::new(288012076730271592404352263030120354813),
+        BaseElement::new(325047146703517166873052872364787133330),
+        BaseElement::new(247522553163194635212949435142184514823),
+        BaseElement::new(34577260838214133574233405952069804975),
+        BaseElement::new(105882134945192447350736876591197551465),
+    ],
+    [
+        BaseElement::new(139696877730346376880995578512477292551),
+        BaseElement::new(82831653250027923412931258444328278567),
+        BaseElement::new(113957422993561688093890972853764306811),
+        BaseElement::new(262129439616977059361753601024706995717),
+        BaseElement::new(180099564122601851194554386962917103631),
+        BaseElement::new(167361694566092106602120335247900790593),
+    ],
+    [
+        BaseElement::new(220742816184106007034210655180011695562),
+        BaseElement::new(338466955766210005737914023033759873807),
+        BaseElement::new(162209281505386665012539495397038885841),
+        BaseElement::new(92221318663116468304024445288842574504),
+        BaseElement::new(285276713832258437641752846153350549010),
+        BaseElement::new(301367664021251777824548424536845586647),
+    ],
+    [
+        BaseElement::new(163897448498513483541601564471858332998),
+        BaseElement::new(147757319808458268289391419161233597316),
+        BaseElement::new(185386497198625770746894774910062212051),
+        BaseElement::new(71619161484850058473082074738399227542),
+        BaseElement::new(112195892258511003115451943492475684593),
+        BaseElement::new(123790149730646155141760072306934042132),
+    ],
+    [
+        BaseElement::new(277523320639062938588001733206959614373),
+        BaseElement::new(139648332082498398743821877529786399144),
+        BaseElement::new(89035923547578123255782669345280164804),
+        BaseElement::new(21998522558035249193628983649002907655),
+        BaseElement::new(248524281680400619862651711311407853119),
+        BaseElement::new(147728353182886435347569305709467788454),
+    ],
+    [
+        BaseElement::new(136508572119074061346078985629362948569),
+        BaseElement::new(109634519222987303091593820912529491864),
+        BaseElement::new(64320508278576850414527308098261656424),
+        BaseElement::new(185129513468489581376322456101581203966),
+        BaseElement::new(211702349489455618340638696528738553344),
+        BaseElement::new(294594570437243079515371109374565062450),
+    ],
+    [
+        BaseElement::new(283005719051514372862781472058295950740),
+        BaseElement::new(59640989240746611962565592022334780571),
+        BaseElement::new(92625585499122279160191751002937256566),
+        BaseElement::new(165911829083136823666304313281082357828),
+        BaseElement::new(80176610232290873102103054663485765909),
+        BaseElement::new(181743097786399932768554401503034402848),
+    ],
+    [
+        BaseElement::new(275177973294756557130145722684487373265),
+        BaseElement::new(305715632217296064251993418511397649351),
+        BaseElement::new(208361652323874791897048409572995735367),
+        BaseElement::new(71671275142012384136566946154516777634),
+        BaseElement::new(304536311794638581237123993835817097388),
+        BaseElement::new(215765289476023478193762654871913452714),
+    ],
+    [
+        BaseElement::new(240566636316227648915348718930430167304),
+        BaseElement::new(261384249943359975250806338199477320900),
+        BaseElement::new(331325017658705651548111168623799863755),
+        BaseElement::new(313262306173762264715174000577522969808),
+        BaseElement::new(333112203426328597273679644434159908952),
+        BaseElement::new(4798939768095130343257903531925243169),
+    ],
+    [
+        BaseElement::new(223451190233654484831079318880306677728),
+        BaseElement::new(183764694537429361753557526197803382880),
+        BaseElement::new(192122065407137248419768421903792330071),
+        BaseElement::new(281107402400303465057742404874318321994),
+        BaseElement::new(186143611756513482611018894799844930361),
+        BaseElement::new(53832459510943230222660292138566015918),
+    ],
+    [
+        BaseElement::new(137769931467087308330655337904653098),
+        BaseElement::new(247205081756846874609593140692361393411),
+        BaseElement::new(180872768763713682019092349954444807989),
+        BaseElement::new(73577310623070176866288275056823574377),
+        BaseElement::new(261213141257819807437861414153529518532),
+        BaseElement::new(160174252449500136670398936842123088863),
+    ],
+    [
+        BaseElement::new(108764571024556361238715518092260626069),
+        BaseElement::new(258429088363192913851753309715379268171),
+        BaseElement::new(91741861151267222420579161346317565942),
+        BaseElement::new(131136081805546331982417395996118202962),
+        BaseElement::new(206841959348361550582402316575018473011),
+        BaseElement::new(157152223151748486832371570217258055922),
+    ],
+    [
+        BaseElement::new(161753804759822819629643748298362743669),
+        BaseElement::new(269799803091553899531884264709449242892),
+        BaseElement::new(220967895309986821956852988420495560697),
+        BaseElement::new(125366093107261892767105681157646711910),
+        BaseElement::new(151066809770859781021567435129825039184),
+        BaseElement::new(232164599722894675373096620846643207471),
+    ],
+    [
+        BaseElement::new(273205810946827784100264923273903217456),
+        BaseElement::new(339398155618178700627813578910541755134),
+        BaseElement::new(192142664199963795696433265259259631872),
+        BaseElement::new(336436384332461352915736295808037885530),
+        BaseElement::new(61483695323383671020849424155829462979),
+        BaseElement::new(192376246773526133584372811819327955629),
+    ],
+    [
+        BaseElement::new(200882859264635599322869895268800569268),
+        BaseElement::new(29863728136837294643886473832644998663),
+        BaseElement::new(282473127363186619708949620782149202740),
+        BaseElement::new(244809515649663603909273508263702680571),
+        BaseElement::new(167176191398705435525781487326447913644),
+        BaseElement::new(65473556494810572923218293377227588231),
+    ],
+    [
+        BaseElement::new(140752595282279365831632206827244185853),
+        BaseElement::new(199585023380357228142984099393799459575),
+        BaseElement::new(174612824601757096237453441336027094211),
+        BaseElement::new(335748459284507443625928363003172577390),
+        BaseElement::new(230823805417845113276536251234068151623),
+        BaseElement::new(333588699987100828566329851939906989391),
+    ],
+    [
+        BaseElement::new(223455023569065408961415982689026662117),
+        BaseElement::new(130944668526792547602437485163496978426),
+        BaseElement::new(156250879752581183076161023714233892815),
+        BaseElement::new(8139747512460739008269276788428073362),
+        BaseElement::new(177064347392382251564461691105903038468),
+        BaseElement::new(111693841451710514040351422944015115747),
+    ],
+    [
+        BaseElement::new(241017448679960344963415604812021150092),
+        BaseElement::new(97121222674798821770038703355024217736),
+        BaseElement::new(80445358471494704190246439738867390807),
+        BaseElement::new(18688931455204881321546766415043342507),
+        BaseElement::new(234166566672591069129651008607827530961),
+        BaseElement::new(13547147197356390745516970424860482180),
+    ],
+    [
+        BaseElement::new(142229118417514302194567430633627114755),
+        BaseElement::new(58044571621637769176150998911144524038),
+        BaseElement::new(84486989238284228317385622488181690259),
+        BaseElement::new(28759830777563658435694973837964489446),
+        BaseElement::new(111209268576594048329198493406761765715),
+        BaseElement::new(215389580628648400749919599473720925609),
+    ],
+    [
+        BaseElement::new(22110979748353193984796664127537601756),
+        BaseElement::new(84950969932702556966362913638386077335),
+        BaseElement::new(156528480731582470483385608231512614141),
+        BaseElement::new(298022020724399507074192488543080906582),
+        BaseElement::new(274414593413695644253589827608789359031),
+        BaseElement::new(147673153458381548949201674404060281120),
+    ],
+    [
+        BaseElement::new(133307705836678022152812108494554206333),
+        BaseElement::new(224954379642121624600200337969448268674),
+        BaseElement::new(165559970570433895733158495083825985001),
+        BaseElement::new(60217690806906120383743286863779848523),
+        BaseElement::new(174052268913731775570223280919176717741),
+        BaseElement::new(98021422025765339281517452566997889175),
+    ],
+    [
+        BaseElement::new(58546950257049948572032097934750793889),
+        BaseElement::new(73964431045270271401850709522061298899),
+        BaseElement::new(13590679463582011852077376785241938792),
+        BaseElement::new(263984405917434456985761716361599563692),
+        BaseElement::new(10686746169316173757147953667998105795),
+        BaseElement::new(262180208480468384452916267570195690372),
+    ],
+    [
+        BaseElement::new(294138236992107824693264830019557057718),
+        BaseElement::new(174276148259889791519175108986657905658),
+        BaseElement::new(126560763675539111405720000463646580062),
+        BaseElement::new(248266570061951309785317263864876248011),
+        BaseElement::new(124199475480439212657565910337725796933),
+        BaseElement::new(129550468141569616517798637300677126823),
+    ],
+    [
+        BaseElement::new(140913756463029639757920352541421871821),
+        BaseElement::new(289360707053513519324217231121582106536),
+        BaseElement::new(65667057128460348363195882488713509087),
+        BaseElement::new(246801459621616154379127230899999055232),
+        BaseElement::new(113927376959779753409976257764118165677),
+        BaseElement::new(147513562385810241227580646975067174941),
+    ],
+    [
+        BaseElement::new(239381300331728243189425477178751559493),
+        BaseElement::new(46912882248058699284073520315238183469),
+        BaseElement::new(196515959191269501698806347855352264674),
+        BaseElement::new(113259529079434447853297517102247757690),
+        BaseElement::new(287149859255544981112763987813670836768),
+        BaseElement::new(285379152638435863723731787584353725478),
+    ],
+    [
+        BaseElement::new(333539826959733781219762567654504050710),
+        BaseElement::new(120001905372508205435942645184381045927),
+        BaseElement::new(14589432422302953711917354858010737268),
+        BaseElement::new(125219125987357603677986319081257168457),
+        BaseElement::new(171811051091899962443700316521984216248),
+        BaseElement::new(336917438220773614118395719846680805786),
+    ],
+    [
+        BaseElement::new(297403243633670446112224690006900705837),
+        BaseElement::new(79405587700821684668655209824445346845),
+        BaseElement::new(58548310763849785459275726828158650162),
+        BaseElement::new(44123787317044335632153748205416874012),
+        BaseElement::new(183014199109384905239054490637960603603),
+        BaseElement::new(248860154229617493058883456492797357159),
+    ],
+    [
+        BaseElement::new(317627628444241475792889909970258318746),
+        BaseElement::new(160849082092481343631692149135543285619),
+        BaseElement::new(335439876487299090655797984685012121351),
+        BaseElement::new(106999071674345712891152688445662884781),
+        BaseElement::new(84916659610326910734145656595674532022),
+        BaseElement::new(291007076515420243308815067555540526044),
+    ],
+    [
+        BaseElement::new(269815119068086307088936435586691675963),
+        BaseElement::new(303506946783278319430277729786443085172),
+        BaseElement::new(209394647166817013908843396365822429444),
+        BaseElement::new(191721932540687890982259583927840410219),
+        BaseElement::new(263196049065051165915932730218988790726),
+        BaseElement::new(102234892522934676199488511942051506943),
+    ],
+    [
+        BaseElement::new(94951693409541850289304773329131294470),
+        BaseElement::new(140954084107657510179428955242456038206),
+        BaseElement::new(128849323596285098561536665425473708108),
+        BaseElement::new(264953453746689957866363580248853469567),
+        BaseElement::new(269915349137430862332568140659424343582),
+        BaseElement::new(11795228834209958887687699028512112912),
+    ],
+    [
+        BaseElement::new(273890364111093248671628912108916061215),
+        BaseElement::new(139554120871069957001418185065857554746),
+        BaseElement::new(54654756153808497345923935747076672269),
+        BaseElement::new(125905277027690045969807119844148156910),
+        BaseElement::new(199254829109086559591985404780144921798),
+        BaseElement::new(93331129064001323385355682861202889643),
+    ],
+    [
+        BaseElement::new(9050973571496130031319083113585990499),
+        BaseElement::new(22204057791934063046346748616728413153),
+        BaseElement::new(104159471324804868148276016919221133519),
+        BaseElement::new(67366949297067802155077464366643798677),
+        BaseElement::new(314996093084507855451404469336950282627),
+        BaseElement::new(286861998345697118713406771182340953517),
+    ],
+    [
+        BaseElement::new(267613817370523893764368241535491363209),
+        BaseElement::new(122219308306817783944683497997159213792),
+        BaseElement::new(274288334691865550143142145817620277614),
+        BaseElement::new(151863713622646503746430773862537030974),
+        BaseElement::new(118520405133193858235859340340494506536),
+        BaseElement::new(210381041921290420141425313869566828558),
+    ],
+    [
+        BaseElement::new(38052582692195594564491999619935804414),
+        BaseElement::new(11010889699138929369372392569321950982),
+        BaseElement::new(103851936799972520232811964126204710052),
+        BaseElement::new(259893717879532587748443796481229139024),
+        BaseElement::new(104680334806720577517492343525893719722),
+        BaseElement::new(243780802680679311105001474497466671104),
+    ],
+    [
+        BaseElement::new(89798717512678925109248915472368580129),
+        BaseElement::new(253803939731000086305239025768738190409),
+        BaseElement::new(318563379140368422276683442256945204595),
+        BaseElement::new(336657069924782307025359654643899825107),
+        BaseElement::new(4672512201070796963287486473181702479),
+        BaseElement::new(22951738589531306917898475023620314118),
+    ],
+    [
+        BaseElement::new(75107356809186026692633931152051847964),
+        BaseElement::new(141722076375972228770204578504072133791),
+        BaseElement::new(58643980527415151588202952024845572908),
+        BaseElement::new(313744917922243638501411340100483378553),
+        BaseElement::new(332395646468919138098102421943661374433),
+        BaseElement::new(251067342380124599787473339633441626076),
+    ],
+    [
+        BaseElement::new(272812379186973450178375848642731527795),
+        BaseElement::new(22523458441109176467092935634194332721),
+        BaseElement::new(167183913585103550076449820436786110211),
+        BaseElement::new(273269661823372893244522361955275658646),
+        BaseElement::new(161936687600889891173160527309008155165),
+        BaseElement::new(25200457947978814598361345643342654561),
+    ],
+    [
+        BaseElement::new(6347628137032295445887256620197286958),
+        BaseElement::new(110019414005791029084696543231835004290),
+        BaseElement::new(315157711760209997443837189267019652253),
+        BaseElement::new(108061029803636691524432884400276747089),
+        BaseElement::new(143861771717846530713356880800872063465),
+        BaseElement::new(252173193790575230876271258887355165726),
+    ],
+    [
+        BaseElement::new(245515379025662717700360579011061088285),
+        BaseElement::new(223039718135162992189575816608401457944),
+        BaseElement::new(279228217070740545046350944099088954884),
+        BaseElement::new(265640717008526559341157544466887808248),
+        BaseElement::new(277578496510127595216342492944719018252),
+        BaseElement::new(21298221251581251174567181538709136922),
+    ],
+    [
+        BaseElement::new(187838392771570457909400412705891619345),
+        BaseElement::new(76379595051937267902534832551987428944),
+        BaseElement::new(290647243809037968480388208654747032653),
+        BaseElement::new(52846434428175365735249413343301780482),
+        BaseElement::new(204974258912840416254922716653866946714),
+        BaseElement::new(223799501721350967492757286280245638786),
+    ],
+    [
+        BaseElement::new(222944335783765683052054283912412078242),
+        BaseElement::new(219233633504034555644806084284084910834),
+        BaseElement::new(93933220633754341025387203112172520709),
+        BaseElement::new(130383490964659220196090842176001673107),
+        BaseElement::new(58597249518266897767825198004313377448),
+        BaseElement::new(194939068645778929936105903237858573663),
+    ],
+    [
+        BaseElement::new(86926453393501260033382614080766430782),
+        BaseElement::new(90572499361201858757960652493248027038),
+        BaseElement::new(164726323226100818798477951209625512591),
+        BaseElement::new(313390861755137804103134349404547018579),
+        BaseElement::new(196116691561427636393238999096421930388),
+        BaseElement::new(273106876783513494059101538907043104979),
+    ],
+    [
+        BaseElement::new(233085270064872588676419565126341311082),
+        BaseElement::new(177209648204697855095189245111355001253),
+        BaseElement::new(112118632183144882919325070533731315187),
+        BaseElement::new(291657628555738988782169309490697537738),
+        BaseElement::new(250243788692136308749087753520102506358),
+        BaseElement::new(234542568643108589822966719098869982090),
+    ],
+    [
+        BaseElement::new(289341765882225721192223058100259957672),
+        BaseElement::new(6178658499040097345560650163683225595),
+        BaseElement::new(130900008622657086541052277085494071927),
+        BaseElement::new(101536960251684316872926868497907902019),
+        BaseElement::new(319837525268270318050180793379537567823),
+        BaseElement::new(39793176344845685662005891646297214478),
+    ],
+    [
+        BaseElement::new(49820572185936691498917481353777371800),
+        BaseElement::new(93226109953433241207285187141394467711),
+        BaseElement::new(144012225700319918399999767381545252928),
+        BaseElement::new(6664712126407118816185578381360336064),
+        BaseElement::new(18677643282636396306858343309174032506),
+        BaseElement::new(287024023254504833326187979220247803395),
+    ],
+    [
+        BaseElement::new(9870435969107479098068090078470954291),
+        BaseElement::new(142604821611077677728236291052609921547),
+        BaseElement::new(93649823556938489943066305927872258195),
+        BaseElement::new(71075419155989935944558316859900493171),
+        BaseElement::new(306799849764107550063326754027033897660),
+        BaseElement::new(226475491781843448845151897414518465993),
+    ],
+    [
+        BaseElement::new(132065788710716290069628877926649960162),
+        BaseElement::new(125605409052686286132633350711136191649),
+        BaseElement::new(333522315004538057371408397209399755843),
+        BaseElement::new(195760940259487309483838935156694233199),
+        BaseElement::new(233800934857554504584475858129219932735),
+        BaseElement::new(288719949287399220704923989007279087428),
+    ],
+    [
+        BaseElement::new(65019354848784080566301424651913800553),
+        BaseElement::new(190197181425624592227101197914638911841),
+        BaseElement::new(70596842702421881169864809023698283358),
+        BaseElement::new(32822080528679603141893672010198248450),
+        BaseElement::new(264546657861141892008995270663752238955),
+        BaseElement::new(278558317451707706706865359781749920152),
+    ],
+    [
+        BaseElement::new(136264449519067871048892459709931150962),
+        BaseElement::new(99449307663926994801107828974606412339),
+        BaseElement::new(56265823368028772373348296235771525126),
+        BaseElement::new(283198780127932509782107727998467555715),
+        BaseElement::new(81681406810622365893840419501550408644),
+        BaseElement::new(85084622007530544092559497977395839817),
+    ],
+    [
+        BaseElement::new(80876930692027534771677829140175076579),
+        BaseElement::new(49874167525887816490670968964310145875),
+        BaseElement::new(271517454671616862888590409407169940471),
+        BaseElement::new(8067521468614910105022281884629559190),
+        BaseElement::new(70912058863728427524115600100653154714),
+        BaseElement::new(18713878045043088321613014343584727058),
+    ],
+    [
+        BaseElement::new(46402021516919938067142233066717274999),
+        BaseElement::new(202804599986943624343427063428671883824),
+        BaseElement::new(106943313965709159059506884668504900639),
+        BaseElement::new(173772346853102208787368516936086384616),
+        BaseElement::new(57293262443034009293643034830014938115),
+        BaseElement::new(280876923533736471773739393604990294905),
+    ],
+    [
+        BaseElement::new(109736134927838108434681745226440597088),
+        BaseElement::new(155869511243371043114040534328120891621),
+        BaseElement::new(208466323249314388592575244213691512864),
+        BaseElement::new(205558293877832473140598836148334684293),
+        BaseElement::new(51440471418825674009952140701648699635),
+        BaseElement::new(107514677180014924912245734233441673986),
+    ],
+    [
+        BaseElement::new(56552424990808770610773347287987178198),
+        BaseElement::new(286133087872769461021894144651764551810),
+        BaseElement::new(55177240400478254146745260675569399254),
+        BaseElement::new(118568395082636011488386345270646069825),
+        BaseElement::new(162605683515822575878491329900709166441),
+        BaseElement::new(122203318544044277924781292820401239876),
+    ],
+    [
+        BaseElement::new(257145963065615431059859713413319363147),
+        BaseElement::new(92421541903773627259500163728183374094),
+        BaseElement::new(179338802171978105891679765277282995594),
+        BaseElement::new(202152135166458291308357530046290280224),
+        BaseElement::new(325449969832966741105614218985605542370),
+        BaseElement::new(231947202488219010421737403991388766477),
+    ],
+    [
+        BaseElement::new(206107153484090854755091894140766642830),
+        BaseElement::new(315737143119311548776019278283719357832),
+        BaseElement::new(226690927041146308143948679228361666341),
+        BaseElement::new(274733397130245335950611331520139103357),
+        BaseElement::new(340151273595583814417697799062059227499),
+        BaseElement::new(2810651706377247083788001906606633161),
+    ],
+    [
+        BaseElement::new(208215154993759169651223552802649120663),
+        BaseElement::new(273005439359628068618089817643240638721),
+        BaseElement::new(8322567717383488118195356398663667616),
+        BaseElement::new(11540602533649239090906805301920075244),
+        BaseElement::new(291134329581774582465108149717597204027),
+        BaseElement::new(154053026364100433616487854804063525810),
+    ],
+    [
+        BaseElement::new(287273521025022811171832573238999609183),
+        BaseElement::new(155500219854862758947055391311084321749),
+        BaseElement::new(241902361070156789069327993762296483672),
+        BaseElement::new(320680790900872441398052627970398544051),
+        BaseElement::new(119470093035881589291605571478741745936),
+        BaseElement::new(302914033389913673840674027592831803378),
+    ],
+    [
+        BaseElement::new(279012207983214784071743169337062030230),
+        BaseElement::new(307210252837688712859712298829426937347),
+        BaseElement::new(339601941514380755273259798646643919106),
+        BaseElement::new(257562890636389725954952448978783851254),
+        BaseElement::new(321475739181824994627970797811356451056),
+        BaseElement::new(56585679546619784711551843149698853686),
+    ],
+    [
+        BaseElement::new(26364942192058493776524902299662931287),
+        BaseElement::new(22496660754250192229504373454858167601),
+        BaseElement::new(143387857170825338894662184109117430313),
+        BaseElement::new(256459602793454113108295564380790715529),
+        BaseElement::new(244433727415267866678774471579599107538),
+        BaseElement::new(148293427814668923087174251669566023835),
+    ],
+    [
+        BaseElement::new(84537642969937025199344077007129714219),
+        BaseElement::new(192058475484040747223725055632647420470),
+        BaseElement::new(282353341979142689426262456178685186135),
+        BaseElement::new(224303718106181112823158769135317648582),
+        BaseElement::new(275941448815971853445440648743184960207),
+        BaseElement::new(199990735077108102010440195805678435034),
+    ],
+    [
+        BaseElement::new(266529573258692162356802160007611670813),
+        BaseElement::new(71550717257480967946854043960001878645),
+        BaseElement::new(116148464755893225407694150505485344249),
+        BaseElement::new(267159186138558033479136557566020283792),
+        BaseElement::new(44322709878996642912269854705161866447),
+        BaseElement::new(158495535613995388289525711043452074480),
+    ],
+    [
+        BaseElement::new(262863631855733971794317250972251948965),
+        BaseElement::new(66467210593098563314157589904420874152),
+        BaseElement::new(68810339455151639858718589368281878502),
+        BaseElement::new(218416969853705360957220055851796191073),
+        BaseElement::new(180284494910899810811802750817959565024),
+        BaseElement::new(166959043641342190305672893926801077533),
+    ],
+    [
+        BaseElement::new(16436033540646381197458309536740797733),
+        BaseElement::new(151183373450997305449035006183949362752),
+        BaseElement::new(259553781211513081666397175866563915776),
+        BaseElement::new(247662012849712249117495476412288222031),
+        BaseElement::new(309750398946625633472100270967438366130),
+        BaseElement::new(229643082207395540480569335990897755031),
+    ],
+    [
+        BaseElement::new(191710148498641269854448345242796873008),
+        BaseElement::new(194327397284962954224302171584781885125),
+        BaseElement::new(209195466381625805965277977529171882484),
+        BaseElement::new(6742406705410373024641205792379227394),
+        BaseElement::new(316827046090790945043921701772899556653),
+        BaseElement::new(237545736911709419875837771551940321737),
+    ],
+    [
+        BaseElement::new(168490132116387842056076799847843601817),
+        BaseElement::new(239631797179696496906928639091170473644),
+        BaseElement::new(35142783716244096235524105006795079981),
+        BaseElement::new(228584487427567058878259945950919441423),
+        BaseElement::new(59365717722878265547167278958992501532),
+        BaseElement::new(233907027804317598036768659191372771437),
+    ],
+    [
+        BaseElement::new(309397825386092340800739415225339382126),
+        BaseElement::new(317217467418700607935502184021921474814),
+        BaseElement::new(137433564598250309815175094974728540446),
+        BaseElement::new(84988286586385277973851261392811402601),
+        BaseElement::new(202470880303687583482906928713140923214),
+        BaseElement::new(312987743991054668467128654043293627867),
+    ],
+    [
+        BaseElement::new(313557078287438347842886112448897679726),
+        BaseElement::new(225045140013942325684296630855289868880),
+        BaseElement::new(334827890634775318958202691083056662013),
+        BaseElement::new(333725921267884564812167490388350503008),
+        BaseElement::new(333505926137933766815225495311165108489),
+        BaseElement::new(286205244851085713139746527619985064482),
+    ],
+    [
+        BaseElement::new(201417382014975775418756082625855211031),
+        BaseElement::new(156322971503169079453432258321686311421),
+        BaseElement::new(128817893574133328379281879677360207580),
+        BaseElement::new(146707283796735022846062008679886409983),
+        BaseElement::new(211336554438017943770602317792308645768),
+        BaseElement::new(62258348085342903609988322755648596951),
+    ],
+    [
+        BaseElement::new(169228537765302760914099741915905479710),
+        BaseElement::new(161843193675847645663462118197590996738),
+        BaseElement::new(336073754566301358960851414111916270170),
+        BaseElement::new(251485021926123790270242165107509582252),
+        BaseElement::new(268313852735169875187140376246518536066),
+        BaseElement::new(202658660307014848600479364079523894499),
+    ],
+    [
+        BaseElement::new(151985527976168015357955963496863957772),
+        BaseElement::new(57832325934671559795136227681428456873),
+        BaseElement::new(309223136519974774949895998158027924339),
+        BaseElement::new(146516910188592372198547168271286778921),
+        BaseElement::new(137269303694376007869408416431602095810),
+        BaseElement::new(225074961040813691957106479998471812847),
+    ],
+    [
+        BaseElement::new(281270720112982226346674261412344601755),
+        BaseElement::new(222732582556160595391132457291757808954),
+        BaseElement::new(9922711081084451637215048750421938395),
+        BaseElement::new(273156161683997160645087807184334368344),
+        BaseElement::new(263019885422701109692694130100827824153),
+        BaseElement::new(300278364193897180011672861497564241786),
+    ],
+    [
+        BaseElement::new(70794102442595576502677674273906580523),
+        BaseElement::new(102627936212680742591011970568640075531),
+        BaseElement::new(316459729340931206057766900093005671454),
+        BaseElement::new(126697845891315218232223431338997334266),
+        BaseElement::new(130617227403038656225101380943268953468),
+        BaseElement::new(242387501394692621925435801560870637878),
+    ],
+    [
+        BaseElement::new(34700386030480154665868301384123126065),
+        BaseElement::new(34083865908781420030124697000775310637),
+        BaseElement::new(93249207112460164239535509916788291911),
+        BaseElement::new(190658334335747212139544862074063748364),
+        BaseElement::new(267281656503653752974550770772739015224),
+        BaseElement::new(205289986539048764277999520514776645209),
+    ],
+    [
+        BaseElement::new(184339051149292260562979074496689944737),
+        BaseElement::new(123257608919555029339309151589999548226),
+        BaseElement::new(44852685001442102364922523633709380037),
+        BaseElement::new(255810339208562937151535940738467312414),
+        BaseElement::new(148837925961795668807678002690862542210),
+        BaseElement::new(323530680461358500405103002012310982410),
+    ],
+    [
+        BaseElement::new(254452225864872919240070113042650859528),
+        BaseElement::new(292115164936717971984521731365888960096),
+        BaseElement::new(84241642575743833737237927348402620716),
+        BaseElement::new(150539207019937946854906055343599808633),
+        BaseElement::new(201056814230623216205943863453494757577),
+        BaseElement::new(135135244864095921986562193283894448089),
+    ],
+    [
+        BaseElement::new(166143236135515359032991850364001241844),
+        BaseElement::new(303499571640647851567702317379544313948),
+        BaseElement::new(87036735322730799937525519583328226417),
+        BaseElement::new(68706866756579367104365403154382429898),
+        BaseElement::new(37091855451745130374336408127866278806),
+        BaseElement::new(299795209521200954168748114019205888764),
+    ],
+    [
+        BaseElement::new(149915308110368716325014586046108979870),
+        BaseElement::new(339329990391993757824466628891287931985),
+        BaseElement::new(36249311389024876687497224950964935445),
+        BaseElement::new(137283123803170852525239076684406302751),
+        BaseElement::new(206518054170805256695513216396841124041),
+        BaseElement::new(19202101140366477487488536715641189634),
+    ],
+    [
+        BaseElement::new(151630327543952425107956454233499065038),
+        BaseElement::new(159016245186820897486465329490817424152),
+        BaseElement::new(227310635227420574059472825065066259336),
+        BaseElement::new(152477295623295755517887217442162973343),
+        BaseElement::new(68544105099450039806393841704520063659),
+        BaseElement::new(290780143917820148204189299754765165245),
+    ],
+    [
+        BaseElement::new(222726835823375608208903574809888324925),
+        BaseElement::new(294148682857745103297779005266530673648),
+        BaseElement::new(119843257526421300622349011743220188536),
+        BaseElement::new(186806233148893917689803613517867953293),
+        BaseElement::new(180802519834856235053091435462550795281),
+        BaseElement::new(7693744224724482658949184048576007852),
+    ],
+    [
+        BaseElement::new(19346817134045687781460166527107201512),
+        BaseElement::new(187101546505996315358973422845951133459),
+        BaseElement::new(75197672809032801373965204307935132145),
+        BaseElement::new(67509989336002612579953622126102742219),
+        BaseElement::new(202564708847692120643676661048528074684),
+        BaseElement::new(246915857664101535549024036798476059946),
+    ],
+    [
+        BaseElement::new(153887044602717752938614485084382268763),
+        BaseElement::new(155366680459952334226531739169216114236),
+        BaseElement::new(217154233504401524017651703228205327929),
+        BaseElement::new(254807600635969793112867834125742149796),
+        BaseElement::new(289066247847754256940218657147601110066),
+        BaseElement::new(222027373476601080815997588360813249342),
+    ],
+    [
+        BaseElement::new(306779470353065561943973071364147808340),
+        BaseElement::new(223641624072970712661465171581931416123),
+        BaseElement::new(333233222497515990372176085203165002719),
+        BaseElement::new(29353963680427183540728492360283113924),
+        BaseElement::new(64251135361420427889107576433571621650),
+        BaseElement::new(115497593258253878954555635139371786834),
+    ],
+    [
+        BaseElement::new(79142370348348289304268474017622190155),
+        BaseElement::new(269111409099921071475271121442819844513),
+        BaseElement::new(54802933611044425377451824350967750504),
+        BaseElement::new(15118667580097424172677693653582594695),
+        BaseElement::new(67129005799058772879486659948124694139),
+        BaseElement::new(180020020920643351532838168478524767300),
+    ],
+    [
+        BaseElement::new(56932882654907590354966363243294644860),
+        BaseElement::new(8103869622257586879579640256367487410),
+        BaseElement::new(238012482417183309142551356225457738627),
+        BaseElement::new(171278172151823558179373421300237022974),
+        BaseElement::new(113379162361292328935412605358378344856),
+        BaseElement::new(94443948202654188603334654208569607369),
+    ],
+    [
+        BaseElement::new(26859582414873812345047453380291067893),
+        BaseElement::new(251528055202290250103017096926949945500),
+        BaseElement::new(292436845586980850212016818715210220884),
+        BaseElement::new(299322315172525313188757869327837976450),
+        BaseElement::new(194278566387195144907272431950137267937),
+        BaseElement::new(134353872378108732517751775269990461338),
+    ],
+    [
+        BaseElement::new(268239298831495790855158392219992998211),
+        BaseElement::new(299490409551317666955004588825292616249),
+        BaseElement::new(149982201821419417415406426148911197490),
+        BaseElement::new(322013772821814723618815920667843218286),
+        BaseElement::new(120250399112222200186278644985221725414),
+        BaseElement::new(25134338065807367220612698306592085276),
+    ],
+    [
+        BaseElement::new(245645990324991226127644995693809782381),
+        BaseElement::new(198730942617898200812472804806322442825),
+        BaseElement::new(259424583814719324510645577944947197652),
+        BaseElement::new(196777498061924678216044199179957454791),
+        BaseElement::new(217054615746173277183908699150453956265),
+        BaseElement::new(112091742896036215923255236968545404917),
+    ],
+    [
+        BaseElement::new(8755527833024454998520580665220362164),
+        BaseElement::new(314316645567158136795746918046112975214),
+        BaseElement::new(257482642053082690404253497758008659583),
+        BaseElement::new(246907905721724755205901638526914223673),
+        BaseElement::new(138896211197113747706074207908832134452),
+        BaseElement::new(21617644552511525599595766723230144498),
+    ],
+    [
+        BaseElement::new(59513235479129382440739779258384491150),
+        BaseElement::new(331798815056341073743043594621168787038),
+        BaseElement::new(131382051845271459871175139751237806126),
+        BaseElement::new(225019281186694987129824333382135753826),
+        BaseElement::new(164447271266840730336295400175928668673),
+        BaseElement::new(217657120401960623438662429356612683314),
+    ],
+    [
+        BaseElement::new(339290014038930609929659726796947210910),
+        BaseElement::new(324084255281458684827460066774323569609),
+        BaseElement::new(309651355310444804289428990197005808171),
+        BaseElement::new(148782005778124056756676169623758250345),
+        BaseElement::new(38556854938677070145116812713563716321),
+        BaseElement::new(207504257170136597203395205058720310101),
+    ],
+    [
+        BaseElement::new(173887250865649662606111986973100727173),
+        BaseElement::new(67125432694751522345217054665413273024),
+        BaseElement::new(96729490121119899766875750451949154491),
+        BaseElement::new(242276809135476143593404987919499971984),
+        BaseElement::new(326154011339696118866539448502151726442),
+        BaseElement::new(10445666917003203808263507615100613821),
+    ],
+    [
+        BaseElement::new(51550774384173325527246236330198958701),
+        BaseElement::new(124555516557451530949078187432094900828),
+        BaseElement::new(217918041165123331189282868738423917695),
+        BaseElement::new(156517656474940045979204142277558951731),
+        BaseElement::new(74035110764663426087486418682515742702),
+        BaseElement::new(294977598783617330748023588268968574021),
+    ],
+];
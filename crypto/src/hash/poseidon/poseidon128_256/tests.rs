@@ -0,0 +1,163 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    BaseElement, ElementDigest, ElementHasher, FieldElement, Hasher, Poseidon128_256, ALPHA, MDS,
+    STATE_WIDTH,
+};
+use core::convert::TryInto;
+use proptest::prelude::*;
+
+use rand_utils::{rand_array, rand_value};
+
+// Expected values in the tests below are obtained by executing a Python implementation of the
+// Poseidon permutation which follows the reference implementation; the implementation uses the
+// same MDS matrix and round constants which are generated via the Grain LFSR as described in the
+// reference implementation.
+
+#[test]
+fn test_sbox() {
+    let state: [BaseElement; STATE_WIDTH] = rand_array();
+
+    let mut expected = state;
+    expected.iter_mut().for_each(|v| *v = v.exp(ALPHA));
+
+    let mut actual = state;
+    Poseidon128_256::apply_sbox(&mut actual);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn apply_permutation() {
+    let mut state: [BaseElement; STATE_WIDTH] = [
+        BaseElement::new(0),
+        BaseElement::new(1),
+        BaseElement::new(2),
+        BaseElement::new(3),
+        BaseElement::new(4),
+        BaseElement::new(5),
+    ];
+
+    Poseidon128_256::apply_permutation(&mut state);
+
+    let expected = vec![
+        BaseElement::new(81378373248873315322353437272319269729),
+        BaseElement::new(302115300657988555426763881932929800989),
+        BaseElement::new(171520974387598235480244682975655749211),
+        BaseElement::new(60930184503962767231499723138223761272),
+        BaseElement::new(226777413635305947637412214175189518542),
+        BaseElement::new(67203973171903148216564305258386581652),
+    ];
+
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn hash_elements() {
+    let elements = (1..=10_u32)
+        .map(|i| BaseElement::from(i * i + 1))
+        .collect::<Vec<_>>();
+
+    let expected = ElementDigest::new([
+        BaseElement::new(24159936103295124675741371639077633637),
+        BaseElement::new(101574932063857251292612716729605062457),
+    ]);
+
+    assert_eq!(expected, Poseidon128_256::hash_elements(&elements));
+}
+
+#[test]
+fn hash_bytes() {
+    let bytes = (1..=32_u8).collect::<Vec<_>>();
+
+    let expected = ElementDigest::new([
+        BaseElement::new(99571146688654415037051186311963628094),
+        BaseElement::new(122651694120018163085743587854249401300),
+    ]);
+
+    assert_eq!(expected, Poseidon128_256::hash(&bytes));
+}
+
+#[test]
+fn hash_elements_vs_merge() {
+    let elements: [BaseElement; 4] = rand_array();
+
+    let digests: [ElementDigest; 2] = [
+        ElementDigest::new(elements[..2].try_into().unwrap()),
+        ElementDigest::new(elements[2..].try_into().unwrap()),
+    ];
+
+    let m_result = Poseidon128_256::merge(&digests);
+    let h_result = Poseidon128_256::hash_elements(&elements);
+    assert_eq!(m_result, h_result);
+}
+
+#[test]
+fn hash_elements_vs_merge_with_int() {
+    let seed = ElementDigest::new(rand_array());
+
+    let val: u64 = rand_value();
+    let m_result = Poseidon128_256::merge_with_int(seed, val);
+
+    let mut elements = seed.as_elements().to_vec();
+    elements.push(BaseElement::from(val));
+    let h_result = Poseidon128_256::hash_elements(&elements);
+
+    assert_eq!(m_result, h_result);
+}
+
+#[test]
+fn hash_padding() {
+    // adding a zero bytes at the end of a byte string should result in a different hash
+    let r1 = Poseidon128_256::hash(&[1_u8, 2, 3]);
+    let r2 = Poseidon128_256::hash(&[1_u8, 2, 3, 0]);
+    assert_ne!(r1, r2);
+
+    // same as above but with multiple zeros
+    let r1 = Poseidon128_256::hash(&[1_u8, 2, 3, 0, 0]);
+    let r2 = Poseidon128_256::hash(&[1_u8, 2, 3, 0, 0, 0, 0]);
+    assert_ne!(r1, r2);
+}
+
+#[test]
+fn hash_elements_padding() {
+    let e1: [BaseElement; 2] = rand_array();
+    let e2 = [e1[0], e1[1], BaseElement::ZERO];
+
+    let r1 = Poseidon128_256::hash_elements(&e1);
+    let r2 = Poseidon128_256::hash_elements(&e2);
+    assert_ne!(r1, r2);
+}
+
+#[inline(always)]
+fn apply_mds_naive(state: &mut [BaseElement; STATE_WIDTH]) {
+    let mut result = [BaseElement::ZERO; STATE_WIDTH];
+    result.iter_mut().zip(MDS).for_each(|(r, mds_row)| {
+        state.iter().zip(mds_row).for_each(|(&s, m)| {
+            *r += m * s;
+        });
+    });
+    *state = result;
+}
+
+proptest! {
+    #[test]
+    fn mds_proptest(a in any::<[u128;STATE_WIDTH]>()) {
+
+        let mut v1 = [BaseElement::ZERO;STATE_WIDTH];
+        let mut v2;
+
+        for i in 0..STATE_WIDTH {
+            v1[i] = BaseElement::new(a[i]);
+        }
+        v2 = v1;
+
+        apply_mds_naive(&mut v1);
+        Poseidon128_256::apply_mds(&mut v2);
+
+        prop_assert_eq!(v1, v2);
+    }
+}
@@ -0,0 +1,984 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::super::mds::mds_f64_12x12::mds_multiply;
+use super::{Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
+use core::convert::TryInto;
+use core::ops::Range;
+use math::{fields::f64::BaseElement, FieldElement, StarkField};
+#[cfg(feature = "safe_only")]
+use utils::collections::Vec;
+
+mod digest;
+pub use digest::ElementDigest;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Sponge state is set to 12 field elements or 96 bytes; 8 elements are reserved for rate and
+/// the remaining 4 elements are reserved for capacity.
+const STATE_WIDTH: usize = 12;
+
+/// The rate portion of the state is located in elements 4 through 11.
+const RATE_RANGE: Range<usize> = 4..12;
+const RATE_WIDTH: usize = RATE_RANGE.end - RATE_RANGE.start;
+
+const INPUT1_RANGE: Range<usize> = 4..8;
+const INPUT2_RANGE: Range<usize> = 8..12;
+
+/// The capacity portion of the state is located in elements 0, 1, 2, and 3.
+const CAPACITY_RANGE: Range<usize> = 0..4;
+
+/// The output of the hash function is a digest which consists of 4 field elements or 32 bytes.
+///
+/// The digest is returned from state elements 4, 5, 6, and 7 (the first four elements of the
+/// rate portion).
+const DIGEST_RANGE: Range<usize> = 4..8;
+const DIGEST_SIZE: usize = DIGEST_RANGE.end - DIGEST_RANGE.start;
+
+/// The number of full rounds is set to 8 and the number of partial rounds is set to 22 to target
+/// 128-bit security level; computed using the round number calculation of the reference
+/// implementation, which includes a security margin of 2 full rounds and 7.5% of partial rounds.
+const NUM_FULL_ROUNDS: usize = 8;
+const NUM_PARTIAL_ROUNDS: usize = 22;
+const NUM_ROUNDS: usize = NUM_FULL_ROUNDS + NUM_PARTIAL_ROUNDS;
+
+/// S-Box power; this is the smallest power for which x^alpha is a permutation of the field.
+///
+/// The constant is defined for tests only because the exponentiation in the code is unrolled for
+/// efficiency reasons.
+#[cfg(test)]
+const ALPHA: u64 = 7;
+
+// HASHER IMPLEMENTATION
+// ================================================================================================
+
+/// Implementation of [Hasher] trait for Poseidon hash function with 256-bit output.
+///
+/// The hash function is implemented according to the Poseidon
+/// [specifications](https://eprint.iacr.org/2019/458.pdf) with the following exceptions:
+/// * When hashing a sequence of elements, we do not append Fp(1) followed by Fp(0) elements
+///   to the end of the sequence as padding. Instead, we initialize the first capacity element
+///   to the number of elements to be hashed, and pad the sequence with Fp(0) elements only. This
+///   ensures consistency of hash outputs between different hashing methods (see section below).
+///   However, it also means that our instantiation of Poseidon cannot be used in a stream mode as
+///   the number of elements to be hashed must be known upfront.
+/// * We use the first 4 elements of the state (rather than the last 4 elements of the state) for
+///   capacity and the remaining 8 elements for rate. The output of the hash function comes from
+///   the first four elements of the rate portion of the state (elements 4, 5, 6, and 7).
+/// * Instead of using a Cauchy matrix as the MDS matrix, we use the same circulant MDS matrix as
+///   [Rp64_256](super::super::Rp64_256) does. The coefficients of this matrix are small powers of
+///   two in frequency domain, which allows us to dramatically reduce MDS matrix multiplication
+///   time.
+///
+/// The round constants are generated in the same way as in the reference implementation, and
+/// the permutation is computed as specified (i.e., partial rounds are not optimized via sparse
+/// matrices), and thus, the state of the permutation after every round is the same as the state
+/// of the reference permutation instantiated with the same MDS matrix.
+///
+/// The parameters used to instantiate the function are:
+/// * Field: 64-bit prime field with modulus 2^64 - 2^32 + 1.
+/// * State width: 12 field elements.
+/// * Capacity size: 4 field elements.
+/// * Number of full rounds: 8.
+/// * Number of partial rounds: 22.
+/// * S-Box degree: 7.
+///
+/// The above parameters target 128-bit security level. The digest consists of four field elements
+/// and it can be serialized into 32 bytes (256 bits).
+///
+/// ## Hash output consistency
+/// Functions [hash_elements()](Poseidon64_256::hash_elements), [merge()](Poseidon64_256::merge),
+/// and [merge_with_int()](Poseidon64_256::merge_with_int) are internally consistent. That is,
+/// computing a hash for the same set of elements using these functions will always produce the
+/// same result. For example, merging two digests using [merge()](Poseidon64_256::merge) will
+/// produce the same result as hashing 8 elements which make up these digests using
+/// [hash_elements()](Poseidon64_256::hash_elements) function.
+///
+/// However, [hash()](Poseidon64_256::hash) function is not consistent with functions mentioned
+/// above, for the same reasons as described for [Rp64_256](super::super::Rp64_256).
+pub struct Poseidon64_256();
+
+impl Hasher for Poseidon64_256 {
+    type Digest = ElementDigest;
+
+    const COLLISION_RESISTANCE: u32 = 128;
+
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Poseidon64_256);
+
+    fn hash(bytes: &[u8]) -> Self::Digest {
+        // compute the number of elements required to represent the string; we will be processing
+        // the string in 7-byte chunks, thus the number of elements will be equal to the number
+        // of such chunks (including a potential partial chunk at the end).
+        let num_elements = if bytes.len() % 7 == 0 {
+            bytes.len() / 7
+        } else {
+            bytes.len() / 7 + 1
+        };
+
+        // initialize state to all zeros, except for the first element of the capacity part, which
+        // is set to the number of elements to be hashed. this is done so that adding zero elements
+        // at the end of the list always results in a different hash.
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(num_elements as u64);
+
+        // break the string into 7-byte chunks, convert each chunk into a field element, and
+        // absorb the element into the rate portion of the state. we use 7-byte chunks because
+        // every 7-byte chunk is guaranteed to map to some field element.
+        let mut i = 0;
+        let mut buf = [0_u8; 8];
+        for chunk in bytes.chunks(7) {
+            if i < num_elements - 1 {
+                buf[..7].copy_from_slice(chunk);
+            } else {
+                // if we are dealing with the last chunk, it may be smaller than 7 bytes long, so
+                // we need to handle it slightly differently. we also append a byte with value 1
+                // to the end of the string; this pads the string in such a way that adding
+                // trailing zeros results in different hash
+                let chunk_len = chunk.len();
+                buf = [0_u8; 8];
+                buf[..chunk_len].copy_from_slice(chunk);
+                buf[chunk_len] = 1;
+            }
+
+            // convert the bytes into a field element and absorb it into the rate portion of the
+            // state; if the rate is filled up, apply the Poseidon permutation and start absorbing
+            // again from zero index.
+            state[RATE_RANGE.start + i] += BaseElement::new(u64::from_le_bytes(buf));
+            i += 1;
+            if i % RATE_WIDTH == 0 {
+                Self::apply_permutation(&mut state);
+                i = 0;
+            }
+        }
+
+        // if we absorbed some elements but didn't apply a permutation to them (would happen when
+        // the number of elements is not a multiple of RATE_WIDTH), apply the Poseidon permutation.
+        // we don't need to apply any extra padding because we injected total number of elements
+        // in the input list into the capacity portion of the state during initialization.
+        if i > 0 {
+            Self::apply_permutation(&mut state);
+        }
+
+        // return the first 4 elements of the state as hash result
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
+        // initialize the state by copying the digest elements into the rate portion of the state
+        // (8 total elements), and set the first capacity element to 8 (the number of elements to
+        // be hashed).
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[RATE_RANGE.start..RATE_RANGE.start + DIGEST_SIZE]
+            .copy_from_slice(values[0].as_elements());
+        state[RATE_RANGE.start + DIGEST_SIZE..RATE_RANGE.end]
+            .copy_from_slice(values[1].as_elements());
+        state[CAPACITY_RANGE.start] = BaseElement::new(RATE_WIDTH as u64);
+
+        // apply the Poseidon permutation and return the first four elements of the state
+        Self::apply_permutation(&mut state);
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn merge_many(values: &[Self::Digest]) -> Self::Digest {
+        // digests consist of base field elements, and thus, can be hashed as a sequence of
+        // elements without going through their byte encoding
+        #[cfg(not(feature = "safe_only"))]
+        let elements = ElementDigest::digests_as_elements(values);
+        #[cfg(feature = "safe_only")]
+        let elements = &values
+            .iter()
+            .flat_map(|digest| digest.as_elements().iter().copied())
+            .collect::<Vec<_>>();
+        Self::hash_elements(elements)
+    }
+
+    fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
+        // initialize the state as follows:
+        // - seed is copied into the first 4 elements of the rate portion of the state.
+        // - if the value fits into a single field element, copy it into the fifth rate element
+        //   and set the first capacity element to 5 (the number of elements to be hashed).
+        // - if the value doesn't fit into a single field element, split it into two field
+        //   elements, copy them into rate elements 5 and 6, and set the first capacity element
+        //   to 6.
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[INPUT1_RANGE].copy_from_slice(seed.as_elements());
+        state[INPUT2_RANGE.start] = BaseElement::new(value);
+        if value < BaseElement::MODULUS {
+            state[CAPACITY_RANGE.start] = BaseElement::new(DIGEST_SIZE as u64 + 1);
+        } else {
+            state[INPUT2_RANGE.start + 1] = BaseElement::new(value / BaseElement::MODULUS);
+            state[CAPACITY_RANGE.start] = BaseElement::new(DIGEST_SIZE as u64 + 2);
+        }
+
+        // apply the Poseidon permutation and return the first four elements of the state
+        Self::apply_permutation(&mut state);
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+}
+
+impl ElementHasher for Poseidon64_256 {
+    type BaseField = BaseElement;
+    type State = SpongeState<BaseElement, STATE_WIDTH>;
+
+    fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // convert the elements into a list of base field elements
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = E::to_base_elements(elements);
+
+        // initialize state to all zeros, except for the first element of the capacity part, which
+        // is set to the number of elements to be hashed. this is done so that adding zero elements
+        // at the end of the list always results in a different hash.
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(elements.len() as u64);
+
+        // absorb elements into the state one by one until the rate portion of the state is filled
+        // up; then apply the Poseidon permutation and start absorbing again; repeat until all
+        // elements have been absorbed
+        let mut i = 0;
+        for &element in elements.iter() {
+            state[RATE_RANGE.start + i] += element;
+            i += 1;
+            if i % RATE_WIDTH == 0 {
+                Self::apply_permutation(&mut state);
+                i = 0;
+            }
+        }
+
+        // if we absorbed some elements but didn't apply a permutation to them (would happen when
+        // the number of elements is not a multiple of RATE_WIDTH), apply the Poseidon permutation.
+        // we don't need to apply any extra padding because we injected total number of elements
+        // in the input list into the capacity portion of the state during initialization.
+        if i > 0 {
+            Self::apply_permutation(&mut state);
+        }
+
+        // return the first 4 elements of the state as hash result
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn begin(num_elements: usize) -> Self::State {
+        // the number of elements to be hashed is injected into the capacity portion of the state
+        // in the same way as in hash_elements()
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(num_elements as u64);
+        SpongeState::new(state)
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = &E::to_base_elements(elements);
+
+        state.absorb(elements, RATE_RANGE, Self::apply_permutation);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        let (mut state, num_pending) = state.into_parts();
+        if num_pending > 0 {
+            Self::apply_permutation(&mut state);
+        }
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn digest_as_elements(digest: &Self::Digest) -> Option<&[Self::BaseField]> {
+        Some(digest.as_elements())
+    }
+}
+
+// HASH FUNCTION IMPLEMENTATION
+// ================================================================================================
+
+impl Poseidon64_256 {
+    // CONSTANTS
+    // --------------------------------------------------------------------------------------------
+
+    /// The number of full rounds; half of the full rounds are applied before the partial rounds,
+    /// and the other half is applied after them.
+    pub const NUM_FULL_ROUNDS: usize = NUM_FULL_ROUNDS;
+
+    /// The number of partial rounds.
+    pub const NUM_PARTIAL_ROUNDS: usize = NUM_PARTIAL_ROUNDS;
+
+    /// Sponge state is set to 12 field elements or 96 bytes; 8 elements are reserved for rate and
+    /// the remaining 4 elements are reserved for capacity.
+    pub const STATE_WIDTH: usize = STATE_WIDTH;
+
+    /// The rate portion of the state is located in elements 4 through 11 (inclusive).
+    pub const RATE_RANGE: Range<usize> = RATE_RANGE;
+
+    /// The capacity portion of the state is located in elements 0, 1, 2, and 3.
+    pub const CAPACITY_RANGE: Range<usize> = CAPACITY_RANGE;
+
+    /// The output of the hash function can be read from state elements 4, 5, 6, and 7.
+    pub const DIGEST_RANGE: Range<usize> = DIGEST_RANGE;
+
+    /// MDS matrix used for computing the linear layer in a Poseidon round.
+    pub const MDS: [[BaseElement; STATE_WIDTH]; STATE_WIDTH] = MDS;
+
+    /// Round constants added to the hasher state at the beginning of every Poseidon round.
+    pub const ARK: [[BaseElement; STATE_WIDTH]; NUM_ROUNDS] = ARK;
+
+    // POSEIDON PERMUTATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Applies Poseidon permutation to the provided state.
+    pub fn apply_permutation(state: &mut [BaseElement; STATE_WIDTH]) {
+        for round in 0..NUM_ROUNDS {
+            Self::apply_round(state, round);
+        }
+    }
+
+    /// Poseidon round function; the S-Box is applied to all state elements in full rounds, and
+    /// only to the first state element in partial rounds.
+    #[inline(always)]
+    pub fn apply_round(state: &mut [BaseElement; STATE_WIDTH], round: usize) {
+        Self::add_constants(state, &ARK[round]);
+        if Self::is_full_round(round) {
+            Self::apply_sbox(state);
+        } else {
+            state[0] = state[0].exp7();
+        }
+        Self::apply_mds(state);
+    }
+
+    /// Returns true if the round with the specified index is a full round.
+    pub const fn is_full_round(round: usize) -> bool {
+        round < NUM_FULL_ROUNDS / 2 || round >= NUM_FULL_ROUNDS / 2 + NUM_PARTIAL_ROUNDS
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+
+    #[inline(always)]
+    fn apply_mds(state: &mut [BaseElement; STATE_WIDTH]) {
+        mds_multiply(state)
+    }
+
+    #[inline(always)]
+    fn add_constants(state: &mut [BaseElement; STATE_WIDTH], ark: &[BaseElement; STATE_WIDTH]) {
+        state.iter_mut().zip(ark).for_each(|(s, &k)| *s += k);
+    }
+
+    #[inline(always)]
+    fn apply_sbox(state: &mut [BaseElement; STATE_WIDTH]) {
+        state.iter_mut().for_each(|s| *s = s.exp7());
+    }
+}
+
+// MDS
+// ================================================================================================
+/// Poseidon MDS matrix; this is the same circulant matrix as the one used by Rp64_256.
+const MDS: [[BaseElement; STATE_WIDTH]; STATE_WIDTH] = [
+    [
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+    ],
+    [
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+    ],
+    [
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+    ],
+    [
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+    ],
+    [
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+    ],
+    [
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+    ],
+    [
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+    ],
+    [
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+    ],
+    [
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+    ],
+    [
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+        BaseElement::new(8),
+    ],
+    [
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+        BaseElement::new(23),
+    ],
+    [
+        BaseElement::new(23),
+        BaseElement::new(8),
+        BaseElement::new(26),
+        BaseElement::new(13),
+        BaseElement::new(10),
+        BaseElement::new(9),
+        BaseElement::new(7),
+        BaseElement::new(6),
+        BaseElement::new(22),
+        BaseElement::new(21),
+        BaseElement::new(8),
+        BaseElement::new(7),
+    ],
+];
+
+// ROUND CONSTANTS
+// ================================================================================================
+
+/// Round constants added to the hasher state at the beginning of every Poseidon round.
+///
+/// The constants are generated using the Grain LFSR in self-shrinking mode as specified in the
+/// reference implementation (`generate_parameters_grain.sage`) with the following parameters:
+/// field = 1 (prime field), s-box = 0 (x^alpha), field size = 64, state width = 12, number of full
+/// rounds = 8, number of partial rounds = 22.
+const ARK: [[BaseElement; STATE_WIDTH]; NUM_ROUNDS] = [
+    [
+        BaseElement::new(1431286215153372998),
+        BaseElement::new(3509349009260703107),
+        BaseElement::new(2289575380984896342),
+        BaseElement::new(10625215922958251110),
+        BaseElement::new(17137022507167291684),
+        BaseElement::new(17143426961497010024),
+        BaseElement::new(9589775313463224365),
+        BaseElement::new(7736066733515538648),
+        BaseElement::new(2217569167061322248),
+        BaseElement::new(10394930802584583083),
+        BaseElement::new(4612393375016695705),
+        BaseElement::new(5332470884919453534),
+    ],
+    [
+        BaseElement::new(8724526834049581439),
+        BaseElement::new(17673787971454860688),
+        BaseElement::new(2519987773101056005),
+        BaseElement::new(7999687124137420323),
+        BaseElement::new(18312454652563306701),
+        BaseElement::new(15136091233824155669),
+        BaseElement::new(1257110570403430003),
+        BaseElement::new(5665449074466664773),
+        BaseElement::new(16178737609685266571),
+        BaseElement::new(52855143527893348),
+        BaseElement::new(8084454992943870230),
+        BaseElement::new(2597062441266647183),
+    ],
+    [
+        BaseElement::new(3342624911463171251),
+        BaseElement::new(6781356195391537436),
+        BaseElement::new(4697929572322733707),
+        BaseElement::new(4179687232228901671),
+        BaseElement::new(17841073646522133059),
+        BaseElement::new(18340176721233187897),
+        BaseElement::new(13152929999122219197),
+        BaseElement::new(6306257051437840427),
+        BaseElement::new(4974451914008050921),
+        BaseElement::new(11258703678970285201),
+        BaseElement::new(581736081259960204),
+        BaseElement::new(18323286026903235604),
+    ],
+    [
+        BaseElement::new(10250026231324330997),
+        BaseElement::new(13321947507807660157),
+        BaseElement::new(13020725208899496943),
+        BaseElement::new(11416990495425192684),
+        BaseElement::new(7221795794796219413),
+        BaseElement::new(2607917872900632985),
+        BaseElement::new(2591896057192169329),
+        BaseElement::new(10485489452304998145),
+        BaseElement::new(9480186048908910015),
+        BaseElement::new(2645141845409940474),
+        BaseElement::new(16242299839765162610),
+        BaseElement::new(12203738590896308135),
+    ],
+    [
+        BaseElement::new(5395176197344543510),
+        BaseElement::new(17941136338888340715),
+        BaseElement::new(7559392505546762987),
+        BaseElement::new(549633128904721280),
+        BaseElement::new(15658455328409267684),
+        BaseElement::new(10078371877170729592),
+        BaseElement::new(2349868247408080783),
+        BaseElement::new(13105911261634181239),
+        BaseElement::new(12868653202234053626),
+        BaseElement::new(9471330315555975806),
+        BaseElement::new(4580289636625406680),
+        BaseElement::new(13222733136951421572),
+    ],
+    [
+        BaseElement::new(4555032575628627551),
+        BaseElement::new(7619130111929922899),
+        BaseElement::new(4547848507246491777),
+        BaseElement::new(5662043532568004632),
+        BaseElement::new(15723873049665279492),
+        BaseElement::new(13585630674756818185),
+        BaseElement::new(6990417929677264473),
+        BaseElement::new(6373257983538884779),
+        BaseElement::new(1005856792729125863),
+        BaseElement::new(17850970025369572891),
+        BaseElement::new(14306783492963476045),
+        BaseElement::new(12653264875831356889),
+    ],
+    [
+        BaseElement::new(10887434669785806501),
+        BaseElement::new(7221072982690633460),
+        BaseElement::new(9953585853856674407),
+        BaseElement::new(13497620366078753434),
+        BaseElement::new(18140292631504202243),
+        BaseElement::new(17311934738088402529),
+        BaseElement::new(6686302214424395771),
+        BaseElement::new(11193071888943695519),
+        BaseElement::new(10233795775801758543),
+        BaseElement::new(3362219552562939863),
+        BaseElement::new(8595401306696186761),
+        BaseElement::new(7753411262943026561),
+    ],
+    [
+        BaseElement::new(12415218859476220947),
+        BaseElement::new(12517451587026875834),
+        BaseElement::new(3257008032900598499),
+        BaseElement::new(2187469039578904770),
+        BaseElement::new(657675168296710415),
+        BaseElement::new(8659969869470208989),
+        BaseElement::new(12526098871288378639),
+        BaseElement::new(12525853395769009329),
+        BaseElement::new(15388161689979551704),
+        BaseElement::new(7880966905416338909),
+        BaseElement::new(2911694411222711481),
+        BaseElement::new(6420652251792580406),
+    ],
+    [
+        BaseElement::new(323544930728360053),
+        BaseElement::new(11718666476052241225),
+        BaseElement::new(2449132068789045592),
+        BaseElement::new(17993014181992530560),
+        BaseElement::new(15161788952257357966),
+        BaseElement::new(3788504801066818367),
+        BaseElement::new(1282111773460545571),
+        BaseElement::new(8849495164481705550),
+        BaseElement::new(8380852402060721190),
+        BaseElement::new(2161980224591127360),
+        BaseElement::new(2440151485689245146),
+        BaseElement::new(17521895002090134367),
+    ],
+    [
+        BaseElement::new(13821005335130766955),
+        BaseElement::new(17513705631114265826),
+        BaseElement::new(17068447856797239529),
+        BaseElement::new(17964439003977043993),
+        BaseElement::new(5685000919538239429),
+        BaseElement::new(11615940660682589106),
+        BaseElement::new(2522854885180605258),
+        BaseElement::new(12584118968072796115),
+        BaseElement::new(17841258728624635591),
+        BaseElement::new(10821564568873127316),
+        BaseElement::new(12929526205313074951),
+        BaseElement::new(15240209309138869842),
+    ],
+    [
+        BaseElement::new(8112988184280322821),
+        BaseElement::new(10264318651796760217),
+        BaseElement::new(11567563749053508498),
+        BaseElement::new(10342172001635729828),
+        BaseElement::new(8518076871621000645),
+        BaseElement::new(9443305710168864155),
+        BaseElement::new(12258139284331692775),
+        BaseElement::new(11225713976478342221),
+        BaseElement::new(1083829959428202152),
+        BaseElement::new(13295679221277307734),
+        BaseElement::new(8702942527907868190),
+        BaseElement::new(3447159893350309030),
+    ],
+    [
+        BaseElement::new(16331987863400672412),
+        BaseElement::new(17004721198375099349),
+        BaseElement::new(14568842036851006853),
+        BaseElement::new(14031093640500276073),
+        BaseElement::new(8047796853787800360),
+        BaseElement::new(18176470296573070531),
+        BaseElement::new(1733280390763076136),
+        BaseElement::new(15280460251950617888),
+        BaseElement::new(5319165528697198957),
+        BaseElement::new(4130010739946422935),
+        BaseElement::new(4862639442103099490),
+        BaseElement::new(11947225653897253435),
+    ],
+    [
+        BaseElement::new(16093634485870170562),
+        BaseElement::new(466101267687143357),
+        BaseElement::new(5269775209624779324),
+        BaseElement::new(12661180512164132421),
+        BaseElement::new(8527855600080265358),
+        BaseElement::new(3509637282341164493),
+        BaseElement::new(14524011473168972347),
+        BaseElement::new(9558935312509120777),
+        BaseElement::new(8282858737521047195),
+        BaseElement::new(10171277103718892682),
+        BaseElement::new(12294317531079789416),
+        BaseElement::new(7182028925080765556),
+    ],
+    [
+        BaseElement::new(2038954051047328382),
+        BaseElement::new(1572125904757759485),
+        BaseElement::new(6023737508444785880),
+        BaseElement::new(8798428950960158590),
+        BaseElement::new(1968909394335647758),
+        BaseElement::new(16968160382228211614),
+        BaseElement::new(32551027029362334),
+        BaseElement::new(3205180815856999908),
+        BaseElement::new(10740246361676213188),
+        BaseElement::new(10169158339754762156),
+        BaseElement::new(15226715702476100867),
+        BaseElement::new(8966100427867584251),
+    ],
+    [
+        BaseElement::new(17917233579925756683),
+        BaseElement::new(7959268962897120034),
+        BaseElement::new(532408456989891872),
+        BaseElement::new(9851667167813963284),
+        BaseElement::new(13448506932345489306),
+        BaseElement::new(16135486720253939622),
+        BaseElement::new(8458050899770540390),
+        BaseElement::new(6021254166081897382),
+        BaseElement::new(15552837092683737625),
+        BaseElement::new(15440505484365682848),
+        BaseElement::new(16088056409693275462),
+        BaseElement::new(6169635475476966421),
+    ],
+    [
+        BaseElement::new(5480704578777097169),
+        BaseElement::new(7516526247262867111),
+        BaseElement::new(3438140470099985472),
+        BaseElement::new(13048600081642942971),
+        BaseElement::new(9829255629799717904),
+        BaseElement::new(17311489510949436164),
+        BaseElement::new(15254947846872712175),
+        BaseElement::new(5825939868327872570),
+        BaseElement::new(850656437239379199),
+        BaseElement::new(12619934071925039179),
+        BaseElement::new(15233049780346247641),
+        BaseElement::new(9298309061465962971),
+    ],
+    [
+        BaseElement::new(741424706267005090),
+        BaseElement::new(17203483336096778815),
+        BaseElement::new(6919908349347460635),
+        BaseElement::new(863377837517698584),
+        BaseElement::new(11632281421519826770),
+        BaseElement::new(17750153240261395489),
+        BaseElement::new(14753366294352507072),
+        BaseElement::new(12793355793496405427),
+        BaseElement::new(16289545878058120229),
+        BaseElement::new(6368259120071113126),
+        BaseElement::new(4057875983396832839),
+        BaseElement::new(13847225916600191037),
+    ],
+    [
+        BaseElement::new(7872218736019578342),
+        BaseElement::new(5426064199624116028),
+        BaseElement::new(9479822711840773905),
+        BaseElement::new(10634838597871962689),
+        BaseElement::new(7081809782259040995),
+        BaseElement::new(1440626909472018594),
+        BaseElement::new(6603963598898808862),
+        BaseElement::new(12662045888242770199),
+        BaseElement::new(18036285107641934643),
+        BaseElement::new(15828843208411476617),
+        BaseElement::new(14102670999874605825),
+        BaseElement::new(15585654191999307703),
+    ],
+    [
+        BaseElement::new(940187017142450256),
+        BaseElement::new(8747386241522630712),
+        BaseElement::new(6750641561540124748),
+        BaseElement::new(7440998025584530008),
+        BaseElement::new(6136358134615751537),
+        BaseElement::new(12413576830284969612),
+        BaseElement::new(11675438539028694710),
+        BaseElement::new(17580553691069642927),
+        BaseElement::new(892707462476851332),
+        BaseElement::new(15167485180850043745),
+        BaseElement::new(9924997173903409412),
+        BaseElement::new(9613966396549972013),
+    ],
+    [
+        BaseElement::new(3242363036477934858),
+        BaseElement::new(8529581814542674199),
+        BaseElement::new(1460135031320476117),
+        BaseElement::new(15230276901939640657),
+        BaseElement::new(3034222759280296577),
+        BaseElement::new(2536834233629877234),
+        BaseElement::new(12229748406346543211),
+        BaseElement::new(13166855996952940567),
+        BaseElement::new(16039201196582061794),
+        BaseElement::new(14239610657545203244),
+        BaseElement::new(4079052969819075917),
+        BaseElement::new(2550303736432259954),
+    ],
+    [
+        BaseElement::new(15415646525902701306),
+        BaseElement::new(16984207496990988313),
+        BaseElement::new(6195489392633771043),
+        BaseElement::new(15696991486732177869),
+        BaseElement::new(17238905290121258980),
+        BaseElement::new(16082743896956175460),
+        BaseElement::new(2607127875797716838),
+        BaseElement::new(4163972359010584653),
+        BaseElement::new(2369705041192477687),
+        BaseElement::new(12936899802672086396),
+        BaseElement::new(17399492193998111961),
+        BaseElement::new(14701188996710188063),
+    ],
+    [
+        BaseElement::new(10673647621461954174),
+        BaseElement::new(10187656820932330866),
+        BaseElement::new(14253604578356758004),
+        BaseElement::new(10632764261170436503),
+        BaseElement::new(2575456097595068268),
+        BaseElement::new(14486510292332525540),
+        BaseElement::new(857634655205127854),
+        BaseElement::new(11539936742927634064),
+        BaseElement::new(3025473245387650600),
+        BaseElement::new(3072205393568168823),
+        BaseElement::new(16220766505279212230),
+        BaseElement::new(13095270286885528495),
+    ],
+    [
+        BaseElement::new(10043771903993878423),
+        BaseElement::new(4580450255883541632),
+        BaseElement::new(5546821308061729354),
+        BaseElement::new(17932404490144193348),
+        BaseElement::new(4055843989895157237),
+        BaseElement::new(506731346742428544),
+        BaseElement::new(1750774988219982266),
+        BaseElement::new(13647783723546009630),
+        BaseElement::new(17180411145007510672),
+        BaseElement::new(7092939346849547588),
+        BaseElement::new(2004811345434270086),
+        BaseElement::new(3930380885080085231),
+    ],
+    [
+        BaseElement::new(5731056810399963425),
+        BaseElement::new(16339249658689415041),
+        BaseElement::new(10896947625319492019),
+        BaseElement::new(58048537304546191),
+        BaseElement::new(12301681553475871944),
+        BaseElement::new(15410898306178483444),
+        BaseElement::new(5248513067045859782),
+        BaseElement::new(11268429244640014487),
+        BaseElement::new(3785322258417388297),
+        BaseElement::new(12573604913857968925),
+        BaseElement::new(10088460126056383905),
+        BaseElement::new(9505879368173225761),
+    ],
+    [
+        BaseElement::new(12331335364636844807),
+        BaseElement::new(15800425329127532993),
+        BaseElement::new(17233569579365152217),
+        BaseElement::new(6580598753390726049),
+        BaseElement::new(6332388716747236070),
+        BaseElement::new(14837976254465985338),
+        BaseElement::new(1387653002144476724),
+        BaseElement::new(15556347971769261667),
+        BaseElement::new(7571094906243962853),
+        BaseElement::new(14097015672565897063),
+        BaseElement::new(1689918468007574312),
+        BaseElement::new(16247594734699408053),
+    ],
+    [
+        BaseElement::new(6376995477333092352),
+        BaseElement::new(962981388472387485),
+        BaseElement::new(2846128944153513179),
+        BaseElement::new(11832408739941285626),
+        BaseElement::new(16892791912968591653),
+        BaseElement::new(14660122210495197643),
+        BaseElement::new(16446079849332856874),
+        BaseElement::new(7976724875926637635),
+        BaseElement::new(13842280498640749771),
+        BaseElement::new(15375657835094741734),
+        BaseElement::new(8871752519026737048),
+        BaseElement::new(6979293996243387512),
+    ],
+    [
+        BaseElement::new(10552448846206288151),
+        BaseElement::new(14987673924494666433),
+        BaseElement::new(18035303280469462414),
+        BaseElement::new(16595113834715919465),
+        BaseElement::new(15208661533916677630),
+        BaseElement::new(4170608138187333497),
+        BaseElement::new(16304084357983152470),
+        BaseElement::new(2331503858766652994),
+        BaseElement::new(8776079357547932587),
+        BaseElement::new(18299646478835171989),
+        BaseElement::new(3681263166902989193),
+        BaseElement::new(12612029705709390274),
+    ],
+    [
+        BaseElement::new(12014669431902405777),
+        BaseElement::new(11319504285297576766),
+        BaseElement::new(5234999940078631477),
+        BaseElement::new(1125448944938006422),
+        BaseElement::new(2164405204907480972),
+        BaseElement::new(6168495504522907053),
+        BaseElement::new(6250236942243891229),
+        BaseElement::new(18269902991411124149),
+        BaseElement::new(9426885685329917236),
+        BaseElement::new(4521800374915508165),
+        BaseElement::new(2213719649464492152),
+        BaseElement::new(9422759956003735939),
+    ],
+    [
+        BaseElement::new(12723275943377720767),
+        BaseElement::new(14785736031955679545),
+        BaseElement::new(15257683393549924851),
+        BaseElement::new(14586462537439744229),
+        BaseElement::new(13109892360729616102),
+        BaseElement::new(18054952537889795742),
+        BaseElement::new(12589969976105374274),
+        BaseElement::new(1436163932748701916),
+        BaseElement::new(14879322534176465619),
+        BaseElement::new(17580838042056220468),
+        BaseElement::new(17970300042937392952),
+        BaseElement::new(1420156878331078790),
+    ],
+    [
+        BaseElement::new(17310902395782251544),
+        BaseElement::new(9021117459098865178),
+        BaseElement::new(9956374953785489337),
+        BaseElement::new(9283926179170577664),
+        BaseElement::new(2866744588122882663),
+        BaseElement::new(12613310502798528952),
+        BaseElement::new(48642999969593367),
+        BaseElement::new(5069344854700671784),
+        BaseElement::new(17704314310866354161),
+        BaseElement::new(15988800480645163458),
+        BaseElement::new(5818851986787837003),
+        BaseElement::new(2578102338873304736),
+    ],
+];
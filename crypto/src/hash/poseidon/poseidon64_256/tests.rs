@@ -0,0 +1,191 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    BaseElement, ElementDigest, ElementHasher, FieldElement, Hasher, Poseidon64_256, StarkField,
+    ALPHA, MDS, STATE_WIDTH,
+};
+use core::convert::TryInto;
+use proptest::prelude::*;
+
+use rand_utils::{rand_array, rand_value};
+
+// Expected values in the tests below are obtained by executing a Python implementation of the
+// Poseidon permutation which follows the reference implementation; the implementation uses the
+// same MDS matrix and round constants which are generated via the Grain LFSR as described in the
+// reference implementation.
+
+#[test]
+fn test_sbox() {
+    let state: [BaseElement; STATE_WIDTH] = rand_array();
+
+    let mut expected = state;
+    expected.iter_mut().for_each(|v| *v = v.exp(ALPHA));
+
+    let mut actual = state;
+    Poseidon64_256::apply_sbox(&mut actual);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn apply_permutation() {
+    let mut state: [BaseElement; STATE_WIDTH] = [
+        BaseElement::new(0),
+        BaseElement::new(1),
+        BaseElement::new(2),
+        BaseElement::new(3),
+        BaseElement::new(4),
+        BaseElement::new(5),
+        BaseElement::new(6),
+        BaseElement::new(7),
+        BaseElement::new(8),
+        BaseElement::new(9),
+        BaseElement::new(10),
+        BaseElement::new(11),
+    ];
+
+    Poseidon64_256::apply_permutation(&mut state);
+
+    let expected = vec![
+        BaseElement::new(4641002866549900661),
+        BaseElement::new(13020144589501907268),
+        BaseElement::new(9291923113014312323),
+        BaseElement::new(2052087091727655297),
+        BaseElement::new(8475168048298067631),
+        BaseElement::new(14012581593613805083),
+        BaseElement::new(3416906160983159606),
+        BaseElement::new(10332525608450155989),
+        BaseElement::new(2098120237155716416),
+        BaseElement::new(17923667669790388350),
+        BaseElement::new(8429766778227274511),
+        BaseElement::new(15580399571007166126),
+    ];
+
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn hash_elements() {
+    let elements = (1..=10_u32)
+        .map(|i| BaseElement::from(i * i + 1))
+        .collect::<Vec<_>>();
+
+    let expected = ElementDigest::new([
+        BaseElement::new(9922166598794340086),
+        BaseElement::new(6807196384781335223),
+        BaseElement::new(7436402566075164338),
+        BaseElement::new(5227134475874092105),
+    ]);
+
+    assert_eq!(expected, Poseidon64_256::hash_elements(&elements));
+}
+
+#[test]
+fn hash_bytes() {
+    let bytes = (1..=32_u8).collect::<Vec<_>>();
+
+    let expected = ElementDigest::new([
+        BaseElement::new(16672800734781334007),
+        BaseElement::new(671725881912323845),
+        BaseElement::new(1315880200662280040),
+        BaseElement::new(1404591563635368003),
+    ]);
+
+    assert_eq!(expected, Poseidon64_256::hash(&bytes));
+}
+
+#[test]
+fn hash_elements_vs_merge() {
+    let elements: [BaseElement; 8] = rand_array();
+
+    let digests: [ElementDigest; 2] = [
+        ElementDigest::new(elements[..4].try_into().unwrap()),
+        ElementDigest::new(elements[4..].try_into().unwrap()),
+    ];
+
+    let m_result = Poseidon64_256::merge(&digests);
+    let h_result = Poseidon64_256::hash_elements(&elements);
+    assert_eq!(m_result, h_result);
+}
+
+#[test]
+fn hash_elements_vs_merge_with_int() {
+    let seed = ElementDigest::new(rand_array());
+
+    // ----- value fits into a field element ------------------------------------------------------
+    let val: BaseElement = rand_value();
+    let m_result = Poseidon64_256::merge_with_int(seed, val.as_int());
+
+    let mut elements = seed.as_elements().to_vec();
+    elements.push(val);
+    let h_result = Poseidon64_256::hash_elements(&elements);
+
+    assert_eq!(m_result, h_result);
+
+    // ----- value does not fit into a field element ----------------------------------------------
+    let val = BaseElement::MODULUS + 2;
+    let m_result = Poseidon64_256::merge_with_int(seed, val);
+
+    let mut elements = seed.as_elements().to_vec();
+    elements.push(BaseElement::new(val));
+    elements.push(BaseElement::new(1));
+    let h_result = Poseidon64_256::hash_elements(&elements);
+
+    assert_eq!(m_result, h_result);
+}
+
+#[test]
+fn hash_padding() {
+    // adding a zero bytes at the end of a byte string should result in a different hash
+    let r1 = Poseidon64_256::hash(&[1_u8, 2, 3]);
+    let r2 = Poseidon64_256::hash(&[1_u8, 2, 3, 0]);
+    assert_ne!(r1, r2);
+
+    // same as above but with multiple zeros
+    let r1 = Poseidon64_256::hash(&[1_u8, 2, 3, 0, 0]);
+    let r2 = Poseidon64_256::hash(&[1_u8, 2, 3, 0, 0, 0, 0]);
+    assert_ne!(r1, r2);
+}
+
+#[test]
+fn hash_elements_padding() {
+    let e1: [BaseElement; 2] = rand_array();
+    let e2 = [e1[0], e1[1], BaseElement::ZERO];
+
+    let r1 = Poseidon64_256::hash_elements(&e1);
+    let r2 = Poseidon64_256::hash_elements(&e2);
+    assert_ne!(r1, r2);
+}
+
+#[inline(always)]
+fn apply_mds_naive(state: &mut [BaseElement; STATE_WIDTH]) {
+    let mut result = [BaseElement::ZERO; STATE_WIDTH];
+    result.iter_mut().zip(MDS).for_each(|(r, mds_row)| {
+        state.iter().zip(mds_row).for_each(|(&s, m)| {
+            *r += m * s;
+        });
+    });
+    *state = result;
+}
+
+proptest! {
+    #[test]
+    fn mds_proptest(a in any::<[u64;STATE_WIDTH]>()) {
+
+        let mut v1 = [BaseElement::ZERO;STATE_WIDTH];
+        let mut v2;
+
+        for i in 0..STATE_WIDTH {
+            v1[i] = BaseElement::new(a[i]);
+        }
+        v2 = v1;
+
+        apply_mds_naive(&mut v1);
+        Poseidon64_256::apply_mds(&mut v2);
+
+        prop_assert_eq!(v1, v2);
+    }
+}
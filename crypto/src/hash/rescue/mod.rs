@@ -3,15 +3,21 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{Digest, ElementHasher, Hasher, StarkField};
+use super::{Digest, ElementHasher, HashFunctionId, Hasher, SpongeState, StarkField};
 
+#[cfg(feature = "f62")]
 mod rp62_248;
+#[cfg(feature = "f62")]
 pub use rp62_248::Rp62_248;
 
+#[cfg(feature = "f64")]
 mod rp64_256;
+#[cfg(feature = "f64")]
 pub use rp64_256::Rp64_256;
 
+#[cfg(feature = "f64")]
 mod rp64_256_jive;
+#[cfg(feature = "f64")]
 pub use rp64_256_jive::RpJive64_256;
 
 // HELPER FUNCTIONS
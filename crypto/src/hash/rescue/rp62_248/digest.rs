@@ -23,6 +23,7 @@ impl ElementDigest {
         &self.0
     }
 
+    #[allow(unsafe_code)]
     pub fn digests_as_elements(digests: &[Self]) -> &[BaseElement] {
         let p = digests.as_ptr();
         let len = digests.len() * DIGEST_SIZE;
@@ -3,9 +3,11 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{exp_acc, Digest, ElementHasher, Hasher};
+use super::{exp_acc, Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
 use core::convert::TryInto;
 use math::{fields::f62::BaseElement, FieldElement, StarkField};
+#[cfg(feature = "safe_only")]
+use utils::collections::Vec;
 
 mod digest;
 pub use digest::ElementDigest;
@@ -94,6 +96,8 @@ impl Hasher for Rp62_248 {
 
     const COLLISION_RESISTANCE: u32 = 124;
 
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Rp62_248);
+
     fn hash(bytes: &[u8]) -> Self::Digest {
         // compute the number of elements required to represent the string; we will be processing
         // the string in 7-byte chunks, thus the number of elements will be equal to the number
@@ -157,7 +161,8 @@ impl Hasher for Rp62_248 {
         // (8 total elements), and set the last capacity element to 8 (the number of elements to
         // be hashed).
         let mut state = [BaseElement::ZERO; STATE_WIDTH];
-        state[..RATE_WIDTH].copy_from_slice(Self::Digest::digests_as_elements(values));
+        state[..DIGEST_SIZE].copy_from_slice(values[0].as_elements());
+        state[DIGEST_SIZE..RATE_WIDTH].copy_from_slice(values[1].as_elements());
         state[STATE_WIDTH - 1] = BaseElement::new(RATE_WIDTH as u64);
 
         // apply the Rescue permutation and return the first four elements of the state
@@ -165,6 +170,19 @@ impl Hasher for Rp62_248 {
         ElementDigest::new(state[..DIGEST_SIZE].try_into().unwrap())
     }
 
+    fn merge_many(values: &[Self::Digest]) -> Self::Digest {
+        // digests consist of base field elements, and thus, can be hashed as a sequence of
+        // elements without going through their byte encoding
+        #[cfg(not(feature = "safe_only"))]
+        let elements = ElementDigest::digests_as_elements(values);
+        #[cfg(feature = "safe_only")]
+        let elements = &values
+            .iter()
+            .flat_map(|digest| digest.as_elements().iter().copied())
+            .collect::<Vec<_>>();
+        Self::hash_elements(elements)
+    }
+
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
         // initialize the state as follows:
         // - seed is copied into the first 4 elements of the state.
@@ -191,10 +209,14 @@ impl Hasher for Rp62_248 {
 
 impl ElementHasher for Rp62_248 {
     type BaseField = BaseElement;
+    type State = SpongeState<BaseElement, STATE_WIDTH>;
 
     fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
         // convert the elements into a list of base field elements
+        #[cfg(not(feature = "safe_only"))]
         let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = E::to_base_elements(elements);
 
         // initialize state to all zeros, except for the last element of the capacity part, which
         // is set to the number of elements to be hashed. this is done so that adding zero elements
@@ -226,6 +248,38 @@ impl ElementHasher for Rp62_248 {
         // return the first 4 elements of the state as hash result
         ElementDigest::new(state[..DIGEST_SIZE].try_into().unwrap())
     }
+
+    fn begin(num_elements: usize) -> Self::State {
+        // the number of elements to be hashed is injected into the capacity portion of the state
+        // in the same way as in hash_elements()
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[STATE_WIDTH - 1] = BaseElement::new(num_elements as u64);
+        SpongeState::new(state)
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = &E::to_base_elements(elements);
+
+        state.absorb(elements, 0..RATE_WIDTH, apply_permutation);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        let (mut state, num_pending) = state.into_parts();
+        if num_pending > 0 {
+            apply_permutation(&mut state);
+        }
+        ElementDigest::new(state[..DIGEST_SIZE].try_into().unwrap())
+    }
+
+    fn digest_as_elements(digest: &Self::Digest) -> Option<&[Self::BaseField]> {
+        Some(digest.as_elements())
+    }
 }
 
 // RESCUE PERMUTATION
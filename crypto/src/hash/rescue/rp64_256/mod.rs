@@ -4,10 +4,12 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::super::mds::mds_f64_12x12::mds_multiply;
-use super::{exp_acc, Digest, ElementHasher, Hasher};
+use super::{exp_acc, Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
 use core::convert::TryInto;
 use core::ops::Range;
 use math::{fields::f64::BaseElement, FieldElement, StarkField};
+#[cfg(feature = "safe_only")]
+use utils::collections::Vec;
 
 mod digest;
 pub use digest::ElementDigest;
@@ -120,6 +122,8 @@ impl Hasher for Rp64_256 {
 
     const COLLISION_RESISTANCE: u32 = 128;
 
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Rp64_256);
+
     fn hash(bytes: &[u8]) -> Self::Digest {
         // compute the number of elements required to represent the string; we will be processing
         // the string in 7-byte chunks, thus the number of elements will be equal to the number
@@ -183,7 +187,10 @@ impl Hasher for Rp64_256 {
         // (8 total elements), and set the first capacity element to 8 (the number of elements to
         // be hashed).
         let mut state = [BaseElement::ZERO; STATE_WIDTH];
-        state[RATE_RANGE].copy_from_slice(Self::Digest::digests_as_elements(values));
+        state[RATE_RANGE.start..RATE_RANGE.start + DIGEST_SIZE]
+            .copy_from_slice(values[0].as_elements());
+        state[RATE_RANGE.start + DIGEST_SIZE..RATE_RANGE.end]
+            .copy_from_slice(values[1].as_elements());
         state[CAPACITY_RANGE.start] = BaseElement::new(RATE_WIDTH as u64);
 
         // apply the Rescue permutation and return the first four elements of the state
@@ -191,6 +198,19 @@ impl Hasher for Rp64_256 {
         ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
     }
 
+    fn merge_many(values: &[Self::Digest]) -> Self::Digest {
+        // digests consist of base field elements, and thus, can be hashed as a sequence of
+        // elements without going through their byte encoding
+        #[cfg(not(feature = "safe_only"))]
+        let elements = ElementDigest::digests_as_elements(values);
+        #[cfg(feature = "safe_only")]
+        let elements = &values
+            .iter()
+            .flat_map(|digest| digest.as_elements().iter().copied())
+            .collect::<Vec<_>>();
+        Self::hash_elements(elements)
+    }
+
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
         // initialize the state as follows:
         // - seed is copied into the first 4 elements of the rate portion of the state.
@@ -217,10 +237,14 @@ impl Hasher for Rp64_256 {
 
 impl ElementHasher for Rp64_256 {
     type BaseField = BaseElement;
+    type State = SpongeState<BaseElement, STATE_WIDTH>;
 
     fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
         // convert the elements into a list of base field elements
+        #[cfg(not(feature = "safe_only"))]
         let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = E::to_base_elements(elements);
 
         // initialize state to all zeros, except for the last element of the capacity part, which
         // is set to the number of elements to be hashed. this is done so that adding zero elements
@@ -252,6 +276,38 @@ impl ElementHasher for Rp64_256 {
         // return the first 4 elements of the state as hash result
         ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
     }
+
+    fn begin(num_elements: usize) -> Self::State {
+        // the number of elements to be hashed is injected into the capacity portion of the state
+        // in the same way as in hash_elements()
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        state[CAPACITY_RANGE.start] = BaseElement::new(num_elements as u64);
+        SpongeState::new(state)
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = &E::to_base_elements(elements);
+
+        state.absorb(elements, RATE_RANGE, Self::apply_permutation);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        let (mut state, num_pending) = state.into_parts();
+        if num_pending > 0 {
+            Self::apply_permutation(&mut state);
+        }
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn digest_as_elements(digest: &Self::Digest) -> Option<&[Self::BaseField]> {
+        Some(digest.as_elements())
+    }
 }
 
 // HASH FUNCTION IMPLEMENTATION
@@ -119,6 +119,28 @@ fn hash_elements_vs_merge() {
     assert_eq!(m_result, h_result);
 }
 
+#[test]
+fn hash_elements_vs_merge_many() {
+    let elements: [BaseElement; 16] = rand_array();
+
+    let digests: [ElementDigest; 4] = [
+        ElementDigest::new(elements[..4].try_into().unwrap()),
+        ElementDigest::new(elements[4..8].try_into().unwrap()),
+        ElementDigest::new(elements[8..12].try_into().unwrap()),
+        ElementDigest::new(elements[12..].try_into().unwrap()),
+    ];
+
+    let m_result = Rp64_256::merge_many(&digests);
+    let h_result = Rp64_256::hash_elements(&elements);
+    assert_eq!(m_result, h_result);
+
+    // merging two digests as a sequence should be the same as merging them as a pair
+    assert_eq!(
+        Rp64_256::merge(&[digests[0], digests[1]]),
+        Rp64_256::merge_many(&digests[..2])
+    );
+}
+
 #[test]
 fn hash_elements_vs_merge_with_int() {
     let seed = ElementDigest::new(rand_array());
@@ -199,7 +221,7 @@ proptest! {
         for i in 0..STATE_WIDTH {
             v1[i] = BaseElement::new(a[i]);
         }
-        v2 = v1.clone();
+        v2 = v1;
 
         apply_mds_naive(&mut v1);
         Rp64_256::apply_mds(&mut v2);
@@ -4,10 +4,12 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::super::mds::mds_f64_8x8::mds_multiply;
-use super::{exp_acc, Digest, ElementHasher, Hasher};
+use super::{exp_acc, Digest, ElementHasher, HashFunctionId, Hasher, SpongeState};
 use core::convert::TryInto;
 use core::ops::Range;
 use math::{fields::f64::BaseElement, FieldElement, StarkField};
+#[cfg(feature = "safe_only")]
+use utils::collections::Vec;
 
 mod digest;
 pub use digest::ElementDigest;
@@ -116,6 +118,8 @@ impl Hasher for RpJive64_256 {
 
     const COLLISION_RESISTANCE: u32 = 128;
 
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::RpJive64_256);
+
     fn hash(bytes: &[u8]) -> Self::Digest {
         // compute the number of elements required to represent the string; we will be processing
         // the string in 7-byte chunks, thus the number of elements will be equal to the number
@@ -185,9 +189,9 @@ impl Hasher for RpJive64_256 {
     // the Jive compression mode designed in https://eprint.iacr.org/2022/840.pdf.
     fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
         // initialize the state by copying the digest elements into the state
-        let initial_state: [BaseElement; STATE_WIDTH] = Self::Digest::digests_as_elements(values)
-            .try_into()
-            .unwrap();
+        let mut initial_state = [BaseElement::ZERO; STATE_WIDTH];
+        initial_state[..DIGEST_SIZE].copy_from_slice(values[0].as_elements());
+        initial_state[DIGEST_SIZE..].copy_from_slice(values[1].as_elements());
         let mut state = initial_state;
 
         // apply the Rescue permutation and apply the final Jive summation
@@ -198,6 +202,19 @@ impl Hasher for RpJive64_256 {
 
     // We do not rely on the sponge construction to build our compression function. Instead, we use
     // the Jive compression mode designed in https://eprint.iacr.org/2022/840.pdf.
+    fn merge_many(values: &[Self::Digest]) -> Self::Digest {
+        // digests consist of base field elements, and thus, can be hashed as a sequence of
+        // elements without going through their byte encoding
+        #[cfg(not(feature = "safe_only"))]
+        let elements = ElementDigest::digests_as_elements(values);
+        #[cfg(feature = "safe_only")]
+        let elements = &values
+            .iter()
+            .flat_map(|digest| digest.as_elements().iter().copied())
+            .collect::<Vec<_>>();
+        Self::hash_elements(elements)
+    }
+
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
         // initialize the state as follows:
         // - seed is copied into the first 4 elements of the state.
@@ -226,10 +243,14 @@ impl Hasher for RpJive64_256 {
 
 impl ElementHasher for RpJive64_256 {
     type BaseField = BaseElement;
+    type State = SpongeState<BaseElement, STATE_WIDTH>;
 
     fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
         // convert the elements into a list of base field elements
+        #[cfg(not(feature = "safe_only"))]
         let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = E::to_base_elements(elements);
 
         // initialize state to all zeros, except for the first element of the capacity part, which
         // is set to 1 if the number of elements is not a multiple of RATE_WIDTH.
@@ -268,6 +289,46 @@ impl ElementHasher for RpJive64_256 {
         // return the first 4 elements of the state as hash result
         ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
     }
+
+    fn begin(num_elements: usize) -> Self::State {
+        // the capacity portion of the state is initialized in the same way as in hash_elements()
+        let mut state = [BaseElement::ZERO; STATE_WIDTH];
+        if num_elements % RATE_WIDTH != 0 {
+            state[CAPACITY_RANGE.start] = BaseElement::ONE;
+        }
+        SpongeState::new(state)
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        #[cfg(not(feature = "safe_only"))]
+        let elements = E::as_base_elements(elements);
+        #[cfg(feature = "safe_only")]
+        let elements = &E::to_base_elements(elements);
+
+        state.absorb(elements, RATE_RANGE, Self::apply_permutation);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        // pad the input in the same way as in hash_elements()
+        let (mut state, mut i) = state.into_parts();
+        if i > 0 {
+            state[RATE_RANGE.start + i] = BaseElement::ONE;
+            i += 1;
+            while i != RATE_WIDTH {
+                state[RATE_RANGE.start + i] = BaseElement::ZERO;
+                i += 1;
+            }
+            Self::apply_permutation(&mut state);
+        }
+        ElementDigest::new(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn digest_as_elements(digest: &Self::Digest) -> Option<&[Self::BaseField]> {
+        Some(digest.as_elements())
+    }
 }
 
 // HASH FUNCTION IMPLEMENTATION
@@ -3,12 +3,15 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{ByteDigest, ElementHasher, Hasher};
+use super::{ByteDigest, ElementHasher, HashFunctionId, Hasher};
 use core::marker::PhantomData;
 use math::{FieldElement, StarkField};
 use sha3::Digest;
 use utils::ByteWriter;
 
+#[cfg(all(test, feature = "f64", feature = "quadratic"))]
+mod tests;
+
 // SHA3 WITH 256-BIT OUTPUT
 // ================================================================================================
 
@@ -21,12 +24,17 @@ impl<B: StarkField> Hasher for Sha3_256<B> {
 
     const COLLISION_RESISTANCE: u32 = 128;
 
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Sha3_256);
+
     fn hash(bytes: &[u8]) -> Self::Digest {
         ByteDigest(sha3::Sha3_256::digest(bytes).into())
     }
 
     fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
-        ByteDigest(sha3::Sha3_256::digest(ByteDigest::digests_as_bytes(values)).into())
+        let mut data = [0; 64];
+        data[..32].copy_from_slice(&values[0].0);
+        data[32..].copy_from_slice(&values[1].0);
+        ByteDigest(sha3::Sha3_256::digest(data).into())
     }
 
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
@@ -39,40 +47,157 @@ impl<B: StarkField> Hasher for Sha3_256<B> {
 
 impl<B: StarkField> ElementHasher for Sha3_256<B> {
     type BaseField = B;
+    type State = ShaHasher;
 
     fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // when element's internal and canonical representations are the same, we can hash
+        // element bytes directly
+        #[cfg(not(feature = "safe_only"))]
         if B::IS_CANONICAL {
-            // when element's internal and canonical representations are the same, we can hash
-            // element bytes directly
             let bytes = E::elements_as_bytes(elements);
-            ByteDigest(sha3::Sha3_256::digest(bytes).into())
-        } else {
-            // when elements' internal and canonical representations differ, we need to serialize
-            // them before hashing
-            let mut hasher = ShaHasher::new();
-            hasher.write(elements);
-            ByteDigest(hasher.finalize())
+            return ByteDigest(sha3::Sha3_256::digest(bytes).into());
         }
+
+        // when elements' internal and canonical representations differ (or when `safe_only`
+        // feature is enabled), we need to serialize them before hashing
+        let mut hasher = ShaHasher::<sha3::Sha3_256>::new();
+        hasher.write(elements);
+        ByteDigest(hasher.finalize())
+    }
+
+    fn begin(_num_elements: usize) -> Self::State {
+        ShaHasher::new()
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        state.absorb(elements);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        ByteDigest(state.finalize())
+    }
+}
+
+// KECCAK WITH 256-BIT OUTPUT
+// ================================================================================================
+
+/// Implementation of the [Hasher](super::Hasher) trait for Keccak hash function with 256-bit
+/// output.
+///
+/// This is the variant of Keccak used by Ethereum (i.e., `keccak256`); it differs from SHA3-256
+/// only in the padding rule, and thus digests computed by this hasher can be reproduced by EVM
+/// contracts byte-for-byte:
+/// * [hash()](Hasher::hash) returns `keccak256(bytes)`.
+/// * [merge()](Hasher::merge) returns `keccak256(left || right)` where `left` and `right` are the
+///   32-byte digests being merged.
+/// * [merge_with_int()](Hasher::merge_with_int) returns `keccak256(seed || value)` where `value`
+///   is encoded as 8 little-endian bytes.
+/// * [hash_elements()](ElementHasher::hash_elements) returns `keccak256` of the concatenation of
+///   element encodings. Each base field element is encoded as its canonical integer value in
+///   little-endian byte order using exactly `B::ELEMENT_BYTES` bytes; an extension field element
+///   is encoded as the concatenation of encodings of its base field coefficients.
+#[allow(non_camel_case_types)]
+pub struct Keccak_256<B: StarkField>(PhantomData<B>);
+
+impl<B: StarkField> Hasher for Keccak_256<B> {
+    type Digest = ByteDigest<32>;
+
+    const COLLISION_RESISTANCE: u32 = 128;
+
+    const ID: Option<HashFunctionId> = Some(HashFunctionId::Keccak256);
+
+    fn hash(bytes: &[u8]) -> Self::Digest {
+        ByteDigest(sha3::Keccak256::digest(bytes).into())
+    }
+
+    fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
+        let mut data = [0; 64];
+        data[..32].copy_from_slice(&values[0].0);
+        data[32..].copy_from_slice(&values[1].0);
+        ByteDigest(sha3::Keccak256::digest(data).into())
+    }
+
+    fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
+        let mut data = [0; 40];
+        data[..32].copy_from_slice(&seed.0);
+        data[32..].copy_from_slice(&value.to_le_bytes());
+        ByteDigest(sha3::Keccak256::digest(data).into())
+    }
+}
+
+impl<B: StarkField> ElementHasher for Keccak_256<B> {
+    type BaseField = B;
+    type State = ShaHasher<sha3::Keccak256>;
+
+    fn hash_elements<E: FieldElement<BaseField = Self::BaseField>>(elements: &[E]) -> Self::Digest {
+        // when element's internal and canonical representations are the same, we can hash
+        // element bytes directly
+        #[cfg(not(feature = "safe_only"))]
+        if B::IS_CANONICAL {
+            let bytes = E::elements_as_bytes(elements);
+            return ByteDigest(sha3::Keccak256::digest(bytes).into());
+        }
+
+        // when elements' internal and canonical representations differ (or when `safe_only`
+        // feature is enabled), we need to serialize them before hashing
+        let mut hasher = ShaHasher::<sha3::Keccak256>::new();
+        hasher.write(elements);
+        ByteDigest(hasher.finalize())
+    }
+
+    fn begin(_num_elements: usize) -> Self::State {
+        ShaHasher::new()
+    }
+
+    fn absorb<E: FieldElement<BaseField = Self::BaseField>>(
+        state: &mut Self::State,
+        elements: &[E],
+    ) {
+        state.absorb(elements);
+    }
+
+    fn finalize(state: Self::State) -> Self::Digest {
+        ByteDigest(state.finalize())
     }
 }
 
 // SHA HASHER
 // ================================================================================================
 
-/// Wrapper around SHA3 hasher to implement [ByteWriter] trait for it.
-struct ShaHasher(sha3::Sha3_256);
+/// Wrapper around SHA3 (or any other [Digest]) hasher to implement [ByteWriter] trait for it.
+///
+/// This is also used as the state of incremental hash computations for SHA3, Keccak, and BLAKE2s
+/// hashers.
+pub struct ShaHasher<D: Digest = sha3::Sha3_256>(D);
 
-impl ShaHasher {
+impl<D: Digest> ShaHasher<D> {
     pub fn new() -> Self {
-        Self(sha3::Sha3_256::new())
+        Self(D::new())
+    }
+
+    /// Absorbs the provided elements into this hasher in the same way as
+    /// [hash_elements()](ElementHasher::hash_elements) does.
+    pub fn absorb<E: FieldElement>(&mut self, elements: &[E]) {
+        #[cfg(not(feature = "safe_only"))]
+        if E::BaseField::IS_CANONICAL {
+            self.0.update(E::elements_as_bytes(elements));
+            return;
+        }
+
+        self.write(elements);
     }
 
     pub fn finalize(self) -> [u8; 32] {
-        self.0.finalize().into()
+        let mut result = [0; 32];
+        result.copy_from_slice(&self.0.finalize());
+        result
     }
 }
 
-impl ByteWriter for ShaHasher {
+impl<D: Digest> ByteWriter for ShaHasher<D> {
     fn write_u8(&mut self, value: u8) {
         self.0.update([value]);
     }
@@ -0,0 +1,88 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{ByteDigest, ElementHasher, Hasher, Keccak_256, Sha3_256};
+use math::{
+    fields::{f64::BaseElement, QuadExtension},
+    FieldElement,
+};
+use utils::{collections::Vec, string::String, Serializable};
+
+type Keccak = Keccak_256<BaseElement>;
+
+// KECCAK-256 KNOWN-ANSWER TESTS
+// ================================================================================================
+// Expected values match `keccak256()` as computed by the EVM for the same byte strings.
+
+#[test]
+fn keccak_hash() {
+    let expected = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+    assert_eq!(expected, to_hex(Keccak::hash(b"")));
+
+    let expected = "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45";
+    assert_eq!(expected, to_hex(Keccak::hash(b"abc")));
+
+    // 200 bytes span two blocks of the sponge
+    let bytes = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+    let expected = "bfb0aa97863e797943cf7c33bb7e880bb4543f3d2703c0923c6901c2af57b890";
+    assert_eq!(expected, to_hex(Keccak::hash(&bytes)));
+
+    // Keccak-256 and SHA3-256 differ only in padding, so their digests must differ
+    assert_ne!(Keccak::hash(b"abc"), Sha3_256::<BaseElement>::hash(b"abc"));
+}
+
+#[test]
+fn keccak_merge() {
+    // keccak256(bytes32(0))
+    let zero = Keccak::hash(&[0; 32]);
+    let expected = "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563";
+    assert_eq!(expected, to_hex(zero));
+
+    // keccak256(abi.encodePacked(bytes32(0), bytes32(0)))
+    let expected = "ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5";
+    assert_eq!(
+        expected,
+        to_hex(Keccak::merge(&[ByteDigest([0; 32]), ByteDigest([0; 32])]))
+    );
+}
+
+#[test]
+fn keccak_merge_with_int() {
+    // keccak256(seed || 42) with the integer encoded as 8 little-endian bytes
+    let mut seed = [0; 32];
+    seed.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+    let expected = "434b9fadf83ee0c3ac2427a9496932984e6b9cb7436cb2b1abd2de9cfdd79350";
+    assert_eq!(
+        expected,
+        to_hex(Keccak::merge_with_int(ByteDigest(seed), 42))
+    );
+}
+
+#[test]
+fn keccak_hash_elements() {
+    // each element is encoded as its canonical value in 8 little-endian bytes
+    let elements = [BaseElement::ONE, BaseElement::new(2), -BaseElement::ONE];
+    let expected = "7cbed7fea083d08efb80fbc42df18f2e20b2cc3b9581c30a2a29d5ef14341278";
+    assert_eq!(expected, to_hex(Keccak::hash_elements(&elements)));
+
+    let mut state = Keccak::begin(elements.len());
+    Keccak::absorb(&mut state, &elements[..1]);
+    Keccak::absorb(&mut state, &elements[1..]);
+    assert_eq!(expected, to_hex(Keccak::finalize(state)));
+
+    // extension elements are encoded as the concatenation of their coefficients
+    let elements = [QuadExtension::new(BaseElement::ONE, BaseElement::new(2))];
+    let mut bytes = Vec::new();
+    BaseElement::ONE.write_into(&mut bytes);
+    BaseElement::new(2).write_into(&mut bytes);
+    assert_eq!(Keccak::hash(&bytes), Keccak::hash_elements(&elements));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn to_hex(digest: ByteDigest<32>) -> String {
+    digest.0.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,172 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Hash-to-field and seed expansion utilities.
+//!
+//! Field elements are derived from a seed digest using an expand-and-reduce construction:
+//!
+//! 1. **Expand** - the seed is expanded into a stream of blocks, where the i-th block is
+//!    hash(`seed` || `i`) computed via [Hasher::merge_with_int()] with `i` starting at 1.
+//! 2. **Reduce** - every base field element consumes two consecutive blocks; the first 16 bytes
+//!    of each block are concatenated into a 256-bit integer in little-endian byte order, and the
+//!    integer is reduced modulo the field modulus.
+//!
+//! For a field with modulus $p < 2^{128}$, the statistical distance between the distribution of
+//! the reduced value and the uniform distribution over the field is at most $p / 2^{256}$, i.e.,
+//! at most $2^{-128}$ for both 64-bit and 128-bit fields. Elements in an extension field are
+//! built from consecutive base field elements.
+//!
+//! The same construction is used by [DefaultRandomCoin](crate::DefaultRandomCoin) to draw field
+//! elements, and thus, elements derived from external data and elements drawn from the coin have
+//! the same distribution.
+
+use crate::{Digest, Hasher};
+use core::convert::TryInto;
+use math::{FieldElement, StarkField};
+use utils::{
+    collections::{InlineVec, Vec},
+    Serializable, SliceReader,
+};
+
+#[cfg(all(test, feature = "f64", feature = "f128", feature = "quadratic"))]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Maximum size of an encoded field element (i.e., an element of a degree 3 extension of a
+/// 128-bit field) for which elements can be derived without heap allocations.
+const MAX_ELEMENT_BYTES: usize = 48;
+
+// HASH TO FIELD
+// ================================================================================================
+
+/// Returns `count` base field elements derived from the specified message.
+///
+/// The seed for the expansion is hash(`len(domain_tag)` || `domain_tag` || `msg`), where the
+/// length of the domain tag is encoded as a single byte; using distinct domain tags for distinct
+/// purposes guarantees that the derived elements are independent even for the same message.
+///
+/// # Panics
+/// Panics if:
+/// * `domain_tag` is longer than 255 bytes.
+/// * The modulus of the base field is greater than 128 bits.
+///
+/// # Examples
+/// ```
+/// # use winter_crypto::{hash_to_field, hashers::Blake3_256};
+/// # use math::fields::f128::BaseElement;
+/// type Blake3 = Blake3_256<BaseElement>;
+///
+/// let e1 = hash_to_field::<BaseElement, Blake3>(b"block-hash", &[1, 2, 3, 4], 2);
+/// let e2 = hash_to_field::<BaseElement, Blake3>(b"block-hash", &[1, 2, 3, 4], 2);
+/// assert_eq!(2, e1.len());
+/// assert_eq!(e1, e2);
+///
+/// // different domain tags result in different elements
+/// let e3 = hash_to_field::<BaseElement, Blake3>(b"user-id", &[1, 2, 3, 4], 2);
+/// assert_ne!(e1, e3);
+/// ```
+pub fn hash_to_field<B, H>(domain_tag: &[u8], msg: &[u8], count: usize) -> Vec<B>
+where
+    B: StarkField,
+    H: Hasher,
+{
+    let seed = hash_message::<H>(domain_tag, msg);
+    let mut counter = 0;
+    (0..count)
+        .map(|_| expand_to_base_element::<B, H>(seed, &mut counter))
+        .collect()
+}
+
+/// Returns `count` extension field elements derived from the specified message.
+///
+/// Each element of the extension field is built from consecutive base field elements derived in
+/// the same way as in [hash_to_field()]; for the base field itself, the result is the same as the
+/// result of [hash_to_field()].
+///
+/// # Panics
+/// Panics if:
+/// * `domain_tag` is longer than 255 bytes.
+/// * The modulus of the base field is greater than 128 bits.
+pub fn hash_to_extension_field<E, H>(domain_tag: &[u8], msg: &[u8], count: usize) -> Vec<E>
+where
+    E: FieldElement,
+    H: Hasher,
+{
+    let seed = hash_message::<H>(domain_tag, msg);
+    let mut counter = 0;
+    (0..count)
+        .map(|_| expand_to_element::<E, H>(seed, &mut counter))
+        .collect()
+}
+
+// SEED EXPANSION
+// ================================================================================================
+
+/// Returns an element of the field `E` derived from the blocks following `counter` in the
+/// expansion of the specified seed, and advances the counter past the consumed blocks.
+pub(crate) fn expand_to_element<E, H>(seed: H::Digest, counter: &mut u64) -> E
+where
+    E: FieldElement,
+    H: Hasher,
+{
+    let extension_degree = E::ELEMENT_BYTES / E::BaseField::ELEMENT_BYTES;
+    if extension_degree == 1 {
+        let element = expand_to_base_element::<E::BaseField, H>(seed, counter);
+        return E::from(element);
+    }
+
+    // extension field elements are serialized as sequences of base field elements, and thus,
+    // we build an element by deserializing the canonical encoding of its base field elements;
+    // the encoding of any supported element fits into the inline buffer
+    let mut bytes = InlineVec::<u8, MAX_ELEMENT_BYTES>::new();
+    for _ in 0..extension_degree {
+        expand_to_base_element::<E::BaseField, H>(seed, counter).write_into(&mut bytes);
+    }
+    E::read_from(&mut SliceReader::new(&bytes))
+        .expect("failed to build an extension field element from base field elements")
+}
+
+/// Returns a base field element derived from the two blocks following `counter` in the
+/// expansion of the specified seed, and advances the counter by 2.
+pub(crate) fn expand_to_base_element<B, H>(seed: H::Digest, counter: &mut u64) -> B
+where
+    B: StarkField,
+    H: Hasher,
+{
+    assert!(
+        B::MODULUS_BITS <= 128,
+        "field modulus cannot be greater than 128 bits"
+    );
+
+    let mut next_block = || {
+        *counter += 1;
+        let block = H::merge_with_int(seed, *counter).as_bytes();
+        u128::from_le_bytes(block[..16].try_into().unwrap())
+    };
+    let low = next_block();
+    let high = next_block();
+
+    // compute (low + high * 2^128) mod p; 2^128 mod p is computed as (2^128 - 1) + 1
+    let shift = B::from(u128::MAX) + B::ONE;
+    B::from(low) + B::from(high) * shift
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns hash(`len(domain_tag)` || `domain_tag` || `msg`).
+fn hash_message<H: Hasher>(domain_tag: &[u8], msg: &[u8]) -> H::Digest {
+    assert!(
+        domain_tag.len() <= u8::MAX as usize,
+        "domain tag cannot be longer than 255 bytes"
+    );
+    let mut data = Vec::with_capacity(domain_tag.len() + msg.len() + 1);
+    data.push(domain_tag.len() as u8);
+    data.extend_from_slice(domain_tag);
+    data.extend_from_slice(msg);
+    H::hash(&data)
+}
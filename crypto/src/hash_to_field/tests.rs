@@ -0,0 +1,151 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::*;
+use crate::{
+    hash::{Blake3_256, Sha3_256},
+    DefaultRandomCoin,
+};
+use math::fields::{f128, f64, QuadExtension};
+
+type Blake3F64 = Blake3_256<f64::BaseElement>;
+type Blake3F128 = Blake3_256<f128::BaseElement>;
+type Sha3F64 = Sha3_256<f64::BaseElement>;
+type Sha3F128 = Sha3_256<f128::BaseElement>;
+
+const DOMAIN_TAG: &[u8] = b"winterfell-test";
+
+// KNOWN-ANSWER TESTS
+// ================================================================================================
+
+#[test]
+fn hash_to_field_vectors_f64() {
+    let expected = [
+        2011441758818377401,
+        5611739984838810214,
+        3674054722322621853,
+    ];
+    let result = hash_to_field::<f64::BaseElement, Blake3F64>(DOMAIN_TAG, b"hello", 3);
+    assert_eq!(to_elements_f64(&expected), result);
+
+    let expected = [
+        18427671211797565436,
+        16174509624610864303,
+        13062402061614387218,
+    ];
+    let result = hash_to_field::<f64::BaseElement, Sha3F64>(DOMAIN_TAG, b"hello", 3);
+    assert_eq!(to_elements_f64(&expected), result);
+}
+
+#[test]
+fn hash_to_field_vectors_f128() {
+    let expected = [
+        19069699632398975721143352989405377664,
+        194237062156730058605810677219283456595,
+        129025272075306518815964260352518851340,
+    ];
+    let result = hash_to_field::<f128::BaseElement, Blake3F128>(DOMAIN_TAG, b"hello", 3);
+    assert_eq!(to_elements_f128(&expected), result);
+
+    let expected = [
+        176144751457622752394812304622928062877,
+        1141074826715228769149136790554347,
+        115166715690392806957408242450562597868,
+    ];
+    let result = hash_to_field::<f128::BaseElement, Sha3F128>(DOMAIN_TAG, b"hello", 3);
+    assert_eq!(to_elements_f128(&expected), result);
+}
+
+// CONSISTENCY TESTS
+// ================================================================================================
+
+#[test]
+fn hash_to_field_domain_separation() {
+    let e1 = hash_to_field::<f128::BaseElement, Blake3F128>(b"tag-a", b"message", 4);
+    let e2 = hash_to_field::<f128::BaseElement, Blake3F128>(b"tag-b", b"message", 4);
+    assert_ne!(e1, e2);
+
+    // moving bytes between the domain tag and the message changes the result
+    let e3 = hash_to_field::<f128::BaseElement, Blake3F128>(b"tag-", b"amessage", 4);
+    assert_ne!(e1, e3);
+
+    // requesting more elements extends the sequence
+    let e4 = hash_to_field::<f128::BaseElement, Blake3F128>(b"tag-a", b"message", 6);
+    assert_eq!(e1, e4[..4]);
+}
+
+#[test]
+#[should_panic(expected = "domain tag cannot be longer than 255 bytes")]
+fn hash_to_field_long_domain_tag() {
+    hash_to_field::<f128::BaseElement, Blake3F128>(&[0; 256], b"message", 1);
+}
+
+#[test]
+fn hash_to_extension_field_consistency() {
+    type E = QuadExtension<f64::BaseElement>;
+
+    let base = hash_to_field::<f64::BaseElement, Blake3F64>(DOMAIN_TAG, b"hello", 6);
+    let result = hash_to_extension_field::<E, Blake3F64>(DOMAIN_TAG, b"hello", 3);
+    let expected = base
+        .chunks(2)
+        .map(|c| E::new(c[0], c[1]))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, result);
+
+    // for the base field, the result is the same as the result of hash_to_field()
+    let result = hash_to_extension_field::<f64::BaseElement, Blake3F64>(DOMAIN_TAG, b"hello", 6);
+    assert_eq!(base, result);
+}
+
+#[test]
+fn random_coin_consistency() {
+    // the coin seeded with the domain-separated message draws the same elements
+    let mut seed = vec![DOMAIN_TAG.len() as u8];
+    seed.extend_from_slice(DOMAIN_TAG);
+    seed.extend_from_slice(b"hello");
+    let mut coin = DefaultRandomCoin::<f64::BaseElement, Blake3F64>::new(&seed);
+
+    let expected = hash_to_extension_field::<QuadExtension<_>, Blake3F64>(DOMAIN_TAG, b"hello", 4);
+    for element in expected {
+        assert_eq!(element, coin.draw::<QuadExtension<_>>().unwrap());
+    }
+}
+
+// BIAS TESTS
+// ================================================================================================
+
+#[test]
+fn hash_to_field_chi_square() {
+    const NUM_BUCKETS: usize = 16;
+    const NUM_SAMPLES: usize = 16_000;
+
+    // the f64 modulus is not a power of two; a biased reduction would skew the distribution of
+    // the low bits of the drawn elements
+    let elements = hash_to_field::<f64::BaseElement, Blake3F64>(DOMAIN_TAG, b"bias", NUM_SAMPLES);
+    let mut buckets = [0usize; NUM_BUCKETS];
+    for element in elements {
+        buckets[(element.as_int() % NUM_BUCKETS as u64) as usize] += 1;
+    }
+
+    // the critical value of the chi-square distribution with 15 degrees of freedom is ~37.7 for
+    // p = 0.001; a threshold of 50 accepts any reasonably uniform distribution
+    let expected = (NUM_SAMPLES / NUM_BUCKETS) as f64;
+    let chi_square: f64 = buckets
+        .iter()
+        .map(|&count| (count as f64 - expected).powi(2) / expected)
+        .sum();
+    assert!(chi_square < 50.0, "chi-square statistic is {chi_square}");
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn to_elements_f64(values: &[u64]) -> Vec<f64::BaseElement> {
+    values.iter().map(|&v| f64::BaseElement::new(v)).collect()
+}
+
+fn to_elements_f128(values: &[u128]) -> Vec<f128::BaseElement> {
+    values.iter().map(|&v| f128::BaseElement::new(v)).collect()
+}
@@ -11,39 +11,68 @@
 //! * **Merkle trees** - which are used as a commitment scheme in the STARK protocol. The
 //!   [MerkleTree] implementation supports concurrent tree construction as well as compact
 //!   aggregation of Merkle paths implemented using a variation of the
-//!   [Octopus](https://eprint.iacr.org/2017/933) algorithm.
-//! * **PRNG** - which is used to generate pseudo-random elements in a finite field. The
-//!   [RandomCoin] implementation uses a cryptographic hash function to generate pseudo-random
-//!   elements form a seed.
+//!   [Octopus](https://eprint.iacr.org/2017/933) algorithm. Trees with arity 4 and 8 are also
+//!   supported to reduce the length of Merkle paths.
+//! * **Vector commitments** - which are defined using the [VectorCommitment] trait. [MerkleTree]
+//!   is the default implementation of the trait; other commitment schemes can be used by the
+//!   prover and the verifier by implementing it.
+//! * **PRNG** - which is used to generate pseudo-random elements in a finite field; PRNGs are
+//!   defined using the [RandomCoin] trait. The [DefaultRandomCoin] implementation uses a
+//!   cryptographic hash function to generate pseudo-random elements form a seed.
+//! * **Hash-to-field** - which is used to derive field elements from arbitrary data with negligible
+//!   bias. The [hash_to_field()] and [hash_to_extension_field()] functions use the same
+//!   construction as the [DefaultRandomCoin] to derive field elements.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(all(feature = "safe_only", not(test)), deny(unsafe_code))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
 extern crate alloc;
 
 mod hash;
-pub use hash::{Digest, ElementHasher, Hasher};
+pub use hash::{Digest, DynDigest, DynHasher, ElementHasher, HashFunctionId, Hasher};
 pub mod hashers {
     //! Contains implementations of currently supported hash functions.
 
+    pub use super::hash::Blake2s_256;
     pub use super::hash::Blake3_192;
     pub use super::hash::Blake3_256;
+    #[cfg(feature = "f64")]
     pub use super::hash::GriffinJive64_256;
+    pub use super::hash::Keccak_256;
+    #[cfg(feature = "f128")]
+    pub use super::hash::Poseidon128_256;
+    #[cfg(feature = "f64")]
+    pub use super::hash::Poseidon64_256;
+    #[cfg(feature = "f62")]
     pub use super::hash::Rp62_248;
+    #[cfg(feature = "f64")]
     pub use super::hash::Rp64_256;
+    #[cfg(feature = "f64")]
     pub use super::hash::RpJive64_256;
     pub use super::hash::Sha3_256;
 }
 
+mod commitment;
+pub use commitment::{CommitmentOpener, VectorCommitment};
+
 mod merkle;
-pub use merkle::{build_merkle_nodes, BatchMerkleProof, MerkleTree};
+pub use merkle::{
+    build_kary_merkle_nodes, build_merkle_nodes, BatchMerkleProof, MerkleTree, ProofNodes,
+};
 
 #[cfg(feature = "concurrent")]
 pub use merkle::concurrent;
 
 mod random;
-pub use random::RandomCoin;
+pub use random::{DefaultRandomCoin, RandomCoin};
+
+#[cfg(feature = "transcript-log")]
+pub mod transcript_log;
+
+mod hash_to_field;
+pub use hash_to_field::{hash_to_extension_field, hash_to_field};
 
 mod errors;
 pub use errors::{MerkleTreeError, RandomCoinError};
@@ -4,20 +4,29 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::Hasher;
-use core::slice;
-use utils::{collections::Vec, iterators::*, rayon};
+use math::log2;
+use utils::{collections::Vec, iterators::*};
+
+#[cfg(not(feature = "safe_only"))]
+use utils::rayon;
 
 // CONSTANTS
 // ================================================================================================
 
 pub const MIN_CONCURRENT_LEAVES: usize = 1024;
 
+/// Number of nodes built by a single call to [Hasher::merge_batch()] when a row of nodes is split
+/// between threads; hash functions which hash several inputs in parallel (e.g., using SIMD
+/// instructions) need batches of at least 8 - 16 inputs to make use of all SIMD lanes.
+const NODE_BATCH_SIZE: usize = 64;
+
 // PUBLIC FUNCTIONS
 // ================================================================================================
 
 /// Builds all internal nodes of the Merkle using all available threads and stores the
 /// results in a single vector such that root of the tree is at position 1, nodes immediately
 /// under the root is at positions 2 and 3 etc.
+#[cfg(not(feature = "safe_only"))]
 pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
     let n = leaves.len() / 2;
 
@@ -25,13 +34,11 @@ pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
     let mut nodes = unsafe { utils::uninit_vector::<H::Digest>(2 * n) };
     nodes[0] = H::Digest::default();
 
-    // re-interpret leaves as an array of two leaves fused together and use it to
-    // build first row of internal nodes (parents of leaves)
-    let two_leaves = unsafe { slice::from_raw_parts(leaves.as_ptr() as *const [H::Digest; 2], n) };
+    // build first row of internal nodes (parents of leaves) in batches
     nodes[n..]
-        .par_iter_mut()
-        .zip(two_leaves.par_iter())
-        .for_each(|(target, source)| *target = H::merge(source));
+        .par_chunks_mut(NODE_BATCH_SIZE)
+        .zip(leaves.par_chunks(2 * NODE_BATCH_SIZE))
+        .for_each(|(target, source)| H::merge_batch(source, 2, target));
 
     // calculate all other tree nodes, we can't use regular iterators  here because
     // access patterns are rather complicated - so, we use regular threads instead
@@ -40,10 +47,8 @@ pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
     let num_subtrees = rayon::current_num_threads().next_power_of_two();
     let batch_size = n / num_subtrees;
 
-    // re-interpret nodes as an array of two nodes fused together
-    let two_nodes = unsafe { slice::from_raw_parts(nodes.as_ptr() as *const [H::Digest; 2], n) };
-
-    // process each subtree in a separate thread
+    // process each subtree in a separate thread; within a subtree, every row of nodes is built
+    // by a single call to the hasher
     rayon::scope(|s| {
         for i in 0..num_subtrees {
             let nodes = unsafe { &mut *(&mut nodes[..] as *mut [H::Digest]) };
@@ -51,9 +56,12 @@ pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
                 let mut batch_size = batch_size / 2;
                 let mut start_idx = n / 2 + batch_size * i;
                 while start_idx >= num_subtrees {
-                    for k in (start_idx..(start_idx + batch_size)).rev() {
-                        nodes[k] = H::merge(&two_nodes[k]);
-                    }
+                    let (parents, children) = nodes.split_at_mut(2 * start_idx);
+                    H::merge_batch(
+                        &children[..2 * batch_size],
+                        2,
+                        &mut parents[start_idx..start_idx + batch_size],
+                    );
                     start_idx /= 2;
                     batch_size /= 2;
                 }
@@ -62,8 +70,79 @@ pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
     });
 
     // finish the tip of the tree
-    for i in (1..num_subtrees).rev() {
-        nodes[i] = H::merge(&two_nodes[i]);
+    let mut row_start = num_subtrees / 2;
+    while row_start > 0 {
+        let (parents, children) = nodes.split_at_mut(2 * row_start);
+        H::merge_batch(&children[..2 * row_start], 2, &mut parents[row_start..]);
+        row_start /= 2;
+    }
+
+    nodes
+}
+
+/// Builds all internal nodes of the Merkle using all available threads and stores the
+/// results in a single vector such that root of the tree is at position 1, nodes immediately
+/// under the root is at positions 2 and 3 etc.
+///
+/// Sub-trees cannot be processed in separate threads without sharing mutable references to the
+/// vector of nodes, and thus, when `safe_only` feature is enabled, the nodes are built one tree
+/// level at a time with nodes within a level being built in parallel.
+#[cfg(feature = "safe_only")]
+pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
+    let n = leaves.len() / 2;
+    let mut nodes = vec![H::Digest::default(); 2 * n];
+
+    // build first row of internal nodes (parents of leaves)
+    nodes[n..]
+        .par_chunks_mut(NODE_BATCH_SIZE)
+        .zip(leaves.par_chunks(2 * NODE_BATCH_SIZE))
+        .for_each(|(target, source)| H::merge_batch(source, 2, target));
+
+    // build all other rows of internal nodes, starting with the row which is the closest to the
+    // leaves; children of nodes in the row at positions [k, 2k) are at positions [2k, 4k)
+    let mut row_start = n / 2;
+    while row_start > 0 {
+        let (parents, children) = nodes.split_at_mut(2 * row_start);
+        parents[row_start..]
+            .par_chunks_mut(NODE_BATCH_SIZE)
+            .zip(children[..2 * row_start].par_chunks(2 * NODE_BATCH_SIZE))
+            .for_each(|(target, source)| H::merge_batch(source, 2, target));
+        row_start /= 2;
+    }
+
+    nodes
+}
+
+/// Builds all internal nodes of a Merkle tree with the specified `arity` using all available
+/// threads and stores the results in a single vector at the same positions as in a binary tree.
+///
+/// The nodes are built one tree level at a time with nodes within a level being built in
+/// parallel in batches of [NODE_BATCH_SIZE] nodes.
+pub fn build_kary_merkle_nodes<H: Hasher>(leaves: &[H::Digest], arity: usize) -> Vec<H::Digest> {
+    let n = leaves.len();
+    let mut nodes = vec![H::Digest::default(); n];
+
+    // build the row of internal nodes which are parents of leaves
+    let mut level_bits = super::level_bits(log2(n) as usize, arity).into_iter();
+    let bits = level_bits
+        .next()
+        .expect("a tree must have at least one level");
+    let mut row_start = n >> bits;
+    nodes[row_start..2 * row_start]
+        .par_chunks_mut(NODE_BATCH_SIZE)
+        .zip(leaves.par_chunks(NODE_BATCH_SIZE << bits))
+        .for_each(|(target, source)| H::merge_batch(source, 1 << bits, target));
+
+    // build all other rows of internal nodes, starting with the row which is the closest to the
+    // leaves
+    for bits in level_bits {
+        let (parents, children) = nodes.split_at_mut(row_start);
+        let parent_start = row_start >> bits;
+        parents[parent_start..2 * parent_start]
+            .par_chunks_mut(NODE_BATCH_SIZE)
+            .zip(children[..row_start].par_chunks(NODE_BATCH_SIZE << bits))
+            .for_each(|(target, source)| H::merge_batch(source, 1 << bits, target));
+        row_start = parent_start;
     }
 
     nodes
@@ -72,7 +151,7 @@ pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
 // TESTS
 // ================================================================================================
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests {
     use crate::hash::{ByteDigest, Sha3_256};
     use math::fields::f128::BaseElement;
@@ -82,10 +161,20 @@ mod tests {
     proptest! {
         #[test]
         fn build_merkle_nodes_concurrent(ref data in vec(any::<[u8; 32]>(), 256..257).no_shrink()) {
-            let leaves = ByteDigest::bytes_as_digests(&data).to_vec();
+            let leaves = data.iter().map(|&bytes| ByteDigest::new(bytes)).collect::<Vec<_>>();
             let sequential = super::super::build_merkle_nodes::<Sha3_256<BaseElement>>(&leaves);
             let concurrent = super::build_merkle_nodes::<Sha3_256<BaseElement>>(&leaves);
             assert_eq!(concurrent, sequential);
         }
+
+        #[test]
+        fn build_kary_merkle_nodes_concurrent(ref data in vec(any::<[u8; 32]>(), 256..257).no_shrink()) {
+            let leaves = data.iter().map(|&bytes| ByteDigest::new(bytes)).collect::<Vec<_>>();
+            for arity in [4, 8] {
+                let sequential = super::super::build_kary_merkle_nodes::<Sha3_256<BaseElement>>(&leaves, arity);
+                let concurrent = super::build_kary_merkle_nodes::<Sha3_256<BaseElement>>(&leaves, arity);
+                assert_eq!(concurrent, sequential);
+            }
+        }
     }
 }
@@ -3,18 +3,21 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::{errors::MerkleTreeError, hash::Hasher};
-use core::slice;
+use crate::{errors::MerkleTreeError, hash::Hasher, VectorCommitment};
 use math::log2;
-use utils::collections::{BTreeMap, BTreeSet, Vec};
+use utils::{
+    collections::{InlineVec, Vec},
+    string::ToString,
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
 
 mod proofs;
-pub use proofs::BatchMerkleProof;
+pub use proofs::{BatchMerkleProof, ProofNodes};
 
 #[cfg(feature = "concurrent")]
 pub mod concurrent;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // TYPES AND INTERFACES
@@ -58,6 +61,14 @@ mod tests;
 /// To verify proofs, [MerkleTree::verify()] and [MerkleTree::verify_batch()] functions can be
 /// used respectively.
 ///
+/// A tree with arity greater than 2 can be built using [MerkleTree::with_arity()] function. In
+/// such a tree, every internal node is a hash of 4 or 8 children computed via
+/// [Hasher::merge_many()], which reduces the number of tree levels by a factor of 2 or 3. When
+/// the depth of the tree is not a multiple of log2 of the arity, the root has fewer children than
+/// other internal nodes. Batch proofs for such trees contain all siblings of the nodes on the
+/// authentication paths at every level; single Merkle paths (i.e., [MerkleTree::prove()] and
+/// [MerkleTree::verify()]) are supported only for binary trees.
+///
 /// # Examples
 /// ```
 /// # use winter_crypto::{MerkleTree, Hasher, hashers::Blake3_256};
@@ -88,6 +99,17 @@ mod tests;
 pub struct MerkleTree<H: Hasher> {
     nodes: Vec<H::Digest>,
     leaves: Vec<H::Digest>,
+    arity: usize,
+}
+
+impl<H: Hasher> Clone for MerkleTree<H> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            leaves: self.leaves.clone(),
+            arity: self.arity,
+        }
+    }
 }
 
 // MERKLE TREE IMPLEMENTATION
@@ -106,6 +128,21 @@ impl<H: Hasher> MerkleTree<H> {
     /// * Fewer than two leaves were provided.
     /// * Number of leaves is not a power of two.
     pub fn new(leaves: Vec<H::Digest>) -> Result<Self, MerkleTreeError> {
+        Self::with_arity(leaves, 2)
+    }
+
+    /// Returns new Merkle tree with the specified `arity` built from the provided leaves using
+    /// hash function specified by the `H` generic parameter.
+    ///
+    /// When `concurrent` feature is enabled, the tree is built using multiple threads.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The arity is not 2, 4, or 8.
+    /// * Fewer than two leaves were provided.
+    /// * Number of leaves is not a power of two.
+    pub fn with_arity(leaves: Vec<H::Digest>, arity: usize) -> Result<Self, MerkleTreeError> {
+        validate_arity(arity)?;
         if leaves.len() < 2 {
             return Err(MerkleTreeError::TooFewLeaves(2, leaves.len()));
         }
@@ -114,16 +151,24 @@ impl<H: Hasher> MerkleTree<H> {
         }
 
         #[cfg(not(feature = "concurrent"))]
-        let nodes = build_merkle_nodes::<H>(&leaves);
+        let nodes = match arity {
+            2 => build_merkle_nodes::<H>(&leaves),
+            _ => build_kary_merkle_nodes::<H>(&leaves, arity),
+        };
 
         #[cfg(feature = "concurrent")]
-        let nodes = if leaves.len() <= concurrent::MIN_CONCURRENT_LEAVES {
-            build_merkle_nodes::<H>(&leaves)
-        } else {
-            concurrent::build_merkle_nodes::<H>(&leaves)
+        let nodes = match (arity, leaves.len() <= concurrent::MIN_CONCURRENT_LEAVES) {
+            (2, true) => build_merkle_nodes::<H>(&leaves),
+            (2, false) => concurrent::build_merkle_nodes::<H>(&leaves),
+            (_, true) => build_kary_merkle_nodes::<H>(&leaves, arity),
+            (_, false) => concurrent::build_kary_merkle_nodes::<H>(&leaves, arity),
         };
 
-        Ok(MerkleTree { nodes, leaves })
+        Ok(MerkleTree {
+            nodes,
+            leaves,
+            arity,
+        })
     }
 
     // PUBLIC ACCESSORS
@@ -137,11 +182,17 @@ impl<H: Hasher> MerkleTree<H> {
     /// Returns depth of the tree.
     ///
     /// The depth of a tree is zero-based. Thus, a tree with two leaves has depth 1, a tree with
-    /// four leaves has depth 2 etc.
+    /// four leaves has depth 2 etc. The depth does not depend on the arity of the tree.
     pub fn depth(&self) -> usize {
         log2(self.leaves.len()) as usize
     }
 
+    /// Returns the number of children of internal nodes of the tree (except for the root, which
+    /// may have fewer children).
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
     /// Returns leaf nodes of the tree.
     pub fn leaves(&self) -> &[H::Digest] {
         &self.leaves
@@ -155,9 +206,13 @@ impl<H: Hasher> MerkleTree<H> {
     /// The leaf itself will be the first element in the path.
     ///
     /// # Errors
-    /// Returns an error if the specified index is greater than or equal to the number of leaves
-    /// in the tree.
+    /// Returns an error if:
+    /// * The arity of the tree is not 2.
+    /// * The specified index is greater than or equal to the number of leaves in the tree.
     pub fn prove(&self, index: usize) -> Result<Vec<H::Digest>, MerkleTreeError> {
+        if self.arity != 2 {
+            return Err(MerkleTreeError::NotBinaryTree(self.arity));
+        }
         if index >= self.leaves.len() {
             return Err(MerkleTreeError::LeafIndexOutOfBounds(
                 self.leaves.len(),
@@ -197,6 +252,9 @@ impl<H: Hasher> MerkleTree<H> {
         }
 
         let index_map = map_indexes(indexes, self.depth())?;
+        if self.arity != 2 {
+            return Ok(self.prove_kary_batch(&index_map));
+        }
         let indexes = normalize_indexes(indexes);
         let mut leaves = vec![H::Digest::default(); index_map.len()];
         let mut nodes: Vec<Vec<H::Digest>> = Vec::with_capacity(indexes.len());
@@ -204,7 +262,7 @@ impl<H: Hasher> MerkleTree<H> {
         // populate the proof with leaf node values
         let n = self.leaves.len();
         let mut next_indexes: Vec<usize> = Vec::new();
-        for index in indexes {
+        for &index in indexes.iter() {
             let missing: Vec<H::Digest> = (index..index + 2)
                 .flat_map(|i| {
                     let v = self.leaves[i];
@@ -244,16 +302,79 @@ impl<H: Hasher> MerkleTree<H> {
 
         Ok(BatchMerkleProof {
             leaves,
-            nodes,
+            nodes: nodes.into(),
             depth: self.depth() as u8,
+            arity: 2,
         })
     }
 
+    /// Computes a batch proof for the leaves in the `index_map` for a tree with arity greater
+    /// than 2.
+    ///
+    /// The tree is traversed from the leaves to the root one level at a time. Queried leaves
+    /// sharing the same parent form a group, and the proof contains a node vector for every
+    /// group. Children of a parent which cannot be computed from the queried leaves are added to
+    /// the node vector of the first group under this parent, in the order in which they are
+    /// consumed by [BatchMerkleProof::get_root()].
+    fn prove_kary_batch(&self, index_map: &IndexMap) -> BatchMerkleProof<H> {
+        let n = self.leaves.len();
+        let level_bits = level_bits(self.depth(), self.arity);
+
+        let mut leaves = vec![H::Digest::default(); index_map.len()];
+        let mut nodes: Vec<Vec<H::Digest>> = Vec::new();
+
+        // positions of known nodes at the current level together with the groups they belong to;
+        // at the leaf level, known nodes are the queried leaves
+        let mut known: Vec<(usize, usize)> = Vec::with_capacity(index_map.len());
+        for (index, position) in index_map.iter() {
+            leaves[position] = self.leaves[index];
+            if known.last().map_or(true, |&(last, _)| {
+                last >> level_bits[0] != index >> level_bits[0]
+            }) {
+                nodes.push(Vec::new());
+            }
+            known.push((index, nodes.len() - 1));
+        }
+
+        // add children which are not known to the proof; nodes of the current level start at
+        // index level_start of the tree (leaves are at index n and above)
+        let mut level_start = n;
+        for &bits in level_bits.iter() {
+            let mut next_known = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let (position, group) = known[i];
+                let parent = position >> bits;
+                for child in (parent << bits)..((parent + 1) << bits) {
+                    if i < known.len() && known[i].0 == child {
+                        i += 1;
+                    } else if level_start == n {
+                        nodes[group].push(self.leaves[child]);
+                    } else {
+                        nodes[group].push(self.nodes[level_start + child]);
+                    }
+                }
+                next_known.push((parent, group));
+            }
+            known = next_known;
+            level_start >>= bits;
+        }
+
+        BatchMerkleProof {
+            leaves,
+            nodes: nodes.into(),
+            depth: self.depth() as u8,
+            arity: self.arity as u8,
+        }
+    }
+
     // VERIFICATION METHODS
     // --------------------------------------------------------------------------------------------
 
     /// Checks whether the `proof` for the specified `index` is valid.
     ///
+    /// This function can verify Merkle paths only for binary trees.
+    ///
     /// # Errors
     /// Returns an error if the specified `proof` (which is a Merkle path) does not resolve to the
     /// specified `root`.
@@ -303,6 +424,108 @@ impl<H: Hasher> MerkleTree<H> {
     }
 }
 
+// VECTOR COMMITMENT
+// ================================================================================================
+
+impl<H: Hasher> VectorCommitment<H> for MerkleTree<H> {
+    const SCHEME_ID: u8 = 0;
+
+    type MultiProof = BatchMerkleProof<H>;
+    type Error = MerkleTreeError;
+
+    fn with_arity(items: Vec<H::Digest>, arity: usize) -> Result<Self, MerkleTreeError> {
+        MerkleTree::with_arity(items, arity)
+    }
+
+    fn commitment(&self) -> H::Digest {
+        *self.root()
+    }
+
+    fn domain_len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    fn open_many(&self, indexes: &[usize]) -> Result<BatchMerkleProof<H>, MerkleTreeError> {
+        self.prove_batch(indexes)
+    }
+
+    fn verify_many(
+        commitment: &H::Digest,
+        indexes: &[usize],
+        proof: &BatchMerkleProof<H>,
+    ) -> Result<(), MerkleTreeError> {
+        MerkleTree::verify_batch(commitment, indexes, proof)
+    }
+
+    /// Writes internal nodes of the batch Merkle proof into the `target`; for raw items, the
+    /// leaves adjacent to the proven leaves are written as raw rows (see
+    /// [BatchMerkleProof::serialize_nodes_with_raw_leaves()]).
+    fn write_multiproof<W: ByteWriter>(
+        proof: &BatchMerkleProof<H>,
+        indexes: &[usize],
+        raw_item_bytes: Option<usize>,
+        target: &mut W,
+    ) {
+        let nodes = match raw_item_bytes {
+            None => proof.serialize_nodes(),
+            Some(row_bytes) => proof.serialize_nodes_with_raw_leaves(indexes, row_bytes),
+        };
+        target.write_u8_slice(&nodes);
+    }
+
+    /// Reads internal nodes of a batch Merkle proof from the `source`; raw items can be used only
+    /// with binary trees.
+    fn read_multiproof<R: ByteReader>(
+        source: &mut R,
+        items: Vec<H::Digest>,
+        raw_item_bytes: Option<usize>,
+        domain_len: usize,
+        arity: usize,
+    ) -> Result<BatchMerkleProof<H>, DeserializationError> {
+        let depth = log2(domain_len) as u8;
+        match raw_item_bytes {
+            None => BatchMerkleProof::deserialize_with_arity(source, items, depth, arity as u8),
+            Some(_) if arity != 2 => Err(DeserializationError::InvalidValue(
+                "raw leaf encoding can be used only with binary Merkle trees".to_string(),
+            )),
+            Some(row_bytes) => {
+                BatchMerkleProof::deserialize_with_raw_leaves(source, items, row_bytes, depth)
+            }
+        }
+    }
+}
+
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<H: Hasher> Serializable for MerkleTree<H> {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    ///
+    /// Only the arity and the leaves of the tree are serialized; internal nodes are fully
+    /// determined by these and are re-computed when the tree is deserialized.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(self.arity as u8);
+        target.write_u32(self.leaves.len() as u32);
+        H::Digest::write_batch_into(&self.leaves, target);
+    }
+}
+
+impl<H: Hasher> Deserializable for MerkleTree<H> {
+    /// Reads the arity and the leaves of a Merkle tree from the specified `source` and builds a
+    /// tree from them.
+    ///
+    /// # Errors
+    /// Returns an error if a valid arity and list of leaves could not be read from the source, or
+    /// if the leaves do not form a valid Merkle tree.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let arity = source.read_u8()? as usize;
+        let num_leaves = source.read_u32()? as usize;
+        let leaves = H::Digest::read_batch_from(source, num_leaves)?;
+        MerkleTree::with_arity(leaves, arity)
+            .map_err(|err| DeserializationError::InvalidValue(err.to_string()))
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
@@ -317,52 +540,161 @@ pub fn build_merkle_nodes<H: Hasher>(leaves: &[H::Digest]) -> Vec<H::Digest> {
     let n = leaves.len() / 2;
 
     // create un-initialized array to hold all intermediate nodes
+    #[cfg(not(feature = "safe_only"))]
     let mut nodes = unsafe { utils::uninit_vector::<H::Digest>(2 * n) };
+    #[cfg(feature = "safe_only")]
+    let mut nodes = vec![H::Digest::default(); 2 * n];
     nodes[0] = H::Digest::default();
 
-    // re-interpret leaves as an array of two leaves fused together
-    let two_leaves = unsafe { slice::from_raw_parts(leaves.as_ptr() as *const [H::Digest; 2], n) };
-
     // build first row of internal nodes (parents of leaves)
-    for (i, j) in (0..n).zip(n..nodes.len()) {
-        nodes[j] = H::merge(&two_leaves[i]);
+    H::merge_batch(leaves, 2, &mut nodes[n..]);
+
+    // build all other rows of internal nodes, starting with the row which is the closest to the
+    // leaves; children of nodes in the row at positions [k, 2k) are at positions [2k, 4k). every
+    // row is built by a single call to the hasher, so that hash functions which hash several
+    // inputs in parallel get as many inputs per call as possible.
+    let mut row_start = n / 2;
+    while row_start > 0 {
+        let (parents, children) = nodes.split_at_mut(2 * row_start);
+        H::merge_batch(&children[..2 * row_start], 2, &mut parents[row_start..]);
+        row_start /= 2;
     }
 
-    // re-interpret nodes as an array of two nodes fused together
-    let two_nodes = unsafe { slice::from_raw_parts(nodes.as_ptr() as *const [H::Digest; 2], n) };
+    nodes
+}
 
-    // calculate all other tree nodes
-    for i in (1..n).rev() {
-        nodes[i] = H::merge(&two_nodes[i]);
+/// Returns the internal nodes of a Merkle tree with the specified `arity` defined by the
+/// specified leaves.
+///
+/// The internal nodes are stored at the same positions as in a binary tree (see
+/// [build_merkle_nodes()]); positions of nodes which are not present in a tree with the
+/// specified arity are set to default digests.
+///
+/// This function is exposed primarily for benchmarking purposes. It is not intended to be used
+/// directly by the end users of the crate.
+pub fn build_kary_merkle_nodes<H: Hasher>(leaves: &[H::Digest], arity: usize) -> Vec<H::Digest> {
+    let n = leaves.len();
+    let mut nodes = vec![H::Digest::default(); n];
+
+    // build the row of internal nodes which are parents of leaves
+    let mut level_bits = level_bits(log2(n) as usize, arity).into_iter();
+    let bits = level_bits
+        .next()
+        .expect("a tree must have at least one level");
+    let mut row_start = n >> bits;
+    H::merge_batch(leaves, 1 << bits, &mut nodes[row_start..2 * row_start]);
+
+    // build all other rows of internal nodes, starting with the row which is the closest to the
+    // leaves; children of nodes in the row at positions [k, 2k) are at positions [2^b * k,
+    // 2^(b + 1) * k) where b is the number of bits consumed by the row
+    for bits in level_bits {
+        let (parents, children) = nodes.split_at_mut(row_start);
+        let parent_start = row_start >> bits;
+        H::merge_batch(
+            &children[..row_start],
+            1 << bits,
+            &mut parents[parent_start..2 * parent_start],
+        );
+        row_start = parent_start;
     }
 
     nodes
 }
 
-fn map_indexes(
-    indexes: &[usize],
-    tree_depth: usize,
-) -> Result<BTreeMap<usize, usize>, MerkleTreeError> {
+/// Returns the number of bits of a leaf index consumed by each level of a tree with the specified
+/// depth and arity, starting with the level closest to the leaves.
+///
+/// Every level consumes log2(arity) bits, except for the topmost level which consumes the
+/// remaining bits when the depth is not a multiple of log2(arity).
+fn level_bits(depth: usize, arity: usize) -> Vec<usize> {
+    let bits = log2(arity) as usize;
+    let mut result = vec![bits; depth / bits];
+    if depth % bits != 0 {
+        result.push(depth % bits);
+    }
+    result
+}
+
+/// Returns a parent node of the specified children.
+fn merge_nodes<H: Hasher>(children: &[H::Digest]) -> H::Digest {
+    match children {
+        [left, right] => H::merge(&[*left, *right]),
+        _ => H::merge_many(children),
+    }
+}
+
+/// Returns an error if the specified arity is not supported by Merkle trees.
+fn validate_arity(arity: usize) -> Result<(), MerkleTreeError> {
+    match arity {
+        2 | 4 | 8 => Ok(()),
+        _ => Err(MerkleTreeError::UnsupportedArity(arity)),
+    }
+}
+
+fn map_indexes(indexes: &[usize], tree_depth: usize) -> Result<IndexMap, MerkleTreeError> {
     let num_leaves = 2usize.pow(tree_depth as u32);
-    let mut map = BTreeMap::new();
+    let mut map = InlineVec::new();
     for (i, index) in indexes.iter().cloned().enumerate() {
-        map.insert(index, i);
+        map.push((index, i));
         if index >= num_leaves {
             return Err(MerkleTreeError::LeafIndexOutOfBounds(num_leaves, index));
         }
     }
 
-    if indexes.len() != map.len() {
+    map.sort_unstable_by_key(|&(index, _)| index);
+    if map.windows(2).any(|pair| pair[0].0 == pair[1].0) {
         return Err(MerkleTreeError::DuplicateLeafIndex);
     }
 
-    Ok(map)
+    Ok(IndexMap(map))
+}
+
+fn normalize_indexes(indexes: &[usize]) -> PathBuffer<usize> {
+    let mut result = indexes
+        .iter()
+        .map(|&index| index - (index & 1))
+        .collect::<PathBuffer<_>>();
+    result.sort_unstable();
+    result.dedup();
+    result
 }
 
-fn normalize_indexes(indexes: &[usize]) -> Vec<usize> {
-    let mut set = BTreeSet::new();
-    for &index in indexes {
-        set.insert(index - (index & 1));
+// PATH BUFFERS
+// ================================================================================================
+
+/// A buffer with an entry for each of the paths aggregated in a batch proof (or for each of the
+/// nodes at a single level of the tree touched by these paths); since there are at most 255 such
+/// paths, the entries are always kept inline.
+pub(crate) type PathBuffer<T> = InlineVec<T, { proofs::MAX_PATHS }>;
+
+// INDEX MAP
+// ================================================================================================
+
+/// A map from leaf indexes to their positions in the list of queried indexes.
+///
+/// The map is backed by a single vector sorted by leaf index; since a batch proof aggregates at
+/// most 255 paths, the vector is kept inline, and building the map does not require heap
+/// allocations.
+struct IndexMap(PathBuffer<(usize, usize)>);
+
+impl IndexMap {
+    /// Returns the position of the specified leaf index in the list of queried indexes, or None
+    /// if the leaf index was not queried.
+    fn get(&self, index: &usize) -> Option<&usize> {
+        self.0
+            .binary_search_by_key(index, |&(index, _)| index)
+            .ok()
+            .map(|position| &self.0[position].1)
+    }
+
+    /// Returns an iterator over (leaf index, position) pairs of this map in ascending order of
+    /// leaf indexes.
+    fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns the number of indexes in this map.
+    fn len(&self) -> usize {
+        self.0.len()
     }
-    set.into_iter().collect()
 }
@@ -3,11 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use super::PathBuffer;
 use crate::{errors::MerkleTreeError, Hasher};
+use core::ops::Index;
 use utils::{
-    collections::{BTreeMap, Vec},
+    collections::{BTreeMap, InlineVec, Vec},
     string::ToString,
-    ByteReader, Deserializable, DeserializationError, Serializable,
+    ByteReader, Deserializable, DeserializationError, Serializable, SliceReader,
 };
 
 // CONSTANTS
@@ -27,14 +29,20 @@ pub(super) const MAX_PATHS: usize = 255;
 ///
 /// Currently, at most 255 paths can be aggregated into a single proof. This limitation is
 /// imposed primarily for serialization purposes.
+///
+/// Proofs for trees with arity greater than 2 can be generated only via
+/// [MerkleTree::prove_batch()](crate::MerkleTree::prove_batch); such proofs cannot be converted
+/// into individual Merkle paths.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BatchMerkleProof<H: Hasher> {
     /// The leaves being proven
     pub leaves: Vec<H::Digest>,
     /// Hashes of Merkle Tree proof values above the leaf layer
-    pub nodes: Vec<Vec<H::Digest>>,
+    pub nodes: ProofNodes<H::Digest>,
     /// Depth of the leaves
     pub depth: u8,
+    /// Arity of the tree from which the proof was generated
+    pub arity: u8,
 }
 
 impl<H: Hasher> BatchMerkleProof<H> {
@@ -112,9 +120,100 @@ impl<H: Hasher> BatchMerkleProof<H> {
 
         BatchMerkleProof {
             leaves,
-            nodes,
+            nodes: nodes.into(),
             depth: (depth - 1) as u8,
+            arity: 2,
+        }
+    }
+
+    /// Constructs a batch Merkle proof from individual Merkle authentication paths given as
+    /// tuples `(index, leaf, path)`, where `path` contains the sibling nodes from the leaf level
+    /// up to (but not including) the root.
+    ///
+    /// Unlike [BatchMerkleProof::from_paths()], this function does not trust the paths to come
+    /// from the same tree (e.g., the paths may be fetched from an external storage): all nodes
+    /// claimed or implied by the paths are checked against each other. The leaves of the
+    /// resulting proof are in the order of the paths, and thus, for paths taken from a
+    /// [MerkleTree](crate::MerkleTree), the resulting proof is the same as the one generated by
+    /// [MerkleTree::prove_batch()](crate::MerkleTree::prove_batch) for the same indexes.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * No paths have been provided (i.e., `paths` is an empty vector).
+    /// * More than 255 paths have been provided.
+    /// * List of indexes contains duplicates.
+    /// * The first path is empty or contains more than 63 nodes.
+    /// * Not all paths have the same length.
+    /// * Any of the indexes is greater than or equal to the number of leaves in a tree of the
+    ///   depth implied by the length of the paths.
+    /// * Two paths claim different nodes at the same position of the tree, or the paths do not
+    ///   resolve to the same root.
+    pub fn from_single_paths(
+        paths: Vec<(usize, H::Digest, Vec<H::Digest>)>,
+    ) -> Result<BatchMerkleProof<H>, MerkleTreeError> {
+        if paths.is_empty() {
+            return Err(MerkleTreeError::TooFewLeafIndexes);
+        }
+        if paths.len() > MAX_PATHS {
+            return Err(MerkleTreeError::TooManyLeafIndexes(MAX_PATHS, paths.len()));
+        }
+
+        let mut indexes = paths.iter().map(|(index, ..)| *index).collect::<Vec<_>>();
+        indexes.sort_unstable();
+        if indexes.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(MerkleTreeError::DuplicateLeafIndex);
+        }
+
+        let depth = paths[0].2.len();
+        if depth == 0 || depth >= usize::BITS as usize {
+            return Err(MerkleTreeError::InvalidProof);
+        }
+        let num_leaves = 1usize << depth;
+
+        // build a partial tree from all nodes claimed by the paths and all nodes computed from
+        // them; nodes are indexed as in a full tree, i.e., the root has index 1, and the leaf at
+        // index i has index i + num_leaves
+        let mut tree = BTreeMap::new();
+        for (index, leaf, path) in paths.iter() {
+            if path.len() != depth {
+                return Err(MerkleTreeError::PathLengthMismatch(depth, path.len()));
+            }
+            if *index >= num_leaves {
+                return Err(MerkleTreeError::LeafIndexOutOfBounds(num_leaves, *index));
+            }
+
+            let mut node_index = index + num_leaves;
+            let mut node = *leaf;
+            insert_node(&mut tree, node_index, node)?;
+            for &sibling in path.iter() {
+                insert_node(&mut tree, node_index ^ 1, sibling)?;
+                node = if node_index & 1 == 0 {
+                    H::merge(&[node, sibling])
+                } else {
+                    H::merge(&[sibling, node])
+                };
+                node_index >>= 1;
+                insert_node(&mut tree, node_index, node)?;
+            }
         }
+
+        // the paths are consistent, and thus, they can be aggregated as if they came from the
+        // same tree; from_paths() orders leaves by index, so we put them back into the order of
+        // the paths
+        let indexes = paths.iter().map(|(index, ..)| *index).collect::<Vec<_>>();
+        let full_paths = paths
+            .iter()
+            .map(|(_, leaf, path)| {
+                let mut full_path = Vec::with_capacity(depth + 1);
+                full_path.push(*leaf);
+                full_path.extend_from_slice(path);
+                full_path
+            })
+            .collect::<Vec<_>>();
+        let mut proof = Self::from_paths(&full_paths, &indexes);
+        proof.leaves = paths.iter().map(|(_, leaf, _)| *leaf).collect();
+
+        Ok(proof)
     }
 
     /// Computes a node to which all Merkle paths aggregated in this proof resolve.
@@ -138,11 +237,14 @@ impl<H: Hasher> BatchMerkleProof<H> {
             ));
         }
 
+        let index_map = super::map_indexes(indexes, self.depth as usize)?;
+        if self.arity != 2 {
+            return self.get_kary_root(&index_map);
+        }
+
         let mut buf = [H::Digest::default(); 2];
-        let mut v = BTreeMap::new();
 
         // replace odd indexes, offset, and sort in ascending order
-        let index_map = super::map_indexes(indexes, self.depth as usize)?;
         let indexes = super::normalize_indexes(indexes);
         if indexes.len() != self.nodes.len() {
             return Err(MerkleTreeError::InvalidProof);
@@ -150,9 +252,10 @@ impl<H: Hasher> BatchMerkleProof<H> {
 
         // for each index use values to compute parent nodes
         let offset = 2usize.pow(self.depth as u32);
-        let mut next_indexes: Vec<usize> = Vec::new();
-        let mut proof_pointers: Vec<usize> = Vec::with_capacity(indexes.len());
-        for (i, index) in indexes.into_iter().enumerate() {
+        let mut next_indexes = PathBuffer::<usize>::new();
+        let mut next_nodes = PathBuffer::<H::Digest>::new();
+        let mut proof_pointers = PathBuffer::<usize>::new();
+        for (i, &index) in indexes.iter().enumerate() {
             // copy values of leaf sibling leaf nodes into the buffer
             match index_map.get(&index) {
                 Some(&index1) => {
@@ -199,27 +302,33 @@ impl<H: Hasher> BatchMerkleProof<H> {
             let parent = H::merge(&buf);
 
             let parent_index = (offset + index) >> 1;
-            v.insert(parent_index, parent);
             next_indexes.push(parent_index);
+            next_nodes.push(parent);
         }
 
-        // iteratively move up, until we get to the root
+        // iteratively move up, until we get to the root; nodes of the current level are kept in
+        // the same order as their indexes, and buffers of the two levels are swapped rather than
+        // re-allocated
+        let mut indexes = indexes;
+        let mut nodes = PathBuffer::<H::Digest>::new();
         for _ in 1..self.depth {
-            let indexes = next_indexes.clone();
-            next_indexes.truncate(0);
+            core::mem::swap(&mut indexes, &mut next_indexes);
+            core::mem::swap(&mut nodes, &mut next_nodes);
+            next_indexes.clear();
+            next_nodes.clear();
 
             let mut i = 0;
             while i < indexes.len() {
                 let node_index = indexes[i];
                 let sibling_index = node_index ^ 1;
 
+                // get the node from the list of nodes hashed at the previous level
+                let node = nodes[i];
+
                 // determine the sibling
                 let sibling: H::Digest;
                 if i + 1 < indexes.len() && indexes[i + 1] == sibling_index {
-                    sibling = match v.get(&sibling_index) {
-                        Some(sibling) => *sibling,
-                        None => return Err(MerkleTreeError::InvalidProof),
-                    };
+                    sibling = nodes[i + 1];
                     i += 1;
                 } else {
                     let pointer = proof_pointers[i];
@@ -230,31 +339,100 @@ impl<H: Hasher> BatchMerkleProof<H> {
                     proof_pointers[i] += 1;
                 }
 
-                // get the node from the map of hashed nodes
-                let node = match v.get(&node_index) {
-                    Some(node) => node,
-                    None => return Err(MerkleTreeError::InvalidProof),
-                };
-
                 // compute parent node from node and sibling
                 if node_index & 1 != 0 {
                     buf[0] = sibling;
-                    buf[1] = *node;
+                    buf[1] = node;
                 } else {
-                    buf[0] = *node;
+                    buf[0] = node;
                     buf[1] = sibling;
                 }
                 let parent = H::merge(&buf);
 
                 // add the parent node to the next set of nodes
                 let parent_index = node_index >> 1;
-                v.insert(parent_index, parent);
                 next_indexes.push(parent_index);
+                next_nodes.push(parent);
 
                 i += 1;
             }
         }
-        v.remove(&1).ok_or(MerkleTreeError::InvalidProof)
+
+        match next_indexes.as_slice() {
+            [1] => Ok(next_nodes[0]),
+            _ => Err(MerkleTreeError::InvalidProof),
+        }
+    }
+
+    /// Computes a node to which all Merkle paths aggregated in this proof resolve for a proof
+    /// generated from a tree with arity greater than 2.
+    ///
+    /// This mirrors the traversal performed by
+    /// [MerkleTree::prove_batch()](crate::MerkleTree::prove_batch): every node vector of the
+    /// proof must be consumed in full.
+    fn get_kary_root(&self, index_map: &super::IndexMap) -> Result<H::Digest, MerkleTreeError> {
+        if super::validate_arity(self.arity as usize).is_err() {
+            return Err(MerkleTreeError::InvalidProof);
+        }
+        let level_bits = super::level_bits(self.depth as usize, self.arity as usize);
+
+        // positions of known nodes at the current level together with the groups they belong to
+        // and their values; at the leaf level, known nodes are the queried leaves
+        let mut known: Vec<(usize, usize, H::Digest)> = Vec::with_capacity(index_map.len());
+        let mut num_groups = 0;
+        for (index, position) in index_map.iter() {
+            let leaf = self
+                .leaves
+                .get(position)
+                .ok_or(MerkleTreeError::InvalidProof)?;
+            if known.last().map_or(true, |&(last, ..)| {
+                last >> level_bits[0] != index >> level_bits[0]
+            }) {
+                num_groups += 1;
+            }
+            known.push((index, num_groups - 1, *leaf));
+        }
+        if num_groups != self.nodes.len() {
+            return Err(MerkleTreeError::InvalidProof);
+        }
+
+        // compute parents of known nodes one level at a time, taking the children which are not
+        // known from the node vectors of the corresponding groups
+        let mut proof_pointers = vec![0; self.nodes.len()];
+        let mut children = Vec::with_capacity(self.arity as usize);
+        for &bits in level_bits.iter() {
+            let mut next_known = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let (position, group, _) = known[i];
+                let parent = position >> bits;
+                children.clear();
+                for child in (parent << bits)..((parent + 1) << bits) {
+                    if i < known.len() && known[i].0 == child {
+                        children.push(known[i].2);
+                        i += 1;
+                    } else {
+                        let pointer = proof_pointers[group];
+                        let node = self.nodes[group]
+                            .get(pointer)
+                            .ok_or(MerkleTreeError::InvalidProof)?;
+                        children.push(*node);
+                        proof_pointers[group] += 1;
+                    }
+                }
+                next_known.push((parent, group, super::merge_nodes::<H>(&children)));
+            }
+            known = next_known;
+        }
+
+        let all_nodes_consumed = proof_pointers
+            .iter()
+            .zip(self.nodes.iter())
+            .all(|(&pointer, nodes)| pointer == nodes.len());
+        match known.as_slice() {
+            [(0, _, root)] if all_nodes_consumed => Ok(*root),
+            _ => Err(MerkleTreeError::InvalidProof),
+        }
     }
 
     /// Computes the uncompressed Merkle paths which aggregate to this proof.
@@ -264,7 +442,11 @@ impl<H: Hasher> BatchMerkleProof<H> {
     /// * No indexes were provided (i.e., `indexes` is an empty slice).
     /// * Number of provided indexes is greater than 255.
     /// * Number of provided indexes does not match the number of leaf nodes in the proof.
+    /// * The proof was generated from a tree with arity other than 2.
     pub fn into_paths(self, indexes: &[usize]) -> Result<Vec<Vec<H::Digest>>, MerkleTreeError> {
+        if self.arity != 2 {
+            return Err(MerkleTreeError::NotBinaryTree(self.arity as usize));
+        }
         if indexes.is_empty() {
             return Err(MerkleTreeError::TooFewLeafIndexes);
         }
@@ -299,7 +481,7 @@ impl<H: Hasher> BatchMerkleProof<H> {
         let offset = 2usize.pow(self.depth as u32);
         let mut next_indexes: Vec<usize> = Vec::new();
         let mut proof_pointers: Vec<usize> = Vec::with_capacity(indexes.len());
-        for (i, index) in indexes.into_iter().enumerate() {
+        for (i, &index) in indexes.iter().enumerate() {
             // copy values of leaf sibling leaf nodes into the buffer
             match index_map.get(&index) {
                 Some(&index1) => {
@@ -437,6 +619,69 @@ impl<H: Hasher> BatchMerkleProof<H> {
         result
     }
 
+    /// Converts all internal proof nodes into a vector of bytes, where the leaves adjacent to the
+    /// proven leaves are raw rows of `row_bytes` bytes padded with zeros to the size of a digest.
+    ///
+    /// Such leaves (i.e., the first nodes of paths for leaves whose siblings were not proven) are
+    /// serialized as row bytes rather than as full digests, and a bit mask marking the paths
+    /// starting with such leaves is serialized in front of the paths. `indexes` must be the
+    /// indexes of the proven leaves.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * The proof was not generated from a binary tree.
+    /// * The number of paths in the proof does not match the number of pairs of sibling leaves
+    ///   among the proven leaves.
+    /// * The proof contains more than 255 Merkle paths.
+    /// * The Merkle paths consist of more than 255 nodes.
+    pub fn serialize_nodes_with_raw_leaves(&self, indexes: &[usize], row_bytes: usize) -> Vec<u8> {
+        assert_eq!(
+            2, self.arity,
+            "raw leaves can be used only with binary Merkle trees"
+        );
+
+        // the proof contains a path for every pair of sibling leaves with proven leaves; a path
+        // starts with a leaf only when one of the siblings was not proven
+        let mut pairs = indexes.iter().map(|&i| i & !1).collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let mut has_sibling_leaf = Vec::with_capacity(pairs.len());
+        let mut i = 0;
+        while i < pairs.len() {
+            let both_proven = i + 1 < pairs.len() && pairs[i + 1] == pairs[i];
+            has_sibling_leaf.push(!both_proven);
+            i += 1 + both_proven as usize;
+        }
+        assert_eq!(
+            has_sibling_leaf.len(),
+            self.nodes.len(),
+            "number of paths in the Merkle proof does not match the number of queried leaf pairs"
+        );
+
+        let mut result = Vec::new();
+        assert!(self.nodes.len() <= u8::MAX as usize, "too many paths");
+        result.push(self.nodes.len() as u8);
+        for mask in has_sibling_leaf.chunks(8) {
+            let mask = mask
+                .iter()
+                .enumerate()
+                .fold(0_u8, |mask, (i, &bit)| mask | (bit as u8) << i);
+            result.push(mask);
+        }
+        for (nodes, &starts_with_leaf) in self.nodes.iter().zip(has_sibling_leaf.iter()) {
+            assert!(nodes.len() <= u8::MAX as usize, "too many nodes");
+            result.push(nodes.len() as u8);
+            for (i, node) in nodes.iter().enumerate() {
+                if i == 0 && starts_with_leaf {
+                    result.extend_from_slice(&node.to_bytes()[..row_bytes]);
+                } else {
+                    result.append(&mut node.to_bytes());
+                }
+            }
+        }
+
+        result
+    }
+
     /// Parses internal nodes from the provided `node_bytes`, and constructs a batch Merkle proof
     /// from these nodes, provided `leaves`, and provided tree `depth`.
     ///
@@ -451,6 +696,28 @@ impl<H: Hasher> BatchMerkleProof<H> {
         leaves: Vec<H::Digest>,
         depth: u8,
     ) -> Result<Self, DeserializationError> {
+        Self::deserialize_with_arity(node_bytes, leaves, depth, 2)
+    }
+
+    /// Parses internal nodes from the provided `node_bytes`, and constructs a batch Merkle proof
+    /// from these nodes, provided `leaves`, provided tree `depth`, and provided tree `arity`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * No leaves were provided (i.e., `leaves` is an empty slice).
+    /// * Number of provided leaves is greater than 255.
+    /// * Tree `depth` was set to zero.
+    /// * Tree `arity` is not 2, 4, or 8.
+    /// * `node_bytes` could not be deserialized into a valid set of internal nodes.
+    pub fn deserialize_with_arity<R: ByteReader>(
+        node_bytes: &mut R,
+        leaves: Vec<H::Digest>,
+        depth: u8,
+        arity: u8,
+    ) -> Result<Self, DeserializationError> {
+        if let Err(err) = super::validate_arity(arity as usize) {
+            return Err(DeserializationError::InvalidValue(err.to_string()));
+        }
         if depth == 0 {
             return Err(DeserializationError::InvalidValue(
                 "tree depth must be greater than zero".to_string(),
@@ -469,28 +736,191 @@ impl<H: Hasher> BatchMerkleProof<H> {
             )));
         }
 
+        // a node vector contains at most arity - 1 siblings for every level of the tree, and thus,
+        // all nodes can be read into a buffer allocated once
         let num_node_vectors = node_bytes.read_u8()? as usize;
-        let mut nodes = Vec::with_capacity(num_node_vectors);
+        let mut nodes =
+            ProofNodes::with_capacity(num_node_vectors * depth as usize * (arity as usize - 1));
         for _ in 0..num_node_vectors {
             // read the number of digests in the vector
             let num_digests = node_bytes.read_u8()? as usize;
 
             // read the digests and add them to the node vector
-            let digests = H::Digest::read_batch_from(node_bytes, num_digests)?;
-            nodes.push(digests);
+            nodes.push(&[]);
+            for _ in 0..num_digests {
+                nodes.push_to_last(H::Digest::read_from(node_bytes)?);
+            }
         }
 
         Ok(BatchMerkleProof {
             leaves,
             nodes,
             depth,
+            arity,
+        })
+    }
+
+    /// Parses internal nodes serialized via
+    /// [serialize_nodes_with_raw_leaves()](BatchMerkleProof::serialize_nodes_with_raw_leaves)
+    /// from the provided `node_bytes`, and constructs a batch Merkle proof of a binary tree from
+    /// these nodes, provided raw `leaves`, and provided tree `depth`.
+    ///
+    /// # Errors
+    /// Returns an error if `node_bytes` could not be deserialized into a valid set of internal
+    /// nodes, or if a raw leaf of `row_bytes` bytes cannot fit into a digest.
+    pub fn deserialize_with_raw_leaves<R: ByteReader>(
+        node_bytes: &mut R,
+        leaves: Vec<H::Digest>,
+        row_bytes: usize,
+        depth: u8,
+    ) -> Result<Self, DeserializationError> {
+        let num_node_vectors = node_bytes.read_u8()? as usize;
+        let mask = node_bytes.read_u8_vec((num_node_vectors + 7) / 8)?;
+
+        let mut nodes = ProofNodes::with_capacity(num_node_vectors * depth as usize);
+        for i in 0..num_node_vectors {
+            let num_digests = node_bytes.read_u8()? as usize;
+            let starts_with_leaf = (mask[i / 8] >> (i % 8)) & 1 == 1;
+            nodes.push(&[]);
+            if starts_with_leaf {
+                if num_digests == 0 {
+                    return Err(DeserializationError::InvalidValue(
+                        "a path marked as starting with a raw leaf cannot be empty".to_string(),
+                    ));
+                }
+                nodes.push_to_last(read_raw_leaf::<H, R>(node_bytes, row_bytes)?);
+            }
+            for _ in starts_with_leaf as usize..num_digests {
+                nodes.push_to_last(H::Digest::read_from(node_bytes)?);
+            }
+        }
+
+        Ok(BatchMerkleProof {
+            leaves,
+            nodes,
+            depth,
+            arity: 2,
         })
     }
 }
 
+// PROOF NODES
+// ================================================================================================
+
+/// Internal nodes of a batch Merkle proof arranged into node vectors.
+///
+/// A proof for a binary tree contains a node vector for every pair of sibling leaves with proven
+/// leaves, and a proof for a tree with arity greater than 2 contains a node vector for every
+/// group of proven leaves sharing the same parent. Nodes of all vectors are stored in a single
+/// vector, and the boundaries of the node vectors (at most 255 of them) are stored inline; thus,
+/// parsing a proof requires a single allocation regardless of the number of proven leaves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofNodes<D: Copy> {
+    nodes: Vec<D>,
+    ends: InlineVec<u32, MAX_PATHS>,
+}
+
+impl<D: Copy> ProofNodes<D> {
+    /// Returns an empty set of node vectors with space reserved for `num_nodes` nodes.
+    pub fn with_capacity(num_nodes: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(num_nodes),
+            ends: InlineVec::new(),
+        }
+    }
+
+    /// Returns the number of node vectors.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns true if there are no node vectors.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns an iterator over the node vectors.
+    pub fn iter(&self) -> impl Iterator<Item = &[D]> {
+        (0..self.len()).map(move |i| &self[i])
+    }
+
+    /// Appends a new node vector containing the provided nodes.
+    pub fn push(&mut self, nodes: &[D]) {
+        self.nodes.extend_from_slice(nodes);
+        self.ends.push(self.nodes.len() as u32);
+    }
+
+    /// Appends a node to the last node vector.
+    ///
+    /// # Panics
+    /// Panics if there are no node vectors.
+    pub fn push_to_last(&mut self, node: D) {
+        self.nodes.push(node);
+        let last = self.ends.len() - 1;
+        self.ends[last] += 1;
+    }
+}
+
+impl<D: Copy> Index<usize> for ProofNodes<D> {
+    type Output = [D];
+
+    fn index(&self, index: usize) -> &[D] {
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        &self.nodes[start as usize..self.ends[index] as usize]
+    }
+}
+
+impl<D: Copy> From<Vec<Vec<D>>> for ProofNodes<D> {
+    fn from(node_vectors: Vec<Vec<D>>) -> Self {
+        let num_nodes = node_vectors.iter().map(|nodes| nodes.len()).sum();
+        let mut result = Self::with_capacity(num_nodes);
+        for nodes in node_vectors.iter() {
+            result.push(nodes);
+        }
+        result
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Reads a raw leaf of `row_bytes` bytes from the `source` and pads it with zeros to the size of
+/// a digest.
+fn read_raw_leaf<H: Hasher, R: ByteReader>(
+    source: &mut R,
+    row_bytes: usize,
+) -> Result<H::Digest, DeserializationError> {
+    let mut bytes = H::Digest::default().to_bytes();
+    if row_bytes > bytes.len() {
+        return Err(DeserializationError::InvalidValue(format!(
+            "a raw leaf cannot contain more than {} bytes, but was {}",
+            bytes.len(),
+            row_bytes
+        )));
+    }
+    bytes[..row_bytes].copy_from_slice(&source.read_u8_vec(row_bytes)?);
+    H::Digest::read_from(&mut SliceReader::new(&bytes))
+}
+
+/// Inserts the `node` at the specified index into the partial `tree`.
+///
+/// # Errors
+/// Returns an error if the tree already contains a different node at the same index.
+fn insert_node<D: PartialEq>(
+    tree: &mut BTreeMap<usize, D>,
+    index: usize,
+    node: D,
+) -> Result<(), MerkleTreeError> {
+    match tree.get(&index) {
+        Some(existing) if *existing != node => Err(MerkleTreeError::ConflictingNodes(index)),
+        Some(_) => Ok(()),
+        None => {
+            tree.insert(index, node);
+            Ok(())
+        }
+    }
+}
+
 /// Two nodes are siblings if index of the left node is even and right node
 /// immediately follows the left node.
 fn are_siblings(left: usize, right: usize) -> bool {
@@ -6,6 +6,7 @@
 use super::*;
 use math::fields::f128::BaseElement;
 use proptest::prelude::*;
+use utils::{Deserializable, Serializable, SliceReader};
 
 type Digest256 = crate::hash::ByteDigest<32>;
 type Blake3_256 = crate::hash::Blake3_256<BaseElement>;
@@ -66,7 +67,7 @@ static LEAVES8: [[u8; 32]; 8] = [
 
 #[test]
 fn new_tree() {
-    let leaves = Digest256::bytes_as_digests(&LEAVES4).to_vec();
+    let leaves = bytes_to_digests(&LEAVES4);
     let tree = MerkleTree::<Blake3_256>::new(leaves.clone()).unwrap();
     assert_eq!(2, tree.depth());
     let root = hash_2x1(
@@ -75,7 +76,7 @@ fn new_tree() {
     );
     assert_eq!(&root, tree.root());
 
-    let leaves = Digest256::bytes_as_digests(&LEAVES8).to_vec();
+    let leaves = bytes_to_digests(&LEAVES8);
     let tree = MerkleTree::<Blake3_256>::new(leaves.clone()).unwrap();
     assert_eq!(3, tree.depth());
     let root = hash_2x1(
@@ -91,10 +92,86 @@ fn new_tree() {
     assert_eq!(&root, tree.root());
 }
 
+#[test]
+fn bytes_as_digests() {
+    let leaves = Digest256::bytes_as_digests(&LEAVES8);
+    assert_eq!(bytes_to_digests(&LEAVES8), leaves);
+
+    let bytes = Digest256::digests_as_bytes(leaves);
+    assert_eq!(LEAVES8.concat(), bytes);
+}
+
+#[test]
+fn tree_serialization() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
+
+    let bytes = tree.to_bytes();
+    let result = MerkleTree::<Blake3_256>::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(tree.root(), result.root());
+    assert_eq!(tree.leaves(), result.leaves());
+    assert_eq!(tree.prove(5).unwrap(), result.prove(5).unwrap());
+
+    // a tree with a number of leaves which is not a power of two cannot be deserialized
+    let mut bytes = vec![2];
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(&LEAVES8[..3].concat());
+    assert!(MerkleTree::<Blake3_256>::read_from(&mut SliceReader::new(&bytes)).is_err());
+}
+
+#[test]
+fn new_kary_tree() {
+    let leaves = bytes_to_digests(&LEAVES8);
+
+    // the root has two children when the depth is not a multiple of log2(arity)
+    let tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), 4).unwrap();
+    assert_eq!(3, tree.depth());
+    assert_eq!(4, tree.arity());
+    let root = hash_2x1(
+        Blake3_256::merge_many(&leaves[..4]),
+        Blake3_256::merge_many(&leaves[4..]),
+    );
+    assert_eq!(&root, tree.root());
+
+    let tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), 8).unwrap();
+    assert_eq!(3, tree.depth());
+    assert_eq!(&Blake3_256::merge_many(&leaves), tree.root());
+
+    // a tree with fewer leaves than its arity consists of the root only
+    let tree = MerkleTree::<Blake3_256>::with_arity(leaves[..2].to_vec(), 8).unwrap();
+    assert_eq!(&hash_2x1(leaves[0], leaves[1]), tree.root());
+
+    assert_eq!(
+        Err(MerkleTreeError::UnsupportedArity(3)),
+        MerkleTree::<Blake3_256>::with_arity(leaves.clone(), 3).map(|_| ())
+    );
+    assert_eq!(
+        Err(MerkleTreeError::UnsupportedArity(16)),
+        MerkleTree::<Blake3_256>::with_arity(leaves, 16).map(|_| ())
+    );
+}
+
+#[test]
+fn kary_tree_serialization() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    let tree = MerkleTree::<Blake3_256>::with_arity(leaves, 4).unwrap();
+
+    let bytes = tree.to_bytes();
+    let result = MerkleTree::<Blake3_256>::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(tree.root(), result.root());
+    assert_eq!(tree.leaves(), result.leaves());
+    assert_eq!(4, result.arity());
+
+    // a tree with an unsupported arity cannot be deserialized
+    let mut bytes = bytes;
+    bytes[0] = 5;
+    assert!(MerkleTree::<Blake3_256>::read_from(&mut SliceReader::new(&bytes)).is_err());
+}
+
 #[test]
 fn prove() {
     // depth 4
-    let leaves = Digest256::bytes_as_digests(&LEAVES4).to_vec();
+    let leaves = bytes_to_digests(&LEAVES4);
     let tree = MerkleTree::<Blake3_256>::new(leaves.clone()).unwrap();
 
     let proof = vec![leaves[1], leaves[0], hash_2x1(leaves[2], leaves[3])];
@@ -104,7 +181,7 @@ fn prove() {
     assert_eq!(proof, tree.prove(2).unwrap());
 
     // depth 5
-    let leaves = Digest256::bytes_as_digests(&LEAVES8).to_vec();
+    let leaves = bytes_to_digests(&LEAVES8);
     let tree = MerkleTree::<Blake3_256>::new(leaves.clone()).unwrap();
 
     let proof = vec![
@@ -133,7 +210,7 @@ fn prove() {
 #[test]
 fn verify() {
     // depth 4
-    let leaves = Digest256::bytes_as_digests(&LEAVES4).to_vec();
+    let leaves = bytes_to_digests(&LEAVES4);
     let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
     let proof = tree.prove(1).unwrap();
     assert!(MerkleTree::<Blake3_256>::verify(*tree.root(), 1, &proof).is_ok());
@@ -142,7 +219,7 @@ fn verify() {
     assert!(MerkleTree::<Blake3_256>::verify(*tree.root(), 2, &proof).is_ok());
 
     // depth 5
-    let leaves = Digest256::bytes_as_digests(&LEAVES8).to_vec();
+    let leaves = bytes_to_digests(&LEAVES8);
     let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
     let proof = tree.prove(1).unwrap();
     assert!(MerkleTree::<Blake3_256>::verify(*tree.root(), 1, &proof).is_ok());
@@ -153,7 +230,7 @@ fn verify() {
 
 #[test]
 fn prove_batch() {
-    let leaves = Digest256::bytes_as_digests(&LEAVES8).to_vec();
+    let leaves = bytes_to_digests(&LEAVES8);
     let tree = MerkleTree::<Blake3_256>::new(leaves.clone()).unwrap();
 
     // 1 index
@@ -168,7 +245,7 @@ fn prove_batch() {
         ),
     ]];
     assert_eq!(expected_values, proof.leaves);
-    assert_eq!(expected_nodes, proof.nodes);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
     assert_eq!(3, proof.depth);
 
     // 2 indexes
@@ -185,7 +262,7 @@ fn prove_batch() {
         vec![leaves[3]],
     ];
     assert_eq!(expected_values, proof.leaves);
-    assert_eq!(expected_nodes, proof.nodes);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
     assert_eq!(3, proof.depth);
 
     // 2 indexes on opposite sides
@@ -196,20 +273,94 @@ fn prove_batch() {
         vec![leaves[7], hash_2x1(leaves[4], leaves[5])],
     ];
     assert_eq!(expected_values, proof.leaves);
-    assert_eq!(expected_nodes, proof.nodes);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
     assert_eq!(3, proof.depth);
 
     // all indexes
     let proof = tree.prove_batch(&[0, 1, 2, 3, 4, 5, 6, 7]).unwrap();
     let expected_nodes: Vec<Vec<Digest256>> = vec![vec![], vec![], vec![], vec![]];
     assert_eq!(leaves, proof.leaves);
-    assert_eq!(expected_nodes, proof.nodes);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
     assert_eq!(3, proof.depth);
 }
 
+#[test]
+fn prove_kary_batch() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    let tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), 4).unwrap();
+
+    // 1 index
+    let proof = tree.prove_batch(&[1]).unwrap();
+    let expected_nodes = vec![vec![
+        leaves[0],
+        leaves[2],
+        leaves[3],
+        Blake3_256::merge_many(&leaves[4..]),
+    ]];
+    assert_eq!(vec![leaves[1]], proof.leaves);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
+    assert_eq!(3, proof.depth);
+    assert_eq!(4, proof.arity);
+
+    // 2 indexes under the same parent
+    let proof = tree.prove_batch(&[2, 0]).unwrap();
+    let expected_nodes = vec![vec![
+        leaves[1],
+        leaves[3],
+        Blake3_256::merge_many(&leaves[4..]),
+    ]];
+    assert_eq!(vec![leaves[2], leaves[0]], proof.leaves);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
+
+    // 2 indexes under different parents
+    let proof = tree.prove_batch(&[6, 1]).unwrap();
+    let expected_nodes = vec![
+        vec![leaves[0], leaves[2], leaves[3]],
+        vec![leaves[4], leaves[5], leaves[7]],
+    ];
+    assert_eq!(vec![leaves[6], leaves[1]], proof.leaves);
+    assert_eq!(ProofNodes::from(expected_nodes), proof.nodes);
+
+    // single paths are not defined for trees with arity greater than 2
+    assert_eq!(Err(MerkleTreeError::NotBinaryTree(4)), tree.prove(1));
+    assert_eq!(
+        Err(MerkleTreeError::NotBinaryTree(4)),
+        proof.into_paths(&[6, 1])
+    );
+}
+
+#[test]
+fn verify_kary_batch() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    for arity in [4, 8] {
+        let tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), arity).unwrap();
+
+        let proof = tree.prove_batch(&[1]).unwrap();
+        assert!(MerkleTree::verify_batch(tree.root(), &[1], &proof).is_ok());
+        assert!(MerkleTree::verify_batch(tree.root(), &[2], &proof).is_err());
+
+        let proof = tree.prove_batch(&[1, 2]).unwrap();
+        assert!(MerkleTree::verify_batch(tree.root(), &[1, 2], &proof).is_ok());
+        assert!(MerkleTree::verify_batch(tree.root(), &[2, 1], &proof).is_err());
+        assert!(MerkleTree::verify_batch(tree.root(), &[1], &proof).is_err());
+        assert!(MerkleTree::verify_batch(tree.root(), &[1, 2, 3], &proof).is_err());
+
+        let proof = tree.prove_batch(&[0, 1, 2, 3, 4, 5, 6, 7]).unwrap();
+        assert!(MerkleTree::verify_batch(tree.root(), &[0, 1, 2, 3, 4, 5, 6, 7], &proof).is_ok());
+
+        // a proof with unused nodes is rejected
+        let mut proof = tree.prove_batch(&[1, 6]).unwrap();
+        assert!(MerkleTree::verify_batch(tree.root(), &[1, 6], &proof).is_ok());
+        let mut nodes = proof.nodes.iter().map(<[_]>::to_vec).collect::<Vec<_>>();
+        nodes[0].push(leaves[0]);
+        proof.nodes = nodes.into();
+        assert!(MerkleTree::verify_batch(tree.root(), &[1, 6], &proof).is_err());
+    }
+}
+
 #[test]
 fn verify_batch() {
-    let leaves = Digest256::bytes_as_digests(&LEAVES8).to_vec();
+    let leaves = bytes_to_digests(&LEAVES8);
     let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
 
     let proof = tree.prove_batch(&[1]).unwrap();
@@ -234,7 +385,7 @@ fn verify_batch() {
 
 #[test]
 fn verify_into_paths() {
-    let leaves = Digest256::bytes_as_digests(&LEAVES8).to_vec();
+    let leaves = bytes_to_digests(&LEAVES8);
     let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
 
     let proof1 = tree.prove(1).unwrap();
@@ -256,6 +407,172 @@ fn verify_into_paths() {
     assert_eq!(proof6, result[2]);
 }
 
+#[test]
+fn from_single_paths() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
+
+    // the resulting proof is the same as the one built from the tree, including the order of
+    // leaves for unsorted indexes
+    for indexes in [
+        vec![1],
+        vec![1, 2],
+        vec![6, 1],
+        vec![5, 3, 4, 1],
+        vec![7, 6, 5, 4, 3, 2, 1, 0],
+    ] {
+        let paths = indexes.iter().map(|&i| single_path(&tree, i)).collect();
+        let proof = BatchMerkleProof::<Blake3_256>::from_single_paths(paths).unwrap();
+        assert_eq!(tree.prove_batch(&indexes).unwrap(), proof);
+        assert!(MerkleTree::verify_batch(tree.root(), &indexes, &proof).is_ok());
+    }
+}
+
+#[test]
+fn from_single_paths_invalid_paths() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    let tree = MerkleTree::<Blake3_256>::new(leaves).unwrap();
+
+    assert_eq!(
+        Err(MerkleTreeError::TooFewLeafIndexes),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(vec![])
+    );
+
+    let paths = vec![single_path(&tree, 1), single_path(&tree, 1)];
+    assert_eq!(
+        Err(MerkleTreeError::DuplicateLeafIndex),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+
+    let mut paths = vec![single_path(&tree, 1), single_path(&tree, 6)];
+    paths[1].2.pop();
+    assert_eq!(
+        Err(MerkleTreeError::PathLengthMismatch(3, 2)),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+
+    let mut paths = vec![single_path(&tree, 1)];
+    paths[0].2.clear();
+    assert_eq!(
+        Err(MerkleTreeError::InvalidProof),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+
+    let mut paths = vec![single_path(&tree, 1), single_path(&tree, 6)];
+    paths[1].0 = 8;
+    assert_eq!(
+        Err(MerkleTreeError::LeafIndexOutOfBounds(8, 8)),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+}
+
+#[test]
+fn from_single_paths_conflicting_nodes() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    let tree = MerkleTree::<Blake3_256>::new(leaves.clone()).unwrap();
+
+    // nodes are indexed as in a full tree: the root has index 1, and the leaf i has index i + 8
+
+    // the path for leaf 2 claims a node above leaves 0 and 1 which differs from the node computed
+    // from the path for leaf 1
+    let mut paths = vec![single_path(&tree, 1), single_path(&tree, 2)];
+    paths[1].2[1] = leaves[0];
+    assert_eq!(
+        Err(MerkleTreeError::ConflictingNodes(4)),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+
+    // the path for leaf 3 claims a value of leaf 2 which differs from the value claimed by the
+    // path for leaf 2
+    let mut paths = vec![single_path(&tree, 2), single_path(&tree, 3)];
+    paths[1].2[0] = leaves[0];
+    assert_eq!(
+        Err(MerkleTreeError::ConflictingNodes(10)),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+
+    // a path for a leaf which is not in the tree is internally consistent, but it resolves to a
+    // different node above the leaves 4 - 7 than the one claimed by the path for leaf 0
+    let mut paths = vec![single_path(&tree, 0), single_path(&tree, 5)];
+    paths[1].1 = leaves[0];
+    assert_eq!(
+        Err(MerkleTreeError::ConflictingNodes(3)),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+
+    // paths taken from two different trees do not resolve to the same root
+    let other_leaves = leaves.iter().rev().cloned().collect();
+    let other_tree = MerkleTree::<Blake3_256>::new(other_leaves).unwrap();
+    let paths = vec![single_path(&tree, 0), single_path(&other_tree, 7)];
+    assert_eq!(
+        Err(MerkleTreeError::ConflictingNodes(3)),
+        BatchMerkleProof::<Blake3_256>::from_single_paths(paths)
+    );
+}
+
+#[test]
+fn vector_commitment() {
+    let leaves = bytes_to_digests(&LEAVES8);
+    for arity in [2, 4, 8] {
+        let tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), arity).unwrap();
+        let commitment = VectorCommitment::commitment(&tree);
+        assert_eq!(*tree.root(), commitment);
+        assert_eq!(8, tree.domain_len());
+
+        // opened items are in the same order as the indexes
+        let indexes = [5, 1, 6];
+        let proof = tree.open_many(&indexes).unwrap();
+        assert_eq!(vec![leaves[5], leaves[1], leaves[6]], proof.leaves);
+        assert!(MerkleTree::<Blake3_256>::verify_many(&commitment, &indexes, &proof).is_ok());
+        assert!(MerkleTree::<Blake3_256>::verify_many(&leaves[0], &indexes, &proof).is_err());
+
+        // the proof survives a serialization round trip
+        let mut bytes = Vec::new();
+        MerkleTree::<Blake3_256>::write_multiproof(&proof, &indexes, None, &mut bytes);
+        let result = MerkleTree::<Blake3_256>::read_multiproof(
+            &mut SliceReader::new(&bytes),
+            proof.leaves.clone(),
+            None,
+            8,
+            arity,
+        )
+        .unwrap();
+        assert_eq!(proof, result);
+    }
+
+    // raw items are supported only for binary trees; siblings of opened items are serialized
+    // as raw items as well
+    let raw_leaves = (0..8u8)
+        .map(|i| {
+            let mut bytes = [0; 32];
+            bytes[..4].fill(i);
+            Digest256::new(bytes)
+        })
+        .collect::<Vec<_>>();
+    let tree = MerkleTree::<Blake3_256>::new(raw_leaves).unwrap();
+    let indexes = [2, 7];
+    let proof = tree.open_many(&indexes).unwrap();
+    let mut bytes = Vec::new();
+    MerkleTree::<Blake3_256>::write_multiproof(&proof, &indexes, Some(4), &mut bytes);
+    let result = MerkleTree::<Blake3_256>::read_multiproof(
+        &mut SliceReader::new(&bytes),
+        proof.leaves.clone(),
+        Some(4),
+        8,
+        2,
+    )
+    .unwrap();
+    assert_eq!(proof, result);
+    assert!(MerkleTree::<Blake3_256>::read_multiproof(
+        &mut SliceReader::new(&bytes),
+        proof.leaves.clone(),
+        Some(4),
+        8,
+        4,
+    )
+    .is_err());
+}
+
 proptest! {
     #[test]
     fn prove_n_verify(tree in random_blake3_merkle_tree(128),
@@ -294,6 +611,24 @@ proptest! {
         prop_assert!(proof1 == proof2);
     }
 
+    #[test]
+    fn batch_proof_from_single_paths(tree in random_blake3_merkle_tree(128),
+                      proof_indices in prop::collection::vec(any::<prop::sample::Index>(), 10..20)
+    )  {
+        let mut indices: Vec<usize> = Vec::new();
+        for idx in proof_indices.iter().map(|idx| idx.index(128)) {
+            if !indices.contains(&idx) {
+                indices.push(idx);
+            }
+        }
+        let proof1 = tree.prove_batch(&indices[..]).unwrap();
+
+        let paths = indices.iter().map(|&idx| single_path(&tree, idx)).collect();
+        let proof2 = BatchMerkleProof::<Blake3_256>::from_single_paths(paths).unwrap();
+
+        prop_assert!(proof1 == proof2);
+    }
+
     #[test]
     fn into_paths(tree in random_blake3_merkle_tree(32),
                       proof_indices in prop::collection::vec(any::<prop::sample::Index>(), 1..30)
@@ -313,17 +648,64 @@ proptest! {
     }
 }
 
+proptest! {
+    #[test]
+    fn prove_batch_n_verify_with_arity(leaves in prop::collection::vec(any::<[u8; 32]>(), 128),
+                      proof_indices in prop::collection::vec(any::<prop::sample::Index>(), 1..40)
+    )  {
+        let leaves = bytes_to_digests(&leaves);
+        let mut indices: Vec<usize> = Vec::new();
+        for idx in proof_indices.iter().map(|idx| idx.index(128)) {
+            if !indices.contains(&idx) {
+                indices.push(idx);
+            }
+        }
+
+        for arity in [2, 4, 8] {
+            let tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), arity).unwrap();
+            let proof = tree.prove_batch(&indices).unwrap();
+            prop_assert!(MerkleTree::verify_batch(tree.root(), &indices, &proof).is_ok());
+
+            // the proof survives a serialization round trip
+            let node_bytes = proof.serialize_nodes();
+            let result = BatchMerkleProof::<Blake3_256>::deserialize_with_arity(
+                &mut SliceReader::new(&node_bytes),
+                proof.leaves.clone(),
+                proof.depth,
+                arity as u8,
+            ).unwrap();
+            prop_assert_eq!(&proof, &result);
+
+            // the proof does not resolve to the root of a tree with a different arity
+            let other_arity = if arity == 8 { 2 } else { arity * 2 };
+            let other_tree = MerkleTree::<Blake3_256>::with_arity(leaves.clone(), other_arity).unwrap();
+            prop_assert!(MerkleTree::verify_batch(other_tree.root(), &indices, &proof).is_err());
+        }
+    }
+}
+
 // HELPER FUNCTIONS
 // --------------------------------------------------------------------------------------------
 fn hash_2x1(v1: Digest256, v2: Digest256) -> Digest256 {
     Blake3_256::merge(&[v1, v2])
 }
 
+/// Returns the path to the leaf at the specified index as a tuple (index, leaf, siblings).
+fn single_path(tree: &MerkleTree<Blake3_256>, index: usize) -> (usize, Digest256, Vec<Digest256>) {
+    let mut path = tree.prove(index).unwrap();
+    let leaf = path.remove(0);
+    (index, leaf, path)
+}
+
+fn bytes_to_digests(bytes: &[[u8; 32]]) -> Vec<Digest256> {
+    bytes.iter().map(|&b| Digest256::new(b)).collect()
+}
+
 pub fn random_blake3_merkle_tree(
     leave_count: usize,
 ) -> impl Strategy<Value = MerkleTree<Blake3_256>> {
     prop::collection::vec(any::<[u8; 32]>(), leave_count).prop_map(|leaves| {
-        let leaves = Digest256::bytes_as_digests(&leaves).to_vec();
+        let leaves = bytes_to_digests(&leaves);
         MerkleTree::<Blake3_256>::new(leaves).unwrap()
     })
 }
@@ -0,0 +1,576 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::RandomCoin;
+#[cfg(feature = "transcript-log")]
+use crate::transcript_log;
+use crate::{
+    errors::RandomCoinError, hash_to_field::expand_to_element, Digest, ElementHasher, Hasher,
+};
+use core::{convert::TryInto, marker::PhantomData};
+use math::{FieldElement, StarkField};
+use utils::{
+    collections::Vec, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
+
+// DEFAULT RANDOM COIN
+// ================================================================================================
+
+/// Pseudo-random element generator for finite fields; this is the default implementation of the
+/// [RandomCoin] trait.
+///
+/// A random coin can be used to draws elements uniformly at random from the specified base field
+// (which is specified via the `B` type parameter) or from any extension of the base field.
+///
+/// Internally we use a cryptographic hash function (which is specified via the `H` type parameter),
+/// to draw elements from the field. The coin works roughly as follows:
+/// - The internal state of the coin consists of a `seed` and a `counter`. At instantiation
+///   time, the `seed` is set to a hash of the provided bytes, and the `counter` is set to 0.
+/// - To draw the next element, we increment the `counter` and compute hash(`seed` || `counter`).
+///   If the resulting value is a valid field element, we return the result; otherwise we try
+///   again until a valid element is found or the number of allowed tries is exceeded.
+/// - We can also re-seed the coin with a new value. During the reseeding procedure, the
+///   seed is set to hash(`old_seed` || `new_seed`), and the counter is reset to 0.
+///
+/// # Examples
+/// ```
+/// # use winter_crypto::{DefaultRandomCoin, hashers::Blake3_256};
+/// # use math::fields::f128::BaseElement;
+/// // instantiate a random coin using BLAKE3 as the hash function
+/// let mut coin = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+///
+/// // should draw different elements each time
+/// let e1 = coin.draw::<BaseElement>().unwrap();;
+/// let e2 = coin.draw::<BaseElement>().unwrap();;
+/// assert_ne!(e1, e2);
+///
+/// let e3 = coin.draw::<BaseElement>().unwrap();;
+/// assert_ne!(e1, e3);
+/// assert_ne!(e2, e3);
+///
+/// // should draw same elements for the same seed
+/// let mut coin1 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+/// let mut coin2 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+/// let e1 = coin1.draw::<BaseElement>().unwrap();;
+/// let e2 = coin2.draw::<BaseElement>().unwrap();;
+/// assert_eq!(e1, e2);
+///
+/// // should draw different elements based on seed
+/// let mut coin1 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+/// let mut coin2 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[2, 3, 4, 5]);
+/// let e1 = coin1.draw::<BaseElement>().unwrap();;
+/// let e2 = coin2.draw::<BaseElement>().unwrap();;
+/// assert_ne!(e1, e2);
+/// ```
+pub struct DefaultRandomCoin<B, H>
+where
+    B: StarkField,
+    H: Hasher,
+{
+    seed: H::Digest,
+    counter: u64,
+    _base_field: PhantomData<B>,
+}
+
+impl<B: StarkField, H: Hasher> DefaultRandomCoin<B, H> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new random coin instantiated with the provided `seed`.
+    pub fn new(seed: &[u8]) -> Self {
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push_labeled(transcript_log::SEED_LABEL, seed, &[]);
+        let seed = H::hash(seed);
+        DefaultRandomCoin {
+            seed,
+            counter: 0,
+            _base_field: PhantomData,
+        }
+    }
+
+    // RESEEDING
+    // --------------------------------------------------------------------------------------------
+
+    /// Reseeds the coin with the specified data by setting the new seed to hash(`seed` || `data`).
+    ///
+    /// # Examples
+    /// ```
+    /// # use winter_crypto::{DefaultRandomCoin, Hasher, hashers::Blake3_256};
+    /// # use math::fields::f128::BaseElement;
+    /// let mut coin1 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    /// let mut coin2 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    ///
+    /// // should draw the same element form both coins
+    /// let e1 = coin1.draw::<BaseElement>().unwrap();
+    /// let e2 = coin2.draw::<BaseElement>().unwrap();;
+    /// assert_eq!(e1, e2);
+    ///
+    /// // after reseeding should draw different elements
+    /// coin2.reseed(Blake3_256::<BaseElement>::hash(&[2, 3, 4, 5]));
+    /// let e1 = coin1.draw::<BaseElement>().unwrap();;
+    /// let e2 = coin2.draw::<BaseElement>().unwrap();;
+    /// assert_ne!(e1, e2);
+    /// ```
+    pub fn reseed(&mut self, data: H::Digest) {
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push(&data.as_bytes(), &[]);
+        self.seed = H::merge(&[self.seed, data]);
+        self.counter = 0;
+    }
+
+    /// Reseeds the coin with the specified value by setting the new seed to hash(`seed` ||
+    /// `value`).
+    ///
+    /// # Examples
+    /// ```
+    /// # use winter_crypto::{DefaultRandomCoin, Hasher, hashers::Blake3_256};
+    /// # use math::fields::f128::BaseElement;
+    /// let mut coin1 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    /// let mut coin2 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    ///
+    /// // should draw the same element form both coins
+    /// let e1 = coin1.draw::<BaseElement>().unwrap();;
+    /// let e2 = coin2.draw::<BaseElement>().unwrap();;
+    /// assert_eq!(e1, e2);
+    ///
+    /// // after reseeding should draw different elements
+    /// coin2.reseed_with_int(42);
+    /// let e1 = coin1.draw::<BaseElement>().unwrap();;
+    /// let e2 = coin2.draw::<BaseElement>().unwrap();;
+    /// assert_ne!(e1, e2);
+    /// ```
+    pub fn reseed_with_int(&mut self, value: u64) {
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push(&value.to_le_bytes(), &[]);
+        self.seed = H::merge_with_int(self.seed, value);
+        self.counter = 0;
+    }
+
+    /// Returns a new coin with the seed set to hash(`seed` || hash(`key`)) and the counter set
+    /// to 0; the state of this coin is not changed.
+    ///
+    /// Values drawn from the returned coin depend only on the current seed of this coin and on
+    /// the `key`, and thus, do not depend on how many values have been drawn from this coin, or
+    /// from coins derived from it using other keys.
+    ///
+    /// # Examples
+    /// ```
+    /// # use winter_crypto::{DefaultRandomCoin, hashers::Blake3_256};
+    /// # use math::fields::f128::BaseElement;
+    /// let mut coin1 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    /// let coin2 = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    ///
+    /// // drawing from the coin does not affect values drawn from derived coins
+    /// let e1 = coin1.derive_keyed(b"foo").draw::<BaseElement>().unwrap();
+    /// coin1.draw::<BaseElement>().unwrap();
+    /// let e2 = coin2.derive_keyed(b"foo").draw::<BaseElement>().unwrap();
+    /// assert_eq!(e1, e2);
+    ///
+    /// // coins derived with different keys draw different elements
+    /// let e3 = coin2.derive_keyed(b"bar").draw::<BaseElement>().unwrap();
+    /// assert_ne!(e2, e3);
+    /// ```
+    pub fn derive_keyed(&self, key: &[u8]) -> Self {
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push(key, &[]);
+        DefaultRandomCoin {
+            seed: H::merge(&[self.seed, H::hash(key)]),
+            counter: 0,
+            _base_field: PhantomData,
+        }
+    }
+
+    // TRANSCRIPT LOG
+    // --------------------------------------------------------------------------------------------
+
+    /// Sets the label with which subsequent interactions with random coins on the current thread
+    /// are recorded into the active [TranscriptLog](crate::transcript_log::TranscriptLog), and
+    /// returns `self` so that the labeled interaction can be chained onto this call.
+    ///
+    /// This method does nothing unless `transcript-log` feature is enabled.
+    #[inline(always)]
+    pub fn label(&mut self, label: &'static str) -> &mut Self {
+        #[cfg(feature = "transcript-log")]
+        transcript_log::set_label(label);
+        #[cfg(not(feature = "transcript-log"))]
+        let _ = label;
+        self
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of leading zeros in the seed if it is interpreted as an integer in
+    /// big-endian byte order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use winter_crypto::{DefaultRandomCoin, hashers::Blake3_256};
+    /// # use math::fields::f128::BaseElement;
+    /// let mut coin = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    ///
+    /// let mut value = 0;
+    /// while coin.check_leading_zeros(value) < 2 {
+    ///     value += 1;
+    /// }
+    ///
+    /// coin.reseed_with_int(value);
+    /// assert!(coin.leading_zeros() >= 2);
+    /// ```
+    pub fn leading_zeros(&self) -> u32 {
+        let bytes = self.seed.as_bytes();
+        let seed_head = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        seed_head.trailing_zeros()
+    }
+
+    /// Computes hash(`seed` || `value`) and returns the number of leading zeros in the resulting
+    /// value if it is interpreted as an integer in big-endian byte order.
+    pub fn check_leading_zeros(&self, value: u64) -> u32 {
+        let new_seed = H::merge_with_int(self.seed, value);
+        let bytes = new_seed.as_bytes();
+        let seed_head = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        seed_head.trailing_zeros()
+    }
+
+    /// Computes hash(`seed` || `value`) and returns the number of leading zeros in the first 8
+    /// bytes of the resulting value interpreted as an integer in big-endian byte order.
+    ///
+    /// Unlike [check_leading_zeros()](DefaultRandomCoin::check_leading_zeros), this counts the zero bits
+    /// at the very beginning of the digest's byte representation.
+    pub fn check_leading_zeros_be(&self, value: u64) -> u32 {
+        let new_seed = H::merge_with_int(self.seed, value);
+        let bytes = new_seed.as_bytes();
+        let seed_head = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+        seed_head.leading_zeros()
+    }
+
+    // DRAW METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the next pseudo-random field element.
+    ///
+    /// Field elements are derived from the PRNG output using the same expand-and-reduce
+    /// construction as [hash_to_field()](crate::hash_to_field): each base field element consumes
+    /// two consecutive PRNG values, and thus, the drawn elements are statistically close to
+    /// uniform without resorting to rejection sampling.
+    ///
+    /// # Errors
+    /// Currently, drawing a field element never fails; the result type is retained for
+    /// compatibility with random coin implementations which rely on rejection sampling.
+    pub fn draw<E>(&mut self) -> Result<E, RandomCoinError>
+    where
+        E: FieldElement<BaseField = B>,
+    {
+        let element = expand_to_element::<E, H>(self.seed, &mut self.counter);
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push(&[], E::elements_as_bytes(&[element]));
+        Ok(element)
+    }
+
+    /// Returns the next pair of pseudo-random field elements.
+    ///
+    /// # Errors
+    /// Returns an error if any of the field elements could not be generated after 100 calls to
+    /// the PRNG;
+    pub fn draw_pair<E>(&mut self) -> Result<(E, E), RandomCoinError>
+    where
+        E: FieldElement<BaseField = B>,
+    {
+        Ok((self.draw()?, self.draw()?))
+    }
+
+    /// Returns the next triplet of pseudo-random field elements.
+    ///
+    /// # Errors
+    /// Returns an error if any of the field elements could not be generated after 100 calls to
+    /// the PRNG;
+    pub fn draw_triple<E>(&mut self) -> Result<(E, E, E), RandomCoinError>
+    where
+        E: FieldElement<BaseField = B>,
+    {
+        Ok((self.draw()?, self.draw()?, self.draw()?))
+    }
+
+    /// Returns a vector of unique integers selected from the range [0, domain_size).
+    ///
+    /// # Errors
+    /// Returns an error if the specified number of unique integers could not be generated
+    /// after 1000 calls to the PRNG.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// - `domain_size` is not a power of two.
+    /// - `num_values` is greater than or equal to `domain_size`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::collections::HashSet;
+    /// # use winter_crypto::{DefaultRandomCoin, hashers::Blake3_256};
+    /// # use math::fields::f128::BaseElement;
+    /// let mut coin = DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+    ///
+    /// let num_values = 20;
+    /// let domain_size = 64;
+    /// let values = coin.draw_integers(num_values, domain_size).unwrap();
+    ///
+    /// assert_eq!(num_values, values.len());
+    ///
+    /// let mut value_set = HashSet::new();
+    /// for value in values {
+    ///     assert!(value < domain_size);
+    ///     assert!(value_set.insert(value));
+    /// }
+    /// ```
+    pub fn draw_integers(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        self.draw_integers_with(num_values, domain_size, u64::from_le_bytes)
+    }
+
+    /// Returns a vector of unique integers selected from the range [0, domain_size), reading
+    /// the bytes of each pseudo-random value in big-endian byte order.
+    ///
+    /// This is identical to [draw_integers()](DefaultRandomCoin::draw_integers) except for the byte
+    /// order used to convert pseudo-random values into integers.
+    ///
+    /// # Errors
+    /// Returns an error if the specified number of unique integers could not be generated
+    /// after 1000 calls to the PRNG.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// - `domain_size` is not a power of two.
+    /// - `num_values` is greater than or equal to `domain_size`.
+    pub fn draw_integers_be(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        self.draw_integers_with(num_values, domain_size, u64::from_be_bytes)
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Draws unique integers from the range [0, domain_size) converting the first 8 bytes of
+    /// each pseudo-random value into an integer using the `to_int` function.
+    fn draw_integers_with(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+        to_int: fn([u8; 8]) -> u64,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        assert!(
+            domain_size.is_power_of_two(),
+            "domain size must be a power of two"
+        );
+        assert!(
+            num_values < domain_size,
+            "number of values must be smaller than domain size"
+        );
+
+        // determine how many bits are needed to represent valid values in the domain
+        let v_mask = (domain_size - 1) as u64;
+
+        // draw values from PRNG until we get as many unique values as specified by num_queries
+        let mut values = Vec::with_capacity(num_values);
+        for _ in 0..1000 {
+            // get the next pseudo-random value and read the first 8 bytes from it
+            let bytes: [u8; 8] = self.next().as_bytes()[..8].try_into().unwrap();
+
+            // convert to integer and limit the integer to the number of bits which can fit
+            // into the specified domain
+            let value = (to_int(bytes) & v_mask) as usize;
+
+            if values.contains(&value) {
+                continue;
+            }
+            values.push(value);
+            if values.len() == num_values {
+                break;
+            }
+        }
+
+        if values.len() < num_values {
+            return Err(RandomCoinError::FailedToDrawIntegers(
+                num_values,
+                values.len(),
+                1000,
+            ));
+        }
+
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push(
+            &[],
+            &values
+                .iter()
+                .flat_map(|&value| (value as u64).to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+
+        Ok(values)
+    }
+
+    /// Updates the state by incrementing the counter and returns hash(seed || counter)
+    fn next(&mut self) -> H::Digest {
+        self.counter += 1;
+        H::merge_with_int(self.seed, self.counter)
+    }
+}
+
+impl<B: StarkField, H: ElementHasher<BaseField = B>> DefaultRandomCoin<B, H> {
+    // ELEMENT-BASED CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new random coin instantiated with the provided `seed` elements.
+    ///
+    /// Unlike [new()](DefaultRandomCoin::new), the seed is absorbed by hashing the field elements
+    /// directly, and thus, a coin instantiated this way can be reproduced by a verifier which
+    /// operates natively over the base field.
+    ///
+    /// # Examples
+    /// ```
+    /// # use winter_crypto::{DefaultRandomCoin, hashers::Rp64_256};
+    /// # use math::fields::f64::BaseElement;
+    /// let seed = [BaseElement::new(1), BaseElement::new(2)];
+    /// let mut coin1 = DefaultRandomCoin::<BaseElement, Rp64_256>::from_elements(&seed);
+    /// let mut coin2 = DefaultRandomCoin::<BaseElement, Rp64_256>::new(&[1, 2]);
+    ///
+    /// // should draw different elements than a coin seeded with the same values as bytes
+    /// let e1 = coin1.draw::<BaseElement>().unwrap();
+    /// let e2 = coin2.draw::<BaseElement>().unwrap();
+    /// assert_ne!(e1, e2);
+    /// ```
+    pub fn from_elements(seed: &[B]) -> Self {
+        #[cfg(feature = "transcript-log")]
+        transcript_log::push_labeled(transcript_log::SEED_LABEL, B::elements_as_bytes(seed), &[]);
+        let seed = H::hash_elements(seed);
+        DefaultRandomCoin {
+            seed,
+            counter: 0,
+            _base_field: PhantomData,
+        }
+    }
+
+    // ELEMENT-BASED ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Computes hash(`seed` || `value`) and returns the number of trailing zero bits in the
+    /// canonical integer representation of the first element of the resulting digest.
+    ///
+    /// If the first element of the digest is zero, the number of bits in the field modulus is
+    /// returned.
+    ///
+    /// # Panics
+    /// Panics if digests of the hash function are not composed of base field elements (i.e.,
+    /// [ElementHasher::digest_as_elements()] returns None).
+    pub fn check_trailing_zeros_in_element(&self, value: u64) -> u32 {
+        let new_seed = H::merge_with_int(self.seed, value);
+        let element = H::digest_as_elements(&new_seed)
+            .expect("digest of the hash function is not composed of field elements")[0];
+
+        let zero = B::PositiveInteger::from(0u32);
+        let one = B::PositiveInteger::from(1u32);
+        let mut value = element.as_int();
+        if value == zero {
+            return B::MODULUS_BITS;
+        }
+
+        let mut num_zeros = 0;
+        while value & one == zero {
+            value = value >> 1;
+            num_zeros += 1;
+        }
+        num_zeros
+    }
+}
+
+// RANDOM COIN IMPLEMENTATION
+// ================================================================================================
+
+impl<B: StarkField, H: ElementHasher<BaseField = B>> RandomCoin for DefaultRandomCoin<B, H> {
+    type BaseField = B;
+    type Hasher = H;
+
+    const COIN_ID: u8 = 0;
+
+    fn new(seed: &[u8]) -> Self {
+        Self::new(seed)
+    }
+
+    fn from_elements(seed: &[B]) -> Self {
+        Self::from_elements(seed)
+    }
+
+    fn reseed(&mut self, data: H::Digest) {
+        self.reseed(data)
+    }
+
+    fn reseed_with_int(&mut self, value: u64) {
+        self.reseed_with_int(value)
+    }
+
+    fn derive_keyed(&self, key: &[u8]) -> Self {
+        self.derive_keyed(key)
+    }
+
+    fn check_leading_zeros(&self, value: u64) -> u32 {
+        self.check_leading_zeros(value)
+    }
+
+    fn check_leading_zeros_be(&self, value: u64) -> u32 {
+        self.check_leading_zeros_be(value)
+    }
+
+    fn check_trailing_zeros_in_element(&self, value: u64) -> u32 {
+        self.check_trailing_zeros_in_element(value)
+    }
+
+    fn draw<E: FieldElement<BaseField = B>>(&mut self) -> Result<E, RandomCoinError> {
+        self.draw()
+    }
+
+    fn draw_integers(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        self.draw_integers(num_values, domain_size)
+    }
+
+    fn draw_integers_be(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        self.draw_integers_be(num_values, domain_size)
+    }
+}
+
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<B: StarkField, H: Hasher> Serializable for DefaultRandomCoin<B, H> {
+    /// Serializes the current state of the coin (its seed and counter) and writes the resulting
+    /// bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.seed.write_into(target);
+        target.write_u64(self.counter);
+    }
+}
+
+impl<B: StarkField, H: Hasher> Deserializable for DefaultRandomCoin<B, H> {
+    /// Reads the state of a coin from the specified `source`; the returned coin draws the same
+    /// values as the coin from which the state was serialized.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let seed = H::Digest::read_from(source)?;
+        let counter = source.read_u64()?;
+        Ok(DefaultRandomCoin {
+            seed,
+            counter,
+            _base_field: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Recording of [RandomCoin](crate::RandomCoin) interactions.
+//!
+//! While a closure is executed via [record()], every interaction with any random coin on the
+//! current thread is appended to a [TranscriptLog]: constructing a coin and reseeding it absorb
+//! bytes into the coin, while drawing values from it squeezes bytes out of it. Each entry is
+//! tagged with the label most recently set via [RandomCoin::label()](crate::RandomCoin::label),
+//! and thus, logs recorded by a prover and by a verifier replaying the same proof can be compared
+//! entry by entry; the first entry at which the two logs differ points to the step of the protocol
+//! at which the transcripts diverged.
+//!
+//! This module is available only when `transcript-log` feature is enabled.
+
+use core::{cell::RefCell, fmt};
+use std::{string::String, vec::Vec};
+use utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Label of the entries recorded when a random coin is constructed.
+pub const SEED_LABEL: &str = "seed";
+
+// RECORDER
+// ================================================================================================
+
+/// State of the recorder for the current thread.
+struct Recorder {
+    label: &'static str,
+    log: TranscriptLog,
+}
+
+std::thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Executes the provided closure and returns its result together with the log of all random coin
+/// interactions performed on the current thread while the closure was executed.
+///
+/// Recording is not nested: if `f` calls this function, the interactions performed by the inner
+/// closure are recorded only into the inner log.
+pub fn record<R, F: FnOnce() -> R>(f: F) -> (R, TranscriptLog) {
+    let recorder = Recorder {
+        label: SEED_LABEL,
+        log: TranscriptLog::new(),
+    };
+    let guard = RecorderGuard {
+        previous: RECORDER.with(|cell| cell.replace(Some(recorder))),
+    };
+    let result = f();
+    let recorder = RECORDER
+        .with(|cell| cell.borrow_mut().take())
+        .expect("transcript recorder was removed while recording");
+    drop(guard);
+    (result, recorder.log)
+}
+
+/// Restores the recorder which was active before [record()] was called; this also happens when
+/// the recorded closure panics.
+struct RecorderGuard {
+    previous: Option<Recorder>,
+}
+
+impl Drop for RecorderGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        RECORDER.with(|cell| cell.replace(previous));
+    }
+}
+
+/// Sets the label of the subsequently recorded entries.
+pub(crate) fn set_label(label: &'static str) {
+    RECORDER.with(|cell| {
+        if let Some(recorder) = cell.borrow_mut().as_mut() {
+            recorder.label = label;
+        }
+    });
+}
+
+/// Records an entry with the specified label.
+pub(crate) fn push_labeled(label: &'static str, absorbed: &[u8], squeezed: &[u8]) {
+    RECORDER.with(|cell| {
+        if let Some(recorder) = cell.borrow_mut().as_mut() {
+            recorder.log.push(TranscriptEntry {
+                label: label.into(),
+                absorbed: absorbed.to_vec(),
+                squeezed: squeezed.to_vec(),
+            });
+        }
+    });
+}
+
+/// Records an entry with the current label.
+pub(crate) fn push(absorbed: &[u8], squeezed: &[u8]) {
+    let label = RECORDER.with(|cell| cell.borrow().as_ref().map(|recorder| recorder.label));
+    if let Some(label) = label {
+        push_labeled(label, absorbed, squeezed);
+    }
+}
+
+// TRANSCRIPT LOG
+// ================================================================================================
+
+/// An ordered list of random coin interactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptLog {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl TranscriptLog {
+    /// Returns a new empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an entry to this log.
+    pub fn push(&mut self, entry: TranscriptEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Returns all entries of this log in the order in which they were recorded.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of entries in this log.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if this log contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the index of the first entry at which this log differs from the `other` log, or
+    /// None if the logs are identical.
+    ///
+    /// When one log is a prefix of the other, the index of the first entry missing from the
+    /// shorter log is returned.
+    pub fn first_divergence(&self, other: &Self) -> Option<usize> {
+        let position = self
+            .entries
+            .iter()
+            .zip(other.entries.iter())
+            .position(|(a, b)| a != b);
+        match position {
+            Some(index) => Some(index),
+            None if self.len() != other.len() => Some(self.len().min(other.len())),
+            None => None,
+        }
+    }
+
+    /// Returns this log encoded as a JSON object with a single `entries` array; byte strings are
+    /// encoded as lowercase hex, and every entry is placed on a separate line so that logs can be
+    /// compared with line-based diff tools.
+    pub fn to_json(&self) -> String {
+        let mut result = String::from("{\"entries\":[");
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            result.push_str("\n{\"label\":\"");
+            result.push_str(&entry.label);
+            result.push_str("\",\"absorbed\":\"");
+            result.push_str(&to_hex(&entry.absorbed));
+            result.push_str("\",\"squeezed\":\"");
+            result.push_str(&to_hex(&entry.squeezed));
+            result.push_str("\"}");
+        }
+        result.push_str("\n]}");
+        result
+    }
+}
+
+impl Serializable for TranscriptLog {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.entries.len() as u32);
+        for entry in self.entries.iter() {
+            entry.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for TranscriptLog {
+    /// Reads a transcript log from the specified `source` and returns the result.
+    ///
+    /// # Errors
+    /// Returns an error if a valid transcript log could not be read from the source.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_entries = source.read_u32()? as usize;
+        let entries = TranscriptEntry::read_batch_from(source, num_entries)?;
+        Ok(TranscriptLog { entries })
+    }
+}
+
+// TRANSCRIPT ENTRY
+// ================================================================================================
+
+/// A single random coin interaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    /// Label of the protocol step during which the interaction was performed.
+    pub label: String,
+    /// Bytes absorbed into the coin; empty for draws.
+    pub absorbed: Vec<u8>,
+    /// Bytes of the values drawn from the coin; empty for reseeding.
+    pub squeezed: Vec<u8>,
+}
+
+impl fmt::Display for TranscriptEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: absorbed [{}], squeezed [{}]",
+            self.label,
+            to_hex(&self.absorbed),
+            to_hex(&self.squeezed)
+        )
+    }
+}
+
+impl Serializable for TranscriptEntry {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u16(self.label.len() as u16);
+        target.write_u8_slice(self.label.as_bytes());
+        target.write_u32(self.absorbed.len() as u32);
+        target.write_u8_slice(&self.absorbed);
+        target.write_u32(self.squeezed.len() as u32);
+        target.write_u8_slice(&self.squeezed);
+    }
+}
+
+impl Deserializable for TranscriptEntry {
+    /// Reads a transcript entry from the specified `source` and returns the result.
+    ///
+    /// # Errors
+    /// Returns an error if a valid transcript entry could not be read from the source.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let label_len = source.read_u16()? as usize;
+        let label = String::from_utf8(source.read_u8_vec(label_len)?).map_err(|err| {
+            DeserializationError::InvalidValue(format!("invalid transcript label: {err}"))
+        })?;
+        let absorbed_len = source.read_u32()? as usize;
+        let absorbed = source.read_u8_vec(absorbed_len)?;
+        let squeezed_len = source.read_u32()? as usize;
+        let squeezed = source.read_u8_vec(squeezed_len)?;
+        Ok(TranscriptEntry {
+            label,
+            absorbed,
+            squeezed,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Encodes the specified bytes as a lowercase hex string.
+fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        result.push(DIGITS[(byte >> 4) as usize] as char);
+        result.push(DIGITS[(byte & 0xf) as usize] as char);
+    }
+    result
+}
@@ -0,0 +1,117 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{record, TranscriptEntry, TranscriptLog, SEED_LABEL};
+use crate::{hashers::Blake3_256, DefaultRandomCoin, Digest, Hasher};
+use math::{fields::f128::BaseElement, FieldElement};
+use utils::{Deserializable, Serializable, SliceReader};
+
+type Coin = DefaultRandomCoin<BaseElement, Blake3_256<BaseElement>>;
+
+#[test]
+fn record_coin_interactions() {
+    let digest = Blake3_256::<BaseElement>::hash(&[5, 6, 7]);
+    let ((element, positions), log) = record(|| {
+        let mut coin = Coin::new(&[1, 2, 3]);
+        coin.label("commitment").reseed(digest);
+        coin.reseed_with_int(42);
+        let element = coin.label("challenge").draw::<BaseElement>().unwrap();
+        let positions = coin.label("queries").draw_integers(2, 64).unwrap();
+        (element, positions)
+    });
+
+    let entries = log.entries();
+    assert_eq!(5, entries.len());
+    assert_eq!(entry(SEED_LABEL, &[1, 2, 3], &[]), entries[0]);
+    assert_eq!(entry("commitment", &digest.as_bytes(), &[]), entries[1]);
+    assert_eq!(entry("commitment", &42u64.to_le_bytes(), &[]), entries[2]);
+    assert_eq!(
+        entry("challenge", &[], BaseElement::elements_as_bytes(&[element])),
+        entries[3]
+    );
+    let positions = positions
+        .iter()
+        .flat_map(|&p| (p as u64).to_le_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(entry("queries", &[], &positions), entries[4]);
+
+    // interactions outside of the recorded closure are not logged
+    let mut coin = Coin::new(&[1, 2, 3]);
+    coin.label("ignored").reseed(digest);
+    let (_, log) = record(|| ());
+    assert!(log.is_empty());
+}
+
+#[test]
+fn record_restores_outer_recorder() {
+    let (inner_log, outer_log) = record(|| {
+        Coin::new(&[1]);
+        let (_, inner_log) = record(|| {
+            Coin::new(&[2]);
+        });
+        Coin::new(&[3]);
+        inner_log
+    });
+    assert_eq!(vec![entry(SEED_LABEL, &[2], &[])], inner_log.entries());
+    assert_eq!(
+        vec![entry(SEED_LABEL, &[1], &[]), entry(SEED_LABEL, &[3], &[])],
+        outer_log.entries()
+    );
+
+    // a panic inside the recorded closure does not leave the recorder active
+    let result = std::panic::catch_unwind(|| record(|| panic!("recording failed")));
+    assert!(result.is_err());
+    Coin::new(&[4]);
+    let (_, log) = record(|| ());
+    assert!(log.is_empty());
+}
+
+#[test]
+fn transcript_log_first_divergence() {
+    let log = build_log(&["a", "b", "c"]);
+    assert_eq!(None, log.first_divergence(&log.clone()));
+    assert_eq!(Some(1), log.first_divergence(&build_log(&["a", "x", "c"])));
+    assert_eq!(Some(2), log.first_divergence(&build_log(&["a", "b"])));
+    assert_eq!(Some(2), build_log(&["a", "b"]).first_divergence(&log));
+    assert_eq!(Some(0), log.first_divergence(&TranscriptLog::new()));
+}
+
+#[test]
+fn transcript_log_serialization() {
+    let mut log = build_log(&["a", "b"]);
+    log.push(entry("c", &[1, 2], &[0xab, 0xcd]));
+
+    let bytes = log.to_bytes();
+    let parsed = TranscriptLog::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(log, parsed);
+    assert!(TranscriptLog::read_from(&mut SliceReader::new(&bytes[..bytes.len() - 1])).is_err());
+
+    let log = build_log(&["c"]);
+    assert_eq!(
+        "{\"entries\":[\n{\"label\":\"c\",\"absorbed\":\"\",\"squeezed\":\"\"}\n]}",
+        log.to_json()
+    );
+    let entry = entry("c", &[1, 2], &[0xab, 0xcd]);
+    assert_eq!("c: absorbed [0102], squeezed [abcd]", entry.to_string());
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn entry(label: &str, absorbed: &[u8], squeezed: &[u8]) -> TranscriptEntry {
+    TranscriptEntry {
+        label: label.into(),
+        absorbed: absorbed.to_vec(),
+        squeezed: squeezed.to_vec(),
+    }
+}
+
+fn build_log(labels: &[&str]) -> TranscriptLog {
+    let mut log = TranscriptLog::new();
+    for label in labels {
+        log.push(entry(label, &[], &[]));
+    }
+    log
+}
@@ -0,0 +1,68 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use examples::fibonacci::fib_small::{FibSmall, FibSmallProver};
+use std::time::Duration;
+use winterfell::{
+    batch, crypto::hashers::Blake3_256, math::fields::f64::BaseElement, FieldExtension,
+    ProofOptions, Prover, TraceTable,
+};
+
+// Compares the time needed to prove a batch of small Fibonacci traces one by one against the time
+// needed to prove the same traces via the batch prover.
+
+const NUM_PROOFS: usize = 1000;
+const SEQUENCE_LENGTH: usize = 256;
+
+type Blake3 = Blake3_256<BaseElement>;
+
+fn prove_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prove_batch");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256);
+    let prover = FibSmallProver::<Blake3>::new(options);
+    let build_traces = || -> Vec<TraceTable<BaseElement>> {
+        (0..NUM_PROOFS)
+            .map(|_| prover.build_trace(SEQUENCE_LENGTH))
+            .collect()
+    };
+
+    // make sure all proofs generated by the batch prover are valid
+    let traces = build_traces();
+    let pub_inputs = prover.get_pub_inputs(&traces[0]);
+    for proof in batch::prove_batch(&prover, traces) {
+        let proof = proof.expect("failed to generate a proof");
+        winterfell::verify::<FibSmall, Blake3>(proof, pub_inputs).expect("invalid proof");
+    }
+
+    let trace_length = SEQUENCE_LENGTH / 2;
+    group.bench_function(BenchmarkId::new("naive", trace_length), |bench| {
+        bench.iter_batched(
+            build_traces,
+            |traces| {
+                traces
+                    .into_iter()
+                    .map(|trace| prover.prove(trace))
+                    .collect::<Vec<_>>()
+            },
+            criterion::BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function(BenchmarkId::new("batch", trace_length), |bench| {
+        bench.iter_batched(
+            build_traces,
+            |traces| batch::prove_batch(&prover, traces),
+            criterion::BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(batch_group, prove_batch);
+criterion_main!(batch_group);
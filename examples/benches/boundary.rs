@@ -0,0 +1,254 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::time::Duration;
+use winterfell::{
+    crypto::hashers::Blake3_256,
+    math::{fields::f128::BaseElement, FieldElement},
+    Air, AirContext, Assertion, AuxTraceRandElements, ByteWriter, EvaluationFrame, FieldExtension,
+    ProofOptions, Prover, Serializable, Trace, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+// Measures the time needed to build boundary constraints, generate a proof, and verify it for an
+// AIR which pins a region of 4096 output cells via sequence assertions. Value polynomials of
+// such assertions are interpolated, evaluated over the constraint evaluation domain by the
+// prover, and evaluated at the out-of-domain point by the verifier; with `concurrent` feature
+// enabled, all of these steps are done in multiple threads. To see the speedup, run this
+// benchmark with and without `--features concurrent`.
+
+const TRACE_LENGTHS: [usize; 2] = [1024, 16_384];
+
+/// Number of columns holding the outputs of the computation.
+const NUM_OUTPUT_COLUMNS: usize = 4;
+
+/// Number of sequence assertions placed against each output column; assertions against the same
+/// column start at consecutive steps, and thus, each of them gets its own divisor.
+const NUM_PHASES: usize = 4;
+
+/// Number of values asserted by a single sequence assertion.
+const SEQUENCE_LENGTH: usize = 256;
+
+type Blake3 = Blake3_256<BaseElement>;
+
+fn boundary_constraints(c: &mut Criterion) {
+    let mut group = c.benchmark_group("boundary_constraints");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256);
+    for &trace_length in TRACE_LENGTHS.iter() {
+        let pub_inputs = PublicInputs::new(trace_length);
+        let air = OutputsAir::new(
+            TraceInfo::new(TRACE_WIDTH, trace_length),
+            pub_inputs,
+            options.clone(),
+        );
+        let coefficients =
+            vec![(BaseElement::ONE, BaseElement::ONE); air.context().num_assertions()];
+
+        group.bench_function(BenchmarkId::from_parameter(trace_length), |bench| {
+            bench
+                .iter(|| air.get_boundary_constraints(&AuxTraceRandElements::new(), &coefficients));
+        });
+    }
+    group.finish();
+}
+
+fn prove_outputs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prove_outputs");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256);
+    for &trace_length in TRACE_LENGTHS.iter() {
+        let prover = OutputsProver::new(options.clone());
+        group.bench_function(BenchmarkId::from_parameter(trace_length), |bench| {
+            bench.iter_batched(
+                || OutputsProver::build_trace(trace_length),
+                |trace| prover.prove(trace).unwrap(),
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn verify_outputs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_outputs");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256);
+    for &trace_length in TRACE_LENGTHS.iter() {
+        let pub_inputs = PublicInputs::new(trace_length);
+        let prover = OutputsProver::new(options.clone());
+        let proof = prover
+            .prove(OutputsProver::build_trace(trace_length))
+            .expect("failed to generate a proof");
+        winterfell::verify::<OutputsAir, Blake3>(proof.clone(), pub_inputs.clone())
+            .expect("invalid proof");
+
+        group.bench_function(BenchmarkId::from_parameter(trace_length), |bench| {
+            bench.iter(|| {
+                winterfell::verify::<OutputsAir, Blake3>(proof.clone(), pub_inputs.clone())
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    boundary_group,
+    boundary_constraints,
+    prove_outputs,
+    verify_outputs
+);
+criterion_main!(boundary_group);
+
+// OUTPUTS AIR
+// ================================================================================================
+
+/// Width of the execution trace: a step counter followed by the output columns.
+const TRACE_WIDTH: usize = NUM_OUTPUT_COLUMNS + 1;
+
+/// Values of the output region; values asserted against output column `j` starting at step `r`
+/// are stored at index `j * NUM_PHASES + r`.
+#[derive(Clone)]
+struct PublicInputs {
+    outputs: Vec<Vec<BaseElement>>,
+}
+
+impl PublicInputs {
+    /// Returns the values of the output region for a trace of the specified length.
+    fn new(trace_length: usize) -> Self {
+        let stride = trace_length / SEQUENCE_LENGTH;
+        let mut outputs = Vec::with_capacity(NUM_OUTPUT_COLUMNS * NUM_PHASES);
+        for column in 0..NUM_OUTPUT_COLUMNS {
+            for phase in 0..NUM_PHASES {
+                let values = (0..SEQUENCE_LENGTH)
+                    .map(|i| output_at(column, phase + i * stride))
+                    .collect();
+                outputs.push(values);
+            }
+        }
+        PublicInputs { outputs }
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        for values in self.outputs.iter() {
+            target.write(values.as_slice());
+        }
+    }
+}
+
+/// A step counter together with columns in which column `j` holds $(i + j)^2$ at step $i$.
+struct OutputsAir {
+    context: AirContext<BaseElement>,
+    outputs: Vec<Vec<BaseElement>>,
+}
+
+impl Air for OutputsAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let degrees = vec![TransitionConstraintDegree::new(1); TRACE_WIDTH];
+        let num_assertions = pub_inputs.outputs.len() + 1;
+        OutputsAir {
+            context: AirContext::new(trace_info, degrees, num_assertions, options),
+            outputs: pub_inputs.outputs,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        result[0] = next[0] - current[0] - E::ONE;
+        // (i + j + 1)^2 = (i + j)^2 + 2 * (i + j) + 1
+        for j in 0..NUM_OUTPUT_COLUMNS {
+            let offset = E::from(j as u32);
+            result[j + 1] = next[j + 1] - current[j + 1] - (current[0] + offset).double() - E::ONE;
+        }
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let stride = self.trace_length() / SEQUENCE_LENGTH;
+        let mut result = vec![Assertion::single(0, 0, BaseElement::ZERO)];
+        for (i, values) in self.outputs.iter().enumerate() {
+            let (column, phase) = (i / NUM_PHASES, i % NUM_PHASES);
+            result.push(Assertion::sequence(
+                column + 1,
+                phase,
+                stride,
+                values.clone(),
+            ));
+        }
+        result
+    }
+}
+
+// OUTPUTS PROVER
+// ================================================================================================
+
+struct OutputsProver {
+    options: ProofOptions,
+}
+
+impl OutputsProver {
+    fn new(options: ProofOptions) -> Self {
+        Self { options }
+    }
+
+    fn build_trace(trace_length: usize) -> TraceTable<BaseElement> {
+        let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
+        trace.fill(
+            |state| {
+                state[0] = BaseElement::ZERO;
+                for j in 0..NUM_OUTPUT_COLUMNS {
+                    state[j + 1] = output_at(j, 0);
+                }
+            },
+            |step, state| {
+                state[0] += BaseElement::ONE;
+                for j in 0..NUM_OUTPUT_COLUMNS {
+                    state[j + 1] = output_at(j, step + 1);
+                }
+            },
+        );
+        trace
+    }
+}
+
+impl Prover for OutputsProver {
+    type BaseField = BaseElement;
+    type Air = OutputsAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        PublicInputs::new(trace.length())
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+/// Returns the value of output column `column` at the specified step.
+fn output_at(column: usize, step: usize) -> BaseElement {
+    BaseElement::new((step + column) as u128).square()
+}
@@ -7,7 +7,9 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use examples::{fibonacci, Example};
 use std::time::Duration;
 use winterfell::{
-    crypto::hashers::Blake3_256, math::fields::f128::BaseElement, FieldExtension, ProofOptions,
+    crypto::hashers::Blake3_256,
+    math::fields::{f128::BaseElement, f64},
+    FieldExtension, ProofOptions,
 };
 
 const SIZES: [usize; 3] = [16_384, 65_536, 262_144];
@@ -29,5 +31,30 @@ fn fibonacci(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(fibonacci_group, fibonacci);
+fn fibonacci_small(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci_small");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    // in a 64-bit field, extension field arithmetic is used in all prover phases which follow
+    // the trace commitment
+    for (name, extension) in [
+        ("quadratic", FieldExtension::Quadratic),
+        ("cubic", FieldExtension::Cubic),
+    ] {
+        let options = ProofOptions::new(32, 8, 0, extension, 4, 256);
+        for &size in SIZES.iter() {
+            let fib = fibonacci::fib_small::FibExample::<Blake3_256<f64::BaseElement>>::new(
+                size,
+                options.clone(),
+            );
+            group.bench_function(BenchmarkId::new(name, size), |bench| {
+                bench.iter(|| fib.prove());
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(fibonacci_group, fibonacci, fibonacci_small);
 criterion_main!(fibonacci_group);
@@ -0,0 +1,208 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+use winterfell::{
+    crypto::hashers::Blake3_256,
+    math::{fields::f128::BaseElement, FieldElement},
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, FieldExtension, ProofOptions, Prover,
+    Serializable, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+// Measures the time needed to verify a proof for an AIR with many assertions; for such AIRs,
+// evaluating boundary constraints at the out-of-domain point makes up a large portion of the
+// verification time which is not spent on hashing. Also compares verifying a proof with the hash
+// function specified statically against selecting it at runtime via `verify_auto()`, and
+// compares proof sizes and verification times for trace and constraint commitments built with
+// Merkle trees of different arity.
+
+const TRACE_LENGTH: usize = 1024;
+const NUM_ASSERTIONS: [usize; 2] = [14, 200];
+
+type Blake3 = Blake3_256<BaseElement>;
+
+fn verify_assertions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_assertions");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256);
+    for &num_assertions in NUM_ASSERTIONS.iter() {
+        let pub_inputs = PublicInputs { num_assertions };
+        let prover = CounterProver::new(options.clone(), pub_inputs);
+        let proof = prover
+            .prove(CounterProver::build_trace(TRACE_LENGTH))
+            .expect("failed to generate a proof");
+        winterfell::verify::<CounterAir, Blake3>(proof.clone(), pub_inputs).expect("invalid proof");
+
+        group.bench_function(BenchmarkId::from_parameter(num_assertions), |bench| {
+            bench.iter(|| winterfell::verify::<CounterAir, Blake3>(proof.clone(), pub_inputs));
+        });
+    }
+    group.finish();
+}
+
+fn verify_dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_dispatch");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256);
+    let pub_inputs = PublicInputs { num_assertions: 14 };
+    let prover = CounterProver::new(options, pub_inputs);
+    let proof = prover
+        .prove(CounterProver::build_trace(TRACE_LENGTH))
+        .expect("failed to generate a proof");
+    winterfell::verify_auto::<CounterAir>(proof.clone(), pub_inputs).expect("invalid proof");
+
+    group.bench_function("static", |bench| {
+        bench.iter(|| winterfell::verify::<CounterAir, Blake3>(proof.clone(), pub_inputs));
+    });
+    group.bench_function("auto", |bench| {
+        bench.iter(|| winterfell::verify_auto::<CounterAir>(proof.clone(), pub_inputs));
+    });
+    group.finish();
+}
+
+fn verify_merkle_arity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_merkle_arity");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    let pub_inputs = PublicInputs { num_assertions: 14 };
+    for arity in [2, 4, 8] {
+        let options =
+            ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256).with_merkle_arity(arity);
+        let prover = CounterProver::new(options, pub_inputs);
+        let proof = prover
+            .prove(CounterProver::build_trace(TRACE_LENGTH))
+            .expect("failed to generate a proof");
+        winterfell::verify::<CounterAir, Blake3>(proof.clone(), pub_inputs).expect("invalid proof");
+
+        // FRI layers are always committed to with binary trees, and thus, only the sizes of trace
+        // and constraint queries depend on the arity
+        let breakdown = proof.size_breakdown();
+        println!(
+            "arity {arity}: proof size is {} B (trace queries {} B, constraint queries {} B)",
+            breakdown.total(),
+            breakdown.trace_queries(),
+            breakdown.constraint_queries()
+        );
+
+        group.bench_function(BenchmarkId::from_parameter(arity), |bench| {
+            bench.iter(|| winterfell::verify::<CounterAir, Blake3>(proof.clone(), pub_inputs));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    verifier_group,
+    verify_assertions,
+    verify_dispatch,
+    verify_merkle_arity
+);
+criterion_main!(verifier_group);
+
+// COUNTER AIR
+// ================================================================================================
+
+/// Specifies how many steps of the trace are asserted to contain their own index.
+#[derive(Clone, Copy)]
+struct PublicInputs {
+    num_assertions: usize,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.num_assertions as u32);
+    }
+}
+
+/// A single column incremented by one at every step.
+struct CounterAir {
+    context: AirContext<BaseElement>,
+    num_assertions: usize,
+}
+
+impl Air for CounterAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let num_assertions = pub_inputs.num_assertions;
+        let degrees = vec![TransitionConstraintDegree::new(1)];
+        CounterAir {
+            context: AirContext::new(trace_info, degrees, num_assertions, options),
+            num_assertions,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        result[0] = frame.next()[0] - frame.current()[0] - E::ONE;
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        // spread the asserted steps over the whole trace
+        let stride = self.trace_length() / self.num_assertions;
+        (0..self.num_assertions)
+            .map(|i| {
+                let step = i * stride;
+                Assertion::single(0, step, BaseElement::new(step as u128))
+            })
+            .collect()
+    }
+}
+
+// COUNTER PROVER
+// ================================================================================================
+
+struct CounterProver {
+    options: ProofOptions,
+    pub_inputs: PublicInputs,
+}
+
+impl CounterProver {
+    fn new(options: ProofOptions, pub_inputs: PublicInputs) -> Self {
+        Self {
+            options,
+            pub_inputs,
+        }
+    }
+
+    fn build_trace(trace_length: usize) -> TraceTable<BaseElement> {
+        let mut trace = TraceTable::new(1, trace_length);
+        trace.fill(
+            |state| state[0] = BaseElement::ZERO,
+            |_, state| state[0] += BaseElement::ONE,
+        );
+        trace
+    }
+}
+
+impl Prover for CounterProver {
+    type BaseField = BaseElement;
+    type Air = CounterAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3;
+
+    fn get_pub_inputs(&self, _trace: &Self::Trace) -> PublicInputs {
+        self.pub_inputs
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
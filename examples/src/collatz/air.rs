@@ -1,147 +1,252 @@
-use std::vec;
-use byteorder::{LittleEndian, ByteOrder};
-
-use core_utils::{Serializable, AsBytes};
-use winterfell::{Air, AirContext, EvaluationFrame, TransitionConstraintDegree, Assertion};
-use core_utils::ByteWriter;
-use crate::utils::{is_binary, are_equal};
-
-use log::debug;
-use super::{
-    TRACE_WIDTH, TraceInfo, ProofOptions, FieldElement, BaseElement, prover,
-};
-
-pub struct PublicInputs {
-    pub initial_num: BaseElement,
-    pub step: BaseElement,
-}
-
-impl Serializable for PublicInputs {
-    fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write(self.initial_num);
-    }
-}
-
-pub struct CollatzAir {
-    context: AirContext<BaseElement>,
-    initial_num: BaseElement,
-    step: BaseElement,
-}
-
-impl Air for CollatzAir {
-    type BaseField = BaseElement;
-    type PublicInputs = PublicInputs;
-
-    // CONSTRUCTOR
-    // --------------------------------------------------------------------------------------------------------
-    fn new(trace_info: TraceInfo, pub_inputs: Self::PublicInputs, options: ProofOptions) -> Self {
-        let degrees: Vec<TransitionConstraintDegree> = vec![
-            TransitionConstraintDegree::new(2),
-            TransitionConstraintDegree::new(2),
-        ];
-        assert_eq!(TRACE_WIDTH, trace_info.width());
-
-        let step = LittleEndian::read_u128(pub_inputs.step.as_bytes()) as usize;
-        let pad_length = prover::ceil_to_power_of_two(step) - (step + 1) + 2;
-        
-        println!("exempt last {} rows", pad_length);
-
-        let context = AirContext::new(trace_info, degrees, 14, options).set_num_transition_exemptions(pad_length);
-
-        CollatzAir { 
-            context, 
-            initial_num: pub_inputs.initial_num,
-            step: pub_inputs.step,
-        }
-    }
-
-    fn context(&self) -> &AirContext<Self::BaseField> {
-        &self.context
-    }
-
-    fn evaluate_transition<E: FieldElement<BaseField = Self::BaseField>>(
-        &self,
-        frame: &EvaluationFrame<E>,
-        _: &[E],
-        result: &mut [E],
-    ) {
-        let current = frame.current();
-        let next = frame.next();
-
-        debug_assert_eq!(TRACE_WIDTH, current.len());
-        debug_assert_eq!(TRACE_WIDTH, next.len());
-
-        // enforce that values in all register must be binary
-        for i in 0..(TRACE_WIDTH-1) {
-            result[0] += is_binary(current[i]);
-        }
-
-        // enforce that each step follow collatz sequence rule
-        let current_num = num_from_state(current);
-        let next_num = num_from_state(next);
-        result[1]  = current[0] * are_equal(current_num * E::from(3 as u32) + E::ONE, next_num);
-        result[1] += (current[0] - E::ONE) * are_equal(current_num, E::from(2 as u32) * next_num);
-        println!("evaluate transition {} to {}, constraint result {:?}", current_num, next_num, result);
-    }
-
-    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
-        debug!(
-            "set assertion for initial number {:?}",
-            self.initial_num,
-        );
-
-        let n = self.initial_num.as_bytes();
-        let mut n = n[0] as u32;
-        let mut bit_vec: Vec<Self::BaseField> = Vec::new();
-        for _ in 0..TRACE_WIDTH {
-            if n & 1 == 1 {
-                bit_vec.push(Self::BaseField::ONE);
-            } else {
-                bit_vec.push(Self::BaseField::ZERO);
-            }
-            n >>= 1;
-        }
-
-        let step = u128_from_field(&self.step) as usize;
-
-        // BOUNDARY CONSTRAINT
-        vec![
-            // enforce the first row is our input initial number
-            Assertion::single(0, 0, bit_vec[0]),
-            Assertion::single(1, 0, bit_vec[1]),
-            Assertion::single(2, 0, bit_vec[2]),
-            Assertion::single(3, 0, bit_vec[3]),
-            Assertion::single(4, 0, bit_vec[4]),
-            Assertion::single(5, 0, bit_vec[5]),
-
-            // enfore the step register in first row is 0
-            Assertion::single(6, 0, Self::BaseField::ZERO),
-
-            // enforce the last step row is one
-            Assertion::single(0, step, Self::BaseField::ONE),
-            Assertion::single(1, step, Self::BaseField::ZERO),
-            Assertion::single(2, step, Self::BaseField::ZERO),
-            Assertion::single(3, step, Self::BaseField::ZERO),
-            Assertion::single(4, step, Self::BaseField::ZERO),
-            Assertion::single(5, step, Self::BaseField::ZERO),
-
-            // enforce the step register in last row is step
-            Assertion::single(6, step, self.step)
-        ]
-    }
-}
-
-
-fn num_from_state<E: FieldElement<BaseField = BaseElement>>(state: &[E]) -> E {
-    let mut n: E = E::ONE;
-    for i in (0..(TRACE_WIDTH-1)).rev() {
-        n *= E::from(2 as u32);
-        n += state[i];
-    }
-    n -= E::from((1 << (TRACE_WIDTH-1)) as u32);
-    n
-}
-
-fn u128_from_field<E: FieldElement<BaseField = BaseElement>>(n: &E) -> u128 {
-    LittleEndian::read_uint128(n.as_bytes(), 16)
-}
+use crate::utils::{are_equal, is_binary};
+use core_utils::{ByteReader, ByteWriter};
+use core_utils::{Deserializable, DeserializationError, Serializable};
+use std::vec;
+use winterfell::{
+    Air, AirContext, Assertion, AssertionError, AssertionStep, EvaluationFrame,
+    TransitionConstraintDegree,
+};
+
+use super::{ExtensibleField, FieldElement, ProofOptions, StarkField, TraceInfo};
+use log::debug;
+
+/// Public inputs of the Collatz AIR: an execution trace may contain several sequences placed
+/// one after another, and for every sequence, the inputs specify its initial number and the
+/// number of steps it takes the sequence to reach one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicInputs<B: StarkField> {
+    pub sequences: Vec<(B, B)>,
+    pub trace_width: usize,
+}
+
+impl<B: StarkField> Serializable for PublicInputs<B> {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        assert!(
+            self.sequences.len() <= u16::MAX as usize,
+            "number of sequences cannot exceed {}, but was {}",
+            u16::MAX,
+            self.sequences.len()
+        );
+        assert!(
+            self.trace_width <= u8::MAX as usize,
+            "trace width cannot exceed {}, but was {}",
+            u8::MAX,
+            self.trace_width
+        );
+        target.write_u16(self.sequences.len() as u16);
+        for &(initial_num, step) in self.sequences.iter() {
+            target.write(initial_num);
+            target.write(step);
+        }
+        target.write_u8(self.trace_width as u8);
+    }
+}
+
+impl<B: StarkField> Deserializable for PublicInputs<B> {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_sequences = source.read_u16()? as usize;
+        let mut sequences = Vec::with_capacity(num_sequences);
+        for _ in 0..num_sequences {
+            let initial_num = B::read_from(source)?;
+            let step = B::read_from(source)?;
+            sequences.push((initial_num, step));
+        }
+        let trace_width = source.read_u8()? as usize;
+        Ok(PublicInputs {
+            sequences,
+            trace_width,
+        })
+    }
+}
+
+/// AIR for Collatz sequences placed into consecutive segments of an execution trace.
+///
+/// All but the last two columns of the trace hold the binary decomposition of the current value
+/// of a sequence. The next to last column is a selector which is set to ONE in every row of a
+/// segment except for its last row, and the last column counts steps of the current sequence.
+/// The Collatz rule is enforced only when the selector is set, and thus, the row which ends one
+/// segment can be followed by the first row of the next segment. When the selector is not set,
+/// the step counter must be reset to ZERO; since the counter is asserted to reach the number of
+/// steps of every sequence at the end of its segment, the selector cannot be cleared within a
+/// segment.
+pub struct CollatzAir<B: StarkField> {
+    context: AirContext<B>,
+    sequences: Vec<(B, B)>,
+}
+
+impl<B> Air for CollatzAir<B>
+where
+    B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    type BaseField = B;
+    type PublicInputs = PublicInputs<B>;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: Self::PublicInputs, options: ProofOptions) -> Self {
+        let degrees: Vec<TransitionConstraintDegree> = vec![
+            TransitionConstraintDegree::new(2),
+            TransitionConstraintDegree::new(3),
+            TransitionConstraintDegree::new(2),
+            TransitionConstraintDegree::new(2),
+        ];
+        // the width of the trace depends on the largest value in the sequences, and thus, it is
+        // determined by the prover and passed to the verifier via public inputs
+        assert_eq!(pub_inputs.trace_width, trace_info.width());
+        assert!(
+            !pub_inputs.sequences.is_empty(),
+            "at least one sequence must be specified"
+        );
+
+        // bits and the step counter are asserted in the first and the last rows of every segment
+        let num_assertions = 2 * (trace_info.width() - 1) * pub_inputs.sequences.len();
+        debug!(
+            "set assertions for {} sequences",
+            pub_inputs.sequences.len()
+        );
+
+        let context = AirContext::new(trace_info, degrees, num_assertions, options);
+
+        CollatzAir {
+            context,
+            sequences: pub_inputs.sequences,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        let width = self.trace_info().width();
+        debug_assert_eq!(width, current.len());
+        debug_assert_eq!(width, next.len());
+        let selector = current[width - 2];
+        let step = current[width - 1];
+
+        // enforce that values in all bit registers must be binary
+        for &bit in current.iter().take(width - 2) {
+            result[0] += is_binary(bit);
+        }
+
+        // enforce that each step within a segment follow collatz sequence rule
+        let current_num = num_from_state(current);
+        let next_num = num_from_state(next);
+        result[1] = current[0] * are_equal(current_num * E::from(3_u32) + E::ONE, next_num);
+        result[1] += (current[0] - E::ONE) * are_equal(current_num, E::from(2_u32) * next_num);
+        result[1] *= selector;
+
+        // enforce that the step counter is incremented within a segment and reset between them
+        result[2] = are_equal(next[width - 1], selector * (step + E::ONE));
+
+        // enforce that the selector is binary
+        result[3] = is_binary(selector);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let width = self.trace_info().width();
+        let mut assertions = Vec::with_capacity(2 * (width - 1) * self.sequences.len());
+
+        // BOUNDARY CONSTRAINTS
+        // segments which do not fit into the trace are rejected by validate_assertions()
+        let segments = self.get_segments().unwrap_or_default();
+        for (&(initial_num, step), (start, end)) in self.sequences.iter().zip(segments) {
+            debug!(
+                "set assertion for initial number {:?} at row {}",
+                initial_num, start
+            );
+
+            // enforce the first row of the segment is our input initial number
+            let mut n = as_u128(initial_num);
+            for column in 0..width - 2 {
+                assertions.push(Assertion::single(
+                    column,
+                    start,
+                    Self::BaseField::from(n & 1),
+                ));
+                n >>= 1;
+            }
+
+            // enfore the step register in first row of the segment is 0
+            assertions.push(Assertion::single(width - 1, start, Self::BaseField::ZERO));
+
+            // enforce the last row of the segment is one
+            assertions.push(Assertion::single(0, end, Self::BaseField::ONE));
+            for column in 1..width - 2 {
+                assertions.push(Assertion::single(column, end, Self::BaseField::ZERO));
+            }
+
+            // enforce the step register in last row of the segment is step
+            assertions.push(Assertion::single(width - 1, end, step));
+        }
+        assertions
+    }
+
+    fn validate_assertions(&self) -> Result<(), AssertionError> {
+        // steps are read from public inputs, and thus, the segments must be checked to fit into
+        // the trace before any assertions are placed against them
+        self.get_segments()?;
+        let (width, length) = (self.trace_info().width(), self.trace_length());
+        self.get_assertions()
+            .iter()
+            .try_for_each(|assertion| assertion.validate(width, length))
+    }
+}
+
+impl<B> CollatzAir<B>
+where
+    B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    /// Returns the first and the last rows of the segment of every sequence; a sequence of `step`
+    /// steps occupies `step + 1` rows starting right after the segment of the previous sequence.
+    ///
+    /// # Errors
+    /// Returns an error if a segment does not fit into the execution trace.
+    fn get_segments(&self) -> Result<Vec<(usize, usize)>, AssertionError> {
+        let trace_length = self.trace_length();
+        let mut start = AssertionStep::new(0);
+        let mut segments = Vec::with_capacity(self.sequences.len());
+        for &(_, step) in self.sequences.iter() {
+            let end = start.plus(as_u128(step));
+            segments.push((
+                start.clamped_or_error(trace_length)?,
+                end.clamped_or_error(trace_length)?,
+            ));
+            start = end.plus(1);
+        }
+        Ok(segments)
+    }
+}
+
+/// Composes a number from its binary decomposition in all but the last two columns of the state.
+pub fn num_from_state<E: FieldElement>(state: &[E]) -> E {
+    let mut n = E::ZERO;
+    for &bit in state[..state.len() - 2].iter().rev() {
+        n = n.double() + bit;
+    }
+    n
+}
+
+/// Returns the canonical integer representation of the specified element; moduli of all fields
+/// the example can be defined over are smaller than 2^128, and thus, the representation always
+/// fits into `u128`.
+fn as_u128<B: StarkField>(element: B) -> u128 {
+    let one = B::PositiveInteger::from(1u32);
+    let mut value = element.as_int();
+    let mut result = 0;
+    for i in 0..B::MODULUS_BITS {
+        if value & one == one {
+            result |= 1 << i;
+        }
+        value = value >> 1;
+    }
+    result
+}
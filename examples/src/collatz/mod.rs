@@ -1,128 +1,274 @@
-use winterfell::{
-    crypto::ElementHasher,
-    math::{fields::f128::BaseElement, log2, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError, TraceInfo
-};
-use crate::{
-    Blake3_192, Blake3_256, Sha3_256, HashFunction, Example, ExampleOptions,
-};
-
-use log::debug;
-use std::time::Instant;
-use core::marker::PhantomData;
-
-mod prover;
-use prover::CollatzProver;
-
-mod air;
-use air::{CollatzAir, PublicInputs};
-
-// CONSTANTS
-// ================================================================================================
-const TRACE_WIDTH: usize = 7;
-
-// COLLATZ PATH EXAMPLE
-// ================================================================================================
-pub fn get_example(
-    options: &ExampleOptions,
-    initial_number: usize,
-) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(28, 8);
-
-    match hash_fn {
-        HashFunction::Blake3_192 => Ok(Box::new(CollatzExample::<Blake3_192>::new(
-            initial_number, options,
-        ))),
-        HashFunction::Blake3_256 => Ok(Box::new(CollatzExample::<Blake3_256>::new(
-            initial_number, options,
-        ))),
-        HashFunction::Sha3_256 => Ok(Box::new(CollatzExample::<Sha3_256>::new(
-            initial_number, options,
-        ))),
-        _ => Err("The specified hash function cannot be used with this example.".to_string()),
-    }
-}
-
-pub struct CollatzExample<H: ElementHasher> {
-    options: ProofOptions,
-    initial_number: usize,
-    step: usize,
-    _hasher: PhantomData<H>,
-}
-
-impl<H: ElementHasher> CollatzExample<H> {
-    pub fn new(initial_number: usize, options: ProofOptions) -> Self {
-        assert!(
-            initial_number < 100,
-            "initial number must be less than 100"
-        );
-        let now: Instant = Instant::now();
-        let step: usize = compute_collatz(initial_number);
-        debug!(
-            "comput collatz sequence from {} using step {} in {} ms",
-            initial_number,
-            step,
-            now.elapsed().as_millis(),
-        );
-
-        CollatzExample {
-            options,
-            initial_number,
-            step,
-            _hasher: PhantomData,
-        }
-    }
-}
-
-impl<H: ElementHasher> Example for CollatzExample<H>
-where
-    H: ElementHasher<BaseField = BaseElement>,
-{
-    fn prove(&self) -> StarkProof {
-        let prover: CollatzProver<H> = CollatzProver::<H>::new(self.options.clone(), self.step);
-
-        // generate the execution trace
-        let now: Instant = Instant::now();
-        let trace: TraceTable<BaseElement> = prover.build_trace(self.initial_number, self.step);
-        let trace_length: usize = trace.length();
-
-        debug!(
-            "Generated execution trace of {} registers and 2^{} steps in {}ms",
-            trace.width(),
-            log2(trace_length),
-            now.elapsed().as_millis()
-        );
-
-        // generate the proof
-        prover.prove(trace).unwrap()
-    }
-
-    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs: PublicInputs = PublicInputs {
-            initial_num: BaseElement::new(self.initial_number as u128),
-            step: BaseElement::new(self.step as u128),
-        };
-        winterfell::verify::<CollatzAir, H>(proof, pub_inputs)
-    }
-
-    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs: PublicInputs = PublicInputs {
-            initial_num: BaseElement::new(self.initial_number as u128),
-            step: BaseElement::new((self.step + 1) as u128),
-        };
-        winterfell::verify::<CollatzAir, H>(proof, pub_inputs)
-    }
-}
-
-fn compute_collatz(initial_number: usize) -> usize {
-    let (mut n, mut step) = (initial_number, 0);
-    while n > 1 {
-        if n & 1 == 1 {
-            n = 3 * n + 1;
-        } else {
-            n >>= 1;
-        }
-        step += 1;
-    }
-    step
-}
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Example, ExampleDefaults, ExampleOptions, FieldType, HashFunction,
+};
+use winterfell::{
+    crypto::{
+        hashers::{
+            Blake2s_256, Blake3_192, Blake3_256, GriffinJive64_256, Keccak_256, Poseidon128_256,
+            Poseidon64_256, Rp64_256, RpJive64_256, Sha3_256,
+        },
+        ElementHasher,
+    },
+    math::{
+        fields::{f128, f62, f64},
+        log2, ExtensibleField, FieldElement, StarkField,
+    },
+    Air, AirDescription, DegeneracyThresholds, FillStatus, ProofOptions, Prover, StarkProof, Trace,
+    TraceBuildError, TraceError, TraceInfo, TraceTable, VerifierError,
+};
+
+use core::marker::PhantomData;
+use log::debug;
+use std::time::Instant;
+
+mod prover;
+use prover::{get_trace_length, CollatzProver};
+
+mod air;
+use air::{CollatzAir, PublicInputs};
+
+#[cfg(test)]
+mod tests;
+
+// COLLATZ PATH EXAMPLE
+// ================================================================================================
+
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(
+    options: &ExampleOptions,
+    initial_numbers: &[usize],
+) -> Result<Box<dyn Example>, String> {
+    if initial_numbers.is_empty() {
+        return Err("At least one initial number must be specified.".to_string());
+    }
+    if initial_numbers.len() > u16::MAX as usize {
+        return Err(format!(
+            "At most {} initial numbers can be specified, but was {}.",
+            u16::MAX,
+            initial_numbers.len()
+        ));
+    }
+    if let Some(n) = initial_numbers.iter().find(|&&n| n < 2) {
+        return Err(format!(
+            "Initial numbers must be greater than one, but was {n}."
+        ));
+    }
+    let initial_numbers = initial_numbers.to_vec();
+    let field = options.field(FieldType::F128);
+    let (options, hash_fn) = options.to_proof_options(field, &DEFAULTS);
+
+    // Rescue Prime and Griffin hash functions are defined only over the 64-bit field, Poseidon is
+    // defined over the 64-bit and 128-bit fields
+    match (field, hash_fn) {
+        (FieldType::F62, _) => {
+            get_field_example::<f62::BaseElement>(hash_fn, initial_numbers, options)
+        }
+        (FieldType::F64, HashFunction::Rp64_256) => Ok(Box::new(
+            CollatzExample::<Rp64_256>::with_initial_numbers(initial_numbers, options),
+        )),
+        (FieldType::F64, HashFunction::RpJive64_256) => Ok(Box::new(
+            CollatzExample::<RpJive64_256>::with_initial_numbers(initial_numbers, options),
+        )),
+        (FieldType::F64, HashFunction::GriffinJive64_256) => {
+            Ok(Box::new(
+                CollatzExample::<GriffinJive64_256>::with_initial_numbers(initial_numbers, options),
+            ))
+        }
+        (FieldType::F64, HashFunction::Poseidon) => Ok(Box::new(
+            CollatzExample::<Poseidon64_256>::with_initial_numbers(initial_numbers, options),
+        )),
+        (FieldType::F64, _) => {
+            get_field_example::<f64::BaseElement>(hash_fn, initial_numbers, options)
+        }
+        (FieldType::F128, HashFunction::Poseidon) => Ok(Box::new(
+            CollatzExample::<Poseidon128_256>::with_initial_numbers(initial_numbers, options),
+        )),
+        (FieldType::F128, _) => {
+            get_field_example::<f128::BaseElement>(hash_fn, initial_numbers, options)
+        }
+    }
+}
+
+/// Instantiates the example over the field `B` using the specified hash function.
+fn get_field_example<B>(
+    hash_fn: HashFunction,
+    initial_numbers: Vec<usize>,
+    options: ProofOptions,
+) -> Result<Box<dyn Example>, String>
+where
+    B: StarkField + ExtensibleField<2> + ExtensibleField<3> + 'static,
+{
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(
+            CollatzExample::<Blake3_192<B>>::with_initial_numbers(initial_numbers, options),
+        )),
+        HashFunction::Blake3_256 => Ok(Box::new(
+            CollatzExample::<Blake3_256<B>>::with_initial_numbers(initial_numbers, options),
+        )),
+        HashFunction::Sha3_256 => Ok(Box::new(
+            CollatzExample::<Sha3_256<B>>::with_initial_numbers(initial_numbers, options),
+        )),
+        HashFunction::Keccak256 => Ok(Box::new(
+            CollatzExample::<Keccak_256<B>>::with_initial_numbers(initial_numbers, options),
+        )),
+        HashFunction::Blake2s256 => Ok(Box::new(
+            CollatzExample::<Blake2s_256<B>>::with_initial_numbers(initial_numbers, options),
+        )),
+        _ => {
+            Err("The specified hash function cannot be used with the specified field.".to_string())
+        }
+    }
+}
+
+pub struct CollatzExample<H: ElementHasher> {
+    options: ProofOptions,
+    initial_numbers: Vec<usize>,
+    steps: Vec<usize>,
+    trace_width: usize,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> CollatzExample<H> {
+    pub fn new(initial_number: usize, options: ProofOptions) -> Self {
+        Self::with_initial_numbers(vec![initial_number], options)
+    }
+
+    /// Returns an example which proves sequences starting at all of the specified numbers in a
+    /// single execution trace.
+    pub fn with_initial_numbers(initial_numbers: Vec<usize>, options: ProofOptions) -> Self {
+        let now: Instant = Instant::now();
+        let mut steps = Vec::with_capacity(initial_numbers.len());
+        let mut max_value = 1;
+        for &initial_number in initial_numbers.iter() {
+            let (step, value) = compute_collatz(initial_number);
+            debug!(
+                "comput collatz sequence from {} using step {} and reaching {}",
+                initial_number, step, value,
+            );
+            steps.push(step);
+            max_value = max_value.max(value);
+        }
+        let trace_width = get_trace_width(max_value);
+        debug!(
+            "computed {} collatz sequences in {} ms",
+            initial_numbers.len(),
+            now.elapsed().as_millis(),
+        );
+
+        CollatzExample {
+            options,
+            initial_numbers,
+            steps,
+            trace_width,
+            _hasher: PhantomData,
+        }
+    }
+
+    fn pub_inputs(&self) -> PublicInputs<H::BaseField> {
+        let sequences = self
+            .initial_numbers
+            .iter()
+            .zip(self.steps.iter())
+            .map(|(&n, &step)| {
+                (
+                    H::BaseField::from(n as u64),
+                    H::BaseField::from(step as u64),
+                )
+            })
+            .collect();
+        PublicInputs {
+            sequences,
+            trace_width: self.trace_width,
+        }
+    }
+}
+
+impl<H: ElementHasher> Example for CollatzExample<H>
+where
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    fn prove(&self) -> StarkProof {
+        let prover: CollatzProver<H> =
+            CollatzProver::<H>::new(self.options.clone(), self.steps.clone());
+
+        // generate the execution trace
+        let now: Instant = Instant::now();
+        let trace: TraceTable<H::BaseField> = prover.build_trace(&self.initial_numbers).unwrap();
+        let trace_length: usize = trace.length();
+
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {}ms",
+            trace.width(),
+            log2(trace_length),
+            now.elapsed().as_millis()
+        );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement = winterfell::verify_and_extract::<CollatzAir<H::BaseField>, H>(
+            proof,
+            self.pub_inputs(),
+        )?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        // claim that the first sequence takes one more step to reach one
+        let mut pub_inputs = self.pub_inputs();
+        pub_inputs.sequences[0].1 += H::BaseField::ONE;
+        winterfell::verify::<CollatzAir<H::BaseField>, H>(proof, pub_inputs)
+    }
+
+    fn describe(&self) -> Option<AirDescription> {
+        // the trace is built by the prover using the same rule
+        let trace_length = get_trace_length(&self.steps);
+        let trace_info = TraceInfo::new(self.trace_width, trace_length);
+        Some(CollatzAir::new(trace_info, self.pub_inputs(), self.options.clone()).describe())
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(self.trace_width, get_trace_length(&self.steps));
+        let air = CollatzAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of steps it takes the Collatz sequence starting at `initial_number` to
+/// reach one, together with the largest value reached along the way.
+fn compute_collatz(initial_number: usize) -> (usize, u64) {
+    let (mut n, mut step) = (initial_number as u64, 0);
+    let mut max_value = n;
+    while n > 1 {
+        if n & 1 == 1 {
+            n = n
+                .checked_mul(3)
+                .and_then(|n| n.checked_add(1))
+                .expect("collatz sequence must not exceed 2^64");
+        } else {
+            n >>= 1;
+        }
+        max_value = max_value.max(n);
+        step += 1;
+    }
+    (step, max_value)
+}
+
+/// Returns the width of the execution trace for sequences which reach `max_value`: values are
+/// decomposed into as many bits as needed to represent `max_value`, and the last two columns
+/// hold the segment selector and the step counter.
+fn get_trace_width(max_value: u64) -> usize {
+    (u64::BITS - max_value.leading_zeros()) as usize + 2
+}
@@ -1,132 +1,195 @@
-use std::{marker::PhantomData};
-use core_utils::AsBytes;
-
-use log::debug;
-use super::air::{CollatzAir, PublicInputs};
-use super::{
-    TRACE_WIDTH, ElementHasher, ProofOptions, TraceTable, BaseElement, Prover, FieldElement
-};
-
-pub struct CollatzProver<H: ElementHasher> {
-    options: ProofOptions,
-    step: usize,
-    _hasher: PhantomData<H>,
-}
-
-impl<H: ElementHasher> CollatzProver<H> {
-    pub fn new(options: ProofOptions, step: usize) -> Self {
-        Self { 
-            options,
-            step, 
-            _hasher: PhantomData, 
-        }
-    }
-
-    pub fn build_trace(
-        &self,
-        initial_number: usize,
-        step: usize,
-    ) -> TraceTable<BaseElement> {
-        // Allocate memory to hold the trace table
-        let trace_length: usize;
-        if step.is_power_of_two() == false {
-            trace_length = ceil_to_power_of_two(step);
-        } else {
-            trace_length = step;
-        }
-        
-        assert!(
-            trace_length.is_power_of_two(),
-            "trace table length must be a power of 2"
-        );
-
-        debug!(
-            "allocate trace table of length {}",
-            trace_length,
-        );
-
-        let mut trace: TraceTable<BaseElement> = TraceTable::new(TRACE_WIDTH, trace_length);
-
-        trace.fill(
-            |state: &mut [BaseElement]| {
-                let mut n = initial_number;
-                for i in 0..(TRACE_WIDTH-1) {
-                    state[i] = BaseElement::new((n & 1_usize) as u128);
-                    n >>= 1;
-                }
-            },
-            |row, state: &mut [BaseElement]| {
-                // Compute number from a row of state
-                let mut n = BaseElement::ONE;
-                for i in (0..(TRACE_WIDTH-1)).rev() {
-                    n *= BaseElement::new(2);
-                    n = n + state[i];
-                }
-                n -= BaseElement::new(1 << (TRACE_WIDTH-1));
-
-                println!("row = {}, n = {:?}, update state {:?}", row, n, state);
-
-                // If the number is ONE, then reach pad table, return directly.
-                if n == BaseElement::ONE {
-                    return;
-                }
-
-                // Update State follow collatz sequence
-                if state[0] == BaseElement::ZERO {
-                    n = n / BaseElement::new(2);
-                } else {
-                    n = n * BaseElement::new(3) + BaseElement::ONE;
-                }
-
-                // Not sure how to conver BaseElement to integer
-                let n_bytes = n.as_bytes();
-                let mut n = n_bytes[0] as u32;
-                for i in 0..(TRACE_WIDTH-1) {
-                    state[i] = BaseElement::new((n & 1) as u128);
-                    n >>= 1;              
-                }
-
-                // Update the computation step
-                state[TRACE_WIDTH-1] += BaseElement::ONE;
-            },
-        );
-
-        trace
-    }
-}
-
-impl<H: ElementHasher> Prover for CollatzProver<H>
-where
-    H: ElementHasher<BaseField = BaseElement>
-{
-    type BaseField = BaseElement;
-    type Air = CollatzAir;
-    type Trace = TraceTable<BaseElement>;
-    type HashFn = H;
-
-    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
-        // Trace table is redundant
-        let mut n = BaseElement::ONE;
-        for i in (0..(TRACE_WIDTH-1)).rev() {
-            n *= BaseElement::new(2);
-            n = n + trace.get(i, 0);
-        }
-        n -= BaseElement::new(1 << (TRACE_WIDTH-1));
-
-        PublicInputs { 
-           initial_num: n,
-           step: BaseElement::new(self.step as u128),
-        }
-    }
-
-    fn options(&self) -> &ProofOptions {
-        &self.options
-    }
-}
-
-// HELPER FUNCTION
-// ---------------------------------------------------------------------
-pub fn ceil_to_power_of_two(n: usize) -> usize {
-    let e = (n as f64).log2().ceil() as usize;
-    1 << e
-}
+use std::marker::PhantomData;
+
+use super::air::{num_from_state, CollatzAir, PublicInputs};
+use super::{
+    compute_collatz, get_trace_width, ElementHasher, ExtensibleField, FieldElement, FillStatus,
+    ProofOptions, Prover, StarkField, TraceBuildError, TraceError, TraceTable,
+};
+use crate::utils::get_padded_trace_length;
+use log::debug;
+
+pub struct CollatzProver<H: ElementHasher> {
+    options: ProofOptions,
+    steps: Vec<usize>,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> CollatzProver<H> {
+    /// Returns a prover for sequences which reach one after the specified numbers of steps; the
+    /// sequences are placed into the execution trace in the same order as their steps.
+    pub fn new(options: ProofOptions, steps: Vec<usize>) -> Self {
+        Self {
+            options,
+            steps,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Builds an execution trace in which the sequence starting at every one of the specified
+    /// initial numbers occupies a contiguous segment of rows.
+    ///
+    /// # Errors
+    /// Returns an error if a sequence does not reach one after the number of steps this prover
+    /// was created for, or if a value of a sequence does not fit into the columns of the trace;
+    /// the error contains the index of the row at which the trace could not be built.
+    ///
+    /// # Panics
+    /// Panics if the number of initial numbers is not equal to the number of sequences this
+    /// prover was created for.
+    pub fn build_trace(
+        &self,
+        initial_numbers: &[usize],
+    ) -> Result<TraceTable<H::BaseField>, TraceError> {
+        assert_eq!(
+            self.steps.len(),
+            initial_numbers.len(),
+            "expected {} initial numbers, but was {}",
+            self.steps.len(),
+            initial_numbers.len()
+        );
+
+        // Allocate memory to hold the trace table; the trace must be wide enough to hold the
+        // binary decomposition of the largest value in all sequences
+        let trace_length = get_trace_length(&self.steps);
+        let max_value = initial_numbers
+            .iter()
+            .map(|&n| compute_collatz(n).1)
+            .max()
+            .unwrap_or(1);
+        let width = get_trace_width(max_value);
+        let num_bits = width - 2;
+
+        assert!(
+            trace_length.is_power_of_two(),
+            "trace table length must be a power of 2"
+        );
+
+        debug!(
+            "allocate trace table of length {} and width {}",
+            trace_length, width,
+        );
+
+        let mut trace: TraceTable<H::BaseField> = TraceTable::new(width, trace_length);
+
+        // index of the last row of every segment
+        let segment_ends = self
+            .steps
+            .iter()
+            .scan(0, |start, &step| {
+                let end = *start + step;
+                *start = end + 1;
+                Some(end)
+            })
+            .collect::<Vec<_>>();
+
+        // every row holds the bits of the current value, the selector, and the step counter; the
+        // selector is cleared in the last row of every segment and in all padding rows
+        let compute_row = |row: usize, state: &mut [H::BaseField]| {
+            let segment = segment_ends.partition_point(|&end| end < row);
+            if segment == segment_ends.len() {
+                // pad the table with rows which hold value one and a reset step counter
+                state.fill(H::BaseField::ZERO);
+                state[0] = H::BaseField::ONE;
+                return Ok(FillStatus::Complete);
+            }
+
+            let (initial_number, step) = (initial_numbers[segment], self.steps[segment]);
+            let i = row + step - segment_ends[segment];
+            let n = if i == 0 {
+                Some(initial_number as u64)
+            } else {
+                // Update State follow collatz sequence
+                let n = value_from_bits(&state[..num_bits]);
+                if n & 1 == 0 {
+                    Some(n >> 1)
+                } else {
+                    n.checked_mul(3).and_then(|n| n.checked_add(1))
+                }
+            };
+            let n = match n {
+                Some(n) if u64::BITS - n.leading_zeros() <= num_bits as u32 => n,
+                _ => {
+                    return Err(TraceBuildError::InvalidState(format!(
+                        "sequence starting at {initial_number} exceeds {num_bits} bits at step {i}"
+                    )))
+                }
+            };
+            if i == step && n != 1 {
+                return Err(TraceBuildError::UnexpectedOutput(format!(
+                    "sequence starting at {initial_number} does not reach one after {step} steps"
+                )));
+            }
+
+            let mut bits = n;
+            for bit in state.iter_mut().take(num_bits) {
+                *bit = H::BaseField::from(bits & 1);
+                bits >>= 1;
+            }
+            state[width - 2] = H::BaseField::from((i < step) as u32);
+            state[width - 1] = H::BaseField::from(i as u64);
+            Ok(FillStatus::Continue)
+        };
+
+        trace.try_fill_with(
+            |state| compute_row(0, state).map(|_| ()),
+            |last_row, state| compute_row(last_row + 1, state),
+        )?;
+        Ok(trace)
+    }
+}
+
+impl<H: ElementHasher> Prover for CollatzProver<H>
+where
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    type BaseField = H::BaseField;
+    type Air = CollatzAir<H::BaseField>;
+    type Trace = TraceTable<H::BaseField>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs<H::BaseField> {
+        // initial numbers are read from the first row of every segment
+        let mut first_row = vec![H::BaseField::ZERO; trace.width()];
+        let mut start = 0;
+        let mut sequences = Vec::with_capacity(self.steps.len());
+        for &step in self.steps.iter() {
+            trace.read_row_into(start, &mut first_row);
+            sequences.push((num_from_state(&first_row), H::BaseField::from(step as u64)));
+            start += step + 1;
+        }
+
+        PublicInputs {
+            sequences,
+            trace_width: trace.width(),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// HELPER FUNCTION
+// ---------------------------------------------------------------------
+
+/// Returns the length of the trace for sequences which reach one after the specified numbers of
+/// steps; the trace must contain a row for every value of every sequence, including the last
+/// one.
+///
+/// # Panics
+/// Panics if the number of rows does not fit into `usize`.
+pub fn get_trace_length(steps: &[usize]) -> usize {
+    let num_rows = steps
+        .iter()
+        .try_fold(0usize, |rows, &step| rows.checked_add(step)?.checked_add(1))
+        .expect("number of trace rows must fit into usize");
+    get_padded_trace_length(num_rows)
+}
+
+/// Returns the value whose binary decomposition (least significant bit first) is held by the
+/// specified columns.
+fn value_from_bits<B: StarkField>(bits: &[B]) -> u64 {
+    bits.iter()
+        .rev()
+        .fold(0, |value, &bit| (value << 1) | (bit == B::ONE) as u64)
+}
@@ -0,0 +1,632 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    compute_collatz, get_trace_length, get_trace_width, CollatzAir, CollatzExample, CollatzProver,
+    Example, PublicInputs,
+};
+use crate::{runner::build_example, Blake3_256, ExampleOptions};
+use structopt::StructOpt;
+use winterfell::{
+    batch,
+    math::{fields::f128::BaseElement, FieldElement, StarkField},
+    testing::{
+        self,
+        proptest::prelude::{prop, Just, ProptestConfig, Strategy},
+        strategies,
+    },
+    Air, AssertionError, ByteReader, ColumnCodec, Deserializable, FieldExtension, ProofOptions,
+    Prover, ProverError, Serializable, SliceReader, StarkProof, Trace, TraceBuildError, TraceError,
+    TraceInfo, TraceTable, VerifierError,
+};
+
+/// Initial numbers of sequences which reach one after 7, 8, 16, and 11 steps; for sequences
+/// of 8 and 16 steps, the number of values in the sequence is one greater than a power of two,
+/// and thus, the trace is padded with almost as many rows as the sequence takes.
+const INITIAL_NUMBERS: [usize; 4] = [3, 6, 7, 52];
+
+#[test]
+fn collatz_test_basic_proof_verification() {
+    for n in INITIAL_NUMBERS {
+        let collatz = Box::new(CollatzExample::<Blake3_256>::new(n, build_options(false)));
+        crate::tests::test_basic_proof_verification(collatz);
+    }
+}
+
+#[test]
+fn collatz_test_basic_proof_verification_extension() {
+    for n in INITIAL_NUMBERS {
+        let collatz = Box::new(CollatzExample::<Blake3_256>::new(n, build_options(true)));
+        crate::tests::test_basic_proof_verification(collatz);
+    }
+}
+
+#[test]
+fn collatz_test_basic_proof_verification_fail() {
+    for n in INITIAL_NUMBERS {
+        let collatz = Box::new(CollatzExample::<Blake3_256>::new(n, build_options(false)));
+        crate::tests::test_basic_proof_verification_fail(collatz);
+    }
+}
+
+#[test]
+fn collatz_test_description() {
+    let options = build_options(false);
+    let collatz = CollatzExample::<Blake3_256>::new(52, options);
+    let description = collatz
+        .describe()
+        .expect("collatz example must support descriptions");
+
+    // the sequence starting at 52 reaches 1 after 11 steps
+    assert_eq!(16, description.trace_length);
+    assert_eq!(14, description.main_assertions.len());
+    assert_eq!(
+        include_str!("description.json").trim_end(),
+        description.to_json()
+    );
+}
+
+#[test]
+fn collatz_test_dependency_graph() {
+    let options = build_options(false);
+    let collatz = CollatzExample::<Blake3_256>::new(52, options.clone());
+    let trace_info = TraceInfo::new(collatz.trace_width, get_trace_length(&collatz.steps));
+    let air = CollatzAir::new(trace_info, collatz.pub_inputs(), options);
+    let graph = air.dependency_graph();
+
+    // the step counter in the last column is read by the constraint which increments it within
+    // a segment and resets it between segments, and thus, no column is left unconstrained
+    let step_column = collatz.trace_width - 1;
+    assert!(graph.constraints[2].current.contains(&step_column));
+    assert!(graph.constraints[2].next.contains(&step_column));
+    assert!(graph.orphaned_columns().is_empty());
+    assert_eq!(include_str!("dependencies.dot").trim_end(), graph.to_dot());
+}
+
+#[test]
+fn collatz_test_step_out_of_range() {
+    let options = build_options(false);
+    let collatz = CollatzExample::<Blake3_256>::new(52, options);
+    let proof = collatz.prove();
+    assert!(collatz.verify(proof.clone()).is_ok());
+
+    // a step beyond the end of the 16-step trace must be rejected rather than cause a panic
+    let pub_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(52), BaseElement::new(30))],
+        trace_width: 8,
+    };
+    assert_eq!(
+        Err(VerifierError::InvalidAssertion(
+            AssertionError::StepOutOfTrace {
+                step: 30,
+                trace_len: 16
+            }
+        )),
+        winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof, pub_inputs)
+    );
+}
+
+#[test]
+fn collatz_test_step_overflow() {
+    let options = build_options(false);
+    let collatz = CollatzExample::<Blake3_256>::new(52, options);
+    let proof = collatz.prove();
+    let verify = |sequences| {
+        let pub_inputs = PublicInputs {
+            sequences,
+            trace_width: 8,
+        };
+        winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof.clone(), pub_inputs)
+    };
+    let out_of_trace = |step| {
+        Err(VerifierError::InvalidAssertion(
+            AssertionError::StepOutOfTrace {
+                step,
+                trace_len: 16,
+            },
+        ))
+    };
+
+    // steps which would wrap around to a step within the trace if truncated to 64 bits
+    let step = (1u128 << 64) + 11;
+    assert_eq!(
+        out_of_trace(step as i128),
+        verify(vec![(BaseElement::new(52), BaseElement::new(step))])
+    );
+
+    // the largest field element is a valid step in public inputs, but it does not even fit
+    // into i128, and thus, the step saturates
+    let step = BaseElement::MODULUS - 1;
+    assert_eq!(
+        out_of_trace(i128::MAX),
+        verify(vec![(BaseElement::new(52), BaseElement::new(step))])
+    );
+
+    // the first row of a segment following a segment which ends at the last step is outside of
+    // the trace, even if the segment itself takes zero steps
+    assert_eq!(
+        out_of_trace(16),
+        verify(vec![
+            (BaseElement::new(52), BaseElement::new(15)),
+            (BaseElement::new(1), BaseElement::new(0)),
+        ])
+    );
+
+    // adding up steps of several sequences must not overflow
+    assert_eq!(
+        out_of_trace(i128::MAX),
+        verify(vec![
+            (BaseElement::new(52), BaseElement::new(step)),
+            (BaseElement::new(52), BaseElement::new(step)),
+        ])
+    );
+}
+
+#[test]
+fn collatz_test_prove_batch_isolates_failures() {
+    let options = build_options(false);
+    let prover = StepFromTraceProver(CollatzProver::new(options, vec![11]));
+
+    // the step of the second trace is moved to step 30, which is outside of the 16-step trace;
+    // this must fail only the proof for this trace
+    let mut invalid_trace = prover.0.build_trace(&[52]).unwrap();
+    invalid_trace.set(invalid_trace.width() - 1, 15, BaseElement::new(30));
+    let traces = vec![
+        prover.0.build_trace(&[52]).unwrap(),
+        invalid_trace,
+        prover.0.build_trace(&[52]).unwrap(),
+    ];
+    let mut proofs = batch::prove_batch(&prover, traces);
+    assert_eq!(3, proofs.len());
+    assert!(matches!(
+        proofs[1],
+        Err(ProverError::InvalidAssertion(
+            AssertionError::StepOutOfTrace { step: 30, .. }
+        ))
+    ));
+
+    for proof in [proofs.remove(2), proofs.remove(0)] {
+        let pub_inputs = PublicInputs {
+            sequences: vec![(BaseElement::new(52), BaseElement::new(11))],
+            trace_width: 8,
+        };
+        let proof = proof.unwrap();
+        assert!(
+            winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof, pub_inputs).is_ok()
+        );
+    }
+}
+
+#[test]
+fn collatz_test_large_initial_numbers() {
+    let options = build_options(false);
+
+    // the sequence starting at 27 reaches 9232 after 77 steps, and thus, values are decomposed
+    // into 14 bits
+    let collatz = CollatzExample::<Blake3_256>::new(27, options.clone());
+    let description = collatz.describe().unwrap();
+    assert_eq!(16, description.main_trace_width);
+    assert_eq!(128, description.trace_length);
+    let proof = collatz.prove();
+    assert!(collatz.verify(proof.clone()).is_ok());
+    assert!(collatz.verify_with_wrong_inputs(proof).is_err());
+
+    // the sequence starting at 2^20 - 1 exceeds 2^32 before reaching 1 after 178 steps
+    let collatz = CollatzExample::<Blake3_256>::new((1 << 20) - 1, options);
+    let description = collatz.describe().unwrap();
+    assert_eq!(35, description.main_trace_width);
+    assert_eq!(256, description.trace_length);
+    let proof = collatz.prove();
+    assert!(collatz.verify(proof.clone()).is_ok());
+    assert!(collatz.verify_with_wrong_inputs(proof).is_err());
+}
+
+#[test]
+fn collatz_test_power_of_two_steps() {
+    // the sequence starting at 6 reaches 1 after exactly 8 steps; the trace must still have a
+    // row for the last value of the sequence
+    let options = build_options(false);
+    let collatz = CollatzExample::<Blake3_256>::new(6, options);
+    assert_eq!(16, collatz.describe().unwrap().trace_length);
+    let proof = collatz.prove();
+    assert!(collatz.verify(proof).is_ok());
+}
+
+#[test]
+fn collatz_test_trace_length() {
+    // the final value of a sequence occupies a row of its own, and thus, 15 steps fit into a
+    // trace of 16 rows, while 16 and 17 steps require 32 rows
+    assert_eq!(16, get_trace_length(&[15]));
+    assert_eq!(32, get_trace_length(&[16]));
+    assert_eq!(32, get_trace_length(&[17]));
+    assert_eq!(TraceInfo::MIN_TRACE_LENGTH, get_trace_length(&[1]));
+    assert_eq!(16, get_trace_length(&[7, 7]));
+    assert_eq!(32, get_trace_length(&[7, 8]));
+    #[cfg(target_pointer_width = "64")]
+    {
+        assert_eq!(1 << 31, get_trace_length(&[(1 << 31) - 1]));
+        assert_eq!(1 << 32, get_trace_length(&[1 << 31]));
+        assert_eq!(1 << 32, get_trace_length(&[(1 << 31) + 1]));
+    }
+}
+
+#[test]
+fn collatz_test_trace_length_boundaries() {
+    // sequences starting at 22, 7, and 14 reach one after 15, 16, and 17 steps respectively
+    let options = build_options(false);
+    for (initial_number, step, trace_length) in [(22, 15, 16), (7, 16, 32), (14, 17, 32)] {
+        assert_eq!(step, compute_collatz(initial_number).0);
+        let collatz = CollatzExample::<Blake3_256>::new(initial_number, options.clone());
+        assert_eq!(trace_length, collatz.describe().unwrap().trace_length);
+        let proof = collatz.prove();
+        assert!(collatz.verify(proof.clone()).is_ok());
+        assert!(collatz.verify_with_wrong_inputs(proof).is_err());
+    }
+}
+
+#[test]
+fn collatz_test_batch() {
+    let options = build_options(false);
+
+    // the sequences reach 1 after 16, 111, and 118 steps, and the largest value of all sequences
+    // is 9232 (reached from 27 and from 97)
+    let collatz = CollatzExample::<Blake3_256>::with_initial_numbers(vec![7, 27, 97], options);
+    let description = collatz.describe().unwrap();
+    assert_eq!(16, description.main_trace_width);
+    assert_eq!(256, description.trace_length);
+    assert_eq!(3 * 2 * 15, description.main_assertions.len());
+    let proof = collatz.prove();
+    assert!(collatz.verify(proof.clone()).is_ok());
+    assert!(collatz.verify_with_wrong_inputs(proof.clone()).is_err());
+
+    // the proof must be rejected if any of the sequences is replaced
+    let pub_inputs = collatz.pub_inputs();
+    for i in 0..3 {
+        let mut wrong_inputs = pub_inputs.clone();
+        wrong_inputs.sequences[i].0 += BaseElement::ONE;
+        let result =
+            winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof.clone(), wrong_inputs);
+        assert!(result.is_err());
+    }
+
+    // the proof must be rejected if the sequences are reordered
+    let mut wrong_inputs = pub_inputs;
+    wrong_inputs.sequences.swap(1, 2);
+    assert!(
+        winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof, wrong_inputs).is_err()
+    );
+}
+
+#[test]
+fn collatz_test_batch_rejects_cleared_selector() {
+    let options = build_options(false);
+    let prover = CollatzProver::<Blake3_256>::new(options, vec![16, 9]);
+    let trace = prover.build_trace(&[7, 12]).unwrap();
+
+    // the selector is cleared within the first segment to skip a step of the sequence; the
+    // step counter is then reset, and thus, cannot reach the asserted number of steps
+    let perturbation = strategies::TracePerturbation {
+        column: trace.width() - 2,
+        row: 5,
+        delta: -BaseElement::ONE,
+    };
+    assert_eq!(
+        BaseElement::ONE,
+        trace.get(perturbation.column, perturbation.row)
+    );
+    assert!(testing::check_perturbed_trace_is_rejected(&prover, &trace, perturbation).is_ok());
+}
+
+#[test]
+fn collatz_test_build_trace_errors() {
+    let options = build_options(false);
+
+    // the sequence starting at 52 reaches one after 11 steps, and thus, the build fails at the
+    // last row of the first segment
+    let prover = CollatzProver::<Blake3_256>::new(options.clone(), vec![5]);
+    let expected = TraceError::FillFailed {
+        row: 5,
+        error: TraceBuildError::UnexpectedOutput(
+            "sequence starting at 52 does not reach one after 5 steps".to_string(),
+        ),
+    };
+    assert_eq!(Err(expected), prover.build_trace(&[52]).map(|_| ()));
+
+    // the second segment starts at row 2; continuing its sequence past one yields 4, which does
+    // not fit into the two bits needed for the values of both sequences
+    let prover = CollatzProver::<Blake3_256>::new(options, vec![1, 3]);
+    let expected = TraceError::FillFailed {
+        row: 4,
+        error: TraceBuildError::InvalidState(
+            "sequence starting at 2 exceeds 2 bits at step 2".to_string(),
+        ),
+    };
+    assert_eq!(Err(expected), prover.build_trace(&[2, 2]).map(|_| ()));
+}
+
+#[test]
+fn collatz_test_initial_numbers_option() {
+    let options =
+        ExampleOptions::from_iter(["winterfell", "collatz", "--initial-numbers", "7,27,97"]);
+    let example = build_example(&options, &options.example).unwrap();
+    let proof = example.prove();
+    assert!(example.verify(proof).is_ok());
+
+    // sequences starting at 1 do not take any steps
+    let options = ExampleOptions::from_iter(["winterfell", "collatz", "--initial-numbers", "7,1"]);
+    assert!(build_example(&options, &options.example).is_err());
+}
+
+#[test]
+fn collatz_test_batch_option() {
+    // sequences starting at 5, 6, and 7 reach one after 5, 8, and 16 steps respectively
+    let options = ExampleOptions::from_iter(["winterfell", "collatz", "-n", "5", "--batch", "3"]);
+    let example = build_example(&options, &options.example).unwrap();
+    let proof = example.prove();
+    assert!(example.verify(proof.clone()).is_ok());
+    assert!(example.verify_with_wrong_inputs(proof.clone()).is_err());
+
+    // the proof attests to the stopping times of all sequences in the batch; the largest value
+    // of all sequences is 52 (reached from 7), and thus, values are decomposed into 6 bits
+    let pub_inputs = PublicInputs {
+        sequences: [(5, 5), (6, 8), (7, 16)]
+            .iter()
+            .map(|&(n, step)| (BaseElement::new(n), BaseElement::new(step)))
+            .collect(),
+        trace_width: 8,
+    };
+    assert!(winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(
+        proof.clone(),
+        pub_inputs.clone()
+    )
+    .is_ok());
+
+    // the proof must be rejected if the stopping time of the last sequence is wrong
+    let mut wrong_inputs = pub_inputs;
+    wrong_inputs.sequences[2].1 = BaseElement::new(15);
+    assert!(
+        winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof, wrong_inputs).is_err()
+    );
+
+    // a batch cannot be empty, and cannot be combined with explicit initial numbers
+    let options = ExampleOptions::from_iter(["winterfell", "collatz", "--batch", "0"]);
+    assert!(build_example(&options, &options.example).is_err());
+
+    // the number of sequences must fit into the serialized public inputs
+    let options = ExampleOptions::from_iter(["winterfell", "collatz", "--batch", "65536"]);
+    assert!(build_example(&options, &options.example).is_err());
+    let options = ExampleOptions::from_iter([
+        "winterfell",
+        "collatz",
+        "--batch",
+        "3",
+        "--initial-numbers",
+        "7,27",
+    ]);
+    assert!(build_example(&options, &options.example).is_err());
+}
+
+#[test]
+#[should_panic(expected = "number of sequences cannot exceed 65535, but was 65536")]
+fn collatz_test_pub_inputs_too_many_sequences() {
+    let pub_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(2), BaseElement::ONE); 1 << 16],
+        trace_width: 4,
+    };
+    let _ = pub_inputs.to_bytes();
+}
+
+#[test]
+#[should_panic(expected = "trace width cannot exceed 255, but was 256")]
+fn collatz_test_pub_inputs_trace_width_too_large() {
+    let pub_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(2), BaseElement::ONE)],
+        trace_width: 256,
+    };
+    let _ = pub_inputs.to_bytes();
+}
+
+#[test]
+fn collatz_test_field_option() {
+    // fields smaller than 128 bits require a field extension to achieve the default security
+    for (field, num_modulus_bits) in [("f62", 62), ("f64", 64), ("f128", 128)] {
+        let args = format!("winterfell --field {field} collatz --initial-numbers 7,27");
+        let options = ExampleOptions::from_iter(args.split_whitespace());
+        let example = build_example(&options, &options.example).unwrap();
+        let proof = example.prove();
+        assert_eq!(num_modulus_bits, proof.context.num_modulus_bits());
+        assert!(example.verify(proof.clone()).is_ok());
+        assert!(example.verify_with_wrong_inputs(proof).is_err());
+    }
+
+    // Rescue Prime hash function is defined only over the 64-bit field
+    let args = "winterfell -h rp64_256 --field f64 collatz -n 7";
+    let options = ExampleOptions::from_iter(args.split_whitespace());
+    let example = build_example(&options, &options.example).unwrap();
+    assert!(example.verify(example.prove()).is_ok());
+    let args = "winterfell -h rp64_256 collatz -n 7";
+    let options = ExampleOptions::from_iter(args.split_whitespace());
+    assert!(build_example(&options, &options.example).is_err());
+}
+
+#[test]
+fn collatz_test_pub_inputs_serialization() {
+    let pub_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(52), BaseElement::new(11))],
+        trace_width: 8,
+    };
+    let bytes = pub_inputs.to_bytes();
+    let mut reader = SliceReader::new(&bytes);
+    assert_eq!(pub_inputs, PublicInputs::read_from(&mut reader).unwrap());
+    assert!(!reader.has_more_bytes());
+
+    // the step count must be bound into the serialized inputs, and thus, into the transcript
+    let wrong_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(52), BaseElement::new(12))],
+        ..pub_inputs
+    };
+    assert_ne!(bytes, wrong_inputs.to_bytes());
+}
+
+#[test]
+fn collatz_test_trace_serialization() {
+    // the sequence starting at 27 reaches 1 after 111 steps, and its largest value is 9232
+    let (step, max_value) = compute_collatz(27);
+    let options = build_options(false);
+    let prover = CollatzProver::<Blake3_256>::new(options, vec![step]);
+    let trace = prover.build_trace(&[27]).unwrap();
+    assert_eq!(16, get_trace_width(max_value));
+    assert_eq!(128, trace.length());
+
+    // bit columns and the selector are bit-packed, and the step counter is stored as 7-bit
+    // integers
+    let mut expected_codecs = vec![ColumnCodec::BitPacked; 15];
+    expected_codecs.push(ColumnCodec::SmallInt(7));
+    assert_eq!(expected_codecs, trace.select_column_codecs());
+
+    let bytes = trace.to_bytes();
+    let mut plain_bytes = Vec::new();
+    trace.write_with_codecs(&mut plain_bytes, &[ColumnCodec::Plain; 16]);
+    assert!(bytes.len() * 50 < plain_bytes.len());
+
+    let result = TraceTable::<BaseElement>::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    for i in 0..trace.width() {
+        assert_eq!(trace.get_column(i), result.get_column(i));
+    }
+}
+
+#[test]
+fn collatz_test_wrong_step() {
+    let options = build_options(false);
+    let collatz = CollatzExample::<Blake3_256>::new(52, options);
+    let proof = collatz.prove();
+
+    // a proof and its public inputs can be stored and verified later
+    let pub_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(52), BaseElement::new(11))],
+        trace_width: 8,
+    };
+    let stored_proof = StarkProof::from_bytes(&proof.to_bytes()).unwrap();
+    let stored_inputs =
+        PublicInputs::read_from(&mut SliceReader::new(&pub_inputs.to_bytes())).unwrap();
+    assert!(
+        winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(stored_proof, stored_inputs)
+            .is_ok()
+    );
+
+    // a proof generated for 11 steps must not be accepted for 12 steps
+    let wrong_inputs = PublicInputs {
+        sequences: vec![(BaseElement::new(52), BaseElement::new(12))],
+        ..pub_inputs
+    };
+    assert!(
+        winterfell::verify::<CollatzAir<BaseElement>, Blake3_256>(proof, wrong_inputs).is_err()
+    );
+}
+
+#[test]
+fn collatz_test_honest_trace_verifies() {
+    let config = ProptestConfig::with_cases(8);
+    testing::run_property(
+        "collatz_honest_trace_verifies",
+        config,
+        collatz_instances(),
+        |(options, n)| {
+            let (step, _) = compute_collatz(n);
+            let prover = CollatzProver::<Blake3_256>::new(options, vec![step]);
+            testing::check_honest_trace_verifies(&prover, prover.build_trace(&[n]).unwrap())
+        },
+    );
+}
+
+#[test]
+fn collatz_test_verification_is_deterministic() {
+    let config = ProptestConfig::with_cases(4);
+    testing::run_property(
+        "collatz_verification_is_deterministic",
+        config,
+        collatz_instances(),
+        |(options, n)| {
+            let (step, _) = compute_collatz(n);
+            let prover = CollatzProver::<Blake3_256>::new(options, vec![step]);
+            testing::check_verification_is_deterministic(&prover, prover.build_trace(&[n]).unwrap())
+        },
+    );
+}
+
+#[test]
+fn collatz_test_perturbed_trace_is_rejected() {
+    // padding rows after the last value of the sequence hold bits which are only required to
+    // be binary; thus, only rows of the sequence are perturbed
+    let strategy = collatz_instances().prop_flat_map(|(options, n)| {
+        let (step, max_value) = compute_collatz(n);
+        let width = get_trace_width(max_value);
+        let perturbation = strategies::trace_perturbation(0..width, 0..step + 1);
+        (Just(options), Just(n), perturbation)
+    });
+    let config = ProptestConfig::with_cases(16);
+    testing::run_property(
+        "collatz_perturbed_trace_is_rejected",
+        config,
+        strategy,
+        |(options, n, perturbation)| {
+            let (step, _) = compute_collatz(n);
+            let prover = CollatzProver::<Blake3_256>::new(options, vec![step]);
+            testing::check_perturbed_trace_is_rejected(
+                &prover,
+                &prover.build_trace(&[n]).unwrap(),
+                perturbation,
+            )
+        },
+    );
+}
+
+/// Returns a strategy which generates small proof options together with initial numbers of
+/// sequences which reach 1 after 8 to 19 steps.
+fn collatz_instances() -> impl Strategy<Value = (ProofOptions, usize)> {
+    (
+        strategies::proof_options(),
+        prop::sample::select(vec![6_usize, 7, 9, 12, 52]),
+    )
+}
+
+fn build_options(use_extension_field: bool) -> ProofOptions {
+    let extension = if use_extension_field {
+        FieldExtension::Quadratic
+    } else {
+        FieldExtension::None
+    };
+    ProofOptions::new(28, 8, 0, extension, 4, 256)
+}
+
+// HELPER PROVER
+// ================================================================================================
+
+/// A prover which reads the number of steps from the largest value of the step counter in the
+/// execution trace rather than from its configuration.
+struct StepFromTraceProver(CollatzProver<Blake3_256>);
+
+impl Prover for StepFromTraceProver {
+    type BaseField = BaseElement;
+    type Air = CollatzAir<BaseElement>;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs<BaseElement> {
+        let step = trace
+            .get_column(trace.width() - 1)
+            .iter()
+            .max_by_key(|step| step.as_int())
+            .copied()
+            .unwrap();
+        let mut pub_inputs = self.0.get_pub_inputs(trace);
+        pub_inputs.sequences[0].1 = step;
+        pub_inputs
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.0.options()
+    }
+}
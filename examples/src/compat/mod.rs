@@ -0,0 +1,182 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Checked-in proofs for detecting changes which break verification of existing proofs.
+//!
+//! Proofs for a fixed set of small instances are checked into the `fixtures` directory. Every
+//! serialized proof starts with the version of the format in which it was serialized, and the
+//! `compat_fixtures` test reads this version from every fixture:
+//!
+//! * if the version is one of the [SUPPORTED_FORMAT_VERSIONS], the fixture must be deserialized
+//!   and verified by the current version of the library;
+//! * otherwise, every format version after the one of the fixture must be described by an entry
+//!   in `MIGRATIONS.md`.
+//!
+//! Thus, a change which makes existing proofs unverifiable must either be reverted, or bump
+//! [PROOF_FORMAT_VERSION] and describe the change in the migration notes. Fixtures can be checked
+//! or regenerated via the `compat` command of the examples binary:
+//!
+//! ```text
+//! cargo run --release -p examples -- compat
+//! cargo run --release -p examples -- compat --update
+//! ```
+//!
+//! Since proof generation is deterministic, regenerating fixtures without a change to the
+//! protocol leaves them unchanged.
+
+use crate::golden::GoldenInstance;
+use core::fmt;
+use winterfell::{DeserializationError, StarkProof, VerifierError, PROOF_FORMAT_VERSION};
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Path to the directory containing the checked-in proofs.
+pub const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/compat/fixtures");
+
+/// Format versions of proofs which the current version of the library can read and verify.
+pub const SUPPORTED_FORMAT_VERSIONS: [u8; 1] = [PROOF_FORMAT_VERSION];
+
+/// Contents of the file containing migration notes for every proof format version.
+const MIGRATION_NOTES: &str = include_str!("MIGRATIONS.md");
+
+/// Prefix of the heading which starts a migration note; the prefix is followed by the version.
+const MIGRATION_NOTE_PREFIX: &str = "## Version ";
+
+/// The set of instances for which proofs are checked in; arguments are interpreted in the same
+/// way as command-line arguments of the examples binary.
+pub const COMPAT_INSTANCES: [GoldenInstance; 2] = [
+    GoldenInstance::new("fib", &["-g", "8", "fib", "-n", "64"]),
+    GoldenInstance::new("collatz", &["-g", "8", "collatz", "-n", "7"]),
+];
+
+/// Checked-in proofs for all instances, in the same order as [COMPAT_INSTANCES].
+const FIXTURES: [&[u8]; 2] = [
+    include_bytes!("fixtures/fib.bin"),
+    include_bytes!("fixtures/collatz.bin"),
+];
+
+// FIXTURES
+// ================================================================================================
+
+/// Returns the path to the checked-in proof for the specified instance.
+pub fn fixture_path(instance: &GoldenInstance) -> String {
+    format!("{}/{}.bin", FIXTURES_DIR, instance.name())
+}
+
+/// Returns format versions described by the checked-in migration notes.
+pub fn migration_notes() -> Vec<u8> {
+    MIGRATION_NOTES
+        .lines()
+        .filter_map(|line| line.strip_prefix(MIGRATION_NOTE_PREFIX))
+        .map(|version| {
+            version
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid migration note version: {version}"))
+        })
+        .collect()
+}
+
+/// Checks the checked-in proof of every instance against the current version of the library;
+/// returns a list of failures, which is empty if all proofs are either verified or covered by
+/// migration notes.
+pub fn check_fixtures() -> Vec<FixtureFailure> {
+    let notes = migration_notes();
+    COMPAT_INSTANCES
+        .iter()
+        .zip(FIXTURES)
+        .filter_map(|(instance, bytes)| {
+            check_fixture(instance, bytes, &notes)
+                .err()
+                .map(|error| FixtureFailure {
+                    name: instance.name(),
+                    error,
+                })
+        })
+        .collect()
+}
+
+/// Checks a single checked-in proof: proofs in one of the supported format versions must be
+/// valid, and proofs in older versions must be covered by migration notes for all newer versions.
+fn check_fixture(
+    instance: &GoldenInstance,
+    bytes: &[u8],
+    notes: &[u8],
+) -> Result<(), FixtureError> {
+    let version = StarkProof::read_format_version(bytes).map_err(FixtureError::InvalidProof)?;
+    if SUPPORTED_FORMAT_VERSIONS.contains(&version) {
+        let proof = StarkProof::from_bytes(bytes).map_err(FixtureError::InvalidProof)?;
+        return instance
+            .build()
+            .verify(proof)
+            .map_err(FixtureError::VerificationFailed);
+    }
+
+    if version > PROOF_FORMAT_VERSION {
+        return Err(FixtureError::UnknownVersion(version));
+    }
+    match (version + 1..=PROOF_FORMAT_VERSION).find(|version| !notes.contains(version)) {
+        Some(version) => Err(FixtureError::MissingMigrationNote(version)),
+        None => Ok(()),
+    }
+}
+
+// FIXTURE FAILURE
+// ================================================================================================
+
+/// Describes an instance for which the checked-in proof is not compatible with the current
+/// version of the library.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FixtureFailure {
+    /// Name of the instance.
+    pub name: &'static str,
+    /// Reason for which the checked-in proof is not compatible.
+    pub error: FixtureError,
+}
+
+impl fmt::Display for FixtureFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "checked-in proof for {} {}", self.name, self.error)
+    }
+}
+
+/// Reasons for which a checked-in proof is not compatible with the current version of the
+/// library.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FixtureError {
+    /// The proof could not be deserialized.
+    InvalidProof(DeserializationError),
+    /// The proof is in a supported format version but failed verification.
+    VerificationFailed(VerifierError),
+    /// The proof declares a format version newer than the current one.
+    UnknownVersion(u8),
+    /// The proof is in an unsupported format version, but no migration note was recorded for
+    /// the specified newer version.
+    MissingMigrationNote(u8),
+}
+
+impl fmt::Display for FixtureError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidProof(err) => {
+                write!(f, "could not be deserialized: {err}")
+            }
+            Self::VerificationFailed(err) => {
+                write!(f, "failed verification: {err}")
+            }
+            Self::UnknownVersion(version) => {
+                write!(f, "declares format version {version}, but the current version is {PROOF_FORMAT_VERSION}")
+            }
+            Self::MissingMigrationNote(version) => {
+                write!(f, "is no longer supported, but no migration note was recorded for format version {version}")
+            }
+        }
+    }
+}
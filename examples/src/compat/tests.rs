@@ -0,0 +1,72 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    check_fixture, check_fixtures, migration_notes, FixtureError, COMPAT_INSTANCES, FIXTURES,
+    SUPPORTED_FORMAT_VERSIONS,
+};
+use winterfell::{StarkProof, PROOF_FORMAT_VERSION};
+
+#[test]
+fn compat_fixtures() {
+    let failures = check_fixtures();
+    assert!(
+        failures.is_empty(),
+        "checked-in proofs are not compatible with the current version:\n{}",
+        failures
+            .iter()
+            .map(|failure| failure.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn compat_migration_notes_cover_all_versions() {
+    let expected = (1..=PROOF_FORMAT_VERSION).collect::<Vec<_>>();
+    assert_eq!(expected, migration_notes());
+    assert!(SUPPORTED_FORMAT_VERSIONS.contains(&PROOF_FORMAT_VERSION));
+}
+
+#[test]
+fn compat_fixture_versions() {
+    let instance = &COMPAT_INSTANCES[0];
+    let bytes = FIXTURES[0];
+    let notes = migration_notes();
+    assert_eq!(Ok(()), check_fixture(instance, bytes, &notes));
+
+    // a fixture in an older format is accepted only if all newer versions have migration notes
+    let mut old_version = bytes.to_vec();
+    old_version[0] = PROOF_FORMAT_VERSION - 1;
+    assert_eq!(Ok(()), check_fixture(instance, &old_version, &notes));
+    assert_eq!(
+        Err(FixtureError::MissingMigrationNote(PROOF_FORMAT_VERSION)),
+        check_fixture(instance, &old_version, &[])
+    );
+
+    // a fixture in a format newer than the current one is rejected
+    let mut new_version = bytes.to_vec();
+    new_version[0] = PROOF_FORMAT_VERSION + 1;
+    assert_eq!(
+        Err(FixtureError::UnknownVersion(PROOF_FORMAT_VERSION + 1)),
+        check_fixture(instance, &new_version, &notes)
+    );
+
+    // a fixture in a supported format must be valid
+    let mut tampered = StarkProof::from_bytes(bytes).unwrap();
+    tampered.pow_nonce += 1;
+    assert!(matches!(
+        check_fixture(instance, &tampered.to_bytes(), &notes),
+        Err(FixtureError::VerificationFailed(_))
+    ));
+    assert!(matches!(
+        check_fixture(instance, &bytes[..bytes.len() - 1], &notes),
+        Err(FixtureError::InvalidProof(_))
+    ));
+    assert!(matches!(
+        check_fixture(instance, &[], &notes),
+        Err(FixtureError::InvalidProof(_))
+    ));
+}
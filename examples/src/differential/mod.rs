@@ -0,0 +1,415 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Differential testing of examples instantiated over different fields.
+//!
+//! An example which is generic over the field can be instantiated, for instance, over both the
+//! 128-bit and the 64-bit fields. Such instantiations are expected to describe the same
+//! computation: their AIRs must have the same shape (i.e., the same trace layout, constraints,
+//! and assertion structure), proofs for both of them must verify, and the same logical change of
+//! the execution trace must be handled the same way by both of them.
+//!
+//! [check_equivalence()](check_equivalence) checks all of the above for a pair of examples which
+//! are declared equivalent. Values of the assertions are not compared, since for many
+//! computations (e.g., computing a Fibonacci term modulo the field modulus) they depend on the
+//! field.
+
+use crate::Example;
+use core::fmt;
+use winterfell::{
+    AirDescription, AssertionDescription, ConstraintDescription, ProverError, TraceError,
+    VerifierError,
+};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// TRACE CELL
+// ================================================================================================
+
+/// A single cell of the main segment of an execution trace.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TraceCell {
+    /// Index of the column in the main trace segment.
+    pub column: usize,
+    /// Index of the step in the execution trace.
+    pub step: usize,
+}
+
+impl TraceCell {
+    /// Returns a cell at the specified column and step.
+    pub fn new(column: usize, step: usize) -> Self {
+        TraceCell { column, step }
+    }
+}
+
+impl fmt::Display for TraceCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {} at step {}", self.column, self.step)
+    }
+}
+
+// DIFFERENTIAL ERROR
+// ================================================================================================
+
+/// Represents a difference in behavior of two examples which were declared equivalent.
+///
+/// Where an error refers to one of the examples, the examples are identified by their position
+/// in the call to [check_equivalence()] (0 for the first example and 1 for the second example).
+#[derive(Debug, PartialEq, Eq)]
+pub enum DifferentialError {
+    /// This error occurs when one of the examples does not support AIR descriptions.
+    DescriptionNotSupported(usize),
+    /// This error occurs when AIR descriptions of the examples are structurally different; the
+    /// value lists all differences.
+    DescriptionMismatch(Vec<String>),
+    /// This error occurs when a proof generated for one of the examples fails to verify.
+    ProofRejected(usize, VerifierError),
+    /// This error occurs when a proof generated for one of the examples verifies against wrong
+    /// public inputs.
+    WrongInputsAccepted(usize),
+    /// This error occurs when one of the examples does not support trace perturbations.
+    PerturbationNotSupported(usize),
+    /// This error occurs when the examples handle the same perturbation of the execution trace
+    /// differently; the values are the perturbed cell and the outcomes for both examples.
+    PerturbationMismatch(TraceCell, String, String),
+}
+
+impl fmt::Display for DifferentialError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DescriptionNotSupported(example) => {
+                write!(f, "example {example} does not support AIR descriptions")
+            }
+            Self::DescriptionMismatch(diffs) => {
+                write!(f, "AIR descriptions are different: {}", diffs.join("; "))
+            }
+            Self::ProofRejected(example, err) => {
+                write!(f, "proof for example {example} failed to verify: {err}")
+            }
+            Self::WrongInputsAccepted(example) => {
+                write!(f, "proof for example {example} verified against wrong public inputs")
+            }
+            Self::PerturbationNotSupported(example) => {
+                write!(f, "example {example} does not support trace perturbations")
+            }
+            Self::PerturbationMismatch(cell, left, right) => {
+                write!(f, "perturbation of {cell} was handled differently: {left} vs. {right}")
+            }
+        }
+    }
+}
+
+// EQUIVALENCE CHECK
+// ================================================================================================
+
+/// Checks that the specified examples, which are declared equivalent, describe the same
+/// computation.
+///
+/// The check is performed as follows:
+/// 1. AIR descriptions of the examples are compared structurally via [compare_descriptions()].
+/// 2. A proof is generated for each example; the proof must verify against the public inputs of
+///    the example, and must not verify against wrong public inputs.
+/// 3. For each of the perturbed cells (see [perturbed_cells()]), both examples generate a proof
+///    from a trace in which the same cell is perturbed. Either both traces must be rejected by
+///    the prover at the same assertion or constraint, or both proofs must lead to the same
+///    verification outcome.
+///
+/// # Errors
+/// Returns an error describing the first difference found between the examples.
+pub fn check_equivalence(left: &dyn Example, right: &dyn Example) -> Result<(), DifferentialError> {
+    let left_description = left
+        .describe()
+        .ok_or(DifferentialError::DescriptionNotSupported(0))?;
+    let right_description = right
+        .describe()
+        .ok_or(DifferentialError::DescriptionNotSupported(1))?;
+    let diffs = compare_descriptions(&left_description, &right_description);
+    if !diffs.is_empty() {
+        return Err(DifferentialError::DescriptionMismatch(diffs));
+    }
+
+    for (i, example) in [left, right].into_iter().enumerate() {
+        example
+            .verify(example.prove())
+            .map_err(|err| DifferentialError::ProofRejected(i, err))?;
+        if example.verify_with_wrong_inputs(example.prove()).is_ok() {
+            return Err(DifferentialError::WrongInputsAccepted(i));
+        }
+    }
+
+    for cell in perturbed_cells(&left_description) {
+        let left_outcome = left
+            .prove_perturbed(cell)
+            .ok_or(DifferentialError::PerturbationNotSupported(0))?;
+        let right_outcome = right
+            .prove_perturbed(cell)
+            .ok_or(DifferentialError::PerturbationNotSupported(1))?;
+        let left_outcome = PerturbationOutcome::new(left, left_outcome);
+        let right_outcome = PerturbationOutcome::new(right, right_outcome);
+        if left_outcome != right_outcome {
+            return Err(DifferentialError::PerturbationMismatch(
+                cell,
+                left_outcome.to_string(),
+                right_outcome.to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a list of structural differences between the specified AIR descriptions; the list is
+/// empty if the descriptions are structurally the same.
+///
+/// All properties of the descriptions are compared except for the asserted values: for asserted
+/// values, only their number and whether they are derived from random elements are compared.
+pub fn compare_descriptions(left: &AirDescription, right: &AirDescription) -> Vec<String> {
+    let mut diffs = Vec::new();
+    compare(
+        &mut diffs,
+        "trace_length",
+        &left.trace_length,
+        &right.trace_length,
+    );
+    compare(
+        &mut diffs,
+        "main_trace_width",
+        &left.main_trace_width,
+        &right.main_trace_width,
+    );
+    compare(
+        &mut diffs,
+        "aux_segment_widths",
+        &left.aux_segment_widths,
+        &right.aux_segment_widths,
+    );
+    compare(
+        &mut diffs,
+        "aux_segment_rand_elements",
+        &left.aux_segment_rand_elements,
+        &right.aux_segment_rand_elements,
+    );
+    compare_constraints(
+        &mut diffs,
+        "main_transition_constraints",
+        &left.main_transition_constraints,
+        &right.main_transition_constraints,
+    );
+    compare_constraints(
+        &mut diffs,
+        "aux_transition_constraints",
+        &left.aux_transition_constraints,
+        &right.aux_transition_constraints,
+    );
+    compare(
+        &mut diffs,
+        "num_transition_exemptions",
+        &left.num_transition_exemptions,
+        &right.num_transition_exemptions,
+    );
+    compare_assertions(
+        &mut diffs,
+        "main_assertions",
+        &left.main_assertions,
+        &right.main_assertions,
+    );
+    compare_assertions(
+        &mut diffs,
+        "aux_assertions",
+        &left.aux_assertions,
+        &right.aux_assertions,
+    );
+    compare(
+        &mut diffs,
+        "periodic_column_lengths",
+        &left.periodic_column_lengths,
+        &right.periodic_column_lengths,
+    );
+    diffs
+}
+
+/// Returns the cells of the main trace segment perturbed by [check_equivalence()].
+///
+/// These are the cells at the first steps of all single and periodic assertions, as well as the
+/// first, the middle, and the last cells of every column of the main trace segment.
+pub fn perturbed_cells(description: &AirDescription) -> Vec<TraceCell> {
+    let last_step = description.trace_length - 1;
+    let mut cells = description
+        .main_assertions
+        .iter()
+        .map(|assertion| TraceCell::new(assertion.column, assertion.first_step))
+        .collect::<Vec<_>>();
+    for column in 0..description.main_trace_width {
+        for step in [0, last_step / 2, last_step] {
+            let cell = TraceCell::new(column, step);
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+    }
+    cells
+}
+
+// PERTURBATION OUTCOME
+// ================================================================================================
+
+/// Field-independent outcome of generating and verifying a proof for a perturbed trace.
+#[derive(Debug, PartialEq, Eq)]
+enum PerturbationOutcome {
+    /// The prover rejected the trace because it does not satisfy an assertion; the values are
+    /// whether the column belongs to an auxiliary segment, the column, and the step.
+    UnsatisfiedAssertion(bool, usize, usize),
+    /// The prover rejected the trace because it does not satisfy a boundary expression; the
+    /// values are the index of the expression and the step.
+    UnsatisfiedBoundaryExpr(usize, usize),
+    /// The prover rejected the trace because it does not satisfy a transition constraint; the
+    /// values are whether the constraint is placed against an auxiliary segment, the index of
+    /// the constraint, and the step.
+    UnsatisfiedTransitionConstraint(bool, usize, usize),
+    /// The prover failed for a reason other than an invalid trace.
+    ProverFailed(String),
+    /// A proof was generated; the value is true if the proof verified.
+    Proved(bool),
+}
+
+impl PerturbationOutcome {
+    fn new(example: &dyn Example, outcome: Result<winterfell::StarkProof, ProverError>) -> Self {
+        match outcome {
+            Ok(proof) => Self::Proved(example.verify(proof).is_ok()),
+            Err(ProverError::InvalidTrace(err)) => match err {
+                TraceError::UnsatisfiedAssertion {
+                    aux, column, step, ..
+                } => Self::UnsatisfiedAssertion(aux, column, step),
+                TraceError::UnsatisfiedBoundaryExpr { index, step } => {
+                    Self::UnsatisfiedBoundaryExpr(index, step)
+                }
+                TraceError::UnsatisfiedTransitionConstraint { aux, index, step } => {
+                    Self::UnsatisfiedTransitionConstraint(aux, index, step)
+                }
+                err => Self::ProverFailed(err.to_string()),
+            },
+            Err(err) => Self::ProverFailed(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for PerturbationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segment = |aux: &bool| if *aux { "auxiliary" } else { "main" };
+        match self {
+            Self::UnsatisfiedAssertion(aux, column, step) => write!(
+                f,
+                "assertion against {} column {column} at step {step} not satisfied",
+                segment(aux)
+            ),
+            Self::UnsatisfiedBoundaryExpr(index, step) => {
+                write!(
+                    f,
+                    "boundary expression {index} at step {step} not satisfied"
+                )
+            }
+            Self::UnsatisfiedTransitionConstraint(aux, index, step) => write!(
+                f,
+                "{} transition constraint {index} at step {step} not satisfied",
+                segment(aux)
+            ),
+            Self::ProverFailed(err) => write!(f, "prover failed: {err}"),
+            Self::Proved(true) => write!(f, "proof verified"),
+            Self::Proved(false) => write!(f, "proof rejected"),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Records a difference under the specified path if the values are not equal.
+fn compare<T: fmt::Debug + PartialEq>(diffs: &mut Vec<String>, path: &str, left: &T, right: &T) {
+    if left != right {
+        diffs.push(format!("{path}: {left:?} != {right:?}"));
+    }
+}
+
+/// Records differences between the constraints under the specified path.
+fn compare_constraints(
+    diffs: &mut Vec<String>,
+    path: &str,
+    left: &[ConstraintDescription],
+    right: &[ConstraintDescription],
+) {
+    compare(diffs, &format!("{path}.len"), &left.len(), &right.len());
+    for (i, (left, right)) in left.iter().zip(right).enumerate() {
+        let path = format!("{path}[{i}]");
+        compare(
+            diffs,
+            &format!("{path}.base_degree"),
+            &left.base_degree,
+            &right.base_degree,
+        );
+        compare(
+            diffs,
+            &format!("{path}.cycles"),
+            &left.cycles,
+            &right.cycles,
+        );
+        compare(
+            diffs,
+            &format!("{path}.evaluation_degree"),
+            &left.evaluation_degree,
+            &right.evaluation_degree,
+        );
+        compare(
+            diffs,
+            &format!("{path}.cyclic"),
+            &left.cyclic,
+            &right.cyclic,
+        );
+    }
+}
+
+/// Records differences between the structures of the assertions under the specified path.
+fn compare_assertions(
+    diffs: &mut Vec<String>,
+    path: &str,
+    left: &[AssertionDescription],
+    right: &[AssertionDescription],
+) {
+    compare(diffs, &format!("{path}.len"), &left.len(), &right.len());
+    for (i, (left, right)) in left.iter().zip(right).enumerate() {
+        let path = format!("{path}[{i}]");
+        compare(
+            diffs,
+            &format!("{path}.column"),
+            &left.column,
+            &right.column,
+        );
+        compare(
+            diffs,
+            &format!("{path}.first_step"),
+            &left.first_step,
+            &right.first_step,
+        );
+        compare(
+            diffs,
+            &format!("{path}.stride"),
+            &left.stride,
+            &right.stride,
+        );
+        compare(
+            diffs,
+            &format!("{path}.num_steps"),
+            &left.num_steps,
+            &right.num_steps,
+        );
+        compare(
+            diffs,
+            &format!("{path}.num_values"),
+            &left.values.as_ref().map(|values| values.len()),
+            &right.values.as_ref().map(|values| values.len()),
+        );
+    }
+}
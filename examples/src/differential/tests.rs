@@ -0,0 +1,124 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    check_equivalence, compare_descriptions, perturbed_cells, DifferentialError, TraceCell,
+};
+use crate::{fibonacci::fib2::FibExample, Example};
+use winterfell::{
+    crypto::hashers::Blake3_256,
+    math::fields::{f128, f64},
+    FieldExtension, ProofOptions, ProverError, StarkProof, VerifierError,
+};
+
+type Fib128 = FibExample<Blake3_256<f128::BaseElement>>;
+type Fib64 = FibExample<Blake3_256<f64::BaseElement>>;
+
+#[test]
+fn fib2_f128_and_f64_are_equivalent() {
+    let fib128 = Fib128::new(64, build_options());
+    let fib64 = Fib64::new(64, build_options());
+    assert_eq!(Ok(()), check_equivalence(&fib128, &fib64));
+}
+
+#[test]
+fn description_mismatch() {
+    let fib16 = Fib128::new(16, build_options());
+    let fib32 = Fib64::new(32, build_options());
+    let expected = vec![
+        "trace_length: 8 != 16".to_string(),
+        "main_transition_constraints[0].evaluation_degree: 7 != 15".to_string(),
+        "main_transition_constraints[1].evaluation_degree: 7 != 15".to_string(),
+        "main_assertions[2].first_step: 7 != 15".to_string(),
+    ];
+    assert_eq!(
+        Err(DifferentialError::DescriptionMismatch(expected)),
+        check_equivalence(&fib16, &fib32)
+    );
+}
+
+#[test]
+fn description_comparison_ignores_asserted_values() {
+    let description = Fib128::new(64, build_options()).describe().unwrap();
+
+    // the asserted result of the computation depends on the field
+    let mut other = Fib64::new(64, build_options()).describe().unwrap();
+    assert_ne!(description.main_assertions, other.main_assertions);
+    assert!(compare_descriptions(&description, &other).is_empty());
+
+    // the number of asserted values and the structure of assertions are compared
+    other.main_assertions[1].stride = 4;
+    other.main_assertions[2].values = None;
+    other.main_assertions.pop();
+    assert_eq!(
+        vec![
+            "main_assertions.len: 3 != 2".to_string(),
+            "main_assertions[1].stride: 0 != 4".to_string(),
+        ],
+        compare_descriptions(&description, &other)
+    );
+}
+
+#[test]
+fn fib2_perturbed_cells() {
+    let description = Fib128::new(16, build_options()).describe().unwrap();
+    let expected = vec![
+        TraceCell::new(0, 0),
+        TraceCell::new(1, 0),
+        TraceCell::new(1, 7),
+        TraceCell::new(0, 3),
+        TraceCell::new(0, 7),
+        TraceCell::new(1, 3),
+    ];
+    assert_eq!(expected, perturbed_cells(&description));
+}
+
+#[test]
+fn perturbation_mismatch() {
+    let fib128 = Fib128::new(16, build_options());
+    let fib64 = ShiftedPerturbation(Fib64::new(16, build_options()));
+    assert_eq!(
+        Err(DifferentialError::PerturbationMismatch(
+            TraceCell::new(0, 0),
+            "assertion against main column 0 at step 0 not satisfied".to_string(),
+            "assertion against main column 1 at step 0 not satisfied".to_string(),
+        )),
+        check_equivalence(&fib128, &fib64)
+    );
+}
+
+// HELPER FUNCTIONS AND TYPES
+// ================================================================================================
+
+fn build_options() -> ProofOptions {
+    ProofOptions::new(28, 8, 0, FieldExtension::Quadratic, 4, 256)
+}
+
+/// An example which perturbs a cell in the column following the requested one, as an incorrectly
+/// instantiated example could.
+struct ShiftedPerturbation<E: Example>(E);
+
+impl<E: Example> Example for ShiftedPerturbation<E> {
+    fn prove(&self) -> StarkProof {
+        self.0.prove()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        self.0.verify(proof)
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        self.0.verify_with_wrong_inputs(proof)
+    }
+
+    fn prove_perturbed(&self, cell: TraceCell) -> Option<Result<StarkProof, ProverError>> {
+        self.0
+            .prove_perturbed(TraceCell::new(cell.column + 1, cell.step))
+    }
+
+    fn describe(&self) -> Option<winterfell::AirDescription> {
+        self.0.describe()
+    }
+}
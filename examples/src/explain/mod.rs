@@ -0,0 +1,316 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Reports of STARK protocol parameters derived from an AIR and proof options.
+//!
+//! Parameters which users choose for an example (e.g., trace length, blowup factor, or FRI
+//! folding factor) determine a number of parameters which are otherwise internal to the prover:
+//! sizes of evaluation domains, degrees to which constraints are adjusted before they are
+//! merged into the composition polynomial, the number of FRI layers, etc. A [ParamsReport]
+//! collects these derived parameters from an instantiated AIR, so that they can be printed
+//! before a proof is generated.
+
+use core::fmt;
+use winterfell::{
+    crypto::ElementHasher,
+    math::{log2, FieldElement},
+    Air, Serializable, TransitionConstraintDegree, TransitionDivisorKind,
+};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// PARAMETERS REPORT
+// ================================================================================================
+
+/// STARK protocol parameters derived from an instance of an AIR and its proof options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsReport {
+    trace_length: usize,
+    main_trace_width: usize,
+    preprocessed_width: usize,
+    aux_trace_width: usize,
+    num_frame_rows: usize,
+    ce_blowup_factor: usize,
+    lde_blowup_factor: usize,
+    composition_degree: usize,
+    num_transition_exemptions: usize,
+    num_assertions: usize,
+    constraints: Vec<ConstraintParams>,
+    num_composition_columns: usize,
+    fri_folding_factor: usize,
+    fri_layer_domain_sizes: Vec<usize>,
+    fri_remainder_size: usize,
+    num_queries: usize,
+    grinding_factor: u32,
+    extension_degree: u32,
+    proof_size: usize,
+    conjectured_security: u32,
+    collision_resistance: u32,
+}
+
+impl ParamsReport {
+    /// Returns a report of parameters derived from the specified AIR for proofs generated using
+    /// hash function `H`.
+    pub fn new<A: Air, H: ElementHasher<BaseField = A::BaseField>>(air: &A) -> Self {
+        let context = air.context();
+        let options = air.options();
+        let trace_length = air.trace_length();
+        let lde_domain_size = air.lde_domain_size();
+
+        let main_constraints = context.main_transition_constraint_degrees().iter();
+        let aux_constraints = context.aux_transition_constraint_degrees().iter();
+        let constraints = main_constraints
+            .map(|degree| (false, degree))
+            .chain(aux_constraints.map(|degree| (true, degree)))
+            .zip(context.transition_exemptions())
+            .map(|((aux, degree), &n)| ConstraintParams::new(air, aux, degree, n))
+            .collect();
+
+        let fri_options = options.to_fri_options();
+        let num_fri_layers = fri_options.num_fri_layers(lde_domain_size);
+        let fri_layer_domain_sizes = (0..num_fri_layers)
+            .map(|i| lde_domain_size / fri_options.folding_factor().pow(i as u32))
+            .collect();
+
+        let mut report = ParamsReport {
+            trace_length,
+            main_trace_width: air.trace_layout().main_trace_width(),
+            preprocessed_width: air.trace_info().preprocessed_width(),
+            aux_trace_width: air.trace_layout().aux_trace_width(),
+            num_frame_rows: context.frame_offsets().len(),
+            ce_blowup_factor: air.ce_blowup_factor(),
+            lde_blowup_factor: air.lde_blowup_factor(),
+            composition_degree: air.composition_degree(),
+            num_transition_exemptions: context.num_transition_exemptions(),
+            num_assertions: context.num_assertions(),
+            constraints,
+            num_composition_columns: air.num_composition_columns(),
+            fri_folding_factor: fri_options.folding_factor(),
+            fri_layer_domain_sizes,
+            fri_remainder_size: fri_options.fri_remainder_size(lde_domain_size),
+            num_queries: options.num_queries(),
+            grinding_factor: options.grinding_factor(),
+            extension_degree: options.field_extension().degree(),
+            proof_size: 0,
+            conjectured_security: air.conjectured_security_level::<H>(),
+            collision_resistance: H::COLLISION_RESISTANCE,
+        };
+        report.proof_size = report.estimate_proof_size(
+            A::BaseField::ELEMENT_BYTES,
+            H::Digest::default().to_bytes().len(),
+            options.num_ood_points(),
+        );
+        report
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the size of the constraint evaluation domain.
+    pub fn ce_domain_size(&self) -> usize {
+        self.trace_length * self.ce_blowup_factor
+    }
+
+    /// Returns the size of the low-degree extension domain.
+    pub fn lde_domain_size(&self) -> usize {
+        self.trace_length * self.lde_blowup_factor
+    }
+
+    /// Returns parameters of all transition constraints; constraints against the main trace
+    /// segment are listed first.
+    pub fn constraints(&self) -> &[ConstraintParams] {
+        &self.constraints
+    }
+
+    /// Returns the number of columns into which the composition polynomial is split.
+    pub fn num_composition_columns(&self) -> usize {
+        self.num_composition_columns
+    }
+
+    /// Returns sizes of the domains of FRI layers (excluding the remainder).
+    pub fn fri_layer_domain_sizes(&self) -> &[usize] {
+        &self.fri_layer_domain_sizes
+    }
+
+    /// Returns an upper bound on the size of a proof (in bytes).
+    ///
+    /// The estimate assumes that all queried positions are distinct and that Merkle
+    /// authentication paths for different positions do not share any nodes; actual proofs are
+    /// usually smaller.
+    pub fn proof_size(&self) -> usize {
+        self.proof_size
+    }
+
+    /// Returns conjectured security level of proofs (in bits).
+    pub fn conjectured_security(&self) -> u32 {
+        self.conjectured_security
+    }
+
+    /// Returns collision resistance of the hash function used by the protocol (in bits).
+    pub fn collision_resistance(&self) -> u32 {
+        self.collision_resistance
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Estimates the size of a proof from the parameters in this report.
+    fn estimate_proof_size(
+        &self,
+        base_element_bytes: usize,
+        digest_bytes: usize,
+        num_ood_points: usize,
+    ) -> usize {
+        let extension_bytes = base_element_bytes * self.extension_degree as usize;
+        let num_trace_segments =
+            1 + (self.preprocessed_width > 0) as usize + (self.aux_trace_width > 0) as usize;
+        let lde_depth = log2(self.lde_domain_size()) as usize;
+
+        // commitments to trace segments, the composition polynomial, and FRI layers (including
+        // the remainder), and the proof-of-work nonce; the commitment to the preprocessed segment
+        // is not included in proofs
+        let num_commitments = num_trace_segments - (self.preprocessed_width > 0) as usize;
+        let mut result =
+            (num_commitments + 1 + self.fri_layer_domain_sizes.len() + 1) * digest_bytes + 8;
+
+        // trace and composition polynomial queries
+        let row_bytes = (self.main_trace_width + self.preprocessed_width) * base_element_bytes
+            + self.aux_trace_width * extension_bytes
+            + self.num_composition_columns * extension_bytes;
+        let path_bytes = (num_trace_segments + 1) * lde_depth * digest_bytes;
+        result += self.num_queries * (row_bytes + path_bytes);
+
+        // out-of-domain frame; composition polynomial columns are evaluated only at the point
+        // they were built for, and there are ce_blowup_factor such columns per point
+        let trace_width = self.main_trace_width + self.preprocessed_width + self.aux_trace_width;
+        result += num_ood_points
+            * (self.num_frame_rows * trace_width + self.ce_blowup_factor)
+            * extension_bytes;
+
+        // FRI layer queries and the remainder
+        for &domain_size in self.fri_layer_domain_sizes.iter() {
+            let depth = log2(domain_size / self.fri_folding_factor) as usize;
+            result += self.num_queries
+                * (self.fri_folding_factor * extension_bytes + depth * digest_bytes);
+        }
+        result + self.fri_remainder_size * extension_bytes
+    }
+}
+
+impl fmt::Display for ParamsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "trace: {} steps; columns: {} main, {} preprocessed, {} auxiliary",
+            self.trace_length, self.main_trace_width, self.preprocessed_width, self.aux_trace_width
+        )?;
+        writeln!(
+            f,
+            "domains: trace 2^{}, constraint evaluation 2^{} (blowup {}), LDE 2^{} (blowup {})",
+            log2(self.trace_length),
+            log2(self.ce_domain_size()),
+            self.ce_blowup_factor,
+            log2(self.lde_domain_size()),
+            self.lde_blowup_factor
+        )?;
+        writeln!(
+            f,
+            "transition constraints: {} (composition degree: {}, transition exemptions: {})",
+            self.constraints.len(),
+            self.composition_degree,
+            self.num_transition_exemptions
+        )?;
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            writeln!(f, "  {i}: {constraint}")?;
+        }
+        writeln!(f, "assertions: {}", self.num_assertions)?;
+        writeln!(f, "composition columns: {}", self.num_composition_columns)?;
+        let layers = self
+            .fri_layer_domain_sizes
+            .iter()
+            .map(|&size| format!("2^{}", log2(size)))
+            .collect::<Vec<_>>();
+        writeln!(
+            f,
+            "FRI: folding factor {}, {} layers [{}], remainder of {} elements",
+            self.fri_folding_factor,
+            layers.len(),
+            layers.join(", "),
+            self.fri_remainder_size
+        )?;
+        writeln!(
+            f,
+            "queries: {}, grinding factor: {}, field extension degree: {}",
+            self.num_queries, self.grinding_factor, self.extension_degree
+        )?;
+        writeln!(
+            f,
+            "estimated proof size: at most {:.1} KB",
+            self.proof_size as f64 / 1024f64
+        )?;
+        write!(
+            f,
+            "conjectured security: {} bits (hash collision resistance: {} bits)",
+            self.conjectured_security, self.collision_resistance
+        )
+    }
+}
+
+// CONSTRAINT PARAMETERS
+// ================================================================================================
+
+/// Parameters of a single transition constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintParams {
+    /// True if the constraint is placed against auxiliary trace segments.
+    pub aux: bool,
+    /// Degree of the constraint with respect to trace columns.
+    pub base_degree: usize,
+    /// Degree of the constraint polynomial.
+    pub evaluation_degree: usize,
+    /// Degree of the divisor of the constraint.
+    pub divisor_degree: usize,
+    /// Degree by which the constraint is adjusted after division, so that its degree becomes
+    /// equal to the composition degree.
+    pub degree_adjustment: usize,
+}
+
+impl ConstraintParams {
+    fn new<A: Air>(
+        air: &A,
+        aux: bool,
+        degree: &TransitionConstraintDegree,
+        num_exemptions: usize,
+    ) -> Self {
+        let trace_length = air.trace_length();
+        let evaluation_degree = degree.get_evaluation_degree(trace_length);
+        let divisor_degree = match degree.divisor() {
+            TransitionDivisorKind::Transition => trace_length - num_exemptions,
+            TransitionDivisorKind::Cyclic => trace_length,
+        };
+        ConstraintParams {
+            aux,
+            base_degree: degree.base(),
+            evaluation_degree,
+            divisor_degree,
+            degree_adjustment: air.composition_degree() + divisor_degree - evaluation_degree,
+        }
+    }
+}
+
+impl fmt::Display for ConstraintParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} segment, degree {}, evaluation degree {}, divisor degree {}, adjusted by {}",
+            if self.aux { "aux" } else { "main" },
+            self.base_degree,
+            self.evaluation_degree,
+            self.divisor_degree,
+            self.degree_adjustment
+        )
+    }
+}
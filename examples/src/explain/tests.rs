@@ -0,0 +1,47 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::{
+    fibonacci::fib2::FibExample,
+    runner::{build_example, get_default_examples},
+    Blake3_256, Example, ExampleOptions,
+};
+use structopt::StructOpt;
+use winterfell::{FieldExtension, ProofOptions};
+
+#[test]
+fn explain_fib_params() {
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256);
+    let fib = FibExample::<Blake3_256>::new(1024, options);
+    let report = fib
+        .explain_params()
+        .expect("fib example must support parameter reports");
+    assert_eq!(include_str!("fib.txt").trim_end(), report.to_string());
+
+    // the report must agree with the proof generated for the same parameters
+    let proof = fib.prove();
+    assert_eq!(
+        proof.security_level::<Blake3_256>(true),
+        report.conjectured_security()
+    );
+    assert_eq!(
+        proof.fri_layer_domain_sizes(),
+        report.fri_layer_domain_sizes()
+    );
+    assert!(proof.to_bytes().len() <= report.proof_size());
+}
+
+#[test]
+fn explain_all_default_examples() {
+    let options = ExampleOptions::from_iter(["winterfell", "run-all"]);
+    for example_type in get_default_examples() {
+        let example = build_example(&options, &example_type).unwrap();
+        let report = example.explain_params();
+        assert!(
+            report.is_some(),
+            "{example_type:?} does not explain its parameters"
+        );
+    }
+}
@@ -3,23 +3,27 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{BaseElement, FieldElement, ProofOptions, TRACE_WIDTH};
+use super::{ExtensibleField, FieldElement, ProofOptions, StarkField, TRACE_WIDTH};
 use crate::utils::are_equal;
 use winterfell::{
-    Air, AirContext, Assertion, EvaluationFrame, TraceInfo, TransitionConstraintDegree,
+    Air, AirContext, Assertion, EvaluationFrame, SplitInputsAir, TraceInfo,
+    TransitionConstraintDegree,
 };
 
 // FIBONACCI AIR
 // ================================================================================================
 
-pub struct FibAir {
-    context: AirContext<BaseElement>,
-    result: BaseElement,
+pub struct FibAir<B: StarkField> {
+    context: AirContext<B>,
+    result: B,
 }
 
-impl Air for FibAir {
-    type BaseField = BaseElement;
-    type PublicInputs = BaseElement;
+impl<B> Air for FibAir<B>
+where
+    B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    type BaseField = B;
+    type PublicInputs = B;
 
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
@@ -69,3 +73,19 @@ impl Air for FibAir {
         ]
     }
 }
+
+// SPLIT INPUTS
+// ================================================================================================
+
+/// The only public input of the computation is the asserted result, and thus, the assertions
+/// of the AIR are built from the result alone.
+impl<B> SplitInputsAir for FibAir<B>
+where
+    B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    type AssertionInputs = B;
+
+    fn from_assertion_inputs(trace_info: TraceInfo, result: B, options: ProofOptions) -> Self {
+        Self::new(trace_info, result, options)
+    }
+}
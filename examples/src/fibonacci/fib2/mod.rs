@@ -4,21 +4,37 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::utils::compute_fib_term;
-use crate::{Blake3_192, Blake3_256, Example, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    differential::TraceCell,
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Example, ExampleDefaults, ExampleOptions, FieldType, HashFunction,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
-    crypto::ElementHasher,
-    math::{fields::f128::BaseElement, log2, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    crypto::{
+        hashers::{
+            Blake2s_256, Blake3_192, Blake3_256, GriffinJive64_256, Keccak_256, Poseidon128_256,
+            Poseidon64_256, Rp64_256, RpJive64_256, Sha3_256,
+        },
+        ElementHasher,
+    },
+    math::{
+        fields::{f128, f62, f64},
+        log2, ExtensibleField, FieldElement, StarkField,
+    },
+    planning::{estimate_prover_cost, CostEstimate, MachineProfile},
+    Air, AirDescription, BatchStarkProof, DegeneracyThresholds, ExpectedOutputs, ProofOptions,
+    Prover, ProverConfig, ProverError, StarkProof, Trace, TraceInfo, TraceTable, VerifierError,
 };
 
 mod air;
-use air::FibAir;
+pub use air::FibAir;
 
 mod prover;
-use prover::FibProver;
+pub use prover::FibProver;
 
 #[cfg(test)]
 mod tests;
@@ -31,33 +47,113 @@ const TRACE_WIDTH: usize = 2;
 // FIBONACCI EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     sequence_length: usize,
+    expected_result: Option<u128>,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(28, 8);
+    let field = options.field(FieldType::F128);
+    let (options, hash_fn) = options.to_proof_options(field, &DEFAULTS);
 
-    match hash_fn {
-        HashFunction::Blake3_192 => Ok(Box::new(FibExample::<Blake3_192>::new(
+    // Rescue Prime and Griffin hash functions are defined only over the 64-bit field, Poseidon is
+    // defined over the 64-bit and 128-bit fields
+    match (field, hash_fn) {
+        (FieldType::F62, _) => get_field_example::<f62::BaseElement>(
+            hash_fn,
             sequence_length,
+            expected_result,
             options,
-        ))),
-        HashFunction::Blake3_256 => Ok(Box::new(FibExample::<Blake3_256>::new(
+        ),
+        (FieldType::F64, HashFunction::Rp64_256) => {
+            build_example::<Rp64_256>(sequence_length, expected_result, options)
+        }
+        (FieldType::F64, HashFunction::RpJive64_256) => {
+            build_example::<RpJive64_256>(sequence_length, expected_result, options)
+        }
+        (FieldType::F64, HashFunction::GriffinJive64_256) => {
+            build_example::<GriffinJive64_256>(sequence_length, expected_result, options)
+        }
+        (FieldType::F64, HashFunction::Poseidon) => {
+            build_example::<Poseidon64_256>(sequence_length, expected_result, options)
+        }
+        (FieldType::F64, _) => get_field_example::<f64::BaseElement>(
+            hash_fn,
             sequence_length,
+            expected_result,
             options,
-        ))),
-        HashFunction::Sha3_256 => Ok(Box::new(FibExample::<Sha3_256>::new(
+        ),
+        (FieldType::F128, HashFunction::Poseidon) => {
+            build_example::<Poseidon128_256>(sequence_length, expected_result, options)
+        }
+        (FieldType::F128, _) => get_field_example::<f128::BaseElement>(
+            hash_fn,
             sequence_length,
+            expected_result,
             options,
-        ))),
-        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+        ),
     }
 }
 
+/// Instantiates the example over the field `B` using the specified hash function.
+fn get_field_example<B>(
+    hash_fn: HashFunction,
+    sequence_length: usize,
+    expected_result: Option<u128>,
+    options: ProofOptions,
+) -> Result<Box<dyn Example>, String>
+where
+    B: StarkField + ExtensibleField<2> + ExtensibleField<3> + 'static,
+{
+    match hash_fn {
+        HashFunction::Blake3_192 => {
+            build_example::<Blake3_192<B>>(sequence_length, expected_result, options)
+        }
+        HashFunction::Blake3_256 => {
+            build_example::<Blake3_256<B>>(sequence_length, expected_result, options)
+        }
+        HashFunction::Sha3_256 => {
+            build_example::<Sha3_256<B>>(sequence_length, expected_result, options)
+        }
+        HashFunction::Keccak256 => {
+            build_example::<Keccak_256<B>>(sequence_length, expected_result, options)
+        }
+        HashFunction::Blake2s256 => {
+            build_example::<Blake2s_256<B>>(sequence_length, expected_result, options)
+        }
+        _ => {
+            Err("The specified hash function cannot be used with the specified field.".to_string())
+        }
+    }
+}
+
+/// Instantiates the example using the hash function `H`; when an expected result is provided,
+/// proofs attest to this result rather than to the one read from the execution trace.
+fn build_example<H>(
+    sequence_length: usize,
+    expected_result: Option<u128>,
+    options: ProofOptions,
+) -> Result<Box<dyn Example>, String>
+where
+    H: ElementHasher + Sync + 'static,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    let example = FibExample::<H>::new(sequence_length, options);
+    Ok(match expected_result {
+        Some(result) => Box::new(example.with_expected_result(H::BaseField::from(result))),
+        None => Box::new(example),
+    })
+}
+
 pub struct FibExample<H: ElementHasher> {
     options: ProofOptions,
     sequence_length: usize,
-    result: BaseElement,
+    result: H::BaseField,
+    expected_result: bool,
     _hasher: PhantomData<H>,
 }
 
@@ -81,19 +177,42 @@ impl<H: ElementHasher> FibExample<H> {
             options,
             sequence_length,
             result,
+            expected_result: false,
             _hasher: PhantomData,
         }
     }
-}
 
-// EXAMPLE IMPLEMENTATION
-// ================================================================================================
+    /// Returns this example configured to prove that the Fibonacci term is equal to the
+    /// specified result rather than to the term read from the execution trace.
+    ///
+    /// The prover checks the execution trace against the result before generating a proof, and
+    /// thus, an incorrect result makes proof generation fail with
+    /// [ProverError::UnexpectedOutput].
+    pub fn with_expected_result(mut self, result: H::BaseField) -> Self {
+        self.result = result;
+        self.expected_result = true;
+        self
+    }
 
-impl<H: ElementHasher> Example for FibExample<H>
+    /// Returns a prover for this example, configured to check execution traces against the
+    /// expected result if one was specified.
+    fn build_prover(&self) -> FibProver<H> {
+        let prover = FibProver::<H>::new(self.options.clone());
+        if self.expected_result {
+            prover.with_expected_result(self.result)
+        } else {
+            prover
+        }
+    }
+}
+
+impl<H: ElementHasher> FibExample<H>
 where
-    H: ElementHasher<BaseField = BaseElement>,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
 {
-    fn prove(&self) -> StarkProof {
+    /// Returns a proof for this example, or an error if the proof could not be generated (e.g.,
+    /// because the execution trace does not end with the expected result).
+    pub fn try_prove(&self) -> Result<StarkProof, ProverError> {
         debug!(
             "Generating proof for computing Fibonacci sequence (2 terms per step) up to {}th term\n\
             ---------------------",
@@ -101,7 +220,7 @@ where
         );
 
         // create a prover
-        let prover = FibProver::<H>::new(self.options.clone());
+        let prover = self.build_prover();
 
         // generate execution trace
         let now = Instant::now();
@@ -115,16 +234,84 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.result)
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+impl<H: ElementHasher + Sync> Example for FibExample<H>
+where
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    fn prove(&self) -> StarkProof {
+        self.try_prove()
+            .unwrap_or_else(|err| panic!("failed to generate proof: {err}"))
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        winterfell::verify::<FibAir, H>(proof, self.result)
+        let statement =
+            winterfell::verify_and_extract::<FibAir<H::BaseField>, H>(proof, self.result)?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        winterfell::verify::<FibAir, H>(proof, self.result + BaseElement::ONE)
+        winterfell::verify::<FibAir<H::BaseField>, H>(proof, self.result + H::BaseField::ONE)
+    }
+
+    fn prove_batch(&self, num_proofs: usize) -> Option<BatchStarkProof> {
+        debug!(
+            "Generating a batch of {} proofs for computing Fibonacci sequence (2 terms per step) \
+            up to {}th term\n---------------------",
+            num_proofs, self.sequence_length
+        );
+
+        // generate the execution traces; all traces have the same length, and thus, the proofs
+        // share the same context
+        let prover = self.build_prover();
+        let traces = (0..num_proofs)
+            .map(|_| prover.build_trace(self.sequence_length))
+            .collect();
+
+        let proofs = winterfell::batch::prove_batch(&prover, traces)
+            .into_iter()
+            .map(|proof| proof.unwrap())
+            .collect();
+        Some(BatchStarkProof::new(proofs))
+    }
+
+    fn verify_batch(&self, batch: BatchStarkProof) -> Vec<Result<(), VerifierError>> {
+        let pub_inputs = vec![self.result; batch.num_proofs()];
+        winterfell::verify_batch::<FibAir<H::BaseField>, H>(batch, pub_inputs)
+    }
+
+    fn prove_perturbed(&self, cell: TraceCell) -> Option<Result<StarkProof, ProverError>> {
+        let prover = FibProver::<H>::new(self.options.clone()).with_trace_validation();
+        let mut trace = prover.build_trace(self.sequence_length);
+        let value = trace.get(cell.column, cell.step);
+        trace.set(cell.column, cell.step, value + H::BaseField::ONE);
+        Some(prover.prove(trace))
+    }
+
+    fn describe(&self) -> Option<AirDescription> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2);
+        Some(FibAir::new(trace_info, self.result, self.options.clone()).describe())
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2);
+        let air = FibAir::new(trace_info, self.result, self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+
+    fn estimate_cost(&self) -> Option<CostEstimate> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2);
+        let air = FibAir::new(trace_info.clone(), self.result, self.options.clone());
+        let profile = MachineProfile::calibrate::<_, H>(&air);
+        Some(estimate_prover_cost(&trace_info, &self.options, &profile))
     }
 }
@@ -4,8 +4,8 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{
-    BaseElement, ElementHasher, FibAir, FieldElement, PhantomData, ProofOptions, Prover, Trace,
-    TraceTable, TRACE_WIDTH,
+    ElementHasher, ExpectedOutputs, ExtensibleField, FibAir, FieldElement, PhantomData,
+    ProofOptions, Prover, ProverConfig, Trace, TraceTable, TRACE_WIDTH,
 };
 
 // FIBONACCI PROVER
@@ -13,6 +13,8 @@ use super::{
 
 pub struct FibProver<H: ElementHasher> {
     options: ProofOptions,
+    validate_trace: bool,
+    expected_outputs: Option<ExpectedOutputs<H::BaseField>>,
     _hasher: PhantomData<H>,
 }
 
@@ -20,13 +22,30 @@ impl<H: ElementHasher> FibProver<H> {
     pub fn new(options: ProofOptions) -> Self {
         Self {
             options,
+            validate_trace: false,
+            expected_outputs: None,
             _hasher: PhantomData,
         }
     }
 
+    /// Returns this prover configured to report execution traces which are not valid against
+    /// [FibAir] as errors rather than panics.
+    pub fn with_trace_validation(mut self) -> Self {
+        self.validate_trace = true;
+        self
+    }
+
+    /// Returns this prover configured to generate proofs only for execution traces which end
+    /// with the specified term of the Fibonacci sequence; the term is then used as the public
+    /// input instead of the term read from the trace.
+    pub fn with_expected_result(mut self, result: H::BaseField) -> Self {
+        self.expected_outputs = Some(ExpectedOutputs::new().with_final_output(1, result));
+        self
+    }
+
     /// Builds an execution trace for computing a Fibonacci sequence of the specified length such
     /// that each row advances the sequence by 2 terms.
-    pub fn build_trace(&self, sequence_length: usize) -> TraceTable<BaseElement> {
+    pub fn build_trace(&self, sequence_length: usize) -> TraceTable<H::BaseField> {
         assert!(
             sequence_length.is_power_of_two(),
             "sequence length must be a power of 2"
@@ -35,8 +54,8 @@ impl<H: ElementHasher> FibProver<H> {
         let mut trace = TraceTable::new(TRACE_WIDTH, sequence_length / 2);
         trace.fill(
             |state| {
-                state[0] = BaseElement::ONE;
-                state[1] = BaseElement::ONE;
+                state[0] = H::BaseField::ONE;
+                state[1] = H::BaseField::ONE;
             },
             |_, state| {
                 state[0] += state[1];
@@ -50,19 +69,32 @@ impl<H: ElementHasher> FibProver<H> {
 
 impl<H: ElementHasher> Prover for FibProver<H>
 where
-    H: ElementHasher<BaseField = BaseElement>,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
 {
-    type BaseField = BaseElement;
-    type Air = FibAir;
-    type Trace = TraceTable<BaseElement>;
+    type BaseField = H::BaseField;
+    type Air = FibAir<H::BaseField>;
+    type Trace = TraceTable<H::BaseField>;
     type HashFn = H;
 
-    fn get_pub_inputs(&self, trace: &Self::Trace) -> BaseElement {
-        let last_step = trace.length() - 1;
-        trace.get(1, last_step)
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> H::BaseField {
+        match &self.expected_outputs {
+            Some(outputs) => outputs.values()[0],
+            None => {
+                let last_step = trace.length() - 1;
+                trace.get(1, last_step)
+            }
+        }
     }
 
     fn options(&self) -> &ProofOptions {
         &self.options
     }
+
+    fn expected_outputs(&self) -> Option<&ExpectedOutputs<H::BaseField>> {
+        self.expected_outputs.as_ref()
+    }
+
+    fn config(&self) -> ProverConfig {
+        ProverConfig::default().with_trace_validation(self.validate_trace)
+    }
 }
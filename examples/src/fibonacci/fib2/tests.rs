@@ -3,7 +3,27 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{super::utils::build_proof_options, Blake3_256};
+use super::{
+    super::utils::{build_proof_options, compute_fib_term},
+    Air, Example, FibAir, FibProver, FieldElement, Trace, TraceTable,
+};
+use crate::{runner::build_example, Blake2s_256, Blake3_256, ExampleOptions, Keccak_256};
+use rand_utils::rand_value;
+use structopt::StructOpt;
+use winterfell::{
+    crypto::hashers::Poseidon128_256,
+    math::{
+        fields::{f128::BaseElement, QuadExtension},
+        StarkField,
+    },
+    test_kit,
+    testing::{
+        self,
+        proptest::prelude::{Just, ProptestConfig, Strategy},
+        strategies,
+    },
+    ProofOptions, ProverError, TraceInfo,
+};
 
 #[test]
 fn fib2_test_basic_proof_verification() {
@@ -23,6 +43,60 @@ fn fib2_test_basic_proof_verification_extension() {
     crate::tests::test_basic_proof_verification(fib);
 }
 
+#[test]
+fn fib2_test_basic_proof_verification_poseidon() {
+    let fib = Box::new(super::FibExample::<Poseidon128_256>::new(
+        16,
+        build_proof_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib2_test_basic_proof_verification_poseidon_fail() {
+    let fib = Box::new(super::FibExample::<Poseidon128_256>::new(
+        16,
+        build_proof_options(true),
+    ));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
+#[test]
+fn fib2_test_basic_proof_verification_keccak() {
+    let fib = Box::new(super::FibExample::<Keccak_256>::new(
+        16,
+        build_proof_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib2_test_basic_proof_verification_keccak_fail() {
+    let fib = Box::new(super::FibExample::<Keccak_256>::new(
+        16,
+        build_proof_options(true),
+    ));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
+#[test]
+fn fib2_test_basic_proof_verification_blake2s() {
+    let fib = Box::new(super::FibExample::<Blake2s_256>::new(
+        16,
+        build_proof_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib2_test_basic_proof_verification_blake2s_fail() {
+    let fib = Box::new(super::FibExample::<Blake2s_256>::new(
+        16,
+        build_proof_options(true),
+    ));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
 #[test]
 fn fib2_test_basic_proof_verification_fail() {
     let fib = Box::new(super::FibExample::<Blake3_256>::new(
@@ -31,3 +105,210 @@ fn fib2_test_basic_proof_verification_fail() {
     ));
     crate::tests::test_basic_proof_verification_fail(fib);
 }
+
+#[test]
+fn fib2_test_field_option() {
+    // fields smaller than 128 bits require a field extension to achieve the default security
+    for (field, num_modulus_bits) in [("f62", 62), ("f64", 64), ("f128", 128)] {
+        let args = format!("winterfell --field {field} fib -n 64");
+        let options = ExampleOptions::from_iter(args.split_whitespace());
+        let fib = build_example(&options, &options.example).unwrap();
+        let proof = fib.prove();
+        assert_eq!(num_modulus_bits, proof.context.num_modulus_bits());
+        assert!(fib.verify(proof.clone()).is_ok());
+        assert!(fib.verify_with_wrong_inputs(proof).is_err());
+    }
+
+    // Rescue Prime and Griffin hash functions are defined only over the 64-bit field
+    for hash_fn in ["rp64_256", "rp_jive64_256", "griffin_jive64_256"] {
+        let args = format!("winterfell -h {hash_fn} --field f64 fib -n 64");
+        let options = ExampleOptions::from_iter(args.split_whitespace());
+        let fib = build_example(&options, &options.example).unwrap();
+        assert!(fib.verify(fib.prove()).is_ok());
+
+        for field in ["f62", "f128"] {
+            let args = format!("winterfell -h {hash_fn} --field {field} fib -n 64");
+            let options = ExampleOptions::from_iter(args.split_whitespace());
+            assert!(build_example(&options, &options.example).is_err());
+        }
+    }
+}
+
+#[test]
+fn fib2_test_description() {
+    let fib = super::FibExample::<Blake3_256>::new(16, build_proof_options(false));
+    let description = fib
+        .describe()
+        .expect("fib2 example must support descriptions");
+    assert_eq!(8, description.trace_length);
+    assert_eq!(2, description.num_transition_constraints());
+    assert_eq!(
+        include_str!("description.json").trim_end(),
+        description.to_json()
+    );
+}
+
+#[test]
+fn fib2_test_dependency_graph() {
+    let air = FibAir::new(
+        TraceInfo::new(2, 8),
+        BaseElement::ONE,
+        build_proof_options(false),
+    );
+    let graph = air.dependency_graph();
+    assert!(graph.orphaned_columns().is_empty());
+    assert_eq!(include_str!("dependencies.dot").trim_end(), graph.to_dot());
+}
+
+#[test]
+#[ignore = "measures proving time; run with --test-threads=1"]
+fn fib2_test_cost_estimate() {
+    let fib = Box::new(super::FibExample::<Blake3_256>::new(
+        8192,
+        build_proof_options(false),
+    ));
+    crate::tests::test_cost_estimate(fib);
+}
+
+#[test]
+fn fib2_test_air_test_kit() {
+    let (air, trace_polys) = build_air_and_trace_polys(64, |_| {});
+    test_kit::boundary_poly_check(&air, &trace_polys);
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+    test_kit::evaluate_at_random_point(
+        &air,
+        &trace_polys,
+        rand_value::<QuadExtension<BaseElement>>(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "transition constraint 0 is not satisfied by the trace")]
+fn fib2_test_air_test_kit_invalid_transition() {
+    let (air, trace_polys) = build_air_and_trace_polys(64, |trace| {
+        trace.set(0, 5, BaseElement::ONE);
+    });
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+#[test]
+#[should_panic(expected = "is not satisfied by the trace; dividing the polynomial of column 1")]
+fn fib2_test_air_test_kit_invalid_assertion() {
+    let (air, trace_polys) = build_air_and_trace_polys(64, |trace| {
+        let last_step = trace.length() - 1;
+        let value = trace.get(1, last_step);
+        trace.set(1, last_step, value + BaseElement::ONE);
+    });
+    test_kit::boundary_poly_check(&air, &trace_polys);
+}
+
+/// Builds a trace for a Fibonacci sequence of the specified length, modifies it using the
+/// provided closure, and returns the AIR for the unmodified sequence together with polynomials
+/// interpolated from the modified trace.
+fn build_air_and_trace_polys<F>(
+    sequence_length: usize,
+    modify: F,
+) -> (FibAir<BaseElement>, Vec<Vec<BaseElement>>)
+where
+    F: FnOnce(&mut TraceTable<BaseElement>),
+{
+    let options = build_proof_options(false);
+    let prover = FibProver::<Blake3_256>::new(options.clone());
+    let mut trace = prover.build_trace(sequence_length);
+    let air = FibAir::new(trace.get_info(), compute_fib_term(sequence_length), options);
+    modify(&mut trace);
+    (air, crate::tests::get_trace_polys(&trace))
+}
+
+#[test]
+fn fib2_test_expected_result() {
+    let result: BaseElement = compute_fib_term(64);
+
+    // a proof generated for the expected result attests to this result
+    let args = format!("winterfell fib -n 64 --expected-result {}", result.as_int());
+    let options = ExampleOptions::from_iter(args.split_whitespace());
+    let fib = build_example(&options, &options.example).unwrap();
+    let proof = fib.prove();
+    assert!(fib.verify(proof.clone()).is_ok());
+    assert!(fib.verify_with_wrong_inputs(proof).is_err());
+
+    // an incorrect expected result makes proof generation fail rather than changing the
+    // statement being proven
+    let wrong = result + BaseElement::ONE;
+    let fib = super::FibExample::<Blake3_256>::new(64, build_proof_options(false))
+        .with_expected_result(wrong);
+    assert_eq!(
+        Err(ProverError::UnexpectedOutput {
+            column: 1,
+            step: 31,
+            expected: wrong.to_string(),
+            actual: Some(result.to_string()),
+        }),
+        fib.try_prove()
+    );
+
+    // the same holds for batches of proofs
+    let prover =
+        FibProver::<Blake3_256>::new(build_proof_options(false)).with_expected_result(wrong);
+    let traces = vec![prover.build_trace(64), prover.build_trace(64)];
+    assert!(winterfell::batch::prove_batch(&prover, traces)
+        .into_iter()
+        .all(|proof| matches!(proof, Err(ProverError::UnexpectedOutput { .. }))));
+}
+
+#[test]
+fn fib2_test_honest_trace_verifies() {
+    let config = ProptestConfig::with_cases(8);
+    testing::run_property(
+        "fib2_honest_trace_verifies",
+        config,
+        fib_instances(),
+        |(options, n)| {
+            let prover = FibProver::<Blake3_256>::new(options);
+            testing::check_honest_trace_verifies(&prover, prover.build_trace(n))
+        },
+    );
+}
+
+#[test]
+fn fib2_test_verification_is_deterministic() {
+    let config = ProptestConfig::with_cases(4);
+    testing::run_property(
+        "fib2_verification_is_deterministic",
+        config,
+        fib_instances(),
+        |(options, n)| {
+            let prover = FibProver::<Blake3_256>::new(options);
+            testing::check_verification_is_deterministic(&prover, prover.build_trace(n))
+        },
+    );
+}
+
+#[test]
+fn fib2_test_perturbed_trace_is_rejected() {
+    // every cell of the trace is constrained either by assertions or by transition constraints
+    let strategy = fib_instances().prop_flat_map(|(options, n)| {
+        let perturbation = strategies::trace_perturbation(0..2, 0..n / 2);
+        (Just(options), Just(n), perturbation)
+    });
+    let config = ProptestConfig::with_cases(16);
+    testing::run_property(
+        "fib2_perturbed_trace_is_rejected",
+        config,
+        strategy,
+        |(options, n, perturbation)| {
+            let prover = FibProver::<Blake3_256>::new(options);
+            testing::check_perturbed_trace_is_rejected(
+                &prover,
+                &prover.build_trace(n),
+                perturbation,
+            )
+        },
+    );
+}
+
+/// Returns a strategy which generates small proof options together with lengths of Fibonacci
+/// sequences between 16 and 64 terms.
+fn fib_instances() -> impl Strategy<Value = (ProofOptions, usize)> {
+    (strategies::proof_options(), 4_u32..=6).prop_map(|(options, log_n)| (options, 1 << log_n))
+}
@@ -4,21 +4,26 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::utils::compute_fib_term;
-use crate::{Example, ExampleOptions, HashFunction};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Example, ExampleDefaults, ExampleOptions, FieldType, HashFunction,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f64::BaseElement, log2, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    Air, AirDescription, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo,
+    TraceTable, VerifierError,
 };
 
 mod air;
-use air::FibSmall;
+pub use air::FibSmall;
 
 mod prover;
-use prover::FibSmallProver;
+pub use prover::FibSmallProver;
 
 #[cfg(test)]
 mod tests;
@@ -31,18 +36,28 @@ const TRACE_WIDTH: usize = 2;
 type Blake3_192 = winterfell::crypto::hashers::Blake3_192<BaseElement>;
 type Blake3_256 = winterfell::crypto::hashers::Blake3_256<BaseElement>;
 type Sha3_256 = winterfell::crypto::hashers::Sha3_256<BaseElement>;
+#[allow(non_camel_case_types)]
+type Blake2s_256 = winterfell::crypto::hashers::Blake2s_256<BaseElement>;
+#[allow(non_camel_case_types)]
+type Keccak_256 = winterfell::crypto::hashers::Keccak_256<BaseElement>;
 type Rp64_256 = winterfell::crypto::hashers::Rp64_256;
 type RpJive64_256 = winterfell::crypto::hashers::RpJive64_256;
 type GriffinJive64_256 = winterfell::crypto::hashers::GriffinJive64_256;
+type Poseidon64_256 = winterfell::crypto::hashers::Poseidon64_256;
 
 // FIBONACCI EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     sequence_length: usize,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(28, 8);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F64, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(FibExample::<Blake3_192>::new(
@@ -57,6 +72,14 @@ pub fn get_example(
             sequence_length,
             options,
         ))),
+        HashFunction::Keccak256 => Ok(Box::new(FibExample::<Keccak_256>::new(
+            sequence_length,
+            options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(FibExample::<Blake2s_256>::new(
+            sequence_length,
+            options,
+        ))),
         HashFunction::Rp64_256 => Ok(Box::new(FibExample::<Rp64_256>::new(
             sequence_length,
             options,
@@ -69,6 +92,10 @@ pub fn get_example(
             sequence_length,
             options,
         ))),
+        HashFunction::Poseidon => Ok(Box::new(FibExample::<Poseidon64_256>::new(
+            sequence_length,
+            options,
+        ))),
     }
 }
 
@@ -133,16 +160,29 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.result).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        winterfell::verify::<FibSmall, H>(proof, self.result)
+        let statement = winterfell::verify_and_extract::<FibSmall, H>(proof, self.result)?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
         winterfell::verify::<FibSmall, H>(proof, self.result + BaseElement::ONE)
     }
+
+    fn describe(&self) -> Option<AirDescription> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2);
+        Some(FibSmall::new(trace_info, self.result, self.options.clone()).describe())
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2);
+        let air = FibSmall::new(trace_info, self.result, self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
@@ -3,20 +3,47 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{super::utils::build_proof_options, Rp64_256};
+use super::{
+    super::utils::{build_proof_options, compute_fib_term},
+    FibExample, FibSmall, FibSmallProver, Poseidon64_256, Rp64_256, TRACE_WIDTH,
+};
+use crate::Example;
+use winterfell::{
+    batch,
+    crypto::{
+        hashers::Blake3_256, DefaultRandomCoin, ElementHasher, HashFunctionId, Hasher, RandomCoin,
+        RandomCoinError,
+    },
+    math::{fields::f64::BaseElement, FieldElement, StarkField},
+    Air, ByteReader, ByteWriter, Context, Deserializable, DeserializationError, FieldExtension,
+    LeafEncoding, ProofOptions, Prover, ProverError, Queries, Serializable, SliceReader,
+    StarkProof, TraceInfo, TranscriptProfile, VerifierError,
+};
 
 #[test]
 fn fib_small_test_basic_proof_verification() {
-    let fib = Box::new(super::FibExample::<Rp64_256>::new(
+    let fib = Box::new(FibExample::<Rp64_256>::new(
         16,
-        build_proof_options(false),
+        ProofOptions::new(8, 8, 0, FieldExtension::None, 4, 256),
     ));
     crate::tests::test_basic_proof_verification(fib);
 }
 
 #[test]
 fn fib_small_test_basic_proof_verification_extension() {
-    let fib = Box::new(super::FibExample::<Rp64_256>::new(
+    let fib = Box::new(FibExample::<Rp64_256>::new(16, build_proof_options(true)));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_small_test_basic_proof_verification_fail() {
+    let fib = Box::new(FibExample::<Rp64_256>::new(16, build_proof_options(true)));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
+#[test]
+fn fib_small_test_basic_proof_verification_poseidon() {
+    let fib = Box::new(FibExample::<Poseidon64_256>::new(
         16,
         build_proof_options(true),
     ));
@@ -24,10 +51,530 @@ fn fib_small_test_basic_proof_verification_extension() {
 }
 
 #[test]
-fn fib_small_test_basic_proof_verification_fail() {
-    let fib = Box::new(super::FibExample::<Rp64_256>::new(
+fn fib_small_test_basic_proof_verification_poseidon_fail() {
+    let fib = Box::new(FibExample::<Poseidon64_256>::new(
         16,
-        build_proof_options(false),
+        build_proof_options(true),
     ));
     crate::tests::test_basic_proof_verification_fail(fib);
 }
+
+#[test]
+fn fib_small_test_insufficient_extension_degree() {
+    // 28 queries with blowup factor 8 target 84 bits of security, but the 64-bit base field
+    // provides fewer than 64 bits
+    let fib = FibExample::<Rp64_256>::new(16, build_proof_options(false));
+    let proof = fib.prove();
+    assert_eq!(
+        Err(VerifierError::InsufficientExtensionDegree(2, 1)),
+        fib.verify(proof)
+    );
+}
+
+#[test]
+fn fib_small_test_insufficient_extension_degree_multiple_ood_points() {
+    // sampling more OOD points does not enlarge the field from which random values are drawn,
+    // and thus, does not relax the extension degree requirement
+    let options = build_proof_options(false);
+    let trace_info = TraceInfo::new(TRACE_WIDTH, 16);
+    assert_eq!(
+        2,
+        FibSmall::required_extension_degree(&options, &trace_info)
+    );
+    let options = options.with_num_ood_points(4);
+    assert_eq!(
+        2,
+        FibSmall::required_extension_degree(&options, &trace_info)
+    );
+
+    let fib = FibExample::<Rp64_256>::new(16, options);
+    let proof = fib.prove();
+    assert_eq!(
+        Err(VerifierError::InsufficientExtensionDegree(2, 1)),
+        fib.verify(proof)
+    );
+}
+
+#[test]
+fn fib_small_test_extension_check_opt_out() {
+    let fib = FibExample::<Rp64_256>::new(16, build_proof_options(false));
+    let proof = fib.prove();
+    let result =
+        winterfell::verify_without_extension_check::<FibSmall, Rp64_256>(proof, fib.result);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn fib_small_test_transcript_profile_mismatch() {
+    let winterfell_options = build_proof_options(true);
+    let eth_stark_options =
+        build_proof_options(true).with_transcript_profile(TranscriptProfile::EthStarkCompat);
+
+    // a proof generated with each profile must verify only under that profile
+    for (options, other_options) in [
+        (winterfell_options.clone(), eth_stark_options.clone()),
+        (eth_stark_options, winterfell_options),
+    ] {
+        let fib = FibExample::<Rp64_256>::new(16, options);
+        let proof = fib.prove();
+        assert!(fib.verify(proof.clone()).is_ok());
+
+        let mut proof = proof;
+        proof.context = Context::new::<BaseElement>(&proof.get_trace_info(), other_options);
+        assert!(fib.verify(proof).is_err());
+    }
+}
+
+#[test]
+fn fib_small_test_arithmetic_transcript() {
+    let options = build_proof_options(true).with_transcript_profile(TranscriptProfile::Arithmetic);
+    let fib = Box::new(FibExample::<Rp64_256>::new(16, options));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_small_test_arithmetic_transcript_fail() {
+    let options = build_proof_options(true).with_transcript_profile(TranscriptProfile::Arithmetic);
+    let fib = Box::new(FibExample::<Rp64_256>::new(16, options));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
+#[test]
+fn fib_small_test_arithmetic_transcript_mismatch() {
+    let winterfell_options = build_proof_options(true);
+    let arithmetic_options =
+        build_proof_options(true).with_transcript_profile(TranscriptProfile::Arithmetic);
+
+    let fib = FibExample::<Rp64_256>::new(16, arithmetic_options);
+    let proof = fib.prove();
+
+    // a proof with the arithmetic transcript cannot be verified with a byte-oriented hasher
+    let result = winterfell::verify::<FibSmall, Blake3_256<BaseElement>>(proof.clone(), fib.result);
+    assert_eq!(Err(VerifierError::UnsupportedTranscriptProfile), result);
+
+    // the public coin is seeded differently from the default transcript
+    let mut proof = proof;
+    proof.context = Context::new::<BaseElement>(&proof.get_trace_info(), winterfell_options);
+    assert!(fib.verify(proof).is_err());
+}
+
+#[test]
+fn fib_small_test_domain_too_large_for_platform() {
+    let fib = FibExample::<Rp64_256>::new(16, build_proof_options(false));
+    let mut proof = fib.prove();
+
+    // a trace of 2^62 steps with blowup factor of 8 results in an LDE domain of 2^65 elements;
+    // the trace length is stored as a power of two right after the trace layout
+    let mut context_bytes = proof.context.to_bytes();
+    context_bytes[proof.trace_layout().to_bytes().len()] = 62;
+    proof.context = Context::read_from(&mut SliceReader::new(&context_bytes)).unwrap();
+    assert_eq!(
+        Err(VerifierError::DomainTooLargeForPlatform(65)),
+        fib.verify(proof)
+    );
+}
+
+#[test]
+fn fib_small_test_prove_batch() {
+    let prover = FibSmallProver::<Rp64_256>::new(build_proof_options(true));
+
+    // traces of two different lengths; each length gets its own shared domain
+    let sequence_lengths = [16, 32, 16, 64, 32, 16];
+    let traces = sequence_lengths
+        .iter()
+        .map(|&length| prover.build_trace(length))
+        .collect();
+    let proofs = batch::prove_batch(&prover, traces);
+    assert_eq!(sequence_lengths.len(), proofs.len());
+
+    for (&length, proof) in sequence_lengths.iter().zip(proofs) {
+        let proof = proof.unwrap();
+        let expected = prover.prove(prover.build_trace(length)).unwrap();
+        assert_eq!(expected.to_bytes(), proof.to_bytes());
+
+        let result = compute_fib_term::<BaseElement>(length);
+        assert!(winterfell::verify::<FibSmall, Rp64_256>(proof, result).is_ok());
+    }
+}
+
+#[test]
+fn fib_small_test_raw_leaf_encoding() {
+    let options = build_proof_options(true).with_leaf_encoding(LeafEncoding::Raw);
+    let fib = Box::new(FibExample::<Rp64_256>::new(16, options));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_small_test_raw_leaf_encoding_fail() {
+    let options = build_proof_options(true).with_leaf_encoding(LeafEncoding::Raw);
+    let fib = Box::new(FibExample::<Rp64_256>::new(16, options));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
+#[test]
+fn fib_small_test_raw_leaf_encoding_proof_size() {
+    // rows of the trace consist of two 64-bit elements, and thus, a raw leaf takes up half the
+    // space of a digest; the leaves of the trace commitment are never hashed either
+    for sequence_length in [16, 1024] {
+        let fib = FibExample::<Rp64_256>::new(sequence_length, build_proof_options(true));
+        let hashed_size = fib.prove().to_bytes().len();
+
+        let options = build_proof_options(true).with_leaf_encoding(LeafEncoding::Raw);
+        let fib = FibExample::<Rp64_256>::new(sequence_length, options);
+        let proof = fib.prove();
+        let raw_size = proof.to_bytes().len();
+        assert!(fib.verify(proof).is_ok());
+        assert!(raw_size < hashed_size);
+    }
+}
+
+#[test]
+fn fib_small_test_raw_leaf_encoding_tampered() {
+    let options = build_proof_options(true).with_leaf_encoding(LeafEncoding::Raw);
+    let fib = FibExample::<Rp64_256>::new(16, options);
+    let proof = fib.prove();
+
+    // a proof with raw leaves cannot be verified as a proof with hashed leaves
+    let mut tampered_proof = proof.clone();
+    tampered_proof.context =
+        Context::new::<BaseElement>(&proof.get_trace_info(), build_proof_options(true));
+    assert!(fib.verify(tampered_proof).is_err());
+
+    // change a queried trace value; the value is the leaf itself, and thus, it no longer matches
+    // the trace commitment
+    let mut tampered_proof = proof.clone();
+    let mut query_bytes = tampered_proof.trace_queries[0].to_bytes();
+    query_bytes[4] ^= 1;
+    tampered_proof.trace_queries[0] =
+        Queries::read_from(&mut SliceReader::new(&query_bytes)).unwrap();
+    assert_eq!(
+        Err(VerifierError::TraceQueryDoesNotMatchCommitment),
+        fib.verify(tampered_proof)
+    );
+
+    // change a raw sibling leaf in the authentication paths; the paths are serialized after the
+    // query values and start with the number of paths followed by a bitmask of paths which start
+    // with a raw leaf
+    let mut tampered_proof = proof;
+    let mut query_bytes = tampered_proof.trace_queries[0].to_bytes();
+    let num_value_bytes = u32::from_le_bytes(query_bytes[..4].try_into().unwrap()) as usize;
+    let paths_offset = 4 + num_value_bytes + 4;
+    let num_paths = query_bytes[paths_offset] as usize;
+    let mut offset = paths_offset + 1 + (num_paths + 7) / 8;
+    for i in 0..num_paths {
+        let num_nodes = query_bytes[offset] as usize;
+        if query_bytes[paths_offset + 1 + i / 8] & (1 << (i % 8)) != 0 {
+            query_bytes[offset + 1] ^= 1;
+            break;
+        }
+        offset += 1 + num_nodes * 32;
+    }
+    tampered_proof.trace_queries[0] =
+        Queries::read_from(&mut SliceReader::new(&query_bytes)).unwrap();
+    assert_eq!(
+        Err(VerifierError::TraceQueryDoesNotMatchCommitment),
+        fib.verify(tampered_proof)
+    );
+}
+
+#[test]
+fn fib_small_test_grinding_hash() {
+    let result = compute_fib_term::<BaseElement>(16);
+    let verify = |proof| winterfell::verify::<FibSmall, Rp64_256>(proof, result);
+
+    // the proof hash function is Rescue Prime, but grinding uses byte-oriented hash functions
+    let options = ProofOptions::new(28, 8, 12, FieldExtension::Quadratic, 4, 256);
+    let option_sets = [
+        options
+            .clone()
+            .with_grinding_hash(HashFunctionId::Blake3_256),
+        options
+            .clone()
+            .with_grinding_hash(HashFunctionId::Blake3_256)
+            .with_wide_grinding_nonce(),
+        options
+            .clone()
+            .with_transcript_profile(TranscriptProfile::EthStarkCompat)
+            .with_grinding_hash(HashFunctionId::Sha3_256)
+            .with_wide_grinding_nonce(),
+        options
+            .clone()
+            .with_transcript_profile(TranscriptProfile::Arithmetic)
+            .with_grinding_hash(HashFunctionId::Blake3_256),
+    ];
+    for grinding_options in option_sets {
+        let prover = FibSmallProver::<Rp64_256>::new(grinding_options.clone());
+        let proof = prover.prove(prover.build_trace(16)).unwrap();
+        assert_eq!(&grinding_options, proof.options());
+        assert_eq!(
+            grinding_options.grinding_nonce_bytes(),
+            proof.size_breakdown().pow_nonce()
+        );
+
+        let proof = StarkProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(Ok(()), verify(proof.clone()));
+
+        // the nonce must provide the proof-of-work bits under the grinding hash function
+        let mut tampered = proof.clone();
+        tampered.pow_nonce += 1;
+        assert_eq!(
+            Err(VerifierError::QuerySeedProofOfWorkVerificationFailed),
+            verify(tampered)
+        );
+
+        // a narrow nonce cannot have bits set beyond the lower 64 bits; for a wide nonce, these
+        // bits change the proof-of-work
+        let mut tampered = proof.clone();
+        tampered.pow_nonce |= 1 << 64;
+        assert!(verify(tampered).is_err());
+
+        // the grinding hash function is recorded in the proof context
+        let mut stripped = proof;
+        let trace_info = stripped.get_trace_info();
+        stripped.context = Context::new::<BaseElement>(&trace_info, options.clone());
+        assert!(verify(stripped).is_err());
+    }
+
+    // algebraic hash functions over a different field cannot be used for grinding
+    let options = options.with_grinding_hash(HashFunctionId::Poseidon128_256);
+    let prover = FibSmallProver::<Rp64_256>::new(options.clone());
+    let error = prover.prove(prover.build_trace(16)).unwrap_err();
+    assert_eq!(
+        ProverError::UnsupportedGrindingHash(HashFunctionId::Poseidon128_256),
+        error
+    );
+
+    let fib = FibExample::<Rp64_256>::new(16, build_proof_options(true));
+    let mut proof = fib.prove();
+    proof.context = Context::new::<BaseElement>(&proof.get_trace_info(), options);
+    assert_eq!(
+        Err(VerifierError::UnsupportedGrindingHash(
+            HashFunctionId::Poseidon128_256
+        )),
+        fib.verify(proof)
+    );
+}
+
+#[test]
+fn fib_small_test_random_coin() {
+    let result = compute_fib_term::<BaseElement>(16);
+    let verify_sponge = |proof| {
+        winterfell::verify_with_random_coin::<FibSmall, Rp64_256, SpongeCoin>(proof, result)
+    };
+
+    let option_sets = [
+        build_proof_options(true),
+        build_proof_options(true).with_transcript_profile(TranscriptProfile::Arithmetic),
+    ];
+    for options in option_sets {
+        let prover = FibSmallProver::<Rp64_256>::new(options);
+        let proof = prover
+            .prove_with_random_coin::<SpongeCoin>(prover.build_trace(16))
+            .unwrap();
+        assert_eq!(SpongeCoin::COIN_ID, proof.context.random_coin());
+
+        // the proof survives a serialization round trip, and is verified with the same coin only
+        // against the correct public inputs
+        let proof = StarkProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(Ok(()), verify_sponge(proof.clone()));
+        assert!(
+            winterfell::verify_with_random_coin::<FibSmall, Rp64_256, SpongeCoin>(
+                proof.clone(),
+                result + BaseElement::ONE
+            )
+            .is_err()
+        );
+
+        // the default verifier draws randomness from the default coin, and thus, rejects it
+        assert_eq!(
+            Err(VerifierError::RandomCoinMismatch {
+                proof: SpongeCoin::COIN_ID,
+                verifier: 0
+            }),
+            winterfell::verify::<FibSmall, Rp64_256>(proof.clone(), result)
+        );
+
+        // removing the coin from the context does not make the proof valid for the default coin
+        let mut stripped = proof;
+        stripped.context = stripped.context.with_random_coin(0);
+        assert!(winterfell::verify::<FibSmall, Rp64_256>(stripped, result).is_err());
+
+        // and a proof generated with the default coin is rejected by the verifier of the sponge
+        let proof = prover.prove(prover.build_trace(16)).unwrap();
+        assert_eq!(
+            Err(VerifierError::RandomCoinMismatch {
+                proof: 0,
+                verifier: SpongeCoin::COIN_ID
+            }),
+            verify_sponge(proof)
+        );
+    }
+
+    // drawing randomness from the default coin explicitly results in the same proof as the
+    // default prover
+    let prover = FibSmallProver::<Rp64_256>::new(build_proof_options(true));
+    let proof = prover.prove(prover.build_trace(16)).unwrap();
+    let default_proof = prover
+        .prove_with_random_coin::<DefaultRandomCoin<BaseElement, Rp64_256>>(prover.build_trace(16))
+        .unwrap();
+    assert_eq!(proof.to_bytes(), default_proof.to_bytes());
+}
+
+/// Random coin which is a duplex sponge over the permutation of [Rp64_256]; digests and seed
+/// elements are absorbed into the rate portion of the state, and elements are squeezed from it.
+#[derive(Clone)]
+struct SpongeCoin {
+    state: [BaseElement; Rp64_256::STATE_WIDTH],
+    num_squeezed: usize,
+}
+
+impl SpongeCoin {
+    /// Overwrites the rate portion of the state with the specified elements (one chunk at a
+    /// time), and applies the permutation after every chunk.
+    fn absorb(&mut self, elements: &[BaseElement]) {
+        for chunk in elements.chunks(Rp64_256::RATE_RANGE.len()) {
+            for (i, &element) in chunk.iter().enumerate() {
+                self.state[Rp64_256::RATE_RANGE.start + i] = element;
+            }
+            Rp64_256::apply_permutation(&mut self.state);
+        }
+        self.num_squeezed = 0;
+    }
+
+    /// Returns the next element of the rate portion of the state; the state is permuted once all
+    /// elements of the rate have been squeezed.
+    fn squeeze(&mut self) -> BaseElement {
+        if self.num_squeezed == Rp64_256::RATE_RANGE.len() {
+            Rp64_256::apply_permutation(&mut self.state);
+            self.num_squeezed = 0;
+        }
+        self.num_squeezed += 1;
+        self.state[Rp64_256::RATE_RANGE.start + self.num_squeezed - 1]
+    }
+
+    /// Returns the first rate element of the state after absorbing the specified `value`.
+    fn peek_with_int(&self, value: u64) -> u64 {
+        let mut coin = self.clone();
+        coin.reseed_with_int(value);
+        coin.squeeze().as_int()
+    }
+}
+
+impl RandomCoin for SpongeCoin {
+    type BaseField = BaseElement;
+    type Hasher = Rp64_256;
+    const COIN_ID: u8 = 1;
+
+    fn new(seed: &[u8]) -> Self {
+        let elements = seed
+            .chunks(7)
+            .map(|chunk| {
+                let mut bytes = [0; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                BaseElement::new(u64::from_le_bytes(bytes))
+            })
+            .collect::<Vec<_>>();
+        let mut coin = Self::from_elements(&elements);
+        coin.reseed_with_int(seed.len() as u64);
+        coin
+    }
+
+    fn from_elements(seed: &[BaseElement]) -> Self {
+        let mut state = [BaseElement::ZERO; Rp64_256::STATE_WIDTH];
+        state[0] = BaseElement::new(seed.len() as u64);
+        let mut coin = Self {
+            state,
+            num_squeezed: 0,
+        };
+        coin.absorb(seed);
+        coin
+    }
+
+    fn reseed(&mut self, data: <Rp64_256 as Hasher>::Digest) {
+        self.absorb(Rp64_256::digest_as_elements(&data).unwrap());
+    }
+
+    fn reseed_with_int(&mut self, value: u64) {
+        let value = [value as u32 as u64, value >> 32].map(BaseElement::new);
+        self.absorb(&value);
+    }
+
+    fn derive_keyed(&self, key: &[u8]) -> Self {
+        let mut coin = self.clone();
+        coin.absorb(&Self::new(key).state[Rp64_256::DIGEST_RANGE]);
+        coin
+    }
+
+    fn check_leading_zeros(&self, value: u64) -> u32 {
+        self.peek_with_int(value).trailing_zeros()
+    }
+
+    fn check_leading_zeros_be(&self, value: u64) -> u32 {
+        self.peek_with_int(value).leading_zeros()
+    }
+
+    fn check_trailing_zeros_in_element(&self, value: u64) -> u32 {
+        self.peek_with_int(value).trailing_zeros()
+    }
+
+    fn draw<E: FieldElement<BaseField = BaseElement>>(&mut self) -> Result<E, RandomCoinError> {
+        let mut bytes = Vec::new();
+        for _ in 0..E::ELEMENT_BYTES / BaseElement::ELEMENT_BYTES {
+            self.squeeze().write_into(&mut bytes);
+        }
+        E::read_from(&mut SliceReader::new(&bytes))
+            .map_err(|_| RandomCoinError::FailedToDrawFieldElement(1))
+    }
+
+    fn draw_integers(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        let mut values = Vec::new();
+        for _ in 0..1000 {
+            let value = (self.squeeze().as_int() % domain_size as u64) as usize;
+            if !values.contains(&value) {
+                values.push(value);
+            }
+            if values.len() == num_values {
+                return Ok(values);
+            }
+        }
+        Err(RandomCoinError::FailedToDrawIntegers(
+            num_values,
+            values.len(),
+            1000,
+        ))
+    }
+
+    fn draw_integers_be(
+        &mut self,
+        num_values: usize,
+        domain_size: usize,
+    ) -> Result<Vec<usize>, RandomCoinError> {
+        self.draw_integers(num_values, domain_size)
+    }
+}
+
+impl Serializable for SpongeCoin {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        for element in self.state {
+            element.write_into(target);
+        }
+        target.write_u8(self.num_squeezed as u8);
+    }
+}
+
+impl Deserializable for SpongeCoin {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let mut state = [BaseElement::ZERO; Rp64_256::STATE_WIDTH];
+        for element in state.iter_mut() {
+            *element = BaseElement::read_from(source)?;
+        }
+        Ok(Self {
+            state,
+            num_squeezed: source.read_u8()? as usize,
+        })
+    }
+}
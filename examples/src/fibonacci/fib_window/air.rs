@@ -0,0 +1,87 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, FieldElement, ProofOptions, TRACE_WIDTH, WINDOW_SIZE};
+use crate::utils::are_equal;
+use winterfell::{
+    Air, AirContext, Assertion, EvaluationFrame, TraceInfo, TransitionConstraintDegree,
+};
+
+// FIBONACCI AIR
+// ================================================================================================
+
+/// AIR for a Fibonacci sequence in which every row of the trace advances the sequence by 2 terms,
+/// but transition constraints relate each row directly to the row located [WINDOW_SIZE] steps
+/// later, rather than to the next row.
+///
+/// The evaluation frame of this AIR consists of the current row and the row at offset
+/// [WINDOW_SIZE]. Since constraints do not relate adjacent rows, the first [WINDOW_SIZE] rows of
+/// the trace are independent of each other and are fixed via assertions.
+pub struct FibWindowAir {
+    context: AirContext<BaseElement>,
+    result: BaseElement,
+}
+
+impl Air for FibWindowAir {
+    type BaseField = BaseElement;
+    type PublicInputs = BaseElement;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: Self::BaseField, options: ProofOptions) -> Self {
+        let degrees = vec![
+            TransitionConstraintDegree::new(1),
+            TransitionConstraintDegree::new(1),
+        ];
+        assert_eq!(TRACE_WIDTH, trace_info.width());
+        let context = AirContext::new(trace_info, degrees, 2 * WINDOW_SIZE + 1, options)
+            .set_frame_offsets(vec![0, WINDOW_SIZE])
+            .set_num_transition_exemptions(WINDOW_SIZE);
+        FibWindowAir {
+            context,
+            result: pub_inputs,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let later = frame.row(1);
+        // expected state width is 2 field elements
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+        debug_assert_eq!(TRACE_WIDTH, later.len());
+
+        // rows which are 4 steps apart are 8 terms apart in the sequence; for terms F(n) and
+        // F(n + 1) in the current row, F(n + 8) = 13 * F(n) + 21 * F(n + 1), and
+        // F(n + 9) = 21 * F(n) + 34 * F(n + 1)
+        let (a, b) = (current[0], current[1]);
+        result[0] = are_equal(later[0], E::from(13u32) * a + E::from(21u32) * b);
+        result[1] = are_equal(later[1], E::from(21u32) * a + E::from(34u32) * b);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        // the first rows of the trace must contain the first terms of the sequence, and the
+        // sequence must terminate with the expected result
+        let mut result = Vec::with_capacity(2 * WINDOW_SIZE + 1);
+        let (mut a, mut b) = (Self::BaseField::ONE, Self::BaseField::ONE);
+        for step in 0..WINDOW_SIZE {
+            result.push(Assertion::single(0, step, a));
+            result.push(Assertion::single(1, step, b));
+            a += b;
+            b += a;
+        }
+        let last_step = self.trace_length() - 1;
+        result.push(Assertion::single(1, last_step, self.result));
+        result
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::utils::compute_fib_term;
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
+use core::marker::PhantomData;
+use log::debug;
+use std::time::Instant;
+use winterfell::{
+    crypto::ElementHasher,
+    math::{fields::f128::BaseElement, log2, FieldElement},
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
+};
+
+mod air;
+use air::FibWindowAir;
+
+mod prover;
+use prover::FibWindowProver;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+const TRACE_WIDTH: usize = 2;
+
+/// Number of trace rows between the two rows related by transition constraints.
+const WINDOW_SIZE: usize = 4;
+
+// FIBONACCI EXAMPLE
+// ================================================================================================
+
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(
+    options: &ExampleOptions,
+    sequence_length: usize,
+) -> Result<Box<dyn Example>, String> {
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
+
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(FibWindowExample::<Blake3_192>::new(
+            sequence_length,
+            options,
+        ))),
+        HashFunction::Blake3_256 => Ok(Box::new(FibWindowExample::<Blake3_256>::new(
+            sequence_length,
+            options,
+        ))),
+        HashFunction::Sha3_256 => Ok(Box::new(FibWindowExample::<Sha3_256>::new(
+            sequence_length,
+            options,
+        ))),
+        HashFunction::Keccak256 => Ok(Box::new(FibWindowExample::<Keccak_256>::new(
+            sequence_length,
+            options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(FibWindowExample::<Blake2s_256>::new(
+            sequence_length,
+            options,
+        ))),
+        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+    }
+}
+
+pub struct FibWindowExample<H: ElementHasher> {
+    options: ProofOptions,
+    sequence_length: usize,
+    result: BaseElement,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> FibWindowExample<H> {
+    pub fn new(sequence_length: usize, options: ProofOptions) -> Self {
+        assert!(
+            sequence_length.is_power_of_two(),
+            "sequence length must be a power of 2"
+        );
+
+        // compute Fibonacci sequence
+        let now = Instant::now();
+        let result = compute_fib_term(sequence_length);
+        debug!(
+            "Computed Fibonacci sequence up to {}th term in {} ms",
+            sequence_length,
+            now.elapsed().as_millis()
+        );
+
+        FibWindowExample {
+            options,
+            sequence_length,
+            result,
+            _hasher: PhantomData,
+        }
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl<H: ElementHasher> Example for FibWindowExample<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for computing Fibonacci sequence (constraints span {} steps) up to \
+            {}th term\n---------------------",
+            WINDOW_SIZE, self.sequence_length
+        );
+
+        // create a prover
+        let prover = FibWindowProver::<H>::new(self.options.clone());
+
+        // generate execution trace
+        let now = Instant::now();
+        let trace = prover.build_trace(self.sequence_length);
+        let trace_width = trace.width();
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace_width,
+            log2(trace_length),
+            now.elapsed().as_millis()
+        );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.result).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement = winterfell::verify_and_extract::<FibWindowAir, H>(proof, self.result)?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        winterfell::verify::<FibWindowAir, H>(proof, self.result + BaseElement::ONE)
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2);
+        let air = FibWindowAir::new(trace_info, self.result, self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    super::utils::{build_proof_options, compute_fib_term},
+    air::FibWindowAir,
+    Air, BaseElement, Blake3_256, FibWindowProver, FieldElement, PhantomData, ProofOptions, Prover,
+    Trace, TraceTable, TRACE_WIDTH, WINDOW_SIZE,
+};
+use crate::utils::are_equal;
+use winterfell::{
+    AirContext, Assertion, EvaluationFrame, FieldExtension, TraceInfo, TransitionConstraintDegree,
+};
+
+#[test]
+fn fib_window_test_basic_proof_verification() {
+    let fib = Box::new(super::FibWindowExample::<Blake3_256>::new(
+        64,
+        build_proof_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_window_test_basic_proof_verification_extension() {
+    let fib = Box::new(super::FibWindowExample::<Blake3_256>::new(
+        64,
+        build_proof_options(true),
+    ));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_window_test_basic_proof_verification_fail() {
+    let fib = Box::new(super::FibWindowExample::<Blake3_256>::new(
+        64,
+        build_proof_options(false),
+    ));
+    crate::tests::test_basic_proof_verification_fail(fib);
+}
+
+#[test]
+fn fib_window_test_multiple_ood_points() {
+    let options = build_proof_options(true).with_num_ood_points(2);
+    let fib = Box::new(super::FibWindowExample::<Blake3_256>::new(64, options));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_window_test_minimal_trace() {
+    // the shortest trace has 8 rows, and thus, the windowed constraints apply to the first 4 rows
+    let fib = Box::new(super::FibWindowExample::<Blake3_256>::new(
+        16,
+        build_proof_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(fib);
+}
+
+#[test]
+fn fib_window_test_frame_offsets() {
+    let air = FibWindowAir::new(
+        TraceInfo::new(TRACE_WIDTH, 32),
+        compute_fib_term(64),
+        build_proof_options(false),
+    );
+    assert_eq!(&[0, WINDOW_SIZE], air.context().frame_offsets());
+}
+
+#[test]
+fn fib_window_test_three_row_frame() {
+    for (extension, num_ood_points) in [
+        (FieldExtension::None, 1),
+        (FieldExtension::Quadratic, 1),
+        (FieldExtension::Quadratic, 2),
+    ] {
+        let options =
+            ProofOptions::new(28, 8, 0, extension, 4, 256).with_num_ood_points(num_ood_points);
+        let prover = ThreeRowProver::new(options);
+        let trace = FibWindowProver::<Blake3_256>::new(prover.options.clone()).build_trace(64);
+        let proof = prover.prove(trace).unwrap();
+
+        let result = compute_fib_term(64);
+        assert!(winterfell::verify::<ThreeRowAir, Blake3_256>(proof.clone(), result).is_ok());
+        assert!(
+            winterfell::verify::<ThreeRowAir, Blake3_256>(proof, result + BaseElement::ONE)
+                .is_err()
+        );
+    }
+}
+
+// THREE-ROW AIR
+// ================================================================================================
+
+/// An AIR for the same trace as [FibWindowAir] whose evaluation frame consists of the current
+/// row, and rows at offsets 2 and 4; rows at offset 2 are 4 terms ahead in the sequence, and thus,
+/// F(n + 4) = 2 * F(n) + 3 * F(n + 1) and F(n + 5) = 3 * F(n) + 5 * F(n + 1).
+struct ThreeRowAir {
+    context: AirContext<BaseElement>,
+    result: BaseElement,
+}
+
+impl Air for ThreeRowAir {
+    type BaseField = BaseElement;
+    type PublicInputs = BaseElement;
+
+    fn new(trace_info: TraceInfo, pub_inputs: Self::BaseField, options: ProofOptions) -> Self {
+        let degrees = vec![TransitionConstraintDegree::new(1); 4];
+        let context = AirContext::new(trace_info, degrees, 2 * WINDOW_SIZE + 1, options)
+            .set_frame_offsets(vec![0, 2, 4])
+            .set_transition_exemptions(vec![2, 2, 4, 4]);
+        ThreeRowAir {
+            context,
+            result: pub_inputs,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let (a, b) = (frame.current()[0], frame.current()[1]);
+        let (middle, last) = (frame.row(1), frame.row(2));
+        result[0] = are_equal(middle[0], E::from(2u32) * a + E::from(3u32) * b);
+        result[1] = are_equal(middle[1], E::from(3u32) * a + E::from(5u32) * b);
+        result[2] = are_equal(last[0], E::from(13u32) * a + E::from(21u32) * b);
+        result[3] = are_equal(last[1], E::from(21u32) * a + E::from(34u32) * b);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let mut result = Vec::new();
+        let (mut a, mut b) = (BaseElement::ONE, BaseElement::ONE);
+        for step in 0..WINDOW_SIZE {
+            result.push(Assertion::single(0, step, a));
+            result.push(Assertion::single(1, step, b));
+            a += b;
+            b += a;
+        }
+        result.push(Assertion::single(1, self.trace_length() - 1, self.result));
+        result
+    }
+}
+
+struct ThreeRowProver {
+    options: ProofOptions,
+    _hasher: PhantomData<Blake3_256>,
+}
+
+impl ThreeRowProver {
+    fn new(options: ProofOptions) -> Self {
+        Self {
+            options,
+            _hasher: PhantomData,
+        }
+    }
+}
+
+impl Prover for ThreeRowProver {
+    type BaseField = BaseElement;
+    type Air = ThreeRowAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> BaseElement {
+        trace.get(1, trace.length() - 1)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
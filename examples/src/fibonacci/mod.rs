@@ -3,10 +3,16 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+#[cfg(feature = "f128")]
 pub mod fib2;
+#[cfg(feature = "f128")]
 pub mod fib8;
 pub mod fib_small;
+#[cfg(feature = "f128")]
+pub mod fib_window;
+#[cfg(feature = "f128")]
 pub mod mulfib2;
+#[cfg(feature = "f128")]
 pub mod mulfib8;
 
-mod utils;
+pub mod utils;
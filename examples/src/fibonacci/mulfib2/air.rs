@@ -3,18 +3,17 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use super::TRACE_WIDTH;
 use crate::utils::are_equal;
 use winterfell::{
     math::{fields::f128::BaseElement, FieldElement},
-    Air, AirContext, Assertion, EvaluationFrame, ProofOptions, TraceInfo,
-    TransitionConstraintDegree,
+    Air, AirContext, Assertion, BoundaryExpr, EvaluationFrame, ProofOptions, StepSelector,
+    TraceInfo, TransitionConstraintDegree,
 };
 
 // FIBONACCI AIR
 // ================================================================================================
 
-const TRACE_WIDTH: usize = 2;
-
 pub struct MulFib2Air {
     context: AirContext<BaseElement>,
     result: BaseElement,
@@ -32,8 +31,12 @@ impl Air for MulFib2Air {
             TransitionConstraintDegree::new(2),
         ];
         assert_eq!(TRACE_WIDTH, trace_info.width());
+        // the product of the registers at the first step is enforced by a boundary expression
+        // of degree 2
+        let context =
+            AirContext::new(trace_info, degrees, 2, options).set_boundary_expr_degrees(vec![2]);
         MulFib2Air {
-            context: AirContext::new(trace_info, degrees, 3, options),
+            context,
             result: pub_inputs,
         }
     }
@@ -62,13 +65,25 @@ impl Air for MulFib2Air {
     }
 
     fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
-        // a valid multiplicative Fibonacci sequence should start with 1, 2 and terminate
-        // with the expected result
+        // a valid multiplicative Fibonacci sequence should start with 1 and terminate with the
+        // expected result; the second term is enforced by the boundary expression below
         let last_step = self.trace_length() - 1;
         vec![
             Assertion::single(0, 0, Self::BaseField::new(1)),
-            Assertion::single(1, 0, Self::BaseField::new(2)),
             Assertion::single(0, last_step, self.result),
         ]
     }
+
+    fn get_boundary_exprs<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+    ) -> Vec<BoundaryExpr<E>> {
+        // since the first term is 1, requiring the product of the first two terms to be 2 is
+        // the same as requiring the second term to be 2
+        vec![BoundaryExpr::new(
+            StepSelector::First,
+            |row| row[0] * row[1],
+            2,
+            E::from(2u32),
+        )]
+    }
 }
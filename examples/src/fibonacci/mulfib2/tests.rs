@@ -3,7 +3,10 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{super::utils::build_proof_options, Blake3_256};
+use super::{
+    super::utils::build_proof_options, BaseElement, Blake3_256, FieldElement, MulFib2Air,
+    MulFib2Prover, Prover, Trace,
+};
 
 #[test]
 fn mulfib2_test_basic_proof_verification() {
@@ -31,3 +34,28 @@ fn mulfib2_test_basic_proof_verification_fail() {
     ));
     crate::tests::test_basic_proof_verification_fail(fib);
 }
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "trace does not satisfy boundary expression 0 at step 0")
+)]
+fn mulfib2_test_boundary_expr_rejected_by_verifier() {
+    let prover = MulFib2Prover::<Blake3_256>::new(build_proof_options(false));
+    let mut trace = prover.build_trace(16);
+
+    // start the sequence with 1, 3 instead of 1, 2; all transition constraints and assertions
+    // are still satisfied, but the product of the first two terms is not 2. in debug builds,
+    // the prover catches this when validating the trace against the AIR.
+    trace.set(1, 0, BaseElement::new(3));
+    for step in 1..trace.length() {
+        let reg0 = trace.get(0, step - 1) * trace.get(1, step - 1);
+        trace.set(0, step, reg0);
+        trace.set(1, step, trace.get(1, step - 1) * reg0);
+    }
+    assert_eq!(BaseElement::ONE, trace.get(0, 0));
+
+    let result = trace.get(0, trace.length() - 1);
+    let proof = prover.prove(trace).unwrap();
+    assert!(winterfell::verify::<MulFib2Air, Blake3_256>(proof, result).is_err());
+}
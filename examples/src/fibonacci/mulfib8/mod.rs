@@ -4,14 +4,20 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::utils::compute_mulfib_term;
-use crate::{Blake3_192, Blake3_256, Example, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f128::BaseElement, log2, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
 };
 
 mod air;
@@ -23,14 +29,24 @@ use prover::MulFib8Prover;
 #[cfg(test)]
 mod tests;
 
+// CONSTANTS
+// ================================================================================================
+
+const TRACE_WIDTH: usize = 8;
+
 // FIBONACCI EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     sequence_length: usize,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(28, 8);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(MulFib8Example::<Blake3_192>::new(
@@ -45,6 +61,14 @@ pub fn get_example(
             sequence_length,
             options,
         ))),
+        HashFunction::Keccak256 => Ok(Box::new(MulFib8Example::<Keccak_256>::new(
+            sequence_length,
+            options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(MulFib8Example::<Blake2s_256>::new(
+            sequence_length,
+            options,
+        ))),
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
@@ -110,16 +134,24 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.result).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        winterfell::verify::<MulFib8Air, H>(proof, self.result)
+        let statement = winterfell::verify_and_extract::<MulFib8Air, H>(proof, self.result)?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
         winterfell::verify::<MulFib8Air, H>(proof, self.result + BaseElement::ONE)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.sequence_length / 8);
+        let air = MulFib8Air::new(trace_info, self.result, self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
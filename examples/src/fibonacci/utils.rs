@@ -3,7 +3,9 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use winterfell::math::{fields::f128::BaseElement, FieldElement};
+#[cfg(feature = "f128")]
+use winterfell::math::fields::f128::BaseElement;
+use winterfell::math::FieldElement;
 
 pub fn compute_fib_term<E: FieldElement>(n: usize) -> E {
     let mut t0 = E::ONE;
@@ -17,6 +19,7 @@ pub fn compute_fib_term<E: FieldElement>(n: usize) -> E {
     t1
 }
 
+#[cfg(feature = "f128")]
 pub fn compute_mulfib_term(n: usize) -> BaseElement {
     let mut t0 = BaseElement::ONE;
     let mut t1 = BaseElement::new(2);
@@ -29,7 +32,6 @@ pub fn compute_mulfib_term(n: usize) -> BaseElement {
     t1
 }
 
-#[cfg(test)]
 pub fn build_proof_options(use_extension_field: bool) -> winterfell::ProofOptions {
     use winterfell::{FieldExtension, ProofOptions};
 
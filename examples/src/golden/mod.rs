@@ -0,0 +1,200 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Golden proofs for checking that proof generation is reproducible across platforms.
+//!
+//! Proof generation is fully deterministic: given the same statement and the same proof options,
+//! the prover produces byte-for-byte identical proofs regardless of the platform it runs on and
+//! regardless of whether `concurrent` feature is enabled. To guard against divergence (e.g., in
+//! field arithmetic or hashing), this module proves a fixed set of instances and compares BLAKE3
+//! digests of the serialized proofs against digests checked into `digests.txt`.
+//!
+//! The digests are checked by the `golden_digests` test, and can be checked or regenerated via
+//! the `golden` command of the examples binary:
+//!
+//! ```text
+//! cargo run --release -p examples -- golden
+//! cargo run --release -p examples -- golden --update
+//! ```
+//!
+//! Digests should be regenerated only when a change to the protocol or to one of the instances
+//! is expected to change the proofs; such changes must be accompanied by the updated digests.
+
+use crate::{runner::build_example, Example, ExampleOptions};
+use core::fmt;
+use structopt::StructOpt;
+use winterfell::StarkProof;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Path to the file containing the expected digests of golden proofs.
+pub const DIGESTS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/golden/digests.txt");
+
+/// Contents of the file containing the expected digests of golden proofs.
+const EXPECTED_DIGESTS: &str = include_str!("digests.txt");
+
+/// The set of instances for which golden proofs are generated. Arguments are interpreted in the
+/// same way as command-line arguments of the examples binary; together, the instances cover both
+/// `f128` and `f64` fields, all field extensions, and arithmetization-friendly hash functions.
+pub const GOLDEN_INSTANCES: [GoldenInstance; 8] = [
+    GoldenInstance::new("fib", &["-g", "8", "fib", "-n", "64"]),
+    GoldenInstance::new("fib_quadratic", &["-g", "8", "-e", "2", "fib", "-n", "64"]),
+    GoldenInstance::new("fib_small", &["-g", "8", "fib-small", "-n", "64"]),
+    GoldenInstance::new(
+        "fib_small_cubic",
+        &["-g", "8", "-e", "3", "fib-small", "-n", "64"],
+    ),
+    GoldenInstance::new(
+        "fib_small_rp64_256",
+        &["-g", "8", "-h", "rp64_256", "fib-small", "-n", "64"],
+    ),
+    GoldenInstance::new("vm", &["-g", "8", "vm", "-n", "64"]),
+    GoldenInstance::new("rom", &["-g", "8", "rom", "-n", "64"]),
+    GoldenInstance::new("rescue", &["-g", "8", "rescue", "-n", "8"]),
+];
+
+// GOLDEN INSTANCE
+// ================================================================================================
+
+/// A named instance of one of the examples for which a golden proof is generated.
+#[derive(Debug, Clone, Copy)]
+pub struct GoldenInstance {
+    name: &'static str,
+    args: &'static [&'static str],
+}
+
+impl GoldenInstance {
+    /// Returns a new instance with the specified name; `args` are parsed as command-line
+    /// arguments of the examples binary.
+    pub const fn new(name: &'static str, args: &'static [&'static str]) -> Self {
+        Self { name, args }
+    }
+
+    /// Returns the name of this instance.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Instantiates the example described by this instance.
+    ///
+    /// # Panics
+    /// Panics if the arguments of this instance are not valid.
+    pub fn build(&self) -> Box<dyn Example> {
+        let args = core::iter::once("winterfell").chain(self.args.iter().copied());
+        let options = ExampleOptions::from_iter_safe(args)
+            .unwrap_or_else(|err| panic!("invalid arguments for {}: {}", self.name, err));
+        build_example(&options, &options.example)
+            .unwrap_or_else(|err| panic!("failed to instantiate {}: {}", self.name, err))
+    }
+
+    /// Generates a proof for this instance.
+    ///
+    /// # Panics
+    /// Panics if the arguments of this instance are not valid.
+    pub fn prove(&self) -> StarkProof {
+        self.build().prove()
+    }
+
+    /// Returns a hex-encoded BLAKE3 digest of a serialized proof for this instance.
+    pub fn digest(&self) -> String {
+        hex::encode(blake3::hash(&self.prove().to_bytes()).as_bytes())
+    }
+}
+
+// DIGESTS
+// ================================================================================================
+
+/// Generates golden proofs for all instances and returns the names of the instances together
+/// with the digests of their proofs.
+pub fn compute_digests() -> Vec<(&'static str, String)> {
+    GOLDEN_INSTANCES
+        .iter()
+        .map(|instance| (instance.name(), instance.digest()))
+        .collect()
+}
+
+/// Returns the names of the instances together with the digests of their proofs as recorded in
+/// the checked-in digests file.
+pub fn expected_digests() -> Vec<(&'static str, &'static str)> {
+    EXPECTED_DIGESTS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next().expect("missing instance name");
+            let digest = parts.next().expect("missing proof digest");
+            (name, digest)
+        })
+        .collect()
+}
+
+/// Generates golden proofs for all instances and compares their digests against the checked-in
+/// digests; returns a list of mismatches, which is empty if all digests match.
+pub fn check_digests() -> Vec<DigestMismatch> {
+    let expected = expected_digests();
+    compute_digests()
+        .into_iter()
+        .filter_map(|(name, actual)| {
+            let expected = expected
+                .iter()
+                .find(|(expected_name, _)| *expected_name == name)
+                .map(|(_, digest)| digest.to_string());
+            (expected.as_deref() != Some(actual.as_str())).then(|| DigestMismatch {
+                name,
+                expected,
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// Renders the specified digests in the format of the checked-in digests file.
+pub fn render_digests(digests: &[(&str, String)]) -> String {
+    let mut result = String::from(
+        "# BLAKE3 digests of golden proofs; regenerate with:\n\
+         # cargo run --release -p examples -- golden --update\n",
+    );
+    for (name, digest) in digests {
+        result.push_str(&format!("{name} {digest}\n"));
+    }
+    result
+}
+
+// DIGEST MISMATCH
+// ================================================================================================
+
+/// Describes an instance for which the digest of the generated proof differs from the checked-in
+/// digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestMismatch {
+    /// Name of the instance.
+    pub name: &'static str,
+    /// The checked-in digest, or None if no digest was recorded for the instance.
+    pub expected: Option<String>,
+    /// Digest of the generated proof.
+    pub actual: String,
+}
+
+impl fmt::Display for DigestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.expected {
+            Some(expected) => write!(
+                f,
+                "proof digest for {} is {}, but expected {}",
+                self.name, self.actual, expected
+            ),
+            None => write!(
+                f,
+                "proof digest for {} is {}, but no digest was recorded",
+                self.name, self.actual
+            ),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{check_digests, expected_digests, render_digests, GOLDEN_INSTANCES};
+
+#[test]
+fn golden_digests() {
+    let mismatches = check_digests();
+    assert!(
+        mismatches.is_empty(),
+        "golden proofs have changed:\n{}",
+        mismatches
+            .iter()
+            .map(|mismatch| mismatch.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn golden_digests_file_lists_all_instances() {
+    let expected = expected_digests();
+    let names = expected.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let instances = GOLDEN_INSTANCES
+        .iter()
+        .map(|instance| instance.name())
+        .collect::<Vec<_>>();
+    assert_eq!(instances, names);
+
+    // rendering the recorded digests must reproduce the checked-in file
+    let digests = expected
+        .iter()
+        .map(|(name, digest)| (*name, digest.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(super::EXPECTED_DIGESTS, render_digests(&digests));
+}
@@ -20,7 +20,7 @@ const TWO: BaseElement = BaseElement::new(2);
 // AGGREGATE LAMPORT PLUS SIGNATURE AIR
 // ================================================================================================
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PublicInputs {
     pub pub_keys: Vec<[BaseElement; 2]>,
     pub messages: Vec<[BaseElement; 2]>,
@@ -6,14 +6,20 @@
 use super::{
     message_to_elements, rescue, Example, PrivateKey, Signature, CYCLE_LENGTH, NUM_HASH_ROUNDS,
 };
-use crate::{Blake3_192, Blake3_256, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, ExampleDefaults, ExampleOptions, FieldType, HashFunction,
+    Keccak_256, Sha3_256,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f128::BaseElement, get_power_series, log2, FieldElement, StarkField},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
 };
 
 mod air;
@@ -30,11 +36,16 @@ const SIG_CYCLE_LENGTH: usize = 128 * CYCLE_LENGTH; // 1024 steps
 
 // LAMPORT MULTI-MESSAGE, MULTI-KEY, SIGNATURE EXAMPLE
 // ================================================================================================
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     num_signatures: usize,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(28, 8);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(LamportAggregateExample::<Blake3_192>::new(
@@ -49,6 +60,14 @@ pub fn get_example(
             num_signatures,
             options,
         ))),
+        HashFunction::Keccak256 => Ok(Box::new(LamportAggregateExample::<Keccak_256>::new(
+            num_signatures,
+            options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(LamportAggregateExample::<Blake2s_256>::new(
+            num_signatures,
+            options,
+        ))),
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
@@ -119,6 +138,13 @@ impl<H: ElementHasher> LamportAggregateExample<H> {
             _hasher: PhantomData,
         }
     }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            pub_keys: self.pub_keys.clone(),
+            messages: self.messages.clone(),
+        }
+    }
 }
 
 // EXAMPLE IMPLEMENTATION
@@ -149,17 +175,16 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs = PublicInputs {
-            pub_keys: self.pub_keys.clone(),
-            messages: self.messages.clone(),
-        };
-        winterfell::verify::<LamportAggregateAir, H>(proof, pub_inputs)
+        let statement =
+            winterfell::verify_and_extract::<LamportAggregateAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -171,4 +196,10 @@ where
         };
         winterfell::verify::<LamportAggregateAir, H>(proof, pub_inputs)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, SIG_CYCLE_LENGTH * self.messages.len());
+        let air = LamportAggregateAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
@@ -7,14 +7,20 @@ use super::{
     message_to_elements, rescue, Example, PrivateKey, Signature, CYCLE_LENGTH as HASH_CYCLE_LENGTH,
     NUM_HASH_ROUNDS,
 };
-use crate::{Blake3_192, Blake3_256, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, ExampleDefaults, ExampleOptions, FieldType, HashFunction,
+    Keccak_256, Sha3_256,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f128::BaseElement, get_power_series, log2, FieldElement, StarkField},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
 };
 
 mod signature;
@@ -35,11 +41,16 @@ const SIG_CYCLE_LENGTH: usize = 128 * HASH_CYCLE_LENGTH; // 1024 steps
 // LAMPORT THRESHOLD SIGNATURE EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     num_signers: usize,
 ) -> Result<Box<dyn Example>, String> {
-    let (_, hash_fn) = options.to_proof_options(28, 8);
+    let (_, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(LamportThresholdExample::<Blake3_192>::new(
@@ -54,6 +65,14 @@ pub fn get_example(
             num_signers,
             options,
         ))),
+        HashFunction::Keccak256 => Ok(Box::new(LamportThresholdExample::<Keccak_256>::new(
+            num_signers,
+            options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(LamportThresholdExample::<Blake2s_256>::new(
+            num_signers,
+            options,
+        ))),
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
@@ -99,7 +118,7 @@ impl<H: ElementHasher> LamportThresholdExample<H> {
             now.elapsed().as_millis()
         );
 
-        let (options, _) = options.to_proof_options(28, 8);
+        let (options, _) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
         LamportThresholdExample {
             options,
@@ -109,6 +128,15 @@ impl<H: ElementHasher> LamportThresholdExample<H> {
             _hasher: PhantomData,
         }
     }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            pub_key_root: self.pub_key.root().to_elements(),
+            num_pub_keys: self.pub_key.num_keys(),
+            num_signatures: self.signatures.len(),
+            message: self.message,
+        }
+    }
 }
 
 // EXAMPLE IMPLEMENTATION
@@ -145,19 +173,16 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs = PublicInputs {
-            pub_key_root: self.pub_key.root().to_elements(),
-            num_pub_keys: self.pub_key.num_keys(),
-            num_signatures: self.signatures.len(),
-            message: self.message,
-        };
-        winterfell::verify::<LamportThresholdAir, H>(proof, pub_inputs)
+        let statement =
+            winterfell::verify_and_extract::<LamportThresholdAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -169,6 +194,14 @@ where
         };
         winterfell::verify::<LamportThresholdAir, H>(proof, pub_inputs)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        // a signature cycle is allocated for each public key, padded to the next power of two
+        let num_cycles = self.pub_key.num_keys().next_power_of_two();
+        let trace_info = TraceInfo::new(TRACE_WIDTH, SIG_CYCLE_LENGTH * num_cycles);
+        let air = LamportThresholdAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
 
 // HELPER FUNCTIONS
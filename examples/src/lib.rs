@@ -2,24 +2,59 @@
 //
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
+
+use differential::TraceCell;
+use explain::ParamsReport;
 use structopt::StructOpt;
 use winterfell::{
-    crypto::hashers::{GriffinJive64_256, Rp64_256, RpJive64_256},
-    math::fields::f128::BaseElement,
-    FieldExtension, ProofOptions, StarkProof, VerifierError,
+    crypto::{
+        hashers::{GriffinJive64_256, Poseidon64_256, Rp64_256, RpJive64_256},
+        Hasher,
+    },
+    AirDescription, BatchStarkProof, FieldExtension, ProofOptions, ProverError, SecurityEstimate,
+    StarkProof, VerifierError,
 };
 
-pub mod fibonacci;
 #[cfg(feature = "std")]
+use winterfell::planning::CostEstimate;
+
+// most of the examples are defined over the 128-bit field; when `f128` feature is disabled, only
+// the examples defined over the 64-bit field are available
+#[cfg(feature = "f128")]
+use winterfell::math::fields::f128::BaseElement;
+#[cfg(not(feature = "f128"))]
+use winterfell::math::fields::f64::BaseElement;
+
+#[cfg(feature = "f128")]
+pub mod collatz;
+#[cfg(all(feature = "std", feature = "f128"))]
+pub mod compat;
+pub mod differential;
+pub mod explain;
+pub mod fibonacci;
+#[cfg(all(feature = "std", feature = "f128"))]
+pub mod golden;
+#[cfg(all(feature = "std", feature = "f128"))]
 pub mod lamport;
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", feature = "f128"))]
 pub mod merkle;
+pub mod profile;
+#[cfg(feature = "f128")]
 pub mod rescue;
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", feature = "f128"))]
 pub mod rescue_raps;
+#[cfg(feature = "f128")]
+pub mod rom;
+#[cfg(feature = "f128")]
+pub mod rotate;
+pub mod runner;
+#[cfg(all(feature = "std", feature = "f128"))]
+pub mod statistics;
 pub mod utils;
+#[cfg(feature = "f128")]
 pub mod vdf;
-pub mod collatz;
+#[cfg(feature = "f128")]
+pub mod vm;
 
 #[cfg(test)]
 mod tests;
@@ -27,14 +62,82 @@ mod tests;
 // TYPES AND INTERFACES
 // ================================================================================================
 
+#[allow(non_camel_case_types)]
+pub type Blake2s_256 = winterfell::crypto::hashers::Blake2s_256<BaseElement>;
 pub type Blake3_192 = winterfell::crypto::hashers::Blake3_192<BaseElement>;
 pub type Blake3_256 = winterfell::crypto::hashers::Blake3_256<BaseElement>;
 pub type Sha3_256 = winterfell::crypto::hashers::Sha3_256<BaseElement>;
+#[allow(non_camel_case_types)]
+pub type Keccak_256 = winterfell::crypto::hashers::Keccak_256<BaseElement>;
 
 pub trait Example {
     fn prove(&self) -> StarkProof;
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError>;
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError>;
+
+    /// Returns a batch of proofs for the specified number of instances of this example, or None
+    /// if the example does not support batch proofs.
+    fn prove_batch(&self, _num_proofs: usize) -> Option<BatchStarkProof> {
+        None
+    }
+
+    /// Verifies every proof in the batch and returns the result of verifying each proof.
+    ///
+    /// By default, the proofs are verified one by one via [verify()](Example::verify).
+    fn verify_batch(&self, batch: BatchStarkProof) -> Vec<Result<(), VerifierError>> {
+        batch
+            .into_proofs()
+            .into_iter()
+            .map(|proof| self.verify(proof))
+            .collect()
+    }
+
+    /// Generates a proof for this example from an execution trace in which the value of the
+    /// specified cell is incremented by one, or returns None if the example does not support
+    /// trace perturbations.
+    ///
+    /// The perturbed trace is validated against the AIR before a proof is generated, and thus, a
+    /// perturbation which makes the trace invalid results in [ProverError::InvalidTrace].
+    fn prove_perturbed(&self, _cell: TraceCell) -> Option<Result<StarkProof, ProverError>> {
+        None
+    }
+
+    /// Returns a description of the AIR against which proofs for this example are verified, or
+    /// None if the example does not support descriptions.
+    fn describe(&self) -> Option<AirDescription> {
+        None
+    }
+
+    /// Returns a report of STARK protocol parameters derived from the AIR and proof options of
+    /// this example, or None if the example does not support such reports.
+    fn explain_params(&self) -> Option<ParamsReport> {
+        None
+    }
+
+    /// Returns estimated time and peak memory of generating a proof for this example (without
+    /// generating the proof), or None if the example does not support cost estimation.
+    #[cfg(feature = "std")]
+    fn estimate_cost(&self) -> Option<CostEstimate> {
+        None
+    }
+}
+
+// EXAMPLE DEFAULTS
+// ================================================================================================
+
+/// Parameters from which default proof options of an example are derived.
+///
+/// Default options depend on the field and the hash function with which the example is
+/// instantiated: the field extension is the smallest one which meets the target security for LDE
+/// domains of up to [ProofOptions::MAX_SECURITY_LDE_DOMAIN_SIZE] elements, and the number of
+/// queries is the smallest one which meets the target security for the blowup and grinding
+/// factors in use. The target is capped at the collision resistance of the hash function.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ExampleDefaults {
+    /// Blowup factor for low degree extension.
+    pub blowup_factor: usize,
+    /// Conjectured security level (in bits) targeted by default proof options.
+    pub target_security: u32,
 }
 
 // EXAMPLE OPTIONS
@@ -50,6 +153,11 @@ pub struct ExampleOptions {
     #[structopt(short = "h", long = "hash_fn", default_value = "blake3_256")]
     hash_fn: String,
 
+    /// Base field of the example (f62, f64, or f128); if not specified, the default field of the
+    /// example is used. Examples which can be instantiated over a single field only ignore this
+    #[structopt(long = "field")]
+    field: Option<String>,
+
     /// Number of queries to include in a proof
     #[structopt(short = "q", long = "queries")]
     num_queries: Option<usize>,
@@ -62,47 +170,144 @@ pub struct ExampleOptions {
     #[structopt(short = "g", long = "grinding", default_value = "16")]
     grinding_factor: u32,
 
-    /// Field extension degree for composition polynomial
-    #[structopt(short = "e", long = "field_extension", default_value = "1")]
-    field_extension: u32,
+    /// Field extension degree for composition polynomial; if not specified, the smallest degree
+    /// which meets the target security of the example is used
+    #[structopt(short = "e", long = "field_extension")]
+    field_extension: Option<u32>,
 
     /// Folding factor for FRI protocol
     #[structopt(short = "f", long = "folding", default_value = "8")]
     folding_factor: usize,
+
+    /// Print parameters derived from the AIR and proof options (e.g., domain sizes, constraint
+    /// degrees, and FRI layers) before generating a proof
+    #[structopt(long = "explain-params")]
+    pub explain_params: bool,
+
+    /// Print a breakdown of the proof security level (i.e., contributions of the field, queries,
+    /// grinding, and the hash function) and of the proof size (i.e., sizes of proof components)
+    /// after generating a proof
+    #[structopt(long = "verbose")]
+    pub verbose: bool,
+
+    /// Path of a file into which the proof is written wrapped into an archival envelope; the
+    /// proof is read back from this file before it is verified
+    #[structopt(long = "archive")]
+    pub archive: Option<String>,
+
+    /// Path of a file into which folded stacks of prover phases are written
+    #[structopt(long = "profile")]
+    pub profile: Option<String>,
+
+    /// Path of a file into which the transcript log of the prover is written; the log of the
+    /// verifier is written into the same path with `.verifier` suffix appended to it
+    #[cfg(feature = "transcript-log")]
+    #[structopt(long = "dump-transcript")]
+    pub dump_transcript: Option<String>,
+
+    /// Path of a file into which transition constraints which the execution trace does not
+    /// satisfy are written
+    #[cfg(feature = "trace-constraints")]
+    #[structopt(long = "dump-constraints")]
+    pub dump_constraints: Option<String>,
 }
 
 impl ExampleOptions {
-    pub fn to_proof_options(&self, q: usize, b: usize) -> (ProofOptions, HashFunction) {
-        let num_queries = self.num_queries.unwrap_or(q);
-        let blowup_factor = self.blowup_factor.unwrap_or(b);
+    /// Returns proof options and the hash function for an example instantiated over the
+    /// specified field.
+    ///
+    /// Options specified via the command line take precedence; all other options are derived
+    /// from `defaults` of the example as described in [ExampleDefaults].
+    pub fn to_proof_options(
+        &self,
+        field: FieldType,
+        defaults: &ExampleDefaults,
+    ) -> (ProofOptions, HashFunction) {
+        let hash_fn = self.hash_fn();
+        let max_num_queries = ProofOptions::MAX_NUM_QUERIES;
+        let blowup_factor = self.blowup_factor.unwrap_or(defaults.blowup_factor);
+        let build_options = |num_queries, field_extension| {
+            ProofOptions::new(
+                num_queries,
+                blowup_factor,
+                self.grinding_factor,
+                field_extension,
+                self.folding_factor,
+                256,
+            )
+        };
+        let security_level = |options: &ProofOptions| {
+            options.conjectured_security_level(
+                field.modulus_bits(),
+                ProofOptions::MAX_SECURITY_LDE_DOMAIN_SIZE,
+                hash_fn.collision_resistance(),
+            )
+        };
+        let target_security = defaults.target_security.min(hash_fn.collision_resistance());
+
+        // use the smallest field extension for which security contributed by the field meets the
+        // target; this is checked with the largest number of queries so that queries do not
+        // limit security
         let field_extension = match self.field_extension {
-            1 => FieldExtension::None,
-            2 => FieldExtension::Quadratic,
-            3 => FieldExtension::Cubic,
-            val => panic!("'{val}' is not a valid field extension option"),
+            Some(1) => FieldExtension::None,
+            Some(2) => FieldExtension::Quadratic,
+            Some(3) => FieldExtension::Cubic,
+            Some(val) => panic!("'{val}' is not a valid field extension option"),
+            None => [
+                FieldExtension::None,
+                FieldExtension::Quadratic,
+                FieldExtension::Cubic,
+            ]
+            .into_iter()
+            .find(|&extension| {
+                security_level(&build_options(max_num_queries, extension)) >= target_security
+            })
+            .unwrap_or(FieldExtension::Cubic),
         };
 
-        let hash_fn = match self.hash_fn.as_str() {
+        // use the smallest number of queries which meets the target; if the target cannot be
+        // met with the other options (e.g., because of a small field extension specified via
+        // the command line), use the smallest number of queries which achieves the highest
+        // security possible
+        let num_queries = self.num_queries.unwrap_or_else(|| {
+            let max_security = security_level(&build_options(max_num_queries, field_extension));
+            let target_security = target_security.min(max_security);
+            (1..=max_num_queries)
+                .find(|&num_queries| {
+                    security_level(&build_options(num_queries, field_extension)) >= target_security
+                })
+                .unwrap_or(max_num_queries)
+        });
+
+        (build_options(num_queries, field_extension), hash_fn)
+    }
+
+    /// Returns the hash function specified via the command line.
+    pub fn hash_fn(&self) -> HashFunction {
+        match self.hash_fn.as_str() {
             "blake3_192" => HashFunction::Blake3_192,
             "blake3_256" => HashFunction::Blake3_256,
             "sha3_256" => HashFunction::Sha3_256,
+            "keccak_256" => HashFunction::Keccak256,
+            "blake2s_256" => HashFunction::Blake2s256,
             "rp64_256" => HashFunction::Rp64_256,
             "rp_jive64_256" => HashFunction::RpJive64_256,
             "griffin_jive64_256" => HashFunction::GriffinJive64_256,
+            "poseidon" => HashFunction::Poseidon,
             val => panic!("'{val}' is not a valid hash function option"),
-        };
+        }
+    }
 
-        (
-            ProofOptions::new(
-                num_queries,
-                blowup_factor,
-                self.grinding_factor,
-                field_extension,
-                self.folding_factor,
-                256,
-            ),
-            hash_fn,
-        )
+    /// Returns the base field over which an example should be instantiated, or `default` if the
+    /// field was not specified.
+    pub fn field(&self, default: FieldType) -> FieldType {
+        match self.field.as_deref() {
+            None => default,
+            Some("f62") => FieldType::F62,
+            Some("f64") => FieldType::F64,
+            Some("f128") => FieldType::F128,
+            Some(val) => panic!("'{val}' is not a valid field option"),
+        }
     }
 
     /// Returns security level of the input proof in bits.
@@ -111,38 +316,82 @@ impl ExampleOptions {
             "blake3_192" => proof.security_level::<Blake3_192>(true),
             "blake3_256" => proof.security_level::<Blake3_256>(true),
             "sha3_256" => proof.security_level::<Sha3_256>(true),
+            "keccak_256" => proof.security_level::<Keccak_256>(true),
+            "blake2s_256" => proof.security_level::<Blake2s_256>(true),
             "rp64_256" => proof.security_level::<Rp64_256>(true),
             "rp_jive64_256" => proof.security_level::<RpJive64_256>(true),
             "griffin_jive64_256" => proof.security_level::<GriffinJive64_256>(true),
+            "poseidon" => proof.security_level::<Poseidon64_256>(true),
             val => panic!("'{val}' is not a valid hash function option"),
         };
 
         security_level as usize
     }
+
+    /// Returns a breakdown of the security level of the input proof; the breakdown is for the
+    /// conjectured security level when `conjectured` is true, and for the provable security
+    /// level otherwise.
+    pub fn get_proof_security_estimate(
+        &self,
+        proof: &StarkProof,
+        conjectured: bool,
+    ) -> SecurityEstimate {
+        match self.hash_fn.as_str() {
+            "blake3_192" => proof.security_estimate::<Blake3_192>(conjectured),
+            "blake3_256" => proof.security_estimate::<Blake3_256>(conjectured),
+            "sha3_256" => proof.security_estimate::<Sha3_256>(conjectured),
+            "keccak_256" => proof.security_estimate::<Keccak_256>(conjectured),
+            "blake2s_256" => proof.security_estimate::<Blake2s_256>(conjectured),
+            "rp64_256" => proof.security_estimate::<Rp64_256>(conjectured),
+            "rp_jive64_256" => proof.security_estimate::<RpJive64_256>(conjectured),
+            "griffin_jive64_256" => proof.security_estimate::<GriffinJive64_256>(conjectured),
+            "poseidon" => proof.security_estimate::<Poseidon64_256>(conjectured),
+            val => panic!("'{val}' is not a valid hash function option"),
+        }
+    }
 }
 
 #[derive(StructOpt, Debug)]
 //#[structopt(about = "available examples")]
 pub enum ExampleType {
     /// Compute a Fibonacci sequence using trace table with 2 registers
+    #[cfg(feature = "f128")]
     Fib {
         /// Length of Fibonacci sequence; must be a power of two
         #[structopt(short = "n", default_value = "1048576")]
         sequence_length: usize,
+        /// Number of proofs of the same sequence which are generated and verified together as a
+        /// batch sharing a single proof context
+        #[structopt(long = "batch")]
+        batch: Option<usize>,
+        /// Term of the Fibonacci sequence which the proof attests to; the prover fails if the
+        /// computed term differs from it. When omitted, the term is read from the execution trace
+        #[structopt(long = "expected-result")]
+        expected_result: Option<u128>,
     },
     /// Compute a Fibonacci sequence using trace table with 8 registers
+    #[cfg(feature = "f128")]
     Fib8 {
         /// Length of Fibonacci sequence; must be a power of two
         #[structopt(short = "n", default_value = "1048576")]
         sequence_length: usize,
     },
+    /// Compute a Fibonacci sequence using constraints which relate rows 4 steps apart
+    #[cfg(feature = "f128")]
+    FibWindow {
+        /// Length of Fibonacci sequence; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        sequence_length: usize,
+    },
     /// Compute a multiplicative Fibonacci sequence using trace table with 2 registers
+    #[cfg(feature = "f128")]
     Mulfib {
         /// Length of Fibonacci sequence; must be a power of two
         #[structopt(short = "n", default_value = "1048576")]
         sequence_length: usize,
     },
     /// Compute a multiplicative Fibonacci sequence using trace table with 8 registers
+    #[cfg(feature = "f128")]
     Mulfib8 {
         /// Length of Fibonacci sequence; must be a power of two
         #[structopt(short = "n", default_value = "1048576")]
@@ -155,55 +404,295 @@ pub enum ExampleType {
         sequence_length: usize,
     },
     /// Execute a simple VDF function
+    #[cfg(feature = "f128")]
     Vdf {
         /// Number of steps in the VDF function; must be a power of two
         #[structopt(short = "n", default_value = "1048576")]
         num_steps: usize,
     },
     /// Similar to the VDF example, but exempts an extra row from transition constraints.
+    #[cfg(feature = "f128")]
     VdfExempt {
         /// Number of steps in the VDF function; must be one less than a power of two
         #[structopt(short = "n", default_value = "1048575")]
         num_steps: usize,
     },
+    /// Rotate a set of registers with constraints enforced over the whole cycle of the trace
+    #[cfg(feature = "f128")]
+    Rotate {
+        /// Number of rotation steps; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        num_steps: usize,
+    },
+    /// Execute a program of a simple VM with operations gated on selector flags
+    #[cfg(feature = "f128")]
+    Vm {
+        /// Number of steps in the program; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        num_steps: usize,
+    },
+    /// Execute a program stored in a ROM which is committed to once for all executions
+    #[cfg(feature = "f128")]
+    Rom {
+        /// Number of steps in the program; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        num_steps: usize,
+    },
     /// Compute a hash chain using Rescue hash function
+    #[cfg(feature = "f128")]
     Rescue {
         /// Length of the hash chain; must be a power of two
         #[structopt(short = "n", default_value = "1024")]
         chain_length: usize,
+
+        /// Keeps the result of the hash chain private by asserting it against its digest
+        #[structopt(long = "hidden")]
+        hidden: bool,
     },
     /// Compute two hash chains absorbing sequences that are a permutation of each other
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "std", feature = "f128"))]
     RescueRaps {
         /// Length of the hash chain; must be a power of two and at least 4
         #[structopt(short = "n", default_value = "1024")]
         chain_length: usize,
     },
     /// Compute a root of a Merkle path using Rescue hash function
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "std", feature = "f128"))]
     Merkle {
         /// Depth of the Merkle tree; must be one less than a power of two
         #[structopt(short = "n", default_value = "7")]
         tree_depth: usize,
     },
+    /// Compute a root of a Merkle tree from a batch of leaves using Rescue hash function
+    #[cfg(all(feature = "std", feature = "f128"))]
+    MerkleBatch {
+        /// Number of leaves to authenticate
+        #[structopt(long = "num-leaves", default_value = "8")]
+        num_leaves: usize,
+        /// Depth of the Merkle tree
+        #[structopt(long = "tree-depth", default_value = "7")]
+        tree_depth: usize,
+    },
     /// Compute an aggregate Lamport+ signature
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "std", feature = "f128"))]
     LamportA {
         /// Number of signatures to aggregate; must be a power of two
         #[structopt(short = "n", default_value = "4")]
         num_signatures: usize,
     },
     /// Compute a threshold Lamport+ signature
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "std", feature = "f128"))]
     LamportT {
         /// Number of signers; must be one less than a power of two
         #[structopt(short = "n", default_value = "3")]
         num_signers: usize,
     },
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "std", feature = "f128"))]
+    Collatz {
+        #[structopt(short = "n", default_value = "52")]
+        num_initial: usize,
+        /// Number of sequences starting at consecutive initial numbers (beginning with -n) which
+        /// are proven together in a single execution trace
+        #[structopt(long = "batch")]
+        batch: Option<usize>,
+        /// Comma-separated initial numbers of sequences proven together in a single execution
+        /// trace (e.g., 7,27,97); overrides -n when specified
+        #[structopt(long = "initial-numbers", use_delimiter = true)]
+        initial_numbers: Vec<usize>,
+    },
+    /// Prove the sum, the sum of squares, and the number of values below a threshold of a random
+    /// private dataset of 32-bit values
+    #[cfg(all(feature = "std", feature = "f128"))]
+    Statistics {
+        /// Number of values in the dataset; must be a power of two
+        #[structopt(short = "n", long = "count", default_value = "1024")]
+        count: usize,
+        /// Threshold below which values are counted
+        #[structopt(short = "t", long = "threshold", default_value = "2147483648")]
+        threshold: u32,
+    },
+    /// Print a JSON description of the AIR used by the specified example instead of proving it
+    Describe {
+        #[structopt(subcommand)]
+        example: DescribedExample,
+    },
+    /// Print estimated proving time and peak memory for the specified example instead of
+    /// proving it
+    #[cfg(all(feature = "std", feature = "f128"))]
+    Estimate {
+        #[structopt(subcommand)]
+        example: EstimatedExample,
+    },
+    /// Run all examples with small parameters and print a summary; exits with a non-zero code if
+    /// any of the examples failed
+    RunAll,
+    /// Check proofs of a fixed set of instances against checked-in digests; exits with a non-zero
+    /// code if any of the digests differ
+    #[cfg(all(feature = "std", feature = "f128"))]
+    Golden {
+        /// Regenerate the checked-in digests instead of checking them
+        #[structopt(long = "update")]
+        update: bool,
+    },
+    /// Check that proofs checked in by earlier versions are still verified, or that their format
+    /// versions are covered by migration notes; exits with a non-zero code otherwise
+    #[cfg(all(feature = "std", feature = "f128"))]
+    Compat {
+        /// Regenerate the checked-in proofs instead of checking them
+        #[structopt(long = "update")]
+        update: bool,
+    },
+}
+
+impl ExampleType {
+    /// Returns parameters from which default proof options of this example are derived, or None
+    /// if this is not an example but a command (e.g., `run-all`).
+    pub fn defaults(&self) -> Option<ExampleDefaults> {
+        match self {
+            #[cfg(feature = "f128")]
+            ExampleType::Fib { .. } => Some(fibonacci::fib2::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Fib8 { .. } => Some(fibonacci::fib8::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::FibWindow { .. } => Some(fibonacci::fib_window::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Mulfib { .. } => Some(fibonacci::mulfib2::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Mulfib8 { .. } => Some(fibonacci::mulfib8::DEFAULTS),
+            ExampleType::FibSmall { .. } => Some(fibonacci::fib_small::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Vdf { .. } => Some(vdf::regular::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::VdfExempt { .. } => Some(vdf::exempt::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Rotate { .. } => Some(rotate::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Vm { .. } => Some(vm::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Rom { .. } => Some(rom::DEFAULTS),
+            #[cfg(feature = "f128")]
+            ExampleType::Rescue { .. } => Some(rescue::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::RescueRaps { .. } => Some(rescue_raps::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::Merkle { .. } => Some(merkle::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::MerkleBatch { .. } => Some(merkle::batch::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::LamportA { .. } => Some(lamport::aggregate::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::LamportT { .. } => Some(lamport::threshold::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::Collatz { .. } => Some(collatz::DEFAULTS),
+            #[cfg(all(feature = "std", feature = "f128"))]
+            ExampleType::Statistics { .. } => Some(statistics::DEFAULTS),
+            _ => None,
+        }
+    }
+}
+
+/// Examples which can be described via the `describe` command; a separate enum is needed because
+/// a command cannot contain itself as a subcommand.
+#[derive(StructOpt, Debug)]
+pub enum DescribedExample {
+    /// Describe the Fibonacci example with 2 registers
+    #[cfg(feature = "f128")]
+    Fib {
+        /// Length of Fibonacci sequence; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        sequence_length: usize,
+    },
+    /// Describe the Fibonacci example with 2 registers in `f64` field
+    FibSmall {
+        /// Length of Fibonacci sequence; must be a power of two
+        #[structopt(short = "n", default_value = "65536")]
+        sequence_length: usize,
+    },
+    /// Describe the simple VM example
+    #[cfg(feature = "f128")]
+    Vm {
+        /// Number of steps in the program; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        num_steps: usize,
+    },
+    /// Describe the Collatz sequence example
+    #[cfg(all(feature = "std", feature = "f128"))]
     Collatz {
         #[structopt(short = "n", default_value = "52")]
         num_initial: usize,
+        /// Number of sequences starting at consecutive initial numbers (beginning with -n) which
+        /// are proven together in a single execution trace
+        #[structopt(long = "batch")]
+        batch: Option<usize>,
+        /// Comma-separated initial numbers of sequences proven together in a single execution
+        /// trace (e.g., 7,27,97); overrides -n when specified
+        #[structopt(long = "initial-numbers", use_delimiter = true)]
+        initial_numbers: Vec<usize>,
+    },
+}
+
+impl DescribedExample {
+    /// Returns the example type which corresponds to this described example.
+    pub fn example_type(&self) -> ExampleType {
+        match *self {
+            #[cfg(feature = "f128")]
+            DescribedExample::Fib { sequence_length } => ExampleType::Fib {
+                sequence_length,
+                batch: None,
+                expected_result: None,
+            },
+            DescribedExample::FibSmall { sequence_length } => {
+                ExampleType::FibSmall { sequence_length }
+            }
+            #[cfg(feature = "f128")]
+            DescribedExample::Vm { num_steps } => ExampleType::Vm { num_steps },
+            #[cfg(all(feature = "std", feature = "f128"))]
+            DescribedExample::Collatz {
+                num_initial,
+                batch,
+                ref initial_numbers,
+            } => ExampleType::Collatz {
+                num_initial,
+                batch,
+                initial_numbers: initial_numbers.clone(),
+            },
+        }
+    }
+}
+
+/// Examples for which proving cost can be estimated via the `estimate` command.
+#[cfg(all(feature = "std", feature = "f128"))]
+#[derive(StructOpt, Debug)]
+pub enum EstimatedExample {
+    /// Estimate the cost of the Fibonacci example with 2 registers
+    Fib {
+        /// Length of Fibonacci sequence; must be a power of two
+        #[structopt(short = "n", default_value = "1048576")]
+        sequence_length: usize,
+    },
+    /// Estimate the cost of the Rescue hash chain example
+    Rescue {
+        /// Length of the hash chain; must be a power of two
+        #[structopt(short = "n", default_value = "1024")]
+        chain_length: usize,
+    },
+}
+
+#[cfg(all(feature = "std", feature = "f128"))]
+impl EstimatedExample {
+    /// Returns the example type which corresponds to this estimated example.
+    pub fn example_type(&self) -> ExampleType {
+        match *self {
+            EstimatedExample::Fib { sequence_length } => ExampleType::Fib {
+                sequence_length,
+                batch: None,
+                expected_result: None,
+            },
+            EstimatedExample::Rescue { chain_length } => ExampleType::Rescue {
+                chain_length,
+                hidden: false,
+            },
+        }
     }
 }
 
@@ -231,6 +720,16 @@ pub enum HashFunction {
     /// When this function is used in the STARK protocol, proof security cannot exceed 128 bits.
     Sha3_256,
 
+    /// Keccak hash function with 256 bit output, as used by Ethereum (i.e., `keccak256`).
+    ///
+    /// When this function is used in the STARK protocol, proof security cannot exceed 128 bits.
+    Keccak256,
+
+    /// BLAKE2s hash function with 256 bit output.
+    ///
+    /// When this function is used in the STARK protocol, proof security cannot exceed 128 bits.
+    Blake2s256,
+
     /// Rescue Prime hash function with 256 bit output. It only works in `f64` field.
     ///
     /// When this function is used in the STARK protocol, proof security cannot exceed 128 bits.
@@ -247,4 +746,57 @@ pub enum HashFunction {
     ///
     /// When this function is used in the STARK protocol, proof security cannot exceed 128 bits.
     GriffinJive64_256,
+
+    /// Poseidon hash function with 256 bit output. It works in `f64` and `f128` fields.
+    ///
+    /// When this function is used in the STARK protocol, proof security cannot exceed 128 bits.
+    Poseidon,
+}
+
+impl HashFunction {
+    /// Returns collision resistance (in bits) of this hash function; proof security cannot exceed
+    /// this value when the function is used in the STARK protocol.
+    pub fn collision_resistance(&self) -> u32 {
+        match self {
+            HashFunction::Blake3_192 => Blake3_192::COLLISION_RESISTANCE,
+            HashFunction::Blake3_256 => Blake3_256::COLLISION_RESISTANCE,
+            HashFunction::Sha3_256 => Sha3_256::COLLISION_RESISTANCE,
+            HashFunction::Keccak256 => Keccak_256::COLLISION_RESISTANCE,
+            HashFunction::Blake2s256 => Blake2s_256::COLLISION_RESISTANCE,
+            HashFunction::Rp64_256 => Rp64_256::COLLISION_RESISTANCE,
+            HashFunction::RpJive64_256 => RpJive64_256::COLLISION_RESISTANCE,
+            HashFunction::GriffinJive64_256 => GriffinJive64_256::COLLISION_RESISTANCE,
+            HashFunction::Poseidon => Poseidon64_256::COLLISION_RESISTANCE,
+        }
+    }
+}
+
+/// Defines a set of base fields over which the provided examples can be instantiated. Most
+/// examples can be instantiated over a single field only.
+///
+/// Choice of a field has a direct impact on proof generation time: arithmetic in smaller fields
+/// is faster, but to achieve the same level of security, proofs for computations defined over
+/// smaller fields need to use field extensions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FieldType {
+    /// 62-bit prime field with modulus 2^62 - 111 * 2^39 + 1.
+    F62,
+
+    /// 64-bit prime field with modulus 2^64 - 2^32 + 1. This is the only field which can be used
+    /// with Rescue Prime and Griffin hash functions.
+    F64,
+
+    /// 128-bit prime field with modulus 2^128 - 45 * 2^40 + 1.
+    F128,
+}
+
+impl FieldType {
+    /// Returns the number of bits in the modulus of this field.
+    pub fn modulus_bits(&self) -> u32 {
+        match self {
+            FieldType::F62 => 62,
+            FieldType::F64 => 64,
+            FieldType::F128 => 128,
+        }
+    }
 }
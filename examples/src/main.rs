@@ -5,100 +5,399 @@
 
 use log::debug;
 use std::io::Write;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use structopt::StructOpt;
-use winterfell::StarkProof;
+use winterfell::{ArchivalEnvelope, ArchiveMetadata, StarkProof};
 
-use examples::{fibonacci, rescue, vdf, ExampleOptions, ExampleType};
-#[cfg(feature = "std")]
-use examples::{lamport, merkle, rescue_raps, collatz};
+#[cfg(feature = "trace-constraints")]
+use std::panic;
+#[cfg(feature = "trace-constraints")]
+use winterfell::constraint_log;
+#[cfg(feature = "transcript-log")]
+use winterfell::crypto::transcript_log::{self, TranscriptLog};
+
+#[cfg(all(feature = "std", feature = "f128"))]
+use examples::{compat, golden};
+use examples::{
+    profile,
+    runner::{build_example, ExampleRegistry},
+    ExampleOptions, ExampleType,
+};
 
 // EXAMPLE RUNNER
 // ================================================================================================
 
 fn main() {
-    // configure logging
-    env_logger::Builder::new()
-        .format(|buf, record| writeln!(buf, "{}", record.args()))
-        .filter_level(log::LevelFilter::Debug)
-        .init();
-
     // read command-line args
     let options = ExampleOptions::from_args();
 
+    // configure logging; when profiling is requested, the logger is wrapped into a profiler which
+    // aggregates timings of prover phases reported via log records
+    let mut logger = env_logger::Builder::new();
+    logger
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .filter_level(log::LevelFilter::Debug);
+    let profiler = match options.profile {
+        Some(_) => Some(
+            profile::install(Some(Box::new(logger.build()))).expect("failed to install profiler"),
+        ),
+        None => {
+            logger.init();
+            None
+        }
+    };
+
     debug!("============================================================");
 
-    // instantiate and prepare the example
-    let example = match options.example {
-        ExampleType::Fib { sequence_length } => {
-            fibonacci::fib2::get_example(&options, sequence_length)
-        }
-        ExampleType::Fib8 { sequence_length } => {
-            fibonacci::fib8::get_example(&options, sequence_length)
-        }
-        ExampleType::Mulfib { sequence_length } => {
-            fibonacci::mulfib2::get_example(&options, sequence_length)
-        }
-        ExampleType::Mulfib8 { sequence_length } => {
-            fibonacci::mulfib8::get_example(&options, sequence_length)
+    // if only a description was requested, print it and exit
+    if let ExampleType::Describe { example } = &options.example {
+        let example = build_example(&options, &example.example_type())
+            .expect("The example failed to initialize.");
+        match example.describe() {
+            Some(description) => println!("{}", description.to_json()),
+            None => debug!("The specified example does not support descriptions."),
         }
-        ExampleType::FibSmall { sequence_length } => {
-            fibonacci::fib_small::get_example(&options, sequence_length)
-        }
-        ExampleType::Vdf { num_steps } => vdf::regular::get_example(&options, num_steps),
-        ExampleType::VdfExempt { num_steps } => vdf::exempt::get_example(&options, num_steps),
-        ExampleType::Rescue { chain_length } => rescue::get_example(&options, chain_length),
-        #[cfg(feature = "std")]
-        ExampleType::RescueRaps { chain_length } => {
-            rescue_raps::get_example(&options, chain_length)
-        }
-        #[cfg(feature = "std")]
-        ExampleType::Merkle { tree_depth } => merkle::get_example(&options, tree_depth),
-        #[cfg(feature = "std")]
-        ExampleType::LamportA { num_signatures } => {
-            lamport::aggregate::get_example(&options, num_signatures)
+        return;
+    }
+
+    // if only a cost estimate was requested, print it and exit
+    #[cfg(all(feature = "std", feature = "f128"))]
+    if let ExampleType::Estimate { example } = &options.example {
+        let example = build_example(&options, &example.example_type())
+            .expect("The example failed to initialize.");
+        match example.estimate_cost() {
+            Some(estimate) => println!("{estimate}"),
+            None => debug!("The specified example does not support cost estimation."),
         }
-        #[cfg(feature = "std")]
-        ExampleType::LamportT { num_signers } => {
-            lamport::threshold::get_example(&options, num_signers)
+        return;
+    }
+
+    // if all examples were requested, run them one by one and report failures via the exit code
+    if let ExampleType::RunAll = &options.example {
+        let summary = ExampleRegistry::with_default_examples(&options).run_all();
+        println!("{summary}");
+        if !summary.all_passed() {
+            std::process::exit(1);
         }
-        #[cfg(feature = "std")]
-        ExampleType::Collatz { num_initial } => {
-            collatz::get_example(&options, num_initial)
+        return;
+    }
+
+    // if golden proofs were requested, check their digests (or regenerate them) and report
+    // mismatches via the exit code
+    #[cfg(all(feature = "std", feature = "f128"))]
+    if let ExampleType::Golden { update } = &options.example {
+        run_golden(*update);
+        return;
+    }
+
+    // if checked-in proofs were requested, check their compatibility (or regenerate them) and
+    // report failures via the exit code
+    #[cfg(all(feature = "std", feature = "f128"))]
+    if let ExampleType::Compat { update } = &options.example {
+        run_compat(*update);
+        return;
+    }
+
+    // instantiate and prepare the example
+    let example =
+        build_example(&options, &options.example).expect("The example failed to initialize.");
+
+    // if requested, print parameters derived from the AIR and proof options before proving
+    if options.explain_params {
+        match example.explain_params() {
+            Some(report) => println!("{report}"),
+            None => debug!("The specified example does not support parameter reports."),
         }
     }
-    .expect("The example failed to initialize.");
+
+    // if a batch of proofs was requested, generate and verify the batch and exit
+    #[cfg(feature = "f128")]
+    if let ExampleType::Fib {
+        batch: Some(num_proofs),
+        ..
+    } = options.example
+    {
+        run_batch(example.as_ref(), num_proofs);
+        return;
+    }
 
     // generate proof
     let now = Instant::now();
     let example = example.as_ref();
-    let proof = example.prove();
+    let prove = || match (profiler, &options.profile) {
+        (Some(profiler), Some(path)) => {
+            let (proof, stacks) = profiler.profile(|| example.prove());
+            stacks
+                .write_to_file(path)
+                .expect("failed to write folded stacks");
+            debug!("Folded stacks of prover phases written to {}", path);
+            proof
+        }
+        _ => example.prove(),
+    };
+    #[cfg(feature = "trace-constraints")]
+    let prove = || match &options.dump_constraints {
+        Some(path) => prove_with_constraint_log(path, prove),
+        None => prove(),
+    };
+    #[cfg(feature = "transcript-log")]
+    let (proof, prover_log) = transcript_log::record(prove);
+    #[cfg(not(feature = "transcript-log"))]
+    let proof = prove();
     debug!(
-        "---------------------\nProof generated in {} ms",
-        now.elapsed().as_millis()
+        "---------------------\nProof generated in {} ms over {}-bit field",
+        now.elapsed().as_millis(),
+        proof.context.num_modulus_bits()
     );
 
     let proof_bytes = proof.to_bytes();
     debug!("Proof size: {:.1} KB", proof_bytes.len() as f64 / 1024f64);
     let security_level = options.get_proof_security_level(&proof);
     debug!("Proof security: {} bits", security_level);
+    if options.verbose {
+        for (setting, conjectured) in [("conjectured", true), ("proven", false)] {
+            let estimate = options.get_proof_security_estimate(&proof, conjectured);
+            debug!(
+                "Proof {} security breakdown: field {} bits, queries {} bits, grinding {} bits, \
+                hash collision resistance {} bits; bottleneck: {:?}",
+                setting,
+                estimate.field_security(),
+                estimate.query_security(),
+                estimate.grinding_bits(),
+                estimate.collision_resistance(),
+                estimate.bottleneck()
+            );
+        }
+        debug!("Proof size breakdown:\n{}", proof.size_breakdown());
+    }
+    let fri_domain_sizes = proof.fri_layer_domain_sizes();
+    match (fri_domain_sizes.first(), fri_domain_sizes.last()) {
+        (Some(first), Some(last)) => debug!(
+            "Proof FRI layers: {} over domains of 2^{} to 2^{} elements; remainder degree: {}",
+            proof.fri_layer_count(),
+            first.trailing_zeros(),
+            last.trailing_zeros(),
+            proof.fri_remainder_degree()
+        ),
+        _ => debug!(
+            "Proof FRI layers: 0; remainder degree: {}",
+            proof.fri_remainder_degree()
+        ),
+    }
     #[cfg(feature = "std")]
     debug!(
         "Proof hash: {}",
         hex::encode(blake3::hash(&proof_bytes).as_bytes())
     );
 
-    // verify the proof
+    // verify the proof; if archival was requested, the proof is first written into the archive
+    // and then read back from it
     debug!("---------------------");
-    let parsed_proof = StarkProof::from_bytes(&proof_bytes).unwrap();
+    let parsed_proof = match &options.archive {
+        Some(path) => archive_proof(&options, &proof, path),
+        None => StarkProof::from_bytes(&proof_bytes).unwrap(),
+    };
     assert_eq!(proof, parsed_proof);
     let now = Instant::now();
-    match example.verify(proof) {
+    #[cfg(feature = "transcript-log")]
+    let (result, verifier_log) = transcript_log::record(|| example.verify(proof));
+    #[cfg(not(feature = "transcript-log"))]
+    let result = example.verify(proof);
+    match result {
         Ok(_) => debug!(
             "Proof verified in {:.1} ms",
             now.elapsed().as_micros() as f64 / 1000f64
         ),
         Err(msg) => debug!("Failed to verify proof: {}", msg),
     }
+
+    #[cfg(feature = "transcript-log")]
+    if let Some(path) = &options.dump_transcript {
+        debug!("---------------------");
+        dump_transcript_logs(path, &prover_log, &verifier_log);
+    }
+    debug!("============================================================");
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Checks digests of golden proofs against the checked-in digests, or regenerates the checked-in
+/// digests if `update` is true.
+#[cfg(all(feature = "std", feature = "f128"))]
+fn run_golden(update: bool) {
+    if update {
+        let digests = golden::compute_digests();
+        std::fs::write(golden::DIGESTS_PATH, golden::render_digests(&digests))
+            .expect("failed to write golden proof digests");
+        println!(
+            "Digests of {} golden proofs written to {}",
+            digests.len(),
+            golden::DIGESTS_PATH
+        );
+        return;
+    }
+
+    let mismatches = golden::check_digests();
+    if mismatches.is_empty() {
+        println!("All {} golden proofs match", golden::GOLDEN_INSTANCES.len());
+    } else {
+        for mismatch in mismatches.iter() {
+            println!("{mismatch}");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Checks checked-in proofs against the current version of the library, or regenerates the
+/// checked-in proofs if `update` is true.
+#[cfg(all(feature = "std", feature = "f128"))]
+fn run_compat(update: bool) {
+    if update {
+        for instance in compat::COMPAT_INSTANCES.iter() {
+            let path = compat::fixture_path(instance);
+            std::fs::write(&path, instance.prove().to_bytes())
+                .expect("failed to write checked-in proof");
+            println!("Proof for {} written to {}", instance.name(), path);
+        }
+        return;
+    }
+
+    let failures = compat::check_fixtures();
+    if failures.is_empty() {
+        println!(
+            "All {} checked-in proofs are compatible",
+            compat::COMPAT_INSTANCES.len()
+        );
+    } else {
+        for failure in failures.iter() {
+            println!("{failure}");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Writes the proof wrapped into an archival envelope into the specified path, and returns the
+/// proof unsealed from the envelope read back from the same path.
+fn archive_proof(options: &ExampleOptions, proof: &StarkProof, path: &str) -> StarkProof {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the Unix epoch")
+        .as_secs();
+    let metadata = ArchiveMetadata {
+        hash_id: options.hash_fn() as u8,
+        created_at,
+    };
+    let envelope = ArchivalEnvelope::seal(proof, metadata, None::<fn(&[u8]) -> Vec<u8>>);
+    let envelope_bytes = envelope.to_bytes();
+    std::fs::write(path, &envelope_bytes).expect("failed to write archival envelope");
+    debug!(
+        "Proof archived into {} ({} bytes, including a {}-byte header)",
+        path,
+        envelope_bytes.len(),
+        envelope_bytes.len() - envelope.proof_bytes().len()
+    );
+
+    let envelope_bytes = std::fs::read(path).expect("failed to read archival envelope");
+    ArchivalEnvelope::unseal(&envelope_bytes, None::<fn(&[u8], &[u8]) -> bool>)
+        .unwrap_or_else(|err| panic!("failed to unseal archived proof: {err}"))
+}
+
+/// Generates a batch of the specified number of proofs for the example, and verifies the batch
+/// after a serialization round trip.
+#[cfg(feature = "f128")]
+fn run_batch(example: &dyn examples::Example, num_proofs: usize) {
+    let now = Instant::now();
+    let batch = example
+        .prove_batch(num_proofs)
+        .expect("The example does not support batch proofs.");
+    debug!(
+        "---------------------\nBatch of {} proofs generated in {} ms",
+        batch.num_proofs(),
+        now.elapsed().as_millis()
+    );
+
+    let batch_bytes = batch.to_bytes();
+    let proofs_size = batch
+        .proofs()
+        .iter()
+        .map(|proof| proof.to_bytes().len())
+        .sum::<usize>();
+    debug!(
+        "Batch size: {:.1} KB ({:.1} KB when proofs are serialized individually)",
+        batch_bytes.len() as f64 / 1024f64,
+        proofs_size as f64 / 1024f64
+    );
+
+    // verify the batch
+    debug!("---------------------");
+    let parsed_batch = winterfell::BatchStarkProof::from_bytes(&batch_bytes).unwrap();
+    assert_eq!(batch, parsed_batch);
+    let now = Instant::now();
+    let results = example.verify_batch(batch);
+    let num_failed = results.iter().filter(|result| result.is_err()).count();
+    for (i, result) in results.iter().enumerate() {
+        if let Err(msg) = result {
+            debug!("Failed to verify proof {}: {}", i, msg);
+        }
+    }
+    debug!(
+        "Batch verified in {:.1} ms; {} of {} proofs are valid",
+        now.elapsed().as_micros() as f64 / 1000f64,
+        results.len() - num_failed,
+        results.len()
+    );
     debug!("============================================================");
 }
+
+/// Generates a proof while recording transition constraints which the execution trace does not
+/// satisfy, and writes the recorded log as JSON into the specified path; the log is written even
+/// if proof generation panics.
+#[cfg(feature = "trace-constraints")]
+fn prove_with_constraint_log(path: &str, prove: impl FnOnce() -> StarkProof) -> StarkProof {
+    let (result, log) =
+        constraint_log::record(|| panic::catch_unwind(panic::AssertUnwindSafe(prove)));
+    std::fs::write(path, log.to_json()).expect("failed to write constraint log");
+    match log.entries().first() {
+        None => debug!(
+            "Constraint log written to {} (no unsatisfied constraints)",
+            path
+        ),
+        Some(first) => debug!(
+            "Constraint log written to {} ({} unsatisfied constraint evaluations); first at {}",
+            path,
+            log.len(),
+            first
+        ),
+    }
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// Writes transcript logs of the prover and the verifier as JSON into the specified path and into
+/// the same path with `.verifier` suffix respectively, and reports the first entry at which the
+/// logs differ.
+#[cfg(feature = "transcript-log")]
+fn dump_transcript_logs(path: &str, prover_log: &TranscriptLog, verifier_log: &TranscriptLog) {
+    let verifier_path = format!("{path}.verifier");
+    std::fs::write(path, prover_log.to_json()).expect("failed to write prover transcript log");
+    std::fs::write(&verifier_path, verifier_log.to_json())
+        .expect("failed to write verifier transcript log");
+    debug!("Transcript logs written to {} and {}", path, verifier_path);
+
+    let describe = |log: &TranscriptLog, index: usize| match log.entries().get(index) {
+        Some(entry) => entry.to_string(),
+        None => "<no entry>".to_string(),
+    };
+    match prover_log.first_divergence(verifier_log) {
+        None => debug!(
+            "Transcript logs are identical ({} entries)",
+            prover_log.len()
+        ),
+        Some(index) => debug!(
+            "Transcript logs diverge at entry {}:\n  prover:   {}\n  verifier: {}",
+            index,
+            describe(prover_log, index),
+            describe(verifier_log, index)
+        ),
+    }
+}
@@ -13,6 +13,7 @@ use winterfell::{
 // MERKLE PATH VERIFICATION AIR
 // ================================================================================================
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct PublicInputs {
     pub tree_root: [BaseElement; 2],
 }
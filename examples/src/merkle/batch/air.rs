@@ -0,0 +1,275 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    rescue, BaseElement, BatchLayout, FieldElement, HashCycle, BIT_COL, HASH_CYCLE_LEN,
+    HASH_STATE_WIDTH, LEAF_COL, LEAF_FLAG_COL, PAIR_FLAG_COL, POP_FLAG_COL, SIBLING_LEAF_COL,
+    STACK_COL,
+};
+use crate::utils::{are_equal, is_zero, not, EvaluationResult};
+use winterfell::{
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, ProofOptions, Serializable, TraceInfo,
+    TransitionConstraintDegree,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+/// Public inputs of the Merkle batch AIR: the root of the tree, and the leaves of the batch,
+/// each specified by its index and its digest.
+///
+/// The depth of the tree and leaf indexes determine the layout of the execution trace, and thus,
+/// they are a part of the public inputs as well.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicInputs {
+    pub tree_root: [BaseElement; 2],
+    pub tree_depth: usize,
+    pub leaves: Vec<(usize, [BaseElement; 2])>,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(&self.tree_root[..]);
+        target.write_u8(self.tree_depth as u8);
+        target.write_u16(self.leaves.len() as u16);
+        for (index, digest) in self.leaves.iter() {
+            target.write_u64(*index as u64);
+            target.write(&digest[..]);
+        }
+    }
+}
+
+// MERKLE BATCH VERIFICATION AIR
+// ================================================================================================
+
+/// AIR for authenticating a batch of leaves against the root of a Merkle tree.
+///
+/// Every 8-step cycle of the trace computes a single tree node using Rescue hash function. At the
+/// start of a cycle, the node hashed by the cycle is either the output of the previous cycle, or
+/// (when the leaf flag is set) the next leaf of the batch. Its sibling is either provided by the
+/// prover, or popped from the stack (when the pop flag is set), or (when the pair flag is set) is
+/// the leaf which precedes the hashed leaf in the batch. When the leaf flag is set, the output of
+/// the previous cycle is pushed onto the stack. Values of all flags and of leaf columns at the
+/// start of every cycle are defined by [BatchLayout] and are fixed via assertions.
+pub struct MerkleBatchAir {
+    context: AirContext<BaseElement>,
+    tree_root: [BaseElement; 2],
+    leaves: Vec<[BaseElement; 2]>,
+    layout: BatchLayout,
+}
+
+impl Air for MerkleBatchAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let indexes = pub_inputs
+            .leaves
+            .iter()
+            .map(|&(index, _)| index)
+            .collect::<Vec<_>>();
+        let layout = BatchLayout::new(pub_inputs.tree_depth, &indexes);
+        assert_eq!(layout.trace_width(), trace_info.width());
+        assert_eq!(layout.trace_length(), trace_info.length());
+
+        let mut degrees = vec![
+            TransitionConstraintDegree::with_cycles(5, vec![HASH_CYCLE_LEN]);
+            HASH_STATE_WIDTH
+        ];
+        degrees.resize(
+            HASH_STATE_WIDTH + layout.trace_width() - STACK_COL,
+            TransitionConstraintDegree::with_cycles(2, vec![HASH_CYCLE_LEN]),
+        );
+        // the number of assertions made against the hash state at the first step depends on
+        // whether the batch starts with two sibling leaves
+        let num_initial_assertions = if layout.cycle(0).sibling_leaf.is_some() {
+            4
+        } else {
+            2
+        };
+        let num_assertions = (STACK_COL - BIT_COL) + num_initial_assertions + 4;
+
+        MerkleBatchAir {
+            context: AirContext::new(trace_info, degrees, num_assertions, options),
+            tree_root: pub_inputs.tree_root,
+            leaves: pub_inputs
+                .leaves
+                .iter()
+                .map(|&(_, digest)| digest)
+                .collect(),
+            layout,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        debug_assert_eq!(self.layout.trace_width(), current.len());
+        debug_assert_eq!(self.layout.trace_width(), next.len());
+
+        // split periodic values into masks and Rescue round constants
+        let hash_flag = periodic_values[0];
+        let ark = &periodic_values[1..];
+
+        // when hash_flag = 1, constraints for Rescue round are enforced
+        rescue::enforce_round(
+            result,
+            &current[..HASH_STATE_WIDTH],
+            &next[..HASH_STATE_WIDTH],
+            ark,
+            hash_flag,
+        );
+
+        // when hash_flag = 0, the hash state is initialized for the next cycle; values of control
+        // columns for the next cycle are read from the next row
+        let hash_init_flag = not(hash_flag);
+        let bit = next[BIT_COL];
+        let not_bit = not(bit);
+        let leaf_flag = next[LEAF_FLAG_COL];
+        let pop_flag = next[POP_FLAG_COL];
+        let pair_flag = next[PAIR_FLAG_COL];
+
+        for i in 0..2 {
+            // the hashed node is either the next leaf or the accumulated hash; it must go into
+            // registers [0, 1] when index bit = 0, and into registers [2, 3] when index bit = 1
+            let node = leaf_flag * next[LEAF_COL + i] + not(leaf_flag) * current[i];
+            let hashed = not_bit * next[i] + bit * next[2 + i];
+            result.agg_constraint(i, hash_init_flag, are_equal(hashed, node));
+
+            // the sibling must be equal to the top of the stack when it is popped, and to the
+            // preceding leaf when two sibling leaves are hashed together
+            let sibling = bit * next[i] + not_bit * next[2 + i];
+            let from_stack = pop_flag * are_equal(sibling, current[STACK_COL + i]);
+            let from_leaf = pair_flag * are_equal(sibling, next[SIBLING_LEAF_COL + i]);
+            result.agg_constraint(2 + i, hash_init_flag, from_stack + from_leaf);
+        }
+
+        // make sure capacity registers of the hash state are reset to zeros
+        result.agg_constraint(4, hash_init_flag, is_zero(next[4]));
+        result.agg_constraint(5, hash_init_flag, is_zero(next[5]));
+
+        // the stack remains unchanged within a cycle; when the leaf flag is set, the accumulated
+        // hash is pushed onto the stack, and when the pop flag is set, the top node is removed
+        let stack_width = current.len() - STACK_COL;
+        for i in 0..stack_width {
+            let col = STACK_COL + i;
+            let pushed = if i < 2 { current[i] } else { current[col - 2] };
+            let popped = if i + 2 < stack_width {
+                current[col + 2]
+            } else {
+                E::ZERO
+            };
+            let change = leaf_flag * are_equal(pushed, current[col])
+                + pop_flag * are_equal(popped, current[col]);
+            result[HASH_STATE_WIDTH + i] =
+                are_equal(next[col], current[col]) - hash_init_flag * change;
+        }
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let num_cycles = self.trace_length() / HASH_CYCLE_LEN;
+        let cycles = (0..num_cycles)
+            .map(|i| self.layout.cycle(i))
+            .collect::<Vec<_>>();
+        let flag_values = |flag: fn(&HashCycle) -> bool| {
+            cycles
+                .iter()
+                .map(|cycle| BaseElement::from(flag(cycle) as u8))
+                .collect::<Vec<_>>()
+        };
+        let leaf_values = |leaf: fn(&HashCycle) -> Option<usize>, i: usize| {
+            cycles
+                .iter()
+                .map(|cycle| leaf(cycle).map_or(BaseElement::ZERO, |j| self.leaves[j][i]))
+                .collect::<Vec<_>>()
+        };
+
+        // control columns and leaf columns are fixed at the first step of every cycle
+        let mut result = vec![
+            Assertion::sequence(BIT_COL, 0, HASH_CYCLE_LEN, flag_values(|c| c.bit)),
+            Assertion::sequence(LEAF_FLAG_COL, 0, HASH_CYCLE_LEN, flag_values(|c| c.is_leaf)),
+            Assertion::sequence(POP_FLAG_COL, 0, HASH_CYCLE_LEN, flag_values(|c| c.pop)),
+            Assertion::sequence(
+                PAIR_FLAG_COL,
+                0,
+                HASH_CYCLE_LEN,
+                flag_values(|c| c.sibling_leaf.is_some()),
+            ),
+        ];
+        for i in 0..2 {
+            let leaf = |c: &HashCycle| if c.is_leaf { Some(c.path) } else { None };
+            result.push(Assertion::sequence(
+                LEAF_COL + i,
+                0,
+                HASH_CYCLE_LEN,
+                leaf_values(leaf, i),
+            ));
+            result.push(Assertion::sequence(
+                SIBLING_LEAF_COL + i,
+                0,
+                HASH_CYCLE_LEN,
+                leaf_values(|c| c.sibling_leaf, i),
+            ));
+        }
+
+        // the first cycle is not preceded by an initialization step, and thus, the leaves it
+        // hashes are placed into the hash state directly
+        let first = &cycles[0];
+        let (leaf_col, sibling_col) = if first.bit { (2, 0) } else { (0, 2) };
+        for i in 0..2 {
+            result.push(Assertion::single(
+                leaf_col + i,
+                0,
+                self.leaves[first.path][i],
+            ));
+        }
+        if let Some(j) = first.sibling_leaf {
+            for i in 0..2 {
+                result.push(Assertion::single(sibling_col + i, 0, self.leaves[j][i]));
+            }
+        }
+
+        // assert that the batch resolves to the tree root, and that hash capacity registers
+        // (registers 4 and 5) are reset to ZERO every 8 steps
+        let root_step = self.layout.root_step();
+        result.extend_from_slice(&[
+            Assertion::single(0, root_step, self.tree_root[0]),
+            Assertion::single(1, root_step, self.tree_root[1]),
+            Assertion::periodic(4, 0, HASH_CYCLE_LEN, BaseElement::ZERO),
+            Assertion::periodic(5, 0, HASH_CYCLE_LEN, BaseElement::ZERO),
+        ]);
+        result
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut result = vec![HASH_CYCLE_MASK.to_vec()];
+        result.append(&mut rescue::get_round_constants());
+        result
+    }
+}
+
+// MASKS
+// ================================================================================================
+const HASH_CYCLE_MASK: [BaseElement; HASH_CYCLE_LEN] = [
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ZERO,
+];
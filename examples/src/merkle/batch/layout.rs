@@ -0,0 +1,178 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{HASH_CYCLE_LEN, STACK_COL};
+use crate::utils::get_padded_trace_length;
+
+// BATCH LAYOUT
+// ================================================================================================
+
+/// Placement of hash cycles in an execution trace which authenticates a batch of Merkle tree
+/// leaves.
+///
+/// Leaves are processed in the order of their indexes. The path of every leaf is hashed only up to
+/// the level at which it merges with the path of the next leaf; the node computed at that level is
+/// pushed onto a stack at the start of the next leaf, and is popped from the stack when a later
+/// path needs it as a sibling. Two leaves which are siblings of each other are hashed together in
+/// a single cycle. The path of the last leaf is hashed all the way up to the root.
+///
+/// The layout depends only on the depth of the tree and on leaf indexes, and thus, it is derived
+/// in the same way by the prover and by the verifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchLayout {
+    cycles: Vec<HashCycle>,
+    num_stack_slots: usize,
+}
+
+/// A single hash cycle of a [BatchLayout]; a cycle computes a tree node from its two children.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HashCycle {
+    /// Position (in the batch) of the leaf whose authentication path is followed by this cycle.
+    pub path: usize,
+    /// Level of the children hashed in this cycle; leaves are located at level 0.
+    pub level: usize,
+    /// Set when the node computed by the previous cycle (or the leaf) is the right child.
+    pub bit: bool,
+    /// Set when the hashed node is a leaf of the batch rather than the output of the previous
+    /// cycle; for all but the first cycle, the output of the previous cycle is pushed onto the
+    /// stack.
+    pub is_leaf: bool,
+    /// Set when the sibling is popped from the top of the stack.
+    pub pop: bool,
+    /// Position (in the batch) of the leaf which is the sibling of the hashed leaf, if any.
+    pub sibling_leaf: Option<usize>,
+}
+
+impl BatchLayout {
+    /// Returns a layout for authenticating leaves at the specified indexes in a Merkle tree of the
+    /// specified depth.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * `indexes` is empty or is not sorted in strictly ascending order.
+    /// * Any of the indexes does not refer to a leaf of a tree of the specified depth.
+    /// * `tree_depth` is zero.
+    pub fn new(tree_depth: usize, indexes: &[usize]) -> Self {
+        assert!(tree_depth > 0, "tree depth must be greater than zero");
+        assert!(!indexes.is_empty(), "batch must contain at least one leaf");
+        for pair in indexes.windows(2) {
+            assert!(
+                pair[0] < pair[1],
+                "leaf indexes must be sorted in strictly ascending order"
+            );
+        }
+        let last_index = indexes[indexes.len() - 1];
+        assert!(
+            last_index.checked_shr(tree_depth as u32).unwrap_or(0) == 0,
+            "leaf index {} is out of bounds for a tree of depth {}",
+            last_index,
+            tree_depth
+        );
+
+        let mut cycles = Vec::new();
+        // nodes currently on the stack, each identified by its level and by its position within
+        // the level
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        let mut num_stack_slots = 1;
+
+        let mut first = 0;
+        while first < indexes.len() {
+            // a leaf with an even index is hashed together with the next leaf when the two are
+            // siblings; the path of the right leaf is followed in such a case
+            let last = if indexes[first] & 1 == 0
+                && first + 1 < indexes.len()
+                && indexes[first + 1] == indexes[first] + 1
+            {
+                first + 1
+            } else {
+                first
+            };
+            let index = indexes[last];
+
+            // the path is followed up to the node which is the sibling of a node on the path of
+            // the next leaf
+            let num_levels = match indexes.get(last + 1) {
+                Some(&next_index) => merge_level(index, next_index) - 1,
+                None => tree_depth,
+            };
+
+            if first > 0 {
+                let prev_index = indexes[first - 1];
+                let level = merge_level(prev_index, index) - 1;
+                stack.push((level, prev_index >> level));
+                num_stack_slots = num_stack_slots.max(stack.len());
+            }
+
+            for level in 0..num_levels {
+                let node = index >> level;
+                let pop = stack.last() == Some(&(level, node ^ 1));
+                if pop {
+                    stack.pop();
+                }
+                cycles.push(HashCycle {
+                    path: last,
+                    level,
+                    bit: node & 1 == 1,
+                    is_leaf: level == 0,
+                    pop,
+                    sibling_leaf: if level == 0 && last > first {
+                        Some(first)
+                    } else {
+                        None
+                    },
+                });
+            }
+
+            first = last + 1;
+        }
+        debug_assert!(stack.is_empty(), "all pushed nodes must be consumed");
+
+        BatchLayout {
+            cycles,
+            num_stack_slots,
+        }
+    }
+
+    /// Returns the number of hash cycles needed to compute the root of the tree.
+    pub fn num_cycles(&self) -> usize {
+        self.cycles.len()
+    }
+
+    /// Returns the hash cycle at the specified position; cycles which follow the computation of
+    /// the root are padding cycles for which no control flags are set.
+    pub fn cycle(&self, index: usize) -> HashCycle {
+        self.cycles.get(index).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of stack slots needed to hold all nodes pushed onto the stack.
+    pub fn num_stack_slots(&self) -> usize {
+        self.num_stack_slots
+    }
+
+    /// Returns the width of an execution trace for this layout.
+    pub fn trace_width(&self) -> usize {
+        STACK_COL + 2 * self.num_stack_slots
+    }
+
+    /// Returns the length of an execution trace for this layout.
+    pub fn trace_length(&self) -> usize {
+        get_padded_trace_length(self.num_cycles() * HASH_CYCLE_LEN)
+    }
+
+    /// Returns the step at which the root of the tree is located in the first two columns of an
+    /// execution trace for this layout.
+    pub fn root_step(&self) -> usize {
+        self.num_cycles() * HASH_CYCLE_LEN - 1
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the lowest level of a tree at which the paths of leaves at the specified indexes
+/// share a node.
+fn merge_level(a: usize, b: usize) -> usize {
+    (usize::BITS - (a ^ b).leading_zeros()) as usize
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::utils::rescue::{
+    self, CYCLE_LENGTH as HASH_CYCLE_LEN, NUM_ROUNDS as NUM_HASH_ROUNDS,
+    STATE_WIDTH as HASH_STATE_WIDTH,
+};
+use crate::{
+    explain::ParamsReport,
+    utils::{
+        check_trace_statistics, log_verified_statement, prove_checked,
+        rescue::{Hash, Rescue128},
+    },
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
+use core::marker::PhantomData;
+use log::debug;
+use rand_utils::{rand_value, rand_vector};
+use std::{collections::BTreeSet, time::Instant};
+use winterfell::{
+    crypto::{Digest, ElementHasher, MerkleTree},
+    math::{fields::f128::BaseElement, log2, FieldElement, StarkField},
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
+};
+
+mod air;
+use air::{MerkleBatchAir, PublicInputs};
+
+mod layout;
+use layout::{BatchLayout, HashCycle};
+
+mod prover;
+use prover::MerkleBatchProver;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Column which holds the position of the hashed node among its siblings.
+const BIT_COL: usize = 6;
+/// Column which is set when the hashed node is a leaf of the batch.
+const LEAF_FLAG_COL: usize = 7;
+/// Column which is set when the sibling is popped from the stack.
+const POP_FLAG_COL: usize = 8;
+/// Column which is set when the sibling is the preceding leaf of the batch.
+const PAIR_FLAG_COL: usize = 9;
+/// First of two columns which hold the hashed leaf.
+const LEAF_COL: usize = 10;
+/// First of two columns which hold the sibling leaf.
+const SIBLING_LEAF_COL: usize = 12;
+/// First column of the stack; every stack slot occupies two columns.
+const STACK_COL: usize = 14;
+
+// MERKLE BATCH MEMBERSHIP EXAMPLE
+// ================================================================================================
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(
+    options: &ExampleOptions,
+    num_leaves: usize,
+    tree_depth: usize,
+) -> Result<Box<dyn Example>, String> {
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
+
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(MerkleBatchExample::<Blake3_192>::new(
+            num_leaves, tree_depth, options,
+        ))),
+        HashFunction::Blake3_256 => Ok(Box::new(MerkleBatchExample::<Blake3_256>::new(
+            num_leaves, tree_depth, options,
+        ))),
+        HashFunction::Sha3_256 => Ok(Box::new(MerkleBatchExample::<Sha3_256>::new(
+            num_leaves, tree_depth, options,
+        ))),
+        HashFunction::Keccak256 => Ok(Box::new(MerkleBatchExample::<Keccak_256>::new(
+            num_leaves, tree_depth, options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(MerkleBatchExample::<Blake2s_256>::new(
+            num_leaves, tree_depth, options,
+        ))),
+        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+    }
+}
+
+pub struct MerkleBatchExample<H: ElementHasher> {
+    options: ProofOptions,
+    tree_root: Hash,
+    tree_depth: usize,
+    leaves: Vec<(usize, Hash)>,
+    paths: Vec<Vec<Hash>>,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> MerkleBatchExample<H> {
+    /// Returns an example which authenticates the specified number of randomly selected leaves
+    /// in a Merkle tree of the specified depth.
+    ///
+    /// # Panics
+    /// Panics if `num_leaves` is zero or is greater than the number of leaves in the tree.
+    pub fn new(num_leaves: usize, tree_depth: usize, options: ProofOptions) -> Self {
+        assert!(num_leaves > 0, "number of leaves must be greater than zero");
+        assert!(
+            tree_depth < 32 && num_leaves <= usize::pow(2, tree_depth as u32),
+            "number of leaves cannot exceed the number of leaves in the tree"
+        );
+        let mut indexes = BTreeSet::new();
+        while indexes.len() < num_leaves {
+            let index = rand_value::<BaseElement>().as_int() % u128::pow(2, tree_depth as u32);
+            indexes.insert(index as usize);
+        }
+        Self::with_indexes(
+            tree_depth,
+            &indexes.into_iter().collect::<Vec<_>>(),
+            options,
+        )
+    }
+
+    /// Returns an example which authenticates leaves at the specified indexes in a Merkle tree
+    /// of the specified depth; indexes must be sorted in strictly ascending order.
+    pub fn with_indexes(tree_depth: usize, indexes: &[usize], options: ProofOptions) -> Self {
+        // build Merkle tree of the specified depth
+        let now = Instant::now();
+        let tree = build_merkle_tree(tree_depth);
+        debug!(
+            "Built Merkle tree of depth {} in {} ms",
+            tree_depth,
+            now.elapsed().as_millis(),
+        );
+
+        // compute Merkle paths from the leaves specified by the indexes
+        let now = Instant::now();
+        let paths = indexes
+            .iter()
+            .map(|&index| tree.prove(index).unwrap())
+            .collect::<Vec<_>>();
+        debug!(
+            "Computed {} Merkle paths to root {} in {} ms",
+            indexes.len(),
+            hex::encode(tree.root().as_bytes()),
+            now.elapsed().as_millis(),
+        );
+
+        MerkleBatchExample {
+            options,
+            tree_root: *tree.root(),
+            tree_depth,
+            leaves: indexes
+                .iter()
+                .zip(paths.iter())
+                .map(|(&index, path)| (index, path[0]))
+                .collect(),
+            paths,
+            _hasher: PhantomData,
+        }
+    }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            tree_root: self.tree_root.to_elements(),
+            tree_depth: self.tree_depth,
+            leaves: self
+                .leaves
+                .iter()
+                .map(|(index, leaf)| (*index, leaf.to_elements()))
+                .collect(),
+        }
+    }
+
+    /// Returns the layout of the execution trace for this example.
+    fn layout(&self) -> BatchLayout {
+        let indexes = self
+            .leaves
+            .iter()
+            .map(|&(index, _)| index)
+            .collect::<Vec<_>>();
+        BatchLayout::new(self.tree_depth, &indexes)
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl<H: ElementHasher> Example for MerkleBatchExample<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for proving membership of {} leaves in a Merkle tree of depth {}\n\
+            ---------------------",
+            self.leaves.len(),
+            self.tree_depth
+        );
+        // create the prover
+        let prover =
+            MerkleBatchProver::<H>::new(self.options.clone(), self.tree_depth, self.leaves.clone());
+
+        // generate the execution trace
+        let now = Instant::now();
+        let trace = prover.build_trace(&self.paths);
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace.width(),
+            log2(trace_length),
+            now.elapsed().as_millis()
+        );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement =
+            winterfell::verify_and_extract::<MerkleBatchAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        // replace the digest of the last leaf with a digest which is not in the tree
+        let mut pub_inputs = self.pub_inputs();
+        let last = pub_inputs.leaves.len() - 1;
+        pub_inputs.leaves[last].1[0] += BaseElement::ONE;
+        winterfell::verify::<MerkleBatchAir, H>(proof, pub_inputs)
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let layout = self.layout();
+        let trace_info = TraceInfo::new(layout.trace_width(), layout.trace_length());
+        let air = MerkleBatchAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn build_merkle_tree(depth: usize) -> MerkleTree<Rescue128> {
+    let num_leaves = usize::pow(2, depth as u32);
+    let leaf_elements: Vec<BaseElement> = rand_vector(num_leaves * 2);
+    let mut leaves = Vec::new();
+    for i in (0..leaf_elements.len()).step_by(2) {
+        leaves.push(Hash::new(leaf_elements[i], leaf_elements[i + 1]));
+    }
+    MerkleTree::new(leaves).unwrap()
+}
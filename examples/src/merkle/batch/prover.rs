@@ -0,0 +1,183 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    rand_vector, rescue, BaseElement, BatchLayout, ElementHasher, FieldElement, Hash, HashCycle,
+    MerkleBatchAir, PhantomData, ProofOptions, Prover, PublicInputs, TraceTable, BIT_COL,
+    HASH_CYCLE_LEN, HASH_STATE_WIDTH, LEAF_COL, LEAF_FLAG_COL, NUM_HASH_ROUNDS, PAIR_FLAG_COL,
+    POP_FLAG_COL, SIBLING_LEAF_COL, STACK_COL,
+};
+
+// MERKLE BATCH PROVER
+// ================================================================================================
+
+pub struct MerkleBatchProver<H: ElementHasher> {
+    options: ProofOptions,
+    tree_depth: usize,
+    leaves: Vec<(usize, Hash)>,
+    layout: BatchLayout,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> MerkleBatchProver<H> {
+    /// Returns a prover for authenticating the specified leaves in a Merkle tree of the specified
+    /// depth; leaves are specified by their indexes and digests, and must be sorted by index.
+    pub fn new(options: ProofOptions, tree_depth: usize, leaves: Vec<(usize, Hash)>) -> Self {
+        let indexes = leaves.iter().map(|&(index, _)| index).collect::<Vec<_>>();
+        let layout = BatchLayout::new(tree_depth, &indexes);
+        Self {
+            options,
+            tree_depth,
+            leaves,
+            layout,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Builds an execution trace which authenticates the leaves of this prover; `paths` must
+    /// contain a Merkle path (as returned by `MerkleTree::prove()`) for every leaf.
+    pub fn build_trace(&self, paths: &[Vec<Hash>]) -> TraceTable<BaseElement> {
+        assert_eq!(
+            self.leaves.len(),
+            paths.len(),
+            "a path is required for every leaf"
+        );
+
+        // allocate memory to hold the trace table
+        let trace_length = self.layout.trace_length();
+        let mut trace = TraceTable::new(self.layout.trace_width(), trace_length);
+
+        // the stack starts with random values; they are never popped from the stack
+        let initial_stack: Vec<BaseElement> = rand_vector(2 * self.layout.num_stack_slots());
+
+        trace.fill(
+            |state| {
+                state[..STACK_COL].fill(BaseElement::ZERO);
+                state[STACK_COL..].copy_from_slice(&initial_stack);
+                self.init_cycle(state, 0, paths);
+            },
+            |step, state| {
+                // For the first 7 steps of each 8-step cycle, compute a single round of Rescue
+                // hash in registers [0..6]. On the 8th step, set up the hash state, the control
+                // columns, and the stack for the next cycle.
+                if step % HASH_CYCLE_LEN < NUM_HASH_ROUNDS {
+                    rescue::apply_round(&mut state[..HASH_STATE_WIDTH], step);
+                } else {
+                    self.init_cycle(state, step / HASH_CYCLE_LEN + 1, paths);
+                }
+            },
+        );
+
+        // control and leaf columns are constrained only at the first step of every cycle; filling
+        // the remaining steps with random values makes degrees of transition constraints stable
+        for col in BIT_COL..STACK_COL {
+            let values: Vec<BaseElement> = rand_vector(trace_length);
+            for (step, &value) in values.iter().enumerate() {
+                if step % HASH_CYCLE_LEN != 0 {
+                    trace.set(col, step, value);
+                }
+            }
+        }
+
+        trace
+    }
+
+    /// Updates the state so that it holds the first row of the specified cycle.
+    fn init_cycle(&self, state: &mut [BaseElement], cycle_num: usize, paths: &[Vec<Hash>]) {
+        let cycle = self.layout.cycle(cycle_num);
+        let output = [state[0], state[1]];
+
+        // the sibling is determined before the stack is updated because it may be popped from
+        // the top of the stack; siblings of padding cycles are arbitrary
+        let sibling = if cycle.pop {
+            [state[STACK_COL], state[STACK_COL + 1]]
+        } else if let Some(j) = cycle.sibling_leaf {
+            self.leaves[j].1.to_elements()
+        } else if cycle_num < self.layout.num_cycles() {
+            paths[cycle.path][cycle.level + 1].to_elements()
+        } else {
+            [BaseElement::ZERO; 2]
+        };
+        let node = if cycle.is_leaf {
+            self.leaves[cycle.path].1.to_elements()
+        } else {
+            output
+        };
+
+        // push the output of the previous cycle onto the stack when a new leaf starts, and
+        // remove the top of the stack when it is used as the sibling
+        let stack = &mut state[STACK_COL..];
+        let stack_width = stack.len();
+        if cycle.is_leaf && cycle_num > 0 {
+            stack.copy_within(..stack_width - 2, 2);
+            stack[..2].copy_from_slice(&output);
+        } else if cycle.pop {
+            stack.copy_within(2.., 0);
+            stack[stack_width - 2..].fill(BaseElement::ZERO);
+        }
+
+        // if index bit is zero, the node goes into registers [0, 1] and the sibling goes into
+        // registers [2, 3]; otherwise, the positions are swapped
+        if cycle.bit {
+            state[..2].copy_from_slice(&sibling);
+            state[2..4].copy_from_slice(&node);
+        } else {
+            state[..2].copy_from_slice(&node);
+            state[2..4].copy_from_slice(&sibling);
+        }
+        // reset the capacity registers of the state to ZERO
+        state[4] = BaseElement::ZERO;
+        state[5] = BaseElement::ZERO;
+
+        self.set_control_columns(state, &cycle);
+    }
+
+    fn set_control_columns(&self, state: &mut [BaseElement], cycle: &HashCycle) {
+        state[BIT_COL] = BaseElement::from(cycle.bit as u8);
+        state[LEAF_FLAG_COL] = BaseElement::from(cycle.is_leaf as u8);
+        state[POP_FLAG_COL] = BaseElement::from(cycle.pop as u8);
+        state[PAIR_FLAG_COL] = BaseElement::from(cycle.sibling_leaf.is_some() as u8);
+
+        let leaf = if cycle.is_leaf {
+            self.leaves[cycle.path].1.to_elements()
+        } else {
+            [BaseElement::ZERO; 2]
+        };
+        state[LEAF_COL..LEAF_COL + 2].copy_from_slice(&leaf);
+
+        let sibling_leaf = match cycle.sibling_leaf {
+            Some(j) => self.leaves[j].1.to_elements(),
+            None => [BaseElement::ZERO; 2],
+        };
+        state[SIBLING_LEAF_COL..SIBLING_LEAF_COL + 2].copy_from_slice(&sibling_leaf);
+    }
+}
+
+impl<H: ElementHasher> Prover for MerkleBatchProver<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    type BaseField = BaseElement;
+    type Air = MerkleBatchAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let root_step = self.layout.root_step();
+        PublicInputs {
+            tree_root: [trace.get(0, root_step), trace.get(1, root_step)],
+            tree_depth: self.tree_depth,
+            leaves: self
+                .leaves
+                .iter()
+                .map(|(index, leaf)| (*index, leaf.to_elements()))
+                .collect(),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
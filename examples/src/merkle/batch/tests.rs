@@ -0,0 +1,81 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BatchLayout, Blake3_256, MerkleBatchExample};
+use winterfell::{FieldExtension, ProofOptions};
+
+#[test]
+fn merkle_batch_test_adjacent_leaves() {
+    // paths of adjacent leaves share all nodes above the leaves, and thus, only 7 nodes are
+    // computed below level 3, and 4 nodes above it
+    let indexes = (8..16).collect::<Vec<_>>();
+    assert_eq!(11, BatchLayout::new(7, &indexes).num_cycles());
+
+    let merkle = Box::new(MerkleBatchExample::<Blake3_256>::with_indexes(
+        7,
+        &indexes,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(merkle);
+}
+
+#[test]
+fn merkle_batch_test_spread_leaves() {
+    // paths of spread leaves do not share any nodes below level 5
+    let indexes = [0, 33, 66, 127];
+    assert_eq!(23, BatchLayout::new(7, &indexes).num_cycles());
+
+    let merkle = Box::new(MerkleBatchExample::<Blake3_256>::with_indexes(
+        7,
+        &indexes,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(merkle);
+}
+
+#[test]
+fn merkle_batch_test_nested_leaves() {
+    // nodes computed for the first three leaves are popped only after the sibling leaves 14 and
+    // 15 are hashed together, and thus, the stack needs three slots
+    let indexes = [3, 8, 13, 14, 15, 100];
+    let layout = BatchLayout::new(7, &indexes);
+    assert_eq!(3, layout.num_stack_slots());
+
+    let merkle = Box::new(MerkleBatchExample::<Blake3_256>::with_indexes(
+        7,
+        &indexes,
+        build_options(true),
+    ));
+    crate::tests::test_basic_proof_verification(merkle);
+}
+
+#[test]
+fn merkle_batch_test_single_leaf() {
+    let merkle = Box::new(MerkleBatchExample::<Blake3_256>::new(
+        1,
+        5,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(merkle);
+}
+
+#[test]
+fn merkle_batch_test_wrong_leaf_fail() {
+    let merkle = Box::new(MerkleBatchExample::<Blake3_256>::new(
+        4,
+        7,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification_fail(merkle);
+}
+
+fn build_options(use_extension_field: bool) -> ProofOptions {
+    let extension = if use_extension_field {
+        FieldExtension::Quadratic
+    } else {
+        FieldExtension::None
+    };
+    ProofOptions::new(28, 8, 0, extension, 4, 256)
+}
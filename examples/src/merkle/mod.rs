@@ -8,8 +8,13 @@ use crate::utils::rescue::{
     STATE_WIDTH as HASH_STATE_WIDTH,
 };
 use crate::{
-    utils::rescue::{Hash, Rescue128},
-    Blake3_192, Blake3_256, Example, ExampleOptions, HashFunction, Sha3_256,
+    explain::ParamsReport,
+    utils::{
+        check_trace_statistics, log_verified_statement, prove_checked,
+        rescue::{Hash, Rescue128},
+    },
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
 };
 use core::marker::PhantomData;
 use log::debug;
@@ -18,9 +23,12 @@ use std::time::Instant;
 use winterfell::{
     crypto::{Digest, ElementHasher, MerkleTree},
     math::{fields::f128::BaseElement, log2, FieldElement, StarkField},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
 };
 
+pub mod batch;
+
 mod air;
 use air::{MerkleAir, PublicInputs};
 
@@ -37,11 +45,16 @@ const TRACE_WIDTH: usize = 7;
 
 // MERKLE AUTHENTICATION PATH EXAMPLE
 // ================================================================================================
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     tree_depth: usize,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(28, 8);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(MerkleExample::<Blake3_192>::new(
@@ -53,6 +66,12 @@ pub fn get_example(
         HashFunction::Sha3_256 => Ok(Box::new(MerkleExample::<Sha3_256>::new(
             tree_depth, options,
         ))),
+        HashFunction::Keccak256 => Ok(Box::new(MerkleExample::<Keccak_256>::new(
+            tree_depth, options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(MerkleExample::<Blake2s_256>::new(
+            tree_depth, options,
+        ))),
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
@@ -104,6 +123,12 @@ impl<H: ElementHasher> MerkleExample<H> {
             _hasher: PhantomData,
         }
     }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            tree_root: self.tree_root.to_elements(),
+        }
+    }
 }
 
 // EXAMPLE IMPLEMENTATION
@@ -133,16 +158,15 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs = PublicInputs {
-            tree_root: self.tree_root.to_elements(),
-        };
-        winterfell::verify::<MerkleAir, H>(proof, pub_inputs)
+        let statement = winterfell::verify_and_extract::<MerkleAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -152,6 +176,12 @@ where
         };
         winterfell::verify::<MerkleAir, H>(proof, pub_inputs)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.path.len() * HASH_CYCLE_LEN);
+        let air = MerkleAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
 
 // HELPER FUNCTIONS
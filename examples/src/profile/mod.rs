@@ -0,0 +1,208 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Lightweight profiler for prover phases.
+//!
+//! The prover reports the time spent in each of its phases via debug log records (e.g.,
+//! "Evaluated constraints over domain of 2^10 elements in 12 ms"). The profiler is implemented
+//! as a logger which forwards all records to an inner logger, and while a profiling session is
+//! active, aggregates the timings reported on the profiled thread into folded stacks of the form
+//! `prove;phase;subphase count_ms`. The resulting text can be consumed by inferno or flamegraph
+//! tooling.
+//!
+//! The profiler needs to be installed as the global logger; when it is not installed, proof
+//! generation is not affected in any way.
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::{
+    fs, io,
+    path::Path,
+    sync::Mutex,
+    thread::{self, ThreadId},
+    time::Instant,
+};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Name of the root frame of all stacks.
+const ROOT_FRAME: &str = "prove";
+
+/// Maps fragments of log messages to the stacks (relative to the root frame) of the phases they
+/// describe; the first matching fragment determines the phase.
+#[rustfmt::skip]
+const PHASES: [(&str, &str); 14] = [
+    ("Generated execution trace", "build_trace"),
+    ("Built domain", "build_domain"),
+    ("Extended execution trace", "commit_trace;extend_trace"),
+    ("Computed execution trace commitment", "commit_trace;build_merkle_tree"),
+    ("Built auxiliary trace segment", "commit_trace;build_aux_segment"),
+    ("Evaluated constraints", "commit_constraints;evaluate_constraints"),
+    ("Converted constraint evaluations", "commit_constraints;interpolate_composition"),
+    ("composition polynomial columns over", "commit_constraints;extend_composition"),
+    ("Computed constraint evaluation commitment", "commit_constraints;build_merkle_tree"),
+    ("Built DEEP composition polynomial", "deep_composition;build_poly"),
+    ("Evaluated DEEP composition polynomial", "deep_composition;evaluate_poly"),
+    ("FRI layers", "fri;build_layers"),
+    ("query positions", "queries;draw_positions"),
+    ("Built proof object", "build_proof"),
+];
+
+/// Stack for timed log records which do not match any of the known phases.
+const OTHER_PHASE: &str = "other";
+
+// PROFILING LOGGER
+// ================================================================================================
+
+/// A logger which aggregates timings of prover phases reported via log records into folded
+/// stacks while a profiling session is active.
+pub struct ProfilingLogger {
+    inner: Option<Box<dyn Log>>,
+    session: Mutex<Option<Session>>,
+}
+
+/// An active profiling session; only records emitted by the profiled thread are aggregated.
+struct Session {
+    thread: ThreadId,
+    stacks: FoldedStacks,
+}
+
+impl ProfilingLogger {
+    /// Profiles execution of the specified closure and returns its result together with the
+    /// folded stacks of prover phases executed by the closure.
+    ///
+    /// The time which was not attributed to any of the phases recorded during this session is
+    /// attributed to the root frame.
+    pub fn profile<T, F: FnOnce() -> T>(&self, f: F) -> (T, FoldedStacks) {
+        *self.session.lock().unwrap() = Some(Session {
+            thread: thread::current().id(),
+            stacks: FoldedStacks::default(),
+        });
+
+        let now = Instant::now();
+        let result = f();
+        let total_ms = now.elapsed().as_millis() as u64;
+
+        let mut stacks = self
+            .session
+            .lock()
+            .unwrap()
+            .take()
+            .expect("profiling session has been closed")
+            .stacks;
+        stacks.add(ROOT_FRAME, total_ms.saturating_sub(stacks.total_ms()));
+        (result, stacks)
+    }
+}
+
+impl Log for ProfilingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match &self.inner {
+            Some(inner) => inner.enabled(metadata),
+            None => true,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(inner) = &self.inner {
+            inner.log(record);
+        }
+
+        let mut session = self.session.lock().unwrap();
+        if let Some(session) = session.as_mut() {
+            if session.thread == thread::current().id() {
+                if let Some((stack, count_ms)) = parse_phase(&record.args().to_string()) {
+                    session.stacks.add(&stack, count_ms);
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            inner.flush();
+        }
+    }
+}
+
+/// Installs a new profiling logger as the global logger and returns a reference to it.
+///
+/// All records are forwarded to the `inner` logger (if provided), and the max log level is set
+/// to debug so that timings of all prover phases are reported.
+///
+/// # Errors
+/// Returns an error if a global logger has already been installed.
+pub fn install(inner: Option<Box<dyn Log>>) -> Result<&'static ProfilingLogger, SetLoggerError> {
+    let logger: &'static ProfilingLogger = Box::leak(Box::new(ProfilingLogger {
+        inner,
+        session: Mutex::new(None),
+    }));
+    log::set_logger(logger)?;
+    log::set_max_level(LevelFilter::Debug);
+    Ok(logger)
+}
+
+// FOLDED STACKS
+// ================================================================================================
+
+/// Aggregated time (in milliseconds) spent in each of the recorded stacks.
+///
+/// Stacks are kept in the order in which they were first recorded.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FoldedStacks {
+    stacks: Vec<(String, u64)>,
+}
+
+impl FoldedStacks {
+    /// Adds `count_ms` to the time spent in the specified stack.
+    pub fn add(&mut self, stack: &str, count_ms: u64) {
+        match self.stacks.iter_mut().find(|(s, _)| s == stack) {
+            Some((_, count)) => *count += count_ms,
+            None => self.stacks.push((stack.to_string(), count_ms)),
+        }
+    }
+
+    /// Returns recorded stacks together with the time spent in each of them.
+    pub fn stacks(&self) -> &[(String, u64)] {
+        &self.stacks
+    }
+
+    /// Returns the total time spent in all recorded stacks.
+    pub fn total_ms(&self) -> u64 {
+        self.stacks.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Returns the stacks formatted as folded-stack lines, one line per stack.
+    pub fn to_folded(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|(stack, count)| format!("{stack} {count}\n"))
+            .collect()
+    }
+
+    /// Writes the stacks formatted as folded-stack lines into the file at the specified path.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_folded())
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the stack and the duration of the phase described by the specified log message, or
+/// None if the message does not report a duration (i.e., does not end with "in <n> ms").
+fn parse_phase(message: &str) -> Option<(String, u64)> {
+    let (description, count_ms) = message.strip_suffix(" ms")?.rsplit_once(" in ")?;
+    let count_ms = count_ms.parse().ok()?;
+
+    let phase = PHASES
+        .iter()
+        .find(|(fragment, _)| description.contains(fragment))
+        .map_or(OTHER_PHASE, |(_, phase)| phase);
+    Some((format!("{ROOT_FRAME};{phase}"), count_ms))
+}
@@ -0,0 +1,94 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{install, parse_phase, FoldedStacks};
+use crate::{fibonacci::fib2::FibExample, Blake3_256, Example};
+use std::fs;
+use winterfell::{FieldExtension, ProofOptions};
+
+#[test]
+fn parse_phase_messages() {
+    assert_eq!(
+        Some((
+            "prove;commit_constraints;evaluate_constraints".to_string(),
+            12
+        )),
+        parse_phase("Evaluated constraints over domain of 2^10 elements in 12 ms")
+    );
+    assert_eq!(
+        Some(("prove;commit_constraints;extend_composition".to_string(), 3)),
+        parse_phase(
+            "Evaluated 2 composition polynomial columns over LDE domain (2^9 elements) in 3 ms"
+        )
+    );
+    assert_eq!(
+        Some(("prove;other".to_string(), 7)),
+        parse_phase("Did something else in 7 ms")
+    );
+
+    // messages which do not report a duration are ignored
+    assert_eq!(None, parse_phase("Proof size: 12.5 KB"));
+    assert_eq!(None, parse_phase("Proof verified in 1.5 ms"));
+}
+
+#[test]
+fn folded_stacks_aggregation() {
+    let mut stacks = FoldedStacks::default();
+    stacks.add("prove;fri;build_layers", 5);
+    stacks.add("prove;build_domain", 1);
+    stacks.add("prove;fri;build_layers", 2);
+    assert_eq!(8, stacks.total_ms());
+    assert_eq!(
+        "prove;fri;build_layers 7\nprove;build_domain 1\n",
+        stacks.to_folded()
+    );
+}
+
+#[test]
+fn profile_fib_proof() {
+    let fib = FibExample::<Blake3_256>::new(1024, build_options());
+    let profiler = install(None).expect("failed to install profiler");
+    let (proof, stacks) = profiler.profile(|| fib.prove());
+    assert!(fib.verify(proof).is_ok());
+
+    let path = std::env::temp_dir().join("winterfell_profile_fib.folded");
+    stacks.write_to_file(&path).unwrap();
+    let folded = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // every line must consist of a non-empty stack of frames rooted at the prove frame and an
+    // integer count separated by a single space
+    assert!(!folded.is_empty());
+    for line in folded.lines() {
+        let (stack, count) = line.rsplit_once(' ').unwrap();
+        assert!(count.parse::<u64>().is_ok());
+        assert_eq!(Some("prove"), stack.split(';').next());
+        assert!(stack
+            .split(';')
+            .all(|frame| !frame.is_empty() && !frame.contains(' ')));
+    }
+
+    // all proving phases must be reported
+    for stack in [
+        "prove;build_trace",
+        "prove;commit_trace;extend_trace",
+        "prove;commit_constraints;evaluate_constraints",
+        "prove;deep_composition;build_poly",
+        "prove;fri;build_layers",
+        "prove;queries;draw_positions",
+        "prove;build_proof",
+    ] {
+        assert!(folded
+            .lines()
+            .any(|line| line.starts_with(&format!("{stack} "))));
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_options() -> ProofOptions {
+    ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256)
+}
@@ -14,7 +14,7 @@ use winterfell::{
 // ================================================================================================
 
 /// Specifies steps on which Rescue transition function is applied.
-const CYCLE_MASK: [BaseElement; CYCLE_LENGTH] = [
+pub(super) const CYCLE_MASK: [BaseElement; CYCLE_LENGTH] = [
     BaseElement::ONE,
     BaseElement::ONE,
     BaseElement::ONE,
@@ -36,6 +36,7 @@ const CYCLE_MASK: [BaseElement; CYCLE_LENGTH] = [
 // RESCUE AIR
 // ================================================================================================
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct PublicInputs {
     pub seed: [BaseElement; 2],
     pub result: [BaseElement; 2],
@@ -128,7 +129,12 @@ impl Air for RescueAir {
 /// when flag = 1, enforces that the next state of the computation is defined like so:
 /// - the first two registers are equal to the values from the previous step
 /// - the other two registers are equal to 0
-fn enforce_hash_copy<E: FieldElement>(result: &mut [E], current: &[E], next: &[E], flag: E) {
+pub(super) fn enforce_hash_copy<E: FieldElement>(
+    result: &mut [E],
+    current: &[E],
+    next: &[E],
+    flag: E,
+) {
     result.agg_constraint(0, flag, are_equal(current[0], next[0]));
     result.agg_constraint(1, flag, are_equal(current[1], next[1]));
     result.agg_constraint(2, flag, is_zero(next[2]));
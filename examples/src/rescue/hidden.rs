@@ -0,0 +1,309 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    air::{enforce_hash_copy, CYCLE_MASK},
+    compute_hash_chain, debug, log2, prove_checked,
+    rescue::{self, RescueGadget},
+    BaseElement, ElementHasher, Example, FieldElement, Instant, PhantomData, ProofOptions, Prover,
+    RescueProver, StarkProof, Trace, TraceTable, VerifierError, CYCLE_LENGTH, NUM_HASH_ROUNDS,
+    TRACE_WIDTH,
+};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, not},
+};
+use rand_utils::rand_array;
+use winterfell::{
+    hidden::{HashGadget, HiddenAssertions},
+    Air, AirContext, Assertion, ByteWriter, DegeneracyThresholds, EvaluationFrame, Serializable,
+    TraceInfo, TransitionConstraintDegree,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Width of the trace with the columns of the two hidden assertions appended to it.
+const HIDDEN_TRACE_WIDTH: usize = TRACE_WIDTH + 2 * (rescue::STATE_WIDTH + 1);
+
+// RESCUE HASH CHAIN WITH HIDDEN RESULT
+// ================================================================================================
+
+/// Same as [RescueExample](super::RescueExample), but the result of the hash chain is not
+/// revealed to the verifier; instead, each element of the result is asserted against its Rescue
+/// digest.
+pub struct HiddenRescueExample<H: ElementHasher> {
+    options: ProofOptions,
+    chain_length: usize,
+    seed: [BaseElement; 2],
+    digests: [[BaseElement; 2]; 2],
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> HiddenRescueExample<H> {
+    pub fn new(chain_length: usize, options: ProofOptions) -> Self {
+        assert!(
+            chain_length.is_power_of_two() && chain_length >= 2,
+            "chain length must be a power of 2 and must be at least 2"
+        );
+        let seed = [BaseElement::from(42u8), BaseElement::from(43u8)];
+
+        // compute the sequence of hashes using external implementation of Rescue hash
+        let now = Instant::now();
+        let result = compute_hash_chain(seed, chain_length);
+        debug!(
+            "Computed a chain of {} Rescue hashes in {} ms",
+            chain_length,
+            now.elapsed().as_millis(),
+        );
+
+        HiddenRescueExample {
+            options,
+            chain_length,
+            seed,
+            digests: result.map(get_digest),
+            _hasher: PhantomData,
+        }
+    }
+
+    fn pub_inputs(&self) -> HiddenPublicInputs {
+        HiddenPublicInputs {
+            seed: self.seed,
+            digests: self.digests,
+        }
+    }
+}
+
+impl<H: ElementHasher> Example for HiddenRescueExample<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for computing a chain of {} Rescue hashes with a hidden result\n\
+            ---------------------",
+            self.chain_length
+        );
+
+        // create a prover
+        let prover = HiddenRescueProver::<H>::new(self.options.clone());
+
+        // generate the execution trace
+        let now = Instant::now();
+        let trace = prover.build_trace(self.seed, self.chain_length);
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace.width(),
+            log2(trace.length()),
+            now.elapsed().as_millis()
+        );
+
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement =
+            winterfell::verify_and_extract::<HiddenRescueAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let mut pub_inputs = self.pub_inputs();
+        pub_inputs.digests[1][1] += BaseElement::ONE;
+        winterfell::verify::<HiddenRescueAir, H>(proof, pub_inputs)
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_length = self.chain_length * CYCLE_LENGTH;
+        let trace_info = TraceInfo::new(HIDDEN_TRACE_WIDTH, trace_length);
+        let air = HiddenRescueAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
+
+// AIR
+// ================================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HiddenPublicInputs {
+    pub seed: [BaseElement; 2],
+    pub digests: [[BaseElement; 2]; 2],
+}
+
+impl Serializable for HiddenPublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(&self.seed[..]);
+        target.write(&self.digests[0][..]);
+        target.write(&self.digests[1][..]);
+    }
+}
+
+pub struct HiddenRescueAir {
+    context: AirContext<BaseElement>,
+    seed: [BaseElement; 2],
+    hidden: HiddenAssertions<BaseElement, RescueGadget>,
+}
+
+impl Air for HiddenRescueAir {
+    type BaseField = BaseElement;
+    type PublicInputs = HiddenPublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: HiddenPublicInputs, options: ProofOptions) -> Self {
+        let hidden = build_hidden_assertions(trace_info.length(), pub_inputs.digests);
+        let mut degrees = vec![
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+        ];
+        degrees.append(&mut hidden.constraint_degrees());
+        assert_eq!(HIDDEN_TRACE_WIDTH, trace_info.width());
+        HiddenRescueAir {
+            context: AirContext::new(trace_info, degrees, 2 + hidden.num_assertions(), options),
+            seed: pub_inputs.seed,
+            hidden,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = &frame.current()[..TRACE_WIDTH];
+        let next = &frame.next()[..TRACE_WIDTH];
+
+        // the first 9 periodic columns are used by the hash chain; the remaining ones are used
+        // by the hidden assertions
+        let hash_flag = periodic_values[0];
+        let ark = &periodic_values[1..9];
+
+        // constraints of the hash chain are the same as in the regular Rescue example
+        rescue::enforce_round(result, current, next, ark, hash_flag);
+        enforce_hash_copy(result, current, next, not(hash_flag));
+
+        // constraints of the hidden assertions are placed after the hash chain constraints
+        self.hidden
+            .evaluate_transition(frame, &periodic_values[9..], &mut result[TRACE_WIDTH..]);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        // assert starting values of the hash chain and digests of its ending values
+        let mut result = vec![
+            Assertion::single(0, 0, self.seed[0]),
+            Assertion::single(1, 0, self.seed[1]),
+        ];
+        result.append(&mut self.hidden.get_assertions());
+        result
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut result = vec![CYCLE_MASK.to_vec()];
+        result.append(&mut rescue::get_round_constants());
+        result.append(&mut self.hidden.get_periodic_column_values());
+        result
+    }
+}
+
+// PROVER
+// ================================================================================================
+
+pub struct HiddenRescueProver<H: ElementHasher> {
+    options: ProofOptions,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> HiddenRescueProver<H> {
+    pub fn new(options: ProofOptions) -> Self {
+        Self {
+            options,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Builds the trace of a regular Rescue hash chain and appends to it the columns of hidden
+    /// assertions against the result of the chain; advice columns are padded with random values.
+    pub fn build_trace(
+        &self,
+        seed: [BaseElement; 2],
+        iterations: usize,
+    ) -> TraceTable<BaseElement> {
+        let trace = RescueProver::<H>::new(self.options.clone()).build_trace(seed, iterations);
+        let last_step = trace.length() - 1;
+        let result = [trace.get(0, last_step), trace.get(1, last_step)];
+
+        let hidden = build_hidden_assertions(trace.length(), result.map(get_digest));
+        let mut columns = (0..TRACE_WIDTH)
+            .map(|i| trace.get_column(i).to_vec())
+            .collect::<Vec<_>>();
+        columns.append(&mut hidden.build_columns(&result, rand_array()));
+        TraceTable::init(columns)
+    }
+}
+
+impl<H: ElementHasher> Prover for HiddenRescueProver<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    type BaseField = BaseElement;
+    type Air = HiddenRescueAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> HiddenPublicInputs {
+        // digests are read from the hash states of the hidden assertions; both assertions are
+        // placed against the last step, and thus, their digests are computed in the last cycle
+        let digest_step = trace.length() - CYCLE_LENGTH + NUM_HASH_ROUNDS;
+        let read_digest = |column: usize| {
+            [
+                trace.get(column, digest_step),
+                trace.get(column + 1, digest_step),
+            ]
+        };
+        HiddenPublicInputs {
+            seed: [trace.get(0, 0), trace.get(1, 0)],
+            digests: [
+                read_digest(TRACE_WIDTH + 1),
+                read_digest(TRACE_WIDTH + rescue::STATE_WIDTH + 2),
+            ],
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns hidden assertions against both elements of the result of the hash chain.
+pub(super) fn build_hidden_assertions(
+    trace_length: usize,
+    digests: [[BaseElement; 2]; 2],
+) -> HiddenAssertions<BaseElement, RescueGadget> {
+    let last_step = trace_length - 1;
+    let assertions = vec![
+        Assertion::hidden(0, last_step, digests[0].to_vec()),
+        Assertion::hidden(1, last_step, digests[1].to_vec()),
+    ];
+    HiddenAssertions::new(TRACE_WIDTH, trace_length, assertions)
+}
+
+/// Returns the Rescue digest of the specified value.
+pub(super) fn get_digest(value: BaseElement) -> [BaseElement; 2] {
+    let digest = RescueGadget::digest(value);
+    [digest[0], digest[1]]
+}
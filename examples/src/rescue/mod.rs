@@ -3,14 +3,21 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::{Blake3_192, Blake3_256, Example, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f128::BaseElement, log2, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    planning::{estimate_prover_cost, CostEstimate, MachineProfile},
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
 };
 
 #[allow(clippy::module_inception)]
@@ -22,6 +29,9 @@ use air::{PublicInputs, RescueAir};
 mod prover;
 use prover::RescueProver;
 
+mod hidden;
+pub use hidden::HiddenRescueExample;
+
 #[cfg(test)]
 mod tests;
 
@@ -35,29 +45,39 @@ const TRACE_WIDTH: usize = 4;
 // RESCUE HASH CHAIN EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 4,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     chain_length: usize,
+    hidden: bool,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(42, 4);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
-        HashFunction::Blake3_192 => Ok(Box::new(RescueExample::<Blake3_192>::new(
-            chain_length,
-            options,
-        ))),
-        HashFunction::Blake3_256 => Ok(Box::new(RescueExample::<Blake3_256>::new(
-            chain_length,
-            options,
-        ))),
-        HashFunction::Sha3_256 => Ok(Box::new(RescueExample::<Sha3_256>::new(
-            chain_length,
-            options,
-        ))),
+        HashFunction::Blake3_192 => Ok(build_example::<Blake3_192>(chain_length, options, hidden)),
+        HashFunction::Blake3_256 => Ok(build_example::<Blake3_256>(chain_length, options, hidden)),
+        HashFunction::Sha3_256 => Ok(build_example::<Sha3_256>(chain_length, options, hidden)),
+        HashFunction::Keccak256 => Ok(build_example::<Keccak_256>(chain_length, options, hidden)),
+        HashFunction::Blake2s256 => Ok(build_example::<Blake2s_256>(chain_length, options, hidden)),
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
 
+fn build_example<H>(chain_length: usize, options: ProofOptions, hidden: bool) -> Box<dyn Example>
+where
+    H: ElementHasher<BaseField = BaseElement> + 'static,
+{
+    if hidden {
+        Box::new(HiddenRescueExample::<H>::new(chain_length, options))
+    } else {
+        Box::new(RescueExample::<H>::new(chain_length, options))
+    }
+}
+
 pub struct RescueExample<H: ElementHasher> {
     options: ProofOptions,
     chain_length: usize,
@@ -91,6 +111,13 @@ impl<H: ElementHasher> RescueExample<H> {
             _hasher: PhantomData,
         }
     }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            seed: self.seed,
+            result: self.result,
+        }
+    }
 }
 
 // EXAMPLE IMPLEMENTATION
@@ -121,17 +148,15 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs = PublicInputs {
-            seed: self.seed,
-            result: self.result,
-        };
-        winterfell::verify::<RescueAir, H>(proof, pub_inputs)
+        let statement = winterfell::verify_and_extract::<RescueAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -141,6 +166,19 @@ where
         };
         winterfell::verify::<RescueAir, H>(proof, pub_inputs)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.chain_length * CYCLE_LENGTH);
+        let air = RescueAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+
+    fn estimate_cost(&self) -> Option<CostEstimate> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.chain_length * CYCLE_LENGTH);
+        let air = RescueAir::new(trace_info.clone(), self.pub_inputs(), self.options.clone());
+        let profile = MachineProfile::calibrate::<_, H>(&air);
+        Some(estimate_prover_cost(&trace_info, &self.options, &profile))
+    }
 }
 
 // HELPER FUNCTIONS
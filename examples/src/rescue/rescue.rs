@@ -4,7 +4,10 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::utils::{are_equal, EvaluationResult};
-use winterfell::math::{fields::f128::BaseElement, FieldElement};
+use winterfell::{
+    hidden::HashGadget,
+    math::{fields::f128::BaseElement, FieldElement},
+};
 
 /// The number of rounds is set to 14 to provide 128-bit security level.
 /// computed using algorithm 7 from https://eprint.iacr.org/2020/1143.pdf
@@ -102,6 +105,39 @@ pub fn get_round_constants() -> Vec<Vec<BaseElement>> {
     constants
 }
 
+// HASH GADGET
+// ================================================================================================
+
+/// Rescue permutation described as a hash gadget, so that values in an execution trace can be
+/// asserted against their Rescue digests without being revealed.
+pub struct RescueGadget;
+
+impl HashGadget<BaseElement> for RescueGadget {
+    const STATE_WIDTH: usize = STATE_WIDTH;
+    const DIGEST_WIDTH: usize = 2;
+    const CYCLE_LENGTH: usize = CYCLE_LENGTH;
+    const NUM_ROUNDS: usize = NUM_ROUNDS;
+    const ROUND_DEGREE: usize = ALPHA as usize;
+
+    fn apply_round(state: &mut [BaseElement], round: usize) {
+        apply_round(state, round);
+    }
+
+    fn get_round_constants() -> Vec<Vec<BaseElement>> {
+        get_round_constants()
+    }
+
+    fn enforce_round<E: FieldElement + From<BaseElement>>(
+        result: &mut [E],
+        current: &[E],
+        next: &[E],
+        round_constants: &[E],
+        flag: E,
+    ) {
+        enforce_round(result, current, next, round_constants, flag);
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
@@ -3,8 +3,17 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::Blake3_256;
-use winterfell::{FieldExtension, ProofOptions};
+use super::{
+    hidden::{
+        build_hidden_assertions, get_digest, HiddenPublicInputs, HiddenRescueAir,
+        HiddenRescueProver,
+    },
+    BaseElement, Blake3_256, FieldElement, Prover, PublicInputs, RescueAir, RescueProver, Trace,
+    TraceTable, TRACE_WIDTH,
+};
+use core_utils::Serializable;
+use rand_utils::rand_value;
+use winterfell::{math::fields::QuadExtension, test_kit, Air, FieldExtension, ProofOptions};
 
 #[test]
 fn rescue_test_basic_proof_verification() {
@@ -33,6 +42,91 @@ fn rescue_test_basic_proof_verification_fail() {
     crate::tests::test_basic_proof_verification_fail(rescue_eg);
 }
 
+#[test]
+#[ignore = "measures proving time; run with --test-threads=1"]
+fn rescue_test_cost_estimate() {
+    let rescue_eg = Box::new(super::RescueExample::<Blake3_256>::new(
+        256,
+        build_options(false),
+    ));
+    crate::tests::test_cost_estimate(rescue_eg);
+}
+
+#[test]
+fn rescue_test_air_test_kit() {
+    let (air, trace_polys) = build_air_and_trace_polys(8, |_| {});
+    test_kit::boundary_poly_check(&air, &trace_polys);
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+    test_kit::evaluate_at_random_point(
+        &air,
+        &trace_polys,
+        rand_value::<QuadExtension<BaseElement>>(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "is not satisfied by the trace or its degree is greater than declared")]
+fn rescue_test_air_test_kit_invalid_transition() {
+    let (air, trace_polys) = build_air_and_trace_polys(8, |trace| {
+        let value = trace.get(2, 5);
+        trace.set(2, 5, value + BaseElement::ONE);
+    });
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+#[test]
+fn rescue_test_hidden_proof_verification() {
+    let rescue_eg = Box::new(super::HiddenRescueExample::<Blake3_256>::new(
+        32,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(rescue_eg);
+}
+
+#[test]
+fn rescue_test_hidden_proof_verification_fail() {
+    let rescue_eg = Box::new(super::HiddenRescueExample::<Blake3_256>::new(
+        32,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification_fail(rescue_eg);
+}
+
+#[test]
+fn rescue_test_hidden_values_not_in_proof() {
+    let options = build_options(true);
+    let prover = HiddenRescueProver::<Blake3_256>::new(options);
+    let trace = prover.build_trace([BaseElement::from(42u8), BaseElement::from(43u8)], 32);
+    let last_step = trace.length() - 1;
+    let values = [trace.get(0, last_step), trace.get(1, last_step)];
+
+    // neither trace openings nor the out-of-domain frame contain the hidden values in plain form
+    let proof_bytes = prover.prove(trace).unwrap().to_bytes();
+    for value in values {
+        let value_bytes = value.to_bytes();
+        assert!(!proof_bytes
+            .windows(value_bytes.len())
+            .any(|window| window == value_bytes.as_slice()));
+    }
+}
+
+#[test]
+fn rescue_test_hidden_air_test_kit() {
+    let (air, trace_polys) = build_hidden_air_and_trace_polys(8, BaseElement::ZERO);
+    test_kit::boundary_poly_check(&air, &trace_polys);
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+#[test]
+#[should_panic(expected = "is not satisfied by the trace or its degree is greater than declared")]
+fn rescue_test_hidden_air_test_kit_wrong_preimage() {
+    // the digest is asserted against a preimage known to the prover, but this preimage is not
+    // the result of the hash chain
+    let (air, trace_polys) = build_hidden_air_and_trace_polys(8, BaseElement::ONE);
+    test_kit::boundary_poly_check(&air, &trace_polys);
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
 fn build_options(use_extension_field: bool) -> ProofOptions {
     let extension = if use_extension_field {
         FieldExtension::Quadratic
@@ -41,3 +135,54 @@ fn build_options(use_extension_field: bool) -> ProofOptions {
     };
     ProofOptions::new(28, 8, 0, extension, 4, 256)
 }
+
+/// Builds a trace for a chain of Rescue hashes of the specified length, modifies it using the
+/// provided closure, and returns the AIR for the unmodified chain together with polynomials
+/// interpolated from the modified trace.
+fn build_air_and_trace_polys<F>(
+    chain_length: usize,
+    modify: F,
+) -> (RescueAir, Vec<Vec<BaseElement>>)
+where
+    F: FnOnce(&mut TraceTable<BaseElement>),
+{
+    let options = build_options(false);
+    let prover = RescueProver::<Blake3_256>::new(options.clone());
+    let seed = [BaseElement::from(42u8), BaseElement::from(43u8)];
+    let mut trace = prover.build_trace(seed, chain_length);
+    let last_step = trace.length() - 1;
+    let pub_inputs = PublicInputs {
+        seed,
+        result: [trace.get(0, last_step), trace.get(1, last_step)],
+    };
+    let air = RescueAir::new(trace.get_info(), pub_inputs, options);
+    modify(&mut trace);
+    (air, crate::tests::get_trace_polys(&trace))
+}
+
+/// Builds a trace for a chain of Rescue hashes of the specified length with a hidden result, and
+/// returns polynomials interpolated from it together with the AIR. The first hidden value is
+/// shifted by `offset`, and the AIR is built against the digest of the shifted value.
+fn build_hidden_air_and_trace_polys(
+    chain_length: usize,
+    offset: BaseElement,
+) -> (HiddenRescueAir, Vec<Vec<BaseElement>>) {
+    let options = build_options(false);
+    let prover = RescueProver::<Blake3_256>::new(options.clone());
+    let seed = [BaseElement::from(42u8), BaseElement::from(43u8)];
+    let trace = prover.build_trace(seed, chain_length);
+    let last_step = trace.length() - 1;
+    let values = [trace.get(0, last_step) + offset, trace.get(1, last_step)];
+
+    let digests = values.map(get_digest);
+    let hidden = build_hidden_assertions(trace.length(), digests);
+    let mut columns = (0..TRACE_WIDTH)
+        .map(|i| trace.get_column(i).to_vec())
+        .collect::<Vec<_>>();
+    columns.append(&mut hidden.build_columns(&values, [1; 32]));
+    let trace = TraceTable::init(columns);
+
+    let pub_inputs = HiddenPublicInputs { seed, digests };
+    let air = HiddenRescueAir::new(trace.get_info(), pub_inputs, options);
+    (air, crate::tests::get_trace_polys(&trace))
+}
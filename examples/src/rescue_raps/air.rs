@@ -9,8 +9,8 @@ use super::{
 };
 use crate::utils::{are_equal, not, EvaluationResult};
 use winterfell::{
-    Air, AirContext, Assertion, AuxTraceRandElements, ByteWriter, EvaluationFrame, Serializable,
-    TraceInfo, TransitionConstraintDegree,
+    rap::MultisetEquality, Air, AirContext, Assertion, AuxTraceRandElements, ByteWriter,
+    EvaluationFrame, Serializable, TraceInfo, TransitionConstraintDegree,
 };
 
 // CONSTANTS
@@ -39,6 +39,7 @@ const CYCLE_MASK: [BaseElement; CYCLE_LENGTH] = [
 // RESCUE AIR
 // ================================================================================================
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct PublicInputs {
     pub result: [[BaseElement; 2]; 2],
 }
@@ -209,14 +210,13 @@ impl Air for RescueRapsAir {
             are_equal(aux_current[1], copied_value_2),
         );
 
-        // Enforce that the permutation argument column scales at each step by (aux[0] + γ) / (aux[1] + γ).
+        // Enforce that the permutation argument column scales at each step by
+        // (γ + δ * aux[0]) / (γ + δ * aux[1]).
+        let permutation = build_permutation_argument(random_elements);
         result.agg_constraint(
             2,
             E::ONE,
-            are_equal(
-                aux_next[2] * (aux_current[1] + random_elements[2]),
-                aux_current[2] * (aux_current[0] + random_elements[2]),
-            ),
+            permutation.evaluate_transition(aux_current, aux_current[2], aux_next[2]),
         );
     }
 
@@ -240,13 +240,10 @@ impl Air for RescueRapsAir {
 
     fn get_aux_assertions<E: FieldElement + From<Self::BaseField>>(
         &self,
-        _aux_rand_elements: &AuxTraceRandElements<E>,
+        aux_rand_elements: &AuxTraceRandElements<E>,
     ) -> Vec<Assertion<E>> {
-        let last_step = self.trace_length() - 1;
-        vec![
-            Assertion::single(2, 0, E::ONE),
-            Assertion::single(2, last_step, E::ONE),
-        ]
+        let random_elements = aux_rand_elements.get_segment_elements(0);
+        build_permutation_argument(random_elements).get_assertions(2, self.trace_length())
     }
 
     fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
@@ -264,6 +261,12 @@ impl Air for RescueRapsAir {
 // HELPER EVALUATORS
 // ------------------------------------------------------------------------------------------------
 
+/// Builds the argument enforcing that values in the first auxiliary column are a permutation of
+/// values in the second auxiliary column; the argument uses the last two random elements.
+fn build_permutation_argument<E: FieldElement>(random_elements: &[E]) -> MultisetEquality<E> {
+    MultisetEquality::from_columns(&random_elements[2..], vec![0], vec![1])
+}
+
 /// when flag = 1, enforces that the next state of the computation is defined like so:
 /// - the first two registers are equal to the values from the previous step
 /// - the other two registers are not restrained, they could be arbitrary elements,
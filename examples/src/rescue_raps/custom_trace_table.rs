@@ -6,7 +6,8 @@
 use core_utils::{collections::Vec, uninit_vector};
 use winterfell::{
     math::{log2, FieldElement, StarkField},
-    EvaluationFrame, Matrix, Trace, TraceInfo, TraceLayout,
+    rap::MultisetEquality,
+    EvaluationFrame, Matrix, Trace, TraceInfo, TraceLayout, TraceLayoutBuilder,
 };
 
 // RAP TRACE TABLE
@@ -99,7 +100,11 @@ impl<B: StarkField> RapTraceTable<B> {
 
         let columns = unsafe { (0..width).map(|_| uninit_vector(length)).collect() };
         Self {
-            layout: TraceLayout::new(width, [3], [3]),
+            layout: TraceLayoutBuilder::new()
+                .main_width(width)
+                .aux_segment(3, 4)
+                .build()
+                .expect("invalid trace layout"),
             trace: Matrix::new(columns),
             meta,
         }
@@ -177,9 +182,10 @@ impl<B: StarkField> Trace for RapTraceTable<B> {
     }
 
     fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<Self::BaseField>) {
-        let next_row_idx = (row_idx + 1) % self.length();
-        self.trace.read_row_into(row_idx, frame.current_mut());
-        self.trace.read_row_into(next_row_idx, frame.next_mut());
+        for i in 0..frame.num_rows() {
+            let frame_row_idx = (row_idx + frame.offsets()[i]) % self.length();
+            self.trace.read_row_into(frame_row_idx, frame.row_mut(i));
+        }
     }
 
     fn main_segment(&self) -> &Matrix<B> {
@@ -202,7 +208,7 @@ impl<B: StarkField> Trace for RapTraceTable<B> {
         let mut current_row = unsafe { uninit_vector(self.width()) };
         let mut next_row = unsafe { uninit_vector(self.width()) };
         self.read_row_into(0, &mut current_row);
-        let mut aux_columns = vec![vec![E::ZERO; self.length()]; self.aux_trace_width()];
+        let mut aux_columns = vec![vec![E::ZERO; self.length()]; 2];
 
         // Columns storing the copied values for the permutation argument are not necessary, but
         // help understanding the construction of RAPs and are kept for illustrative purposes.
@@ -211,27 +217,26 @@ impl<B: StarkField> Trace for RapTraceTable<B> {
         aux_columns[1][0] =
             rand_elements[0] * current_row[4].into() + rand_elements[1] * current_row[5].into();
 
-        // Permutation argument column
-        aux_columns[2][0] = E::ONE;
-
-        for index in 1..self.length() {
-            // At every last step before a new hash iteration,
-            // copy the permuted values into the auxiliary columns
-            if (index % super::CYCLE_LENGTH) == super::NUM_HASH_ROUNDS {
-                self.read_row_into(index, &mut current_row);
-                self.read_row_into(index + 1, &mut next_row);
-
-                aux_columns[0][index] = rand_elements[0] * (next_row[0] - current_row[0]).into()
-                    + rand_elements[1] * (next_row[1] - current_row[1]).into();
-                aux_columns[1][index] = rand_elements[0] * (next_row[4] - current_row[4]).into()
-                    + rand_elements[1] * (next_row[5] - current_row[5]).into();
-            }
-
-            let num = aux_columns[0][index - 1] + rand_elements[2];
-            let denom = aux_columns[1][index - 1] + rand_elements[2];
-            aux_columns[2][index] = aux_columns[2][index - 1] * num * denom.inv();
+        // At every last step before a new hash iteration,
+        // copy the permuted values into the auxiliary columns
+        for index in (super::NUM_HASH_ROUNDS..self.length()).step_by(super::CYCLE_LENGTH) {
+            self.read_row_into(index, &mut current_row);
+            self.read_row_into(index + 1, &mut next_row);
+
+            aux_columns[0][index] = rand_elements[0] * (next_row[0] - current_row[0]).into()
+                + rand_elements[1] * (next_row[1] - current_row[1]).into();
+            aux_columns[1][index] = rand_elements[0] * (next_row[4] - current_row[4]).into()
+                + rand_elements[1] * (next_row[5] - current_row[5]).into();
         }
 
+        // Permutation argument column
+        let permutation = MultisetEquality::from_columns(&rand_elements[2..], vec![0], vec![1]);
+        let permutation_column = permutation.build_column::<E, _>(self.length(), 2, |i, row| {
+            row[0] = aux_columns[0][i];
+            row[1] = aux_columns[1][i];
+        });
+        aux_columns.push(permutation_column);
+
         Some(Matrix::new(aux_columns))
     }
 }
@@ -3,7 +3,12 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::{Blake3_192, Blake3_256, Example, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
 use core::marker::PhantomData;
 use log::debug;
 use rand_utils::rand_array;
@@ -11,7 +16,8 @@ use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f128::BaseElement, log2, ExtensionOf, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, VerifierError,
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo,
+    TraceLayoutBuilder, VerifierError,
 };
 
 mod custom_trace_table;
@@ -38,11 +44,16 @@ const TRACE_WIDTH: usize = 4 * 2;
 // RESCUE SPLIT HASH CHAIN EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 4,
+    target_security: 100,
+};
+
 pub fn get_example(
     options: &ExampleOptions,
     chain_length: usize,
 ) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(42, 4);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(RescueRapsExample::<Blake3_192>::new(
@@ -57,6 +68,14 @@ pub fn get_example(
             chain_length,
             options,
         ))),
+        HashFunction::Keccak256 => Ok(Box::new(RescueRapsExample::<Keccak_256>::new(
+            chain_length,
+            options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(RescueRapsExample::<Blake2s_256>::new(
+            chain_length,
+            options,
+        ))),
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
@@ -104,6 +123,12 @@ impl<H: ElementHasher> RescueRapsExample<H> {
             _hasher: PhantomData,
         }
     }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            result: self.result,
+        }
+    }
 }
 
 // EXAMPLE IMPLEMENTATION
@@ -134,16 +159,16 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs = PublicInputs {
-            result: self.result,
-        };
-        winterfell::verify::<RescueRapsAir, H>(proof, pub_inputs)
+        let statement =
+            winterfell::verify_and_extract::<RescueRapsAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -152,6 +177,21 @@ where
         };
         winterfell::verify::<RescueRapsAir, H>(proof, pub_inputs)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        // the layout is the same as the one of the trace built by the prover: the main segment
+        // holds the states of both hash chains, and a single auxiliary segment of 3 columns is
+        // built using 4 random elements
+        let layout = TraceLayoutBuilder::new()
+            .main_width(2 * STATE_WIDTH)
+            .aux_segment(3, 4)
+            .build()
+            .expect("invalid trace layout");
+        let trace_info =
+            TraceInfo::new_multi_segment(layout, self.chain_length * CYCLE_LENGTH, vec![]);
+        let air = RescueRapsAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
 
 // HELPER FUNCTIONS
@@ -33,6 +33,24 @@ fn rescue_test_basic_proof_verification_fail() {
     crate::tests::test_basic_proof_verification_fail(rescue_eg);
 }
 
+#[test]
+fn rescue_test_multiple_ood_points() {
+    let rescue_eg = Box::new(super::RescueRapsExample::<Blake3_256>::new(
+        128,
+        build_options(true).with_num_ood_points(2),
+    ));
+    crate::tests::test_basic_proof_verification(rescue_eg);
+}
+
+#[test]
+fn rescue_test_combined_trace_column() {
+    let rescue_eg = Box::new(super::RescueRapsExample::<Blake3_256>::new(
+        128,
+        build_options(true).with_combined_trace_column(),
+    ));
+    crate::tests::test_basic_proof_verification(rescue_eg);
+}
+
 fn build_options(use_extension_field: bool) -> ProofOptions {
     let extension = if use_extension_field {
         FieldExtension::Quadratic
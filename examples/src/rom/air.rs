@@ -0,0 +1,88 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, FieldElement, ProofOptions};
+use crate::utils::are_equal;
+use winterfell::{
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, Serializable, TraceInfo,
+    TransitionConstraintDegree,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicInputs {
+    pub seed: BaseElement,
+    pub result: BaseElement,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.seed);
+        target.write(self.result);
+    }
+}
+
+// ROM AIR
+// ================================================================================================
+
+/// AIR for a trace consisting of a single accumulator column in the main trace segment and a
+/// single ROM column in the preprocessed trace segment.
+///
+/// Columns of the preprocessed segment follow the columns of the main segment in the evaluation
+/// frame; thus, the accumulator is at index 0 and the ROM value is at index 1.
+pub struct RomAir {
+    context: AirContext<BaseElement>,
+    seed: BaseElement,
+    result: BaseElement,
+}
+
+impl Air for RomAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        assert_eq!(1, trace_info.layout().main_trace_width());
+        assert_eq!(1, trace_info.preprocessed_width());
+
+        let degrees = vec![TransitionConstraintDegree::new(2)];
+        RomAir {
+            context: AirContext::new(trace_info, degrees, 2, options),
+            seed: pub_inputs.seed,
+            result: pub_inputs.result,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        debug_assert_eq!(2, current.len());
+        debug_assert_eq!(2, next.len());
+
+        // the accumulator is multiplied by the ROM value of the current step and incremented
+        let (acc, rom) = (current[0], current[1]);
+        result[0] = are_equal(next[0], acc * rom + E::ONE);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.seed),
+            Assertion::single(0, last_step, self.result),
+        ]
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
+use log::debug;
+use std::time::Instant;
+use winterfell::{
+    crypto::ElementHasher,
+    math::{fields::f128::BaseElement, log2, FieldElement},
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, VerifierError,
+};
+
+mod air;
+use air::{PublicInputs, RomAir};
+
+mod prover;
+use prover::RomProver;
+
+#[cfg(test)]
+mod tests;
+
+// ROM EXAMPLE
+// ================================================================================================
+
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(options: &ExampleOptions, num_steps: usize) -> Result<Box<dyn Example>, String> {
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
+
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(RomExample::<Blake3_192>::new(num_steps, options))),
+        HashFunction::Blake3_256 => Ok(Box::new(RomExample::<Blake3_256>::new(num_steps, options))),
+        HashFunction::Sha3_256 => Ok(Box::new(RomExample::<Sha3_256>::new(num_steps, options))),
+        HashFunction::Keccak256 => Ok(Box::new(RomExample::<Keccak_256>::new(num_steps, options))),
+        HashFunction::Blake2s256 => {
+            Ok(Box::new(RomExample::<Blake2s_256>::new(num_steps, options)))
+        }
+        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+    }
+}
+
+/// Runs a fixed "program" stored in a read-only memory (ROM) against a public seed.
+///
+/// The ROM is a single column of constants which is the same for every execution of the program,
+/// and thus, it is committed to once as a preprocessed trace segment. At every step, the
+/// accumulator in the main trace segment is updated as acc' = acc * rom + 1, where rom is the
+/// ROM value at that step. Proofs for different seeds reuse the ROM commitment, and the verifier
+/// checks every proof against the same commitment, which it obtains once out of band.
+pub struct RomExample<H: ElementHasher<BaseField = BaseElement>> {
+    prover: RomProver<H>,
+    rom_commitment: H::Digest,
+    seed: BaseElement,
+    result: BaseElement,
+}
+
+impl<H: ElementHasher<BaseField = BaseElement>> RomExample<H> {
+    pub fn new(num_steps: usize, options: ProofOptions) -> Self {
+        assert!(
+            num_steps.is_power_of_two(),
+            "number of steps must be a power of 2"
+        );
+        assert!(num_steps >= 8, "number of steps must be at least 8");
+
+        // commit to the ROM; this needs to be done only once for all executions of the program
+        let now = Instant::now();
+        let prover = RomProver::<H>::new(build_rom(num_steps), options);
+        let rom_commitment = prover.rom_commitment();
+        debug!(
+            "Committed to ROM of 2^{} steps in {} ms",
+            log2(num_steps),
+            now.elapsed().as_millis()
+        );
+
+        let seed = BaseElement::new(3);
+        let result = compute_result(seed, prover.rom());
+
+        RomExample {
+            prover,
+            rom_commitment,
+            seed,
+            result,
+        }
+    }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            seed: self.seed,
+            result: self.result,
+        }
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl<H: ElementHasher<BaseField = BaseElement>> Example for RomExample<H> {
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for running a ROM program of {} steps\n\
+            ---------------------",
+            self.prover.rom().len()
+        );
+
+        // generate execution trace
+        let now = Instant::now();
+        let trace = self.prover.build_trace(self.seed);
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace.width(),
+            log2(trace.length()),
+            now.elapsed().as_millis()
+        );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&self.prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        winterfell::verify_with_preprocessed_segment::<RomAir, H>(
+            proof,
+            self.pub_inputs(),
+            self.rom_commitment,
+        )
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let mut pub_inputs = self.pub_inputs();
+        pub_inputs.result += BaseElement::ONE;
+        winterfell::verify_with_preprocessed_segment::<RomAir, H>(
+            proof,
+            pub_inputs,
+            self.rom_commitment,
+        )
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        // the main segment holds the accumulator, and the preprocessed segment holds the ROM
+        let trace_info = TraceInfo::new(1, self.prover.rom().len()).with_preprocessed_segment(1);
+        let air = RomAir::new(trace_info, self.pub_inputs(), self.prover.options().clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the ROM of the program; the value at step i is i^3 + 7.
+fn build_rom(num_steps: usize) -> Vec<BaseElement> {
+    (0..num_steps as u128)
+        .map(|i| BaseElement::new(i * i * i + 7))
+        .collect()
+}
+
+/// Runs the program stored in the specified ROM against the specified seed and returns the value
+/// of the accumulator at the last step.
+fn compute_result(seed: BaseElement, rom: &[BaseElement]) -> BaseElement {
+    rom[..rom.len() - 1]
+        .iter()
+        .fold(seed, |acc, &value| acc * value + BaseElement::ONE)
+}
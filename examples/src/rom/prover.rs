@@ -0,0 +1,79 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, ElementHasher, FieldElement, ProofOptions, Prover, PublicInputs, RomAir};
+use winterfell::{PreprocessedSegment, Trace, TraceTable};
+
+// ROM PROVER
+// ================================================================================================
+
+/// Prover for programs stored in a ROM; the ROM is committed to when the prover is created, and
+/// this commitment is reused for all proofs generated by the prover.
+pub struct RomProver<H: ElementHasher<BaseField = BaseElement>> {
+    options: ProofOptions,
+    rom: PreprocessedSegment<BaseElement, H>,
+}
+
+impl<H: ElementHasher<BaseField = BaseElement>> RomProver<H> {
+    pub fn new(rom: Vec<BaseElement>, options: ProofOptions) -> Self {
+        let rom = PreprocessedSegment::new(vec![rom], &options);
+        Self::with_segment(rom, options)
+    }
+
+    /// Returns a new prover for the program stored in the ROM which has already been committed
+    /// to as the specified preprocessed segment.
+    pub fn with_segment(rom: PreprocessedSegment<BaseElement, H>, options: ProofOptions) -> Self {
+        Self { options, rom }
+    }
+
+    /// Returns the commitment to the ROM; proofs generated by this prover must be verified
+    /// against this commitment.
+    pub fn rom_commitment(&self) -> H::Digest {
+        self.rom.commitment()
+    }
+
+    /// Returns the values stored in the ROM.
+    pub fn rom(&self) -> &[BaseElement] {
+        self.rom.get_column(0)
+    }
+
+    /// Builds an execution trace of the program stored in the ROM for the specified seed.
+    pub fn build_trace(&self, seed: BaseElement) -> TraceTable<BaseElement> {
+        let rom = self.rom();
+        let mut trace = TraceTable::new(1, rom.len());
+        trace.fill(
+            |state| {
+                state[0] = seed;
+            },
+            |step, state| {
+                state[0] = state[0] * rom[step] + BaseElement::ONE;
+            },
+        );
+        trace
+    }
+}
+
+impl<H: ElementHasher<BaseField = BaseElement>> Prover for RomProver<H> {
+    type BaseField = BaseElement;
+    type Air = RomAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let last_step = trace.length() - 1;
+        PublicInputs {
+            seed: trace.get(0, 0),
+            result: trace.get(0, last_step),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    fn preprocessed_segment(&self) -> Option<&PreprocessedSegment<Self::BaseField, Self::HashFn>> {
+        Some(&self.rom)
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{build_rom, compute_result, BaseElement, Blake3_256, RomAir, RomExample, RomProver};
+use winterfell::{
+    checkpoint::Checkpoint, FieldExtension, PreprocessedSegment, ProofOptions, Prover, ProverError,
+    StarkProof, TraceTable, VerifierError,
+};
+
+#[test]
+fn rom_test_basic_proof_verification() {
+    let rom = Box::new(RomExample::<Blake3_256>::new(16, build_options(false)));
+    crate::tests::test_basic_proof_verification(rom);
+}
+
+#[test]
+fn rom_test_basic_proof_verification_extension() {
+    let rom = Box::new(RomExample::<Blake3_256>::new(16, build_options(true)));
+    crate::tests::test_basic_proof_verification(rom);
+}
+
+#[test]
+fn rom_test_basic_proof_verification_fail() {
+    let rom = Box::new(RomExample::<Blake3_256>::new(16, build_options(false)));
+    crate::tests::test_basic_proof_verification_fail(rom);
+}
+
+#[test]
+fn rom_test_combined_trace_column() {
+    let options = build_options(true).with_combined_trace_column();
+    let rom = Box::new(RomExample::<Blake3_256>::new(16, options));
+    crate::tests::test_basic_proof_verification(rom);
+}
+
+#[test]
+fn rom_test_shared_commitment() {
+    let prover = RomProver::<Blake3_256>::new(build_rom(32), build_options(false));
+    let commitment = prover.rom_commitment();
+
+    // proofs for different seeds are verified against the same ROM commitment
+    for seed in [1u128, 2, 42].map(BaseElement::new) {
+        let (proof, pub_inputs) = prover
+            .prove_returning_inputs(prover.build_trace(seed))
+            .unwrap();
+        assert_eq!(compute_result(seed, prover.rom()), pub_inputs.result);
+
+        let bytes = proof.to_bytes();
+        let proof = StarkProof::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            Ok(()),
+            winterfell::verify_with_preprocessed_segment::<RomAir, Blake3_256>(
+                proof, pub_inputs, commitment
+            )
+        );
+    }
+
+    // the commitment does not depend on the prover; it depends only on the ROM and the options
+    let other = RomProver::<Blake3_256>::new(build_rom(32), build_options(false));
+    assert_eq!(commitment, other.rom_commitment());
+    let other = RomProver::<Blake3_256>::new(build_rom(32), build_options(true));
+    assert_eq!(commitment, other.rom_commitment());
+}
+
+#[test]
+fn rom_test_wrong_commitment() {
+    let prover = RomProver::<Blake3_256>::new(build_rom(16), build_options(false));
+    let (proof, pub_inputs) = prover
+        .prove_returning_inputs(prover.build_trace(BaseElement::new(3)))
+        .unwrap();
+
+    // a proof cannot be verified against a commitment to a different ROM
+    let mut rom = build_rom(16);
+    rom[5] += BaseElement::new(1);
+    let other = RomProver::<Blake3_256>::new(rom, build_options(false));
+    assert!(
+        winterfell::verify_with_preprocessed_segment::<RomAir, Blake3_256>(
+            proof.clone(),
+            pub_inputs.clone(),
+            other.rom_commitment()
+        )
+        .is_err()
+    );
+
+    // a proof with a preprocessed segment cannot be verified without a commitment
+    assert_eq!(
+        Err(VerifierError::PreprocessedSegmentMismatch),
+        winterfell::verify::<RomAir, Blake3_256>(proof, pub_inputs)
+    );
+}
+
+#[test]
+fn rom_test_incompatible_segment() {
+    let prover = RomProver::<Blake3_256>::new(build_rom(16), build_options(false));
+
+    // the ROM was committed to for traces of 16 steps
+    let mut trace = TraceTable::new(1, 32);
+    trace.fill(|state| state[0] = BaseElement::new(3), |_, _| {});
+    assert!(matches!(
+        prover.prove(trace),
+        Err(ProverError::IncompatiblePreprocessedSegment(_))
+    ));
+
+    // the ROM was committed to using a different blowup factor
+    let options = ProofOptions::new(28, 16, 0, FieldExtension::None, 4, 256);
+    let rom = PreprocessedSegment::new(vec![build_rom(16)], &build_options(false));
+    let other = RomProver::<Blake3_256>::with_segment(rom, options);
+    assert!(matches!(
+        other.prove(prover.build_trace(BaseElement::new(3))),
+        Err(ProverError::IncompatiblePreprocessedSegment(_))
+    ));
+}
+
+#[test]
+fn rom_test_checkpoints() {
+    let prover = RomProver::<Blake3_256>::new(build_rom(16), build_options(false));
+    let seed = BaseElement::new(3);
+    let (proof, pub_inputs) = prover
+        .prove_returning_inputs(prover.build_trace(seed))
+        .unwrap();
+
+    // resuming from every checkpoint results in the same proof as an uninterrupted run
+    let mut checkpoints = Vec::new();
+    prover
+        .prove_with_checkpoints(prover.build_trace(seed), &mut checkpoints)
+        .unwrap();
+    for checkpoint in checkpoints.iter() {
+        let mut checkpoint = Checkpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+        let resumed_proof = prover
+            .resume_proof(pub_inputs.clone(), &mut checkpoint)
+            .unwrap();
+        assert_eq!(proof.to_bytes(), resumed_proof.to_bytes());
+    }
+}
+
+fn build_options(use_extension_field: bool) -> ProofOptions {
+    let extension = if use_extension_field {
+        FieldExtension::Quadratic
+    } else {
+        FieldExtension::None
+    };
+    ProofOptions::new(28, 8, 0, extension, 4, 256)
+}
@@ -0,0 +1,116 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, FieldElement, ProofOptions};
+use crate::utils::are_equal;
+use winterfell::{
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, Serializable, TraceInfo,
+    TransitionConstraintDegree, TransitionDivisorKind,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicInputs {
+    pub seed: Vec<BaseElement>,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(&self.seed[..]);
+    }
+}
+
+// ROTATE AIR
+// ================================================================================================
+
+/// AIR for a trace consisting of a set of rotating registers followed by a step counter and a
+/// column holding the square of the step counter.
+///
+/// The number of rotating registers is defined by the number of values in the seed, and the
+/// width of the trace must be two greater than that.
+pub struct RotateAir {
+    context: AirContext<BaseElement>,
+    seed: Vec<BaseElement>,
+}
+
+impl Air for RotateAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let num_registers = pub_inputs.seed.len();
+        assert!(num_registers >= 2, "at least two registers are required");
+        assert_eq!(num_registers + 2, trace_info.width());
+
+        // rotation and square constraints must hold on all steps including the last one (where
+        // the next row wraps around to the first row); the step counter constraint is a regular
+        // transition constraint and does not apply to the last step.
+        let rotation =
+            TransitionConstraintDegree::new(1).with_divisor(TransitionDivisorKind::Cyclic);
+        let square = TransitionConstraintDegree::new(2).with_divisor(TransitionDivisorKind::Cyclic);
+        let mut degrees = vec![rotation; num_registers];
+        degrees.push(TransitionConstraintDegree::new(1));
+        degrees.push(square);
+
+        RotateAir {
+            context: AirContext::new(trace_info, degrees, num_registers + 2, options),
+            seed: pub_inputs.seed,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        let num_registers = self.seed.len();
+        debug_assert_eq!(num_registers + 2, current.len());
+        debug_assert_eq!(num_registers + 2, next.len());
+
+        // every register takes the value of the following register, and the last register takes
+        // the value of the first one
+        for i in 0..num_registers {
+            result[i] = are_equal(next[i], current[(i + 1) % num_registers]);
+        }
+
+        // the step counter is incremented by one at every step except for the last one
+        let counter = num_registers;
+        result[counter] = are_equal(next[counter], current[counter] + E::ONE);
+
+        // the last column holds the square of the step counter; since this constraint is cyclic,
+        // it applies to the last row of the trace as well
+        let square = num_registers + 1;
+        result[square] = are_equal(current[square], current[counter] * current[counter]);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let counter = self.seed.len();
+        let last_step = self.trace_length() - 1;
+        let mut result = self
+            .seed
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| Assertion::single(i, 0, value))
+            .collect::<Vec<_>>();
+        result.push(Assertion::single(counter, 0, BaseElement::ZERO));
+        result.push(Assertion::single(
+            counter,
+            last_step,
+            BaseElement::new(last_step as u128),
+        ));
+        result
+    }
+}
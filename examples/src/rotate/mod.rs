@@ -0,0 +1,163 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
+use core::marker::PhantomData;
+use log::debug;
+use std::time::Instant;
+use winterfell::{
+    crypto::ElementHasher,
+    math::{fields::f128::BaseElement, log2, FieldElement},
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
+};
+
+mod air;
+use air::{PublicInputs, RotateAir};
+
+mod prover;
+use prover::RotateProver;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of rotating registers in the trace of this example; since registers return to their
+/// initial positions after this many steps, the trace length must be a multiple of this number.
+const NUM_REGISTERS: usize = 4;
+
+// ROTATE EXAMPLE
+// ================================================================================================
+
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(options: &ExampleOptions, num_steps: usize) -> Result<Box<dyn Example>, String> {
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
+
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(RotateExample::<Blake3_192>::new(
+            num_steps, options,
+        ))),
+        HashFunction::Blake3_256 => Ok(Box::new(RotateExample::<Blake3_256>::new(
+            num_steps, options,
+        ))),
+        HashFunction::Sha3_256 => Ok(Box::new(RotateExample::<Sha3_256>::new(num_steps, options))),
+        HashFunction::Keccak256 => Ok(Box::new(RotateExample::<Keccak_256>::new(
+            num_steps, options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(RotateExample::<Blake2s_256>::new(
+            num_steps, options,
+        ))),
+        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+    }
+}
+
+/// Rotates values of a set of registers by one position at every step.
+///
+/// Rotation constraints are cyclic: unlike regular transition constraints, they are enforced on
+/// the last step of the trace as well, where the next row wraps around to the first row. Thus,
+/// the proof shows that rotating the last row of the trace yields its first row, and the trace
+/// describes a closed cycle. The trace also contains a step counter which is constrained by a
+/// regular transition constraint (which is not enforced on the last step), and the square of the
+/// step counter; the latter constraint is cyclic as well, and thus, it is enforced on the last
+/// row of the trace which a regular transition constraint would leave unconstrained.
+pub struct RotateExample<H: ElementHasher> {
+    options: ProofOptions,
+    num_steps: usize,
+    seed: Vec<BaseElement>,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> RotateExample<H> {
+    pub fn new(num_steps: usize, options: ProofOptions) -> Self {
+        assert!(
+            num_steps.is_power_of_two(),
+            "number of steps must be a power of 2"
+        );
+        assert!(num_steps >= 8, "number of steps must be at least 8");
+
+        let seed = (0..NUM_REGISTERS as u128)
+            .map(|i| BaseElement::new(i + 1))
+            .collect();
+
+        RotateExample {
+            options,
+            num_steps,
+            seed,
+            _hasher: PhantomData,
+        }
+    }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            seed: self.seed.clone(),
+        }
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl<H: ElementHasher> Example for RotateExample<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for rotating {} registers over {} steps\n\
+            ---------------------",
+            self.seed.len(),
+            self.num_steps
+        );
+
+        // create a prover
+        let prover = RotateProver::<H>::new(self.options.clone());
+
+        // generate execution trace
+        let now = Instant::now();
+        let trace = RotateProver::<H>::build_trace(&self.seed, self.num_steps);
+
+        let trace_width = trace.width();
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace_width,
+            log2(trace_length),
+            now.elapsed().as_millis()
+        );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement = winterfell::verify_and_extract::<RotateAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let mut pub_inputs = self.pub_inputs();
+        pub_inputs.seed[0] += BaseElement::ONE;
+        winterfell::verify::<RotateAir, H>(proof, pub_inputs)
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        // the trace holds the rotating registers, the step counter, and its square
+        let trace_info = TraceInfo::new(NUM_REGISTERS + 2, self.num_steps);
+        let air = RotateAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
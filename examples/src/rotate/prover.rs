@@ -0,0 +1,71 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    BaseElement, ElementHasher, FieldElement, PhantomData, ProofOptions, Prover, PublicInputs,
+    RotateAir, TraceTable,
+};
+
+// ROTATE PROVER
+// ================================================================================================
+
+pub struct RotateProver<H: ElementHasher> {
+    options: ProofOptions,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> RotateProver<H> {
+    pub fn new(options: ProofOptions) -> Self {
+        Self {
+            options,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Builds an execution trace of the specified length in which registers initialized with
+    /// the seed values are rotated by one position at every step; every row also contains the
+    /// index of the step and its square.
+    ///
+    /// The last row of the trace rotates back into the first row only when the trace length is
+    /// a multiple of the number of registers.
+    pub fn build_trace(seed: &[BaseElement], trace_length: usize) -> TraceTable<BaseElement> {
+        let num_registers = seed.len();
+        let mut trace = TraceTable::new(num_registers + 2, trace_length);
+        trace.fill(
+            |state| {
+                state[..num_registers].copy_from_slice(seed);
+                state[num_registers] = BaseElement::ZERO;
+                state[num_registers + 1] = BaseElement::ZERO;
+            },
+            |_, state| {
+                state[..num_registers].rotate_left(1);
+                state[num_registers] += BaseElement::ONE;
+                state[num_registers + 1] = state[num_registers].square();
+            },
+        );
+        trace
+    }
+}
+
+impl<H: ElementHasher> Prover for RotateProver<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    type BaseField = BaseElement;
+    type Air = RotateAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let num_registers = trace.width() - 2;
+        PublicInputs {
+            seed: (0..num_registers).map(|i| trace.get(i, 0)).collect(),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
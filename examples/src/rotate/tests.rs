@@ -0,0 +1,56 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, Blake3_256, FieldElement, RotateAir, RotateExample, RotateProver};
+use winterfell::{FieldExtension, ProofOptions, Prover, Trace};
+
+#[test]
+fn rotate_test_basic_proof_verification() {
+    let rotate = Box::new(RotateExample::<Blake3_256>::new(16, build_options(false)));
+    crate::tests::test_basic_proof_verification(rotate);
+}
+
+#[test]
+fn rotate_test_basic_proof_verification_extension() {
+    let rotate = Box::new(RotateExample::<Blake3_256>::new(16, build_options(true)));
+    crate::tests::test_basic_proof_verification(rotate);
+}
+
+#[test]
+fn rotate_test_basic_proof_verification_fail() {
+    let rotate = Box::new(RotateExample::<Blake3_256>::new(16, build_options(false)));
+    crate::tests::test_basic_proof_verification_fail(rotate);
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "main transition constraint 0 did not evaluate to ZERO at step 15")
+)]
+fn rotate_test_broken_wrap_row_rejected() {
+    // with 3 registers and 16 steps, all steps except for the last one rotate the registers
+    // correctly, but rotating the last row does not yield the first row; in debug builds, the
+    // prover catches this when validating the trace against the AIR.
+    let seed = [1u128, 2, 3].map(BaseElement::new);
+    let trace = RotateProver::<Blake3_256>::build_trace(&seed, 16);
+    let mut last_row = [BaseElement::ZERO; 5];
+    trace.read_row_into(trace.length() - 1, &mut last_row);
+    last_row[..3].rotate_left(1);
+    assert_ne!(seed[..], last_row[..3]);
+
+    let prover = RotateProver::<Blake3_256>::new(build_options(false));
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    assert!(winterfell::verify::<RotateAir, Blake3_256>(proof, pub_inputs).is_err());
+}
+
+fn build_options(use_extension_field: bool) -> ProofOptions {
+    let extension = if use_extension_field {
+        FieldExtension::Quadratic
+    } else {
+        FieldExtension::None
+    };
+    ProofOptions::new(28, 8, 0, extension, 4, 256)
+}
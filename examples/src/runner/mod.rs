@@ -0,0 +1,394 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Error-tolerant runner for the provided examples.
+//!
+//! The runner executes a set of registered examples one after another: for every example, a
+//! proof is generated, serialized, deserialized, and verified. Each example is executed inside
+//! [catch_unwind()](std::panic::catch_unwind), and thus, an example which panics (or fails to
+//! verify its proof) is recorded as failed without preventing the remaining examples from being
+//! executed. Once all examples have been executed, a summary of the run can be printed via its
+//! [Display] implementation.
+
+#[cfg(all(feature = "std", feature = "f128"))]
+use crate::{collatz, lamport, merkle, rescue_raps, statistics};
+use crate::{fibonacci, Example, ExampleOptions, ExampleType};
+#[cfg(feature = "f128")]
+use crate::{rescue, rom, rotate, vdf, vm};
+use core::fmt;
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+use winterfell::StarkProof;
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// EXAMPLE BUILDER
+// ================================================================================================
+
+/// Instantiates the specified example using the provided options.
+pub fn build_example(
+    options: &ExampleOptions,
+    example: &ExampleType,
+) -> Result<Box<dyn Example>, String> {
+    match *example {
+        #[cfg(feature = "f128")]
+        ExampleType::Fib {
+            sequence_length,
+            expected_result,
+            ..
+        } => fibonacci::fib2::get_example(options, sequence_length, expected_result),
+        #[cfg(feature = "f128")]
+        ExampleType::Fib8 { sequence_length } => {
+            fibonacci::fib8::get_example(options, sequence_length)
+        }
+        #[cfg(feature = "f128")]
+        ExampleType::FibWindow { sequence_length } => {
+            fibonacci::fib_window::get_example(options, sequence_length)
+        }
+        #[cfg(feature = "f128")]
+        ExampleType::Mulfib { sequence_length } => {
+            fibonacci::mulfib2::get_example(options, sequence_length)
+        }
+        #[cfg(feature = "f128")]
+        ExampleType::Mulfib8 { sequence_length } => {
+            fibonacci::mulfib8::get_example(options, sequence_length)
+        }
+        ExampleType::FibSmall { sequence_length } => {
+            fibonacci::fib_small::get_example(options, sequence_length)
+        }
+        #[cfg(feature = "f128")]
+        ExampleType::Vdf { num_steps } => vdf::regular::get_example(options, num_steps),
+        #[cfg(feature = "f128")]
+        ExampleType::VdfExempt { num_steps } => vdf::exempt::get_example(options, num_steps),
+        #[cfg(feature = "f128")]
+        ExampleType::Rotate { num_steps } => rotate::get_example(options, num_steps),
+        #[cfg(feature = "f128")]
+        ExampleType::Vm { num_steps } => vm::get_example(options, num_steps),
+        #[cfg(feature = "f128")]
+        ExampleType::Rom { num_steps } => rom::get_example(options, num_steps),
+        #[cfg(feature = "f128")]
+        ExampleType::Rescue {
+            chain_length,
+            hidden,
+        } => rescue::get_example(options, chain_length, hidden),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::RescueRaps { chain_length } => rescue_raps::get_example(options, chain_length),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Merkle { tree_depth } => merkle::get_example(options, tree_depth),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::MerkleBatch {
+            num_leaves,
+            tree_depth,
+        } => merkle::batch::get_example(options, num_leaves, tree_depth),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::LamportA { num_signatures } => {
+            lamport::aggregate::get_example(options, num_signatures)
+        }
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::LamportT { num_signers } => {
+            lamport::threshold::get_example(options, num_signers)
+        }
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Collatz {
+            num_initial,
+            batch,
+            ref initial_numbers,
+        } => match (batch, initial_numbers.is_empty()) {
+            (None, true) => collatz::get_example(options, &[num_initial]),
+            (None, false) => collatz::get_example(options, initial_numbers),
+            (Some(batch), true) => {
+                let initial_numbers = (num_initial..num_initial + batch).collect::<Vec<_>>();
+                collatz::get_example(options, &initial_numbers)
+            }
+            (Some(_), false) => {
+                Err("Batch size cannot be combined with explicit initial numbers.".to_string())
+            }
+        },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Statistics { count, threshold } => {
+            statistics::get_example(options, count, threshold)
+        }
+        ExampleType::Describe { .. } => Err("Descriptions cannot be nested.".to_string()),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Estimate { .. } => Err("Estimates cannot be nested.".to_string()),
+        ExampleType::RunAll => Err("The run-all command cannot be nested.".to_string()),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Golden { .. } => Err("The golden command cannot be nested.".to_string()),
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Compat { .. } => Err("The compat command cannot be nested.".to_string()),
+    }
+}
+
+/// Returns all examples parameterized to run quickly.
+pub fn get_default_examples() -> Vec<ExampleType> {
+    vec![
+        #[cfg(feature = "f128")]
+        ExampleType::Fib {
+            sequence_length: 64,
+            batch: None,
+            expected_result: None,
+        },
+        #[cfg(feature = "f128")]
+        ExampleType::Fib8 {
+            sequence_length: 64,
+        },
+        #[cfg(feature = "f128")]
+        ExampleType::FibWindow {
+            sequence_length: 64,
+        },
+        #[cfg(feature = "f128")]
+        ExampleType::Mulfib {
+            sequence_length: 64,
+        },
+        #[cfg(feature = "f128")]
+        ExampleType::Mulfib8 {
+            sequence_length: 64,
+        },
+        ExampleType::FibSmall {
+            sequence_length: 64,
+        },
+        #[cfg(feature = "f128")]
+        ExampleType::Vdf { num_steps: 64 },
+        #[cfg(feature = "f128")]
+        ExampleType::VdfExempt { num_steps: 63 },
+        #[cfg(feature = "f128")]
+        ExampleType::Rotate { num_steps: 64 },
+        #[cfg(feature = "f128")]
+        ExampleType::Vm { num_steps: 64 },
+        #[cfg(feature = "f128")]
+        ExampleType::Rom { num_steps: 64 },
+        #[cfg(feature = "f128")]
+        ExampleType::Rescue {
+            chain_length: 8,
+            hidden: false,
+        },
+        #[cfg(feature = "f128")]
+        ExampleType::Rescue {
+            chain_length: 8,
+            hidden: true,
+        },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::RescueRaps { chain_length: 8 },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Merkle { tree_depth: 7 },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::MerkleBatch {
+            num_leaves: 8,
+            tree_depth: 7,
+        },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::LamportA { num_signatures: 4 },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::LamportT { num_signers: 3 },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Collatz {
+            num_initial: 52,
+            batch: None,
+            initial_numbers: vec![],
+        },
+        #[cfg(all(feature = "std", feature = "f128"))]
+        ExampleType::Statistics {
+            count: 64,
+            threshold: 1 << 31,
+        },
+    ]
+}
+
+// EXAMPLE REGISTRY
+// ================================================================================================
+
+/// A function which instantiates an example.
+type ExampleBuilder<'a> = Box<dyn Fn() -> Result<Box<dyn Example>, String> + 'a>;
+
+/// A list of named examples executed by the runner.
+#[derive(Default)]
+pub struct ExampleRegistry<'a> {
+    examples: Vec<(String, ExampleBuilder<'a>)>,
+}
+
+impl<'a> ExampleRegistry<'a> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a registry containing all examples returned by [get_default_examples()]
+    /// instantiated with the provided options.
+    pub fn with_default_examples(options: &'a ExampleOptions) -> Self {
+        let mut registry = Self::new();
+        for example in get_default_examples() {
+            let name = format!("{example:?}");
+            registry.register(name, move || build_example(options, &example));
+        }
+        registry
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Registers an example under the specified name; `builder` is invoked when the example is
+    /// about to be executed.
+    pub fn register<F>(&mut self, name: impl Into<String>, builder: F)
+    where
+        F: Fn() -> Result<Box<dyn Example>, String> + 'a,
+    {
+        self.examples.push((name.into(), Box::new(builder)));
+    }
+
+    /// Returns the number of registered examples.
+    pub fn len(&self) -> usize {
+        self.examples.len()
+    }
+
+    /// Returns true if no examples have been registered.
+    pub fn is_empty(&self) -> bool {
+        self.examples.is_empty()
+    }
+
+    // RUNNER
+    // --------------------------------------------------------------------------------------------
+
+    /// Executes all registered examples in the order in which they were registered and returns
+    /// a summary of the run.
+    ///
+    /// A panic raised by an example (including a panic raised while the example is being
+    /// instantiated) is caught and recorded as a failure of this example.
+    pub fn run_all(&self) -> RunSummary {
+        let results = self
+            .examples
+            .iter()
+            .map(|(name, builder)| {
+                let now = Instant::now();
+                // the builder and the example are not used after a panic, and thus, no broken
+                // invariants can be observed through them
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| run_example(builder)))
+                    .unwrap_or_else(|payload| Err(format!("panicked: {}", panic_message(payload))));
+                RunResult {
+                    name: name.clone(),
+                    error: outcome.err(),
+                    duration: now.elapsed(),
+                }
+            })
+            .collect();
+        RunSummary { results }
+    }
+}
+
+// RUN SUMMARY
+// ================================================================================================
+
+/// Result of executing a single example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunResult {
+    pub name: String,
+    /// Description of the failure, or None if the example passed.
+    pub error: Option<String>,
+    pub duration: Duration,
+}
+
+impl RunResult {
+    /// Returns true if the example passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Results of executing all examples of a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunSummary {
+    results: Vec<RunResult>,
+}
+
+impl RunSummary {
+    /// Returns results of all executed examples in the order of their execution.
+    pub fn results(&self) -> &[RunResult] {
+        &self.results
+    }
+
+    /// Returns the number of examples which failed.
+    pub fn num_failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| !result.passed())
+            .count()
+    }
+
+    /// Returns true if all examples passed.
+    pub fn all_passed(&self) -> bool {
+        self.num_failed() == 0
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .results
+            .iter()
+            .map(|result| result.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("example".len());
+
+        writeln!(
+            f,
+            "{:<width$} | {:<6} | {:>9} | error",
+            "example", "result", "time (ms)"
+        )?;
+        writeln!(f, "{:-<width$}-+--------+-----------+------", "")?;
+        for result in self.results.iter() {
+            let (status, error) = match &result.error {
+                None => ("passed", ""),
+                Some(error) => ("FAILED", error.as_str()),
+            };
+            writeln!(
+                f,
+                "{:<width$} | {:<6} | {:>9} | {}",
+                result.name,
+                status,
+                result.duration.as_millis(),
+                error
+            )?;
+        }
+        write!(
+            f,
+            "{} of {} examples failed",
+            self.num_failed(),
+            self.results.len()
+        )
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Instantiates an example, generates a proof for it, and verifies the proof after passing it
+/// through serialization.
+fn run_example(builder: &ExampleBuilder) -> Result<(), String> {
+    let example = builder().map_err(|err| format!("failed to initialize: {err}"))?;
+    let proof_bytes = example.prove().to_bytes();
+    let proof = StarkProof::from_bytes(&proof_bytes)
+        .map_err(|err| format!("failed to deserialize proof: {err}"))?;
+    example
+        .verify(proof)
+        .map_err(|err| format!("failed to verify proof: {err}"))
+}
+
+/// Extracts a message from the payload of a caught panic.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{build_example, get_default_examples, ExampleRegistry};
+use crate::{Example, ExampleOptions, ExampleType};
+use structopt::StructOpt;
+use winterfell::{StarkProof, VerifierError};
+
+#[test]
+fn run_all_continues_after_failures() {
+    let options = build_options();
+    let fib = ExampleType::Fib {
+        sequence_length: 64,
+        batch: None,
+        expected_result: None,
+    };
+
+    let mut registry = ExampleRegistry::new();
+    registry.register("panicking", || Ok(Box::new(PanickingExample)));
+    registry.register("fib", || build_example(&options, &fib));
+    registry.register("uninitialized", || Err("bad parameters".to_string()));
+    registry.register("wrong inputs", || {
+        Ok(Box::new(WrongInputsExample(build_example(&options, &fib)?)))
+    });
+    registry.register("fib again", || build_example(&options, &fib));
+    assert_eq!(5, registry.len());
+
+    let summary = registry.run_all();
+    let results = summary.results();
+    assert_eq!(5, results.len());
+    assert_eq!(3, summary.num_failed());
+    assert!(!summary.all_passed());
+
+    assert_eq!("panicking", results[0].name);
+    assert_eq!(
+        Some("panicked: proof generation failed".to_string()),
+        results[0].error
+    );
+    assert!(results[1].passed());
+    assert_eq!(
+        Some("failed to initialize: bad parameters".to_string()),
+        results[2].error
+    );
+    assert!(results[3]
+        .error
+        .as_ref()
+        .unwrap()
+        .starts_with("failed to verify proof"));
+    assert!(results[4].passed());
+
+    let table = summary.to_string();
+    assert!(table.contains("panicking"));
+    assert!(table.contains("FAILED"));
+    assert!(table.ends_with("3 of 5 examples failed"));
+}
+
+#[test]
+fn run_all_empty_registry() {
+    let registry = ExampleRegistry::new();
+    assert!(registry.is_empty());
+    let summary = registry.run_all();
+    assert!(summary.all_passed());
+    assert!(summary.to_string().ends_with("0 of 0 examples failed"));
+}
+
+#[test]
+fn run_all_nested() {
+    let options = build_options();
+    assert!(build_example(&options, &ExampleType::RunAll).is_err());
+}
+
+#[test]
+fn default_options_meet_target_security() {
+    let hash_fns = [
+        "blake3_192",
+        "blake3_256",
+        "sha3_256",
+        "keccak_256",
+        "blake2s_256",
+        "rp64_256",
+        "rp_jive64_256",
+        "griffin_jive64_256",
+    ];
+    for example_type in get_default_examples() {
+        let defaults = example_type.defaults().unwrap();
+        for field in ["f62", "f64", "f128"] {
+            for hash_fn in hash_fns {
+                let args = ["winterfell", "-h", hash_fn, "--field", field, "run-all"];
+                let options = ExampleOptions::from_iter(args);
+                // skip combinations of a field and a hash function not supported by the example
+                let example = match build_example(&options, &example_type) {
+                    Ok(example) => example,
+                    Err(_) => continue,
+                };
+                let report = example.explain_params().unwrap();
+                let target_security = defaults.target_security.min(report.collision_resistance());
+                assert!(
+                    report.conjectured_security() >= target_security,
+                    "{example_type:?} over {field} with {hash_fn} provides {} bits of security, \
+                    but {target_security} bits are targeted",
+                    report.conjectured_security()
+                );
+            }
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_options() -> ExampleOptions {
+    ExampleOptions::from_iter(["winterfell", "run-all"])
+}
+
+/// An example which panics when asked to generate a proof.
+struct PanickingExample;
+
+impl Example for PanickingExample {
+    fn prove(&self) -> StarkProof {
+        panic!("proof generation failed");
+    }
+
+    fn verify(&self, _proof: StarkProof) -> Result<(), VerifierError> {
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, _proof: StarkProof) -> Result<(), VerifierError> {
+        Ok(())
+    }
+}
+
+/// An example which verifies proofs of the wrapped example against wrong public inputs.
+struct WrongInputsExample(Box<dyn Example>);
+
+impl Example for WrongInputsExample {
+    fn prove(&self) -> StarkProof {
+        self.0.prove()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        self.0.verify_with_wrong_inputs(proof)
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        self.0.verify(proof)
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    rescue, BaseElement, FieldElement, LtGadget, ProofOptions, RangeCheck, BITS_OFFSET, COUNT_COL,
+    CYCLE_LENGTH, DIGEST_COL, LT_OFFSET, SUM_COL, SUM_SQ_COL, TRACE_WIDTH, VALUE_BITS, VALUE_COL,
+};
+use crate::utils::{are_equal, is_zero, not, EvaluationResult};
+use winterfell::{
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, Serializable, TraceInfo,
+    TransitionConstraintDegree,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Specifies steps on which Rescue transition function is applied.
+const CYCLE_MASK: [BaseElement; CYCLE_LENGTH] = [
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ONE,
+    BaseElement::ZERO,
+    BaseElement::ZERO,
+];
+
+// STATISTICS AIR
+// ================================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicInputs {
+    pub count: usize,
+    pub threshold: u32,
+    pub sum: BaseElement,
+    pub sum_of_squares: BaseElement,
+    pub count_below: BaseElement,
+    pub commitment: [BaseElement; 2],
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u64(self.count as u64);
+        target.write_u32(self.threshold);
+        target.write(self.sum);
+        target.write(self.sum_of_squares);
+        target.write(self.count_below);
+        target.write(&self.commitment[..]);
+    }
+}
+
+/// AIR for proving the sum and the sum of squares of a dataset committed to by a hash chain.
+///
+/// Every value of the dataset occupies a cycle of 16 steps. On the first step of a cycle, the
+/// value is added to the first element of the digest computed in the previous cycle, the result
+/// is hashed over the next 14 steps, and the value is added to the running sums. On every step,
+/// the value must also be equal to the sum of its binary decomposition; this ensures that all
+/// values are smaller than 2^32, and thus, the sums cannot wrap around the field modulus.
+///
+/// On every step, the value is also compared with the public threshold using the less-than
+/// gadget, and on the first step of a cycle, the resulting flag is added to the running count of
+/// values below the threshold.
+pub struct StatisticsAir {
+    context: AirContext<BaseElement>,
+    range_check: RangeCheck,
+    lt_gadget: LtGadget,
+    threshold: BaseElement,
+    sum: BaseElement,
+    sum_of_squares: BaseElement,
+    count_below: BaseElement,
+    commitment: [BaseElement; 2],
+}
+
+impl Air for StatisticsAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        assert_eq!(TRACE_WIDTH, trace_info.width());
+        assert_eq!(
+            pub_inputs.count * CYCLE_LENGTH,
+            trace_info.length(),
+            "trace length does not match the number of values"
+        );
+
+        let mut degrees = vec![
+            // Rescue hash state
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![CYCLE_LENGTH]),
+            // absorption of the value into the digest
+            TransitionConstraintDegree::with_cycles(1, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(1, vec![CYCLE_LENGTH]),
+            // running sums
+            TransitionConstraintDegree::with_cycles(1, vec![CYCLE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(2, vec![CYCLE_LENGTH]),
+        ];
+
+        // binary decomposition of the value, followed by the running count of values below the
+        // threshold and the comparison of the value with the threshold
+        let range_check = RangeCheck::new(VALUE_BITS);
+        let lt_gadget = LtGadget::new(VALUE_BITS);
+        degrees.append(&mut range_check.constraint_degrees());
+        degrees.push(TransitionConstraintDegree::with_cycles(
+            1,
+            vec![CYCLE_LENGTH],
+        ));
+        degrees.append(&mut lt_gadget.constraint_degrees());
+
+        StatisticsAir {
+            context: AirContext::new(trace_info, degrees, 12, options),
+            range_check,
+            lt_gadget,
+            threshold: BaseElement::from(pub_inputs.threshold),
+            sum: pub_inputs.sum,
+            sum_of_squares: pub_inputs.sum_of_squares,
+            count_below: pub_inputs.count_below,
+            commitment: pub_inputs.commitment,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+        debug_assert_eq!(TRACE_WIDTH, next.len());
+
+        // split periodic values into flags and Rescue round constants
+        let hash_flag = periodic_values[0];
+        let absorb_flag = periodic_values[1];
+        let last_flag = periodic_values[2];
+        let ark = &periodic_values[3..];
+
+        // when hash_flag = 1, constraints for Rescue round are enforced
+        let state_range = 0..rescue::STATE_WIDTH;
+        rescue::enforce_round(
+            result,
+            &current[state_range.clone()],
+            &next[state_range],
+            ark,
+            hash_flag,
+        );
+
+        // when hash_flag = 0, the digest is carried over to the next step and the rest of the
+        // hash state is reset; on the last step of a cycle, the first element of the digest is
+        // carried over into the digest column instead, as the value is absorbed into it
+        let copy_flag = not(hash_flag);
+        result.agg_constraint(0, copy_flag - last_flag, are_equal(current[0], next[0]));
+        result.agg_constraint(1, copy_flag, are_equal(current[1], next[1]));
+        result.agg_constraint(2, copy_flag, is_zero(next[2]));
+        result.agg_constraint(3, copy_flag, is_zero(next[3]));
+        result.agg_constraint(4, last_flag, are_equal(current[0], next[DIGEST_COL]));
+
+        // on the first step of a cycle, the hash state must contain the sum of the value and the
+        // digest, and the value is added to the running sums
+        let value = current[VALUE_COL];
+        result.agg_constraint(
+            5,
+            absorb_flag,
+            are_equal(current[0], current[DIGEST_COL] + value),
+        );
+        result[6] = are_equal(next[SUM_COL], current[SUM_COL] + absorb_flag * value);
+        result[7] = are_equal(
+            next[SUM_SQ_COL],
+            current[SUM_SQ_COL] + absorb_flag * value.square(),
+        );
+
+        // the value must be equal to the sum of its bits, and all bits must be binary
+        let bits = &current[BITS_OFFSET..LT_OFFSET];
+        self.range_check.enforce(&mut result[8..], value, bits);
+
+        // the value is compared with the threshold, and on the first step of a cycle, the
+        // comparison flag is added to the running count
+        let lt_advice = &current[LT_OFFSET..TRACE_WIDTH];
+        let count_idx = 8 + self.range_check.num_constraints();
+        let is_below = self.lt_gadget.enforce(
+            &mut result[count_idx + 1..],
+            value,
+            E::from(self.threshold),
+            lt_advice,
+        );
+        result[count_idx] = are_equal(next[COUNT_COL], current[COUNT_COL] + absorb_flag * is_below);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        // the hash state, the digest column, the sums, and the count start at zero and end with
+        // the public values; the first element of the hash state is defined by the value absorbed
+        // into it
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(1, 0, BaseElement::ZERO),
+            Assertion::single(2, 0, BaseElement::ZERO),
+            Assertion::single(3, 0, BaseElement::ZERO),
+            Assertion::single(DIGEST_COL, 0, BaseElement::ZERO),
+            Assertion::single(SUM_COL, 0, BaseElement::ZERO),
+            Assertion::single(SUM_SQ_COL, 0, BaseElement::ZERO),
+            Assertion::single(COUNT_COL, 0, BaseElement::ZERO),
+            Assertion::single(0, last_step, self.commitment[0]),
+            Assertion::single(1, last_step, self.commitment[1]),
+            Assertion::single(SUM_COL, last_step, self.sum),
+            Assertion::single(SUM_SQ_COL, last_step, self.sum_of_squares),
+            Assertion::single(COUNT_COL, last_step, self.count_below),
+        ]
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut result = vec![
+            CYCLE_MASK.to_vec(),
+            build_step_mask(0),
+            build_step_mask(CYCLE_LENGTH - 1),
+        ];
+        result.append(&mut rescue::get_round_constants());
+        result
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns a periodic column which is set to one only on the specified step of every cycle.
+fn build_step_mask(step: usize) -> Vec<BaseElement> {
+    let mut mask = vec![BaseElement::ZERO; CYCLE_LENGTH];
+    mask[step] = BaseElement::ONE;
+    mask
+}
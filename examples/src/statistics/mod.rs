@@ -0,0 +1,253 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::rescue::rescue;
+use crate::{
+    explain::ParamsReport,
+    utils::{
+        check_trace_statistics,
+        gadgets::{LtGadget, RangeCheck},
+        log_verified_statement, prove_checked,
+    },
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
+use core::marker::PhantomData;
+use log::debug;
+use rand_utils::rand_vector;
+use std::time::Instant;
+use winterfell::{
+    crypto::ElementHasher,
+    math::{fields::f128::BaseElement, log2, FieldElement, StarkField},
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
+};
+
+mod air;
+use air::{PublicInputs, StatisticsAir};
+
+mod prover;
+use prover::StatisticsProver;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+const CYCLE_LENGTH: usize = 16;
+const NUM_HASH_ROUNDS: usize = 14;
+
+/// Number of bits in every value of the dataset.
+const VALUE_BITS: usize = 32;
+
+// trace layout: 4 columns of Rescue hash state, followed by the first element of the digest
+// into which the current value is absorbed, the value column, the running sum, the running sum
+// of squares, the running count of values below the threshold, the binary decomposition of the
+// value, and the advice columns of the gadget comparing the value with the threshold
+const DIGEST_COL: usize = 4;
+const VALUE_COL: usize = 5;
+const SUM_COL: usize = 6;
+const SUM_SQ_COL: usize = 7;
+const COUNT_COL: usize = 8;
+const BITS_OFFSET: usize = 9;
+const LT_OFFSET: usize = BITS_OFFSET + VALUE_BITS;
+const TRACE_WIDTH: usize = LT_OFFSET + VALUE_BITS + 1;
+
+// AGGREGATE STATISTICS EXAMPLE
+// ================================================================================================
+
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(
+    options: &ExampleOptions,
+    count: usize,
+    threshold: u32,
+) -> Result<Box<dyn Example>, String> {
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
+
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(StatisticsExample::<Blake3_192>::new(
+            count, threshold, options,
+        ))),
+        HashFunction::Blake3_256 => Ok(Box::new(StatisticsExample::<Blake3_256>::new(
+            count, threshold, options,
+        ))),
+        HashFunction::Sha3_256 => Ok(Box::new(StatisticsExample::<Sha3_256>::new(
+            count, threshold, options,
+        ))),
+        HashFunction::Keccak256 => Ok(Box::new(StatisticsExample::<Keccak_256>::new(
+            count, threshold, options,
+        ))),
+        HashFunction::Blake2s256 => Ok(Box::new(StatisticsExample::<Blake2s_256>::new(
+            count, threshold, options,
+        ))),
+        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+    }
+}
+
+/// Proves that the sum and the sum of squares of a private dataset of 32-bit values (and hence,
+/// its mean and variance), as well as the number of values below a public threshold, are equal
+/// to public values.
+///
+/// The dataset is bound to the proof by a chain of Rescue hashes absorbing one value at a time;
+/// the digest of this chain is a part of the public inputs.
+pub struct StatisticsExample<H: ElementHasher> {
+    options: ProofOptions,
+    values: Vec<BaseElement>,
+    threshold: u32,
+    sum: BaseElement,
+    sum_of_squares: BaseElement,
+    count_below: BaseElement,
+    commitment: [BaseElement; 2],
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> StatisticsExample<H> {
+    pub fn new(count: usize, threshold: u32, options: ProofOptions) -> Self {
+        assert!(count.is_power_of_two(), "count must be a power of 2");
+
+        // generate a random dataset of 32-bit values
+        let values = rand_vector::<u32>(count)
+            .into_iter()
+            .map(BaseElement::from)
+            .collect::<Vec<_>>();
+
+        // compute the statistics and the commitment to the dataset
+        let now = Instant::now();
+        let (sum, sum_of_squares) = compute_sums(&values);
+        let count_below = compute_count_below(&values, threshold);
+        let commitment = compute_commitment(&values);
+        let mean = sum.as_int() as f64 / count as f64;
+        let variance = sum_of_squares.as_int() as f64 / count as f64 - mean * mean;
+        debug!(
+            "Computed statistics of {} values (mean: {:.2}, variance: {:.2}, below {}: {}) in {} ms",
+            count,
+            mean,
+            variance,
+            threshold,
+            count_below,
+            now.elapsed().as_millis(),
+        );
+
+        StatisticsExample {
+            options,
+            values,
+            threshold,
+            sum,
+            sum_of_squares,
+            count_below,
+            commitment,
+            _hasher: PhantomData,
+        }
+    }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            count: self.values.len(),
+            threshold: self.threshold,
+            sum: self.sum,
+            sum_of_squares: self.sum_of_squares,
+            count_below: self.count_below,
+            commitment: self.commitment,
+        }
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl<H: ElementHasher> Example for StatisticsExample<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for statistics of {} values\n\
+            ---------------------",
+            self.values.len()
+        );
+
+        // create a prover
+        let prover = StatisticsProver::<H>::new(self.options.clone(), self.threshold);
+
+        // generate the execution trace
+        let now = Instant::now();
+        let trace = prover.build_trace(&self.values);
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace.width(),
+            log2(trace_length),
+            now.elapsed().as_millis()
+        );
+
+        // binary decomposition of the value, the comparison flag, and the binary decomposition of
+        // the difference computed by the comparison gadget are all binary columns
+        let bit_columns = (BITS_OFFSET..TRACE_WIDTH).collect::<Vec<_>>();
+        let thresholds = DegeneracyThresholds::new().with_binary_columns(&bit_columns);
+        check_trace_statistics(&trace, &thresholds);
+
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement =
+            winterfell::verify_and_extract::<StatisticsAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let pub_inputs = PublicInputs {
+            sum: self.sum + BaseElement::ONE,
+            ..self.pub_inputs()
+        };
+        winterfell::verify::<StatisticsAir, H>(proof, pub_inputs)
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.values.len() * CYCLE_LENGTH);
+        let air = StatisticsAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the sum and the sum of squares of the specified values.
+///
+/// For 32-bit values, neither of the sums can overflow the field modulus as long as there are
+/// fewer than 2^63 values.
+fn compute_sums(values: &[BaseElement]) -> (BaseElement, BaseElement) {
+    values.iter().fold(
+        (BaseElement::ZERO, BaseElement::ZERO),
+        |(sum, sum_sq), &v| (sum + v, sum_sq + v.square()),
+    )
+}
+
+/// Returns the number of values which are smaller than the specified threshold.
+fn compute_count_below(values: &[BaseElement], threshold: u32) -> BaseElement {
+    let count = values
+        .iter()
+        .filter(|value| value.as_int() < threshold as u128)
+        .count();
+    BaseElement::from(count as u64)
+}
+
+/// Returns the digest of a chain of Rescue hashes which absorbs the specified values one at a
+/// time into the first element of the hash state.
+fn compute_commitment(values: &[BaseElement]) -> [BaseElement; 2] {
+    let mut result = [BaseElement::ZERO; 2];
+    for &value in values {
+        let input = [result[0] + value, result[1]];
+        rescue::hash(input, &mut result);
+    }
+    result
+}
@@ -0,0 +1,125 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    rescue, BaseElement, ElementHasher, FieldElement, LtGadget, PhantomData, ProofOptions, Prover,
+    PublicInputs, RangeCheck, StarkField, StatisticsAir, Trace, TraceTable, BITS_OFFSET, COUNT_COL,
+    CYCLE_LENGTH, DIGEST_COL, LT_OFFSET, NUM_HASH_ROUNDS, SUM_COL, SUM_SQ_COL, TRACE_WIDTH,
+    VALUE_BITS, VALUE_COL,
+};
+
+// STATISTICS PROVER
+// ================================================================================================
+
+pub struct StatisticsProver<H: ElementHasher> {
+    options: ProofOptions,
+    threshold: u32,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> StatisticsProver<H> {
+    pub fn new(options: ProofOptions, threshold: u32) -> Self {
+        Self {
+            options,
+            threshold,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Builds an execution trace which accumulates the sums, the count of values below the
+    /// threshold, and the commitment for the specified values.
+    ///
+    /// Only the lower 32 bits of every value are written into the binary decomposition columns
+    /// and compared with the threshold; thus, a trace built from a value which does not fit into
+    /// 32 bits is not valid.
+    pub fn build_trace(&self, values: &[BaseElement]) -> TraceTable<BaseElement> {
+        assert!(
+            values.len().is_power_of_two(),
+            "number of values must be a power of 2"
+        );
+
+        // allocate memory to hold the trace table
+        let trace_length = values.len() * CYCLE_LENGTH;
+        let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
+        let threshold = self.threshold;
+
+        trace.fill(
+            |state| {
+                // the digest and the sums start at zero, and thus, the first value is absorbed
+                // into an empty hash state
+                state[..BITS_OFFSET].fill(BaseElement::ZERO);
+                set_value(state, values[0], threshold);
+                state[0] = values[0];
+            },
+            |step, state| {
+                // add the value to the sums (and to the count, if it is below the threshold) on the
+                // first step of every cycle
+                if step % CYCLE_LENGTH == 0 {
+                    let value = state[VALUE_COL];
+                    state[SUM_COL] += value;
+                    state[SUM_SQ_COL] += value.square();
+                    state[COUNT_COL] += state[LT_OFFSET];
+                }
+
+                // for the first 14 steps in every cycle, compute a single round of Rescue hash;
+                // for the remaining 2 steps, carry over the digest to the next step, and at the
+                // end of the cycle, absorb the next value into the first element of the digest
+                let cycle_step = step % CYCLE_LENGTH;
+                if cycle_step < NUM_HASH_ROUNDS {
+                    rescue::apply_round(&mut state[..rescue::STATE_WIDTH], step);
+                } else {
+                    state[2] = BaseElement::ZERO;
+                    state[3] = BaseElement::ZERO;
+                }
+                if cycle_step == CYCLE_LENGTH - 1 {
+                    let value = values[(step + 1) / CYCLE_LENGTH];
+                    state[DIGEST_COL] = state[0];
+                    state[0] += value;
+                    set_value(state, value, threshold);
+                }
+            },
+        );
+
+        trace
+    }
+}
+
+impl<H: ElementHasher> Prover for StatisticsProver<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    type BaseField = BaseElement;
+    type Air = StatisticsAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let last_step = trace.length() - 1;
+        PublicInputs {
+            count: trace.length() / CYCLE_LENGTH,
+            threshold: self.threshold,
+            sum: trace.get(SUM_COL, last_step),
+            sum_of_squares: trace.get(SUM_SQ_COL, last_step),
+            count_below: trace.get(COUNT_COL, last_step),
+            commitment: [trace.get(0, last_step), trace.get(1, last_step)],
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Writes the value, the binary decomposition of its lower 32 bits, and the advice for comparing
+/// its lower 32 bits with the threshold into the state.
+fn set_value(state: &mut [BaseElement], value: BaseElement, threshold: u32) {
+    state[VALUE_COL] = value;
+    let value = value.as_int() as u32 as u64;
+    RangeCheck::new(VALUE_BITS).fill(value, &mut state[BITS_OFFSET..LT_OFFSET]);
+    LtGadget::new(VALUE_BITS).fill(value, threshold as u64, &mut state[LT_OFFSET..TRACE_WIDTH]);
+}
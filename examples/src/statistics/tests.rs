@@ -0,0 +1,171 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    compute_commitment, compute_count_below, compute_sums, BaseElement, Blake3_256, FieldElement,
+    PublicInputs, StatisticsAir, StatisticsProver, Trace, CYCLE_LENGTH, LT_OFFSET,
+};
+use rand_utils::{rand_value, rand_vector};
+use winterfell::{
+    math::fields::QuadExtension, test_kit, Air, FieldExtension, ProofOptions, TraceTable,
+};
+
+/// Threshold below which values are counted in tests.
+const THRESHOLD: u32 = 1 << 31;
+
+#[test]
+fn statistics_test_basic_proof_verification() {
+    let statistics = Box::new(super::StatisticsExample::<Blake3_256>::new(
+        64,
+        THRESHOLD,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification(statistics);
+}
+
+#[test]
+fn statistics_test_basic_proof_verification_extension() {
+    let statistics = Box::new(super::StatisticsExample::<Blake3_256>::new(
+        64,
+        THRESHOLD,
+        build_options(true),
+    ));
+    crate::tests::test_basic_proof_verification(statistics);
+}
+
+#[test]
+fn statistics_test_basic_proof_verification_fail() {
+    let statistics = Box::new(super::StatisticsExample::<Blake3_256>::new(
+        64,
+        THRESHOLD,
+        build_options(false),
+    ));
+    crate::tests::test_basic_proof_verification_fail(statistics);
+}
+
+#[test]
+fn statistics_test_air_test_kit() {
+    let (air, trace_polys) = build_air_and_trace_polys(&rand_values(8), THRESHOLD, |_| {});
+    test_kit::boundary_poly_check(&air, &trace_polys);
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+    test_kit::evaluate_at_random_point(
+        &air,
+        &trace_polys,
+        rand_value::<QuadExtension<BaseElement>>(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "transition constraint 8 is not satisfied by the trace")]
+fn statistics_test_value_exceeds_32_bits() {
+    let mut values = rand_values(8);
+    values[3] = BaseElement::from(u32::MAX) + BaseElement::from(5u8);
+    let (air, trace_polys) = build_air_and_trace_polys(&values, THRESHOLD, |_| {});
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+#[test]
+#[should_panic(expected = "transition constraint 8 is not satisfied by the trace")]
+fn statistics_test_value_wraps_around_modulus() {
+    // a value of -1 would decrease the sum, but its lower 32 bits do not compose into it
+    let mut values = rand_values(8);
+    values[5] = -BaseElement::ONE;
+    let (air, trace_polys) = build_air_and_trace_polys(&values, THRESHOLD, |_| {});
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+#[test]
+fn statistics_test_count_below_edge_values() {
+    // values equal to the threshold are not counted; values right below it are
+    let threshold = 1000;
+    let values = [0, 999, 1000, 1001, u32::MAX, 1, 1000, 5]
+        .into_iter()
+        .map(BaseElement::from)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        BaseElement::from(4u8),
+        compute_count_below(&values, threshold)
+    );
+
+    let (air, trace_polys) = build_air_and_trace_polys(&values, threshold, |_| {});
+    test_kit::boundary_poly_check(&air, &trace_polys);
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+
+    // a zero threshold and a threshold equal to the largest value are valid as well
+    for threshold in [0, u32::MAX] {
+        let (air, trace_polys) = build_air_and_trace_polys(&values, threshold, |_| {});
+        test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+    }
+}
+
+#[test]
+#[should_panic(expected = "transition constraint 42 is not satisfied by the trace")]
+fn statistics_test_lt_advice_claims_below() {
+    // the value 1000 is not below the threshold, but the advice claims otherwise; the lower 32
+    // bits of the difference for the wrong flag are the same as for the correct flag, and thus,
+    // the difference bits are left unchanged
+    let mut values = rand_values(8);
+    values[2] = BaseElement::from(1000u32);
+    let (air, trace_polys) = build_air_and_trace_polys(&values, 1000, |trace| {
+        trace.set(LT_OFFSET, 2 * CYCLE_LENGTH + 5, BaseElement::ONE);
+    });
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+#[test]
+#[should_panic(expected = "transition constraint 42 is not satisfied by the trace")]
+fn statistics_test_lt_advice_claims_not_below() {
+    // the value 999 is below the threshold, but the advice claims otherwise
+    let mut values = rand_values(8);
+    values[6] = BaseElement::from(999u32);
+    let (air, trace_polys) = build_air_and_trace_polys(&values, 1000, |trace| {
+        trace.set(LT_OFFSET, 6 * CYCLE_LENGTH + 5, BaseElement::ZERO);
+    });
+    test_kit::evaluate_at_random_point(&air, &trace_polys, rand_value::<BaseElement>());
+}
+
+fn build_options(use_extension_field: bool) -> ProofOptions {
+    let extension = if use_extension_field {
+        FieldExtension::Quadratic
+    } else {
+        FieldExtension::None
+    };
+    ProofOptions::new(28, 8, 0, extension, 4, 256)
+}
+
+fn rand_values(count: usize) -> Vec<BaseElement> {
+    rand_vector::<u32>(count)
+        .into_iter()
+        .map(BaseElement::from)
+        .collect()
+}
+
+/// Builds a trace for the specified values and threshold, modifies it using the provided
+/// closure, and returns the AIR for the sums, the count, and the commitment computed from these
+/// values together with polynomials interpolated from the modified trace.
+fn build_air_and_trace_polys<F>(
+    values: &[BaseElement],
+    threshold: u32,
+    modify: F,
+) -> (StatisticsAir, Vec<Vec<BaseElement>>)
+where
+    F: FnOnce(&mut TraceTable<BaseElement>),
+{
+    let options = build_options(false);
+    let prover = StatisticsProver::<Blake3_256>::new(options.clone(), threshold);
+    let mut trace = prover.build_trace(values);
+    modify(&mut trace);
+    let (sum, sum_of_squares) = compute_sums(values);
+    let pub_inputs = PublicInputs {
+        count: values.len(),
+        threshold,
+        sum,
+        sum_of_squares,
+        count_below: compute_count_below(values, threshold),
+        commitment: compute_commitment(values),
+    };
+    let air = StatisticsAir::new(trace.get_info(), pub_inputs, options);
+    (air, crate::tests::get_trace_polys(&trace))
+}
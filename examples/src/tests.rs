@@ -4,6 +4,18 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::Example;
+#[cfg(feature = "std")]
+use std::time::Instant;
+#[cfg(feature = "f128")]
+use winterfell::{math::StarkField, test_kit, TraceTable};
+
+/// Number of proofs generated to measure proving time for comparison against the estimated time.
+#[cfg(feature = "std")]
+const NUM_PROVING_RUNS: usize = 3;
+
+/// Maximum factor by which measured proving time may differ from the estimated time.
+#[cfg(feature = "std")]
+const COST_ESTIMATE_TOLERANCE: f64 = 3.0;
 
 pub fn test_basic_proof_verification(e: Box<dyn Example>) {
     let proof = e.prove();
@@ -15,3 +27,416 @@ pub fn test_basic_proof_verification_fail(e: Box<dyn Example>) {
     let verified = e.verify_with_wrong_inputs(proof);
     assert!(verified.is_err());
 }
+
+/// Makes sure that the proving time estimated for the example is within a factor of
+/// [COST_ESTIMATE_TOLERANCE] of the median of several measured proving times.
+///
+/// Proving time is measured in the same environment in which the machine profile is calibrated
+/// (e.g., with the same optimization level), and thus, the tolerance accounts for noise and for
+/// costs which are not modeled, but not for systematic differences between environments. Tests
+/// running concurrently distort the measurement, and thus, tests which call this function are
+/// ignored by default and run separately with `--ignored --test-threads=1`.
+#[cfg(feature = "std")]
+pub fn test_cost_estimate(e: Box<dyn Example>) {
+    let estimate = e
+        .estimate_cost()
+        .expect("example does not support cost estimation");
+    let mut times = (0..NUM_PROVING_RUNS)
+        .map(|_| {
+            let now = Instant::now();
+            e.prove();
+            now.elapsed()
+        })
+        .collect::<Vec<_>>();
+    times.sort();
+    let actual = times[NUM_PROVING_RUNS / 2].as_secs_f64();
+    let estimated = estimate.total_time().as_secs_f64();
+
+    let ratio = actual / estimated;
+    assert!(
+        (1.0 / COST_ESTIMATE_TOLERANCE..=COST_ESTIMATE_TOLERANCE).contains(&ratio),
+        "proving took {actual:.3} s, but {estimated:.3} s was estimated"
+    );
+}
+
+/// Returns polynomials interpolated from the columns of the specified trace.
+#[cfg(feature = "f128")]
+pub fn get_trace_polys<B: StarkField>(trace: &TraceTable<B>) -> Vec<Vec<B>> {
+    let columns = (0..trace.width())
+        .map(|i| trace.get_column(i))
+        .collect::<Vec<_>>();
+    test_kit::interpolate_trace_columns(&columns)
+}
+
+// WINDOW ASSERTIONS
+// ================================================================================================
+
+#[cfg(feature = "f128")]
+mod windows {
+    use crate::utils::are_equal;
+    use winterfell::{
+        crypto::hashers::Blake3_256,
+        math::{fields::f128::BaseElement, FieldElement},
+        Air, AirContext, Assertion, ByteWriter, EvaluationFrame, FieldExtension, ProofOptions,
+        Prover, Serializable, StarkProof, TraceInfo, TraceTable, TransitionConstraintDegree,
+        VerifierError,
+    };
+
+    /// Length of execution traces built for window assertion tests.
+    const TRACE_LENGTH: usize = 64;
+
+    /// A window of consecutive steps described by its first step, its length, and the value
+    /// asserted against it.
+    type Window = (usize, usize, BaseElement);
+
+    #[derive(Clone)]
+    struct WindowInputs(Vec<Window>);
+
+    impl Serializable for WindowInputs {
+        fn write_into<W: ByteWriter>(&self, target: &mut W) {
+            for &(first_step, length, value) in self.0.iter() {
+                target.write_u64(first_step as u64);
+                target.write_u64(length as u64);
+                target.write(value);
+            }
+        }
+    }
+
+    /// AIR for a trace consisting of a step counter and a column which is constrained only by
+    /// window assertions.
+    struct WindowAir {
+        context: AirContext<BaseElement>,
+        windows: Vec<Window>,
+    }
+
+    impl Air for WindowAir {
+        type BaseField = BaseElement;
+        type PublicInputs = WindowInputs;
+
+        fn new(trace_info: TraceInfo, pub_inputs: WindowInputs, options: ProofOptions) -> Self {
+            let degrees = vec![TransitionConstraintDegree::new(1)];
+            let num_assertions = pub_inputs.0.len() + 1;
+            WindowAir {
+                context: AirContext::new(trace_info, degrees, num_assertions, options),
+                windows: pub_inputs.0,
+            }
+        }
+
+        fn context(&self) -> &AirContext<Self::BaseField> {
+            &self.context
+        }
+
+        fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+            &self,
+            frame: &EvaluationFrame<E>,
+            _periodic_values: &[E],
+            result: &mut [E],
+        ) {
+            result[0] = are_equal(frame.next()[0], frame.current()[0] + E::ONE);
+        }
+
+        fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+            let mut result = vec![Assertion::single(0, 0, BaseElement::ZERO)];
+            for &(first_step, length, value) in self.windows.iter() {
+                result.push(Assertion::window(1, first_step, length, value).unwrap());
+            }
+            result
+        }
+    }
+
+    struct WindowProver {
+        options: ProofOptions,
+        windows: Vec<Window>,
+    }
+
+    impl WindowProver {
+        /// Builds a trace in which the second column holds the asserted values within the
+        /// windows, and the square of the step outside of them.
+        fn build_trace(&self) -> TraceTable<BaseElement> {
+            let counter = (0..TRACE_LENGTH as u128)
+                .map(BaseElement::new)
+                .collect::<Vec<_>>();
+            let mut values = counter.iter().map(|&step| step * step).collect::<Vec<_>>();
+            for &(first_step, length, value) in self.windows.iter() {
+                values[first_step..first_step + length].fill(value);
+            }
+            TraceTable::init(vec![counter, values])
+        }
+    }
+
+    impl Prover for WindowProver {
+        type BaseField = BaseElement;
+        type Air = WindowAir;
+        type Trace = TraceTable<BaseElement>;
+        type HashFn = Blake3_256<BaseElement>;
+
+        fn get_pub_inputs(&self, _trace: &Self::Trace) -> WindowInputs {
+            WindowInputs(self.windows.clone())
+        }
+
+        fn options(&self) -> &ProofOptions {
+            &self.options
+        }
+    }
+
+    fn build_prover(windows: Vec<Window>) -> WindowProver {
+        WindowProver {
+            options: ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256),
+            windows,
+        }
+    }
+
+    fn verify(proof: StarkProof, windows: Vec<Window>) -> Result<(), VerifierError> {
+        winterfell::verify::<WindowAir, Blake3_256<BaseElement>>(proof, WindowInputs(windows))
+    }
+
+    #[test]
+    fn window_assertions_proof_verification() {
+        for length in [2, 4, 16] {
+            // one window at the start of the trace and one in the middle of it
+            let windows = vec![
+                (0, length, BaseElement::new(7)),
+                (2 * length, length, BaseElement::new(11)),
+            ];
+            let prover = build_prover(windows.clone());
+            let proof = prover.prove(prover.build_trace()).unwrap();
+            let result = verify(proof, windows);
+            assert!(result.is_ok(), "windows of length {length}: {result:?}");
+        }
+
+        // windows of different lengths in the same trace
+        let windows = vec![
+            (0, 2, BaseElement::new(3)),
+            (4, 4, BaseElement::new(5)),
+            (16, 16, BaseElement::new(7)),
+            (48, 4, BaseElement::new(9)),
+        ];
+        let prover = build_prover(windows.clone());
+        let proof = prover.prove(prover.build_trace()).unwrap();
+        assert!(verify(proof, windows).is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "trace does not satisfy assertion main_trace(1, 21)")
+    )]
+    fn window_assertions_wrong_cell_rejected() {
+        // in debug builds, the prover catches the wrong cell when validating the trace against
+        // the AIR; otherwise, the proof does not verify
+        let windows = vec![(16, 16, BaseElement::new(11))];
+        let prover = build_prover(windows.clone());
+        let mut trace = prover.build_trace();
+        trace.set(1, 21, BaseElement::new(12));
+
+        let proof = prover.prove(trace).unwrap();
+        assert!(verify(proof, windows).is_err());
+    }
+}
+
+// OUTPUT REGION ASSERTIONS
+// ================================================================================================
+
+#[cfg(feature = "f128")]
+mod outputs {
+    use winterfell::{
+        crypto::hashers::Blake3_256,
+        math::{fields::f128::BaseElement, FieldElement, StarkField},
+        Air, AirContext, Assertion, AuxTraceRandElements, ByteWriter, EvaluationFrame,
+        FieldExtension, ProofOptions, Prover, Serializable, StarkProof, TraceInfo, TraceTable,
+        TransitionConstraintDegree, VerifierError,
+    };
+
+    /// Length of execution traces built for output region tests.
+    const TRACE_LENGTH: usize = 1024;
+
+    /// Number of columns holding the outputs of the computation.
+    const NUM_OUTPUT_COLUMNS: usize = 4;
+
+    /// Number of sequence assertions placed against each output column; assertions against the
+    /// same column start at consecutive steps, and thus, each of them gets its own divisor.
+    const NUM_PHASES: usize = 4;
+
+    /// Stride of the sequence assertions; together, the assertions pin all 4096 cells of the
+    /// output columns.
+    const STRIDE: usize = NUM_PHASES;
+
+    /// BLAKE3 digest of a serialized proof for the output region; boundary constraints of the
+    /// output region are processed in multiple threads when `concurrent` feature is enabled, and
+    /// the proof must be the same regardless of whether the feature is enabled.
+    const PROOF_DIGEST: &str = "d3a31e9cd034cb7cc0ceb2c5f4bba8ade45d321be2dacc1ae6b244afedfcabbb";
+
+    /// Values of the output region; values asserted against output column `j` starting at step
+    /// `r` are stored at index `j * NUM_PHASES + r`.
+    #[derive(Clone)]
+    struct OutputInputs(Vec<Vec<BaseElement>>);
+
+    impl Serializable for OutputInputs {
+        fn write_into<W: ByteWriter>(&self, target: &mut W) {
+            for values in self.0.iter() {
+                target.write(values.as_slice());
+            }
+        }
+    }
+
+    /// AIR for a trace consisting of a step counter and columns in which column `j` holds
+    /// $(i + j)^2$ at step $i$.
+    struct OutputAir {
+        context: AirContext<BaseElement>,
+        outputs: Vec<Vec<BaseElement>>,
+    }
+
+    impl Air for OutputAir {
+        type BaseField = BaseElement;
+        type PublicInputs = OutputInputs;
+
+        fn new(trace_info: TraceInfo, pub_inputs: OutputInputs, options: ProofOptions) -> Self {
+            let degrees = vec![TransitionConstraintDegree::new(1); NUM_OUTPUT_COLUMNS + 1];
+            let num_assertions = pub_inputs.0.len() + 1;
+            OutputAir {
+                context: AirContext::new(trace_info, degrees, num_assertions, options),
+                outputs: pub_inputs.0,
+            }
+        }
+
+        fn context(&self) -> &AirContext<Self::BaseField> {
+            &self.context
+        }
+
+        fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+            &self,
+            frame: &EvaluationFrame<E>,
+            _periodic_values: &[E],
+            result: &mut [E],
+        ) {
+            let current = frame.current();
+            let next = frame.next();
+            result[0] = next[0] - current[0] - E::ONE;
+            // (i + j + 1)^2 = (i + j)^2 + 2 * (i + j) + 1
+            for j in 0..NUM_OUTPUT_COLUMNS {
+                let offset = E::from(j as u32);
+                result[j + 1] =
+                    next[j + 1] - current[j + 1] - (current[0] + offset).double() - E::ONE;
+            }
+        }
+
+        fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+            let mut result = vec![Assertion::single(0, 0, BaseElement::ZERO)];
+            for (i, values) in self.outputs.iter().enumerate() {
+                let (column, phase) = (i / NUM_PHASES, i % NUM_PHASES);
+                result.push(Assertion::sequence(
+                    column + 1,
+                    phase,
+                    STRIDE,
+                    values.clone(),
+                ));
+            }
+            result
+        }
+    }
+
+    struct OutputProver {
+        options: ProofOptions,
+    }
+
+    impl OutputProver {
+        fn build_trace(&self) -> TraceTable<BaseElement> {
+            let mut columns = vec![(0..TRACE_LENGTH as u128)
+                .map(BaseElement::new)
+                .collect::<Vec<_>>()];
+            for column in 0..NUM_OUTPUT_COLUMNS {
+                columns.push((0..TRACE_LENGTH).map(|i| output_at(column, i)).collect());
+            }
+            TraceTable::init(columns)
+        }
+    }
+
+    impl Prover for OutputProver {
+        type BaseField = BaseElement;
+        type Air = OutputAir;
+        type Trace = TraceTable<BaseElement>;
+        type HashFn = Blake3_256<BaseElement>;
+
+        fn get_pub_inputs(&self, _trace: &Self::Trace) -> OutputInputs {
+            build_outputs()
+        }
+
+        fn options(&self) -> &ProofOptions {
+            &self.options
+        }
+    }
+
+    /// Returns the value of output column `column` at the specified step.
+    fn output_at(column: usize, step: usize) -> BaseElement {
+        BaseElement::new((step + column) as u128).square()
+    }
+
+    fn build_outputs() -> OutputInputs {
+        let mut outputs = Vec::new();
+        for column in 0..NUM_OUTPUT_COLUMNS {
+            for phase in 0..NUM_PHASES {
+                let values = (phase..TRACE_LENGTH)
+                    .step_by(STRIDE)
+                    .map(|step| output_at(column, step))
+                    .collect();
+                outputs.push(values);
+            }
+        }
+        OutputInputs(outputs)
+    }
+
+    fn build_prover() -> OutputProver {
+        OutputProver {
+            options: ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 256),
+        }
+    }
+
+    fn verify(proof: StarkProof, outputs: OutputInputs) -> Result<(), VerifierError> {
+        winterfell::verify::<OutputAir, Blake3_256<BaseElement>>(proof, outputs)
+    }
+
+    #[test]
+    fn output_region_proof_verification() {
+        let outputs = build_outputs();
+        let num_cells = outputs.0.iter().map(|values| values.len()).sum::<usize>();
+        assert_eq!(4096, num_cells);
+
+        let prover = build_prover();
+        let proof = prover.prove(prover.build_trace()).unwrap();
+        let digest = hex::encode(blake3::hash(&proof.to_bytes()).as_bytes());
+        assert_eq!(PROOF_DIGEST, digest);
+        assert!(verify(proof.clone(), outputs.clone()).is_ok());
+
+        // changing a single output cell makes verification fail
+        let mut wrong_outputs = outputs;
+        wrong_outputs.0[5][17] += BaseElement::ONE;
+        assert!(verify(proof, wrong_outputs).is_err());
+    }
+
+    #[test]
+    fn output_region_value_polys() {
+        let trace_info = TraceInfo::new(NUM_OUTPUT_COLUMNS + 1, TRACE_LENGTH);
+        let air = OutputAir::new(trace_info, build_outputs(), build_prover().options);
+        let num_assertions = air.context().num_assertions();
+        let coefficients = vec![(BaseElement::ONE, BaseElement::ONE); num_assertions];
+        let constraints = air.get_boundary_constraints(&AuxTraceRandElements::new(), &coefficients);
+
+        // there is a group for each phase of the sequence assertions, and one more for the
+        // assertion against the step counter
+        assert_eq!(NUM_PHASES + 1, constraints.main_constraints().len());
+
+        // value polynomials evaluate to the asserted values at the asserted steps
+        let trace = build_prover().build_trace();
+        let g = BaseElement::get_root_of_unity(TRACE_LENGTH.trailing_zeros());
+        for group in constraints.main_constraints() {
+            for constraint in group.constraints().iter().filter(|c| c.poly().len() > 1) {
+                let (first_step, _) = constraint.poly_offset();
+                for step in (first_step..TRACE_LENGTH).step_by(STRIDE * 37) {
+                    let x = g.exp((step as u64).into());
+                    let value = trace.get(constraint.column(), step);
+                    assert_eq!(BaseElement::ZERO, constraint.evaluate_at(x, value));
+                }
+            }
+        }
+    }
+}
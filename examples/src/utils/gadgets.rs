@@ -0,0 +1,189 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Reusable constraint gadgets for range checks and comparisons of small values.
+//!
+//! A gadget describes a set of advice columns which the prover fills in alongside the regular
+//! columns of an execution trace, and a set of transition constraints over these columns. To use
+//! a gadget, an AIR reserves the advice columns in its trace layout, appends the constraint
+//! degrees returned by the gadget to its own constraint degrees, and calls the gadget's `enforce`
+//! method from `evaluate_transition()`; the prover calls the gadget's `fill` method for every row
+//! of the trace.
+
+use super::{are_equal, is_binary};
+use winterfell::{
+    math::{FieldElement, StarkField},
+    TransitionConstraintDegree,
+};
+
+// RANGE CHECK
+// ================================================================================================
+
+/// Range check which ensures that a value is smaller than 2^k for some k <= 32.
+///
+/// The value is decomposed into k binary advice columns, with the least significant bit in the
+/// first column. The gadget defines k + 1 constraints: the first one ensures that the value is
+/// equal to the sum of its bits (degree 1), and the remaining k ensure that each of the bits is
+/// binary (degree 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeCheck {
+    num_bits: usize,
+}
+
+impl RangeCheck {
+    /// Returns a range check for values smaller than 2^`num_bits`.
+    ///
+    /// # Panics
+    /// Panics if `num_bits` is zero or greater than 32.
+    pub fn new(num_bits: usize) -> Self {
+        assert!(
+            num_bits > 0 && num_bits <= 32,
+            "number of bits must be between 1 and 32, but was {num_bits}"
+        );
+        RangeCheck { num_bits }
+    }
+
+    /// Returns the number of bits in values checked by this gadget.
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    /// Returns the number of advice columns required by this gadget.
+    pub fn num_advice_columns(&self) -> usize {
+        self.num_bits
+    }
+
+    /// Returns the number of constraints defined by this gadget.
+    pub fn num_constraints(&self) -> usize {
+        self.num_bits + 1
+    }
+
+    /// Returns degrees of the constraints defined by this gadget, assuming the checked value is
+    /// a linear expression over trace columns.
+    pub fn constraint_degrees(&self) -> Vec<TransitionConstraintDegree> {
+        let mut result = vec![TransitionConstraintDegree::new(1)];
+        result.resize(self.num_constraints(), TransitionConstraintDegree::new(2));
+        result
+    }
+
+    /// Evaluates constraints of this gadget against the specified value and its binary
+    /// decomposition, and writes the results into the first `num_constraints()` slots of
+    /// `result`.
+    pub fn enforce<E: FieldElement>(&self, result: &mut [E], value: E, advice: &[E]) {
+        debug_assert_eq!(self.num_advice_columns(), advice.len());
+
+        let mut power_of_two = E::ONE;
+        let mut composed_value = E::ZERO;
+        for (i, &bit) in advice.iter().enumerate() {
+            composed_value += bit * power_of_two;
+            power_of_two = power_of_two.double();
+            result[1 + i] = is_binary(bit);
+        }
+        result[0] = are_equal(value, composed_value);
+    }
+
+    /// Writes the binary decomposition of the specified value into the advice columns.
+    ///
+    /// Only the lower `num_bits()` bits of the value are written; thus, if the value is not
+    /// smaller than 2^`num_bits()`, the resulting advice does not satisfy the constraints.
+    pub fn fill<B: StarkField>(&self, value: u64, advice: &mut [B]) {
+        debug_assert_eq!(self.num_advice_columns(), advice.len());
+        for (i, bit) in advice.iter_mut().enumerate() {
+            *bit = B::from(((value >> i) & 1) as u8);
+        }
+    }
+}
+
+// LESS-THAN GADGET
+// ================================================================================================
+
+/// Comparison gadget which computes a binary flag set to one if and only if a < b for values
+/// a and b smaller than 2^k for some k <= 32.
+///
+/// The gadget uses k + 1 advice columns: a borrow column which holds the flag, followed by the
+/// binary decomposition of a - b + borrow * 2^k. This difference fits into k bits only when the
+/// borrow is set correctly: when a < b and the borrow is zero, the difference wraps around the
+/// field modulus; when a >= b and the borrow is one, the difference is at least 2^k.
+///
+/// The gadget defines k + 2 constraints: the k + 1 constraints of the [RangeCheck] gadget for the
+/// difference (of degrees 1 and 2), followed by a constraint ensuring that the borrow is binary
+/// (degree 2). These constraints do not check that a and b are smaller than 2^k; this must be
+/// ensured by other constraints of the AIR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LtGadget {
+    range_check: RangeCheck,
+}
+
+impl LtGadget {
+    /// Returns a comparison gadget for values smaller than 2^`num_bits`.
+    ///
+    /// # Panics
+    /// Panics if `num_bits` is zero or greater than 32.
+    pub fn new(num_bits: usize) -> Self {
+        LtGadget {
+            range_check: RangeCheck::new(num_bits),
+        }
+    }
+
+    /// Returns the number of bits in values compared by this gadget.
+    pub fn num_bits(&self) -> usize {
+        self.range_check.num_bits()
+    }
+
+    /// Returns the number of advice columns required by this gadget.
+    pub fn num_advice_columns(&self) -> usize {
+        self.range_check.num_advice_columns() + 1
+    }
+
+    /// Returns the number of constraints defined by this gadget.
+    pub fn num_constraints(&self) -> usize {
+        self.range_check.num_constraints() + 1
+    }
+
+    /// Returns degrees of the constraints defined by this gadget, assuming the compared values
+    /// are linear expressions over trace columns.
+    pub fn constraint_degrees(&self) -> Vec<TransitionConstraintDegree> {
+        let mut result = self.range_check.constraint_degrees();
+        result.push(TransitionConstraintDegree::new(2));
+        result
+    }
+
+    /// Evaluates constraints of this gadget against the specified values and advice, writes the
+    /// results into the first `num_constraints()` slots of `result`, and returns the flag which
+    /// is one if a < b and zero otherwise.
+    ///
+    /// The returned flag is a single advice column, and thus, it can be used in other
+    /// constraints of the AIR without increasing their degree by more than one.
+    pub fn enforce<E: FieldElement>(&self, result: &mut [E], a: E, b: E, advice: &[E]) -> E {
+        debug_assert_eq!(self.num_advice_columns(), advice.len());
+
+        let borrow = advice[0];
+        let shift = E::from(1u64 << self.num_bits());
+        let difference = a - b + borrow * shift;
+        self.range_check.enforce(result, difference, &advice[1..]);
+        result[self.range_check.num_constraints()] = is_binary(borrow);
+        borrow
+    }
+
+    /// Writes the borrow flag and the binary decomposition of the difference for the specified
+    /// values into the advice columns.
+    ///
+    /// # Panics
+    /// Panics if either of the values is not smaller than 2^`num_bits()`.
+    pub fn fill<B: StarkField>(&self, a: u64, b: u64, advice: &mut [B]) {
+        debug_assert_eq!(self.num_advice_columns(), advice.len());
+        let shift = 1u64 << self.num_bits();
+        assert!(
+            a < shift && b < shift,
+            "compared values must be smaller than 2^{}",
+            self.num_bits()
+        );
+
+        let borrow = a < b;
+        advice[0] = B::from(borrow as u8);
+        self.range_check
+            .fill(a + borrow as u64 * shift - b, &mut advice[1..]);
+    }
+}
@@ -3,12 +3,17 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use core::ops::Range;
+use core::{fmt::Debug, ops::Range};
+use log::debug;
 use winterfell::{
     math::{FieldElement, StarkField},
-    Trace, TraceTable,
+    Air, DegeneracyThresholds, Prover, ProverError, StarkProof, Trace, TraceInfo, TraceStatistics,
+    TraceTable, VerifiedStatement, DEFAULT_DISTINCT_VALUES_CAP,
 };
 
+pub mod gadgets;
+
+#[cfg(feature = "f128")]
 pub mod rescue;
 
 // CONSTRAINT EVALUATION HELPERS
@@ -57,6 +62,84 @@ impl<E: FieldElement> EvaluationResult<E> for Vec<E> {
 // OTHER FUNCTIONS
 // ================================================================================================
 
+/// Returns the length of an execution trace which holds the specified number of rows, i.e., the
+/// smallest power of two which is not smaller than `num_rows` and not smaller than the minimum
+/// trace length.
+///
+/// A computation of `n` steps requires `n + 1` rows, since the final state occupies a row of its
+/// own; callers are expected to account for that row in `num_rows`.
+///
+/// # Panics
+/// Panics if the trace length does not fit into `usize`.
+pub fn get_padded_trace_length(num_rows: usize) -> usize {
+    num_rows
+        .checked_next_power_of_two()
+        .expect("trace length must fit into usize")
+        .max(TraceInfo::MIN_TRACE_LENGTH)
+}
+
+/// Generates a proof for the provided trace, and makes sure that the proof attests to the public
+/// inputs the caller expects it to be verified against.
+///
+/// The public inputs of a proof are derived by the prover from the trace; a proof generated for
+/// a trace which does not match the expected inputs would fail verification later on, and thus,
+/// the mismatch is reported right away instead.
+///
+/// # Errors
+/// Returns the same errors as [Prover::prove()].
+///
+/// # Panics
+/// Panics if the public inputs derived by the prover differ from `expected`.
+pub fn prove_checked<P: Prover>(
+    prover: &P,
+    trace: P::Trace,
+    expected: &<P::Air as Air>::PublicInputs,
+) -> Result<StarkProof, ProverError>
+where
+    <P::Air as Air>::PublicInputs: Debug + PartialEq,
+{
+    let (proof, pub_inputs) = prover.prove_returning_inputs(trace)?;
+    check_pub_inputs(expected, &pub_inputs);
+    Ok(proof)
+}
+
+/// Panics if the public inputs derived by a prover differ from the public inputs the caller
+/// expects the proof to be verified against.
+pub fn check_pub_inputs<P: Debug + PartialEq>(expected: &P, derived: &P) {
+    assert!(
+        expected == derived,
+        "prover derived public inputs {:?} which differ from the expected public inputs {:?}",
+        derived,
+        expected
+    );
+}
+
+/// Logs the facts authenticated by a verified proof.
+pub fn log_verified_statement<A: Air>(statement: &VerifiedStatement<A>) {
+    let trace_info = statement.trace_info();
+    debug!(
+        "Verified a proof for an execution trace of {} steps and {} columns with {} bits of conjectured and {} bits of proven security",
+        trace_info.length(),
+        trace_info.width(),
+        statement.conjectured_security_level(),
+        statement.proven_security_level()
+    );
+}
+
+/// Checks the main segment of an execution trace for degenerate columns, and logs a warning for
+/// every such column (see [TraceStatistics::warn_on_degenerate()]).
+///
+/// Computing trace statistics requires a full pass over the trace, and thus, the check is
+/// performed only in debug builds.
+pub fn check_trace_statistics<T: Trace>(trace: &T, thresholds: &DegeneracyThresholds) {
+    if cfg!(debug_assertions) {
+        let stats = TraceStatistics::new(trace.main_segment(), DEFAULT_DISTINCT_VALUES_CAP);
+        if stats.warn_on_degenerate(thresholds).is_empty() {
+            debug!("Found no degenerate columns in the main segment of the execution trace");
+        }
+    }
+}
+
 /// Prints out an execution trace.
 pub fn print_trace<E: StarkField>(
     trace: &TraceTable<E>,
@@ -5,14 +5,14 @@
 
 use super::{BaseElement, FieldElement, ProofOptions, ALPHA, FORTY_TWO};
 use winterfell::{
-    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, Serializable, TraceInfo,
-    TransitionConstraintDegree,
+    Air, AirContext, Assertion, AssertionStep, ByteWriter, EvaluationFrame, Serializable,
+    TraceInfo, TransitionConstraintDegree,
 };
 
 // PUBLIC INPUTS
 // ================================================================================================
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct VdfInputs {
     pub seed: BaseElement,
     pub result: BaseElement,
@@ -66,7 +66,9 @@ impl Air for VdfAir {
     fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
         // the second boundary constraint is applied to the second to last step
         // as the last step will contain garbage
-        let second_to_last_step = self.trace_length() - 2;
+        let second_to_last_step = AssertionStep::last_minus(1)
+            .clamped_or_error(self.trace_length())
+            .expect("trace must contain at least two steps");
         vec![
             Assertion::single(0, 0, self.seed),
             Assertion::single(0, second_to_last_step, self.result),
@@ -3,14 +3,20 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::{Blake3_192, Blake3_256, Example, ExampleOptions, HashFunction, Sha3_256};
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
 use core::marker::PhantomData;
 use log::debug;
 use std::time::Instant;
 use winterfell::{
     crypto::ElementHasher,
     math::{fields::f128::BaseElement, log2, FieldElement},
-    ProofOptions, Prover, StarkProof, Trace, TraceTable, VerifierError,
+    Air, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo, TraceTable,
+    VerifierError,
 };
 
 mod air;
@@ -32,13 +38,22 @@ const FORTY_TWO: BaseElement = BaseElement::new(42);
 // VDF EXAMPLE
 // ================================================================================================
 
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 2,
+    target_security: 100,
+};
+
 pub fn get_example(options: &ExampleOptions, num_steps: usize) -> Result<Box<dyn Example>, String> {
-    let (options, hash_fn) = options.to_proof_options(85, 2);
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
 
     match hash_fn {
         HashFunction::Blake3_192 => Ok(Box::new(VdfExample::<Blake3_192>::new(num_steps, options))),
         HashFunction::Blake3_256 => Ok(Box::new(VdfExample::<Blake3_256>::new(num_steps, options))),
         HashFunction::Sha3_256 => Ok(Box::new(VdfExample::<Sha3_256>::new(num_steps, options))),
+        HashFunction::Keccak256 => Ok(Box::new(VdfExample::<Keccak_256>::new(num_steps, options))),
+        HashFunction::Blake2s256 => {
+            Ok(Box::new(VdfExample::<Blake2s_256>::new(num_steps, options)))
+        }
         _ => Err("The specified hash function cannot be used with this example.".to_string()),
     }
 }
@@ -76,6 +91,13 @@ impl<H: ElementHasher> VdfExample<H> {
             _hasher: PhantomData,
         }
     }
+
+    fn pub_inputs(&self) -> VdfInputs {
+        VdfInputs {
+            seed: self.seed,
+            result: self.result,
+        }
+    }
 }
 
 // EXAMPLE IMPLEMENTATION
@@ -107,17 +129,15 @@ where
             log2(trace_length),
             now.elapsed().as_millis()
         );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
 
-        // generate the proof
-        prover.prove(trace).unwrap()
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
-        let pub_inputs = VdfInputs {
-            seed: self.seed,
-            result: self.result,
-        };
-        winterfell::verify::<VdfAir, H>(proof, pub_inputs)
+        let statement = winterfell::verify_and_extract::<VdfAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
     }
 
     fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -127,6 +147,13 @@ where
         };
         winterfell::verify::<VdfAir, H>(proof, pub_inputs)
     }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        // the trace contains an extra row which is exempt from transition constraints
+        let trace_info = TraceInfo::new(1, self.num_steps + 1);
+        let air = VdfAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
 }
 
 // VDF FUNCTION
@@ -3,8 +3,14 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::Blake3_256;
-use winterfell::{FieldExtension, ProofOptions};
+use super::{
+    air::{VdfAir, VdfInputs},
+    BaseElement, Blake3_256, FieldElement, Prover, Trace, TraceTable, VdfProver, VerifierError,
+    FORTY_TWO, INV_ALPHA,
+};
+use winterfell::{
+    Air, AirContext, Assertion, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo,
+};
 
 #[test]
 fn vdf_test_basic_proof_verification() {
@@ -33,6 +39,122 @@ fn vdf_test_basic_proof_verification_fail() {
     crate::tests::test_basic_proof_verification_fail(fib);
 }
 
+#[test]
+fn vdf_test_exemption_mismatch() {
+    // a proof generated against an AIR which exempts the last two rows from transition
+    // constraints is rejected by a verifier whose AIR exempts only the last row
+    let trace = build_trace();
+    let pub_inputs = get_pub_inputs(&trace);
+    let prover = VdfProver::<Blake3_256>::new(build_options(false));
+    let proof = prover.prove(trace).unwrap();
+    assert_eq!(
+        Err(VerifierError::ExemptionMismatch {
+            proof: vec![2],
+            air: vec![1]
+        }),
+        winterfell::verify::<NarrowVdfAir, Blake3_256>(proof, pub_inputs)
+    );
+
+    // and the other way around: a proof generated against an AIR with a narrower exemption
+    // window for a trace which satisfies constraints of both AIRs is rejected as well
+    let trace = build_valid_trace();
+    let pub_inputs = get_pub_inputs(&trace);
+    let prover = NarrowVdfProver {
+        options: build_options(false),
+    };
+    let proof = prover.prove(trace).unwrap();
+    assert!(
+        winterfell::verify::<NarrowVdfAir, Blake3_256>(proof.clone(), pub_inputs.clone()).is_ok()
+    );
+    assert_eq!(
+        Err(VerifierError::ExemptionMismatch {
+            proof: vec![1],
+            air: vec![2]
+        }),
+        winterfell::verify::<VdfAir, Blake3_256>(proof, pub_inputs)
+    );
+}
+
+// NARROW EXEMPTION VDF
+// ================================================================================================
+
+/// Same as [VdfAir], but exempts only the last row of the trace from transition constraints.
+struct NarrowVdfAir {
+    inner: VdfAir,
+    context: AirContext<BaseElement>,
+}
+
+impl Air for NarrowVdfAir {
+    type BaseField = BaseElement;
+    type PublicInputs = VdfInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: VdfInputs, options: ProofOptions) -> Self {
+        let inner = VdfAir::new(trace_info, pub_inputs, options);
+        let context = inner.context().clone().set_num_transition_exemptions(1);
+        Self { inner, context }
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        self.inner
+            .evaluate_transition(frame, periodic_values, result)
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        self.inner.get_assertions()
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+struct NarrowVdfProver {
+    options: ProofOptions,
+}
+
+impl Prover for NarrowVdfProver {
+    type BaseField = BaseElement;
+    type Air = NarrowVdfAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> VdfInputs {
+        get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_trace() -> TraceTable<BaseElement> {
+    VdfProver::<Blake3_256>::build_trace(BaseElement::new(123), 128)
+}
+
+/// Builds a trace in which the last row is a valid VDF state rather than garbage.
+fn build_valid_trace() -> TraceTable<BaseElement> {
+    let mut column = vec![BaseElement::new(123)];
+    for i in 0..127 {
+        column.push((column[i] - FORTY_TWO).exp(INV_ALPHA));
+    }
+    TraceTable::init(vec![column])
+}
+
+fn get_pub_inputs(trace: &TraceTable<BaseElement>) -> VdfInputs {
+    VdfInputs {
+        seed: trace.get(0, 0),
+        result: trace.get(0, trace.length() - 2),
+    }
+}
+
 fn build_options(use_extension_field: bool) -> ProofOptions {
     let extension = if use_extension_field {
         FieldExtension::Quadratic
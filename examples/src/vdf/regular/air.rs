@@ -12,7 +12,7 @@ use winterfell::{
 // PUBLIC INPUTS
 // ================================================================================================
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct VdfInputs {
     pub seed: BaseElement,
     pub result: BaseElement,
@@ -0,0 +1,99 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{selectors, BaseElement, FieldElement, ProofOptions, SelectorSet, TRACE_WIDTH};
+use crate::utils::are_equal;
+use winterfell::{
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, Serializable, TraceInfo,
+    TransitionConstraintDegree,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicInputs {
+    pub initial: BaseElement,
+    pub result: BaseElement,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.initial);
+        target.write(self.result);
+    }
+}
+
+// VM AIR
+// ================================================================================================
+
+pub struct VmAir {
+    context: AirContext<BaseElement>,
+    selectors: SelectorSet,
+    initial: BaseElement,
+    result: BaseElement,
+}
+
+impl Air for VmAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        assert_eq!(TRACE_WIDTH, trace_info.width());
+
+        // the selector set defines constraints for the flags; the accumulator transition is
+        // described by a single constraint combining constraints of all operations gated on their
+        // flags. since the flags are mutually exclusive, only the constraint of the operation
+        // executed in a given row is enforced; the degree of the combined constraint is the
+        // highest degree among the gated constraints (the one for MUL).
+        let selectors = selectors();
+        let mut degrees = selectors.constraint_degrees();
+        degrees.push(selectors.gate_degree(&TransitionConstraintDegree::new(2)));
+
+        VmAir {
+            context: AirContext::new(trace_info, degrees, 2, options),
+            selectors,
+            initial: pub_inputs.initial,
+            result: pub_inputs.result,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        // expected state width is 5 field elements
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+        debug_assert_eq!(TRACE_WIDTH, next.len());
+
+        // enforce that exactly one operation flag is set
+        self.selectors.evaluate_constraints(current, result);
+
+        // enforce the accumulator transition of the selected operation
+        let (acc, operand) = (current[0], current[1]);
+        let s = &self.selectors;
+        result[s.num_constraints()] = s.gate(current, 0, are_equal(next[0], acc + operand))
+            + s.gate(current, 1, are_equal(next[0], acc * operand))
+            + s.gate(current, 2, are_equal(next[0], acc));
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.initial),
+            Assertion::single(0, last_step, self.result),
+        ]
+    }
+}
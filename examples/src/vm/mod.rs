@@ -0,0 +1,233 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::{
+    explain::ParamsReport,
+    utils::{check_trace_statistics, log_verified_statement, prove_checked},
+    Blake2s_256, Blake3_192, Blake3_256, Example, ExampleDefaults, ExampleOptions, FieldType,
+    HashFunction, Keccak_256, Sha3_256,
+};
+use core::marker::PhantomData;
+use log::debug;
+use std::time::Instant;
+use winterfell::{
+    crypto::ElementHasher,
+    math::{fields::f128::BaseElement, log2, FieldElement},
+    selectors::SelectorSet,
+    Air, AirDescription, DegeneracyThresholds, ProofOptions, Prover, StarkProof, Trace, TraceInfo,
+    TraceTable, VerifierError,
+};
+
+mod air;
+use air::{PublicInputs, VmAir};
+
+mod prover;
+use prover::VmProver;
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// The trace consists of an accumulator column, an operand column, and one flag column per
+/// operation.
+const TRACE_WIDTH: usize = 2 + NUM_OPS;
+
+const NUM_OPS: usize = 3;
+const FLAG_COLUMNS: core::ops::Range<usize> = 2..2 + NUM_OPS;
+
+// OPERATIONS
+// ================================================================================================
+
+/// Operations supported by the VM; each operation updates the accumulator using the operand
+/// of the current row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// acc' = acc + operand
+    Add = 0,
+    /// acc' = acc * operand
+    Mul = 1,
+    /// acc' = acc
+    Noop = 2,
+}
+
+impl Op {
+    /// Returns the index of the selector flag of this operation.
+    pub fn flag_idx(&self) -> usize {
+        *self as usize
+    }
+
+    /// Returns the result of applying this operation to the provided accumulator value.
+    pub fn apply(&self, acc: BaseElement, operand: BaseElement) -> BaseElement {
+        match self {
+            Op::Add => acc + operand,
+            Op::Mul => acc * operand,
+            Op::Noop => acc,
+        }
+    }
+}
+
+/// Returns the selector set describing operation flags of the VM trace.
+fn selectors() -> SelectorSet {
+    SelectorSet::new(FLAG_COLUMNS)
+}
+
+// VM EXAMPLE
+// ================================================================================================
+
+pub const DEFAULTS: ExampleDefaults = ExampleDefaults {
+    blowup_factor: 8,
+    target_security: 100,
+};
+
+pub fn get_example(options: &ExampleOptions, num_steps: usize) -> Result<Box<dyn Example>, String> {
+    let (options, hash_fn) = options.to_proof_options(FieldType::F128, &DEFAULTS);
+
+    match hash_fn {
+        HashFunction::Blake3_192 => Ok(Box::new(VmExample::<Blake3_192>::new(num_steps, options))),
+        HashFunction::Blake3_256 => Ok(Box::new(VmExample::<Blake3_256>::new(num_steps, options))),
+        HashFunction::Sha3_256 => Ok(Box::new(VmExample::<Sha3_256>::new(num_steps, options))),
+        HashFunction::Keccak256 => Ok(Box::new(VmExample::<Keccak_256>::new(num_steps, options))),
+        HashFunction::Blake2s256 => Ok(Box::new(VmExample::<Blake2s_256>::new(num_steps, options))),
+        _ => Err("The specified hash function cannot be used with this example.".to_string()),
+    }
+}
+
+/// Executes a program of a simple accumulator VM.
+///
+/// Every step of the program executes one of the operations described by [Op]; the operation
+/// is selected by a set of mutually exclusive flags in the trace. Constraints of individual
+/// operations are gated on their flags via a [SelectorSet], and thus, each row of the trace is
+/// constrained only by the operation it executes.
+///
+/// The program itself is not a part of the public inputs: the proof shows that there exists
+/// a valid sequence of operations which transforms the initial accumulator value into the
+/// result.
+pub struct VmExample<H: ElementHasher> {
+    options: ProofOptions,
+    program: Vec<(Op, BaseElement)>,
+    initial: BaseElement,
+    result: BaseElement,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> VmExample<H> {
+    pub fn new(num_steps: usize, options: ProofOptions) -> Self {
+        assert!(
+            num_steps.is_power_of_two(),
+            "number of steps must be a power of 2"
+        );
+        assert!(num_steps >= 8, "number of steps must be at least 8");
+
+        // build a program which cycles through all operations; the last step of the trace is
+        // not constrained by transition constraints, and thus, the program executes one less
+        // operation than the number of steps
+        let program = (0..num_steps - 1)
+            .map(|i| {
+                let op = [Op::Add, Op::Mul, Op::Noop][i % NUM_OPS];
+                (op, BaseElement::new(i as u128 + 1))
+            })
+            .collect::<Vec<_>>();
+
+        // execute the program
+        let now = Instant::now();
+        let initial = BaseElement::new(3);
+        let result = execute(&program, initial);
+        debug!(
+            "Executed a program of {} operations in {} ms",
+            program.len(),
+            now.elapsed().as_millis()
+        );
+
+        VmExample {
+            options,
+            program,
+            initial,
+            result,
+            _hasher: PhantomData,
+        }
+    }
+
+    fn pub_inputs(&self) -> PublicInputs {
+        PublicInputs {
+            initial: self.initial,
+            result: self.result,
+        }
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl<H: ElementHasher> Example for VmExample<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for executing a VM program of {} operations\n\
+            ---------------------",
+            self.program.len()
+        );
+
+        // create a prover
+        let prover = VmProver::<H>::new(self.options.clone());
+
+        // generate execution trace and make sure selector flags in it are valid
+        let now = Instant::now();
+        let trace = prover.build_trace(&self.program, self.initial);
+        prover
+            .validate_trace(&trace)
+            .expect("execution trace contains invalid selector flags");
+
+        let trace_width = trace.width();
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace_width,
+            log2(trace_length),
+            now.elapsed().as_millis()
+        );
+        check_trace_statistics(&trace, &DegeneracyThresholds::new());
+
+        prove_checked(&prover, trace, &self.pub_inputs()).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let statement = winterfell::verify_and_extract::<VmAir, H>(proof, self.pub_inputs())?;
+        log_verified_statement(&statement);
+        Ok(())
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let pub_inputs = PublicInputs {
+            initial: self.initial,
+            result: self.result + BaseElement::ONE,
+        };
+        winterfell::verify::<VmAir, H>(proof, pub_inputs)
+    }
+
+    fn describe(&self) -> Option<AirDescription> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.program.len() + 1);
+        Some(VmAir::new(trace_info, self.pub_inputs(), self.options.clone()).describe())
+    }
+
+    fn explain_params(&self) -> Option<ParamsReport> {
+        let trace_info = TraceInfo::new(TRACE_WIDTH, self.program.len() + 1);
+        let air = VmAir::new(trace_info, self.pub_inputs(), self.options.clone());
+        Some(ParamsReport::new::<_, H>(&air))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the value of the accumulator after executing the provided program.
+fn execute(program: &[(Op, BaseElement)], initial: BaseElement) -> BaseElement {
+    program
+        .iter()
+        .fold(initial, |acc, (op, operand)| op.apply(acc, *operand))
+}
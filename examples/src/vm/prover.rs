@@ -0,0 +1,101 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    selectors, BaseElement, ElementHasher, FieldElement, Op, PhantomData, ProofOptions, Prover,
+    PublicInputs, Trace, TraceTable, VmAir, TRACE_WIDTH,
+};
+use winterfell::SelectorError;
+
+// VM PROVER
+// ================================================================================================
+
+pub struct VmProver<H: ElementHasher> {
+    options: ProofOptions,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ElementHasher> VmProver<H> {
+    pub fn new(options: ProofOptions) -> Self {
+        Self {
+            options,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Builds an execution trace for the provided program starting with the specified value of
+    /// the accumulator.
+    ///
+    /// The trace is one row longer than the program; the last row executes a NOOP operation.
+    pub fn build_trace(
+        &self,
+        program: &[(Op, BaseElement)],
+        initial: BaseElement,
+    ) -> TraceTable<BaseElement> {
+        let trace_length = program.len() + 1;
+        assert!(
+            trace_length.is_power_of_two(),
+            "program length must be one less than a power of 2"
+        );
+
+        let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
+        trace.fill(
+            |state| {
+                state[0] = initial;
+                write_op(state, program[0]);
+            },
+            |step, state| {
+                let (op, operand) = program[step];
+                state[0] = op.apply(state[0], operand);
+                let next_op = program.get(step + 1).copied();
+                write_op(state, next_op.unwrap_or((Op::Noop, BaseElement::ZERO)));
+            },
+        );
+
+        trace
+    }
+
+    /// Checks that exactly one operation flag is set in every row of the provided trace.
+    pub fn validate_trace(&self, trace: &TraceTable<BaseElement>) -> Result<(), SelectorError> {
+        selectors().validate_rows(trace.length(), |step, row| trace.read_row_into(step, row))
+    }
+}
+
+impl<H: ElementHasher> Prover for VmProver<H>
+where
+    H: ElementHasher<BaseField = BaseElement>,
+{
+    type BaseField = BaseElement;
+    type Air = VmAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = H;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let last_step = trace.length() - 1;
+        PublicInputs {
+            initial: trace.get(0, 0),
+            result: trace.get(0, last_step),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Writes the operand and the operation flags of the specified operation into the state.
+fn write_op(state: &mut [BaseElement], (op, operand): (Op, BaseElement)) {
+    state[1] = operand;
+    for (i, flag) in state[2..].iter_mut().enumerate() {
+        *flag = if i == op.flag_idx() {
+            BaseElement::ONE
+        } else {
+            BaseElement::ZERO
+        };
+    }
+}
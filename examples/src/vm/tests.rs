@@ -0,0 +1,81 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, Blake3_256, FieldElement, Op, PublicInputs, VmAir, VmExample, VmProver};
+use winterfell::{FieldExtension, ProofOptions, Prover, SelectorError, Trace};
+
+#[test]
+fn vm_test_basic_proof_verification() {
+    let vm = Box::new(VmExample::<Blake3_256>::new(16, build_options(false)));
+    crate::tests::test_basic_proof_verification(vm);
+}
+
+#[test]
+fn vm_test_basic_proof_verification_extension() {
+    let vm = Box::new(VmExample::<Blake3_256>::new(16, build_options(true)));
+    crate::tests::test_basic_proof_verification(vm);
+}
+
+#[test]
+fn vm_test_basic_proof_verification_fail() {
+    let vm = Box::new(VmExample::<Blake3_256>::new(16, build_options(false)));
+    crate::tests::test_basic_proof_verification_fail(vm);
+}
+
+#[test]
+fn vm_test_multiple_flags_caught_by_prover() {
+    let vm = VmExample::<Blake3_256>::new(16, build_options(false));
+    let prover = VmProver::<Blake3_256>::new(build_options(false));
+    let mut trace = prover.build_trace(&vm.program, vm.initial);
+    assert_eq!(Ok(()), prover.validate_trace(&trace));
+
+    // step 3 executes an ADD operation; setting the MUL flag as well must be caught
+    assert_eq!(Op::Add, vm.program[3].0);
+    trace.set(2 + Op::Mul.flag_idx(), 3, BaseElement::ONE);
+    assert_eq!(
+        Err(SelectorError::MultipleFlagsSet(3, 0, 1)),
+        prover.validate_trace(&trace)
+    );
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "main transition constraint 3 did not evaluate to ZERO at step 3")
+)]
+fn vm_test_multiple_flags_rejected_by_verifier() {
+    let vm = VmExample::<Blake3_256>::new(16, build_options(false));
+    let prover = VmProver::<Blake3_256>::new(build_options(false));
+    let mut trace = prover.build_trace(&vm.program, vm.initial);
+
+    // with both ADD and NOOP flags set at step 3 and the operand set to zero, the gated
+    // constraints of both operations are satisfied, but the one-hot constraint is not. in debug
+    // builds, the prover catches this when validating the trace against the AIR.
+    assert_eq!(Op::Add, vm.program[3].0);
+    trace.set(1, 3, BaseElement::ZERO);
+    trace.set(2 + Op::Noop.flag_idx(), 3, BaseElement::ONE);
+    for step in 4..trace.length() {
+        let acc = vm.program[step - 1]
+            .0
+            .apply(trace.get(0, step - 1), trace.get(1, step - 1));
+        trace.set(0, step, acc);
+    }
+
+    let pub_inputs = PublicInputs {
+        initial: vm.initial,
+        result: trace.get(0, trace.length() - 1),
+    };
+    let proof = prover.prove(trace).unwrap();
+    assert!(winterfell::verify::<VmAir, Blake3_256>(proof, pub_inputs).is_err());
+}
+
+fn build_options(use_extension_field: bool) -> ProofOptions {
+    let extension = if use_extension_field {
+        FieldExtension::Quadratic
+    } else {
+        FieldExtension::None
+    };
+    ProofOptions::new(28, 8, 0, extension, 4, 256)
+}
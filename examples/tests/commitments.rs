@@ -0,0 +1,400 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Exercises the ways in which the prover commits to the trace, the constraint evaluations, and
+//! the FRI remainder: combined trace columns, remainder digests, raw leaf encoding, salted
+//! commitments, Merkle trees of higher arity, and custom vector commitment schemes.
+
+#![cfg(feature = "f128")]
+
+mod common;
+
+use common::{check_fib_proof, prove_fib, verify_fib};
+use examples::{
+    fibonacci::{
+        fib2::{FibAir, FibProver},
+        utils::{build_proof_options, compute_fib_term},
+    },
+    Blake3_192, Blake3_256,
+};
+use winterfell::{
+    checkpoint::Checkpoint,
+    crypto::{
+        hashers::Poseidon128_256, BatchMerkleProof, ElementHasher, Hasher, MerkleTree,
+        MerkleTreeError, VectorCommitment,
+    },
+    math::{fft, fields::f128::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, LeafEncoding, OodFrame, Prover,
+    ProverError, Queries, Serializable, SliceReader, StarkProof, VerifierError,
+};
+
+// COMBINED TRACE COLUMN
+// ================================================================================================
+
+#[test]
+fn fib2_combined_trace_column() {
+    check_fib_proof::<Blake3_256>(16, build_proof_options(false).with_combined_trace_column());
+    let options = build_proof_options(true)
+        .with_combined_trace_column()
+        .with_num_ood_points(2);
+    check_fib_proof::<Blake3_256>(16, options);
+}
+
+#[test]
+fn fib2_combined_trace_column_tampered() {
+    let options = build_proof_options(false).with_combined_trace_column();
+    let (proof, result) = prove_fib::<Blake3_256>(16, options);
+
+    // the combined trace column is committed to after the trace segment, and is queried at the
+    // same positions
+    assert_eq!(2, proof.trace_queries.len());
+    assert_eq!(
+        proof.trace_queries.len() + 1 + 1 + proof.fri_proof.num_layers(),
+        proof.commitments.clone().to_bytes().len() / 32
+    );
+
+    // change the out-of-domain evaluation of the combined trace column; this evaluation is
+    // serialized at the very end of the out-of-domain frame
+    let mut tampered_proof = proof.clone();
+    let mut ood_frame_bytes = proof.ood_frame.to_bytes();
+    let evaluation_offset = ood_frame_bytes.len() - BaseElement::ELEMENT_BYTES;
+    ood_frame_bytes[evaluation_offset] ^= 1;
+    tampered_proof.ood_frame =
+        OodFrame::read_from(&mut SliceReader::new(&ood_frame_bytes)).unwrap();
+    assert_eq!(
+        Err(VerifierError::CombinedTraceColumnMismatch),
+        verify_fib::<Blake3_256>(tampered_proof, result)
+    );
+
+    // change a queried value of the combined trace column; the value no longer matches the
+    // combined trace column commitment
+    let mut tampered_proof = proof;
+    let mut query_bytes = tampered_proof.trace_queries[1].to_bytes();
+    query_bytes[4] ^= 1;
+    tampered_proof.trace_queries[1] =
+        Queries::read_from(&mut SliceReader::new(&query_bytes)).unwrap();
+    assert_eq!(
+        Err(VerifierError::TraceQueryDoesNotMatchCommitment),
+        verify_fib::<Blake3_256>(tampered_proof, result)
+    );
+}
+
+// FRI REMAINDER DIGEST
+// ================================================================================================
+
+#[test]
+fn fib2_fri_remainder_digest() {
+    for use_extension_field in [false, true] {
+        let options = build_proof_options(use_extension_field).with_fri_remainder_digest();
+        check_fib_proof::<Blake3_256>(16, options);
+    }
+}
+
+#[test]
+fn fib2_fri_remainder_digest_tampered() {
+    let options = build_proof_options(false).with_fri_remainder_digest();
+    let (proof, result) = prove_fib::<Blake3_256>(1024, options);
+
+    // the remainder digest is appended to the FRI layer commitments
+    let commitment_bytes = proof.commitments.clone().to_bytes();
+    assert_eq!(
+        proof.trace_queries.len() + 1 + proof.fri_proof.num_layers() + 1 + 1,
+        commitment_bytes.len() / 32
+    );
+
+    // change the last remainder value while leaving FRI layers untouched; the remainder is
+    // serialized right before the number of partitions at the very end of the FRI proof
+    let mut fri_proof_bytes = proof.fri_proof.to_bytes();
+    let remainder_offset = fri_proof_bytes.len() - 1 - BaseElement::ELEMENT_BYTES;
+    fri_proof_bytes[remainder_offset] ^= 1;
+    let mut tampered_proof = proof.clone();
+    tampered_proof.fri_proof =
+        Deserializable::read_from(&mut SliceReader::new(&fri_proof_bytes)).unwrap();
+    assert_eq!(
+        proof.fri_proof.num_layers(),
+        tampered_proof.fri_proof.num_layers()
+    );
+    assert_eq!(
+        Err(VerifierError::FriRemainderDigestMismatch),
+        verify_fib::<Blake3_256>(tampered_proof.clone(), result)
+    );
+
+    // replacing the digest with the digest of the modified remainder does not help either, as
+    // the query positions are no longer the ones the proof was built for
+    let remainder = tampered_proof
+        .fri_proof
+        .parse_remainder::<BaseElement>()
+        .unwrap();
+    let mut coefficients = remainder;
+    let inv_twiddles = fft::get_inv_twiddles::<BaseElement>(coefficients.len());
+    fft::interpolate_poly(&mut coefficients, &inv_twiddles);
+    let digest = Blake3_256::hash_elements(&coefficients);
+    let digest_offset = commitment_bytes.len() - 32;
+    let mut tampered_commitments = commitment_bytes;
+    tampered_commitments[digest_offset..].copy_from_slice(&digest.to_bytes());
+    tampered_proof.commitments =
+        Deserializable::read_from(&mut SliceReader::new(&tampered_commitments)).unwrap();
+    assert!(verify_fib::<Blake3_256>(tampered_proof, result).is_err());
+}
+
+// RAW LEAF ENCODING
+// ================================================================================================
+
+#[test]
+fn fib2_raw_leaf_encoding() {
+    // rows of the trace consist of two 128-bit elements, and thus, fill a 256-bit digest exactly
+    let options = build_proof_options(false).with_leaf_encoding(LeafEncoding::Raw);
+    check_fib_proof::<Blake3_256>(16, options);
+
+    let options = build_proof_options(false)
+        .with_combined_trace_column()
+        .with_leaf_encoding(LeafEncoding::Raw);
+    check_fib_proof::<Blake3_256>(16, options);
+}
+
+#[test]
+fn fib2_raw_leaf_encoding_unsupported() {
+    // rows of 32 bytes do not fit into a 192-bit digest
+    let options = build_proof_options(false).with_leaf_encoding(LeafEncoding::Raw);
+    let prover = FibProver::<Blake3_192>::new(options);
+    let trace = prover.build_trace(16);
+    assert_eq!(
+        Err(ProverError::UnsupportedLeafEncoding(32)),
+        prover.prove(trace).map(|_| ())
+    );
+}
+
+// SALTED COMMITMENTS
+// ================================================================================================
+
+#[test]
+fn fib2_salted_commitments() {
+    check_fib_proof::<Blake3_256>(16, build_proof_options(false).with_zk());
+
+    let options = build_proof_options(true)
+        .with_combined_trace_column()
+        .with_merkle_arity(4)
+        .with_zk();
+    check_fib_proof::<Blake3_256>(16, options);
+}
+
+#[test]
+fn fib2_salted_commitments_unsalted_verifier() {
+    let options = build_proof_options(false).with_zk();
+    let (proof, result) = prove_fib::<Blake3_256>(16, options.clone());
+
+    // salts are drawn anew for every proof, and thus, proofs of the same trace differ
+    let (other_proof, _) = prove_fib::<Blake3_256>(16, options);
+    assert_ne!(proof.commitments, other_proof.commitments);
+
+    // salted proofs are accepted by the default verifier, but not by the unsalted one
+    assert_eq!(Ok(()), verify_fib::<Blake3_256>(proof.clone(), result));
+    assert_eq!(
+        Err(VerifierError::UnsupportedSaltedCommitments),
+        winterfell::verify_unsalted::<FibAir<BaseElement>, Blake3_256>(proof.clone(), result)
+    );
+
+    // a proof with unsalted commitments is accepted by both verifiers
+    let (unsalted_proof, _) = prove_fib::<Blake3_256>(16, build_proof_options(false));
+    assert_eq!(
+        Ok(()),
+        winterfell::verify_unsalted::<FibAir<BaseElement>, Blake3_256>(unsalted_proof, result)
+    );
+
+    // change the salt of the first queried trace row; salts are stored in front of the Merkle
+    // authentication paths
+    let mut tampered_proof = proof;
+    let mut query_bytes = tampered_proof.trace_queries[0].to_bytes();
+    let num_value_bytes = u32::from_le_bytes(query_bytes[..4].try_into().unwrap()) as usize;
+    query_bytes[4 + num_value_bytes + 4] ^= 1;
+    tampered_proof.trace_queries[0] =
+        Queries::read_from(&mut SliceReader::new(&query_bytes)).unwrap();
+    assert_eq!(
+        Err(VerifierError::TraceQueryDoesNotMatchCommitment),
+        verify_fib::<Blake3_256>(tampered_proof, result)
+    );
+}
+
+#[test]
+fn fib2_salted_commitments_checkpoint_resume() {
+    // salts are derived from a seed stored in checkpoints, and thus, resumed proofs are the same
+    // as the proof generated without interruptions even though salts are random
+    let options = build_proof_options(false).with_zk();
+    let prover = FibProver::<Blake3_256>::new(options);
+    let result = compute_fib_term(64);
+
+    let mut checkpoints = Vec::new();
+    let proof = prover
+        .prove_with_checkpoints(prover.build_trace(64), &mut checkpoints)
+        .unwrap();
+    for checkpoint in checkpoints.iter() {
+        let mut checkpoint = Checkpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+        let resumed_proof = prover.resume_proof(result, &mut checkpoint).unwrap();
+        assert_eq!(proof.to_bytes(), resumed_proof.to_bytes());
+    }
+    assert_eq!(Ok(()), verify_fib::<Blake3_256>(proof, result));
+}
+
+// MERKLE TREE ARITY
+// ================================================================================================
+
+#[test]
+fn fib2_merkle_arity() {
+    for arity in [4, 8] {
+        let options = build_proof_options(false).with_merkle_arity(arity);
+        check_fib_proof::<Blake3_256>(64, options.clone());
+
+        // algebraic hash functions merge the children of a node as a sequence of elements
+        check_fib_proof::<Poseidon128_256>(64, options.with_combined_trace_column());
+    }
+
+    // proofs for trees with arity greater than 2 survive a serialization round trip
+    let options = build_proof_options(true).with_merkle_arity(8);
+    let (proof, result) = prove_fib::<Blake3_256>(64, options);
+    assert_eq!(8, proof.options().merkle_arity());
+    let proof = StarkProof::from_bytes(&proof.to_bytes()).unwrap();
+    assert_eq!(Ok(()), verify_fib::<Blake3_256>(proof, result));
+}
+
+// VECTOR COMMITMENT SCHEMES
+// ================================================================================================
+
+#[test]
+fn fib2_vector_commitment() {
+    let result = compute_fib_term(32);
+    let verify_tagged = |proof| {
+        winterfell::verify_with_commitment::<FibAir<BaseElement>, Blake3_256, TaggedMerkleTree>(
+            proof, result,
+        )
+    };
+
+    let option_sets = [
+        build_proof_options(false),
+        build_proof_options(true).with_merkle_arity(4),
+        build_proof_options(false)
+            .with_zk()
+            .with_combined_trace_column(),
+    ];
+    for options in option_sets {
+        let prover = FibProver::<Blake3_256>::new(options);
+        let proof = prover
+            .prove_with_commitment::<TaggedMerkleTree>(prover.build_trace(32))
+            .unwrap();
+        assert_eq!(
+            TaggedMerkleTree::SCHEME_ID,
+            proof.context.commitment_scheme()
+        );
+
+        // the proof survives a serialization round trip, and is verified with the same scheme
+        let proof = StarkProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(Ok(()), verify_tagged(proof.clone()));
+
+        // the default verifier checks queries against Merkle tree roots, and thus, rejects it
+        assert_eq!(
+            Err(VerifierError::CommitmentSchemeMismatch {
+                proof: TaggedMerkleTree::SCHEME_ID,
+                verifier: 0
+            }),
+            verify_fib::<Blake3_256>(proof, result)
+        );
+
+        // and a proof committed to via Merkle trees is rejected by the verifier of the scheme
+        let proof = prover.prove(prover.build_trace(32)).unwrap();
+        assert_eq!(
+            Err(VerifierError::CommitmentSchemeMismatch {
+                proof: 0,
+                verifier: TaggedMerkleTree::SCHEME_ID
+            }),
+            verify_tagged(proof)
+        );
+    }
+
+    // committing via Merkle trees explicitly results in the same proof as the default prover
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let proof = prover.prove(prover.build_trace(32)).unwrap();
+    let merkle_proof = prover
+        .prove_with_commitment::<MerkleTree<Blake3_256>>(prover.build_trace(32))
+        .unwrap();
+    assert_eq!(proof.to_bytes(), merkle_proof.to_bytes());
+}
+
+/// Vector commitment scheme which commits to the root of a Merkle tree hashed together with a
+/// domain separation tag; the commitment is different from the root of the tree, and thus,
+/// queries of proofs committed to via this scheme cannot be verified against Merkle tree roots.
+#[derive(Clone)]
+struct TaggedMerkleTree(MerkleTree<Blake3_256>);
+
+impl TaggedMerkleTree {
+    fn tag(root: <Blake3_256 as Hasher>::Digest) -> <Blake3_256 as Hasher>::Digest {
+        Blake3_256::merge(&[root, Blake3_256::hash(b"tagged merkle tree")])
+    }
+}
+
+impl VectorCommitment<Blake3_256> for TaggedMerkleTree {
+    const SCHEME_ID: u8 = 7;
+    type MultiProof = BatchMerkleProof<Blake3_256>;
+    type Error = MerkleTreeError;
+
+    fn with_arity(
+        items: Vec<<Blake3_256 as Hasher>::Digest>,
+        arity: usize,
+    ) -> Result<Self, MerkleTreeError> {
+        MerkleTree::with_arity(items, arity).map(Self)
+    }
+
+    fn commitment(&self) -> <Blake3_256 as Hasher>::Digest {
+        Self::tag(*self.0.root())
+    }
+
+    fn domain_len(&self) -> usize {
+        self.0.leaves().len()
+    }
+
+    fn open_many(&self, indexes: &[usize]) -> Result<Self::MultiProof, MerkleTreeError> {
+        self.0.prove_batch(indexes)
+    }
+
+    fn verify_many(
+        commitment: &<Blake3_256 as Hasher>::Digest,
+        indexes: &[usize],
+        proof: &Self::MultiProof,
+    ) -> Result<(), MerkleTreeError> {
+        if Self::tag(proof.get_root(indexes)?) != *commitment {
+            return Err(MerkleTreeError::InvalidProof);
+        }
+        Ok(())
+    }
+
+    fn write_multiproof<W: ByteWriter>(
+        proof: &Self::MultiProof,
+        indexes: &[usize],
+        raw_item_bytes: Option<usize>,
+        target: &mut W,
+    ) {
+        MerkleTree::write_multiproof(proof, indexes, raw_item_bytes, target)
+    }
+
+    fn read_multiproof<R: ByteReader>(
+        source: &mut R,
+        items: Vec<<Blake3_256 as Hasher>::Digest>,
+        raw_item_bytes: Option<usize>,
+        domain_len: usize,
+        arity: usize,
+    ) -> Result<Self::MultiProof, DeserializationError> {
+        MerkleTree::read_multiproof(source, items, raw_item_bytes, domain_len, arity)
+    }
+}
+
+impl Serializable for TaggedMerkleTree {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.0.write_into(target)
+    }
+}
+
+impl Deserializable for TaggedMerkleTree {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        MerkleTree::read_from(source).map(Self)
+    }
+}
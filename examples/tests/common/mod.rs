@@ -0,0 +1,79 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Helpers shared by integration tests which exercise the prover and the verifier via proofs of
+//! the Fibonacci example (2 terms per step).
+
+#![allow(dead_code)]
+
+use examples::fibonacci::fib2::{FibAir, FibProver};
+use winterfell::{
+    crypto::ElementHasher,
+    math::{ExtensibleField, FieldElement},
+    BatchStarkProof, ProofOptions, Prover, StarkProof, VerifierError,
+};
+
+/// Returns a proof for a Fibonacci sequence of the specified length generated with hash
+/// function `H`, together with the public inputs (i.e., the last term of the sequence) against
+/// which the proof is verifiable.
+pub fn prove_fib<H>(sequence_length: usize, options: ProofOptions) -> (StarkProof, H::BaseField)
+where
+    H: ElementHasher + Sync,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    let prover = FibProver::<H>::new(options);
+    let trace = prover.build_trace(sequence_length);
+    prover.prove_returning_inputs(trace).unwrap()
+}
+
+/// Returns a batch of `num_proofs` proofs for a Fibonacci sequence of the specified length
+/// generated with hash function `H`, together with the public inputs shared by all proofs.
+pub fn prove_fib_batch<H>(
+    sequence_length: usize,
+    options: ProofOptions,
+    num_proofs: usize,
+) -> (BatchStarkProof, H::BaseField)
+where
+    H: ElementHasher + Sync,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    let prover = FibProver::<H>::new(options);
+    let traces = (0..num_proofs)
+        .map(|_| prover.build_trace(sequence_length))
+        .collect::<Vec<_>>();
+    let result = prover.get_pub_inputs(&traces[0]);
+    let proofs = winterfell::batch::prove_batch(&prover, traces)
+        .into_iter()
+        .map(|proof| proof.unwrap())
+        .collect();
+    (BatchStarkProof::new(proofs), result)
+}
+
+/// Verifies a proof for a Fibonacci sequence generated with hash function `H` against the
+/// specified result.
+pub fn verify_fib<H>(proof: StarkProof, result: H::BaseField) -> Result<(), VerifierError>
+where
+    H: ElementHasher,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    winterfell::verify::<FibAir<H::BaseField>, H>(proof, result)
+}
+
+/// Makes sure that a proof for a Fibonacci sequence of the specified length generated with the
+/// specified options verifies against the correct result, but not against a wrong one; returns
+/// the proof together with the correct result.
+pub fn check_fib_proof<H>(
+    sequence_length: usize,
+    options: ProofOptions,
+) -> (StarkProof, H::BaseField)
+where
+    H: ElementHasher + Sync,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3>,
+{
+    let (proof, result) = prove_fib::<H>(sequence_length, options);
+    assert_eq!(Ok(()), verify_fib::<H>(proof.clone(), result));
+    assert!(verify_fib::<H>(proof.clone(), result + H::BaseField::ONE).is_err());
+    (proof, result)
+}
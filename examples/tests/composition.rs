@@ -0,0 +1,358 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Exercises the ways in which constraints are composed and checked: multiple out-of-domain
+//! points, explicitly sent composition polynomials, keyed composition coefficients, and
+//! transition exemptions which differ between constraints.
+
+#![cfg(feature = "f128")]
+
+mod common;
+
+use common::{check_fib_proof, prove_fib, verify_fib};
+use examples::{
+    fibonacci::{
+        fib2::{FibAir, FibProver},
+        utils::{build_proof_options, compute_fib_term},
+    },
+    Blake3_256,
+};
+use winterfell::{
+    math::{fields::f128::BaseElement, FieldElement},
+    Air, AirContext, Assertion, CompositionCoefficients, ConstraintDescriptor, EvaluationFrame,
+    ProofOptions, Prover, ProverConfig, ProverError, TraceError, TraceInfo, TraceTable,
+    TransitionConstraintDegree, VerifierError,
+};
+
+// MULTIPLE OUT-OF-DOMAIN POINTS
+// ================================================================================================
+
+#[test]
+fn fib2_multiple_ood_points() {
+    for use_extension_field in [false, true] {
+        let options = build_proof_options(use_extension_field).with_num_ood_points(2);
+        check_fib_proof::<Blake3_256>(16, options);
+    }
+}
+
+#[test]
+fn fib2_multiple_ood_points_proof() {
+    let (single_point_proof, _) = prove_fib::<Blake3_256>(16, build_proof_options(false));
+
+    let options = build_proof_options(false).with_num_ood_points(2);
+    let (proof, result) = prove_fib::<Blake3_256>(16, options);
+
+    // the proof contains OOD evaluations and composition polynomial columns for both points;
+    // the second point raises the security of the DEEP/ALI step up to the security of the FRI
+    // commit phase, but the security level of the proof is still limited by the queries
+    assert!(proof.to_bytes().len() > single_point_proof.to_bytes().len());
+    let estimate = proof.security_estimate::<Blake3_256>(true);
+    let single_point_estimate = single_point_proof.security_estimate::<Blake3_256>(true);
+    assert_eq!(121, single_point_estimate.field_security());
+    assert_eq!(122, estimate.field_security());
+    assert_eq!(
+        estimate.security_level(),
+        single_point_estimate.security_level()
+    );
+
+    // a proof with 2 OOD points cannot be verified as a proof with a single OOD point
+    let mut proof = proof;
+    proof.context = single_point_proof.context.clone();
+    assert!(verify_fib::<Blake3_256>(proof, result).is_err());
+}
+
+// EXPLICIT COMPOSITION
+// ================================================================================================
+
+#[test]
+fn fib2_explicit_composition() {
+    for use_extension_field in [false, true] {
+        let options = build_proof_options(use_extension_field).with_explicit_composition(256);
+        check_fib_proof::<Blake3_256>(512, options);
+    }
+}
+
+#[test]
+fn fib2_explicit_composition_proof_size() {
+    // the number of coefficients sent explicitly grows linearly with trace length, while the size
+    // of constraint queries grows only logarithmically with it; with default options, explicit
+    // composition results in a smaller proof for a trace of 2^7 steps, but for traces of 2^8
+    // steps and above, the coefficients outweigh the Merkle authentication paths they replace.
+    let (explicit_size, committed_size) = get_proof_sizes(256, build_proof_options(false));
+    assert!(explicit_size < committed_size);
+
+    let (explicit_size, committed_size) = get_proof_sizes(512, build_proof_options(false));
+    assert!(explicit_size > committed_size);
+
+    let (explicit_size, committed_size) = get_proof_sizes(2048, build_proof_options(false));
+    assert!(explicit_size > committed_size);
+}
+
+#[test]
+fn fib2_explicit_composition_oversized_coefficients() {
+    let options = build_proof_options(false).with_explicit_composition(256);
+    let (mut proof, result) = prove_fib::<Blake3_256>(512, options);
+
+    // append an extra coefficient to the composition polynomial coefficients
+    let coefficients = proof.composition_coefficients.take().unwrap();
+    let num_columns = coefficients.num_elements::<BaseElement>() / 256;
+    let mut coefficients = coefficients
+        .parse::<BaseElement>(num_columns, 256)
+        .unwrap()
+        .concat();
+    coefficients.push(BaseElement::ONE);
+    proof.composition_coefficients = Some(CompositionCoefficients::new(&[&coefficients]));
+
+    let expected = 256 * num_columns;
+    assert_eq!(
+        Err(VerifierError::InvalidCompositionCoefficientCount(
+            expected,
+            expected + 1
+        )),
+        verify_fib::<Blake3_256>(proof, result)
+    );
+}
+
+// KEYED COMPOSITION COEFFICIENTS
+// ================================================================================================
+
+#[test]
+fn fib2_keyed_coefficients() {
+    let options = build_proof_options(false);
+    let prover = KeyedFibProver(FibProver::<Blake3_256>::new(options));
+    let result = compute_fib_term(32);
+    let proof = prover.prove(prover.0.build_trace(32)).unwrap();
+
+    // the verifier must derive coefficients the same way as the prover
+    let verified = winterfell::verify::<KeyedFibAir, Blake3_256>(proof.clone(), result);
+    assert!(verified.is_ok());
+    let verified = verify_fib::<Blake3_256>(proof, result);
+    assert!(verified.is_err());
+}
+
+// TRANSITION EXEMPTIONS
+// ================================================================================================
+
+#[test]
+fn fib2_transition_exemptions() {
+    let prover = ExemptFibProver(FibProver::<Blake3_256>::new(build_proof_options(false)));
+
+    // the second column of the last row does not affect the first constraint, and thus, only
+    // the second constraint needs to be exempt from the transition into the last row
+    let mut trace = prover.0.build_trace(32);
+    trace.set(1, 15, trace.get(1, 15) + BaseElement::ONE);
+    let result = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    assert!(winterfell::verify::<ExemptFibAir, Blake3_256>(proof.clone(), result).is_ok());
+    assert_eq!(
+        Err(VerifierError::ExemptionMismatch {
+            proof: vec![1, 2],
+            air: vec![1, 1]
+        }),
+        verify_fib::<Blake3_256>(proof, result)
+    );
+
+    // the first column of the last row is read by both constraints, but only the second
+    // constraint is exempt from the transition into the last row
+    let mut trace = prover.0.build_trace(32);
+    trace.set(0, 15, trace.get(0, 15) + BaseElement::ONE);
+    assert!(matches!(
+        prover.prove(trace),
+        Err(ProverError::InvalidTrace(
+            TraceError::UnsatisfiedTransitionConstraint {
+                aux: false,
+                index: 0,
+                step: 14
+            }
+        ))
+    ));
+}
+
+#[test]
+fn fib2_transition_exemptions_mismatch() {
+    // a trace which satisfies both constraints over all transitions is valid for AIRs with
+    // exemptions [1, 2] and [2, 1]; both AIRs exempt at most two rows, but a proof generated
+    // against one of them must not be accepted by a verifier instantiated with the other
+    let prover = ExemptFibProver(FibProver::<Blake3_256>::new(build_proof_options(false)));
+    let trace = prover.0.build_trace(32);
+    let result = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    assert_eq!(2, proof.context.num_transition_exemptions());
+    assert!(winterfell::verify::<ExemptFibAir, Blake3_256>(proof.clone(), result).is_ok());
+    assert_eq!(
+        Err(VerifierError::ExemptionMismatch {
+            proof: vec![1, 2],
+            air: vec![2, 1]
+        }),
+        winterfell::verify::<SwappedExemptFibAir, Blake3_256>(proof, result)
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns sizes of proofs for a Fibonacci sequence of the specified length generated with and
+/// without explicit composition using the specified options.
+fn get_proof_sizes(sequence_length: usize, options: ProofOptions) -> (usize, usize) {
+    let trace_length = sequence_length / 2;
+    let explicit_options = options.clone().with_explicit_composition(trace_length);
+    let (explicit_proof, result) = prove_fib::<Blake3_256>(sequence_length, explicit_options);
+    assert!(explicit_proof.composition_coefficients.is_some());
+    let explicit_size = explicit_proof.to_bytes().len();
+    assert!(verify_fib::<Blake3_256>(explicit_proof, result).is_ok());
+
+    let (committed_proof, _) = prove_fib::<Blake3_256>(sequence_length, options);
+    assert!(committed_proof.composition_coefficients.is_none());
+    (explicit_size, committed_proof.to_bytes().len())
+}
+
+/// Fibonacci AIR with constraints described by constraint descriptors and composition
+/// coefficients derived from identifiers of the constraints; constraints and assertions are
+/// the same as in [FibAir].
+struct KeyedFibAir {
+    context: AirContext<BaseElement>,
+    fib: FibAir<BaseElement>,
+}
+
+impl Air for KeyedFibAir {
+    type BaseField = BaseElement;
+    type PublicInputs = BaseElement;
+
+    fn new(trace_info: TraceInfo, pub_inputs: BaseElement, options: ProofOptions) -> Self {
+        let descriptors = vec![
+            ConstraintDescriptor::new("fib_first", TransitionConstraintDegree::new(1)),
+            ConstraintDescriptor::new("fib_second", TransitionConstraintDegree::new(1)),
+        ];
+        let context =
+            AirContext::new_with_descriptors(trace_info.clone(), descriptors, 3, options.clone())
+                .set_keyed_coefficients(true);
+        KeyedFibAir {
+            context,
+            fib: FibAir::new(trace_info, pub_inputs, options),
+        }
+    }
+
+    fn context(&self) -> &AirContext<BaseElement> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = BaseElement>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        self.fib.evaluate_transition(frame, periodic_values, result)
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<BaseElement>> {
+        self.fib.get_assertions()
+    }
+}
+
+struct KeyedFibProver(FibProver<Blake3_256>);
+
+impl Prover for KeyedFibProver {
+    type BaseField = BaseElement;
+    type Air = KeyedFibAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> BaseElement {
+        self.0.get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.0.options()
+    }
+}
+
+/// Fibonacci AIR in which the second constraint does not apply to the last two rows of the
+/// trace; constraints and assertions are the same as in [FibAir].
+struct ExemptFibAir {
+    context: AirContext<BaseElement>,
+    fib: FibAir<BaseElement>,
+}
+
+impl Air for ExemptFibAir {
+    type BaseField = BaseElement;
+    type PublicInputs = BaseElement;
+
+    fn new(trace_info: TraceInfo, pub_inputs: BaseElement, options: ProofOptions) -> Self {
+        let fib = FibAir::new(trace_info, pub_inputs, options);
+        let context = fib.context().clone().set_transition_exemptions(vec![1, 2]);
+        ExemptFibAir { context, fib }
+    }
+
+    fn context(&self) -> &AirContext<BaseElement> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = BaseElement>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        self.fib.evaluate_transition(frame, periodic_values, result)
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<BaseElement>> {
+        self.fib.get_assertions()
+    }
+}
+
+/// Same as [ExemptFibAir], but the first constraint (rather than the second one) does not apply
+/// to the last two rows of the trace.
+struct SwappedExemptFibAir(ExemptFibAir);
+
+impl Air for SwappedExemptFibAir {
+    type BaseField = BaseElement;
+    type PublicInputs = BaseElement;
+
+    fn new(trace_info: TraceInfo, pub_inputs: BaseElement, options: ProofOptions) -> Self {
+        let fib = FibAir::new(trace_info, pub_inputs, options);
+        let context = fib.context().clone().set_transition_exemptions(vec![2, 1]);
+        SwappedExemptFibAir(ExemptFibAir { context, fib })
+    }
+
+    fn context(&self) -> &AirContext<BaseElement> {
+        self.0.context()
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = BaseElement>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        self.0.evaluate_transition(frame, periodic_values, result)
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<BaseElement>> {
+        self.0.get_assertions()
+    }
+}
+
+struct ExemptFibProver(FibProver<Blake3_256>);
+
+impl Prover for ExemptFibProver {
+    type BaseField = BaseElement;
+    type Air = ExemptFibAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> BaseElement {
+        self.0.get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.0.options()
+    }
+
+    fn config(&self) -> ProverConfig {
+        ProverConfig::default().with_trace_validation(true)
+    }
+}
@@ -0,0 +1,359 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Makes sure that proofs, batches of proofs, and archival envelopes survive serialization,
+//! that their serialized layout matches the reported structure, and that malformed encodings
+//! are rejected.
+
+#![cfg(feature = "f128")]
+
+mod common;
+
+use common::{prove_fib, prove_fib_batch, verify_fib};
+use examples::{fibonacci::fib2::FibAir, fibonacci::utils::build_proof_options, Blake3_256};
+use winterfell::{
+    math::{fields::f128::BaseElement, FieldElement},
+    ArchivalEnvelope, ArchiveError, ArchiveMetadata, BatchStarkProof, Deserializable,
+    DeserializationError, FieldExtension, GrindingStrategy, ProofOptions, Serializable,
+    SliceReader, StarkProof, VerifierError, PROOF_FORMAT_VERSION,
+};
+
+// PROOF SERIALIZATION
+// ================================================================================================
+
+#[test]
+fn fib2_serde_round_trip() {
+    let (proof, result) = prove_fib::<Blake3_256>(64, build_proof_options(false));
+
+    // in JSON, proof components are encoded as hex strings
+    let json = serde_json::to_string(&proof).unwrap();
+    let context = format!("\"context\":\"{}\"", hex::encode(proof.context.to_bytes()));
+    assert!(json.contains(&context));
+    assert!(json.contains(&format!("\"pow_nonce\":{}", proof.pow_nonce)));
+    let decoded: StarkProof = serde_json::from_str(&json).unwrap();
+    assert_eq!(proof, decoded);
+    assert!(verify_fib::<Blake3_256>(decoded, result).is_ok());
+
+    // in binary formats, proof components are encoded as byte strings
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&proof, &mut bytes).unwrap();
+    let decoded: StarkProof = ciborium::de::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(proof, decoded);
+    assert!(verify_fib::<Blake3_256>(decoded, result).is_ok());
+
+    // a tampered proof is rejected after the round trip
+    let mut tampered = proof.clone();
+    tampered.pow_nonce += 1;
+    let json = serde_json::to_string(&tampered).unwrap();
+    let decoded: StarkProof = serde_json::from_str(&json).unwrap();
+    assert!(verify_fib::<Blake3_256>(decoded, result).is_err());
+}
+
+#[test]
+fn fib2_proof_size_breakdown() {
+    let grinding = GrindingStrategy::TimeBoxed {
+        max_millis: 10,
+        fallback_extra_queries: 8,
+    };
+    let all_options = [
+        build_proof_options(false),
+        build_proof_options(true),
+        build_proof_options(false).with_explicit_composition(1024),
+        build_proof_options(false).with_grinding_strategy(grinding),
+        build_proof_options(false).with_fri_remainder_digest(),
+    ];
+    for options in all_options {
+        let (proof, _) = prove_fib::<Blake3_256>(1024, options);
+        let breakdown = proof.size_breakdown();
+
+        // sizes of all components add up to the size of the serialized proof
+        assert_eq!(proof.to_bytes().len(), breakdown.total());
+        assert_eq!(proof.fri_layer_count(), breakdown.fri_layers().len());
+        assert_eq!(
+            proof.composition_coefficients.is_some(),
+            breakdown.composition_coefficients() != 0
+        );
+        assert_eq!(1 + proof.context.to_bytes().len(), breakdown.context());
+        let pow_nonce_bytes = 8 + proof.options().uses_time_boxed_grinding() as usize;
+        assert_eq!(pow_nonce_bytes, breakdown.pow_nonce());
+
+        // the table lists every FRI layer and ends with the total
+        let table = breakdown.to_string();
+        let last_layer = format!("FRI layer {}", breakdown.fri_layers().len() - 1);
+        assert!(table.contains(&last_layer));
+        let total = format!("{} B", breakdown.total());
+        assert!(table.lines().last().unwrap().ends_with(&total));
+    }
+}
+
+#[test]
+fn fib2_proof_format_version() {
+    let (proof, _) = prove_fib::<Blake3_256>(64, build_proof_options(false));
+    let bytes = proof.to_bytes();
+    assert_eq!(PROOF_FORMAT_VERSION, bytes[0]);
+    assert_eq!(
+        Ok(PROOF_FORMAT_VERSION),
+        StarkProof::read_format_version(&bytes)
+    );
+
+    // proofs serialized in other format versions are rejected, but their version can be read
+    let mut other_version = bytes;
+    other_version[0] = PROOF_FORMAT_VERSION + 1;
+    assert_eq!(
+        Ok(PROOF_FORMAT_VERSION + 1),
+        StarkProof::read_format_version(&other_version)
+    );
+    assert!(matches!(
+        StarkProof::from_bytes(&other_version),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    let (batch, _) = prove_fib_batch::<Blake3_256>(64, build_proof_options(false), 2);
+    let mut batch_bytes = batch.to_bytes();
+    assert_eq!(PROOF_FORMAT_VERSION, batch_bytes[0]);
+    batch_bytes[0] = PROOF_FORMAT_VERSION + 1;
+    assert!(matches!(
+        BatchStarkProof::from_bytes(&batch_bytes),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    assert_eq!(
+        Err(DeserializationError::UnexpectedEOF),
+        StarkProof::read_format_version(&[])
+    );
+}
+
+// BATCHES OF PROOFS
+// ================================================================================================
+
+#[test]
+fn fib2_batch_proof() {
+    let grinding = GrindingStrategy::TimeBoxed {
+        max_millis: 10,
+        fallback_extra_queries: 8,
+    };
+    let all_options = [
+        build_proof_options(false),
+        build_proof_options(false).with_grinding_strategy(grinding),
+    ];
+    for options in all_options {
+        let (batch, result) = prove_fib_batch::<Blake3_256>(64, options, 3);
+        assert_eq!(3, batch.num_proofs());
+
+        // the format version and the shared context are serialized only once, followed by the
+        // number of proofs
+        let bytes = batch.to_bytes();
+        let context_size = 1 + batch.context().to_bytes().len();
+        let proofs_size = batch
+            .proofs()
+            .iter()
+            .map(|proof| proof.to_bytes().len())
+            .sum::<usize>();
+        assert_eq!(proofs_size - 2 * context_size + 4, bytes.len());
+        let decoded = BatchStarkProof::from_bytes(&bytes).unwrap();
+        assert_eq!(batch, decoded);
+
+        // every proof in the batch is verified against its own public inputs
+        let results =
+            winterfell::verify_batch::<FibAir<BaseElement>, Blake3_256>(decoded, vec![result; 3]);
+        assert_eq!(vec![Ok(()); 3], results);
+        let mut pub_inputs = vec![result; 3];
+        pub_inputs[1] += BaseElement::ONE;
+        let results =
+            winterfell::verify_batch::<FibAir<BaseElement>, Blake3_256>(batch.clone(), pub_inputs);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+
+        // the number of public inputs must match the number of proofs
+        let results =
+            winterfell::verify_batch::<FibAir<BaseElement>, Blake3_256>(batch, vec![result; 2]);
+        assert_eq!(
+            vec![Err(VerifierError::BatchSizeMismatch(3, 2)); 3],
+            results
+        );
+    }
+}
+
+#[test]
+fn fib2_batch_proof_invalid_bytes() {
+    let (batch, _) = prove_fib_batch::<Blake3_256>(64, build_proof_options(false), 2);
+    let bytes = batch.to_bytes();
+
+    // a batch with no proofs is rejected
+    let context_size = 1 + batch.context().to_bytes().len();
+    let mut empty = bytes[..context_size].to_vec();
+    empty.extend_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(
+        BatchStarkProof::from_bytes(&empty),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // a batch which declares more proofs than it contains is rejected
+    let mut truncated = bytes.clone();
+    truncated[context_size] = 3;
+    assert!(BatchStarkProof::from_bytes(&truncated).is_err());
+
+    // trailing bytes are rejected
+    let mut extended = bytes;
+    extended.push(0);
+    assert_eq!(
+        Err(DeserializationError::UnconsumedBytes),
+        BatchStarkProof::from_bytes(&extended)
+    );
+}
+
+#[test]
+#[should_panic(expected = "all proofs in a batch must have the same context")]
+fn fib2_batch_proof_context_mismatch() {
+    let proofs = [16, 32]
+        .iter()
+        .map(|&length| prove_fib::<Blake3_256>(length, build_proof_options(false)).0)
+        .collect();
+    BatchStarkProof::new(proofs);
+}
+
+// ARCHIVAL ENVELOPES
+// ================================================================================================
+
+#[test]
+fn fib2_archival_envelope() {
+    // the creator signs envelopes with a keyed hash of the header; only the holder of the key can
+    // check the signature
+    const KEY: [u8; 32] = [7; 32];
+    let signer = |header: &[u8]| blake3::keyed_hash(&KEY, header).as_bytes().to_vec();
+    let verifier = |header: &[u8], signature: &[u8]| signer(header) == signature;
+
+    let (proof, result) = prove_fib::<Blake3_256>(64, build_proof_options(false));
+    let metadata = ArchiveMetadata {
+        hash_id: 1,
+        created_at: 1_700_000_000,
+    };
+    let envelope = ArchivalEnvelope::seal(&proof, metadata, Some(signer));
+    let header = envelope.header();
+    assert_eq!(128, header.field_id());
+    assert_eq!(1, header.hash_id());
+    assert_eq!(2, header.trace_width());
+    assert_eq!(32, header.trace_length());
+    assert_eq!(1_700_000_000, header.created_at());
+    assert_eq!(
+        blake3::hash(&proof.to_bytes()).as_bytes(),
+        header.proof_digest()
+    );
+
+    // the proof unsealed from the envelope is the same as the original proof
+    let bytes = envelope.to_bytes();
+    let unsealed = ArchivalEnvelope::unseal(&bytes, Some(verifier)).unwrap();
+    assert_eq!(proof, unsealed);
+    assert!(verify_fib::<Blake3_256>(unsealed, result).is_ok());
+
+    // truncated envelopes are rejected
+    assert_eq!(
+        Err(ArchiveError::Truncated(bytes.len(), bytes.len() - 10)),
+        ArchivalEnvelope::unseal(&bytes[..bytes.len() - 10], Some(verifier))
+    );
+
+    // changing the proof invalidates the proof digest
+    let mut tampered = bytes.clone();
+    tampered[bytes.len() - 9] ^= 1;
+    assert_eq!(
+        Err(ArchiveError::DigestMismatch),
+        ArchivalEnvelope::unseal(&tampered, Some(verifier))
+    );
+
+    // changing the header invalidates the signature
+    let mut tampered = bytes.clone();
+    tampered[24] ^= 1;
+    assert_eq!(
+        Err(ArchiveError::InvalidSignature),
+        ArchivalEnvelope::unseal(&tampered, Some(verifier))
+    );
+
+    // when the signature is not checked, the trace shape in the header is still checked against
+    // the proof
+    let mut tampered = bytes;
+    tampered[12] = 3;
+    assert_eq!(
+        Err(ArchiveError::TraceShapeMismatch),
+        ArchivalEnvelope::unseal(&tampered, None::<fn(&[u8], &[u8]) -> bool>)
+    );
+
+    // an unsigned envelope is rejected when a signature verifier is provided
+    let envelope = ArchivalEnvelope::seal(&proof, metadata, None::<fn(&[u8]) -> Vec<u8>>);
+    assert_eq!(None, envelope.signature());
+    assert_eq!(
+        Err(ArchiveError::MissingSignature),
+        ArchivalEnvelope::unseal(&envelope.to_bytes(), Some(verifier))
+    );
+}
+
+// FRI STRUCTURE
+// ================================================================================================
+
+#[test]
+fn fib2_fri_structure() {
+    // sequence of 1024 terms is computed in a trace of 512 steps, and thus, the LDE domain has
+    // 4096 elements; the FRI remainder has at most 256 elements. Proof options do not support
+    // folding factor 2, and thus, all folding factors supported by proof options are covered.
+    let cases: [(usize, &[usize], usize); 3] = [
+        (4, &[4096, 1024], 31),
+        (8, &[4096, 512], 7),
+        (16, &[4096], 31),
+    ];
+    for (folding_factor, domain_sizes, remainder_degree) in cases {
+        let options = ProofOptions::new(28, 8, 0, FieldExtension::None, folding_factor, 256);
+        let (proof, result) = prove_fib::<Blake3_256>(1024, options);
+        assert_eq!(domain_sizes.len(), proof.fri_layer_count());
+        assert_eq!(domain_sizes.to_vec(), proof.fri_layer_domain_sizes());
+        assert_eq!(remainder_degree, proof.fri_remainder_degree());
+        assert!(verify_fib::<Blake3_256>(proof, result).is_ok());
+    }
+}
+
+#[test]
+fn fib2_fri_structure_truncated_layers() {
+    let (mut proof, result) = prove_fib::<Blake3_256>(1024, build_proof_options(false));
+    let num_layers = proof.fri_layer_count();
+    assert_eq!(2, num_layers);
+
+    // drop the last FRI layer from the proof; the remainder is left unchanged
+    let fri_proof_bytes = remove_last_fri_layer(&proof.fri_proof.to_bytes());
+    proof.fri_proof = Deserializable::read_from(&mut SliceReader::new(&fri_proof_bytes)).unwrap();
+    assert_eq!(num_layers - 1, proof.fri_layer_count());
+    assert_eq!(vec![4096], proof.fri_layer_domain_sizes());
+    assert_eq!(
+        Err(VerifierError::FriStructureMismatch(
+            num_layers,
+            num_layers - 1
+        )),
+        verify_fib::<Blake3_256>(proof, result)
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Removes the last layer from the specified serialized FRI proof.
+fn remove_last_fri_layer(bytes: &[u8]) -> Vec<u8> {
+    let read_u32 =
+        |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+
+    // each layer consists of opened positions, followed by query values and Merkle paths, each
+    // prefixed with their length
+    let num_layers = bytes[0] as usize;
+    let mut layer_start = 1;
+    let mut layer_end = 1;
+    for _ in 0..num_layers {
+        layer_start = layer_end;
+        let values_offset = layer_start + 1 + 4 * bytes[layer_start] as usize;
+        let paths_offset = values_offset + 4 + read_u32(values_offset);
+        layer_end = paths_offset + 4 + read_u32(paths_offset);
+    }
+
+    let mut result = vec![num_layers as u8 - 1];
+    result.extend_from_slice(&bytes[1..layer_start]);
+    result.extend_from_slice(&bytes[layer_end..]);
+    result
+}
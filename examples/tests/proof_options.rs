@@ -0,0 +1,121 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Makes sure that security estimates of proofs match the proof options they were generated
+//! with, and that options derived from a security target yield proofs which meet the target.
+
+#![cfg(feature = "f128")]
+
+mod common;
+
+use common::prove_fib;
+use examples::{
+    fibonacci::{fib2::FibAir, utils::build_proof_options},
+    Blake3_192, Blake3_256,
+};
+use winterfell::{
+    math::fields::f128::BaseElement, FieldExtension, ProofOptions, SecurityBottleneck,
+    SecurityError,
+};
+
+// SECURITY ESTIMATES
+// ================================================================================================
+
+#[test]
+fn fib2_security_estimate() {
+    // sequence of 1024 terms is computed in a trace of 512 steps, and thus, the LDE domain has
+    // 4096 elements; the field contributes 128 - log2(8 * 513 + 511) = 115 bits (the DEEP/ALI
+    // step is weaker than the FRI commit phase which contributes 128 - 12 = 116 bits), and 28
+    // queries with blowup factor 8 contribute 3 * 28 = 84 bits (grinding factor is 0)
+    let (proof, _) = prove_fib::<Blake3_256>(1024, build_proof_options(false));
+    let estimate = proof.security_estimate::<Blake3_256>(true);
+    assert_eq!(115, estimate.field_security());
+    assert_eq!(84, estimate.query_security());
+    assert_eq!(0, estimate.grinding_bits());
+    assert_eq!(128, estimate.collision_resistance());
+    assert_eq!(SecurityBottleneck::Queries, estimate.bottleneck());
+    assert_eq!(83, estimate.security_level());
+    assert_eq!(
+        proof.security_level::<Blake3_256>(true),
+        estimate.security_level()
+    );
+
+    // with quadratic extension and 96 queries, the queries contribute 3 * 96 = 288 bits, and
+    // the hash function becomes the bottleneck
+    let options = ProofOptions::new(96, 8, 0, FieldExtension::Quadratic, 4, 256);
+    let (extended_proof, _) = prove_fib::<Blake3_192>(1024, options);
+    let estimate = extended_proof.security_estimate::<Blake3_192>(true);
+    assert_eq!(243, estimate.field_security());
+    assert_eq!(288, estimate.query_security());
+    assert_eq!(
+        SecurityBottleneck::CollisionResistance,
+        estimate.bottleneck()
+    );
+    assert_eq!(96, estimate.security_level());
+
+    // provable security of the original proof is limited by the queries which contribute only
+    // 41 bits under the Johnson bound; the field contributes 69 bits
+    let estimate = proof.security_estimate::<Blake3_256>(false);
+    assert_eq!(69, estimate.field_security());
+    assert_eq!(41, estimate.query_security());
+    assert_eq!(SecurityBottleneck::Queries, estimate.bottleneck());
+    assert_eq!(40, estimate.security_level());
+    assert_eq!(
+        proof.security_level::<Blake3_256>(false),
+        estimate.security_level()
+    );
+}
+
+// OPTIONS FROM SECURITY TARGETS
+// ================================================================================================
+
+#[test]
+fn fib2_options_with_security() {
+    let targets = [
+        (80, true, FieldExtension::None),
+        (96, true, FieldExtension::None),
+        (100, true, FieldExtension::None),
+        (120, true, FieldExtension::Quadratic),
+        (128, true, FieldExtension::Quadratic),
+        (96, false, FieldExtension::Quadratic),
+    ];
+    for (target, conjectured, field_extension) in targets {
+        let options = ProofOptions::with_security::<BaseElement, Blake3_256>(
+            target,
+            conjectured,
+            field_extension,
+        )
+        .unwrap();
+        let (proof, result) = prove_fib::<Blake3_256>(1024, options);
+        assert!(proof.security_level::<Blake3_256>(conjectured) >= target);
+        assert_eq!(
+            Ok(()),
+            winterfell::verify::<FibAir<BaseElement>, Blake3_256>(proof, result)
+        );
+    }
+}
+
+#[test]
+fn fib2_options_with_security_unreachable() {
+    // provable security of a 128-bit field over LDE domain of 2^24 elements is limited to 63 bits
+    let result =
+        ProofOptions::with_security::<BaseElement, Blake3_256>(100, false, FieldExtension::None);
+    assert!(matches!(
+        result,
+        Err(SecurityError::InsufficientFieldSecurity(_, 63))
+    ));
+    assert_eq!(
+        Err(SecurityError::InsufficientCollisionResistance(100, 96)),
+        ProofOptions::with_security::<BaseElement, Blake3_192>(100, true, FieldExtension::None)
+    );
+
+    // 128-bit field over LDE domain of 2^24 elements contributes only 103 bits of security
+    let result =
+        ProofOptions::with_security::<BaseElement, Blake3_256>(120, true, FieldExtension::None);
+    assert!(matches!(
+        result,
+        Err(SecurityError::InsufficientFieldSecurity(_, 103))
+    ));
+}
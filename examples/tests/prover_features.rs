@@ -0,0 +1,346 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Exercises optional features of the prover: proving from interpolated trace polynomials,
+//! checkpointing and resuming proof generation, trace validation, constraint logs, and
+//! time-boxed grinding.
+
+#![cfg(feature = "f128")]
+
+mod common;
+
+use common::verify_fib;
+use examples::{
+    fibonacci::{
+        fib2::{FibAir, FibProver},
+        utils::{build_proof_options, compute_fib_term},
+    },
+    Blake3_256,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use winterfell::{
+    checkpoint::{Checkpoint, CheckpointSink, ProofPhase},
+    math::{fields::f128::BaseElement, FieldElement},
+    CheckpointError, FieldExtension, GrindingClock, GrindingStrategy, Matrix, ProofOptions, Prover,
+    ProverError, StarkProof, Trace, TraceError, TraceTable, VerifierError,
+};
+
+// PROVING FROM POLYNOMIALS
+// ================================================================================================
+
+#[test]
+fn fib2_prove_from_polys() {
+    for options in [build_proof_options(false), build_proof_options(true)] {
+        let prover = FibProver::<Blake3_256>::new(options);
+        let trace = prover.build_trace(32);
+        let trace_polys = trace.main_segment().interpolate_columns();
+
+        // a proof built from the interpolated trace is the same as the one built from the trace
+        let expected = prover.prove(prover.build_trace(32)).unwrap();
+        let proof = prover.prove_from_polys(trace, trace_polys).unwrap();
+        assert_eq!(expected.to_bytes(), proof.to_bytes());
+        let result = compute_fib_term::<BaseElement>(32);
+        assert!(verify_fib::<Blake3_256>(proof, result).is_ok());
+    }
+}
+
+#[test]
+fn fib2_prove_from_polys_degree_bound() {
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let trace = prover.build_trace(32);
+    let trace_length = trace.length();
+    let expected = prover.prove(prover.build_trace(32)).unwrap();
+
+    // polynomials padded with zero coefficients beyond the trace length are accepted
+    let mut columns = trace.main_segment().interpolate_columns().into_columns();
+    for column in columns.iter_mut() {
+        column.resize(trace_length * 2, BaseElement::ZERO);
+    }
+    let proof = prover
+        .prove_from_polys(trace, Matrix::new(columns.clone()))
+        .unwrap();
+    assert_eq!(expected.to_bytes(), proof.to_bytes());
+
+    // a polynomial of degree equal to the trace length is rejected
+    columns[1][trace_length] = BaseElement::ONE;
+    let result = prover.prove_from_polys(prover.build_trace(32), Matrix::new(columns.clone()));
+    assert_eq!(
+        Err(ProverError::TracePolynomialDegreeTooHigh(1, trace_length)),
+        result.map(|_| ())
+    );
+
+    // a polynomial is required for every column of the main trace segment
+    columns.truncate(1);
+    let result = prover.prove_from_polys(prover.build_trace(32), Matrix::new(columns));
+    assert_eq!(
+        Err(ProverError::MismatchedTracePolynomials(2, 1)),
+        result.map(|_| ())
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "polynomial of column 0 does not match the main segment")]
+fn fib2_prove_from_polys_inconsistent() {
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let trace_polys = prover.build_trace(32).main_segment().interpolate_columns();
+    let mut columns = trace_polys.into_columns();
+    columns[0][0] += BaseElement::ONE;
+    let _ = prover.prove_from_polys(prover.build_trace(32), Matrix::new(columns));
+}
+
+// CHECKPOINTS
+// ================================================================================================
+
+#[test]
+fn fib2_checkpoint_resume() {
+    // a proof for a trace of 2^16 steps; the folding factor of 8 keeps the number of FRI layers
+    // (and thus, the number of resumed proofs) small
+    let sequence_length = 1 << 17;
+    let options = ProofOptions::new(28, 8, 0, FieldExtension::None, 8, 256);
+    let prover = FibProver::<Blake3_256>::new(options);
+    let result = compute_fib_term(sequence_length);
+
+    let proof = prover.prove(prover.build_trace(sequence_length)).unwrap();
+
+    let mut checkpoints = Vec::new();
+    let checkpointed_proof = prover
+        .prove_with_checkpoints(prover.build_trace(sequence_length), &mut checkpoints)
+        .unwrap();
+    assert_eq!(proof.to_bytes(), checkpointed_proof.to_bytes());
+
+    // a checkpoint is saved after trace commitment, constraint commitment and each FRI layer
+    let phases = checkpoints.iter().map(|c| c.phase()).collect::<Vec<_>>();
+    assert_eq!(ProofPhase::TraceCommitted, phases[0]);
+    assert_eq!(ProofPhase::ConstraintsCommitted, phases[1]);
+    assert_eq!(ProofPhase::FriLayerCommitted(0), phases[2]);
+    assert_eq!(
+        ProofPhase::FriLayerCommitted(phases.len() - 3),
+        phases[phases.len() - 1]
+    );
+
+    // resuming from every checkpoint results in the same proof as an uninterrupted run
+    for checkpoint in checkpoints.iter() {
+        let mut checkpoint = Checkpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+        let resumed_proof = prover.resume_proof(result, &mut checkpoint).unwrap();
+        assert_eq!(proof.to_bytes(), resumed_proof.to_bytes());
+    }
+
+    // kill proof generation at the first boundary and resume from the saved checkpoint
+    let mut sink = KillSink::new(1);
+    let err = prover
+        .prove_with_checkpoints(prover.build_trace(sequence_length), &mut sink)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ProverError::CheckpointFailed(CheckpointError::SinkFailed(_))
+    ));
+    let mut checkpoints = sink.checkpoints;
+    let resumed_proof = prover.resume_proof(result, &mut checkpoints).unwrap();
+    assert_eq!(proof.to_bytes(), resumed_proof.to_bytes());
+
+    // a checkpoint cannot be resumed for different public inputs
+    assert_eq!(
+        Err(ProverError::CheckpointFailed(
+            CheckpointError::PublicInputsMismatch
+        )),
+        prover.resume_proof(result + BaseElement::ONE, &mut checkpoints)
+    );
+
+    // a checkpoint cannot be resumed by a prover with different proof options
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    assert_eq!(
+        Err(ProverError::CheckpointFailed(
+            CheckpointError::ContextMismatch
+        )),
+        prover.resume_proof(result, &mut checkpoints)
+    );
+}
+
+// TRACE VALIDATION
+// ================================================================================================
+
+#[test]
+fn fib2_trace_validation() {
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false)).with_trace_validation();
+    let mut trace = prover.build_trace(16);
+    trace.set(0, 3, trace.get(0, 3) + BaseElement::ONE);
+
+    // the first constraint is violated by the transition into the perturbed row
+    assert!(matches!(
+        prover.prove(trace),
+        Err(ProverError::InvalidTrace(
+            TraceError::UnsatisfiedTransitionConstraint {
+                aux: false,
+                index: 0,
+                step: 2
+            }
+        ))
+    ));
+}
+
+#[test]
+#[cfg(feature = "trace-constraints")]
+fn fib2_constraint_log() {
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let (proof, log) = prover.prove_with_constraint_log(prover.build_trace(16));
+    assert!(proof.is_ok());
+    assert!(log.is_empty());
+
+    // the perturbed row breaks both constraints of the transition into it, and the first
+    // constraint of the transition out of it
+    let mut trace = prover.build_trace(16);
+    trace.set(0, 3, trace.get(0, 3) + BaseElement::ONE);
+    let (proof, log) = prover.prove_with_constraint_log(trace);
+    assert!(matches!(
+        proof,
+        Err(ProverError::InvalidTrace(
+            TraceError::UnsatisfiedTransitionConstraint {
+                aux: false,
+                index: 0,
+                step: 2
+            }
+        ))
+    ));
+    let violations = log
+        .entries()
+        .iter()
+        .map(|entry| (entry.step, entry.aux, entry.index, entry.value.clone()))
+        .collect::<Vec<_>>();
+    let minus_one = (-BaseElement::ONE).to_string();
+    assert_eq!(
+        vec![
+            (2, false, 0, BaseElement::ONE.to_string()),
+            (2, false, 1, minus_one.clone()),
+            (3, false, 0, minus_one),
+        ],
+        violations
+    );
+}
+
+// TIME-BOXED GRINDING
+// ================================================================================================
+
+#[test]
+fn fib2_time_boxed_grinding() {
+    let strategy = GrindingStrategy::TimeBoxed {
+        max_millis: 10,
+        fallback_extra_queries: 8,
+    };
+    let options =
+        ProofOptions::new(28, 8, 16, FieldExtension::None, 4, 256).with_grinding_strategy(strategy);
+
+    // when the clock does not advance, grinding continues until the nonce provides all bits
+    let prover = TimeBoxedFibProver::new(options.clone(), 0);
+    let trace = prover.0.build_trace(16);
+    let result = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    assert_eq!(0, proof.num_extra_queries);
+    assert_eq!(28, proof.num_queries());
+    assert_eq!(Ok(()), verify_fib::<Blake3_256>(proof, result));
+
+    // when the deadline passes after the first batch of nonces, the prover falls back to extra
+    // queries to compensate for the missing bits
+    let prover = TimeBoxedFibProver::new(options, 1000);
+    let proof = prover.prove(prover.0.build_trace(16)).unwrap();
+    assert!(proof.num_extra_queries > 0);
+    assert_eq!(28 + proof.num_extra_queries, proof.num_queries());
+    let proof_bytes = proof.to_bytes();
+    assert_eq!(proof, StarkProof::from_bytes(&proof_bytes).unwrap());
+    assert_eq!(Ok(()), verify_fib::<Blake3_256>(proof.clone(), result));
+
+    // a nonce which provides fewer bits requires more extra queries than the proof contains
+    let mut weak_proof = proof.clone();
+    weak_proof.pow_nonce += 1;
+    match verify_fib::<Blake3_256>(weak_proof, result) {
+        Err(VerifierError::InsufficientExtraQueries(expected, actual)) => {
+            assert!(expected > actual);
+            assert_eq!(proof.num_extra_queries, actual);
+        }
+        result => panic!("expected insufficient extra queries, but was {result:?}"),
+    }
+
+    // the number of extra queries cannot exceed the number allowed by proof options
+    let mut bloated_proof = proof;
+    bloated_proof.num_extra_queries = 9;
+    assert!(matches!(
+        verify_fib::<Blake3_256>(bloated_proof, result),
+        Err(VerifierError::ProofDeserializationError(_))
+    ));
+}
+
+// HELPER TYPES
+// ================================================================================================
+
+/// A checkpoint sink which fails once the specified number of checkpoints has been saved,
+/// simulating a prover killed right after reaching a phase boundary.
+struct KillSink {
+    max_checkpoints: usize,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl KillSink {
+    fn new(max_checkpoints: usize) -> Self {
+        Self {
+            max_checkpoints,
+            checkpoints: Vec::new(),
+        }
+    }
+}
+
+impl CheckpointSink for KillSink {
+    fn save(&mut self, checkpoint: Checkpoint) -> Result<(), CheckpointError> {
+        if self.checkpoints.len() == self.max_checkpoints {
+            return Err(CheckpointError::SinkFailed("prover was killed".to_string()));
+        }
+        self.checkpoints.push(checkpoint);
+        Ok(())
+    }
+}
+
+/// A prover which bounds the time spent on grinding by a clock advancing by a fixed number of
+/// milliseconds every time it is read.
+struct TimeBoxedFibProver(FibProver<Blake3_256>, SteppingClock);
+
+impl TimeBoxedFibProver {
+    fn new(options: ProofOptions, step_millis: u64) -> Self {
+        let clock = SteppingClock {
+            now: AtomicU64::new(0),
+            step_millis,
+        };
+        Self(FibProver::new(options), clock)
+    }
+}
+
+impl Prover for TimeBoxedFibProver {
+    type BaseField = BaseElement;
+    type Air = FibAir<BaseElement>;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> BaseElement {
+        self.0.get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.0.options()
+    }
+
+    fn grinding_clock(&self) -> &dyn GrindingClock {
+        &self.1
+    }
+}
+
+/// A clock which advances by `step_millis` milliseconds every time it is read.
+struct SteppingClock {
+    now: AtomicU64,
+    step_millis: u64,
+}
+
+impl GrindingClock for SteppingClock {
+    fn now_millis(&self) -> u64 {
+        self.now.fetch_add(self.step_millis, Ordering::Relaxed)
+    }
+}
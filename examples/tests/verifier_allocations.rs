@@ -0,0 +1,113 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Makes sure the number of heap allocations performed by the verifier does not grow.
+//!
+//! This test lives in its own binary because it installs a global allocator which counts
+//! allocations; the count is meaningful only while no other test runs concurrently.
+
+use examples::fibonacci::fib_small::{FibSmall, FibSmallProver};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use winterfell::{
+    crypto::hashers::Blake3_256, math::fields::f64::BaseElement, FieldExtension, ProofOptions,
+    Prover,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Maximum number of heap allocations (including re-allocations) performed while verifying a
+/// proof of 1024 steps of the small Fibonacci computation; the verifier performed 116
+/// allocations when this bound was set.
+///
+/// Remaining allocations are performed once per proof or once per FRI layer: the AIR, its
+/// assertions, and constraint divisors are built for every verification, and every parsed batch
+/// Merkle proof stores its nodes in a single vector. Collections with an entry per query are kept
+/// inline (up to 128 queries), and thus, the number of allocations does not depend on the number
+/// of queries.
+const MAX_VERIFIER_ALLOCATIONS: usize = 120;
+
+/// Numbers of queries for which the allocations are counted; the counts must be the same for all
+/// of them. Larger numbers of queries would require a cubic field extension for the proof options
+/// to be accepted by the verifier.
+const NUM_QUERIES: [usize; 4] = [4, 12, 20, 32];
+
+// COUNTING ALLOCATOR
+// ================================================================================================
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static NUM_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.load(Ordering::SeqCst) {
+            NUM_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.load(Ordering::SeqCst) {
+            NUM_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// TESTS
+// ================================================================================================
+
+#[test]
+fn fib_small_verifier_allocations() {
+    let counts = NUM_QUERIES
+        .iter()
+        .map(|&num_queries| count_verifier_allocations(num_queries))
+        .collect::<Vec<_>>();
+
+    assert!(
+        counts.iter().all(|&count| count == counts[0]),
+        "number of verifier allocations depends on the number of queries: {counts:?} heap \
+        allocations for {NUM_QUERIES:?} queries"
+    );
+    assert!(
+        counts[0] <= MAX_VERIFIER_ALLOCATIONS,
+        "verifier performed {} heap allocations, but at most {MAX_VERIFIER_ALLOCATIONS} were \
+        expected",
+        counts[0]
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of heap allocations performed while verifying a proof generated with the
+/// specified number of queries.
+fn count_verifier_allocations(num_queries: usize) -> usize {
+    let options = ProofOptions::new(num_queries, 8, 16, FieldExtension::Quadratic, 8, 256);
+    let prover = FibSmallProver::<Blake3_256<BaseElement>>::new(options);
+    let trace = prover.build_trace(1024);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+
+    NUM_ALLOCATIONS.store(0, Ordering::SeqCst);
+    COUNTING.store(true, Ordering::SeqCst);
+    let result = winterfell::verify::<FibSmall, Blake3_256<BaseElement>>(proof, pub_inputs);
+    COUNTING.store(false, Ordering::SeqCst);
+
+    assert!(result.is_ok());
+    NUM_ALLOCATIONS.load(Ordering::SeqCst)
+}
@@ -0,0 +1,305 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Exercises the entry points of the prover and the verifier which go beyond generating and
+//! verifying a single proof: public inputs returned by the prover, statements extracted by the
+//! verifier, split public inputs, automatic hash function selection, and transcript logs.
+
+#![cfg(feature = "f128")]
+
+mod common;
+
+use common::prove_fib;
+use examples::{
+    fibonacci::{
+        fib2::{FibAir, FibProver},
+        utils::{build_proof_options, compute_fib_term},
+    },
+    utils::prove_checked,
+    Blake2s_256, Blake3_192, Blake3_256, Keccak_256, Sha3_256,
+};
+use winterfell::{
+    crypto::{
+        hashers::{
+            GriffinJive64_256, Poseidon128_256, Poseidon64_256, Rp62_248, Rp64_256, RpJive64_256,
+        },
+        ElementHasher, HashFunctionId,
+    },
+    math::{fields::f128::BaseElement, ExtensibleField, FieldElement},
+    pub_inputs_seed, verify_split_inputs, AutoHashField, Context, ProofOptions, Prover,
+    Serializable, TraceTable, VerifierError,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+#[test]
+fn fib2_returned_pub_inputs() {
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let expected: BaseElement = compute_fib_term(32);
+
+    // the returned public inputs are the ones the proof is verifiable against
+    let trace = prover.build_trace(32);
+    let (proof, pub_inputs) = prover.prove_returning_inputs(trace).unwrap();
+    assert_eq!(expected, pub_inputs);
+    assert!(winterfell::verify::<FibAir<BaseElement>, Blake3_256>(proof, pub_inputs).is_ok());
+
+    // the caller assumes a sequence of 32 terms while the trace is built for 64 terms; comparing
+    // the returned public inputs against the assumption exposes the mismatch before the proof
+    // is verified against the wrong inputs
+    let trace = prover.build_trace(64);
+    let (proof, pub_inputs) = prover.prove_returning_inputs(trace).unwrap();
+    assert_eq!(compute_fib_term::<BaseElement>(64), pub_inputs);
+    assert_ne!(expected, pub_inputs);
+    assert!(
+        winterfell::verify::<FibAir<BaseElement>, Blake3_256>(proof.clone(), pub_inputs).is_ok()
+    );
+    assert!(winterfell::verify::<FibAir<BaseElement>, Blake3_256>(proof, expected).is_err());
+
+    // the same proof is returned when the derived public inputs are checked by the examples
+    let trace = prover.build_trace(32);
+    let proof = prove_checked(&prover, trace, &expected).unwrap();
+    assert!(winterfell::verify::<FibAir<BaseElement>, Blake3_256>(proof, expected).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "differ from the expected public inputs")]
+fn fib2_returned_pub_inputs_mismatch() {
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let trace = prover.build_trace(64);
+    let _ = prove_checked(&prover, trace, &compute_fib_term::<BaseElement>(32));
+}
+
+// VERIFIED STATEMENTS
+// ================================================================================================
+
+#[test]
+fn fib2_verified_statement() {
+    let (proof, pub_inputs) = prove_fib::<Blake3_256>(64, build_proof_options(false));
+
+    // the caller guesses that the proof was generated for a sequence of 128 terms (i.e., for a
+    // trace of 64 steps); the statement exposes the trace length bound by the proof instead
+    let guessed_trace_length = 64;
+    let statement = winterfell::verify_and_extract::<FibAir<BaseElement>, Blake3_256>(
+        proof.clone(),
+        pub_inputs,
+    )
+    .unwrap();
+    assert_eq!(
+        proof.context.trace_length(),
+        statement.trace_info().length()
+    );
+    assert_eq!(32, statement.trace_info().length());
+    assert_ne!(guessed_trace_length, statement.trace_info().length());
+    assert_eq!(proof.options(), statement.options());
+    assert_eq!(
+        proof.security_level::<Blake3_256>(true),
+        statement.conjectured_security_level()
+    );
+    assert_eq!(
+        proof.security_level::<Blake3_256>(false),
+        statement.proven_security_level()
+    );
+    assert_eq!(pub_inputs, statement.into_pub_inputs());
+
+    // no statement is returned for a proof which does not verify
+    let result = winterfell::verify_and_extract::<FibAir<BaseElement>, Blake3_256>(
+        proof,
+        pub_inputs + BaseElement::ONE,
+    );
+    assert!(result.is_err());
+}
+
+// SPLIT PUBLIC INPUTS
+// ================================================================================================
+
+#[test]
+fn fib2_split_inputs() {
+    let prover = DigestSeededFibProver(FibProver::<Blake3_256>::new(build_proof_options(false)));
+    let trace = prover.0.build_trace(16);
+    let result = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+
+    // the verifier receives only the digest of public inputs and the asserted result
+    let seed = pub_inputs_seed::<Blake3_256>(&result.to_bytes());
+    assert_eq!(
+        Ok(()),
+        verify_split_inputs::<FibAir<BaseElement>, Blake3_256>(proof.clone(), seed, result)
+    );
+
+    // the transcript of the proof is seeded with the digest, and thus, the proof does not verify
+    // against serialized public inputs; the same holds for proofs seeded with serialized inputs
+    assert!(winterfell::verify::<FibAir<BaseElement>, Blake3_256>(proof.clone(), result).is_err());
+    let (regular_proof, _) = prove_fib::<Blake3_256>(16, build_proof_options(false));
+    assert!(
+        verify_split_inputs::<FibAir<BaseElement>, Blake3_256>(regular_proof, seed, result)
+            .is_err()
+    );
+
+    // a seed which does not bind the asserted result causes the public coin of the verifier to
+    // diverge from the one of the prover
+    let wrong_result = result + BaseElement::ONE;
+    let wrong_seed = pub_inputs_seed::<Blake3_256>(&wrong_result.to_bytes());
+    assert!(verify_split_inputs::<FibAir<BaseElement>, Blake3_256>(
+        proof.clone(),
+        wrong_seed,
+        result
+    )
+    .is_err());
+    assert!(
+        verify_split_inputs::<FibAir<BaseElement>, Blake3_256>(proof, seed, wrong_result).is_err()
+    );
+}
+
+// AUTOMATIC HASH FUNCTION SELECTION
+// ================================================================================================
+
+#[test]
+fn fib2_auto_verification() {
+    check_auto_verification::<Blake3_192>();
+    check_auto_verification::<Blake3_256>();
+    check_auto_verification::<Sha3_256>();
+    check_auto_verification::<Keccak_256>();
+    check_auto_verification::<Blake2s_256>();
+    check_auto_verification::<Poseidon128_256>();
+    check_auto_verification::<Rp62_248>();
+    check_auto_verification::<Rp64_256>();
+    check_auto_verification::<RpJive64_256>();
+    check_auto_verification::<GriffinJive64_256>();
+    check_auto_verification::<Poseidon64_256>();
+}
+
+#[test]
+fn fib2_auto_verification_spoofed_hash_fn() {
+    let (proof, result) = prove_fib::<Blake3_256>(16, build_proof_options(false));
+    assert_eq!(Some(HashFunctionId::Blake3_256), proof.context.hash_fn());
+
+    // the hash function is bound into the transcript, and thus, a proof which claims to be
+    // generated with a different hash function does not verify with that hash function
+    let mut spoofed = proof.clone();
+    spoofed.context = spoofed.context.with_hash_fn(HashFunctionId::Sha3_256);
+    assert!(winterfell::verify_auto::<FibAir<BaseElement>>(spoofed.clone(), result).is_err());
+    assert_eq!(
+        Err(VerifierError::HashFunctionMismatch {
+            proof: HashFunctionId::Sha3_256,
+            verifier: HashFunctionId::Blake3_256,
+        }),
+        winterfell::verify::<FibAir<BaseElement>, Blake3_256>(spoofed, result)
+    );
+
+    // algebraic hash functions defined over a different field cannot be selected
+    let mut spoofed = proof.clone();
+    spoofed.context = spoofed.context.with_hash_fn(HashFunctionId::Rp64_256);
+    assert_eq!(
+        Err(VerifierError::UnsupportedHashFunction(
+            HashFunctionId::Rp64_256
+        )),
+        winterfell::verify_auto::<FibAir<BaseElement>>(spoofed, result)
+    );
+
+    // a proof with the hash function stripped from its context cannot be verified via the auto
+    // path, and does not verify with the original hash function either
+    let mut stripped = proof;
+    stripped.context =
+        Context::new::<BaseElement>(&stripped.get_trace_info(), stripped.options().clone());
+    assert_eq!(
+        Err(VerifierError::UnknownHashFunction),
+        winterfell::verify_auto::<FibAir<BaseElement>>(stripped.clone(), result)
+    );
+    assert!(winterfell::verify::<FibAir<BaseElement>, Blake3_256>(stripped, result).is_err());
+}
+
+// TRANSCRIPT LOG
+// ================================================================================================
+
+#[test]
+#[cfg(feature = "transcript-log")]
+fn fib2_transcript_log() {
+    use winterfell::{
+        crypto::transcript_log::SEED_LABEL, transcript_labels as labels, Commitments,
+        Deserializable, SliceReader,
+    };
+
+    let prover = FibProver::<Blake3_256>::new(build_proof_options(false));
+    let result = compute_fib_term(32);
+    let (proof, prover_log) = prover
+        .prove_with_transcript_log(prover.build_trace(32))
+        .unwrap();
+
+    // the verifier replays exactly the same transcript as the prover
+    let (verified, verifier_log) = winterfell::verify_with_transcript_log::<
+        FibAir<BaseElement>,
+        Blake3_256,
+    >(proof.clone(), result);
+    assert!(verified.is_ok());
+    assert_eq!(prover_log, verifier_log);
+    assert_eq!(None, prover_log.first_divergence(&verifier_log));
+
+    let entries = prover_log.entries();
+    assert_eq!(SEED_LABEL, entries[0].label);
+    assert_eq!(labels::TRACE_COMMITMENT, entries[1].label);
+    assert_eq!(labels::POW_NONCE, entries[entries.len() - 2].label);
+    assert_eq!(labels::QUERY_POSITIONS, entries[entries.len() - 1].label);
+
+    // tampering with the trace commitment makes the transcripts diverge right after the seed
+    let mut tampered_proof = proof;
+    let mut commitment_bytes = tampered_proof.commitments.to_bytes();
+    commitment_bytes[2] ^= 1;
+    tampered_proof.commitments =
+        Commitments::read_from(&mut SliceReader::new(&commitment_bytes)).unwrap();
+    let (verified, verifier_log) = winterfell::verify_with_transcript_log::<
+        FibAir<BaseElement>,
+        Blake3_256,
+    >(tampered_proof, result);
+    assert!(verified.is_err());
+    assert_eq!(Some(1), prover_log.first_divergence(&verifier_log));
+    let (expected, actual) = (&prover_log.entries()[1], &verifier_log.entries()[1]);
+    assert_eq!(expected.label, actual.label);
+    assert_ne!(expected.absorbed, actual.absorbed);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure that a proof generated with hash function `H` records `H` in its context and is
+/// accepted by [winterfell::verify_auto()] only against the correct public inputs.
+fn check_auto_verification<H>()
+where
+    H: ElementHasher + Sync,
+    H::BaseField: ExtensibleField<2> + ExtensibleField<3> + AutoHashField,
+{
+    // 64-bit fields require an extension field to meet the security target of the proof options
+    let (proof, result) = prove_fib::<H>(16, build_proof_options(true));
+    assert_eq!(H::ID, proof.context.hash_fn());
+    assert_eq!(
+        Ok(()),
+        winterfell::verify_auto::<FibAir<H::BaseField>>(proof.clone(), result)
+    );
+    assert!(
+        winterfell::verify_auto::<FibAir<H::BaseField>>(proof, result + H::BaseField::ONE).is_err()
+    );
+}
+
+/// A prover which seeds the public coin with the digest of serialized public inputs.
+struct DigestSeededFibProver(FibProver<Blake3_256>);
+
+impl Prover for DigestSeededFibProver {
+    type BaseField = BaseElement;
+    type Air = FibAir<BaseElement>;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> BaseElement {
+        self.0.get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.0.options()
+    }
+
+    fn seeds_with_pub_inputs_digest(&self) -> bool {
+        true
+    }
+}
@@ -11,7 +11,7 @@ use crypto::RandomCoinError;
 // ================================================================================================
 
 /// Defines errors which can occur during FRI proof verification.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VerifierError {
     /// Attempt to draw a random value from a public coin failed.
     PublicCoinError(RandomCoinError),
@@ -24,6 +24,26 @@ pub enum VerifierError {
     LayerCommitmentMismatch,
     /// Degree-respecting projection was not performed correctly at one of the layers.
     InvalidLayerFolding(usize),
+    /// A FRI layer was opened at a position different from the position obtained by folding
+    /// the positions queried at the first layer.
+    FriPositionMismatch {
+        /// Index of the layer.
+        layer: usize,
+        /// Position obtained by folding the positions queried at the first layer.
+        expected: usize,
+        /// Position at which the layer was opened.
+        actual: usize,
+    },
+    /// The number of positions at which a FRI layer was opened is not equal to the number of
+    /// distinct positions obtained by folding the positions queried at the first layer.
+    FriPositionCountMismatch {
+        /// Index of the layer.
+        layer: usize,
+        /// Number of distinct positions obtained by folding the first layer positions.
+        expected: usize,
+        /// Number of positions at which the layer was opened.
+        actual: usize,
+    },
     /// FRI remainder did not match the commitment.
     RemainderCommitmentMismatch,
     /// Degree-respecting projection was not performed correctly at the last layer.
@@ -55,6 +75,12 @@ impl fmt::Display for VerifierError {
             Self::InvalidLayerFolding(layer) => {
                 write!(f, "degree-respecting projection is not consistent at layer {layer}")
             }
+            Self::FriPositionMismatch { layer, expected, actual } => {
+                write!(f, "FRI layer {layer} was opened at position {actual}, but position {expected} was expected")
+            }
+            Self::FriPositionCountMismatch { layer, expected, actual } => {
+                write!(f, "FRI layer {layer} was opened at {actual} positions, but {expected} positions were expected")
+            }
             Self::RemainderCommitmentMismatch => {
                 write!(f, "FRI remainder did not match the commitment")
             }
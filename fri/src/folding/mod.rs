@@ -11,12 +11,13 @@
 #[cfg(feature = "concurrent")]
 use utils::iterators::*;
 
+use core::ops::Deref;
 use math::{
     batch_inversion,
     fft::{get_inv_twiddles, serial_fft},
     get_power_series_with_offset, polynom, FieldElement, StarkField,
 };
-use utils::{collections::Vec, iter_mut, uninit_vector};
+use utils::{collections::Vec, iter_mut};
 
 // DEGREE-RESPECTING PROJECTION
 // ================================================================================================
@@ -88,16 +89,43 @@ where
     B: StarkField,
     E: FieldElement<BaseField = B>,
 {
-    // build offset inverses and twiddles used during polynomial interpolation
     let inv_offsets = get_inv_offsets(values.len(), domain_offset, N);
+    apply_drp_with_inv_offsets(values, &inv_offsets, alpha)
+}
+
+/// Applies degree-respecting projection to evaluations of a polynomial using pre-computed
+/// inverses of domain offsets.
+///
+/// This is the same as [apply_drp()], but `inv_offsets` must contain the values returned by
+/// [get_inv_offsets()] for the domain of the evaluations.
+pub(crate) fn apply_drp_with_inv_offsets<B, E, const N: usize>(
+    values: &[[E; N]],
+    inv_offsets: &[B],
+    alpha: E,
+) -> Vec<E>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+{
+    debug_assert_eq!(values.len(), inv_offsets.len());
+
+    // build twiddles used during polynomial interpolation
     let inv_twiddles = get_inv_twiddles::<B>(N);
     let len_offset = E::inv((N as u64).into());
 
-    let mut result = unsafe { uninit_vector(values.len()) };
-    iter_mut!(result)
+    // fold values in chunks sized so that values folded within a chunk fit into cache
+    #[cfg(feature = "concurrent")]
+    let chunk_size = get_chunk_size(values.len(), N * E::ELEMENT_BYTES, cache_size_hint());
+
+    #[cfg(not(feature = "safe_only"))]
+    let mut result = unsafe { utils::uninit_vector(values.len()) };
+    #[cfg(feature = "safe_only")]
+    let mut result = vec![E::ZERO; values.len()];
+
+    iter_mut!(result, chunk_size)
         .zip(values)
         .zip(inv_offsets)
-        .for_each(|((result, values), domain_offset)| {
+        .for_each(|((result, values), &domain_offset)| {
             // interpolate the values into a polynomial; this is similar to interpolation with
             // offset implemented in math::fft module
             let mut poly = *values;
@@ -161,24 +189,41 @@ pub fn fold_positions(
     source_domain_size: usize,
     folding_factor: usize,
 ) -> Vec<usize> {
-    let target_domain_size = source_domain_size / folding_factor;
+    let mut result = Vec::with_capacity(positions.len());
+    fold_positions_into(positions, source_domain_size, folding_factor, &mut result);
+    result
+}
 
-    let mut result = Vec::new();
+/// Maps positions in the source domain to positions in the folded domain in the same way as
+/// [fold_positions()], appending the folded positions to `result`.
+pub(crate) fn fold_positions_into<P>(
+    positions: &[usize],
+    source_domain_size: usize,
+    folding_factor: usize,
+    result: &mut P,
+) where
+    P: Extend<usize> + Deref<Target = [usize]>,
+{
+    let target_domain_size = source_domain_size / folding_factor;
     for position in positions {
         let position = position % target_domain_size;
         // make sure we don't record duplicated values
         if !result.contains(&position) {
-            result.push(position);
+            result.extend(Some(position));
         }
     }
-
-    result
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
 
-fn get_inv_offsets<B>(domain_size: usize, domain_offset: B, folding_factor: usize) -> Vec<B>
+/// Returns inverses of the offsets used by degree-respecting projection of evaluations which,
+/// after being folded by `folding_factor`, span a domain of `domain_size` elements.
+pub(crate) fn get_inv_offsets<B>(
+    domain_size: usize,
+    domain_offset: B,
+    folding_factor: usize,
+) -> Vec<B>
 where
     B: StarkField,
 {
@@ -62,6 +62,7 @@
 //! * Swastik Kooparty's [talk on DEEP-FRI](https://www.youtube.com/watch?v=txo_kPSn59Y&list=PLcIyXLwiPilWvjvNkhMn283LV370Pk5CT&index=6)
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(all(feature = "safe_only", not(test)), forbid(unsafe_code))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -21,9 +21,10 @@ use utils::{
 /// [FriVerifier](crate::FriVerifier) via [VerifierChannel](crate::VerifierChannel) interface.
 ///
 /// A proof consists of zero or more layers and a remainder. Each layer contains a set of
-/// polynomial evaluations at positions queried by the verifier as well as Merkle authentication
-/// paths for these evaluations (the Merkle paths are compressed into a batch Merkle proof). The
-/// remainder is a list of field elements.
+/// polynomial evaluations at positions queried by the verifier, the positions for which these
+/// evaluations were opened, as well as Merkle authentication paths for these evaluations (the
+/// Merkle paths are compressed into a batch Merkle proof). The remainder is a list of field
+/// elements.
 ///
 /// All values in a proof are stored as vectors of bytes. Thus, the values must be parsed before
 /// they can be returned to the user. To do this, [parse_layers()](FriProof::parse_layers())
@@ -96,17 +97,22 @@ impl FriProof {
 
     /// Returns the size of this proof in bytes.
     pub fn size(&self) -> usize {
-        // +1 for number of layers, +1 for remainder length, +1 for number of partitions
+        // +1 for number of layers, +2 for remainder length, +1 for number of partitions
         self.layers
             .iter()
-            .fold(self.remainder.len() + 3, |acc, layer| acc + layer.size())
+            .fold(self.remainder.len() + 4, |acc, layer| acc + layer.size())
+    }
+
+    /// Returns the sizes of the layers of this proof in bytes.
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        self.layers.iter().map(|layer| layer.size()).collect()
     }
 
     // PARSING
     // --------------------------------------------------------------------------------------------
 
-    /// Decomposes this proof into vectors of query values for each layer and corresponding Merkle
-    /// authentication paths for each query (grouped into batch Merkle proofs).
+    /// Decomposes this proof into vectors of opened positions and query values for each layer and
+    /// corresponding Merkle authentication paths for each query (grouped into batch Merkle proofs).
     ///
     /// # Panics
     /// Panics if:
@@ -122,7 +128,7 @@ impl FriProof {
         self,
         mut domain_size: usize,
         folding_factor: usize,
-    ) -> Result<(Vec<Vec<E>>, Vec<BatchMerkleProof<H>>), DeserializationError>
+    ) -> Result<(Vec<Vec<usize>>, Vec<Vec<E>>, Vec<BatchMerkleProof<H>>), DeserializationError>
     where
         E: FieldElement,
         H: ElementHasher<BaseField = E::BaseField>,
@@ -137,6 +143,7 @@ impl FriProof {
         );
         assert!(folding_factor > 1, "folding factor must be greater than 1");
 
+        let mut layer_positions = Vec::new();
         let mut layer_proofs = Vec::new();
         let mut layer_queries = Vec::new();
         let num_remainder_elements = self.num_remainder_elements::<E>();
@@ -144,9 +151,10 @@ impl FriProof {
         // parse all layers
         for (i, layer) in self.layers.into_iter().enumerate() {
             domain_size /= folding_factor;
-            let (qv, mp) = layer.parse(domain_size, folding_factor).map_err(|err| {
+            let (qp, qv, mp) = layer.parse(domain_size, folding_factor).map_err(|err| {
                 DeserializationError::InvalidValue(format!("failed to parse FRI layer {i}: {err}"))
             })?;
+            layer_positions.push(qp);
             layer_proofs.push(mp);
             layer_queries.push(qv);
         }
@@ -158,7 +166,7 @@ impl FriProof {
             )));
         }
 
-        Ok((layer_queries, layer_proofs))
+        Ok((layer_positions, layer_queries, layer_proofs))
     }
 
     /// Returns a vector of remainder values (last FRI layer) parsed from this proof.
@@ -222,8 +230,15 @@ impl Deserializable for FriProof {
         let num_remainder_bytes = source.read_u16()? as usize;
         let remainder = source.read_u8_vec(num_remainder_bytes)?;
 
-        // read number of partitions
+        // read number of partitions (which was stored as a power of two); the number cannot
+        // be greater than the largest power of two representable as usize on this platform
         let num_partitions = source.read_u8()?;
+        if num_partitions as u32 >= usize::BITS {
+            return Err(DeserializationError::InvalidValue(format!(
+                "number of partitions cannot be greater than 2^{}, but was 2^{num_partitions}",
+                usize::BITS - 1
+            )));
+        }
 
         Ok(FriProof {
             layers,
@@ -238,6 +253,7 @@ impl Deserializable for FriProof {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct FriProofLayer {
+    positions: Vec<usize>,
     values: Vec<u8>,
     paths: Vec<u8>,
 }
@@ -245,16 +261,25 @@ pub struct FriProofLayer {
 impl FriProofLayer {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
-    /// Creates a new proof layer from the specified query values and the corresponding Merkle
-    /// paths aggregated into a single batch Merkle proof.
+    /// Creates a new proof layer from the specified positions in the folded domain, query values
+    /// at these positions, and the corresponding Merkle paths aggregated into a single batch
+    /// Merkle proof.
     ///
     /// # Panics
-    /// Panics if `query_values` is an empty slice.
+    /// Panics if:
+    /// * `query_values` is an empty slice.
+    /// * The number of positions is not equal to the number of query values.
     pub(crate) fn new<H: Hasher, E: FieldElement, const N: usize>(
+        positions: &[usize],
         query_values: Vec<[E; N]>,
         merkle_proof: BatchMerkleProof<H>,
     ) -> Self {
         assert!(!query_values.is_empty(), "query values cannot be empty");
+        assert_eq!(
+            positions.len(),
+            query_values.len(),
+            "number of positions must be equal to the number of query values"
+        );
 
         // TODO: add debug check that values actually hash into the leaf nodes of the batch proof
 
@@ -262,6 +287,7 @@ impl FriProofLayer {
         // we care about internal nodes only because leaf nodes can be reconstructed from hashes
         // of query values
         FriProofLayer {
+            positions: positions.to_vec(),
             values: query_values.to_bytes(),
             paths: merkle_proof.serialize_nodes(),
         }
@@ -272,25 +298,30 @@ impl FriProofLayer {
 
     /// Returns the size of this proof layer in bytes.
     pub fn size(&self) -> usize {
-        // +4 for length of values, +4 for length of paths
-        self.values.len() + 4 + self.paths.len() + 4
+        // +1 for number of positions, +4 for each position, +4 for length of values, +4 for
+        // length of paths
+        self.positions.len() * 4 + 1 + self.values.len() + 4 + self.paths.len() + 4
     }
 
     // PARSING
     // --------------------------------------------------------------------------------------------
-    /// Decomposes this layer into a combination of query values and corresponding Merkle
-    /// authentication paths (grouped together into a single batch Merkle proof).
+    /// Decomposes this layer into a combination of opened positions, query values and
+    /// corresponding Merkle authentication paths (grouped together into a single batch Merkle
+    /// proof).
     ///
     /// # Errors
     /// Returns an error if:
     /// * This layer does not contain at least one query.
+    /// * The number of opened positions is not equal to the number of queries, or any of the
+    ///   positions is outside of the domain of the specified size.
     /// * Parsing of any of the query values or the corresponding Merkle paths fails.
     /// * Not all bytes have been consumed while parsing this layer.
+    #[allow(clippy::type_complexity)]
     pub fn parse<H, E>(
         self,
         domain_size: usize,
         folding_factor: usize,
-    ) -> Result<(Vec<E>, BatchMerkleProof<H>), DeserializationError>
+    ) -> Result<(Vec<usize>, Vec<E>, BatchMerkleProof<H>), DeserializationError>
     where
         E: FieldElement,
         H: ElementHasher<BaseField = E::BaseField>,
@@ -310,17 +341,26 @@ impl FriProofLayer {
                 "a FRI layer must contain at least one query".to_string(),
             ));
         }
-        let mut hashed_queries = vec![H::Digest::default(); num_queries];
-        let mut query_values = Vec::with_capacity(num_queries * folding_factor);
+        if self.positions.len() != num_queries {
+            return Err(DeserializationError::InvalidValue(format!(
+                "number of opened positions ({}) is not equal to the number of queries ({num_queries})",
+                self.positions.len(),
+            )));
+        }
+        if let Some(position) = self.positions.iter().find(|&&p| p >= domain_size) {
+            return Err(DeserializationError::InvalidValue(format!(
+                "opened position {position} is outside of the domain of size {domain_size}"
+            )));
+        }
 
-        // read bytes corresponding to each query, convert them into field elements,
-        // and also hash them to build leaf nodes of the batch Merkle proof
+        // read bytes corresponding to all queries at once, convert them into field elements,
+        // and then hash values of each query to build leaf nodes of the batch Merkle proof
         let mut reader = SliceReader::new(&self.values);
-        for query_hash in hashed_queries.iter_mut() {
-            let mut qe = E::read_batch_from(&mut reader, folding_factor)?;
-            *query_hash = H::hash_elements(&qe);
-            query_values.append(&mut qe);
-        }
+        let query_values = E::read_batch_from(&mut reader, num_queries * folding_factor)?;
+        let hashed_queries = query_values
+            .chunks(folding_factor)
+            .map(H::hash_elements)
+            .collect::<Vec<_>>();
         if reader.has_more_bytes() {
             return Err(DeserializationError::UnconsumedBytes);
         }
@@ -333,7 +373,7 @@ impl FriProofLayer {
             return Err(DeserializationError::UnconsumedBytes);
         }
 
-        Ok((query_values, merkle_proof))
+        Ok((self.positions, query_values, merkle_proof))
     }
 }
 
@@ -343,6 +383,12 @@ impl FriProofLayer {
 impl Serializable for FriProofLayer {
     /// Serializes this proof layer and writes the resulting bytes to the specified `target`.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        // write opened positions
+        target.write_u8(self.positions.len() as u8);
+        for &position in self.positions.iter() {
+            target.write_u32(position as u32);
+        }
+
         // write value bytes
         target.write_u32(self.values.len() as u32);
         target.write_u8_slice(&self.values);
@@ -359,6 +405,13 @@ impl Deserializable for FriProofLayer {
     /// # Errors
     /// Returns an error if a valid layer could not be read from the specified source.
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        // read opened positions
+        let num_positions = source.read_u8()? as usize;
+        let mut positions = Vec::with_capacity(num_positions);
+        for _ in 0..num_positions {
+            positions.push(source.read_u32()? as usize);
+        }
+
         // read values
         let num_value_bytes = source.read_u32()?;
         if num_value_bytes == 0 {
@@ -372,6 +425,10 @@ impl Deserializable for FriProofLayer {
         let num_paths_bytes = source.read_u32()?;
         let paths = source.read_u8_vec(num_paths_bytes as usize)?;
 
-        Ok(FriProofLayer { values, paths })
+        Ok(FriProofLayer {
+            positions,
+            values,
+            paths,
+        })
     }
 }
@@ -4,7 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use core::marker::PhantomData;
-use crypto::{Hasher, RandomCoin};
+use crypto::{DefaultRandomCoin, Hasher};
 use math::{FieldElement, StarkField};
 use utils::collections::Vec;
 
@@ -55,7 +55,7 @@ pub trait ProverChannel<E: FieldElement> {
 /// Though this implementation is intended primarily for testing purposes, it can be used in
 /// production use cases as well.
 pub struct DefaultProverChannel<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> {
-    public_coin: RandomCoin<B, H>,
+    public_coin: DefaultRandomCoin<B, H>,
     commitments: Vec<H::Digest>,
     domain_size: usize,
     num_queries: usize,
@@ -83,7 +83,7 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> DefaultProverChan
             "number of queries must be greater than zero"
         );
         DefaultProverChannel {
-            public_coin: RandomCoin::new(&[]),
+            public_coin: DefaultRandomCoin::new(&[]),
             commitments: Vec::new(),
             domain_size,
             num_queries,
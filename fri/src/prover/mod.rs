@@ -4,20 +4,23 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::{
-    folding::{apply_drp, fold_positions},
+    folding::{apply_drp_with_inv_offsets, fold_positions, get_inv_offsets},
     proof::{FriProof, FriProofLayer},
-    utils::hash_values,
+    utils::{hash_remainder_coefficients, hash_values},
     FriOptions,
 };
-use core::marker::PhantomData;
+use core::{marker::PhantomData, mem};
 use crypto::{ElementHasher, Hasher, MerkleTree};
 use math::{FieldElement, StarkField};
-use utils::{collections::Vec, flatten_vector_elements, group_slice_elements, transpose_slice};
+use utils::{
+    collections::Vec, flatten_vector_elements, group_slice_elements, transpose_slice, ByteReader,
+    ByteWriter, Deserializable, DeserializationError, Serializable,
+};
 
 mod channel;
 pub use channel::{DefaultProverChannel, ProverChannel};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // TYPES AND INTERFACES
@@ -95,6 +98,7 @@ where
     options: FriOptions,
     layers: Vec<FriLayer<B, E, H>>,
     remainder: FriRemainder<E>,
+    inv_offsets: Vec<Vec<B>>,
     _channel: PhantomData<C>,
 }
 
@@ -124,10 +128,34 @@ where
             options,
             layers: Vec::new(),
             remainder: FriRemainder(vec![]),
+            inv_offsets: Vec::new(),
             _channel: PhantomData,
         }
     }
 
+    /// Returns a new FRI prover instantiated with the provided `options` for evaluations over a
+    /// domain of `domain_size` elements.
+    ///
+    /// Domain offsets used in the degree-respecting projection of every FRI layer are computed
+    /// here rather than during the commit phase. These values do not depend on the evaluations or
+    /// on the randomness drawn from the channel, and thus, the prover can be instantiated while
+    /// the evaluations are still being committed to; the generated proofs are the same as for a
+    /// prover instantiated with [FriProver::new()].
+    pub fn with_domain_size(options: FriOptions, domain_size: usize) -> Self {
+        let folding_factor = options.folding_factor();
+        let domain_offset = options.domain_offset();
+        let mut inv_offsets = Vec::new();
+        let mut layer_size = domain_size;
+        for _ in 0..options.num_fri_layers(domain_size) {
+            layer_size /= folding_factor;
+            inv_offsets.push(get_inv_offsets(layer_size, domain_offset, folding_factor));
+        }
+
+        let mut prover = Self::new(options);
+        prover.inv_offsets = inv_offsets;
+        prover
+    }
+
     // ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -147,6 +175,22 @@ where
         self.layers.len()
     }
 
+    /// Returns a digest of the coefficients of the remainder polynomial computed during the last
+    /// execution of the [build_remainder()](FriProver::build_remainder()) method.
+    ///
+    /// See [hash_remainder_coefficients()](crate::utils::hash_remainder_coefficients) for how the
+    /// digest is computed.
+    ///
+    /// # Panics
+    /// Panics if the remainder has not been built yet.
+    pub fn remainder_digest(&self) -> H::Digest {
+        assert!(
+            !self.remainder.0.is_empty(),
+            "FRI remainder has not been built yet"
+        );
+        hash_remainder_coefficients::<H, E>(&self.remainder.0)
+    }
+
     /// Clears a vector of internally stored layers.
     pub fn reset(&mut self) {
         self.layers.clear();
@@ -177,15 +221,33 @@ where
         // reduce the degree by folding_factor at each iteration until the remaining polynomial
         // is small enough
         for _ in 0..self.options.num_fri_layers(evaluations.len()) {
-            match self.folding_factor() {
-                2 => self.build_layer::<2>(channel, &mut evaluations),
-                4 => self.build_layer::<4>(channel, &mut evaluations),
-                8 => self.build_layer::<8>(channel, &mut evaluations),
-                16 => self.build_layer::<16>(channel, &mut evaluations),
-                _ => unimplemented!("folding factor {} is not supported", self.folding_factor()),
-            }
+            self.build_layer(channel, &mut evaluations);
+        }
+
+        self.build_remainder(channel, evaluations);
+    }
+
+    /// Executes a single step of the commit phase of the FRI protocol.
+    ///
+    /// The `evaluations` are committed to as the next FRI layer, and are then replaced with the
+    /// result of applying the DRP to them. This, together with
+    /// [build_remainder()](FriProver::build_remainder()), can be used instead of
+    /// [build_layers()](FriProver::build_layers()) when the commit phase needs to be interrupted
+    /// between layers; the number of layers to build is defined by
+    /// [FriOptions::num_fri_layers()].
+    pub fn build_layer(&mut self, channel: &mut C, evaluations: &mut Vec<E>) {
+        match self.folding_factor() {
+            2 => self.build_folded_layer::<2>(channel, evaluations),
+            4 => self.build_folded_layer::<4>(channel, evaluations),
+            8 => self.build_folded_layer::<8>(channel, evaluations),
+            16 => self.build_folded_layer::<16>(channel, evaluations),
+            _ => unimplemented!("folding factor {} is not supported", self.folding_factor()),
         }
+    }
 
+    /// Completes the commit phase of the FRI protocol by committing to the `evaluations` of the
+    /// remainder.
+    pub fn build_remainder(&mut self, channel: &mut C, mut evaluations: Vec<E>) {
         self.set_remainder(channel, &mut evaluations);
 
         // make sure remainder length does not exceed max allowed value
@@ -200,7 +262,7 @@ where
 
     /// Builds a single FRI layer by first committing to the `evaluations`, then drawing a random
     /// alpha from the channel and use it to perform degree-respecting projection.
-    fn build_layer<const N: usize>(&mut self, channel: &mut C, evaluations: &mut Vec<E>) {
+    fn build_folded_layer<const N: usize>(&mut self, channel: &mut C, evaluations: &mut Vec<E>) {
         // commit to the evaluations at the current layer; we do this by first transposing the
         // evaluations into a matrix of N columns, and then building a Merkle tree from the
         // rows of this matrix; we do this so that we could de-commit to N values with a single
@@ -214,7 +276,13 @@ where
         // draw a pseudo-random coefficient from the channel, and use it in degree-respecting
         // projection to reduce the degree of evaluations by N
         let alpha = channel.draw_fri_alpha();
-        *evaluations = apply_drp(&transposed_evaluations, self.domain_offset(), alpha);
+        let inv_offsets = match self.inv_offsets.get_mut(self.layers.len()) {
+            Some(inv_offsets) if inv_offsets.len() == transposed_evaluations.len() => {
+                mem::take(inv_offsets)
+            }
+            _ => get_inv_offsets(transposed_evaluations.len(), self.domain_offset(), N),
+        };
+        *evaluations = apply_drp_with_inv_offsets(&transposed_evaluations, &inv_offsets, alpha);
         self.layers.push(FriLayer {
             tree: evaluation_tree,
             evaluations: flatten_vector_elements(transposed_evaluations),
@@ -230,6 +298,61 @@ where
         self.remainder = FriRemainder(evaluations.to_vec());
     }
 
+    // SERIALIZATION / DESERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes FRI layers built so far (excluding the remainder) and writes the resulting bytes
+    /// into the `target`.
+    ///
+    /// For each layer, the evaluations and leaves of the layer's Merkle tree are written; internal
+    /// nodes of the trees are not serialized.
+    pub fn write_layers_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.layers.len() as u32);
+        for layer in self.layers.iter() {
+            layer.tree.write_into(target);
+            target.write_u32(layer.evaluations.len() as u32);
+            E::write_batch_into(&layer.evaluations, target);
+        }
+    }
+
+    /// Reads FRI layers from the specified `source` and sets them as the layers of this prover;
+    /// the commit phase can then be continued via [build_layer()](FriProver::build_layer()).
+    ///
+    /// # Errors
+    /// Returns an error if valid FRI layers could not be read from the `source`.
+    ///
+    /// # Panics
+    /// Panics if the prover state is dirty (the vector of layers is not empty).
+    pub fn read_layers_from<R: ByteReader>(
+        &mut self,
+        source: &mut R,
+    ) -> Result<(), DeserializationError> {
+        assert!(
+            self.layers.is_empty(),
+            "a prior proof generation request has not been completed yet"
+        );
+
+        let num_layers = source.read_u32()? as usize;
+        for _ in 0..num_layers {
+            let tree = MerkleTree::read_from(source)?;
+            let num_evaluations = source.read_u32()? as usize;
+            let evaluations = E::read_batch_from(source, num_evaluations)?;
+            if evaluations.len() != tree.leaves().len() * self.folding_factor() {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "FRI layer with {} evaluations cannot be committed to by a tree with {} leaves",
+                    evaluations.len(),
+                    tree.leaves().len()
+                )));
+            }
+            self.layers.push(FriLayer {
+                tree,
+                evaluations,
+                _base_field: PhantomData,
+            });
+        }
+        Ok(())
+    }
+
     // QUERY PHASE
     // --------------------------------------------------------------------------------------------
     /// Executes query phase of FRI protocol.
@@ -307,5 +430,5 @@ fn query_layer<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher, const N
         queried_values.push(evaluations[position]);
     }
 
-    FriProofLayer::new(queried_values, proof)
+    FriProofLayer::new(positions, queried_values, proof)
 }
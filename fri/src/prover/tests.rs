@@ -3,12 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{DefaultProverChannel, FriProver};
+use super::{query_layer, DefaultProverChannel, FriProver};
 use crate::{
+    folding::fold_positions,
     verifier::{DefaultVerifierChannel, FriVerifier},
     FriOptions, FriProof, VerifierError,
 };
-use crypto::{hashers::Blake3_256, Hasher, RandomCoin};
+use crypto::{hashers::Blake3_256, DefaultRandomCoin, Hasher};
 use math::{fft, fields::f128::BaseElement, FieldElement};
 use utils::{collections::Vec, Deserializable, Serializable, SliceReader};
 
@@ -45,9 +46,254 @@ fn fri_folding_4() {
     )
 }
 
+// INTERRUPTED COMMIT PHASE TEST
+// ================================================================================================
+
+#[test]
+fn fri_proof_size() {
+    let trace_length = 1 << 10;
+    let options = FriOptions::new(8, 4, 16);
+    let evaluations = build_evaluations(trace_length, options.blowup_factor());
+
+    let mut channel = build_prover_channel(trace_length, &options);
+    let mut prover = FriProver::new(options.clone());
+    prover.build_layers(&mut channel, evaluations);
+    let positions = channel.draw_query_positions();
+    let proof = prover.build_proof(&positions);
+
+    assert_eq!(proof.to_bytes().len(), proof.size());
+    assert_eq!(proof.num_layers(), proof.layer_sizes().len());
+}
+
+#[test]
+fn fri_resume_from_layers() {
+    let trace_length = 1 << 10;
+    let options = FriOptions::new(8, 4, 16);
+    let evaluations = build_evaluations(trace_length, options.blowup_factor());
+    let num_layers = options.num_fri_layers(evaluations.len());
+
+    // build the proof without interruptions
+    let mut channel = build_prover_channel(trace_length, &options);
+    let mut prover = FriProver::new(options.clone());
+    prover.build_layers(&mut channel, evaluations.clone());
+    let positions = channel.draw_query_positions();
+    let expected = prover.build_proof(&positions).to_bytes();
+
+    // build the first two layers, serialize them, and continue with a new prover instance
+    let mut channel = build_prover_channel(trace_length, &options);
+    let mut prover = FriProver::new(options.clone());
+    let mut layer_evaluations = evaluations;
+    for _ in 0..2 {
+        prover.build_layer(&mut channel, &mut layer_evaluations);
+    }
+    let mut layer_bytes = Vec::new();
+    prover.write_layers_into(&mut layer_bytes);
+
+    let mut prover = FriProver::new(options.clone());
+    prover
+        .read_layers_from(&mut SliceReader::new(&layer_bytes))
+        .unwrap();
+    for _ in 2..num_layers {
+        prover.build_layer(&mut channel, &mut layer_evaluations);
+    }
+    prover.build_remainder(&mut channel, layer_evaluations);
+    assert_eq!(num_layers, prover.num_layers());
+
+    let positions = channel.draw_query_positions();
+    assert_eq!(expected, prover.build_proof(&positions).to_bytes());
+}
+
+#[test]
+fn fri_precomputed_domain() {
+    let trace_length = 1 << 10;
+    for folding_factor in [2, 4, 8, 16] {
+        let options = FriOptions::new(8, folding_factor, 32);
+        let evaluations = build_evaluations(trace_length, options.blowup_factor());
+
+        let mut channel = build_prover_channel(trace_length, &options);
+        let mut prover = FriProver::new(options.clone());
+        prover.build_layers(&mut channel, evaluations.clone());
+        let positions = channel.draw_query_positions();
+        let expected = prover.build_proof(&positions).to_bytes();
+
+        // a prover with pre-computed domain offsets must generate exactly the same proof
+        let mut channel = build_prover_channel(trace_length, &options);
+        let mut prover = FriProver::with_domain_size(options.clone(), evaluations.len());
+        prover.build_layers(&mut channel, evaluations);
+        let positions = channel.draw_query_positions();
+        assert_eq!(expected, prover.build_proof(&positions).to_bytes());
+    }
+}
+
+// ADVERSARIAL QUERY TESTS
+// ================================================================================================
+
+#[test]
+fn fri_reordered_layer_records() {
+    let (prover, commitments, evaluations, positions) = build_fri_prover();
+    let expected = get_folded_positions(&prover, &positions, 1);
+    assert!(expected.len() > 1);
+
+    // swap the first two records of the second layer; the records remain valid openings of
+    // the layer commitment, but they are no longer in the order implied by the query positions
+    let proof = build_tampered_proof(&prover, &positions, |depth, positions| {
+        if depth == 1 {
+            positions.swap(0, 1);
+        }
+    });
+    assert_eq!(
+        Err(VerifierError::FriPositionMismatch {
+            layer: 1,
+            expected: expected[0],
+            actual: expected[1],
+        }),
+        verify_tampered_proof(
+            proof,
+            commitments,
+            &evaluations,
+            &positions,
+            &prover.options
+        )
+    );
+}
+
+#[test]
+fn fri_substituted_layer_record() {
+    let (prover, commitments, evaluations, positions) = build_fri_prover();
+    let expected = get_folded_positions(&prover, &positions, 0);
+    let domain_size = prover.layers[0].evaluations.len() / prover.options.folding_factor();
+    let substitute = (0..domain_size)
+        .find(|position| !expected.contains(position))
+        .unwrap();
+
+    // replace the first record of the first layer with a valid opening at another position
+    let proof = build_tampered_proof(&prover, &positions, |depth, positions| {
+        if depth == 0 {
+            positions[0] = substitute;
+        }
+    });
+    assert_eq!(
+        Err(VerifierError::FriPositionMismatch {
+            layer: 0,
+            expected: expected[0],
+            actual: substitute,
+        }),
+        verify_tampered_proof(
+            proof,
+            commitments,
+            &evaluations,
+            &positions,
+            &prover.options
+        )
+    );
+}
+
+#[test]
+fn fri_missing_layer_record() {
+    let (prover, commitments, evaluations, positions) = build_fri_prover();
+    let expected = get_folded_positions(&prover, &positions, 1);
+
+    // drop the last record of the second layer
+    let proof = build_tampered_proof(&prover, &positions, |depth, positions| {
+        if depth == 1 {
+            positions.pop();
+        }
+    });
+    assert_eq!(
+        Err(VerifierError::FriPositionCountMismatch {
+            layer: 1,
+            expected: expected.len(),
+            actual: expected.len() - 1,
+        }),
+        verify_tampered_proof(
+            proof,
+            commitments,
+            &evaluations,
+            &positions,
+            &prover.options
+        )
+    );
+}
+
 // TEST UTILS
 // ================================================================================================
 
+type TestProver = FriProver<
+    BaseElement,
+    BaseElement,
+    DefaultProverChannel<BaseElement, BaseElement, Blake3>,
+    Blake3,
+>;
+
+/// Builds FRI layers for a polynomial of degree 2^10 - 1 and returns the prover together with
+/// layer commitments, evaluations of the polynomial and query positions.
+fn build_fri_prover() -> (
+    TestProver,
+    Vec<<Blake3 as Hasher>::Digest>,
+    Vec<BaseElement>,
+    Vec<usize>,
+) {
+    let trace_length = 1 << 10;
+    let options = FriOptions::new(8, 4, 16);
+    let evaluations = build_evaluations(trace_length, options.blowup_factor());
+
+    let mut channel = build_prover_channel(trace_length, &options);
+    let mut prover = FriProver::new(options);
+    prover.build_layers(&mut channel, evaluations.clone());
+    let positions = channel.draw_query_positions();
+    let commitments = channel.layer_commitments().to_vec();
+    (prover, commitments, evaluations, positions)
+}
+
+/// Returns positions at which the FRI layer at the specified depth is opened by an honest prover.
+fn get_folded_positions(prover: &TestProver, positions: &[usize], depth: usize) -> Vec<usize> {
+    let folding_factor = prover.options.folding_factor();
+    let mut positions = positions.to_vec();
+    let mut domain_size = prover.layers[0].evaluations.len();
+    for _ in 0..=depth {
+        positions = fold_positions(&positions, domain_size, folding_factor);
+        domain_size /= folding_factor;
+    }
+    positions
+}
+
+/// Builds a FRI proof in the same way as the prover does, but lets `tamper` modify positions at
+/// which each layer is opened; all openings remain valid against layer commitments.
+fn build_tampered_proof<F>(prover: &TestProver, positions: &[usize], tamper: F) -> FriProof
+where
+    F: Fn(usize, &mut Vec<usize>),
+{
+    assert_eq!(4, prover.options.folding_factor());
+    let layers = (0..prover.layers.len())
+        .map(|depth| {
+            let mut positions = get_folded_positions(prover, positions, depth);
+            tamper(depth, &mut positions);
+            query_layer::<BaseElement, BaseElement, Blake3, 4>(&prover.layers[depth], &positions)
+        })
+        .collect();
+    FriProof::new(layers, prover.remainder.0.clone(), 1)
+}
+
+fn verify_tampered_proof(
+    proof: FriProof,
+    commitments: Vec<<Blake3 as Hasher>::Digest>,
+    evaluations: &[BaseElement],
+    positions: &[usize],
+    options: &FriOptions,
+) -> Result<(), VerifierError> {
+    let domain_size = evaluations.len();
+    let max_degree = domain_size / options.blowup_factor() - 1;
+    verify_proof(
+        proof,
+        commitments,
+        evaluations,
+        max_degree,
+        domain_size,
+        positions,
+        options,
+    )
+}
+
 pub fn build_prover_channel(
     trace_length: usize,
     options: &FriOptions,
@@ -92,7 +338,7 @@ pub fn verify_proof(
         options.folding_factor(),
     )
     .unwrap();
-    let mut coin = RandomCoin::<BaseElement, Blake3>::new(&[]);
+    let mut coin = DefaultRandomCoin::<BaseElement, Blake3>::new(&[]);
     let verifier = FriVerifier::new(&mut channel, &mut coin, options.clone(), max_degree)?;
     let queried_evaluations = positions
         .iter()
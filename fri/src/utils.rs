@@ -4,8 +4,8 @@
 // LICENSE file in the root directory of this source tree.
 
 use crypto::ElementHasher;
-use math::FieldElement;
-use utils::{collections::Vec, iter_mut, uninit_vector};
+use math::{fft, FieldElement};
+use utils::{collections::Vec, iter_mut};
 
 #[cfg(feature = "concurrent")]
 use utils::iterators::*;
@@ -26,7 +26,7 @@ pub fn map_positions_to_indexes(
     let target_domain_size = source_domain_size / folding_factor;
     let partition_size = target_domain_size / num_partitions;
 
-    let mut result = Vec::new();
+    let mut result = Vec::with_capacity(positions.len());
     for position in positions {
         let partition_idx = position % num_partitions;
         let local_idx = (position - partition_idx) / num_partitions;
@@ -43,9 +43,32 @@ where
     E: FieldElement,
     H: ElementHasher<BaseField = E::BaseField>,
 {
-    let mut result: Vec<H::Digest> = unsafe { uninit_vector(values.len()) };
+    #[cfg(not(feature = "safe_only"))]
+    let mut result: Vec<H::Digest> = unsafe { utils::uninit_vector(values.len()) };
+    #[cfg(feature = "safe_only")]
+    let mut result: Vec<H::Digest> = vec![H::Digest::default(); values.len()];
+
     iter_mut!(result, 1024).zip(values).for_each(|(r, v)| {
         *r = H::hash_elements(v);
     });
     result
 }
+
+/// Returns a digest of the coefficients of the polynomial implied by the `remainder` evaluations.
+///
+/// The polynomial is interpolated over the subgroup of the same size as the `remainder` (i.e.,
+/// the domain of the remainder is not shifted), and its coefficients are hashed in the order of
+/// increasing degree.
+///
+/// # Panics
+/// Panics if the length of the `remainder` is not a power of two.
+pub fn hash_remainder_coefficients<H, E>(remainder: &[E]) -> H::Digest
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+{
+    let mut coefficients = remainder.to_vec();
+    let inv_twiddles = fft::get_inv_twiddles::<E::BaseField>(coefficients.len());
+    fft::interpolate_poly(&mut coefficients, &inv_twiddles);
+    H::hash_elements(&coefficients)
+}
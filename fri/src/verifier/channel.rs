@@ -40,6 +40,14 @@ pub trait VerifierChannel<E: FieldElement> {
         &mut self,
     ) -> Vec<<<Self as VerifierChannel<E>>::Hasher as Hasher>::Digest>;
 
+    /// Reads and removes from the channel the positions at which the prover opened the next FRI
+    /// layer.
+    ///
+    /// The positions are specified in the folded domain of the layer (i.e., a position refers to a
+    /// single leaf of the layer's commitment Merkle tree), and are expected to be in the same
+    /// order as the queries and authentication paths for the layer.
+    fn take_next_fri_layer_positions(&mut self) -> Vec<usize>;
+
     /// Reads and removes from the channel evaluations of the polynomial at the queried positions
     /// for the next FRI layer.
     ///
@@ -127,6 +135,7 @@ pub trait VerifierChannel<E: FieldElement> {
 /// production use cases as well.
 pub struct DefaultVerifierChannel<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
     layer_commitments: Vec<H::Digest>,
+    layer_positions: Vec<Vec<usize>>,
     layer_proofs: Vec<BatchMerkleProof<H>>,
     layer_queries: Vec<Vec<E>>,
     remainder: Vec<E>,
@@ -151,11 +160,12 @@ where
         let num_partitions = proof.num_partitions();
 
         let remainder = proof.parse_remainder()?;
-        let (layer_queries, layer_proofs) =
+        let (layer_positions, layer_queries, layer_proofs) =
             proof.parse_layers::<H, E>(domain_size, folding_factor)?;
 
         Ok(DefaultVerifierChannel {
             layer_commitments,
+            layer_positions,
             layer_proofs,
             layer_queries,
             remainder,
@@ -179,6 +189,10 @@ where
         self.layer_commitments.drain(..).collect()
     }
 
+    fn take_next_fri_layer_positions(&mut self) -> Vec<usize> {
+        self.layer_positions.remove(0)
+    }
+
     fn take_next_fri_layer_proof(&mut self) -> BatchMerkleProof<H> {
         self.layer_proofs.remove(0)
     }
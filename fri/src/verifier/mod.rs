@@ -5,15 +5,37 @@
 
 //! Contains an implementation of FRI verifier and associated components.
 
-use crate::{folding::fold_positions, utils::map_positions_to_indexes, FriOptions, VerifierError};
-use core::{convert::TryInto, marker::PhantomData, mem};
+use crate::{
+    folding::fold_positions_into, utils::map_positions_to_indexes, FriOptions, VerifierError,
+};
+use core::{marker::PhantomData, mem};
 use crypto::{ElementHasher, RandomCoin};
 use math::{fft, log2, polynom, FieldElement, StarkField};
-use utils::collections::Vec;
+use utils::collections::{InlineVec, Vec};
 
 mod channel;
 pub use channel::{DefaultVerifierChannel, VerifierChannel};
 
+// CONSTANTS
+// ================================================================================================
+
+/// Number of query positions (and of evaluations at these positions) which the verifier keeps
+/// inline while checking FRI layers. This matches the maximum number of queries allowed by STARK
+/// proof options; proofs with more queries are still verified, but the positions of such proofs
+/// are moved to the heap.
+const MAX_INLINE_QUERIES: usize = 128;
+
+/// Number of FRI layers for which the verifier keeps the α values drawn during the commit phase
+/// inline; this covers all layers of proofs with folding factor 2 for domains of up to $2^{24}$
+/// elements.
+const MAX_INLINE_FRI_LAYERS: usize = 24;
+
+/// Query positions tracked by the verifier across FRI layers.
+type QueryPositions = InlineVec<usize, MAX_INLINE_QUERIES>;
+
+/// Evaluations at the query positions tracked by the verifier across FRI layers.
+type QueryEvaluations<E> = InlineVec<E, MAX_INLINE_QUERIES>;
+
 // FRI VERIFIER
 // ================================================================================================
 /// Implements the verifier component of the FRI protocol.
@@ -48,6 +70,8 @@ pub use channel::{DefaultVerifierChannel, VerifierChannel};
 /// the verifier sends a set of positions in the domain *D* to the prover, and the prover responds
 /// with polynomial evaluations at these positions (together with corresponding Merkle paths)
 /// across all FRI layers. The verifier then checks that:
+/// * At each layer, the evaluations were opened at the positions obtained by folding the queried
+///   positions by the folding factor as many times as there are layers above it.
 /// * The Merkle paths are valid against the layer commitments the verifier received during
 ///   the commit phase.
 /// * The evaluations are consistent across FRI layers (i.e., the degree-respecting projection
@@ -66,7 +90,7 @@ where
     domain_size: usize,
     domain_generator: B,
     layer_commitments: Vec<H::Digest>,
-    layer_alphas: Vec<E>,
+    layer_alphas: InlineVec<E, MAX_INLINE_FRI_LAYERS>,
     options: FriOptions,
     num_partitions: usize,
     _channel: PhantomData<C>,
@@ -88,7 +112,8 @@ where
     /// Creating a FRI verifier executes the commit phase of the FRI protocol from the verifier's
     /// perspective. Specifically, the verifier reads FRI layer commitments from the `channel`,
     /// and for each commitment, updates the `public_coin` with this commitment and then draws
-    /// a random value α from the coin.
+    /// a random value α from the coin; no α is drawn for the commitment to the remainder, as the
+    /// remainder is not folded.
     ///
     /// The verifier stores layer commitments and corresponding α values in its internal state,
     /// and, thus, an instance of FRI verifier can be used to verify only a single proof.
@@ -98,12 +123,15 @@ where
     /// * `max_poly_degree` is inconsistent with the number of FRI layers read from the channel
     ///   and `folding_factor` specified in the `options` parameter.
     /// * An error was encountered while drawing a random α value from the coin.
-    pub fn new(
+    pub fn new<R>(
         channel: &mut C,
-        public_coin: &mut RandomCoin<B, H>,
+        public_coin: &mut R,
         options: FriOptions,
         max_poly_degree: usize,
-    ) -> Result<Self, VerifierError> {
+    ) -> Result<Self, VerifierError>
+    where
+        R: RandomCoin<BaseField = B, Hasher = H>,
+    {
         // infer evaluation domain info
         let domain_size = max_poly_degree.next_power_of_two() * options.blowup_factor();
         let domain_generator = B::get_root_of_unity(log2(domain_size));
@@ -112,18 +140,22 @@ where
 
         // read layer commitments from the channel and use them to build a list of alphas
         let layer_commitments = channel.read_fri_layer_commitments();
-        let mut layer_alphas = Vec::with_capacity(layer_commitments.len());
+        let mut layer_alphas = InlineVec::new();
         let mut max_degree_plus_1 = max_poly_degree + 1;
         for (depth, commitment) in layer_commitments.iter().enumerate() {
             public_coin.reseed(*commitment);
+
+            // the remainder layer is not folded, and thus, the prover does not draw an alpha
+            // for it
+            if depth == layer_commitments.len() - 1 {
+                break;
+            }
             let alpha = public_coin.draw().map_err(VerifierError::PublicCoinError)?;
             layer_alphas.push(alpha);
 
             // make sure the degree can be reduced by the folding factor at all layers
             // but the remainder layer
-            if depth != layer_commitments.len() - 1
-                && max_degree_plus_1 % options.folding_factor() != 0
-            {
+            if max_degree_plus_1 % options.folding_factor() != 0 {
                 return Err(VerifierError::DegreeTruncation(
                     max_degree_plus_1 - 1,
                     options.folding_factor(),
@@ -193,6 +225,8 @@ where
     /// Returns an error if:
     /// * The length of `evaluations` is not equal to the length of `positions`.
     /// * An unsupported folding factor was specified by the `options` for this verifier.
+    /// * Any of the FRI layers was not opened at exactly the positions obtained by folding
+    ///   `positions`, in the order in which they are obtained.
     /// * Decommitments to polynomial evaluations don't match the commitment value at any of the
     ///   FRI layers.
     /// * The verifier detects an error in how the degree-respecting projection was applied
@@ -243,13 +277,26 @@ where
         let mut domain_generator = self.domain_generator;
         let mut domain_size = self.domain_size;
         let mut max_degree_plus_1 = self.max_poly_degree + 1;
-        let mut positions = positions.to_vec();
-        let mut evaluations = evaluations.to_vec();
+        let layer_0_positions = positions;
+        let mut positions = QueryPositions::from_slice(positions);
+        let mut folded_positions = QueryPositions::new();
+        let mut evaluations = QueryEvaluations::<E>::from_slice(evaluations);
 
         for depth in 0..self.options.num_fri_layers(self.domain_size) {
-            // determine which evaluations were queried in the folded layer
-            let mut folded_positions =
-                fold_positions(&positions, domain_size, self.options.folding_factor());
+            // determine which evaluations must have been queried in the folded layer; these are
+            // computed directly from the positions queried at the first layer rather than from
+            // the positions of the previous layer, and then compared against the positions for
+            // which the layer was actually opened
+            folded_positions.clear();
+            fold_positions_into(
+                layer_0_positions,
+                self.domain_size,
+                N.pow(depth as u32 + 1),
+                &mut folded_positions,
+            );
+            let opened_positions = channel.take_next_fri_layer_positions();
+            verify_positions(depth, &folded_positions, &opened_positions)?;
+
             // determine where these evaluations are in the commitment Merkle tree
             let position_indexes = map_positions_to_indexes(
                 &folded_positions,
@@ -271,9 +318,11 @@ where
             #[rustfmt::skip]
             let xs = folded_positions.iter().map(|&i| {
                 let xe = domain_generator.exp_vartime((i as u64).into()) * self.options.domain_offset();
-                folding_roots.iter()
-                    .map(|&r| E::from(xe * r))
-                    .collect::<Vec<_>>().try_into().unwrap()
+                let mut row = [E::ZERO; N];
+                for (x, &r) in row.iter_mut().zip(folding_roots.iter()) {
+                    *x = E::from(xe * r);
+                }
+                row
             })
             .collect::<Vec<_>>();
 
@@ -285,7 +334,8 @@ where
 
             // check that when the polynomials are evaluated at alpha, the result is equal to
             // the corresponding column value
-            evaluations = row_polys.iter().map(|p| polynom::eval(p, alpha)).collect();
+            evaluations.clear();
+            evaluations.extend(row_polys.iter().map(|p| polynom::eval(p, alpha)));
 
             // make sure next degree reduction does not result in degree truncation
             if max_degree_plus_1 % N != 0 {
@@ -309,7 +359,7 @@ where
         // of the previous layer
         let remainder_commitment = self.layer_commitments.last().unwrap();
         let remainder = channel.read_remainder::<N>(remainder_commitment)?;
-        for (&position, evaluation) in positions.iter().zip(evaluations) {
+        for (&position, &evaluation) in positions.iter().zip(evaluations.iter()) {
             if remainder[position] != evaluation {
                 return Err(VerifierError::InvalidRemainderFolding);
             }
@@ -320,6 +370,34 @@ where
     }
 }
 
+// POSITION VERIFICATION
+// ================================================================================================
+/// Returns Ok(()) if a FRI layer at the specified depth was opened at exactly the `expected`
+/// positions and in the same order.
+fn verify_positions(
+    depth: usize,
+    expected: &[usize],
+    opened: &[usize],
+) -> Result<(), VerifierError> {
+    if expected.len() != opened.len() {
+        return Err(VerifierError::FriPositionCountMismatch {
+            layer: depth,
+            expected: expected.len(),
+            actual: opened.len(),
+        });
+    }
+    for (&expected, &actual) in expected.iter().zip(opened) {
+        if expected != actual {
+            return Err(VerifierError::FriPositionMismatch {
+                layer: depth,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
 // REMAINDER DEGREE VERIFICATION
 // ================================================================================================
 /// Returns Ok(true) if values in the `remainder` slice represent evaluations of a polynomial
@@ -363,10 +441,10 @@ fn get_query_values<E: FieldElement, const N: usize>(
     positions: &[usize],
     folded_positions: &[usize],
     domain_size: usize,
-) -> Vec<E> {
+) -> QueryEvaluations<E> {
     let row_length = domain_size / N;
 
-    let mut result = Vec::new();
+    let mut result = QueryEvaluations::new();
     for position in positions {
         let idx = folded_positions
             .iter()
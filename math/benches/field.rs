@@ -14,7 +14,7 @@ use winter_math::{
     batch_inversion,
     fields::{f128, f62, f64},
     fields::{CubeExtension, QuadExtension},
-    ExtensibleField, FieldElement, StarkField,
+    ExtensibleField, ExtensionOf, FieldElement, StarkField,
 };
 
 const SIZES: [usize; 3] = [262_144, 524_288, 1_048_576];
@@ -111,6 +111,17 @@ where
             bench.iter(|| black_box(x) * black_box(y))
         });
 
+        group.bench_function("quad/square", |bench| {
+            let x = rand_value::<QuadExtension<B>>();
+            bench.iter(|| black_box(x).square())
+        });
+
+        group.bench_function("quad/mul_base", |bench| {
+            let x = rand_value::<QuadExtension<B>>();
+            let y = rand_value::<B>();
+            bench.iter(|| black_box(x).mul_base(black_box(y)))
+        });
+
         batch_ops::<QuadExtension<B>, WallTime>(&mut group, "quad");
         array_ops::<QuadExtension<B>, WallTime>(&mut group, "quad");
     }
@@ -140,6 +151,19 @@ where
             let y = rand_value::<CubeExtension<B>>();
             bench.iter(|| black_box(x) * black_box(y))
         });
+
+        group.bench_function("cube/square", |bench| {
+            let x = rand_value::<CubeExtension<B>>();
+            bench.iter(|| black_box(x).square())
+        });
+
+        group.bench_function("cube/mul_base", |bench| {
+            let x = rand_value::<CubeExtension<B>>();
+            let y = rand_value::<B>();
+            bench.iter(|| black_box(x).mul_base(black_box(y)))
+        });
+
+        batch_ops::<CubeExtension<B>, WallTime>(&mut group, "cube");
     }
 }
 
@@ -274,6 +298,35 @@ pub fn batch_ops<E: FieldElement, M: Measurement>(group: &mut BenchmarkGroup<M>,
     });
 }
 
+// EXPONENTIATION
+// ================================================================================================
+
+/// Compares variants of exponentiation for full-width exponents and for 20-bit exponents (e.g.,
+/// positions in the LDE domain at which the verifier computes powers of the domain generator).
+pub fn exp_ops<B: StarkField>(c: &mut Criterion, field_name: &str) {
+    let mut group = c.benchmark_group(format!("exp/{field_name}"));
+
+    let x = rand_value::<B>();
+    let full_width = rand_value::<B>().as_int();
+    let short = B::PositiveInteger::from(rand_value::<u32>() >> 12);
+
+    for (name, power) in [("full", full_width), ("20_bits", short)] {
+        group.bench_function(format!("{name}/vartime"), |bench| {
+            bench.iter(|| black_box(x).exp_vartime(black_box(power)))
+        });
+        group.bench_function(format!("{name}/const_time"), |bench| {
+            bench.iter(|| black_box(x).exp_const_time(black_box(power)))
+        });
+        for window_bits in [2, 3, 4, 5] {
+            group.bench_function(format!("{name}/windowed_{window_bits}"), |bench| {
+                bench.iter(|| black_box(x).exp_windowed(black_box(power), window_bits))
+            });
+        }
+    }
+
+    group.finish();
+}
+
 // GENERIC BENCHMARK RUNNER
 // ================================================================================================
 
@@ -283,8 +336,14 @@ fn bench_field_ops(c: &mut Criterion) {
     field_ops::<f128::BaseElement>(c, "f128");
 }
 
+fn bench_exp_ops(c: &mut Criterion) {
+    exp_ops::<f62::BaseElement>(c, "f62");
+    exp_ops::<f64::BaseElement>(c, "f64");
+    exp_ops::<f128::BaseElement>(c, "f128");
+}
+
 // CRITERION BOILERPLATE
 // ================================================================================================
 
-criterion_group!(field_group, batch_inv, bench_field_ops);
+criterion_group!(field_group, batch_inv, bench_field_ops, bench_exp_ops);
 criterion_main!(field_group);
@@ -7,7 +7,7 @@ use crate::{
     field::{FieldElement, StarkField},
     utils::log2,
 };
-use utils::{collections::Vec, iterators::*, rayon, uninit_vector};
+use utils::{collections::Vec, iterators::*, rayon};
 
 // POLYNOMIAL EVALUATION
 // ================================================================================================
@@ -30,7 +30,10 @@ pub fn evaluate_poly_with_offset<B: StarkField, E: FieldElement<BaseField = B>>(
 ) -> Vec<E> {
     let domain_size = p.len() * blowup_factor;
     let g = B::get_root_of_unity(log2(domain_size));
-    let mut result = unsafe { uninit_vector(domain_size) };
+    #[cfg(not(feature = "safe_only"))]
+    let mut result = unsafe { utils::uninit_vector(domain_size) };
+    #[cfg(feature = "safe_only")]
+    let mut result = vec![E::ZERO; domain_size];
 
     result
         .as_mut_slice()
@@ -92,6 +95,7 @@ where
 // PERMUTATIONS
 // ================================================================================================
 
+#[cfg(not(feature = "safe_only"))]
 pub fn permute<E: FieldElement>(v: &mut [E]) {
     let n = v.len();
     let num_batches = rayon::current_num_threads().next_power_of_two();
@@ -115,6 +119,16 @@ pub fn permute<E: FieldElement>(v: &mut [E]) {
     });
 }
 
+/// Permutes the values in a single thread.
+///
+/// Sharing the slice of values between threads requires re-interpreting mutable references,
+/// which is not possible without unsafe code; thus, the permutation is not parallelized when
+/// `safe_only` feature is enabled.
+#[cfg(feature = "safe_only")]
+pub fn permute<E: FieldElement>(v: &mut [E]) {
+    super::fft_inputs::FftInputs::permute(v);
+}
+
 // SPLIT-RADIX FFT
 // ================================================================================================
 
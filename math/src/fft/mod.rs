@@ -28,7 +28,7 @@ mod concurrent;
 
 use utils::collections::Vec;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // CONSTANTS
@@ -5,7 +5,7 @@
 
 use super::fft_inputs::FftInputs;
 use crate::{field::StarkField, utils::log2, FieldElement};
-use utils::{collections::Vec, uninit_vector};
+use utils::collections::Vec;
 
 // POLYNOMIAL EVALUATION
 // ================================================================================================
@@ -35,7 +35,10 @@ where
 {
     let domain_size = p.len() * blowup_factor;
     let g = B::get_root_of_unity(log2(domain_size));
-    let mut result = unsafe { uninit_vector(domain_size) };
+    #[cfg(not(feature = "safe_only"))]
+    let mut result = unsafe { utils::uninit_vector(domain_size) };
+    #[cfg(feature = "safe_only")]
+    let mut result = vec![E::ZERO; domain_size];
 
     result
         .as_mut_slice()
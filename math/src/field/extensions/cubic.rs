@@ -3,12 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{ExtensibleField, ExtensionOf, FieldElement};
+use super::{parse_coefficients, ExtensibleField, ExtensionOf, FieldElement};
 use core::{
     convert::TryFrom,
     fmt,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     slice,
+    str::FromStr,
 };
 use utils::{
     collections::Vec, string::ToString, AsBytes, ByteReader, ByteWriter, Deserializable,
@@ -41,6 +42,7 @@ impl<B: ExtensibleField<3>> CubeExtension<B> {
     /// Converts a vector of base elements into a vector of elements in a cubic extension field
     /// by fusing three adjacent base elements together. The output vector is half the length of
     /// the source vector.
+    #[cfg(not(feature = "safe_only"))]
     fn base_to_cubic_vector(source: Vec<B>) -> Vec<Self> {
         debug_assert!(
             source.len() % 3 == 0,
@@ -104,6 +106,7 @@ impl<B: ExtensibleField<3>> FieldElement for CubeExtension<B> {
         Self(result[0], result[1], result[2])
     }
 
+    #[allow(unsafe_code)]
     fn elements_as_bytes(elements: &[Self]) -> &[u8] {
         unsafe {
             slice::from_raw_parts(
@@ -113,6 +116,7 @@ impl<B: ExtensibleField<3>> FieldElement for CubeExtension<B> {
         }
     }
 
+    #[allow(unsafe_code)]
     unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError> {
         if bytes.len() % Self::ELEMENT_BYTES != 0 {
             return Err(DeserializationError::InvalidValue(format!(
@@ -134,6 +138,7 @@ impl<B: ExtensibleField<3>> FieldElement for CubeExtension<B> {
         Ok(slice::from_raw_parts(p as *const Self, len))
     }
 
+    #[cfg(not(feature = "safe_only"))]
     fn zeroed_vector(n: usize) -> Vec<Self> {
         // get three times the number of base elements and re-interpret them as cubic field
         // elements
@@ -141,11 +146,16 @@ impl<B: ExtensibleField<3>> FieldElement for CubeExtension<B> {
         Self::base_to_cubic_vector(result)
     }
 
+    #[allow(unsafe_code)]
     fn as_base_elements(elements: &[Self]) -> &[Self::BaseField] {
         let ptr = elements.as_ptr();
         let len = elements.len() * 3;
         unsafe { slice::from_raw_parts(ptr as *const Self::BaseField, len) }
     }
+
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField> {
+        elements.iter().flat_map(|e| [e.0, e.1, e.2]).collect()
+    }
 }
 
 impl<B: ExtensibleField<3>> ExtensionOf<B> for CubeExtension<B> {
@@ -166,7 +176,39 @@ impl<B: ExtensibleField<3>> Randomizable for CubeExtension<B> {
 
 impl<B: ExtensibleField<3>> fmt::Display for CubeExtension<B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({}, {}, {})", self.0, self.1, self.2)
+        write!(f, "{:#x}", self)
+    }
+}
+
+impl<B: ExtensibleField<3>> fmt::LowerHex for CubeExtension<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#x} + {:#x}*u + {:#x}*u^2", self.0, self.1, self.2)
+        } else {
+            write!(f, "{:x} + {:x}*u + {:x}*u^2", self.0, self.1, self.2)
+        }
+    }
+}
+
+impl<B: ExtensibleField<3>> fmt::UpperHex for CubeExtension<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#X} + {:#X}*u + {:#X}*u^2", self.0, self.1, self.2)
+        } else {
+            write!(f, "{:X} + {:X}*u + {:X}*u^2", self.0, self.1, self.2)
+        }
+    }
+}
+
+impl<B: ExtensibleField<3>> FromStr for CubeExtension<B> {
+    type Err = DeserializationError;
+
+    /// Parses an extension field element from a string of the form `a + b*u + c*u^2`, where
+    /// `a`, `b`, and `c` are base field elements in either hexadecimal (`0x`-prefixed) or
+    /// decimal form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coefficients = parse_coefficients::<B>(s, 3)?;
+        Ok(Self(coefficients[0], coefficients[1], coefficients[2]))
     }
 }
 
@@ -288,7 +330,7 @@ impl<B: ExtensibleField<3>> From<u8> for CubeExtension<B> {
     }
 }
 
-impl<'a, B: ExtensibleField<3>> TryFrom<&'a [u8]> for CubeExtension<B> {
+impl<B: ExtensibleField<3>> TryFrom<&[u8]> for CubeExtension<B> {
     type Error = DeserializationError;
 
     /// Converts a slice of bytes into a field element; returns error if the value encoded in bytes
@@ -314,6 +356,7 @@ impl<'a, B: ExtensibleField<3>> TryFrom<&'a [u8]> for CubeExtension<B> {
 }
 
 impl<B: ExtensibleField<3>> AsBytes for CubeExtension<B> {
+    #[allow(unsafe_code)]
     fn as_bytes(&self) -> &[u8] {
         // TODO: take endianness into account
         let self_ptr: *const Self = self;
@@ -344,12 +387,14 @@ impl<B: ExtensibleField<3>> Deserializable for CubeExtension<B> {
 // TESTS
 // ================================================================================================
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f64"))]
 mod tests {
-    use super::{CubeExtension, DeserializationError, FieldElement};
+    use super::{CubeExtension, FieldElement};
     use crate::field::f64::BaseElement;
     use rand_utils::rand_value;
 
+    use super::DeserializationError;
+
     // BASIC ALGEBRA
     // --------------------------------------------------------------------------------------------
 
@@ -492,4 +537,22 @@ mod tests {
             CubeExtension::<BaseElement>::as_base_elements(&elements)
         );
     }
+
+    #[test]
+    fn to_base_elements() {
+        let elements = rand_utils::rand_vector::<CubeExtension<BaseElement>>(64);
+        let expected = elements
+            .iter()
+            .flat_map(|e| [e.0, e.1, e.2])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected,
+            CubeExtension::<BaseElement>::to_base_elements(&elements)
+        );
+
+        assert_eq!(
+            CubeExtension::<BaseElement>::as_base_elements(&elements),
+            CubeExtension::<BaseElement>::to_base_elements(&elements)
+        );
+    }
 }
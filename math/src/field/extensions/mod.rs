@@ -3,10 +3,56 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+#[cfg(feature = "quadratic")]
 mod quadratic;
+#[cfg(feature = "quadratic")]
 pub use quadratic::QuadExtension;
 
+#[cfg(feature = "cubic")]
 mod cubic;
+#[cfg(feature = "cubic")]
 pub use cubic::CubeExtension;
 
 use super::{ExtensibleField, ExtensionOf, FieldElement};
+
+use core::str::FromStr;
+use utils::{collections::Vec, DeserializationError};
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Parses coefficients of an extension field element from a string of the form
+/// `a + b*u + c*u^2 ...`, where the number of terms must be equal to `num_coefficients`.
+///
+/// Coefficients are parsed using [FromStr] implementation of the base field, and thus, can be
+/// either `0x`-prefixed hexadecimal or decimal values.
+fn parse_coefficients<B>(s: &str, num_coefficients: usize) -> Result<Vec<B>, DeserializationError>
+where
+    B: FromStr<Err = DeserializationError>,
+{
+    let terms = s.split('+').map(|t| t.trim()).collect::<Vec<_>>();
+    if terms.len() != num_coefficients {
+        return Err(DeserializationError::InvalidValue(format!(
+            "expected {} terms in extension field element '{}', but found {}",
+            num_coefficients,
+            s,
+            terms.len()
+        )));
+    }
+
+    let mut result = Vec::with_capacity(num_coefficients);
+    for (i, term) in terms.into_iter().enumerate() {
+        let coefficient = match i {
+            0 => Some(term),
+            1 => term.strip_suffix("*u"),
+            _ => term.strip_suffix(&format!("*u^{i}")),
+        }
+        .ok_or_else(|| {
+            DeserializationError::InvalidValue(format!(
+                "term {i} of extension field element '{s}' is malformed"
+            ))
+        })?;
+        result.push(coefficient.trim().parse()?);
+    }
+    Ok(result)
+}
@@ -3,12 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{ExtensibleField, ExtensionOf, FieldElement};
+use super::{parse_coefficients, ExtensibleField, ExtensionOf, FieldElement};
 use core::{
     convert::TryFrom,
     fmt,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     slice,
+    str::FromStr,
 };
 use utils::{
     collections::Vec, string::ToString, AsBytes, ByteReader, ByteWriter, Deserializable,
@@ -41,6 +42,7 @@ impl<B: ExtensibleField<2>> QuadExtension<B> {
     /// Converts a vector of base elements into a vector of elements in a quadratic extension
     /// field by fusing two adjacent base elements together. The output vector is half the length
     /// of the source vector.
+    #[cfg(not(feature = "safe_only"))]
     fn base_to_quad_vector(source: Vec<B>) -> Vec<Self> {
         debug_assert!(
             source.len() % 2 == 0,
@@ -97,6 +99,7 @@ impl<B: ExtensibleField<2>> FieldElement for QuadExtension<B> {
         Self(result[0], result[1])
     }
 
+    #[allow(unsafe_code)]
     fn elements_as_bytes(elements: &[Self]) -> &[u8] {
         unsafe {
             slice::from_raw_parts(
@@ -106,6 +109,7 @@ impl<B: ExtensibleField<2>> FieldElement for QuadExtension<B> {
         }
     }
 
+    #[allow(unsafe_code)]
     unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError> {
         if bytes.len() % Self::ELEMENT_BYTES != 0 {
             return Err(DeserializationError::InvalidValue(format!(
@@ -127,17 +131,23 @@ impl<B: ExtensibleField<2>> FieldElement for QuadExtension<B> {
         Ok(slice::from_raw_parts(p as *const Self, len))
     }
 
+    #[cfg(not(feature = "safe_only"))]
     fn zeroed_vector(n: usize) -> Vec<Self> {
         // get twice the number of base elements, and re-interpret them as quad field elements
         let result = B::zeroed_vector(n * 2);
         Self::base_to_quad_vector(result)
     }
 
+    #[allow(unsafe_code)]
     fn as_base_elements(elements: &[Self]) -> &[Self::BaseField] {
         let ptr = elements.as_ptr();
         let len = elements.len() * 2;
         unsafe { slice::from_raw_parts(ptr as *const Self::BaseField, len) }
     }
+
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField> {
+        elements.iter().flat_map(|e| [e.0, e.1]).collect()
+    }
 }
 
 impl<B: ExtensibleField<2>> ExtensionOf<B> for QuadExtension<B> {
@@ -158,7 +168,38 @@ impl<B: ExtensibleField<2>> Randomizable for QuadExtension<B> {
 
 impl<B: ExtensibleField<2>> fmt::Display for QuadExtension<B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
+        write!(f, "{:#x}", self)
+    }
+}
+
+impl<B: ExtensibleField<2>> fmt::LowerHex for QuadExtension<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#x} + {:#x}*u", self.0, self.1)
+        } else {
+            write!(f, "{:x} + {:x}*u", self.0, self.1)
+        }
+    }
+}
+
+impl<B: ExtensibleField<2>> fmt::UpperHex for QuadExtension<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#X} + {:#X}*u", self.0, self.1)
+        } else {
+            write!(f, "{:X} + {:X}*u", self.0, self.1)
+        }
+    }
+}
+
+impl<B: ExtensibleField<2>> FromStr for QuadExtension<B> {
+    type Err = DeserializationError;
+
+    /// Parses an extension field element from a string of the form `a + b*u`, where `a` and `b`
+    /// are base field elements in either hexadecimal (`0x`-prefixed) or decimal form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coefficients = parse_coefficients::<B>(s, 2)?;
+        Ok(Self(coefficients[0], coefficients[1]))
     }
 }
 
@@ -279,7 +320,7 @@ impl<B: ExtensibleField<2>> From<u8> for QuadExtension<B> {
     }
 }
 
-impl<'a, B: ExtensibleField<2>> TryFrom<&'a [u8]> for QuadExtension<B> {
+impl<B: ExtensibleField<2>> TryFrom<&[u8]> for QuadExtension<B> {
     type Error = DeserializationError;
 
     /// Converts a slice of bytes into a field element; returns error if the value encoded in bytes
@@ -305,6 +346,7 @@ impl<'a, B: ExtensibleField<2>> TryFrom<&'a [u8]> for QuadExtension<B> {
 }
 
 impl<B: ExtensibleField<2>> AsBytes for QuadExtension<B> {
+    #[allow(unsafe_code)]
     fn as_bytes(&self) -> &[u8] {
         // TODO: take endianness into account
         let self_ptr: *const Self = self;
@@ -333,12 +375,14 @@ impl<B: ExtensibleField<2>> Deserializable for QuadExtension<B> {
 // TESTS
 // ================================================================================================
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f64"))]
 mod tests {
-    use super::{DeserializationError, FieldElement, QuadExtension};
+    use super::{FieldElement, QuadExtension};
     use crate::field::f64::BaseElement;
     use rand_utils::rand_value;
 
+    use super::DeserializationError;
+
     // BASIC ALGEBRA
     // --------------------------------------------------------------------------------------------
 
@@ -450,4 +494,19 @@ mod tests {
             QuadExtension::<BaseElement>::as_base_elements(&elements)
         );
     }
+
+    #[test]
+    fn to_base_elements() {
+        let elements = rand_utils::rand_vector::<QuadExtension<BaseElement>>(64);
+        let expected = elements.iter().flat_map(|e| [e.0, e.1]).collect::<Vec<_>>();
+        assert_eq!(
+            expected,
+            QuadExtension::<BaseElement>::to_base_elements(&elements)
+        );
+
+        assert_eq!(
+            QuadExtension::<BaseElement>::as_base_elements(&elements),
+            QuadExtension::<BaseElement>::to_base_elements(&elements)
+        );
+    }
 }
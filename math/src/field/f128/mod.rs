@@ -13,10 +13,11 @@
 use super::{ExtensibleField, FieldElement, StarkField};
 use core::{
     convert::{TryFrom, TryInto},
-    fmt::{Debug, Display, Formatter},
+    fmt::{Debug, Display, Formatter, LowerHex, UpperHex},
     mem,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     slice,
+    str::FromStr,
 };
 use utils::{
     collections::Vec,
@@ -25,7 +26,7 @@ use utils::{
     Serializable,
 };
 
-#[cfg(test)]
+#[cfg(all(test, feature = "quadratic"))]
 mod tests;
 
 // CONSTANTS
@@ -78,6 +79,7 @@ impl FieldElement for BaseElement {
         BaseElement(self.0)
     }
 
+    #[allow(unsafe_code)]
     fn elements_as_bytes(elements: &[Self]) -> &[u8] {
         // TODO: take endianness into account
         let p = elements.as_ptr();
@@ -85,6 +87,7 @@ impl FieldElement for BaseElement {
         unsafe { slice::from_raw_parts(p as *const u8, len) }
     }
 
+    #[allow(unsafe_code)]
     unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError> {
         if bytes.len() % Self::ELEMENT_BYTES != 0 {
             return Err(DeserializationError::InvalidValue(format!(
@@ -105,6 +108,7 @@ impl FieldElement for BaseElement {
         Ok(slice::from_raw_parts(p as *const Self, len))
     }
 
+    #[cfg(not(feature = "safe_only"))]
     fn zeroed_vector(n: usize) -> Vec<Self> {
         // this uses a specialized vector initialization code which requests zero-filled memory
         // from the OS; unfortunately, this works only for built-in types and we can't use
@@ -124,6 +128,10 @@ impl FieldElement for BaseElement {
     fn as_base_elements(elements: &[Self]) -> &[Self::BaseField] {
         elements
     }
+
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField> {
+        elements.to_vec()
+    }
 }
 
 impl StarkField for BaseElement {
@@ -168,7 +176,52 @@ impl Randomizable for BaseElement {
 
 impl Display for BaseElement {
     fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{:#x}", self)
+    }
+}
+
+impl LowerHex for BaseElement {
+    /// Formats the canonical representation of this element as a zero-padded hexadecimal
+    /// string of 32 digits; the `0x` prefix is included when the alternate flag is set.
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:032x}", self.as_int())
+    }
+}
+
+impl UpperHex for BaseElement {
+    /// Formats the canonical representation of this element as a zero-padded hexadecimal
+    /// string of 32 digits; the `0x` prefix is included when the alternate flag is set.
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:032X}", self.as_int())
+    }
+}
+
+impl FromStr for BaseElement {
+    type Err = DeserializationError;
+
+    /// Parses a field element from either a hexadecimal string prefixed with `0x`, or from a
+    /// decimal string. The parsed value must be smaller than the field modulus.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(digits) => u128::from_str_radix(digits, 16),
+            None => s.parse::<u128>(),
+        }
+        .map_err(|err| {
+            DeserializationError::InvalidValue(format!("failed to parse '{s}': {err}"))
+        })?;
+        if value >= M {
+            return Err(DeserializationError::InvalidValue(format!(
+                "invalid field element: value {value} is greater than or equal to the field modulus"
+            )));
+        }
+        Ok(Self::new(value))
     }
 }
 
@@ -337,7 +390,7 @@ impl From<[u8; 16]> for BaseElement {
     }
 }
 
-impl<'a> TryFrom<&'a [u8]> for BaseElement {
+impl TryFrom<&[u8]> for BaseElement {
     type Error = String;
 
     /// Converts a slice of bytes into a field element; returns error if the value encoded in bytes
@@ -357,7 +410,31 @@ impl<'a> TryFrom<&'a [u8]> for BaseElement {
     }
 }
 
+impl From<BaseElement> for u128 {
+    /// Converts a field element into the canonical integer representation of the element.
+    fn from(value: BaseElement) -> Self {
+        value.as_int()
+    }
+}
+
+impl TryFrom<BaseElement> for u64 {
+    type Error = String;
+
+    /// Converts a field element into a 64-bit value; returns error if the canonical integer
+    /// representation of the element does not fit into 64 bits.
+    fn try_from(value: BaseElement) -> Result<Self, Self::Error> {
+        u64::try_from(value.as_int()).map_err(|_| {
+            format!(
+                "cannot convert field element into u64: value {} is greater than {}",
+                value.as_int(),
+                u64::MAX
+            )
+        })
+    }
+}
+
 impl AsBytes for BaseElement {
+    #[allow(unsafe_code)]
     fn as_bytes(&self) -> &[u8] {
         // TODO: take endianness into account
         let self_ptr: *const BaseElement = self;
@@ -4,8 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{
-    AsBytes, BaseElement, ByteReader, Deserializable, DeserializationError, FieldElement,
-    StarkField, M,
+    BaseElement, ByteReader, Deserializable, DeserializationError, FieldElement, StarkField, M,
 };
 use crate::field::{ExtensionOf, QuadExtension};
 use core::convert::TryFrom;
@@ -115,6 +114,42 @@ fn inv() {
     }
 }
 
+#[test]
+fn exp_variants() {
+    // by Fermat's little theorem, a^(p - 1) = 1 for all non-zero a
+    let m = BaseElement::MODULUS;
+    assert_eq!(
+        BaseElement::ONE,
+        rand_value::<BaseElement>().exp_const_time(m - 1)
+    );
+
+    let a: BaseElement = rand_value();
+    for power in [0, 1, 3, m - 1, m, u128::MAX] {
+        let expected = a.exp_vartime(power);
+        assert_eq!(expected, a.exp_const_time(power));
+        for window_bits in 1..=8 {
+            assert_eq!(expected, a.exp_windowed(power, window_bits));
+        }
+    }
+
+    // test random values
+    let powers: Vec<u128> = rand_vector::<BaseElement>(100)
+        .iter()
+        .map(|p| p.as_int())
+        .collect();
+    for power in powers {
+        let expected = a.exp_vartime(power);
+        assert_eq!(expected, a.exp_const_time(power));
+        assert_eq!(expected, a.exp_windowed(power, 5));
+    }
+
+    for power in [0, 1, u128::MAX] {
+        let expected = BaseElement::ZERO.exp_vartime(power);
+        assert_eq!(expected, BaseElement::ZERO.exp_const_time(power));
+        assert_eq!(expected, BaseElement::ZERO.exp_windowed(power, 4));
+    }
+}
+
 #[test]
 fn conjugate() {
     let a: BaseElement = rand_value();
@@ -122,6 +157,55 @@ fn conjugate() {
     assert_eq!(a, b);
 }
 
+// BOUNDARY VALUES
+// ================================================================================================
+
+/// Operands which exercise carry and reduction paths of the field arithmetic together with the
+/// expected values of a + b, a - b, a * b, and a^(-1). The expected values were computed with
+/// arbitrary-precision integers, and thus, they do not depend on the platform these tests run on.
+#[rustfmt::skip]
+const BOUNDARY_VALUES: [(u128, u128, u128, u128, u128, u128); 8] = [
+    (340282366920938463463374557953744961536, 340282366920938463463374557953744961536, 340282366920938463463374557953744961535, 0, 1, 340282366920938463463374557953744961536),
+    (340282366920938463463374557953744961536, 2, 1, 340282366920938463463374557953744961534, 340282366920938463463374557953744961535, 340282366920938463463374557953744961536),
+    (340282366920938463444927813880035409921, 18446744073709551616, 0, 340282366920938463426481069806325858305, 340282366920938463463374508475721711618, 912708432164343615821699884711936),
+    (170141183460469231731687303715884105728, 170141183460469231731687303715884105728, 49478023249919, 0, 255211775191315866293710799745992097793, 29243015902371999634444403960543445702),
+    (18446744073709551615, 18446744073709551617, 36893488147419103232, 340282366920938463463374557953744961535, 49478023249918, 204161021195482326713235225232697279283),
+    (170141183460469231731687278976872480769, 340282366920938463463374557953744961534, 170141183460469231731687278976872480766, 170141183460469231731687278976872480772, 170141183460469231731687278976872480767, 2),
+    (170141183460469231750134047789593657343, 340282366920938463444927813880035409920, 170141183460469231731687303715884105726, 170141183460469231768580791863303208960, 170140727106253149578326037983538446339, 121597630174754997404923959396669927301),
+    (1, 340282366920938463463374557953744961536, 0, 2, 340282366920938463463374557953744961536, 1),
+];
+
+#[test]
+fn boundary_values() {
+    for &(a, b, sum, diff, prod, inv) in BOUNDARY_VALUES.iter() {
+        let (a, b) = (BaseElement::new(a), BaseElement::new(b));
+        assert_eq!(sum, (a + b).as_int(), "failed for: {a} + {b}");
+        assert_eq!(diff, (a - b).as_int(), "failed for: {a} - {b}");
+        assert_eq!(prod, (a * b).as_int(), "failed for: {a} * {b}");
+        assert_eq!(inv, a.inv().as_int(), "failed for: {a}^(-1)");
+        assert_eq!(a * a, a.square(), "failed for: {a}^2");
+        assert_eq!(a + a, a.double(), "failed for: 2 * {a}");
+    }
+
+    // reduction of values which do not fit into the field
+    assert_eq!(49478023249918, BaseElement::from(u128::MAX).as_int());
+    assert_eq!(49478023249918, BaseElement::new(u128::MAX).as_int());
+}
+
+#[test]
+fn integer_conversions() {
+    // values near the modulus fit into u128 but not into u64
+    let e = BaseElement::new(M - 1);
+    assert_eq!(M - 1, u128::from(e));
+    assert_eq!(M - 1, u128::from(-BaseElement::ONE));
+    assert!(u64::try_from(e).is_err());
+
+    // values produced by arithmetic are converted from their canonical representation
+    assert_eq!(Ok(1), u64::try_from(e * e));
+    assert_eq!(Ok(u64::MAX), u64::try_from(BaseElement::from(u64::MAX)));
+    assert!(u64::try_from(BaseElement::from(u64::MAX) + BaseElement::ONE).is_err());
+}
+
 // ROOTS OF UNITY
 // ================================================================================================
 
@@ -206,6 +290,20 @@ fn bytes_as_elements() {
     assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
 }
 
+#[test]
+fn elements_as_bytes_random() {
+    use utils::Serializable;
+
+    // the field is canonical, and thus, re-interpreted and serialized bytes must be the same
+    let source = rand_vector::<BaseElement>(64);
+    let mut expected = Vec::new();
+    source.write_into(&mut expected);
+    assert_eq!(expected, BaseElement::elements_as_bytes(&source));
+
+    let result = unsafe { BaseElement::bytes_as_elements(&expected) };
+    assert_eq!(source, result.unwrap());
+}
+
 #[test]
 fn read_elements_from() {
     let bytes: Vec<u8> = vec![
@@ -243,6 +341,53 @@ fn read_elements_from() {
     }
 }
 
+// FORMATTING AND PARSING
+// ================================================================================================
+
+#[test]
+fn format_hex() {
+    let a = BaseElement::new(0xabcd);
+    assert_eq!("0x0000000000000000000000000000abcd", format!("{}", a));
+    assert_eq!("0000000000000000000000000000abcd", format!("{:x}", a));
+    assert_eq!("0x0000000000000000000000000000ABCD", format!("{:#X}", a));
+}
+
+#[test]
+fn parse_from_str() {
+    let a: BaseElement = rand_value();
+    assert_eq!(a, format!("{}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#x}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#X}", a).parse().unwrap());
+    assert_eq!(BaseElement::new(42), " 42 ".parse().unwrap());
+
+    assert!(matches!(
+        super::M.to_string().parse::<BaseElement>(),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        "0xzz".parse::<BaseElement>(),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn parse_extensions_from_str() {
+    let a: QuadExtension<BaseElement> = rand_value();
+    assert_eq!(a, format!("{}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#X}", a).parse().unwrap());
+
+    let c = <QuadExtension<BaseElement>>::new(BaseElement::new(3), BaseElement::new(5));
+    assert_eq!(
+        "0x00000000000000000000000000000003 + 0x00000000000000000000000000000005*u",
+        format!("{}", c)
+    );
+    assert_eq!(c, "3 + 5*u".parse().unwrap());
+    assert!("3 + 5".parse::<QuadExtension<BaseElement>>().is_err());
+    assert!("3 + 5*u + 7*u^2"
+        .parse::<QuadExtension<BaseElement>>()
+        .is_err());
+}
+
 // INITIALIZATION
 // ================================================================================================
 
@@ -260,13 +405,13 @@ fn zeroed_vector() {
 
 impl BaseElement {
     pub fn to_big_uint(&self) -> BigUint {
-        BigUint::from_bytes_le(self.as_bytes())
+        BigUint::from(self.as_int())
     }
 
     pub fn from_big_uint(value: BigUint) -> Self {
         let bytes = value.to_bytes_le();
         let mut buffer = [0u8; 16];
         buffer[0..bytes.len()].copy_from_slice(&bytes);
-        BaseElement::try_from(buffer).unwrap()
+        BaseElement::from(buffer)
     }
 }
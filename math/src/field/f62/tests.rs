@@ -3,13 +3,15 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{AsBytes, BaseElement, DeserializationError, FieldElement, Serializable, StarkField};
+use super::{BaseElement, DeserializationError, FieldElement, Serializable, StarkField};
 use crate::field::{CubeExtension, ExtensionOf, QuadExtension};
 use core::convert::TryFrom;
 use num_bigint::BigUint;
 use proptest::prelude::*;
 use rand_utils::rand_value;
 
+use utils::AsBytes;
+
 // MANUAL TESTS
 // ================================================================================================
 
@@ -90,6 +92,31 @@ fn exp() {
     assert_eq!(a.exp(3), a * a * a);
 }
 
+#[test]
+fn exp_variants() {
+    // by Fermat's little theorem, a^(p - 1) = 1 for all non-zero a
+    let m = BaseElement::MODULUS;
+    assert_eq!(
+        BaseElement::ONE,
+        rand_value::<BaseElement>().exp_const_time(m - 1)
+    );
+
+    let a: BaseElement = rand_value();
+    for power in [0, 1, 3, m - 1, m, u64::MAX] {
+        let expected = a.exp_vartime(power);
+        assert_eq!(expected, a.exp_const_time(power));
+        for window_bits in 1..=8 {
+            assert_eq!(expected, a.exp_windowed(power, window_bits));
+        }
+    }
+
+    for power in [0, 1, u64::MAX] {
+        let expected = BaseElement::ZERO.exp_vartime(power);
+        assert_eq!(expected, BaseElement::ZERO.exp_const_time(power));
+        assert_eq!(expected, BaseElement::ZERO.exp_windowed(power, 4));
+    }
+}
+
 #[test]
 fn inv() {
     // identity
@@ -104,6 +131,20 @@ fn element_as_int() {
     assert_eq!(v % super::M, e.as_int());
 }
 
+#[test]
+fn element_into_int() {
+    let e = BaseElement::new(super::M - 1);
+    assert_eq!(super::M - 1, u64::from(e));
+    assert_eq!((super::M - 1) as u128, u128::from(e));
+    assert_eq!(super::M - 1, u64::from(-BaseElement::ONE));
+
+    // elements are stored in Montgomery form, but are converted from their canonical
+    // representation
+    let e = e * e;
+    assert_eq!(1, u64::from(e));
+    assert_ne!(1, e.0);
+}
+
 #[test]
 fn equals() {
     let a = BaseElement::ONE;
@@ -209,6 +250,41 @@ fn cube_mul_base() {
     assert_eq!(expected, a.mul_base(b0));
 }
 
+// BOUNDARY VALUES
+// ------------------------------------------------------------------------------------------------
+
+/// Operands which exercise carry and reduction paths of the field arithmetic together with the
+/// expected values of a + b, a - b, a * b, and a^(-1). The expected values were computed with
+/// arbitrary-precision integers, and thus, they do not depend on the platform these tests run on.
+#[rustfmt::skip]
+const BOUNDARY_VALUES: [(u64, u64, u64, u64, u64, u64); 8] = [
+    (4611624995532046336, 4611624995532046336, 4611624995532046335, 0, 1, 4611624995532046336),
+    (4611624995532046336, 2, 1, 4611624995532046334, 4611624995532046335, 4611624995532046336),
+    (2305843009213693952, 2305843009213693952, 61022895341567, 0, 4333249129581360530, 4611502951356301311),
+    (4294967296, 2147483648, 6442450944, 2147483648, 122045790683134, 4611624994458318721),
+    (4611624445776232449, 549755813889, 1, 4611623896020418560, 612427976671297537, 8388497),
+    (2305812497766023169, 4611624995532046334, 2305812497766023166, 2305812497766023172, 2305812497766023167, 2),
+    (4611624991237079041, 1099511627776, 1095216660480, 4611623891725451265, 4549137550702281729, 1073727616),
+    (1, 4611624995532046336, 0, 2, 4611624995532046336, 1),
+];
+
+#[test]
+fn boundary_values() {
+    for &(a, b, sum, diff, prod, inv) in BOUNDARY_VALUES.iter() {
+        let (a, b) = (BaseElement::new(a), BaseElement::new(b));
+        assert_eq!(sum, (a + b).as_int(), "failed for: {a} + {b}");
+        assert_eq!(diff, (a - b).as_int(), "failed for: {a} - {b}");
+        assert_eq!(prod, (a * b).as_int(), "failed for: {a} * {b}");
+        assert_eq!(inv, a.inv().as_int(), "failed for: {a}^(-1)");
+        assert_eq!(a * a, a.square(), "failed for: {a}^2");
+        assert_eq!(a + a, a.double(), "failed for: 2 * {a}");
+    }
+
+    // reduction of values which do not fit into the field
+    assert_eq!(244091581366267, BaseElement::new(u64::MAX).as_int());
+    assert_eq!(630444561284293699, BaseElement::from(u128::MAX).as_int());
+}
+
 // ROOTS OF UNITY
 // ------------------------------------------------------------------------------------------------
 
@@ -299,6 +375,57 @@ fn bytes_as_elements() {
     assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
 }
 
+// FORMATTING AND PARSING
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_hex() {
+    let a = BaseElement::new(0xabcd);
+    assert_eq!("43981", format!("{}", a));
+    assert_eq!("000000000000abcd", format!("{:x}", a));
+    assert_eq!("0x000000000000ABCD", format!("{:#X}", a));
+}
+
+#[test]
+fn parse_from_str() {
+    let a: BaseElement = rand_value();
+    assert_eq!(a, format!("{}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#x}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#X}", a).parse().unwrap());
+    assert_eq!(BaseElement::new(42), " 42 ".parse().unwrap());
+
+    assert!(matches!(
+        super::M.to_string().parse::<BaseElement>(),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        "0xzz".parse::<BaseElement>(),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn parse_extensions_from_str() {
+    let a: QuadExtension<BaseElement> = rand_value();
+    assert_eq!(a, format!("{}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#X}", a).parse().unwrap());
+
+    let b: CubeExtension<BaseElement> = rand_value();
+    assert_eq!(b, format!("{}", b).parse().unwrap());
+    assert_eq!(b, format!("{:#X}", b).parse().unwrap());
+
+    let c = <QuadExtension<BaseElement>>::new(BaseElement::new(3), BaseElement::new(5));
+    assert_eq!(
+        "0x0000000000000003 + 0x0000000000000005*u",
+        format!("{}", c)
+    );
+    assert_eq!(c, "3 + 5*u".parse().unwrap());
+    assert!("3 + 5".parse::<QuadExtension<BaseElement>>().is_err());
+    assert!("3 + 5*u + 7*u^2"
+        .parse::<QuadExtension<BaseElement>>()
+        .is_err());
+}
+
 // INITIALIZATION
 // ------------------------------------------------------------------------------------------------
 
@@ -359,6 +486,14 @@ proptest! {
         prop_assert_eq!(expected, result.as_int());
     }
 
+    #[test]
+    fn exp_variants_proptest(a in any::<u64>(), b in any::<u64>(), window_bits in 1..=8u32) {
+        let a = BaseElement::from(a);
+        let expected = a.exp_vartime(b);
+        prop_assert_eq!(expected, a.exp_const_time(b));
+        prop_assert_eq!(expected, a.exp_windowed(b, window_bits));
+    }
+
     #[test]
     fn inv_proptest(a in any::<u64>()) {
         let a = BaseElement::from(a);
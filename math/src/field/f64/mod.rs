@@ -17,17 +17,18 @@
 use super::{ExtensibleField, FieldElement, StarkField};
 use core::{
     convert::{TryFrom, TryInto},
-    fmt::{Debug, Display, Formatter},
+    fmt::{Debug, Display, Formatter, LowerHex, UpperHex},
     mem,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     slice,
+    str::FromStr,
 };
 use utils::{
     collections::Vec, string::ToString, AsBytes, ByteReader, ByteWriter, Deserializable,
     DeserializationError, Randomizable, Serializable,
 };
 
-#[cfg(test)]
+#[cfg(all(test, feature = "quadratic", feature = "cubic"))]
 mod tests;
 
 // CONSTANTS
@@ -36,6 +37,11 @@ mod tests;
 /// Field modulus = 2^64 - 2^32 + 1
 const M: u64 = 0xFFFFFFFF00000001;
 
+/// M * 2^64; products of internal values are accumulated below this bound when multiplying
+/// cubic extension field elements, so that each accumulator can be reduced with a single
+/// Montgomery reduction.
+const M_WIDE: u128 = (M as u128) << 64;
+
 /// 2^128 mod M; this is used for conversion of elements into Montgomery representation.
 const R2: u64 = 0xFFFFFFFE00000001;
 
@@ -115,6 +121,11 @@ impl FieldElement for BaseElement {
 
     #[inline]
     fn exp(self, power: Self::PositiveInteger) -> Self {
+        self.exp_const_time(power)
+    }
+
+    #[inline]
+    fn exp_const_time(self, power: Self::PositiveInteger) -> Self {
         let mut b: Self;
         let mut r = Self::ONE;
         for i in (0..64).rev() {
@@ -165,6 +176,7 @@ impl FieldElement for BaseElement {
         Self(self.0)
     }
 
+    #[allow(unsafe_code)]
     fn elements_as_bytes(elements: &[Self]) -> &[u8] {
         // TODO: take endianness into account.
         let p = elements.as_ptr();
@@ -172,6 +184,7 @@ impl FieldElement for BaseElement {
         unsafe { slice::from_raw_parts(p as *const u8, len) }
     }
 
+    #[allow(unsafe_code)]
     unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError> {
         if bytes.len() % Self::ELEMENT_BYTES != 0 {
             return Err(DeserializationError::InvalidValue(format!(
@@ -192,6 +205,7 @@ impl FieldElement for BaseElement {
         Ok(slice::from_raw_parts(p as *const Self, len))
     }
 
+    #[cfg(not(feature = "safe_only"))]
     fn zeroed_vector(n: usize) -> Vec<Self> {
         // this uses a specialized vector initialization code which requests zero-filled memory
         // from the OS; unfortunately, this works only for built-in types and we can't use
@@ -210,6 +224,10 @@ impl FieldElement for BaseElement {
     fn as_base_elements(elements: &[Self]) -> &[Self::BaseField] {
         elements
     }
+
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField> {
+        elements.to_vec()
+    }
 }
 
 impl StarkField for BaseElement {
@@ -242,12 +260,16 @@ impl StarkField for BaseElement {
     // x/2^64 modulo M. This is exactly what mont_red_cst does only that it does it more efficiently
     // using the fact that a field element in Montgomery form is stored as a u64 and thus one can
     // use this to simplify mont_red_cst in this case.
+    //
+    // For x = 0 the subtraction below yields M rather than 0, and thus, the result is mapped to
+    // its canonical value in constant time.
     #[inline]
     fn as_int(&self) -> Self::PositiveInteger {
         let x = self.0;
         let (r, c) = x.overflowing_add(x << 32);
         let res = r.wrapping_sub(r >> 32).wrapping_sub(c as u64);
-        M - res
+        let res = M - res;
+        res ^ (equals(res, M) & M)
     }
 }
 
@@ -265,6 +287,51 @@ impl Display for BaseElement {
     }
 }
 
+impl LowerHex for BaseElement {
+    /// Formats the canonical representation of this element as a zero-padded hexadecimal
+    /// string of 16 digits; the `0x` prefix is included when the alternate flag is set.
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:016x}", self.as_int())
+    }
+}
+
+impl UpperHex for BaseElement {
+    /// Formats the canonical representation of this element as a zero-padded hexadecimal
+    /// string of 16 digits; the `0x` prefix is included when the alternate flag is set.
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:016X}", self.as_int())
+    }
+}
+
+impl FromStr for BaseElement {
+    type Err = DeserializationError;
+
+    /// Parses a field element from either a hexadecimal string prefixed with `0x`, or from a
+    /// decimal string. The parsed value must be smaller than the field modulus.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(digits) => u64::from_str_radix(digits, 16),
+            None => s.parse::<u64>(),
+        }
+        .map_err(|err| {
+            DeserializationError::InvalidValue(format!("failed to parse '{s}': {err}"))
+        })?;
+        if value >= M {
+            return Err(DeserializationError::InvalidValue(format!(
+                "invalid field element: value {value} is greater than or equal to the field modulus"
+            )));
+        }
+        Ok(Self::new(value))
+    }
+}
+
 // EQUALITY CHECKS
 // ================================================================================================
 
@@ -415,28 +482,23 @@ impl ExtensibleField<2> for BaseElement {
 impl ExtensibleField<3> for BaseElement {
     #[inline(always)]
     fn mul(a: [Self; 3], b: [Self; 3]) -> [Self; 3] {
-        // performs multiplication in the extension field using 6 multiplications, 9 additions,
-        // and 4 subtractions in the base field. overall, a single multiplication in the extension
-        // field is roughly equal to 12 multiplications in the base field.
-        let a0b0 = a[0] * b[0];
-        let a1b1 = a[1] * b[1];
-        let a2b2 = a[2] * b[2];
-
-        let a0b0_a0b1_a1b0_a1b1 = (a[0] + a[1]) * (b[0] + b[1]);
-        let a0b0_a0b2_a2b0_a2b2 = (a[0] + a[2]) * (b[0] + b[2]);
-        let a1b1_a1b2_a2b1_a2b2 = (a[1] + a[2]) * (b[1] + b[2]);
-
-        let a0b0_minus_a1b1 = a0b0 - a1b1;
-
-        let a0b0_a1b2_a2b1 = a1b1_a1b2_a2b1_a2b2 + a0b0_minus_a1b1 - a2b2;
-        let a0b1_a1b0_a1b2_a2b1_a2b2 =
-            a0b0_a0b1_a1b0_a1b1 + a1b1_a1b2_a2b1_a2b2 - a1b1.double() - a0b0;
-        let a0b2_a1b1_a2b0_a2b2 = a0b0_a0b2_a2b0_a2b2 - a0b0_minus_a1b1;
-
+        // performs multiplication in the extension field using 9 multiplications and 3 Montgomery
+        // reductions: the coefficients of the product polynomial are accumulated as 128-bit
+        // values, reduced modulo x^3 - x - 1 (i.e., x^3 = x + 1 and x^4 = x^2 + x), and only then
+        // reduced modulo the field modulus. this is faster than a Karatsuba-style schedule with 6
+        // multiplications because every multiplication in the base field requires a reduction.
+        let d0 = mul_wide(a[0], b[0]);
+        let d1 = add_wide(mul_wide(a[0], b[1]), mul_wide(a[1], b[0]));
+        let d2 = add_wide(
+            add_wide(mul_wide(a[0], b[2]), mul_wide(a[1], b[1])),
+            mul_wide(a[2], b[0]),
+        );
+        let d3 = add_wide(mul_wide(a[1], b[2]), mul_wide(a[2], b[1]));
+        let d4 = mul_wide(a[2], b[2]);
         [
-            a0b0_a1b2_a2b1,
-            a0b1_a1b0_a1b2_a2b1_a2b2,
-            a0b2_a1b1_a2b0_a2b2,
+            reduce_wide(add_wide(d0, d3)),
+            reduce_wide(add_wide(add_wide(d1, d3), d4)),
+            reduce_wide(add_wide(d2, d4)),
         ]
     }
 
@@ -526,7 +588,7 @@ impl From<[u8; 8]> for BaseElement {
     }
 }
 
-impl<'a> TryFrom<&'a [u8]> for BaseElement {
+impl TryFrom<&[u8]> for BaseElement {
     type Error = DeserializationError;
 
     /// Converts a slice of bytes into a field element; returns error if the value encoded in bytes
@@ -560,7 +622,22 @@ impl<'a> TryFrom<&'a [u8]> for BaseElement {
     }
 }
 
+impl From<BaseElement> for u64 {
+    /// Converts a field element into the canonical integer representation of the element.
+    fn from(value: BaseElement) -> Self {
+        value.as_int()
+    }
+}
+
+impl From<BaseElement> for u128 {
+    /// Converts a field element into the canonical integer representation of the element.
+    fn from(value: BaseElement) -> Self {
+        value.as_int() as u128
+    }
+}
+
 impl AsBytes for BaseElement {
+    #[allow(unsafe_code)]
     fn as_bytes(&self) -> &[u8] {
         // TODO: take endianness into account
         let self_ptr: *const BaseElement = self;
@@ -600,6 +677,31 @@ fn exp_acc<const N: usize>(base: BaseElement, tail: BaseElement) -> BaseElement
     result * tail
 }
 
+/// Returns the product of internal values of two field elements as a 128-bit value; the result is
+/// smaller than M * 2^64, and thus, can be passed to [reduce_wide()] directly.
+#[inline(always)]
+const fn mul_wide(a: BaseElement, b: BaseElement) -> u128 {
+    (a.0 as u128) * (b.0 as u128)
+}
+
+/// Returns (a + b) mod M * 2^64 for a and b in the range [0, M * 2^64).
+#[inline(always)]
+const fn add_wide(a: u128, b: u128) -> u128 {
+    let (sum, overflow) = a.overflowing_add(b);
+    let (reduced, underflow) = sum.overflowing_sub(M_WIDE);
+    if overflow || !underflow {
+        reduced
+    } else {
+        sum
+    }
+}
+
+/// Reduces a 128-bit accumulator in the range [0, M * 2^64) into a field element.
+#[inline(always)]
+const fn reduce_wide(x: u128) -> BaseElement {
+    BaseElement(mont_red_cst(x))
+}
+
 /// Montgomery reduction (variable time)
 #[allow(dead_code)]
 #[inline(always)]
@@ -113,6 +113,31 @@ fn exp() {
     assert_eq!(a.exp(7), a.exp7());
 }
 
+#[test]
+fn exp_variants() {
+    // by Fermat's little theorem, a^(p - 1) = 1 for all non-zero a
+    let m = BaseElement::MODULUS;
+    assert_eq!(
+        BaseElement::ONE,
+        rand_value::<BaseElement>().exp_const_time(m - 1)
+    );
+
+    let a: BaseElement = rand_value();
+    for power in [0, 1, 3, m - 1, m, u64::MAX] {
+        let expected = a.exp_vartime(power);
+        assert_eq!(expected, a.exp_const_time(power));
+        for window_bits in 1..=8 {
+            assert_eq!(expected, a.exp_windowed(power, window_bits));
+        }
+    }
+
+    for power in [0, 1, u64::MAX] {
+        let expected = BaseElement::ZERO.exp_vartime(power);
+        assert_eq!(expected, BaseElement::ZERO.exp_const_time(power));
+        assert_eq!(expected, BaseElement::ZERO.exp_windowed(power, 4));
+    }
+}
+
 #[test]
 fn inv() {
     // identity
@@ -127,6 +152,20 @@ fn element_as_int() {
     assert_eq!(v % super::M, e.as_int());
 }
 
+#[test]
+fn element_into_int() {
+    let e = BaseElement::new(super::M - 1);
+    assert_eq!(super::M - 1, u64::from(e));
+    assert_eq!((super::M - 1) as u128, u128::from(e));
+    assert_eq!(super::M - 1, u64::from(-BaseElement::ONE));
+
+    // elements are stored in Montgomery form, but are converted from their canonical
+    // representation
+    let e = e * e;
+    assert_eq!(1, u64::from(e));
+    assert_ne!(1, e.inner());
+}
+
 #[test]
 fn equals() {
     let a = BaseElement::ONE;
@@ -138,6 +177,41 @@ fn equals() {
     assert_eq!(a.to_bytes(), b.to_bytes());
 }
 
+// BOUNDARY VALUES
+// ------------------------------------------------------------------------------------------------
+
+/// Operands which exercise carry and reduction paths of the field arithmetic together with the
+/// expected values of a + b, a - b, a * b, and a^(-1). The expected values were computed with
+/// arbitrary-precision integers, and thus, they do not depend on the platform these tests run on.
+#[rustfmt::skip]
+const BOUNDARY_VALUES: [(u64, u64, u64, u64, u64, u64); 8] = [
+    (18446744069414584320, 18446744069414584320, 18446744069414584319, 0, 1, 18446744069414584320),
+    (18446744069414584320, 2, 1, 18446744069414584318, 18446744069414584319, 18446744069414584320),
+    (4294967296, 4294967296, 8589934592, 0, 4294967295, 18446744065119617026),
+    (4294967295, 4294967297, 8589934592, 18446744069414584319, 4294967294, 18446744065119617025),
+    (9223372036854775808, 9223372036854775808, 4294967295, 0, 18446744068340842497, 18446744060824649729),
+    (18446744065119617024, 4294967296, 18446744069414584320, 18446744060824649728, 18446744060824649730, 6148914691236517205),
+    (9223372034707292161, 18446744069414584318, 9223372034707292158, 9223372034707292164, 9223372034707292159, 2),
+    (1, 18446744069414584320, 0, 2, 18446744069414584320, 1),
+];
+
+#[test]
+fn boundary_values() {
+    for &(a, b, sum, diff, prod, inv) in BOUNDARY_VALUES.iter() {
+        let (a, b) = (BaseElement::new(a), BaseElement::new(b));
+        assert_eq!(sum, (a + b).as_int(), "failed for: {a} + {b}");
+        assert_eq!(diff, (a - b).as_int(), "failed for: {a} - {b}");
+        assert_eq!(prod, (a * b).as_int(), "failed for: {a} * {b}");
+        assert_eq!(inv, a.inv().as_int(), "failed for: {a}^(-1)");
+        assert_eq!(a * a, a.square(), "failed for: {a}^2");
+        assert_eq!(a + a, a.double(), "failed for: 2 * {a}");
+    }
+
+    // reduction of values which do not fit into the field
+    assert_eq!(4294967294, BaseElement::new(u64::MAX).as_int());
+    assert_eq!(18446744065119617024, BaseElement::from(u128::MAX).as_int());
+}
+
 // ROOTS OF UNITY
 // ------------------------------------------------------------------------------------------------
 
@@ -228,6 +302,75 @@ fn bytes_as_elements() {
     assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
 }
 
+#[test]
+fn elements_as_bytes_random() {
+    use utils::AsBytes;
+
+    let source = rand_utils::rand_vector::<BaseElement>(64);
+    let expected = source
+        .iter()
+        .flat_map(|e| e.inner().to_le_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(expected, BaseElement::elements_as_bytes(&source));
+
+    let element_bytes = source.iter().flat_map(|e| e.as_bytes().to_vec());
+    assert_eq!(expected, element_bytes.collect::<Vec<_>>());
+
+    let result = unsafe { BaseElement::bytes_as_elements(&expected) };
+    assert_eq!(source, result.unwrap());
+}
+
+// FORMATTING AND PARSING
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_hex() {
+    let a = BaseElement::new(0xabcd);
+    assert_eq!("43981", format!("{}", a));
+    assert_eq!("000000000000abcd", format!("{:x}", a));
+    assert_eq!("0x000000000000ABCD", format!("{:#X}", a));
+}
+
+#[test]
+fn parse_from_str() {
+    let a: BaseElement = rand_value();
+    assert_eq!(a, format!("{}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#x}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#X}", a).parse().unwrap());
+    assert_eq!(BaseElement::new(42), " 42 ".parse().unwrap());
+
+    assert!(matches!(
+        M.to_string().parse::<BaseElement>(),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        "0xzz".parse::<BaseElement>(),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn parse_extensions_from_str() {
+    let a: QuadExtension<BaseElement> = rand_value();
+    assert_eq!(a, format!("{}", a).parse().unwrap());
+    assert_eq!(a, format!("{:#X}", a).parse().unwrap());
+
+    let b: CubeExtension<BaseElement> = rand_value();
+    assert_eq!(b, format!("{}", b).parse().unwrap());
+    assert_eq!(b, format!("{:#X}", b).parse().unwrap());
+
+    let c = <QuadExtension<BaseElement>>::new(BaseElement::new(3), BaseElement::new(5));
+    assert_eq!(
+        "0x0000000000000003 + 0x0000000000000005*u",
+        format!("{}", c)
+    );
+    assert_eq!(c, "3 + 5*u".parse().unwrap());
+    assert!("3 + 5".parse::<QuadExtension<BaseElement>>().is_err());
+    assert!("3 + 5*u + 7*u^2"
+        .parse::<QuadExtension<BaseElement>>()
+        .is_err());
+}
+
 // INITIALIZATION
 // ------------------------------------------------------------------------------------------------
 
@@ -238,6 +381,20 @@ fn zeroed_vector() {
     for element in result.into_iter() {
         assert_eq!(BaseElement::ZERO, element);
     }
+
+    // specialized implementations must match the generic one
+    assert_eq!(
+        vec![BaseElement::ZERO; 1000],
+        BaseElement::zeroed_vector(1000)
+    );
+    assert_eq!(
+        vec![QuadExtension::<BaseElement>::ZERO; 1000],
+        QuadExtension::<BaseElement>::zeroed_vector(1000)
+    );
+    assert_eq!(
+        vec![CubeExtension::<BaseElement>::ZERO; 1000],
+        CubeExtension::<BaseElement>::zeroed_vector(1000)
+    );
 }
 
 // QUADRATIC EXTENSION
@@ -461,6 +618,14 @@ proptest! {
         prop_assert_eq!(expected, result.as_int());
     }
 
+    #[test]
+    fn exp_variants_proptest(a in any::<u64>(), b in any::<u64>(), window_bits in 1..=8u32) {
+        let a = BaseElement::from(a);
+        let expected = a.exp_vartime(b);
+        prop_assert_eq!(expected, a.exp_const_time(b));
+        prop_assert_eq!(expected, a.exp_windowed(b, window_bits));
+    }
+
     #[test]
     fn inv_proptest(a in any::<u64>()) {
         let a = BaseElement::from(a);
@@ -505,6 +670,17 @@ proptest! {
         prop_assert_eq!(expected, a.square());
     }
 
+    #[test]
+    fn quad_mul_naive_proptest(a in quad_strategy(), b in quad_strategy()) {
+        let expected = build_quad(naive_quad_mul(a, b));
+        prop_assert_eq!(expected, build_quad(a) * build_quad(b));
+        prop_assert_eq!(build_quad(naive_quad_mul(a, a)), build_quad(a).square());
+        prop_assert_eq!(
+            build_quad(naive_quad_mul(a, [b[0], BaseElement::ZERO])),
+            build_quad(a).mul_base(b[0])
+        );
+    }
+
     // CUBIC EXTENSION
     // --------------------------------------------------------------------------------------------
     #[test]
@@ -527,4 +703,68 @@ proptest! {
 
         prop_assert_eq!(expected, a.square());
     }
+
+    #[test]
+    fn cube_mul_naive_proptest(a in cube_strategy(), b in cube_strategy()) {
+        let expected = build_cube(naive_cube_mul(a, b));
+        prop_assert_eq!(expected, build_cube(a) * build_cube(b));
+        prop_assert_eq!(build_cube(naive_cube_mul(a, a)), build_cube(a).square());
+        prop_assert_eq!(
+            build_cube(naive_cube_mul(a, [b[0], BaseElement::ZERO, BaseElement::ZERO])),
+            build_cube(a).mul_base(b[0])
+        );
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns a strategy for base field elements which favors values next to zero and to the field
+/// modulus, as these are the values for which intermediate results of extension field
+/// multiplication are the closest to their bounds.
+fn element_strategy() -> impl Strategy<Value = BaseElement> {
+    prop_oneof![
+        any::<u64>(),
+        0..16u64,
+        (M - 16)..M,
+        (M >> 1) - 8..(M >> 1) + 8,
+    ]
+    .prop_map(BaseElement::new)
+}
+
+fn quad_strategy() -> impl Strategy<Value = [BaseElement; 2]> {
+    [element_strategy(), element_strategy()]
+}
+
+fn cube_strategy() -> impl Strategy<Value = [BaseElement; 3]> {
+    [element_strategy(), element_strategy(), element_strategy()]
+}
+
+fn build_quad(a: [BaseElement; 2]) -> QuadExtension<BaseElement> {
+    QuadExtension::new(a[0], a[1])
+}
+
+fn build_cube(a: [BaseElement; 3]) -> CubeExtension<BaseElement> {
+    CubeExtension::new(a[0], a[1], a[2])
+}
+
+/// Multiplies extension field elements via schoolbook multiplication of polynomials followed by
+/// reduction modulo x^2 - x + 2 (i.e., x^2 = x - 2).
+fn naive_quad_mul(a: [BaseElement; 2], b: [BaseElement; 2]) -> [BaseElement; 2] {
+    let d0 = a[0] * b[0];
+    let d1 = a[0] * b[1] + a[1] * b[0];
+    let d2 = a[1] * b[1];
+    [d0 - d2 - d2, d1 + d2]
+}
+
+/// Multiplies extension field elements via schoolbook multiplication of polynomials followed by
+/// reduction modulo x^3 - x - 1 (i.e., x^3 = x + 1 and x^4 = x^2 + x).
+fn naive_cube_mul(a: [BaseElement; 3], b: [BaseElement; 3]) -> [BaseElement; 3] {
+    let mut d = [BaseElement::ZERO; 5];
+    for i in 0..3 {
+        for j in 0..3 {
+            d[i + j] += a[i] * b[j];
+        }
+    }
+    [d[0] + d[3], d[1] + d[3] + d[4], d[2] + d[4]]
 }
@@ -6,9 +6,21 @@
 mod traits;
 pub use traits::{ExtensibleField, ExtensionOf, FieldElement, StarkField};
 
+#[cfg(feature = "f128")]
 pub mod f128;
+#[cfg(feature = "f62")]
 pub mod f62;
+#[cfg(feature = "f64")]
 pub mod f64;
 
+#[cfg(any(feature = "quadratic", feature = "cubic"))]
 mod extensions;
-pub use extensions::{CubeExtension, QuadExtension};
+#[cfg(feature = "cubic")]
+pub use extensions::CubeExtension;
+#[cfg(feature = "quadratic")]
+pub use extensions::QuadExtension;
+
+#[cfg(feature = "tracked")]
+mod tracked;
+#[cfg(feature = "tracked")]
+pub use tracked::Tracked;
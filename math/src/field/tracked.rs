@@ -0,0 +1,312 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{ExtensionOf, FieldElement, StarkField};
+use core::{
+    convert::TryFrom,
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+use utils::{
+    collections::Vec, string::ToString, AsBytes, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Randomizable, Serializable,
+};
+
+// TRACKED ELEMENT
+// ================================================================================================
+
+/// A base field element which records the inputs of a computation it was derived from.
+///
+/// Inputs are identified by their positions in a batch of at most 64 inputs; bit `i` of the read
+/// set of an element is set if the element depends on the input at position `i`. Every
+/// arithmetic operation combines the read sets of its operands, while comparisons look only at
+/// the values of the elements. Thus, a dependency which exists only through a branch on a value
+/// is not recorded.
+///
+/// This type is meant for instrumentation of generic code (e.g., evaluation of transition
+/// constraints) rather than for arithmetic. Elements cannot be re-interpreted as slices of bytes
+/// or of base field elements; the corresponding methods of [FieldElement] panic.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Tracked<B: StarkField> {
+    value: B,
+    reads: u64,
+}
+
+impl<B: StarkField> Tracked<B> {
+    /// Returns an element which holds the specified value and depends only on the input at the
+    /// specified position of a batch.
+    ///
+    /// # Panics
+    /// Panics if `position` is greater than or equal to 64.
+    pub fn input(value: B, position: usize) -> Self {
+        assert!(position < 64, "input position must be smaller than 64");
+        Self {
+            value,
+            reads: 1 << position,
+        }
+    }
+
+    /// Returns the value of this element.
+    pub fn value(&self) -> B {
+        self.value
+    }
+
+    /// Returns positions of the inputs this element depends on, encoded as a bitmask.
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+
+    fn untracked(value: B) -> Self {
+        Self { value, reads: 0 }
+    }
+}
+
+impl<B: StarkField> FieldElement for Tracked<B> {
+    type PositiveInteger = B::PositiveInteger;
+    type BaseField = B;
+
+    const ELEMENT_BYTES: usize = B::ELEMENT_BYTES;
+    const IS_CANONICAL: bool = B::IS_CANONICAL;
+    const ZERO: Self = Self {
+        value: B::ZERO,
+        reads: 0,
+    };
+    const ONE: Self = Self {
+        value: B::ONE,
+        reads: 0,
+    };
+
+    fn inv(self) -> Self {
+        Self {
+            value: self.value.inv(),
+            reads: self.reads,
+        }
+    }
+
+    fn conjugate(&self) -> Self {
+        *self
+    }
+
+    fn elements_as_bytes(_elements: &[Self]) -> &[u8] {
+        unimplemented!("tracked elements cannot be viewed as bytes")
+    }
+
+    #[allow(unsafe_code)]
+    unsafe fn bytes_as_elements(_bytes: &[u8]) -> Result<&[Self], DeserializationError> {
+        unimplemented!("bytes cannot be viewed as tracked elements")
+    }
+
+    fn as_base_elements(_elements: &[Self]) -> &[Self::BaseField] {
+        unimplemented!("tracked elements cannot be viewed as base field elements")
+    }
+
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField> {
+        elements.iter().map(|element| element.value).collect()
+    }
+}
+
+impl<B: StarkField> ExtensionOf<B> for Tracked<B> {
+    fn mul_base(self, other: B) -> Self {
+        Self {
+            value: self.value * other,
+            reads: self.reads,
+        }
+    }
+}
+
+impl<B: StarkField> PartialEq for Tracked<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<B: StarkField> Eq for Tracked<B> {}
+
+impl<B: StarkField> Randomizable for Tracked<B> {
+    const VALUE_SIZE: usize = B::VALUE_SIZE;
+
+    fn from_random_bytes(source: &[u8]) -> Option<Self> {
+        B::from_random_bytes(source).map(Self::untracked)
+    }
+}
+
+// FORMATTING AND SERIALIZATION
+// ------------------------------------------------------------------------------------------------
+
+impl<B: StarkField> fmt::Display for Tracked<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
+impl<B: StarkField> fmt::LowerHex for Tracked<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.value, f)
+    }
+}
+
+impl<B: StarkField> fmt::UpperHex for Tracked<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.value, f)
+    }
+}
+
+impl<B: StarkField> FromStr for Tracked<B> {
+    type Err = DeserializationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        B::from_str(s).map(Self::untracked)
+    }
+}
+
+impl<B: StarkField> AsBytes for Tracked<B> {
+    fn as_bytes(&self) -> &[u8] {
+        self.value.as_bytes()
+    }
+}
+
+impl<B: StarkField> Serializable for Tracked<B> {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.value.write_into(target);
+    }
+}
+
+impl<B: StarkField> Deserializable for Tracked<B> {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        B::read_from(source).map(Self::untracked)
+    }
+}
+
+// OVERLOADED OPERATORS
+// ------------------------------------------------------------------------------------------------
+
+impl<B: StarkField> Add for Tracked<B> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            value: self.value + rhs.value,
+            reads: self.reads | rhs.reads,
+        }
+    }
+}
+
+impl<B: StarkField> AddAssign for Tracked<B> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl<B: StarkField> Sub for Tracked<B> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            value: self.value - rhs.value,
+            reads: self.reads | rhs.reads,
+        }
+    }
+}
+
+impl<B: StarkField> SubAssign for Tracked<B> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl<B: StarkField> Mul for Tracked<B> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            value: self.value * rhs.value,
+            reads: self.reads | rhs.reads,
+        }
+    }
+}
+
+impl<B: StarkField> MulAssign for Tracked<B> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl<B: StarkField> Div for Tracked<B> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self {
+            value: self.value / rhs.value,
+            reads: self.reads | rhs.reads,
+        }
+    }
+}
+
+impl<B: StarkField> DivAssign for Tracked<B> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs
+    }
+}
+
+impl<B: StarkField> Neg for Tracked<B> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            value: -self.value,
+            reads: self.reads,
+        }
+    }
+}
+
+// TYPE CONVERSIONS
+// ------------------------------------------------------------------------------------------------
+
+impl<B: StarkField> From<B> for Tracked<B> {
+    fn from(value: B) -> Self {
+        Self::untracked(value)
+    }
+}
+
+impl<B: StarkField> From<u128> for Tracked<B> {
+    fn from(value: u128) -> Self {
+        Self::untracked(B::from(value))
+    }
+}
+
+impl<B: StarkField> From<u64> for Tracked<B> {
+    fn from(value: u64) -> Self {
+        Self::untracked(B::from(value))
+    }
+}
+
+impl<B: StarkField> From<u32> for Tracked<B> {
+    fn from(value: u32) -> Self {
+        Self::untracked(B::from(value))
+    }
+}
+
+impl<B: StarkField> From<u16> for Tracked<B> {
+    fn from(value: u16) -> Self {
+        Self::untracked(B::from(value))
+    }
+}
+
+impl<B: StarkField> From<u8> for Tracked<B> {
+    fn from(value: u8) -> Self {
+        Self::untracked(B::from(value))
+    }
+}
+
+impl<'a, B: StarkField> TryFrom<&'a [u8]> for Tracked<B> {
+    type Error = DeserializationError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        B::try_from(bytes)
+            .map(Self::untracked)
+            .map_err(|_| DeserializationError::InvalidValue("invalid field element".to_string()))
+    }
+}
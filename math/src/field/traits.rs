@@ -5,11 +5,13 @@
 
 use core::{
     convert::TryFrom,
-    fmt::{Debug, Display},
+    fmt::{Debug, Display, LowerHex, UpperHex},
+    mem,
     ops::{
         Add, AddAssign, BitAnd, Div, DivAssign, Mul, MulAssign, Neg, Shl, Shr, ShrAssign, Sub,
         SubAssign,
     },
+    str::FromStr,
 };
 use utils::{
     collections::Vec, AsBytes, Deserializable, DeserializationError, Randomizable, Serializable,
@@ -31,6 +33,9 @@ pub trait FieldElement:
     + Clone
     + Debug
     + Display
+    + LowerHex
+    + UpperHex
+    + FromStr<Err = DeserializationError>
     + Default
     + Send
     + Sync
@@ -145,6 +150,102 @@ pub trait FieldElement:
         r
     }
 
+    /// Exponentiates this field element by `power` parameter in constant time.
+    ///
+    /// The exponentiation is performed using Montgomery ladder: the same sequence of field
+    /// operations is executed for every bit of `power` (including its leading zero bits), and
+    /// the ladder registers are swapped arithmetically rather than by branching on the bits.
+    /// Thus, this function should be used when either the element or the `power` is secret.
+    #[must_use]
+    fn exp_const_time(self, power: Self::PositiveInteger) -> Self {
+        let int_one = Self::PositiveInteger::from(1u32);
+        let num_bits = (mem::size_of::<Self::PositiveInteger>() * 8) as u32;
+
+        // r1 = r0 * self holds after every step; for a set bit, r0 and r1 are swapped before the
+        // step and swapped back after it, and thus, consecutive swaps can be merged together
+        let mut r0 = Self::ONE;
+        let mut r1 = self;
+        let mut swapped = false;
+        for i in (0..num_bits).rev() {
+            let bit = (power >> i) & int_one == int_one;
+            conditional_swap(&mut r0, &mut r1, bit ^ swapped);
+            swapped = bit;
+            r1 *= r0;
+            r0 = r0.square();
+        }
+        conditional_swap(&mut r0, &mut r1, swapped);
+
+        r0
+    }
+
+    /// Exponentiates this field element by `power` parameter using a fixed window of
+    /// `window_bits` bits.
+    ///
+    /// Powers of this element up to 2^`window_bits` - 1 are computed first, and then `power` is
+    /// processed `window_bits` bits at a time starting with its most significant bits. For large
+    /// exponents, this requires fewer multiplications than
+    /// [exp_vartime()](FieldElement::exp_vartime). This function is also variable time, and
+    /// thus, it should be used with public exponents only.
+    ///
+    /// # Panics
+    /// Panics if `window_bits` is zero or greater than 8.
+    #[must_use]
+    fn exp_windowed(self, power: Self::PositiveInteger, window_bits: u32) -> Self {
+        assert!(
+            (1..=8).contains(&window_bits),
+            "window must be between 1 and 8 bits, but was {window_bits}"
+        );
+
+        let int_zero = Self::PositiveInteger::from(0u32);
+        let int_one = Self::PositiveInteger::from(1u32);
+
+        if power == int_zero {
+            return Self::ONE;
+        } else if self == Self::ZERO {
+            return Self::ZERO;
+        }
+
+        // compute self^0, ..., self^(2^window_bits - 1)
+        let table_size = 1 << window_bits;
+        let mut table = Vec::with_capacity(table_size);
+        table.push(Self::ONE);
+        table.push(self);
+        for i in 2..table_size {
+            table.push(table[i - 1] * self);
+        }
+
+        // determine the number of windows needed to cover all significant bits of power
+        let mut num_bits = 0;
+        let mut p = power;
+        while p > int_zero {
+            p >>= int_one;
+            num_bits += 1;
+        }
+        let num_windows = (num_bits + window_bits - 1) / window_bits;
+
+        let mut r = Self::ONE;
+        for window in (0..num_windows).rev() {
+            if window + 1 < num_windows {
+                for _ in 0..window_bits {
+                    r = r.square();
+                }
+            }
+
+            let mut digit = 0;
+            for i in (window * window_bits..(window + 1) * window_bits).rev() {
+                digit <<= 1;
+                if i < num_bits && (power >> i) & int_one == int_one {
+                    digit |= 1;
+                }
+            }
+            if digit != 0 {
+                r *= table[digit];
+            }
+        }
+
+        r
+    }
+
     /// Returns a multiplicative inverse of this field element. If this element is ZERO, ZERO is
     /// returned.
     #[must_use]
@@ -178,6 +279,7 @@ pub trait FieldElement:
     /// # Safety
     /// This function is unsafe because it does not check whether underlying bytes represent valid
     /// field elements according to their internal representation.
+    #[allow(unsafe_code)]
     unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError>;
 
     // UTILITIES
@@ -196,6 +298,12 @@ pub trait FieldElement:
     /// output list will contain decompositions of each extension element into underlying base
     /// elements.
     fn as_base_elements(elements: &[Self]) -> &[Self::BaseField];
+
+    /// Copies a list of field elements into a list of elements in the underlying base field.
+    ///
+    /// The output list is the same as the one returned by [FieldElement::as_base_elements()], but
+    /// the conversion does not re-interpret the underlying memory.
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField>;
 }
 
 // STARK FIELD
@@ -300,3 +408,15 @@ impl<E: FieldElement> ExtensionOf<E> for E {
         self * other
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Swaps `a` and `b` if `choice` is true; the swap is computed arithmetically, and thus, the same
+/// field operations are executed regardless of the value of `choice`.
+#[inline(always)]
+fn conditional_swap<E: FieldElement>(a: &mut E, b: &mut E, choice: bool) {
+    let delta = E::from(choice as u8) * (*a - *b);
+    *a -= delta;
+    *b += delta;
+}
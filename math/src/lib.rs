@@ -49,6 +49,10 @@
 //! * For [f64](crate::fields::f64) field, the polynomial is x<sup>3</sup> - x - 1.
 //! * For [f128](crate::fields::f128) field, cubic extensions are not supported.
 //!
+//! Each of the fields and each of the extensions is compiled only when the corresponding crate
+//! feature (`f62`, `f64`, `f128`, `quadratic`, and `cubic`) is enabled; all of them are enabled
+//! by default.
+//!
 //! # Polynomials
 //! [Polynomials](polynom) module implements basic polynomial operations such as:
 //!
@@ -88,6 +92,7 @@
 //! Number of threads can be configured via `RAYON_NUM_THREADS` environment variable
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(all(feature = "safe_only", not(test)), deny(unsafe_code))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -104,11 +109,18 @@ pub mod fields {
     //! This module contains concrete implementations of base STARK fields as well as extensions
     //! of these field.
 
+    #[cfg(feature = "f128")]
     pub use super::field::f128;
+    #[cfg(feature = "f62")]
     pub use super::field::f62;
+    #[cfg(feature = "f64")]
     pub use super::field::f64;
+    #[cfg(feature = "cubic")]
     pub use super::field::CubeExtension;
+    #[cfg(feature = "quadratic")]
     pub use super::field::QuadExtension;
+    #[cfg(feature = "tracked")]
+    pub use super::field::Tracked;
 }
 
 mod utils;
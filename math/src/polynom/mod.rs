@@ -28,7 +28,7 @@ use crate::{field::FieldElement, utils::batch_inversion};
 use core::mem;
 use utils::{collections::Vec, group_vector_elements};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // POLYNOMIAL EVALUATION
@@ -636,7 +636,11 @@ where
 // HELPER FUNCTIONS
 // ================================================================================================
 fn get_zero_roots<E: FieldElement>(xs: &[E]) -> Vec<E> {
+    #[cfg(not(feature = "safe_only"))]
     let mut result = unsafe { utils::uninit_vector(xs.len() + 1) };
+    #[cfg(feature = "safe_only")]
+    let mut result = vec![E::ZERO; xs.len() + 1];
+
     fill_zero_roots(xs, &mut result);
     result
 }
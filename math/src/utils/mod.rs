@@ -4,7 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::{field::FieldElement, ExtensionOf};
-use utils::{batch_iter_mut, collections::Vec, iter_mut, uninit_vector};
+use utils::{batch_iter_mut, collections::Vec, iter_mut};
 
 #[cfg(feature = "concurrent")]
 use utils::iterators::*;
@@ -37,7 +37,11 @@ pub fn get_power_series<E>(b: E, n: usize) -> Vec<E>
 where
     E: FieldElement,
 {
-    let mut result = unsafe { uninit_vector(n) };
+    #[cfg(not(feature = "safe_only"))]
+    let mut result = unsafe { utils::uninit_vector(n) };
+    #[cfg(feature = "safe_only")]
+    let mut result = vec![E::ZERO; n];
+
     batch_iter_mut!(&mut result, 1024, |batch: &mut [E], batch_offset: usize| {
         let start = b.exp((batch_offset as u64).into());
         fill_power_series(batch, b, start);
@@ -72,7 +76,11 @@ pub fn get_power_series_with_offset<E>(b: E, s: E, n: usize) -> Vec<E>
 where
     E: FieldElement,
 {
-    let mut result = unsafe { uninit_vector(n) };
+    #[cfg(not(feature = "safe_only"))]
+    let mut result = unsafe { utils::uninit_vector(n) };
+    #[cfg(feature = "safe_only")]
+    let mut result = vec![E::ZERO; n];
+
     batch_iter_mut!(&mut result, 1024, |batch: &mut [E], batch_offset: usize| {
         let start = s * b.exp((batch_offset as u64).into());
         fill_power_series(batch, b, start);
@@ -178,7 +186,11 @@ pub fn batch_inversion<E>(values: &[E]) -> Vec<E>
 where
     E: FieldElement,
 {
-    let mut result: Vec<E> = unsafe { uninit_vector(values.len()) };
+    #[cfg(not(feature = "safe_only"))]
+    let mut result: Vec<E> = unsafe { utils::uninit_vector(values.len()) };
+    #[cfg(feature = "safe_only")]
+    let mut result: Vec<E> = vec![E::ZERO; values.len()];
+
     batch_iter_mut!(&mut result, 1024, |batch: &mut [E], batch_offset: usize| {
         let start = batch_offset;
         let end = start + batch.len();
@@ -0,0 +1,150 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::time::Duration;
+use winter_prover::{
+    crypto::hashers::Blake3_256,
+    iterators::MIN_CHUNK_SIZE,
+    math::{fields::f64::BaseElement, FieldElement},
+    Air, AirContext, Assertion, EvaluationFrame, FieldExtension, ProofOptions, Prover,
+    ProverConfig, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+// Compares proof generation time with adaptive chunk sizes against fixed chunk sizes which
+// match the previous behavior of the prover (one chunk per thread) for traces of different
+// widths.
+
+const TRACE_WIDTHS: [usize; 3] = [2, 16, 96];
+const TRACE_LENGTH: usize = 1 << 20;
+
+fn prove_chunking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prove_chunking");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(30));
+
+    #[cfg(feature = "concurrent")]
+    let num_threads = winter_prover::iterators::rayon_num_threads().next_power_of_two();
+    #[cfg(not(feature = "concurrent"))]
+    let num_threads = 1;
+
+    for &width in TRACE_WIDTHS.iter() {
+        let adaptive = SquaresProver::new(ProverConfig::new());
+        group.bench_function(BenchmarkId::new("adaptive", width), |bench| {
+            bench.iter_batched(
+                || build_trace(width, TRACE_LENGTH),
+                |trace| adaptive.prove(trace).unwrap(),
+                BatchSize::LargeInput,
+            );
+        });
+
+        // the constraint evaluation domain for this AIR is 2x the trace length
+        let chunk_size = (2 * TRACE_LENGTH / num_threads).max(MIN_CHUNK_SIZE);
+        let fixed = SquaresProver::new(ProverConfig::new().with_chunk_size(chunk_size));
+        group.bench_function(BenchmarkId::new("fixed", width), |bench| {
+            bench.iter_batched(
+                || build_trace(width, TRACE_LENGTH),
+                |trace| fixed.prove(trace).unwrap(),
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(chunking_group, prove_chunking);
+criterion_main!(chunking_group);
+
+// SQUARES AIR
+// ================================================================================================
+
+/// An AIR in which every column of the trace squares its value at each step.
+struct SquaresAir {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for SquaresAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let width = trace_info.width();
+        let degrees = vec![TransitionConstraintDegree::new(2); width];
+        SquaresAir {
+            context: AirContext::new(trace_info, degrees, width, options),
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        for (i, result) in result.iter_mut().enumerate() {
+            *result = next[i] - current[i].square();
+        }
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        (0..self.trace_info().width())
+            .map(|i| Assertion::single(i, 0, BaseElement::from(i as u32 + 2)))
+            .collect()
+    }
+}
+
+struct SquaresProver {
+    options: ProofOptions,
+    config: ProverConfig,
+}
+
+impl SquaresProver {
+    fn new(config: ProverConfig) -> Self {
+        SquaresProver {
+            options: ProofOptions::new(28, 4, 0, FieldExtension::Quadratic, 8, 256),
+            config,
+        }
+    }
+}
+
+impl Prover for SquaresProver {
+    type BaseField = BaseElement;
+    type Air = SquaresAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256<BaseElement>;
+
+    fn get_pub_inputs(&self, _trace: &Self::Trace) {}
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    fn config(&self) -> ProverConfig {
+        self.config
+    }
+}
+
+fn build_trace(width: usize, length: usize) -> TraceTable<BaseElement> {
+    let mut trace = TraceTable::new(width, length);
+    trace.fill(
+        |state| {
+            for (i, value) in state.iter_mut().enumerate() {
+                *value = BaseElement::from(i as u32 + 2);
+            }
+        },
+        |_, state| {
+            for value in state.iter_mut() {
+                *value = value.square();
+            }
+        },
+    );
+    trace
+}
@@ -0,0 +1,53 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand_utils::rand_vector;
+use std::time::Duration;
+use winter_prover::{
+    crypto::{hashers::Blake3_256, MerkleTree},
+    math::fields::f64::BaseElement,
+    Matrix, ProverConfig,
+};
+
+// Compares the time needed to commit to rows of a very wide matrix when rows are gathered into a
+// buffer before hashing against the time needed when rows are streamed into the hasher.
+
+const NUM_COLS: usize = 300;
+const NUM_ROWS: usize = 1 << 20;
+
+type Blake3 = Blake3_256<BaseElement>;
+
+fn commit_to_rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("commit_to_rows");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(30));
+
+    let matrix = Matrix::new(
+        (0..NUM_COLS)
+            .map(|_| rand_vector::<BaseElement>(NUM_ROWS))
+            .collect(),
+    );
+
+    let buffered = ProverConfig::new().with_streaming_width(usize::MAX);
+    group.bench_function(BenchmarkId::new("buffered", NUM_COLS), |bench| {
+        bench.iter(|| {
+            let tree: MerkleTree<Blake3> = matrix.commit_to_rows_with_config(&buffered);
+            tree
+        });
+    });
+
+    let streamed = ProverConfig::new().with_streaming_width(0);
+    group.bench_function(BenchmarkId::new("streamed", NUM_COLS), |bench| {
+        bench.iter(|| {
+            let tree: MerkleTree<Blake3> = matrix.commit_to_rows_with_config(&streamed);
+            tree
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(commitment_group, commit_to_rows);
+criterion_main!(commitment_group);
@@ -0,0 +1,61 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+use winter_prover::{
+    math::{fields::f64::BaseElement, FieldElement},
+    TraceTable,
+};
+
+// Compares the time needed to fill a trace row by row against the time needed to fill the same
+// trace column by column; with `concurrent` feature enabled, columns are filled in parallel, and
+// the speedup should be close to the number of threads for traces wider than the thread pool.
+
+const TRACE_WIDTHS: [usize; 2] = [8, 64];
+const TRACE_LENGTH: usize = 1 << 22;
+
+fn fill_trace(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fill_trace");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    for &width in TRACE_WIDTHS.iter() {
+        group.bench_function(BenchmarkId::new("fill", width), |bench| {
+            let mut trace = TraceTable::<BaseElement>::new(width, TRACE_LENGTH);
+            bench.iter(|| {
+                trace.fill(
+                    |state| {
+                        for (i, value) in state.iter_mut().enumerate() {
+                            *value = BaseElement::from(i as u32 + 2);
+                        }
+                    },
+                    |_, state| {
+                        for value in state.iter_mut() {
+                            *value = value.square();
+                        }
+                    },
+                )
+            });
+        });
+
+        group.bench_function(BenchmarkId::new("fill_columns", width), |bench| {
+            let mut trace = TraceTable::<BaseElement>::new(width, TRACE_LENGTH);
+            bench.iter(|| {
+                trace.fill_columns(|i, column| {
+                    let mut value = BaseElement::from(i as u32 + 2);
+                    for cell in column.iter_mut() {
+                        *cell = value;
+                        value = value.square();
+                    }
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(fill_group, fill_trace);
+criterion_main!(fill_group);
@@ -0,0 +1,75 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::time::Duration;
+use winter_prover::{
+    crypto::{hashers::Rp64_256, DefaultRandomCoin, HashFunctionId},
+    find_pow_nonce,
+    math::fields::f64::BaseElement,
+    FieldExtension, PowPuzzle, ProofOptions,
+};
+
+// Compares the throughput of query seed grinding when proof-of-work is computed with the hash
+// function of the protocol (Rescue Prime) against grinding with byte-oriented hash functions
+// selected via proof options. The throughput is reported as the number of nonces tried per
+// second; the number of nonces is the value of the smallest nonce providing the target bits.
+//
+// Grinding with Rescue Prime is benchmarked only for targets of up to 20 bits, as grinding for
+// 24 bits takes too long for a benchmark.
+
+const GRINDING_FACTORS: [u32; 3] = [16, 20, 24];
+const MAX_PROOF_HASH_GRINDING_FACTOR: u32 = 20;
+
+type Coin = DefaultRandomCoin<BaseElement, Rp64_256>;
+
+fn grinding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grinding");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    let coin = Coin::new(&[1, 2, 3, 4]);
+    let base_options = ProofOptions::new(28, 8, 0, FieldExtension::Quadratic, 4, 256);
+    let setups = [
+        ("rp64_256", base_options.clone()),
+        (
+            "blake3_256",
+            base_options
+                .clone()
+                .with_grinding_hash(HashFunctionId::Blake3_256),
+        ),
+        (
+            "blake3_256_wide",
+            base_options
+                .clone()
+                .with_grinding_hash(HashFunctionId::Blake3_256)
+                .with_wide_grinding_nonce(),
+        ),
+        (
+            "sha3_256",
+            base_options.with_grinding_hash(HashFunctionId::Sha3_256),
+        ),
+    ];
+
+    for grinding_factor in GRINDING_FACTORS {
+        for (name, options) in setups.iter() {
+            if options.grinding_hash().is_none() && grinding_factor > MAX_PROOF_HASH_GRINDING_FACTOR
+            {
+                continue;
+            }
+
+            let puzzle = PowPuzzle::new(options, &coin).unwrap();
+            let nonce = find_pow_nonce(&puzzle, grinding_factor);
+            group.throughput(Throughput::Elements(nonce as u64));
+            group.bench_function(BenchmarkId::new(*name, grinding_factor), |bench| {
+                bench.iter(|| find_pow_nonce(&puzzle, grinding_factor));
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(grinding_group, grinding);
+criterion_main!(grinding_group);
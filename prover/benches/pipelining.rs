@@ -0,0 +1,232 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::time::Duration;
+use winter_prover::{
+    crypto::hashers::Blake3_256,
+    math::{fields::f64::BaseElement, ExtensionOf, FieldElement},
+    Air, AirContext, Assertion, AuxTraceRandElements, EvaluationFrame, FieldExtension, Matrix,
+    ProofOptions, Prover, ProverConfig, Trace, TraceInfo, TraceLayout, TraceLayoutBuilder,
+    TransitionConstraintDegree,
+};
+
+// Compares proof generation time when transition constraints are evaluated while the trace is
+// being hashed (the default when `concurrent` feature is enabled) against evaluating all
+// constraints after the trace commitment has been built. Traces are benchmarked with and without
+// an auxiliary segment; the overlap requires `concurrent` feature to be enabled.
+
+const TRACE_WIDTHS: [usize; 2] = [2, 8];
+const TRACE_LENGTH: usize = 1 << 22;
+
+fn prove_pipelining(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prove_pipelining");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(60));
+
+    for with_aux in [false, true] {
+        for &width in TRACE_WIDTHS.iter() {
+            let param = if with_aux {
+                format!("{width}+aux")
+            } else {
+                width.to_string()
+            };
+            for pipelined in [false, true] {
+                let name = if pipelined { "pipelined" } else { "sequential" };
+                let config = ProverConfig::new().with_pipelined_evaluation(pipelined);
+                let prover = SquaresProver::new(config);
+                group.bench_function(BenchmarkId::new(name, &param), |bench| {
+                    bench.iter_batched(
+                        || SquaresTrace::new(width, TRACE_LENGTH, with_aux),
+                        |trace| prover.prove(trace).unwrap(),
+                        BatchSize::LargeInput,
+                    );
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(pipelining_group, prove_pipelining);
+criterion_main!(pipelining_group);
+
+// SQUARES AIR
+// ================================================================================================
+
+/// An AIR in which every column of the trace squares its value at each step.
+struct SquaresAir {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for SquaresAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let width = trace_info.layout().main_trace_width();
+        let degrees = vec![TransitionConstraintDegree::new(2); width];
+        let context = if trace_info.is_multi_segment() {
+            let aux_degrees = vec![TransitionConstraintDegree::new(1)];
+            AirContext::new_multi_segment(trace_info, degrees, aux_degrees, width, 1, options)
+        } else {
+            AirContext::new(trace_info, degrees, width, options)
+        };
+        SquaresAir { context }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        for (i, result) in result.iter_mut().enumerate() {
+            *result = next[i] - current[i].square();
+        }
+    }
+
+    fn evaluate_aux_transition<F, E>(
+        &self,
+        main_frame: &EvaluationFrame<F>,
+        aux_frame: &EvaluationFrame<E>,
+        _periodic_values: &[F],
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        result: &mut [E],
+    ) where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+        let alpha = aux_rand_elements.get_segment_elements(0)[0];
+        let value = main_frame.current()[0];
+        result[0] = aux_frame.next()[0] - (aux_frame.current()[0] + alpha.mul_base(value));
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        (0..self.trace_info().layout().main_trace_width())
+            .map(|i| Assertion::single(i, 0, BaseElement::from(i as u32 + 2)))
+            .collect()
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        _aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Vec<Assertion<E>> {
+        vec![Assertion::single(0, 0, E::ZERO)]
+    }
+}
+
+struct SquaresProver {
+    options: ProofOptions,
+    config: ProverConfig,
+}
+
+impl SquaresProver {
+    fn new(config: ProverConfig) -> Self {
+        SquaresProver {
+            options: ProofOptions::new(28, 4, 0, FieldExtension::Quadratic, 8, 256),
+            config,
+        }
+    }
+}
+
+impl Prover for SquaresProver {
+    type BaseField = BaseElement;
+    type Air = SquaresAir;
+    type Trace = SquaresTrace;
+    type HashFn = Blake3_256<BaseElement>;
+
+    fn get_pub_inputs(&self, _trace: &Self::Trace) {}
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    fn config(&self) -> ProverConfig {
+        self.config
+    }
+}
+
+// SQUARES TRACE
+// ================================================================================================
+
+/// A trace in which every column of the main segment squares its value at each step; when an
+/// auxiliary segment is present, it consists of a running sum of the first main column.
+struct SquaresTrace {
+    layout: TraceLayout,
+    main_segment: Matrix<BaseElement>,
+}
+
+impl SquaresTrace {
+    fn new(width: usize, length: usize, with_aux: bool) -> Self {
+        let columns = (0..width)
+            .map(|i| {
+                let mut column = vec![BaseElement::from(i as u32 + 2); length];
+                for step in 1..length {
+                    column[step] = column[step - 1].square();
+                }
+                column
+            })
+            .collect();
+        let mut layout = TraceLayoutBuilder::new().main_width(width);
+        if with_aux {
+            layout = layout.aux_segment(1, 1);
+        }
+        SquaresTrace {
+            layout: layout.build().expect("invalid trace layout"),
+            main_segment: Matrix::new(columns),
+        }
+    }
+}
+
+impl Trace for SquaresTrace {
+    type BaseField = BaseElement;
+
+    fn layout(&self) -> &TraceLayout {
+        &self.layout
+    }
+
+    fn length(&self) -> usize {
+        self.main_segment.num_rows()
+    }
+
+    fn meta(&self) -> &[u8] {
+        &[]
+    }
+
+    fn main_segment(&self) -> &Matrix<BaseElement> {
+        &self.main_segment
+    }
+
+    fn build_aux_segment<E: FieldElement<BaseField = Self::BaseField>>(
+        &mut self,
+        aux_segments: &[Matrix<E>],
+        rand_elements: &[E],
+    ) -> Option<Matrix<E>> {
+        if !aux_segments.is_empty() {
+            return None;
+        }
+        let mut column = vec![E::ZERO; self.length()];
+        for step in 1..self.length() {
+            let value = self.main_segment.get(0, step - 1);
+            column[step] = column[step - 1] + rand_elements[0].mul_base(value);
+        }
+        Some(Matrix::new(vec![column]))
+    }
+
+    fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<Self::BaseField>) {
+        for i in 0..frame.num_rows() {
+            let frame_row_idx = (row_idx + frame.offsets()[i]) % self.length();
+            self.main_segment
+                .read_row_into(frame_row_idx, frame.row_mut(i));
+        }
+    }
+}
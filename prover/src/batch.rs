@@ -0,0 +1,110 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Generation of proofs for batches of execution traces.
+//!
+//! When many small traces of the same computation are proven, fixed per-proof costs (e.g.,
+//! computing FFT twiddles and the constraint evaluation domain) dominate proof generation time.
+//! [prove_batch()] amortizes these costs by building a [StarkDomain] once for every distinct
+//! trace length in the batch and sharing it between all proofs for traces of this length.
+//!
+//! When the crate is compiled with `concurrent` feature enabled, proofs in a batch are also
+//! generated in parallel. At most as many proofs as there are threads in the rayon thread pool
+//! are generated at the same time, and thus, the memory required by proof generation does not
+//! grow with the size of the batch.
+
+use crate::{Air, Prover, ProverError, StarkDomain, StarkProof, Trace};
+use utils::collections::{BTreeMap, Vec};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
+
+// BATCH PROVER
+// ================================================================================================
+
+/// Returns STARK proofs attesting to correct executions of a computation defined by the provided
+/// traces.
+///
+/// The proofs are returned in the same order as the traces, and are identical to the proofs
+/// which would be returned by [Prover::prove()] for each trace. A failure to generate a proof for
+/// one of the traces does not affect generation of proofs for other traces.
+///
+/// Traces of the same length share the same FFT twiddles and constraint evaluation domain. In
+/// `concurrent` mode, traces are proven in chunks of as many traces as there are threads in the
+/// rayon thread pool, with proofs within a chunk being generated in parallel.
+pub fn prove_batch<P>(prover: &P, traces: Vec<P::Trace>) -> Vec<Result<StarkProof, ProverError>>
+where
+    P: Prover + Sync,
+    P::Trace: Send,
+{
+    let domains = build_domains(prover, &traces);
+
+    #[cfg(not(feature = "concurrent"))]
+    let result = traces
+        .into_iter()
+        .map(|trace| prove_with_shared_domain(prover, &domains, trace))
+        .collect();
+
+    #[cfg(feature = "concurrent")]
+    let result = {
+        let max_concurrent_proofs = rayon_num_threads();
+        let mut result = Vec::with_capacity(traces.len());
+        let mut traces = traces.into_iter();
+        loop {
+            let chunk = traces
+                .by_ref()
+                .take(max_concurrent_proofs)
+                .collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
+            }
+            result.par_extend(
+                chunk
+                    .into_par_iter()
+                    .map(|trace| prove_with_shared_domain(prover, &domains, trace)),
+            );
+        }
+        result
+    };
+
+    result
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds a STARK domain for every distinct trace length among the specified traces.
+///
+/// A domain for a given trace length is built from the AIR instantiated for the first trace of
+/// this length; the domain is used for other traces only if it is compatible with their AIRs.
+fn build_domains<P: Prover>(
+    prover: &P,
+    traces: &[P::Trace],
+) -> BTreeMap<usize, StarkDomain<P::BaseField>> {
+    let mut domains = BTreeMap::new();
+    for trace in traces {
+        domains.entry(trace.length()).or_insert_with(|| {
+            let air = P::Air::new(
+                trace.get_info(),
+                prover.get_pub_inputs(trace),
+                prover.options().clone(),
+            );
+            StarkDomain::new(&air)
+        });
+    }
+    domains
+}
+
+/// Returns a proof for the specified trace built over the domain for traces of its length.
+fn prove_with_shared_domain<P: Prover>(
+    prover: &P,
+    domains: &BTreeMap<usize, StarkDomain<P::BaseField>>,
+    trace: P::Trace,
+) -> Result<StarkProof, ProverError> {
+    let domain = domains
+        .get(&trace.length())
+        .expect("no domain was built for the trace length");
+    prover.prove_with_domain(trace, domain)
+}
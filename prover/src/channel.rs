@@ -3,98 +3,236 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use crate::{
+    checkpoint::TRANSCRIPT_STATE_VERSION, constraints::CompositionPoly, errors::CheckpointError,
+    grinding::find_pow_nonce, GrindingClock,
+};
 use air::{
-    proof::{Commitments, Context, OodFrame, Queries, StarkProof},
-    Air, ConstraintCompositionCoefficients, DeepCompositionCoefficients,
+    proof::{
+        CommitmentLayout, Commitments, CompositionCoefficients, Context, OodFrame, Queries,
+        StarkProof,
+    },
+    transcript_labels as labels, Air, ConstraintCompositionCoefficients,
+    DeepCompositionCoefficients, GrindingStrategy, LdePos, PowPuzzle,
+};
+use core::{cmp::Reverse, marker::PhantomData};
+use crypto::{
+    CommitmentOpener, DefaultRandomCoin, ElementHasher, MerkleTree, RandomCoin, VectorCommitment,
 };
-use core::marker::PhantomData;
-use crypto::{ElementHasher, RandomCoin};
 use fri::{self, FriProof};
 use math::FieldElement;
-use utils::{collections::Vec, Serializable};
+use utils::{
+    collections::Vec, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
 
 #[cfg(feature = "concurrent")]
 use utils::iterators::*;
 
+// CONSTANTS
+// ================================================================================================
+
+/// Number of nonces tried by time-boxed grinding between two checks of the grinding deadline.
+const GRINDING_BATCH_SIZE: u64 = 1024;
+
 // TYPES AND INTERFACES
 // ================================================================================================
 
-pub struct ProverChannel<'a, A, E, H>
-where
+pub struct ProverChannel<
+    'a,
+    A,
+    E,
+    H,
+    V = MerkleTree<H>,
+    C = DefaultRandomCoin<<A as Air>::BaseField, H>,
+> where
     A: Air,
     E: FieldElement<BaseField = A::BaseField>,
     H: ElementHasher<BaseField = A::BaseField>,
+    V: VectorCommitment<H>,
+    C: RandomCoin<BaseField = A::BaseField, Hasher = H>,
 {
     air: &'a A,
-    public_coin: RandomCoin<A::BaseField, H>,
+    public_coin: C,
     context: Context,
     commitments: Commitments,
+    composition_coefficients: Option<CompositionCoefficients>,
     ood_frame: OodFrame,
-    pow_nonce: u64,
+    pow_nonce: u128,
+    num_extra_queries: usize,
+    grinding_clock: &'a dyn GrindingClock,
+    main_trace_opener: Option<&'a dyn CommitmentOpener<H, V>>,
     _field_element: PhantomData<E>,
 }
 
 // PROVER CHANNEL IMPLEMENTATION
 // ================================================================================================
 
-impl<'a, A, E, H> ProverChannel<'a, A, E, H>
+impl<'a, A, E, H, V, C> ProverChannel<'a, A, E, H, V, C>
 where
     A: Air,
     E: FieldElement<BaseField = A::BaseField>,
     H: ElementHasher<BaseField = A::BaseField>,
+    V: VectorCommitment<H>,
+    C: RandomCoin<BaseField = A::BaseField, Hasher = H>,
 {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
-    /// Creates a new prover channel for the specified `air` and public inputs.
-    pub fn new(air: &'a A, pub_inputs_bytes: Vec<u8>) -> Self {
-        let context = Context::new::<A::BaseField>(air.trace_info(), air.options().clone());
+    /// Creates a new prover channel for the specified `air` and public inputs; `grinding_clock`
+    /// bounds the time spent on grinding when proof options specify time-boxed grinding.
+    pub fn new(
+        air: &'a A,
+        pub_inputs_bytes: Vec<u8>,
+        grinding_clock: &'a dyn GrindingClock,
+    ) -> Self {
+        let context = build_context::<A, H, V, C>(air);
 
-        // build a seed for the public coin; the initial seed is the hash of public inputs and proof
-        // context, but as the protocol progresses, the coin will be reseeded with the info sent to
-        // the verifier
-        let mut coin_seed = pub_inputs_bytes;
-        context.write_into(&mut coin_seed);
+        // build the public coin; the initial seed is the hash of public inputs and proof context
+        // (encoded as defined by the transcript profile), but as the protocol progresses, the
+        // coin will be reseeded with the info sent to the verifier
+        let public_coin = air
+            .options()
+            .transcript_profile()
+            .build_public_coin::<C>(&context, &pub_inputs_bytes);
 
         ProverChannel {
             air,
-            public_coin: RandomCoin::new(&coin_seed),
+            public_coin,
             context,
             commitments: Commitments::default(),
+            composition_coefficients: None,
             ood_frame: OodFrame::default(),
             pow_nonce: 0,
+            num_extra_queries: 0,
+            grinding_clock,
+            main_trace_opener: None,
             _field_element: PhantomData,
         }
     }
 
+    /// Returns this channel with the main trace commitment opened via the specified `opener`
+    /// (if any) rather than via the Merkle tree of the main trace segment held by the prover.
+    pub fn with_main_trace_opener(
+        mut self,
+        opener: Option<&'a dyn CommitmentOpener<H, V>>,
+    ) -> Self {
+        self.main_trace_opener = opener;
+        self
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the context of the proof generated via this channel.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Returns the opener through which the main trace commitment is opened at query positions,
+    /// or None if the commitment is opened via the Merkle tree held by the prover.
+    pub fn main_trace_opener(&self) -> Option<&'a dyn CommitmentOpener<H, V>> {
+        self.main_trace_opener
+    }
+
+    /// Returns the layout which maps query positions onto leaves of the commitments sent over
+    /// this channel; the verifier derives the same layout from the context of the proof.
+    pub fn commitment_layout(&self) -> CommitmentLayout {
+        CommitmentLayout::from_context(&self.context)
+    }
+
     // COMMITMENT METHODS
     // --------------------------------------------------------------------------------------------
 
+    /// Binds the commitment to the preprocessed trace segment to the transcript.
+    ///
+    /// Unlike other commitments, this commitment is not sent to the verifier as a part of the
+    /// proof, since the verifier obtains it out of band.
+    pub fn commit_preprocessed_segment(&mut self, segment_root: H::Digest) {
+        self.public_coin
+            .label(labels::PREPROCESSED_COMMITMENT)
+            .reseed(segment_root);
+    }
+
     /// Commits the prover the extended execution trace.
     pub fn commit_trace(&mut self, trace_root: H::Digest) {
         self.commitments.add::<H>(&trace_root);
-        self.public_coin.reseed(trace_root);
+        self.public_coin
+            .label(labels::TRACE_COMMITMENT)
+            .reseed(trace_root);
+    }
+
+    /// Commits the prover to the combined trace column; the commitment is appended to the trace
+    /// commitments.
+    pub fn commit_combined_trace_column(&mut self, column_root: H::Digest) {
+        self.commitments.add::<H>(&column_root);
+        self.public_coin
+            .label(labels::COMBINED_TRACE_COMMITMENT)
+            .reseed(column_root);
     }
 
     /// Commits the prover to the evaluations of the constraint composition polynomial.
     pub fn commit_constraints(&mut self, constraint_root: H::Digest) {
         self.commitments.add::<H>(&constraint_root);
-        self.public_coin.reseed(constraint_root);
+        self.public_coin
+            .label(labels::CONSTRAINT_COMMITMENT)
+            .reseed(constraint_root);
+    }
+
+    /// Commits the prover to the digest of the FRI remainder polynomial; the digest is appended
+    /// to the FRI layer commitments.
+    pub fn commit_fri_remainder_digest(&mut self, remainder_digest: H::Digest) {
+        self.commitments.add::<H>(&remainder_digest);
+        self.public_coin
+            .label(labels::FRI_REMAINDER_DIGEST)
+            .reseed(remainder_digest);
+    }
+
+    /// Sends coefficients of the constraint composition polynomial to the verifier in the clear.
+    ///
+    /// The prover is committed to the coefficients by writing their hash into the channel in
+    /// place of the constraint evaluation commitment; the verifier recomputes this hash from the
+    /// coefficients included in the proof, and thus, the public coin is reseeded with the same
+    /// value as long as the coefficients have not been tampered with.
+    pub fn send_composition_coefficients(&mut self, composition_poly: &CompositionPoly<E>) {
+        let columns = (0..composition_poly.num_columns())
+            .map(|i| composition_poly.get_column(i))
+            .collect::<Vec<_>>();
+        let coefficients = columns.concat();
+        self.commit_constraints(H::hash_elements(&coefficients));
+        self.composition_coefficients = Some(CompositionCoefficients::new(&columns));
     }
 
     /// Saves the evaluations of trace polynomials over the out-of-domain evaluation frame. This
     /// also reseeds the public coin with the hashes of the evaluation frame states.
+    ///
+    /// When multiple out-of-domain points are used, this is invoked once for every point.
     pub fn send_ood_trace_states(&mut self, trace_states: &[Vec<E>]) {
-        self.ood_frame.set_trace_states(trace_states);
+        self.ood_frame.add_trace_states(trace_states);
+        self.public_coin.label(labels::OOD_TRACE_FRAME);
         for trace_state in trace_states {
             self.public_coin.reseed(H::hash_elements(trace_state));
         }
     }
 
+    /// Saves the evaluation of the combined trace column at the out-of-domain point. This also
+    /// reseeds the public coin with the hash of the evaluation.
+    ///
+    /// When multiple out-of-domain points are used, this is invoked once for every point.
+    pub fn send_ood_combined_trace_evaluation(&mut self, evaluation: E) {
+        self.ood_frame.add_combined_trace_evaluation(evaluation);
+        self.public_coin
+            .label(labels::OOD_COMBINED_TRACE_EVALUATION)
+            .reseed(H::hash_elements(&[evaluation]));
+    }
+
     /// Saves the evaluations of constraint composition polynomial columns at the out-of-domain
     /// point. This also reseeds the public coin wit the hash of the evaluations.
+    ///
+    /// When multiple out-of-domain points are used, this is invoked once for every point.
     pub fn send_ood_constraint_evaluations(&mut self, evaluations: &[E]) {
-        self.ood_frame.set_constraint_evaluations(evaluations);
-        self.public_coin.reseed(H::hash_elements(evaluations));
+        self.ood_frame.add_constraint_evaluations(evaluations);
+        self.public_coin
+            .label(labels::OOD_CONSTRAINT_EVALUATIONS)
+            .reseed(H::hash_elements(evaluations));
     }
 
     // PUBLIC COIN METHODS
@@ -105,15 +243,30 @@ where
     ///
     /// The elements are drawn from the public coin uniformly at random.
     pub fn get_aux_trace_segment_rand_elements(&mut self, aux_segment_idx: usize) -> Vec<E> {
+        self.public_coin.label(labels::AUX_RAND_ELEMENTS);
         self.air
             .get_aux_trace_segment_random_elements(aux_segment_idx, &mut self.public_coin)
             .expect("failed to draw random elements for an auxiliary trace segment")
     }
 
-    /// Returns a set of coefficients for constructing a constraint composition polynomial.
+    /// Returns a coefficient for computing the random linear combination of all trace columns.
+    ///
+    /// The coefficient is drawn from the public coin uniformly at random.
+    pub fn get_combined_trace_coefficient(&mut self) -> E {
+        self.public_coin
+            .label(labels::COMBINED_TRACE_COEFFICIENT)
+            .draw()
+            .expect("failed to draw combined trace column coefficient")
+    }
+
+    /// Returns sets of coefficients for constructing constraint composition polynomials, one set
+    /// per out-of-domain point.
     ///
     /// The coefficients are drawn from the public coin uniformly at random.
-    pub fn get_constraint_composition_coeffs(&mut self) -> ConstraintCompositionCoefficients<E> {
+    pub fn get_constraint_composition_coeffs(
+        &mut self,
+    ) -> Vec<ConstraintCompositionCoefficients<E>> {
+        self.public_coin.label(labels::CONSTRAINT_COEFFICIENTS);
         self.air
             .get_constraint_composition_coefficients(&mut self.public_coin)
             .expect("failed to draw composition coefficients")
@@ -121,13 +274,17 @@ where
 
     /// Returns an out-of-domain point drawn uniformly at random from the public coin.
     pub fn get_ood_point(&mut self) -> E {
-        self.public_coin.draw().expect("failed to draw OOD point")
+        self.public_coin
+            .label(labels::OOD_POINT)
+            .draw()
+            .expect("failed to draw OOD point")
     }
 
     /// Returns a set of coefficients for constructing a DEEP composition polynomial.
     ///
     /// The coefficients are drawn from the public coin uniformly at random.
     pub fn get_deep_composition_coeffs(&mut self) -> DeepCompositionCoefficients<E> {
+        self.public_coin.label(labels::DEEP_COEFFICIENTS);
         self.air
             .get_deep_composition_coefficients(&mut self.public_coin)
             .expect("failed to draw DEEP composition coefficients")
@@ -136,34 +293,212 @@ where
     /// Returns a set of positions in the LDE domain against which the evaluations of trace and
     /// constraint composition polynomials should be queried.
     ///
-    /// The positions are drawn from the public coin uniformly at random.
-    pub fn get_query_positions(&mut self) -> Vec<usize> {
-        let num_queries = self.context.options().num_queries();
+    /// The positions are drawn from the public coin uniformly at random according to the
+    /// transcript profile specified in the proof options. When the proof-of-work nonce is missing
+    /// some of the bits specified by the grinding factor, extra positions are drawn to compensate
+    /// for them.
+    pub fn get_query_positions(&mut self) -> Vec<LdePos> {
+        let num_queries = self.context.options().num_queries() + self.num_extra_queries;
         let lde_domain_size = self.context.lde_domain_size();
-        self.public_coin
-            .draw_integers(num_queries, lde_domain_size)
+        self.context
+            .options()
+            .transcript_profile()
+            .draw_query_positions(&mut self.public_coin, num_queries, lde_domain_size)
             .expect("failed to draw query position")
     }
 
-    /// Determines a nonce, which when hashed with the current seed of the public coin results
-    /// in a new seed with the number of leading zeros equal to the grinding_factor specified
-    /// in the proof options.
+    /// Determines a nonce, which when hashed with the current seed of the public coin provides
+    /// the number of proof-of-work bits equal to the grinding_factor specified in the proof
+    /// options, and absorbs this nonce into the public coin.
+    ///
+    /// The way proof-of-work bits are counted and the nonce is absorbed is defined by the
+    /// transcript profile and the grinding hash function specified in the proof options (see
+    /// [PowPuzzle]).
+    ///
+    /// The smallest suitable nonce is always selected (even when the search is performed in
+    /// multiple threads), and thus, proofs do not depend on whether `concurrent` feature is
+    /// enabled. With time-boxed grinding, the search may stop before a suitable nonce is found
+    /// (see [GrindingStrategy::TimeBoxed]); the nonce then yields fewer proof-of-work bits, and
+    /// the number of queries drawn afterwards is increased accordingly.
     pub fn grind_query_seed(&mut self) {
+        let options = self.context.options();
+        let puzzle = PowPuzzle::new(options, &self.public_coin)
+            .expect("grinding hash function is not supported for the base field");
+        let nonce = match options.grinding_strategy() {
+            GrindingStrategy::FixedBits => find_pow_nonce(&puzzle, options.grinding_factor()),
+            GrindingStrategy::TimeBoxed { max_millis, .. } => {
+                self.find_time_boxed_pow_nonce(&puzzle, max_millis as u64)
+            }
+        };
+
+        let pow_bits = puzzle.get_pow_bits(nonce);
+        self.num_extra_queries = options.num_extra_queries(pow_bits);
+        self.pow_nonce = nonce;
+        let profile = options.transcript_profile();
+        if options.uses_wide_grinding_nonce() {
+            profile.absorb_wide_pow_nonce(&mut self.public_coin, nonce);
+        } else {
+            profile.absorb_pow_nonce(&mut self.public_coin, nonce as u64);
+        }
+    }
+
+    /// Returns the smallest nonce which provides the number of proof-of-work bits equal to the
+    /// grinding factor if such a nonce is found within `max_millis` milliseconds; otherwise,
+    /// returns the smallest of the nonces providing the most bits among the nonces tried until
+    /// the deadline, as long as it provides at least the minimum number of bits allowed by the
+    /// proof options.
+    fn find_time_boxed_pow_nonce(&self, puzzle: &PowPuzzle<C>, max_millis: u64) -> u128 {
         let grinding_factor = self.context.options().grinding_factor();
+        let min_grinding_factor = self.context.options().min_grinding_factor();
 
-        #[cfg(not(feature = "concurrent"))]
-        let nonce = (1..u64::MAX)
-            .find(|&nonce| self.public_coin.check_leading_zeros(nonce) >= grinding_factor)
-            .expect("nonce not found");
+        // nonces are compared by the number of bits they provide (bits beyond the grinding
+        // factor do not count), and then by their value, so that the smallest nonce wins
+        let nonce_key = |nonce: u64| {
+            let pow_bits = puzzle.get_pow_bits(nonce as u128);
+            (pow_bits.min(grinding_factor), Reverse(nonce))
+        };
 
-        #[cfg(feature = "concurrent")]
-        let nonce = (1..u64::MAX)
-            .into_par_iter()
-            .find_any(|&nonce| self.public_coin.check_leading_zeros(nonce) >= grinding_factor)
-            .expect("nonce not found");
+        let start = self.grinding_clock.now_millis();
+        let (mut best_bits, mut best_nonce) = (0, 1);
+        let mut batch_start = 1;
+        loop {
+            let batch = batch_start..batch_start + GRINDING_BATCH_SIZE;
 
-        self.pow_nonce = nonce;
-        self.public_coin.reseed_with_int(nonce);
+            #[cfg(not(feature = "concurrent"))]
+            let (pow_bits, Reverse(nonce)) = batch.map(&nonce_key).max().expect("batch is empty");
+
+            #[cfg(feature = "concurrent")]
+            let (pow_bits, Reverse(nonce)) = batch
+                .into_par_iter()
+                .map(&nonce_key)
+                .max()
+                .expect("batch is empty");
+
+            if pow_bits > best_bits {
+                best_bits = pow_bits;
+                best_nonce = nonce;
+            }
+            if best_bits >= grinding_factor {
+                return best_nonce as u128;
+            }
+            let elapsed = self.grinding_clock.now_millis().saturating_sub(start);
+            if elapsed >= max_millis && best_bits >= min_grinding_factor {
+                return best_nonce as u128;
+            }
+            batch_start += GRINDING_BATCH_SIZE;
+        }
+    }
+
+    // TRANSCRIPT STATE
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes the state of the transcript simulated by this channel and writes the resulting
+    /// bytes into the `target`.
+    ///
+    /// The state consists of the transcript state format version, proof context, state of the
+    /// public coin, and all values sent to the verifier so far (commitments, composition
+    /// polynomial coefficients, out-of-domain frame, and proof-of-work nonce).
+    pub fn write_state_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(TRANSCRIPT_STATE_VERSION);
+        self.context.write_into(target);
+        self.public_coin.write_into(target);
+        self.commitments.write_into(target);
+        match &self.composition_coefficients {
+            Some(coefficients) => {
+                target.write_u8(1);
+                coefficients.write_into(target);
+            }
+            None => target.write_u8(0),
+        }
+        self.ood_frame.write_into(target);
+        let nonce_bytes = self.context.options().grinding_nonce_bytes();
+        target.write_u8_slice(&self.pow_nonce.to_le_bytes()[..nonce_bytes]);
+        if self.context.options().uses_time_boxed_grinding() {
+            target.write_u8(self.num_extra_queries as u8);
+        }
+    }
+
+    /// Reads the transcript state format version and the proof context from the `source`.
+    ///
+    /// This is the first part of a serialized transcript state; the remainder of the state can
+    /// be read via [read_state_from()](ProverChannel::read_state_from) once an instance of AIR
+    /// has been built for the returned context.
+    ///
+    /// # Errors
+    /// Returns an error if the state was serialized using an unsupported transcript state format
+    /// version, or if a valid context could not be read from the `source`.
+    pub fn read_context_from<R: ByteReader>(source: &mut R) -> Result<Context, CheckpointError> {
+        let version = source
+            .read_u8()
+            .map_err(CheckpointError::MalformedCheckpoint)?;
+        if version != TRANSCRIPT_STATE_VERSION {
+            return Err(CheckpointError::UnsupportedTranscriptVersion(version));
+        }
+        Context::read_from(source).map_err(CheckpointError::MalformedCheckpoint)
+    }
+
+    /// Returns a channel for the specified `air` restored from the remainder of the transcript
+    /// state read from the `source`; the returned channel behaves identically to the channel
+    /// from which the state was serialized.
+    ///
+    /// # Errors
+    /// Returns an error if the `context` does not match the context defined by the `air`, or if
+    /// the state could not be deserialized.
+    pub fn read_state_from<R: ByteReader>(
+        air: &'a A,
+        context: Context,
+        source: &mut R,
+        grinding_clock: &'a dyn GrindingClock,
+    ) -> Result<Self, CheckpointError> {
+        if context != build_context::<A, H, V, C>(air) {
+            return Err(CheckpointError::ContextMismatch);
+        }
+        Self::read_state_fields(air, context, source, grinding_clock)
+            .map_err(CheckpointError::MalformedCheckpoint)
+    }
+
+    /// Reads the fields of a transcript state (following the context) from the `source`.
+    fn read_state_fields<R: ByteReader>(
+        air: &'a A,
+        context: Context,
+        source: &mut R,
+        grinding_clock: &'a dyn GrindingClock,
+    ) -> Result<Self, DeserializationError> {
+        let public_coin = C::read_from(source)?;
+        let commitments = Commitments::read_from(source)?;
+        let composition_coefficients = match source.read_u8()? {
+            0 => None,
+            1 => Some(CompositionCoefficients::read_from(source)?),
+            value => {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "composition coefficients flag must be 0 or 1, but was {value}"
+                )))
+            }
+        };
+        let ood_frame = OodFrame::read_from(source)?;
+        let pow_nonce = if context.options().uses_wide_grinding_nonce() {
+            source.read_u128()?
+        } else {
+            source.read_u64()? as u128
+        };
+        let num_extra_queries = if context.options().uses_time_boxed_grinding() {
+            source.read_u8()? as usize
+        } else {
+            0
+        };
+        Ok(ProverChannel {
+            air,
+            public_coin,
+            context,
+            commitments,
+            composition_coefficients,
+            ood_frame,
+            pow_nonce,
+            num_extra_queries,
+            grinding_clock,
+            main_trace_opener: None,
+            _field_element: PhantomData,
+        })
     }
 
     // PROOF BUILDER
@@ -182,8 +517,10 @@ where
             ood_frame: self.ood_frame,
             trace_queries,
             constraint_queries,
+            composition_coefficients: self.composition_coefficients,
             fri_proof,
             pow_nonce: self.pow_nonce,
+            num_extra_queries: self.num_extra_queries,
         }
     }
 }
@@ -191,22 +528,51 @@ where
 // FRI PROVER CHANNEL IMPLEMENTATION
 // ================================================================================================
 
-impl<'a, A, E, H> fri::ProverChannel<E> for ProverChannel<'a, A, E, H>
+impl<'a, A, E, H, V, C> fri::ProverChannel<E> for ProverChannel<'a, A, E, H, V, C>
 where
     A: Air,
     E: FieldElement<BaseField = A::BaseField>,
     H: ElementHasher<BaseField = A::BaseField>,
+    V: VectorCommitment<H>,
+    C: RandomCoin<BaseField = A::BaseField, Hasher = H>,
 {
     type Hasher = H;
 
     /// Commits the prover to a FRI layer.
     fn commit_fri_layer(&mut self, layer_root: H::Digest) {
         self.commitments.add::<H>(&layer_root);
-        self.public_coin.reseed(layer_root);
+        self.public_coin.label(labels::FRI_LAYER).reseed(layer_root);
     }
 
     /// Returns a new alpha drawn from the public coin.
     fn draw_fri_alpha(&mut self) -> E {
-        self.public_coin.draw().expect("failed to draw FRI alpha")
+        self.public_coin
+            .label(labels::FRI_LAYER)
+            .draw()
+            .expect("failed to draw FRI alpha")
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds the proof context for the computation described by the specified AIR; besides the
+/// trace info and the proof options, the context binds the transition exemptions of each
+/// constraint of the AIR, the identifier of the hash function `H` (if it has one), the identifier of the
+/// vector commitment scheme `V`, and the identifier of the random coin `C`.
+fn build_context<A, H, V, C>(air: &A) -> Context
+where
+    A: Air,
+    H: ElementHasher,
+    V: VectorCommitment<H>,
+    C: RandomCoin,
+{
+    let context = Context::new::<A::BaseField>(air.trace_info(), air.options().clone())
+        .with_transition_exemptions(air.context().transition_exemptions())
+        .with_commitment_scheme(V::SCHEME_ID)
+        .with_random_coin(C::COIN_ID);
+    match H::ID {
+        Some(hash_fn) => context.with_hash_fn(hash_fn),
+        None => context,
     }
 }
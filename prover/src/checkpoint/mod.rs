@@ -0,0 +1,512 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Checkpoints of partially generated proofs.
+//!
+//! A long-running proof generation can be interrupted at well-defined phase boundaries and
+//! resumed later (potentially, on a different machine). The prover saves a [Checkpoint] into a
+//! [CheckpointSink] after each of the following phases:
+//!
+//! * [ProofPhase::TraceCommitted] - after all trace segments have been committed to. The
+//!   checkpoint contains trace segment LDEs, trace polynomials, and random elements used to build
+//!   the auxiliary trace segments. For a trace of length $n$ with $w$ columns and blowup factor
+//!   $b$, this is roughly $(b + 1) \cdot n \cdot w$ field elements and $b \cdot n$ digests per
+//!   trace segment.
+//! * [ProofPhase::ConstraintsCommitted] - after the prover has committed to the constraint
+//!   composition polynomial. In addition to the data of the previous phase (excluding random
+//!   elements), the checkpoint contains the $c$ composition polynomial columns ($n \cdot c$ field
+//!   elements) and their LDE ($b \cdot n \cdot c$ field elements and $b \cdot n$ digests); the LDE
+//!   is omitted when the composition polynomial is sent explicitly.
+//! * [ProofPhase::FriLayerCommitted] - after each FRI layer has been committed to. The checkpoint
+//!   contains trace and constraint LDEs and their commitments (but not trace and composition
+//!   polynomials, which are no longer needed), FRI layers built so far, and evaluations to be
+//!   committed to in the next FRI layer. FRI layers add at most $2 \cdot b \cdot n$ extension
+//!   field elements and $2 \cdot b \cdot n / f$ digests, where $f$ is the FRI folding factor.
+//!
+//! Only the leaves of Merkle trees are stored in checkpoints; internal tree nodes are recomputed
+//! when the prover resumes from a checkpoint. Proofs resumed from a checkpoint are byte-for-byte
+//! identical to proofs generated without interruptions.
+//!
+//! When proof options request salted commitments, checkpoints also contain the seed from which
+//! salts of committed leaves are derived. Anyone holding such a checkpoint can recompute the salts,
+//! and thus, checkpoints of salted proofs must be kept as private as the execution trace itself.
+//!
+//! # Checkpoint format
+//! A serialized checkpoint starts with [CHECKPOINT_VERSION], followed by the proof phase, the
+//! serialized public inputs, and the serialized proof generation state. The state starts with the
+//! state of the transcript (prefixed by [TRANSCRIPT_STATE_VERSION]) which includes the proof
+//! context, the state of the public coin, and all values sent to the verifier so far. A prover
+//! refuses to resume from checkpoints with different versions of either format because even a
+//! minor change in how the transcript state is interpreted would result in an invalid proof.
+
+use crate::{
+    constraints::{CompositionPoly, ConstraintCommitment},
+    errors::CheckpointError,
+    trace::{TraceCommitment, TracePolyTable},
+};
+use air::AuxTraceRandElements;
+use crypto::{ElementHasher, MerkleTree, VectorCommitment};
+use fri::{FriOptions, FriProver};
+use math::{FieldElement, StarkField};
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable, SliceReader,
+};
+
+#[cfg(feature = "std")]
+use std::{fs, path::PathBuf};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Version of the checkpoint serialization format.
+pub const CHECKPOINT_VERSION: u8 = 6;
+
+/// Version of the transcript state serialization format.
+pub const TRANSCRIPT_STATE_VERSION: u8 = 2;
+
+// PROOF PHASE
+// ================================================================================================
+
+/// Phase of proof generation after which a checkpoint was saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofPhase {
+    /// All trace segments have been committed to.
+    TraceCommitted,
+    /// The constraint composition polynomial has been committed to.
+    ConstraintsCommitted,
+    /// The FRI layer with the specified index has been committed to.
+    FriLayerCommitted(usize),
+}
+
+impl ProofPhase {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        match self {
+            Self::TraceCommitted => target.write_u8(0),
+            Self::ConstraintsCommitted => target.write_u8(1),
+            Self::FriLayerCommitted(layer_idx) => {
+                target.write_u8(2);
+                target.write_u32(*layer_idx as u32);
+            }
+        }
+    }
+
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        match source.read_u8()? {
+            0 => Ok(Self::TraceCommitted),
+            1 => Ok(Self::ConstraintsCommitted),
+            2 => Ok(Self::FriLayerCommitted(source.read_u32()? as usize)),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "value {value} cannot be deserialized as a proof phase"
+            ))),
+        }
+    }
+}
+
+// CHECKPOINT
+// ================================================================================================
+
+/// A resumable snapshot of a partially generated proof.
+///
+/// Checkpoints are created by [Prover::prove_with_checkpoints()](crate::Prover::prove_with_checkpoints)
+/// and consumed by [Prover::resume_proof()](crate::Prover::resume_proof). The contents of a
+/// checkpoint are opaque; a checkpoint can be persisted via [Checkpoint::to_bytes()] and restored
+/// via [Checkpoint::from_bytes()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    phase: ProofPhase,
+    pub_inputs: Vec<u8>,
+    state: Vec<u8>,
+}
+
+impl Checkpoint {
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the phase of proof generation after which this checkpoint was saved.
+    pub fn phase(&self) -> ProofPhase {
+        self.phase
+    }
+
+    /// Returns serialized public inputs of the computation for which the proof is generated.
+    pub fn pub_inputs_bytes(&self) -> &[u8] {
+        &self.pub_inputs
+    }
+
+    /// Returns the size of this checkpoint in bytes.
+    pub fn size(&self) -> usize {
+        self.state.len() + self.pub_inputs.len()
+    }
+
+    // SERIALIZATION / DESERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes this checkpoint into a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.size() + 16);
+        result.write_u8(CHECKPOINT_VERSION);
+        self.phase.write_into(&mut result);
+        result.write_u32(self.pub_inputs.len() as u32);
+        result.write_u8_slice(&self.pub_inputs);
+        result.write_u64(self.state.len() as u64);
+        result.write_u8_slice(&self.state);
+        result
+    }
+
+    /// Returns a checkpoint read from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if the checkpoint was serialized using an unsupported format version, or
+    /// if a valid checkpoint could not be read from the `source`.
+    pub fn from_bytes(source: &[u8]) -> Result<Self, CheckpointError> {
+        let mut source = SliceReader::new(source);
+        let version = source
+            .read_u8()
+            .map_err(CheckpointError::MalformedCheckpoint)?;
+        if version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+        Self::read_fields(&mut source).map_err(CheckpointError::MalformedCheckpoint)
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Reads the fields of a checkpoint (following the version) from the `source`.
+    fn read_fields(source: &mut SliceReader) -> Result<Self, DeserializationError> {
+        let phase = ProofPhase::read_from(source)?;
+        let num_pub_inputs_bytes = source.read_u32()? as usize;
+        let pub_inputs = source.read_u8_vec(num_pub_inputs_bytes)?;
+        let num_state_bytes = source.read_u64()? as usize;
+        let state = source.read_u8_vec(num_state_bytes)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(Self {
+            phase,
+            pub_inputs,
+            state,
+        })
+    }
+
+    /// Returns serialized state of proof generation at the phase of this checkpoint.
+    pub(crate) fn state(&self) -> &[u8] {
+        &self.state
+    }
+}
+
+// CHECKPOINT SINK AND SOURCE
+// ================================================================================================
+
+/// Defines a destination for checkpoints saved during proof generation.
+pub trait CheckpointSink {
+    /// Saves the specified checkpoint.
+    ///
+    /// A later checkpoint supersedes all checkpoints saved before it, and thus, a sink may keep
+    /// only the latest checkpoint.
+    ///
+    /// # Errors
+    /// Returns an error if the checkpoint could not be saved; this aborts proof generation.
+    fn save(&mut self, checkpoint: Checkpoint) -> Result<(), CheckpointError>;
+}
+
+/// Defines an origin of a checkpoint from which proof generation can be resumed.
+pub trait CheckpointSource {
+    /// Returns the checkpoint from which proof generation should be resumed.
+    ///
+    /// # Errors
+    /// Returns an error if a checkpoint could not be loaded.
+    fn load(&mut self) -> Result<Checkpoint, CheckpointError>;
+}
+
+impl CheckpointSink for Vec<Checkpoint> {
+    /// Appends the checkpoint to this vector.
+    fn save(&mut self, checkpoint: Checkpoint) -> Result<(), CheckpointError> {
+        self.push(checkpoint);
+        Ok(())
+    }
+}
+
+impl CheckpointSource for Vec<Checkpoint> {
+    /// Returns the latest checkpoint saved into this vector.
+    fn load(&mut self) -> Result<Checkpoint, CheckpointError> {
+        self.last().cloned().ok_or_else(|| {
+            CheckpointError::SourceFailed("no checkpoints have been saved".to_string())
+        })
+    }
+}
+
+impl CheckpointSource for Checkpoint {
+    fn load(&mut self) -> Result<Checkpoint, CheckpointError> {
+        Ok(self.clone())
+    }
+}
+
+// CHECKPOINT FILE
+// ================================================================================================
+
+/// A file which holds the latest checkpoint saved into it.
+///
+/// Checkpoints are first written into a temporary file in the same directory, which is then
+/// renamed to the target path; thus, an interruption while a checkpoint is being saved leaves the
+/// previously saved checkpoint intact.
+#[cfg(feature = "std")]
+pub struct CheckpointFile {
+    path: PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl CheckpointFile {
+    /// Returns a checkpoint file at the specified path.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl CheckpointSink for CheckpointFile {
+    fn save(&mut self, checkpoint: Checkpoint) -> Result<(), CheckpointError> {
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, checkpoint.to_bytes())
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .map_err(|err| CheckpointError::SinkFailed(err.to_string()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl CheckpointSource for CheckpointFile {
+    fn load(&mut self) -> Result<Checkpoint, CheckpointError> {
+        let bytes =
+            fs::read(&self.path).map_err(|err| CheckpointError::SourceFailed(err.to_string()))?;
+        Checkpoint::from_bytes(&bytes)
+    }
+}
+
+// CHECKPOINT WRITER
+// ================================================================================================
+
+/// Saves checkpoints of proof generation into an optional sink.
+///
+/// When no sink is provided, proof generation state is not serialized at all, and thus,
+/// checkpointing imposes no overhead on proofs generated without it.
+pub(crate) struct CheckpointWriter<'a> {
+    sink: Option<&'a mut dyn CheckpointSink>,
+    pub_inputs: Vec<u8>,
+}
+
+impl<'a> CheckpointWriter<'a> {
+    /// Returns a new writer which saves checkpoints for the specified public inputs into the
+    /// provided sink.
+    pub fn new(sink: Option<&'a mut dyn CheckpointSink>, pub_inputs: Vec<u8>) -> Self {
+        Self { sink, pub_inputs }
+    }
+
+    /// Saves a checkpoint for the specified phase, using the `write_state` closure to serialize
+    /// proof generation state.
+    pub fn save<F>(&mut self, phase: ProofPhase, write_state: F) -> Result<(), CheckpointError>
+    where
+        F: FnOnce(&mut Vec<u8>),
+    {
+        if let Some(sink) = self.sink.as_mut() {
+            let mut state = Vec::new();
+            write_state(&mut state);
+            sink.save(Checkpoint {
+                phase,
+                pub_inputs: self.pub_inputs.clone(),
+                state,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// PROOF GENERATION STATES
+// ================================================================================================
+
+/// State of proof generation after all trace segments have been committed to.
+pub(crate) struct TraceCommittedState<E, H, V = MerkleTree<H>>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    pub trace_commitment: TraceCommitment<E, H, V>,
+    pub trace_polys: TracePolyTable<E>,
+    pub aux_rand_elements: AuxTraceRandElements<E>,
+}
+
+impl<E, H, V> TraceCommittedState<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    pub fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.trace_commitment.write_into(target);
+        self.trace_polys.write_into(target);
+        for i in 0..self.trace_commitment.trace_table().num_aux_segments() {
+            write_elements(self.aux_rand_elements.get_segment_elements(i), target);
+        }
+    }
+
+    pub fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let trace_commitment = TraceCommitment::<E, H, V>::read_from(source)?;
+        let trace_polys = TracePolyTable::read_from(source)?;
+        let mut aux_rand_elements = AuxTraceRandElements::new();
+        for _ in 0..trace_commitment.trace_table().num_aux_segments() {
+            aux_rand_elements.add_segment_elements(read_elements(source)?);
+        }
+        Ok(Self {
+            trace_commitment,
+            trace_polys,
+            aux_rand_elements,
+        })
+    }
+}
+
+/// State of proof generation after the constraint composition polynomial has been committed to.
+///
+/// Constraint commitment is None when the composition polynomial is sent to the verifier
+/// explicitly.
+pub(crate) struct ConstraintsCommittedState<E, H, V = MerkleTree<H>>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    pub trace_commitment: TraceCommitment<E, H, V>,
+    pub trace_polys: TracePolyTable<E>,
+    pub composition_poly: CompositionPoly<E>,
+    pub constraint_commitment: Option<ConstraintCommitment<E, H, V>>,
+}
+
+impl<E, H, V> ConstraintsCommittedState<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    pub fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.trace_commitment.write_into(target);
+        self.trace_polys.write_into(target);
+        self.composition_poly.write_into(target);
+        write_constraint_commitment(&self.constraint_commitment, target);
+    }
+
+    pub fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(Self {
+            trace_commitment: TraceCommitment::read_from(source)?,
+            trace_polys: TracePolyTable::read_from(source)?,
+            composition_poly: CompositionPoly::read_from(source)?,
+            constraint_commitment: read_constraint_commitment(source)?,
+        })
+    }
+}
+
+/// State of proof generation during the commit phase of the FRI protocol.
+///
+/// The evaluations are the evaluations to be committed to in the next FRI layer (or as the FRI
+/// remainder once all layers have been built).
+pub(crate) struct FriState<B, E, C, H, V = MerkleTree<H>>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    C: fri::ProverChannel<E, Hasher = H>,
+    H: ElementHasher<BaseField = B>,
+    V: VectorCommitment<H>,
+{
+    pub trace_commitment: TraceCommitment<E, H, V>,
+    pub constraint_commitment: Option<ConstraintCommitment<E, H, V>>,
+    pub fri_prover: FriProver<B, E, C, H>,
+    pub evaluations: Vec<E>,
+}
+
+impl<B, E, C, H, V> FriState<B, E, C, H, V>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    C: fri::ProverChannel<E, Hasher = H>,
+    H: ElementHasher<BaseField = B>,
+    V: VectorCommitment<H>,
+{
+    pub fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.trace_commitment.write_into(target);
+        write_constraint_commitment(&self.constraint_commitment, target);
+        self.fri_prover.write_layers_into(target);
+        write_elements(&self.evaluations, target);
+    }
+
+    pub fn read_from<R: ByteReader>(
+        source: &mut R,
+        options: FriOptions,
+    ) -> Result<Self, DeserializationError> {
+        let trace_commitment = TraceCommitment::read_from(source)?;
+        let constraint_commitment = read_constraint_commitment(source)?;
+        let mut fri_prover = FriProver::new(options);
+        fri_prover.read_layers_from(source)?;
+        Ok(Self {
+            trace_commitment,
+            constraint_commitment,
+            fri_prover,
+            evaluations: read_elements(source)?,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Writes a length-prefixed vector of field elements into the `target`.
+pub(crate) fn write_elements<E: FieldElement, W: ByteWriter>(elements: &[E], target: &mut W) {
+    target.write_u32(elements.len() as u32);
+    E::write_batch_into(elements, target);
+}
+
+/// Reads a length-prefixed vector of field elements from the `source`.
+pub(crate) fn read_elements<E: FieldElement, R: ByteReader>(
+    source: &mut R,
+) -> Result<Vec<E>, DeserializationError> {
+    let num_elements = source.read_u32()? as usize;
+    E::read_batch_from(source, num_elements)
+}
+
+/// Writes an optional constraint commitment into the `target`.
+fn write_constraint_commitment<E, H, V, W>(
+    commitment: &Option<ConstraintCommitment<E, H, V>>,
+    target: &mut W,
+) where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+    W: ByteWriter,
+{
+    match commitment {
+        Some(commitment) => {
+            target.write_u8(1);
+            commitment.write_into(target);
+        }
+        None => target.write_u8(0),
+    }
+}
+
+/// Reads an optional constraint commitment from the `source`.
+fn read_constraint_commitment<E, H, V, R>(
+    source: &mut R,
+) -> Result<Option<ConstraintCommitment<E, H, V>>, DeserializationError>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+    R: ByteReader,
+{
+    match source.read_u8()? {
+        0 => Ok(None),
+        1 => Ok(Some(ConstraintCommitment::read_from(source)?)),
+        value => Err(DeserializationError::InvalidValue(format!(
+            "constraint commitment flag must be 0 or 1, but was {value}"
+        ))),
+    }
+}
@@ -16,7 +16,8 @@ use utils::iterators::*;
 pub struct DeepCompositionPoly<E: FieldElement> {
     coefficients: Vec<E>,
     cc: DeepCompositionCoefficients<E>,
-    z: E,
+    z: Vec<E>,
+    frame_offsets: Vec<usize>,
     field_extension: bool,
 }
 
@@ -26,7 +27,9 @@ impl<E: FieldElement> DeepCompositionPoly<E> {
     /// Returns a new DEEP composition polynomial. Initially, this polynomial will be empty, and
     /// the intent is to populate the coefficients via add_trace_polys() and add_constraint_polys()
     /// methods.
-    pub fn new<A>(air: &A, z: E, cc: DeepCompositionCoefficients<E>) -> Self
+    ///
+    /// `z` contains all out-of-domain points sampled during the DEEP step of the protocol.
+    pub fn new<A>(air: &A, z: Vec<E>, cc: DeepCompositionCoefficients<E>) -> Self
     where
         A: Air<BaseField = E::BaseField>,
     {
@@ -34,6 +37,7 @@ impl<E: FieldElement> DeepCompositionPoly<E> {
             coefficients: vec![],
             cc,
             z,
+            frame_offsets: air.context().frame_offsets().to_vec(),
             field_extension: !air.options().field_extension().is_none(),
         }
     }
@@ -67,104 +71,146 @@ impl<E: FieldElement> DeepCompositionPoly<E> {
     ///   to the way described above. This is needed in order to verify that the trace is defined
     ///   over the base field, rather than the extension field.
     ///
-    /// Note that evaluations of T_i(z) and T_i(z * g) are passed in via the `ood_frame` parameter.
+    /// When the evaluation frame of the AIR uses row offsets other than the default ones, z * g
+    /// above is replaced with z * g^k for the offset k of the second row of the frame, and a
+    /// polynomial (T_i(x) - T_i(z * g^k)) / (x - z * g^k) is added to T(x) for every other row of
+    /// the frame as well, each with its own coefficient.
+    ///
+    /// When multiple out-of-domain points are used, the above is done for every point z using
+    /// coefficients drawn for that point, and the results are added together.
+    ///
+    /// Note that evaluations of T_i(z) and T_i(z * g) are passed in via the `ood_frame` parameter;
+    /// for the kth out-of-domain point and a frame with n rows, these are located at indexes
+    /// n * k and n * k + 1 respectively, followed by evaluations for other rows of the frame.
     pub fn add_trace_polys(
         &mut self,
         trace_polys: TracePolyTable<E>,
         ood_trace_states: Vec<Vec<E>>,
     ) {
         assert!(self.coefficients.is_empty());
+        let num_offsets = self.frame_offsets.len();
+        assert_eq!(num_offsets * self.z.len(), ood_trace_states.len());
 
-        // compute a second out-of-domain point offset from z by exactly trace generator; this
-        // point defines the "next" computation state in relation to point z
         let trace_length = trace_polys.poly_size();
         let g = E::from(E::BaseField::get_root_of_unity(log2(trace_length)));
-        let next_z = self.z * g;
-
-        // combine trace polynomials into 2 composition polynomials T'(x) and T''(x), and if
-        // we are using a field extension, also T'''(x)
-        let mut t1_composition = E::zeroed_vector(trace_length);
-        let mut t2_composition = E::zeroed_vector(trace_length);
-        let mut t3_composition = if self.field_extension {
-            E::zeroed_vector(trace_length)
-        } else {
-            Vec::new()
-        };
-
-        // index of a trace polynomial; we declare it here so that we can maintain index continuity
-        // across all trace segments
-        let mut i = 0;
-
-        // --- merge polynomials of the main trace segment ----------------------------------------
-        for poly in trace_polys.main_trace_polys() {
-            // compute T'(x) = T(x) - T(z), multiply it by a pseudo-random coefficient,
-            // and add the result into composition polynomial
-            acc_trace_poly::<E::BaseField, E>(
-                &mut t1_composition,
-                poly,
-                ood_trace_states[0][i],
-                self.cc.trace[i].0,
-            );
-
-            // compute T''(x) = T(x) - T(z * g), multiply it by a pseudo-random coefficient,
-            // and add the result into composition polynomial
-            acc_trace_poly::<E::BaseField, E>(
-                &mut t2_composition,
-                poly,
-                ood_trace_states[1][i],
-                self.cc.trace[i].1,
-            );
-
-            // when extension field is enabled, compute T'''(x) = T(x) - T(z_conjugate), multiply
-            // it by a pseudo-random coefficient, and add the result into composition polynomial
-            if self.field_extension {
+
+        for (point_idx, ood_trace_states) in ood_trace_states.chunks(num_offsets).enumerate() {
+            let z = self.z[point_idx];
+            let cc = self.cc.trace_coefficients(point_idx, self.z.len());
+            let cc_offsets = self.cc.offset_coefficients(point_idx, self.z.len());
+
+            // compute out-of-domain points offset from z by the row offsets of the frame; the
+            // second of these points defines the "next" computation state in relation to point z
+            let offset_z = self
+                .frame_offsets
+                .iter()
+                .map(|&offset| z * g.exp((offset as u32).into()))
+                .collect::<Vec<_>>();
+
+            // combine trace polynomials into 2 composition polynomials T'(x) and T''(x), one
+            // composition polynomial for every other row of the frame, and if we are using a
+            // field extension, also T'''(x)
+            let mut t1_composition = E::zeroed_vector(trace_length);
+            let mut t2_composition = E::zeroed_vector(trace_length);
+            let mut extra_compositions = (2..num_offsets)
+                .map(|_| E::zeroed_vector(trace_length))
+                .collect::<Vec<_>>();
+            let mut t3_composition = if self.field_extension {
+                E::zeroed_vector(trace_length)
+            } else {
+                Vec::new()
+            };
+
+            // index of a trace polynomial; we declare it here so that we can maintain index
+            // continuity across all trace segments
+            let mut i = 0;
+
+            // --- merge polynomials of the main trace segment ------------------------------------
+            for poly in trace_polys.main_trace_polys() {
+                // compute T'(x) = T(x) - T(z), multiply it by a pseudo-random coefficient,
+                // and add the result into composition polynomial
+                acc_trace_poly::<E::BaseField, E>(
+                    &mut t1_composition,
+                    poly,
+                    ood_trace_states[0][i],
+                    cc[i].0,
+                );
+
+                // compute T''(x) = T(x) - T(z * g), multiply it by a pseudo-random coefficient,
+                // and add the result into composition polynomial
                 acc_trace_poly::<E::BaseField, E>(
-                    &mut t3_composition,
+                    &mut t2_composition,
                     poly,
-                    ood_trace_states[0][i].conjugate(),
-                    self.cc.trace[i].2,
+                    ood_trace_states[1][i],
+                    cc[i].1,
                 );
+
+                // do the same for all other rows of the frame
+                for (j, composition) in extra_compositions.iter_mut().enumerate() {
+                    acc_trace_poly::<E::BaseField, E>(
+                        composition,
+                        poly,
+                        ood_trace_states[j + 2][i],
+                        cc_offsets[i * (num_offsets - 2) + j],
+                    );
+                }
+
+                // when extension field is enabled, compute T'''(x) = T(x) - T(z_conjugate),
+                // multiply it by a pseudo-random coefficient, and add the result into
+                // composition polynomial
+                if self.field_extension {
+                    acc_trace_poly::<E::BaseField, E>(
+                        &mut t3_composition,
+                        poly,
+                        ood_trace_states[0][i].conjugate(),
+                        cc[i].2,
+                    );
+                }
+
+                i += 1;
             }
 
-            i += 1;
-        }
+            // --- merge polynomials of the auxiliary trace segments ------------------------------
 
-        // --- merge polynomials of the auxiliary trace segments ----------------------------------
-
-        // since trace polynomials are already in an extension field (when extension fields are
-        // used), we don't apply conjugate composition to them
-        for poly in trace_polys.aux_trace_polys() {
-            // compute T'(x) = T(x) - T(z), multiply it by a pseudo-random coefficient,
-            // and add the result into composition polynomial
-            acc_trace_poly::<E, E>(
-                &mut t1_composition,
-                poly,
-                ood_trace_states[0][i],
-                self.cc.trace[i].0,
-            );
-
-            // compute T''(x) = T(x) - T(z * g), multiply it by a pseudo-random coefficient,
-            // and add the result into composition polynomial
-            acc_trace_poly::<E, E>(
-                &mut t2_composition,
-                poly,
-                ood_trace_states[1][i],
-                self.cc.trace[i].1,
-            );
-
-            i += 1;
-        }
+            // since trace polynomials are already in an extension field (when extension fields
+            // are used), we don't apply conjugate composition to them
+            for poly in trace_polys.aux_trace_polys() {
+                // compute T'(x) = T(x) - T(z), multiply it by a pseudo-random coefficient,
+                // and add the result into composition polynomial
+                acc_trace_poly::<E, E>(&mut t1_composition, poly, ood_trace_states[0][i], cc[i].0);
 
-        // divide the composition polynomials by (x - z), (x - z * g), and (x - z_conjugate)
-        // respectively, and add the resulting polynomials together; the output of this step
-        // is a single trace polynomial T(x) and deg(T(x)) = trace_length - 2.
-        let trace_poly = merge_trace_compositions(
-            vec![t1_composition, t2_composition, t3_composition],
-            vec![self.z, next_z, self.z.conjugate()],
-        );
+                // compute T''(x) = T(x) - T(z * g), multiply it by a pseudo-random coefficient,
+                // and add the result into composition polynomial
+                acc_trace_poly::<E, E>(&mut t2_composition, poly, ood_trace_states[1][i], cc[i].1);
+
+                // do the same for all other rows of the frame
+                for (j, composition) in extra_compositions.iter_mut().enumerate() {
+                    let cc = cc_offsets[i * (num_offsets - 2) + j];
+                    acc_trace_poly::<E, E>(composition, poly, ood_trace_states[j + 2][i], cc);
+                }
+
+                i += 1;
+            }
+
+            // divide the composition polynomials by (x - z), (x - z * g^k) for all other row
+            // offsets k, and (x - z_conjugate) respectively, and add the resulting polynomials
+            // together; the output of this step is a single trace polynomial T(x) and
+            // deg(T(x)) = trace_length - 2.
+            let mut compositions = vec![t1_composition, t2_composition];
+            compositions.append(&mut extra_compositions);
+            compositions.push(t3_composition);
+            let mut divisors = offset_z;
+            divisors.push(z.conjugate());
+            let trace_poly = merge_trace_compositions(compositions, divisors);
+
+            // add the trace polynomial for this point to the DEEP composition polynomial
+            if self.coefficients.is_empty() {
+                self.coefficients = trace_poly;
+            } else {
+                add_in_place(&mut self.coefficients, &trace_poly);
+            }
+        }
 
-        // set the coefficients of the DEEP composition polynomial
-        self.coefficients = trace_poly;
         assert_eq!(self.poly_size() - 2, self.degree());
     }
 
@@ -175,37 +221,51 @@ impl<E: FieldElement> DeepCompositionPoly<E> {
     /// add_trace_polys() method has been executed. The composition is done as follows:
     ///
     /// - For each H_i(x), compute H'_i(x) = (H_i(x) - H(z^m)) / (x - z^m), where H_i(x) is the
-    ///   ith composition polynomial column and m is the total number of columns.
+    ///   ith composition polynomial column and m is the number of columns per point.
     /// - Then, combine all H_i(x) polynomials together by computing H(x) = sum(H_i(x) * cc_i) for
     ///   all i, where cc_i is the coefficient for the random linear combination drawn from the
     ///   public coin.
     ///
-    /// Note that evaluations of H_i(x) at z^m are passed in via the `ood_evaluations` parameter.
+    /// When multiple out-of-domain points are used, the composition polynomial consists of a
+    /// separate set of columns for every point; the above is done for every point z using the
+    /// columns and coefficients of that point, and the results are added together.
+    ///
+    /// Note that evaluations of H_i(x) at z^m are passed in via the `ood_evaluations` parameter;
+    /// evaluations for the first point are followed by evaluations for the second point etc.
     pub fn add_composition_poly(
         &mut self,
         composition_poly: CompositionPoly<E>,
         ood_evaluations: Vec<E>,
     ) {
         assert!(!self.coefficients.is_empty());
-
-        // compute z^m
-        let num_columns = composition_poly.num_columns() as u32;
-        let z_m = self.z.exp(num_columns.into());
-
-        let mut column_polys = composition_poly.into_columns();
-
-        // Divide out the OOD point z from column polynomials
-        iter_mut!(column_polys)
-            .zip(ood_evaluations)
-            .for_each(|(poly, value_at_z_m)| {
-                // compute H'_i(x) = (H_i(x) - H_i(z^m)) / (x - z^m)
-                poly[0] -= value_at_z_m;
-                polynom::syn_div_in_place(poly, 1, z_m);
-            });
-
-        // add H'_i(x) * cc_i for all i into the DEEP composition polynomial
-        for (i, poly) in column_polys.into_iter().enumerate() {
-            mul_acc::<E, E>(&mut self.coefficients, &poly, self.cc.constraints[i]);
+        assert_eq!(self.z.len(), composition_poly.num_points());
+
+        let num_columns = composition_poly.num_point_columns();
+        assert_eq!(self.z.len() * num_columns, ood_evaluations.len());
+
+        let mut columns = composition_poly.into_columns();
+        for (point_idx, (column_polys, ood_evaluations)) in columns
+            .chunks_mut(num_columns)
+            .zip(ood_evaluations.chunks(num_columns))
+            .enumerate()
+        {
+            // compute z^m
+            let z_m = self.z[point_idx].exp((num_columns as u32).into());
+
+            // Divide out the OOD point z from column polynomials
+            iter_mut!(column_polys)
+                .zip(ood_evaluations)
+                .for_each(|(poly, &value_at_z_m)| {
+                    // compute H'_i(x) = (H_i(x) - H_i(z^m)) / (x - z^m)
+                    poly[0] -= value_at_z_m;
+                    polynom::syn_div_in_place(poly, 1, z_m);
+                });
+
+            // add H'_i(x) * cc_i for all i into the DEEP composition polynomial
+            let cc = self.cc.constraint_coefficients(point_idx, self.z.len());
+            for (poly, &cc) in column_polys.iter().zip(cc) {
+                mul_acc::<E, E>(&mut self.coefficients, poly, cc);
+            }
         }
         assert_eq!(self.poly_size() - 2, self.degree());
     }
@@ -0,0 +1,186 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use utils::iterators::{cache_size_hint, get_chunk_size};
+
+#[cfg(all(
+    test,
+    feature = "f62",
+    feature = "f64",
+    feature = "f128",
+    feature = "quadratic"
+))]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Default minimum row width (in columns) at which rows are streamed into the hasher during
+/// commitment instead of being gathered into a buffer first.
+const DEFAULT_STREAMING_WIDTH: usize = 256;
+
+// PROVER CONFIG
+// ================================================================================================
+/// Prover-side settings which affect how a proof is computed, but not the proof itself.
+///
+/// Unlike [ProofOptions](air::ProofOptions), these settings are not included in a proof and
+/// have no effect on its contents: proofs generated with different configs for the same trace
+/// are identical.
+///
+/// Currently, the config controls how work is split into chunks during constraint evaluation
+/// and trace commitment. By default, chunk sizes are computed from the width of the rows being
+/// processed and the size of the L2 cache, which is detected at runtime when possible.
+///
+/// The config also controls when matrix rows are hashed by streaming their elements directly
+/// into the hasher column block by column block: for very wide rows this avoids materializing
+/// each row in a separate buffer before hashing it.
+///
+/// The config also controls whether transition constraints are evaluated while the commitment to
+/// the execution trace is being built (see
+/// [with_pipelined_evaluation()](ProverConfig::with_pipelined_evaluation)); by default, this is
+/// done only when `concurrent` feature is enabled.
+///
+/// Lastly, the config controls how an execution trace is validated against the AIR. By default,
+/// the trace is validated only in debug builds, and the prover panics if the trace is not valid.
+/// When trace validation is enabled, the trace is validated in all builds, and an invalid trace
+/// causes the prover to return [ProverError::InvalidTrace](crate::ProverError::InvalidTrace).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProverConfig {
+    cache_size: usize,
+    chunk_size: Option<usize>,
+    streaming_width: usize,
+    pipelined_evaluation: bool,
+    validate_trace: bool,
+}
+
+impl ProverConfig {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new config using the detected L2 cache size and adaptive chunk sizes.
+    ///
+    /// Transition constraint evaluation is pipelined with trace commitment when `concurrent`
+    /// feature is enabled.
+    pub fn new() -> Self {
+        ProverConfig {
+            cache_size: cache_size_hint(),
+            chunk_size: None,
+            streaming_width: DEFAULT_STREAMING_WIDTH,
+            pipelined_evaluation: cfg!(feature = "concurrent"),
+            validate_trace: false,
+        }
+    }
+
+    /// Sets the cache size (in bytes) used to compute adaptive chunk sizes.
+    ///
+    /// # Panics
+    /// Panics if `cache_size` is zero.
+    pub fn with_cache_size(mut self, cache_size: usize) -> Self {
+        assert!(cache_size > 0, "cache size must be greater than zero");
+        self.cache_size = cache_size;
+        self
+    }
+
+    /// Sets a fixed chunk size (in rows) to be used instead of adaptive chunk sizes.
+    ///
+    /// If the fixed chunk size is larger than the number of rows being processed, all rows are
+    /// processed as a single chunk.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is not a power of two.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(
+            chunk_size.is_power_of_two(),
+            "chunk size must be a power of two, but was {chunk_size}"
+        );
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Sets the minimum row width (in columns) at which rows are streamed into the hasher during
+    /// commitment.
+    ///
+    /// Setting this to `usize::MAX` disables streaming; setting it to 0 causes all rows to be
+    /// streamed.
+    pub fn with_streaming_width(mut self, streaming_width: usize) -> Self {
+        self.streaming_width = streaming_width;
+        self
+    }
+
+    /// Specifies whether transition constraints should be evaluated while the commitment to the
+    /// execution trace is being built.
+    ///
+    /// Evaluations of transition constraints do not depend on the commitment to the trace, and
+    /// only need to be merged using constraint composition coefficients (which are drawn after
+    /// the commitment is built). Thus, when this is enabled, main transition constraints are
+    /// evaluated into a table while the extended main trace segment is hashed, and auxiliary
+    /// transition constraints are evaluated into a table while the last auxiliary segment is
+    /// hashed; the tables are merged once the coefficients are drawn. Without `concurrent`
+    /// feature, the tables are built after the hashing is done, and thus, this is enabled by
+    /// default only when `concurrent` feature is enabled.
+    ///
+    /// The tables hold one element per transition constraint for every step of the constraint
+    /// evaluation domain, and thus, this trades memory for proving time. Main transition
+    /// constraints are not pipelined for traces with a preprocessed segment, and no evaluations
+    /// are pipelined when proof generation is resumed from a checkpoint.
+    pub fn with_pipelined_evaluation(mut self, enabled: bool) -> Self {
+        self.pipelined_evaluation = enabled;
+        self
+    }
+
+    /// Specifies whether the execution trace should be validated against the AIR before a proof
+    /// is generated, with validation failures reported as errors rather than panics.
+    ///
+    /// Validation is a very expensive operation, and thus, it should be enabled only in tests.
+    pub fn with_trace_validation(mut self, enabled: bool) -> Self {
+        self.validate_trace = enabled;
+        self
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the cache size (in bytes) used to compute adaptive chunk sizes.
+    pub fn cache_size(&self) -> usize {
+        self.cache_size
+    }
+
+    /// Returns the fixed chunk size set for this config, if any.
+    pub fn chunk_size(&self) -> Option<usize> {
+        self.chunk_size
+    }
+
+    /// Returns the minimum row width (in columns) at which rows are streamed into the hasher
+    /// during commitment.
+    pub fn streaming_width(&self) -> usize {
+        self.streaming_width
+    }
+
+    /// Returns true if transition constraints are evaluated while the commitment to the execution
+    /// trace is being built.
+    pub fn pipelines_evaluation(&self) -> bool {
+        self.pipelined_evaluation
+    }
+
+    /// Returns true if the execution trace is validated against the AIR in all builds, and
+    /// validation failures are reported as errors.
+    pub fn validates_trace(&self) -> bool {
+        self.validate_trace
+    }
+
+    /// Returns the number of rows to be processed in a single chunk when processing `num_rows`
+    /// rows, each of which touches `row_size` bytes of data.
+    pub fn get_chunk_size(&self, num_rows: usize, row_size: usize) -> usize {
+        match self.chunk_size {
+            Some(chunk_size) => chunk_size.min(num_rows),
+            None => get_chunk_size(num_rows, row_size, self.cache_size),
+        }
+    }
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::ProverConfig;
+use crate::{
+    tests::build_fib_trace, Air, AirContext, Assertion, AuxTraceRandElements, EvaluationFrame,
+    FieldExtension, Matrix, ProofOptions, Prover, Trace, TraceInfo, TraceLayout,
+    TraceLayoutBuilder, TraceTable, TransitionConstraintDegree,
+};
+use crypto::{
+    hashers::{Blake3_256, Rp64_256, Sha3_256},
+    ElementHasher, MerkleTree,
+};
+use math::{
+    fields::{f128::BaseElement, f62, f64, QuadExtension},
+    ExtensionOf, FieldElement,
+};
+use rand_utils::rand_vector;
+use utils::collections::Vec;
+
+type Blake3 = Blake3_256<BaseElement>;
+
+// CHUNK SIZES
+// ================================================================================================
+
+#[test]
+fn fixed_chunk_size() {
+    let config = ProverConfig::new().with_chunk_size(32);
+    assert_eq!(Some(32), config.chunk_size());
+    assert_eq!(32, config.get_chunk_size(1024, 16));
+    assert_eq!(32, config.get_chunk_size(1024, 1 << 20));
+    assert_eq!(16, config.get_chunk_size(16, 16));
+}
+
+#[test]
+fn adaptive_chunk_size() {
+    let config = ProverConfig::new().with_cache_size(256 * 1024);
+    assert_eq!(None, config.chunk_size());
+    assert_eq!(256 * 1024, config.cache_size());
+
+    // wider rows result in smaller chunks
+    let narrow = config.get_chunk_size(1 << 20, 2 * 2 * 16);
+    let wide = config.get_chunk_size(1 << 20, 2 * 96 * 16);
+    assert!(wide < narrow);
+}
+
+#[test]
+#[should_panic(expected = "chunk size must be a power of two, but was 24")]
+fn chunk_size_not_power_of_two() {
+    ProverConfig::new().with_chunk_size(24);
+}
+
+// PROOF CONSISTENCY
+// ================================================================================================
+
+#[test]
+fn commitments_do_not_depend_on_chunk_size() {
+    let columns = (0..7).map(|_| rand_vector::<BaseElement>(256)).collect();
+    let matrix = Matrix::new(columns);
+
+    let expected: MerkleTree<Blake3> = matrix.commit_to_rows();
+    for chunk_size in [1, 16, 64, 256, 1024] {
+        let config = ProverConfig::new().with_chunk_size(chunk_size);
+        let tree: MerkleTree<Blake3> = matrix.commit_to_rows_with_config(&config);
+        assert_eq!(expected.root(), tree.root());
+    }
+}
+
+#[test]
+fn streamed_commitments_match_buffered_commitments() {
+    let buffered = ProverConfig::new().with_streaming_width(usize::MAX);
+    let streamed = ProverConfig::new().with_streaming_width(0);
+    assert_eq!(256, ProverConfig::new().streaming_width());
+
+    for num_cols in [3, 64, 300] {
+        let matrix = Matrix::new(
+            (0..num_cols)
+                .map(|_| rand_vector::<BaseElement>(64))
+                .collect(),
+        );
+        check_streamed_commitment::<Blake3, _>(&matrix, &buffered, &streamed);
+        check_streamed_commitment::<Sha3_256<BaseElement>, _>(&matrix, &buffered, &streamed);
+
+        let matrix = Matrix::new(
+            (0..num_cols)
+                .map(|_| rand_vector::<f62::BaseElement>(64))
+                .collect(),
+        );
+        check_streamed_commitment::<Blake3_256<f62::BaseElement>, _>(&matrix, &buffered, &streamed);
+
+        let matrix = Matrix::new(
+            (0..num_cols)
+                .map(|_| rand_vector::<QuadExtension<f64::BaseElement>>(64))
+                .collect(),
+        );
+        check_streamed_commitment::<Rp64_256, _>(&matrix, &buffered, &streamed);
+    }
+}
+
+#[test]
+fn proofs_do_not_depend_on_chunk_size() {
+    let expected = FibProver::new(ProverConfig::default())
+        .prove(build_fib_trace(512))
+        .unwrap();
+    for chunk_size in [16, 64, 1024] {
+        let prover = FibProver::new(ProverConfig::new().with_chunk_size(chunk_size));
+        let proof = prover.prove(build_fib_trace(512)).unwrap();
+        assert_eq!(expected.to_bytes(), proof.to_bytes());
+    }
+}
+
+#[test]
+fn pipelined_evaluation_is_enabled_with_concurrent_feature() {
+    assert_eq!(
+        cfg!(feature = "concurrent"),
+        ProverConfig::new().pipelines_evaluation()
+    );
+}
+
+#[test]
+fn proofs_do_not_depend_on_pipelined_evaluation() {
+    let sequential = ProverConfig::new().with_pipelined_evaluation(false);
+    let expected = FibProver::new(sequential)
+        .prove(build_fib_trace(512))
+        .unwrap();
+    for chunk_size in [16, 1024] {
+        let config = ProverConfig::new()
+            .with_chunk_size(chunk_size)
+            .with_pipelined_evaluation(true);
+        let proof = FibProver::new(config).prove(build_fib_trace(512)).unwrap();
+        assert_eq!(expected.to_bytes(), proof.to_bytes());
+    }
+}
+
+#[test]
+fn proofs_with_aux_segment_do_not_depend_on_pipelined_evaluation() {
+    let sequential = ProverConfig::new().with_pipelined_evaluation(false);
+    let expected = RunningSumProver::new(sequential)
+        .prove(RunningSumTrace::new(512))
+        .unwrap();
+    for chunk_size in [16, 1024] {
+        let config = ProverConfig::new()
+            .with_chunk_size(chunk_size)
+            .with_pipelined_evaluation(true);
+        let proof = RunningSumProver::new(config)
+            .prove(RunningSumTrace::new(512))
+            .unwrap();
+        assert_eq!(expected.to_bytes(), proof.to_bytes());
+    }
+}
+
+fn check_streamed_commitment<H, E>(
+    matrix: &Matrix<E>,
+    buffered: &ProverConfig,
+    streamed: &ProverConfig,
+) where
+    H: ElementHasher<BaseField = E::BaseField>,
+    E: FieldElement,
+{
+    let expected: MerkleTree<H> = matrix.commit_to_rows_with_config(buffered);
+    let tree: MerkleTree<H> = matrix.commit_to_rows_with_config(streamed);
+    assert_eq!(expected.root(), tree.root());
+}
+
+// FIBONACCI PROVER
+// ================================================================================================
+
+struct FibAir {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for FibAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let degrees = vec![
+            TransitionConstraintDegree::new(1),
+            TransitionConstraintDegree::new(1),
+        ];
+        FibAir {
+            context: AirContext::new(trace_info, degrees, 2, options),
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        result[0] = next[0] - (current[0] + current[1]);
+        result[1] = next[1] - (current[1] + next[0]);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        vec![
+            Assertion::single(0, 0, BaseElement::ONE),
+            Assertion::single(1, 0, BaseElement::ONE),
+        ]
+    }
+}
+
+struct FibProver {
+    options: ProofOptions,
+    config: ProverConfig,
+}
+
+impl FibProver {
+    fn new(config: ProverConfig) -> Self {
+        FibProver {
+            options: ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 32),
+            config,
+        }
+    }
+}
+
+impl Prover for FibProver {
+    type BaseField = BaseElement;
+    type Air = FibAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3;
+
+    fn get_pub_inputs(&self, _trace: &Self::Trace) {}
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    fn config(&self) -> ProverConfig {
+        self.config
+    }
+}
+
+// RUNNING SUM PROVER
+// ================================================================================================
+
+/// An AIR with the same main trace segment as [FibAir], and an auxiliary segment consisting of a
+/// single column which accumulates the first main column scaled by a random element.
+struct RunningSumAir {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for RunningSumAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let main_degrees = vec![
+            TransitionConstraintDegree::new(1),
+            TransitionConstraintDegree::new(1),
+        ];
+        let aux_degrees = vec![TransitionConstraintDegree::new(1)];
+        RunningSumAir {
+            context: AirContext::new_multi_segment(
+                trace_info,
+                main_degrees,
+                aux_degrees,
+                2,
+                1,
+                options,
+            ),
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        result[0] = next[0] - (current[0] + current[1]);
+        result[1] = next[1] - (current[1] + next[0]);
+    }
+
+    fn evaluate_aux_transition<F, E>(
+        &self,
+        main_frame: &EvaluationFrame<F>,
+        aux_frame: &EvaluationFrame<E>,
+        _periodic_values: &[F],
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        result: &mut [E],
+    ) where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+        let alpha = aux_rand_elements.get_segment_elements(0)[0];
+        let value = main_frame.current()[0];
+        result[0] = aux_frame.next()[0] - (aux_frame.current()[0] + alpha.mul_base(value));
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        vec![
+            Assertion::single(0, 0, BaseElement::ONE),
+            Assertion::single(1, 0, BaseElement::ONE),
+        ]
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        _aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Vec<Assertion<E>> {
+        vec![Assertion::single(0, 0, E::ZERO)]
+    }
+}
+
+struct RunningSumTrace {
+    layout: TraceLayout,
+    main_segment: Matrix<BaseElement>,
+}
+
+impl RunningSumTrace {
+    fn new(length: usize) -> Self {
+        let fib_trace = build_fib_trace(length * 2);
+        let columns = (0..fib_trace.main_trace_width())
+            .map(|i| fib_trace.main_segment().get_column(i).to_vec())
+            .collect();
+        RunningSumTrace {
+            layout: TraceLayoutBuilder::new()
+                .main_width(2)
+                .aux_segment(1, 1)
+                .build()
+                .expect("invalid trace layout"),
+            main_segment: Matrix::new(columns),
+        }
+    }
+}
+
+impl Trace for RunningSumTrace {
+    type BaseField = BaseElement;
+
+    fn layout(&self) -> &TraceLayout {
+        &self.layout
+    }
+
+    fn length(&self) -> usize {
+        self.main_segment.num_rows()
+    }
+
+    fn meta(&self) -> &[u8] {
+        &[]
+    }
+
+    fn main_segment(&self) -> &Matrix<BaseElement> {
+        &self.main_segment
+    }
+
+    fn build_aux_segment<E: FieldElement<BaseField = Self::BaseField>>(
+        &mut self,
+        aux_segments: &[Matrix<E>],
+        rand_elements: &[E],
+    ) -> Option<Matrix<E>> {
+        if !aux_segments.is_empty() {
+            return None;
+        }
+        let mut column = vec![E::ZERO; self.length()];
+        for i in 1..self.length() {
+            let value = self.main_segment.get(0, i - 1);
+            column[i] = column[i - 1] + rand_elements[0].mul_base(value);
+        }
+        Some(Matrix::new(vec![column]))
+    }
+
+    fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<Self::BaseField>) {
+        for i in 0..frame.num_rows() {
+            let frame_row_idx = (row_idx + frame.offsets()[i]) % self.length();
+            self.main_segment
+                .read_row_into(frame_row_idx, frame.row_mut(i));
+        }
+    }
+}
+
+struct RunningSumProver {
+    options: ProofOptions,
+    config: ProverConfig,
+}
+
+impl RunningSumProver {
+    fn new(config: ProverConfig) -> Self {
+        RunningSumProver {
+            options: ProofOptions::new(28, 8, 0, FieldExtension::None, 4, 32),
+            config,
+        }
+    }
+}
+
+impl Prover for RunningSumProver {
+    type BaseField = BaseElement;
+    type Air = RunningSumAir;
+    type Trace = RunningSumTrace;
+    type HashFn = Blake3;
+
+    fn get_pub_inputs(&self, _trace: &Self::Trace) {}
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    fn config(&self) -> ProverConfig {
+        self.config
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Recording of transition constraints which an execution trace does not satisfy.
+//!
+//! While a closure is executed via [record()], every prover on the current thread validates
+//! execution traces against their AIRs (in release builds as well as in debug builds), and every
+//! transition constraint evaluation which is expected to be ZERO but is not is appended to a
+//! [ConstraintLog]. Unlike regular trace validation, validation performed while recording does
+//! not stop at the first unsatisfied constraint; the prover returns
+//! [ProverError::InvalidTrace](crate::ProverError::InvalidTrace) once the entire trace has been
+//! checked.
+//!
+//! This module is available only when `trace-constraints` feature is enabled; when the feature is
+//! disabled, constraint evaluations are not recorded at all.
+
+use core::{cell::RefCell, fmt};
+use math::FieldElement;
+use std::{string::String, vec::Vec};
+
+// RECORDER
+// ================================================================================================
+
+std::thread_local! {
+    static RECORDER: RefCell<Option<ConstraintLog>> = const { RefCell::new(None) };
+}
+
+/// Executes the provided closure and returns its result together with the log of all transition
+/// constraint violations found on the current thread while the closure was executed.
+///
+/// Recording is not nested: if `f` calls this function, the violations found while the inner
+/// closure is executed are recorded only into the inner log.
+pub fn record<R, F: FnOnce() -> R>(f: F) -> (R, ConstraintLog) {
+    let guard = RecorderGuard {
+        previous: RECORDER.with(|cell| cell.replace(Some(ConstraintLog::new()))),
+    };
+    let result = f();
+    let log = RECORDER
+        .with(|cell| cell.borrow_mut().take())
+        .expect("constraint recorder was removed while recording");
+    drop(guard);
+    (result, log)
+}
+
+/// Restores the recorder which was active before [record()] was called; this also happens when
+/// the recorded closure panics.
+struct RecorderGuard {
+    previous: Option<ConstraintLog>,
+}
+
+impl Drop for RecorderGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        RECORDER.with(|cell| cell.replace(previous));
+    }
+}
+
+/// Returns true if constraint violations are being recorded on the current thread.
+pub(crate) fn is_recording() -> bool {
+    RECORDER.with(|cell| cell.borrow().is_some())
+}
+
+/// Records a violation of the specified transition constraint, and returns true if the violation
+/// was recorded (i.e., if recording is active on the current thread).
+pub(crate) fn push<E: FieldElement>(aux: bool, index: usize, step: usize, value: E) -> bool {
+    RECORDER.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(log) => {
+            log.push(ConstraintViolation {
+                step,
+                aux,
+                index,
+                value: value.to_string(),
+            });
+            true
+        }
+        None => false,
+    })
+}
+
+// CONSTRAINT LOG
+// ================================================================================================
+
+/// An ordered list of transition constraint violations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintLog {
+    entries: Vec<ConstraintViolation>,
+}
+
+impl ConstraintLog {
+    /// Returns a new empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an entry to this log.
+    pub fn push(&mut self, entry: ConstraintViolation) {
+        self.entries.push(entry);
+    }
+
+    /// Returns all entries of this log in the order in which they were recorded; for a single
+    /// trace, the entries are ordered by step, and within a step, violations of main constraints
+    /// precede violations of auxiliary constraints.
+    pub fn entries(&self) -> &[ConstraintViolation] {
+        &self.entries
+    }
+
+    /// Returns the number of entries in this log.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if this log contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns this log encoded as a JSON object with a single `entries` array; every entry is
+    /// placed on a separate line so that logs can be compared with line-based diff tools.
+    pub fn to_json(&self) -> String {
+        let mut result = String::from("{\"entries\":[");
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            result.push_str(&format!(
+                "\n{{\"step\":{},\"segment\":\"{}\",\"constraint\":{},\"value\":\"{}\"}}",
+                entry.step,
+                entry.segment(),
+                entry.index,
+                entry.value
+            ));
+        }
+        result.push_str("\n]}");
+        result
+    }
+}
+
+// CONSTRAINT VIOLATION
+// ================================================================================================
+
+/// A transition constraint which did not evaluate to ZERO at a step of an execution trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    /// Step of the execution trace at which the evaluation frame starts.
+    pub step: usize,
+    /// True if the constraint is placed against auxiliary trace segments.
+    pub aux: bool,
+    /// Index of the constraint among the main or the auxiliary transition constraints.
+    pub index: usize,
+    /// Value to which the constraint evaluated.
+    pub value: String,
+}
+
+impl ConstraintViolation {
+    /// Returns the name of the trace segment against which the constraint is placed.
+    fn segment(&self) -> &'static str {
+        if self.aux {
+            "aux"
+        } else {
+            "main"
+        }
+    }
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "step {}: {} transition constraint {} evaluated to {}",
+            self.step,
+            self.segment(),
+            self.index,
+            self.value
+        )
+    }
+}
@@ -4,9 +4,15 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::StarkDomain;
-use air::{Air, AuxTraceRandElements, ConstraintDivisor};
+use air::{Air, AuxTraceRandElements, BoundaryExprConstraint, ConstraintDivisor};
 use math::{fft, ExtensionOf, FieldElement};
-use utils::collections::{BTreeMap, Vec};
+use utils::{
+    collections::{BTreeMap, Vec},
+    iter,
+};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
 
 // CONSTANTS
 // ================================================================================================
@@ -24,35 +30,38 @@ const SMALL_POLY_DEGREE: usize = 63;
 ///
 /// We transform the constraints defined in the [air] crate into specialized constraints here
 /// to make evaluation of these constraints more efficient in the prover context.
-pub struct BoundaryConstraints<E: FieldElement>(Vec<BoundaryConstraintGroup<E>>);
+///
+/// Boundary expressions (if any) are evaluated together with the group of constraints having
+/// the same divisor, and thus, do not introduce additional columns into the constraint
+/// evaluation table unless no assertion is placed against the step of an expression.
+pub struct BoundaryConstraints<E: FieldElement> {
+    groups: Vec<BoundaryConstraintGroup<E>>,
+    exprs: Vec<BoundaryExprTerm<E>>,
+}
 
 impl<E: FieldElement> BoundaryConstraints<E> {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
-    /// Returns a new instance of [BoundaryConstraints] constructed from the constraints defined
-    /// by an instance of AIR for a specific computation.
+    /// Returns a new instance of [BoundaryConstraints] constructed from the constraints and
+    /// boundary expressions defined by an instance of AIR for a specific computation.
     pub fn new<A: Air<BaseField = E::BaseField>>(
         air: &A,
         aux_rand_elements: &AuxTraceRandElements<E>,
         composition_coefficients: &[(E, E)],
+        expr_coefficients: &[(E, E)],
     ) -> Self {
         // get constraints from the AIR instance
         let source = air.get_boundary_constraints(aux_rand_elements, composition_coefficients);
 
-        // initialize a map of twiddles here so that we can keep track of already computed
-        // twiddles; this helps us avoid building twiddles over and over again for constraints
-        // defined over the same domain. twiddles are relevant only for large polynomial
-        // constraints.
-        let mut twiddle_map = BTreeMap::new();
+        // build twiddles for all domains over which large polynomial constraints are defined;
+        // this helps us avoid building twiddles over and over again for constraints defined
+        // over the same domain, and allows evaluating large polynomial constraints in parallel
+        let twiddle_map = build_twiddle_map(&source);
 
         // transform constraints against the main segment of the execution trace into specialized
-        // constraints
-        let mut result = source
-            .main_constraints()
-            .iter()
-            .map(|group| {
-                BoundaryConstraintGroup::from_main_constraints(group, air, &mut twiddle_map)
-            })
+        // constraints; when `concurrent` feature is enabled, this is done in multiple threads
+        let mut result = iter!(source.main_constraints())
+            .map(|group| BoundaryConstraintGroup::from_main_constraints(group, air, &twiddle_map))
             .collect::<Vec<BoundaryConstraintGroup<E>>>();
 
         // transform constraints against auxiliary trace segments (if any) into specialized
@@ -62,16 +71,48 @@ impl<E: FieldElement> BoundaryConstraints<E> {
         // that we always end up with a single constraint group for the same divisor.
         for group in source.aux_constraints() {
             match result.iter_mut().find(|g| &g.divisor == group.divisor()) {
-                Some(x) => x.add_aux_constraints(group, air, &mut twiddle_map),
+                Some(x) => x.add_aux_constraints(group, air, &twiddle_map),
                 None => {
                     let group =
-                        BoundaryConstraintGroup::from_aux_constraints(group, air, &mut twiddle_map);
+                        BoundaryConstraintGroup::from_aux_constraints(group, air, &twiddle_map);
                     result.push(group);
                 }
             };
         }
 
-        Self(result)
+        // attach boundary expressions to the groups with the same divisor; if there is no such
+        // group, an empty group is created so that the divisor gets its own column in the
+        // constraint evaluation table
+        let mut exprs = Vec::new();
+        for constraint in air.get_boundary_expr_constraints(expr_coefficients) {
+            let group_idx = match result
+                .iter()
+                .position(|g| &g.divisor == constraint.divisor())
+            {
+                Some(idx) => idx,
+                None => {
+                    let divisor = constraint.divisor().clone();
+                    result.push(BoundaryConstraintGroup::new(
+                        divisor,
+                        0,
+                        air.domain_offset(),
+                    ));
+                    result.len() - 1
+                }
+            };
+            exprs.push(BoundaryExprTerm {
+                domain_offset_exp: air
+                    .domain_offset()
+                    .exp(constraint.degree_adjustment().into()),
+                constraint,
+                group_idx,
+            });
+        }
+
+        Self {
+            groups: result,
+            exprs,
+        }
     }
 
     // PUBLIC ACCESSORS
@@ -79,7 +120,7 @@ impl<E: FieldElement> BoundaryConstraints<E> {
 
     /// Returns a vector of all boundary constraint divisors.
     pub fn get_divisors(&self) -> Vec<ConstraintDivisor<E::BaseField>> {
-        self.0.iter().map(|g| g.divisor.clone()).collect()
+        self.groups.iter().map(|g| g.divisor.clone()).collect()
     }
 
     // EVALUATORS
@@ -95,12 +136,13 @@ impl<E: FieldElement> BoundaryConstraints<E> {
         result: &mut [E],
     ) {
         let x = domain.get_ce_x_at(step);
-        for (group, result) in self.0.iter().zip(result.iter_mut()) {
+        for (group, result) in self.groups.iter().zip(result.iter_mut()) {
             // evaluate the group and save the result
             let (power, offset_exp) = (group.degree_adjustment, group.domain_offset_exp);
             let xp = domain.get_ce_x_power_at(step, power, offset_exp);
             *result = group.evaluate_main(main_state, step, x, xp);
         }
+        self.evaluate_exprs(main_state, domain, step, result);
     }
 
     /// Evaluates boundary constraints against all segments of an execution trace at the
@@ -114,15 +156,45 @@ impl<E: FieldElement> BoundaryConstraints<E> {
         result: &mut [E],
     ) {
         let x = domain.get_ce_x_at(step);
-        for (group, result) in self.0.iter().zip(result.iter_mut()) {
+        for (group, result) in self.groups.iter().zip(result.iter_mut()) {
             // evaluate the group and save the result
             let (power, offset_exp) = (group.degree_adjustment, group.domain_offset_exp);
             let xp = domain.get_ce_x_power_at(step, power, offset_exp);
             *result = group.evaluate_all(main_state, aux_state, step, x, xp);
         }
+        self.evaluate_exprs(main_state, domain, step, result);
+    }
+
+    /// Evaluates boundary expressions against the main segment of an execution trace at the
+    /// specified step of constraint evaluation domain, and adds the results to the evaluations
+    /// of the groups with the same divisors.
+    fn evaluate_exprs(
+        &self,
+        main_state: &[E::BaseField],
+        domain: &StarkDomain<E::BaseField>,
+        step: usize,
+        result: &mut [E],
+    ) {
+        for term in self.exprs.iter() {
+            let power = term.constraint.degree_adjustment();
+            let xp = domain.get_ce_x_power_at(step, power, term.domain_offset_exp);
+            result[term.group_idx] += term.constraint.evaluate_at(main_state, xp);
+        }
     }
 }
 
+// BOUNDARY EXPRESSION TERM
+// ================================================================================================
+
+/// A boundary expression constraint together with the index of the constraint group which has
+/// the same divisor; the domain offset exponent is pre-computed to avoid exponentiations during
+/// constraint evaluation.
+struct BoundaryExprTerm<E: FieldElement> {
+    constraint: BoundaryExprConstraint<E::BaseField, E>,
+    group_idx: usize,
+    domain_offset_exp: E::BaseField,
+}
+
 // BOUNDARY CONSTRAINT GROUP
 // ================================================================================================
 
@@ -181,7 +253,7 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
     pub fn from_main_constraints<A: Air<BaseField = E::BaseField>>(
         source: &air::BoundaryConstraintGroup<E::BaseField, E>,
         air: &A,
-        twiddle_map: &mut BTreeMap<usize, Vec<E::BaseField>>,
+        twiddle_map: &BTreeMap<usize, Vec<E::BaseField>>,
     ) -> Self {
         let mut result = Self::new(
             source.divisor().clone(),
@@ -189,6 +261,7 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
             air.domain_offset(),
         );
 
+        let mut large_poly = Vec::new();
         for constraint in source.constraints() {
             if constraint.poly().len() == 1 {
                 let constraint = SingleValueConstraint::new(constraint);
@@ -197,10 +270,10 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
                 let constraint = SmallPolyConstraint::new(constraint);
                 result.main_small_poly.push(constraint);
             } else {
-                let constraint = LargePolyConstraint::new(constraint, air, twiddle_map);
-                result.main_large_poly.push(constraint);
+                large_poly.push(constraint);
             }
         }
+        result.main_large_poly = build_large_poly_constraints(&large_poly, air, twiddle_map);
 
         result
     }
@@ -214,7 +287,7 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
     pub fn from_aux_constraints<A: Air<BaseField = E::BaseField>>(
         group: &air::BoundaryConstraintGroup<E, E>,
         air: &A,
-        twiddle_map: &mut BTreeMap<usize, Vec<E::BaseField>>,
+        twiddle_map: &BTreeMap<usize, Vec<E::BaseField>>,
     ) -> Self {
         let mut result = Self::new(
             group.divisor().clone(),
@@ -240,7 +313,7 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
         &mut self,
         group: &air::BoundaryConstraintGroup<E, E>,
         air: &A,
-        twiddle_map: &mut BTreeMap<usize, Vec<E::BaseField>>,
+        twiddle_map: &BTreeMap<usize, Vec<E::BaseField>>,
     ) {
         assert_eq!(
             group.divisor(),
@@ -248,6 +321,7 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
             "inconsistent constraint divisor"
         );
 
+        let mut large_poly = Vec::new();
         for constraint in group.constraints() {
             if constraint.poly().len() == 1 {
                 let constraint = SingleValueConstraint::new(constraint);
@@ -256,10 +330,11 @@ impl<E: FieldElement> BoundaryConstraintGroup<E> {
                 let constraint = SmallPolyConstraint::new(constraint);
                 self.aux_small_poly.push(constraint);
             } else {
-                let constraint = LargePolyConstraint::new(constraint, air, twiddle_map);
-                self.aux_large_poly.push(constraint);
+                large_poly.push(constraint);
             }
         }
+        let large_poly = build_large_poly_constraints(&large_poly, air, twiddle_map);
+        self.aux_large_poly.extend(large_poly);
     }
 
     // EVALUATORS
@@ -448,19 +523,14 @@ where
     pub fn new<A: Air<BaseField = F::BaseField>>(
         source: &air::BoundaryConstraint<F, E>,
         air: &A,
-        twiddle_map: &mut BTreeMap<usize, Vec<F::BaseField>>,
+        twiddles: &[F::BaseField],
     ) -> Self {
         debug_assert!(
             source.poly().len() >= SMALL_POLY_DEGREE,
             "not a large poly constraint"
         );
-        // evaluate the polynomial over the entire constraint evaluation domain; first
-        // get twiddles for the evaluation; if twiddles haven't been built yet, build them
+        // evaluate the polynomial over the entire constraint evaluation domain
         let poly_length = source.poly().len();
-        let twiddles = twiddle_map
-            .entry(poly_length)
-            .or_insert_with(|| fft::get_twiddles(poly_length));
-
         let values = fft::evaluate_poly_with_offset(
             source.poly(),
             twiddles,
@@ -497,3 +567,55 @@ where
         (self.coefficients.0 + self.coefficients.1.mul_base(xp)).mul_base(evaluation)
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds twiddles for evaluating all large polynomial constraints defined in the specified
+/// boundary constraints over the constraint evaluation domain; the twiddles are keyed by the
+/// length of the polynomial.
+fn build_twiddle_map<E: FieldElement>(
+    source: &air::BoundaryConstraints<E>,
+) -> BTreeMap<usize, Vec<E::BaseField>> {
+    let main_lengths = source
+        .main_constraints()
+        .iter()
+        .flat_map(|group| group.constraints().iter().map(|c| c.poly().len()));
+    let aux_lengths = source
+        .aux_constraints()
+        .iter()
+        .flat_map(|group| group.constraints().iter().map(|c| c.poly().len()));
+
+    let mut twiddle_map = BTreeMap::new();
+    for poly_length in main_lengths.chain(aux_lengths) {
+        if poly_length >= SMALL_POLY_DEGREE {
+            twiddle_map
+                .entry(poly_length)
+                .or_insert_with(|| fft::get_twiddles(poly_length));
+        }
+    }
+    twiddle_map
+}
+
+/// Transforms the specified source constraints into large polynomial constraints by evaluating
+/// their value polynomials over the constraint evaluation domain.
+///
+/// When `concurrent` feature is enabled, the polynomials are evaluated in multiple threads; the
+/// order of the returned constraints matches the order of the source constraints.
+fn build_large_poly_constraints<F, E, A>(
+    source: &[&air::BoundaryConstraint<F, E>],
+    air: &A,
+    twiddle_map: &BTreeMap<usize, Vec<F::BaseField>>,
+) -> Vec<LargePolyConstraint<F, E>>
+where
+    F: FieldElement,
+    E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
+    A: Air<BaseField = F::BaseField>,
+{
+    iter!(source)
+        .map(|constraint| {
+            let twiddles = &twiddle_map[&constraint.poly().len()];
+            LargePolyConstraint::new(constraint, air, twiddles)
+        })
+        .collect()
+}
@@ -4,10 +4,17 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::Matrix;
-use air::proof::Queries;
-use crypto::{ElementHasher, MerkleTree};
-use math::FieldElement;
-use utils::collections::Vec;
+use crate::LeafSalts;
+use air::{
+    proof::{CommitmentLayout, Queries},
+    LdePos, LeafEncoding,
+};
+use crypto::{ElementHasher, MerkleTree, VectorCommitment};
+use math::{log2, FieldElement};
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable,
+};
 
 // CONSTRAINT COMMITMENT
 // ================================================================================================
@@ -16,56 +23,153 @@ use utils::collections::Vec;
 ///
 /// The commitment consists of two components:
 /// * Evaluations of composition polynomial columns over the LDE domain.
-/// * Merkle tree where each leaf in the tree corresponds to a row in the composition polynomial
-///   evaluation matrix.
-pub struct ConstraintCommitment<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
+/// * Vector commitment (a Merkle tree by default) where each leaf corresponds to a row in the
+///   composition polynomial evaluation matrix.
+///
+/// When proof options request salted commitments, the commitment also contains the salts of
+/// leaves of the commitment.
+pub struct ConstraintCommitment<
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H> = MerkleTree<H>,
+> {
     evaluations: Matrix<E>,
-    commitment: MerkleTree<H>,
+    commitment: V,
+    salts: Option<LeafSalts<H>>,
 }
 
-impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> ConstraintCommitment<E, H> {
+impl<E, H, V> ConstraintCommitment<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
     /// Creates a new constraint evaluation commitment from the provided composition polynomial
-    /// evaluations and the corresponding Merkle tree commitment.
-    pub fn new(evaluations: Matrix<E>, commitment: MerkleTree<H>) -> ConstraintCommitment<E, H> {
+    /// evaluations and the corresponding vector commitment.
+    pub fn new(evaluations: Matrix<E>, commitment: V) -> Self {
         assert_eq!(
             evaluations.num_rows(),
-            commitment.leaves().len(),
+            commitment.domain_len(),
             "number of rows in constraint evaluation matrix must be the same as number of leaves in constraint commitment"
         );
         ConstraintCommitment {
             evaluations,
             commitment,
+            salts: None,
         }
     }
 
-    /// Returns the root of the commitment Merkle tree.
+    /// Returns this constraint commitment with the provided salts of leaves of the commitment;
+    /// the commitment must be built with these salts.
+    pub fn with_salts(mut self, salts: Option<LeafSalts<H>>) -> Self {
+        self.salts = salts;
+        self
+    }
+
+    /// Returns the root of the commitment Merkle tree (or, more generally, the commitment to the
+    /// vector of leaves).
     pub fn root(&self) -> H::Digest {
-        *self.commitment.root()
+        self.commitment.commitment()
     }
 
     /// Returns the depth of the commitment Merkle tree.
     #[allow(unused)]
     pub fn tree_depth(&self) -> usize {
-        self.commitment.depth()
+        log2(self.commitment.domain_len()) as usize
     }
 
     /// Returns constraint evaluations at the specified positions along with Merkle authentication
-    /// paths from the root of the commitment to these evaluations.
-    pub fn query(self, positions: &[usize]) -> Queries {
-        // build Merkle authentication paths to the leaves specified by positions
-        let merkle_proof = self
+    /// paths (or, more generally, a proof opening the commitment) from the root of the commitment
+    /// to these evaluations.
+    pub fn query(self, positions: &[LdePos], layout: &CommitmentLayout) -> Queries {
+        // build Merkle authentication paths to the leaves containing the specified positions
+        let leaf_indexes = layout.leaf_indexes(positions);
+        let proof = self
             .commitment
-            .prove_batch(positions)
+            .open_many(&leaf_indexes)
             .expect("failed to generate a Merkle proof for constraint queries");
 
         // determine a set of evaluations corresponding to each position
         let mut evaluations = Vec::new();
         for &position in positions {
             let mut row = vec![E::ZERO; self.evaluations.num_cols()];
-            self.evaluations.read_row_into(position, &mut row);
+            self.evaluations
+                .read_row_into(position.as_usize(), &mut row);
             evaluations.push(row);
         }
 
-        Queries::new(merkle_proof, evaluations)
+        let salts = self
+            .salts
+            .map(|salts| leaf_indexes.iter().map(|&i| salts.get(i)).collect());
+        Queries::with_commitment_proof::<H, V, E>(
+            proof,
+            evaluations,
+            &leaf_indexes,
+            LeafEncoding::Hashed,
+            salts,
+        )
+    }
+}
+
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<E, H, V> Serializable for ConstraintCommitment<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    ///
+    /// For Merkle trees, only the leaves of the tree are serialized; internal nodes of the tree
+    /// are re-computed when the commitment is deserialized.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.evaluations.write_into(target);
+        self.commitment.write_into(target);
+        match &self.salts {
+            Some(salts) => {
+                target.write_u8(1);
+                salts.write_into(target);
+            }
+            None => target.write_u8(0),
+        }
+    }
+}
+
+impl<E, H, V> Deserializable for ConstraintCommitment<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    /// Reads a constraint evaluation commitment from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid commitment could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let evaluations = Matrix::read_from(source)?;
+        let commitment = V::read_from(source)?;
+        if evaluations.num_rows() != commitment.domain_len() {
+            return Err(DeserializationError::InvalidValue(
+                "number of rows in constraint evaluation matrix must be the same as number of leaves in constraint commitment"
+                    .to_string(),
+            ));
+        }
+        let salts = match source.read_u8()? {
+            0 => None,
+            1 => Some(LeafSalts::read_from(source)?),
+            value => {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "leaf salts flag must be 0 or 1, but was {value}"
+                )))
+            }
+        };
+
+        Ok(ConstraintCommitment {
+            evaluations,
+            commitment,
+            salts,
+        })
     }
 }
@@ -5,45 +5,65 @@
 
 use super::{Matrix, StarkDomain};
 use math::{polynom, FieldElement, StarkField};
-use utils::{collections::Vec, uninit_vector};
+use utils::{
+    collections::Vec, uninit_vector, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable,
+};
 
 // COMPOSITION POLYNOMIAL
 // ================================================================================================
 /// Represents a composition polynomial split into columns with each column being of length equal
 /// to trace_length. Thus, for example, if the composition polynomial has degree 2N - 1, where N
 /// is the trace length, it will be stored as two columns of size N (each of degree N - 1).
+///
+/// When proof options specify more than one out-of-domain point, a separate composition
+/// polynomial is built for every point; columns of all these polynomials are stored together:
+/// columns of the polynomial for the first point are followed by columns of the polynomial for
+/// the second point etc.
 pub struct CompositionPoly<E: FieldElement> {
     data: Matrix<E>,
+    num_points: usize,
 }
 
 impl<E: FieldElement> CompositionPoly<E> {
-    /// Returns a new composition polynomial.
-    pub fn new(coefficients: Vec<E>, trace_length: usize) -> Self {
+    /// Returns a new composition polynomial built from the specified polynomials (one per
+    /// out-of-domain point) in coefficient form.
+    pub fn new(polys: Vec<Vec<E>>, trace_length: usize) -> Self {
         assert!(
-            coefficients.len().is_power_of_two(),
-            "size of composition polynomial must be a power of 2, but was {}",
-            coefficients.len(),
+            !polys.is_empty(),
+            "at least one composition polynomial is required"
         );
         assert!(
             trace_length.is_power_of_two(),
             "trace length must be a power of 2, but was {trace_length}"
         );
-        assert!(
-            trace_length < coefficients.len(),
-            "trace length must be smaller than size of composition polynomial"
-        );
-        assert!(
-            coefficients[coefficients.len() - 1] != E::ZERO,
-            "expected composition polynomial of degree {}, but was {}",
-            coefficients.len() - 1,
-            polynom::degree_of(&coefficients)
-        );
 
-        let num_columns = coefficients.len() / trace_length;
-        let polys = transpose(coefficients, num_columns);
+        let num_points = polys.len();
+        let mut columns = Vec::new();
+        for coefficients in polys {
+            assert!(
+                coefficients.len().is_power_of_two(),
+                "size of composition polynomial must be a power of 2, but was {}",
+                coefficients.len(),
+            );
+            assert!(
+                trace_length < coefficients.len(),
+                "trace length must be smaller than size of composition polynomial"
+            );
+            assert!(
+                coefficients[coefficients.len() - 1] != E::ZERO,
+                "expected composition polynomial of degree {}, but was {}",
+                coefficients.len() - 1,
+                polynom::degree_of(&coefficients)
+            );
+
+            let num_columns = coefficients.len() / trace_length;
+            columns.append(&mut transpose(coefficients, num_columns));
+        }
 
         CompositionPoly {
-            data: Matrix::new(polys),
+            data: Matrix::new(columns),
+            num_points,
         }
     }
 
@@ -51,16 +71,33 @@ impl<E: FieldElement> CompositionPoly<E> {
     // --------------------------------------------------------------------------------------------
 
     /// Returns the number of individual column polynomials used to describe this composition
-    /// polynomial.
+    /// polynomial; this includes columns of the polynomials for all out-of-domain points.
     pub fn num_columns(&self) -> usize {
         self.data.num_cols()
     }
 
+    /// Returns the number of out-of-domain points for which this composition polynomial was
+    /// built.
+    pub fn num_points(&self) -> usize {
+        self.num_points
+    }
+
+    /// Returns the number of column polynomials used to describe the composition polynomial for
+    /// a single out-of-domain point.
+    pub fn num_point_columns(&self) -> usize {
+        self.num_columns() / self.num_points
+    }
+
     /// Returns the length of individual column polynomials; this is guaranteed to be a power of 2.
     pub fn column_len(&self) -> usize {
         self.data.num_rows()
     }
 
+    /// Returns coefficients of the column polynomial at the specified index.
+    pub fn get_column(&self, col_idx: usize) -> &[E] {
+        self.data.get_column(col_idx)
+    }
+
     /// Returns the degree of individual column polynomial.
     #[allow(unused)]
     pub fn column_degree(&self) -> usize {
@@ -87,11 +124,16 @@ impl<E: FieldElement> CompositionPoly<E> {
         self.data.evaluate_columns_over(domain)
     }
 
-    /// Returns evaluations of all composition polynomial columns at point z^m, where m is
-    /// the number of column polynomials.
-    pub fn evaluate_at(&self, z: E) -> Vec<E> {
-        let z_m = z.exp((self.num_columns() as u32).into());
-        self.data.evaluate_columns_at(z_m)
+    /// Returns evaluations of the columns of the composition polynomial for the out-of-domain
+    /// point with the specified index at z^m, where m is the number of column polynomials per
+    /// point.
+    pub fn evaluate_at(&self, point_idx: usize, z: E) -> Vec<E> {
+        let num_point_columns = self.num_point_columns();
+        let z_m = z.exp((num_point_columns as u32).into());
+        let columns = point_idx * num_point_columns..(point_idx + 1) * num_point_columns;
+        columns
+            .map(|col_idx| polynom::eval(self.data.get_column(col_idx), z_m))
+            .collect()
     }
 
     /// Transforms this composition polynomial into a vector of individual column polynomials.
@@ -100,6 +142,36 @@ impl<E: FieldElement> CompositionPoly<E> {
     }
 }
 
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<E: FieldElement> Serializable for CompositionPoly<E> {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(self.num_points as u8);
+        self.data.write_into(target);
+    }
+}
+
+impl<E: FieldElement> Deserializable for CompositionPoly<E> {
+    /// Reads a composition polynomial from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid composition polynomial could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_points = source.read_u8()? as usize;
+        let data = Matrix::read_from(source)?;
+        if num_points == 0 || data.num_cols() % num_points != 0 {
+            return Err(DeserializationError::InvalidValue(format!(
+                "{} composition polynomial columns cannot be split between {} points",
+                data.num_cols(),
+                num_points
+            )));
+        }
+        Ok(CompositionPoly { data, num_points })
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
@@ -129,7 +201,7 @@ fn transpose<E: FieldElement>(coefficients: Vec<E>, num_columns: usize) -> Vec<V
 // TESTS
 // ================================================================================================
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests {
 
     use math::fields::f128::BaseElement;
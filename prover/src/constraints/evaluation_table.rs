@@ -25,6 +25,7 @@ pub struct ConstraintEvaluationTable<'a, E: FieldElement> {
     evaluations: Vec<Vec<E>>,
     divisors: Vec<ConstraintDivisor<E::BaseField>>,
     domain: &'a StarkDomain<E::BaseField>,
+    num_points: usize,
 
     #[cfg(debug_assertions)]
     main_transition_evaluations: Vec<Vec<E::BaseField>>,
@@ -32,6 +33,10 @@ pub struct ConstraintEvaluationTable<'a, E: FieldElement> {
     aux_transition_evaluations: Vec<Vec<E>>,
     #[cfg(debug_assertions)]
     expected_transition_degrees: Vec<usize>,
+    #[cfg(debug_assertions)]
+    transition_divisor_indexes: Vec<usize>,
+    #[cfg(debug_assertions)]
+    num_transition_divisors: usize,
 }
 
 impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
@@ -39,10 +44,14 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
     // --------------------------------------------------------------------------------------------
     /// Returns a new constraint evaluation table with number of columns equal to the number of
     /// specified divisors, and number of rows equal to the size of constraint evaluation domain.
+    ///
+    /// Columns are split evenly between `num_points` out-of-domain points; columns of each
+    /// point are combined into a separate composition polynomial.
     #[cfg(not(debug_assertions))]
     pub fn new(
         domain: &'a StarkDomain<E::BaseField>,
         divisors: Vec<ConstraintDivisor<E::BaseField>>,
+        num_points: usize,
     ) -> Self {
         let num_columns = divisors.len();
         let num_rows = domain.ce_domain_size();
@@ -50,6 +59,7 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
             evaluations: uninit_matrix(num_columns, num_rows),
             divisors,
             domain,
+            num_points,
         }
     }
 
@@ -60,6 +70,7 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
     pub fn new(
         domain: &'a StarkDomain<E::BaseField>,
         divisors: Vec<ConstraintDivisor<E::BaseField>>,
+        num_points: usize,
         transition_constraints: &TransitionConstraints<E>,
     ) -> Self {
         let num_columns = divisors.len();
@@ -72,13 +83,20 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
         let expected_transition_degrees =
             build_transition_constraint_degrees(transition_constraints, domain.trace_length());
 
+        // also record divisors of all transition constraints so that actual degrees can be
+        // computed using the right divisor
+        let transition_divisor_indexes = transition_constraints.divisor_indexes().to_vec();
+
         ConstraintEvaluationTable {
             evaluations: uninit_matrix(num_columns, num_rows),
             divisors,
             domain,
+            num_points,
             main_transition_evaluations: uninit_matrix(num_tm_columns, num_rows),
             aux_transition_evaluations: uninit_matrix(num_ta_columns, num_rows),
             expected_transition_degrees,
+            transition_divisor_indexes,
+            num_transition_divisors: transition_constraints.num_divisors(),
         }
     }
 
@@ -91,8 +109,11 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
         self.evaluations[0].len()
     }
 
-    /// Returns number of columns in this table. The first column always contains the value of
-    /// combined transition constraint evaluations; the remaining columns contain values of
+    /// Returns number of columns in this table. The columns are split into equal chunks, one per
+    /// out-of-domain point. Within each chunk, the first columns contain values of combined
+    /// transition constraint evaluations, one column per distinct number of transition
+    /// exemptions; if any of the transition constraints are cyclic, the next column contains the
+    /// value of combined cyclic constraint evaluations. The remaining columns contain values of
     /// assertion constraint evaluations combined based on common divisors.
     #[allow(dead_code)]
     pub fn num_columns(&self) -> usize {
@@ -104,7 +125,7 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
 
     /// Break the table into the number of specified fragments. All fragments can be updated
     /// independently - e.g. in different threads.
-    pub fn fragments(&mut self, num_fragments: usize) -> Vec<EvaluationTableFragment<E>> {
+    pub fn fragments(&mut self, num_fragments: usize) -> Vec<EvaluationTableFragment<'_, E>> {
         let fragment_size = self.num_rows() / num_fragments;
         assert!(
             fragment_size >= MIN_FRAGMENT_SIZE,
@@ -154,32 +175,43 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
     // CONSTRAINT COMPOSITION
     // --------------------------------------------------------------------------------------------
     /// Divides constraint evaluation columns by their respective divisor (in evaluation form),
-    /// combines the results into a single column, and interpolates this column into a composition
-    /// polynomial in coefficient form.
+    /// combines the results into a single column per out-of-domain point, and interpolates these
+    /// columns into composition polynomials in coefficient form.
     pub fn into_poly(self) -> Result<CompositionPoly<E>, ProverError> {
-        // allocate memory for the combined polynomial
-        let mut combined_poly = E::zeroed_vector(self.num_rows());
-
-        // iterate over all columns of the constraint evaluation table, divide each column
-        // by the evaluations of its corresponding divisor, and add all resulting evaluations
-        // together into a single vector
-        for (column, divisor) in self.evaluations.into_iter().zip(self.divisors.iter()) {
-            // in debug mode, make sure post-division degree of each column matches the expected
-            // degree
-            #[cfg(debug_assertions)]
-            validate_column_degree(&column, divisor, self.domain, column.len() - 1)?;
-
-            // divide the column by the divisor and accumulate the result into combined_poly
-            acc_column(column, divisor, self.domain, &mut combined_poly);
-        }
+        let num_point_columns = self.evaluations.len() / self.num_points;
+        let inv_twiddles = self.domain.ce_inv_twiddles();
+
+        let mut polys = Vec::with_capacity(self.num_points);
+        let mut columns = self.evaluations.into_iter().zip(self.divisors.iter());
+        for _ in 0..self.num_points {
+            // allocate memory for the combined polynomial
+            let mut combined_poly = E::zeroed_vector(self.domain.ce_domain_size());
+
+            // iterate over the columns of the constraint evaluation table for the current point,
+            // divide each column by the evaluations of its corresponding divisor, and add all
+            // resulting evaluations together into a single vector
+            for (column, divisor) in columns.by_ref().take(num_point_columns) {
+                // in debug mode, make sure post-division degree of each column matches the
+                // expected degree
+                #[cfg(debug_assertions)]
+                validate_column_degree(&column, divisor, self.domain, column.len() - 1)?;
+
+                // divide the column by the divisor and accumulate the result into combined_poly
+                acc_column(column, divisor, self.domain, &mut combined_poly);
+            }
 
-        // at this point, combined_poly contains evaluations of the combined constraint polynomial;
-        // we interpolate this polynomial to transform it into coefficient form.
-        let inv_twiddles = fft::get_inv_twiddles::<E::BaseField>(combined_poly.len());
-        fft::interpolate_poly_with_offset(&mut combined_poly, &inv_twiddles, self.domain.offset());
+            // at this point, combined_poly contains evaluations of the combined constraint
+            // polynomial; we interpolate this polynomial to transform it into coefficient form.
+            fft::interpolate_poly_with_offset(
+                &mut combined_poly,
+                inv_twiddles,
+                self.domain.offset(),
+            );
+            polys.push(combined_poly);
+        }
 
         let trace_length = self.domain.trace_length();
-        Ok(CompositionPoly::new(combined_poly, trace_length))
+        Ok(CompositionPoly::new(polys, trace_length))
     }
 
     // DEBUG HELPERS
@@ -187,14 +219,15 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
 
     #[cfg(debug_assertions)]
     pub fn validate_transition_degrees(&mut self) {
-        // evaluate transition constraint divisor (which is assumed to be the first one in the
+        // evaluate transition constraint divisors (which are assumed to be at the front of the
         // divisor list) over the constraint evaluation domain. this is used later to compute
         // actual degrees of transition constraint evaluations.
-        let div_values = evaluate_divisor::<E::BaseField>(
-            &self.divisors[0],
-            self.num_rows(),
-            self.domain.offset(),
-        );
+        let div_values = self.divisors[..self.num_transition_divisors]
+            .iter()
+            .map(|divisor| {
+                evaluate_divisor::<E::BaseField>(divisor, self.num_rows(), self.domain.offset())
+            })
+            .collect::<Vec<_>>();
 
         // collect actual degrees for all transition constraints by interpolating saved
         // constraint evaluations into polynomials and checking their degree; also
@@ -204,15 +237,18 @@ impl<'a, E: FieldElement> ConstraintEvaluationTable<'a, E> {
         let inv_twiddles = fft::get_inv_twiddles::<E::BaseField>(self.num_rows());
 
         // first process transition constraint evaluations for the main trace segment
+        let mut divisor_indexes = self.transition_divisor_indexes.iter();
         for evaluations in self.main_transition_evaluations.iter() {
-            let degree = get_transition_poly_degree(evaluations, &inv_twiddles, &div_values);
+            let div_values = &div_values[*divisor_indexes.next().expect("missing divisor index")];
+            let degree = get_transition_poly_degree(evaluations, &inv_twiddles, div_values);
             actual_degrees.push(degree);
             max_degree = core::cmp::max(max_degree, degree);
         }
 
         // then process transition constraint evaluations for auxiliary trace segments
         for evaluations in self.aux_transition_evaluations.iter() {
-            let degree = get_transition_poly_degree(evaluations, &inv_twiddles, &div_values);
+            let div_values = &div_values[*divisor_indexes.next().expect("missing divisor index")];
+            let degree = get_transition_poly_degree(evaluations, &inv_twiddles, div_values);
             actual_degrees.push(degree);
             max_degree = core::cmp::max(max_degree, degree);
         }
@@ -327,10 +363,8 @@ fn acc_column<E: FieldElement>(
     domain: &StarkDomain<E::BaseField>,
     result: &mut [E],
 ) {
-    let numerator = divisor.numerator();
-    assert_eq!(numerator.len(), 1, "complex divisors are not yet supported");
-
-    // compute inverse evaluations of the divisor's numerator, which has the form (x^a - b)
+    // compute inverse evaluations of the divisor's numerator, which is a product of terms of
+    // the form (x^a - b)
     let z = get_inv_evaluation(divisor, domain);
 
     // divide column values by the divisor; for boundary constraints this computed simply as
@@ -374,36 +408,49 @@ fn acc_column<E: FieldElement>(
 }
 
 /// Computes evaluations of the divisor's numerator over the domain of the specified size and offset.
+///
+/// Evaluations of a term $(x^a - b)$ repeat with the period of $n / a$, where $n$ is the size of
+/// the domain; since degrees of all terms are powers of two, evaluations of the numerator repeat
+/// with the period of the term of the smallest degree, and only this many evaluations are computed.
 fn get_inv_evaluation<B: StarkField>(
     divisor: &ConstraintDivisor<B>,
     domain: &StarkDomain<B>,
 ) -> Vec<B> {
-    let numerator = divisor.numerator();
-    let a = numerator[0].0 as u64; // numerator degree
-    let b = numerator[0].1;
-
-    // this guarantees that we can use get_ce_x_power_at() below but limits execution trace length
-    // to be at most 2^32. in the future, we should revisit this to allow execution traces of
-    // greater length.
-    assert!(
-        a <= u32::MAX as u64,
-        "constraint divisor numerator degree cannot exceed {}, but was {}",
-        u32::MAX,
-        a
-    );
-
-    let n = domain.ce_domain_size() / a as usize;
-    let domain_offset_exp = domain.offset().exp(a.into());
+    let terms = divisor
+        .numerator()
+        .iter()
+        .map(|&(degree, b)| {
+            // this guarantees that we can use get_ce_x_power_at() below but limits execution trace
+            // length to be at most 2^32. in the future, we should revisit this to allow execution
+            // traces of greater length.
+            assert!(
+                degree <= u32::MAX as usize,
+                "constraint divisor numerator degree cannot exceed {}, but was {}",
+                u32::MAX,
+                degree
+            );
+            let a = degree as u64;
+            (a, b, domain.offset().exp(a.into()))
+        })
+        .collect::<Vec<_>>();
+    let min_degree = terms
+        .iter()
+        .map(|&(a, ..)| a)
+        .min()
+        .expect("divisor numerator is empty");
+    let n = domain.ce_domain_size() / min_degree as usize;
 
-    // compute x^a - b for all x
+    // compute the product of x^a - b over all terms for all x
     let mut evaluations = unsafe { uninit_vector(n) };
     batch_iter_mut!(
         &mut evaluations,
         128, // min batch size
         |batch: &mut [B], batch_offset: usize| {
             for (i, evaluation) in batch.iter_mut().enumerate() {
-                let x = domain.get_ce_x_power_at(batch_offset + i, a, domain_offset_exp);
-                *evaluation = x - b;
+                *evaluation = terms.iter().fold(B::ONE, |result, &(a, b, offset_exp)| {
+                    let x = domain.get_ce_x_power_at(batch_offset + i, a, offset_exp);
+                    result * (x - b)
+                });
             }
         }
     );
@@ -423,7 +470,9 @@ fn get_inv_evaluation<B: StarkField>(
 /// constraints which rely on periodic columns this computation is slightly more complex.
 ///
 /// The general idea is that evaluation degree is the degree of rational function `C(x) / z(x)`,
-/// where `C(x)` is the constraint polynomial and `z(x)` is the divisor polynomial.
+/// where `C(x)` is the constraint polynomial and `z(x)` is the divisor polynomial. For cyclic
+/// constraints of degree 1, `C(x)` must be a zero polynomial (its degree is smaller than the
+/// degree of its divisor), and thus, the expected degree is 0.
 #[cfg(debug_assertions)]
 fn build_transition_constraint_degrees<E: FieldElement>(
     constraints: &TransitionConstraints<E>,
@@ -431,12 +480,17 @@ fn build_transition_constraint_degrees<E: FieldElement>(
 ) -> Vec<usize> {
     let mut result = Vec::new();
 
-    for degree in constraints.main_constraint_degrees() {
-        result.push(degree.get_evaluation_degree(trace_length) - constraints.divisor().degree())
-    }
-
-    for degree in constraints.aux_constraint_degrees() {
-        result.push(degree.get_evaluation_degree(trace_length) - constraints.divisor().degree())
+    let degrees = constraints
+        .main_constraint_degrees()
+        .iter()
+        .chain(constraints.aux_constraint_degrees().iter());
+    for (i, degree) in degrees.enumerate() {
+        let divisor_degree = constraints.get_constraint_divisor(i).degree();
+        result.push(
+            degree
+                .get_evaluation_degree(trace_length)
+                .saturating_sub(divisor_degree),
+        )
     }
 
     result
@@ -476,6 +530,13 @@ fn validate_column_degree<B: StarkField, E: FieldElement<BaseField = B>>(
     domain: &StarkDomain<B>,
     expected_degree: usize,
 ) -> Result<(), ProverError> {
+    // a column can be all zeros only when constraints of this column are satisfied for any input
+    // (e.g., cyclic constraints of degree 1 which encode a rotation of trace columns); the degree
+    // of such column is undefined and the column does not affect the composition polynomial
+    if column.iter().all(|&value| value == E::ZERO) {
+        return Ok(());
+    }
+
     // build domain for divisor evaluation, and evaluate it over this domain
     let div_values = evaluate_divisor(divisor, column.len(), domain.offset());
 
@@ -4,34 +4,31 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{
-    super::TraceLde, evaluation_table::EvaluationTableFragment, BoundaryConstraints,
-    ConstraintEvaluationTable, PeriodicValueTable, StarkDomain,
+    super::TraceLde, evaluation_table::EvaluationTableFragment, AuxTransitionTable,
+    BoundaryConstraints, ConstraintEvaluationTable, MainTransitionTable, PeriodicValueTable,
+    ProverConfig, StarkDomain,
 };
 use air::{
-    Air, AuxTraceRandElements, ConstraintCompositionCoefficients, EvaluationFrame,
+    Air, AuxTraceRandElements, CePos, ConstraintCompositionCoefficients, EvaluationFrame,
     TransitionConstraints,
 };
 use math::FieldElement;
-use utils::iter_mut;
+use utils::{collections::Vec, iter_mut};
 
 #[cfg(feature = "concurrent")]
-use utils::{iterators::*, rayon};
-
-// CONSTANTS
-// ================================================================================================
-
-#[cfg(feature = "concurrent")]
-const MIN_CONCURRENT_DOMAIN_SIZE: usize = 8192;
+use utils::iterators::*;
 
 // CONSTRAINT EVALUATOR
 // ================================================================================================
 
 pub struct ConstraintEvaluator<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> {
     air: &'a A,
-    boundary_constraints: BoundaryConstraints<E>,
-    transition_constraints: TransitionConstraints<E>,
+    boundary_constraints: Vec<BoundaryConstraints<E>>,
+    transition_constraints: Vec<TransitionConstraints<E>>,
     aux_rand_elements: AuxTraceRandElements<E>,
     periodic_values: PeriodicValueTable<E::BaseField>,
+    main_transitions: Option<MainTransitionTable<E::BaseField>>,
+    aux_transitions: Option<AuxTransitionTable<E>>,
 }
 
 impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<'a, A, E> {
@@ -39,23 +36,39 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
     // --------------------------------------------------------------------------------------------
     /// Returns a new evaluator which can be used to evaluate transition and boundary constraints
     /// over extended execution trace.
+    ///
+    /// Constraints are composed separately for each set of composition coefficients (one set per
+    /// out-of-domain point).
+    ///
+    /// The table of periodic values does not depend on the randomness drawn by the prover, and
+    /// thus, it is built by the caller; this way it can be built while the trace commitment is
+    /// still being computed.
     pub fn new(
         air: &'a A,
         aux_rand_elements: AuxTraceRandElements<E>,
-        composition_coefficients: ConstraintCompositionCoefficients<E>,
+        composition_coefficients: Vec<ConstraintCompositionCoefficients<E>>,
+        periodic_values: PeriodicValueTable<E::BaseField>,
     ) -> Self {
         // build transition constraint groups; these will be used to compose transition constraint
         // evaluations
-        let transition_constraints =
-            air.get_transition_constraints(&composition_coefficients.transition);
-
-        // build periodic value table
-        let periodic_values = PeriodicValueTable::new(air);
+        let transition_constraints = composition_coefficients
+            .iter()
+            .map(|coefficients| air.get_transition_constraints(&coefficients.transition))
+            .collect();
 
         // build boundary constraint groups; these will be used to evaluate and compose boundary
         // constraint evaluations.
-        let boundary_constraints =
-            BoundaryConstraints::new(air, &aux_rand_elements, &composition_coefficients.boundary);
+        let boundary_constraints = composition_coefficients
+            .iter()
+            .map(|coefficients| {
+                BoundaryConstraints::new(
+                    air,
+                    &aux_rand_elements,
+                    &coefficients.boundary,
+                    &coefficients.boundary_exprs,
+                )
+            })
+            .collect();
 
         ConstraintEvaluator {
             air,
@@ -63,18 +76,49 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
             transition_constraints,
             aux_rand_elements,
             periodic_values,
+            main_transitions: None,
+            aux_transitions: None,
         }
     }
 
+    /// Sets evaluations of main transition constraints to be used instead of evaluating these
+    /// constraints during [evaluate()](Self::evaluate).
+    ///
+    /// The evaluations do not depend on the randomness drawn by the prover, and thus, they can be
+    /// computed while the trace commitment is still being computed. They must have been computed
+    /// against the same trace.
+    pub fn with_main_transitions(
+        mut self,
+        main_transitions: MainTransitionTable<E::BaseField>,
+    ) -> Self {
+        self.main_transitions = Some(main_transitions);
+        self
+    }
+
+    /// Sets evaluations of auxiliary transition constraints to be used instead of evaluating
+    /// these constraints during [evaluate()](Self::evaluate).
+    ///
+    /// The evaluations depend only on the random elements used to build auxiliary trace segments,
+    /// and thus, they can be computed while the commitment to the last auxiliary segment is still
+    /// being computed. They must have been computed against the same trace and random elements.
+    pub fn with_aux_transitions(mut self, aux_transitions: AuxTransitionTable<E>) -> Self {
+        self.aux_transitions = Some(aux_transitions);
+        self
+    }
+
     // EVALUATOR
     // --------------------------------------------------------------------------------------------
     /// Evaluates constraints against the provided extended execution trace. Constraints are
     /// evaluated over a constraint evaluation domain. This is an optimization because constraint
     /// evaluation domain can be many times smaller than the full LDE domain.
+    ///
+    /// The evaluation domain is split into fragments sized according to the specified `config`;
+    /// the fragmentation does not affect the results.
     pub fn evaluate(
         self,
         trace: &TraceLde<E>,
         domain: &'a StarkDomain<E::BaseField>,
+        config: &ProverConfig,
     ) -> ConstraintEvaluationTable<'a, E> {
         assert_eq!(
             trace.trace_len(),
@@ -82,38 +126,25 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
             "extended trace length is not consistent with evaluation domain"
         );
 
-        // build a list of constraint divisors; currently, all transition constraints have the same
-        // divisor which we put at the front of the list; boundary constraint divisors are appended
-        // after that
-        let mut divisors = vec![self.transition_constraints.divisor().clone()];
-        divisors.append(&mut self.boundary_constraints.get_divisors());
+        let mut evaluation_table = self.build_evaluation_table(domain);
 
-        // allocate space for constraint evaluations; when we are in debug mode, we also allocate
-        // memory to hold all transition constraint evaluations (before they are merged into a
-        // single value) so that we can check their degrees later
-        #[cfg(not(debug_assertions))]
-        let mut evaluation_table = ConstraintEvaluationTable::<E>::new(domain, divisors);
-        #[cfg(debug_assertions)]
-        let mut evaluation_table =
-            ConstraintEvaluationTable::<E>::new(domain, divisors, &self.transition_constraints);
-
-        // when `concurrent` feature is enabled, break the evaluation table into multiple fragments
-        // to evaluate them into multiple threads; unless the constraint evaluation domain is small,
-        // then don't bother with concurrent evaluation
-
-        #[cfg(not(feature = "concurrent"))]
-        let num_fragments = 1;
-
-        #[cfg(feature = "concurrent")]
-        let num_fragments = if domain.ce_domain_size() >= MIN_CONCURRENT_DOMAIN_SIZE {
-            rayon::current_num_threads().next_power_of_two()
-        } else {
-            1
-        };
+        // break the evaluation table into fragments; the size of a fragment is chosen so that
+        // the data touched while evaluating a fragment (evaluation frames and evaluation table
+        // rows) fits into cache. when `concurrent` feature is enabled, fragments are evaluated
+        // in multiple threads.
+        let fragment_size = config.get_chunk_size(
+            domain.ce_domain_size(),
+            self.get_row_size(trace, evaluation_table.num_columns()),
+        );
+        let num_fragments = domain.ce_domain_size() / fragment_size;
 
         // evaluate constraints for each fragment; if the trace consist of multiple segments
         // we evaluate constraints for all segments. otherwise, we evaluate constraints only
         // for the main segment.
+        debug_assert!(
+            self.aux_transitions.is_none() || self.air.trace_info().is_multi_segment(),
+            "auxiliary transition evaluations cannot be used with single-segment traces"
+        );
         let mut fragments = evaluation_table.fragments(num_fragments);
         iter_mut!(fragments).for_each(|fragment| {
             if self.air.trace_info().is_multi_segment() {
@@ -134,9 +165,58 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
     // EVALUATION HELPERS
     // --------------------------------------------------------------------------------------------
 
+    /// Allocates an evaluation table with one column per constraint divisor and out-of-domain
+    /// point.
+    fn build_evaluation_table(
+        &self,
+        domain: &'a StarkDomain<E::BaseField>,
+    ) -> ConstraintEvaluationTable<'a, E> {
+        // build a list of constraint divisors for every out-of-domain point; transition
+        // constraint divisors (one per distinct number of transition exemptions and, if any
+        // constraints are cyclic, one for cyclic constraints) are put at the front of the list;
+        // boundary constraint divisors are appended after that
+        let mut divisors = Vec::new();
+        for (t_constraints, b_constraints) in self
+            .transition_constraints
+            .iter()
+            .zip(self.boundary_constraints.iter())
+        {
+            divisors.append(&mut t_constraints.get_divisors());
+            divisors.append(&mut b_constraints.get_divisors());
+        }
+        let num_points = self.transition_constraints.len();
+
+        // allocate space for constraint evaluations; when we are in debug mode, we also allocate
+        // memory to hold all transition constraint evaluations (before they are merged into a
+        // single value) so that we can check their degrees later; raw evaluations do not depend
+        // on composition coefficients, and thus, the first set of transition constraints is used
+        #[cfg(not(debug_assertions))]
+        let evaluation_table = ConstraintEvaluationTable::<E>::new(domain, divisors, num_points);
+        #[cfg(debug_assertions)]
+        let evaluation_table = ConstraintEvaluationTable::<E>::new(
+            domain,
+            divisors,
+            num_points,
+            &self.transition_constraints[0],
+        );
+        evaluation_table
+    }
+
+    /// Returns the number of bytes touched while evaluating constraints at a single step: this
+    /// includes all rows of the evaluation frame across all trace segments, as well as a row of
+    /// the evaluation table.
+    fn get_row_size(&self, trace: &TraceLde<E>, num_evaluation_columns: usize) -> usize {
+        let frame_size = trace.main_trace_width() * E::BaseField::ELEMENT_BYTES
+            + trace.aux_trace_width() * E::ELEMENT_BYTES;
+        self.air.context().frame_offsets().len() * frame_size
+            + num_evaluation_columns * E::ELEMENT_BYTES
+    }
+
     /// Evaluates constraints for a single fragment of the evaluation table.
     ///
-    /// This evaluates constraints only over the main segment of the execution trace.
+    /// This evaluates constraints only over the main segment of the execution trace. All buffers
+    /// are allocated once per fragment and are reused at every step; thus, the number of
+    /// allocations does not depend on the size of the fragment.
     fn evaluate_fragment_main(
         &self,
         trace: &TraceLde<E>,
@@ -144,41 +224,53 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
         fragment: &mut EvaluationTableFragment<E>,
     ) {
         // initialize buffers to hold trace values and evaluation results at each step;
-        let mut main_frame = EvaluationFrame::new(trace.main_trace_width());
+        let frame_offsets = self.air.context().frame_offsets();
+        let mut main_frame = EvaluationFrame::with_offsets(trace.main_trace_width(), frame_offsets);
         let mut evaluations = vec![E::ZERO; fragment.num_columns()];
         let mut t_evaluations = vec![E::BaseField::ZERO; self.num_main_transition_constraints()];
+        let num_t_columns = self.transition_constraints[0].num_divisors();
+        let num_point_columns = evaluations.len() / self.transition_constraints.len();
 
         // this will be used to convert steps in constraint evaluation domain to steps in
         // LDE domain
-        let lde_shift = domain.ce_to_lde_blowup().trailing_zeros();
+        let ce_to_lde_blowup = domain.ce_to_lde_blowup();
 
         for i in 0..fragment.num_rows() {
             let step = i + fragment.offset();
+            let lde_step = CePos(step).to_lde_pos(ce_to_lde_blowup);
 
             // update evaluation frame buffer with data from the execution trace; this will
             // read current and next rows from the trace into the buffer; data in the trace
             // table is extended over the LDE domain, so, we need to convert step in constraint
             // evaluation domain, into a step in LDE domain, in case these domains are different
-            trace.read_main_trace_frame_into(step << lde_shift, &mut main_frame);
-
-            // evaluate transition constraints and save the merged result the first slot of the
-            // evaluations buffer
-            evaluations[0] =
-                self.evaluate_main_transition(&main_frame, domain, step, &mut t_evaluations);
+            trace.read_main_trace_frame_into(lde_step, &mut main_frame);
+
+            // evaluate transition constraints (unless they have been evaluated already); these
+            // evaluations are shared by all out-of-domain points
+            let main_evaluations = match self.main_transitions.as_ref() {
+                Some(main_transitions) => main_transitions.get_row(step),
+                None => {
+                    self.evaluate_main_transition(&main_frame, step, &mut t_evaluations);
+                    &t_evaluations
+                }
+            };
 
             // when in debug mode, save transition constraint evaluations
             #[cfg(debug_assertions)]
-            fragment.update_transition_evaluations(i, &t_evaluations, &[]);
+            fragment.update_transition_evaluations(i, main_evaluations, &[]);
 
-            // evaluate boundary constraints; the results go into remaining slots of the
-            // evaluations buffer
+            // for every out-of-domain point, save the merged transition results into the first
+            // slots of the point's chunk of the evaluations buffer (one slot per transition
+            // constraint divisor), and evaluate boundary constraints into the remaining slots
             let main_state = main_frame.current();
-            self.boundary_constraints.evaluate_main(
-                main_state,
-                domain,
-                step,
-                &mut evaluations[1..],
-            );
+            for (point_idx, point_evaluations) in
+                evaluations.chunks_mut(num_point_columns).enumerate()
+            {
+                let (t_columns, b_columns) = point_evaluations.split_at_mut(num_t_columns);
+                self.merge_main_transition(point_idx, main_evaluations, domain, step, t_columns);
+                self.boundary_constraints[point_idx]
+                    .evaluate_main(main_state, domain, step, b_columns);
+            }
 
             // record the result in the evaluation table
             fragment.update_row(i, &evaluations);
@@ -188,7 +280,8 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
     /// Evaluates constraints for a single fragment of the evaluation table.
     ///
     /// This evaluates constraints only over all segments of the execution trace (i.e. main segment
-    /// and all auxiliary segments).
+    /// and all auxiliary segments). Similarly to [Self::evaluate_fragment_main()], no memory is
+    /// allocated per step.
     fn evaluate_fragment_full(
         &self,
         trace: &TraceLde<E>,
@@ -196,51 +289,69 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
         fragment: &mut EvaluationTableFragment<E>,
     ) {
         // initialize buffers to hold trace values and evaluation results at each step
-        let mut main_frame = EvaluationFrame::new(trace.main_trace_width());
-        let mut aux_frame = EvaluationFrame::new(trace.aux_trace_width());
+        let frame_offsets = self.air.context().frame_offsets();
+        let mut main_frame = EvaluationFrame::with_offsets(trace.main_trace_width(), frame_offsets);
+        let mut aux_frame = EvaluationFrame::with_offsets(trace.aux_trace_width(), frame_offsets);
         let mut tm_evaluations = vec![E::BaseField::ZERO; self.num_main_transition_constraints()];
         let mut ta_evaluations = vec![E::ZERO; self.num_aux_transition_constraints()];
         let mut evaluations = vec![E::ZERO; fragment.num_columns()];
+        let num_t_columns = self.transition_constraints[0].num_divisors();
+        let num_point_columns = evaluations.len() / self.transition_constraints.len();
 
         // this will be used to convert steps in constraint evaluation domain to steps in
         // LDE domain
-        let lde_shift = domain.ce_to_lde_blowup().trailing_zeros();
+        let ce_to_lde_blowup = domain.ce_to_lde_blowup();
 
         for i in 0..fragment.num_rows() {
             let step = i + fragment.offset();
+            let lde_step = CePos(step).to_lde_pos(ce_to_lde_blowup);
 
             // read both the main and the auxiliary evaluation frames from the trace
-            trace.read_main_trace_frame_into(step << lde_shift, &mut main_frame);
-            trace.read_aux_trace_frame_into(step << lde_shift, &mut aux_frame);
-
-            // evaluate transition constraints and save the merged result the first slot of the
-            // evaluations buffer; we evaluate and compose constraints in the same function, we
-            // can just add up the results of evaluating main and auxiliary constraints.
-            evaluations[0] =
-                self.evaluate_main_transition(&main_frame, domain, step, &mut tm_evaluations);
-            evaluations[0] += self.evaluate_aux_transition(
-                &main_frame,
-                &aux_frame,
-                domain,
-                step,
-                &mut ta_evaluations,
-            );
+            trace.read_main_trace_frame_into(lde_step, &mut main_frame);
+            trace.read_aux_trace_frame_into(lde_step, &mut aux_frame);
+
+            // evaluate transition constraints (unless they have been evaluated already); these
+            // evaluations are shared by all out-of-domain points
+            let main_evaluations = match self.main_transitions.as_ref() {
+                Some(main_transitions) => main_transitions.get_row(step),
+                None => {
+                    self.evaluate_main_transition(&main_frame, step, &mut tm_evaluations);
+                    &tm_evaluations
+                }
+            };
+            let aux_evaluations = match self.aux_transitions.as_ref() {
+                Some(aux_transitions) => aux_transitions.get_row(step),
+                None => {
+                    self.evaluate_aux_transition(
+                        &main_frame,
+                        &aux_frame,
+                        step,
+                        &mut ta_evaluations,
+                    );
+                    &ta_evaluations
+                }
+            };
 
             // when in debug mode, save transition constraint evaluations
             #[cfg(debug_assertions)]
-            fragment.update_transition_evaluations(i, &tm_evaluations, &ta_evaluations);
+            fragment.update_transition_evaluations(i, main_evaluations, aux_evaluations);
 
-            // evaluate boundary constraints; the results go into remaining slots of the
-            // evaluations buffer
+            // for every out-of-domain point, save the merged transition results into the first
+            // slots of the point's chunk of the evaluations buffer (one slot per transition
+            // constraint divisor); since constraints are merged per divisor, we can just add up
+            // the results of merging main and auxiliary constraints. boundary constraints are
+            // evaluated into the remaining slots of the chunk.
             let main_state = main_frame.current();
             let aux_state = aux_frame.current();
-            self.boundary_constraints.evaluate_all(
-                main_state,
-                aux_state,
-                domain,
-                step,
-                &mut evaluations[1..],
-            );
+            for (point_idx, point_evaluations) in
+                evaluations.chunks_mut(num_point_columns).enumerate()
+            {
+                let (t_columns, b_columns) = point_evaluations.split_at_mut(num_t_columns);
+                self.merge_main_transition(point_idx, main_evaluations, domain, step, t_columns);
+                self.merge_aux_transition(point_idx, aux_evaluations, domain, step, t_columns);
+                self.boundary_constraints[point_idx]
+                    .evaluate_all(main_state, aux_state, domain, step, b_columns);
+            }
 
             // record the result in the evaluation table
             fragment.update_row(i, &evaluations);
@@ -251,18 +362,14 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
     // --------------------------------------------------------------------------------------------
 
     /// Evaluates transition constraints of the main execution trace at the specified step of the
-    /// constraint evaluation domain.
-    ///
-    /// `x` is the corresponding domain value at the specified step. That is, x = s * g^step,
-    /// where g is the generator of the constraint evaluation domain, and s is the domain offset.
+    /// constraint evaluation domain and saves the results into `evaluations`.
     #[rustfmt::skip]
     fn evaluate_main_transition(
         &self,
         main_frame: &EvaluationFrame<E::BaseField>,
-        domain: &StarkDomain<A::BaseField>,
         step: usize,
         evaluations: &mut [E::BaseField],
-    ) -> E {
+    ) {
         // TODO: use a more efficient way to zero out memory
         evaluations.fill(E::BaseField::ZERO);
 
@@ -272,30 +379,46 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
         // evaluate transition constraints over the main segment of the execution trace and save
         // the results into evaluations buffer
         self.air.evaluate_transition(main_frame, periodic_values, evaluations);
+    }
 
-        // merge transition constraint evaluations into a single value and return it;
-        // we can do this here because all transition constraints have the same divisor.
-        self.transition_constraints.main_constraints().iter().fold(E::ZERO, |result, group| {
+    /// Merges evaluations of transition constraints of the main execution trace at the specified
+    /// step of the constraint evaluation domain using composition coefficients of the specified
+    /// out-of-domain point.
+    ///
+    /// The merged evaluations are written into `result` separately for each transition
+    /// constraint divisor, in the order of [TransitionConstraints::get_divisors()]; previous
+    /// values in `result` are overwritten.
+    fn merge_main_transition(
+        &self,
+        point_idx: usize,
+        evaluations: &[E::BaseField],
+        domain: &StarkDomain<A::BaseField>,
+        step: usize,
+        result: &mut [E],
+    ) {
+        // merge transition constraint evaluations into a single value per divisor; we can do
+        // this here because all constraints in the same group have the same divisor.
+        result.fill(E::ZERO);
+        for group in self.transition_constraints[point_idx]
+            .main_constraints()
+            .iter()
+        {
             let (power, offset_exp) = (group.degree_adjustment(), group.domain_offset_exp());
             let xp = domain.get_ce_x_power_at(step, power, offset_exp);
-            result + group.merge_evaluations(evaluations, xp)
-        })
+            result[group.divisor_idx()] += group.merge_evaluations(evaluations, xp);
+        }
     }
 
-    /// Evaluates all transition constraints (i.e., for main and auxiliary trace segments) at the
-    /// specified step of the constraint evaluation domain.
-    ///
-    /// `x` is the corresponding domain value at the specified step. That is, x = s * g^step,
-    /// where g is the generator of the constraint evaluation domain, and s is the domain offset.
+    /// Evaluates transition constraints of auxiliary trace segments at the specified step of the
+    /// constraint evaluation domain and saves the results into `evaluations`.
     #[rustfmt::skip]
     fn evaluate_aux_transition(
         &self,
         main_frame: &EvaluationFrame<E::BaseField>,
         aux_frame: &EvaluationFrame<E>,
-        domain: &StarkDomain<A::BaseField>,
         step: usize,
         evaluations: &mut [E],
-    ) -> E {
+    ) {
         // TODO: use a more efficient way to zero out memory
         evaluations.fill(E::ZERO);
 
@@ -311,14 +434,34 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
             &self.aux_rand_elements,
             evaluations,
         );
+    }
 
-        // merge transition constraint evaluations into a single value and return it;
-        // we can do this here because all transition constraints have the same divisor.
-        self.transition_constraints.aux_constraints().iter().fold(E::ZERO, |result, group| {
+    /// Merges evaluations of transition constraints of auxiliary trace segments at the specified
+    /// step of the constraint evaluation domain using composition coefficients of the specified
+    /// out-of-domain point.
+    ///
+    /// Similarly to [Self::merge_main_transition()], merged evaluations are computed separately
+    /// for each transition constraint divisor; the results are added to the values already in
+    /// `result`.
+    fn merge_aux_transition(
+        &self,
+        point_idx: usize,
+        evaluations: &[E],
+        domain: &StarkDomain<A::BaseField>,
+        step: usize,
+        result: &mut [E],
+    ) {
+        // merge transition constraint evaluations into a single value per divisor; we can do
+        // this here because all constraints in the same group have the same divisor.
+        for group in self.transition_constraints[point_idx]
+            .aux_constraints()
+            .iter()
+        {
             let (power, offset_exp) = (group.degree_adjustment(), group.domain_offset_exp());
             let xp = domain.get_ce_x_power_at(step, power, offset_exp);
-            result + group.merge_evaluations::<E::BaseField, E>(evaluations, xp)
-        })
+            let evaluation = group.merge_evaluations::<E::BaseField, E>(evaluations, xp);
+            result[group.divisor_idx()] += evaluation;
+        }
     }
 
     // ACCESSORS
@@ -327,11 +470,77 @@ impl<'a, A: Air, E: FieldElement<BaseField = A::BaseField>> ConstraintEvaluator<
     /// Returns the number of transition constraints applied against the main segment of the
     /// execution trace.
     fn num_main_transition_constraints(&self) -> usize {
-        self.transition_constraints.num_main_constraints()
+        self.transition_constraints[0].num_main_constraints()
     }
 
     /// Returns the number of transition constraints applied against all auxiliary trace segments.
     fn num_aux_transition_constraints(&self) -> usize {
-        self.transition_constraints.num_aux_constraints()
+        self.transition_constraints[0].num_aux_constraints()
+    }
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(all(test, feature = "f128", feature = "std"))]
+mod tests {
+    use super::ConstraintEvaluator;
+    use crate::{
+        constraints::PeriodicValueTable,
+        tests::{alloc_counter::count_allocations, MockAir},
+        trace::TraceLde,
+        Matrix, StarkDomain,
+    };
+    use air::{Air, Assertion, AuxTraceRandElements};
+    use crypto::{hashers::Blake3_256, DefaultRandomCoin};
+    use math::fields::f128::BaseElement;
+    use rand_utils::rand_vector;
+    use utils::collections::Vec;
+
+    #[test]
+    fn evaluate_fragment_no_per_step_allocations() {
+        // the number of allocations made while evaluating a fragment must be the same regardless
+        // of the number of steps in the fragment
+        let num_allocations = count_fragment_allocations(16);
+        assert_eq!(num_allocations, count_fragment_allocations(64));
+        assert_eq!(num_allocations, count_fragment_allocations(256));
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of allocations made while evaluating constraints for a trace of the
+    /// specified length in a single fragment.
+    fn count_fragment_allocations(trace_length: usize) -> usize {
+        let assertions = vec![Assertion::sequence(1, 0, 8, rand_vector(trace_length / 8))];
+        let air = MockAir::with_assertions(assertions, trace_length);
+        let domain = StarkDomain::new(&air);
+
+        let columns = (0..air.trace_info().width())
+            .map(|_| rand_vector::<BaseElement>(trace_length))
+            .collect::<Vec<_>>();
+        let lde = Matrix::new(columns)
+            .interpolate_columns()
+            .evaluate_columns_over(&domain);
+        let trace = TraceLde::<BaseElement>::new(lde, domain.trace_to_lde_blowup());
+
+        let mut coin =
+            DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+        let coefficients = air
+            .get_constraint_composition_coefficients(&mut coin)
+            .unwrap();
+        let evaluator = ConstraintEvaluator::new(
+            &air,
+            AuxTraceRandElements::new(),
+            coefficients,
+            PeriodicValueTable::new(&air),
+        );
+
+        let mut table = evaluator.build_evaluation_table(&domain);
+        let mut fragments = table.fragments(1);
+        let (_, num_allocations) = count_allocations(|| {
+            evaluator.evaluate_fragment_main(&trace, &domain, &mut fragments[0])
+        });
+        num_allocations
     }
 }
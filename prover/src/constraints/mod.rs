@@ -3,13 +3,16 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{ConstraintDivisor, Matrix, ProverError, StarkDomain};
+use super::{ConstraintDivisor, Matrix, ProverConfig, ProverError, StarkDomain};
 
 mod boundary;
 use boundary::BoundaryConstraints;
 
 mod periodic_table;
-use periodic_table::PeriodicValueTable;
+pub use periodic_table::PeriodicValueTable;
+
+mod transition_table;
+pub use transition_table::{AuxTransitionTable, MainTransitionTable};
 
 mod evaluator;
 pub use evaluator::ConstraintEvaluator;
@@ -94,7 +94,7 @@ impl<B: StarkField> PeriodicValueTable<B> {
 // TESTS
 // ================================================================================================
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 mod tests {
     use crate::tests::MockAir;
     use air::Air;
@@ -0,0 +1,209 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{Matrix, PeriodicValueTable, ProverConfig, StarkDomain};
+use crate::trace::{read_frame_columns_into, read_frame_into, TraceLde};
+use air::{Air, AuxTraceRandElements, CePos, EvaluationFrame};
+use math::{FieldElement, StarkField};
+use utils::collections::Vec;
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
+
+// MAIN TRANSITION TABLE
+// ================================================================================================
+/// Evaluations of transition constraints placed against the main trace segment over the
+/// constraint evaluation domain.
+///
+/// The evaluations are recorded before they are merged using constraint composition
+/// coefficients, and thus, the table can be built before the coefficients are drawn (i.e., while
+/// the commitment to the main trace segment is still being computed). Evaluations for the same
+/// step are adjacent to each other.
+pub struct MainTransitionTable<B: StarkField> {
+    evaluations: Vec<B>,
+    num_constraints: usize,
+}
+
+impl<B: StarkField> MainTransitionTable<B> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Evaluates main transition constraints of the specified AIR against the extended main trace
+    /// segment at every step of the constraint evaluation domain.
+    ///
+    /// The domain is split into chunks sized according to the specified `config`; when
+    /// `concurrent` feature is enabled, chunks are evaluated in multiple threads.
+    pub fn new<A: Air<BaseField = B>>(
+        air: &A,
+        main_trace_lde: &Matrix<B>,
+        domain: &StarkDomain<B>,
+        periodic_values: &PeriodicValueTable<B>,
+        config: &ProverConfig,
+    ) -> Self {
+        assert_eq!(
+            main_trace_lde.num_rows(),
+            domain.lde_domain_size(),
+            "extended trace length is not consistent with evaluation domain"
+        );
+
+        let num_constraints = air.context().num_main_transition_constraints();
+        let mut evaluations = vec![B::ZERO; domain.ce_domain_size() * num_constraints];
+        if num_constraints == 0 {
+            return MainTransitionTable {
+                evaluations,
+                num_constraints,
+            };
+        }
+
+        // size chunks so that evaluation frames and evaluations touched while evaluating a chunk
+        // fit into cache
+        let frame_offsets = air.context().frame_offsets();
+        let row_size =
+            (frame_offsets.len() * main_trace_lde.num_cols() + num_constraints) * B::ELEMENT_BYTES;
+        let chunk_size = config.get_chunk_size(domain.ce_domain_size(), row_size);
+        let ce_to_lde_blowup = domain.ce_to_lde_blowup();
+        let trace_to_lde_blowup = domain.trace_to_lde_blowup();
+
+        #[cfg(not(feature = "concurrent"))]
+        let chunks = evaluations.chunks_mut(chunk_size * num_constraints);
+        #[cfg(feature = "concurrent")]
+        let chunks = evaluations.par_chunks_mut(chunk_size * num_constraints);
+
+        chunks.enumerate().for_each(|(i, chunk)| {
+            let mut frame = EvaluationFrame::with_offsets(main_trace_lde.num_cols(), frame_offsets);
+            for (j, result) in chunk.chunks_exact_mut(num_constraints).enumerate() {
+                let step = i * chunk_size + j;
+                let lde_step = CePos(step).to_lde_pos(ce_to_lde_blowup);
+                read_frame_into(main_trace_lde, trace_to_lde_blowup, lde_step, &mut frame);
+                air.evaluate_transition(&frame, periodic_values.get_row(step), result);
+            }
+        });
+
+        MainTransitionTable {
+            evaluations,
+            num_constraints,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns evaluations of all main transition constraints at the specified step of the
+    /// constraint evaluation domain.
+    pub fn get_row(&self, ce_step: usize) -> &[B] {
+        let start = ce_step * self.num_constraints;
+        &self.evaluations[start..start + self.num_constraints]
+    }
+}
+
+// AUXILIARY TRANSITION TABLE
+// ================================================================================================
+/// Evaluations of transition constraints placed against auxiliary trace segments over the
+/// constraint evaluation domain.
+///
+/// Similarly to [MainTransitionTable], the evaluations are recorded before they are merged using
+/// constraint composition coefficients. Thus, the table can be built while the commitment to the
+/// last auxiliary trace segment is still being computed (i.e., after all random elements needed
+/// to build auxiliary segments have been drawn). Evaluations for the same step are adjacent to
+/// each other.
+pub struct AuxTransitionTable<E: FieldElement> {
+    evaluations: Vec<E>,
+    num_constraints: usize,
+}
+
+impl<E: FieldElement> AuxTransitionTable<E> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Evaluates auxiliary transition constraints of the specified AIR at every step of the
+    /// constraint evaluation domain.
+    ///
+    /// Evaluation frames are read from all segments of the provided `trace_lde`, followed by the
+    /// columns of `aux_segment_lde`; the latter is the last auxiliary segment of the trace which
+    /// has not been added to `trace_lde` yet. `aux_rand_elements` must contain random elements for
+    /// all auxiliary segments, including the last one.
+    ///
+    /// The domain is split into chunks sized according to the specified `config`; when
+    /// `concurrent` feature is enabled, chunks are evaluated in multiple threads.
+    pub fn new<A: Air<BaseField = E::BaseField>>(
+        air: &A,
+        trace_lde: &TraceLde<E>,
+        aux_segment_lde: &Matrix<E>,
+        domain: &StarkDomain<E::BaseField>,
+        periodic_values: &PeriodicValueTable<E::BaseField>,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        config: &ProverConfig,
+    ) -> Self {
+        assert_eq!(
+            aux_segment_lde.num_rows(),
+            domain.lde_domain_size(),
+            "extended trace length is not consistent with evaluation domain"
+        );
+
+        let num_constraints = air.context().num_aux_transition_constraints();
+        let mut evaluations = vec![E::ZERO; domain.ce_domain_size() * num_constraints];
+        if num_constraints == 0 {
+            return AuxTransitionTable {
+                evaluations,
+                num_constraints,
+            };
+        }
+
+        // size chunks so that evaluation frames and evaluations touched while evaluating a chunk
+        // fit into cache
+        let frame_offsets = air.context().frame_offsets();
+        let main_width = trace_lde.main_trace_width();
+        let aux_width = trace_lde.aux_trace_width() + aux_segment_lde.num_cols();
+        let row_size = frame_offsets.len()
+            * (main_width * E::BaseField::ELEMENT_BYTES + aux_width * E::ELEMENT_BYTES)
+            + num_constraints * E::ELEMENT_BYTES;
+        let chunk_size = config.get_chunk_size(domain.ce_domain_size(), row_size);
+        let ce_to_lde_blowup = domain.ce_to_lde_blowup();
+        let trace_to_lde_blowup = domain.trace_to_lde_blowup();
+
+        #[cfg(not(feature = "concurrent"))]
+        let chunks = evaluations.chunks_mut(chunk_size * num_constraints);
+        #[cfg(feature = "concurrent")]
+        let chunks = evaluations.par_chunks_mut(chunk_size * num_constraints);
+
+        chunks.enumerate().for_each(|(i, chunk)| {
+            let mut main_frame = EvaluationFrame::with_offsets(main_width, frame_offsets);
+            let mut aux_frame = EvaluationFrame::with_offsets(aux_width, frame_offsets);
+            for (j, result) in chunk.chunks_exact_mut(num_constraints).enumerate() {
+                let step = i * chunk_size + j;
+                let lde_step = CePos(step).to_lde_pos(ce_to_lde_blowup);
+                trace_lde.read_main_trace_frame_into(lde_step, &mut main_frame);
+                trace_lde.read_aux_trace_frame_into(lde_step, &mut aux_frame);
+                read_frame_columns_into(
+                    aux_segment_lde,
+                    trace_to_lde_blowup,
+                    lde_step,
+                    trace_lde.aux_trace_width(),
+                    &mut aux_frame,
+                );
+                air.evaluate_aux_transition(
+                    &main_frame,
+                    &aux_frame,
+                    periodic_values.get_row(step),
+                    aux_rand_elements,
+                    result,
+                );
+            }
+        });
+
+        AuxTransitionTable {
+            evaluations,
+            num_constraints,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns evaluations of all auxiliary transition constraints at the specified step of the
+    /// constraint evaluation domain.
+    pub fn get_row(&self, ce_step: usize) -> &[E] {
+        let start = ce_step * self.num_constraints;
+        &self.evaluations[start..start + self.num_constraints]
+    }
+}
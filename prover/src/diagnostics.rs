@@ -0,0 +1,557 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Diagnostics for debugging constraint evaluation and composition.
+//!
+//! The only diagnostic currently available is a composition consistency check: the committed
+//! composition polynomial is evaluated over a coset of the trace domain and is compared against
+//! the combination of constraints recomputed directly from trace polynomials at every point of
+//! the coset (in the same way as the verifier does at the out-of-domain point). When the two
+//! disagree, the check reports the first mismatching point together with the constraint which
+//! explains the difference.
+
+use crate::{constraints::CompositionPoly, trace::TracePolyTable};
+use air::{
+    Air, AuxTraceRandElements, CoefficientPairs, ConstraintCompositionCoefficients, EvaluationFrame,
+};
+use core::fmt;
+use math::{fft, log2, polynom, FieldElement, StarkField};
+use utils::collections::Vec;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Name of the environment variable which enables the composition consistency check during
+/// proof generation in debug builds.
+pub const CHECK_COMPOSITION_ENV_VAR: &str = "WINTER_CHECK_COMPOSITION";
+
+/// Maximum number of coset points used to attribute a mismatch to a single constraint.
+const MAX_ATTRIBUTION_POINTS: usize = 8;
+
+// COMPOSITION CONSISTENCY CHECK
+// ================================================================================================
+
+/// Returns true if the composition consistency check was requested via
+/// [CHECK_COMPOSITION_ENV_VAR] environment variable.
+pub fn is_composition_check_enabled() -> bool {
+    std::env::var_os(CHECK_COMPOSITION_ENV_VAR).is_some()
+}
+
+/// Makes sure the composition polynomial for the out-of-domain point with the specified index is
+/// exactly the combination of constraints of the specified AIR computed with the specified
+/// coefficients.
+///
+/// The combination is recomputed directly from trace polynomials at every point `offset * g^i`
+/// of the coset of the trace domain shifted by the domain offset, and is compared against the
+/// evaluation of the composition polynomial at the same point. The divisors of all constraints
+/// are non-zero over this coset, and thus, every contribution can be computed exactly.
+///
+/// # Errors
+/// Returns an error describing the first point `i` at which the two sides differ; if the
+/// difference can be explained by contributions of a single constraint (e.g., because the
+/// constraint was composed with a different coefficient), the error also names this constraint.
+pub fn check_composition_consistency<A, E>(
+    air: &A,
+    trace_polys: &TracePolyTable<E>,
+    composition_poly: &CompositionPoly<E>,
+    point_idx: usize,
+    coefficients: &ConstraintCompositionCoefficients<E>,
+    aux_rand_elements: &AuxTraceRandElements<E>,
+) -> Result<(), CompositionMismatch<E>>
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+{
+    let points = CosetPoints::new(air, trace_polys, aux_rand_elements);
+    let actual = evaluate_composition_poly(composition_poly, point_idx, air.domain_offset());
+    let expected = points.compose(air, coefficients, aux_rand_elements, 0..points.len());
+
+    let row = match (0..points.len()).find(|&i| actual[i] != expected[i]) {
+        Some(row) => row,
+        None => return Ok(()),
+    };
+
+    // the difference between composed and recomputed values is linear in the coefficients; if
+    // it is a multiple of the contribution of a single coefficient at all sampled points, this
+    // coefficient (and thus, the constraint it belongs to) is the source of the mismatch
+    let samples = row..points.len().min(row + MAX_ATTRIBUTION_POINTS);
+    let diffs = samples
+        .clone()
+        .map(|i| actual[i] - expected[i])
+        .collect::<Vec<_>>();
+    let constraint = ConstraintSource::enumerate(coefficients).find(|&(source, is_adjusted)| {
+        let basis = source.basis_coefficients(coefficients, is_adjusted);
+        let contributions = points.compose(air, &basis, aux_rand_elements, samples.clone());
+        if contributions[0] == E::ZERO {
+            return false;
+        }
+        let ratio = diffs[0] / contributions[0];
+        diffs
+            .iter()
+            .zip(contributions.iter())
+            .all(|(&diff, &contribution)| diff == ratio * contribution)
+    });
+
+    Err(CompositionMismatch {
+        row,
+        expected: expected[row],
+        actual: actual[row],
+        constraint: constraint.map(|(source, _)| source),
+    })
+}
+
+// COMPOSITION MISMATCH
+// ================================================================================================
+
+/// Describes a point of the trace domain coset at which the composition polynomial is not equal
+/// to the combination of constraints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositionMismatch<E: FieldElement> {
+    /// Index `i` of the point `offset * g^i` of the coset.
+    pub row: usize,
+    /// Combination of constraints recomputed from trace polynomials at the point.
+    pub expected: E,
+    /// Evaluation of the composition polynomial at the point.
+    pub actual: E,
+    /// Constraint which explains the difference, if the difference can be attributed to a
+    /// single constraint; if contributions of several constraints are proportional to each other
+    /// (e.g., for two transition constraints of degree 1), the first of them is reported.
+    pub constraint: Option<ConstraintSource>,
+}
+
+impl<E: FieldElement> fmt::Display for CompositionMismatch<E> {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "composition polynomial does not match the combination of constraints at point {} of the trace domain coset; expected {}, but was {}", self.row, self.expected, self.actual)?;
+        match self.constraint {
+            Some(constraint) => write!(f, "; the difference is explained by {constraint}"),
+            None => write!(f, "; the difference could not be attributed to a single constraint"),
+        }
+    }
+}
+
+/// Identifies a constraint by the index of its composition coefficients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintSource {
+    /// Transition constraint at the specified index.
+    Transition(usize),
+    /// Boundary constraint composed with the boundary coefficients at the specified index.
+    Boundary(usize),
+    /// Boundary expression at the specified index.
+    BoundaryExpr(usize),
+}
+
+impl ConstraintSource {
+    /// Returns all constraints described by the specified coefficients; every constraint is
+    /// listed twice: once for its base coefficient, and once for the coefficient of its degree
+    /// adjustment term.
+    fn enumerate<E: FieldElement>(
+        coefficients: &ConstraintCompositionCoefficients<E>,
+    ) -> impl Iterator<Item = (Self, bool)> {
+        let transition = (0..coefficients.transition.len()).map(Self::Transition);
+        let boundary = (0..coefficients.boundary.len()).map(Self::Boundary);
+        let exprs = (0..coefficients.boundary_exprs.len()).map(Self::BoundaryExpr);
+        transition
+            .chain(boundary)
+            .chain(exprs)
+            .flat_map(|source| [(source, false), (source, true)])
+    }
+
+    /// Returns coefficients which are all zeros except for one of the coefficients of this
+    /// constraint which is set to one.
+    fn basis_coefficients<E: FieldElement>(
+        &self,
+        coefficients: &ConstraintCompositionCoefficients<E>,
+        is_adjusted: bool,
+    ) -> ConstraintCompositionCoefficients<E> {
+        let mut result = ConstraintCompositionCoefficients {
+            transition: CoefficientPairs::from_elem(
+                (E::ZERO, E::ZERO),
+                coefficients.transition.len(),
+            ),
+            boundary: CoefficientPairs::from_elem((E::ZERO, E::ZERO), coefficients.boundary.len()),
+            boundary_exprs: CoefficientPairs::from_elem(
+                (E::ZERO, E::ZERO),
+                coefficients.boundary_exprs.len(),
+            ),
+        };
+        let pair = match *self {
+            Self::Transition(i) => &mut result.transition[i],
+            Self::Boundary(i) => &mut result.boundary[i],
+            Self::BoundaryExpr(i) => &mut result.boundary_exprs[i],
+        };
+        if is_adjusted {
+            pair.1 = E::ONE;
+        } else {
+            pair.0 = E::ONE;
+        }
+        result
+    }
+}
+
+impl fmt::Display for ConstraintSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transition(i) => write!(f, "transition constraint {i}"),
+            Self::Boundary(i) => write!(f, "boundary constraint {i}"),
+            Self::BoundaryExpr(i) => write!(f, "boundary expression {i}"),
+        }
+    }
+}
+
+// COSET POINTS
+// ================================================================================================
+
+/// Evaluation frames and transition constraint evaluations at all points of the trace domain
+/// coset; these do not depend on composition coefficients, and thus, are computed only once.
+struct CosetPoints<E: FieldElement> {
+    x: Vec<E>,
+    main_frames: Vec<EvaluationFrame<E>>,
+    aux_frames: Vec<Option<EvaluationFrame<E>>>,
+    main_evaluations: Vec<Vec<E>>,
+    aux_evaluations: Vec<Vec<E>>,
+}
+
+impl<E: FieldElement> CosetPoints<E> {
+    fn new<A: Air<BaseField = E::BaseField>>(
+        air: &A,
+        trace_polys: &TracePolyTable<E>,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Self {
+        let n = air.trace_length();
+        let offset = air.domain_offset();
+        let twiddles = fft::get_twiddles::<E::BaseField>(n);
+        let g = E::BaseField::get_root_of_unity(log2(n));
+        let x = (0..n)
+            .map(|i| E::from(offset * g.exp_vartime((i as u64).into())))
+            .collect::<Vec<_>>();
+
+        // evaluate trace polynomials over the coset; since g * x_i = x_{i + 1}, the row of the
+        // frame at offset k at point i is the current row of the frame at point i + k
+        let main_columns = trace_polys
+            .main_trace_polys()
+            .map(|poly| fft::evaluate_poly_with_offset(poly, &twiddles, offset, 1))
+            .collect::<Vec<_>>();
+        let aux_columns = trace_polys
+            .aux_trace_polys()
+            .map(|poly| fft::evaluate_poly_with_offset(poly, &twiddles, offset, 1))
+            .collect::<Vec<_>>();
+        let read_row = |i: usize| -> (Vec<E>, Vec<E>) {
+            let main = main_columns.iter().map(|c| E::from(c[i % n])).collect();
+            let aux = aux_columns.iter().map(|c| c[i % n]).collect();
+            (main, aux)
+        };
+
+        // only the number of constraints in each segment is needed here
+        let num_constraints = air.context().num_transition_constraints();
+        let t_constraints =
+            air.get_transition_constraints(&vec![(E::ZERO, E::ZERO); num_constraints]);
+        let periodic_polys = air.get_periodic_column_polys();
+
+        let mut result = CosetPoints {
+            main_frames: Vec::with_capacity(n),
+            aux_frames: Vec::with_capacity(n),
+            main_evaluations: Vec::with_capacity(n),
+            aux_evaluations: Vec::with_capacity(n),
+            x: Vec::new(),
+        };
+        for (i, &x) in x.iter().enumerate() {
+            let frame_offsets = air.context().frame_offsets();
+            let (main_rows, aux_rows): (Vec<_>, Vec<_>) = frame_offsets
+                .iter()
+                .map(|&offset| read_row(i + offset))
+                .unzip();
+            let main_frame = EvaluationFrame::from_offset_rows(frame_offsets, main_rows);
+            let aux_frame = if air.trace_layout().num_aux_segments() > 0 {
+                Some(EvaluationFrame::from_offset_rows(frame_offsets, aux_rows))
+            } else {
+                None
+            };
+
+            let periodic_values = periodic_polys
+                .iter()
+                .map(|poly| {
+                    let num_cycles = n / poly.len();
+                    polynom::eval(poly, x.exp_vartime((num_cycles as u32).into()))
+                })
+                .collect::<Vec<_>>();
+
+            let mut main_evaluations = E::zeroed_vector(t_constraints.num_main_constraints());
+            air.evaluate_transition(&main_frame, &periodic_values, &mut main_evaluations);
+            let mut aux_evaluations = E::zeroed_vector(t_constraints.num_aux_constraints());
+            if let Some(aux_frame) = &aux_frame {
+                air.evaluate_aux_transition(
+                    &main_frame,
+                    aux_frame,
+                    &periodic_values,
+                    aux_rand_elements,
+                    &mut aux_evaluations,
+                );
+            }
+
+            result.main_frames.push(main_frame);
+            result.aux_frames.push(aux_frame);
+            result.main_evaluations.push(main_evaluations);
+            result.aux_evaluations.push(aux_evaluations);
+        }
+        result.x = x;
+        result
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Returns the combination of all constraints computed with the specified coefficients at
+    /// each of the specified points.
+    fn compose<A: Air<BaseField = E::BaseField>>(
+        &self,
+        air: &A,
+        coefficients: &ConstraintCompositionCoefficients<E>,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        points: core::ops::Range<usize>,
+    ) -> Vec<E> {
+        let t_constraints = air.get_transition_constraints(&coefficients.transition);
+        let b_constraints = air.get_boundary_constraints(aux_rand_elements, &coefficients.boundary);
+        let e_constraints = air.get_boundary_expr_constraints::<E, E>(&coefficients.boundary_exprs);
+
+        points
+            .map(|i| {
+                let x = self.x[i];
+                let mut result = t_constraints.combine_evaluations::<E>(
+                    &self.main_evaluations[i],
+                    &self.aux_evaluations[i],
+                    x,
+                );
+
+                for group in b_constraints.main_constraints().iter() {
+                    let xp = x.exp_vartime(group.degree_adjustment().into());
+                    result += group.evaluate_at(self.main_frames[i].current(), x, xp);
+                }
+                if let Some(aux_frame) = &self.aux_frames[i] {
+                    for group in b_constraints.aux_constraints().iter() {
+                        let xp = x.exp_vartime(group.degree_adjustment().into());
+                        result += group.evaluate_at(aux_frame.current(), x, xp);
+                    }
+                }
+
+                for constraint in e_constraints.iter() {
+                    let xp = x.exp_vartime(constraint.degree_adjustment().into());
+                    let numerator = constraint.evaluate_at(self.main_frames[i].current(), xp);
+                    result += numerator / constraint.divisor().evaluate_at(x);
+                }
+
+                result
+            })
+            .collect()
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Evaluates the composition polynomial for the out-of-domain point with the specified index at
+/// all points of the trace domain coset.
+///
+/// The composition polynomial is $H(x) = \sum_i x^i \cdot H_i(x^m)$, where $H_i$ are its $m$
+/// columns for the specified point. Since $x^n$ is equal to $offset^n$ for all points of the coset, $H$ is first reduced
+/// to a polynomial of degree smaller than $n$ which agrees with $H$ over the coset, and the
+/// reduced polynomial is then evaluated via FFT.
+fn evaluate_composition_poly<E: FieldElement>(
+    composition_poly: &CompositionPoly<E>,
+    point_idx: usize,
+    offset: E::BaseField,
+) -> Vec<E> {
+    let n = composition_poly.column_len();
+    let m = composition_poly.num_point_columns();
+    let first_column = point_idx * m;
+    let offset_n = E::from(offset.exp_vartime((n as u64).into()));
+
+    let mut reduced = E::zeroed_vector(n);
+    let mut scale = E::ONE;
+    for chunk in 0..m {
+        // coefficients j in [chunk * n, (chunk + 1) * n) are multiplied by (offset^n)^chunk
+        for (r, value) in reduced.iter_mut().enumerate() {
+            let j = chunk * n + r;
+            *value += composition_poly.get_column(first_column + j % m)[j / m] * scale;
+        }
+        scale *= offset_n;
+    }
+
+    let twiddles = fft::get_twiddles::<E::BaseField>(n);
+    fft::evaluate_poly_with_offset(&reduced, &twiddles, offset, 1)
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(all(test, feature = "f128"))]
+mod tests {
+    use super::{check_composition_consistency, ConstraintSource};
+    use crate::{
+        constraints::{CompositionPoly, ConstraintEvaluator, PeriodicValueTable},
+        tests::build_fib_trace,
+        trace::{TraceLde, TracePolyTable},
+        ProverConfig, StarkDomain, Trace,
+    };
+    use air::{
+        Air, AirContext, Assertion, AuxTraceRandElements, ConstraintCompositionCoefficients,
+        EvaluationFrame, FieldExtension, ProofOptions, TraceInfo, TransitionConstraintDegree,
+    };
+    use crypto::{hashers::Blake3_256, DefaultRandomCoin};
+    use math::{fields::f128::BaseElement, FieldElement};
+    use utils::collections::Vec;
+
+    #[test]
+    fn composition_consistency() {
+        let air = FibAir::new(TraceInfo::new(2, 16), (), options());
+        let trace_polys = build_trace_polys(&air);
+        let aux_rand_elements = AuxTraceRandElements::new();
+        let coefficients = draw_coefficients(&air);
+
+        let composition_poly = build_composition_poly(&air, &trace_polys, coefficients.clone());
+        assert!(check_composition_consistency(
+            &air,
+            &trace_polys,
+            &composition_poly,
+            0,
+            &coefficients,
+            &aux_rand_elements
+        )
+        .is_ok());
+
+        // composing the second transition constraint with a wrong coefficient must be attributed
+        // to this constraint
+        let mut wrong_coefficients = coefficients.clone();
+        wrong_coefficients.transition[1].0 += BaseElement::ONE;
+        let composition_poly = build_composition_poly(&air, &trace_polys, wrong_coefficients);
+        let mismatch = check_composition_consistency(
+            &air,
+            &trace_polys,
+            &composition_poly,
+            0,
+            &coefficients,
+            &aux_rand_elements,
+        )
+        .unwrap_err();
+        assert_eq!(0, mismatch.row);
+        assert_ne!(mismatch.expected, mismatch.actual);
+        assert_eq!(Some(ConstraintSource::Transition(1)), mismatch.constraint);
+
+        // the same holds for the degree adjustment coefficient of a boundary constraint
+        let mut wrong_coefficients = coefficients.clone();
+        wrong_coefficients.boundary[2].1 += BaseElement::ONE;
+        let composition_poly = build_composition_poly(&air, &trace_polys, wrong_coefficients);
+        let mismatch = check_composition_consistency(
+            &air,
+            &trace_polys,
+            &composition_poly,
+            0,
+            &coefficients,
+            &aux_rand_elements,
+        )
+        .unwrap_err();
+        assert_eq!(Some(ConstraintSource::Boundary(2)), mismatch.constraint);
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+
+    fn options() -> ProofOptions {
+        ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256)
+    }
+
+    fn build_trace_polys(air: &FibAir) -> TracePolyTable<BaseElement> {
+        let trace = build_fib_trace(air.trace_length() * 2);
+        TracePolyTable::new(trace.main_segment().interpolate_columns())
+    }
+
+    fn draw_coefficients(air: &FibAir) -> ConstraintCompositionCoefficients<BaseElement> {
+        let mut coin =
+            DefaultRandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+        air.get_constraint_composition_coefficients(&mut coin)
+            .unwrap()
+            .swap_remove(0)
+    }
+
+    fn build_composition_poly(
+        air: &FibAir,
+        trace_polys: &TracePolyTable<BaseElement>,
+        coefficients: ConstraintCompositionCoefficients<BaseElement>,
+    ) -> CompositionPoly<BaseElement> {
+        let domain = StarkDomain::new(air);
+        let lde = trace_polys
+            .main_trace_polys()
+            .map(|poly| poly.to_vec())
+            .collect::<Vec<_>>();
+        let lde = crate::Matrix::new(lde).evaluate_columns_over(&domain);
+        let trace_lde = TraceLde::new(lde, domain.trace_to_lde_blowup());
+
+        let evaluator = ConstraintEvaluator::new(
+            air,
+            AuxTraceRandElements::new(),
+            vec![coefficients],
+            PeriodicValueTable::new(air),
+        );
+        evaluator
+            .evaluate(&trace_lde, &domain, &ProverConfig::default())
+            .into_poly()
+            .unwrap()
+    }
+
+    // FIBONACCI AIR
+    // --------------------------------------------------------------------------------------------
+
+    /// Each row of the trace holds two consecutive pairs of Fibonacci numbers.
+    struct FibAir {
+        context: AirContext<BaseElement>,
+        result: BaseElement,
+    }
+
+    impl Air for FibAir {
+        type BaseField = BaseElement;
+        type PublicInputs = ();
+
+        fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+            let trace = build_fib_trace(trace_info.length() * 2);
+            let result = trace.get(1, trace_info.length() - 1);
+            let degrees = vec![
+                TransitionConstraintDegree::new(1),
+                TransitionConstraintDegree::new(2),
+            ];
+            FibAir {
+                context: AirContext::new(trace_info, degrees, 3, options),
+                result,
+            }
+        }
+
+        fn context(&self) -> &AirContext<Self::BaseField> {
+            &self.context
+        }
+
+        fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+            &self,
+            frame: &EvaluationFrame<E>,
+            _periodic_values: &[E],
+            result: &mut [E],
+        ) {
+            let current = frame.current();
+            let next = frame.next();
+            result[0] = next[0] - (current[0] + current[1]);
+            // the second constraint is of degree 2 so that the contributions of the two
+            // constraints are not proportional to each other
+            result[1] = (next[1] - (current[0] + current[1].double())) * current[0];
+        }
+
+        fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+            let last_step = self.trace_length() - 1;
+            vec![
+                Assertion::single(0, 0, BaseElement::ONE),
+                Assertion::single(1, 0, BaseElement::ONE),
+                Assertion::single(1, last_step, self.result),
+            ]
+        }
+    }
+}
@@ -15,9 +15,18 @@ pub struct StarkDomain<B: StarkField> {
     /// vector is half the length of the trace domain size.
     trace_twiddles: Vec<B>,
 
+    /// Inverse twiddles which can be used to interpolate trace polynomials. Length of this vector
+    /// is half the length of the trace domain size.
+    trace_inv_twiddles: Vec<B>,
+
     /// [g^i for i in (0..ce_domain_size)] where g is the constraint evaluation domain generator.
     ce_domain: Vec<B>,
 
+    /// Inverse twiddles which can be used to interpolate polynomials from their evaluations over
+    /// the constraint evaluation domain. Length of this vector is half the length of the
+    /// constraint evaluation domain size.
+    ce_inv_twiddles: Vec<B>,
+
     /// LDE domain size / constraint evaluation domain size
     ce_to_lde_blowup: usize,
 
@@ -37,20 +46,33 @@ impl<B: StarkField> StarkDomain<B> {
     /// Returns a new STARK domain initialized with the provided `context`.
     pub fn new<A: Air<BaseField = B>>(air: &A) -> Self {
         let trace_twiddles = fft::get_twiddles(air.trace_length());
+        let trace_inv_twiddles = fft::get_inv_twiddles(air.trace_length());
 
         // build constraint evaluation domain
         let domain_gen = B::get_root_of_unity(log2(air.ce_domain_size()));
         let ce_domain = get_power_series(domain_gen, air.ce_domain_size());
+        let ce_inv_twiddles = fft::get_inv_twiddles(air.ce_domain_size());
 
         StarkDomain {
             trace_twiddles,
+            trace_inv_twiddles,
             ce_domain,
+            ce_inv_twiddles,
             ce_to_lde_blowup: air.lde_domain_size() / air.ce_domain_size(),
             ce_domain_mod_mask: air.ce_domain_size() - 1,
             domain_offset: air.domain_offset(),
         }
     }
 
+    /// Returns true if this domain is the same as the domain which would be built for the
+    /// specified `air`, and thus, can be used to generate a proof against it.
+    pub fn is_compatible_with<A: Air<BaseField = B>>(&self, air: &A) -> bool {
+        self.trace_length() == air.trace_length()
+            && self.ce_domain_size() == air.ce_domain_size()
+            && self.lde_domain_size() == air.lde_domain_size()
+            && self.domain_offset == air.domain_offset()
+    }
+
     // EXECUTION TRACE
     // --------------------------------------------------------------------------------------------
 
@@ -64,6 +86,11 @@ impl<B: StarkField> StarkDomain<B> {
         &self.trace_twiddles
     }
 
+    /// Returns inverse twiddles which can be used to interpolate trace polynomials.
+    pub fn trace_inv_twiddles(&self) -> &[B] {
+        &self.trace_inv_twiddles
+    }
+
     /// Returns blowup factor from trace to constraint evaluation domain.
     pub fn trace_to_ce_blowup(&self) -> usize {
         self.ce_domain_size() / self.trace_length()
@@ -88,6 +115,12 @@ impl<B: StarkField> StarkDomain<B> {
         B::get_root_of_unity(log2(self.ce_domain_size()))
     }
 
+    /// Returns inverse twiddles which can be used to interpolate polynomials from their
+    /// evaluations over the constraint evaluation domain.
+    pub fn ce_inv_twiddles(&self) -> &[B] {
+        &self.ce_inv_twiddles
+    }
+
     /// Returns blowup factor from constraint evaluation to LDE domain.
     pub fn ce_to_lde_blowup(&self) -> usize {
         self.ce_to_lde_blowup
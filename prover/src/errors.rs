@@ -5,7 +5,11 @@
 
 //! Contains common error types for prover and verifier.
 
-use core::fmt;
+use crate::checkpoint::{CHECKPOINT_VERSION, TRANSCRIPT_STATE_VERSION};
+use air::AssertionError;
+use core::{fmt, ops::Range};
+use crypto::HashFunctionId;
+use utils::{string::String, DeserializationError};
 
 // PROVER ERROR
 // ================================================================================================
@@ -19,8 +23,64 @@ pub enum ProverError {
     /// table do not all have the same degree.
     MismatchedConstraintPolynomialDegree(usize, usize),
     /// This error occurs when the base field specified by the AIR does not support field extension
-    /// of degree specified by proof options.
+    /// of degree specified by proof options, or when the crate was compiled without the feature
+    /// enabling this extension.
     UnsupportedFieldExtension(usize),
+    /// This error occurs when the transcript profile specified by proof options cannot be used
+    /// with the hash function of the prover (e.g., when the arithmetic transcript profile is used
+    /// with a hash function which does not produce digests composed of field elements).
+    UnsupportedTranscriptProfile,
+    /// This error occurs when the grinding hash function specified by proof options cannot be
+    /// used with the base field of the prover (e.g., when an algebraic hash function over a
+    /// different field is selected), or when the crate was compiled without the feature enabling
+    /// this hash function.
+    UnsupportedGrindingHash(HashFunctionId),
+    /// This error occurs when the leaf encoding specified by proof options cannot be used for
+    /// rows of trace commitments with the hash function of the prover (e.g., when raw leaves are
+    /// requested for rows which are longer than a digest). The value is the number of bytes in
+    /// the widest row of trace commitments.
+    UnsupportedLeafEncoding(usize),
+    /// This error occurs when proof options request salted commitments, but the prover does not
+    /// provide a source of random bytes from which salts could be derived.
+    MissingSaltSource,
+    /// This error occurs when a proof is requested with a vector commitment scheme other than
+    /// Merkle trees, but the prover has a preprocessed trace segment or a main trace opener, both
+    /// of which are bound to Merkle trees. The value is the identifier of the requested scheme.
+    UnsupportedCommitmentScheme(u8),
+    /// This error occurs when a proof is requested with a random coin other than the default
+    /// one, but the prover has a preprocessed trace segment; proofs for such traces can be
+    /// verified only with the default coin. The value is the identifier of the requested coin.
+    UnsupportedRandomCoin(u8),
+    /// This error occurs when the number of trace polynomials provided to the prover does not
+    /// match the width of the main trace segment.
+    MismatchedTracePolynomials(usize, usize),
+    /// This error occurs when the degree of a trace polynomial provided to the prover is not
+    /// smaller than the trace length. The values are the index of the column and the degree of
+    /// its polynomial.
+    TracePolynomialDegreeTooHigh(usize, usize),
+    /// This error occurs when the preprocessed trace segment of the prover cannot be used with
+    /// the execution trace or the proof options (e.g., when the segment was built for traces of
+    /// a different length).
+    IncompatiblePreprocessedSegment(String),
+    /// This error occurs when an assertion specified by the AIR cannot be placed against the
+    /// execution trace (e.g., when it is placed against a step outside of the trace).
+    InvalidAssertion(AssertionError),
+    /// This error occurs when a checkpoint of a partially generated proof could not be saved, or
+    /// when proof generation could not be resumed from a checkpoint.
+    CheckpointFailed(CheckpointError),
+    /// This error occurs when trace validation is enabled in the prover config, and the execution
+    /// trace does not satisfy an assertion, a boundary expression, or a transition constraint of
+    /// the AIR.
+    InvalidTrace(TraceError),
+    /// This error occurs when the execution trace does not contain an output expected by the
+    /// caller of the prover (see [ExpectedOutputs](crate::ExpectedOutputs)); `actual` is None
+    /// when the output is placed against a column outside of the main trace segment.
+    UnexpectedOutput {
+        column: usize,
+        step: usize,
+        expected: String,
+        actual: Option<String>,
+    },
 }
 
 impl fmt::Display for ProverError {
@@ -36,6 +96,193 @@ impl fmt::Display for ProverError {
             Self::UnsupportedFieldExtension(degree) => {
                 write!(f, "field extension of degree {degree} is not supported for the specified base field")
             }
+            Self::UnsupportedTranscriptProfile => {
+                write!(f, "transcript profile is not supported for the specified hash function")
+            }
+            Self::UnsupportedGrindingHash(hash_fn) => {
+                write!(f, "grinding hash function {hash_fn} is not supported for the specified base field")
+            }
+            Self::UnsupportedLeafEncoding(row_bytes) => {
+                write!(f, "leaf encoding is not supported for trace rows of {row_bytes} bytes and the specified hash function")
+            }
+            Self::MissingSaltSource => {
+                write!(f, "salted commitments were requested, but no source of random salts is available")
+            }
+            Self::UnsupportedCommitmentScheme(scheme_id) => {
+                write!(f, "commitment scheme {scheme_id} cannot be used with a preprocessed trace segment or a main trace opener")
+            }
+            Self::UnsupportedRandomCoin(coin_id) => {
+                write!(f, "random coin {coin_id} cannot be used with a preprocessed trace segment")
+            }
+            Self::MismatchedTracePolynomials(expected, actual) => {
+                write!(f, "expected {expected} trace polynomials, but {actual} were provided")
+            }
+            Self::TracePolynomialDegreeTooHigh(column, degree) => {
+                write!(f, "polynomial of trace column {column} has degree {degree} which is not smaller than the trace length")
+            }
+            Self::IncompatiblePreprocessedSegment(msg) => {
+                write!(f, "preprocessed trace segment is incompatible with the execution trace: {msg}")
+            }
+            Self::InvalidAssertion(err) => {
+                write!(f, "assertion is not valid for the execution trace: {err}")
+            }
+            Self::CheckpointFailed(err) => {
+                write!(f, "proof checkpointing failed: {err}")
+            }
+            Self::InvalidTrace(err) => {
+                write!(f, "execution trace is not valid against the AIR: {err}")
+            }
+            Self::UnexpectedOutput { column, step, expected, actual: Some(actual) } => {
+                write!(f, "execution trace contains {actual} at main_trace({column}, {step}), but the expected output is {expected}")
+            }
+            Self::UnexpectedOutput { column, step, expected, actual: None } => {
+                write!(f, "expected output {expected} is placed at main_trace({column}, {step}) outside of the main trace segment")
+            }
+        }
+    }
+}
+
+// CHECKPOINT ERROR
+// ================================================================================================
+/// Represents an error returned when saving or resuming from a checkpoint of a partially
+/// generated proof.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// This error occurs when a checkpoint sink fails to save a checkpoint.
+    SinkFailed(String),
+    /// This error occurs when a checkpoint source fails to provide a checkpoint.
+    SourceFailed(String),
+    /// This error occurs when a checkpoint was created using an unsupported checkpoint format
+    /// version.
+    UnsupportedVersion(u8),
+    /// This error occurs when the transcript state in a checkpoint was serialized using an
+    /// unsupported transcript state format version.
+    UnsupportedTranscriptVersion(u8),
+    /// This error occurs when a checkpoint could not be deserialized.
+    MalformedCheckpoint(DeserializationError),
+    /// This error occurs when a checkpoint was created for a different computation context (i.e.,
+    /// different trace info or proof options) than the one of the resuming prover.
+    ContextMismatch,
+    /// This error occurs when a checkpoint was created for public inputs different from the ones
+    /// provided to the resuming prover.
+    PublicInputsMismatch,
+}
+
+impl fmt::Display for CheckpointError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SinkFailed(err) => {
+                write!(f, "failed to save checkpoint: {err}")
+            }
+            Self::SourceFailed(err) => {
+                write!(f, "failed to load checkpoint: {err}")
+            }
+            Self::UnsupportedVersion(version) => {
+                write!(f, "checkpoint format version {version} is not supported; expected version {CHECKPOINT_VERSION}")
+            }
+            Self::UnsupportedTranscriptVersion(version) => {
+                write!(f, "transcript state format version {version} is not supported; expected version {TRANSCRIPT_STATE_VERSION}")
+            }
+            Self::MalformedCheckpoint(err) => {
+                write!(f, "checkpoint could not be deserialized: {err}")
+            }
+            Self::ContextMismatch => {
+                write!(f, "checkpoint was created for a different computation context")
+            }
+            Self::PublicInputsMismatch => {
+                write!(f, "checkpoint was created for different public inputs")
+            }
+        }
+    }
+}
+
+// TRACE ERROR
+// ================================================================================================
+/// Represents an error returned while building an execution trace or validating it against an
+/// AIR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceError {
+    /// This error occurs when a closure filling an execution trace (or a fragment of it) panics;
+    /// `rows` specifies the range of rows of the execution trace which were being filled, and
+    /// `message` contains the panic payload (if it was a string).
+    FillPanic { rows: Range<usize>, message: String },
+    /// This error occurs when a fallible closure filling an execution trace returns an error;
+    /// `row` is the index of the row which was being computed.
+    FillFailed { row: usize, error: TraceBuildError },
+    /// This error occurs when a value in the execution trace does not match the value asserted
+    /// by the AIR; `column` is the index of the column in the main trace segment (or across all
+    /// auxiliary segments when `aux` is true), and `value` is the asserted value.
+    UnsatisfiedAssertion {
+        aux: bool,
+        column: usize,
+        step: usize,
+        value: String,
+    },
+    /// This error occurs when a row of the execution trace does not satisfy a boundary
+    /// expression of the AIR.
+    UnsatisfiedBoundaryExpr { index: usize, step: usize },
+    /// This error occurs when a transition constraint of the AIR does not evaluate to zero over
+    /// the evaluation frame starting at the specified step of the execution trace.
+    UnsatisfiedTransitionConstraint {
+        aux: bool,
+        index: usize,
+        step: usize,
+    },
+}
+
+impl fmt::Display for TraceError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FillPanic { rows, message } if rows.len() == 1 => {
+                write!(f, "trace fill closure panicked while computing row {}: {message}", rows.start)
+            }
+            Self::FillPanic { rows, message } => {
+                write!(f, "trace fill closure panicked while computing rows {}..{}: {message}", rows.start, rows.end)
+            }
+            Self::FillFailed { row, error } => {
+                write!(f, "trace fill closure failed while computing row {row}: {error}")
+            }
+            Self::UnsatisfiedAssertion { aux, column, step, value } => {
+                let segment = if *aux { "aux_trace" } else { "main_trace" };
+                write!(f, "trace does not satisfy assertion {segment}({column}, {step}) == {value}")
+            }
+            Self::UnsatisfiedBoundaryExpr { index, step } => {
+                write!(f, "trace does not satisfy boundary expression {index} at step {step}")
+            }
+            Self::UnsatisfiedTransitionConstraint { aux, index, step } => {
+                let segment = if *aux { "auxiliary" } else { "main" };
+                write!(f, "{segment} transition constraint {index} did not evaluate to ZERO at step {step}")
+            }
+        }
+    }
+}
+
+// TRACE BUILD ERROR
+// ================================================================================================
+/// Represents an error returned by a closure passed to
+/// [TraceTable::try_fill_with()](crate::TraceTable::try_fill_with).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceBuildError {
+    /// This error occurs when the state of a row cannot be computed from the state of the
+    /// previous row (e.g., because a value does not fit into the columns of the trace).
+    InvalidState(String),
+    /// This error occurs when the computation described by the trace ends in a state other than
+    /// the expected one.
+    UnexpectedOutput(String),
+}
+
+impl fmt::Display for TraceBuildError {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidState(reason) => {
+                write!(f, "invalid trace state: {reason}")
+            }
+            Self::UnexpectedOutput(reason) => {
+                write!(f, "unexpected computation output: {reason}")
+            }
         }
     }
 }
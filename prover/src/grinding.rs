@@ -0,0 +1,85 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Query seed grinding and clocks which bound the time spent on it.
+//!
+//! When proof options specify the [TimeBoxed](crate::GrindingStrategy::TimeBoxed) grinding
+//! strategy, the prover reads the clock returned by
+//! [Prover::grinding_clock()](crate::Prover::grinding_clock) when grinding starts, and then
+//! after every batch of nonces it tries, to determine whether the grinding deadline has passed.
+
+use air::PowPuzzle;
+use crypto::RandomCoin;
+
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
+
+// NONCE SEARCH
+// ================================================================================================
+
+/// Returns the smallest nonce which provides at least `grinding_factor` proof-of-work bits for
+/// the specified `puzzle`.
+///
+/// Nonces are tried in increasing order starting from 1. When `concurrent` feature is enabled,
+/// the search is performed in multiple threads, but the smallest suitable nonce is still
+/// returned, and thus, the result does not depend on the number of threads. Only nonces smaller
+/// than 2^64 are tried even for puzzles with wide nonces; for grinding factors of up to 32 bits,
+/// a suitable nonce is found among them with overwhelming probability.
+pub fn find_pow_nonce<C: RandomCoin>(puzzle: &PowPuzzle<C>, grinding_factor: u32) -> u128 {
+    #[cfg(not(feature = "concurrent"))]
+    let nonce = (1..u64::MAX)
+        .find(|&nonce| puzzle.get_pow_bits(nonce as u128) >= grinding_factor)
+        .expect("nonce not found");
+
+    #[cfg(feature = "concurrent")]
+    let nonce = (1..u64::MAX)
+        .into_par_iter()
+        .find_first(|&nonce| puzzle.get_pow_bits(nonce as u128) >= grinding_factor)
+        .expect("nonce not found");
+
+    nonce as u128
+}
+
+// GRINDING CLOCK
+// ================================================================================================
+
+/// A source of time for the time-boxed grinding strategy.
+pub trait GrindingClock: Send + Sync {
+    /// Returns the number of milliseconds elapsed since an arbitrary point in time; the point
+    /// must not change while a proof is being generated.
+    fn now_millis(&self) -> u64;
+}
+
+/// A clock backed by the system time.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl GrindingClock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// A clock which never advances.
+///
+/// With this clock, the grinding deadline never passes, and thus, time-boxed grinding always
+/// finds the same nonce as grinding with the [FixedBits](crate::GrindingStrategy::FixedBits)
+/// strategy would.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrozenClock;
+
+impl GrindingClock for FrozenClock {
+    fn now_millis(&self) -> u64 {
+        0
+    }
+}
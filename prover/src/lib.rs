@@ -14,6 +14,18 @@
 //! machine). The number of threads can be configured via `RAYON_NUM_THREADS` environment
 //! variable.
 //!
+//! When the crate is compiled with `diagnostics` feature enabled, debug builds of the prover
+//! check that the constraint composition polynomial is consistent with the constraints of the
+//! AIR if `WINTER_CHECK_COMPOSITION` environment variable is set. When the check fails, proof
+//! generation panics with a message naming the first mismatching point of the trace domain
+//! coset and, if possible, the constraint which was composed incorrectly.
+//!
+//! When the crate is compiled with `trace-constraints` feature enabled, transition constraints
+//! which an execution trace does not satisfy can be recorded via [constraint_log::record()] or
+//! [Prover::prove_with_constraint_log()]; every entry of the log contains the step of the trace,
+//! the index of the constraint, and the value to which the constraint evaluated. When the feature
+//! is disabled, constraint evaluation is not instrumented at all.
+//!
 //! # Usage
 //! To generate a proof that a computation was executed correctly, you'll need to do the
 //! following:
@@ -44,11 +56,28 @@
 extern crate alloc;
 
 pub use air::{
-    proof::StarkProof, Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint,
-    BoundaryConstraintGroup, ConstraintCompositionCoefficients, ConstraintDivisor,
-    DeepCompositionCoefficients, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo,
-    TraceLayout, TransitionConstraintDegree, TransitionConstraintGroup,
+    hidden,
+    proof::{
+        ArchivalEnvelope, ArchiveHeader, ArchiveMetadata, BatchStarkProof, CommitmentLayout,
+        Commitments, CompositionCoefficients, Context, LeafPosition, OodFrame, ProofSizeBreakdown,
+        Queries, SecurityBottleneck, SecurityEstimate, StarkProof, PROOF_FORMAT_VERSION,
+    },
+    pub_inputs_seed, rap, selectors, transcript_labels, Air, AirContext, AirDescription,
+    ArchiveError, Assertion, AssertionDescription, AssertionError, AssertionStep,
+    AuxTraceRandElements, BoundaryConstraint, BoundaryConstraintGroup, BoundaryExpr, CePos,
+    ConstraintCompositionCoefficients, ConstraintDescription, ConstraintDescriptor,
+    ConstraintDivisor, DeepCompositionCoefficients, EvaluationFrame, FieldExtension,
+    GrindingStrategy, LdePos, LeafEncoding, PowPuzzle, ProofOptions, SecurityError, SelectorError,
+    SplitInputsAir, StepSelector, TraceInfo, TraceLayout, TraceLayoutBuilder, TraceLayoutError,
+    TraceRow, TranscriptProfile, TransitionConstraintDegree, TransitionConstraintGroup,
+    TransitionDivisorKind,
 };
+
+#[cfg(feature = "test-kit")]
+pub use air::test_kit;
+#[cfg(feature = "dependency-graph")]
+pub use air::{ConstraintDependencies, DependencyGraph, DEFAULT_NUM_DEPENDENCY_SAMPLES};
+
 pub use utils::{
     iterators, ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
     SliceReader,
@@ -58,14 +87,20 @@ use fri::FriProver;
 use utils::collections::Vec;
 
 pub use math;
-use math::{
-    fft::infer_degree,
-    fields::{CubeExtension, QuadExtension},
-    ExtensibleField, FieldElement, StarkField,
-};
+#[cfg(feature = "cubic")]
+use math::fields::CubeExtension;
+#[cfg(feature = "quadratic")]
+use math::fields::QuadExtension;
+use math::{fft::infer_degree, polynom, ExtensibleField, FieldElement, StarkField};
 
 pub use crypto;
-use crypto::{ElementHasher, MerkleTree};
+use crypto::{
+    CommitmentOpener, DefaultRandomCoin, DynHasher, ElementHasher, MerkleTree, RandomCoin,
+    VectorCommitment,
+};
+
+#[cfg(feature = "transcript-log")]
+use crypto::transcript_log::{self, TranscriptLog};
 
 #[cfg(feature = "std")]
 use log::debug;
@@ -74,6 +109,9 @@ use math::log2;
 #[cfg(feature = "std")]
 use std::time::Instant;
 
+mod config;
+pub use config::ProverConfig;
+
 mod domain;
 pub use domain::StarkDomain;
 
@@ -81,22 +119,61 @@ mod matrix;
 pub use matrix::Matrix;
 
 mod constraints;
-use constraints::{CompositionPoly, ConstraintCommitment, ConstraintEvaluator};
+use constraints::{
+    AuxTransitionTable, CompositionPoly, ConstraintCommitment, ConstraintEvaluator,
+    MainTransitionTable, PeriodicValueTable,
+};
 
 mod composer;
 use composer::DeepCompositionPoly;
 
 mod trace;
-pub use trace::{Trace, TraceTable, TraceTableFragment};
+pub use trace::{
+    ColumnCodec, ColumnStatistics, DegeneracyThresholds, DegenerateColumn, FillStatus,
+    PreprocessedSegment, Trace, TraceStatistics, TraceTable, TraceTableFragment,
+    DEFAULT_DISTINCT_VALUES_CAP,
+};
 use trace::{TraceCommitment, TraceLde, TracePolyTable};
 
 mod channel;
 use channel::ProverChannel;
 
+mod grinding;
+#[cfg(feature = "std")]
+pub use grinding::SystemClock;
+pub use grinding::{find_pow_nonce, FrozenClock, GrindingClock};
+
+mod outputs;
+pub use outputs::ExpectedOutputs;
+
+mod salts;
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub use salts::OsSaltSource;
+pub use salts::{LeafSalts, SaltSource};
+
 mod errors;
-pub use errors::ProverError;
+pub use errors::{CheckpointError, ProverError, TraceBuildError, TraceError};
+
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+
+#[cfg(feature = "trace-constraints")]
+pub mod constraint_log;
+#[cfg(feature = "trace-constraints")]
+use constraint_log::ConstraintLog;
+
+pub mod batch;
+
+#[cfg(feature = "std")]
+pub mod planning;
+
+pub mod checkpoint;
+use checkpoint::{
+    Checkpoint, CheckpointSink, CheckpointSource, CheckpointWriter, ConstraintsCommittedState,
+    FriState, ProofPhase, TraceCommittedState,
+};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "f128"))]
 pub mod tests;
 
 // PROVER
@@ -150,6 +227,103 @@ pub trait Prover {
     // PROVIDED METHODS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns [ProverConfig] which this prover uses to tune proof generation.
+    ///
+    /// Prover config does not affect the generated proofs; it controls only how the work is
+    /// scheduled (e.g., how constraint evaluation and trace commitment are split into chunks).
+    /// The default implementation returns [ProverConfig::default()].
+    fn config(&self) -> ProverConfig {
+        ProverConfig::default()
+    }
+
+    /// Returns the preprocessed trace segment which this prover includes in every proof, or None
+    /// if proofs generated by this prover do not have a preprocessed segment.
+    ///
+    /// When a segment is returned, trace info of every trace passed to this prover is extended
+    /// with the segment (see [TraceInfo::with_preprocessed_segment()]); thus, the AIR sees the
+    /// segment's columns right after the columns of the main trace segment. Proofs generated
+    /// with a preprocessed segment must be verified against the segment's commitment (see
+    /// [PreprocessedSegment::commitment()]).
+    ///
+    /// The default implementation returns None.
+    fn preprocessed_segment(&self) -> Option<&PreprocessedSegment<Self::BaseField, Self::HashFn>> {
+        None
+    }
+
+    /// Returns true if this prover seeds the public coin with the digest of serialized public
+    /// inputs (see [pub_inputs_seed()]) rather than with the serialized inputs themselves.
+    ///
+    /// Proofs generated by such a prover can be verified by parties which receive only the
+    /// digest of public inputs together with the values needed to build assertions (see
+    /// [SplitInputsAir]), but cannot be verified against the full public inputs.
+    ///
+    /// The default implementation returns false.
+    fn seeds_with_pub_inputs_digest(&self) -> bool {
+        false
+    }
+
+    /// Returns the clock which bounds the time spent on query seed grinding when proof options
+    /// specify the [TimeBoxed](GrindingStrategy::TimeBoxed) grinding strategy; the clock is not
+    /// used with any other strategy.
+    ///
+    /// The default implementation returns [SystemClock] when `std` feature is enabled, and
+    /// [FrozenClock] otherwise; thus, without `std` feature, time-boxed grinding never stops
+    /// before the nonce provides all the bits specified by the grinding factor unless this
+    /// method is overridden.
+    fn grinding_clock(&self) -> &dyn GrindingClock {
+        #[cfg(feature = "std")]
+        return &SystemClock;
+
+        #[cfg(not(feature = "std"))]
+        return &FrozenClock;
+    }
+
+    /// Returns the opener through which the commitment to the main trace segment is opened at
+    /// query positions, or None if the commitment is opened via the Merkle tree built by the
+    /// prover.
+    ///
+    /// This can be used when the Merkle tree of the main trace segment is kept in an external
+    /// storage: the opener can then fetch Merkle paths for queried rows from the storage and
+    /// aggregate them into a batch proof (see [crypto::BatchMerkleProof::from_single_paths()]).
+    /// The tree opened by the opener must have the same root as the tree built by the prover.
+    /// Single Merkle paths can be aggregated only for binary trees; when proof options specify
+    /// a greater Merkle tree arity, the opener must return proofs built by
+    /// [MerkleTree::prove_batch()].
+    ///
+    /// The default implementation returns None.
+    fn main_trace_opener(&self) -> Option<&dyn CommitmentOpener<Self::HashFn>> {
+        None
+    }
+
+    /// Returns the outputs which the caller of this prover expects execution traces to contain,
+    /// or None if the outputs are not known in advance.
+    ///
+    /// When outputs are returned, every trace is checked against them before a proof for it is
+    /// generated, and proof generation fails with [ProverError::UnexpectedOutput] if the trace
+    /// does not contain them. In this case, [get_pub_inputs()](Prover::get_pub_inputs) should
+    /// build public inputs from the expected values rather than from the trace, so that the
+    /// proven statement is the one supplied by the caller.
+    ///
+    /// The default implementation returns None.
+    fn expected_outputs(&self) -> Option<&ExpectedOutputs<Self::BaseField>> {
+        None
+    }
+
+    /// Returns the source of random bytes from which salts of leaves of trace and constraint
+    /// commitments are derived when proof options request salted commitments (see
+    /// [ProofOptions::with_zk()]); the source is not used otherwise.
+    ///
+    /// The default implementation returns [OsSaltSource] when `std` feature is enabled (on
+    /// targets other than WebAssembly), and None otherwise; when no source is available, proof
+    /// generation with salted commitments fails with [ProverError::MissingSaltSource].
+    fn salt_source(&self) -> Option<&dyn SaltSource> {
+        #[cfg(all(feature = "std", not(target_family = "wasm")))]
+        return Some(&OsSaltSource);
+
+        #[cfg(not(all(feature = "std", not(target_family = "wasm"))))]
+        return None;
+    }
+
     /// Returns a STARK proof attesting to a correct execution of a computation defined by the
     /// provided trace.
     ///
@@ -159,73 +333,637 @@ pub trait Prover {
     /// [Self::get_pub_inputs()](Prover::get_pub_inputs) for the provided trace.
     #[rustfmt::skip]
     fn prove(&self, trace: Self::Trace) -> Result<StarkProof, ProverError> {
+        self.validate_proof_options()?;
+
         // figure out which version of the generic proof generation procedure to run. this is a sort
         // of static dispatch for selecting two generic parameter: extension field and hash function.
         match self.options().field_extension() {
-            FieldExtension::None => self.generate_proof::<Self::BaseField>(trace),
+            FieldExtension::None => self.generate_proof::<Self::BaseField>(trace, None, None, None),
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => {
+                self.generate_proof::<QuadExtension<Self::BaseField>>(trace, None, None, None)
+            }
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => {
+                self.generate_proof::<CubeExtension<Self::BaseField>>(trace, None, None, None)
+            }
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
+
+    /// Returns a STARK proof attesting to a correct execution of a computation defined by the
+    /// provided trace, using the provided polynomials of the main trace segment instead of
+    /// interpolating them from the trace.
+    ///
+    /// `main_trace_polys` must contain a polynomial in coefficient form for every column of the
+    /// main trace segment, such that evaluating the polynomials over the trace domain yields the
+    /// main segment of the trace. The trace is still used to build public inputs and auxiliary
+    /// trace segments, but the main segment is extended directly from the provided polynomials.
+    /// For consistent inputs, the returned proof is identical to the one returned from
+    /// [Self::prove()](Prover::prove).
+    ///
+    /// Columns of `main_trace_polys` may be shorter or longer than the trace as long as the
+    /// degree of every polynomial is smaller than the trace length.
+    ///
+    /// # Errors
+    /// Returns an error if the number of provided polynomials does not match the width of the
+    /// main trace segment, or if the degree of any of the polynomials is not smaller than the
+    /// trace length.
+    fn prove_from_polys(
+        &self,
+        trace: Self::Trace,
+        main_trace_polys: Matrix<Self::BaseField>,
+    ) -> Result<StarkProof, ProverError> {
+        self.validate_proof_options()?;
+        let polys =
+            normalize_trace_polys(main_trace_polys, trace.main_trace_width(), trace.length())?;
+        match self.options().field_extension() {
+            FieldExtension::None => {
+                self.generate_proof::<Self::BaseField>(trace, None, None, Some(polys))
+            }
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => self.generate_proof::<QuadExtension<Self::BaseField>>(
+                trace,
+                None,
+                None,
+                Some(polys),
+            ),
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => self.generate_proof::<CubeExtension<Self::BaseField>>(
+                trace,
+                None,
+                None,
+                Some(polys),
+            ),
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
+
+    /// Returns a STARK proof attesting to a correct execution of a computation defined by the
+    /// provided trace together with the public inputs against which the proof was generated.
+    ///
+    /// The public inputs are the value returned from [Self::get_pub_inputs()](Prover::get_pub_inputs)
+    /// for the provided trace, and thus, the returned proof is verifiable against them. Comparing
+    /// the returned public inputs against the ones the caller expects detects a mismatch between
+    /// the prover and the caller directly, rather than via a failed proof verification.
+    ///
+    /// [Self::get_pub_inputs()](Prover::get_pub_inputs) is expected to be deterministic, as it is
+    /// invoked both to build the returned value and to generate the proof.
+    ///
+    /// # Errors
+    /// Returns the same errors as [prove()](Prover::prove).
+    fn prove_returning_inputs(
+        &self,
+        trace: Self::Trace,
+    ) -> Result<(StarkProof, <<Self as Prover>::Air as Air>::PublicInputs), ProverError> {
+        let pub_inputs = self.get_pub_inputs(&trace);
+        let proof = self.prove(trace)?;
+        Ok((proof, pub_inputs))
+    }
+
+    /// Returns a STARK proof attesting to a correct execution of a computation defined by the
+    /// provided trace together with the log of all public coin interactions performed while
+    /// generating the proof.
+    ///
+    /// Replaying the returned proof via `verify_with_transcript_log()` of the verifier results
+    /// in an identical log, unless the proof was modified.
+    ///
+    /// # Errors
+    /// Returns the same errors as [prove()](Prover::prove).
+    #[cfg(feature = "transcript-log")]
+    fn prove_with_transcript_log(
+        &self,
+        trace: Self::Trace,
+    ) -> Result<(StarkProof, TranscriptLog), ProverError> {
+        let (proof, log) = transcript_log::record(|| self.prove(trace));
+        Ok((proof?, log))
+    }
+
+    /// Returns the result of generating a STARK proof for the provided trace together with the
+    /// log of all transition constraints which the trace does not satisfy.
+    ///
+    /// The trace is validated against the AIR regardless of the build profile, and validation
+    /// does not stop at the first unsatisfied constraint; if the log is not empty, the returned
+    /// result is [ProverError::InvalidTrace]. The log is returned even if proof generation fails,
+    /// so that it can be inspected or dumped.
+    #[cfg(feature = "trace-constraints")]
+    fn prove_with_constraint_log(
+        &self,
+        trace: Self::Trace,
+    ) -> (Result<StarkProof, ProverError>, ConstraintLog) {
+        constraint_log::record(|| self.prove(trace))
+    }
+
+    /// Returns STARK proofs attesting to correct executions of a computation defined by the
+    /// provided traces.
+    ///
+    /// The proofs are returned in the same order as the traces. A failure to generate a proof for
+    /// one of the traces does not affect generation of proofs for other traces.
+    ///
+    /// The default implementation invokes [prove()](Prover::prove) for each of the traces in
+    /// turn. [batch::prove_batch()] generates the same proofs, but amortizes the fixed costs of
+    /// proof generation across the batch, and thus, should be preferred for large batches of
+    /// small traces.
+    fn prove_batch(&self, traces: Vec<Self::Trace>) -> Vec<Result<StarkProof, ProverError>> {
+        traces.into_iter().map(|trace| self.prove(trace)).collect()
+    }
+
+    /// Returns a STARK proof attesting to a correct execution of a computation defined by the
+    /// provided trace, committing to the execution trace and constraint evaluations via the
+    /// vector commitment scheme `V` rather than via Merkle trees.
+    ///
+    /// The identifier of the scheme is recorded in the context of the returned proof (unless `V`
+    /// is [MerkleTree], in which case the proof is identical to the one returned from
+    /// [prove()](Prover::prove)), and thus, the proof can be verified only with the same scheme.
+    /// Layers of the FRI protocol are always committed to via Merkle trees.
+    ///
+    /// # Errors
+    /// In addition to the errors returned from [prove()](Prover::prove), returns an error if the
+    /// prover has a preprocessed trace segment or a main trace opener, as both of these are
+    /// bound to Merkle trees.
+    #[rustfmt::skip]
+    fn prove_with_commitment<V>(&self, trace: Self::Trace) -> Result<StarkProof, ProverError>
+    where
+        V: VectorCommitment<Self::HashFn>,
+    {
+        self.validate_proof_options()?;
+        if self.preprocessed_segment().is_some() || self.main_trace_opener().is_some() {
+            return Err(ProverError::UnsupportedCommitmentScheme(V::SCHEME_ID));
+        }
+
+        match self.options().field_extension() {
+            FieldExtension::None => {
+                self.generate_proof_with_commitment::<Self::BaseField, V, DefaultRandomCoin<Self::BaseField, Self::HashFn>>(trace, None, None, None, None, None)
+            }
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => {
+                self.generate_proof_with_commitment::<QuadExtension<Self::BaseField>, V, DefaultRandomCoin<Self::BaseField, Self::HashFn>>(trace, None, None, None, None, None)
+            }
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => {
+                self.generate_proof_with_commitment::<CubeExtension<Self::BaseField>, V, DefaultRandomCoin<Self::BaseField, Self::HashFn>>(trace, None, None, None, None, None)
+            }
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
+
+    /// Returns a STARK proof attesting to a correct execution of a computation defined by the
+    /// provided trace, drawing all randomness of the protocol from the random coin `C` rather
+    /// than from [DefaultRandomCoin].
+    ///
+    /// The identifier of the coin is recorded in the context of the returned proof (unless `C`
+    /// is [DefaultRandomCoin], in which case the proof is identical to the one returned from
+    /// [prove()](Prover::prove)), and thus, the proof can be verified only with the same coin.
+    ///
+    /// # Errors
+    /// In addition to the errors returned from [prove()](Prover::prove), returns an error if `C`
+    /// is not [DefaultRandomCoin] and the prover has a preprocessed trace segment.
+    #[rustfmt::skip]
+    fn prove_with_random_coin<C>(&self, trace: Self::Trace) -> Result<StarkProof, ProverError>
+    where
+        C: RandomCoin<BaseField = Self::BaseField, Hasher = Self::HashFn>,
+    {
+        self.validate_proof_options()?;
+        if C::COIN_ID != 0 && self.preprocessed_segment().is_some() {
+            return Err(ProverError::UnsupportedRandomCoin(C::COIN_ID));
+        }
+        let preprocessed = self
+            .preprocessed_segment()
+            .map(|segment| (segment, segment.tree().clone()));
+        let opener = self.main_trace_opener();
+
+        match self.options().field_extension() {
+            FieldExtension::None => {
+                self.generate_proof_with_commitment::<Self::BaseField, _, C>(trace, None, None, None, preprocessed, opener)
+            }
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => {
+                self.generate_proof_with_commitment::<QuadExtension<Self::BaseField>, _, C>(trace, None, None, None, preprocessed, opener)
+            }
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => {
+                self.generate_proof_with_commitment::<CubeExtension<Self::BaseField>, _, C>(trace, None, None, None, preprocessed, opener)
+            }
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
+
+    /// Returns a STARK proof attesting to a correct execution of a computation defined by the
+    /// provided trace, saving checkpoints of proof generation into the specified `sink`.
+    ///
+    /// A checkpoint is saved after the trace has been committed to, after the constraint
+    /// composition polynomial has been committed to, and after each FRI layer has been committed
+    /// to (see [checkpoint] module for details). Proof generation can be resumed from any of
+    /// these checkpoints via [resume_proof()](Prover::resume_proof); the resumed proof is
+    /// identical to the proof returned from [prove()](Prover::prove).
+    ///
+    /// # Errors
+    /// In addition to the errors returned from [prove()](Prover::prove), returns an error if the
+    /// sink fails to save a checkpoint.
+    fn prove_with_checkpoints<S: CheckpointSink>(
+        &self,
+        trace: Self::Trace,
+        sink: &mut S,
+    ) -> Result<StarkProof, ProverError> {
+        self.validate_proof_options()?;
+        match self.options().field_extension() {
+            FieldExtension::None => {
+                self.generate_proof::<Self::BaseField>(trace, Some(sink), None, None)
+            }
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => {
+                self.generate_proof::<QuadExtension<Self::BaseField>>(trace, Some(sink), None, None)
+            }
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => {
+                self.generate_proof::<CubeExtension<Self::BaseField>>(trace, Some(sink), None, None)
+            }
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
+
+    /// Resumes generation of a STARK proof from a checkpoint loaded from the specified `source`,
+    /// and returns the resulting proof.
+    ///
+    /// The checkpoint must have been saved by [prove_with_checkpoints()](Prover::prove_with_checkpoints)
+    /// of a prover with the same proof options for an execution trace with the specified
+    /// public inputs.
+    ///
+    /// # Errors
+    /// In addition to the errors returned from [prove()](Prover::prove), returns an error if:
+    /// * A checkpoint could not be loaded from the `source` or could not be deserialized.
+    /// * The checkpoint was saved using an unsupported checkpoint or transcript state format.
+    /// * The checkpoint was saved for different public inputs, trace info, or proof options.
+    fn resume_proof<S: CheckpointSource>(
+        &self,
+        pub_inputs: <<Self as Prover>::Air as Air>::PublicInputs,
+        source: &mut S,
+    ) -> Result<StarkProof, ProverError> {
+        let checkpoint = source.load().map_err(ProverError::CheckpointFailed)?;
+        self.resume_from_checkpoint(pub_inputs, checkpoint, None)
+    }
+
+    /// Resumes generation of a STARK proof from a checkpoint loaded from the specified `source`
+    /// the same way as [resume_proof()](Prover::resume_proof) does, and saves checkpoints of the
+    /// remaining phases of proof generation into the specified `sink`.
+    fn resume_proof_with_checkpoints<S: CheckpointSource, K: CheckpointSink>(
+        &self,
+        pub_inputs: <<Self as Prover>::Air as Air>::PublicInputs,
+        source: &mut S,
+        sink: &mut K,
+    ) -> Result<StarkProof, ProverError> {
+        let checkpoint = source.load().map_err(ProverError::CheckpointFailed)?;
+        self.resume_from_checkpoint(pub_inputs, checkpoint, Some(sink))
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Makes sure the proof options of this prover can be used with its base field and hash
+    /// function.
+    #[doc(hidden)]
+    fn validate_proof_options(&self) -> Result<(), ProverError> {
+        if !self
+            .options()
+            .transcript_profile()
+            .is_supported::<Self::HashFn>()
+        {
+            return Err(ProverError::UnsupportedTranscriptProfile);
+        }
+        if let Some(hash_fn) = self.options().grinding_hash() {
+            if DynHasher::<Self::BaseField>::new(hash_fn).is_none() {
+                return Err(ProverError::UnsupportedGrindingHash(hash_fn));
+            }
+        }
+        match self.options().field_extension() {
+            FieldExtension::None => Ok(()),
+            #[cfg(feature = "quadratic")]
             FieldExtension::Quadratic => {
                 if !<QuadExtension<Self::BaseField>>::is_supported() {
                     return Err(ProverError::UnsupportedFieldExtension(2));
                 }
-                self.generate_proof::<QuadExtension<Self::BaseField>>(trace)
+                Ok(())
             }
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
             FieldExtension::Cubic => {
                 if !<CubeExtension<Self::BaseField>>::is_supported() {
                     return Err(ProverError::UnsupportedFieldExtension(3));
                 }
-                self.generate_proof::<CubeExtension<Self::BaseField>>(trace)
+                Ok(())
             }
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
         }
     }
 
-    // HELPER METHODS
-    // --------------------------------------------------------------------------------------------
+    /// Returns a STARK proof for the provided trace, building the proof over the specified
+    /// `domain` if it can be used for the trace.
+    #[doc(hidden)]
+    fn prove_with_domain(
+        &self,
+        trace: Self::Trace,
+        domain: &StarkDomain<Self::BaseField>,
+    ) -> Result<StarkProof, ProverError> {
+        self.validate_proof_options()?;
+        match self.options().field_extension() {
+            FieldExtension::None => {
+                self.generate_proof::<Self::BaseField>(trace, None, Some(domain), None)
+            }
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => self.generate_proof::<QuadExtension<Self::BaseField>>(
+                trace,
+                None,
+                Some(domain),
+                None,
+            ),
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => self.generate_proof::<CubeExtension<Self::BaseField>>(
+                trace,
+                None,
+                Some(domain),
+                None,
+            ),
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
+
+    /// Resumes proof generation from the specified checkpoint, dispatching on the extension field
+    /// specified by the proof options.
+    #[doc(hidden)]
+    fn resume_from_checkpoint(
+        &self,
+        pub_inputs: <<Self as Prover>::Air as Air>::PublicInputs,
+        checkpoint: Checkpoint,
+        sink: Option<&mut dyn CheckpointSink>,
+    ) -> Result<StarkProof, ProverError> {
+        self.validate_proof_options()?;
+        match self.options().field_extension() {
+            FieldExtension::None => {
+                self.resume_generate_proof::<Self::BaseField>(pub_inputs, checkpoint, sink)
+            }
+            #[cfg(feature = "quadratic")]
+            FieldExtension::Quadratic => self
+                .resume_generate_proof::<QuadExtension<Self::BaseField>>(
+                    pub_inputs, checkpoint, sink,
+                ),
+            #[cfg(not(feature = "quadratic"))]
+            FieldExtension::Quadratic => Err(ProverError::UnsupportedFieldExtension(2)),
+            #[cfg(feature = "cubic")]
+            FieldExtension::Cubic => self.resume_generate_proof::<CubeExtension<Self::BaseField>>(
+                pub_inputs, checkpoint, sink,
+            ),
+            #[cfg(not(feature = "cubic"))]
+            FieldExtension::Cubic => Err(ProverError::UnsupportedFieldExtension(3)),
+        }
+    }
 
     /// Performs the actual proof generation procedure, generating the proof that the provided
-    /// execution `trace` is valid against this prover's AIR.
+    /// execution `trace` is valid against this prover's AIR. If a checkpoint `sink` is provided,
+    /// checkpoints of proof generation are saved into it. If a `domain` is provided and it is
+    /// compatible with the AIR instantiated for the trace, it is used instead of building a new
+    /// domain. If `main_trace_polys` are provided, the main trace segment is extended from them
+    /// instead of from the interpolated main segment of the trace.
     /// TODO: make this function un-callable externally?
     #[doc(hidden)]
-    fn generate_proof<E>(&self, mut trace: Self::Trace) -> Result<StarkProof, ProverError>
+    fn generate_proof<E>(
+        &self,
+        trace: Self::Trace,
+        sink: Option<&mut dyn CheckpointSink>,
+        domain: Option<&StarkDomain<Self::BaseField>>,
+        main_trace_polys: Option<Matrix<Self::BaseField>>,
+    ) -> Result<StarkProof, ProverError>
     where
         E: FieldElement<BaseField = Self::BaseField>,
+    {
+        let preprocessed = self
+            .preprocessed_segment()
+            .map(|segment| (segment, segment.tree().clone()));
+        self.generate_proof_with_commitment::<E, MerkleTree<Self::HashFn>, DefaultRandomCoin<Self::BaseField, Self::HashFn>>(
+            trace,
+            sink,
+            domain,
+            main_trace_polys,
+            preprocessed,
+            self.main_trace_opener(),
+        )
+    }
+
+    /// Performs the actual proof generation procedure the same way as
+    /// [generate_proof()](Prover::generate_proof), but commits to the execution trace and the
+    /// constraint evaluations via the vector commitment scheme `V`.
+    ///
+    /// The commitment to the `preprocessed` segment (if any) must have been built with the same
+    /// scheme; the main trace commitment is opened via the `opener` if one is provided. The
+    /// randomness of the protocol is drawn from the random coin `C`.
+    #[doc(hidden)]
+    #[allow(clippy::type_complexity)]
+    fn generate_proof_with_commitment<E, V, C>(
+        &self,
+        mut trace: Self::Trace,
+        sink: Option<&mut dyn CheckpointSink>,
+        domain: Option<&StarkDomain<Self::BaseField>>,
+        main_trace_polys: Option<Matrix<Self::BaseField>>,
+        preprocessed: Option<(&PreprocessedSegment<Self::BaseField, Self::HashFn>, V)>,
+        opener: Option<&dyn CommitmentOpener<Self::HashFn, V>>,
+    ) -> Result<StarkProof, ProverError>
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+        V: VectorCommitment<Self::HashFn>,
+        C: RandomCoin<BaseField = Self::BaseField, Hasher = Self::HashFn>,
     {
         // 0 ----- instantiate AIR and prover channel ---------------------------------------------
 
+        // make sure the trace contains the outputs expected by the caller (if any) before the
+        // public inputs are built
+        if let Some(outputs) = self.expected_outputs() {
+            outputs.check(&trace)?;
+        }
+
         // serialize public inputs; these will be included in the seed for the public coin
         let pub_inputs = self.get_pub_inputs(&trace);
         let mut pub_inputs_bytes = Vec::new();
         pub_inputs.write_into(&mut pub_inputs_bytes);
 
+        // when the prover has a preprocessed trace segment, make sure it can be used with the
+        // trace, and extend the trace info with the segment
+        let (preprocessed, preprocessed_tree) = match preprocessed {
+            Some((segment, tree)) => (Some(segment), Some(tree)),
+            None => (None, None),
+        };
+        let mut trace_info = trace.get_info();
+        if let Some(segment) = preprocessed {
+            segment.check_compatibility(&trace_info, self.options())?;
+            trace_info = trace_info.with_preprocessed_segment(segment.num_cols());
+        }
+
         // create an instance of AIR for the provided parameters. this takes a generic description
         // of the computation (provided via AIR type), and creates a description of a specific
         // execution of the computation for the provided public inputs.
-        let air = Self::Air::new(trace.get_info(), pub_inputs, self.options().clone());
+        let air = Self::Air::new(trace_info, pub_inputs, self.options().clone());
+
+        // make sure the assertions of the AIR can be placed against the execution trace
+        air.validate_assertions()
+            .map_err(ProverError::InvalidAssertion)?;
 
         // create a channel which is used to simulate interaction between the prover and the
         // verifier; the channel will be used to commit to values and to draw randomness that
         // should come from the verifier.
-        let mut channel = ProverChannel::<Self::Air, E, Self::HashFn>::new(&air, pub_inputs_bytes);
+        let seed_bytes = if self.seeds_with_pub_inputs_digest() {
+            pub_inputs_seed::<Self::HashFn>(&pub_inputs_bytes).to_bytes()
+        } else {
+            pub_inputs_bytes.clone()
+        };
+        let mut channel = ProverChannel::<Self::Air, E, Self::HashFn, V, C>::new(
+            &air,
+            seed_bytes,
+            self.grinding_clock(),
+        )
+        .with_main_trace_opener(opener);
+
+        // make sure rows of all trace commitments can be encoded into leaves of Merkle trees as
+        // specified by the proof options
+        let row_bytes = channel.context().max_trace_row_bytes::<E>();
+        if !air
+            .options()
+            .leaf_encoding()
+            .is_supported::<Self::HashFn>(row_bytes)
+        {
+            return Err(ProverError::UnsupportedLeafEncoding(row_bytes));
+        }
 
-        // 1 ----- Commit to the execution trace --------------------------------------------------
+        // when proof options request salted commitments, draw the seed from which salts of all
+        // committed leaves are derived
+        let salts = if air.options().is_zk() {
+            let source = self.salt_source().ok_or(ProverError::MissingSaltSource)?;
+            Some(LeafSalts::<Self::HashFn>::draw(source))
+        } else {
+            None
+        };
 
-        // build computation domain; this is used later for polynomial evaluations
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-        let domain = StarkDomain::new(&air);
-        #[cfg(feature = "std")]
-        debug!(
-            "Built domain of 2^{} elements in {} ms",
-            log2(domain.lde_domain_size()),
-            now.elapsed().as_millis()
-        );
-
-        // extend the main execution trace and build a Merkle tree from the extended trace
-        let (main_trace_lde, main_trace_tree, main_trace_polys) =
-            self.build_trace_commitment::<Self::BaseField>(trace.main_segment(), &domain);
+        // 1 ----- Commit to the execution trace --------------------------------------------------
 
-        // commit to the LDE of the main trace by writing the root of its Merkle tree into
-        // the channel
-        channel.commit_trace(*main_trace_tree.root());
+        // build computation domain (unless a compatible domain was provided); this is used later
+        // for polynomial evaluations
+        let new_domain;
+        let domain = match domain {
+            Some(domain) if domain.is_compatible_with(&air) => domain,
+            _ => {
+                #[cfg(feature = "std")]
+                let now = Instant::now();
+                new_domain = StarkDomain::new(&air);
+                #[cfg(feature = "std")]
+                debug!(
+                    "Built domain of 2^{} elements in {} ms",
+                    log2(new_domain.lde_domain_size()),
+                    now.elapsed().as_millis()
+                );
+                &new_domain
+            }
+        };
+
+        // extend the main execution trace and build a Merkle tree from the extended trace; the
+        // table of periodic values used in constraint evaluation does not depend on the trace
+        // commitment, and thus, it is built concurrently with the commitment
+        let main_trace_salts = salts.as_ref().map(|salts| salts.trace_segment(0));
+        let pipelined = self.config().pipelines_evaluation();
+        let (main_trace_lde, main_trace_tree, main_trace_polys, periodic_values, main_transitions) =
+            if pipelined && preprocessed.is_none() {
+                // main transition constraints do not depend on composition coefficients, and
+                // thus, they are evaluated over the extended trace while the trace is hashed
+                let periodic_values = PeriodicValueTable::new(&air);
+                let main_trace_polys = match main_trace_polys {
+                    Some(trace_polys) => {
+                        #[cfg(debug_assertions)]
+                        validate_trace_polys(&trace_polys, trace.main_segment(), domain);
+                        trace_polys
+                    }
+                    None => self.interpolate_trace(trace.main_segment(), domain),
+                };
+                let main_trace_lde = self.evaluate_trace_polys(&main_trace_polys, domain);
+                let config = self.config();
+                let (main_trace_tree, main_transitions) = overlap(
+                    || {
+                        self.commit_to_trace_lde::<Self::BaseField, V>(
+                            &main_trace_lde,
+                            main_trace_salts.as_ref(),
+                        )
+                    },
+                    || {
+                        MainTransitionTable::new(
+                            &air,
+                            &main_trace_lde,
+                            domain,
+                            &periodic_values,
+                            &config,
+                        )
+                    },
+                );
+                (
+                    main_trace_lde,
+                    main_trace_tree,
+                    main_trace_polys,
+                    periodic_values,
+                    Some(main_transitions),
+                )
+            } else {
+                let ((main_trace_lde, main_trace_tree, main_trace_polys), periodic_values) =
+                    overlap(
+                        || match main_trace_polys {
+                            Some(trace_polys) => {
+                                // make sure the provided polynomials describe the main segment
+                                // of the trace; we do this in debug mode only because this
+                                // requires interpolating the trace
+                                #[cfg(debug_assertions)]
+                                validate_trace_polys(&trace_polys, trace.main_segment(), domain);
+                                self.extend_trace_polys::<Self::BaseField, V>(
+                                    trace_polys,
+                                    domain,
+                                    main_trace_salts.as_ref(),
+                                )
+                            }
+                            None => self.build_trace_commitment::<Self::BaseField, V>(
+                                trace.main_segment(),
+                                domain,
+                                main_trace_salts.as_ref(),
+                            ),
+                        },
+                        || PeriodicValueTable::new(&air),
+                    );
+                (
+                    main_trace_lde,
+                    main_trace_tree,
+                    main_trace_polys,
+                    periodic_values,
+                    None,
+                )
+            };
+
+        // bind the commitment to the preprocessed segment (if any) to the transcript, and commit
+        // to the LDE of the main trace by writing the root of its Merkle tree into the channel
+        if let Some(segment) = preprocessed {
+            channel.commit_preprocessed_segment(segment.commitment());
+        }
+        channel.commit_trace(main_trace_tree.commitment());
 
         // initialize trace commitment and trace polynomial table structs with the main trace
         // data; for multi-segment traces these structs will be used as accumulators of all
@@ -234,13 +972,22 @@ pub trait Prover {
             main_trace_lde,
             main_trace_tree,
             domain.trace_to_lde_blowup(),
-        );
+        )
+        .with_salts(salts);
         let mut trace_polys = TracePolyTable::new(main_trace_polys);
 
+        // append the preprocessed segment (if any); the segment has already been extended and
+        // committed to, and thus, its LDE and polynomials are copied into the structs as is
+        if let (Some(segment), Some(tree)) = (preprocessed, preprocessed_tree) {
+            trace_commitment.add_preprocessed_segment(segment.lde(), tree);
+            trace_polys.add_preprocessed_segment(segment.polys());
+        }
+
         // build auxiliary trace segments (if any), and append the resulting segments to trace
         // commitment and trace polynomial table structs
         let mut aux_trace_segments = Vec::new();
         let mut aux_trace_rand_elements = AuxTraceRandElements::new();
+        let mut aux_transitions = None;
         for i in 0..trace.layout().num_aux_segments() {
             #[cfg(feature = "std")]
             let now = Instant::now();
@@ -260,212 +1007,293 @@ pub trait Prover {
                 now.elapsed().as_millis()
             );
 
-            // extend the auxiliary trace segment and build a Merkle tree from the extended trace
+            aux_trace_rand_elements.add_segment_elements(rand_elements);
+
+            // extend the auxiliary trace segment and build a Merkle tree from the extended trace;
+            // once random elements for all auxiliary segments have been drawn, auxiliary
+            // transition constraints do not depend on composition coefficients, and thus, they
+            // are evaluated over the extended trace while the last segment is hashed
+            let aux_segment_salts = trace_commitment
+                .salts()
+                .map(|salts| salts.trace_segment(i + 1));
             let (aux_segment_lde, aux_segment_tree, aux_segment_polys) =
-                self.build_trace_commitment::<E>(&aux_segment, &domain);
+                if pipelined && i + 1 == trace.layout().num_aux_segments() {
+                    let aux_segment_polys = self.interpolate_trace(&aux_segment, domain);
+                    let aux_segment_lde = self.evaluate_trace_polys(&aux_segment_polys, domain);
+                    let config = self.config();
+                    let (aux_segment_tree, transitions) = overlap(
+                        || {
+                            self.commit_to_trace_lde::<E, V>(
+                                &aux_segment_lde,
+                                aux_segment_salts.as_ref(),
+                            )
+                        },
+                        || {
+                            AuxTransitionTable::new(
+                                &air,
+                                trace_commitment.trace_table(),
+                                &aux_segment_lde,
+                                domain,
+                                &periodic_values,
+                                &aux_trace_rand_elements,
+                                &config,
+                            )
+                        },
+                    );
+                    aux_transitions = Some(transitions);
+                    (aux_segment_lde, aux_segment_tree, aux_segment_polys)
+                } else {
+                    self.build_trace_commitment::<E, V>(
+                        &aux_segment,
+                        domain,
+                        aux_segment_salts.as_ref(),
+                    )
+                };
 
             // commit to the LDE of the extended auxiliary trace segment  by writing the root of
             // its Merkle tree into the channel
-            channel.commit_trace(*aux_segment_tree.root());
+            channel.commit_trace(aux_segment_tree.commitment());
 
             // append the segment to the trace commitment and trace polynomial table structs
             trace_commitment.add_segment(aux_segment_lde, aux_segment_tree);
             trace_polys.add_aux_segment(aux_segment_polys);
-            aux_trace_rand_elements.add_segment_elements(rand_elements);
             aux_trace_segments.push(aux_segment);
         }
+        air.validate_aux_assertions(&aux_trace_rand_elements)
+            .map_err(ProverError::InvalidAssertion)?;
 
         // make sure the specified trace (including auxiliary segments) is valid against the AIR.
-        // This checks validity of both, assertions and state transitions. We do this in debug
-        // mode only because this is a very expensive operation.
-        #[cfg(debug_assertions)]
-        trace.validate(&air, &aux_trace_segments, &aux_trace_rand_elements);
-
-        // 2 ----- evaluate constraints -----------------------------------------------------------
-        // evaluate constraints specified by the AIR over the constraint evaluation domain, and
-        // compute random linear combinations of these evaluations using coefficients drawn from
-        // the channel; this step evaluates only constraint numerators, thus, only constraints with
-        // identical denominators are merged together. the results are saved into a constraint
-        // evaluation table where each column contains merged evaluations of constraints with
-        // identical denominators.
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-        let constraint_coeffs = channel.get_constraint_composition_coeffs();
-        let evaluator = ConstraintEvaluator::new(&air, aux_trace_rand_elements, constraint_coeffs);
-        let constraint_evaluations = evaluator.evaluate(trace_commitment.trace_table(), &domain);
-        #[cfg(feature = "std")]
-        debug!(
-            "Evaluated constraints over domain of 2^{} elements in {} ms",
-            log2(constraint_evaluations.num_rows()),
-            now.elapsed().as_millis()
-        );
-
-        // 3 ----- commit to constraint evaluations -----------------------------------------------
-
-        // first, build constraint composition polynomial from the constraint evaluation table:
-        // - divide all constraint evaluation columns by their respective divisors
-        // - combine them into a single column of evaluations,
-        // - interpolate the column into a polynomial in coefficient form
-        // - "break" the polynomial into a set of column polynomials each of degree equal to
-        //   trace_length - 1
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-        let composition_poly = constraint_evaluations.into_poly()?;
-        #[cfg(feature = "std")]
-        debug!(
-            "Converted constraint evaluations into {} composition polynomial columns of degree {} in {} ms",
-            composition_poly.num_columns(),
-            composition_poly.column_degree(),
-            now.elapsed().as_millis()
-        );
-
-        // then, build a commitment to the evaluations of the composition polynomial columns
-        let constraint_commitment =
-            self.build_constraint_commitment::<E>(&composition_poly, &domain);
-
-        // then, commit to the evaluations of constraints by writing the root of the constraint
-        // Merkle tree into the channel
-        channel.commit_constraints(constraint_commitment.root());
-
-        // 4 ----- build DEEP composition polynomial ----------------------------------------------
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-
-        // draw an out-of-domain point z. Depending on the type of E, the point is drawn either
-        // from the base field or from an extension field defined by E.
-        //
-        // The purpose of sampling from the extension field here (instead of the base field) is to
-        // increase security. Soundness is limited by the size of the field that the random point
-        // is drawn from, and we can potentially save on performance by only drawing this point
-        // from an extension field, rather than increasing the size of the field overall.
-        let z = channel.get_ood_point();
-
-        // evaluate trace and constraint polynomials at the OOD point z, and send the results to
-        // the verifier. the trace polynomials are actually evaluated over two points: z and z * g,
-        // where g is the generator of the trace domain.
-        let ood_trace_states = trace_polys.get_ood_frame(z);
-        channel.send_ood_trace_states(&ood_trace_states);
-
-        let ood_evaluations = composition_poly.evaluate_at(z);
-        channel.send_ood_constraint_evaluations(&ood_evaluations);
-
-        // draw random coefficients to use during DEEP polynomial composition, and use them to
-        // initialize the DEEP composition polynomial
-        let deep_coefficients = channel.get_deep_composition_coeffs();
-        let mut deep_composition_poly = DeepCompositionPoly::new(&air, z, deep_coefficients);
-
-        // combine all trace polynomials together and merge them into the DEEP composition
-        // polynomial
-        deep_composition_poly.add_trace_polys(trace_polys, ood_trace_states);
+        // This checks validity of both, assertions and state transitions. Unless the config
+        // requires it (or constraint violations are being recorded), we do this in debug mode only
+        // because this is a very expensive operation.
+        #[cfg(feature = "trace-constraints")]
+        let validate_trace = self.config().validates_trace() || constraint_log::is_recording();
+        #[cfg(not(feature = "trace-constraints"))]
+        let validate_trace = self.config().validates_trace();
+        if validate_trace {
+            trace
+                .try_validate(
+                    &air,
+                    preprocessed.map(|segment| segment.columns()),
+                    &aux_trace_segments,
+                    &aux_trace_rand_elements,
+                )
+                .map_err(ProverError::InvalidTrace)?;
+        } else {
+            #[cfg(debug_assertions)]
+            trace.validate(
+                &air,
+                preprocessed.map(|segment| segment.columns()),
+                &aux_trace_segments,
+                &aux_trace_rand_elements,
+            );
+        }
 
-        // merge columns of constraint composition polynomial into the DEEP composition polynomial;
-        deep_composition_poly.add_composition_poly(composition_poly, ood_evaluations);
+        // when the proof options require it, draw a random coefficient gamma and commit to the
+        // combined trace column sum(gamma^i * T_i(x)); since gamma is drawn after all trace
+        // segments have been committed to, the combination cannot be influenced by the prover
+        if air.options().uses_combined_trace_column() {
+            let gamma = channel.get_combined_trace_coefficient();
+            let combined_lde =
+                Matrix::new(vec![trace_commitment.trace_table().combine_columns(gamma)]);
+            let combined_salts = trace_commitment
+                .salts()
+                .map(|salts| salts.combined_column());
+            let combined_tree: V = combined_lde.commit_to_rows_with_scheme(
+                air.options().leaf_encoding(),
+                air.options().merkle_arity(),
+                combined_salts.as_ref(),
+                &self.config(),
+            );
+            channel.commit_combined_trace_column(combined_tree.commitment());
+            trace_commitment.set_combined_column(gamma, combined_lde, combined_tree);
+        }
 
-        // raise the degree of the DEEP composition polynomial by one to make sure it is equal to
-        // trace_length - 1
-        deep_composition_poly.adjust_degree();
+        // save a checkpoint of the committed trace, and continue proof generation by committing
+        // to the constraint composition polynomial
+        let state = TraceCommittedState {
+            trace_commitment,
+            trace_polys,
+            aux_rand_elements: aux_trace_rand_elements,
+        };
+        let mut checkpoints = CheckpointWriter::new(sink, pub_inputs_bytes);
+        checkpoints
+            .save(ProofPhase::TraceCommitted, |target| {
+                channel.write_state_into(target);
+                state.write_into(target);
+            })
+            .map_err(ProverError::CheckpointFailed)?;
+
+        commit_to_constraints(
+            self,
+            &air,
+            domain,
+            channel,
+            state,
+            periodic_values,
+            main_transitions,
+            aux_transitions,
+            checkpoints,
+        )
+    }
 
-        #[cfg(feature = "std")]
-        debug!(
-            "Built DEEP composition polynomial of degree {} in {} ms",
-            deep_composition_poly.degree(),
-            now.elapsed().as_millis()
-        );
+    /// Restores the state of proof generation from the specified checkpoint and continues proof
+    /// generation from the phase at which the checkpoint was saved.
+    #[doc(hidden)]
+    fn resume_generate_proof<E>(
+        &self,
+        pub_inputs: <<Self as Prover>::Air as Air>::PublicInputs,
+        checkpoint: Checkpoint,
+        sink: Option<&mut dyn CheckpointSink>,
+    ) -> Result<StarkProof, ProverError>
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
+        // make sure the checkpoint was saved for the same public inputs
+        let mut pub_inputs_bytes = Vec::new();
+        pub_inputs.write_into(&mut pub_inputs_bytes);
+        if pub_inputs_bytes != checkpoint.pub_inputs_bytes() {
+            return Err(ProverError::CheckpointFailed(
+                CheckpointError::PublicInputsMismatch,
+            ));
+        }
 
-        // make sure the degree of the DEEP composition polynomial is equal to trace polynomial
-        // degree
-        assert_eq!(domain.trace_length() - 1, deep_composition_poly.degree());
+        // the transcript state starts with the proof context; we use it to instantiate the same
+        // AIR as the one used before the checkpoint was saved, and then restore the channel
+        let mut source = SliceReader::new(checkpoint.state());
+        let context = ProverChannel::<Self::Air, E, Self::HashFn>::read_context_from(&mut source)
+            .map_err(ProverError::CheckpointFailed)?;
+        if context.options() != self.options() {
+            return Err(ProverError::CheckpointFailed(
+                CheckpointError::ContextMismatch,
+            ));
+        }
+        let air = Self::Air::new(context.get_trace_info(), pub_inputs, self.options().clone());
+        air.validate_assertions()
+            .map_err(ProverError::InvalidAssertion)?;
+        let channel = ProverChannel::<Self::Air, E, Self::HashFn>::read_state_from(
+            &air,
+            context,
+            &mut source,
+            self.grinding_clock(),
+        )
+        .map_err(ProverError::CheckpointFailed)?
+        .with_main_trace_opener(self.main_trace_opener());
 
-        // 5 ----- evaluate DEEP composition polynomial over LDE domain ---------------------------
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-        let deep_evaluations = deep_composition_poly.evaluate(&domain);
-        // we check the following condition in debug mode only because infer_degree is an expensive
-        // operation
-        debug_assert_eq!(
-            domain.trace_length() - 1,
-            infer_degree(&deep_evaluations, domain.offset())
-        );
-        #[cfg(feature = "std")]
-        debug!(
-            "Evaluated DEEP composition polynomial over LDE domain (2^{} elements) in {} ms",
-            log2(domain.lde_domain_size()),
-            now.elapsed().as_millis()
-        );
+        let domain = StarkDomain::new(&air);
+        let checkpoints = CheckpointWriter::new(sink, pub_inputs_bytes);
+
+        // restore the state of proof generation and continue from the checkpointed phase
+        match checkpoint.phase() {
+            ProofPhase::TraceCommitted => {
+                let state = read_phase_state(&mut source, TraceCommittedState::read_from)?;
+                let periodic_values = PeriodicValueTable::new(&air);
+                commit_to_constraints(
+                    self,
+                    &air,
+                    &domain,
+                    channel,
+                    state,
+                    periodic_values,
+                    None,
+                    None,
+                    checkpoints,
+                )
+            }
+            ProofPhase::ConstraintsCommitted => {
+                let state = read_phase_state(&mut source, ConstraintsCommittedState::read_from)?;
+                let fri_prover = FriProver::new(air.options().to_fri_options());
+                build_deep_composition(&air, &domain, channel, state, fri_prover, checkpoints)
+            }
+            ProofPhase::FriLayerCommitted(_) => {
+                let fri_options = air.options().to_fri_options();
+                let state = read_phase_state(&mut source, |source| {
+                    FriState::read_from(source, fri_options)
+                })?;
+                build_fri_layers(&air, &domain, channel, state, checkpoints)
+            }
+        }
+    }
 
-        // 6 ----- compute FRI layers for the composition polynomial ------------------------------
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-        let mut fri_prover = FriProver::new(air.options().to_fri_options());
-        fri_prover.build_layers(&mut channel, deep_evaluations);
-        #[cfg(feature = "std")]
-        debug!(
-            "Computed {} FRI layers from composition polynomial evaluations in {} ms",
-            fri_prover.num_layers(),
-            now.elapsed().as_millis()
-        );
+    /// Computes a low-degree extension (LDE) of the provided execution trace over the specified
+    /// domain and build a commitment to the extended trace.
+    ///
+    /// The extension is performed by interpolating each column of the execution trace into a
+    /// polynomial of degree = trace_length - 1, and then evaluating the polynomial over the LDE
+    /// domain.
+    ///
+    /// Trace commitment is computed by hashing each row of the extended execution trace, and then
+    /// building a Merkle tree from the resulting hashes. When `salts` are provided, the hash of
+    /// each row is merged with the salt of the row before it is placed into the tree.
+    fn build_trace_commitment<E, V>(
+        &self,
+        trace: &Matrix<E>,
+        domain: &StarkDomain<Self::BaseField>,
+        salts: Option<&LeafSalts<Self::HashFn>>,
+    ) -> (Matrix<E>, V, Matrix<E>)
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+        V: VectorCommitment<Self::HashFn>,
+    {
+        let trace_polys = self.interpolate_trace(trace, domain);
+        self.extend_trace_polys(trace_polys, domain, salts)
+    }
 
-        // 7 ----- determine query positions ------------------------------------------------------
+    /// Interpolates each column of the provided execution trace into a polynomial of degree =
+    /// trace_length - 1.
+    fn interpolate_trace<E>(
+        &self,
+        trace: &Matrix<E>,
+        domain: &StarkDomain<Self::BaseField>,
+    ) -> Matrix<E>
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
         #[cfg(feature = "std")]
         let now = Instant::now();
-
-        // apply proof-of-work to the query seed
-        channel.grind_query_seed();
-
-        // generate pseudo-random query positions
-        let query_positions = channel.get_query_positions();
+        let trace_polys = trace.interpolate_columns_with_twiddles(domain.trace_inv_twiddles());
         #[cfg(feature = "std")]
         debug!(
-            "Determined {} query positions in {} ms",
-            query_positions.len(),
+            "Interpolated execution trace of {} columns and 2^{} steps in {} ms",
+            trace_polys.num_cols(),
+            log2(trace_polys.num_rows()),
             now.elapsed().as_millis()
         );
-
-        // 8 ----- build proof object -------------------------------------------------------------
-        #[cfg(feature = "std")]
-        let now = Instant::now();
-
-        // generate FRI proof
-        let fri_proof = fri_prover.build_proof(&query_positions);
-
-        // query the execution trace at the selected position; for each query, we need the
-        // state of the trace at that position + Merkle authentication path
-        let trace_queries = trace_commitment.query(&query_positions);
-
-        // query the constraint commitment at the selected positions; for each query, we need just
-        // a Merkle authentication path. this is because constraint evaluations for each step are
-        // merged into a single value and Merkle authentication paths contain these values already
-        let constraint_queries = constraint_commitment.query(&query_positions);
-
-        // build the proof object
-        let proof = channel.build_proof(trace_queries, constraint_queries, fri_proof);
-        #[cfg(feature = "std")]
-        debug!("Built proof object in {} ms", now.elapsed().as_millis());
-
-        Ok(proof)
+        trace_polys
     }
 
-    /// Computes a low-degree extension (LDE) of the provided execution trace over the specified
-    /// domain and build a commitment to the extended trace.
-    ///
-    /// The extension is performed by interpolating each column of the execution trace into a
-    /// polynomial of degree = trace_length - 1, and then evaluating the polynomial over the LDE
-    /// domain.
+    /// Evaluates the provided trace polynomials over the LDE domain and builds a commitment to
+    /// the extended trace.
     ///
     /// Trace commitment is computed by hashing each row of the extended execution trace, and then
-    /// building a Merkle tree from the resulting hashes.
-    fn build_trace_commitment<E>(
+    /// building a Merkle tree from the resulting hashes. When `salts` are provided, the hash of
+    /// each row is merged with the salt of the row before it is placed into the tree.
+    fn extend_trace_polys<E, V>(
         &self,
-        trace: &Matrix<E>,
+        trace_polys: Matrix<E>,
+        domain: &StarkDomain<Self::BaseField>,
+        salts: Option<&LeafSalts<Self::HashFn>>,
+    ) -> (Matrix<E>, V, Matrix<E>)
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+        V: VectorCommitment<Self::HashFn>,
+    {
+        let trace_lde = self.evaluate_trace_polys(&trace_polys, domain);
+        let trace_tree = self.commit_to_trace_lde(&trace_lde, salts);
+        (trace_lde, trace_tree, trace_polys)
+    }
+
+    /// Evaluates the provided trace polynomials over the LDE domain.
+    fn evaluate_trace_polys<E>(
+        &self,
+        trace_polys: &Matrix<E>,
         domain: &StarkDomain<Self::BaseField>,
-    ) -> (Matrix<E>, MerkleTree<Self::HashFn>, Matrix<E>)
+    ) -> Matrix<E>
     where
         E: FieldElement<BaseField = Self::BaseField>,
     {
-        // extend the execution trace
         #[cfg(feature = "std")]
         let now = Instant::now();
-        let trace_polys = trace.interpolate_columns();
         let trace_lde = trace_polys.evaluate_columns_over(domain);
         #[cfg(feature = "std")]
         debug!(
@@ -476,19 +1304,38 @@ pub trait Prover {
             domain.trace_to_lde_blowup(),
             now.elapsed().as_millis()
         );
+        trace_lde
+    }
 
-        // build trace commitment
+    /// Builds a commitment to the provided extended execution trace.
+    ///
+    /// The commitment is computed by hashing each row of the extended execution trace, and then
+    /// building a Merkle tree from the resulting hashes. When `salts` are provided, the hash of
+    /// each row is merged with the salt of the row before it is placed into the tree.
+    fn commit_to_trace_lde<E, V>(
+        &self,
+        trace_lde: &Matrix<E>,
+        salts: Option<&LeafSalts<Self::HashFn>>,
+    ) -> V
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+        V: VectorCommitment<Self::HashFn>,
+    {
         #[cfg(feature = "std")]
         let now = Instant::now();
-        let trace_tree = trace_lde.commit_to_rows();
+        let trace_tree: V = trace_lde.commit_to_rows_with_scheme(
+            self.options().leaf_encoding(),
+            self.options().merkle_arity(),
+            salts,
+            &self.config(),
+        );
         #[cfg(feature = "std")]
         debug!(
-            "Computed execution trace commitment (Merkle tree of depth {}) in {} ms",
-            trace_tree.depth(),
+            "Computed execution trace commitment (over 2^{} rows) in {} ms",
+            log2(trace_tree.domain_len()),
             now.elapsed().as_millis()
         );
-
-        (trace_lde, trace_tree, trace_polys)
+        trace_tree
     }
 
     /// Evaluates constraint composition polynomial over the LDE domain and builds a commitment
@@ -498,14 +1345,17 @@ pub trait Prover {
     /// domain.
     ///
     /// The commitment is computed by hashing each row in the evaluation matrix, and then building
-    /// a Merkle tree from the resulting hashes.
-    fn build_constraint_commitment<E>(
+    /// a Merkle tree from the resulting hashes. When `salts` are provided, the hash of each row
+    /// is merged with the salt of the row before it is placed into the tree.
+    fn build_constraint_commitment<E, V>(
         &self,
         composition_poly: &CompositionPoly<E>,
         domain: &StarkDomain<Self::BaseField>,
-    ) -> ConstraintCommitment<E, Self::HashFn>
+        salts: Option<LeafSalts<Self::HashFn>>,
+    ) -> ConstraintCommitment<E, Self::HashFn, V>
     where
         E: FieldElement<BaseField = Self::BaseField>,
+        V: VectorCommitment<Self::HashFn>,
     {
         // evaluate composition polynomial columns over the LDE domain
         #[cfg(feature = "std")]
@@ -522,14 +1372,487 @@ pub trait Prover {
         // build constraint evaluation commitment
         #[cfg(feature = "std")]
         let now = Instant::now();
-        let commitment = composed_evaluations.commit_to_rows();
-        let constraint_commitment = ConstraintCommitment::new(composed_evaluations, commitment);
+        let commitment = composed_evaluations.commit_to_rows_with_scheme(
+            LeafEncoding::Hashed,
+            self.options().merkle_arity(),
+            salts.as_ref(),
+            &self.config(),
+        );
+        let constraint_commitment =
+            ConstraintCommitment::new(composed_evaluations, commitment).with_salts(salts);
         #[cfg(feature = "std")]
         debug!(
-            "Computed constraint evaluation commitment (Merkle tree of depth {}) in {} ms",
+            "Computed constraint evaluation commitment (over 2^{} rows) in {} ms",
             constraint_commitment.tree_depth(),
             now.elapsed().as_millis()
         );
         constraint_commitment
     }
 }
+
+// PROOF GENERATION STAGES
+// ================================================================================================
+
+/// Evaluates constraints against the committed execution trace and commits to the resulting
+/// constraint composition polynomial; then continues proof generation by building the DEEP
+/// composition polynomial.
+#[allow(clippy::too_many_arguments)]
+fn commit_to_constraints<P, E, V, C>(
+    prover: &P,
+    air: &P::Air,
+    domain: &StarkDomain<P::BaseField>,
+    mut channel: ProverChannel<P::Air, E, P::HashFn, V, C>,
+    state: TraceCommittedState<E, P::HashFn, V>,
+    periodic_values: PeriodicValueTable<P::BaseField>,
+    main_transitions: Option<MainTransitionTable<P::BaseField>>,
+    aux_transitions: Option<AuxTransitionTable<E>>,
+    mut checkpoints: CheckpointWriter,
+) -> Result<StarkProof, ProverError>
+where
+    P: Prover + ?Sized,
+    E: FieldElement<BaseField = P::BaseField>,
+    V: VectorCommitment<P::HashFn>,
+    C: RandomCoin<BaseField = P::BaseField, Hasher = P::HashFn>,
+{
+    let TraceCommittedState {
+        trace_commitment,
+        trace_polys,
+        aux_rand_elements,
+    } = state;
+
+    // 2 ----- evaluate constraints ----------------------------------------------------------------
+    // evaluate constraints specified by the AIR over the constraint evaluation domain, and
+    // compute random linear combinations of these evaluations using coefficients drawn from
+    // the channel; this step evaluates only constraint numerators, thus, only constraints with
+    // identical denominators are merged together. the results are saved into a constraint
+    // evaluation table where each column contains merged evaluations of constraints with
+    // identical denominators.
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+    let constraint_coeffs = channel.get_constraint_composition_coeffs();
+    #[cfg(all(feature = "diagnostics", debug_assertions))]
+    let diagnostic_inputs = diagnostics::is_composition_check_enabled()
+        .then(|| (constraint_coeffs.clone(), aux_rand_elements.clone()));
+    let mut evaluator =
+        ConstraintEvaluator::new(air, aux_rand_elements, constraint_coeffs, periodic_values);
+    if let Some(main_transitions) = main_transitions {
+        evaluator = evaluator.with_main_transitions(main_transitions);
+    }
+    if let Some(aux_transitions) = aux_transitions {
+        evaluator = evaluator.with_aux_transitions(aux_transitions);
+    }
+    let constraint_evaluations =
+        evaluator.evaluate(trace_commitment.trace_table(), domain, &prover.config());
+    #[cfg(feature = "std")]
+    debug!(
+        "Evaluated constraints over domain of 2^{} elements in {} ms",
+        log2(constraint_evaluations.num_rows()),
+        now.elapsed().as_millis()
+    );
+
+    // 3 ----- commit to constraint evaluations ----------------------------------------------------
+
+    // first, build constraint composition polynomial from the constraint evaluation table; for
+    // every out-of-domain point:
+    // - divide all constraint evaluation columns of the point by their respective divisors
+    // - combine them into a single column of evaluations,
+    // - interpolate the column into a polynomial in coefficient form
+    // - "break" the polynomial into a set of column polynomials each of degree equal to
+    //   trace_length - 1
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+    let composition_poly = constraint_evaluations.into_poly()?;
+    #[cfg(feature = "std")]
+    debug!(
+        "Converted constraint evaluations into {} composition polynomial columns of degree {} in {} ms",
+        composition_poly.num_columns(),
+        composition_poly.column_degree(),
+        now.elapsed().as_millis()
+    );
+
+    // in debug builds with `diagnostics` feature enabled, make sure the composition polynomial
+    // is consistent with the constraints of the AIR when requested via an environment variable
+    #[cfg(all(feature = "diagnostics", debug_assertions))]
+    if let Some((coefficients, aux_rand_elements)) = diagnostic_inputs {
+        for (point_idx, coefficients) in coefficients.iter().enumerate() {
+            if let Err(mismatch) = diagnostics::check_composition_consistency(
+                air,
+                &trace_polys,
+                &composition_poly,
+                point_idx,
+                coefficients,
+                &aux_rand_elements,
+            ) {
+                panic!("{}", mismatch);
+            }
+        }
+    }
+
+    // then, either send the composition polynomial to the verifier explicitly, or build a
+    // commitment to the evaluations of the composition polynomial columns and commit to them
+    // by writing the root of the constraint Merkle tree into the channel.
+    //
+    // sending the polynomial explicitly does not reduce soundness: the verifier evaluates
+    // the polynomial at the OOD point and at the query positions itself, and since the
+    // verifier makes sure the number of coefficients matches the degree of the composition
+    // polynomial, the prover is bound to a polynomial of the same degree as a committed one
+    // would have. the coefficients are also absorbed into the public coin before the OOD
+    // point is drawn, and thus, the prover cannot choose them after seeing the OOD point.
+    //
+    // the FRI prover used later on does not depend on the constraint commitment, and thus, the
+    // FRI domain is set up concurrently with building the commitment.
+    let fri_options = air.options().to_fri_options();
+    let explicit_composition = air.options().uses_explicit_composition(air.trace_length());
+    let (constraint_commitment, fri_prover) = if explicit_composition {
+        channel.send_composition_coefficients(&composition_poly);
+        (None, FriProver::new(fri_options))
+    } else {
+        let salts = trace_commitment.salts().map(|salts| salts.constraints());
+        let (constraint_commitment, fri_prover) = overlap(
+            || prover.build_constraint_commitment::<E, V>(&composition_poly, domain, salts),
+            || FriProver::with_domain_size(fri_options, domain.lde_domain_size()),
+        );
+        channel.commit_constraints(constraint_commitment.root());
+        (Some(constraint_commitment), fri_prover)
+    };
+
+    let state = ConstraintsCommittedState {
+        trace_commitment,
+        trace_polys,
+        composition_poly,
+        constraint_commitment,
+    };
+    checkpoints
+        .save(ProofPhase::ConstraintsCommitted, |target| {
+            channel.write_state_into(target);
+            state.write_into(target);
+        })
+        .map_err(ProverError::CheckpointFailed)?;
+
+    build_deep_composition(air, domain, channel, state, fri_prover, checkpoints)
+}
+
+/// Builds the DEEP composition polynomial from the committed trace and constraint composition
+/// polynomials and evaluates it over the LDE domain; then continues proof generation by executing
+/// the commit phase of the FRI protocol.
+#[allow(clippy::type_complexity)]
+fn build_deep_composition<'a, A, E, H, V, C>(
+    air: &'a A,
+    domain: &StarkDomain<A::BaseField>,
+    mut channel: ProverChannel<'a, A, E, H, V, C>,
+    state: ConstraintsCommittedState<E, H, V>,
+    fri_prover: FriProver<A::BaseField, E, ProverChannel<'a, A, E, H, V, C>, H>,
+    checkpoints: CheckpointWriter,
+) -> Result<StarkProof, ProverError>
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+    H: ElementHasher<BaseField = A::BaseField>,
+    V: VectorCommitment<H>,
+    C: RandomCoin<BaseField = A::BaseField, Hasher = H>,
+{
+    let ConstraintsCommittedState {
+        trace_commitment,
+        trace_polys,
+        composition_poly,
+        constraint_commitment,
+    } = state;
+
+    // 4 ----- build DEEP composition polynomial ---------------------------------------------------
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+
+    // draw out-of-domain points z. Depending on the type of E, the points are drawn either
+    // from the base field or from an extension field defined by E.
+    //
+    // The purpose of sampling from the extension field here (instead of the base field) is to
+    // increase security. Soundness is limited by the size of the field that the random point
+    // is drawn from, and we can potentially save on performance by only drawing this point
+    // from an extension field, rather than increasing the size of the field overall.
+    //
+    // for each point z, evaluate trace and constraint polynomials at z, and send the results
+    // to the verifier before drawing the next point. the trace polynomials are actually
+    // evaluated over points z * g^k for every row offset k of the evaluation frame (by default,
+    // just z and z * g), where g is the generator of the trace domain.
+    let num_ood_points = air.options().num_ood_points();
+    let frame_offsets = air.context().frame_offsets();
+    let mut ood_points = Vec::with_capacity(num_ood_points);
+    let mut ood_trace_states = Vec::with_capacity(frame_offsets.len() * num_ood_points);
+    let mut ood_evaluations = Vec::with_capacity(composition_poly.num_columns());
+    for point_idx in 0..num_ood_points {
+        let z = channel.get_ood_point();
+
+        let trace_states = trace_polys.get_ood_frame(z, frame_offsets);
+        channel.send_ood_trace_states(&trace_states);
+
+        // the combined trace column at z is the same linear combination of the trace
+        // polynomial evaluations at z
+        if let Some(gamma) = trace_commitment.combined_column_coefficient() {
+            channel.send_ood_combined_trace_evaluation(polynom::eval(&trace_states[0], gamma));
+        }
+
+        let evaluations = composition_poly.evaluate_at(point_idx, z);
+        channel.send_ood_constraint_evaluations(&evaluations);
+
+        ood_points.push(z);
+        ood_trace_states.extend(trace_states);
+        ood_evaluations.extend(evaluations);
+    }
+
+    // draw random coefficients to use during DEEP polynomial composition, and use them to
+    // initialize the DEEP composition polynomial
+    let deep_coefficients = channel.get_deep_composition_coeffs();
+    let mut deep_composition_poly = DeepCompositionPoly::new(air, ood_points, deep_coefficients);
+
+    // combine all trace polynomials together and merge them into the DEEP composition
+    // polynomial
+    deep_composition_poly.add_trace_polys(trace_polys, ood_trace_states);
+
+    // merge columns of constraint composition polynomial into the DEEP composition polynomial;
+    deep_composition_poly.add_composition_poly(composition_poly, ood_evaluations);
+
+    // raise the degree of the DEEP composition polynomial by one to make sure it is equal to
+    // trace_length - 1
+    deep_composition_poly.adjust_degree();
+
+    #[cfg(feature = "std")]
+    debug!(
+        "Built DEEP composition polynomial of degree {} in {} ms",
+        deep_composition_poly.degree(),
+        now.elapsed().as_millis()
+    );
+
+    // make sure the degree of the DEEP composition polynomial is equal to trace polynomial
+    // degree
+    assert_eq!(domain.trace_length() - 1, deep_composition_poly.degree());
+
+    // 5 ----- evaluate DEEP composition polynomial over LDE domain --------------------------------
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+    let deep_evaluations = deep_composition_poly.evaluate(domain);
+    // we check the following condition in debug mode only because infer_degree is an expensive
+    // operation
+    debug_assert_eq!(
+        domain.trace_length() - 1,
+        infer_degree(&deep_evaluations, domain.offset())
+    );
+    #[cfg(feature = "std")]
+    debug!(
+        "Evaluated DEEP composition polynomial over LDE domain (2^{} elements) in {} ms",
+        log2(domain.lde_domain_size()),
+        now.elapsed().as_millis()
+    );
+
+    let state = FriState {
+        trace_commitment,
+        constraint_commitment,
+        fri_prover,
+        evaluations: deep_evaluations,
+    };
+    build_fri_layers(air, domain, channel, state, checkpoints)
+}
+
+/// Executes the commit phase of the FRI protocol, continuing from the FRI layers built so far;
+/// then completes proof generation by determining query positions and building the proof.
+#[allow(clippy::type_complexity)]
+fn build_fri_layers<'a, A, E, H, V, C>(
+    air: &'a A,
+    domain: &StarkDomain<A::BaseField>,
+    mut channel: ProverChannel<'a, A, E, H, V, C>,
+    state: FriState<A::BaseField, E, ProverChannel<'a, A, E, H, V, C>, H, V>,
+    mut checkpoints: CheckpointWriter,
+) -> Result<StarkProof, ProverError>
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+    H: ElementHasher<BaseField = A::BaseField>,
+    V: VectorCommitment<H>,
+    C: RandomCoin<BaseField = A::BaseField, Hasher = H>,
+{
+    // 6 ----- compute FRI layers for the composition polynomial -----------------------------------
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+    let mut state = state;
+    let num_fri_layers = air
+        .options()
+        .to_fri_options()
+        .num_fri_layers(domain.lde_domain_size());
+    for layer_idx in state.fri_prover.num_layers()..num_fri_layers {
+        state
+            .fri_prover
+            .build_layer(&mut channel, &mut state.evaluations);
+        checkpoints
+            .save(ProofPhase::FriLayerCommitted(layer_idx), |target| {
+                channel.write_state_into(target);
+                state.write_into(target);
+            })
+            .map_err(ProverError::CheckpointFailed)?;
+    }
+    let FriState {
+        trace_commitment,
+        constraint_commitment,
+        mut fri_prover,
+        evaluations,
+    } = state;
+    fri_prover.build_remainder(&mut channel, evaluations);
+    // when enabled, bind the query positions to the remainder polynomial in its canonical form
+    if air.options().uses_fri_remainder_digest() {
+        channel.commit_fri_remainder_digest(fri_prover.remainder_digest());
+    }
+    #[cfg(feature = "std")]
+    debug!(
+        "Computed {} FRI layers from composition polynomial evaluations in {} ms",
+        fri_prover.num_layers(),
+        now.elapsed().as_millis()
+    );
+
+    // 7 ----- determine query positions -----------------------------------------------------------
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+
+    // apply proof-of-work to the query seed
+    channel.grind_query_seed();
+
+    // generate pseudo-random query positions
+    let query_positions = channel.get_query_positions();
+    #[cfg(feature = "std")]
+    debug!(
+        "Determined {} query positions in {} ms",
+        query_positions.len(),
+        now.elapsed().as_millis()
+    );
+
+    // 8 ----- build proof object ------------------------------------------------------------------
+    #[cfg(feature = "std")]
+    let now = Instant::now();
+
+    // generate FRI proof
+    let fri_proof = fri_prover.build_proof(&LdePos::to_indexes(&query_positions));
+
+    // query the execution trace at the selected position; for each query, we need the
+    // state of the trace at that position + Merkle authentication path
+    let commitment_layout = channel.commitment_layout();
+    let leaf_encoding = air.options().leaf_encoding();
+    let trace_queries = match channel.main_trace_opener() {
+        Some(opener) => trace_commitment.query_with_opener(
+            &query_positions,
+            &commitment_layout,
+            leaf_encoding,
+            opener,
+        ),
+        None => trace_commitment.query(&query_positions, &commitment_layout, leaf_encoding),
+    };
+
+    // query the constraint commitment at the selected positions; for each query, we need just
+    // a Merkle authentication path. this is because constraint evaluations for each step are
+    // merged into a single value and Merkle authentication paths contain these values already.
+    // when the composition polynomial was sent explicitly, there is nothing to query.
+    let constraint_queries = match constraint_commitment {
+        Some(constraint_commitment) => {
+            constraint_commitment.query(&query_positions, &commitment_layout)
+        }
+        None => Queries::default(),
+    };
+
+    // build the proof object
+    let proof = channel.build_proof(trace_queries, constraint_queries, fri_proof);
+    #[cfg(feature = "std")]
+    debug!("Built proof object in {} ms", now.elapsed().as_millis());
+
+    Ok(proof)
+}
+
+/// Makes sure the provided trace polynomials can be used as polynomials of the main segment of a
+/// trace with the specified width and length, and returns the polynomials with all columns
+/// resized to the trace length.
+fn normalize_trace_polys<B: StarkField>(
+    trace_polys: Matrix<B>,
+    trace_width: usize,
+    trace_length: usize,
+) -> Result<Matrix<B>, ProverError> {
+    if trace_polys.num_cols() != trace_width {
+        return Err(ProverError::MismatchedTracePolynomials(
+            trace_width,
+            trace_polys.num_cols(),
+        ));
+    }
+
+    let mut columns = trace_polys.into_columns();
+    for (col_idx, column) in columns.iter_mut().enumerate() {
+        let degree = polynom::degree_of(column);
+        if column.len() > trace_length && degree >= trace_length {
+            return Err(ProverError::TracePolynomialDegreeTooHigh(col_idx, degree));
+        }
+        column.resize(trace_length, B::ZERO);
+    }
+    Ok(Matrix::new(columns))
+}
+
+/// Makes sure the provided trace polynomials evaluate to the specified main trace segment over
+/// the trace domain.
+///
+/// # Panics
+/// Panics if the polynomial of any column does not match the interpolated column of the trace.
+#[cfg(debug_assertions)]
+fn validate_trace_polys<B: StarkField>(
+    trace_polys: &Matrix<B>,
+    main_segment: &Matrix<B>,
+    domain: &StarkDomain<B>,
+) {
+    let expected = main_segment.interpolate_columns_with_twiddles(domain.trace_inv_twiddles());
+    for (col_idx, (poly, expected)) in trace_polys.columns().zip(expected.columns()).enumerate() {
+        assert!(
+            poly == expected,
+            "polynomial of column {col_idx} does not match the main segment of the execution trace"
+        );
+    }
+}
+
+/// Reads the state of proof generation using the specified reader, and makes sure the state is
+/// followed by no other data.
+fn read_phase_state<'a, T, F>(source: &mut SliceReader<'a>, read_state: F) -> Result<T, ProverError>
+where
+    F: FnOnce(&mut SliceReader<'a>) -> Result<T, DeserializationError>,
+{
+    let state = read_state(source)
+        .map_err(|err| ProverError::CheckpointFailed(CheckpointError::MalformedCheckpoint(err)))?;
+    if source.has_more_bytes() {
+        return Err(ProverError::CheckpointFailed(
+            CheckpointError::MalformedCheckpoint(DeserializationError::UnconsumedBytes),
+        ));
+    }
+    Ok(state)
+}
+
+/// Executes `a` and `b` and returns their results.
+///
+/// When `concurrent` feature is enabled, `b` is executed in the rayon thread pool while `a` is
+/// being executed in the current thread; this is used to perform work which does not depend on
+/// the randomness drawn from the channel while a commitment is being built.
+#[cfg(feature = "concurrent")]
+fn overlap<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA,
+    B: FnOnce() -> RB + Send,
+    RB: Send,
+{
+    let mut result_b = None;
+    let result_a = utils::rayon::in_place_scope(|scope| {
+        scope.spawn(|_| result_b = Some(b()));
+        a()
+    });
+    (
+        result_a,
+        result_b.expect("concurrent task did not complete"),
+    )
+}
+
+/// Executes `a` and `b` and returns their results.
+#[cfg(not(feature = "concurrent"))]
+fn overlap<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA,
+    B: FnOnce() -> RB,
+{
+    (a(), b())
+}
@@ -3,15 +3,25 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::StarkDomain;
-use core::{iter::FusedIterator, slice};
-use crypto::{ElementHasher, MerkleTree};
+use super::{LeafSalts, ProverConfig, StarkDomain};
+use air::LeafEncoding;
+use core::{iter::FusedIterator, mem, slice};
+use crypto::{ElementHasher, MerkleTree, VectorCommitment};
 use math::{fft, polynom, FieldElement};
-use utils::{batch_iter_mut, collections::Vec, iter, iter_mut, uninit_vector};
+use utils::{
+    collections::Vec, iter, iter_mut, uninit_vector, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, RowMajorView, Serializable,
+};
 
 #[cfg(feature = "concurrent")]
 use utils::iterators::*;
 
+// CONSTANTS
+// ================================================================================================
+
+/// Number of columns read at a time when streaming rows into the hasher during commitment.
+const STREAMING_BLOCK_WIDTH: usize = 8;
+
 // MATRIX
 // ================================================================================================
 
@@ -131,15 +141,28 @@ impl<E: FieldElement> Matrix<E> {
     // --------------------------------------------------------------------------------------------
 
     /// Returns an iterator over the columns of this matrix.
-    pub fn columns(&self) -> ColumnIter<E> {
+    pub fn columns(&self) -> ColumnIter<'_, E> {
         ColumnIter::new(self)
     }
 
     /// Returns a mutable iterator over the columns of this matrix.
-    pub fn columns_mut(&mut self) -> ColumnIterMut<E> {
+    pub fn columns_mut(&mut self) -> ColumnIterMut<'_, E> {
         ColumnIterMut::new(self)
     }
 
+    /// Appends copies of all columns of the `other` matrix to the end of this matrix.
+    ///
+    /// # Panics
+    /// Panics if the number of rows in the `other` matrix is not the same as in this matrix.
+    pub fn append_columns(&mut self, other: &Self) {
+        assert_eq!(
+            self.num_rows(),
+            other.num_rows(),
+            "all matrix columns must have the same length"
+        );
+        self.columns.extend(other.columns.iter().cloned());
+    }
+
     // POLYNOMIAL METHODS
     // --------------------------------------------------------------------------------------------
 
@@ -155,10 +178,27 @@ impl<E: FieldElement> Matrix<E> {
     ///   coefficients of a degree `num_rows - 1` polynomial.
     pub fn interpolate_columns(&self) -> Self {
         let inv_twiddles = fft::get_inv_twiddles::<E::BaseField>(self.num_rows());
+        self.interpolate_columns_with_twiddles(&inv_twiddles)
+    }
+
+    /// Interpolates columns of the matrix into polynomials in coefficient form using the
+    /// provided inverse twiddles, and returns the result.
+    ///
+    /// This is identical to [Matrix::interpolate_columns()] except that the inverse twiddles are
+    /// not computed, and thus, can be shared between many interpolations over the same domain.
+    ///
+    /// # Panics
+    /// Panics if the number of `inv_twiddles` is not half the number of rows in this matrix.
+    pub fn interpolate_columns_with_twiddles(&self, inv_twiddles: &[E::BaseField]) -> Self {
+        assert_eq!(
+            self.num_rows() / 2,
+            inv_twiddles.len(),
+            "number of inverse twiddles must be half the number of rows"
+        );
         let columns = iter!(self.columns)
             .map(|evaluations| {
                 let mut column = evaluations.clone();
-                fft::interpolate_poly(&mut column, &inv_twiddles);
+                fft::interpolate_poly(&mut column, inv_twiddles);
                 column
             })
             .collect();
@@ -224,30 +264,226 @@ impl<E: FieldElement> Matrix<E> {
     ///   becomes a leaf in the tree. Thus, the number of leaves in the tree is equal to the
     ///   number of rows in the matrix.
     /// * The resulting Merkle tree is return as the commitment to the entire matrix.
+    ///
+    /// Rows are hashed in chunks sized according to the default [ProverConfig].
     pub fn commit_to_rows<H>(&self) -> MerkleTree<H>
     where
         H: ElementHasher<BaseField = E::BaseField>,
     {
+        self.commit_to_rows_with_config(&ProverConfig::default())
+    }
+
+    /// Returns a commitment to this matrix in which rows are encoded into leaves of the Merkle
+    /// tree using the specified `leaf_encoding`.
+    ///
+    /// For [LeafEncoding::Hashed], this is the same as
+    /// [commit_to_rows_with_config()](Matrix::commit_to_rows_with_config); for
+    /// [LeafEncoding::Raw], every leaf contains the elements of the corresponding row padded with
+    /// zeros to the size of a digest.
+    ///
+    /// # Panics
+    /// Panics if rows of this matrix cannot be encoded into leaves using the specified encoding.
+    pub fn commit_to_rows_with_encoding<H>(
+        &self,
+        leaf_encoding: LeafEncoding,
+        config: &ProverConfig,
+    ) -> MerkleTree<H>
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.commit_to_rows_with_arity(leaf_encoding, 2, config)
+    }
+
+    /// Returns a commitment to this matrix in which rows are encoded into leaves of a Merkle tree
+    /// with the specified `arity` using the specified `leaf_encoding`.
+    ///
+    /// For arity 2, this is the same as
+    /// [commit_to_rows_with_encoding()](Matrix::commit_to_rows_with_encoding).
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Rows of this matrix cannot be encoded into leaves using the specified encoding.
+    /// * `arity` is not 2, 4, or 8.
+    pub fn commit_to_rows_with_arity<H>(
+        &self,
+        leaf_encoding: LeafEncoding,
+        arity: usize,
+        config: &ProverConfig,
+    ) -> MerkleTree<H>
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.commit_to_rows_with_salts(leaf_encoding, arity, None, config)
+    }
+
+    /// Returns a commitment to this matrix in which rows are encoded into leaves of a Merkle tree
+    /// with the specified `arity` using the specified `leaf_encoding`, and, if `salts` are
+    /// provided, every leaf is salted with the salt of its index (see
+    /// [LeafEncoding::salted_leaf()]).
+    ///
+    /// Without salts, this is the same as
+    /// [commit_to_rows_with_arity()](Matrix::commit_to_rows_with_arity).
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Rows of this matrix cannot be encoded into leaves using the specified encoding.
+    /// * `arity` is not 2, 4, or 8.
+    /// * `salts` are provided and the encoding is [Raw](LeafEncoding::Raw).
+    pub fn commit_to_rows_with_salts<H>(
+        &self,
+        leaf_encoding: LeafEncoding,
+        arity: usize,
+        salts: Option<&LeafSalts<H>>,
+        config: &ProverConfig,
+    ) -> MerkleTree<H>
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        self.commit_to_rows_with_scheme(leaf_encoding, arity, salts, config)
+    }
+
+    /// Returns a commitment of the vector commitment scheme `V` to this matrix in which rows are
+    /// encoded into leaves the same way as in
+    /// [commit_to_rows_with_salts()](Matrix::commit_to_rows_with_salts).
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * Rows of this matrix cannot be encoded into leaves using the specified encoding.
+    /// * The scheme cannot commit to the leaves with the specified `arity`.
+    /// * `salts` are provided and the encoding is [Raw](LeafEncoding::Raw).
+    pub fn commit_to_rows_with_scheme<H, V>(
+        &self,
+        leaf_encoding: LeafEncoding,
+        arity: usize,
+        salts: Option<&LeafSalts<H>>,
+        config: &ProverConfig,
+    ) -> V
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+        V: VectorCommitment<H>,
+    {
+        let leaves = match (leaf_encoding, salts) {
+            (LeafEncoding::Hashed, None) => self.hash_rows::<H>(config),
+            (LeafEncoding::Hashed, Some(salts)) => {
+                let mut leaves = self.hash_rows::<H>(config);
+                iter_mut!(leaves).enumerate().for_each(|(i, leaf)| {
+                    *leaf = LeafEncoding::salted_leaf::<H>(*leaf, salts.get(i));
+                });
+                leaves
+            }
+            (LeafEncoding::Raw, Some(_)) => {
+                panic!("raw leaf encoding cannot be used with salted commitments")
+            }
+            (LeafEncoding::Raw, None) => {
+                // raw rows are no longer than a digest, and thus, are encoded one at a time
+                let mut leaves = unsafe { uninit_vector::<H::Digest>(self.num_rows()) };
+                iter_mut!(leaves).enumerate().for_each(|(i, leaf)| {
+                    let mut row = vec![E::ZERO; self.num_cols()];
+                    self.read_row_into(i, &mut row);
+                    *leaf = leaf_encoding.encode_row::<H, E>(&row);
+                });
+                leaves
+            }
+        };
+
+        V::with_arity(leaves, arity).expect("failed to build trace commitment")
+    }
+
+    /// Returns a commitment to this matrix hashing rows in chunks sized according to the
+    /// specified `config`.
+    ///
+    /// The resulting commitment is the same as the one returned by
+    /// [commit_to_rows()](Matrix::commit_to_rows) regardless of the config.
+    pub fn commit_to_rows_with_config<H>(&self, config: &ProverConfig) -> MerkleTree<H>
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        MerkleTree::new(self.hash_rows::<H>(config)).expect("failed to construct trace Merkle tree")
+    }
+
+    /// Returns hashes of all rows of this matrix computed in chunks sized according to the
+    /// specified `config`.
+    fn hash_rows<H>(&self, config: &ProverConfig) -> Vec<H::Digest>
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        // very wide rows are streamed into the hasher without being materialized
+        if self.num_cols() >= config.streaming_width() {
+            return self.hash_rows_streaming::<H>(config);
+        }
+
         // allocate vector to store row hashes
         let mut row_hashes = unsafe { uninit_vector::<H::Digest>(self.num_rows()) };
 
-        // iterate though matrix rows, hashing each row; the hashing is done by first copying a
-        // row into row_buf to avoid heap allocations, and then by applying the hash function to
-        // the buffer.
-        batch_iter_mut!(
-            &mut row_hashes,
-            128, // min batch size
-            |batch: &mut [H::Digest], batch_offset: usize| {
-                let mut row_buf = vec![E::ZERO; self.num_cols()];
-                for (i, row_hash) in batch.iter_mut().enumerate() {
-                    self.read_row_into(i + batch_offset, &mut row_buf);
-                    *row_hash = H::hash_elements(&row_buf);
+        // iterate though matrix rows in chunks, hashing each row; the rows of a chunk are first
+        // transposed into a row-major buffer (in cache-friendly tiles), and then the hash function
+        // is applied to all rows of the buffer at once. wider rows are hashed in smaller chunks so
+        // that the rows of a chunk stay in cache.
+        let chunk_size = config.get_chunk_size(self.num_rows(), self.num_cols() * E::ELEMENT_BYTES);
+        let view = RowMajorView::new(&self.columns);
+
+        #[cfg(not(feature = "concurrent"))]
+        let chunks = row_hashes.chunks_mut(chunk_size);
+        #[cfg(feature = "concurrent")]
+        let chunks = row_hashes.par_chunks_mut(chunk_size);
+
+        chunks.enumerate().for_each(|(chunk_idx, chunk)| {
+            let mut rows = vec![E::ZERO; chunk.len() * self.num_cols()];
+            view.read_rows_into(chunk_idx * chunk_size, &mut rows);
+            H::hash_elements_batch(&rows, self.num_cols(), chunk);
+        });
+
+        row_hashes
+    }
+
+    /// Returns hashes of all rows of this matrix computed by streaming row elements into
+    /// incremental hasher states.
+    ///
+    /// For each chunk of rows, a hasher state is kept for every row of the chunk; columns are
+    /// then visited in blocks of [STREAMING_BLOCK_WIDTH] columns, and the elements of each row in
+    /// the block are absorbed into the state of that row. Since blocks are visited in order,
+    /// every row is absorbed in the same element order as in the buffered path, and thus, the
+    /// resulting hashes are the same.
+    fn hash_rows_streaming<H>(&self, config: &ProverConfig) -> Vec<H::Digest>
+    where
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        // allocate vector to store row hashes
+        let mut row_hashes = unsafe { uninit_vector::<H::Digest>(self.num_rows()) };
+
+        // every row of a chunk touches its hasher state and a single block of elements at a time
+        let num_base_elements = self.num_cols() * E::ELEMENT_BYTES / E::BaseField::ELEMENT_BYTES;
+        let row_size = mem::size_of::<H::State>() + STREAMING_BLOCK_WIDTH * E::ELEMENT_BYTES;
+        let chunk_size = config.get_chunk_size(self.num_rows(), row_size);
+
+        #[cfg(not(feature = "concurrent"))]
+        let chunks = row_hashes.chunks_mut(chunk_size);
+        #[cfg(feature = "concurrent")]
+        let chunks = row_hashes.par_chunks_mut(chunk_size);
+
+        chunks.enumerate().for_each(|(chunk_idx, chunk)| {
+            let rows = chunk_idx * chunk_size..chunk_idx * chunk_size + chunk.len();
+            let mut states = (0..chunk.len())
+                .map(|_| H::begin(num_base_elements))
+                .collect::<Vec<_>>();
+
+            let mut block = [E::ZERO; STREAMING_BLOCK_WIDTH];
+            for columns in self.columns.chunks(STREAMING_BLOCK_WIDTH) {
+                let block = &mut block[..columns.len()];
+                for (state, row_idx) in states.iter_mut().zip(rows.clone()) {
+                    for (value, column) in block.iter_mut().zip(columns) {
+                        *value = column[row_idx];
+                    }
+                    H::absorb(state, block);
                 }
             }
-        );
 
-        // build Merkle tree out of hashed rows
-        MerkleTree::new(row_hashes).expect("failed to construct trace Merkle tree")
+            for (row_hash, state) in chunk.iter_mut().zip(states) {
+                *row_hash = H::finalize(state);
+            }
+        });
+
+        row_hashes
     }
 
     // CONVERSIONS
@@ -261,6 +497,43 @@ impl<E: FieldElement> Matrix<E> {
     }
 }
 
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<E: FieldElement> Serializable for Matrix<E> {
+    /// Serializes `self` column by column and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.num_cols() as u32);
+        target.write_u32(self.num_rows() as u32);
+        for column in self.columns() {
+            E::write_batch_into(column, target);
+        }
+    }
+}
+
+impl<E: FieldElement> Deserializable for Matrix<E> {
+    /// Reads a matrix from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid matrix could not be read from the `source`, or if the
+    /// dimensions of the matrix violate the restrictions imposed on matrixes.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_cols = source.read_u32()? as usize;
+        let num_rows = source.read_u32()? as usize;
+        if num_cols == 0 || num_rows <= 1 || !num_rows.is_power_of_two() {
+            return Err(DeserializationError::InvalidValue(format!(
+                "a matrix with {num_cols} columns and {num_rows} rows is not valid"
+            )));
+        }
+
+        let mut columns = Vec::with_capacity(num_cols);
+        for _ in 0..num_cols {
+            columns.push(E::read_batch_from(source, num_rows)?);
+        }
+        Ok(Self { columns })
+    }
+}
+
 // COLUMN ITERATOR
 // ================================================================================================
 
@@ -0,0 +1,174 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Outputs of a computation supplied by the caller of the prover.
+//!
+//! AIRs commonly assert that some cells of the execution trace (e.g., the last row) are equal to
+//! values from public inputs. When the prover derives these values by reading them from the
+//! trace, a buggy trace does not make proof generation fail; instead, it silently changes the
+//! statement being proven. A prover which is given [ExpectedOutputs] instead checks the trace
+//! against them before proving (see [Prover::expected_outputs()](crate::Prover::expected_outputs)),
+//! and is expected to build its public inputs from the expected values, so that the proven
+//! statement is the one supplied by the caller.
+
+use crate::{ProverError, Trace};
+use air::AssertionStep;
+use math::StarkField;
+use utils::{collections::Vec, string::ToString};
+
+// EXPECTED OUTPUTS
+// ================================================================================================
+
+/// A set of values which specific cells of the main segment of an execution trace are expected
+/// to contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedOutputs<B: StarkField> {
+    outputs: Vec<(usize, AssertionStep, B)>,
+}
+
+impl<B: StarkField> ExpectedOutputs<B> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns an empty set of expected outputs.
+    pub fn new() -> Self {
+        Self {
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Returns this set extended with the expectation that the specified column of the main
+    /// trace segment contains `value` at the specified step.
+    pub fn with_output(mut self, column: usize, step: AssertionStep, value: B) -> Self {
+        self.outputs.push((column, step, value));
+        self
+    }
+
+    /// Returns this set extended with the expectation that the specified column of the main
+    /// trace segment contains `value` at the last step of the trace.
+    pub fn with_final_output(self, column: usize, value: B) -> Self {
+        self.with_output(column, AssertionStep::last_minus(0), value)
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of expected outputs in this set.
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Returns true if this set contains no expected outputs.
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// Returns the expected values in the order in which they were added to this set.
+    pub fn values(&self) -> Vec<B> {
+        self.outputs.iter().map(|&(_, _, value)| value).collect()
+    }
+
+    // CHECKS
+    // --------------------------------------------------------------------------------------------
+
+    /// Checks that the main segment of the provided trace contains all expected outputs.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * An output is placed against a column or a step outside of the main trace segment.
+    /// * A cell of the main trace segment does not contain the expected value; the error is
+    ///   reported for the first such output in the order in which outputs were added to this set.
+    pub fn check<T: Trace<BaseField = B>>(&self, trace: &T) -> Result<(), ProverError> {
+        let main_segment = trace.main_segment();
+        for &(column, step, expected) in self.outputs.iter() {
+            let step = step
+                .clamped_or_error(trace.length())
+                .map_err(ProverError::InvalidAssertion)?;
+            if column >= main_segment.num_cols() {
+                return Err(ProverError::UnexpectedOutput {
+                    column,
+                    step,
+                    expected: expected.to_string(),
+                    actual: None,
+                });
+            }
+
+            let actual = main_segment.get(column, step);
+            if actual != expected {
+                return Err(ProverError::UnexpectedOutput {
+                    column,
+                    step,
+                    expected: expected.to_string(),
+                    actual: Some(actual.to_string()),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: StarkField> Default for ExpectedOutputs<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(all(test, feature = "f128"))]
+mod tests {
+    use super::ExpectedOutputs;
+    use crate::{tests::build_fib_trace, ProverError, Trace};
+    use air::{AssertionError, AssertionStep};
+    use math::{fields::f128::BaseElement, FieldElement};
+    use utils::string::ToString;
+
+    #[test]
+    fn check_expected_outputs() {
+        let trace = build_fib_trace(16);
+        let last = trace.length() - 1;
+        let result = trace.main_segment().get(1, last);
+
+        // matching outputs
+        let outputs = ExpectedOutputs::new()
+            .with_output(0, AssertionStep::new(0), BaseElement::ONE)
+            .with_final_output(1, result);
+        assert_eq!(Ok(()), outputs.check(&trace));
+        assert_eq!(vec![BaseElement::ONE, result], outputs.values());
+
+        // the first mismatching output is reported
+        let wrong = result + BaseElement::ONE;
+        let outputs = ExpectedOutputs::new()
+            .with_final_output(1, wrong)
+            .with_output(0, AssertionStep::new(0), BaseElement::ZERO);
+        assert_eq!(
+            Err(ProverError::UnexpectedOutput {
+                column: 1,
+                step: last,
+                expected: wrong.to_string(),
+                actual: Some(result.to_string()),
+            }),
+            outputs.check(&trace)
+        );
+
+        // outputs outside of the main trace segment
+        let outputs = ExpectedOutputs::new().with_final_output(2, result);
+        assert!(matches!(
+            outputs.check(&trace),
+            Err(ProverError::UnexpectedOutput { actual: None, .. })
+        ));
+        let outputs = ExpectedOutputs::new().with_output(0, AssertionStep::new(8), result);
+        assert_eq!(
+            Err(ProverError::InvalidAssertion(
+                AssertionError::StepOutOfTrace {
+                    step: 8,
+                    trace_len: 8
+                }
+            )),
+            outputs.check(&trace)
+        );
+    }
+}
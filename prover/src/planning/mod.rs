@@ -0,0 +1,578 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Estimation of proving time and memory without generating a proof.
+//!
+//! Proving cost is dominated by a small number of operations: NTTs used to interpolate and extend
+//! trace and composition polynomials, hashing used to build Merkle trees and to grind the query
+//! seed, and evaluations of transition constraints over the constraint evaluation domain. This
+//! module counts these operations analytically for a given trace shape and set of proof options
+//! (see [estimate_prover_cost()]), and converts the counts into wall-clock time using per-operation
+//! costs measured on the current machine (see [MachineProfile::calibrate()]).
+//!
+//! The estimates are approximate: they ignore operations whose cost does not grow with the size
+//! of the trace (e.g., building query openings), and assume that concurrent work is spread
+//! perfectly across all available threads. Building the execution trace is done outside of the
+//! prover, and thus, is not included in the estimates; in debug builds, however, the estimates
+//! include the expensive checks of the trace and of constraint degrees which the prover performs
+//! only in such builds. Estimated peak memory accounts only for the largest
+//! tables held by the prover at the same time (trace and composition polynomial LDEs, Merkle
+//! trees, and FRI layers).
+
+use air::{Air, EvaluationFrame, ProofOptions, TraceInfo, TransitionDivisorKind};
+use core::{
+    fmt, mem,
+    ops::Add,
+    sync::atomic::{AtomicU8, Ordering},
+    time::Duration,
+};
+use crypto::{Digest, ElementHasher, Hasher};
+use math::{fft, log2, FieldElement, StarkField};
+use std::time::Instant;
+use utils::{collections::Vec, Serializable};
+
+#[cfg(all(test, feature = "f128"))]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of times each microbenchmark is repeated during calibration; the fastest run is used.
+const NUM_CALIBRATION_RUNS: usize = 5;
+
+/// Size of the domain over which NTT cost is measured.
+const NTT_PROBE_SIZE: usize = 1 << 12;
+
+/// Number of field operations, hash merges, and hashed rows in a single calibration run.
+const OP_PROBE_SIZE: usize = 1 << 12;
+
+/// Ratio between the number of field operations and the number of inversions in a single
+/// calibration run; inversions are much more expensive than other field operations.
+const INVERSION_PROBE_RATIO: usize = 16;
+
+/// Number of elements in every row hashed during calibration.
+const HASH_PROBE_ROW_WIDTH: usize = 8;
+
+/// Number of evaluation frames over which transition constraints are evaluated during
+/// calibration.
+const CONSTRAINT_PROBE_SIZE: usize = 1 << 8;
+
+/// Number of field operations performed for every element of a FRI layer when the layer is
+/// folded.
+const FRI_FOLDING_OPS: u64 = 4;
+
+/// Number of field operations performed for every element divided by a constraint divisor; the
+/// divisor is inverted in batches, which takes three multiplications per element.
+const DIVISION_OPS: u64 = 3;
+
+/// Accumulates a byte of the result of every calibration run; since updating this value is an
+/// observable side effect, the compiler cannot optimize away the work done by the runs.
+static CALIBRATION_SINK: AtomicU8 = AtomicU8::new(0);
+
+// UNIT COSTS
+// ================================================================================================
+
+/// Costs (in nanoseconds) of operations which dominate proof generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitCosts {
+    /// Cost of a single NTT butterfly in the base field; an NTT over a domain of size $n$
+    /// performs $n \cdot \log_2 n$ butterflies.
+    pub ntt_butterfly: f64,
+    /// Cost of a single multiplication followed by an addition in the base field.
+    pub field_op: f64,
+    /// Cost of a single inversion in the base field.
+    pub field_inversion: f64,
+    /// Cost of hashing a single base field element into a leaf of a Merkle tree.
+    pub hashed_element: f64,
+    /// Cost of merging two digests into one.
+    pub hash_merge: f64,
+    /// Cost of evaluating all main transition constraints of an AIR over a single frame in the
+    /// base field.
+    pub constraint_row: f64,
+}
+
+// MACHINE PROFILE
+// ================================================================================================
+
+/// Costs of operations performed by the prover measured on the current machine for a specific
+/// AIR and hash function.
+///
+/// Besides per-operation costs, a profile records properties of the AIR and the hash function
+/// which are needed to count operations (e.g., the constraint evaluation blowup factor, the
+/// numbers of constraints and constraint divisors, and the sizes of field elements and digests).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineProfile {
+    costs: UnitCosts,
+    ce_blowup_factor: usize,
+    num_constraints: usize,
+    num_transition_constraints: usize,
+    num_transition_divisors: usize,
+    num_divisors: usize,
+    element_bytes: usize,
+    digest_bytes: usize,
+    num_threads: usize,
+}
+
+impl MachineProfile {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a profile with per-operation costs measured by running small microbenchmarks on
+    /// the current machine.
+    ///
+    /// NTT and field operation costs are measured in the base field of the AIR, and hashing costs
+    /// are measured using hash function `H`. Cost of constraint evaluation is measured by
+    /// evaluating transition constraints of the specified AIR over a set of synthetic frames.
+    /// Calibration takes a few milliseconds in release builds.
+    pub fn calibrate<A, H>(air: &A) -> Self
+    where
+        A: Air,
+        H: ElementHasher<BaseField = A::BaseField>,
+    {
+        let costs = UnitCosts {
+            ntt_butterfly: measure_ntt::<A::BaseField>(),
+            field_op: measure_field_op::<A::BaseField>(),
+            field_inversion: measure_field_inversion::<A::BaseField>(),
+            hashed_element: measure_hashed_element::<A::BaseField, H>(),
+            hash_merge: measure_hash_merge::<H>(),
+            constraint_row: measure_constraint_row(air),
+        };
+        Self::with_costs::<A, H>(air, costs)
+    }
+
+    /// Returns a profile with the specified per-operation costs for the specified AIR and hash
+    /// function.
+    ///
+    /// Boundary constraint divisors are counted only for assertions against the main trace
+    /// segment, since assertions against auxiliary segments depend on random elements which are
+    /// not known before a proof is generated.
+    pub fn with_costs<A, H>(air: &A, costs: UnitCosts) -> Self
+    where
+        A: Air,
+        H: Hasher,
+    {
+        #[cfg(feature = "concurrent")]
+        let num_threads = utils::rayon::current_num_threads();
+        #[cfg(not(feature = "concurrent"))]
+        let num_threads = 1;
+
+        // transition constraints share one divisor, or two if any of the constraints are cyclic;
+        // boundary constraints share a divisor when their assertions have the same stride, first
+        // step and window length
+        let context = air.context();
+        let num_transition_divisors = if context
+            .main_transition_constraint_degrees()
+            .iter()
+            .chain(context.aux_transition_constraint_degrees())
+            .any(|degree| degree.divisor() == TransitionDivisorKind::Cyclic)
+        {
+            2
+        } else {
+            1
+        };
+        let mut assertion_groups = air
+            .get_assertions()
+            .iter()
+            .map(|assertion| {
+                let window_length = assertion.window_length();
+                (assertion.stride(), assertion.first_step(), window_length)
+            })
+            .collect::<Vec<_>>();
+        assertion_groups.sort_unstable();
+        assertion_groups.dedup();
+        let num_transition_constraints =
+            context.num_main_transition_constraints() + context.num_aux_transition_constraints();
+
+        MachineProfile {
+            costs,
+            ce_blowup_factor: air.ce_blowup_factor(),
+            num_constraints: num_transition_constraints + context.num_assertions(),
+            num_transition_constraints,
+            num_transition_divisors,
+            num_divisors: num_transition_divisors + assertion_groups.len(),
+            element_bytes: A::BaseField::ELEMENT_BYTES,
+            digest_bytes: mem::size_of::<H::Digest>(),
+            num_threads,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns per-operation costs recorded in this profile.
+    pub fn costs(&self) -> &UnitCosts {
+        &self.costs
+    }
+
+    /// Returns the number of threads across which the prover spreads its work.
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the time it takes to perform the specified operations on this machine.
+    fn time(&self, ops: &OpCounts) -> Duration {
+        let costs = &self.costs;
+        let nanos = ops.ntt_butterflies as f64 * costs.ntt_butterfly
+            + ops.field_ops as f64 * costs.field_op
+            + ops.field_inversions as f64 * costs.field_inversion
+            + ops.hashed_elements as f64 * costs.hashed_element
+            + ops.hash_merges as f64 * costs.hash_merge
+            + ops.constraint_rows as f64 * costs.constraint_row;
+        Duration::from_nanos((nanos / self.num_threads as f64) as u64)
+    }
+}
+
+// OPERATION COUNTS
+// ================================================================================================
+
+/// Numbers of operations which dominate a phase of proof generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    /// Number of NTT butterflies in the base field; NTTs in extension fields are counted as
+    /// several NTTs in the base field.
+    pub ntt_butterflies: u64,
+    /// Number of multiplications followed by additions in the base field.
+    pub field_ops: u64,
+    /// Number of inversions in the base field.
+    pub field_inversions: u64,
+    /// Number of base field elements hashed into leaves of Merkle trees.
+    pub hashed_elements: u64,
+    /// Number of merges of two digests into one.
+    pub hash_merges: u64,
+    /// Number of frames over which main transition constraints are evaluated.
+    pub constraint_rows: u64,
+}
+
+impl Add for OpCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        OpCounts {
+            ntt_butterflies: self.ntt_butterflies + other.ntt_butterflies,
+            field_ops: self.field_ops + other.field_ops,
+            field_inversions: self.field_inversions + other.field_inversions,
+            hashed_elements: self.hashed_elements + other.hashed_elements,
+            hash_merges: self.hash_merges + other.hash_merges,
+            constraint_rows: self.constraint_rows + other.constraint_rows,
+        }
+    }
+}
+
+// COST ESTIMATE
+// ================================================================================================
+
+/// Estimated cost of generating a proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// Operations needed to extend and commit to all trace segments.
+    pub trace_ops: OpCounts,
+    /// Operations needed to evaluate constraints and to build and commit to the constraint
+    /// composition polynomial.
+    pub constraint_ops: OpCounts,
+    /// Operations needed to build the DEEP composition polynomial, to build and commit to FRI
+    /// layers, and to grind the query seed.
+    pub fri_ops: OpCounts,
+    /// Estimated time of extending and committing to all trace segments.
+    pub trace_time: Duration,
+    /// Estimated time of building and committing to the constraint composition polynomial.
+    pub constraint_time: Duration,
+    /// Estimated time of the FRI phase (including query seed grinding).
+    pub fri_time: Duration,
+    /// Estimated peak memory (in bytes) held by the prover.
+    pub peak_memory: usize,
+}
+
+impl CostEstimate {
+    /// Returns the estimated wall-clock time of generating a proof.
+    pub fn total_time(&self) -> Duration {
+        self.trace_time + self.constraint_time + self.fri_time
+    }
+
+    /// Returns the total number of operations needed to generate a proof.
+    pub fn total_ops(&self) -> OpCounts {
+        self.trace_ops + self.constraint_ops + self.fri_ops
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "estimated proving time: {} ms (trace: {} ms, constraints: {} ms, fri: {} ms)",
+            self.total_time().as_millis(),
+            self.trace_time.as_millis(),
+            self.constraint_time.as_millis(),
+            self.fri_time.as_millis()
+        )?;
+        write!(
+            f,
+            "estimated peak memory: {:.1} MB",
+            self.peak_memory as f64 / (1024 * 1024) as f64
+        )
+    }
+}
+
+// COST ESTIMATION
+// ================================================================================================
+
+/// Returns estimated time and peak memory of generating a proof for an execution trace described
+/// by `trace_info` using the specified proof options on a machine described by `profile`.
+///
+/// The profile must have been built for the AIR and the hash function which are used to generate
+/// the proof; the trace described by `trace_info` does not need to be the same as the trace for
+/// which the AIR was instantiated during calibration.
+pub fn estimate_prover_cost(
+    trace_info: &TraceInfo,
+    options: &ProofOptions,
+    profile: &MachineProfile,
+) -> CostEstimate {
+    let n = trace_info.length() as u64;
+    let lde_size = n * options.blowup_factor() as u64;
+    let extension = options.field_extension().degree() as u64;
+    let main_width = trace_info.layout().main_trace_width() as u64;
+    let aux_width = trace_info.layout().aux_trace_width() as u64 * extension;
+    let num_trace_segments = trace_info.layout().num_segments() as u64;
+    let ce_blowup = profile.ce_blowup_factor as u64;
+    let ce_domain_size = n * ce_blowup;
+    let num_ood_points = options.num_ood_points() as u64;
+    let composition_width = ce_blowup * extension * num_ood_points;
+
+    // --- trace ----------------------------------------------------------------------------------
+    // every column is interpolated over the trace domain and evaluated over the LDE domain, and
+    // rows of the LDE are hashed into Merkle trees (one per segment); in debug builds, the prover
+    // also validates the trace by evaluating constraints over every step of the trace, and
+    // interpolates the main segment once more to check the trace polynomials
+    let trace_width = main_width + aux_width;
+    let mut trace_ops = OpCounts {
+        ntt_butterflies: trace_width * (ntt_butterflies(n) + ntt_butterflies(lde_size)),
+        hashed_elements: trace_width * lde_size,
+        hash_merges: num_trace_segments * (lde_size - 1),
+        ..Default::default()
+    };
+    if cfg!(debug_assertions) {
+        trace_ops.ntt_butterflies += main_width * ntt_butterflies(n);
+        trace_ops.constraint_rows = n;
+    }
+
+    // --- constraints ----------------------------------------------------------------------------
+    // constraints are evaluated over the constraint evaluation domain, merged using random
+    // coefficients (separately for every out-of-domain point), and divided by their divisors;
+    // the resulting evaluations are interpolated into one composition polynomial per point,
+    // which is then split into columns, each of which is evaluated over the LDE domain
+    let num_constraints = profile.num_constraints as u64;
+    let num_divisors = profile.num_divisors as u64;
+    let mut constraint_ops = OpCounts {
+        ntt_butterflies: num_ood_points * extension * ntt_butterflies(ce_domain_size),
+        field_ops: num_ood_points
+            * extension
+            * ce_domain_size
+            * (2 * num_constraints + DIVISION_OPS * num_divisors),
+        constraint_rows: ce_domain_size,
+        ..Default::default()
+    };
+    if options.uses_explicit_composition(trace_info.length()) {
+        constraint_ops.hashed_elements = composition_width * n;
+    } else {
+        constraint_ops.ntt_butterflies += composition_width * ntt_butterflies(lde_size);
+        constraint_ops.hashed_elements = composition_width * lde_size;
+        constraint_ops.hash_merges = lde_size - 1;
+    }
+
+    // in debug builds, the prover also checks the degree of every transition constraint and of
+    // every column of the constraint evaluation table; every check divides evaluations by
+    // divisor values (computed directly, via an exponentiation and an inversion per element),
+    // and interpolates the results
+    if cfg!(debug_assertions) {
+        let num_transition_constraints = profile.num_transition_constraints as u64;
+        let num_divisor_evaluations = profile.num_transition_divisors as u64 + num_divisors;
+        let num_checks = extension * (num_transition_constraints + num_divisors);
+        constraint_ops.ntt_butterflies += num_checks * ntt_butterflies(ce_domain_size);
+        constraint_ops.field_ops +=
+            2 * log2(trace_info.length()) as u64 * num_divisor_evaluations * ce_domain_size;
+        constraint_ops.field_inversions += (num_checks + num_divisor_evaluations) * ce_domain_size;
+    }
+
+    // --- fri ------------------------------------------------------------------------------------
+    // the DEEP composition polynomial combines all trace polynomials and the composition
+    // polynomial columns of the point divided by (x - z) for every out-of-domain point z, and is
+    // evaluated over the LDE domain; every FRI layer is then committed to and folded; finally,
+    // the query seed is ground with the expected number of hash evaluations
+    let deep_width = trace_width + composition_width / num_ood_points;
+    let mut fri_ops = OpCounts {
+        ntt_butterflies: extension * ntt_butterflies(lde_size),
+        field_ops: 2 * extension * deep_width * num_ood_points * n,
+        hash_merges: 1 << options.grinding_factor(),
+        ..Default::default()
+    };
+    let fri_options = options.to_fri_options();
+    let folding_factor = fri_options.folding_factor() as u64;
+    let mut layer_size = lde_size;
+    for _ in 0..fri_options.num_fri_layers(lde_size as usize) {
+        fri_ops.hashed_elements += extension * layer_size;
+        fri_ops.hash_merges += layer_size / folding_factor - 1;
+        fri_ops.field_ops += FRI_FOLDING_OPS * extension * layer_size;
+        layer_size /= folding_factor;
+    }
+
+    CostEstimate {
+        trace_time: profile.time(&trace_ops),
+        constraint_time: profile.time(&constraint_ops),
+        fri_time: profile.time(&fri_ops),
+        trace_ops,
+        constraint_ops,
+        fri_ops,
+        peak_memory: estimate_peak_memory(trace_info, options, profile) as usize,
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of butterflies in an NTT over a domain of the specified size.
+fn ntt_butterflies(domain_size: u64) -> u64 {
+    domain_size * log2(domain_size as usize) as u64
+}
+
+/// Returns the estimated peak memory (in bytes) held by the prover.
+///
+/// Trace polynomials, trace and composition polynomial LDEs, and Merkle trees built from these
+/// LDEs are held until the end of proof generation. On top of these, the prover holds either
+/// the constraint evaluation table (while building the composition polynomial), or the DEEP
+/// composition polynomial evaluations together with FRI layers (during the FRI phase).
+fn estimate_peak_memory(
+    trace_info: &TraceInfo,
+    options: &ProofOptions,
+    profile: &MachineProfile,
+) -> u64 {
+    let n = trace_info.length() as u64;
+    let lde_size = n * options.blowup_factor() as u64;
+    let extension = options.field_extension().degree() as u64;
+    let element_bytes = profile.element_bytes as u64;
+    let digest_bytes = profile.digest_bytes as u64;
+    let trace_width = (trace_info.layout().main_trace_width()
+        + trace_info.layout().aux_trace_width() * extension as usize) as u64;
+    let composition_width =
+        profile.ce_blowup_factor as u64 * extension * options.num_ood_points() as u64;
+    let num_trees = trace_info.layout().num_segments() as u64 + 1;
+
+    // execution trace, trace polynomials, and trace LDE
+    let trace_bytes = (2 * n + lde_size) * trace_width * element_bytes;
+    // composition polynomial columns and their LDE
+    let composition_bytes = (n + lde_size) * composition_width * element_bytes;
+    // Merkle trees for all trace segments and for the composition polynomial
+    let tree_bytes = num_trees * 2 * lde_size * digest_bytes;
+
+    // constraint evaluation table is freed before the FRI phase begins; FRI layers and their
+    // Merkle trees together are at most twice the size of the first layer
+    let evaluation_bytes = n * composition_width * element_bytes;
+    let fri_bytes = 2 * lde_size * (extension * element_bytes + digest_bytes);
+
+    trace_bytes + composition_bytes + tree_bytes + evaluation_bytes.max(fri_bytes)
+}
+
+// CALIBRATION
+// ================================================================================================
+
+/// Returns the time (in nanoseconds) of the fastest of several runs of `run`, divided by the
+/// number of operations performed in a single run.
+///
+/// Every run must return a byte which depends on the result of the work done by the run.
+fn measure<F: FnMut() -> u8>(num_ops: usize, mut run: F) -> f64 {
+    let mut best = Duration::MAX;
+    for _ in 0..NUM_CALIBRATION_RUNS {
+        let now = Instant::now();
+        CALIBRATION_SINK.fetch_xor(run(), Ordering::Relaxed);
+        best = best.min(now.elapsed());
+    }
+    best.as_nanos() as f64 / num_ops as f64
+}
+
+/// Returns a vector of the specified length filled with arbitrary field elements.
+fn probe_values<E: FieldElement>(length: usize) -> Vec<E> {
+    (0..length as u64)
+        .map(|i| E::from(i.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+        .collect()
+}
+
+/// Measures the cost of a single NTT butterfly in field `B`.
+fn measure_ntt<B: StarkField>() -> f64 {
+    let twiddles = fft::get_twiddles::<B>(NTT_PROBE_SIZE);
+    let values = probe_values::<B>(NTT_PROBE_SIZE);
+    measure(ntt_butterflies(NTT_PROBE_SIZE as u64) as usize, || {
+        let mut values = values.clone();
+        fft::evaluate_poly(&mut values, &twiddles);
+        values[0].to_bytes()[0]
+    })
+}
+
+/// Measures the cost of a single multiplication followed by an addition in field `B`.
+fn measure_field_op<B: StarkField>() -> f64 {
+    let values = probe_values::<B>(OP_PROBE_SIZE);
+    measure(OP_PROBE_SIZE, || {
+        let result = values
+            .iter()
+            .fold(B::ONE, |acc, &value| acc * value + value);
+        result.to_bytes()[0]
+    })
+}
+
+/// Measures the cost of a single inversion in field `B`.
+fn measure_field_inversion<B: StarkField>() -> f64 {
+    let values = probe_values::<B>(OP_PROBE_SIZE / INVERSION_PROBE_RATIO);
+    measure(values.len(), || {
+        let result = values
+            .iter()
+            .fold(B::ZERO, |acc, &value| acc + (value + B::ONE).inv());
+        result.to_bytes()[0]
+    })
+}
+
+/// Measures the cost of hashing a single element of field `B` using hash function `H`.
+fn measure_hashed_element<B: StarkField, H: ElementHasher<BaseField = B>>() -> f64 {
+    let values = probe_values::<B>(OP_PROBE_SIZE * HASH_PROBE_ROW_WIDTH);
+    measure(values.len(), || {
+        values
+            .chunks(HASH_PROBE_ROW_WIDTH)
+            .fold(0, |acc, row| acc ^ H::hash_elements(row).as_bytes()[0])
+    })
+}
+
+/// Measures the cost of merging two digests using hash function `H`.
+fn measure_hash_merge<H: Hasher>() -> f64 {
+    measure(OP_PROBE_SIZE, || {
+        let mut digest = H::hash(&[]);
+        for _ in 0..OP_PROBE_SIZE {
+            digest = H::merge(&[digest, digest]);
+        }
+        digest.as_bytes()[0]
+    })
+}
+
+/// Measures the cost of evaluating main transition constraints of the specified AIR over a
+/// single frame.
+fn measure_constraint_row<A: Air>(air: &A) -> f64 {
+    let frame_width = air.trace_info().main_frame_width();
+    let num_periodic_columns = air.get_periodic_column_values().len();
+    let num_constraints = air.context().num_main_transition_constraints();
+
+    let frame_offsets = air.context().frame_offsets();
+    let frame_rows = probe_values::<A::BaseField>(frame_offsets.len() * frame_width)
+        .chunks(frame_width)
+        .map(|row| row.to_vec())
+        .collect();
+    let frame = EvaluationFrame::from_offset_rows(frame_offsets, frame_rows);
+    let periodic_values = probe_values::<A::BaseField>(num_periodic_columns);
+    let mut result = vec![A::BaseField::ZERO; num_constraints];
+
+    measure(CONSTRAINT_PROBE_SIZE, || {
+        for _ in 0..CONSTRAINT_PROBE_SIZE {
+            air.evaluate_transition(&frame, &periodic_values, &mut result);
+        }
+        result.first().map_or(0, |value| value.to_bytes()[0])
+    })
+}
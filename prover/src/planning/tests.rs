@@ -0,0 +1,152 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{estimate_prover_cost, MachineProfile, OpCounts, UnitCosts};
+use crate::tests::MockAir;
+use air::{FieldExtension, ProofOptions, TraceInfo};
+use core::time::Duration;
+use crypto::hashers::Blake3_256;
+use math::fields::f128::BaseElement;
+
+type Blake3 = Blake3_256<BaseElement>;
+
+/// Unit costs under which estimated time (in nanoseconds) is equal to the number of operations.
+const UNIT_COSTS: UnitCosts = UnitCosts {
+    ntt_butterfly: 1.0,
+    field_op: 1.0,
+    field_inversion: 1.0,
+    hashed_element: 1.0,
+    hash_merge: 1.0,
+    constraint_row: 1.0,
+};
+
+#[test]
+fn estimate_op_counts() {
+    // mock AIR has a single transition constraint of degree 2, and thus, the constraint
+    // evaluation domain is twice the size of the trace domain
+    let air = MockAir::with_trace_length(1024);
+    let profile = MachineProfile::with_costs::<_, Blake3>(&air, UNIT_COSTS);
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+    let estimate = estimate_prover_cost(&TraceInfo::new(4, 1024), &options, &profile);
+
+    // 4 columns are interpolated over 2^10 steps and extended to 2^13 elements; in debug builds,
+    // the columns are interpolated once more and the trace is validated
+    let mut expected = OpCounts {
+        ntt_butterflies: 4 * (1024 * 10 + 8192 * 13),
+        hashed_elements: 4 * 8192,
+        hash_merges: 8191,
+        ..Default::default()
+    };
+    if cfg!(debug_assertions) {
+        expected.ntt_butterflies += 4 * 1024 * 10;
+        expected.constraint_rows = 1024;
+    }
+    assert_eq!(expected, estimate.trace_ops);
+
+    // a transition constraint and an assertion (which mock AIR declares, but does not define) are
+    // evaluated over 2^11 elements and divided by a single divisor; the composition polynomial is
+    // interpolated over 2^11 elements and split into 2 columns
+    let mut expected = OpCounts {
+        ntt_butterflies: 2048 * 11 + 2 * 8192 * 13,
+        field_ops: 2048 * (2 * 2 + 3),
+        hashed_elements: 2 * 8192,
+        hash_merges: 8191,
+        constraint_rows: 2048,
+        ..Default::default()
+    };
+    // in debug builds, degrees of the transition constraint and of the single evaluation column
+    // are checked; each check evaluates the divisor and interpolates the divided evaluations
+    if cfg!(debug_assertions) {
+        expected.ntt_butterflies += 2 * 2048 * 11;
+        expected.field_ops += 2 * 10 * 2 * 2048;
+        expected.field_inversions = 4 * 2048;
+    }
+    assert_eq!(expected, estimate.constraint_ops);
+
+    // FRI layers of 2^13, 2^11, and 2^9 elements are committed to; grinding factor of zero
+    // requires a single hash evaluation
+    let expected = OpCounts {
+        ntt_butterflies: 8192 * 13,
+        field_ops: 2 * 6 * 1024 + 4 * (8192 + 2048 + 512),
+        hashed_elements: 8192 + 2048 + 512,
+        hash_merges: 2047 + 511 + 127 + 1,
+        ..Default::default()
+    };
+    assert_eq!(expected, estimate.fri_ops);
+
+    // with unit costs, estimated time is equal to the number of operations
+    let ops = estimate.total_ops();
+    let num_ops = ops.ntt_butterflies
+        + ops.field_ops
+        + ops.field_inversions
+        + ops.hashed_elements
+        + ops.hash_merges
+        + ops.constraint_rows;
+    assert_eq!(Duration::from_nanos(num_ops), estimate.total_time());
+}
+
+#[test]
+fn estimate_peak_memory() {
+    let air = MockAir::with_trace_length(1024);
+    let profile = MachineProfile::with_costs::<_, Blake3>(&air, UNIT_COSTS);
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+    let estimate = estimate_prover_cost(&TraceInfo::new(4, 1024), &options, &profile);
+
+    // trace, trace polynomials, and trace LDE; composition polynomial and its LDE; two Merkle
+    // trees; and FRI layers which are larger than the constraint evaluation table
+    let expected = (2 * 1024 + 8192) * 4 * 16
+        + (1024 + 8192) * 2 * 16
+        + 2 * 2 * 8192 * 32
+        + 2 * 8192 * (16 + 32);
+    assert_eq!(expected, estimate.peak_memory);
+
+    // doubling the trace length doubles peak memory
+    let estimate = estimate_prover_cost(&TraceInfo::new(4, 2048), &options, &profile);
+    assert_eq!(2 * expected, estimate.peak_memory);
+}
+
+#[test]
+fn estimate_scales_with_options() {
+    let air = MockAir::with_trace_length(1024);
+    let profile = MachineProfile::with_costs::<_, Blake3>(&air, UNIT_COSTS);
+    let trace_info = TraceInfo::new(4, 1024);
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 256);
+    let base = estimate_prover_cost(&trace_info, &options, &profile);
+
+    // grinding adds the expected number of hash evaluations
+    let options = ProofOptions::new(32, 8, 16, FieldExtension::None, 4, 256);
+    let estimate = estimate_prover_cost(&trace_info, &options, &profile);
+    assert_eq!(
+        base.fri_ops.hash_merges + (1 << 16) - 1,
+        estimate.fri_ops.hash_merges
+    );
+    assert_eq!(base.trace_ops, estimate.trace_ops);
+
+    // explicit composition skips the LDE of the composition polynomial
+    let options = options.with_explicit_composition(1024);
+    let estimate = estimate_prover_cost(&trace_info, &options, &profile);
+    assert_eq!(0, estimate.constraint_ops.hash_merges);
+    assert!(estimate.constraint_time < base.constraint_time);
+
+    // field extension makes the constraint and FRI phases more expensive
+    let options = ProofOptions::new(32, 8, 0, FieldExtension::Quadratic, 4, 256);
+    let estimate = estimate_prover_cost(&trace_info, &options, &profile);
+    assert_eq!(base.trace_ops, estimate.trace_ops);
+    assert!(estimate.constraint_time > base.constraint_time);
+    assert!(estimate.fri_time > base.fri_time);
+}
+
+#[test]
+fn calibrate_machine_profile() {
+    let air = MockAir::with_trace_length(1024);
+    let profile = MachineProfile::calibrate::<_, Blake3>(&air);
+    let costs = profile.costs();
+    assert!(costs.ntt_butterfly > 0.0);
+    assert!(costs.field_op > 0.0);
+    assert!(costs.field_inversion > 0.0);
+    assert!(costs.hashed_element > 0.0);
+    assert!(costs.hash_merge > 0.0);
+    assert!(costs.constraint_row >= 0.0);
+}
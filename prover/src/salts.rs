@@ -0,0 +1,139 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Salts of leaves of salted trace and constraint commitments.
+//!
+//! When proof options request salted commitments (see
+//! [ProofOptions::with_zk()](air::ProofOptions::with_zk)), the prover
+//! reads a random seed from the source returned by
+//! [Prover::salt_source()](crate::Prover::salt_source) once per proof. Every commitment derives
+//! its own seed from this seed, and the salt of every leaf of a commitment is derived from the
+//! seed of the commitment and the index of the leaf. Thus, salts of all leaves are determined by
+//! a single secret seed, and salts of opened leaves can be recomputed without storing salts of
+//! all leaves.
+
+use crypto::Hasher;
+use utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of random bytes read from a salt source to build the seed of salts for a proof.
+const SEED_BYTES: usize = 32;
+
+/// Index from which the seed of salts of the combined trace column is derived; seeds of salts of
+/// trace segments are derived from the indexes of the segments.
+const COMBINED_COLUMN_INDEX: u64 = u64::MAX - 1;
+
+/// Index from which the seed of salts of the constraint commitment is derived.
+const CONSTRAINT_COMMITMENT_INDEX: u64 = u64::MAX;
+
+// SALT SOURCE
+// ================================================================================================
+
+/// A source of random bytes from which salts of salted commitments are derived.
+///
+/// Salts hide the rows of a commitment only if they cannot be predicted; thus, the bytes must be
+/// drawn from a cryptographically secure random number generator.
+pub trait SaltSource: Send + Sync {
+    /// Fills the provided buffer with random bytes.
+    fn fill_bytes(&self, dest: &mut [u8]);
+}
+
+/// A salt source backed by the random number generator of the operating system.
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OsSaltSource;
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+impl SaltSource for OsSaltSource {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        getrandom::getrandom(dest).expect("failed to read random bytes from the operating system");
+    }
+}
+
+// LEAF SALTS
+// ================================================================================================
+
+/// Salts of leaves of a salted commitment Merkle tree.
+///
+/// The salt of a leaf at index `i` is `H::merge_with_int(seed, i)`; leaves of the tree are then
+/// built via [LeafEncoding::salted_leaf()](air::LeafEncoding::salted_leaf).
+#[derive(Debug)]
+pub struct LeafSalts<H: Hasher> {
+    seed: H::Digest,
+}
+
+impl<H: Hasher> Clone for LeafSalts<H> {
+    fn clone(&self) -> Self {
+        Self { seed: self.seed }
+    }
+}
+
+impl<H: Hasher> LeafSalts<H> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns salts derived from a seed read from the specified source.
+    pub fn draw(source: &dyn SaltSource) -> Self {
+        let mut bytes = [0u8; SEED_BYTES];
+        source.fill_bytes(&mut bytes);
+        Self {
+            seed: H::hash(&bytes),
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the salt of the leaf at the specified index.
+    pub fn get(&self, leaf_index: usize) -> H::Digest {
+        H::merge_with_int(self.seed, leaf_index as u64)
+    }
+
+    /// Returns salts of the trace segment at the specified index; the main trace segment has
+    /// index 0, and auxiliary trace segments follow it.
+    pub fn trace_segment(&self, segment_index: usize) -> Self {
+        self.derive(segment_index as u64)
+    }
+
+    /// Returns salts of the combined trace column.
+    pub fn combined_column(&self) -> Self {
+        self.derive(COMBINED_COLUMN_INDEX)
+    }
+
+    /// Returns salts of the constraint commitment.
+    pub fn constraints(&self) -> Self {
+        self.derive(CONSTRAINT_COMMITMENT_INDEX)
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    fn derive(&self, index: u64) -> Self {
+        Self {
+            seed: H::merge_with_int(self.seed, index),
+        }
+    }
+}
+
+impl<H: Hasher> Serializable for LeafSalts<H> {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.seed.write_into(target);
+    }
+}
+
+impl<H: Hasher> Deserializable for LeafSalts<H> {
+    /// Reads leaf salts from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid seed could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(Self {
+            seed: H::Digest::read_from(source)?,
+        })
+    }
+}
@@ -28,6 +28,51 @@ pub fn build_fib_trace(length: usize) -> TraceTable<BaseElement> {
     TraceTable::init(vec![reg1, reg2])
 }
 
+// COUNTING ALLOCATOR
+// ================================================================================================
+
+/// A global allocator which counts the number of allocations made by each thread; this is used
+/// to make sure that hot loops of the prover do not allocate memory.
+#[cfg(feature = "std")]
+pub mod alloc_counter {
+    use core::cell::Cell;
+    use std::alloc::{GlobalAlloc, Layout, System};
+
+    struct CountingAllocator;
+
+    std::thread_local! {
+        static NUM_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            // the counter may be unavailable while a thread is being torn down
+            let _ = NUM_ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = NUM_ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Returns the number of allocations made by the current thread while executing `f`.
+    pub fn count_allocations<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+        let start = NUM_ALLOCATIONS.with(|n| n.get());
+        let result = f();
+        let end = NUM_ALLOCATIONS.with(|n| n.get());
+        (result, end - start)
+    }
+}
+
 // MOCK AIR
 // ================================================================================================
 
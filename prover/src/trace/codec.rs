@@ -0,0 +1,353 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::ColumnStatistics;
+use core::fmt;
+use math::StarkField;
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable, SliceReader,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Maximum number of distinct values in a column encoded with [ColumnCodec::Dictionary].
+const MAX_DICTIONARY_SIZE: usize = 256;
+
+/// Maximum width of values (in bits) in a column encoded with [ColumnCodec::SmallInt].
+const MAX_SMALL_INT_WIDTH: u8 = 64;
+
+// COLUMN CODEC
+// ================================================================================================
+
+/// Encoding of a single column of an execution trace in the serialized trace format.
+///
+/// Columns of many execution traces consist of bits or of small integers; storing every value
+/// of such columns as a full field element wastes most of the space. Column codecs encode such
+/// columns compactly:
+/// * `Plain` - every value is stored as a field element.
+/// * `BitPacked` - the column contains only ZERO and ONE values; every value is stored as a
+///   single bit.
+/// * `SmallInt(width)` - canonical integer representations of all values are smaller than
+///   2^`width`; every value is stored in `width` bits.
+/// * `Dictionary` - the column contains at most 256 distinct values; the distinct values are
+///   stored as field elements, and every value of the column is stored as an index into them.
+///
+/// Bits are packed into bytes starting with the least significant bit, and the last byte of a
+/// column is padded with zero bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnCodec {
+    Plain,
+    BitPacked,
+    SmallInt(u8),
+    Dictionary,
+}
+
+impl ColumnCodec {
+    // CODEC SELECTION
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the codec which encodes the specified column into the smallest number of bytes;
+    /// when several codecs yield the same size, the simpler codec is returned (in the order in
+    /// which codecs are listed in [ColumnCodec]).
+    pub fn select<B: StarkField>(column: &[B]) -> Self {
+        let stats = ColumnStatistics::new(column, MAX_DICTIONARY_SIZE + 1);
+        let mut candidates = Vec::with_capacity(3);
+        if let Some(max) = to_small_int(stats.max()) {
+            match bit_width(max) {
+                1 => candidates.push(ColumnCodec::BitPacked),
+                width => candidates.push(ColumnCodec::SmallInt(width)),
+            }
+        }
+        if stats.num_distinct() <= MAX_DICTIONARY_SIZE {
+            candidates.push(ColumnCodec::Dictionary);
+        }
+
+        let num_distinct = stats.num_distinct();
+        candidates
+            .into_iter()
+            .fold(ColumnCodec::Plain, |best, codec| {
+                let size = codec.encoded_size::<B>(column.len(), num_distinct);
+                if size < best.encoded_size::<B>(column.len(), num_distinct) {
+                    codec
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Returns true if all values of the specified column can be encoded with this codec.
+    pub fn supports<B: StarkField>(&self, column: &[B]) -> bool {
+        match self {
+            ColumnCodec::Plain => true,
+            ColumnCodec::BitPacked => column.iter().all(|&v| v == B::ZERO || v == B::ONE),
+            ColumnCodec::SmallInt(width) => {
+                (1..=MAX_SMALL_INT_WIDTH).contains(width)
+                    && column
+                        .iter()
+                        .all(|&v| to_small_int(v).map_or(false, |v| bit_width(v) <= *width))
+            }
+            ColumnCodec::Dictionary => {
+                ColumnStatistics::new(column, MAX_DICTIONARY_SIZE + 1).num_distinct()
+                    <= MAX_DICTIONARY_SIZE
+            }
+        }
+    }
+
+    /// Returns the number of bytes needed to encode a column of `num_rows` values with
+    /// `num_distinct` distinct values using this codec.
+    fn encoded_size<B: StarkField>(&self, num_rows: usize, num_distinct: usize) -> usize {
+        match self {
+            ColumnCodec::Plain => num_rows * B::ELEMENT_BYTES,
+            ColumnCodec::BitPacked => packed_size(num_rows, 1),
+            ColumnCodec::SmallInt(width) => packed_size(num_rows, *width),
+            ColumnCodec::Dictionary => {
+                1 + num_distinct * B::ELEMENT_BYTES
+                    + packed_size(num_rows, index_width(num_distinct))
+            }
+        }
+    }
+
+    // ENCODING / DECODING
+    // --------------------------------------------------------------------------------------------
+
+    /// Encodes the specified column using this codec and writes the result into the `target`.
+    ///
+    /// # Panics
+    /// Panics if the column cannot be encoded with this codec.
+    pub(crate) fn encode<B: StarkField, W: ByteWriter>(&self, column: &[B], target: &mut W) {
+        assert!(
+            self.supports(column),
+            "column cannot be encoded with {self} codec"
+        );
+        match self {
+            ColumnCodec::Plain => B::write_batch_into(column, target),
+            ColumnCodec::BitPacked => {
+                let values = column.iter().map(|&v| (v == B::ONE) as u64);
+                pack_bits(values, 1, target);
+            }
+            ColumnCodec::SmallInt(width) => {
+                let values = column.iter().map(|&v| to_small_int(v).unwrap());
+                pack_bits(values, *width, target);
+            }
+            ColumnCodec::Dictionary => {
+                // the dictionary is sorted by canonical integer representations of its values so
+                // that a decoder can make sure that the dictionary contains no duplicates
+                let mut dictionary = Vec::<B>::new();
+                for &value in column {
+                    if let Err(position) = find_in_dictionary(&dictionary, value) {
+                        dictionary.insert(position, value);
+                    }
+                }
+                target.write_u8((dictionary.len() - 1) as u8);
+                B::write_batch_into(&dictionary, target);
+                let indexes = column.iter().map(|&v| {
+                    find_in_dictionary(&dictionary, v).expect("value is in the dictionary") as u64
+                });
+                pack_bits(indexes, index_width(dictionary.len()), target);
+            }
+        }
+    }
+
+    /// Reads a column of `num_rows` values encoded with this codec from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if the `source` does not contain a valid encoding of a column, or if any
+    /// of the values does not decode to a canonical field element.
+    pub(crate) fn decode<B: StarkField, R: ByteReader>(
+        &self,
+        source: &mut R,
+        num_rows: usize,
+    ) -> Result<Vec<B>, DeserializationError> {
+        match self {
+            ColumnCodec::Plain => B::read_batch_from(source, num_rows),
+            ColumnCodec::BitPacked => {
+                let values = unpack_bits(source, num_rows, 1)?;
+                Ok(values.into_iter().map(B::from).collect())
+            }
+            ColumnCodec::SmallInt(width) => unpack_bits(source, num_rows, *width)?
+                .into_iter()
+                .map(from_small_int)
+                .collect(),
+            ColumnCodec::Dictionary => {
+                let size = source.read_u8()? as usize + 1;
+                let dictionary = B::read_batch_from(source, size)?;
+                for i in 1..size {
+                    if dictionary[i - 1].as_int() >= dictionary[i].as_int() {
+                        return Err(DeserializationError::InvalidValue(
+                            "dictionary values must be distinct and sorted".to_string(),
+                        ));
+                    }
+                }
+                unpack_bits(source, num_rows, index_width(size))?
+                    .into_iter()
+                    .map(|index| {
+                        dictionary.get(index as usize).copied().ok_or_else(|| {
+                            DeserializationError::InvalidValue(format!(
+                                "dictionary index {index} is out of bounds for a dictionary \
+                                of {size} values"
+                            ))
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for ColumnCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnCodec::Plain => write!(f, "plain"),
+            ColumnCodec::BitPacked => write!(f, "bit-packed"),
+            ColumnCodec::SmallInt(width) => write!(f, "{width}-bit integer"),
+            ColumnCodec::Dictionary => write!(f, "dictionary"),
+        }
+    }
+}
+
+impl Serializable for ColumnCodec {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        match self {
+            ColumnCodec::Plain => target.write_u8(0),
+            ColumnCodec::BitPacked => target.write_u8(1),
+            ColumnCodec::SmallInt(width) => {
+                target.write_u8(2);
+                target.write_u8(*width);
+            }
+            ColumnCodec::Dictionary => target.write_u8(3),
+        }
+    }
+}
+
+impl Deserializable for ColumnCodec {
+    /// Reads a column codec from the specified `source` and returns the result.
+    ///
+    /// # Errors
+    /// Returns an error if the codec tag is not valid, or if the width of small integers is zero
+    /// or greater than 64.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        match source.read_u8()? {
+            0 => Ok(ColumnCodec::Plain),
+            1 => Ok(ColumnCodec::BitPacked),
+            2 => match source.read_u8()? {
+                width @ 1..=MAX_SMALL_INT_WIDTH => Ok(ColumnCodec::SmallInt(width)),
+                width => Err(DeserializationError::InvalidValue(format!(
+                    "small integer width must be between 1 and {MAX_SMALL_INT_WIDTH}, but was {width}"
+                ))),
+            },
+            3 => Ok(ColumnCodec::Dictionary),
+            tag => Err(DeserializationError::InvalidValue(format!(
+                "column codec tag {tag} is not valid"
+            ))),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the canonical integer representation of the specified value if it fits into 64 bits.
+fn to_small_int<B: StarkField>(value: B) -> Option<u64> {
+    let bytes = value.to_bytes();
+    let (low, high) = bytes.split_at(8);
+    high.iter()
+        .all(|&b| b == 0)
+        .then(|| u64::from_le_bytes(low.try_into().expect("slice has 8 bytes")))
+}
+
+/// Returns the field element with the specified canonical integer representation.
+///
+/// # Errors
+/// Returns an error if the value is not smaller than the field modulus.
+fn from_small_int<B: StarkField>(value: u64) -> Result<B, DeserializationError> {
+    let mut bytes = vec![0u8; B::ELEMENT_BYTES];
+    bytes[..8].copy_from_slice(&value.to_le_bytes());
+    B::read_from(&mut SliceReader::new(&bytes))
+}
+
+/// Returns the number of bits needed to represent the specified value; at least one bit is
+/// needed for every value.
+fn bit_width(value: u64) -> u8 {
+    (u64::BITS - value.leading_zeros()).max(1) as u8
+}
+
+/// Returns the number of bits needed to encode indexes into a dictionary of the specified size.
+fn index_width(dictionary_size: usize) -> u8 {
+    (usize::BITS - (dictionary_size - 1).leading_zeros()) as u8
+}
+
+/// Returns the number of bytes needed to pack `num_values` values of `width` bits each.
+fn packed_size(num_values: usize, width: u8) -> usize {
+    (num_values * width as usize + 7) / 8
+}
+
+/// Looks up the specified value in a dictionary sorted by canonical integer representations.
+fn find_in_dictionary<B: StarkField>(dictionary: &[B], value: B) -> Result<usize, usize> {
+    let value = value.as_int();
+    dictionary.binary_search_by(|v| {
+        v.as_int()
+            .partial_cmp(&value)
+            .expect("integers must be comparable")
+    })
+}
+
+/// Writes the lowest `width` bits of every value into the `target`, starting with the least
+/// significant bit.
+fn pack_bits<W: ByteWriter>(values: impl Iterator<Item = u64>, width: u8, target: &mut W) {
+    let (mut buffer, mut num_bits) = (0u128, 0u32);
+    for value in values {
+        buffer |= (value as u128) << num_bits;
+        num_bits += width as u32;
+        while num_bits >= 8 {
+            target.write_u8(buffer as u8);
+            buffer >>= 8;
+            num_bits -= 8;
+        }
+    }
+    if num_bits > 0 {
+        target.write_u8(buffer as u8);
+    }
+}
+
+/// Reads `num_values` values of `width` bits each packed by [pack_bits()] from the `source`.
+///
+/// # Errors
+/// Returns an error if the `source` does not contain enough bytes, or if the bits padding the
+/// last byte are not all zeros.
+fn unpack_bits<R: ByteReader>(
+    source: &mut R,
+    num_values: usize,
+    width: u8,
+) -> Result<Vec<u64>, DeserializationError> {
+    let bytes = source.read_u8_vec(packed_size(num_values, width))?;
+    let mask = if width == 64 {
+        u64::MAX
+    } else {
+        (1u64 << width) - 1
+    };
+
+    let mut result = Vec::with_capacity(num_values);
+    let (mut buffer, mut num_bits) = (0u128, 0u32);
+    let mut bytes = bytes.into_iter();
+    for _ in 0..num_values {
+        while num_bits < width as u32 {
+            let byte = bytes.next().expect("packed values are not truncated");
+            buffer |= (byte as u128) << num_bits;
+            num_bits += 8;
+        }
+        result.push(buffer as u64 & mask);
+        buffer >>= width;
+        num_bits -= width as u32;
+    }
+    if buffer != 0 {
+        return Err(DeserializationError::InvalidValue(
+            "bits padding packed values must be zeros".to_string(),
+        ));
+    }
+    Ok(result)
+}
@@ -3,11 +3,18 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::Matrix;
-use air::proof::Queries;
-use crypto::{ElementHasher, MerkleTree};
+use crate::{LeafSalts, Matrix};
+use air::{
+    proof::{CommitmentLayout, Queries},
+    LdePos, LeafEncoding,
+};
+use core::ops::Range;
+use crypto::{CommitmentOpener, ElementHasher, MerkleTree, VectorCommitment};
 use math::FieldElement;
-use utils::collections::Vec;
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable,
+};
 
 use super::TraceLde;
 
@@ -18,43 +25,110 @@ use super::TraceLde;
 ///
 /// The describes one or more trace segments, each consisting of the following components:
 /// * Evaluations of a trace segment's polynomials over the LDE domain.
-/// * Merkle tree where each leaf in the tree corresponds to a row in the trace LDE matrix.
-pub struct TraceCommitment<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
+/// * Merkle tree where each leaf in the tree corresponds to a row in the trace LDE matrix; trees
+///   can be replaced with commitments of any other [VectorCommitment] scheme via the `V` type
+///   parameter.
+///
+/// When the trace has a preprocessed segment, the commitment also contains the Merkle tree of this
+/// segment; evaluations of the segment's polynomials are stored in the trace LDE right after the
+/// columns of the main segment.
+///
+/// When proof options enable it, the commitment also contains a combined trace column, i.e.,
+/// evaluations of a random linear combination of all trace columns over the LDE domain together
+/// with a Merkle tree built from these evaluations.
+///
+/// When proof options request salted commitments, the commitment also contains the salts from
+/// which salts of leaves of all trees (except for the tree of the preprocessed segment) are
+/// derived.
+pub struct TraceCommitment<
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H> = MerkleTree<H>,
+> {
     trace_lde: TraceLde<E>,
-    main_segment_tree: MerkleTree<H>,
-    aux_segment_trees: Vec<MerkleTree<H>>,
+    main_segment_tree: V,
+    preprocessed_width: usize,
+    preprocessed_tree: Option<V>,
+    aux_segment_trees: Vec<V>,
+    combined_column: Option<CombinedColumn<E, V>>,
+    salts: Option<LeafSalts<H>>,
 }
 
-impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceCommitment<E, H> {
+/// Combined trace column along with the random coefficient used to build it.
+struct CombinedColumn<E: FieldElement, V> {
+    gamma: E,
+    lde: Matrix<E>,
+    tree: V,
+}
+
+impl<E, H, V> TraceCommitment<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
     /// Creates a new trace commitment from the provided main trace low-degree extension and the
     /// corresponding Merkle tree commitment.
-    pub fn new(
-        main_trace_lde: Matrix<E::BaseField>,
-        main_trace_tree: MerkleTree<H>,
-        blowup: usize,
-    ) -> Self {
+    pub fn new(main_trace_lde: Matrix<E::BaseField>, main_trace_tree: V, blowup: usize) -> Self {
         assert_eq!(
             main_trace_lde.num_rows(),
-            main_trace_tree.leaves().len(),
+            main_trace_tree.domain_len(),
             "number of rows in trace LDE must be the same as number of leaves in trace commitment"
         );
         Self {
             trace_lde: TraceLde::new(main_trace_lde, blowup),
             main_segment_tree: main_trace_tree,
+            preprocessed_width: 0,
+            preprocessed_tree: None,
             aux_segment_trees: Vec::new(),
+            combined_column: None,
+            salts: None,
         }
     }
 
+    /// Returns this trace commitment with leaves of its trees salted with salts derived from the
+    /// provided salts; the trees added to this commitment must be built with salts returned from
+    /// [LeafSalts::trace_segment()] and [LeafSalts::combined_column()].
+    pub fn with_salts(mut self, salts: Option<LeafSalts<H>>) -> Self {
+        self.salts = salts;
+        self
+    }
+
     // STATE MUTATORS
     // --------------------------------------------------------------------------------------------
 
+    /// Adds the provided preprocessed segment LDE and Merkle tree to this trace commitment.
+    ///
+    /// # Panics
+    /// Panics if the preprocessed segment has already been added, or if the number of rows in
+    /// the segment LDE is not the same as in the trace LDE.
+    pub fn add_preprocessed_segment(
+        &mut self,
+        preprocessed_lde: &Matrix<E::BaseField>,
+        preprocessed_tree: V,
+    ) {
+        assert!(
+            self.preprocessed_tree.is_none(),
+            "preprocessed segment has already been added"
+        );
+        assert_eq!(
+            preprocessed_lde.num_rows(),
+            preprocessed_tree.domain_len(),
+            "number of rows in trace LDE must be the same as number of leaves in trace commitment"
+        );
+
+        self.trace_lde.add_preprocessed_segment(preprocessed_lde);
+        self.preprocessed_width = preprocessed_lde.num_cols();
+        self.preprocessed_tree = Some(preprocessed_tree);
+    }
+
     /// Adds the provided auxiliary segment trace LDE and Merkle tree to this trace commitment.
-    pub fn add_segment(&mut self, aux_segment_lde: Matrix<E>, aux_segment_tree: MerkleTree<H>) {
+    pub fn add_segment(&mut self, aux_segment_lde: Matrix<E>, aux_segment_tree: V) {
         assert_eq!(
             aux_segment_lde.num_rows(),
-            aux_segment_tree.leaves().len(),
+            aux_segment_tree.domain_len(),
             "number of rows in trace LDE must be the same as number of leaves in trace commitment"
         );
 
@@ -62,6 +136,31 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceCommitmen
         self.aux_segment_trees.push(aux_segment_tree);
     }
 
+    /// Sets the combined trace column of this commitment to the provided LDE of the random linear
+    /// combination of all trace columns computed with coefficient `gamma` and the Merkle tree
+    /// built from this LDE.
+    ///
+    /// # Panics
+    /// Panics if the combined column has already been set, if the LDE does not consist of a
+    /// single column, or if the number of its rows is not the same as in the trace LDE.
+    pub fn set_combined_column(&mut self, gamma: E, lde: Matrix<E>, tree: V) {
+        assert!(
+            self.combined_column.is_none(),
+            "combined trace column has already been set"
+        );
+        assert_eq!(
+            1,
+            lde.num_cols(),
+            "combined trace column must consist of one column"
+        );
+        assert_eq!(
+            self.trace_lde.trace_len(),
+            tree.domain_len(),
+            "number of rows in trace LDE must be the same as number of leaves in trace commitment"
+        );
+        self.combined_column = Some(CombinedColumn { gamma, lde, tree });
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
@@ -72,22 +171,109 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceCommitmen
         &self.trace_lde
     }
 
+    /// Returns the coefficient used to build the combined trace column, or None if this
+    /// commitment does not contain a combined trace column.
+    pub fn combined_column_coefficient(&self) -> Option<E> {
+        self.combined_column.as_ref().map(|column| column.gamma)
+    }
+
+    /// Returns the salts from which salts of leaves of this commitment are derived, or None if
+    /// leaves of this commitment are not salted.
+    pub fn salts(&self) -> Option<&LeafSalts<H>> {
+        self.salts.as_ref()
+    }
+
     // QUERY TRACE
     // --------------------------------------------------------------------------------------------
     /// Returns trace table rows at the specified positions along with Merkle authentication paths
-    /// from the commitment root to these rows.
-    pub fn query(&self, positions: &[usize]) -> Vec<Queries> {
-        // build queries for the main trace segment
+    /// from the commitment root to these rows; positions are mapped onto leaves of the commitment
+    /// trees via the specified `layout`, and the rows are assumed to be encoded into the leaves
+    /// using the specified `leaf_encoding`.
+    pub fn query(
+        &self,
+        positions: &[LdePos],
+        layout: &CommitmentLayout,
+        leaf_encoding: LeafEncoding,
+    ) -> Vec<Queries> {
+        self.query_with_opener(positions, layout, leaf_encoding, &self.main_segment_tree)
+    }
+
+    /// Returns trace table rows at the specified positions along with Merkle authentication paths
+    /// from the commitment root to these rows, the same as [TraceCommitment::query()] does, but
+    /// builds authentication paths for the main trace segment via the specified `opener` rather
+    /// than from the Merkle tree of the main segment held by this commitment.
+    ///
+    /// This is useful when the Merkle tree of the main trace segment is kept in an external
+    /// storage, and authentication paths are fetched from there.
+    ///
+    /// # Panics
+    /// Panics if the root of the tree opened by the `opener` is not the same as the root of the
+    /// main trace segment commitment, or if the `opener` fails to open the tree.
+    pub fn query_with_opener(
+        &self,
+        positions: &[LdePos],
+        layout: &CommitmentLayout,
+        leaf_encoding: LeafEncoding,
+        opener: &dyn CommitmentOpener<H, V>,
+    ) -> Vec<Queries> {
+        assert_eq!(
+            self.main_segment_tree.commitment(),
+            opener.root(),
+            "opener root must be the same as the main trace segment commitment root"
+        );
+
+        // build queries for the main trace segment; columns of the preprocessed segment (if any)
+        // follow the columns of the main segment in the trace LDE
+        let main_segment_lde = self.trace_lde.get_main_segment();
+        let main_segment_width = main_segment_lde.num_cols() - self.preprocessed_width;
         let mut result = vec![build_segment_queries(
-            self.trace_lde.get_main_segment(),
-            &self.main_segment_tree,
+            main_segment_lde,
+            0..main_segment_width,
+            opener,
             positions,
+            layout,
+            leaf_encoding,
+            self.salts.as_ref().map(|salts| salts.trace_segment(0)),
         )];
 
+        // build queries for the preprocessed trace segment (if any)
+        if let Some(preprocessed_tree) = &self.preprocessed_tree {
+            result.push(build_segment_queries(
+                main_segment_lde,
+                main_segment_width..main_segment_lde.num_cols(),
+                preprocessed_tree,
+                positions,
+                layout,
+                leaf_encoding,
+                None,
+            ));
+        }
+
         // build queries for auxiliary trace segments
         for (i, segment_tree) in self.aux_segment_trees.iter().enumerate() {
             let segment_lde = self.trace_lde.get_aux_segment(i);
-            result.push(build_segment_queries(segment_lde, segment_tree, positions));
+            result.push(build_segment_queries(
+                segment_lde,
+                0..segment_lde.num_cols(),
+                segment_tree,
+                positions,
+                layout,
+                leaf_encoding,
+                self.salts.as_ref().map(|salts| salts.trace_segment(i + 1)),
+            ));
+        }
+
+        // build queries for the combined trace column (if any)
+        if let Some(column) = &self.combined_column {
+            result.push(build_segment_queries(
+                &column.lde,
+                0..1,
+                &column.tree,
+                positions,
+                layout,
+                leaf_encoding,
+                self.salts.as_ref().map(|salts| salts.combined_column()),
+            ));
         }
 
         result
@@ -97,44 +283,186 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceCommitmen
     // --------------------------------------------------------------------------------------------
 
     /// Returns the root of the commitment Merkle tree.
-    #[cfg(test)]
+    #[cfg(all(test, feature = "f128"))]
     pub fn main_trace_root(&self) -> H::Digest {
-        *self.main_segment_tree.root()
+        self.main_segment_tree.commitment()
     }
 
     /// Returns the entire trace for the column at the specified index.
-    #[cfg(test)]
+    #[cfg(all(test, feature = "f128"))]
     pub fn get_main_trace_column(&self, col_idx: usize) -> &[E::BaseField] {
         self.trace_lde.get_main_segment().get_column(col_idx)
     }
 }
 
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<E, H, V> Serializable for TraceCommitment<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    ///
+    /// For Merkle trees, only the leaves of the trees are serialized; internal nodes of the trees
+    /// are re-computed when the commitment is deserialized.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.trace_lde.write_into(target);
+        self.main_segment_tree.write_into(target);
+        target.write_u8(self.preprocessed_width as u8);
+        if let Some(tree) = &self.preprocessed_tree {
+            tree.write_into(target);
+        }
+        for segment_tree in self.aux_segment_trees.iter() {
+            segment_tree.write_into(target);
+        }
+        match &self.combined_column {
+            Some(column) => {
+                target.write_u8(1);
+                column.gamma.write_into(target);
+                column.lde.write_into(target);
+                column.tree.write_into(target);
+            }
+            None => target.write_u8(0),
+        }
+        match &self.salts {
+            Some(salts) => {
+                target.write_u8(1);
+                salts.write_into(target);
+            }
+            None => target.write_u8(0),
+        }
+    }
+}
+
+impl<E, H, V> Deserializable for TraceCommitment<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    /// Reads a trace commitment from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid commitment could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let trace_lde = TraceLde::<E>::read_from(source)?;
+        let main_segment_tree = V::read_from(source)?;
+        let preprocessed_width = source.read_u8()? as usize;
+        if preprocessed_width >= trace_lde.main_trace_width() {
+            return Err(DeserializationError::InvalidValue(
+                "preprocessed segment must be narrower than the main trace LDE".to_string(),
+            ));
+        }
+        let preprocessed_tree = match preprocessed_width {
+            0 => None,
+            _ => Some(V::read_from(source)?),
+        };
+        let mut aux_segment_trees = Vec::new();
+        for _ in 0..trace_lde.num_aux_segments() {
+            aux_segment_trees.push(V::read_from(source)?);
+        }
+
+        let combined_column = match source.read_u8()? {
+            0 => None,
+            1 => Some(CombinedColumn {
+                gamma: E::read_from(source)?,
+                lde: Matrix::read_from(source)?,
+                tree: V::read_from(source)?,
+            }),
+            value => {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "combined trace column flag must be 0 or 1, but was {value}"
+                )))
+            }
+        };
+        if let Some(column) = &combined_column {
+            if column.lde.num_cols() != 1 || column.lde.num_rows() != trace_lde.trace_len() {
+                return Err(DeserializationError::InvalidValue(
+                    "combined trace column must consist of one column of trace LDE length"
+                        .to_string(),
+                ));
+            }
+        }
+
+        let salts = match source.read_u8()? {
+            0 => None,
+            1 => Some(LeafSalts::read_from(source)?),
+            value => {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "leaf salts flag must be 0 or 1, but was {value}"
+                )))
+            }
+        };
+
+        let mut trees = core::iter::once(&main_segment_tree)
+            .chain(preprocessed_tree.iter())
+            .chain(aux_segment_trees.iter())
+            .chain(combined_column.iter().map(|column| &column.tree));
+        if trees.any(|tree| tree.domain_len() != trace_lde.trace_len()) {
+            return Err(DeserializationError::InvalidValue(
+                "number of rows in trace LDE must be the same as number of leaves in trace commitment"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Self {
+            trace_lde,
+            main_segment_tree,
+            preprocessed_width,
+            preprocessed_tree,
+            aux_segment_trees,
+            combined_column,
+            salts,
+        })
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
-fn build_segment_queries<E, H>(
+fn build_segment_queries<E, H, V, O>(
     segment_lde: &Matrix<E>,
-    segment_tree: &MerkleTree<H>,
-    positions: &[usize],
+    columns: Range<usize>,
+    segment_tree: &O,
+    positions: &[LdePos],
+    layout: &CommitmentLayout,
+    leaf_encoding: LeafEncoding,
+    salts: Option<LeafSalts<H>>,
 ) -> Queries
 where
     E: FieldElement,
     H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+    O: CommitmentOpener<H, V> + ?Sized,
 {
     // allocate memory for queried trace states
     let mut trace_states = Vec::with_capacity(positions.len());
 
-    // copy values from the trace segment LDE at the specified positions into rows
-    // and append the rows to trace_states
+    // copy values from the specified columns of the trace segment LDE at the specified positions
+    // into rows and append the rows to trace_states
     for &i in positions.iter() {
-        let row = segment_lde.columns().map(|column| column[i]).collect();
+        let row = columns
+            .clone()
+            .map(|col_idx| segment_lde.get(col_idx, i.as_usize()))
+            .collect();
         trace_states.push(row);
     }
 
-    // build Merkle authentication paths to the leaves specified by positions
+    // build Merkle authentication paths to the leaves containing the specified positions
+    let leaf_indexes = layout.leaf_indexes(positions);
     let trace_proof = segment_tree
-        .prove_batch(positions)
+        .open(&leaf_indexes)
         .expect("failed to generate a Merkle proof for trace queries");
 
-    Queries::new(trace_proof, trace_states)
+    let salts = salts.map(|salts| leaf_indexes.iter().map(|&i| salts.get(i)).collect());
+    Queries::with_commitment_proof::<H, V, E>(
+        trace_proof,
+        trace_states,
+        &leaf_indexes,
+        leaf_encoding,
+        salts,
+    )
 }
@@ -3,23 +3,40 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{matrix::MultiColumnIter, Matrix};
-use air::{Air, AuxTraceRandElements, EvaluationFrame, TraceInfo, TraceLayout};
+use super::{matrix::MultiColumnIter, Matrix, TraceError};
+use air::{
+    Air, AuxTraceRandElements, EvaluationFrame, TraceInfo, TraceLayout, TransitionConstraintDegree,
+    TransitionDivisorKind,
+};
 use math::{polynom, FieldElement, StarkField};
+use utils::{collections::Vec, string::ToString};
 
 mod trace_lde;
 pub use trace_lde::TraceLde;
+pub(crate) use trace_lde::{read_frame_columns_into, read_frame_into};
 
 mod poly_table;
 pub use poly_table::TracePolyTable;
 
 mod trace_table;
-pub use trace_table::{TraceTable, TraceTableFragment};
+pub use trace_table::{FillStatus, TraceTable, TraceTableFragment};
 
 mod commitment;
 pub use commitment::TraceCommitment;
 
-#[cfg(test)]
+mod preprocessed;
+pub use preprocessed::PreprocessedSegment;
+
+mod codec;
+pub use codec::ColumnCodec;
+
+mod statistics;
+pub use statistics::{
+    ColumnStatistics, DegeneracyThresholds, DegenerateColumn, TraceStatistics,
+    DEFAULT_DISTINCT_VALUES_CAP,
+};
+
+#[cfg(all(test, feature = "f128"))]
 mod tests;
 
 // TRACE TRAIT
@@ -73,6 +90,14 @@ pub trait Trace: Sized {
     ) -> Option<Matrix<E>>;
 
     /// Reads an evaluation frame from the main trace segment at the specified row.
+    ///
+    /// A row of the trace should be read into each of the rows of the frame: the row at index
+    /// `row_idx + k` (wrapping around at the end of the trace) for every row offset `k` of the
+    /// frame (see [EvaluationFrame::offsets()]).
+    ///
+    /// For traces with a preprocessed segment, the frame is wider than the main trace segment;
+    /// in such cases, only the leading columns of the frame (one for each column of the main
+    /// segment) should be written to.
     fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<Self::BaseField>);
 
     // PROVIDED METHODS
@@ -97,15 +122,48 @@ pub trait Trace: Sized {
     // --------------------------------------------------------------------------------------------
     /// Checks if this trace is valid against the specified AIR, and panics if not.
     ///
+    /// If the AIR describes a trace with a preprocessed segment, `preprocessed` must contain the
+    /// columns of this segment; these are appended to the main evaluation frame.
+    ///
     /// NOTE: this is a very expensive operation and is intended for use only in debug mode.
     fn validate<A, E>(
         &self,
         air: &A,
+        preprocessed: Option<&Matrix<Self::BaseField>>,
         aux_segments: &[Matrix<E>],
         aux_rand_elements: &AuxTraceRandElements<E>,
     ) where
         A: Air<BaseField = Self::BaseField>,
         E: FieldElement<BaseField = Self::BaseField>,
+    {
+        if let Err(err) = self.try_validate(air, preprocessed, aux_segments, aux_rand_elements) {
+            panic!("{err}");
+        }
+    }
+
+    /// Checks if this trace is valid against the specified AIR, and returns an error describing
+    /// the first assertion, boundary expression, or transition constraint which the trace does
+    /// not satisfy.
+    ///
+    /// If the AIR describes a trace with a preprocessed segment, `preprocessed` must contain the
+    /// columns of this segment; these are appended to the main evaluation frame.
+    ///
+    /// NOTE: this is a very expensive operation and is intended for use only in debug mode or in
+    /// tests.
+    ///
+    /// # Panics
+    /// Panics if the width of this trace or of the preprocessed segment is inconsistent with the
+    /// AIR.
+    fn try_validate<A, E>(
+        &self,
+        air: &A,
+        preprocessed: Option<&Matrix<Self::BaseField>>,
+        aux_segments: &[Matrix<E>],
+        aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Result<(), TraceError>
+    where
+        A: Air<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField>,
     {
         // make sure the width align; if they don't something went terribly wrong
         assert_eq!(
@@ -115,20 +173,41 @@ pub trait Trace: Sized {
             self.main_trace_width(),
             air.trace_layout().main_trace_width(),
         );
+        let preprocessed_width = preprocessed.map_or(0, |columns| columns.num_cols());
+        assert_eq!(
+            preprocessed_width,
+            air.trace_info().preprocessed_width(),
+            "inconsistent preprocessed segment width: expected {}, but was {}",
+            air.trace_info().preprocessed_width(),
+            preprocessed_width,
+        );
 
         // --- 1. make sure the assertions are valid ----------------------------------------------
 
         // first, check assertions against the main segment of the execution trace
         for assertion in air.get_assertions() {
+            let mut result = Ok(());
             assertion.apply(self.length(), |step, value| {
-                assert!(
-                    value == self.main_segment().get(assertion.column(), step),
-                    "trace does not satisfy assertion main_trace({}, {}) == {}",
-                    assertion.column(),
-                    step,
-                    value
-                );
+                if result.is_ok() && value != self.main_segment().get(assertion.column(), step) {
+                    result = Err(TraceError::UnsatisfiedAssertion {
+                        aux: false,
+                        column: assertion.column(),
+                        step,
+                        value: value.to_string(),
+                    });
+                }
             });
+            result?;
+        }
+
+        // then, check boundary expressions against rows of the main segment
+        let mut row = vec![Self::BaseField::ZERO; self.main_trace_width()];
+        for (i, expr) in air.get_boundary_exprs().iter().enumerate() {
+            let step = expr.step_selector().get_step(self.length());
+            self.main_segment().read_row_into(step, &mut row);
+            if expr.evaluate(&row) != Self::BaseField::ZERO {
+                return Err(TraceError::UnsatisfiedBoundaryExpr { index: i, step });
+            }
         }
 
         // then, check assertions against auxiliary trace segments
@@ -147,15 +226,18 @@ pub trait Trace: Sized {
             }
 
             // get the matrix and verify the assertion against it
+            let mut result = Ok(());
             assertion.apply(self.length(), |step, value| {
-                assert!(
-                    value == aux_segments[segment_idx].get(column_idx, step),
-                    "trace does not satisfy assertion aux_trace({}, {}) == {}",
-                    assertion.column(),
-                    step,
-                    value
-                );
+                if result.is_ok() && value != aux_segments[segment_idx].get(column_idx, step) {
+                    result = Err(TraceError::UnsatisfiedAssertion {
+                        aux: true,
+                        column: assertion.column(),
+                        step,
+                        value: value.to_string(),
+                    });
+                }
             });
+            result?;
         }
 
         // --- 2. make sure this trace satisfies all transition constraints -----------------------
@@ -167,9 +249,14 @@ pub trait Trace: Sized {
 
         // initialize buffers to hold evaluation frames and results of constraint evaluations
         let mut x = Self::BaseField::ONE;
-        let mut main_frame = EvaluationFrame::new(self.main_trace_width());
+        let frame_offsets = air.context().frame_offsets();
+        let mut main_frame =
+            EvaluationFrame::with_offsets(air.trace_info().main_frame_width(), frame_offsets);
         let mut aux_frame = if air.trace_info().is_multi_segment() {
-            Some(EvaluationFrame::<E>::new(self.aux_trace_width()))
+            Some(EvaluationFrame::<E>::with_offsets(
+                self.aux_trace_width(),
+                frame_offsets,
+            ))
         } else {
             None
         };
@@ -177,9 +264,38 @@ pub trait Trace: Sized {
             vec![Self::BaseField::ZERO; air.context().num_main_transition_constraints()];
         let mut aux_evaluations = vec![E::ZERO; air.context().num_aux_transition_constraints()];
 
-        // we check transition constraints on all steps except the last k steps, where k is the
-        // number of steps exempt from transition constraints (guaranteed to be at least 1)
-        for step in 0..self.length() - air.context().num_transition_exemptions() {
+        // record the number of steps on which each of the constraints must hold; a constraint
+        // with k exemptions must hold on all steps except the last k steps (k is guaranteed to
+        // be at least 1), while cyclic constraints must hold on all steps
+        let context = air.context();
+        let (main_exemptions, aux_exemptions) = context
+            .transition_exemptions()
+            .split_at(context.num_main_transition_constraints());
+        let main_num_steps = get_num_constrained_steps(
+            context.main_transition_constraint_degrees(),
+            main_exemptions,
+            self.length(),
+        );
+        let aux_num_steps = get_num_constrained_steps(
+            context.aux_transition_constraint_degrees(),
+            aux_exemptions,
+            self.length(),
+        );
+
+        // while constraint violations are being recorded, all steps are checked, and the first
+        // violation is reported only after the entire trace has been checked
+        #[cfg(feature = "trace-constraints")]
+        let mut first_violation = None;
+
+        // we check transition constraints only on the steps on which at least one of the
+        // constraints must hold
+        let num_steps = main_num_steps
+            .iter()
+            .chain(aux_num_steps.iter())
+            .copied()
+            .max()
+            .unwrap_or(0);
+        for step in 0..num_steps {
             // build periodic values
             for (p, v) in periodic_values_polys.iter().zip(periodic_values.iter_mut()) {
                 let num_cycles = air.trace_length() / p.len();
@@ -190,12 +306,24 @@ pub trait Trace: Sized {
             // evaluate transition constraints for the main trace segment and make sure they all
             // evaluate to zeros
             self.read_main_frame(step, &mut main_frame);
+            if let Some(columns) = preprocessed {
+                read_preprocessed_frame(columns, self.main_trace_width(), step, &mut main_frame);
+            }
             air.evaluate_transition(&main_frame, &periodic_values, &mut main_evaluations);
             for (i, &evaluation) in main_evaluations.iter().enumerate() {
-                assert!(
-                    evaluation == Self::BaseField::ZERO,
-                    "main transition constraint {i} did not evaluate to ZERO at step {step}"
-                );
+                if step < main_num_steps[i] && evaluation != Self::BaseField::ZERO {
+                    let err = TraceError::UnsatisfiedTransitionConstraint {
+                        aux: false,
+                        index: i,
+                        step,
+                    };
+                    #[cfg(feature = "trace-constraints")]
+                    if crate::constraint_log::push(false, i, step, evaluation) {
+                        first_violation.get_or_insert(err);
+                        continue;
+                    }
+                    return Err(err);
+                }
             }
 
             // evaluate transition constraints for auxiliary trace segments (if any) and make
@@ -210,16 +338,32 @@ pub trait Trace: Sized {
                     &mut aux_evaluations,
                 );
                 for (i, &evaluation) in aux_evaluations.iter().enumerate() {
-                    assert!(
-                        evaluation == E::ZERO,
-                        "auxiliary transition constraint {i} did not evaluate to ZERO at step {step}"
-                    );
+                    if step < aux_num_steps[i] && evaluation != E::ZERO {
+                        let err = TraceError::UnsatisfiedTransitionConstraint {
+                            aux: true,
+                            index: i,
+                            step,
+                        };
+                        #[cfg(feature = "trace-constraints")]
+                        if crate::constraint_log::push(true, i, step, evaluation) {
+                            first_violation.get_or_insert(err);
+                            continue;
+                        }
+                        return Err(err);
+                    }
                 }
             }
 
             // update x coordinate of the domain
             x *= g;
         }
+
+        #[cfg(feature = "trace-constraints")]
+        if let Some(err) = first_violation {
+            return Err(err);
+        }
+
+        Ok(())
     }
 }
 
@@ -236,12 +380,44 @@ fn read_aux_frame<E>(aux_segments: &[Matrix<E>], row_idx: usize, frame: &mut Eva
 where
     E: FieldElement,
 {
-    for (column, current_value) in MultiColumnIter::new(aux_segments).zip(frame.current_mut()) {
-        *current_value = column[row_idx];
+    for i in 0..frame.num_rows() {
+        let frame_row_idx = (row_idx + frame.offsets()[i]) % aux_segments[0].num_rows();
+        for (column, value) in MultiColumnIter::new(aux_segments).zip(frame.row_mut(i)) {
+            *value = column[frame_row_idx];
+        }
     }
+}
 
-    let next_row_idx = (row_idx + 1) % aux_segments[0].num_rows();
-    for (column, next_value) in MultiColumnIter::new(aux_segments).zip(frame.next_mut()) {
-        *next_value = column[next_row_idx];
+/// Reads rows of the preprocessed segment columns into the specified frame (one row for each of
+/// the row offsets of the frame), starting at the column `offset` of the frame.
+fn read_preprocessed_frame<B>(
+    columns: &Matrix<B>,
+    offset: usize,
+    row_idx: usize,
+    frame: &mut EvaluationFrame<B>,
+) where
+    B: StarkField,
+{
+    for i in 0..frame.num_rows() {
+        let frame_row_idx = (row_idx + frame.offsets()[i]) % columns.num_rows();
+        columns.read_row_into(frame_row_idx, &mut frame.row_mut(i)[offset..]);
     }
 }
+
+/// Returns the number of steps at the start of the execution trace on which each of the
+/// specified transition constraints must hold; cyclic constraints must hold on all steps, while
+/// other constraints are not checked on the steps exempt from them.
+fn get_num_constrained_steps(
+    degrees: &[TransitionConstraintDegree],
+    exemptions: &[usize],
+    trace_length: usize,
+) -> Vec<usize> {
+    degrees
+        .iter()
+        .zip(exemptions)
+        .map(|(degree, &num_exemptions)| match degree.divisor() {
+            TransitionDivisorKind::Transition => trace_length - num_exemptions,
+            TransitionDivisorKind::Cyclic => trace_length,
+        })
+        .collect()
+}
@@ -8,7 +8,10 @@ use crate::{
     Matrix,
 };
 use math::{log2, FieldElement, StarkField};
-use utils::collections::Vec;
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable,
+};
 
 // TRACE POLYNOMIAL TABLE
 // ================================================================================================
@@ -37,6 +40,12 @@ impl<E: FieldElement> TracePolyTable<E> {
     // STATE MUTATORS
     // --------------------------------------------------------------------------------------------
 
+    /// Appends the provided preprocessed segment polynomials to the polynomials of the main
+    /// trace segment in this polynomial table.
+    pub fn add_preprocessed_segment(&mut self, preprocessed_polys: &Matrix<E::BaseField>) {
+        self.main_segment_polys.append_columns(preprocessed_polys);
+    }
+
     /// Adds the provided auxiliary segment polynomials to this polynomial table.
     pub fn add_aux_segment(&mut self, aux_segment_polys: Matrix<E>) {
         assert_eq!(
@@ -65,19 +74,24 @@ impl<E: FieldElement> TracePolyTable<E> {
     }
 
     /// Returns an out-of-domain evaluation frame constructed by evaluating trace polynomials
-    /// for all columns at points z and z * g, where g is the generator of the trace domain.
-    pub fn get_ood_frame(&self, z: E) -> Vec<Vec<E>> {
+    /// for all columns at points z * g^k for every row offset k in `frame_offsets`, where g is
+    /// the generator of the trace domain.
+    pub fn get_ood_frame(&self, z: E, frame_offsets: &[usize]) -> Vec<Vec<E>> {
         let g = E::from(E::BaseField::get_root_of_unity(log2(self.poly_size())));
-        vec![self.evaluate_at(z), self.evaluate_at(z * g)]
+        frame_offsets
+            .iter()
+            .map(|&offset| self.evaluate_at(z * g.exp_vartime((offset as u32).into())))
+            .collect()
     }
 
-    /// Returns an iterator over the polynomials of the main trace segment.
-    pub fn main_trace_polys(&self) -> ColumnIter<E::BaseField> {
+    /// Returns an iterator over the polynomials of the main trace segment followed by the
+    /// polynomials of the preprocessed segment (if any).
+    pub fn main_trace_polys(&self) -> ColumnIter<'_, E::BaseField> {
         self.main_segment_polys.columns()
     }
 
     /// Returns an iterator over the polynomials of all auxiliary trace segments.
-    pub fn aux_trace_polys(&self) -> MultiColumnIter<E> {
+    pub fn aux_trace_polys(&self) -> MultiColumnIter<'_, E> {
         MultiColumnIter::new(self.aux_segment_polys.as_slice())
     }
 
@@ -85,14 +99,50 @@ impl<E: FieldElement> TracePolyTable<E> {
     // --------------------------------------------------------------------------------------------
 
     /// Returns the number of polynomials in the main segment of the trace.
-    #[cfg(test)]
+    #[cfg(all(test, feature = "f128"))]
     pub fn num_main_trace_polys(&self) -> usize {
         self.main_segment_polys.num_cols()
     }
 
     /// Returns a polynomial from the main segment of the trace at the specified index.
-    #[cfg(test)]
+    #[cfg(all(test, feature = "f128"))]
     pub fn get_main_trace_poly(&self, idx: usize) -> &[E::BaseField] {
         self.main_segment_polys.get_column(idx)
     }
 }
+
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<E: FieldElement> Serializable for TracePolyTable<E> {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.main_segment_polys.write_into(target);
+        target.write_u8(self.aux_segment_polys.len() as u8);
+        for segment_polys in self.aux_segment_polys.iter() {
+            segment_polys.write_into(target);
+        }
+    }
+}
+
+impl<E: FieldElement> Deserializable for TracePolyTable<E> {
+    /// Reads a table of trace polynomials from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid table could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let mut result = Self::new(Matrix::read_from(source)?);
+        let num_aux_segments = source.read_u8()? as usize;
+        for _ in 0..num_aux_segments {
+            let segment_polys = Matrix::read_from(source)?;
+            if segment_polys.num_rows() != result.poly_size() {
+                return Err(DeserializationError::InvalidValue(
+                    "polynomials in auxiliary segment must be of the same size as in the main segment"
+                        .to_string(),
+                ));
+            }
+            result.aux_segment_polys.push(segment_polys);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::{Matrix, ProverConfig, ProverError};
+use air::{LeafEncoding, ProofOptions, TraceInfo};
+use crypto::{ElementHasher, MerkleTree};
+use math::{fft, log2, StarkField};
+use utils::collections::Vec;
+
+// PREPROCESSED TRACE SEGMENT
+// ================================================================================================
+
+/// A set of trace columns which are committed to once and shared across many proofs.
+///
+/// A preprocessed segment describes columns which are the same for every execution of a
+/// computation (e.g., a program ROM or a lookup table). The columns are interpolated, extended
+/// and committed to when the segment is built; proofs generated by a prover which returns the
+/// segment from [Prover::preprocessed_segment()](crate::Prover::preprocessed_segment) reuse this
+/// work and include only openings of the commitment at the queried positions. The commitment
+/// itself is not included in the proofs; instead, it must be provided to the verifier out of band.
+///
+/// Columns of the segment are placed right after the columns of the main trace segment in the
+/// main evaluation frame, and thus, transition constraints can reference them as if they were
+/// columns of the main segment.
+///
+/// A segment can be used only for traces of the same length as the segment, and only with proof
+/// options specifying the same blowup factor and leaf encoding as the options with which the
+/// segment was built.
+#[derive(Debug)]
+pub struct PreprocessedSegment<B: StarkField, H: ElementHasher<BaseField = B>> {
+    columns: Matrix<B>,
+    polys: Matrix<B>,
+    lde: Matrix<B>,
+    tree: MerkleTree<H>,
+    blowup: usize,
+    leaf_encoding: LeafEncoding,
+}
+
+impl<B: StarkField, H: ElementHasher<BaseField = B>> PreprocessedSegment<B, H> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new preprocessed segment consisting of the specified columns and committed to
+    /// using the blowup factor and the leaf encoding specified by `options`.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * The list of columns is empty or contains more than 255 columns.
+    /// * Not all of the columns have the same length.
+    /// * Length of the columns is not a power of two or is smaller than 8.
+    /// * Rows of the segment cannot be encoded into leaves using the leaf encoding specified by
+    ///   `options`.
+    pub fn new(columns: Vec<Vec<B>>, options: &ProofOptions) -> Self {
+        assert!(
+            columns.len() <= TraceInfo::MAX_TRACE_WIDTH,
+            "number of preprocessed columns cannot be greater than {}, but was {}",
+            TraceInfo::MAX_TRACE_WIDTH,
+            columns.len()
+        );
+        let columns = Matrix::new(columns);
+        assert!(
+            columns.num_rows() >= TraceInfo::MIN_TRACE_LENGTH,
+            "length of preprocessed columns must be at least {}, but was {}",
+            TraceInfo::MIN_TRACE_LENGTH,
+            columns.num_rows()
+        );
+
+        // interpolate the columns and extend them over the LDE domain in the same way as the
+        // prover extends the main trace segment
+        let polys = columns.interpolate_columns();
+        let blowup = options.blowup_factor();
+        let twiddles = fft::get_twiddles::<B>(columns.num_rows());
+        let lde = Matrix::new(
+            polys
+                .columns()
+                .map(|poly| {
+                    fft::evaluate_poly_with_offset(
+                        poly,
+                        &twiddles,
+                        options.domain_offset::<B>(),
+                        blowup,
+                    )
+                })
+                .collect(),
+        );
+
+        let leaf_encoding = options.leaf_encoding();
+        let tree = lde.commit_to_rows_with_arity(
+            leaf_encoding,
+            options.merkle_arity(),
+            &ProverConfig::default(),
+        );
+
+        Self {
+            columns,
+            polys,
+            lde,
+            tree,
+            blowup,
+            leaf_encoding,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the commitment to this segment; this commitment must be provided to the verifier
+    /// to verify proofs generated using this segment.
+    pub fn commitment(&self) -> H::Digest {
+        *self.tree.root()
+    }
+
+    /// Returns the number of columns in this segment.
+    pub fn num_cols(&self) -> usize {
+        self.columns.num_cols()
+    }
+
+    /// Returns the number of rows in this segment; this must be the same as the length of traces
+    /// for which the segment is used.
+    pub fn trace_length(&self) -> usize {
+        self.columns.num_rows()
+    }
+
+    /// Returns the column of this segment at the specified index.
+    pub fn get_column(&self, col_idx: usize) -> &[B] {
+        self.columns.get_column(col_idx)
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the columns of this segment.
+    pub(crate) fn columns(&self) -> &Matrix<B> {
+        &self.columns
+    }
+
+    /// Returns polynomials interpolated from the columns of this segment.
+    pub(crate) fn polys(&self) -> &Matrix<B> {
+        &self.polys
+    }
+
+    /// Returns evaluations of the polynomials of this segment over the LDE domain.
+    pub(crate) fn lde(&self) -> &Matrix<B> {
+        &self.lde
+    }
+
+    /// Returns the Merkle tree built from the rows of the extended segment.
+    pub(crate) fn tree(&self) -> &MerkleTree<H> {
+        &self.tree
+    }
+
+    /// Makes sure this segment can be used to generate a proof for a trace described by the
+    /// specified trace info using the specified proof options.
+    pub(crate) fn check_compatibility(
+        &self,
+        trace_info: &TraceInfo,
+        options: &ProofOptions,
+    ) -> Result<(), ProverError> {
+        if self.trace_length() != trace_info.length() {
+            return Err(ProverError::IncompatiblePreprocessedSegment(format!(
+                "segment was built for traces of 2^{} steps, but the trace has 2^{} steps",
+                log2(self.trace_length()),
+                log2(trace_info.length())
+            )));
+        }
+        if self.blowup != options.blowup_factor() {
+            return Err(ProverError::IncompatiblePreprocessedSegment(format!(
+                "segment was built with blowup factor {}, but proof options specify {}",
+                self.blowup,
+                options.blowup_factor()
+            )));
+        }
+        if self.leaf_encoding != options.leaf_encoding() {
+            return Err(ProverError::IncompatiblePreprocessedSegment(
+                "segment was built with a different leaf encoding than specified by proof options"
+                    .into(),
+            ));
+        }
+        if self.tree.arity() != options.merkle_arity() {
+            return Err(ProverError::IncompatiblePreprocessedSegment(format!(
+                "segment was built with Merkle tree arity {}, but proof options specify {}",
+                self.tree.arity(),
+                options.merkle_arity()
+            )));
+        }
+        if trace_info.width() + self.num_cols() > TraceInfo::MAX_TRACE_WIDTH {
+            return Err(ProverError::IncompatiblePreprocessedSegment(format!(
+                "total number of trace columns cannot be greater than {}, but was {}",
+                TraceInfo::MAX_TRACE_WIDTH,
+                trace_info.width() + self.num_cols()
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::Matrix;
+use core::fmt;
+use log::warn;
+use math::StarkField;
+use utils::{collections::Vec, iter};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Default number of distinct values tracked for every column of an execution trace.
+pub const DEFAULT_DISTINCT_VALUES_CAP: usize = 256;
+
+// TRACE STATISTICS
+// ================================================================================================
+/// Per-column statistics of the main segment of an execution trace.
+///
+/// Statistics are meant to catch degenerate witnesses: a bug in a trace builder (e.g., a
+/// misindexed write) may leave a whole column constant, and if the constraints happen to be
+/// satisfiable by such a trace, the resulting proof proves something trivial. Computing the
+/// statistics requires a full pass over the trace, and thus, it is opt-in; see
+/// [TraceStatistics::warn_on_degenerate()] for a way to check the statistics against a set of
+/// expectations.
+///
+/// When `concurrent` feature is enabled, statistics of different columns are computed in
+/// parallel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStatistics<B: StarkField> {
+    columns: Vec<ColumnStatistics<B>>,
+}
+
+impl<B: StarkField> TraceStatistics<B> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Computes statistics for all columns of the specified trace segment; at most
+    /// `distinct_values_cap` distinct values are counted for every column.
+    ///
+    /// # Panics
+    /// Panics if `distinct_values_cap` is smaller than 2.
+    pub fn new(segment: &Matrix<B>, distinct_values_cap: usize) -> Self {
+        assert!(
+            distinct_values_cap >= 2,
+            "distinct values cap must be at least 2, but was {distinct_values_cap}"
+        );
+        let columns = segment.columns().collect::<Vec<_>>();
+        let columns = iter!(columns)
+            .map(|column| ColumnStatistics::new(column, distinct_values_cap))
+            .collect();
+        TraceStatistics { columns }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns statistics of all columns in the order in which the columns appear in the trace.
+    pub fn columns(&self) -> &[ColumnStatistics<B>] {
+        &self.columns
+    }
+
+    /// Returns statistics of the column at the specified index.
+    pub fn get_column(&self, col_idx: usize) -> &ColumnStatistics<B> {
+        &self.columns[col_idx]
+    }
+
+    /// Returns the number of columns described by these statistics.
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    // DEGENERACY CHECKS
+    // --------------------------------------------------------------------------------------------
+
+    /// Checks these statistics against the specified thresholds, logs a warning for every
+    /// degenerate column, and returns the list of all detected issues.
+    ///
+    /// A column is reported as degenerate when:
+    /// * The column contains a single value, unless it is declared constant in the thresholds.
+    /// * The column is declared binary, but contains no ones or contains values other than zero
+    ///   and one.
+    /// * The fraction of zeros in the column exceeds the maximum fraction of zeros specified in
+    ///   the thresholds (if any).
+    ///
+    /// The returned list is empty when none of the columns is degenerate; callers which would
+    /// rather fail than log (e.g., tests) can turn a non-empty list into an error.
+    pub fn warn_on_degenerate(
+        &self,
+        thresholds: &DegeneracyThresholds,
+    ) -> Vec<DegenerateColumn<B>> {
+        let mut result = Vec::new();
+        for (column, stats) in self.columns.iter().enumerate() {
+            if stats.is_constant() && !thresholds.constant_columns.contains(&column) {
+                result.push(DegenerateColumn::Constant(column, stats.min()));
+            }
+            if thresholds.binary_columns.contains(&column) {
+                if !stats.is_binary() {
+                    result.push(DegenerateColumn::NotBinary(column));
+                } else if stats.num_ones() == 0 {
+                    result.push(DegenerateColumn::BinaryWithoutOnes(column));
+                }
+            }
+            if let Some(max_zero_fraction) = thresholds.max_zero_fraction {
+                if stats.zero_fraction() > max_zero_fraction {
+                    result.push(DegenerateColumn::TooManyZeros(
+                        column,
+                        stats.zero_fraction(),
+                    ));
+                }
+            }
+        }
+
+        for issue in result.iter() {
+            warn!("degenerate execution trace: {}", issue);
+        }
+        result
+    }
+}
+
+// COLUMN STATISTICS
+// ================================================================================================
+/// Statistics of a single column of an execution trace.
+///
+/// Minimum and maximum values are determined by comparing integer representations of the
+/// column values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnStatistics<B: StarkField> {
+    min: B,
+    max: B,
+    num_distinct: usize,
+    distinct_values_cap: usize,
+    num_zeros: usize,
+    num_ones: usize,
+    length: usize,
+}
+
+impl<B: StarkField> ColumnStatistics<B> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Computes statistics of the specified column; at most `distinct_values_cap` distinct values
+    /// are counted.
+    ///
+    /// # Panics
+    /// Panics if the column is empty.
+    pub fn new(column: &[B], distinct_values_cap: usize) -> Self {
+        assert!(!column.is_empty(), "column must contain at least one value");
+        let (mut min, mut max) = (column[0], column[0]);
+        let (mut min_int, mut max_int) = (min.as_int(), max.as_int());
+
+        // distinct values are kept sorted so that a value can be looked up via binary search
+        let mut distinct = Vec::with_capacity(distinct_values_cap);
+        let (mut num_zeros, mut num_ones) = (0, 0);
+        for &value in column {
+            let value_int = value.as_int();
+            if value_int < min_int {
+                (min, min_int) = (value, value_int);
+            }
+            if value_int > max_int {
+                (max, max_int) = (value, value_int);
+            }
+
+            if value == B::ZERO {
+                num_zeros += 1;
+            } else if value == B::ONE {
+                num_ones += 1;
+            }
+
+            if distinct.len() < distinct_values_cap {
+                let position = distinct.binary_search_by(|v: &B::PositiveInteger| {
+                    v.partial_cmp(&value_int)
+                        .expect("integers must be comparable")
+                });
+                if let Err(position) = position {
+                    distinct.insert(position, value_int);
+                }
+            }
+        }
+
+        ColumnStatistics {
+            min,
+            max,
+            num_distinct: distinct.len(),
+            distinct_values_cap,
+            num_zeros,
+            num_ones,
+            length: column.len(),
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the smallest value of the column.
+    pub fn min(&self) -> B {
+        self.min
+    }
+
+    /// Returns the largest value of the column.
+    pub fn max(&self) -> B {
+        self.max
+    }
+
+    /// Returns the number of distinct values in the column; the result never exceeds the cap on
+    /// the number of distinct values, and if it is equal to the cap, the column may contain more
+    /// distinct values.
+    pub fn num_distinct(&self) -> usize {
+        self.num_distinct
+    }
+
+    /// Returns true if the number of distinct values in the column reached the cap on the number
+    /// of distinct values.
+    pub fn is_distinct_capped(&self) -> bool {
+        self.num_distinct == self.distinct_values_cap
+    }
+
+    /// Returns the number of zeros in the column.
+    pub fn num_zeros(&self) -> usize {
+        self.num_zeros
+    }
+
+    /// Returns the number of ones in the column.
+    pub fn num_ones(&self) -> usize {
+        self.num_ones
+    }
+
+    /// Returns the fraction of column values which are zeros.
+    pub fn zero_fraction(&self) -> f64 {
+        self.num_zeros as f64 / self.length as f64
+    }
+
+    /// Returns true if all values in the column are the same.
+    pub fn is_constant(&self) -> bool {
+        self.num_distinct == 1
+    }
+
+    /// Returns true if all values in the column are either zeros or ones.
+    pub fn is_binary(&self) -> bool {
+        self.num_zeros + self.num_ones == self.length
+    }
+}
+
+// DEGENERACY THRESHOLDS
+// ================================================================================================
+/// Expectations against which [TraceStatistics] are checked for degenerate columns.
+///
+/// By default, every constant column is reported as degenerate, no columns are expected to be
+/// binary, and the fraction of zeros in a column is not limited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DegeneracyThresholds {
+    binary_columns: Vec<usize>,
+    constant_columns: Vec<usize>,
+    max_zero_fraction: Option<f64>,
+}
+
+impl DegeneracyThresholds {
+    /// Returns new thresholds with default expectations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the specified columns as binary; such columns are reported as degenerate when
+    /// they contain no ones or contain values other than zero and one.
+    pub fn with_binary_columns(mut self, columns: &[usize]) -> Self {
+        self.binary_columns.extend_from_slice(columns);
+        self
+    }
+
+    /// Declares the specified columns as constant; such columns are not reported as degenerate
+    /// when they contain a single value.
+    pub fn with_constant_columns(mut self, columns: &[usize]) -> Self {
+        self.constant_columns.extend_from_slice(columns);
+        self
+    }
+
+    /// Sets the maximum fraction of zeros in a column; columns containing more zeros are
+    /// reported as degenerate.
+    ///
+    /// # Panics
+    /// Panics if `fraction` is not in the range [0, 1].
+    pub fn with_max_zero_fraction(mut self, fraction: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "maximum fraction of zeros must be in the range [0, 1], but was {fraction}"
+        );
+        self.max_zero_fraction = Some(fraction);
+        self
+    }
+}
+
+// DEGENERATE COLUMN
+// ================================================================================================
+/// Describes a degenerate column of an execution trace detected by
+/// [TraceStatistics::warn_on_degenerate()].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DegenerateColumn<B: StarkField> {
+    /// The column at the specified index contains only the specified value.
+    Constant(usize, B),
+    /// The column at the specified index is declared binary but contains no ones.
+    BinaryWithoutOnes(usize),
+    /// The column at the specified index is declared binary but contains values other than zero
+    /// and one.
+    NotBinary(usize),
+    /// The fraction of zeros in the column at the specified index exceeds the maximum.
+    TooManyZeros(usize, f64),
+}
+
+impl<B: StarkField> fmt::Display for DegenerateColumn<B> {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Constant(column, value) => {
+                write!(f, "column {column} is constant (all values are {value})")
+            }
+            Self::BinaryWithoutOnes(column) => {
+                write!(f, "binary column {column} contains no ones")
+            }
+            Self::NotBinary(column) => {
+                write!(f, "binary column {column} contains values other than zero and one")
+            }
+            Self::TooManyZeros(column, fraction) => {
+                write!(f, "{:.1}% of values in column {column} are zeros", fraction * 100.0)
+            }
+        }
+    }
+}
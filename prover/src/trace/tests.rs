@@ -5,15 +5,25 @@
 
 use crate::{
     tests::{build_fib_trace, MockAir},
-    trace::TracePolyTable,
-    StarkDomain, Trace, TraceCommitment,
+    trace::{
+        ColumnCodec, DegeneracyThresholds, DegenerateColumn, FillStatus, TracePolyTable,
+        TraceStatistics,
+    },
+    Air, CommitmentLayout, Context, LdePos, LeafEncoding, ProverConfig, StarkDomain, Trace,
+    TraceBuildError, TraceCommitment, TraceError, TraceInfo, TraceTable,
+};
+use crypto::{
+    hashers::Blake3_256, BatchMerkleProof, CommitmentOpener, Digest, ElementHasher, MerkleTree,
+    MerkleTreeError,
 };
-use crypto::{hashers::Blake3_256, ElementHasher, MerkleTree};
 use math::{
     fields::f128::BaseElement, get_power_series, get_power_series_with_offset, log2, polynom,
     FieldElement, StarkField,
 };
-use utils::collections::Vec;
+use utils::{collections::Vec, Deserializable, DeserializationError, Serializable, SliceReader};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
 
 type Blake3 = Blake3_256<BaseElement>;
 
@@ -127,10 +137,684 @@ fn commit_trace_table() {
     assert_eq!(*expected_tree.root(), trace_comm.main_trace_root())
 }
 
+#[test]
+fn query_trace_commitment() {
+    let trace_length = 8;
+    let air = MockAir::with_trace_length(trace_length);
+    let trace = build_fib_trace(trace_length * 2);
+    let domain = StarkDomain::new(&air);
+
+    let trace_polys = trace.main_segment().interpolate_columns();
+    let trace_lde = trace_polys.evaluate_columns_over(&domain);
+    let trace_tree = trace_lde.commit_to_rows::<Blake3>();
+    let trace_comm = TraceCommitment::<BaseElement, Blake3>::new(
+        trace_lde,
+        trace_tree,
+        domain.trace_to_lde_blowup(),
+    );
+
+    // the prover and the verifier derive commitment layout from the proof context in the same
+    // way; openings built via the layout must verify against the leaves the layout maps to
+    let context = Context::new::<BaseElement>(air.trace_info(), air.options().clone());
+    let layout = CommitmentLayout::from_context(&context);
+    assert_eq!(domain.lde_domain_size(), layout.lde_domain_size());
+
+    let positions = [LdePos(3), LdePos(17), LdePos(4), LdePos(60)];
+    let queries = trace_comm
+        .query(&positions, &layout, LeafEncoding::Hashed)
+        .remove(0);
+    let (proof, states) = queries
+        .parse::<Blake3, BaseElement>(layout.lde_domain_size(), positions.len(), 2)
+        .unwrap();
+    let leaf_indexes = layout.leaf_indexes(&positions);
+    assert_eq!(vec![3, 17, 4, 60], leaf_indexes);
+    assert!(MerkleTree::verify_batch(&trace_comm.main_trace_root(), &leaf_indexes, &proof).is_ok());
+
+    let trace_table = trace_comm.trace_table();
+    for (i, position) in positions.iter().enumerate() {
+        let expected = (0..2)
+            .map(|col| trace_table.get_main_segment().get(col, position.as_usize()))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, states.get_row(i));
+    }
+}
+
+#[test]
+fn query_trace_commitment_with_opener() {
+    let trace_length = 8;
+    let air = MockAir::with_trace_length(trace_length);
+    let trace = build_fib_trace(trace_length * 2);
+    let domain = StarkDomain::new(&air);
+
+    let trace_polys = trace.main_segment().interpolate_columns();
+    let trace_lde = trace_polys.evaluate_columns_over(&domain);
+    let trace_tree = trace_lde.commit_to_rows::<Blake3>();
+    let opener = PathOpener(trace_tree.clone());
+    let trace_comm = TraceCommitment::<BaseElement, Blake3>::new(
+        trace_lde,
+        trace_tree,
+        domain.trace_to_lde_blowup(),
+    );
+
+    // queries built from individual Merkle paths are the same as queries built from the tree
+    let context = Context::new::<BaseElement>(air.trace_info(), air.options().clone());
+    let layout = CommitmentLayout::from_context(&context);
+    let positions = [LdePos(3), LdePos(17), LdePos(4), LdePos(60)];
+    assert_eq!(
+        trace_comm.query(&positions, &layout, LeafEncoding::Hashed),
+        trace_comm.query_with_opener(&positions, &layout, LeafEncoding::Hashed, &opener)
+    );
+}
+
+#[test]
+#[should_panic(expected = "opener root must be the same as the main trace segment commitment root")]
+fn query_trace_commitment_with_wrong_opener() {
+    let trace_length = 8;
+    let air = MockAir::with_trace_length(trace_length);
+    let domain = StarkDomain::new(&air);
+
+    let trace_lde = build_fib_trace(trace_length * 2)
+        .main_segment()
+        .interpolate_columns()
+        .evaluate_columns_over(&domain);
+    let trace_tree = trace_lde.commit_to_rows::<Blake3>();
+
+    // an opener for a tree with the same leaves in a different order
+    let other_leaves = trace_tree.leaves().iter().rev().cloned().collect();
+    let opener = PathOpener(MerkleTree::new(other_leaves).unwrap());
+
+    let trace_comm = TraceCommitment::<BaseElement, Blake3>::new(
+        trace_lde,
+        trace_tree,
+        domain.trace_to_lde_blowup(),
+    );
+
+    let context = Context::new::<BaseElement>(air.trace_info(), air.options().clone());
+    let layout = CommitmentLayout::from_context(&context);
+    trace_comm.query_with_opener(&[LdePos(3)], &layout, LeafEncoding::Hashed, &opener);
+}
+
+#[test]
+fn query_raw_trace_commitment() {
+    let trace_length = 8;
+    let air = MockAir::with_trace_length(trace_length);
+    let trace = build_fib_trace(trace_length * 2);
+    let domain = StarkDomain::new(&air);
+
+    // a row of two 128-bit elements is as long as a Blake3_256 digest, and thus, every leaf of
+    // the tree is the row itself
+    let trace_polys = trace.main_segment().interpolate_columns();
+    let trace_lde = trace_polys.evaluate_columns_over(&domain);
+    let trace_tree = trace_lde
+        .commit_to_rows_with_encoding::<Blake3>(LeafEncoding::Raw, &ProverConfig::default());
+    for (i, leaf) in trace_tree.leaves().iter().enumerate() {
+        let mut row = [BaseElement::ZERO; 2];
+        trace_lde.read_row_into(i, &mut row);
+        assert_eq!(BaseElement::elements_as_bytes(&row), &leaf.as_bytes()[..]);
+    }
+    let trace_comm = TraceCommitment::<BaseElement, Blake3>::new(
+        trace_lde,
+        trace_tree,
+        domain.trace_to_lde_blowup(),
+    );
+
+    // positions 4 and 5 are siblings, and thus, only the other positions require sibling leaves
+    let context = Context::new::<BaseElement>(air.trace_info(), air.options().clone());
+    let layout = CommitmentLayout::from_context(&context);
+    let positions = [LdePos(3), LdePos(17), LdePos(4), LdePos(60), LdePos(5)];
+    let queries = trace_comm
+        .query(&positions, &layout, LeafEncoding::Raw)
+        .remove(0);
+    let (proof, states) = queries
+        .clone()
+        .parse_with_encoding::<Blake3, BaseElement>(
+            layout.lde_domain_size(),
+            positions.len(),
+            2,
+            LeafEncoding::Raw,
+        )
+        .unwrap();
+    let leaf_indexes = layout.leaf_indexes(&positions);
+    assert!(MerkleTree::verify_batch(&trace_comm.main_trace_root(), &leaf_indexes, &proof).is_ok());
+
+    let trace_table = trace_comm.trace_table();
+    for (i, position) in positions.iter().enumerate() {
+        let expected = (0..2)
+            .map(|col| trace_table.get_main_segment().get(col, position.as_usize()))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, states.get_row(i));
+    }
+
+    // raw queries cannot be verified as queries of hashed leaves
+    let result = queries.parse::<Blake3, BaseElement>(layout.lde_domain_size(), positions.len(), 2);
+    assert!(result.map_or(true, |(proof, _)| {
+        MerkleTree::verify_batch(&trace_comm.main_trace_root(), &leaf_indexes, &proof).is_err()
+    }));
+}
+
+#[test]
+fn trace_table_statistics() {
+    // columns: a counter, all zeros, alternating zeros and ones, a constant, and three values
+    let trace_length = 16;
+    let trace = TraceTable::init(vec![
+        (0..trace_length as u32).map(BaseElement::from).collect(),
+        vec![BaseElement::ZERO; trace_length],
+        (0..trace_length as u32)
+            .map(|i| BaseElement::from(i % 2))
+            .collect(),
+        vec![BaseElement::new(7); trace_length],
+        (0..trace_length as u32)
+            .map(|i| BaseElement::from(i % 3 + 5))
+            .collect(),
+    ]);
+    let stats = trace.statistics();
+    assert_eq!(5, stats.num_columns());
+
+    let counter = stats.get_column(0);
+    assert_eq!(BaseElement::ZERO, counter.min());
+    assert_eq!(BaseElement::new(15), counter.max());
+    assert_eq!(16, counter.num_distinct());
+    assert_eq!(1, counter.num_zeros());
+    assert_eq!(1, counter.num_ones());
+    assert!(!counter.is_constant());
+    assert!(!counter.is_binary());
+
+    let zeros = stats.get_column(1);
+    assert!(zeros.is_constant());
+    assert!(zeros.is_binary());
+    assert_eq!(1.0, zeros.zero_fraction());
+
+    let bits = stats.get_column(2);
+    assert_eq!(2, bits.num_distinct());
+    assert!(bits.is_binary());
+    assert_eq!(0.5, bits.zero_fraction());
+
+    assert_eq!(BaseElement::new(7), stats.get_column(3).max());
+    assert_eq!(0.0, stats.get_column(3).zero_fraction());
+    assert_eq!(BaseElement::new(5), stats.get_column(4).min());
+    assert_eq!(BaseElement::new(7), stats.get_column(4).max());
+    assert_eq!(3, stats.get_column(4).num_distinct());
+
+    // the number of distinct values is capped
+    let stats = TraceStatistics::new(trace.main_segment(), 4);
+    assert_eq!(4, stats.get_column(0).num_distinct());
+    assert!(stats.get_column(0).is_distinct_capped());
+    assert_eq!(3, stats.get_column(4).num_distinct());
+    assert!(!stats.get_column(4).is_distinct_capped());
+}
+
+#[test]
+fn trace_table_degenerate_columns() {
+    let trace_length = 16;
+    let trace = TraceTable::init(vec![
+        (0..trace_length as u32).map(BaseElement::from).collect(),
+        vec![BaseElement::ZERO; trace_length],
+        (0..trace_length as u32)
+            .map(|i| BaseElement::from(i % 2))
+            .collect(),
+        vec![BaseElement::new(7); trace_length],
+    ]);
+    let stats = trace.statistics();
+
+    // by default, only constant columns are reported
+    assert_eq!(
+        vec![
+            DegenerateColumn::Constant(1, BaseElement::ZERO),
+            DegenerateColumn::Constant(3, BaseElement::new(7)),
+        ],
+        stats.warn_on_degenerate(&DegeneracyThresholds::new())
+    );
+
+    // binary columns without ones and with non-binary values are reported
+    let thresholds = DegeneracyThresholds::new()
+        .with_binary_columns(&[0, 1, 2])
+        .with_constant_columns(&[3]);
+    assert_eq!(
+        vec![
+            DegenerateColumn::NotBinary(0),
+            DegenerateColumn::Constant(1, BaseElement::ZERO),
+            DegenerateColumn::BinaryWithoutOnes(1),
+        ],
+        stats.warn_on_degenerate(&thresholds)
+    );
+
+    // columns with too many zeros are reported
+    let thresholds = DegeneracyThresholds::new()
+        .with_constant_columns(&[1, 3])
+        .with_max_zero_fraction(0.25);
+    assert_eq!(
+        vec![
+            DegenerateColumn::TooManyZeros(1, 1.0),
+            DegenerateColumn::TooManyZeros(2, 0.5),
+        ],
+        stats.warn_on_degenerate(&thresholds)
+    );
+    assert_eq!(
+        "50.0% of values in column 2 are zeros",
+        DegenerateColumn::<BaseElement>::TooManyZeros(2, 0.5).to_string()
+    );
+
+    // statistics are computed for traces of the maximum width
+    let width = TraceInfo::MAX_TRACE_WIDTH;
+    let mut columns = vec![
+        (0..trace_length as u32)
+            .map(BaseElement::from)
+            .collect::<Vec<_>>();
+        width
+    ];
+    columns[width - 1] = vec![BaseElement::ONE; trace_length];
+    let stats = TraceTable::init(columns).statistics();
+    assert_eq!(width, stats.num_columns());
+    assert_eq!(
+        vec![DegenerateColumn::Constant(width - 1, BaseElement::ONE)],
+        stats.warn_on_degenerate(&DegeneracyThresholds::new())
+    );
+}
+
+#[test]
+fn trace_table_serialization() {
+    let trace = build_codec_trace();
+    let expected_codecs = vec![
+        ColumnCodec::BitPacked,
+        ColumnCodec::SmallInt(7),
+        ColumnCodec::Dictionary,
+        ColumnCodec::Plain,
+    ];
+    assert_eq!(expected_codecs, trace.select_column_codecs());
+
+    // the trace is restored from its compressed encoding
+    let bytes = trace.to_bytes();
+    let result = read_trace(&bytes).unwrap();
+    assert_eq!(trace.width(), result.width());
+    assert_eq!(trace.length(), result.length());
+    assert_eq!(trace.meta(), result.meta());
+    for i in 0..trace.width() {
+        assert_eq!(trace.get_column(i), result.get_column(i));
+    }
+
+    // header: width, length, meta length, and 5 bytes of codecs; bit-packed column takes 1 byte,
+    // 7-bit integers take 7 bytes, and the dictionary takes 1 + 2 * 16 + 1 bytes
+    assert_eq!(7 + 5 + 1 + 7 + 34 + 8 * 16, bytes.len());
+
+    // the trace is restored from an encoding with a weaker codec forced for every column
+    let codecs = vec![
+        ColumnCodec::SmallInt(3),
+        ColumnCodec::Dictionary,
+        ColumnCodec::Plain,
+        ColumnCodec::Plain,
+    ];
+    let mut forced_bytes = Vec::new();
+    trace.write_with_codecs(&mut forced_bytes, &codecs);
+    assert!(forced_bytes.len() > bytes.len());
+    let result = read_trace(&forced_bytes).unwrap();
+    for i in 0..trace.width() {
+        assert_eq!(trace.get_column(i), result.get_column(i));
+    }
+}
+
+#[test]
+#[should_panic(expected = "column cannot be encoded with bit-packed codec")]
+fn trace_table_serialization_unsupported_codec() {
+    let trace = build_codec_trace();
+    let codecs = vec![ColumnCodec::BitPacked; trace.width()];
+    trace.write_with_codecs(&mut Vec::new(), &codecs);
+}
+
+#[test]
+fn trace_table_deserialization_errors() {
+    let bytes = build_codec_trace().to_bytes();
+
+    // column codec tag is not valid
+    let mut corrupted = bytes.clone();
+    corrupted[7] = 9;
+    assert_eq!(
+        Err(DeserializationError::InvalidValue(
+            "column codec tag 9 is not valid".to_string()
+        )),
+        read_trace(&corrupted).map(|_| ())
+    );
+
+    // trace length is not a power of two
+    let mut corrupted = bytes.clone();
+    corrupted[1] = 9;
+    assert_eq!(
+        Err(DeserializationError::InvalidValue(
+            "execution trace length 9 is not valid".to_string()
+        )),
+        read_trace(&corrupted).map(|_| ())
+    );
+
+    // dictionary value is not a canonical field element
+    let mut corrupted = bytes.clone();
+    let dictionary_start = 7 + 5 + 1 + 7 + 1;
+    corrupted[dictionary_start..dictionary_start + 16].fill(0xff);
+    assert!(matches!(
+        read_trace(&corrupted),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // serialized trace is truncated
+    assert!(matches!(
+        read_trace(&bytes[..bytes.len() - 1]),
+        Err(DeserializationError::UnexpectedEOF)
+    ));
+}
+
+#[test]
+fn column_codec_decoding_errors() {
+    // padding bits of the last byte must be zeros
+    let source = [0b0000_1101u8];
+    let result = ColumnCodec::BitPacked.decode::<BaseElement, _>(&mut SliceReader::new(&source), 3);
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+    let source = [0b0000_0101u8];
+    let result = ColumnCodec::BitPacked.decode::<BaseElement, _>(&mut SliceReader::new(&source), 3);
+    assert_eq!(
+        vec![BaseElement::ONE, BaseElement::ZERO, BaseElement::ONE],
+        result.unwrap()
+    );
+
+    // dictionary of 3 values with index 3
+    let mut source = vec![2u8];
+    BaseElement::write_batch_into(&[1u32, 2, 3].map(BaseElement::from), &mut source);
+    source.push(0b1100_0000);
+    let result =
+        ColumnCodec::Dictionary.decode::<BaseElement, _>(&mut SliceReader::new(&source), 4);
+    assert_eq!(
+        Err(DeserializationError::InvalidValue(
+            "dictionary index 3 is out of bounds for a dictionary of 3 values".to_string()
+        )),
+        result
+    );
+
+    // dictionary values are not sorted
+    let mut source = vec![1u8];
+    BaseElement::write_batch_into(&[2u32, 1].map(BaseElement::from), &mut source);
+    source.push(0);
+    let result =
+        ColumnCodec::Dictionary.decode::<BaseElement, _>(&mut SliceReader::new(&source), 4);
+    assert!(matches!(result, Err(DeserializationError::InvalidValue(_))));
+
+    // small integer width must be between 1 and 64
+    assert!(ColumnCodec::read_from(&mut SliceReader::new(&[2, 0])).is_err());
+    assert!(ColumnCodec::read_from(&mut SliceReader::new(&[2, 65])).is_err());
+    assert_eq!(
+        ColumnCodec::SmallInt(64),
+        ColumnCodec::read_from(&mut SliceReader::new(&[2, 64])).unwrap()
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn trace_table_try_fill_panic() {
+    let mut trace = TraceTable::<BaseElement>::new(2, 16);
+    let result = trace.try_fill(
+        |state| state[0] = BaseElement::ONE,
+        |step, state| {
+            // reading past the end of the state at step 5 fails while computing row 6
+            let column = if step == 5 { 2 } else { 0 };
+            state[1] = state[column] + BaseElement::ONE;
+        },
+    );
+
+    match result {
+        Err(TraceError::FillPanic { rows, message }) => {
+            assert_eq!(6..7, rows);
+            assert!(message.contains("index out of bounds"), "{}", message);
+        }
+        result => panic!("expected fill panic, but was {result:?}"),
+    }
+
+    // a panic in the init closure is attributed to the first row
+    let result = trace.try_fill(|_| panic!("bad init"), |_, _| {});
+    let expected = TraceError::FillPanic {
+        rows: 0..1,
+        message: "bad init".to_string(),
+    };
+    assert_eq!(Err(expected), result);
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "trace fill closure panicked while computing row 10: step 9")]
+fn trace_table_fill_panic() {
+    let mut trace = TraceTable::<BaseElement>::new(2, 16);
+    trace.fill(
+        |state| state[0] = BaseElement::ONE,
+        |step, _| {
+            if step == 9 {
+                panic!("step {step}");
+            }
+        },
+    );
+}
+
+#[test]
+fn trace_table_try_fill_with() {
+    // the computation completes at row 5; the remaining rows are padded with its state
+    let mut trace = TraceTable::<BaseElement>::new(2, 16);
+    let result = trace.try_fill_with(
+        |state| {
+            state[0] = BaseElement::ONE;
+            Ok(())
+        },
+        |step, state| {
+            state[0] += BaseElement::ONE;
+            state[1] = BaseElement::from(step as u32);
+            if step == 4 {
+                Ok(FillStatus::Complete)
+            } else {
+                Ok(FillStatus::Continue)
+            }
+        },
+    );
+    assert_eq!(Ok(()), result);
+
+    let expected_first_column = [1, 2, 3, 4, 5, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6]
+        .iter()
+        .map(|&v| BaseElement::from(v as u32))
+        .collect::<Vec<_>>();
+    assert_eq!(expected_first_column, trace.get_column(0));
+    for row in 5..16 {
+        assert_eq!(BaseElement::from(4u32), trace.get(1, row));
+    }
+
+    // without completion, every row is computed by the update closure
+    let result = trace.try_fill_with(
+        |state| {
+            state[0] = BaseElement::ZERO;
+            Ok(())
+        },
+        |_, state| {
+            state[0] += BaseElement::ONE;
+            Ok(FillStatus::Continue)
+        },
+    );
+    assert_eq!(Ok(()), result);
+    assert_eq!(BaseElement::from(15u32), trace.get(0, 15));
+}
+
+#[test]
+fn trace_table_fill_columns() {
+    // every column squares its value at each step; filling columns independently must produce
+    // the same trace as filling it row by row
+    let mut expected = TraceTable::<BaseElement>::new(4, 32);
+    expected.fill(
+        |state| {
+            for (i, value) in state.iter_mut().enumerate() {
+                *value = BaseElement::from(i as u32 + 2);
+            }
+        },
+        |_, state| {
+            for value in state.iter_mut() {
+                *value = value.square();
+            }
+        },
+    );
+
+    let mut trace = TraceTable::<BaseElement>::new(4, 32);
+    trace.fill_columns(|i, column| {
+        column[0] = BaseElement::from(i as u32 + 2);
+        for step in 1..column.len() {
+            column[step] = column[step - 1].square();
+        }
+    });
+
+    for i in 0..4 {
+        assert_eq!(expected.get_column(i), trace.get_column(i));
+    }
+}
+
+#[test]
+fn trace_table_try_fill_with_error() {
+    let mut trace = TraceTable::<BaseElement>::new(2, 16);
+    trace.fill(|state| state[0] = BaseElement::ZERO, |_, _| {});
+
+    // an error at step 6 aborts the build while computing row 7
+    let result = trace.try_fill_with(
+        |state| {
+            state[0] = BaseElement::ONE;
+            Ok(())
+        },
+        |step, state| {
+            if step == 6 {
+                return Err(TraceBuildError::InvalidState(format!("bad step {step}")));
+            }
+            state[0] += BaseElement::ONE;
+            Ok(FillStatus::Continue)
+        },
+    );
+    let expected = TraceError::FillFailed {
+        row: 7,
+        error: TraceBuildError::InvalidState("bad step 6".to_string()),
+    };
+    assert_eq!(Err(expected), result);
+
+    // rows computed before the error are written, and the rest of the trace is left unchanged
+    assert_eq!(BaseElement::from(7u32), trace.get(0, 6));
+    for row in 7..16 {
+        assert_eq!(BaseElement::ZERO, trace.get(0, row));
+    }
+
+    // an error in the init closure is attributed to the first row
+    let err = trace
+        .try_fill_with(
+            |_| Err(TraceBuildError::UnexpectedOutput("bad init".to_string())),
+            |_, _| Ok(FillStatus::Continue),
+        )
+        .unwrap_err();
+    let expected = TraceError::FillFailed {
+        row: 0,
+        error: TraceBuildError::UnexpectedOutput("bad init".to_string()),
+    };
+    assert_eq!(expected, err);
+    assert_eq!(
+        "trace fill closure failed while computing row 0: unexpected computation output: bad init",
+        err.to_string()
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn trace_table_fragment_try_fill_panic() {
+    let mut trace = TraceTable::<BaseElement>::new(2, 16);
+    let results = trace
+        .fragments(4)
+        .map(|mut fragment| {
+            let offset = fragment.offset();
+            fragment.try_fill(
+                |state| state[0] = BaseElement::ONE,
+                |step, _| {
+                    if offset + step == 10 {
+                        panic!("bad row {}", offset + step + 1);
+                    }
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for (i, result) in results.into_iter().enumerate() {
+        if i == 2 {
+            let expected = TraceError::FillPanic {
+                rows: 8..12,
+                message: "bad row 11".to_string(),
+            };
+            assert_eq!(Err(expected), result);
+        } else {
+            assert_eq!(Ok(()), result);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "trace fill closure panicked while computing rows 4..8: bad row")]
+fn trace_table_fragment_fill_panic() {
+    let mut trace = TraceTable::<BaseElement>::new(2, 16);
+    trace.fragments(4).for_each(|mut fragment| {
+        let offset = fragment.offset();
+        fragment.fill(
+            |state| state[0] = BaseElement::ONE,
+            |step, _| {
+                if offset + step == 6 {
+                    panic!("bad row");
+                }
+            },
+        );
+    });
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
+fn read_trace(bytes: &[u8]) -> Result<TraceTable<BaseElement>, DeserializationError> {
+    TraceTable::read_from(&mut SliceReader::new(bytes))
+}
+
+/// Builds a trace with a binary column, a column of 7-bit integers, a column of two large
+/// values, and a column of distinct large values.
+fn build_codec_trace() -> TraceTable<BaseElement> {
+    let trace_length = 8;
+    let large = BaseElement::new(u128::MAX);
+    TraceTable::init(vec![
+        (0..trace_length as u32)
+            .map(|i| BaseElement::from((i % 3 == 0) as u32))
+            .collect(),
+        (0..trace_length as u32)
+            .map(|i| BaseElement::from(i * 15))
+            .collect(),
+        (0..trace_length)
+            .map(|i| if i % 2 == 0 { -BaseElement::ONE } else { large })
+            .collect(),
+        (0..trace_length as u32)
+            .map(|i| -BaseElement::from(i))
+            .collect(),
+    ])
+}
+
 fn build_lde_domain<B: StarkField>(domain_size: usize) -> Vec<B> {
     let g = B::get_root_of_unity(log2(domain_size));
     get_power_series_with_offset(g, B::GENERATOR, domain_size)
 }
+
+// HELPER TYPES
+// ================================================================================================
+
+/// Opens a Merkle tree by aggregating individual Merkle paths, as would be done for a tree kept
+/// in an external storage.
+struct PathOpener(MerkleTree<Blake3>);
+
+impl CommitmentOpener<Blake3> for PathOpener {
+    fn root(&self) -> <Blake3 as crypto::Hasher>::Digest {
+        *self.0.root()
+    }
+
+    fn open(&self, indexes: &[usize]) -> Result<BatchMerkleProof<Blake3>, MerkleTreeError> {
+        let mut paths = Vec::with_capacity(indexes.len());
+        for &index in indexes {
+            let mut path = self.0.prove(index)?;
+            let leaf = path.remove(0);
+            paths.push((index, leaf, path));
+        }
+        BatchMerkleProof::from_single_paths(paths)
+    }
+}
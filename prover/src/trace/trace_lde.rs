@@ -4,9 +4,12 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::Matrix;
-use air::EvaluationFrame;
+use air::{EvaluationFrame, LdePos};
 use math::FieldElement;
-use utils::collections::Vec;
+use utils::{
+    collections::Vec, string::ToString, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Serializable,
+};
 
 // TRACE LOW DEGREE EXTENSION
 // ================================================================================================
@@ -32,6 +35,16 @@ impl<E: FieldElement> TraceLde<E> {
     // STATE MUTATORS
     // --------------------------------------------------------------------------------------------
 
+    /// Appends columns of the provided preprocessed segment LDE to the columns of the main
+    /// segment of this trace LDE.
+    ///
+    /// Columns of the preprocessed segment are not distinguished from the columns of the main
+    /// segment in this table, and thus, they are read into main trace frames and combined with
+    /// other trace columns right after the columns of the main segment.
+    pub fn add_preprocessed_segment(&mut self, preprocessed_lde: &Matrix<E::BaseField>) {
+        self.main_segment_lde.append_columns(preprocessed_lde);
+    }
+
     /// Adds the provided auxiliary segment LDE to this trace LDE.
     pub fn add_aux_segment(&mut self, aux_segment_lde: Matrix<E>) {
         assert_eq!(
@@ -45,7 +58,8 @@ impl<E: FieldElement> TraceLde<E> {
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
-    /// Returns number of columns in the main segment of the execution trace.
+    /// Returns number of columns in the main segment of the execution trace, including columns
+    /// of the preprocessed segment (if any).
     pub fn main_trace_width(&self) -> usize {
         self.main_segment_lde.num_cols()
     }
@@ -57,6 +71,11 @@ impl<E: FieldElement> TraceLde<E> {
             .fold(0, |s, m| s + m.num_cols())
     }
 
+    /// Returns number of auxiliary segments in the execution trace.
+    pub fn num_aux_segments(&self) -> usize {
+        self.aux_segment_ldes.len()
+    }
+
     /// Returns the number of rows in the execution trace.
     pub fn trace_len(&self) -> usize {
         self.main_segment_lde.num_rows()
@@ -67,37 +86,59 @@ impl<E: FieldElement> TraceLde<E> {
         self.blowup
     }
 
-    /// Reads current and next rows from the main trace segment into the specified frame.
+    /// Reads rows from the main trace segment into the specified frame; a row is read for each
+    /// of the row offsets of the frame (by default, these are the current and the next rows).
     pub fn read_main_trace_frame_into(
         &self,
-        lde_step: usize,
+        lde_step: LdePos,
         frame: &mut EvaluationFrame<E::BaseField>,
     ) {
-        // at the end of the trace, next state wraps around and we read the first step again
-        let next_lde_step = (lde_step + self.blowup()) % self.trace_len();
-
         // copy main trace segment values into the frame
-        self.main_segment_lde
-            .read_row_into(lde_step, frame.current_mut());
-        self.main_segment_lde
-            .read_row_into(next_lde_step, frame.next_mut());
+        read_frame_into(&self.main_segment_lde, self.blowup(), lde_step, frame);
     }
 
-    /// Reads current and next rows from the auxiliary trace segment into the specified frame.
-    pub fn read_aux_trace_frame_into(&self, lde_step: usize, frame: &mut EvaluationFrame<E>) {
-        // at the end of the trace, next state wraps around and we read the first step again
-        let next_lde_step = (lde_step + self.blowup()) % self.trace_len();
-
+    /// Reads rows from the auxiliary trace segment into the specified frame; a row is read for
+    /// each of the row offsets of the frame (by default, these are the current and the next
+    /// rows).
+    pub fn read_aux_trace_frame_into(&self, lde_step: LdePos, frame: &mut EvaluationFrame<E>) {
         //copy auxiliary trace segment values into the frame
-        let mut offset = 0;
-        for segment in self.aux_segment_ldes.iter() {
-            segment.read_row_into(lde_step, &mut frame.current_mut()[offset..]);
-            segment.read_row_into(next_lde_step, &mut frame.next_mut()[offset..]);
-            offset += segment.num_cols();
+        for row_idx in 0..frame.num_rows() {
+            let step = self.get_frame_row_step(lde_step, frame.offsets()[row_idx]);
+            let mut offset = 0;
+            for segment in self.aux_segment_ldes.iter() {
+                segment.read_row_into(step, &mut frame.row_mut(row_idx)[offset..]);
+                offset += segment.num_cols();
+            }
         }
     }
 
-    /// Returns a reference to [Matrix] representing the main trace segment.
+    /// Returns evaluations of the random linear combination sum(gamma^i * T_i(x)) of all trace
+    /// columns over the LDE domain, where T_i is the polynomial of the i-th column across all
+    /// trace segments (main segment columns first).
+    pub fn combine_columns(&self, gamma: E) -> Vec<E> {
+        let mut result = vec![E::ZERO; self.trace_len()];
+        let mut coefficient = E::ONE;
+        for column in self.main_segment_lde.columns() {
+            for (value, &x) in result.iter_mut().zip(column) {
+                *value += coefficient.mul_base(x);
+            }
+            coefficient *= gamma;
+        }
+        for column in self
+            .aux_segment_ldes
+            .iter()
+            .flat_map(|segment| segment.columns())
+        {
+            for (value, &x) in result.iter_mut().zip(column) {
+                *value += coefficient * x;
+            }
+            coefficient *= gamma;
+        }
+        result
+    }
+
+    /// Returns a reference to [Matrix] representing the main trace segment followed by the
+    /// preprocessed segment (if any).
     pub fn get_main_segment(&self) -> &Matrix<E::BaseField> {
         &self.main_segment_lde
     }
@@ -107,4 +148,96 @@ impl<E: FieldElement> TraceLde<E> {
     pub fn get_aux_segment(&self, aux_segment_idx: usize) -> &Matrix<E> {
         &self.aux_segment_ldes[aux_segment_idx]
     }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the LDE step of the frame row located `row_offset` trace steps after the specified
+    /// LDE step; at the end of the trace, rows wrap around to the start of the trace.
+    fn get_frame_row_step(&self, lde_step: LdePos, row_offset: usize) -> usize {
+        get_frame_row_step(lde_step, row_offset, self.blowup(), self.trace_len())
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Reads rows of the provided extended trace segment into the specified frame; a row is read for
+/// each of the row offsets of the frame.
+///
+/// `blowup` must be the blowup factor with which the segment was extended; this is used to read
+/// the same frames as [TraceLde::read_main_trace_frame_into()] reads before the segment is
+/// added to a [TraceLde].
+pub(crate) fn read_frame_into<E: FieldElement>(
+    segment_lde: &Matrix<E>,
+    blowup: usize,
+    lde_step: LdePos,
+    frame: &mut EvaluationFrame<E>,
+) {
+    read_frame_columns_into(segment_lde, blowup, lde_step, 0, frame);
+}
+
+/// Reads rows of the provided extended trace segment into the columns of the specified frame
+/// starting with column `col_offset`; columns of the frame before `col_offset` are not modified.
+///
+/// This is used to read frames spanning several segments before the last of these segments is
+/// added to a [TraceLde].
+pub(crate) fn read_frame_columns_into<E: FieldElement>(
+    segment_lde: &Matrix<E>,
+    blowup: usize,
+    lde_step: LdePos,
+    col_offset: usize,
+    frame: &mut EvaluationFrame<E>,
+) {
+    for row_idx in 0..frame.num_rows() {
+        let step = get_frame_row_step(
+            lde_step,
+            frame.offsets()[row_idx],
+            blowup,
+            segment_lde.num_rows(),
+        );
+        segment_lde.read_row_into(step, &mut frame.row_mut(row_idx)[col_offset..]);
+    }
+}
+
+fn get_frame_row_step(lde_step: LdePos, row_offset: usize, blowup: usize, lde_len: usize) -> usize {
+    (lde_step.as_usize() + row_offset * blowup) % lde_len
+}
+
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<E: FieldElement> Serializable for TraceLde<E> {
+    /// Serializes `self` and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.blowup as u32);
+        self.main_segment_lde.write_into(target);
+        target.write_u8(self.aux_segment_ldes.len() as u8);
+        for segment_lde in self.aux_segment_ldes.iter() {
+            segment_lde.write_into(target);
+        }
+    }
+}
+
+impl<E: FieldElement> Deserializable for TraceLde<E> {
+    /// Reads a trace low-degree extension table from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid table could not be read from the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let blowup = source.read_u32()? as usize;
+        let mut result = Self::new(Matrix::read_from(source)?, blowup);
+        let num_aux_segments = source.read_u8()? as usize;
+        for _ in 0..num_aux_segments {
+            let segment_lde = Matrix::read_from(source)?;
+            if segment_lde.num_rows() != result.trace_len() {
+                return Err(DeserializationError::InvalidValue(
+                    "number of rows in auxiliary segment must be the same as in the main segment"
+                        .to_string(),
+                ));
+            }
+            result.aux_segment_ldes.push(segment_lde);
+        }
+        Ok(result)
+    }
 }
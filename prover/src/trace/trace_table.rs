@@ -3,10 +3,14 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{Matrix, Trace};
-use air::{EvaluationFrame, TraceInfo, TraceLayout};
+use super::{ColumnCodec, Matrix, Trace, TraceStatistics, DEFAULT_DISTINCT_VALUES_CAP};
+use crate::{TraceBuildError, TraceError};
+use air::{EvaluationFrame, TraceInfo, TraceLayout, TraceLayoutBuilder};
 use math::{log2, FieldElement, StarkField};
-use utils::{collections::Vec, uninit_vector};
+use utils::{
+    collections::Vec, iter_mut, string::ToString, uninit_vector, ByteReader, ByteWriter,
+    Deserializable, DeserializationError, Serializable,
+};
 
 #[cfg(not(feature = "concurrent"))]
 use utils::collections::vec;
@@ -14,6 +18,12 @@ use utils::collections::vec;
 #[cfg(feature = "concurrent")]
 use utils::{iterators::*, rayon};
 
+#[cfg(feature = "std")]
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
 // CONSTANTS
 // ================================================================================================
 
@@ -59,6 +69,11 @@ const MIN_FRAGMENT_LENGTH: usize = 2;
 /// [fill()](TraceTableFragment::fill) method to fill all fragments with data in parallel.
 /// The semantics of the fragment's [TraceTableFragment::fill()] method are identical to the
 /// semantics of the [TraceTable::fill()] method.
+///
+/// For computations in which every column of the trace can be computed independently of other
+/// columns, the [fill_columns()](TraceTable::fill_columns) method can be used instead; it fills
+/// all columns of the trace in parallel.
+#[derive(Clone)]
 pub struct TraceTable<B: StarkField> {
     layout: TraceLayout,
     trace: Matrix<B>,
@@ -131,7 +146,7 @@ impl<B: StarkField> TraceTable<B> {
 
         let columns = unsafe { (0..width).map(|_| uninit_vector(length)).collect() };
         Self {
-            layout: TraceLayout::new(width, [0], [0]),
+            layout: build_main_segment_layout(width),
             trace: Matrix::new(columns),
             meta,
         }
@@ -182,7 +197,7 @@ impl<B: StarkField> TraceTable<B> {
         }
 
         Self {
-            layout: TraceLayout::new(columns.len(), [0], [0]),
+            layout: build_main_segment_layout(columns.len()),
             trace: Matrix::new(columns),
             meta: vec![],
         }
@@ -227,19 +242,122 @@ impl<B: StarkField> TraceTable<B> {
     ///   - index of the last updated row (starting with 0).
     ///   - a mutable reference to the last updated state; the contents of the state are copied
     ///     into the next row of the trace after the closure returns.
+    ///
+    /// # Panics
+    /// Panics if either of the closures panics; when `std` feature is enabled, the panic message
+    /// names the row which was being computed (see [try_fill()](TraceTable::try_fill)).
     pub fn fill<I, U>(&mut self, init: I, update: U)
     where
         I: Fn(&mut [B]),
         U: Fn(usize, &mut [B]),
+    {
+        #[cfg(feature = "std")]
+        if let Err(err) = self.try_fill(init, update) {
+            panic!("{err}");
+        }
+
+        #[cfg(not(feature = "std"))]
+        {
+            let (width, length) = (self.main_trace_width(), self.length());
+            let trace = &mut self.trace;
+            fill_rows(
+                width,
+                length,
+                init,
+                update,
+                |i, row| trace.update_row(i, row),
+                &mut 0,
+            );
+        }
+    }
+
+    /// Fills all rows in the execution trace in the same way as [fill()](TraceTable::fill) does,
+    /// but returns an error instead of unwinding if either of the closures panics.
+    ///
+    /// # Errors
+    /// Returns [TraceError::FillPanic] if either of the closures panics; the error contains the
+    /// index of the row which was being computed and the panic message. In such a case, the
+    /// trace is left partially filled.
+    #[cfg(feature = "std")]
+    pub fn try_fill<I, U>(&mut self, init: I, update: U) -> Result<(), TraceError>
+    where
+        I: Fn(&mut [B]),
+        U: Fn(usize, &mut [B]),
+    {
+        let (width, length) = (self.main_trace_width(), self.length());
+        let trace = &mut self.trace;
+        catch_fill_panic(|current_row| {
+            fill_rows(
+                width,
+                length,
+                init,
+                update,
+                |i, row| trace.update_row(i, row),
+                current_row,
+            )
+        })
+        .map_err(|(row, message)| TraceError::FillPanic {
+            rows: row..row + 1,
+            message,
+        })
+    }
+
+    /// Fills all rows in the execution trace using closures which can fail.
+    ///
+    /// The closures are executed in the same way as in [fill()](TraceTable::fill), except that:
+    /// - `init` and `update` closures return a [TraceBuildError] if the state of the next row
+    ///   cannot be computed; in such a case, filling stops immediately.
+    /// - `update` closure returns [FillStatus::Complete] if the computation described by the
+    ///   trace is finished; the state left by the closure is then copied into the next row and
+    ///   into all remaining rows of the trace, and the closure is not executed again.
+    ///
+    /// Unlike [try_fill()](TraceTable::try_fill), this method does not catch panics.
+    ///
+    /// # Errors
+    /// Returns [TraceError::FillFailed] if either of the closures returns an error; the error
+    /// contains the index of the row which was being computed. In such a case, the rows starting
+    /// with that row are left unchanged.
+    pub fn try_fill_with<I, U>(&mut self, init: I, update: U) -> Result<(), TraceError>
+    where
+        I: Fn(&mut [B]) -> Result<(), TraceBuildError>,
+        U: Fn(usize, &mut [B]) -> Result<FillStatus, TraceBuildError>,
     {
         let mut state = vec![B::ZERO; self.main_trace_width()];
-        init(&mut state);
+        init(&mut state).map_err(|error| TraceError::FillFailed { row: 0, error })?;
         self.update_row(0, &state);
 
         for i in 0..self.length() - 1 {
-            update(i, &mut state);
+            let status = update(i, &mut state)
+                .map_err(|error| TraceError::FillFailed { row: i + 1, error })?;
             self.update_row(i + 1, &state);
+            if status == FillStatus::Complete {
+                for row in i + 2..self.length() {
+                    self.update_row(row, &state);
+                }
+                break;
+            }
         }
+        Ok(())
+    }
+
+    /// Fills all columns in the execution trace independently of each other.
+    ///
+    /// The `generator` closure is executed once for every column of the trace; it receives the
+    /// index of the column and a mutable reference to all values of the column, and must write
+    /// every one of these values. When `concurrent` feature is enabled, the columns are filled
+    /// in multiple threads.
+    ///
+    /// This method is meant for traces in which values of a column do not depend on values of
+    /// other columns. For traces in which every row depends on the previous row, use
+    /// [fill()](TraceTable::fill), optionally on [fragments](TraceTable::fragments) of the trace.
+    pub fn fill_columns<F>(&mut self, generator: F)
+    where
+        F: Fn(usize, &mut [B]) + Send + Sync,
+    {
+        let mut columns = self.trace.columns_mut().collect::<Vec<_>>();
+        iter_mut!(columns)
+            .enumerate()
+            .for_each(|(i, column)| generator(i, column));
     }
 
     /// Updates a single row in the execution trace with provided data.
@@ -259,7 +377,10 @@ impl<B: StarkField> TraceTable<B> {
     /// Panics if `fragment_length` is smaller than 2, greater than the length of the trace,
     /// or is not a power of two.
     #[cfg(not(feature = "concurrent"))]
-    pub fn fragments(&mut self, fragment_length: usize) -> vec::IntoIter<TraceTableFragment<B>> {
+    pub fn fragments(
+        &mut self,
+        fragment_length: usize,
+    ) -> vec::IntoIter<TraceTableFragment<'_, B>> {
         self.build_fragments(fragment_length).into_iter()
     }
 
@@ -281,7 +402,7 @@ impl<B: StarkField> TraceTable<B> {
 
     /// Returns a vector of trace fragments each covering the number of steps specified by the
     /// `fragment_length` parameter.
-    fn build_fragments(&mut self, fragment_length: usize) -> Vec<TraceTableFragment<B>> {
+    fn build_fragments(&mut self, fragment_length: usize) -> Vec<TraceTableFragment<'_, B>> {
         assert!(
             fragment_length >= MIN_FRAGMENT_LENGTH,
             "fragment length must be at least {MIN_FRAGMENT_LENGTH}, but was {fragment_length}"
@@ -338,6 +459,106 @@ impl<B: StarkField> TraceTable<B> {
     pub fn read_row_into(&self, step: usize, target: &mut [B]) {
         self.trace.read_row_into(step, target);
     }
+
+    // STATISTICS
+    // --------------------------------------------------------------------------------------------
+
+    /// Computes per-column statistics of this execution trace; up to
+    /// [DEFAULT_DISTINCT_VALUES_CAP] distinct values are counted for every column.
+    ///
+    /// This requires a full pass over the trace, and is meant to be used for debugging trace
+    /// builders (see [TraceStatistics::warn_on_degenerate()]).
+    pub fn statistics(&self) -> TraceStatistics<B> {
+        TraceStatistics::new(&self.trace, DEFAULT_DISTINCT_VALUES_CAP)
+    }
+
+    // SERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the codecs which encode every column of this trace into the smallest number of
+    /// bytes (see [ColumnCodec::select()]).
+    ///
+    /// This is the set of codecs used when the trace is serialized via [Serializable] trait.
+    pub fn select_column_codecs(&self) -> Vec<ColumnCodec> {
+        self.trace.columns().map(ColumnCodec::select).collect()
+    }
+
+    /// Serializes this trace encoding its columns with the specified codecs, and writes the
+    /// resulting bytes into the `target`.
+    ///
+    /// Codecs for the columns are written into the header of the serialized trace, and thus, a
+    /// trace serialized with any set of codecs can be read via [Deserializable] trait.
+    ///
+    /// # Panics
+    /// Panics if the number of codecs is not equal to the width of this trace, or if any of the
+    /// columns cannot be encoded with the corresponding codec.
+    pub fn write_with_codecs<W: ByteWriter>(&self, target: &mut W, codecs: &[ColumnCodec]) {
+        assert_eq!(
+            self.width(),
+            codecs.len(),
+            "expected {} column codecs, but was {}",
+            self.width(),
+            codecs.len()
+        );
+        target.write_u8(self.width() as u8);
+        target.write_u32(self.length() as u32);
+        target.write_u16(self.meta.len() as u16);
+        target.write_u8_slice(&self.meta);
+        codecs.write_into(target);
+        for (column, codec) in self.trace.columns().zip(codecs) {
+            codec.encode(column, target);
+        }
+    }
+}
+
+// SERIALIZATION / DESERIALIZATION
+// ================================================================================================
+
+impl<B: StarkField> Serializable for TraceTable<B> {
+    /// Serializes `self` encoding every column with the codec which yields the smallest encoding
+    /// of the column, and writes the resulting bytes into the `target`.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.write_with_codecs(target, &self.select_column_codecs());
+    }
+}
+
+impl<B: StarkField> Deserializable for TraceTable<B> {
+    /// Reads an execution trace from the specified `source`.
+    ///
+    /// # Errors
+    /// Returns an error if a valid execution trace could not be read from the `source`; this
+    /// includes the cases when the dimensions of the trace are not valid, and when any of the
+    /// values of the trace does not decode to a canonical field element.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let width = source.read_u8()? as usize;
+        if width == 0 {
+            return Err(DeserializationError::InvalidValue(
+                "execution trace must consist of at least one column".to_string(),
+            ));
+        }
+        let length = source.read_u32()? as usize;
+        if length < TraceInfo::MIN_TRACE_LENGTH
+            || !length.is_power_of_two()
+            || log2(length) > B::TWO_ADICITY
+        {
+            return Err(DeserializationError::InvalidValue(format!(
+                "execution trace length {length} is not valid"
+            )));
+        }
+        let meta_len = source.read_u16()? as usize;
+        let meta = source.read_u8_vec(meta_len)?;
+
+        let codecs = ColumnCodec::read_batch_from(source, width)?;
+        let columns = codecs
+            .iter()
+            .map(|codec| codec.decode(source, length))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            layout: build_main_segment_layout(width),
+            trace: Matrix::new(columns),
+            meta,
+        })
+    }
 }
 
 // TRACE TRAIT IMPLEMENTATION
@@ -359,9 +580,10 @@ impl<B: StarkField> Trace for TraceTable<B> {
     }
 
     fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<Self::BaseField>) {
-        let next_row_idx = (row_idx + 1) % self.length();
-        self.trace.read_row_into(row_idx, frame.current_mut());
-        self.trace.read_row_into(next_row_idx, frame.next_mut());
+        for i in 0..frame.num_rows() {
+            let frame_row_idx = (row_idx + frame.offsets()[i]) % self.length();
+            self.trace.read_row_into(frame_row_idx, frame.row_mut(i));
+        }
     }
 
     fn main_segment(&self) -> &Matrix<B> {
@@ -437,25 +659,151 @@ impl<'a, B: StarkField> TraceTableFragment<'a, B> {
     ///   - index of the last updated row (starting with 0).
     ///   - a mutable reference to the last updated state; the contents of the state are copied
     ///     into the next row of the fragment after the closure returns.
+    ///
+    /// # Panics
+    /// Panics if either of the closures panics; when `std` feature is enabled, the panic message
+    /// names the range of trace rows covered by this fragment (see
+    /// [try_fill()](TraceTableFragment::try_fill)). Thus, when fragments are filled in parallel,
+    /// the failed fragment can be identified from the panic propagated out of the thread pool.
     pub fn fill<I, T>(&mut self, init_state: I, update_state: T)
     where
         I: Fn(&mut [B]),
         T: Fn(usize, &mut [B]),
     {
-        let mut state = vec![B::ZERO; self.width()];
-        init_state(&mut state);
-        self.update_row(0, &state);
+        #[cfg(feature = "std")]
+        if let Err(err) = self.try_fill(init_state, update_state) {
+            panic!("{err}");
+        }
 
-        for i in 0..self.length() - 1 {
-            update_state(i, &mut state);
-            self.update_row(i + 1, &state);
+        #[cfg(not(feature = "std"))]
+        {
+            let (width, length) = (self.width(), self.length());
+            let data = &mut self.data;
+            let write_row = |i, row: &[B]| write_fragment_row(data, i, row);
+            fill_rows(width, length, init_state, update_state, write_row, &mut 0);
         }
     }
 
+    /// Fills all rows in the fragment in the same way as [fill()](TraceTableFragment::fill)
+    /// does, but returns an error instead of unwinding if either of the closures panics.
+    ///
+    /// # Errors
+    /// Returns [TraceError::FillPanic] if either of the closures panics; the error contains the
+    /// range of trace rows covered by this fragment and the panic message. In such a case, the
+    /// fragment is left partially filled.
+    #[cfg(feature = "std")]
+    pub fn try_fill<I, T>(&mut self, init_state: I, update_state: T) -> Result<(), TraceError>
+    where
+        I: Fn(&mut [B]),
+        T: Fn(usize, &mut [B]),
+    {
+        let (width, length, offset) = (self.width(), self.length(), self.offset);
+        let data = &mut self.data;
+        let write_row = |i, row: &[B]| write_fragment_row(data, i, row);
+        catch_fill_panic(|current_row| {
+            fill_rows(
+                width,
+                length,
+                init_state,
+                update_state,
+                write_row,
+                current_row,
+            )
+        })
+        .map_err(|(_, message)| TraceError::FillPanic {
+            rows: offset..offset + length,
+            message,
+        })
+    }
+
     /// Updates a single row in the fragment with provided data.
     pub fn update_row(&mut self, row_idx: usize, row_data: &[B]) {
-        for (column, &value) in self.data.iter_mut().zip(row_data) {
-            column[row_idx] = value;
-        }
+        write_fragment_row(&mut self.data, row_idx, row_data);
+    }
+}
+
+// FILL STATUS
+// ================================================================================================
+
+/// Indicates whether the closure filling an execution trace via
+/// [TraceTable::try_fill_with()] should be executed for the next row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillStatus {
+    /// The computation is not finished; the closure is executed again for the next row.
+    Continue,
+    /// The computation is finished; the current state is used to pad the rest of the trace.
+    Complete,
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns a layout of a trace which consists of a single segment of the specified width.
+///
+/// # Panics
+/// Panics if `width` is zero or is greater than 255.
+fn build_main_segment_layout(width: usize) -> TraceLayout {
+    TraceLayoutBuilder::new()
+        .main_width(width)
+        .build()
+        .unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Computes `num_rows` rows by executing `init` and `update` closures, and writes each computed
+/// row via `write_row`; before a row is computed, its index is recorded in `current_row` so that
+/// the row can be identified if either of the closures panics.
+fn fill_rows<B, I, U, W>(
+    width: usize,
+    num_rows: usize,
+    init: I,
+    update: U,
+    mut write_row: W,
+    current_row: &mut usize,
+) where
+    B: StarkField,
+    I: Fn(&mut [B]),
+    U: Fn(usize, &mut [B]),
+    W: FnMut(usize, &[B]),
+{
+    let mut state = vec![B::ZERO; width];
+    *current_row = 0;
+    init(&mut state);
+    write_row(0, &state);
+
+    for i in 0..num_rows - 1 {
+        *current_row = i + 1;
+        update(i, &mut state);
+        write_row(i + 1, &state);
+    }
+}
+
+/// Copies the provided row into the columns of a trace fragment at the specified index.
+fn write_fragment_row<B: StarkField>(data: &mut [&mut [B]], row_idx: usize, row_data: &[B]) {
+    for (column, &value) in data.iter_mut().zip(row_data) {
+        column[row_idx] = value;
+    }
+}
+
+/// Executes the provided fill procedure, and if it panics, returns the index of the row which was
+/// being computed together with the panic message.
+#[cfg(feature = "std")]
+fn catch_fill_panic<F>(fill: F) -> Result<(), (usize, String)>
+where
+    F: FnOnce(&mut usize),
+{
+    let mut current_row = 0;
+    catch_unwind(AssertUnwindSafe(|| fill(&mut current_row)))
+        .map_err(|payload| (current_row, get_panic_message(payload)))
+}
+
+/// Returns the message of a panic if the panic payload is a string.
+#[cfg(feature = "std")]
+fn get_panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic payload is not a string".to_string()
     }
 }
@@ -0,0 +1,58 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+use winter_utils::transpose_into;
+
+// Compares cache-blocked transposition of column-major matrices against gathering rows one at a
+// time. Values are 4 bytes wide so that the largest matrix (100 columns and 2^22 rows) together
+// with its transposition fits into a few GB of memory.
+
+const SHAPES: [(usize, usize); 3] = [(100, 1 << 22), (2, 1 << 22), (1000, 1 << 14)];
+
+fn transpose(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transpose");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    for &(width, height) in SHAPES.iter() {
+        let columns = build_columns(width, height);
+        let mut rows = vec![0u32; width * height];
+        let shape = format!("{width}x{height}");
+
+        group.bench_function(BenchmarkId::new("naive", &shape), |bench| {
+            bench.iter(|| naive_transpose(&columns, &mut rows))
+        });
+
+        group.bench_function(BenchmarkId::new("tiled", &shape), |bench| {
+            bench.iter(|| transpose_into(&columns, &mut rows, width, height))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(transpose_group, transpose);
+criterion_main!(transpose_group);
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_columns(width: usize, height: usize) -> Vec<Vec<u32>> {
+    (0..width)
+        .map(|i| (0..height as u32).map(|j| j ^ i as u32).collect())
+        .collect()
+}
+
+/// Gathers every row of the matrix from its columns one row at a time.
+fn naive_transpose(columns: &[Vec<u32>], rows: &mut [u32]) {
+    let width = columns.len();
+    for (row_idx, row) in rows.chunks_mut(width).enumerate() {
+        for (value, column) in row.iter_mut().zip(columns) {
+            *value = column[row_idx];
+        }
+    }
+}
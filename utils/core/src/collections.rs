@@ -8,6 +8,11 @@
 //! When `std` feature is enabled, this module exports collections from the Rust standard library.
 //! When `alloc` feature is enabled, same collected are provided without relying on the Rust
 //! standard library.
+//!
+//! This module also exports [InlineVec], a vector which keeps a bounded number of elements inline
+//! and allocates only when the bound is exceeded.
+
+pub use crate::inline_vec::InlineVec;
 
 #[cfg(not(feature = "std"))]
 pub use alloc::collections::{BTreeMap, BTreeSet};
@@ -0,0 +1,240 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::collections::Vec;
+use core::{
+    fmt,
+    iter::FromIterator,
+    ops::{Deref, DerefMut},
+};
+
+// INLINE VECTOR
+// ================================================================================================
+
+/// A vector which stores up to `N` elements inline, and moves its elements to the heap only when
+/// more than `N` elements are pushed into it.
+///
+/// This is meant for collections whose sizes are bounded in all but exceptional cases (e.g., the
+/// number of queries in a proof), so that building such collections does not require heap
+/// allocations. Once the elements have been moved to the heap, they stay there until the vector
+/// is dropped, even if the vector is truncated to fewer than `N` elements.
+///
+/// Elements are stored in an array of `N` elements initialized with default values, and thus,
+/// the vector can hold only elements which implement [Copy] and [Default]; this keeps the
+/// implementation free of unsafe code.
+#[derive(Clone)]
+pub struct InlineVec<T: Copy + Default, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+#[derive(Clone)]
+enum Storage<T: Copy + Default, const N: usize> {
+    Inline([T; N], usize),
+    Heap(Vec<T>),
+}
+
+impl<T: Copy + Default, const N: usize> InlineVec<T, N> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new empty vector.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::Inline([T::default(); N], 0),
+        }
+    }
+
+    /// Returns a vector of `len` elements, all set to `value`.
+    pub fn from_elem(value: T, len: usize) -> Self {
+        let mut result = Self::new();
+        result.resize(len, value);
+        result
+    }
+
+    /// Returns a vector containing a copy of the elements of the provided slice.
+    pub fn from_slice(source: &[T]) -> Self {
+        let mut result = Self::new();
+        result.extend_from_slice(source);
+        result
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of elements in this vector.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(_, len) => *len,
+            Storage::Heap(items) => items.len(),
+        }
+    }
+
+    /// Returns true if this vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the elements of this vector have been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Returns the elements of this vector as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        match &self.storage {
+            Storage::Inline(items, len) => &items[..*len],
+            Storage::Heap(items) => items,
+        }
+    }
+
+    /// Returns the elements of this vector as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.storage {
+            Storage::Inline(items, len) => &mut items[..*len],
+            Storage::Heap(items) => items,
+        }
+    }
+
+    // MODIFIERS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends an element to the end of this vector.
+    pub fn push(&mut self, value: T) {
+        match &mut self.storage {
+            Storage::Inline(items, len) if *len < N => {
+                items[*len] = value;
+                *len += 1;
+            }
+            Storage::Inline(items, len) => {
+                let mut heap = Vec::with_capacity(2 * N + 1);
+                heap.extend_from_slice(&items[..*len]);
+                heap.push(value);
+                self.storage = Storage::Heap(heap);
+            }
+            Storage::Heap(items) => items.push(value),
+        }
+    }
+
+    /// Appends all elements of the provided slice to the end of this vector.
+    pub fn extend_from_slice(&mut self, source: &[T]) {
+        match &mut self.storage {
+            Storage::Inline(items, len) if *len + source.len() <= N => {
+                items[*len..*len + source.len()].copy_from_slice(source);
+                *len += source.len();
+            }
+            Storage::Inline(items, len) => {
+                let mut heap = Vec::with_capacity(*len + source.len());
+                heap.extend_from_slice(&items[..*len]);
+                heap.extend_from_slice(source);
+                self.storage = Storage::Heap(heap);
+            }
+            Storage::Heap(items) => items.extend_from_slice(source),
+        }
+    }
+
+    /// Resizes this vector to `new_len` elements; new elements (if any) are set to `value`.
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+        } else {
+            for _ in len..new_len {
+                self.push(value);
+            }
+        }
+    }
+
+    /// Shortens this vector to `new_len` elements; does nothing if the vector is already shorter.
+    pub fn truncate(&mut self, new_len: usize) {
+        match &mut self.storage {
+            Storage::Inline(_, len) => *len = core::cmp::min(*len, new_len),
+            Storage::Heap(items) => items.truncate(new_len),
+        }
+    }
+
+    /// Removes all elements from this vector.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T: Copy + Default + PartialEq, const N: usize> InlineVec<T, N> {
+    /// Removes consecutive repeated elements from this vector.
+    pub fn dedup(&mut self) {
+        let items = self.as_mut_slice();
+        if items.is_empty() {
+            return;
+        }
+        let mut num_unique = 1;
+        for i in 1..items.len() {
+            if items[i] != items[num_unique - 1] {
+                items[num_unique] = items[i];
+                num_unique += 1;
+            }
+        }
+        self.truncate(num_unique);
+    }
+}
+
+// TRAIT IMPLEMENTATIONS
+// ================================================================================================
+
+impl<T: Copy + Default, const N: usize> Default for InlineVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Deref for InlineVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> DerefMut for InlineVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Extend<T> for InlineVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Copy + Default, const N: usize> FromIterator<T> for InlineVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<'a, T: Copy + Default, const N: usize> IntoIterator for &'a InlineVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<T: Copy + Default + PartialEq, const N: usize> PartialEq for InlineVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Copy + Default + Eq, const N: usize> Eq for InlineVec<T, N> {}
+
+impl<T: Copy + Default + fmt::Debug, const N: usize> fmt::Debug for InlineVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
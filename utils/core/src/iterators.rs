@@ -115,3 +115,77 @@ macro_rules! batch_iter_mut {
         $c($e, 0);
     };
 }
+
+// CHUNKING
+// ================================================================================================
+
+/// L2 cache size (in bytes) assumed when the actual cache size cannot be detected.
+pub const DEFAULT_CACHE_SIZE: usize = 256 * 1024;
+
+/// The smallest number of items [get_chunk_size()] places into a single chunk, unless the
+/// total number of items is smaller than that.
+pub const MIN_CHUNK_SIZE: usize = 64;
+
+/// Returns the number of items which should be processed together as a single chunk.
+///
+/// The chunk size is chosen so that data touched while processing a chunk (`item_size` bytes
+/// per item) fits into a cache of `cache_size` bytes. The result is additionally bounded so
+/// that:
+/// * A chunk contains at least [MIN_CHUNK_SIZE] items (or `num_items` if it is smaller), which
+///   keeps scheduling overhead low for very wide items.
+/// * When `concurrent` feature is enabled, there are at least as many chunks as there are
+///   threads, so that all threads get a share of the work.
+///
+/// If `num_items` is a power of two, the returned chunk size is a power of two as well, and
+/// thus, evenly divides `num_items`.
+pub fn get_chunk_size(num_items: usize, item_size: usize, cache_size: usize) -> usize {
+    if num_items <= MIN_CHUNK_SIZE {
+        return num_items.max(1);
+    }
+
+    #[cfg(feature = "concurrent")]
+    let num_threads = rayon_num_threads().next_power_of_two();
+    #[cfg(not(feature = "concurrent"))]
+    let num_threads = 1;
+
+    let max_chunk_size = (num_items / num_threads).max(MIN_CHUNK_SIZE);
+    let target = (cache_size / item_size.max(1)).max(1);
+    // round the target down to a power of two
+    let target = 1 << (usize::BITS - 1 - target.leading_zeros());
+    target.clamp(MIN_CHUNK_SIZE, max_chunk_size)
+}
+
+/// Returns the size (in bytes) of the L2 cache of the current machine.
+///
+/// The size is detected only when `std` feature is enabled and the code is running on Linux;
+/// in all other cases, [DEFAULT_CACHE_SIZE] is returned.
+pub fn cache_size_hint() -> usize {
+    #[cfg(all(feature = "std", target_os = "linux"))]
+    if let Some(size) = detect_cache_size() {
+        return size;
+    }
+    DEFAULT_CACHE_SIZE
+}
+
+/// Reads the size of the L2 cache of the first CPU from sysfs.
+#[cfg(all(feature = "std", target_os = "linux"))]
+fn detect_cache_size() -> Option<usize> {
+    let size = std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cache/index2/size").ok()?;
+    parse_cache_size(size.trim())
+}
+
+/// Parses cache size strings such as "512K" or "2M" into a number of bytes.
+#[cfg(all(feature = "std", target_os = "linux"))]
+fn parse_cache_size(size: &str) -> Option<usize> {
+    let (digits, multiplier) = match size.as_bytes().last()? {
+        b'K' => (&size[..size.len() - 1], 1024),
+        b'M' => (&size[..size.len() - 1], 1024 * 1024),
+        _ => (size, 1),
+    };
+    let size = digits.parse::<usize>().ok()? * multiplier;
+    if size == 0 {
+        None
+    } else {
+        Some(size)
+    }
+}
@@ -5,18 +5,30 @@
 
 //! This crate contains utility traits, functions, and macros used by other crates of Winterfell
 //! STARK prover and verifier.
+//!
+//! When `safe_only` feature is enabled, the crate is compiled with `#![deny(unsafe_code)]`, and
+//! functions which re-interpret memory are implemented using safe standard library APIs. The
+//! public API of the crate is the same in both modes: [uninit_vector()] cannot be implemented
+//! without unsafe code, and thus, it is the only function exempt from the lint; code reachable
+//! from the verifier does not call it when `safe_only` feature is enabled. The feature requires
+//! Rust 1.88 or later.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(all(feature = "safe_only", not(test)), deny(unsafe_code))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
 extern crate alloc;
 
-use core::{convert::TryInto, mem, slice};
+use core::convert::TryInto;
+#[cfg(not(feature = "safe_only"))]
+use core::{mem, slice};
 
 pub mod collections;
 use collections::Vec;
 
+mod inline_vec;
+
 pub mod string;
 use string::ToString;
 
@@ -25,6 +37,9 @@ pub mod iterators;
 mod errors;
 pub use errors::DeserializationError;
 
+mod transpose;
+pub use transpose::{transpose_into, RowMajorView, RowTile, RowTiles, TRANSPOSE_TILE_HEIGHT};
+
 #[cfg(test)]
 mod tests;
 
@@ -118,6 +133,10 @@ impl<T: Serializable, const N: usize> Serializable for &[[T; N]] {
 // DESERIALIZABLE
 // ================================================================================================
 
+/// Maximum number of bytes allocated by [Deserializable::read_batch_from()] before any elements
+/// have been read.
+pub const MAX_PREALLOCATED_BYTES: usize = 1 << 20;
+
 /// Defines how to deserialize `Self` from bytes.
 pub trait Deserializable: Sized {
     // REQUIRED METHODS
@@ -147,11 +166,16 @@ pub trait Deserializable: Sized {
     ///
     /// Note: if the error occurs, the reader is not rolled back to the state prior to calling
     /// this function.
+    ///
+    /// Memory for the elements is allocated up front, but never for more than
+    /// [MAX_PREALLOCATED_BYTES] bytes; thus, a malformed number of elements cannot cause a large
+    /// allocation before the elements are actually read.
     fn read_batch_from<R: ByteReader>(
         source: &mut R,
         num_elements: usize,
     ) -> Result<Vec<Self>, DeserializationError> {
-        let mut result = Vec::new();
+        let max_elements = MAX_PREALLOCATED_BYTES / core::cmp::max(core::mem::size_of::<Self>(), 1);
+        let mut result = Vec::with_capacity(core::cmp::min(num_elements, max_elements));
         for _ in 0..num_elements {
             let element = Self::read_from(source)?;
             result.push(element)
@@ -393,6 +417,16 @@ impl ByteWriter for Vec<u8> {
     }
 }
 
+impl<const N: usize> ByteWriter for collections::InlineVec<u8, N> {
+    fn write_u8(&mut self, value: u8) {
+        self.push(value);
+    }
+
+    fn write_u8_slice(&mut self, values: &[u8]) {
+        self.extend_from_slice(values);
+    }
+}
+
 // AS BYTES
 // ================================================================================================
 
@@ -408,18 +442,25 @@ pub trait AsBytes {
 impl<const N: usize, const M: usize> AsBytes for [[u8; N]; M] {
     /// Flattens a two-dimensional array of bytes into a slice of bytes.
     fn as_bytes(&self) -> &[u8] {
-        let p = self.as_ptr();
-        let len = N * M;
-        unsafe { slice::from_raw_parts(p as *const u8, len) }
+        #[cfg(feature = "safe_only")]
+        #[allow(clippy::incompatible_msrv)]
+        let result = self.as_flattened();
+
+        #[cfg(not(feature = "safe_only"))]
+        let result = {
+            let p = self.as_ptr();
+            let len = N * M;
+            unsafe { slice::from_raw_parts(p as *const u8, len) }
+        };
+
+        result
     }
 }
 
 impl<const N: usize> AsBytes for [[u8; N]] {
     /// Flattens a slice of byte arrays into a slice of bytes.
     fn as_bytes(&self) -> &[u8] {
-        let p = self.as_ptr();
-        let len = self.len() * N;
-        unsafe { slice::from_raw_parts(p as *const u8, len) }
+        flatten_slice_elements(self)
     }
 }
 
@@ -433,7 +474,7 @@ impl<const N: usize> AsBytes for [[u8; N]] {
 ///
 /// # Safety
 /// Using values from the returned vector before initializing them will lead to undefined behavior.
-#[allow(clippy::uninit_vec)]
+#[allow(unsafe_code, clippy::uninit_vec)]
 pub unsafe fn uninit_vector<T>(length: usize) -> Vec<T> {
     let mut vector = Vec::with_capacity(length);
     vector.set_len(length);
@@ -466,11 +507,20 @@ pub fn group_vector_elements<T, const N: usize>(source: Vec<T>) -> Vec<[T; N]> {
         N,
         source.len()
     );
-    let mut v = mem::ManuallyDrop::new(source);
-    let p = v.as_mut_ptr();
-    let len = v.len() / N;
-    let cap = v.capacity() / N;
-    unsafe { Vec::from_raw_parts(p as *mut [T; N], len, cap) }
+
+    #[cfg(feature = "safe_only")]
+    let result = safe::group_vector_elements(source);
+
+    #[cfg(not(feature = "safe_only"))]
+    let result = {
+        let mut v = mem::ManuallyDrop::new(source);
+        let p = v.as_mut_ptr();
+        let len = v.len() / N;
+        let cap = v.capacity() / N;
+        unsafe { Vec::from_raw_parts(p as *mut [T; N], len, cap) }
+    };
+
+    result
 }
 
 /// Transmutes a slice of `n` elements into a slice of `n` / `N` elements, each of which is
@@ -494,9 +544,19 @@ pub fn group_slice_elements<T, const N: usize>(source: &[T]) -> &[[T; N]] {
         0,
         "source length must be divisible by {N}"
     );
-    let p = source.as_ptr();
-    let len = source.len() / N;
-    unsafe { slice::from_raw_parts(p as *const [T; N], len) }
+
+    #[cfg(feature = "safe_only")]
+    #[allow(clippy::incompatible_msrv)]
+    let result = source.as_chunks::<N>().0;
+
+    #[cfg(not(feature = "safe_only"))]
+    let result = {
+        let p = source.as_ptr();
+        let len = source.len() / N;
+        unsafe { slice::from_raw_parts(p as *const [T; N], len) }
+    };
+
+    result
 }
 
 /// Transmutes a slice of `n` arrays each of length `N`, into a slice of `N` * `n` elements.
@@ -511,9 +571,18 @@ pub fn group_slice_elements<T, const N: usize>(source: &[T]) -> &[[T; N]] {
 /// assert_eq!(&[1, 2, 3, 4, 5, 6, 7, 8], b);
 /// ```
 pub fn flatten_slice_elements<T, const N: usize>(source: &[[T; N]]) -> &[T] {
-    let p = source.as_ptr();
-    let len = source.len() * N;
-    unsafe { slice::from_raw_parts(p as *const T, len) }
+    #[cfg(feature = "safe_only")]
+    #[allow(clippy::incompatible_msrv)]
+    let result = source.as_flattened();
+
+    #[cfg(not(feature = "safe_only"))]
+    let result = {
+        let p = source.as_ptr();
+        let len = source.len() * N;
+        unsafe { slice::from_raw_parts(p as *const T, len) }
+    };
+
+    result
 }
 
 /// Transmutes a vector of `n` arrays each of length `N`, into a vector of `N` * `n` elements.
@@ -528,11 +597,20 @@ pub fn flatten_slice_elements<T, const N: usize>(source: &[[T; N]]) -> &[T] {
 /// assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], b);
 /// ```
 pub fn flatten_vector_elements<T, const N: usize>(source: Vec<[T; N]>) -> Vec<T> {
-    let v = mem::ManuallyDrop::new(source);
-    let p = v.as_ptr();
-    let len = v.len() * N;
-    let cap = v.capacity() * N;
-    unsafe { Vec::from_raw_parts(p as *mut T, len, cap) }
+    #[cfg(feature = "safe_only")]
+    #[allow(clippy::incompatible_msrv)]
+    let result = source.into_flattened();
+
+    #[cfg(not(feature = "safe_only"))]
+    let result = {
+        let v = mem::ManuallyDrop::new(source);
+        let p = v.as_ptr();
+        let len = v.len() * N;
+        let cap = v.capacity() * N;
+        unsafe { Vec::from_raw_parts(p as *mut T, len, cap) }
+    };
+
+    result
 }
 
 // TRANSPOSING
@@ -563,7 +641,12 @@ pub fn transpose_slice<T: Copy + Send + Sync, const N: usize>(source: &[T]) -> V
         source.len()
     );
 
+    #[cfg(feature = "safe_only")]
+    let mut result = safe::init_rows(source, row_count);
+
+    #[cfg(not(feature = "safe_only"))]
     let mut result = unsafe { group_vector_elements(uninit_vector(row_count * N)) };
+
     iter_mut!(result, 1024)
         .enumerate()
         .for_each(|(i, element)| {
@@ -574,6 +657,43 @@ pub fn transpose_slice<T: Copy + Send + Sync, const N: usize>(source: &[T]) -> V
     result
 }
 
+// SAFE IMPLEMENTATIONS
+// ================================================================================================
+
+/// Safe counterparts of functions which allocate un-initialized memory or take ownership of
+/// re-interpreted memory; these are used when `safe_only` feature is enabled, and are also
+/// compiled for tests to check that both implementations behave identically.
+///
+/// Same as the rest of `safe_only` mode, these functions require a more recent version of Rust
+/// than the minimum version supported by the crate.
+#[cfg(any(feature = "safe_only", test))]
+#[allow(clippy::incompatible_msrv)]
+mod safe {
+    use super::collections::Vec;
+
+    /// Moves elements of the `source` vector into a vector of `N`-element arrays; the length of
+    /// the source must be divisible by `N`.
+    pub fn group_vector_elements<T, const N: usize>(source: Vec<T>) -> Vec<[T; N]> {
+        let mut elements = source.into_iter();
+        let mut result = Vec::with_capacity(elements.len() / N);
+        while elements.len() != 0 {
+            result.push(core::array::from_fn(|_| {
+                elements.next().expect("no element")
+            }));
+        }
+        result
+    }
+
+    /// Returns a vector of `row_count` rows each initialized to copies of the first element of
+    /// the `source`; the returned vector is empty if `row_count` is zero.
+    pub fn init_rows<T: Copy, const N: usize>(source: &[T], row_count: usize) -> Vec<[T; N]> {
+        match source.first() {
+            Some(&element) => vec![[element; N]; row_count],
+            None => Vec::new(),
+        }
+    }
+}
+
 // RANDOMNESS
 // ================================================================================================
 
@@ -3,7 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{collections::Vec, ByteReader, ByteWriter, Serializable, SliceReader};
+use super::{
+    collections::{InlineVec, Vec},
+    iterators::{get_chunk_size, MIN_CHUNK_SIZE},
+    transpose_into, AsBytes, ByteReader, ByteWriter, RowMajorView, Serializable, SliceReader,
+    TRANSPOSE_TILE_HEIGHT,
+};
+use proptest::prelude::*;
 
 // VECTOR UTILS TESTS
 // ================================================================================================
@@ -28,6 +34,49 @@ fn group_vector_elements() {
     }
 }
 
+#[test]
+fn safe_vector_utils_equivalence() {
+    let values = rand_values(48);
+
+    let expected = super::safe::group_vector_elements::<u64, 4>(values.clone());
+    assert_eq!(
+        expected,
+        super::group_vector_elements::<u64, 4>(values.clone())
+    );
+    assert_eq!(
+        &expected[..],
+        super::group_slice_elements::<u64, 4>(&values)
+    );
+    assert_eq!(&expected[..], values.as_chunks::<4>().0);
+
+    assert_eq!(&values[..], super::flatten_slice_elements(&expected));
+    assert_eq!(values, super::flatten_vector_elements(expected.clone()));
+
+    let transposed = super::transpose_slice::<u64, 4>(&values);
+    let mut expected = super::safe::init_rows::<u64, 4>(&values, values.len() / 4);
+    for (i, row) in expected.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = values[i + j * values.len() / 4];
+        }
+    }
+    assert_eq!(expected, transposed);
+    assert!(super::safe::init_rows::<u64, 4>(&[], 0).is_empty());
+}
+
+#[test]
+fn safe_as_bytes_equivalence() {
+    let values = rand_values(32)
+        .iter()
+        .map(|v| v.to_le_bytes())
+        .collect::<Vec<_>>();
+    let expected = values.iter().flatten().copied().collect::<Vec<u8>>();
+    assert_eq!(expected, values.as_bytes());
+    assert_eq!(&expected[..], values.as_flattened());
+
+    let array: [[u8; 8]; 4] = [values[0], values[1], values[2], values[3]];
+    assert_eq!(&expected[..32], array.as_bytes());
+}
+
 // SLICE READER TESTS
 // ================================================================================================
 
@@ -147,3 +196,220 @@ fn write_serializable_array_batch() {
         assert_eq!(i, reader.read_u128().unwrap());
     }
 }
+
+// CHUNKING
+// ================================================================================================
+
+#[test]
+fn chunk_size_bounds() {
+    let num_items = 1 << 20;
+    let cache_size = 256 * 1024;
+
+    // tiny inputs are processed as a single chunk
+    assert_eq!(16, get_chunk_size(16, 8, cache_size));
+    assert_eq!(1, get_chunk_size(0, 8, cache_size));
+
+    // very wide items still get the minimum chunk size
+    assert_eq!(
+        MIN_CHUNK_SIZE,
+        get_chunk_size(num_items, 1 << 20, cache_size)
+    );
+
+    // wide items get smaller chunks than narrow items
+    let wide = get_chunk_size(num_items, 96 * 16 * 2, cache_size);
+    let narrow = get_chunk_size(num_items, 2 * 16 * 2, cache_size);
+    assert!(wide < narrow);
+
+    // chunk sizes always evenly divide the number of items
+    for item_size in [1, 3, 100, 1000, 100_000] {
+        let chunk_size = get_chunk_size(num_items, item_size, cache_size);
+        assert!(chunk_size.is_power_of_two());
+        assert_eq!(0, num_items % chunk_size);
+    }
+}
+
+// TRANSPOSING
+// ================================================================================================
+
+#[test]
+fn transpose_into_shapes() {
+    // very narrow, very wide, and non-square shapes, including heights which are not multiples
+    // of the tile height
+    let shapes = [
+        (1, 1),
+        (1, 1000),
+        (1000, 1),
+        (3, TRANSPOSE_TILE_HEIGHT + 1),
+        (17, 3 * TRANSPOSE_TILE_HEIGHT),
+        (100, 257),
+    ];
+    for (width, height) in shapes {
+        let columns = rand_columns(width, height);
+        let mut rows = vec![0; width * height];
+        transpose_into(&columns, &mut rows, width, height);
+        assert_eq!(naive_transpose(&columns), rows, "shape {width}x{height}");
+    }
+
+    // empty matrices are transposed into empty buffers
+    transpose_into::<u64>(&[], &mut [], 0, 0);
+    transpose_into::<u64>(&[vec![], vec![]], &mut [], 2, 0);
+}
+
+#[test]
+#[should_panic(expected = "destination must contain 6 elements, but was 5")]
+fn transpose_into_wrong_destination_length() {
+    transpose_into(&[vec![1u64, 2, 3], vec![4, 5, 6]], &mut [0; 5], 2, 3);
+}
+
+#[test]
+#[should_panic(expected = "all columns must contain 3 elements")]
+fn transpose_into_ragged_columns() {
+    transpose_into(&[vec![1u64, 2, 3], vec![4, 5]], &mut [0; 6], 2, 3);
+}
+
+#[test]
+fn row_major_view_tiles() {
+    let columns = rand_columns(5, 10);
+    let expected = naive_transpose(&columns);
+    let view = RowMajorView::new(&columns).with_tile_height(4);
+    assert_eq!(3, view.num_tiles());
+    assert_eq!(3, view.iter().len());
+
+    // the last tile contains the remaining rows
+    let tiles = view.iter().collect::<Vec<_>>();
+    assert_eq!(
+        vec![(0, 4), (4, 4), (8, 2)],
+        tiles
+            .iter()
+            .map(|tile| (tile.first_row(), tile.num_rows()))
+            .collect::<Vec<_>>()
+    );
+    for tile in tiles.iter() {
+        for (i, row) in tile.rows().enumerate() {
+            let row_idx = tile.first_row() + i;
+            assert_eq!(&expected[row_idx * 5..(row_idx + 1) * 5], row);
+            assert_eq!(row, tile.row(i));
+        }
+    }
+
+    // reading a range of rows which spans several tiles
+    let mut rows = vec![0; 3 * 5];
+    view.read_rows_into(3, &mut rows);
+    assert_eq!(&expected[3 * 5..6 * 5], rows.as_slice());
+
+    // a view of a matrix without columns produces no tiles
+    let empty: [Vec<u64>; 0] = [];
+    assert_eq!(0, RowMajorView::new(&empty).num_tiles());
+}
+
+#[cfg(feature = "concurrent")]
+#[test]
+fn row_major_view_par_iter() {
+    use super::iterators::*;
+
+    let columns = rand_columns(7, 1000);
+    let view = RowMajorView::new(&columns);
+    let tiles = view.par_iter().collect::<Vec<_>>();
+    assert_eq!(view.iter().collect::<Vec<_>>(), tiles);
+}
+
+#[test]
+#[should_panic(expected = "rows 8..11 are out of bounds for a matrix with 10 rows")]
+fn row_major_view_rows_out_of_bounds() {
+    let columns = rand_columns(2, 10);
+    RowMajorView::new(&columns).read_rows_into(8, &mut [0; 6]);
+}
+
+// INLINE VECTOR
+// ================================================================================================
+
+#[test]
+fn inline_vec_spills_past_capacity() {
+    let mut v = InlineVec::<u64, 4>::new();
+    v.extend_from_slice(&[1, 2, 3]);
+    v.push(4);
+    assert!(!v.spilled());
+    assert_eq!(&[1, 2, 3, 4], v.as_slice());
+
+    v.push(5);
+    assert!(v.spilled());
+    assert_eq!(&[1, 2, 3, 4, 5], v.as_slice());
+
+    // elements stay on the heap once they have been moved there
+    v.truncate(2);
+    assert!(v.spilled());
+    assert_eq!(&[1, 2], v.as_slice());
+
+    let v = InlineVec::<u64, 4>::from_slice(&[1, 2, 3, 4, 5, 6]);
+    assert!(v.spilled());
+    assert_eq!(6, v.len());
+}
+
+#[test]
+fn inline_vec_dedup() {
+    let mut v = [3_u64, 1, 3, 2, 1, 3]
+        .iter()
+        .copied()
+        .collect::<InlineVec<_, 8>>();
+    v.sort_unstable();
+    v.dedup();
+    assert_eq!(&[1, 2, 3], v.as_slice());
+
+    let mut v = InlineVec::<u64, 2>::from_elem(7, 5);
+    v.dedup();
+    assert_eq!(&[7], v.as_slice());
+}
+
+proptest! {
+    #[test]
+    fn transpose_twice_is_identity(width in 1_usize..40, height in 1_usize..300, seed: u64) {
+        let columns = (0..width)
+            .map(|i| (0..height as u64).map(|j| seed ^ (i as u64) << 32 ^ j).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // transposing the rows of the transposition (viewed as columns of the transposed
+        // matrix) yields the original columns
+        let mut rows = vec![0; width * height];
+        transpose_into(&columns, &mut rows, width, height);
+        prop_assert_eq!(naive_transpose(&columns), rows.clone());
+
+        let transposed = rows.chunks(width).map(|row| row.to_vec()).collect::<Vec<_>>();
+        let mut result = vec![0; width * height];
+        transpose_into(&transposed, &mut result, height, width);
+        prop_assert_eq!(columns.concat(), result);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns a deterministic sequence of pseudo-random values generated with xorshift.
+fn rand_values(n: usize) -> Vec<u64> {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        })
+        .collect()
+}
+
+/// Returns a matrix with the specified number of columns of the specified length filled with
+/// pseudo-random values.
+fn rand_columns(width: usize, height: usize) -> Vec<Vec<u64>> {
+    rand_values(width * height)
+        .chunks(height.max(1))
+        .take(width)
+        .map(|column| column.to_vec())
+        .collect()
+}
+
+/// Gathers rows of the matrix from its columns one row at a time.
+fn naive_transpose(columns: &[Vec<u64>]) -> Vec<u64> {
+    let height = columns.first().map_or(0, |column| column.len());
+    (0..height)
+        .flat_map(|row_idx| columns.iter().map(move |column| column[row_idx]))
+        .collect()
+}
@@ -0,0 +1,348 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Cache-blocked transposition of column-major matrices into row-major order.
+//!
+//! Reading a row of a column-major matrix touches one cache line per column, and thus, gathering
+//! rows one at a time from a wide matrix thrashes the cache. The functions in this module copy
+//! values in tiles instead: a tile spans [TRANSPOSE_TILE_HEIGHT] rows and is filled in blocks of
+//! a few columns at a time, so that the values read from every column and the rows being written
+//! stay in cache while the block is copied.
+
+use crate::collections::Vec;
+
+#[cfg(feature = "concurrent")]
+use crate::iterators::*;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Default number of rows in a single tile of a transposition.
+pub const TRANSPOSE_TILE_HEIGHT: usize = 1024;
+
+/// Number of columns copied into a tile at a time.
+const TRANSPOSE_BLOCK_WIDTH: usize = 16;
+
+// TRANSPOSITION
+// ================================================================================================
+
+/// Transposes a column-major matrix with the specified `width` and `height` into `dst_rows`, so
+/// that row `i` of the matrix is written into `dst_rows[i * width..(i + 1) * width]`.
+///
+/// When `concurrent` feature is enabled, tiles of the matrix are transposed using multiple
+/// threads.
+///
+/// # Panics
+/// Panics if:
+/// * The number of columns in `src_cols` is not equal to `width`.
+/// * Not all of the columns contain `height` elements.
+/// * The length of `dst_rows` is not equal to `width` * `height`.
+///
+/// # Example
+/// ```
+/// # use winter_utils::transpose_into;
+/// let columns = vec![vec![1_u32, 2, 3], vec![4, 5, 6]];
+/// let mut rows = vec![0; 6];
+/// transpose_into(&columns, &mut rows, 2, 3);
+///
+/// assert_eq!(vec![1, 4, 2, 5, 3, 6], rows);
+/// ```
+pub fn transpose_into<T: Copy + Send + Sync>(
+    src_cols: &[Vec<T>],
+    dst_rows: &mut [T],
+    width: usize,
+    height: usize,
+) {
+    assert_eq!(
+        width,
+        src_cols.len(),
+        "expected {} columns, but was {}",
+        width,
+        src_cols.len()
+    );
+    assert!(
+        src_cols.iter().all(|column| column.len() == height),
+        "all columns must contain {height} elements"
+    );
+    assert_eq!(
+        width * height,
+        dst_rows.len(),
+        "destination must contain {} elements, but was {}",
+        width * height,
+        dst_rows.len()
+    );
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let view = RowMajorView::new(src_cols);
+    let tile_size = TRANSPOSE_TILE_HEIGHT * width;
+
+    #[cfg(not(feature = "concurrent"))]
+    let tiles = dst_rows.chunks_mut(tile_size);
+    #[cfg(feature = "concurrent")]
+    let tiles = dst_rows.par_chunks_mut(tile_size);
+
+    tiles.enumerate().for_each(|(tile_idx, tile)| {
+        view.read_rows_into(tile_idx * TRANSPOSE_TILE_HEIGHT, tile);
+    });
+}
+
+// ROW-MAJOR VIEW
+// ================================================================================================
+
+/// A view of a column-major matrix which produces rows of the matrix in tiles.
+///
+/// The view does not copy the matrix; instead, rows are transposed lazily when a tile is
+/// requested (via [RowMajorView::get_tile()] or the iterators returned by
+/// [RowMajorView::iter()] and `RowMajorView::par_iter()`), or when a range of rows is read
+/// into a caller-provided buffer (via [RowMajorView::read_rows_into()]).
+///
+/// # Example
+/// ```
+/// # use winter_utils::RowMajorView;
+/// let columns = vec![vec![1_u32, 2, 3], vec![4, 5, 6]];
+/// let view = RowMajorView::new(&columns).with_tile_height(2);
+///
+/// let rows = view.iter().flat_map(|tile| tile.into_values()).collect::<Vec<_>>();
+/// assert_eq!(vec![1, 4, 2, 5, 3, 6], rows);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RowMajorView<'a, T> {
+    columns: &'a [Vec<T>],
+    num_rows: usize,
+    tile_height: usize,
+}
+
+impl<'a, T: Copy> RowMajorView<'a, T> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a view of the matrix consisting of the specified columns; the view produces tiles
+    /// of [TRANSPOSE_TILE_HEIGHT] rows.
+    ///
+    /// # Panics
+    /// Panics if not all of the columns have the same length.
+    pub fn new(columns: &'a [Vec<T>]) -> Self {
+        let num_rows = columns.first().map_or(0, |column| column.len());
+        assert!(
+            columns.iter().all(|column| column.len() == num_rows),
+            "all columns must have the same length"
+        );
+        RowMajorView {
+            columns,
+            num_rows,
+            tile_height: TRANSPOSE_TILE_HEIGHT,
+        }
+    }
+
+    /// Returns a view which produces tiles of the specified number of rows; the last tile may
+    /// contain fewer rows.
+    ///
+    /// # Panics
+    /// Panics if `tile_height` is zero.
+    pub fn with_tile_height(mut self, tile_height: usize) -> Self {
+        assert!(tile_height > 0, "tile height must be greater than zero");
+        self.tile_height = tile_height;
+        self
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of columns in the underlying matrix.
+    pub fn num_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the number of rows in the underlying matrix.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns the number of rows in every tile produced by this view (except possibly the last
+    /// one).
+    pub fn tile_height(&self) -> usize {
+        self.tile_height
+    }
+
+    /// Returns the number of tiles produced by this view.
+    pub fn num_tiles(&self) -> usize {
+        if self.num_cols() == 0 {
+            0
+        } else {
+            (self.num_rows + self.tile_height - 1) / self.tile_height
+        }
+    }
+
+    // TRANSPOSITION
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the tile at the specified index.
+    ///
+    /// # Panics
+    /// Panics if `tile_idx` is not smaller than the number of tiles.
+    pub fn get_tile(&self, tile_idx: usize) -> RowTile<T> {
+        assert!(
+            tile_idx < self.num_tiles(),
+            "tile index {} is out of bounds for a view with {} tiles",
+            tile_idx,
+            self.num_tiles()
+        );
+        let first_row = tile_idx * self.tile_height;
+        let num_rows = self.tile_height.min(self.num_rows - first_row);
+
+        // the buffer is initialized with an arbitrary value of the matrix, all of which are
+        // overwritten right away
+        let mut values = vec![self.columns[0][first_row]; num_rows * self.num_cols()];
+        self.read_rows_into(first_row, &mut values);
+        RowTile {
+            first_row,
+            width: self.num_cols(),
+            values,
+        }
+    }
+
+    /// Returns an iterator over the tiles of this view.
+    pub fn iter(&self) -> RowTiles<'_, 'a, T> {
+        RowTiles {
+            view: self,
+            next_tile: 0,
+        }
+    }
+
+    /// Writes consecutive rows of the matrix starting with `first_row` into `dst` in row-major
+    /// order; the number of rows written is the length of `dst` divided by the number of
+    /// columns.
+    ///
+    /// # Panics
+    /// Panics if the length of `dst` is not divisible by the number of columns, or if the rows
+    /// extend beyond the end of the matrix.
+    pub fn read_rows_into(&self, first_row: usize, dst: &mut [T]) {
+        let width = self.num_cols();
+        if width == 0 {
+            assert!(
+                dst.is_empty(),
+                "cannot read rows of a matrix with no columns"
+            );
+            return;
+        }
+        assert_eq!(
+            0,
+            dst.len() % width,
+            "destination length must be divisible by {}, but was {}",
+            width,
+            dst.len()
+        );
+        let num_rows = dst.len() / width;
+        assert!(
+            first_row + num_rows <= self.num_rows,
+            "rows {}..{} are out of bounds for a matrix with {} rows",
+            first_row,
+            first_row + num_rows,
+            self.num_rows
+        );
+
+        // rows are copied in tiles; within a tile, a few columns are copied at a time so that
+        // the segments of these columns stay in cache while the rows of the tile are filled in
+        for (tile_idx, tile) in dst.chunks_mut(TRANSPOSE_TILE_HEIGHT * width).enumerate() {
+            let tile_offset = first_row + tile_idx * TRANSPOSE_TILE_HEIGHT;
+            for block_start in (0..width).step_by(TRANSPOSE_BLOCK_WIDTH) {
+                let block_end = (block_start + TRANSPOSE_BLOCK_WIDTH).min(width);
+                let block = &self.columns[block_start..block_end];
+                for (row_idx, row) in tile.chunks_exact_mut(width).enumerate() {
+                    let src_row = tile_offset + row_idx;
+                    for (target, column) in row[block_start..block_end].iter_mut().zip(block) {
+                        *target = column[src_row];
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "concurrent")]
+impl<'a, T: Copy + Send + Sync> RowMajorView<'a, T> {
+    /// Returns a parallel iterator over the tiles of this view; tiles are transposed using
+    /// multiple threads.
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = RowTile<T>> + '_ {
+        (0..self.num_tiles())
+            .into_par_iter()
+            .map(move |tile_idx| self.get_tile(tile_idx))
+    }
+}
+
+// ROW TILE
+// ================================================================================================
+
+/// A set of consecutive rows of a matrix stored in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowTile<T> {
+    first_row: usize,
+    width: usize,
+    values: Vec<T>,
+}
+
+impl<T> RowTile<T> {
+    /// Returns the index of the first row of this tile in the underlying matrix.
+    pub fn first_row(&self) -> usize {
+        self.first_row
+    }
+
+    /// Returns the number of rows in this tile.
+    pub fn num_rows(&self) -> usize {
+        self.values.len() / self.width
+    }
+
+    /// Returns the row at the specified index within this tile.
+    ///
+    /// # Panics
+    /// Panics if `row_idx` is not smaller than the number of rows in this tile.
+    pub fn row(&self, row_idx: usize) -> &[T] {
+        &self.values[row_idx * self.width..(row_idx + 1) * self.width]
+    }
+
+    /// Returns an iterator over the rows of this tile.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.values.chunks_exact(self.width)
+    }
+
+    /// Returns the values of this tile in row-major order.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Consumes this tile and returns its values in row-major order.
+    pub fn into_values(self) -> Vec<T> {
+        self.values
+    }
+}
+
+/// An iterator over the tiles of a [RowMajorView].
+#[derive(Debug)]
+pub struct RowTiles<'v, 'a, T> {
+    view: &'v RowMajorView<'a, T>,
+    next_tile: usize,
+}
+
+impl<'v, 'a, T: Copy> Iterator for RowTiles<'v, 'a, T> {
+    type Item = RowTile<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_tile == self.view.num_tiles() {
+            return None;
+        }
+        let tile = self.view.get_tile(self.next_tile);
+        self.next_tile += 1;
+        Some(tile)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.view.num_tiles() - self.next_tile;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'v, 'a, T: Copy> ExactSizeIterator for RowTiles<'v, 'a, T> {}
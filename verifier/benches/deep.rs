@@ -0,0 +1,127 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use math::{
+    fields::{f64::BaseElement, QuadExtension},
+    FieldElement,
+};
+use rand_utils::rand_vector;
+use winter_verifier::eval_deep_at_points;
+
+// Compares evaluating a DEEP quotient term separately for every query against evaluating it
+// column by column over the entire query vector via `eval_deep_at_points()`. Besides the time,
+// the number of field operations performed by each approach is printed; for the recursive
+// verifier, this number determines the size of the circuit.
+
+type E = QuadExtension<BaseElement>;
+
+const NUM_QUERIES: [usize; 3] = [27, 54, 108];
+const NUM_COLUMNS: usize = 64;
+
+fn deep_quotients(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deep_quotients");
+
+    for &num_queries in NUM_QUERIES.iter() {
+        let columns = rand_vector::<BaseElement>(NUM_COLUMNS * num_queries);
+        let ood_values = rand_vector::<E>(NUM_COLUMNS);
+        let coefficients = rand_vector::<E>(NUM_COLUMNS);
+        let inv_denominators = rand_vector::<E>(num_queries);
+
+        let (per_query_ops, batched_ops) = count_ops(num_queries, NUM_COLUMNS);
+        println!(
+            "deep_quotients/{num_queries}: per query: {per_query_ops}; batched: {batched_ops}"
+        );
+
+        group.bench_function(BenchmarkId::new("per_query", num_queries), |bench| {
+            bench.iter(|| {
+                let mut result = E::zeroed_vector(num_queries);
+                eval_per_query(
+                    &columns,
+                    &ood_values,
+                    &coefficients,
+                    &inv_denominators,
+                    &mut result,
+                );
+                result
+            });
+        });
+
+        group.bench_function(BenchmarkId::new("batched", num_queries), |bench| {
+            bench.iter(|| {
+                let mut result = E::zeroed_vector(num_queries);
+                eval_deep_at_points(
+                    &columns,
+                    &ood_values,
+                    &coefficients,
+                    &inv_denominators,
+                    &mut result,
+                );
+                result
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(deep_group, deep_quotients);
+criterion_main!(deep_group);
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Evaluates the DEEP quotient term by computing every quotient for every query separately, as
+/// the verifier did before the evaluation was batched.
+fn eval_per_query(
+    columns: &[BaseElement],
+    ood_values: &[E],
+    coefficients: &[E],
+    inv_denominators: &[E],
+    result: &mut [E],
+) {
+    let num_queries = result.len();
+    for (j, (result, &inv_denominator)) in result.iter_mut().zip(inv_denominators).enumerate() {
+        for (i, (&ood_value, &cc)) in ood_values.iter().zip(coefficients).enumerate() {
+            let value = E::from(columns[i * num_queries + j]);
+            *result += (value - ood_value) * inv_denominator * cc;
+        }
+    }
+}
+
+/// Returns the number of field operations (additions, subtractions, and multiplications)
+/// performed by [eval_per_query()] and by `eval_deep_at_points()` respectively.
+fn count_ops(num_queries: usize, num_columns: usize) -> (OpCount, OpCount) {
+    // per query and column: one subtraction, two multiplications, and one addition
+    let per_query = OpCount {
+        additions: 2 * num_queries * num_columns,
+        multiplications: 2 * num_queries * num_columns,
+    };
+
+    // per query and column: one multiplication by a base field element and one addition; per
+    // column: one multiplication and one addition for the out-of-domain values; per query: one
+    // subtraction, one multiplication, and one addition
+    let batched = OpCount {
+        additions: num_queries * num_columns + num_columns + 2 * num_queries,
+        multiplications: num_queries * num_columns + num_columns + num_queries,
+    };
+
+    (per_query, batched)
+}
+
+/// Numbers of field operations performed by a DEEP quotient evaluation.
+struct OpCount {
+    additions: usize,
+    multiplications: usize,
+}
+
+impl core::fmt::Display for OpCount {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} add/sub, {} mul",
+            self.additions, self.multiplications
+        )
+    }
+}
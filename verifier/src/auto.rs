@@ -0,0 +1,98 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{Air, StarkProof, VerifierError};
+use crypto::HashFunctionId;
+use math::StarkField;
+
+#[cfg(any(feature = "f62", feature = "f64", feature = "f128"))]
+use super::verify;
+#[cfg(feature = "f128")]
+use crypto::hashers::Poseidon128_256;
+#[cfg(feature = "f62")]
+use crypto::hashers::Rp62_248;
+#[cfg(feature = "f64")]
+use crypto::hashers::{GriffinJive64_256, Poseidon64_256, Rp64_256, RpJive64_256};
+#[cfg(feature = "f128")]
+use math::fields::f128;
+#[cfg(feature = "f62")]
+use math::fields::f62;
+#[cfg(feature = "f64")]
+use math::fields::f64;
+
+// AUTO HASH FIELD
+// ================================================================================================
+
+/// Defines which algebraic hash functions can be selected at runtime by
+/// [verify_auto()](crate::verify_auto) for AIRs over this base field.
+///
+/// Hash functions which can be used with any field (e.g., BLAKE3 or SHA3) are selected by
+/// [verify_auto()](crate::verify_auto) directly; algebraic hash functions are defined over a
+/// specific field, and thus, can be selected only via the base field of the AIR. This trait is
+/// implemented for the base fields defined in the math crate; for other fields, an empty
+/// implementation makes only the hash functions which can be used with any field available.
+pub trait AutoHashField: StarkField {
+    /// Verifies the `proof` for a computation specified by `AIR` using the algebraic hash
+    /// function specified by `hash_fn`.
+    ///
+    /// # Errors
+    /// Returns [VerifierError::UnsupportedHashFunction] if the hash function cannot be used with
+    /// this field, or the errors returned by [verify()](crate::verify) otherwise.
+    fn verify_algebraic<AIR: Air<BaseField = Self>>(
+        hash_fn: HashFunctionId,
+        proof: StarkProof,
+        pub_inputs: AIR::PublicInputs,
+    ) -> Result<(), VerifierError> {
+        let _ = (proof, pub_inputs);
+        Err(VerifierError::UnsupportedHashFunction(hash_fn))
+    }
+}
+
+#[cfg(feature = "f62")]
+impl AutoHashField for f62::BaseElement {
+    fn verify_algebraic<AIR: Air<BaseField = Self>>(
+        hash_fn: HashFunctionId,
+        proof: StarkProof,
+        pub_inputs: AIR::PublicInputs,
+    ) -> Result<(), VerifierError> {
+        match hash_fn {
+            HashFunctionId::Rp62_248 => verify::<AIR, Rp62_248>(proof, pub_inputs),
+            _ => Err(VerifierError::UnsupportedHashFunction(hash_fn)),
+        }
+    }
+}
+
+#[cfg(feature = "f64")]
+impl AutoHashField for f64::BaseElement {
+    fn verify_algebraic<AIR: Air<BaseField = Self>>(
+        hash_fn: HashFunctionId,
+        proof: StarkProof,
+        pub_inputs: AIR::PublicInputs,
+    ) -> Result<(), VerifierError> {
+        match hash_fn {
+            HashFunctionId::Rp64_256 => verify::<AIR, Rp64_256>(proof, pub_inputs),
+            HashFunctionId::RpJive64_256 => verify::<AIR, RpJive64_256>(proof, pub_inputs),
+            HashFunctionId::GriffinJive64_256 => {
+                verify::<AIR, GriffinJive64_256>(proof, pub_inputs)
+            }
+            HashFunctionId::Poseidon64_256 => verify::<AIR, Poseidon64_256>(proof, pub_inputs),
+            _ => Err(VerifierError::UnsupportedHashFunction(hash_fn)),
+        }
+    }
+}
+
+#[cfg(feature = "f128")]
+impl AutoHashField for f128::BaseElement {
+    fn verify_algebraic<AIR: Air<BaseField = Self>>(
+        hash_fn: HashFunctionId,
+        proof: StarkProof,
+        pub_inputs: AIR::PublicInputs,
+    ) -> Result<(), VerifierError> {
+        match hash_fn {
+            HashFunctionId::Poseidon128_256 => verify::<AIR, Poseidon128_256>(proof, pub_inputs),
+            _ => Err(VerifierError::UnsupportedHashFunction(hash_fn)),
+        }
+    }
+}
@@ -5,13 +5,13 @@
 
 use crate::VerifierError;
 use air::{
-    proof::{Queries, StarkProof, Table},
-    Air, EvaluationFrame,
+    proof::{CommitmentLayout, CompositionCoefficients, Queries, StarkProof, Table},
+    Air, EvaluationFrame, GrindingStrategy, LdePos, LeafEncoding, PowerTable,
 };
-use crypto::{BatchMerkleProof, ElementHasher, MerkleTree};
+use crypto::{BatchMerkleProof, ElementHasher, MerkleTree, VectorCommitment};
 use fri::VerifierChannel as FriVerifierChannel;
-use math::{FieldElement, StarkField};
-use utils::{collections::Vec, string::ToString};
+use math::{polynom, FieldElement, StarkField};
+use utils::{collections::Vec, string::ToString, DeserializationError};
 
 // VERIFIER CHANNEL
 // ================================================================================================
@@ -20,43 +20,72 @@ use utils::{collections::Vec, string::ToString};
 ///
 /// A channel is instantiated for a specific proof, which is parsed into structs over the
 /// appropriate field (specified by type parameter `E`). This also validates that the proof is
-/// well-formed in the context of the computation for the specified [Air].
-pub struct VerifierChannel<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
+/// well-formed in the context of the computation for the specified [Air]. Trace and constraint
+/// queries are checked against commitments of the vector commitment scheme `V`.
+pub struct VerifierChannel<E, H, V = MerkleTree<H>>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    // mapping of query positions onto commitment leaves; derived from the proof context in the
+    // same way as it is done by the prover
+    commitment_layout: CommitmentLayout,
     // trace queries
     trace_roots: Vec<H::Digest>,
-    trace_queries: Option<TraceQueries<E, H>>,
+    preprocessed_root: Option<H::Digest>,
+    trace_queries: Option<TraceQueries<E, H, V>>,
+    // combined trace column queries
+    combined_trace_root: Option<H::Digest>,
+    combined_trace_queries: Option<CombinedTraceQueries<E, H, V>>,
     // constraint queries
     constraint_root: H::Digest,
-    constraint_queries: Option<ConstraintQueries<E, H>>,
+    constraint_queries: Option<ConstraintQueries<E, H, V>>,
+    explicit_composition: Option<ExplicitComposition<E>>,
     // FRI proof
     fri_roots: Option<Vec<H::Digest>>,
+    fri_layer_positions: Vec<Vec<usize>>,
     fri_layer_proofs: Vec<BatchMerkleProof<H>>,
     fri_layer_queries: Vec<Vec<E>>,
     fri_remainder: Option<Vec<E>>,
+    fri_remainder_digest: Option<H::Digest>,
     fri_num_partitions: usize,
     // out-of-domain frame
-    ood_trace_frame: Option<TraceOodFrame<E>>,
-    ood_constraint_evaluations: Option<Vec<E>>,
+    ood_trace_frames: Option<Vec<TraceOodFrame<E>>>,
+    ood_constraint_evaluations: Option<Vec<Vec<E>>>,
+    ood_combined_trace_evaluations: Option<Vec<E>>,
     // query proof-of-work
-    pow_nonce: u64,
+    pow_nonce: u128,
+    num_extra_queries: usize,
 }
 
-impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChannel<E, H> {
+impl<E, H, V> VerifierChannel<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
     /// Creates and returns a new [VerifierChannel] initialized from the specified `proof`.
+    ///
+    /// `preprocessed_root` must be the commitment to the preprocessed trace segment if the proof
+    /// declares such a segment, and None otherwise.
     pub fn new<A: Air<BaseField = E::BaseField>>(
         air: &A,
         proof: StarkProof,
+        preprocessed_root: Option<H::Digest>,
     ) -> Result<Self, VerifierError> {
         let StarkProof {
             context,
             commitments,
             trace_queries,
             constraint_queries,
+            composition_coefficients,
             ood_frame,
             fri_proof,
             pow_nonce,
+            num_extra_queries,
         } = proof;
 
         // make AIR and proof base fields are the same
@@ -64,57 +93,170 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
             return Err(VerifierError::InconsistentBaseField);
         }
 
+        // make sure rows of trace commitments can be encoded into leaves of Merkle trees as
+        // specified by the proof
+        let row_bytes = context.max_trace_row_bytes::<E>();
+        if !air.options().leaf_encoding().is_supported::<H>(row_bytes) {
+            return Err(VerifierError::UnsupportedLeafEncoding(row_bytes));
+        }
+
         let num_trace_segments = air.trace_layout().num_segments();
-        let main_trace_width = air.trace_layout().main_trace_width();
+        let main_frame_width = air.trace_info().main_frame_width();
         let aux_trace_width = air.trace_layout().aux_trace_width();
         let lde_domain_size = air.lde_domain_size();
         let fri_options = air.options().to_fri_options();
+        let uses_combined_trace_column = air.options().uses_combined_trace_column();
+        let uses_fri_remainder_digest = air.options().uses_fri_remainder_digest();
+        let commitment_layout = CommitmentLayout::from_context(&context);
+
+        // make sure the low-degree proof has the number of FRI layers implied by the proof options
+        let num_fri_layers = fri_options.num_fri_layers(lde_domain_size);
+        if fri_proof.num_layers() != num_fri_layers {
+            return Err(VerifierError::FriStructureMismatch(
+                num_fri_layers,
+                fri_proof.num_layers(),
+            ));
+        }
 
         // --- parse commitments ------------------------------------------------------------------
-        let (trace_roots, constraint_root, fri_roots) = commitments
+        // the commitment to the combined trace column (if any) follows trace segment commitments,
+        // and the FRI remainder digest (if any) follows FRI layer commitments
+        let (mut trace_roots, constraint_root, mut fri_roots) = commitments
             .parse::<H>(
-                num_trace_segments,
-                fri_options.num_fri_layers(lde_domain_size),
+                num_trace_segments + uses_combined_trace_column as usize,
+                num_fri_layers + uses_fri_remainder_digest as usize,
             )
             .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
+        let combined_trace_root = if uses_combined_trace_column {
+            trace_roots.pop()
+        } else {
+            None
+        };
+        let fri_remainder_digest = if uses_fri_remainder_digest {
+            fri_roots.pop()
+        } else {
+            None
+        };
 
         // --- parse trace and constraint queries -------------------------------------------------
-        let trace_queries = TraceQueries::new(trace_queries, air)?;
-        let constraint_queries = ConstraintQueries::new(constraint_queries, air)?;
+        // extra queries can be drawn only by the time-boxed grinding strategy, and their number
+        // is bounded by the proof options
+        let max_extra_queries = match air.options().grinding_strategy() {
+            GrindingStrategy::FixedBits => 0,
+            GrindingStrategy::TimeBoxed {
+                fallback_extra_queries,
+                ..
+            } => fallback_extra_queries,
+        };
+        if num_extra_queries > max_extra_queries {
+            return Err(VerifierError::ProofDeserializationError(format!(
+                "proof cannot contain more than {max_extra_queries} extra queries, but contained \
+                {num_extra_queries}"
+            )));
+        }
+        let num_queries = air.options().num_queries() + num_extra_queries;
+
+        let mut trace_queries = trace_queries;
+        let combined_trace_queries = if uses_combined_trace_column {
+            let queries = trace_queries.pop().ok_or_else(|| {
+                VerifierError::ProofDeserializationError(
+                    "combined trace column queries are missing".to_string(),
+                )
+            })?;
+            Some(CombinedTraceQueries::new(queries, air, num_queries)?)
+        } else {
+            None
+        };
+        let trace_queries = TraceQueries::new(trace_queries, air, num_queries)?;
+        let (constraint_queries, explicit_composition) =
+            if air.options().uses_explicit_composition(air.trace_length()) {
+                let composition = ExplicitComposition::new::<A, H>(
+                    air,
+                    constraint_queries,
+                    composition_coefficients,
+                    &constraint_root,
+                )?;
+                (None, Some(composition))
+            } else {
+                if composition_coefficients.is_some() {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "composition coefficients cannot be included in proofs without explicit \
+                        composition"
+                            .to_string(),
+                    ));
+                }
+                (
+                    Some(ConstraintQueries::new(
+                        constraint_queries,
+                        air,
+                        num_queries,
+                    )?),
+                    None,
+                )
+            };
 
         // --- parse FRI proofs -------------------------------------------------------------------
         let fri_num_partitions = fri_proof.num_partitions();
         let fri_remainder = fri_proof
             .parse_remainder()
             .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
-        let (fri_layer_queries, fri_layer_proofs) = fri_proof
+        let (fri_layer_positions, fri_layer_queries, fri_layer_proofs) = fri_proof
             .parse_layers::<H, E>(lde_domain_size, fri_options.folding_factor())
             .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
 
-        // --- parse out-of-domain evaluation frame -----------------------------------------------
-        let (ood_main_trace_frame, ood_aux_trace_frame, ood_constraint_evaluations) = ood_frame
-            .parse(main_trace_width, aux_trace_width, air.ce_blowup_factor())
+        // --- parse out-of-domain evaluation frames ----------------------------------------------
+        let num_ood_points = air.options().num_ood_points();
+        let ood_combined_trace_evaluations = ood_frame
+            .parse_combined_trace_evaluations(if uses_combined_trace_column {
+                num_ood_points
+            } else {
+                0
+            })
             .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
-        let ood_trace_frame = TraceOodFrame::new(ood_main_trace_frame, ood_aux_trace_frame);
+        let parsed_ood_frames = ood_frame
+            .parse_points(
+                main_frame_width,
+                aux_trace_width,
+                air.context().frame_offsets(),
+                air.ce_blowup_factor(),
+                num_ood_points,
+            )
+            .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
+        let mut ood_trace_frames = Vec::with_capacity(parsed_ood_frames.len());
+        let mut ood_constraint_evaluations = Vec::with_capacity(parsed_ood_frames.len());
+        for (main_frame, aux_frame, evaluations) in parsed_ood_frames {
+            ood_trace_frames.push(TraceOodFrame::new(main_frame, aux_frame));
+            ood_constraint_evaluations.push(evaluations);
+        }
 
         Ok(VerifierChannel {
+            commitment_layout,
             // trace queries
             trace_roots,
+            preprocessed_root,
             trace_queries: Some(trace_queries),
+            // combined trace column queries
+            combined_trace_root,
+            combined_trace_queries,
             // constraint queries
             constraint_root,
-            constraint_queries: Some(constraint_queries),
+            constraint_queries,
+            explicit_composition,
             // FRI proof
             fri_roots: Some(fri_roots),
+            fri_layer_positions,
             fri_layer_proofs,
             fri_layer_queries,
             fri_remainder: Some(fri_remainder),
+            fri_remainder_digest,
             fri_num_partitions,
             // out-of-domain evaluation
-            ood_trace_frame: Some(ood_trace_frame),
+            ood_trace_frames: Some(ood_trace_frames),
             ood_constraint_evaluations: Some(ood_constraint_evaluations),
+            ood_combined_trace_evaluations: Some(ood_combined_trace_evaluations),
             // query seed
             pow_nonce,
+            num_extra_queries,
         })
     }
 
@@ -129,67 +271,171 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
         &self.trace_roots
     }
 
+    /// Returns the commitment to the preprocessed trace segment, or None if the proof does not
+    /// declare a preprocessed trace segment.
+    ///
+    /// Unlike other commitments, this commitment is not sent by the prover but is provided to the
+    /// verifier out of band.
+    pub fn read_preprocessed_commitment(&self) -> Option<H::Digest> {
+        self.preprocessed_root
+    }
+
+    /// Returns the commitment to the combined trace column sent by the prover, or None if the
+    /// proof does not commit to a combined trace column.
+    pub fn read_combined_trace_commitment(&self) -> Option<H::Digest> {
+        self.combined_trace_root
+    }
+
     /// Returns constraint evaluation commitment sent by the prover.
     pub fn read_constraint_commitment(&self) -> H::Digest {
         self.constraint_root
     }
 
-    /// Returns trace polynomial evaluations at out-of-domain points z and z * g, where g is the
-    /// generator of the LDE domain.
+    /// Returns trace polynomial evaluations at points z and z * g for each of the out-of-domain
+    /// points z, where g is the generator of the LDE domain.
     ///
     /// For computations requiring multiple trace segments, evaluations of auxiliary trace
-    /// polynomials are also included as the second value of the returned tuple. Otherwise, the
+    /// polynomials are also included as the second value of each returned tuple. Otherwise, the
     /// second value is None.
-    pub fn read_ood_trace_frame(&mut self) -> (EvaluationFrame<E>, Option<EvaluationFrame<E>>) {
-        let frame = self.ood_trace_frame.take().expect("already read");
-        (frame.main_frame, frame.aux_frame)
+    pub fn read_ood_trace_frames(
+        &mut self,
+    ) -> Vec<(EvaluationFrame<E>, Option<EvaluationFrame<E>>)> {
+        self.ood_trace_frames
+            .take()
+            .expect("already read")
+            .into_iter()
+            .map(|frame| (frame.main_frame, frame.aux_frame))
+            .collect()
     }
 
-    /// Returns evaluations of composition polynomial columns at z^m, where z is the out-of-domain
-    /// point, and m is the number of composition polynomial columns.
-    pub fn read_ood_constraint_evaluations(&mut self) -> Vec<E> {
+    /// Returns evaluations of composition polynomial columns at z^m for each of the out-of-domain
+    /// points z, where m is the number of composition polynomial columns.
+    pub fn read_ood_constraint_evaluations(&mut self) -> Vec<Vec<E>> {
         self.ood_constraint_evaluations
             .take()
             .expect("already read")
     }
 
+    /// Returns evaluations of the combined trace column at each of the out-of-domain points; the
+    /// returned vector is empty if the proof does not commit to a combined trace column.
+    pub fn read_ood_combined_trace_evaluations(&mut self) -> Vec<E> {
+        self.ood_combined_trace_evaluations
+            .take()
+            .expect("already read")
+    }
+
+    /// Returns evaluations of explicitly sent composition polynomial columns for the
+    /// out-of-domain point with the specified index at the specified point, or None if the
+    /// composition polynomial was committed to rather than sent explicitly.
+    pub fn evaluate_explicit_composition_at(&self, point_idx: usize, x: E) -> Option<Vec<E>> {
+        self.explicit_composition
+            .as_ref()
+            .map(|composition| composition.evaluate_at(point_idx, x))
+    }
+
+    /// Returns the digest of the FRI remainder polynomial sent by the prover, or None if the proof
+    /// options do not require this digest. This also checks that the digest is valid against the
+    /// FRI remainder included in the proof.
+    ///
+    /// # Errors
+    /// Returns an error if the digest was not computed from the coefficients of the remainder
+    /// polynomial.
+    pub fn read_fri_remainder_digest(&self) -> Result<Option<H::Digest>, VerifierError> {
+        let digest = match self.fri_remainder_digest {
+            Some(digest) => digest,
+            None => return Ok(None),
+        };
+        let remainder = self.fri_remainder.as_ref().expect("already read");
+        if fri::utils::hash_remainder_coefficients::<H, E>(remainder) != digest {
+            return Err(VerifierError::FriRemainderDigestMismatch);
+        }
+        Ok(Some(digest))
+    }
+
     /// Returns query proof-of-work nonce sent by the prover.
-    pub fn read_pow_nonce(&self) -> u64 {
+    pub fn read_pow_nonce(&self) -> u128 {
         self.pow_nonce
     }
 
+    /// Returns the number of queries the prover drew in addition to the number of queries
+    /// specified by the proof options.
+    pub fn read_num_extra_queries(&self) -> usize {
+        self.num_extra_queries
+    }
+
     /// Returns trace states at the specified positions of the LDE domain. This also checks if
     /// the trace states are valid against the trace commitment sent by the prover.
     ///
+    /// For computations with a preprocessed trace segment, states of this segment follow the
+    /// states of the main segment in each row of the first returned table.
+    ///
     /// For computations requiring multiple trace segments, trace states for auxiliary segments
     /// are also included as the second value of the returned tuple (trace states for all auxiliary
     /// segments are merged into a single table). Otherwise, the second value is None.
     #[allow(clippy::type_complexity)]
     pub fn read_queried_trace_states(
         &mut self,
-        positions: &[usize],
+        positions: &[LdePos],
     ) -> Result<(Table<E::BaseField>, Option<Table<E>>), VerifierError> {
         let queries = self.trace_queries.take().expect("already read");
 
-        // make sure the states included in the proof correspond to the trace commitment
-        for (root, proof) in self.trace_roots.iter().zip(queries.query_proofs.iter()) {
-            MerkleTree::verify_batch(root, positions, proof)
+        // make sure the states included in the proof correspond to the trace commitment; queries
+        // of the preprocessed segment (if any) follow the queries of the main segment
+        let leaf_indexes = self.commitment_layout.leaf_indexes(positions);
+        let roots = self.trace_roots[..1]
+            .iter()
+            .chain(self.preprocessed_root.iter())
+            .chain(self.trace_roots[1..].iter());
+        for (root, proof) in roots.zip(queries.query_proofs.iter()) {
+            V::verify_many(root, &leaf_indexes, proof)
                 .map_err(|_| VerifierError::TraceQueryDoesNotMatchCommitment)?;
         }
 
         Ok((queries.main_states, queries.aux_states))
     }
 
+    /// Returns combined trace column values at the specified positions of the LDE domain, or None
+    /// if the proof does not commit to a combined trace column. This also checks if the values
+    /// are valid against the combined trace column commitment sent by the prover.
+    pub fn read_queried_combined_trace_values(
+        &mut self,
+        positions: &[LdePos],
+    ) -> Result<Option<Vec<E>>, VerifierError> {
+        let (root, queries) = match (
+            &self.combined_trace_root,
+            self.combined_trace_queries.take(),
+        ) {
+            (Some(root), Some(queries)) => (root, queries),
+            _ => return Ok(None),
+        };
+
+        let leaf_indexes = self.commitment_layout.leaf_indexes(positions);
+        V::verify_many(root, &leaf_indexes, &queries.query_proof)
+            .map_err(|_| VerifierError::TraceQueryDoesNotMatchCommitment)?;
+
+        Ok(Some(queries.values))
+    }
+
     /// Returns constraint evaluations at the specified positions of the LDE domain. This also
     /// checks if the constraint evaluations are valid against the constraint commitment sent by
     /// the prover.
+    ///
+    /// When the composition polynomial was sent explicitly, the evaluations are computed by
+    /// evaluating the polynomial columns at the specified positions directly; `lde_powers` must
+    /// contain powers of the LDE domain generator at these positions.
     pub fn read_constraint_evaluations(
         &mut self,
-        positions: &[usize],
+        positions: &[LdePos],
+        lde_powers: &PowerTable<E::BaseField>,
     ) -> Result<Table<E>, VerifierError> {
+        if let Some(ref composition) = self.explicit_composition {
+            return Ok(composition.evaluate_at_positions(positions, lde_powers));
+        }
+
         let queries = self.constraint_queries.take().expect("already read");
 
-        MerkleTree::verify_batch(&self.constraint_root, positions, &queries.query_proofs)
+        let leaf_indexes = self.commitment_layout.leaf_indexes(positions);
+        V::verify_many(&self.constraint_root, &leaf_indexes, &queries.query_proofs)
             .map_err(|_| VerifierError::ConstraintQueryDoesNotMatchCommitment)?;
 
         Ok(queries.evaluations)
@@ -199,10 +445,11 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
 // FRI VERIFIER CHANNEL IMPLEMENTATION
 // ================================================================================================
 
-impl<E, H> FriVerifierChannel<E> for VerifierChannel<E, H>
+impl<E, H, V> FriVerifierChannel<E> for VerifierChannel<E, H, V>
 where
     E: FieldElement,
     H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
 {
     type Hasher = H;
 
@@ -214,6 +461,10 @@ where
         self.fri_roots.take().expect("already read")
     }
 
+    fn take_next_fri_layer_positions(&mut self) -> Vec<usize> {
+        self.fri_layer_positions.remove(0)
+    }
+
     fn take_next_fri_layer_proof(&mut self) -> BatchMerkleProof<H> {
         self.fri_layer_proofs.remove(0)
     }
@@ -232,61 +483,116 @@ where
 
 /// Container of trace query data, including:
 /// * Queried states for all trace segments.
-/// * Merkle authentication paths for all queries.
+/// * Opening proofs (e.g., Merkle authentication paths) for all queries.
 ///
-/// Trace states for all auxiliary segments are stored in a single table.
-struct TraceQueries<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
-    query_proofs: Vec<BatchMerkleProof<H>>,
+/// Trace states of the preprocessed segment (if any) are appended to the trace states of the main
+/// segment, and trace states for all auxiliary segments are stored in a single table.
+struct TraceQueries<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    query_proofs: Vec<V::MultiProof>,
     main_states: Table<E::BaseField>,
     aux_states: Option<Table<E>>,
 }
 
-impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E, H> {
+impl<E, H, V> TraceQueries<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
     /// Parses the provided trace queries into trace states in the specified field and
-    /// corresponding Merkle authentication paths.
+    /// corresponding opening proofs.
     pub fn new<A: Air<BaseField = E::BaseField>>(
         mut queries: Vec<Queries>,
         air: &A,
+        num_queries: usize,
     ) -> Result<Self, VerifierError> {
+        let preprocessed_width = air.trace_info().preprocessed_width();
+        let num_queried_segments =
+            air.trace_layout().num_segments() + (preprocessed_width > 0) as usize;
         assert_eq!(
             queries.len(),
-            air.trace_layout().num_segments(),
+            num_queried_segments,
             "expected {} trace segment queries, but received {}",
-            air.trace_layout().num_segments(),
+            num_queried_segments,
             queries.len()
         );
 
-        let num_queries = air.options().num_queries();
+        let leaf_encoding = air.options().leaf_encoding();
+        let merkle_arity = air.options().merkle_arity();
+        let salted = air.options().is_zk();
 
-        // parse main trace segment queries; parsing also validates that hashes of each table row
-        // form the leaves of Merkle authentication paths in the proofs
+        // parse main trace segment queries; parsing also validates that encodings of each table
+        // row form the leaves of Merkle authentication paths in the proofs
         let main_segment_width = air.trace_layout().main_trace_width();
         let main_segment_queries = queries.remove(0);
-        let (main_segment_query_proofs, main_segment_states) = main_segment_queries
-            .parse::<H, E::BaseField>(air.lde_domain_size(), num_queries, main_segment_width)
+        let (main_segment_query_proofs, main_segment_states) = parse_queries::<H, V, E::BaseField>(
+            main_segment_queries,
+            air.lde_domain_size(),
+            num_queries,
+            main_segment_width,
+            leaf_encoding,
+            merkle_arity,
+            salted,
+        )
+        .map_err(|err| {
+            VerifierError::ProofDeserializationError(format!(
+                "main trace segment query deserialization failed: {err}"
+            ))
+        })?;
+
+        // all query proofs will be aggregated into a single vector
+        let mut query_proofs = vec![main_segment_query_proofs];
+
+        // parse preprocessed trace segment queries (if any), and append resulting states to the
+        // states of the main trace segment; the preprocessed segment is public, and thus, its
+        // leaves are never salted
+        let main_segment_states = if preprocessed_width > 0 {
+            let (segment_query_proof, segment_trace_states) = parse_queries::<H, V, E::BaseField>(
+                queries.remove(0),
+                air.lde_domain_size(),
+                num_queries,
+                preprocessed_width,
+                leaf_encoding,
+                merkle_arity,
+                false,
+            )
             .map_err(|err| {
                 VerifierError::ProofDeserializationError(format!(
-                    "main trace segment query deserialization failed: {err}"
+                    "preprocessed trace segment query deserialization failed: {err}"
                 ))
             })?;
-
-        // all query proofs will be aggregated into a single vector
-        let mut query_proofs = vec![main_segment_query_proofs];
+            query_proofs.push(segment_query_proof);
+            Table::merge(vec![main_segment_states, segment_trace_states])
+        } else {
+            main_segment_states
+        };
 
         // parse auxiliary trace segment queries (if any), and merge resulting tables into a
-        // single table; parsing also validates that hashes of each table row form the leaves
+        // single table; parsing also validates that encodings of each table row form the leaves
         // of Merkle authentication paths in the proofs
         let aux_trace_states = if air.trace_info().is_multi_segment() {
             let mut aux_trace_states = Vec::new();
             for (i, segment_queries) in queries.into_iter().enumerate() {
                 let segment_width = air.trace_layout().get_aux_segment_width(i);
-                let (segment_query_proof, segment_trace_states) = segment_queries
-                    .parse::<H, E>(air.lde_domain_size(), num_queries, segment_width)
-                    .map_err(|err| {
-                        VerifierError::ProofDeserializationError(format!(
-                            "auxiliary trace segment query deserialization failed: {err}"
-                        ))
-                    })?;
+                let (segment_query_proof, segment_trace_states) = parse_queries::<H, V, E>(
+                    segment_queries,
+                    air.lde_domain_size(),
+                    num_queries,
+                    segment_width,
+                    leaf_encoding,
+                    merkle_arity,
+                    salted,
+                )
+                .map_err(|err| {
+                    VerifierError::ProofDeserializationError(format!(
+                        "auxiliary trace segment query deserialization failed: {err}"
+                    ))
+                })?;
 
                 query_proofs.push(segment_query_proof);
                 aux_trace_states.push(segment_trace_states);
@@ -306,32 +612,100 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E
     }
 }
 
+// COMBINED TRACE COLUMN QUERIES
+// ================================================================================================
+
+/// Container of combined trace column query data, including:
+/// * Queried values of the combined trace column.
+/// * Opening proofs (e.g., Merkle authentication paths) for all queries.
+struct CombinedTraceQueries<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    query_proof: V::MultiProof,
+    values: Vec<E>,
+}
+
+impl<E, H, V> CombinedTraceQueries<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    /// Parses the provided combined trace column queries into values in the specified field and
+    /// corresponding opening proofs.
+    pub fn new<A: Air<BaseField = E::BaseField>>(
+        queries: Queries,
+        air: &A,
+        num_queries: usize,
+    ) -> Result<Self, VerifierError> {
+        let (query_proof, values) = parse_queries::<H, V, E>(
+            queries,
+            air.lde_domain_size(),
+            num_queries,
+            1,
+            air.options().leaf_encoding(),
+            air.options().merkle_arity(),
+            air.options().is_zk(),
+        )
+        .map_err(|err| {
+            VerifierError::ProofDeserializationError(format!(
+                "combined trace column query deserialization failed: {err}"
+            ))
+        })?;
+
+        Ok(Self {
+            query_proof,
+            values: values.rows().map(|row| row[0]).collect(),
+        })
+    }
+}
+
 // CONSTRAINT QUERIES
 // ================================================================================================
 
 /// Container of constraint evaluation query data, including:
 /// * Queried constraint evaluation values.
-/// * Merkle authentication paths for all queries.
-struct ConstraintQueries<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
-    query_proofs: BatchMerkleProof<H>,
+/// * Opening proofs (e.g., Merkle authentication paths) for all queries.
+struct ConstraintQueries<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    query_proofs: V::MultiProof,
     evaluations: Table<E>,
 }
 
-impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> ConstraintQueries<E, H> {
+impl<E, H, V> ConstraintQueries<E, H, V>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
     /// Parses the provided constraint queries into evaluations in the specified field and
-    /// corresponding Merkle authentication paths.
+    /// corresponding opening proofs.
     pub fn new<A: Air<BaseField = E::BaseField>>(
         queries: Queries,
         air: &A,
+        num_queries: usize,
     ) -> Result<Self, VerifierError> {
-        let num_queries = air.options().num_queries();
-        let (query_proofs, evaluations) = queries
-            .parse::<H, E>(air.lde_domain_size(), num_queries, air.ce_blowup_factor())
-            .map_err(|err| {
-                VerifierError::ProofDeserializationError(format!(
-                    "constraint evaluation query deserialization failed: {err}"
-                ))
-            })?;
+        let (query_proofs, evaluations) = parse_queries::<H, V, E>(
+            queries,
+            air.lde_domain_size(),
+            num_queries,
+            air.num_composition_columns(),
+            LeafEncoding::Hashed,
+            air.options().merkle_arity(),
+            air.options().is_zk(),
+        )
+        .map_err(|err| {
+            VerifierError::ProofDeserializationError(format!(
+                "constraint evaluation query deserialization failed: {err}"
+            ))
+        })?;
 
         Ok(Self {
             query_proofs,
@@ -340,6 +714,133 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> ConstraintQuer
     }
 }
 
+// QUERY PARSING
+// ================================================================================================
+
+/// Parses the provided queries into query values and the corresponding opening proofs of the
+/// vector commitment scheme `V`; when `salted` is true, leaves of the commitment are built from
+/// hashes of query values and salts included in the queries, and `leaf_encoding` is ignored.
+fn parse_queries<H, V, E>(
+    queries: Queries,
+    domain_size: usize,
+    num_queries: usize,
+    values_per_query: usize,
+    leaf_encoding: LeafEncoding,
+    merkle_arity: usize,
+    salted: bool,
+) -> Result<(V::MultiProof, Table<E>), DeserializationError>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+    V: VectorCommitment<H>,
+{
+    queries.parse_with_commitment::<H, V, E>(
+        domain_size,
+        num_queries,
+        values_per_query,
+        leaf_encoding,
+        salted,
+        merkle_arity,
+    )
+}
+
+// EXPLICIT COMPOSITION
+// ================================================================================================
+
+/// Container of constraint composition polynomial columns sent by the prover in the clear,
+/// together with the offset of the LDE domain needed to evaluate them at query positions.
+///
+/// Columns of the composition polynomials for all out-of-domain points are stored together:
+/// columns for the first point are followed by columns for the second point etc.
+struct ExplicitComposition<E: FieldElement> {
+    columns: Vec<Vec<E>>,
+    num_point_columns: usize,
+    domain_offset: E::BaseField,
+}
+
+impl<E: FieldElement> ExplicitComposition<E> {
+    /// Parses the provided coefficients into composition polynomial columns, and makes sure
+    /// they are consistent with the constraint commitment included in the proof.
+    ///
+    /// The number of coefficients must match the degree of the composition polynomial exactly;
+    /// otherwise, the prover could send a polynomial of a higher degree which is not bound by
+    /// the same soundness error as the committed composition polynomial.
+    pub fn new<A, H>(
+        air: &A,
+        constraint_queries: Queries,
+        coefficients: Option<CompositionCoefficients>,
+        constraint_root: &H::Digest,
+    ) -> Result<Self, VerifierError>
+    where
+        A: Air<BaseField = E::BaseField>,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        if constraint_queries != Queries::default() {
+            return Err(VerifierError::ProofDeserializationError(
+                "constraint queries must be empty when composition polynomial is sent explicitly"
+                    .to_string(),
+            ));
+        }
+        let coefficients = coefficients.ok_or_else(|| {
+            VerifierError::ProofDeserializationError(
+                "composition polynomial coefficients are missing".to_string(),
+            )
+        })?;
+
+        let num_columns = air.num_composition_columns();
+        let column_len = air.trace_length();
+        let expected = num_columns * column_len;
+        let actual = coefficients.num_elements::<E>();
+        if actual != expected {
+            return Err(VerifierError::InvalidCompositionCoefficientCount(
+                expected, actual,
+            ));
+        }
+
+        let columns = coefficients
+            .parse::<E>(num_columns, column_len)
+            .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
+
+        // the prover commits to the coefficients by using their hash as constraint commitment
+        if H::hash_elements(&columns.concat()) != *constraint_root {
+            return Err(VerifierError::ConstraintQueryDoesNotMatchCommitment);
+        }
+
+        Ok(Self {
+            columns,
+            num_point_columns: air.ce_blowup_factor(),
+            domain_offset: air.domain_offset(),
+        })
+    }
+
+    /// Returns evaluations of the columns for the out-of-domain point with the specified index
+    /// at the specified point.
+    pub fn evaluate_at(&self, point_idx: usize, x: E) -> Vec<E> {
+        self.columns
+            .chunks(self.num_point_columns)
+            .nth(point_idx)
+            .expect("invalid out-of-domain point index")
+            .iter()
+            .map(|column| polynom::eval(column, x))
+            .collect()
+    }
+
+    /// Returns evaluations of all columns at the specified positions of the LDE domain; each
+    /// row of the returned table contains evaluations for a single position.
+    pub fn evaluate_at_positions(
+        &self,
+        positions: &[LdePos],
+        lde_powers: &PowerTable<E::BaseField>,
+    ) -> Table<E> {
+        let mut evaluations = Vec::with_capacity(positions.len() * self.columns.len());
+        for position in positions {
+            let x = E::from(lde_powers.get(position.as_usize()) * self.domain_offset);
+            evaluations.extend(self.columns.iter().map(|column| polynom::eval(column, x)));
+        }
+        Table::from_elements(evaluations, self.columns.len())
+    }
+}
+
 // TRACE OUT-OF-DOMAIN FRAME
 // ================================================================================================
 
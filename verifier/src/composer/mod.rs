@@ -0,0 +1,420 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use air::{
+    proof::Table, Air, DeepCompositionCoefficients, EvaluationFrame, FieldExtension, LdePos,
+    PowerTable,
+};
+use math::{batch_inversion, ExtensionOf, FieldElement};
+use utils::collections::Vec;
+
+#[cfg(all(test, feature = "f128", feature = "quadratic"))]
+mod tests;
+
+// DEEP COMPOSER
+// ================================================================================================
+
+pub struct DeepComposer<E: FieldElement> {
+    field_extension: FieldExtension,
+    cc: DeepCompositionCoefficients<E>,
+    x_coordinates: Vec<E>,
+    z: Vec<Vec<E>>,
+    num_constraint_columns: usize,
+    num_denominators: usize,
+    inv_denominators: Vec<E>,
+}
+
+impl<E: FieldElement> DeepComposer<E> {
+    /// Creates a new composer for computing DEEP composition polynomial values.
+    ///
+    /// `z` contains all out-of-domain points sampled during the DEEP step of the protocol, and
+    /// `lde_powers` contains powers of the LDE domain generator at all query positions.
+    ///
+    /// Denominators of all DEEP quotients are computed for all queries and all out-of-domain
+    /// points here, and are then inverted together using a single batch inversion. This way,
+    /// computing compositions requires just one field inversion rather than an inversion for
+    /// every queried value.
+    pub fn new<A: Air<BaseField = E::BaseField>>(
+        air: &A,
+        query_positions: &[LdePos],
+        lde_powers: &PowerTable<E::BaseField>,
+        z: &[E],
+        cc: DeepCompositionCoefficients<E>,
+    ) -> Self {
+        debug_assert_eq!(air.lde_domain_size(), lde_powers.domain_size());
+
+        // compute LDE domain coordinates for all query positions
+        let domain_offset = air.domain_offset();
+        let x_coordinates: Vec<E> = query_positions
+            .iter()
+            .map(|&p| E::from(lde_powers.get(p.as_usize()) * domain_offset))
+            .collect();
+
+        // for every out-of-domain point z, compute points z * g^k for all row offsets k of the
+        // evaluation frame; the first of these points is always z itself
+        let g_trace = air.trace_domain_generator();
+        let z: Vec<Vec<E>> = z
+            .iter()
+            .map(|&z| {
+                air.context()
+                    .frame_offsets()
+                    .iter()
+                    .map(|&offset| match offset {
+                        0 => z,
+                        _ => z * E::from(g_trace.exp_vartime((offset as u32).into())),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // compute denominators for all out-of-domain points and all queries, and invert them;
+        // for a single point, these are (x - z * g^k) for all row offsets k, followed by
+        // (x - z^m), and, when field extension is used, by (x - z_conjugate); every denominator
+        // is computed for all query points x in a row, so that DEEP quotients can be evaluated
+        // over the entire query vector at once
+        let field_extension = air.options().field_extension();
+        let num_constraint_columns = air.ce_blowup_factor();
+        let num_offsets = air.context().frame_offsets().len();
+        let num_denominators = num_offsets + if field_extension.is_none() { 1 } else { 2 };
+        let mut denominators = Vec::with_capacity(z.len() * x_coordinates.len() * num_denominators);
+        for z_points in z.iter() {
+            let z = z_points[0];
+            let z_m = z.exp_vartime((num_constraint_columns as u32).into());
+            let z_conjugate = z.conjugate();
+            for &point in z_points
+                .iter()
+                .chain([z_m, z_conjugate].iter())
+                .take(num_denominators)
+            {
+                denominators.extend(x_coordinates.iter().map(|&x| x - point));
+            }
+        }
+        let inv_denominators = batch_inversion(&denominators);
+
+        DeepComposer {
+            field_extension,
+            cc,
+            x_coordinates,
+            z,
+            num_constraint_columns,
+            num_denominators,
+            inv_denominators,
+        }
+    }
+
+    /// For each queried trace state, combines column values into a single value by computing
+    /// their random linear combinations as follows:
+    ///
+    /// - Assume each column value is an evaluation of a trace polynomial T_i(x).
+    /// - For each T_i(x) compute T'_i(x) = (T_i(x) - T_i(z)) / (x - z) and
+    ///   T''_i = (T_i(x) - T_i(z * g)) / (x - z * g), where z is the out-of-domain point and
+    ///   g is the generation of the LDE domain.
+    /// - Then, combine all T'_i(x) and T''_i(x) values together by computing
+    ///   T(x) = sum(T'_i(x) * cc'_i + T''_i(x) * cc''_i) for all i, where cc'_i and cc''_i are
+    ///   the coefficients for the random linear combination drawn from the public coin.
+    /// - In cases when the proof was generated using an extension field, we also compute
+    ///   T'''_i(x) = (T_i(x) - T_i(z_conjugate)) / (x - z_conjugate), and add it to T(x) similarly
+    ///   to the way described above. This is needed in order to verify that the trace is defined
+    ///   over the base field, rather than the extension field.
+    ///
+    /// When the evaluation frame of the AIR uses row offsets other than the default ones, z * g
+    /// above is replaced with z * g^k for the offset k of the second row of the frame, and a
+    /// quotient (T_i(x) - T_i(z * g^k)) / (x - z * g^k) is added to T(x) for every other row of
+    /// the frame as well, each with its own coefficient.
+    ///
+    /// When multiple out-of-domain points are used, the above is done for every point z using
+    /// coefficients drawn for that point, and the results are added together.
+    ///
+    /// Note that values of T_i(z) and T_i(z * g) are received from the prover and passed into
+    /// this function via the `ood_frames` parameter; this parameter contains a frame for each of
+    /// the out-of-domain points.
+    pub fn compose_trace_columns(
+        &self,
+        queried_main_trace_states: Table<E::BaseField>,
+        queried_aux_trace_states: Option<Table<E>>,
+        ood_frames: Vec<(EvaluationFrame<E>, Option<EvaluationFrame<E>>)>,
+    ) -> Vec<E> {
+        assert_eq!(self.z.len(), ood_frames.len());
+
+        // transpose queried states so that values of every column at all query points are stored
+        // together; this way, each DEEP quotient is evaluated over the whole query vector at once
+        let main_columns = transpose(&queried_main_trace_states);
+        let aux_columns = queried_aux_trace_states.as_ref().map(transpose);
+
+        // coefficients of the linear combination for a single DEEP quotient are collected into
+        // this buffer for every quotient
+        let num_main_columns = queried_main_trace_states.num_columns();
+        let num_aux_columns = queried_aux_trace_states
+            .as_ref()
+            .map_or(0, |t| t.num_columns());
+        let mut coefficients = Vec::with_capacity(num_main_columns.max(num_aux_columns));
+
+        let num_offsets = self.num_offsets();
+        let mut result = E::zeroed_vector(queried_main_trace_states.num_rows());
+        for (point_idx, (ood_main_frame, ood_aux_frame)) in ood_frames.iter().enumerate() {
+            let cc = self.cc.trace_coefficients(point_idx, self.z.len());
+            let cc_offsets = self.cc.offset_coefficients(point_idx, self.z.len());
+
+            // compose columns of the main trace segment
+            self.compose_frame(
+                point_idx,
+                &main_columns,
+                ood_main_frame,
+                &cc[..num_main_columns],
+                cc_offsets,
+                &mut coefficients,
+                &mut result,
+            );
+
+            // when extension field is enabled compute
+            // T'''_i(x) = (T_i(x) - T_i(z_conjugate)) / (x - z_conjugate); we do this only for the
+            // main trace since auxiliary trace columns are in the extension field.
+            if !self.field_extension.is_none() {
+                let conjugate_values = ood_main_frame
+                    .row(0)
+                    .iter()
+                    .map(|v| v.conjugate())
+                    .collect::<Vec<_>>();
+                coefficients.clear();
+                coefficients.extend(cc[..num_main_columns].iter().map(|cc| cc.2));
+                eval_deep_at_points(
+                    &main_columns,
+                    &conjugate_values,
+                    &coefficients,
+                    self.get_inv_denominators(point_idx, num_offsets + 1),
+                    &mut result,
+                );
+            }
+
+            // if the trace has auxiliary segments, compose columns from these segments as well;
+            // composition of the main trace columns has consumed some number of composition
+            // coefficients already, and thus, coefficients for auxiliary columns follow them
+            if let Some(ref aux_columns) = aux_columns {
+                let ood_aux_frame = ood_aux_frame.as_ref().expect("missing auxiliary OOD frame");
+                self.compose_frame::<E>(
+                    point_idx,
+                    aux_columns,
+                    ood_aux_frame,
+                    &cc[num_main_columns..],
+                    &cc_offsets[num_main_columns * (num_offsets - 2)..],
+                    &mut coefficients,
+                    &mut result,
+                );
+            }
+        }
+
+        result
+    }
+
+    /// For each queried set of composition polynomial column evaluations, combine evaluations
+    /// into a single value by computing their random linear combination as follows:
+    ///
+    /// - Assume each queried value is an evaluation of a composition polynomial column H_i(x).
+    /// - For each H_i(x), compute H'_i(x) = (H_i(x) - H(z^m)) / (x - z^m), where m is the
+    ///   number of composition polynomial columns per out-of-domain point.
+    /// - Then, combine all H_i(x) values together by computing H(x) = sum(H_i(x) * cc_i) for
+    ///   all i, where cc_i is the coefficient for the random linear combination drawn from the
+    ///   public coin.
+    ///
+    /// When multiple out-of-domain points are used, queried evaluations contain a separate set of
+    /// columns for every point; the above is done for every point z using the columns and
+    /// coefficients of that point, and the results are added together.
+    ///
+    /// Note that values of H_i(z^m)are received from teh prover and passed into this function
+    /// via the `ood_evaluations` parameter; this parameter contains evaluations for each of the
+    /// out-of-domain points.
+    pub fn compose_constraint_evaluations(
+        &self,
+        queried_evaluations: Table<E>,
+        ood_evaluations: Vec<Vec<E>>,
+    ) -> Vec<E> {
+        assert_eq!(queried_evaluations.num_rows(), self.x_coordinates.len());
+        assert_eq!(self.z.len(), ood_evaluations.len());
+        assert_eq!(
+            self.z.len() * self.num_constraint_columns,
+            queried_evaluations.num_columns()
+        );
+
+        // the denominator (x - z^m) follows the denominators for all rows of the frame
+        let zm_idx = self.num_offsets();
+        let columns = transpose(&queried_evaluations);
+        let point_columns_len = self.num_constraint_columns * queried_evaluations.num_rows();
+        let mut result = E::zeroed_vector(queried_evaluations.num_rows());
+        for (point_idx, (ood_evaluations, columns)) in ood_evaluations
+            .iter()
+            .zip(columns.chunks(point_columns_len))
+            .enumerate()
+        {
+            assert_eq!(self.num_constraint_columns, ood_evaluations.len());
+            let cc = self.cc.constraint_coefficients(point_idx, self.z.len());
+
+            // compute H'_i(x) = (H_i(x) - H(z^m)) / (x - z^m), multiply it by a pseudo-random
+            // coefficient, and add the result to H(x)
+            eval_deep_at_points(
+                columns,
+                ood_evaluations,
+                cc,
+                self.get_inv_denominators(point_idx, zm_idx),
+                &mut result,
+            );
+        }
+
+        result
+    }
+
+    /// Combines trace and constraint compositions together, and also rases the degree of the
+    /// resulting value by one to match trace polynomial degree. This is needed because when
+    /// we divide evaluations by (x - z), (x - z * g) etc. the degree is reduced by one - so,
+    /// we compensate for it here.
+    #[rustfmt::skip]
+    pub fn combine_compositions(&self, t_composition: Vec<E>, c_composition: Vec<E>) -> Vec<E> {
+        assert_eq!(t_composition.len(), self.x_coordinates.len());
+        assert_eq!(c_composition.len(), self.x_coordinates.len());
+
+        let mut result = Vec::with_capacity(self.x_coordinates.len());
+        for ((&x, t), c) in self.x_coordinates.iter().zip(t_composition).zip(c_composition) {
+            // compute C(x) by adding the two compositions together
+            let composition = t + c;
+
+            // raise the degree of C(x) by computing C'(x) = C(x) * (cc_0 + x * cc_1), where
+            // cc_0 and cc_1 are the coefficients for the random linear combination drawn from
+            // the public coin.
+            result.push(composition * (self.cc.degree.0 + x * self.cc.degree.1));
+        }
+
+        result
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of rows in the evaluation frame.
+    fn num_offsets(&self) -> usize {
+        self.z[0].len()
+    }
+
+    /// Adds quotients (T_i(x) - T_i(z * g^k)) / (x - z * g^k) for all rows k of the `frame` and
+    /// all `columns` of a trace segment, multiplied by their composition coefficients, into the
+    /// `result`; the coefficients for rows beyond the first two are read from `cc_offsets`.
+    ///
+    /// `columns` contains values of all columns of the segment at all query points arranged by
+    /// columns, and `coefficients` is used as a buffer for the coefficients of every quotient.
+    #[allow(clippy::too_many_arguments)]
+    fn compose_frame<F>(
+        &self,
+        point_idx: usize,
+        columns: &[F],
+        frame: &EvaluationFrame<E>,
+        cc: &[(E, E, E)],
+        cc_offsets: &[E],
+        coefficients: &mut Vec<E>,
+        result: &mut [E],
+    ) where
+        F: FieldElement,
+        E: ExtensionOf<F>,
+    {
+        let num_extra_rows = self.num_offsets() - 2;
+        for row_idx in 0..frame.num_rows() {
+            coefficients.clear();
+            coefficients.extend((0..cc.len()).map(|i| match row_idx {
+                0 => cc[i].0,
+                1 => cc[i].1,
+                _ => cc_offsets[i * num_extra_rows + row_idx - 2],
+            }));
+            eval_deep_at_points(
+                columns,
+                frame.row(row_idx),
+                coefficients,
+                self.get_inv_denominators(point_idx, row_idx),
+                result,
+            );
+        }
+    }
+
+    /// Returns inverted DEEP denominators at all query points for the specified out-of-domain
+    /// point and the denominator with the specified index.
+    fn get_inv_denominators(&self, point_idx: usize, denominator_idx: usize) -> &[E] {
+        let num_queries = self.x_coordinates.len();
+        let start = (point_idx * self.num_denominators + denominator_idx) * num_queries;
+        &self.inv_denominators[start..start + num_queries]
+    }
+}
+
+// DEEP KERNEL
+// ================================================================================================
+
+/// Evaluates a single DEEP quotient term at all query points and adds the results into `result`.
+///
+/// For columns T_i, the term is defined as sum(cc_i * (T_i(x) - T_i(p)) / (x - p)), where p is
+/// an out-of-domain point (e.g., z, z * g, or z^m), T_i(p) are specified by `ood_values`, and
+/// cc_i are specified by `coefficients`. `columns` contains values T_i(x) at all query points x
+/// arranged by columns (i.e., values of the first column at all points, followed by values of
+/// the second column etc.), and `inv_denominators` contains 1 / (x - p) for the same points in
+/// the same order.
+///
+/// The term is computed as (sum(cc_i * T_i(x)) - sum(cc_i * T_i(p))) / (x - p): the linear
+/// combination of columns is accumulated column by column over the entire query vector, the
+/// linear combination of out-of-domain values is computed only once, and every query point
+/// requires just a single subtraction and a single multiplication by the inverted denominator.
+/// The results are exactly the same as when every quotient is computed for each query point
+/// separately.
+///
+/// # Panics
+/// Panics if:
+/// * Lengths of `ood_values` and `coefficients` are not the same.
+/// * Lengths of `inv_denominators` and `result` are not the same.
+/// * Length of `columns` is not equal to the number of coefficients times the length of `result`.
+pub fn eval_deep_at_points<F, E>(
+    columns: &[F],
+    ood_values: &[E],
+    coefficients: &[E],
+    inv_denominators: &[E],
+    result: &mut [E],
+) where
+    F: FieldElement,
+    E: FieldElement + ExtensionOf<F>,
+{
+    assert_eq!(ood_values.len(), coefficients.len());
+    assert_eq!(inv_denominators.len(), result.len());
+    assert_eq!(coefficients.len() * result.len(), columns.len());
+    if result.is_empty() {
+        return;
+    }
+
+    // compute sum(cc_i * T_i(x)) for all query points x
+    let mut combination = E::zeroed_vector(result.len());
+    for (column, &cc) in columns.chunks(result.len()).zip(coefficients) {
+        for (acc, &value) in combination.iter_mut().zip(column) {
+            *acc += cc.mul_base(value);
+        }
+    }
+
+    // compute sum(cc_i * T_i(p))
+    let ood_combination = ood_values
+        .iter()
+        .zip(coefficients)
+        .fold(E::ZERO, |acc, (&value, &cc)| acc + value * cc);
+
+    for ((result, combination), &inv_denominator) in
+        result.iter_mut().zip(combination).zip(inv_denominators)
+    {
+        *result += (combination - ood_combination) * inv_denominator;
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns values of the `table` arranged by columns.
+fn transpose<F: FieldElement>(table: &Table<F>) -> Vec<F> {
+    let num_rows = table.num_rows();
+    let mut columns = F::zeroed_vector(num_rows * table.num_columns());
+    for (row_idx, row) in table.rows().enumerate() {
+        for (col_idx, &value) in row.iter().enumerate() {
+            columns[col_idx * num_rows + row_idx] = value;
+        }
+    }
+    columns
+}
@@ -0,0 +1,507 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{eval_deep_at_points, DeepComposer};
+use air::{
+    proof::{Context, Table},
+    Air, AirContext, Assertion, DeepCompositionCoefficients, EvaluationFrame, FieldExtension,
+    LdePos, PowerTable, ProofOptions, TraceInfo, TransitionConstraintDegree,
+};
+use core::{
+    cell::Cell,
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+use math::{
+    fields::{f128::BaseElement, QuadExtension},
+    ExtensionOf, FieldElement,
+};
+use rand_utils::{rand_value, rand_vector};
+use utils::{
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Randomizable, Serializable,
+};
+
+use utils::AsBytes;
+
+const NUM_QUERIES: usize = 54;
+const TRACE_WIDTH: usize = 8;
+const TRACE_LENGTH: usize = 1024;
+
+// DEEP COMPOSITION TESTS
+// ================================================================================================
+
+#[test]
+fn deep_composition_batch_inversion() {
+    // without field extension, every query requires inversions of (x - z), (x - z * g), and
+    // (x - z^m); all of them are now inverted together
+    let (counts, naive_counts) = compose_and_count(FieldExtension::None);
+    assert_eq!(NUM_QUERIES * (2 * TRACE_WIDTH + 2), naive_counts.inversions);
+    assert_eq!(1, counts.inversions);
+    assert!(counts.multiplications <= naive_counts.multiplications + 3 * 3 * NUM_QUERIES);
+}
+
+#[test]
+fn deep_composition_batch_inversion_extension() {
+    // with field extension, every query also requires inversion of (x - z_conjugate)
+    let (counts, naive_counts) = compose_and_count(FieldExtension::Quadratic);
+    assert_eq!(NUM_QUERIES * (3 * TRACE_WIDTH + 2), naive_counts.inversions);
+    assert_eq!(1, counts.inversions);
+    assert!(counts.multiplications <= naive_counts.multiplications + 3 * 4 * NUM_QUERIES);
+}
+
+#[test]
+fn deep_kernel_batched_evaluation() {
+    for num_queries in [27, 54, 108] {
+        let columns = rand_vector::<BaseElement>(TRACE_WIDTH * num_queries);
+        let ood_values = rand_vector::<CountingElement>(TRACE_WIDTH);
+        let coefficients = rand_vector::<CountingElement>(TRACE_WIDTH);
+        let inv_denominators = rand_vector::<CountingElement>(num_queries);
+        let initial = rand_vector::<CountingElement>(num_queries);
+
+        // evaluate the quotients over all query points at once
+        reset_op_counts();
+        let mut result = initial.clone();
+        eval_deep_at_points(
+            &columns,
+            &ood_values,
+            &coefficients,
+            &inv_denominators,
+            &mut result,
+        );
+        let counts = get_op_counts();
+
+        // evaluate every quotient for every query separately
+        reset_op_counts();
+        let mut expected = initial;
+        for (j, expected) in expected.iter_mut().enumerate() {
+            for i in 0..TRACE_WIDTH {
+                let value = CountingElement::from(columns[i * num_queries + j]);
+                *expected += (value - ood_values[i]) * inv_denominators[j] * coefficients[i];
+            }
+        }
+        let naive_counts = get_op_counts();
+
+        assert_eq!(expected, result);
+        assert_eq!(2 * num_queries * TRACE_WIDTH, naive_counts.multiplications);
+        assert_eq!(
+            num_queries * TRACE_WIDTH + TRACE_WIDTH + num_queries,
+            counts.multiplications
+        );
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Computes DEEP composition for random queries using [DeepComposer] and using a reference
+/// implementation which divides every queried value by its denominator; makes sure both results
+/// are the same, and returns field operation counts for both implementations.
+fn compose_and_count(field_extension: FieldExtension) -> (OpCounts, OpCounts) {
+    let options = ProofOptions::new(NUM_QUERIES, 8, 0, field_extension, 4, 256);
+    let air = TestAir::new(
+        TraceInfo::new(TRACE_WIDTH, TRACE_LENGTH),
+        (),
+        options.clone(),
+    );
+    let num_columns = air.num_composition_columns();
+
+    let query_positions = rand_vector::<u64>(NUM_QUERIES)
+        .into_iter()
+        .map(|p| LdePos(p as usize % air.lde_domain_size()))
+        .collect::<Vec<_>>();
+    let z = [rand_value::<CountingElement>()];
+    let cc = DeepCompositionCoefficients {
+        trace: (0..TRACE_WIDTH)
+            .map(|_| (rand_value(), rand_value(), rand_value()))
+            .collect(),
+        trace_offsets: Vec::new(),
+        constraints: rand_vector(num_columns),
+        degree: (rand_value(), rand_value()),
+    };
+
+    let trace_states = rand_vector::<BaseElement>(NUM_QUERIES * TRACE_WIDTH);
+    let ood_frame = EvaluationFrame::from_rows(rand_vector(TRACE_WIDTH), rand_vector(TRACE_WIDTH));
+    let evaluations = rand_vector::<CountingElement>(NUM_QUERIES * num_columns);
+    let ood_evaluations: Vec<CountingElement> = rand_vector(num_columns);
+
+    // compose values using the composer
+    reset_op_counts();
+    let lde_powers = PowerTable::new(
+        air.lde_domain_size(),
+        query_positions.iter().map(|p| p.as_usize()),
+    );
+    let composer = DeepComposer::new(&air, &query_positions, &lde_powers, &z, cc.clone());
+    let t_composition = composer.compose_trace_columns(
+        Table::from_elements(trace_states.clone(), TRACE_WIDTH),
+        None,
+        vec![(ood_frame.clone(), None)],
+    );
+    let c_composition = composer.compose_constraint_evaluations(
+        Table::from_elements(evaluations.clone(), num_columns),
+        vec![ood_evaluations.clone()],
+    );
+    let result = composer.combine_compositions(t_composition, c_composition);
+    let counts = get_op_counts();
+
+    // compose values using the reference implementation
+    reset_op_counts();
+    let expected = compose_naively(
+        &air,
+        &query_positions,
+        z[0],
+        &cc,
+        &trace_states,
+        &ood_frame,
+        &evaluations,
+        &ood_evaluations,
+    );
+    let naive_counts = get_op_counts();
+
+    assert_eq!(expected, result);
+    (counts, naive_counts)
+}
+
+/// Computes DEEP composition by performing a division for every DEEP quotient.
+///
+/// Domain elements are computed using the public helpers of the proof [Context] rather than
+/// the AIR, and thus, this also makes sure that the helpers are consistent with the verifier.
+#[allow(clippy::too_many_arguments)]
+fn compose_naively(
+    air: &TestAir,
+    query_positions: &[LdePos],
+    z: CountingElement,
+    cc: &DeepCompositionCoefficients<CountingElement>,
+    trace_states: &[BaseElement],
+    ood_frame: &EvaluationFrame<CountingElement>,
+    evaluations: &[CountingElement],
+    ood_evaluations: &[CountingElement],
+) -> Vec<CountingElement> {
+    let context = Context::new::<BaseElement>(air.trace_info(), air.options().clone());
+    let z_g = z * CountingElement::from(context.trace_domain_generator::<BaseElement>());
+    let z_m = z.exp_vartime((ood_evaluations.len() as u32).into());
+    let extension = !air.options().field_extension().is_none();
+
+    let mut result = Vec::with_capacity(query_positions.len());
+    for (i, &p) in query_positions.iter().enumerate() {
+        let x = CountingElement::from(context.lde_domain_element::<BaseElement>(p));
+
+        let mut composition = CountingElement::ZERO;
+        let row = &trace_states[i * TRACE_WIDTH..(i + 1) * TRACE_WIDTH];
+        for (j, &value) in row.iter().enumerate() {
+            let value = CountingElement::from(value);
+            composition += (value - ood_frame.current()[j]) / (x - z) * cc.trace[j].0;
+            composition += (value - ood_frame.next()[j]) / (x - z_g) * cc.trace[j].1;
+            if extension {
+                let t3 = (value - ood_frame.current()[j].conjugate()) / (x - z.conjugate());
+                composition += t3 * cc.trace[j].2;
+            }
+        }
+
+        let num_columns = ood_evaluations.len();
+        let row = &evaluations[i * num_columns..(i + 1) * num_columns];
+        for (j, &evaluation) in row.iter().enumerate() {
+            composition += (evaluation - ood_evaluations[j]) / (x - z_m) * cc.constraints[j];
+        }
+
+        result.push(composition * (cc.degree.0 + x * cc.degree.1));
+    }
+    result
+}
+
+// TEST AIR
+// ================================================================================================
+
+/// An AIR with a single quadratic transition constraint; only the parameters of the AIR are
+/// used by the composer.
+struct TestAir {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for TestAir {
+    type BaseField = BaseElement;
+    type PublicInputs = ();
+
+    fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+        let degrees = vec![TransitionConstraintDegree::new(2)];
+        TestAir {
+            context: AirContext::new(trace_info, degrees, 1, options),
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        result[0] = frame.next()[0] - frame.current()[0].square();
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        vec![Assertion::single(0, 0, BaseElement::ONE)]
+    }
+}
+
+// COUNTING FIELD ELEMENT
+// ================================================================================================
+
+thread_local! {
+    static NUM_INVERSIONS: Cell<usize> = const { Cell::new(0) };
+    static NUM_MULTIPLICATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Numbers of field operations performed since the last call to [reset_op_counts()].
+#[derive(Debug)]
+struct OpCounts {
+    inversions: usize,
+    multiplications: usize,
+}
+
+fn reset_op_counts() {
+    NUM_INVERSIONS.with(|c| c.set(0));
+    NUM_MULTIPLICATIONS.with(|c| c.set(0));
+}
+
+fn get_op_counts() -> OpCounts {
+    OpCounts {
+        inversions: NUM_INVERSIONS.with(|c| c.get()),
+        multiplications: NUM_MULTIPLICATIONS.with(|c| c.get()),
+    }
+}
+
+fn count_inversion() {
+    NUM_INVERSIONS.with(|c| c.set(c.get() + 1));
+}
+
+fn count_multiplication() {
+    NUM_MULTIPLICATIONS.with(|c| c.set(c.get() + 1));
+}
+
+/// An element of the quadratic extension of the 128-bit field which counts the inversions and
+/// multiplications performed on it; divisions are counted as an inversion and a multiplication.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+struct CountingElement(QuadExtension<BaseElement>);
+
+type Inner = QuadExtension<BaseElement>;
+
+impl FieldElement for CountingElement {
+    type PositiveInteger = <Inner as FieldElement>::PositiveInteger;
+    type BaseField = BaseElement;
+
+    const ELEMENT_BYTES: usize = Inner::ELEMENT_BYTES;
+    const IS_CANONICAL: bool = Inner::IS_CANONICAL;
+    const ZERO: Self = Self(Inner::ZERO);
+    const ONE: Self = Self(Inner::ONE);
+
+    fn inv(self) -> Self {
+        count_inversion();
+        Self(self.0.inv())
+    }
+
+    fn conjugate(&self) -> Self {
+        Self(self.0.conjugate())
+    }
+
+    fn elements_as_bytes(elements: &[Self]) -> &[u8] {
+        Inner::elements_as_bytes(as_inner(elements))
+    }
+
+    unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError> {
+        let elements = Inner::bytes_as_elements(bytes)?;
+        Ok(core::slice::from_raw_parts(
+            elements.as_ptr() as *const Self,
+            elements.len(),
+        ))
+    }
+
+    fn as_base_elements(elements: &[Self]) -> &[Self::BaseField] {
+        Inner::as_base_elements(as_inner(elements))
+    }
+
+    fn to_base_elements(elements: &[Self]) -> Vec<Self::BaseField> {
+        let elements = elements.iter().map(|e| e.0).collect::<Vec<_>>();
+        Inner::to_base_elements(&elements)
+    }
+}
+
+fn as_inner(elements: &[CountingElement]) -> &[Inner] {
+    // this is safe because CountingElement is a transparent wrapper around the inner element
+    unsafe { core::slice::from_raw_parts(elements.as_ptr() as *const Inner, elements.len()) }
+}
+
+impl ExtensionOf<BaseElement> for CountingElement {
+    fn mul_base(self, other: BaseElement) -> Self {
+        count_multiplication();
+        Self(self.0.mul_base(other))
+    }
+}
+
+impl Add for CountingElement {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for CountingElement {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl Sub for CountingElement {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for CountingElement {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl Mul for CountingElement {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        count_multiplication();
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl MulAssign for CountingElement {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl Div for CountingElement {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl DivAssign for CountingElement {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs
+    }
+}
+
+impl Neg for CountingElement {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl From<BaseElement> for CountingElement {
+    fn from(value: BaseElement) -> Self {
+        Self(Inner::from(value))
+    }
+}
+
+impl From<u128> for CountingElement {
+    fn from(value: u128) -> Self {
+        Self(Inner::from(value))
+    }
+}
+
+impl From<u64> for CountingElement {
+    fn from(value: u64) -> Self {
+        Self(Inner::from(value))
+    }
+}
+
+impl From<u32> for CountingElement {
+    fn from(value: u32) -> Self {
+        Self(Inner::from(value))
+    }
+}
+
+impl From<u16> for CountingElement {
+    fn from(value: u16) -> Self {
+        Self(Inner::from(value))
+    }
+}
+
+impl From<u8> for CountingElement {
+    fn from(value: u8) -> Self {
+        Self(Inner::from(value))
+    }
+}
+
+impl TryFrom<&[u8]> for CountingElement {
+    type Error = DeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Inner::try_from(bytes).map(Self)
+    }
+}
+
+impl FromStr for CountingElement {
+    type Err = DeserializationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Inner::from_str(s).map(Self)
+    }
+}
+
+impl fmt::Display for CountingElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for CountingElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for CountingElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl AsBytes for CountingElement {
+    fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl Randomizable for CountingElement {
+    const VALUE_SIZE: usize = Inner::VALUE_SIZE;
+
+    fn from_random_bytes(bytes: &[u8]) -> Option<Self> {
+        Inner::from_random_bytes(bytes).map(Self)
+    }
+}
+
+impl Serializable for CountingElement {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.0.write_into(target)
+    }
+}
+
+impl Deserializable for CountingElement {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Inner::read_from(source).map(Self)
+    }
+}
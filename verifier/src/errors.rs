@@ -5,20 +5,102 @@
 
 //! Contains common error types for prover and verifier.
 
+use air::AssertionError;
 use core::fmt;
-use utils::string::String;
+use crypto::HashFunctionId;
+use utils::{collections::Vec, string::String};
 
 // VERIFIER ERROR
 // ================================================================================================
 /// Represents an error returned by the verifier during an execution of the protocol.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VerifierError {
     /// This error occurs when base field read by a verifier from a proof does not match the
     /// base field of AIR with which the verifier was instantiated.
     InconsistentBaseField,
     /// This error occurs when the base field in which the proof was generated does not support
-    /// field extension of degree specified by the proof.
+    /// field extension of degree specified by the proof, or when the crate was compiled without
+    /// the feature enabling this extension.
     UnsupportedFieldExtension(usize),
+    /// This error occurs when the proof was generated using a field extension of a smaller
+    /// degree than required by the AIR to meet the security target implied by the proof options.
+    /// The first value is the required degree, and the second value is the degree specified by
+    /// the proof.
+    InsufficientExtensionDegree(u32, u32),
+    /// This error occurs when the LDE domain of the computation described by the proof is too
+    /// large to be indexed by `usize` on the platform running the verifier (e.g., a domain of
+    /// 2^32 elements on a 32-bit platform). The value is log2 of the LDE domain size.
+    DomainTooLargeForPlatform(u32),
+    /// This error occurs when an assertion specified by the AIR cannot be placed against the
+    /// execution trace described by the proof (e.g., when it is placed against a step specified
+    /// in public inputs which is outside of the trace).
+    InvalidAssertion(AssertionError),
+    /// This error occurs when the transcript profile specified by the proof cannot be used with
+    /// the hash function of the verifier (e.g., when the arithmetic transcript profile is used
+    /// with a hash function which does not produce digests composed of field elements).
+    UnsupportedTranscriptProfile,
+    /// This error occurs when the grinding hash function specified by the proof cannot be used
+    /// with the base field of the verifier, or when the crate was compiled without the feature
+    /// enabling this hash function.
+    UnsupportedGrindingHash(HashFunctionId),
+    /// This error occurs when the leaf encoding specified by the proof cannot be used for rows of
+    /// trace commitments with the hash function of the verifier (e.g., when raw leaves are used
+    /// for rows which are longer than a digest). The value is the number of bytes in the widest
+    /// row of trace commitments.
+    UnsupportedLeafEncoding(usize),
+    /// This error occurs when the proof declares a preprocessed trace segment but the commitment
+    /// to this segment was not provided to the verifier, or when the commitment was provided but
+    /// the proof does not declare a preprocessed trace segment.
+    PreprocessedSegmentMismatch,
+    /// This error occurs when the proof was generated with salted commitments (see
+    /// [ProofOptions::with_zk()](air::ProofOptions::with_zk)), but the verifier accepts only
+    /// proofs with unsalted commitments.
+    UnsupportedSaltedCommitments,
+    /// This error occurs when the transition exemptions bound into the proof context are
+    /// different from the transition exemptions defined by the AIR with which the verifier was
+    /// instantiated for at least one of the transition constraints.
+    ExemptionMismatch {
+        /// Number of transition exemptions of each constraint specified by the proof.
+        proof: Vec<usize>,
+        /// Number of transition exemptions of each constraint defined by the AIR.
+        air: Vec<usize>,
+    },
+    /// This error occurs when the hash function is selected from the proof context at runtime,
+    /// but the proof context does not specify the hash function with which the proof was
+    /// generated.
+    UnknownHashFunction,
+    /// This error occurs when the hash function specified by the proof context cannot be used
+    /// with the base field of the AIR, or when the crate was compiled without the feature
+    /// enabling this hash function.
+    UnsupportedHashFunction(HashFunctionId),
+    /// This error occurs when the hash function specified by the proof context is different from
+    /// the hash function with which the verifier was instantiated.
+    HashFunctionMismatch {
+        /// Hash function specified by the proof.
+        proof: HashFunctionId,
+        /// Hash function of the verifier.
+        verifier: HashFunctionId,
+    },
+    /// This error occurs when the vector commitment scheme specified by the proof context is
+    /// different from the commitment scheme with which the verifier was instantiated.
+    CommitmentSchemeMismatch {
+        /// Identifier of the commitment scheme specified by the proof.
+        proof: u8,
+        /// Identifier of the commitment scheme of the verifier.
+        verifier: u8,
+    },
+    /// This error occurs when the random coin specified by the proof context is different from
+    /// the random coin with which the verifier was instantiated.
+    RandomCoinMismatch {
+        /// Identifier of the random coin specified by the proof.
+        proof: u8,
+        /// Identifier of the random coin of the verifier.
+        verifier: u8,
+    },
+    /// This error occurs when the number of public inputs provided for a batch of proofs is
+    /// different from the number of proofs in the batch. The first value is the number of
+    /// proofs, and the second value is the number of public inputs.
+    BatchSizeMismatch(usize, usize),
     /// This error occurs when a verifier cannot deserialize the specified proof.
     ProofDeserializationError(String),
     /// This error occurs when a verifier fails to draw a random value from a random coin
@@ -30,13 +112,36 @@ pub enum VerifierError {
     /// This error occurs when Merkle authentication paths of trace queries do not resolve to the
     /// execution trace commitment included in the proof.
     TraceQueryDoesNotMatchCommitment,
+    /// This error occurs when evaluations or openings of the combined trace column included in the
+    /// proof are not equal to the random linear combination of the corresponding evaluations or
+    /// openings of individual trace columns.
+    CombinedTraceColumnMismatch,
     /// This error occurs when Merkle authentication paths of constraint evaluation queries do not
-    /// resolve to the constraint evaluation commitment included in the proof.
+    /// resolve to the constraint evaluation commitment included in the proof, or when the hash of
+    /// explicitly sent composition polynomial coefficients does not match this commitment.
     ConstraintQueryDoesNotMatchCommitment,
+    /// This error occurs when the number of composition polynomial coefficients sent in explicit
+    /// composition mode is different from the number implied by the degree of the composition
+    /// polynomial. The first value is the expected number, and the second value is the number of
+    /// coefficients included in the proof.
+    InvalidCompositionCoefficientCount(usize, usize),
     /// This error occurs when the proof-of-work nonce hashed with the current state of the public
     /// coin resolves to a value which does not meet the proof-of-work threshold specified by the
     // proof options.
     QuerySeedProofOfWorkVerificationFailed,
+    /// This error occurs when the proof-of-work nonce provides fewer bits than the grinding
+    /// factor, and the proof does not contain enough extra queries to compensate for the missing
+    /// bits. The first value is the required number of extra queries, and the second value is
+    /// the number of extra queries contained in the proof.
+    InsufficientExtraQueries(usize, usize),
+    /// This error occurs when the number of FRI layers in the low-degree proof is different from
+    /// the number of layers implied by the proof options and the size of the LDE domain. The first
+    /// value is the expected number of layers, and the second value is the number of layers
+    /// included in the proof.
+    FriStructureMismatch(usize, usize),
+    /// This error occurs when the digest of the FRI remainder polynomial included in the proof
+    /// does not match the digest of the coefficients of the FRI remainder included in the proof.
+    FriRemainderDigestMismatch,
     /// This error occurs when the DEEP composition polynomial evaluations derived from trace and
     /// constraint evaluation queries do not represent a polynomial of the degree expected by the
     /// verifier.
@@ -53,6 +158,51 @@ impl fmt::Display for VerifierError {
             Self::UnsupportedFieldExtension(degree) => {
                 write!(f, "field extension of degree {degree} is not supported for the proof base field")
             }
+            Self::InsufficientExtensionDegree(required, actual) => {
+                write!(f, "field extension of degree {actual} is insufficient for the proof options; degree {required} is required")
+            }
+            Self::DomainTooLargeForPlatform(log2) => {
+                write!(f, "LDE domain of 2^{log2} elements is too large for a {}-bit platform", usize::BITS)
+            }
+            Self::InvalidAssertion(err) => {
+                write!(f, "assertion is not valid for the execution trace: {err}")
+            }
+            Self::UnsupportedTranscriptProfile => {
+                write!(f, "transcript profile of the proof is not supported for the specified hash function")
+            }
+            Self::UnsupportedGrindingHash(hash_fn) => {
+                write!(f, "grinding hash function {hash_fn} of the proof is not supported for the specified base field")
+            }
+            Self::UnsupportedLeafEncoding(row_bytes) => {
+                write!(f, "leaf encoding of the proof is not supported for trace rows of {row_bytes} bytes and the specified hash function")
+            }
+            Self::PreprocessedSegmentMismatch => {
+                write!(f, "commitment to the preprocessed trace segment must be provided if and only if the proof declares this segment")
+            }
+            Self::UnsupportedSaltedCommitments => {
+                write!(f, "proof was generated with salted commitments, but the verifier accepts only unsalted commitments")
+            }
+            Self::ExemptionMismatch { proof, air } => {
+                write!(f, "proof was generated with transition exemptions {proof:?}, but the AIR defines {air:?}")
+            }
+            Self::UnknownHashFunction => {
+                write!(f, "proof context does not specify the hash function of the proof")
+            }
+            Self::UnsupportedHashFunction(hash_fn) => {
+                write!(f, "hash function {hash_fn} is not supported for the base field of the specified AIR")
+            }
+            Self::HashFunctionMismatch { proof, verifier } => {
+                write!(f, "proof was generated with hash function {proof}, but the verifier uses {verifier}")
+            }
+            Self::CommitmentSchemeMismatch { proof, verifier } => {
+                write!(f, "proof was generated with commitment scheme {proof}, but the verifier uses commitment scheme {verifier}")
+            }
+            Self::RandomCoinMismatch { proof, verifier } => {
+                write!(f, "proof was generated with random coin {proof}, but the verifier uses random coin {verifier}")
+            }
+            Self::BatchSizeMismatch(num_proofs, num_inputs) => {
+                write!(f, "batch contains {num_proofs} proofs, but {num_inputs} public inputs were provided")
+            }
             Self::ProofDeserializationError(msg) => {
                 write!(f, "proof deserialization failed: {msg}")
             }
@@ -65,12 +215,27 @@ impl fmt::Display for VerifierError {
             Self::TraceQueryDoesNotMatchCommitment => {
                 write!(f, "trace query did not match the commitment")
             }
+            Self::CombinedTraceColumnMismatch => {
+                write!(f, "combined trace column is inconsistent with individual trace columns")
+            }
             Self::ConstraintQueryDoesNotMatchCommitment => {
                 write!(f, "constraint query did not match the commitment")
             }
+            Self::InvalidCompositionCoefficientCount(expected, actual) => {
+                write!(f, "expected {expected} composition polynomial coefficients, but received {actual}")
+            }
             Self::QuerySeedProofOfWorkVerificationFailed => {
                 write!(f, "query seed proof-of-work verification failed")
             }
+            Self::InsufficientExtraQueries(expected, actual) => {
+                write!(f, "expected at least {expected} extra queries to compensate for missing proof-of-work bits, but received {actual}")
+            }
+            Self::FriStructureMismatch(expected, actual) => {
+                write!(f, "expected low-degree proof with {expected} FRI layers, but the proof has {actual} layers")
+            }
+            Self::FriRemainderDigestMismatch => {
+                write!(f, "FRI remainder digest did not match the coefficients of the FRI remainder")
+            }
             Self::FriVerificationFailed(err) => {
                 write!(f, "verification of low-degree proof failed: {err}")
             }
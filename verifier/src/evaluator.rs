@@ -3,27 +3,70 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use air::{Air, AuxTraceRandElements, ConstraintCompositionCoefficients, EvaluationFrame};
-use math::{polynom, FieldElement};
-use utils::collections::Vec;
+use air::{
+    Air, AuxTraceRandElements, BoundaryConstraintGroup, BoundaryConstraints,
+    ConstraintCompositionCoefficients, EvaluationFrame, PowerTable, TransitionConstraints,
+};
+use math::{polynom, ExtensionOf, FieldElement};
+use utils::{
+    collections::{BTreeMap, Vec},
+    iter,
+};
+
+#[cfg(feature = "concurrent")]
+use utils::iterators::*;
+
+// CONSTRAINT CONSTRUCTION
+// ================================================================================================
+
+/// Builds transition and boundary constraints of the computation described by the AIR.
+///
+/// The constraints are built once per verification, and are then evaluated at every out-of-domain
+/// point. All powers of the trace domain generator needed to build the constraints (i.e., offsets
+/// of assertion divisors and exemption points of the transition divisor) are computed once into
+/// a single [PowerTable].
+pub fn build_constraints<A: Air, E: FieldElement<BaseField = A::BaseField>>(
+    air: &A,
+    composition_coefficients: &ConstraintCompositionCoefficients<E>,
+    aux_rand_elements: &AuxTraceRandElements<E>,
+) -> (TransitionConstraints<E>, BoundaryConstraints<E>) {
+    let main_assertions = air.get_assertions();
+    let aux_assertions = air.get_aux_assertions(aux_rand_elements);
+    let powers = PowerTable::for_constraints(air.context(), &main_assertions, &aux_assertions);
+
+    let t_constraints = TransitionConstraints::with_powers(
+        air.context(),
+        &composition_coefficients.transition,
+        &powers,
+    );
+    let b_constraints = BoundaryConstraints::with_powers(
+        air.context(),
+        main_assertions,
+        aux_assertions,
+        &composition_coefficients.boundary,
+        &powers,
+    );
+
+    (t_constraints, b_constraints)
+}
 
 // CONSTRAINT EVALUATION
 // ================================================================================================
 
 /// Evaluates constraints for the specified evaluation frame.
+#[allow(clippy::too_many_arguments)]
 pub fn evaluate_constraints<A: Air, E: FieldElement<BaseField = A::BaseField>>(
     air: &A,
-    composition_coefficients: ConstraintCompositionCoefficients<E>,
+    t_constraints: &TransitionConstraints<E>,
+    b_constraints: &BoundaryConstraints<E>,
+    composition_coefficients: &ConstraintCompositionCoefficients<E>,
     main_trace_frame: &EvaluationFrame<E>,
     aux_trace_frame: &Option<EvaluationFrame<E>>,
-    aux_rand_elements: AuxTraceRandElements<E>,
+    aux_rand_elements: &AuxTraceRandElements<E>,
     x: E,
 ) -> E {
     // 1 ----- evaluate transition constraints ----------------------------------------------------
 
-    // initialize a buffer to hold transition constraint evaluations
-    let t_constraints = air.get_transition_constraints(&composition_coefficients.transition);
-
     // compute values of periodic columns at x
     let periodic_values = air
         .get_periodic_column_polys()
@@ -46,7 +89,7 @@ pub fn evaluate_constraints<A: Air, E: FieldElement<BaseField = A::BaseField>>(
             main_trace_frame,
             aux_trace_frame,
             &periodic_values,
-            &aux_rand_elements,
+            aux_rand_elements,
             &mut t_evaluations2,
         );
     }
@@ -58,43 +101,81 @@ pub fn evaluate_constraints<A: Air, E: FieldElement<BaseField = A::BaseField>>(
 
     // 2 ----- evaluate boundary constraints ------------------------------------------------------
 
-    // get boundary constraints grouped by common divisor from the AIR
-    let b_constraints =
-        air.get_boundary_constraints(&aux_rand_elements, &composition_coefficients.boundary);
-
-    // cache power of x here so that we only re-compute it when degree_adjustment changes
-    let mut degree_adjustment = b_constraints.main_constraints()[0].degree_adjustment();
-    let mut xp = x.exp_vartime(degree_adjustment.into());
-
-    // iterate over boundary constraint groups for the main trace segment (each group has a
-    // distinct divisor), evaluate constraints in each group and add their combination to the
-    // result
-    for group in b_constraints.main_constraints().iter() {
-        // if adjustment degree hasn't changed, no need to recompute `xp` - so just reuse the
-        // previous value; otherwise, compute new `xp`
-        if group.degree_adjustment() != degree_adjustment {
-            degree_adjustment = group.degree_adjustment();
-            xp = x.exp_vartime(degree_adjustment.into());
-        }
-        // evaluate all constraints in the group, and add the evaluation to the result
-        result += group.evaluate_at(main_trace_frame.current(), x, xp);
+    // compute powers of x for all distinct degree adjustments here so that we compute each
+    // power only once
+    let mut xp_map = BTreeMap::new();
+    let main_adjustments = b_constraints
+        .main_constraints()
+        .iter()
+        .map(|g| g.degree_adjustment());
+    let aux_adjustments = b_constraints
+        .aux_constraints()
+        .iter()
+        .map(|g| g.degree_adjustment());
+    for degree_adjustment in main_adjustments.chain(aux_adjustments) {
+        xp_map
+            .entry(degree_adjustment)
+            .or_insert_with(|| x.exp_vartime(degree_adjustment.into()));
     }
 
-    // iterate over boundary constraint groups for auxiliary trace segments (each group has a
-    // distinct divisor), evaluate constraints in each group and add their combination to the
-    // result
+    // evaluate boundary constraint groups for the main trace segment (each group has a distinct
+    // divisor), and add their combination to the result
+    result += evaluate_boundary_groups(
+        b_constraints.main_constraints(),
+        main_trace_frame.current(),
+        x,
+        &xp_map,
+    );
+
+    // evaluate boundary constraint groups for auxiliary trace segments (each group has a
+    // distinct divisor), and add their combination to the result
     if let Some(aux_trace_frame) = aux_trace_frame {
-        for group in b_constraints.aux_constraints().iter() {
-            // if adjustment degree hasn't changed, no need to recompute `xp` - so just reuse the
-            // previous value; otherwise, compute new `xp`
-            if group.degree_adjustment() != degree_adjustment {
-                degree_adjustment = group.degree_adjustment();
-                xp = x.exp_vartime(degree_adjustment.into());
-            }
-            // evaluate all constraints in the group, and add the evaluation to the result
-            result += group.evaluate_at(aux_trace_frame.current(), x, xp);
-        }
+        result += evaluate_boundary_groups(
+            b_constraints.aux_constraints(),
+            aux_trace_frame.current(),
+            x,
+            &xp_map,
+        );
+    }
+
+    // 3 ----- evaluate boundary expressions ------------------------------------------------------
+
+    // boundary expressions are evaluated on the current row of the main trace frame; each
+    // expression is divided by its own divisor (x - g^s)
+    let e_constraints =
+        air.get_boundary_expr_constraints::<E, E>(&composition_coefficients.boundary_exprs);
+    for constraint in e_constraints.iter() {
+        let xp = x.exp_vartime(constraint.degree_adjustment().into());
+        let numerator = constraint.evaluate_at(main_trace_frame.current(), xp);
+        result += numerator / constraint.divisor().evaluate_at(x);
     }
 
     result
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Evaluates all constraints in the specified boundary constraint groups at `x` and returns the
+/// sum of the group evaluations; `xp_map` must contain `x^degree_adjustment` for the degree
+/// adjustment factors of all groups.
+///
+/// When `concurrent` feature is enabled, the groups are evaluated in multiple threads. The
+/// evaluations are added up in the order of the groups, and thus, the result is identical to the
+/// one computed sequentially.
+fn evaluate_boundary_groups<F, E>(
+    groups: &[BoundaryConstraintGroup<F, E>],
+    state: &[E],
+    x: E,
+    xp_map: &BTreeMap<u64, E>,
+) -> E
+where
+    F: FieldElement,
+    E: FieldElement<BaseField = F::BaseField> + ExtensionOf<F>,
+{
+    iter!(groups)
+        .map(|group| group.evaluate_at(state, x, xp_map[&group.degree_adjustment()]))
+        .collect::<Vec<E>>()
+        .into_iter()
+        .fold(E::ZERO, |result, evaluation| result + evaluation)
+}
@@ -15,6 +15,21 @@
 //! 2. Execute [verify()] function and supply the AIR of your computation together with the
 //!    [StarkProof] and related public inputs as parameters.
 //!
+//! If the facts authenticated by the proof (e.g., the length of the execution trace) are needed
+//! after the proof was verified, [verify_and_extract()] can be used instead of [verify()].
+//!
+//! Many proofs generated for the same computation with the same context (e.g., via
+//! `prove_batch()` of the prover) can be bundled into a [BatchStarkProof] and verified with
+//! [verify_batch()].
+//!
+//! If the verifier receives only a digest of public inputs together with the values needed to
+//! build assertions of the computation (e.g., when the inputs are hashed by another party),
+//! [verify_split_inputs()] can be used to verify proofs generated with the transcript seeded by
+//! the digest (see [SplitInputsAir]).
+//!
+//! If the hash function of a proof is known only at runtime, [verify_auto()] can be used to
+//! verify the proof with the hash function recorded in the proof context.
+//!
 //! # Performance
 //! Proof verification is extremely fast and is nearly independent of the complexity of the
 //! computation being verified. In vast majority of cases proofs can be verified in 3 - 5 ms
@@ -25,48 +40,78 @@
 //! asserted values. But for the impact to be noticeable, the number of asserted values would
 //! need to be in tens of thousands. And even for hundreds of thousands of asserted values, the
 //! verification time should not exceed 50 ms.
+//!
+//! # Heap allocations
+//! The verifier does not allocate memory per query when computing roots of batch Merkle proofs
+//! or when checking folding of FRI layers: scratch buffers are sized once by the number of
+//! queries and re-used across tree levels and queries. The remaining allocations come from
+//! parsing the proof (e.g., every path of a batch Merkle proof is stored in a separate vector)
+//! and from instantiating the AIR and its constraints; their number depends on the structure of
+//! the AIR and on the number of FRI layers, rather than on the size of the computation.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(all(feature = "safe_only", not(test)), forbid(unsafe_code))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
 extern crate alloc;
 
 pub use air::{
-    proof::StarkProof, Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint,
-    BoundaryConstraintGroup, ConstraintCompositionCoefficients, ConstraintDivisor,
-    DeepCompositionCoefficients, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo,
+    proof::{BatchStarkProof, StarkProof},
+    pub_inputs_seed, Air, AirContext, Assertion, AssertionError, AuxTraceRandElements,
+    BoundaryConstraint, BoundaryConstraintGroup, CePos, ConstraintCompositionCoefficients,
+    ConstraintDivisor, DeepCompositionCoefficients, EvaluationFrame, FieldExtension, LdePos,
+    LeafEncoding, PowerTable, ProofOptions, SplitInputsAir, TraceInfo, TraceRow, TranscriptProfile,
     TransitionConstraintDegree, TransitionConstraintGroup,
 };
 
 pub use math;
-use math::{
-    fields::{CubeExtension, QuadExtension},
-    FieldElement,
-};
+#[cfg(feature = "cubic")]
+use math::fields::CubeExtension;
+#[cfg(feature = "quadratic")]
+use math::fields::QuadExtension;
+use math::{polynom, FieldElement};
 
-use utils::collections::Vec;
+use utils::collections::{InlineVec, Vec};
 pub use utils::{
     ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
 };
 
 pub use crypto;
-use crypto::{ElementHasher, RandomCoin};
+use crypto::{
+    hashers::{Blake2s_256, Blake3_192, Blake3_256, Keccak_256, Sha3_256},
+    DefaultRandomCoin, DynHasher, ElementHasher, HashFunctionId, MerkleTree, RandomCoin,
+    VectorCommitment,
+};
 
+#[cfg(feature = "transcript-log")]
+use crypto::transcript_log::{self, TranscriptLog};
+
+use air::{proof::Context, transcript_labels as labels, PowPuzzle};
 use fri::FriVerifier;
 
+/// Random coin of all verification functions other than [verify_with_random_coin()].
+type DefaultCoin<AIR, HashFn> = DefaultRandomCoin<<AIR as Air>::BaseField, HashFn>;
+
 mod channel;
 use channel::VerifierChannel;
 
 mod evaluator;
-use evaluator::evaluate_constraints;
+use evaluator::{build_constraints, evaluate_constraints};
 
 mod composer;
+pub use composer::eval_deep_at_points;
 use composer::DeepComposer;
 
 mod errors;
 pub use errors::VerifierError;
 
+mod statement;
+pub use statement::VerifiedStatement;
+
+mod auto;
+pub use auto::AutoHashField;
+
 // VERIFIER
 // ================================================================================================
 /// Verifies that the specified computation was executed correctly against the specified inputs.
@@ -80,61 +125,578 @@ pub use errors::VerifierError;
 /// a correct execution of the computation. This could happen for many various reasons, including:
 /// - The specified proof was generated for a different computation.
 /// - The specified proof was generated for this computation but for different public inputs.
-#[rustfmt::skip]
+/// - The specified proof was generated using a field extension of a smaller degree than the one
+///   required by [Air::required_extension_degree()] for the proof's options.
+/// - The specified proof was generated for a number of transition exemptions different from the
+///   one defined by the AIR.
+/// - The LDE domain of the computation described by the proof is too large to be indexed by
+///   `usize` on the current platform.
 pub fn verify<AIR: Air, HashFn: ElementHasher<BaseField = AIR::BaseField>>(
     proof: StarkProof,
     pub_inputs: AIR::PublicInputs,
 ) -> Result<(), VerifierError> {
-    // build a seed for the public coin; the initial seed is the hash of public inputs and proof
-    // context, but as the protocol progresses, the coin will be reseeded with the info received
-    // from the prover
-    let mut public_coin_seed = Vec::new();
-    pub_inputs.write_into(&mut public_coin_seed);
-    proof.context.write_into(&mut public_coin_seed);
+    verify_proof::<AIR, HashFn>(proof, pub_inputs, None, true)
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs,
+/// and returns the facts authenticated by the proof.
+///
+/// This is identical to [verify()] except that, if the verification is successful, the returned
+/// [VerifiedStatement] contains the public inputs against which the proof was verified together
+/// with the trace info, the proof options, and the conjectured and provable security levels of
+/// the proof. Trace info and proof options are read from the proof itself, and thus, they may
+/// differ from the values the caller expected them to be; they are, however, guaranteed to be the
+/// values bound by the proof.
+///
+/// # Errors
+/// Returns the same errors as [verify()].
+pub fn verify_and_extract<AIR, HashFn>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<VerifiedStatement<AIR>, VerifierError>
+where
+    AIR: Air,
+    AIR::PublicInputs: Clone,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    // trace info and security level can be computed only for proofs which fit the platform
+    check_platform(&proof.context)?;
+    let trace_info = proof.get_trace_info();
+    let options = proof.options().clone();
+    let conjectured_security_level = proof.security_level::<HashFn>(true);
+    let proven_security_level = proof.security_level::<HashFn>(false);
+
+    verify_proof::<AIR, HashFn>(proof, pub_inputs.clone(), None, true)?;
+    Ok(VerifiedStatement::new(
+        pub_inputs,
+        trace_info,
+        options,
+        conjectured_security_level,
+        proven_security_level,
+    ))
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs
+/// without checking that the proof uses a large enough field extension.
+///
+/// This is identical to [verify()] except that proofs generated with a field extension of a
+/// smaller degree than the one required by [Air::required_extension_degree()] are not rejected.
+/// Such proofs may provide much lower security than implied by their options, and thus, this
+/// function is intended only for research and testing purposes.
+pub fn verify_without_extension_check<AIR, HashFn>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    verify_proof::<AIR, HashFn>(proof, pub_inputs, None, false)
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs
+/// without accepting proofs with salted commitments.
+///
+/// This is identical to [verify()] except that proofs generated with salted commitments (see
+/// [ProofOptions::with_zk()]) are rejected before any of their queries are parsed. This mirrors
+/// verifiers which do not implement salted commitments (e.g., verifiers ported to other
+/// environments), and can be used to make sure that accepted proofs are also accepted by them.
+///
+/// # Errors
+/// Returns the same errors as [verify()], and also returns
+/// [VerifierError::UnsupportedSaltedCommitments] if the proof was generated with salted
+/// commitments.
+pub fn verify_unsalted<AIR, HashFn>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    if proof.options().is_zk() {
+        return Err(VerifierError::UnsupportedSaltedCommitments);
+    }
+    verify_proof::<AIR, HashFn>(proof, pub_inputs, None, true)
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs
+/// and the specified preprocessed trace segment.
+///
+/// This is identical to [verify()] except that the computation is expected to have a
+/// preprocessed trace segment (see [TraceInfo::with_preprocessed_segment()]), and
+/// `preprocessed_commitment` must be the commitment to this segment. The commitment is not
+/// included in the proof; thus, it is expected to be obtained by the verifier out of band (e.g.,
+/// computed once when the preprocessed columns are fixed), and the same commitment can be used
+/// to verify many proofs.
+///
+/// # Errors
+/// Returns the same errors as [verify()], and also returns an error if the proof does not declare
+/// a preprocessed trace segment.
+pub fn verify_with_preprocessed_segment<AIR, HashFn>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+    preprocessed_commitment: HashFn::Digest,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    verify_proof::<AIR, HashFn>(proof, pub_inputs, Some(preprocessed_commitment), true)
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs
+/// with trace and constraint commitments of the vector commitment scheme `V`.
+///
+/// This is identical to [verify()] except that trace and constraint queries are checked against
+/// commitments of the scheme `V` rather than against Merkle tree roots. The proof must have been
+/// generated with the same scheme (e.g., via `prove_with_commitment()` of the prover); for
+/// [MerkleTree], this function is identical to [verify()].
+///
+/// # Errors
+/// Returns the same errors as [verify()], and also returns
+/// [VerifierError::CommitmentSchemeMismatch] if the proof was generated with a different
+/// commitment scheme.
+pub fn verify_with_commitment<AIR, HashFn, V>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+    V: VectorCommitment<HashFn>,
+{
+    let mut pub_inputs_bytes = Vec::new();
+    pub_inputs.write_into(&mut pub_inputs_bytes);
+    verify_seeded_proof::<AIR, HashFn, V, DefaultCoin<AIR, HashFn>, _>(
+        proof,
+        &pub_inputs_bytes,
+        |trace_info, options| AIR::new(trace_info, pub_inputs, options),
+        None,
+        true,
+    )
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs
+/// with all randomness of the protocol drawn from the random coin `C`.
+///
+/// This is identical to [verify()] except that the public coin of the verifier is an instance of
+/// `C` rather than of [DefaultRandomCoin]. The proof must have been generated with the same coin
+/// (e.g., via `prove_with_random_coin()` of the prover); for [DefaultRandomCoin], this function
+/// is identical to [verify()].
+///
+/// # Errors
+/// Returns the same errors as [verify()], and also returns [VerifierError::RandomCoinMismatch]
+/// if the proof was generated with a different random coin.
+pub fn verify_with_random_coin<AIR, HashFn, C>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+    C: RandomCoin<BaseField = AIR::BaseField, Hasher = HashFn>,
+{
+    let mut pub_inputs_bytes = Vec::new();
+    pub_inputs.write_into(&mut pub_inputs_bytes);
+    verify_seeded_proof::<AIR, HashFn, MerkleTree<HashFn>, C, _>(
+        proof,
+        &pub_inputs_bytes,
+        |trace_info, options| AIR::new(trace_info, pub_inputs, options),
+        None,
+        true,
+    )
+}
+
+/// Verifies the proof in the same way as [verify()], and returns the result of the verification
+/// together with the log of all public coin interactions performed while verifying the proof.
+///
+/// The log is returned even if the verification fails, and thus, it can be compared to the log
+/// recorded by the prover (see [TranscriptLog::first_divergence()]) to find the step at which
+/// the transcript replayed by the verifier deviated from the one simulated by the prover.
+#[cfg(feature = "transcript-log")]
+pub fn verify_with_transcript_log<AIR, HashFn>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> (Result<(), VerifierError>, TranscriptLog)
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    transcript_log::record(|| verify_proof::<AIR, HashFn>(proof, pub_inputs, None, true))
+}
+
+/// Verifies that every proof in the specified batch attests to a correct execution of the
+/// computation against the corresponding public inputs.
+///
+/// Returns the result of verifying each proof in the same order as the proofs in the `batch`;
+/// the proof at index `i` is verified against `pub_inputs[i]`, and a failure to verify one of the
+/// proofs does not affect verification of other proofs.
+///
+/// The context shared by all proofs in the batch is validated only once (e.g., the proof options
+/// are checked against the hash function and the field extension required by the AIR). All
+/// other steps are performed separately for each proof; in particular, Merkle authentication
+/// paths are not batched across proofs, as each proof commits to its own Merkle trees.
+///
+/// # Errors
+/// For each proof, returns the same errors as [verify()]. If the shared context of the batch is
+/// invalid, the error is returned for every proof in the batch. If the number of public inputs
+/// is different from the number of proofs in the batch, [VerifierError::BatchSizeMismatch] is
+/// returned for every proof in the batch.
+pub fn verify_batch<AIR, HashFn>(
+    batch: BatchStarkProof,
+    pub_inputs: Vec<AIR::PublicInputs>,
+) -> Vec<Result<(), VerifierError>>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    let num_proofs = batch.num_proofs();
+    if pub_inputs.len() != num_proofs {
+        let err = VerifierError::BatchSizeMismatch(num_proofs, pub_inputs.len());
+        return vec![Err(err); num_proofs];
+    }
+
+    if let Err(err) = check_context::<AIR, HashFn, MerkleTree<HashFn>, DefaultCoin<AIR, HashFn>>(
+        batch.context(),
+        false,
+        true,
+    ) {
+        return vec![Err(err); num_proofs];
+    }
+
+    batch
+        .into_proofs()
+        .into_iter()
+        .zip(pub_inputs)
+        .map(|(proof, pub_inputs)| {
+            let mut pub_inputs_bytes = Vec::new();
+            pub_inputs.write_into(&mut pub_inputs_bytes);
+            verify_checked_proof::<AIR, HashFn, MerkleTree<HashFn>, DefaultCoin<AIR, HashFn>, _>(
+                proof,
+                &pub_inputs_bytes,
+                |trace_info, options| AIR::new(trace_info, pub_inputs, options),
+                None,
+            )
+        })
+        .collect()
+}
+
+/// Verifies that the specified computation was executed correctly against public inputs which
+/// are split into the seed of the public coin and the values defining assertions of the
+/// computation.
+///
+/// This is identical to [verify()] except that the public coin is seeded with `seed` in place
+/// of serialized public inputs, and the AIR is instantiated from `assertion_inputs` via
+/// [SplitInputsAir::from_assertion_inputs()]. Thus, proofs can be verified by parties which
+/// cannot serialize public inputs, but receive their digest from elsewhere. Such proofs must be
+/// generated by a prover which seeds the transcript with the digest of serialized public inputs
+/// (see [pub_inputs_seed()]).
+///
+/// # Soundness
+/// The seed must bind the assertion inputs: the caller is responsible for making sure that
+/// `seed` is the digest of public inputs which contain `assertion_inputs`, as this cannot be
+/// checked by the verifier. If the seed does not commit to the assertion values, a malicious
+/// prover may choose the assertions after learning the randomness drawn from the public coin.
+///
+/// # Errors
+/// Returns the same errors as [verify()]. In particular, if the seed was computed from public
+/// inputs other than the ones for which the proof was generated, the public coin of the verifier
+/// diverges from the one of the prover, and the proof is rejected.
+pub fn verify_split_inputs<AIR, HashFn>(
+    proof: StarkProof,
+    seed: HashFn::Digest,
+    assertion_inputs: AIR::AssertionInputs,
+) -> Result<(), VerifierError>
+where
+    AIR: SplitInputsAir,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+{
+    verify_seeded_proof::<AIR, HashFn, MerkleTree<HashFn>, DefaultCoin<AIR, HashFn>, _>(
+        proof,
+        &seed.to_bytes(),
+        |trace_info, options| AIR::from_assertion_inputs(trace_info, assertion_inputs, options),
+        None,
+        true,
+    )
+}
+
+/// Verifies that the specified computation was executed correctly against the specified inputs
+/// using the hash function recorded in the proof context.
+///
+/// This is identical to [verify()] except that the hash function is not a type parameter, but is
+/// selected at runtime based on the [HashFunctionId] recorded in the context of the `proof` by
+/// the prover. Hash functions which can be used with any field are available for every AIR, while
+/// algebraic hash functions are available only for AIRs over their own base field (see
+/// [AutoHashField]). Once the hash function is selected, the proof is verified via [verify()]
+/// instantiated with this hash function, and thus, the overhead of runtime selection is limited
+/// to a single match.
+///
+/// # Errors
+/// In addition to the errors returned by [verify()], returns an error if:
+/// - The proof context does not specify the hash function with which the proof was generated.
+/// - The hash function specified by the proof context cannot be used with the base field of the
+///   AIR, or was not enabled via features of this crate.
+pub fn verify_auto<AIR>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    AIR::BaseField: AutoHashField,
+{
+    let hash_fn = proof
+        .context
+        .hash_fn()
+        .ok_or(VerifierError::UnknownHashFunction)?;
+    match hash_fn {
+        HashFunctionId::Blake3_192 => verify::<AIR, Blake3_192<AIR::BaseField>>(proof, pub_inputs),
+        HashFunctionId::Blake3_256 => verify::<AIR, Blake3_256<AIR::BaseField>>(proof, pub_inputs),
+        HashFunctionId::Sha3_256 => verify::<AIR, Sha3_256<AIR::BaseField>>(proof, pub_inputs),
+        HashFunctionId::Keccak256 => verify::<AIR, Keccak_256<AIR::BaseField>>(proof, pub_inputs),
+        HashFunctionId::Blake2s256 => verify::<AIR, Blake2s_256<AIR::BaseField>>(proof, pub_inputs),
+        _ => AIR::BaseField::verify_algebraic::<AIR>(hash_fn, proof, pub_inputs),
+    }
+}
+
+/// Verifies the proof, optionally checking that the proof uses a field extension of the degree
+/// required by the AIR.
+///
+/// `preprocessed_commitment` must be provided if and only if the proof declares a preprocessed
+/// trace segment.
+fn verify_proof<AIR: Air, HashFn: ElementHasher<BaseField = AIR::BaseField>>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+    preprocessed_commitment: Option<HashFn::Digest>,
+    check_extension_degree: bool,
+) -> Result<(), VerifierError> {
+    let mut pub_inputs_bytes = Vec::new();
+    pub_inputs.write_into(&mut pub_inputs_bytes);
+    verify_seeded_proof::<AIR, HashFn, MerkleTree<HashFn>, DefaultCoin<AIR, HashFn>, _>(
+        proof,
+        &pub_inputs_bytes,
+        |trace_info, options| AIR::new(trace_info, pub_inputs, options),
+        preprocessed_commitment,
+        check_extension_degree,
+    )
+}
+
+/// Verifies the proof against a public coin seeded with `seed_bytes` (in place of serialized
+/// public inputs) and the AIR returned from `build_air`; trace and constraint queries are checked
+/// against commitments of the vector commitment scheme `V`, and randomness is drawn from the
+/// random coin `C`.
+fn verify_seeded_proof<AIR, HashFn, V, C, F>(
+    proof: StarkProof,
+    seed_bytes: &[u8],
+    build_air: F,
+    preprocessed_commitment: Option<HashFn::Digest>,
+    check_extension_degree: bool,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+    V: VectorCommitment<HashFn>,
+    C: RandomCoin<BaseField = AIR::BaseField, Hasher = HashFn>,
+    F: FnOnce(TraceInfo, ProofOptions) -> AIR,
+{
+    check_context::<AIR, HashFn, V, C>(
+        &proof.context,
+        preprocessed_commitment.is_some(),
+        check_extension_degree,
+    )?;
+    verify_checked_proof::<AIR, HashFn, V, C, F>(
+        proof,
+        seed_bytes,
+        build_air,
+        preprocessed_commitment,
+    )
+}
+
+/// Makes sure that proofs with the specified context can be verified with the `AIR`, `HashFn`,
+/// the commitment scheme `V`, and the random coin `C` on this platform, given whether the
+/// commitment to the preprocessed trace segment was provided to the verifier.
+fn check_context<AIR, HashFn, V, C>(
+    context: &Context,
+    has_preprocessed_commitment: bool,
+    check_extension_degree: bool,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+    V: VectorCommitment<HashFn>,
+    C: RandomCoin<BaseField = AIR::BaseField, Hasher = HashFn>,
+{
+    // make sure the computation described by the proof can be processed on this platform; this
+    // must happen before the trace length or the LDE domain size are used as usize values
+    check_platform(context)?;
+
+    // make sure the commitment to the preprocessed trace segment was provided if and only if the
+    // proof declares such a segment
+    if (context.preprocessed_width() > 0) != has_preprocessed_commitment {
+        return Err(VerifierError::PreprocessedSegmentMismatch);
+    }
+
+    // make sure the field extension used by the proof is large enough to meet the security
+    // target implied by the proof options
+    let options = context.options();
+    if check_extension_degree {
+        let required = AIR::required_extension_degree(options, &context.get_trace_info());
+        let actual = options.field_extension().degree();
+        if actual < required {
+            return Err(VerifierError::InsufficientExtensionDegree(required, actual));
+        }
+    }
+
+    // make sure the transcript profile specified by the proof can be used with the hash function
+    if !options.transcript_profile().is_supported::<HashFn>() {
+        return Err(VerifierError::UnsupportedTranscriptProfile);
+    }
+
+    // make sure the grinding hash function specified by the proof can be used with the base field
+    if let Some(hash_fn) = options.grinding_hash() {
+        if DynHasher::<AIR::BaseField>::new(hash_fn).is_none() {
+            return Err(VerifierError::UnsupportedGrindingHash(hash_fn));
+        }
+    }
+
+    // make sure the proof was generated with the hash function of the verifier, if both hash
+    // functions are known
+    if let (Some(proof), Some(verifier)) = (context.hash_fn(), HashFn::ID) {
+        if proof != verifier {
+            return Err(VerifierError::HashFunctionMismatch { proof, verifier });
+        }
+    }
+
+    // make sure the proof was generated with the commitment scheme of the verifier
+    if context.commitment_scheme() != V::SCHEME_ID {
+        return Err(VerifierError::CommitmentSchemeMismatch {
+            proof: context.commitment_scheme(),
+            verifier: V::SCHEME_ID,
+        });
+    }
+
+    // make sure the proof was generated with the random coin of the verifier
+    if context.random_coin() != C::COIN_ID {
+        return Err(VerifierError::RandomCoinMismatch {
+            proof: context.random_coin(),
+            verifier: C::COIN_ID,
+        });
+    }
+
+    Ok(())
+}
+
+/// Verifies a proof with a context which was already checked via [check_context()] against a
+/// public coin seeded with `seed_bytes` and the AIR returned from `build_air`.
+#[rustfmt::skip]
+fn verify_checked_proof<AIR, HashFn, V, C, F>(
+    proof: StarkProof,
+    seed_bytes: &[u8],
+    build_air: F,
+    preprocessed_commitment: Option<HashFn::Digest>,
+) -> Result<(), VerifierError>
+where
+    AIR: Air,
+    HashFn: ElementHasher<BaseField = AIR::BaseField>,
+    V: VectorCommitment<HashFn>,
+    C: RandomCoin<BaseField = AIR::BaseField, Hasher = HashFn>,
+    F: FnOnce(TraceInfo, ProofOptions) -> AIR,
+{
+    // build the public coin; the initial seed is the hash of public inputs and proof context
+    // (encoded as defined by the transcript profile), but as the protocol progresses, the coin
+    // will be reseeded with the info received from the prover
+    let profile = proof.options().transcript_profile();
+    let public_coin = profile.build_public_coin::<C>(&proof.context, seed_bytes);
 
     // create AIR instance for the computation specified in the proof
-    let air = AIR::new(proof.get_trace_info(), pub_inputs, proof.options().clone());
+    let air = build_air(proof.get_trace_info(), proof.options().clone());
+
+    // make sure the assertions of the AIR can be placed against the execution trace described
+    // by the proof; assertions may depend on public inputs, and thus, need to be validated before
+    // boundary constraints are built from them
+    air.validate_assertions().map_err(VerifierError::InvalidAssertion)?;
+
+    // make sure the proof was generated for the same transition exemptions as defined by the
+    // AIR; exemptions determine the divisors of transition constraints, and thus, accepting a
+    // proof with different exemptions for any of the constraints could leave some transitions
+    // unconstrained. the proof context records no exemptions when every constraint has a single
+    // exemption.
+    let air_exemptions = air.context().transition_exemptions();
+    let proof_exemptions = match proof.context.transition_exemptions() {
+        [] => vec![1; air_exemptions.len()],
+        exemptions => exemptions.to_vec(),
+    };
+    if proof_exemptions != air_exemptions {
+        return Err(VerifierError::ExemptionMismatch {
+            proof: proof_exemptions,
+            air: air_exemptions.to_vec(),
+        });
+    }
 
     // figure out which version of the generic proof verification procedure to run. this is a sort
     // of static dispatch for selecting two generic parameter: extension field and hash function.
     match air.options().field_extension() {
         FieldExtension::None => {
-            let public_coin = RandomCoin::new(&public_coin_seed);
-            let channel = VerifierChannel::new(&air, proof)?;
-            perform_verification::<AIR, AIR::BaseField, HashFn>(air, channel, public_coin)
+            let channel = VerifierChannel::new(&air, proof, preprocessed_commitment)?;
+            perform_verification::<AIR, AIR::BaseField, HashFn, V, C>(air, channel, public_coin)
         },
+        #[cfg(feature = "quadratic")]
         FieldExtension::Quadratic => {
             if !<QuadExtension<AIR::BaseField>>::is_supported() {
                 return Err(VerifierError::UnsupportedFieldExtension(2));
             }
-            let public_coin = RandomCoin::new(&public_coin_seed);
-            let channel = VerifierChannel::new(&air, proof)?;
-            perform_verification::<AIR, QuadExtension<AIR::BaseField>, HashFn>(air, channel, public_coin)
+            let channel = VerifierChannel::new(&air, proof, preprocessed_commitment)?;
+            perform_verification::<AIR, QuadExtension<AIR::BaseField>, HashFn, V, C>(air, channel, public_coin)
         },
+        #[cfg(not(feature = "quadratic"))]
+        FieldExtension::Quadratic => Err(VerifierError::UnsupportedFieldExtension(2)),
+        #[cfg(feature = "cubic")]
         FieldExtension::Cubic => {
             if !<CubeExtension<AIR::BaseField>>::is_supported() {
                 return Err(VerifierError::UnsupportedFieldExtension(3));
             }
-            let public_coin = RandomCoin::new(&public_coin_seed);
-            let channel = VerifierChannel::new(&air, proof)?;
-            perform_verification::<AIR, CubeExtension<AIR::BaseField>, HashFn>(air, channel, public_coin)
+            let channel = VerifierChannel::new(&air, proof, preprocessed_commitment)?;
+            perform_verification::<AIR, CubeExtension<AIR::BaseField>, HashFn, V, C>(air, channel, public_coin)
         },
+        #[cfg(not(feature = "cubic"))]
+        FieldExtension::Cubic => Err(VerifierError::UnsupportedFieldExtension(3)),
+    }
+}
+
+/// Makes sure the LDE domain of the computation described by the proof can be indexed by `usize`
+/// on the current platform.
+fn check_platform(context: &Context) -> Result<(), VerifierError> {
+    if !context.fits_platform() {
+        let lde_domain_size_log2 = context.lde_domain_size_log2();
+        return Err(VerifierError::DomainTooLargeForPlatform(
+            lde_domain_size_log2,
+        ));
     }
+    Ok(())
 }
 
 // VERIFICATION PROCEDURE
 // ================================================================================================
+/// Number of elements in a row of an out-of-domain trace frame (with main and auxiliary trace
+/// columns combined) which the verifier keeps inline while absorbing the row into the public coin.
+const MAX_INLINE_OOD_ROW_WIDTH: usize = 256;
+
+/// A row of an out-of-domain trace frame assembled by the verifier.
+type OodRow<E> = InlineVec<E, MAX_INLINE_OOD_ROW_WIDTH>;
+
 /// Performs the actual verification by reading the data from the `channel` and making sure it
 /// attests to a correct execution of the computation specified by the provided `air`.
-fn perform_verification<A, E, H>(
+fn perform_verification<A, E, H, V, C>(
     air: A,
-    mut channel: VerifierChannel<E, H>,
-    mut public_coin: RandomCoin<A::BaseField, H>,
+    mut channel: VerifierChannel<E, H, V>,
+    mut public_coin: C,
 ) -> Result<(), VerifierError>
 where
     A: Air,
     E: FieldElement<BaseField = A::BaseField>,
     H: ElementHasher<BaseField = A::BaseField>,
+    V: VectorCommitment<H>,
+    C: RandomCoin<BaseField = A::BaseField, Hasher = H>,
 {
     // 1 ----- trace commitment -------------------------------------------------------------------
     // Read the commitments to evaluations of the trace polynomials over the LDE domain sent by the
@@ -147,86 +709,171 @@ where
     // constraint composition polynomial.
     let trace_commitments = channel.read_trace_commitments();
 
+    // when the trace has a preprocessed segment, reseed the coin with the commitment to this
+    // segment first; this commitment was not sent by the prover, but it still needs to be bound
+    // to the transcript before any randomness is drawn
+    if let Some(commitment) = channel.read_preprocessed_commitment() {
+        public_coin
+            .label(labels::PREPROCESSED_COMMITMENT)
+            .reseed(commitment);
+    }
+
     // reseed the coin with the commitment to the main trace segment
-    public_coin.reseed(trace_commitments[0]);
+    public_coin
+        .label(labels::TRACE_COMMITMENT)
+        .reseed(trace_commitments[0]);
 
     // process auxiliary trace segments (if any), to build a set of random elements for each segment
     let mut aux_trace_rand_elements = AuxTraceRandElements::<E>::new();
     for (i, commitment) in trace_commitments.iter().skip(1).enumerate() {
         let rand_elements = air
-            .get_aux_trace_segment_random_elements(i, &mut public_coin)
+            .get_aux_trace_segment_random_elements(i, public_coin.label(labels::AUX_RAND_ELEMENTS))
             .map_err(|_| VerifierError::RandomCoinError)?;
         aux_trace_rand_elements.add_segment_elements(rand_elements);
-        public_coin.reseed(*commitment);
+        public_coin
+            .label(labels::TRACE_COMMITMENT)
+            .reseed(*commitment);
     }
-
-    // build random coefficients for the composition polynomial
+    air.validate_aux_assertions(&aux_trace_rand_elements)
+        .map_err(VerifierError::InvalidAssertion)?;
+
+    // when the proof commits to the combined trace column, draw the coefficient gamma which
+    // the prover used to combine trace columns, and reseed the coin with the commitment to the
+    // combined column
+    let combined_trace_coefficient = match channel.read_combined_trace_commitment() {
+        Some(commitment) => {
+            let gamma = public_coin
+                .label(labels::COMBINED_TRACE_COEFFICIENT)
+                .draw::<E>()
+                .map_err(|_| VerifierError::RandomCoinError)?;
+            public_coin
+                .label(labels::COMBINED_TRACE_COMMITMENT)
+                .reseed(commitment);
+            Some(gamma)
+        }
+        None => None,
+    };
+
+    // build random coefficients for the composition polynomials; one set of coefficients is
+    // drawn for every out-of-domain point
     let constraint_coeffs = air
-        .get_constraint_composition_coefficients(&mut public_coin)
+        .get_constraint_composition_coefficients(public_coin.label(labels::CONSTRAINT_COEFFICIENTS))
         .map_err(|_| VerifierError::RandomCoinError)?;
 
+    // build transition and boundary constraints for every set of coefficients; each of these is
+    // evaluated at the out-of-domain point drawn for it
+    let constraints = constraint_coeffs
+        .iter()
+        .map(|coefficients| build_constraints(&air, coefficients, &aux_trace_rand_elements))
+        .collect::<Vec<_>>();
+
     // 2 ----- constraint commitment --------------------------------------------------------------
     // read the commitment to evaluations of the constraint composition polynomial over the LDE
-    // domain sent by the prover, use it to update the public coin, and draw an out-of-domain point
-    // z from the coin; in the interactive version of the protocol, the verifier sends this point z
-    // to the prover, and the prover evaluates trace and constraint composition polynomials at z,
-    // and sends the results back to the verifier.
+    // domain sent by the prover, and use it to update the public coin.
     let constraint_commitment = channel.read_constraint_commitment();
-    public_coin.reseed(constraint_commitment);
-    let z = public_coin
-        .draw::<E>()
-        .map_err(|_| VerifierError::RandomCoinError)?;
+    public_coin
+        .label(labels::CONSTRAINT_COMMITMENT)
+        .reseed(constraint_commitment);
 
     // 3 ----- OOD consistency check --------------------------------------------------------------
-    // make sure that evaluations obtained by evaluating constraints over the out-of-domain frame
-    // are consistent with the evaluations of composition polynomial columns sent by the prover
-
-    // read the out-of-domain trace frames (the main trace frame and auxiliary trace frame, if
-    // provided) sent by the prover and evaluate constraints over them; also, reseed the public
-    // coin with the OOD frames received from the prover.
-    let (ood_main_trace_frame, ood_aux_trace_frame) = channel.read_ood_trace_frame();
-    let ood_constraint_evaluation_1 = evaluate_constraints(
-        &air,
-        constraint_coeffs,
-        &ood_main_trace_frame,
-        &ood_aux_trace_frame,
-        aux_trace_rand_elements,
-        z,
-    );
-
-    if let Some(ref aux_trace_frame) = ood_aux_trace_frame {
-        // when the trace contains auxiliary segments, append auxiliary trace elements at the
-        // end of main trace elements for both current and next rows in the frame. this is
-        // needed to be consistent with how the prover writes OOD frame into the channel.
-
-        let mut current = ood_main_trace_frame.current().to_vec();
-        current.extend_from_slice(aux_trace_frame.current());
-        public_coin.reseed(H::hash_elements(&current));
-
-        let mut next = ood_main_trace_frame.next().to_vec();
-        next.extend_from_slice(aux_trace_frame.next());
-        public_coin.reseed(H::hash_elements(&next));
-    } else {
-        public_coin.reseed(H::hash_elements(ood_main_trace_frame.current()));
-        public_coin.reseed(H::hash_elements(ood_main_trace_frame.next()));
-    }
-
-    // read evaluations of composition polynomial columns sent by the prover, and reduce them into
-    // a single value by computing sum(z^i * value_i), where value_i is the evaluation of the ith
-    // column polynomial at z^m, where m is the total number of column polynomials; also, reseed
-    // the public coin with the OOD constraint evaluations received from the prover.
+    // for each of the out-of-domain points, draw the point z from the public coin and make sure
+    // that evaluations obtained by evaluating constraints (composed with coefficients drawn for
+    // this point) over the out-of-domain frame at z are consistent with the evaluations of the
+    // point's composition polynomial columns sent by the prover; in
+    // the interactive version of the protocol, the verifier sends each point z to the prover, and
+    // the prover evaluates trace and constraint composition polynomials at z, and sends the
+    // results back to the verifier.
+    let ood_trace_frames = channel.read_ood_trace_frames();
     let ood_constraint_evaluations = channel.read_ood_constraint_evaluations();
-    let ood_constraint_evaluation_2 = ood_constraint_evaluations
-        .iter()
-        .enumerate()
-        .fold(E::ZERO, |result, (i, &value)| {
-            result + z.exp_vartime((i as u32).into()) * value
-        });
-    public_coin.reseed(H::hash_elements(&ood_constraint_evaluations));
-
-    // finally, make sure the values are the same
-    if ood_constraint_evaluation_1 != ood_constraint_evaluation_2 {
-        return Err(VerifierError::InconsistentOodConstraintEvaluations);
+    let ood_combined_trace_evaluations = channel.read_ood_combined_trace_evaluations();
+    let mut ood_points = InlineVec::<E, { ProofOptions::MAX_OOD_POINTS }>::new();
+    for (i, ((ood_main_trace_frame, ood_aux_trace_frame), ood_constraint_evaluations)) in
+        ood_trace_frames
+            .iter()
+            .zip(ood_constraint_evaluations.iter())
+            .enumerate()
+    {
+        let z = public_coin
+            .label(labels::OOD_POINT)
+            .draw::<E>()
+            .map_err(|_| VerifierError::RandomCoinError)?;
+        ood_points.push(z);
+
+        // evaluate constraints over the out-of-domain trace frames (the main trace frame and
+        // auxiliary trace frame, if provided) sent by the prover; also, reseed the public coin
+        // with the OOD frames received from the prover.
+        let (t_constraints, b_constraints) = &constraints[i];
+        let ood_constraint_evaluation_1 = evaluate_constraints(
+            &air,
+            t_constraints,
+            b_constraints,
+            &constraint_coeffs[i],
+            ood_main_trace_frame,
+            ood_aux_trace_frame,
+            &aux_trace_rand_elements,
+            z,
+        );
+
+        public_coin.label(labels::OOD_TRACE_FRAME);
+        for row_idx in 0..ood_main_trace_frame.num_rows() {
+            if let Some(ref aux_trace_frame) = ood_aux_trace_frame {
+                // when the trace contains auxiliary segments, append auxiliary trace elements at
+                // the end of main trace elements for every row in the frame. this is needed to be
+                // consistent with how the prover writes OOD frame into the channel.
+                let mut row = OodRow::<E>::from_slice(ood_main_trace_frame.row(row_idx));
+                row.extend_from_slice(aux_trace_frame.row(row_idx));
+                public_coin.reseed(H::hash_elements(&row));
+            } else {
+                public_coin.reseed(H::hash_elements(ood_main_trace_frame.row(row_idx)));
+            }
+        }
+
+        // make sure the evaluation of the combined trace column at z sent by the prover is the
+        // same linear combination of trace polynomial evaluations at z, and reseed the public
+        // coin with this evaluation
+        if let Some(gamma) = combined_trace_coefficient {
+            let evaluation = ood_combined_trace_evaluations[i];
+            let mut current = OodRow::<E>::from_slice(ood_main_trace_frame.current());
+            if let Some(ref aux_trace_frame) = ood_aux_trace_frame {
+                current.extend_from_slice(aux_trace_frame.current());
+            }
+            if polynom::eval(&current, gamma) != evaluation {
+                return Err(VerifierError::CombinedTraceColumnMismatch);
+            }
+            public_coin
+                .label(labels::OOD_COMBINED_TRACE_EVALUATION)
+                .reseed(H::hash_elements(&[evaluation]));
+        }
+
+        // reduce evaluations of composition polynomial columns sent by the prover into a single
+        // value by computing sum(z^i * value_i), where value_i is the evaluation of the ith
+        // column polynomial at z^m, where m is the number of column polynomials per point; also,
+        // reseed the public coin with the OOD constraint evaluations received from the prover.
+        let ood_constraint_evaluation_2 = ood_constraint_evaluations
+            .iter()
+            .enumerate()
+            .fold(E::ZERO, |result, (i, &value)| {
+                result + z.exp_vartime((i as u32).into()) * value
+            });
+        public_coin
+            .label(labels::OOD_CONSTRAINT_EVALUATIONS)
+            .reseed(H::hash_elements(ood_constraint_evaluations));
+
+        // finally, make sure the values are the same
+        if ood_constraint_evaluation_1 != ood_constraint_evaluation_2 {
+            return Err(VerifierError::InconsistentOodConstraintEvaluations);
+        }
+
+        // when the composition polynomial was sent explicitly, evaluate its columns at z^m
+        // directly and make sure the evaluations sent by the prover are the same; since the
+        // coefficients were absorbed into the public coin before z was drawn, this ties the check
+        // above to the same polynomial which is later evaluated at the query positions
+        let z_m = z.exp_vartime((ood_constraint_evaluations.len() as u32).into());
+        if let Some(evaluations) = channel.evaluate_explicit_composition_at(i, z_m) {
+            if &evaluations != ood_constraint_evaluations {
+                return Err(VerifierError::InconsistentOodConstraintEvaluations);
+            }
+        }
     }
 
     // 4 ----- FRI commitments --------------------------------------------------------------------
@@ -235,7 +882,7 @@ where
     // and the prover uses them to compute the DEEP composition polynomial. the prover, then
     // applies FRI protocol to the evaluations of the DEEP composition polynomial.
     let deep_coefficients = air
-        .get_deep_composition_coefficients::<E, H>(&mut public_coin)
+        .get_deep_composition_coefficients::<E, C>(public_coin.label(labels::DEEP_COEFFICIENTS))
         .map_err(|_| VerifierError::RandomCoinError)?;
 
     // instantiates a FRI verifier with the FRI layer commitments read from the channel. From the
@@ -245,45 +892,115 @@ where
     // the prover, and the prover uses them to compute and commit to the subsequent FRI layers.
     let fri_verifier = FriVerifier::new(
         &mut channel,
-        &mut public_coin,
+        public_coin.label(labels::FRI_LAYER),
         air.options().to_fri_options(),
         air.trace_poly_degree(),
     )
     .map_err(VerifierError::FriVerificationFailed)?;
     // TODO: make sure air.lde_domain_size() == fri_verifier.domain_size()
 
+    // when enabled, make sure the digest of the FRI remainder polynomial sent by the prover was
+    // computed from the coefficients of the remainder, and update the public coin with it; this
+    // binds the query positions drawn below to the remainder polynomial in its canonical form
+    if let Some(remainder_digest) = channel.read_fri_remainder_digest()? {
+        public_coin
+            .label(labels::FRI_REMAINDER_DIGEST)
+            .reseed(remainder_digest);
+    }
+
     // 5 ----- trace and constraint queries -------------------------------------------------------
-    // read proof-of-work nonce sent by the prover and update the public coin with it
+    // read proof-of-work nonce sent by the prover, make sure the proof-of-work specified by the
+    // grinding factor is satisfied, and update the public coin with the nonce; the way this is
+    // done is defined by the transcript profile and the grinding hash function specified in the
+    // proof options. with time-boxed grinding, the nonce may provide fewer bits, as long as the
+    // prover drew enough extra queries to compensate for the missing bits
+    let options = air.options();
     let pow_nonce = channel.read_pow_nonce();
-    public_coin.reseed_with_int(pow_nonce);
-
-    // make sure the proof-of-work specified by the grinding factor is satisfied
-    if public_coin.leading_zeros() < air.options().grinding_factor() {
+    if !options.uses_wide_grinding_nonce() && pow_nonce > u64::MAX as u128 {
+        return Err(VerifierError::QuerySeedProofOfWorkVerificationFailed);
+    }
+    let pow_bits = PowPuzzle::new(options, &public_coin)
+        .map_err(VerifierError::UnsupportedGrindingHash)?
+        .get_pow_bits(pow_nonce);
+    if pow_bits < air.options().min_grinding_factor() {
         return Err(VerifierError::QuerySeedProofOfWorkVerificationFailed);
     }
+    let num_extra_queries = air.options().num_extra_queries(pow_bits);
+    if channel.read_num_extra_queries() < num_extra_queries {
+        return Err(VerifierError::InsufficientExtraQueries(
+            num_extra_queries,
+            channel.read_num_extra_queries(),
+        ));
+    }
+    let profile = options.transcript_profile();
+    if options.uses_wide_grinding_nonce() {
+        profile.absorb_wide_pow_nonce(&mut public_coin, pow_nonce);
+    } else {
+        profile.absorb_pow_nonce(&mut public_coin, pow_nonce as u64);
+    }
 
     // draw pseudo-random query positions for the LDE domain from the public coin; in the
     // interactive version of the protocol, the verifier sends these query positions to the prover,
     // and the prover responds with decommitments against these positions for trace and constraint
     // composition polynomial evaluations.
-    let query_positions = public_coin
-        .draw_integers(air.options().num_queries(), air.lde_domain_size())
+    let query_positions = profile
+        .draw_query_positions(
+            &mut public_coin,
+            air.options().num_queries() + channel.read_num_extra_queries(),
+            air.lde_domain_size(),
+        )
         .map_err(|_| VerifierError::RandomCoinError)?;
 
     // read evaluations of trace and constraint composition polynomials at the queried positions;
     // this also checks that the read values are valid against trace and constraint commitments
     let (queried_main_trace_states, queried_aux_trace_states) =
         channel.read_queried_trace_states(&query_positions)?;
-    let queried_constraint_evaluations = channel.read_constraint_evaluations(&query_positions)?;
+    // compute powers of the LDE domain generator at all query positions once; these define the
+    // x coordinates of the queried positions for constraint evaluations and DEEP composition
+    let lde_powers = PowerTable::new(
+        air.lde_domain_size(),
+        query_positions.iter().map(|p| p.as_usize()),
+    );
+    let queried_constraint_evaluations =
+        channel.read_constraint_evaluations(&query_positions, &lde_powers)?;
+
+    // when the proof commits to the combined trace column, make sure its values at the queried
+    // positions are the same linear combinations of the queried trace states
+    if let Some(gamma) = combined_trace_coefficient {
+        let combined_values = channel
+            .read_queried_combined_trace_values(&query_positions)?
+            .expect("combined trace column values are missing");
+        let mut state = Vec::with_capacity(air.trace_info().width());
+        for (i, &value) in combined_values.iter().enumerate() {
+            state.clear();
+            state.extend(
+                queried_main_trace_states
+                    .get_row(i)
+                    .iter()
+                    .map(|&value| E::from(value)),
+            );
+            if let Some(ref aux_trace_states) = queried_aux_trace_states {
+                state.extend_from_slice(aux_trace_states.get_row(i));
+            }
+            if polynom::eval(&state, gamma) != value {
+                return Err(VerifierError::CombinedTraceColumnMismatch);
+            }
+        }
+    }
 
     // 6 ----- DEEP composition -------------------------------------------------------------------
     // compute evaluations of the DEEP composition polynomial at the queried positions
-    let composer = DeepComposer::new(&air, &query_positions, z, deep_coefficients);
+    let composer = DeepComposer::new(
+        &air,
+        &query_positions,
+        &lde_powers,
+        &ood_points,
+        deep_coefficients,
+    );
     let t_composition = composer.compose_trace_columns(
         queried_main_trace_states,
         queried_aux_trace_states,
-        ood_main_trace_frame,
-        ood_aux_trace_frame,
+        ood_trace_frames,
     );
     let c_composition = composer
         .compose_constraint_evaluations(queried_constraint_evaluations, ood_constraint_evaluations);
@@ -293,6 +1010,10 @@ where
     // make sure that evaluations of the DEEP composition polynomial we computed in the previous
     // step are in fact evaluations of a polynomial of degree equal to trace polynomial degree
     fri_verifier
-        .verify(&mut channel, &deep_evaluations, &query_positions)
+        .verify(
+            &mut channel,
+            &deep_evaluations,
+            &LdePos::to_indexes(&query_positions),
+        )
         .map_err(VerifierError::FriVerificationFailed)
 }
@@ -0,0 +1,112 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use air::{Air, ProofOptions, TraceInfo};
+use core::fmt;
+
+// VERIFIED STATEMENT
+// ================================================================================================
+/// Facts authenticated by a successfully verified STARK proof.
+///
+/// A verified statement is returned from [verify_and_extract()](crate::verify_and_extract). All
+/// of its values are the ones bound by the verified proof: the public inputs are the inputs
+/// against which the proof was verified, while the trace info and proof options are read from
+/// the context of the proof (rather than from the values the caller may expect them to be).
+/// Thus, they can be used to make further decisions without re-deriving them from other sources.
+pub struct VerifiedStatement<A: Air> {
+    pub_inputs: A::PublicInputs,
+    trace_info: TraceInfo,
+    options: ProofOptions,
+    conjectured_security_level: u32,
+    proven_security_level: u32,
+}
+
+impl<A: Air> VerifiedStatement<A> {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    /// Returns a new statement; this must be called only after the proof was verified.
+    pub(crate) fn new(
+        pub_inputs: A::PublicInputs,
+        trace_info: TraceInfo,
+        options: ProofOptions,
+        conjectured_security_level: u32,
+        proven_security_level: u32,
+    ) -> Self {
+        VerifiedStatement {
+            pub_inputs,
+            trace_info,
+            options,
+            conjectured_security_level,
+            proven_security_level,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the public inputs against which the proof was verified.
+    pub fn pub_inputs(&self) -> &A::PublicInputs {
+        &self.pub_inputs
+    }
+
+    /// Returns info of the execution trace described by the proof.
+    pub fn trace_info(&self) -> &TraceInfo {
+        &self.trace_info
+    }
+
+    /// Returns the options with which the proof was generated.
+    pub fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    /// Returns conjectured security level (in bits) of the proof.
+    pub fn conjectured_security_level(&self) -> u32 {
+        self.conjectured_security_level
+    }
+
+    /// Returns provable security level (in bits) of the proof (see
+    /// [StarkProof::security_level()](air::proof::StarkProof::security_level)).
+    pub fn proven_security_level(&self) -> u32 {
+        self.proven_security_level
+    }
+
+    /// Consumes this statement and returns the public inputs against which the proof was verified.
+    pub fn into_pub_inputs(self) -> A::PublicInputs {
+        self.pub_inputs
+    }
+}
+
+impl<A: Air> Clone for VerifiedStatement<A>
+where
+    A::PublicInputs: Clone,
+{
+    fn clone(&self) -> Self {
+        VerifiedStatement {
+            pub_inputs: self.pub_inputs.clone(),
+            trace_info: self.trace_info.clone(),
+            options: self.options.clone(),
+            conjectured_security_level: self.conjectured_security_level,
+            proven_security_level: self.proven_security_level,
+        }
+    }
+}
+
+impl<A: Air> fmt::Debug for VerifiedStatement<A>
+where
+    A::PublicInputs: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifiedStatement")
+            .field("pub_inputs", &self.pub_inputs)
+            .field("trace_info", &self.trace_info)
+            .field("options", &self.options)
+            .field(
+                "conjectured_security_level",
+                &self.conjectured_security_level,
+            )
+            .field("proven_security_level", &self.proven_security_level)
+            .finish()
+    }
+}
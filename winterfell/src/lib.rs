@@ -526,12 +526,47 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "trace-constraints")]
+pub use prover::constraint_log;
+#[cfg(feature = "std")]
+pub use prover::planning;
+#[cfg(feature = "test-kit")]
+pub use prover::test_kit;
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub use prover::OsSaltSource;
+#[cfg(feature = "std")]
+pub use prover::SystemClock;
 pub use prover::{
-    crypto, iterators, math, Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint,
-    BoundaryConstraintGroup, ByteReader, ByteWriter, ConstraintCompositionCoefficients,
-    ConstraintDivisor, DeepCompositionCoefficients, Deserializable, DeserializationError,
-    EvaluationFrame, FieldExtension, Matrix, ProofOptions, Prover, ProverError, Serializable,
-    SliceReader, StarkProof, Trace, TraceInfo, TraceLayout, TraceTable, TraceTableFragment,
-    TransitionConstraintDegree, TransitionConstraintGroup,
+    batch, checkpoint, crypto, hidden, iterators, math, pub_inputs_seed, rap, selectors,
+    transcript_labels, Air, AirContext, AirDescription, ArchivalEnvelope, ArchiveError,
+    ArchiveHeader, ArchiveMetadata, Assertion, AssertionDescription, AssertionError, AssertionStep,
+    AuxTraceRandElements, BatchStarkProof, BoundaryConstraint, BoundaryConstraintGroup,
+    BoundaryExpr, ByteReader, ByteWriter, CePos, CheckpointError, ColumnCodec, ColumnStatistics,
+    CommitmentLayout, Commitments, CompositionCoefficients, ConstraintCompositionCoefficients,
+    ConstraintDescription, ConstraintDescriptor, ConstraintDivisor, Context,
+    DeepCompositionCoefficients, DegeneracyThresholds, DegenerateColumn, Deserializable,
+    DeserializationError, EvaluationFrame, ExpectedOutputs, FieldExtension, FillStatus,
+    FrozenClock, GrindingClock, GrindingStrategy, LdePos, LeafEncoding, LeafPosition, LeafSalts,
+    Matrix, OodFrame, PowPuzzle, PreprocessedSegment, ProofOptions, ProofSizeBreakdown, Prover,
+    ProverConfig, ProverError, Queries, SaltSource, SecurityBottleneck, SecurityError,
+    SecurityEstimate, SelectorError, Serializable, SliceReader, SplitInputsAir, StarkProof,
+    StepSelector, Trace, TraceBuildError, TraceError, TraceInfo, TraceLayout, TraceLayoutBuilder,
+    TraceLayoutError, TraceRow, TraceStatistics, TraceTable, TraceTableFragment, TranscriptProfile,
+    TransitionConstraintDegree, TransitionConstraintGroup, TransitionDivisorKind,
+    DEFAULT_DISTINCT_VALUES_CAP, PROOF_FORMAT_VERSION,
 };
-pub use verifier::{verify, VerifierError};
+#[cfg(feature = "dependency-graph")]
+pub use prover::{ConstraintDependencies, DependencyGraph, DEFAULT_NUM_DEPENDENCY_SAMPLES};
+
+pub use verifier::{
+    eval_deep_at_points, verify, verify_and_extract, verify_auto, verify_batch,
+    verify_split_inputs, verify_unsalted, verify_with_commitment, verify_with_preprocessed_segment,
+    verify_with_random_coin, verify_without_extension_check, AutoHashField, VerifiedStatement,
+    VerifierError,
+};
+
+#[cfg(feature = "transcript-log")]
+pub use verifier::verify_with_transcript_log;
+
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -0,0 +1,221 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Property-based testing of AIRs.
+//!
+//! This module contains [proptest] strategies (see [strategies]) and a set of metamorphic
+//! properties which should hold for any correctly defined computation:
+//! * an execution trace built by an honest trace builder yields a proof which verifies
+//!   ([check_honest_trace_verifies()]);
+//! * verification of a proof is deterministic ([check_verification_is_deterministic()]);
+//! * a change of a single constrained cell of an honest execution trace either causes the
+//!   prover to reject the trace, or yields a proof which does not verify against the public
+//!   inputs of the honest trace ([check_perturbed_trace_is_rejected()]).
+//!
+//! The properties are parameterized by a [Prover] and return [TestCaseError] on failure, and
+//! thus, they can be used within the closures passed to [run_property()] or to proptest's own
+//! test runners. The properties assume that the prover does not include a preprocessed segment
+//! into its proofs.
+//!
+//! [run_property()] runs a property against values generated from a seed; the seed is read from
+//! [SEED_ENV_VAR] environment variable if it is set and is chosen at random otherwise. When the
+//! property fails, the seed is included into the panic message, so that the failure can be
+//! reproduced.
+
+use crate::{
+    verify, Air, PreprocessedSegment, ProofOptions, Prover, ProverConfig, ProverError, StarkProof,
+    TraceTable,
+};
+use core::fmt::Debug;
+use proptest::{
+    strategy::Strategy,
+    test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner},
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use proptest;
+
+pub mod strategies;
+use strategies::TracePerturbation;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Name of the environment variable from which [run_property()] reads the seed of the random
+/// number generator.
+pub const SEED_ENV_VAR: &str = "WINTERFELL_PROPTEST_SEED";
+
+// PROPERTY RUNNER
+// ================================================================================================
+
+/// Runs the `test` against `config.cases` values generated by the specified strategy.
+///
+/// Values are generated from a seed which is read from [SEED_ENV_VAR] environment variable, or
+/// is chosen at random if the variable is not set.
+///
+/// # Panics
+/// Panics if the property fails for any of the generated values; the panic message contains the
+/// `name` of the property, the minimal failing value, and the seed from which the values were
+/// generated. Also panics if [SEED_ENV_VAR] is set to a value which is not a valid `u64`.
+pub fn run_property<S, F>(name: &str, config: Config, strategy: S, test: F)
+where
+    S: Strategy,
+    S::Value: Debug,
+    F: Fn(S::Value) -> Result<(), TestCaseError>,
+{
+    let seed = match std::env::var(SEED_ENV_VAR) {
+        Ok(seed) => seed
+            .parse::<u64>()
+            .unwrap_or_else(|_| panic!("{SEED_ENV_VAR} must be a u64, but was '{seed}'")),
+        Err(_) => random_seed(),
+    };
+
+    // failures are reproduced from the seed, and thus, they are not persisted
+    let config = Config {
+        failure_persistence: None,
+        ..config
+    };
+    let mut runner = TestRunner::new_with_rng(config, seeded_rng(seed));
+    match runner.run(&strategy, test) {
+        Ok(()) => (),
+        Err(TestError::Fail(reason, value)) => panic!(
+            "property '{name}' failed: {reason}\nminimal failing input: {value:?}\n\
+            to reproduce, set {SEED_ENV_VAR}={seed}"
+        ),
+        Err(TestError::Abort(reason)) => panic!(
+            "property '{name}' was aborted: {reason}\nto reproduce, set {SEED_ENV_VAR}={seed}"
+        ),
+    }
+}
+
+/// Returns a random number generator for proptest runners expanded from the specified seed.
+fn seeded_rng(seed: u64) -> TestRng {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    TestRng::from_seed(RngAlgorithm::ChaCha, &bytes)
+}
+
+/// Returns a seed derived from the current time.
+fn random_seed() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is earlier than UNIX epoch");
+    elapsed.as_secs() ^ (elapsed.subsec_nanos() as u64).rotate_left(32)
+}
+
+// METAMORPHIC PROPERTIES
+// ================================================================================================
+
+/// Checks that a proof generated for the specified trace verifies against the public inputs of
+/// the trace.
+pub fn check_honest_trace_verifies<P: Prover>(
+    prover: &P,
+    trace: P::Trace,
+) -> Result<(), TestCaseError> {
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prove(prover, trace)?;
+    verify::<P::Air, P::HashFn>(proof, pub_inputs)
+        .map_err(|err| TestCaseError::fail(format!("honest proof failed to verify: {err}")))
+}
+
+/// Checks that verification of a proof generated for the specified trace gives the same result
+/// when it is repeated, and when it is repeated after the proof is serialized and deserialized.
+pub fn check_verification_is_deterministic<P: Prover>(
+    prover: &P,
+    trace: P::Trace,
+) -> Result<(), TestCaseError> {
+    let pub_inputs = [
+        prover.get_pub_inputs(&trace),
+        prover.get_pub_inputs(&trace),
+        prover.get_pub_inputs(&trace),
+    ];
+    let proof = prove(prover, trace)?;
+    let proof_bytes = proof.to_bytes();
+    let restored_proof = StarkProof::from_bytes(&proof_bytes)
+        .map_err(|err| TestCaseError::fail(format!("failed to deserialize proof: {err}")))?;
+
+    let [inputs1, inputs2, inputs3] = pub_inputs;
+    let result1 = verify::<P::Air, P::HashFn>(proof.clone(), inputs1);
+    let result2 = verify::<P::Air, P::HashFn>(proof, inputs2);
+    let result3 = verify::<P::Air, P::HashFn>(restored_proof, inputs3);
+    if result1 != result2 || result1 != result3 {
+        return Err(TestCaseError::fail(format!(
+            "repeated verification is not deterministic: {result1:?}, {result2:?}, {result3:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that after the specified perturbation is applied to the trace, the prover either
+/// rejects the trace, or generates a proof which does not verify against the public inputs of
+/// the original trace.
+///
+/// The perturbed trace is validated against the AIR with validation failures reported as errors
+/// (see [ProverConfig::with_trace_validation()]). Thus, the property holds only if the perturbed
+/// cell is constrained by the AIR; perturbations of unconstrained cells (e.g., padding rows which
+/// are exempt from transition constraints) yield valid traces.
+pub fn check_perturbed_trace_is_rejected<P>(
+    prover: &P,
+    trace: &TraceTable<P::BaseField>,
+    perturbation: TracePerturbation<P::BaseField>,
+) -> Result<(), TestCaseError>
+where
+    P: Prover<Trace = TraceTable<<P as Prover>::BaseField>>,
+{
+    let pub_inputs = prover.get_pub_inputs(trace);
+    let mut perturbed_trace = trace.clone();
+    perturbation.apply(&mut perturbed_trace);
+
+    let prover = ValidatingProver(prover);
+    match prover.prove(perturbed_trace) {
+        Err(ProverError::InvalidTrace(_)) => Ok(()),
+        Err(err) => Err(TestCaseError::fail(format!(
+            "perturbed trace was rejected for a reason other than trace validation: {err}"
+        ))),
+        Ok(proof) => match verify::<P::Air, P::HashFn>(proof, pub_inputs) {
+            Ok(_) => Err(TestCaseError::fail(
+                "proof of a perturbed trace verified against honest public inputs",
+            )),
+            Err(_) => Ok(()),
+        },
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Generates a proof for the specified trace converting prover errors into test failures.
+fn prove<P: Prover>(prover: &P, trace: P::Trace) -> Result<StarkProof, TestCaseError> {
+    prover
+        .prove(trace)
+        .map_err(|err| TestCaseError::fail(format!("failed to generate proof: {err}")))
+}
+
+/// Wrapper around a prover which validates execution traces against the AIR and reports
+/// validation failures as errors.
+struct ValidatingProver<'a, P: Prover>(&'a P);
+
+impl<'a, P: Prover> Prover for ValidatingProver<'a, P> {
+    type BaseField = P::BaseField;
+    type Air = P::Air;
+    type Trace = P::Trace;
+    type HashFn = P::HashFn;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> <<Self as Prover>::Air as Air>::PublicInputs {
+        self.0.get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.0.options()
+    }
+
+    fn config(&self) -> ProverConfig {
+        self.0.config().with_trace_validation(true)
+    }
+
+    fn preprocessed_segment(&self) -> Option<&PreprocessedSegment<Self::BaseField, Self::HashFn>> {
+        self.0.preprocessed_segment()
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Proptest strategies for field elements, proof options, and execution trace perturbations.
+
+use crate::{math::StarkField, FieldExtension, ProofOptions, TraceTable};
+use core::{fmt, ops::Range};
+use proptest::prelude::*;
+
+// FIELD ELEMENTS
+// ================================================================================================
+
+/// Returns a strategy which generates elements of the base field `B`.
+///
+/// Besides uniformly distributed elements, the strategy favors values at which arithmetic bugs
+/// tend to surface: ZERO, ONE, small integers, and elements close to the field modulus.
+pub fn base_element<B: StarkField + 'static>() -> BoxedStrategy<B> {
+    prop_oneof![
+        1 => Just(B::ZERO),
+        1 => Just(B::ONE),
+        1 => Just(-B::ONE),
+        2 => any::<u16>().prop_map(B::from),
+        2 => any::<u16>().prop_map(|value| -B::from(value)),
+        8 => any::<u128>().prop_map(B::from),
+    ]
+    .boxed()
+}
+
+/// Returns a strategy which generates elements of the 62-bit field.
+#[cfg(feature = "f62")]
+pub fn f62_element() -> BoxedStrategy<crate::math::fields::f62::BaseElement> {
+    base_element()
+}
+
+/// Returns a strategy which generates elements of the 64-bit field.
+#[cfg(feature = "f64")]
+pub fn f64_element() -> BoxedStrategy<crate::math::fields::f64::BaseElement> {
+    base_element()
+}
+
+/// Returns a strategy which generates elements of the 128-bit field.
+#[cfg(feature = "f128")]
+pub fn f128_element() -> BoxedStrategy<crate::math::fields::f128::BaseElement> {
+    base_element()
+}
+
+// PROOF OPTIONS
+// ================================================================================================
+
+/// Returns a strategy which generates small proof options.
+///
+/// The options are chosen so that proofs for short traces can be generated quickly: the number
+/// of queries is between 4 and 16, blowup factor is 4, 8, or 16, and grinding factor is at most
+/// 2. Thus, the generated options do not provide meaningful security and must be used only in
+/// tests.
+///
+/// The maximum FRI remainder size is at least 64: with a smaller remainder, the last FRI layer
+/// for blowup factor 16 and folding factor 8 may be folded below the degree of the remainder,
+/// and the verifier rejects such proofs.
+pub fn proof_options() -> impl Strategy<Value = ProofOptions> {
+    (
+        4_usize..=16,
+        prop::sample::select(vec![4_usize, 8, 16]),
+        0_u32..=2,
+        field_extension(),
+        prop::sample::select(vec![4_usize, 8]),
+        prop::sample::select(vec![64_usize, 128]),
+    )
+        .prop_map(
+            |(num_queries, blowup_factor, grinding_factor, extension, folding, remainder)| {
+                ProofOptions::new(
+                    num_queries,
+                    blowup_factor,
+                    grinding_factor,
+                    extension,
+                    folding,
+                    remainder,
+                )
+            },
+        )
+}
+
+#[cfg(feature = "quadratic")]
+fn field_extension() -> impl Strategy<Value = FieldExtension> {
+    prop::sample::select(vec![FieldExtension::None, FieldExtension::Quadratic])
+}
+
+#[cfg(not(feature = "quadratic"))]
+fn field_extension() -> impl Strategy<Value = FieldExtension> {
+    Just(FieldExtension::None)
+}
+
+// TRACE PERTURBATIONS
+// ================================================================================================
+
+/// A change of a single cell of an execution trace.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TracePerturbation<B: StarkField> {
+    /// Index of the column of the perturbed cell.
+    pub column: usize,
+    /// Index of the row of the perturbed cell.
+    pub row: usize,
+    /// Non-zero value added to the perturbed cell.
+    pub delta: B,
+}
+
+impl<B: StarkField> TracePerturbation<B> {
+    /// Adds the delta of this perturbation to the corresponding cell of the specified trace.
+    ///
+    /// # Panics
+    /// Panics if the perturbed cell is out of bounds for the trace.
+    pub fn apply(&self, trace: &mut TraceTable<B>) {
+        let value = trace.get(self.column, self.row);
+        trace.set(self.column, self.row, value + self.delta);
+    }
+}
+
+impl<B: StarkField> fmt::Debug for TracePerturbation<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "trace({}, {}) += {}", self.column, self.row, self.delta)
+    }
+}
+
+/// Returns a strategy which generates perturbations of a single cell within the specified
+/// ranges of columns and rows of an execution trace.
+///
+/// Restricting the ranges is useful for AIRs which leave some cells of the trace unconstrained
+/// (e.g., padding rows): a change of such a cell does not make the trace invalid.
+///
+/// # Panics
+/// Panics if either of the ranges is empty.
+pub fn trace_perturbation<B: StarkField + 'static>(
+    columns: Range<usize>,
+    rows: Range<usize>,
+) -> impl Strategy<Value = TracePerturbation<B>> {
+    assert!(
+        !columns.is_empty(),
+        "range of perturbed columns must not be empty"
+    );
+    assert!(
+        !rows.is_empty(),
+        "range of perturbed rows must not be empty"
+    );
+    let delta = base_element::<B>().prop_filter("delta must not be ZERO", |&d| d != B::ZERO);
+    (columns, rows, delta).prop_map(|(column, row, delta)| TracePerturbation { column, row, delta })
+}